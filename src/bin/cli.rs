@@ -6,9 +6,15 @@ use std::io::{self, Write};
 
 use clap::{Parser, Subcommand};
 
-use engram::embedding::create_embedder;
+use engram::embedding::{
+    benchmark_embedder, create_embedder, normalize_embedder, similarity_matrix,
+    SimilarityMatrixOptions, TfIdfEmbedder, TfIdfVocabulary,
+};
 use engram::error::Result;
-use engram::graph::KnowledgeGraph;
+use engram::graph::{
+    EntityNodeOptions, KnowledgeGraph, LabelOptions, LayoutConfig, RenderOptions, StyleRegistry,
+};
+use engram::interop::{import_session, ImportOptions, ImportReport, ParserRegistry};
 use engram::search::{hybrid_search, SearchConfig};
 use engram::storage::queries::*;
 use engram::storage::Storage;
@@ -88,9 +94,66 @@ enum Commands {
     },
     /// Show statistics
     Stats,
+    /// Rebuild the adjacency table used by graph traversals
+    RebuildAdjacency,
+    /// Recompute the text signatures used for near-duplicate lookup
+    RebuildSignatures,
+    /// Recount the persisted TF-IDF vocabulary
+    RebuildVocabulary,
+    /// Export the pairwise embedding similarity matrix of a set of memories
+    SimilarityMatrix {
+        /// Output format (json, csv)
+        #[arg(short, long, default_value = "csv")]
+        format: String,
+        /// Output file (- for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: String,
+        /// Memory IDs (comma-separated); overrides the filters
+        #[arg(long)]
+        ids: Option<String>,
+        /// Filter by workspace
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Filter by tags (comma-separated)
+        #[arg(long)]
+        tags: Option<String>,
+        /// Maximum memories to select with the filters
+        #[arg(short, long, default_value = "500")]
+        limit: i64,
+        /// Round similarities to this many decimal places
+        #[arg(long)]
+        precision: Option<u32>,
+    },
+    /// Measure embedding throughput at several batch sizes on stored memories
+    EmbedBenchmark {
+        /// Embedding model (tfidf, local, candle, openai, ...)
+        #[arg(short, long, env = "ENGRAM_EMBEDDING_MODEL", default_value = "tfidf")]
+        model: String,
+        /// Model directory for local and candle models
+        #[arg(long, env = "ENGRAM_LOCAL_MODEL_DIR")]
+        model_dir: Option<String>,
+        /// Embedding dimensions (must match the model)
+        #[arg(long, default_value = "384")]
+        dimensions: usize,
+        /// Device for candle models: auto, cpu, metal[:N] or cuda[:N]
+        #[arg(long, env = "ENGRAM_EMBEDDING_DEVICE")]
+        device: Option<String>,
+        /// Most padded tokens per candle forward pass
+        #[arg(long, env = "ENGRAM_EMBED_MAX_BATCH_TOKENS")]
+        max_batch_tokens: Option<usize>,
+        /// Batch sizes to time (comma-separated)
+        #[arg(short, long, default_value = "1,8,32,64,128")]
+        batch_sizes: String,
+        /// Memories embedded per batch size
+        #[arg(short, long, default_value = "256")]
+        limit: i64,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
     /// Export knowledge graph
     Graph {
-        /// Output format (html, json)
+        /// Output format (html, json, graphml, svg, png)
         #[arg(short, long, default_value = "html")]
         format: String,
         /// Output file (- for stdout)
@@ -99,6 +162,30 @@ enum Commands {
         /// Maximum nodes
         #[arg(short, long, default_value = "500")]
         max_nodes: i64,
+        /// Maximum node label length (characters)
+        #[arg(long, default_value = "50")]
+        label_length: usize,
+        /// Node label source (first_line, title, summary)
+        #[arg(long, default_value = "first_line")]
+        label_source: String,
+        /// Compute node positions here instead of with in-browser physics
+        #[arg(long)]
+        precompute_layout: bool,
+        /// Collapse communities into supernodes until at most this many nodes remain
+        #[arg(long)]
+        summarize: Option<usize>,
+        /// Add extracted entities and linked identities as nodes
+        #[arg(long)]
+        bipartite: bool,
+        /// Merge links between the same two memories into one edge
+        #[arg(long)]
+        aggregate_parallel_edges: bool,
+        /// Draw the tag hierarchy instead of memories (html, dot or json)
+        #[arg(long)]
+        tag_tree: bool,
+        /// Drill-down URL for each tag of a tag tree, with {tag} replaced by its path
+        #[arg(long)]
+        tag_link: Option<String>,
     },
     /// Link two memories
     Link {
@@ -115,6 +202,27 @@ enum Commands {
         /// Memory ID
         id: i64,
     },
+    /// Import agent framework logs as sessions
+    ImportLogs {
+        /// Log files, or directories searched for .json/.jsonl/.ndjson files
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Log format: auto, openai, anthropic or langgraph
+        #[arg(short, long, default_value = "auto")]
+        format: String,
+        /// Workspace for imported sessions
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Agent ID for sessions whose log doesn't name one
+        #[arg(long)]
+        agent_id: Option<String>,
+        /// Index the sessions without capturing memories
+        #[arg(long)]
+        no_capture: bool,
+        /// Show the sessions found without importing them
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Interactive mode
     Interactive,
     /// Create, load, or inspect .egm snapshots
@@ -289,9 +397,8 @@ fn main() -> Result<()> {
             limit,
             explain,
         } => {
-            let embedding_config = EmbeddingConfig::default();
-            let embedder = create_embedder(&embedding_config)?;
-            let query_embedding = embedder.embed(&query).ok();
+            let embedder = query_embedder(&storage)?;
+            let query_embedding = embedder.embed_query(&query).ok();
 
             let options = SearchOptions {
                 limit: Some(limit),
@@ -330,17 +437,201 @@ fn main() -> Result<()> {
             println!("{}", serde_json::to_string_pretty(&stats)?);
         }
 
+        Commands::RebuildAdjacency => {
+            let report = storage.with_transaction(engram::storage::rebuild_adjacency)?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+
+        Commands::RebuildSignatures => {
+            let report = storage.with_transaction(engram::storage::rebuild_text_signatures)?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+
+        Commands::RebuildVocabulary => {
+            let stats = storage.with_transaction(engram::storage::rebuild_tfidf_vocabulary)?;
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        }
+
+        Commands::SimilarityMatrix {
+            format,
+            output,
+            ids,
+            workspace,
+            tags,
+            limit,
+            precision,
+        } => {
+            let ids = ids.map(|ids| {
+                ids.split(',')
+                    .filter_map(|id| id.trim().parse().ok())
+                    .collect()
+            });
+            let options = SimilarityMatrixOptions {
+                ids,
+                list: ListOptions {
+                    limit: Some(limit),
+                    workspace,
+                    tags: tags.map(|t| t.split(',').map(|s| s.trim().to_string()).collect()),
+                    ..Default::default()
+                },
+                precision,
+            };
+            let matrix = storage.with_connection(|conn| similarity_matrix(conn, &options))?;
+            let content = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&matrix)?,
+                _ => matrix.to_csv(),
+            };
+            if output == "-" {
+                print!("{}", content);
+            } else {
+                std::fs::write(&output, content)?;
+                println!(
+                    "Similarity matrix of {} memories exported to {}",
+                    matrix.ids.len(),
+                    output
+                );
+            }
+            if !matrix.missing_embeddings.is_empty() {
+                eprintln!(
+                    "Skipped {} memories without a comparable embedding",
+                    matrix.missing_embeddings.len()
+                );
+            }
+        }
+
+        Commands::EmbedBenchmark {
+            model,
+            model_dir,
+            dimensions,
+            device,
+            max_batch_tokens,
+            batch_sizes,
+            limit,
+            format,
+        } => {
+            let batch_sizes: Vec<usize> = batch_sizes
+                .split(',')
+                .filter_map(|size| size.trim().parse().ok())
+                .collect();
+            let texts: Vec<String> = storage
+                .with_connection(|conn| {
+                    list_memories(
+                        conn,
+                        &ListOptions {
+                            limit: Some(limit),
+                            ..Default::default()
+                        },
+                    )
+                })?
+                .into_iter()
+                .map(|m| m.content)
+                .collect();
+            if texts.is_empty() {
+                println!("No memories to embed.");
+                return Ok(());
+            }
+
+            // Every batch size gets a forward pass of its own, so the
+            // inference batch follows the benchmarked one
+            let embedder_for = |batch_size: usize| {
+                create_embedder(&EmbeddingConfig {
+                    model: model.clone(),
+                    model_path: model_dir
+                        .as_ref()
+                        .map(|dir| shellexpand::tilde(dir).to_string()),
+                    dimensions,
+                    device: device.clone(),
+                    inference_batch_size: Some(batch_size),
+                    max_batch_tokens,
+                    ..Default::default()
+                })
+            };
+            let mut report: Option<engram::embedding::EmbeddingBenchmark> = None;
+            for &batch_size in &batch_sizes {
+                let embedder = embedder_for(batch_size)?;
+                let run = benchmark_embedder(embedder.as_ref(), &texts, &[batch_size])?;
+                report = Some(match report {
+                    None => run,
+                    Some(mut report) => {
+                        report.timings.extend(run.timings);
+                        report
+                    }
+                });
+            }
+            let Some(mut report) = report else {
+                println!("No batch sizes to time.");
+                return Ok(());
+            };
+            if let Some(best) = report
+                .timings
+                .iter()
+                .max_by(|a, b| a.texts_per_second.total_cmp(&b.texts_per_second))
+            {
+                report.best_batch_size = best.batch_size;
+            }
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!(
+                    "{} ({} dimensions): {} memories, {} characters",
+                    report.model, report.dimensions, report.texts, report.total_chars
+                );
+                println!("{:>10} {:>12} {:>12} {:>14}", "batch", "ms", "texts/s", "chars/s");
+                for timing in &report.timings {
+                    println!(
+                        "{:>10} {:>12.1} {:>12.1} {:>14.0}",
+                        timing.batch_size,
+                        timing.elapsed_ms,
+                        timing.texts_per_second,
+                        timing.chars_per_second
+                    );
+                }
+                println!("Fastest batch size: {}", report.best_batch_size);
+            }
+        }
+
         Commands::Graph {
             format,
             output,
             max_nodes,
+            label_length,
+            label_source,
+            precompute_layout,
+            summarize,
+            bipartite,
+            aggregate_parallel_edges,
+            tag_tree,
+            tag_link,
         } => {
+            if tag_tree {
+                let hierarchy = storage.with_connection(get_tag_hierarchy)?;
+                let graph = KnowledgeGraph::from_tag_hierarchy(&hierarchy);
+                let styles = StyleRegistry::global();
+                let content = match format.as_str() {
+                    "dot" => graph.to_tag_tree_dot(styles, tag_link.as_deref()),
+                    "json" => serde_json::to_string_pretty(&graph.to_visjs_json_with(styles))?,
+                    _ => graph.to_tag_tree_html(styles, tag_link.as_deref()),
+                };
+                if output == "-" {
+                    println!("{}", content);
+                } else {
+                    std::fs::write(&output, content)?;
+                    println!("Tag tree exported to {}", output);
+                }
+                return Ok(());
+            }
+
+            let labels = LabelOptions {
+                max_length: label_length.max(1),
+                source: label_source.parse().unwrap_or_default(),
+            };
             let options = ListOptions {
                 limit: Some(max_nodes),
                 ..Default::default()
             };
 
-            let (memories, crossrefs) = storage.with_connection(|conn| {
+            let graph = storage.with_connection(|conn| {
                 let memories = list_memories(conn, &options)?;
                 let mut all_crossrefs = Vec::new();
                 for memory in &memories {
@@ -348,14 +639,63 @@ fn main() -> Result<()> {
                         all_crossrefs.extend(refs);
                     }
                 }
-                Ok((memories, all_crossrefs))
+                let mut graph =
+                    KnowledgeGraph::from_data_with_labels(&memories, &all_crossrefs, &labels);
+                if bipartite {
+                    graph.add_entity_nodes(conn, &EntityNodeOptions::default())?;
+                }
+                Ok(graph)
             })?;
 
-            let graph = KnowledgeGraph::from_data(&memories, &crossrefs);
+            let styles = StyleRegistry::global();
+            let summary = summarize.map(|limit| graph.summarize(limit));
+            let shown = summary.as_ref().map_or(&graph, |s| &s.graph);
+            let layout = precompute_layout.then(|| shown.force_layout(&LayoutConfig::default()));
 
-            let content = match format.as_str() {
-                "json" => serde_json::to_string_pretty(&graph.to_visjs_json())?,
-                _ => graph.to_html(),
+            let aggregated = (aggregate_parallel_edges && summary.is_none())
+                .then(|| graph.aggregate_parallel_edges());
+
+            if matches!(format.as_str(), "svg" | "png") {
+                let rendered = aggregated.as_ref().map_or(shown, |a| &a.graph);
+                let options = RenderOptions::default();
+                let image = if format == "png" {
+                    #[cfg(feature = "graph-png")]
+                    {
+                        rendered.to_png(styles, layout.as_ref(), &options)?
+                    }
+                    #[cfg(not(feature = "graph-png"))]
+                    {
+                        eprintln!("PNG rendering requires the graph-png feature");
+                        std::process::exit(1);
+                    }
+                } else {
+                    rendered.to_svg(styles, layout.as_ref(), &options).into_bytes()
+                };
+                if output == "-" {
+                    std::io::Write::write_all(&mut std::io::stdout(), &image)?;
+                } else {
+                    std::fs::write(&output, image)?;
+                    println!("Graph exported to {}", output);
+                }
+                return Ok(());
+            }
+
+            let content = match (format.as_str(), &summary, &aggregated) {
+                ("json", Some(summary), _) => serde_json::to_string_pretty(
+                    &summary.to_visjs_json_with_layout(styles, layout.as_ref()),
+                )?,
+                ("json", None, Some(aggregated)) => serde_json::to_string_pretty(
+                    &aggregated.to_visjs_json_with_layout(styles, layout.as_ref()),
+                )?,
+                ("json", None, None) => serde_json::to_string_pretty(
+                    &graph.to_visjs_json_with_layout(styles, layout.as_ref()),
+                )?,
+                ("graphml", _, _) => shown.to_graphml(),
+                (_, Some(summary), _) => summary.to_html_with(styles),
+                (_, None, Some(aggregated)) => {
+                    aggregated.to_html_with_layout(styles, layout.as_ref())
+                }
+                (_, None, None) => graph.to_html_with_layout(styles, layout.as_ref()),
             };
 
             if output == "-" {
@@ -397,6 +737,68 @@ fn main() -> Result<()> {
             }
         }
 
+        Commands::ImportLogs {
+            paths,
+            format,
+            workspace,
+            agent_id,
+            no_capture,
+            dry_run,
+        } => {
+            let registry = ParserRegistry::default();
+            let options = ImportOptions {
+                workspace,
+                agent_id,
+                capture: !no_capture,
+                ..Default::default()
+            };
+            let mut files = Vec::new();
+            for path in &paths {
+                collect_log_files(std::path::Path::new(path), &mut files)?;
+            }
+
+            let mut report = ImportReport::default();
+            for file in files {
+                let parsed = std::fs::read_to_string(&file)
+                    .map_err(Into::into)
+                    .and_then(|input| registry.parse(&input, Some(&format)));
+                let parsed = match parsed {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        report.errors.push(format!("{}: {}", file.display(), e));
+                        continue;
+                    }
+                };
+                report.files += 1;
+                println!(
+                    "{}: {} sessions ({})",
+                    file.display(),
+                    parsed.sessions.len(),
+                    parsed.format
+                );
+                for session in &parsed.sessions {
+                    if dry_run {
+                        println!(
+                            "  {} ({} messages)",
+                            session.session_id,
+                            session.messages.len()
+                        );
+                        continue;
+                    }
+                    let imported = storage.with_transaction(|conn| {
+                        import_session(conn, parsed.format, session, &options)
+                    });
+                    match imported {
+                        Ok(imported) => report.add(&imported),
+                        Err(e) => report.errors.push(format!("{}: {}", session.session_id, e)),
+                    }
+                }
+            }
+            if !dry_run {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+
         #[cfg(feature = "agent-portability")]
         Commands::Snapshot { action } => match action {
             SnapshotAction::Create {
@@ -650,9 +1052,8 @@ fn main() -> Result<()> {
                     }
                     _ if line.starts_with("search ") => {
                         let query = line[7..].trim();
-                        let embedding_config = EmbeddingConfig::default();
-                        let embedder = create_embedder(&embedding_config)?;
-                        let query_embedding = embedder.embed(query).ok();
+                        let embedder = query_embedder(&storage)?;
+                        let query_embedding = embedder.embed_query(query).ok();
 
                         let options = SearchOptions {
                             limit: Some(5),
@@ -693,11 +1094,48 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn truncate(s: &str, max: usize) -> String {
-    let first_line = s.lines().next().unwrap_or(s);
-    if first_line.len() <= max {
-        first_line.to_string()
-    } else {
-        format!("{}...", &first_line[..max - 3])
+/// Log files under `path`, in name order
+fn collect_log_files(path: &std::path::Path, files: &mut Vec<std::path::PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries: Vec<_> = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        let is_log = entry
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e, "json" | "jsonl" | "ndjson"));
+        if entry.is_dir() || is_log {
+            collect_log_files(&entry, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Embedder for search queries, weighting terms by the stored TF-IDF
+/// vocabulary so queries match the server's vectors
+fn query_embedder(storage: &Storage) -> Result<std::sync::Arc<dyn engram::embedding::Embedder>> {
+    let mut config = EmbeddingConfig::default();
+    // Queries must be normalized the way the server normalized documents
+    if let Ok(steps) = std::env::var("ENGRAM_EMBEDDING_NORMALIZE") {
+        config.normalization = steps.parse()?;
+    }
+    if config.model != "tfidf" {
+        return create_embedder(&config);
     }
+    let vocabulary = storage.with_connection(TfIdfVocabulary::load)?;
+    Ok(normalize_embedder(
+        std::sync::Arc::new(
+            TfIdfEmbedder::new(config.dimensions).with_vocabulary(std::sync::Arc::new(vocabulary)),
+        ),
+        &config.normalization,
+    ))
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    engram::graph::label::truncate_label(s, max)
 }
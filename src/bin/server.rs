@@ -5,25 +5,35 @@
 use std::sync::Arc;
 
 use clap::Parser;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use serde_json::{json, Value};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use engram::auth::{ApiKeyManager, AuthContext};
+use engram::budget::{self, CancelHandle};
 use engram::embedding::create_embedder;
 use engram::error::Result;
+use engram::graph::{builder::publish_mutations, GraphBuilder, LabelOptions};
 use engram::mcp::{
-    get_prompt, get_tool_definitions_tiered, handlers, http_transport, list_prompts, list_resources,
-    methods, read_resource, InitializeResult, McpHandler, McpRequest, McpResponse, McpServer,
-    PromptCapabilities, ResourceCapabilities, ServerCapabilities, ToolCallResult, ToolsCapability,
-    MCP_PROTOCOL_VERSION, MCP_PROTOCOL_VERSION_LEGACY,
+    access, get_prompt, get_tool_definitions_tiered, handlers, http_transport, list_prompts,
+    list_resources, methods, read_resource, InitializeResult, McpHandler, McpRequest, McpResponse,
+    McpServer, PromptCapabilities, ResourceCapabilities, ServerCapabilities, ToolCallResult,
+    ToolsCapability, MCP_PROTOCOL_VERSION, MCP_PROTOCOL_VERSION_LEGACY,
 };
-use engram::realtime::{RealtimeManager, RealtimeServer};
-use engram::search::{FuzzyEngine, SearchConfig};
+use engram::realtime::{CoalescingConfig, RealtimeManager, RealtimeServer, StorageEventLog};
+use engram::search::{create_cross_encoder, CrossEncoderBackend, FuzzyEngine, SearchConfig};
 use engram::storage::Storage;
 #[cfg(feature = "meilisearch")]
 use engram::storage::{MeilisearchBackend, MeilisearchIndexer, SqliteBackend};
 use engram::types::*;
 
+/// Memories loaded into the live graph at startup; later ones join as they
+/// are created.
+const LIVE_GRAPH_MAX_NODES: i64 = 1000;
+
+/// How often pending rows of the embedding queue are fed to the worker
+const EMBEDDING_FEED_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Transport mode for the MCP server.
 #[derive(Debug, Clone, clap::ValueEnum)]
 enum TransportMode {
@@ -62,10 +72,101 @@ struct Args {
     #[arg(long, env = "ENGRAM_CLOUD_ENCRYPT")]
     encrypt: bool,
 
-    /// Embedding model (openai, tfidf)
+    /// Embedding model (openai, local, candle, cohere, voyage, hf, tfidf)
     #[arg(long, env = "ENGRAM_EMBEDDING_MODEL", default_value = "tfidf")]
     embedding_model: String,
 
+    /// Model directory for local embeddings (model.onnx + tokenizer.json;
+    /// config.json + model.safetensors + tokenizer.json for candle)
+    /// Default: ~/.local/share/engram/models/all-MiniLM-L6-v2
+    #[arg(long, env = "ENGRAM_LOCAL_MODEL_DIR")]
+    local_model_dir: Option<String>,
+
+    /// Device for candle embeddings: auto, cpu, metal[:N] or cuda[:N]
+    #[arg(long, env = "ENGRAM_EMBEDDING_DEVICE")]
+    embedding_device: Option<String>,
+
+    /// Texts per forward pass for candle embeddings (default: 32)
+    #[arg(long, env = "ENGRAM_EMBED_INFERENCE_BATCH")]
+    embed_inference_batch: Option<usize>,
+
+    /// Most padded tokens per forward pass for candle embeddings, to bound
+    /// GPU memory (default: 16384)
+    #[arg(long, env = "ENGRAM_EMBED_MAX_BATCH_TOKENS")]
+    embed_max_batch_tokens: Option<usize>,
+
+    /// Shrink embeddings to fewer dimensions: truncate:<dims> for Matryoshka
+    /// models or pca:<dims> (fit with memory_reduce_embeddings)
+    #[arg(long, env = "ENGRAM_EMBEDDING_REDUCTION")]
+    embedding_reduction: Option<String>,
+
+    /// Normalize text before embedding, e.g.
+    /// markdown,urls,whitespace,lowercase,code:placeholder,max:8000:head_tail
+    #[arg(long, env = "ENGRAM_EMBEDDING_NORMALIZE")]
+    embedding_normalize: Option<String>,
+
+    /// Embed a summary of memories longer than this many characters, as the
+    /// model would truncate them (default: about the model's input limit;
+    /// 0 = never)
+    #[arg(long, env = "ENGRAM_EMBED_SUMMARIZE_OVER_CHARS")]
+    embed_summarize_over_chars: Option<usize>,
+
+    /// Summarizer for long memories: extractive or llm (uses the session
+    /// summary model)
+    #[arg(long, env = "ENGRAM_EMBED_SUMMARIZER", default_value = "extractive")]
+    embed_summarizer: String,
+
+    /// Prepended to search queries before embedding (e.g. "query: " for E5)
+    #[arg(long, env = "ENGRAM_EMBEDDING_QUERY_PREFIX")]
+    embedding_query_prefix: Option<String>,
+
+    /// Prepended to memories before embedding (e.g. "passage: " for E5)
+    #[arg(long, env = "ENGRAM_EMBEDDING_DOCUMENT_PREFIX")]
+    embedding_document_prefix: Option<String>,
+
+    /// Most tokens sent to the embedding model per memory (default: 8191 for
+    /// OpenAI, no limit otherwise; 0 = no limit)
+    #[arg(long, env = "ENGRAM_EMBED_MAX_TOKENS")]
+    embed_max_tokens: Option<usize>,
+
+    /// Memories over the token limit: truncate, or chunk_average to embed
+    /// every chunk and average the vectors
+    #[arg(long, env = "ENGRAM_EMBED_LONG_TEXT", default_value = "truncate")]
+    embed_long_text: String,
+
+    /// Append a TF-IDF vector of this many dimensions to each embedding so
+    /// exact keyword matches survive semantic ranking (0 = off)
+    #[arg(long, env = "ENGRAM_HYBRID_SPARSE_DIMS", default_value = "0")]
+    hybrid_sparse_dims: usize,
+
+    /// Share of similarity given to the TF-IDF part of hybrid embeddings
+    #[arg(long, env = "ENGRAM_HYBRID_SPARSE_WEIGHT", default_value = "0.3")]
+    hybrid_sparse_weight: f32,
+
+    /// Cross-encoder for reranking search results: onnx (neural-rerank
+    /// feature), or cohere, jina, voyage or tei (api-rerank feature).
+    /// Searches rerank with it by default when set.
+    #[arg(long, env = "ENGRAM_RERANK_BACKEND")]
+    rerank_backend: Option<String>,
+
+    /// Reranker model: a directory with model.onnx and tokenizer.json for
+    /// onnx, or a model name for the APIs (default: the provider's)
+    #[arg(long, env = "ENGRAM_RERANK_MODEL")]
+    rerank_model: Option<String>,
+
+    /// Base URL of the rerank API (required for tei)
+    #[arg(long, env = "ENGRAM_RERANK_URL")]
+    rerank_url: Option<String>,
+
+    /// API key for hosted rerankers
+    #[arg(long, env = "ENGRAM_RERANK_API_KEY")]
+    rerank_api_key: Option<String>,
+
+    /// JSON file listing databases to attach read-only for federated search
+    /// (default: ~/.config/engram/federation.json when present)
+    #[arg(long, env = "ENGRAM_FEDERATION_CONFIG")]
+    federation_config: Option<String>,
+
     /// OpenAI API key
     #[arg(long, env = "OPENAI_API_KEY")]
     openai_key: Option<String>,
@@ -90,6 +191,29 @@ struct Args {
     #[arg(long, env = "OPENAI_EMBEDDING_DIMENSIONS")]
     openai_embedding_dimensions: Option<usize>,
 
+    /// Cohere API key (for --embedding-model cohere)
+    #[arg(long, env = "COHERE_API_KEY")]
+    cohere_key: Option<String>,
+
+    /// Voyage AI API key (for --embedding-model voyage)
+    #[arg(long, env = "VOYAGE_API_KEY")]
+    voyage_key: Option<String>,
+
+    /// Hugging Face access token (for --embedding-model hf)
+    #[arg(long, env = "HF_TOKEN")]
+    hf_token: Option<String>,
+
+    /// text-embeddings-inference server or Inference Endpoint URL for
+    /// --embedding-model hf (default: the hosted Inference API)
+    #[arg(long, env = "ENGRAM_HF_BASE_URL")]
+    hf_base_url: Option<String>,
+
+    /// Model name for the Cohere, Voyage or Hugging Face backend
+    /// (default: embed-english-v3.0 / voyage-2 / sentence-transformers/all-MiniLM-L6-v2;
+    /// leave unset for a TEI server)
+    #[arg(long, env = "ENGRAM_PROVIDER_EMBEDDING_MODEL")]
+    provider_embedding_model: Option<String>,
+
     /// Sync debounce in ms
     #[arg(long, env = "ENGRAM_SYNC_DEBOUNCE_MS", default_value = "5000")]
     sync_debounce_ms: u64,
@@ -103,6 +227,14 @@ struct Args {
     #[arg(long, env = "ENGRAM_CLEANUP_INTERVAL", default_value = "3600")]
     cleanup_interval_seconds: u64,
 
+    /// Days an expired memory stays recoverable before cleanup purges it
+    #[arg(
+        long,
+        env = "ENGRAM_EXPIRED_GRACE_DAYS",
+        default_value_t = engram::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS
+    )]
+    expired_grace_days: i64,
+
     /// Compression scheduler interval in seconds (0 = disabled)
     /// Auto-summarizes old, rarely-accessed memories at this interval
     #[arg(long, env = "ENGRAM_COMPRESSION_INTERVAL", default_value = "0")]
@@ -120,10 +252,45 @@ struct Args {
     #[arg(long, env = "ENGRAM_COMPRESSION_MIN_ACCESS", default_value = "3")]
     compression_min_access: i32,
 
+    /// Fact review escalation interval in seconds (0 = disabled)
+    /// Queues long-unverified, frequently retrieved facts for review
+    #[arg(long, env = "ENGRAM_FACT_REVIEW_INTERVAL", default_value = "0")]
+    fact_review_interval_seconds: u64,
+
+    /// Freshness contract check interval in seconds (0 = disabled)
+    /// Flags memories overdue for re-verification so search demotes them
+    #[arg(long, env = "ENGRAM_FRESHNESS_CHECK_INTERVAL", default_value = "3600")]
+    freshness_check_interval_seconds: u64,
+
+    /// Recommendation interval in seconds (0 = disabled)
+    /// Suggests memories active sessions probably need as realtime events
+    #[arg(long, env = "ENGRAM_RECOMMENDATION_INTERVAL", default_value = "0")]
+    recommendation_interval_seconds: u64,
+
+    /// Interval in seconds for writing buffered cache-hit access counts back
+    /// to the store (0 = write every access through immediately)
+    #[arg(long, env = "ENGRAM_ACCESS_FLUSH_INTERVAL", default_value = "30")]
+    access_flush_interval_seconds: u64,
+
     /// WebSocket server port for real-time events (0 = disabled)
     #[arg(long, env = "ENGRAM_WS_PORT", default_value = "0")]
     ws_port: u16,
 
+    /// Window in milliseconds for coalescing bursts of memory
+    /// created/updated/deleted events into bulk events (0 = disabled)
+    #[arg(long, env = "ENGRAM_EVENT_COALESCE_MS", default_value = "250")]
+    event_coalesce_ms: u64,
+
+    /// Seconds between WebSocket heartbeats; clients silent for three
+    /// intervals are disconnected
+    #[arg(long, env = "ENGRAM_WS_HEARTBEAT_SECS", default_value = "30")]
+    ws_heartbeat_secs: u64,
+
+    /// Secret WebSocket resume tokens are signed with. Without it tokens
+    /// are signed with a random key and don't survive a restart.
+    #[arg(long, env = "ENGRAM_WS_RESUME_SECRET")]
+    ws_resume_secret: Option<String>,
+
     /// Transport mode: stdio (default), http, or both
     #[arg(long, env = "ENGRAM_TRANSPORT", value_enum, default_value = "stdio")]
     transport: TransportMode,
@@ -136,6 +303,10 @@ struct Args {
     #[arg(long, env = "ENGRAM_HTTP_API_KEY")]
     http_api_key: Option<String>,
 
+    /// Serve the web dashboard at /ui/ on the HTTP transport
+    #[arg(long, env = "ENGRAM_DASHBOARD")]
+    dashboard: bool,
+
     /// gRPC transport port (used when --transport is grpc)
     #[cfg(feature = "grpc")]
     #[arg(long, env = "ENGRAM_GRPC_PORT", default_value = "50051")]
@@ -165,6 +336,10 @@ struct Args {
     #[cfg(feature = "meilisearch")]
     #[arg(long, env = "MEILISEARCH_SYNC_INTERVAL", default_value = "60")]
     meilisearch_sync_interval: u64,
+
+    /// Hide deprecated tools from tools/list and reject calls to them
+    #[arg(long, env = "ENGRAM_DISABLE_DEPRECATED_TOOLS", default_value_t = false)]
+    disable_deprecated_tools: bool,
 }
 
 /// MCP request handler
@@ -177,8 +352,22 @@ struct EngramHandler {
     realtime: Option<RealtimeManager>,
     /// Embedding cache for performance optimization
     embedding_cache: Arc<engram::embedding::EmbeddingCache>,
+    /// Lanes of the background embedding worker
+    embedding_queue: Option<engram::embedding::EmbeddingQueue>,
     /// Search result cache (Phase 4 - ENG-36)
     search_cache: Arc<engram::search::SearchResultCache>,
+    /// Read-through cache of hot memories
+    memory_cache: Arc<engram::storage::MemoryCache>,
+    /// Persona activated by the connected agent
+    persona: Arc<engram::storage::ActivePersona>,
+    /// Approximate nearest-neighbour index over embeddings
+    vector_index: Arc<engram::search::vector_index::VectorIndexHandle>,
+    /// Cross-encoder for reranking, if configured
+    cross_encoder: Option<Arc<dyn engram::search::CrossEncoder>>,
+    /// Read-only databases for federated search, if configured
+    federation: Option<Arc<engram::storage::Federation>>,
+    /// Days expired memories stay recoverable before cleanup purges them
+    expired_grace_days: i64,
     /// Meilisearch backend for Phase 7 MCP tools
     #[cfg(feature = "meilisearch")]
     meili: Option<Arc<engram::storage::MeilisearchBackend>>,
@@ -197,14 +386,26 @@ impl EngramHandler {
     fn new(storage: Storage, embedder: Arc<dyn engram::embedding::Embedder>) -> Self {
         Self {
             storage,
-            embedder,
             fuzzy_engine: Arc::new(Mutex::new(FuzzyEngine::new())),
-            search_config: SearchConfig::default(),
+            search_config: SearchConfig {
+                hybrid_dense_dims: embedder.hybrid_dense_dims(),
+                ..Default::default()
+            },
+            embedder,
             realtime: None,
             embedding_cache: Arc::new(engram::embedding::EmbeddingCache::default()),
+            embedding_queue: None,
             search_cache: Arc::new(engram::search::SearchResultCache::new(
                 engram::search::AdaptiveCacheConfig::default(),
             )),
+            memory_cache: Arc::new(engram::storage::MemoryCache::default()),
+            persona: Arc::new(engram::storage::ActivePersona::new()),
+            vector_index: Arc::new(engram::search::vector_index::VectorIndexHandle::new(
+                engram::search::vector_index::VectorIndexConfig::from_env(),
+            )),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: engram::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             #[cfg(feature = "meilisearch")]
             meili: None,
             #[cfg(feature = "meilisearch")]
@@ -224,9 +425,9 @@ impl EngramHandler {
 
     /// Build a `HandlerContext` from this handler's shared state and delegate
     /// to the domain-module dispatch function.
-    fn handle_tool_call(&self, name: &str, params: Value) -> Value {
+    fn handle_tool_call(&self, name: &str, params: Value, cancel: CancelHandle) -> Value {
         let ctx = self.make_context();
-        handlers::dispatch(&ctx, name, params)
+        handlers::dispatch_cancellable(&ctx, name, params, cancel)
     }
 
     /// Construct a `HandlerContext` from this handler's shared state.
@@ -238,7 +439,14 @@ impl EngramHandler {
             search_config: self.search_config.clone(),
             realtime: self.realtime.clone(),
             embedding_cache: self.embedding_cache.clone(),
+            embedding_queue: self.embedding_queue.clone(),
             search_cache: self.search_cache.clone(),
+            memory_cache: self.memory_cache.clone(),
+            persona: self.persona.clone(),
+            vector_index: self.vector_index.clone(),
+            cross_encoder: self.cross_encoder.clone(),
+            federation: self.federation.clone(),
+            expired_grace_days: self.expired_grace_days,
             #[cfg(feature = "meilisearch")]
             meili: self.meili.clone(),
             #[cfg(feature = "meilisearch")]
@@ -254,8 +462,9 @@ impl EngramHandler {
     }
 }
 
-impl McpHandler for EngramHandler {
-    fn handle_request(&self, request: McpRequest) -> McpResponse {
+impl EngramHandler {
+    /// Handle a request, limited to `auth`'s permissions when given
+    fn handle(&self, request: McpRequest, auth: Option<&AuthContext>) -> McpResponse {
         match request.method.as_str() {
             methods::INITIALIZE => {
                 // Negotiate protocol version: if the client requests the legacy version, respond
@@ -311,9 +520,26 @@ impl McpHandler for EngramHandler {
                     error: None,
                 }
             }
+            methods::CANCELLED => {
+                if let Some(id) = request.params.get("requestId") {
+                    budget::request_cancel(id);
+                }
+                McpResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: None,
+                    result: None,
+                    error: None,
+                }
+            }
             methods::LIST_TOOLS => {
                 let tier = std::env::var("ENGRAM_TOOL_TIER").ok();
-                let tools = get_tool_definitions_tiered(tier.as_deref());
+                let mut tools = get_tool_definitions_tiered(tier.as_deref());
+                if let Some(persona) = self.persona.get() {
+                    tools.retain(|tool| persona.allows_tool(&tool.name));
+                }
+                if let Some(auth) = auth {
+                    access::filter_tools(&mut tools, auth);
+                }
                 McpResponse::success(request.id, json!({"tools": tools}))
             }
             methods::CALL_TOOL => {
@@ -328,7 +554,18 @@ impl McpHandler for EngramHandler {
                     .cloned()
                     .unwrap_or(json!({}));
 
-                let result = self.handle_tool_call(name, arguments);
+                // Lets `notifications/cancelled` stop the call while it runs
+                let registration = request.id.as_ref().map(budget::register_request);
+                let cancel = registration
+                    .as_ref()
+                    .map(|r| r.handle())
+                    .unwrap_or_default();
+                let result = match auth {
+                    Some(auth) if !access::call_allowed(auth, name, &arguments) => json!({
+                        "error": format!("Tool '{}' is not permitted for this API key", name)
+                    }),
+                    _ => self.handle_tool_call(name, arguments, cancel),
+                };
                 let tool_result = ToolCallResult::json(&result);
                 McpResponse::success(request.id, json!(tool_result))
             }
@@ -406,6 +643,30 @@ impl McpHandler for EngramHandler {
     }
 }
 
+impl McpHandler for EngramHandler {
+    fn handle_request(&self, request: McpRequest) -> McpResponse {
+        self.handle(request, None)
+    }
+
+    fn handle_request_as(&self, request: McpRequest, auth: &AuthContext) -> McpResponse {
+        self.handle(request, Some(auth))
+    }
+
+    fn authenticate(&self, token: &str) -> Option<AuthContext> {
+        // Databases without the auth tables have no per-user keys
+        let claims = self
+            .storage
+            .with_connection(|conn| ApiKeyManager::new(conn).validate_key(token))
+            .ok()
+            .flatten()?;
+        Some(AuthContext {
+            user_id: claims.user_id,
+            permissions: claims.permissions,
+            namespace: claims.namespace,
+        })
+    }
+}
+
 fn main() -> Result<()> {
     // Initialize logging to stderr (stdout is for MCP protocol)
     tracing_subscriber::registry()
@@ -418,6 +679,7 @@ fn main() -> Result<()> {
         .init();
 
     let args = Args::parse();
+    engram::mcp::versioning::set_deprecated_tools_disabled(args.disable_deprecated_tools);
 
     // Expand ~ in path
     let db_path = shellexpand::tilde(&args.db_path).to_string();
@@ -487,36 +749,249 @@ fn main() -> Result<()> {
 
     // Create embedder
     // Determine dimensions: use explicit config, or default based on model
-    let dimensions = args.openai_embedding_dimensions.unwrap_or_else(|| {
-        if args.embedding_model == "openai" {
-            1536 // Default for text-embedding-3-small
-        } else {
-            384 // Default for TF-IDF
-        }
-    });
+    let default_dimensions = match args.embedding_model.as_str() {
+        "openai" => 1536,            // Default for text-embedding-3-small
+        "cohere" | "voyage" => 1024, // Default for embed-english-v3.0 and voyage-2
+        _ => 384,                    // Default for TF-IDF and all-MiniLM-L6-v2
+    };
+    let dimensions = args
+        .openai_embedding_dimensions
+        .unwrap_or(default_dimensions);
+    // Roughly 4 characters per token of the model's input limit
+    let default_summarize_over_chars = match args.embedding_model.as_str() {
+        "openai" => Some(30_000),          // 8191 tokens
+        "voyage" => Some(16_000),          // 4000 tokens
+        "cohere" => Some(8_000),           // 2048 tokens
+        "hf" => Some(2_000),               // 512 tokens
+        "local" | "candle" => Some(1_000), // 256 tokens
+        _ => None,                         // TF-IDF reads the whole text
+    };
+
+    // Cohere, Voyage and Hugging Face take their own key and model; the
+    // OpenAI base URL and model name only apply to OpenAI-compatible APIs
+    let (api_key, base_url, embedding_model) = match args.embedding_model.as_str() {
+        "cohere" => (args.cohere_key, None, args.provider_embedding_model),
+        "voyage" => (args.voyage_key, None, args.provider_embedding_model),
+        "hf" => (
+            args.hf_token,
+            args.hf_base_url,
+            args.provider_embedding_model,
+        ),
+        _ => (
+            args.openai_key,
+            if args.openai_base_url == "https://api.openai.com/v1" {
+                None // Use default
+            } else {
+                Some(args.openai_base_url)
+            },
+            Some(args.openai_embedding_model),
+        ),
+    };
 
     let embedding_config = EmbeddingConfig {
         model: args.embedding_model,
-        api_key: args.openai_key,
-        base_url: if args.openai_base_url == "https://api.openai.com/v1" {
-            None // Use default
-        } else {
-            Some(args.openai_base_url)
-        },
-        embedding_model: Some(args.openai_embedding_model),
-        model_path: None,
+        api_key,
+        base_url,
+        embedding_model,
+        model_path: args
+            .local_model_dir
+            .map(|dir| shellexpand::tilde(&dir).to_string()),
         dimensions,
         batch_size: 100,
+        worker_concurrency: 2,
+        normalization: match args.embedding_normalize {
+            Some(ref steps) => steps.parse()?,
+            None => Default::default(),
+        },
+        summarize_over_chars: args
+            .embed_summarize_over_chars
+            .or(default_summarize_over_chars),
+        summarizer: args.embed_summarizer,
+        query_prefix: args.embedding_query_prefix,
+        document_prefix: args.embedding_document_prefix,
+        max_input_tokens: args.embed_max_tokens,
+        long_text: args.embed_long_text.parse()?,
+        device: args.embedding_device,
+        inference_batch_size: args.embed_inference_batch,
+        max_batch_tokens: args.embed_max_batch_tokens,
+    };
+    let mut embedder = create_embedder(&embedding_config)?;
+    let uses_tfidf = embedding_config.model == "tfidf" || args.hybrid_sparse_dims > 0;
+    let vocabulary = if uses_tfidf {
+        // Weight terms by the stored vocabulary, so vectors stay comparable
+        // across restarts, and follow the counts new memories add to it
+        let vocabulary =
+            Arc::new(storage.with_transaction(engram::embedding::TfIdfVocabulary::open)?);
+        let refresh_vocabulary = vocabulary.clone();
+        let refresh_storage = storage.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(std::time::Duration::from_secs(30));
+            if let Err(e) = refresh_storage.with_connection(|conn| refresh_vocabulary.refresh(conn))
+            {
+                tracing::warn!("TF-IDF vocabulary refresh failed: {}", e);
+            }
+        });
+        Some(vocabulary)
+    } else {
+        None
     };
-    let embedder = create_embedder(&embedding_config)?;
+    if let Some(vocabulary) = vocabulary
+        .as_ref()
+        .filter(|_| embedding_config.model == "tfidf")
+    {
+        embedder = engram::embedding::summarize_long_texts(
+            engram::embedding::normalize_embedder(
+                Arc::new(
+                    engram::embedding::TfIdfEmbedder::new(embedder.dimensions())
+                        .with_vocabulary(vocabulary.clone()),
+                ),
+                &embedding_config.normalization,
+            ),
+            &embedding_config,
+        )?;
+    }
+    if let Some(ref reduction) = args.embedding_reduction {
+        let handle = Arc::new(engram::embedding::ReductionHandle::new(reduction.parse()?));
+        storage.with_connection(|conn| handle.load(conn))?;
+        embedder = Arc::new(engram::embedding::ReducingEmbedder::new(embedder, handle));
+    }
+    if let Some(vocabulary) = vocabulary.filter(|_| args.hybrid_sparse_dims > 0) {
+        let sparse = engram::embedding::normalize_embedder(
+            Arc::new(
+                engram::embedding::TfIdfEmbedder::new(args.hybrid_sparse_dims)
+                    .with_vocabulary(vocabulary),
+            ),
+            &embedding_config.normalization,
+        );
+        embedder = Arc::new(
+            engram::embedding::HybridEmbedder::new(embedder, sparse)
+                .with_sparse_weight(args.hybrid_sparse_weight),
+        );
+    }
+
+    // Similarity is only computed against vectors of this model, or of older
+    // models bridged into its space by an embedding adapter
+    storage.with_connection(|conn| {
+        engram::embedding::set_active_embedding_model(conn, embedder.model_name())
+    })?;
+
+    // Shared with the handlers; migrations and the embedding worker add the
+    // vectors they store to it
+    let vector_index = Arc::new(engram::search::vector_index::VectorIndexHandle::new(
+        engram::search::vector_index::VectorIndexConfig::from_env(),
+    ));
+
+    // Pick up an embedding migration interrupted by a restart, as long as
+    // this server still embeds with the migration's target model.
+    if let Some(migration) =
+        storage.with_connection(engram::embedding::active_embedding_migration)?
+    {
+        if migration.status != engram::embedding::MigrationStatus::Running {
+            tracing::info!(
+                "Embedding migration {} is {}",
+                migration.id,
+                migration.status.as_str()
+            );
+        } else if migration.target_model == embedder.model_name()
+            && migration.target_dimensions == embedder.dimensions()
+        {
+            tracing::info!(
+                "Resuming embedding migration {} at memory {} ({}/{})",
+                migration.id,
+                migration.cursor,
+                migration.items_embedded + migration.items_skipped,
+                migration.items_total
+            );
+            engram::embedding::spawn_embedding_migration(
+                storage.clone(),
+                embedder.clone(),
+                migration.id,
+                Some(vector_index.clone()),
+            )?;
+        } else {
+            tracing::warn!(
+                "Embedding migration {} targets {} but the server embeds with {}; not resuming",
+                migration.id,
+                migration.target_model,
+                embedder.model_name()
+            );
+        }
+    }
 
     // Create real-time manager.
     // Always created so both the WebSocket server (when ws_port > 0) and
     // the HTTP SSE endpoint (GET /v1/events) can share the same broadcast channel.
-    let realtime_manager = Some(RealtimeManager::new());
+    let coalescing = match args.event_coalesce_ms {
+        0 => CoalescingConfig::new(),
+        ms => CoalescingConfig::memory_writes(std::time::Duration::from_millis(ms)),
+    };
+    let heartbeat = std::time::Duration::from_secs(args.ws_heartbeat_secs.max(1));
+    let mut realtime = RealtimeManager::new()
+        .with_coalescing(coalescing)
+        .with_event_log(Arc::new(StorageEventLog::new(storage.clone())))
+        .with_heartbeat_interval(heartbeat)
+        .with_graph_explorer(Arc::new(engram::graph::GraphExplorer::new(storage.clone())));
+    if let Some(ref secret) = args.ws_resume_secret {
+        realtime = realtime.with_resume_secret(secret);
+    }
+    let realtime_manager = Some(realtime);
 
     // Create handler and server
     let mut handler = EngramHandler::new(storage.clone(), embedder);
+    handler.search_config.sparse_weight = args.hybrid_sparse_weight;
+    handler.vector_index = vector_index;
+    handler.expired_grace_days = args.expired_grace_days.max(0);
+
+    // Embed queued memories in the background. Interactive writes are
+    // submitted to the worker's high lane by their handlers; everything else
+    // pending (bulk ingest, batches, rows left by a restart) is fed to the
+    // low lane every few seconds. Embeddings land in the shared cache and
+    // the vector index.
+    let embedding_queue = engram::embedding::EmbeddingQueue::new(embedding_config.batch_size);
+    handler.embedding_queue = Some(embedding_queue.clone());
+    let worker = engram::embedding::EmbeddingWorker::with_embedder(
+        handler.embedder.clone(),
+        &embedding_config,
+        embedding_queue.clone(),
+        storage.shared_connection(),
+    )
+    .with_cache(handler.embedding_cache.clone())
+    .with_vector_index(handler.vector_index.clone());
+    let feed_storage = storage.clone();
+    std::thread::Builder::new()
+        .name("engram-embedding-worker".to_string())
+        .spawn(move || {
+            let rt = match tokio::runtime::Runtime::new() {
+                Ok(rt) => rt,
+                Err(e) => {
+                    tracing::error!("Failed to start embedding worker runtime: {}", e);
+                    return;
+                }
+            };
+            rt.block_on(async move {
+                tokio::spawn(async move {
+                    let mut ticks = tokio::time::interval(EMBEDDING_FEED_INTERVAL);
+                    loop {
+                        ticks.tick().await;
+                        let storage = feed_storage.clone();
+                        let queue = embedding_queue.clone();
+                        let fed = tokio::task::spawn_blocking(move || {
+                            storage.with_connection(|conn| queue.feed_pending(conn))
+                        })
+                        .await;
+                        match fed {
+                            Ok(Ok(0)) => {}
+                            Ok(Ok(count)) => {
+                                tracing::debug!("Queued {} memories for embedding", count)
+                            }
+                            Ok(Err(e)) => tracing::warn!("Embedding queue feed failed: {}", e),
+                            Err(e) => tracing::warn!("Embedding queue feed panicked: {}", e),
+                        }
+                    }
+                });
+                worker.run().await;
+            });
+        })?;
     if let Some(ref manager) = realtime_manager {
         handler = handler.with_realtime(manager.clone());
     }
@@ -526,13 +1001,68 @@ fn main() -> Result<()> {
         handler.meili_indexer = meili_indexer_for_handler;
         handler.meili_sync_interval = meili_sync_interval;
     }
+    if let Some(ref backend) = args.rerank_backend {
+        let backend = match backend.as_str() {
+            "onnx" => CrossEncoderBackend::Onnx {
+                model_dir: args
+                    .rerank_model
+                    .as_deref()
+                    .map(|dir| shellexpand::tilde(dir).to_string().into())
+                    .ok_or_else(|| {
+                        engram::error::EngramError::Config(
+                            "The onnx reranker needs --rerank-model <model dir>".to_string(),
+                        )
+                    })?,
+            },
+            provider => CrossEncoderBackend::Api {
+                provider: provider.parse()?,
+                model: args.rerank_model.clone(),
+                base_url: args.rerank_url.clone(),
+                api_key: args.rerank_api_key.clone(),
+            },
+        };
+        let cross_encoder = create_cross_encoder(&backend)?;
+        tracing::info!(
+            "Reranking with cross-encoder {}",
+            cross_encoder.model_name()
+        );
+        handler.cross_encoder = Some(cross_encoder);
+    }
+    let federation_path = args
+        .federation_config
+        .as_deref()
+        .map(|path| std::path::PathBuf::from(shellexpand::tilde(path).to_string()));
+    if let Some(config) =
+        engram::storage::FederationConfig::load_optional(federation_path.as_deref())?
+    {
+        let federation = engram::storage::Federation::open(&config)?;
+        tracing::info!("Federated search across {}", federation.labels().join(", "));
+        handler.federation = Some(Arc::new(federation));
+    }
+    if args.access_flush_interval_seconds == 0 {
+        handler.memory_cache =
+            Arc::new(engram::storage::MemoryCache::default().with_access_flush_threshold(1));
+    }
+    handler.memory_cache.watch(&storage);
     let handler = Arc::new(handler);
     let server = McpServer::new(handler.clone());
 
+    // Keep the memory cache coherent with writes that are only announced on
+    // the realtime channel (sync pulls, other transports).
+    if let Some(ref manager) = realtime_manager {
+        let cache = handler.memory_cache.clone();
+        let events = manager.subscribe();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+            rt.block_on(engram::storage::memory_cache::follow_events(cache, events));
+        });
+    }
+
     // Start background cleanup thread if enabled
     if args.cleanup_interval_seconds > 0 {
         let cleanup_storage = storage.clone();
         let interval = std::time::Duration::from_secs(args.cleanup_interval_seconds);
+        let grace_days = args.expired_grace_days.max(0);
 
         std::thread::spawn(move || {
             tracing::info!(
@@ -544,7 +1074,7 @@ fn main() -> Result<()> {
                 std::thread::sleep(interval);
 
                 match cleanup_storage.with_transaction(|conn| {
-                    engram::storage::queries::cleanup_expired_memories(conn)
+                    engram::storage::queries::cleanup_expired_memories(conn, grace_days)
                 }) {
                     Ok(deleted) => {
                         if deleted > 0 {
@@ -597,6 +1127,162 @@ fn main() -> Result<()> {
         });
     }
 
+    // Start background fact review escalation if enabled
+    if args.fact_review_interval_seconds > 0 {
+        let review_storage = storage.clone();
+        let interval = std::time::Duration::from_secs(args.fact_review_interval_seconds);
+
+        std::thread::spawn(move || {
+            tracing::info!(
+                "Fact review escalation started (interval: {}s)",
+                interval.as_secs()
+            );
+
+            let config = engram::intelligence::EscalationConfig::default();
+            loop {
+                std::thread::sleep(interval);
+
+                match review_storage.with_transaction(|conn| {
+                    engram::intelligence::escalate_unverified_facts(conn, &config)
+                }) {
+                    Ok(queued) => {
+                        if !queued.is_empty() {
+                            tracing::info!("Queued {} unverified facts for review", queued.len());
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Fact review escalation error: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    // Start background freshness contract checks if enabled
+    if args.freshness_check_interval_seconds > 0 {
+        let freshness_storage = storage.clone();
+        let interval = std::time::Duration::from_secs(args.freshness_check_interval_seconds);
+
+        std::thread::spawn(move || {
+            tracing::info!(
+                "Freshness contract check started (interval: {}s)",
+                interval.as_secs()
+            );
+
+            loop {
+                match freshness_storage.with_transaction(|conn| {
+                    engram::intelligence::check_freshness_contracts(conn, chrono::Utc::now())
+                }) {
+                    Ok(report) => {
+                        if report.newly_flagged > 0 || report.cleared > 0 {
+                            tracing::info!(
+                                "Freshness check flagged {} and cleared {} memories ({} violating)",
+                                report.newly_flagged,
+                                report.cleared,
+                                report.violating
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Freshness check error: {}", e);
+                    }
+                }
+
+                std::thread::sleep(interval);
+            }
+        });
+    }
+
+    // Start periodic write-back of buffered access counts if enabled
+    if args.access_flush_interval_seconds > 0 {
+        let flush_storage = storage.clone();
+        let cache = handler.memory_cache.clone();
+        let interval = std::time::Duration::from_secs(args.access_flush_interval_seconds);
+
+        std::thread::spawn(move || {
+            tracing::info!(
+                "Access count flush started (interval: {}s)",
+                interval.as_secs()
+            );
+
+            loop {
+                std::thread::sleep(interval);
+
+                if let Err(e) = flush_storage.with_transaction(|conn| cache.flush_accesses(conn)) {
+                    tracing::error!("Access count flush error: {}", e);
+                }
+            }
+        });
+    }
+
+    // Start periodic memory recommendations for active sessions if enabled
+    if args.recommendation_interval_seconds > 0 {
+        if let Some(ref manager) = realtime_manager {
+            let recommend_storage = storage.clone();
+            let embedder = handler.embedder.clone();
+            let manager = manager.clone();
+            let interval = std::time::Duration::from_secs(args.recommendation_interval_seconds);
+
+            std::thread::spawn(move || {
+                tracing::info!(
+                    "Memory recommendations started (interval: {}s)",
+                    interval.as_secs()
+                );
+
+                let mut tracker = engram::intelligence::RecommendationTracker::new();
+                loop {
+                    std::thread::sleep(interval);
+
+                    let since = chrono::Utc::now() - chrono::Duration::hours(1);
+                    let sessions = match recommend_storage
+                        .with_connection(|conn| engram::intelligence::active_sessions(conn, since))
+                    {
+                        Ok(sessions) => sessions,
+                        Err(e) => {
+                            tracing::error!("Recommendation error: {}", e);
+                            continue;
+                        }
+                    };
+                    tracker.retain_sessions(&sessions);
+
+                    for session_id in sessions {
+                        let options = engram::intelligence::RecommendationOptions {
+                            session_id: Some(session_id.clone()),
+                            ..Default::default()
+                        };
+                        match recommend_storage.with_connection(|conn| {
+                            engram::intelligence::recommend_memories(
+                                conn,
+                                Some(embedder.as_ref()),
+                                &options,
+                            )
+                        }) {
+                            Ok(set) => {
+                                let new = tracker.take_new(&session_id, set.recommendations);
+                                if !new.is_empty() {
+                                    manager.broadcast(
+                                        engram::realtime::RealtimeEvent::memory_suggested(
+                                            &session_id,
+                                            &set.workspace,
+                                            &new,
+                                        ),
+                                    );
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!(
+                                    "Recommendation error for session {}: {}",
+                                    session_id,
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    }
+
     // Start WebSocket server in background if ws_port > 0.
     // Clone the manager so it can also be shared with the HTTP transport SSE endpoint.
     if args.ws_port > 0 {
@@ -616,51 +1302,96 @@ fn main() -> Result<()> {
         }
     }
 
+    // Keep a live knowledge graph and stream its mutations to graph
+    // subscribers (`/ws/graph` on the WebSocket server, `/v1/graph/ws` on
+    // the HTTP transport) whenever something can show them.
+    if args.ws_port > 0 || args.dashboard {
+        if let Some(ref manager) = realtime_manager {
+            let graph_storage = storage.clone();
+            let graph_manager = manager.clone();
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+                rt.block_on(async {
+                    match graph_storage.with_connection(|conn| {
+                        GraphBuilder::load(conn, LIVE_GRAPH_MAX_NODES, LabelOptions::default())
+                    }) {
+                        Ok(builder) => {
+                            tracing::info!(
+                                "Live graph loaded ({} nodes, {} edges)",
+                                builder.node_count(),
+                                builder.edge_count()
+                            );
+                            let builder = Arc::new(RwLock::new(builder));
+                            publish_mutations(builder, graph_storage, graph_manager).await;
+                        }
+                        Err(e) => tracing::error!("Failed to load live graph: {}", e),
+                    }
+                });
+            });
+        }
+    }
+
     tracing::info!("Engram MCP server starting...");
 
-    match args.transport {
-        TransportMode::Stdio => {
-            server.run()?;
-        }
+    // Cache-hit access counts not yet written back are flushed on shutdown:
+    // when stdin closes, or on Ctrl+C/SIGTERM for the network transports
+    let shutdown_storage = storage.clone();
+    let shutdown_cache = handler.memory_cache.clone();
+
+    let served: engram::error::Result<()> = match args.transport {
+        TransportMode::Stdio => server.run(),
         TransportMode::Http => {
             let rt = tokio::runtime::Runtime::new()
                 .map_err(|e| engram::error::EngramError::Internal(e.to_string()))?;
             rt.block_on(async {
-                http_transport::serve_http(
+                http_transport::serve_http_with_shutdown(
                     handler,
                     args.http_port,
                     args.http_api_key,
                     realtime_manager,
+                    args.dashboard,
+                    shutdown_signal(),
                 )
                 .await
                 .map_err(|e| engram::error::EngramError::Internal(e.to_string()))
-            })?;
+            })
         }
         TransportMode::Both => {
             let http_handler = handler.clone();
             let http_port = args.http_port;
             let http_api_key = args.http_api_key.clone();
             let http_realtime = realtime_manager.clone();
+            let http_dashboard = args.dashboard;
+            let http_storage = storage.clone();
+            let http_cache = handler.memory_cache.clone();
 
             std::thread::spawn(move || {
                 let rt = tokio::runtime::Runtime::new()
                     .expect("Failed to create HTTP transport runtime");
-                rt.block_on(async {
-                    if let Err(e) = http_transport::serve_http(
+                let stopped = rt.block_on(async {
+                    http_transport::serve_http_with_shutdown(
                         http_handler,
                         http_port,
                         http_api_key,
                         http_realtime,
+                        http_dashboard,
+                        shutdown_signal(),
                     )
                     .await
-                    {
-                        tracing::error!("HTTP transport error: {}", e);
-                    }
                 });
+                match stopped {
+                    Err(e) => tracing::error!("HTTP transport error: {}", e),
+                    // The signal handler replaced the default termination,
+                    // so stdio (blocked on stdin) is stopped along with HTTP
+                    Ok(()) => {
+                        flush_pending_accesses(&http_storage, &http_cache);
+                        std::process::exit(0);
+                    }
+                }
             });
 
             // Run stdio in the main thread
-            server.run()?;
+            server.run()
         }
         #[cfg(feature = "grpc")]
         TransportMode::Grpc => {
@@ -669,19 +1400,59 @@ fn main() -> Result<()> {
             let rt = tokio::runtime::Runtime::new()
                 .map_err(|e| engram::error::EngramError::Internal(e.to_string()))?;
             rt.block_on(async {
-                grpc_transport::serve_grpc(
+                grpc_transport::serve_grpc_with_shutdown(
                     handler,
                     args.grpc_port,
                     args.grpc_api_key,
                     realtime_manager,
+                    shutdown_signal(),
                 )
                 .await
                 .map_err(|e| engram::error::EngramError::Internal(e.to_string()))
-            })?;
+            })
+        }
+    };
+
+    flush_pending_accesses(&shutdown_storage, &shutdown_cache);
+
+    served
+}
+
+/// Completes on Ctrl+C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
         }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
+    tracing::info!("Shutdown signal received, stopping...");
+}
 
-    Ok(())
+/// Write back cache-hit access counts before the process exits
+fn flush_pending_accesses(storage: &Storage, cache: &engram::storage::MemoryCache) {
+    if let Err(e) = storage.with_transaction(|conn| cache.flush_accesses(conn)) {
+        tracing::error!("Final access count flush error: {}", e);
+    }
 }
 
 #[cfg(test)]
@@ -696,11 +1467,18 @@ mod tests {
             search_cache: Arc::new(engram::search::result_cache::SearchResultCache::new(
                 Default::default(),
             )),
+            memory_cache: Arc::new(engram::storage::MemoryCache::default()),
+            persona: Arc::new(engram::storage::ActivePersona::new()),
+            vector_index: Arc::new(engram::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: engram::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             embedder,
             fuzzy_engine: Arc::new(Mutex::new(FuzzyEngine::new())),
             search_config: SearchConfig::default(),
             realtime: None,
             embedding_cache: Arc::new(engram::embedding::EmbeddingCache::default()),
+            embedding_queue: None,
             #[cfg(feature = "langfuse")]
             langfuse_runtime: tokio::runtime::Runtime::new()
                 .expect("Failed to create Langfuse runtime"),
@@ -729,6 +1507,7 @@ mod tests {
                 "path": file_path.to_string_lossy(),
                 "format": "md"
             }),
+            CancelHandle::new(),
         );
         assert!(first.get("error").is_none(), "first ingest error: {first}");
         assert!(
@@ -745,6 +1524,7 @@ mod tests {
                 "path": file_path.to_string_lossy(),
                 "format": "md"
             }),
+            CancelHandle::new(),
         );
         assert!(
             second.get("error").is_none(),
@@ -0,0 +1,322 @@
+//! Cooperative time budgets and cancellation for tool calls
+//!
+//! Tool dispatch runs every call under a budget: a deadline plus a
+//! cancellation flag. Long-running loops in storage, graph and ingest code
+//! call [`checkpoint`] between units of work and stop early once it returns
+//! `true`, keeping what they have so far; dispatch then marks the response
+//! `truncated: true`.
+//!
+//! The budget lives in a thread-local because a tool call runs on the thread
+//! that dispatched it. Outside a call [`checkpoint`] always returns `false`.
+//!
+//! A call's deadline comes from, in order: its `timeout_ms` argument, the
+//! tool's entry in `ENGRAM_TOOL_TIMEOUTS` (`tool=ms,tool=ms`),
+//! `ENGRAM_TOOL_TIMEOUT_MS`, or [`DEFAULT_TOOL_TIMEOUT_MS`]. `0` disables it.
+//! In-flight requests registered with [`register_request`] can be cancelled
+//! by id with [`request_cancel`]. JSON-RPC ids are only unique per client, so
+//! both look ids up in the caller's scope, which transports set with
+//! [`with_request_scope`] (an HTTP session or API key user, say).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde_json::Value;
+
+/// Default time budget of a tool call
+pub const DEFAULT_TOOL_TIMEOUT_MS: u64 = 30_000;
+
+/// Environment variable overriding [`DEFAULT_TOOL_TIMEOUT_MS`]
+pub const TOOL_TIMEOUT_ENV: &str = "ENGRAM_TOOL_TIMEOUT_MS";
+
+/// Environment variable with per-tool budgets, e.g.
+/// `memory_traverse=5000,memory_export=120000`
+pub const TOOL_TIMEOUTS_ENV: &str = "ENGRAM_TOOL_TIMEOUTS";
+
+/// Shared flag that cancels the call it was entered with
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the call to stop at its next checkpoint
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+struct Frame {
+    deadline: Option<Instant>,
+    cancel: CancelHandle,
+    truncated: bool,
+}
+
+thread_local! {
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An entered budget; leaving it on drop restores the enclosing one
+pub struct BudgetGuard {
+    depth: usize,
+    // Frames are per thread, so the guard must stay on the entering thread
+    _not_send: PhantomData<*const ()>,
+}
+
+impl BudgetGuard {
+    /// Whether a checkpoint stopped work early under this budget
+    pub fn truncated(&self) -> bool {
+        FRAMES.with(|frames| {
+            frames
+                .borrow()
+                .get(self.depth - 1)
+                .is_some_and(|frame| frame.truncated)
+        })
+    }
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        FRAMES.with(|frames| frames.borrow_mut().truncate(self.depth - 1));
+    }
+}
+
+/// Run the rest of the current scope under a budget of `timeout`
+/// (`None` = no deadline) that `cancel` can cut short.
+///
+/// Budgets nest: an inner call also stops when an enclosing budget runs out.
+pub fn enter(timeout: Option<Duration>, cancel: CancelHandle) -> BudgetGuard {
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        frames.push(Frame {
+            deadline: timeout.map(|t| Instant::now() + t),
+            cancel,
+            truncated: false,
+        });
+        BudgetGuard {
+            depth: frames.len(),
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Returns `true` when the current call is out of time or cancelled and
+/// should stop, returning partial results.
+pub fn checkpoint() -> bool {
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let now = Instant::now();
+        let stop = frames
+            .iter()
+            .any(|frame| frame.cancel.is_cancelled() || frame.deadline.is_some_and(|d| now >= d));
+        if stop {
+            for frame in frames.iter_mut() {
+                frame.truncated = true;
+            }
+        }
+        stop
+    })
+}
+
+/// Time budget for a call to `tool_name` with `params`
+pub fn tool_timeout(tool_name: &str, params: &Value) -> Option<Duration> {
+    let ms = params
+        .get("timeout_ms")
+        .and_then(|v| v.as_u64())
+        .or_else(|| {
+            std::env::var(TOOL_TIMEOUTS_ENV)
+                .ok()
+                .and_then(|spec| per_tool_timeout(&spec, tool_name))
+        })
+        .or_else(|| {
+            std::env::var(TOOL_TIMEOUT_ENV)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+        })
+        .unwrap_or(DEFAULT_TOOL_TIMEOUT_MS);
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+fn per_tool_timeout(spec: &str, tool_name: &str) -> Option<u64> {
+    spec.split(',').find_map(|entry| {
+        let (name, ms) = entry.split_once('=')?;
+        (name.trim() == tool_name).then(|| ms.trim().parse().ok())?
+    })
+}
+
+// =============================================================================
+// In-flight requests
+// =============================================================================
+
+/// Registered requests by (scope, id). A client may reuse an id while an
+/// earlier request with it still runs, so each key holds every such request.
+static IN_FLIGHT: Lazy<Mutex<HashMap<(String, String), Vec<CancelHandle>>>> =
+    Lazy::new(Default::default);
+
+thread_local! {
+    static REQUEST_SCOPE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Restores the enclosing request scope on drop
+struct ScopeGuard(String);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.0);
+        REQUEST_SCOPE.with(|scope| *scope.borrow_mut() = previous);
+    }
+}
+
+/// Run `f` with request ids registered and cancelled in `scope`, so clients
+/// sharing a server can't cancel each other's requests. Outside any scope
+/// ids share the empty scope, which suits a single stdio client.
+pub fn with_request_scope<T>(scope: &str, f: impl FnOnce() -> T) -> T {
+    let previous = REQUEST_SCOPE.with(|current| current.replace(scope.to_string()));
+    let _guard = ScopeGuard(previous);
+    f()
+}
+
+fn request_key(id: &Value) -> (String, String) {
+    let id = match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    (REQUEST_SCOPE.with(|scope| scope.borrow().clone()), id)
+}
+
+/// A request that can be cancelled by id until this is dropped
+pub struct RequestRegistration {
+    key: (String, String),
+    handle: CancelHandle,
+}
+
+impl RequestRegistration {
+    /// Handle to enter the request's budget with
+    pub fn handle(&self) -> CancelHandle {
+        self.handle.clone()
+    }
+}
+
+impl Drop for RequestRegistration {
+    fn drop(&mut self) {
+        let mut in_flight = IN_FLIGHT.lock();
+        if let Some(handles) = in_flight.get_mut(&self.key) {
+            handles.retain(|handle| !Arc::ptr_eq(&handle.0, &self.handle.0));
+            if handles.is_empty() {
+                in_flight.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// Register an in-flight request under its JSON-RPC id in the current
+/// request scope
+pub fn register_request(id: &Value) -> RequestRegistration {
+    let key = request_key(id);
+    let handle = CancelHandle::new();
+    IN_FLIGHT
+        .lock()
+        .entry(key.clone())
+        .or_default()
+        .push(handle.clone());
+    RequestRegistration { key, handle }
+}
+
+/// Cancel an in-flight request of the current request scope by id. Returns
+/// `false` if no such request is running.
+pub fn request_cancel(id: &Value) -> bool {
+    match IN_FLIGHT.lock().get(&request_key(id)) {
+        Some(handles) => {
+            handles.iter().for_each(CancelHandle::cancel);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_checkpoint_stops_at_deadline_or_cancel() {
+        assert!(!checkpoint());
+
+        let outer = enter(Some(Duration::from_secs(60)), CancelHandle::new());
+        assert!(!checkpoint());
+        {
+            let inner = enter(Some(Duration::ZERO), CancelHandle::new());
+            assert!(checkpoint());
+            assert!(inner.truncated());
+        }
+        // The inner call's truncation shows in the outer result, but the
+        // outer budget itself still has time
+        assert!(outer.truncated());
+        assert!(!checkpoint());
+        drop(outer);
+
+        let registration = register_request(&json!(7));
+        let _guard = enter(None, registration.handle());
+        assert!(!checkpoint());
+        assert!(request_cancel(&json!(7)));
+        assert!(checkpoint());
+        drop(registration);
+        assert!(!request_cancel(&json!(7)));
+    }
+
+    #[test]
+    fn test_request_ids_are_scoped() {
+        let first = with_request_scope("session:a", || register_request(&json!(1)));
+        let second = with_request_scope("session:b", || register_request(&json!(1)));
+
+        assert!(!request_cancel(&json!(1)));
+        assert!(with_request_scope("session:b", || request_cancel(&json!(
+            1
+        ))));
+        assert!(!first.handle().is_cancelled());
+        assert!(second.handle().is_cancelled());
+
+        // Finishing one request leaves a reused id's other request registered
+        let reused = with_request_scope("session:a", || register_request(&json!(1)));
+        drop(first);
+        assert!(with_request_scope("session:a", || request_cancel(&json!(
+            1
+        ))));
+        assert!(reused.handle().is_cancelled());
+    }
+
+    #[test]
+    fn test_tool_timeout_resolution() {
+        assert_eq!(
+            tool_timeout("memory_traverse", &json!({"timeout_ms": 250})),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            tool_timeout("memory_traverse", &json!({"timeout_ms": 0})),
+            None
+        );
+        assert_eq!(
+            per_tool_timeout(
+                "memory_export=120000, memory_traverse = 500",
+                "memory_traverse"
+            ),
+            Some(500)
+        );
+        assert_eq!(
+            per_tool_timeout("memory_export=120000", "memory_traverse"),
+            None
+        );
+    }
+}
@@ -0,0 +1,633 @@
+//! Linear adapters between embedding spaces
+//!
+//! Vectors from different models live in unrelated spaces, so comparing a
+//! query embedded with the current model against memories still embedded
+//! with an older one gives meaningless scores. Once an active model is
+//! recorded with [`set_active_embedding_model`], [`get_embedding`] and the
+//! vector index only return vectors of that model.
+//!
+//! While a migration to a new model is still running, older vectors can stay
+//! searchable through a [`LinearAdapter`]: [`fit_embedding_adapter`]
+//! re-embeds a sample of old-model memories with the current model, fits a
+//! ridge-regularized linear map from the old space to the new one on those
+//! pairs, and stores it in `embedding_adapters`. Old vectors are then
+//! projected on read until their re-embedding lands.
+//!
+//! [`get_embedding`]: super::get_embedding
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::{cosine_similarity, Embedder};
+use crate::error::{EngramError, Result};
+use crate::storage::Storage;
+use crate::types::MemoryId;
+
+/// Share of the sample held out to score a fitted adapter.
+const HOLDOUT_FRACTION: usize = 5;
+
+/// Adapters loaded from `embedding_adapters`, keyed by
+/// `source_model:source_dims:target_model:fitted_at`.
+static ADAPTERS: Lazy<RwLock<HashMap<String, Arc<LinearAdapter>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+// ---------------------------------------------------------------------------
+// Adapter
+// ---------------------------------------------------------------------------
+
+/// Affine map from one model's embedding space into another's.
+#[derive(Debug, Clone)]
+pub struct LinearAdapter {
+    source_dims: usize,
+    target_dims: usize,
+    /// `target_dims` rows of `source_dims` weights followed by a bias.
+    weights: Vec<f32>,
+}
+
+impl LinearAdapter {
+    /// Fit by ridge regression on `(source, target)` vector pairs.
+    pub fn fit(pairs: &[(Vec<f32>, Vec<f32>)], ridge: f64) -> Result<Self> {
+        let (first_source, first_target) = pairs.first().ok_or_else(|| {
+            EngramError::InvalidInput("No vector pairs to fit an adapter on".to_string())
+        })?;
+        let (source_dims, target_dims) = (first_source.len(), first_target.len());
+        if pairs
+            .iter()
+            .any(|(s, t)| s.len() != source_dims || t.len() != target_dims)
+        {
+            return Err(EngramError::InvalidInput(
+                "Adapter training pairs have mixed dimensions".to_string(),
+            ));
+        }
+
+        let n = pairs.len();
+        let q = source_dims + 1;
+        let x: Vec<f64> = pairs
+            .iter()
+            .flat_map(|(s, _)| s.iter().map(|&v| v as f64).chain(std::iter::once(1.0)))
+            .collect();
+        let y: Vec<f64> = pairs
+            .iter()
+            .flat_map(|(_, t)| t.iter().map(|&v| v as f64))
+            .collect();
+        let ridge = ridge.max(1e-9);
+
+        let mut weights = vec![0.0f32; target_dims * q];
+        if n <= q {
+            // Dual form: W = Yᵀ (X Xᵀ + λI)⁻¹ X, solving an n × n system
+            let mut gram = vec![0.0f64; n * n];
+            for i in 0..n {
+                for j in 0..=i {
+                    let dot: f64 = (0..q).map(|c| x[i * q + c] * x[j * q + c]).sum();
+                    gram[i * n + j] = dot;
+                    gram[j * n + i] = dot;
+                }
+                gram[i * n + i] += ridge;
+            }
+            let mut alpha = y;
+            cholesky_solve(&mut gram, n, &mut alpha, target_dims)?;
+            for r in 0..target_dims {
+                for i in 0..n {
+                    let a = alpha[i * target_dims + r];
+                    for c in 0..q {
+                        weights[r * q + c] += (a * x[i * q + c]) as f32;
+                    }
+                }
+            }
+        } else {
+            // Primal form: W = ((XᵀX + λI)⁻¹ XᵀY)ᵀ, solving a q × q system
+            let mut gram = vec![0.0f64; q * q];
+            let mut rhs = vec![0.0f64; q * target_dims];
+            for i in 0..n {
+                let row = &x[i * q..(i + 1) * q];
+                for a in 0..q {
+                    for b in 0..=a {
+                        gram[a * q + b] += row[a] * row[b];
+                    }
+                    for t in 0..target_dims {
+                        rhs[a * target_dims + t] += row[a] * y[i * target_dims + t];
+                    }
+                }
+            }
+            for a in 0..q {
+                for b in 0..a {
+                    gram[b * q + a] = gram[a * q + b];
+                }
+                gram[a * q + a] += ridge;
+            }
+            cholesky_solve(&mut gram, q, &mut rhs, target_dims)?;
+            for r in 0..target_dims {
+                for c in 0..q {
+                    weights[r * q + c] = rhs[c * target_dims + r] as f32;
+                }
+            }
+        }
+
+        Ok(Self {
+            source_dims,
+            target_dims,
+            weights,
+        })
+    }
+
+    /// Map `vector` into the target space, or `None` if it is not a vector
+    /// of the source space.
+    pub fn project(&self, vector: &[f32]) -> Option<Vec<f32>> {
+        if vector.len() != self.source_dims {
+            return None;
+        }
+        let q = self.source_dims + 1;
+        Some(
+            self.weights
+                .chunks_exact(q)
+                .map(|row| {
+                    row[..self.source_dims]
+                        .iter()
+                        .zip(vector)
+                        .map(|(w, v)| w * v)
+                        .sum::<f32>()
+                        + row[self.source_dims]
+                })
+                .collect(),
+        )
+    }
+
+    pub fn source_dims(&self) -> usize {
+        self.source_dims
+    }
+
+    pub fn target_dims(&self) -> usize {
+        self.target_dims
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.weights.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    fn from_bytes(source_dims: usize, target_dims: usize, bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != target_dims * (source_dims + 1) * 4 {
+            return Err(EngramError::Storage(format!(
+                "Embedding adapter has {} bytes, expected {}x{} weights",
+                bytes.len(),
+                target_dims,
+                source_dims + 1
+            )));
+        }
+        Ok(Self {
+            source_dims,
+            target_dims,
+            weights: bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+        })
+    }
+}
+
+/// Solve `a · x = b` in place for a symmetric positive definite `a`
+/// (`k × k`) and `m` right-hand sides (`b` is `k × m`, row-major).
+fn cholesky_solve(a: &mut [f64], k: usize, b: &mut [f64], m: usize) -> Result<()> {
+    for j in 0..k {
+        let mut diag = a[j * k + j];
+        for p in 0..j {
+            diag -= a[j * k + p] * a[j * k + p];
+        }
+        if diag <= 0.0 {
+            return Err(EngramError::Internal(
+                "Adapter fit is ill-conditioned; increase the ridge penalty".to_string(),
+            ));
+        }
+        let diag = diag.sqrt();
+        a[j * k + j] = diag;
+        for i in j + 1..k {
+            let mut sum = a[i * k + j];
+            for p in 0..j {
+                sum -= a[i * k + p] * a[j * k + p];
+            }
+            a[i * k + j] = sum / diag;
+        }
+    }
+    for c in 0..m {
+        for i in 0..k {
+            let mut sum = b[i * m + c];
+            for p in 0..i {
+                sum -= a[i * k + p] * b[p * m + c];
+            }
+            b[i * m + c] = sum / a[i * k + i];
+        }
+        for i in (0..k).rev() {
+            let mut sum = b[i * m + c];
+            for p in i + 1..k {
+                sum -= a[p * k + i] * b[p * m + c];
+            }
+            b[i * m + c] = sum / a[i * k + i];
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Active space
+// ---------------------------------------------------------------------------
+
+/// Record `model` as the model queries are embedded with.
+pub fn set_active_embedding_model(conn: &Connection, model: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO embedding_space (id, model, updated_at) VALUES (1, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            model = excluded.model,
+            updated_at = excluded.updated_at",
+        params![model, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// The model queries are embedded with, if one was recorded.
+pub fn active_embedding_model(conn: &Connection) -> Result<Option<String>> {
+    Ok(conn
+        .prepare_cached("SELECT model FROM embedding_space WHERE id = 1")?
+        .query_row([], |row| row.get(0))
+        .optional()?)
+}
+
+/// `vector`, stored under `model`, in a form comparable with the active
+/// model's vectors: unchanged if it is of that model (or none is recorded),
+/// projected if an adapter bridges the two, otherwise `None`.
+pub(crate) fn compatible_vector(
+    conn: &Connection,
+    active: Option<&str>,
+    model: &str,
+    vector: Vec<f32>,
+) -> Result<Option<Vec<f32>>> {
+    let Some(active) = active.filter(|active| *active != model) else {
+        return Ok(Some(vector));
+    };
+    Ok(load_adapter(conn, model, vector.len(), active)?
+        .and_then(|adapter| adapter.project(&vector)))
+}
+
+fn load_adapter(
+    conn: &Connection,
+    source_model: &str,
+    source_dims: usize,
+    target_model: &str,
+) -> Result<Option<Arc<LinearAdapter>>> {
+    let fitted_at: Option<String> = conn
+        .prepare_cached(
+            "SELECT fitted_at FROM embedding_adapters
+             WHERE source_model = ? AND source_dims = ? AND target_model = ?",
+        )?
+        .query_row(
+            params![source_model, source_dims as i64, target_model],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(fitted_at) = fitted_at else {
+        return Ok(None);
+    };
+
+    let key = format!(
+        "{}:{}:{}:{}",
+        source_model, source_dims, target_model, fitted_at
+    );
+    if let Some(adapter) = ADAPTERS.read().get(&key) {
+        return Ok(Some(adapter.clone()));
+    }
+    let (target_dims, weights): (i64, Vec<u8>) = conn.query_row(
+        "SELECT target_dims, weights FROM embedding_adapters
+         WHERE source_model = ? AND source_dims = ? AND target_model = ?",
+        params![source_model, source_dims as i64, target_model],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let adapter = Arc::new(LinearAdapter::from_bytes(
+        source_dims,
+        target_dims.max(0) as usize,
+        &weights,
+    )?);
+    ADAPTERS.write().insert(key, adapter.clone());
+    Ok(Some(adapter))
+}
+
+// ---------------------------------------------------------------------------
+// Fitting
+// ---------------------------------------------------------------------------
+
+/// Options for [`fit_embedding_adapter`].
+#[derive(Debug, Clone)]
+pub struct AdapterOptions {
+    /// Model to bridge from; defaults to the most common model other than
+    /// the embedder's.
+    pub source_model: Option<String>,
+    /// Memories to re-embed as training pairs.
+    pub sample_size: usize,
+    /// Ridge penalty of the regression.
+    pub ridge: f64,
+    pub seed: u64,
+}
+
+impl Default for AdapterOptions {
+    fn default() -> Self {
+        Self {
+            source_model: None,
+            sample_size: 256,
+            ridge: 0.01,
+            seed: 42,
+        }
+    }
+}
+
+/// A stored adapter.
+#[derive(Debug, Clone, Serialize)]
+pub struct AdapterInfo {
+    pub source_model: String,
+    pub source_dims: usize,
+    pub target_model: String,
+    pub target_dims: usize,
+    pub samples: usize,
+    /// Mean cosine similarity between projected and re-embedded vectors on
+    /// the held-out part of the sample.
+    pub holdout_cosine: Option<f32>,
+    pub fitted_at: String,
+    /// Vectors of the source model this adapter currently bridges.
+    pub bridged_embeddings: usize,
+}
+
+/// Fit and store an adapter from older vectors into `embedder`'s space.
+pub fn fit_embedding_adapter(
+    storage: &Storage,
+    embedder: &dyn Embedder,
+    options: &AdapterOptions,
+) -> Result<AdapterInfo> {
+    let target_model = embedder.model_name().to_string();
+    let (source_model, sample) = storage.with_connection(|conn| {
+        let source_model = match &options.source_model {
+            Some(model) => model.clone(),
+            None => conn
+                .query_row(
+                    "SELECT model FROM embeddings WHERE model != ?
+                     GROUP BY model ORDER BY COUNT(*) DESC, model LIMIT 1",
+                    params![target_model],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| {
+                    EngramError::InvalidInput(format!(
+                        "No embeddings of a model other than '{}' to adapt",
+                        target_model
+                    ))
+                })?,
+        };
+        let sample = sample_source_vectors(conn, &source_model, options)?;
+        Ok((source_model, sample))
+    })?;
+    if source_model == target_model {
+        return Err(EngramError::InvalidInput(
+            "Source and target embedding models are the same".to_string(),
+        ));
+    }
+    if sample.len() < 2 {
+        return Err(EngramError::InvalidInput(format!(
+            "Need at least 2 memories embedded with '{}' to fit an adapter",
+            source_model
+        )));
+    }
+
+    let texts: Vec<&str> = sample.iter().map(|(_, text, _)| text.as_str()).collect();
+    let targets = embedder.embed_batch(&texts)?;
+    let mut pairs: Vec<(Vec<f32>, Vec<f32>)> = sample
+        .into_iter()
+        .zip(targets)
+        .map(|((_, _, source), target)| (source, target))
+        .collect();
+    let source_dims = pairs[0].0.len();
+    pairs.retain(|(source, _)| source.len() == source_dims);
+
+    // Score on a held-out fifth, then refit on everything
+    let holdout = pairs.len() / HOLDOUT_FRACTION;
+    let holdout_cosine = if holdout > 0 {
+        let (test, train) = pairs.split_at(holdout);
+        let adapter = LinearAdapter::fit(train, options.ridge)?;
+        let total: f32 = test
+            .iter()
+            .filter_map(|(source, target)| {
+                adapter
+                    .project(source)
+                    .map(|projected| cosine_similarity(&projected, target))
+            })
+            .sum();
+        Some(total / test.len() as f32)
+    } else {
+        None
+    };
+    let adapter = LinearAdapter::fit(&pairs, options.ridge)?;
+
+    let fitted_at = Utc::now().to_rfc3339();
+    storage.with_transaction(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO embedding_adapters
+                (source_model, source_dims, target_model, target_dims, weights,
+                 samples, holdout_cosine, fitted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                source_model,
+                source_dims as i64,
+                target_model,
+                adapter.target_dims() as i64,
+                adapter.to_bytes(),
+                pairs.len() as i64,
+                holdout_cosine,
+                fitted_at
+            ],
+        )?;
+        Ok(())
+    })?;
+
+    storage.with_connection(|conn| {
+        list_embedding_adapters(conn)?
+            .into_iter()
+            .find(|info| {
+                info.source_model == source_model
+                    && info.source_dims == source_dims
+                    && info.target_model == target_model
+            })
+            .ok_or_else(|| EngramError::Storage("Stored embedding adapter not found".to_string()))
+    })
+}
+
+/// Randomly chosen memories embedded with `model`: id, content and vector.
+fn sample_source_vectors(
+    conn: &Connection,
+    model: &str,
+    options: &AdapterOptions,
+) -> Result<Vec<(MemoryId, String, Vec<f32>)>> {
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.content, e.embedding
+         FROM embeddings e
+         JOIN memories m ON m.id = e.memory_id
+         WHERE e.model = ? AND m.valid_to IS NULL
+         ORDER BY m.id",
+    )?;
+    let mut rows = stmt
+        .query_map(params![model], |row| {
+            let bytes: Vec<u8> = row.get(2)?;
+            let vector = bytes
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect();
+            Ok((row.get(0)?, row.get(1)?, vector))
+        })?
+        .collect::<std::result::Result<Vec<(MemoryId, String, Vec<f32>)>, _>>()?;
+    rows.shuffle(&mut StdRng::seed_from_u64(options.seed));
+    rows.truncate(options.sample_size);
+    Ok(rows)
+}
+
+/// All stored adapters.
+pub fn list_embedding_adapters(conn: &Connection) -> Result<Vec<AdapterInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT a.source_model, a.source_dims, a.target_model, a.target_dims, a.samples,
+                a.holdout_cosine, a.fitted_at,
+                (SELECT COUNT(*) FROM embeddings e
+                 WHERE e.model = a.source_model AND e.dimensions = a.source_dims)
+         FROM embedding_adapters a
+         ORDER BY a.fitted_at DESC",
+    )?;
+    let adapters = stmt
+        .query_map([], |row| {
+            Ok(AdapterInfo {
+                source_model: row.get(0)?,
+                source_dims: row.get::<_, i64>(1)?.max(0) as usize,
+                target_model: row.get(2)?,
+                target_dims: row.get::<_, i64>(3)?.max(0) as usize,
+                samples: row.get::<_, i64>(4)?.max(0) as usize,
+                holdout_cosine: row.get::<_, Option<f64>>(5)?.map(|v| v as f32),
+                fitted_at: row.get(6)?,
+                bridged_embeddings: row.get::<_, i64>(7)?.max(0) as usize,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(adapters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::queue::store_embedding;
+    use crate::embedding::{get_embedding, TfIdfEmbedder};
+    use crate::types::{CreateMemoryInput, MemoryType};
+
+    /// A fixed linear transform of TF-IDF vectors, standing in for a new model
+    struct ShuffledEmbedder(TfIdfEmbedder);
+
+    impl Embedder for ShuffledEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let v = self.0.embed(text)?;
+            Ok((0..24)
+                .map(|i| v[i % v.len()] * 2.0 - v[(i * 7 + 3) % v.len()])
+                .collect())
+        }
+
+        fn dimensions(&self) -> usize {
+            24
+        }
+
+        fn model_name(&self) -> &str {
+            "shuffled"
+        }
+    }
+
+    #[test]
+    fn test_fit_recovers_linear_map() {
+        // y = (2 x0 - x1, x2 + 1); fewer pairs than inputs uses the dual form
+        let pair = |x: [f32; 3]| (x.to_vec(), vec![2.0 * x[0] - x[1], x[2] + 1.0]);
+        let points = [
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.5, -1.0, 2.0],
+            [-1.0, 0.5, 0.0],
+        ];
+        for n in [3, 6] {
+            let pairs: Vec<_> = points[..n].iter().map(|&x| pair(x)).collect();
+            let adapter = LinearAdapter::fit(&pairs, 1e-6).unwrap();
+            for (source, target) in &pairs {
+                let projected = adapter.project(source).unwrap();
+                for (p, t) in projected.iter().zip(target) {
+                    assert!((p - t).abs() < 1e-2, "n={n}: {projected:?} vs {target:?}");
+                }
+            }
+        }
+        assert!(LinearAdapter::fit(&[pair([1.0, 0.0, 0.0])], 0.01)
+            .unwrap()
+            .project(&[1.0])
+            .is_none());
+    }
+
+    #[test]
+    fn test_adapter_bridges_old_vectors() {
+        let storage = Storage::open_in_memory().unwrap();
+        let old = TfIdfEmbedder::new(16);
+        let new = ShuffledEmbedder(TfIdfEmbedder::new(16));
+        let topics = ["billing", "deploys", "oncall", "roadmap", "hiring"];
+        let mut ids = Vec::new();
+        storage
+            .with_transaction(|conn| {
+                for i in 0..40 {
+                    let content = format!(
+                        "{} notes {} and {} review {}",
+                        topics[i % 5],
+                        i,
+                        topics[(i / 5) % 5],
+                        i * 3
+                    );
+                    let memory = crate::storage::queries::create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.clone(),
+                            memory_type: MemoryType::Note,
+                            ..Default::default()
+                        },
+                    )?;
+                    let vector = old.embed(&content)?;
+                    store_embedding(conn, memory.id, &vector, "tfidf", 16, None, "now")?;
+                    ids.push((memory.id, content));
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let info = fit_embedding_adapter(&storage, &new, &AdapterOptions::default()).unwrap();
+        assert_eq!(info.source_model, "tfidf");
+        assert_eq!(info.target_dims, 24);
+        assert_eq!(info.bridged_embeddings, 40);
+        assert!(info.holdout_cosine.unwrap() > 0.95, "{:?}", info);
+
+        storage
+            .with_connection(|conn| {
+                // No active model: vectors come back as stored
+                let (id, content) = &ids[0];
+                assert_eq!(get_embedding(conn, *id)?.unwrap().len(), 16);
+
+                set_active_embedding_model(conn, "shuffled")?;
+                let projected = get_embedding(conn, *id)?.unwrap();
+                let expected = new.embed(content)?;
+                assert!(cosine_similarity(&projected, &expected) > 0.95);
+
+                // Without a bridge, other models' vectors are not compared
+                set_active_embedding_model(conn, "other")?;
+                assert_eq!(get_embedding(conn, *id)?, None);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+//! Natively async embedding interface
+//!
+//! [`Embedder`] is synchronous, which suits the local backends (TF-IDF,
+//! ONNX) but forces the hosted ones to block a thread on their HTTP calls.
+//! [`AsyncEmbedder`] is the same interface as `async fn`s: the hosted
+//! backends implement it directly, and [`to_async`] runs any other embedder
+//! on Tokio's blocking pool, so async callers (the embedding queue, query
+//! embedding for search) never stall a runtime thread.
+//!
+//! The synchronous methods of the hosted backends go through [`block_on`],
+//! which works on multi-threaded runtimes, current-thread runtimes and
+//! outside any runtime alike.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use super::Embedder;
+use crate::error::{EngramError, Result};
+
+/// Async counterpart of [`Embedder`]
+#[async_trait]
+pub trait AsyncEmbedder: Send + Sync {
+    /// Generate embedding for a single text
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+
+    /// Generate embedding for a search query
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed(text).await
+    }
+
+    /// Generate embeddings for multiple texts (batch)
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.embed(text).await?);
+        }
+        Ok(embeddings)
+    }
+
+    /// Get embedding dimensions
+    fn dimensions(&self) -> usize;
+
+    /// Get model name
+    fn model_name(&self) -> &str;
+
+    /// See [`Embedder::summarized_by`]
+    fn summarized_by(&self, _text: &str) -> Option<&str> {
+        None
+    }
+}
+
+/// Runs a synchronous embedder on the blocking thread pool
+pub struct BlockingEmbedder {
+    inner: Arc<dyn Embedder>,
+}
+
+impl BlockingEmbedder {
+    pub fn new(inner: Arc<dyn Embedder>) -> Self {
+        Self { inner }
+    }
+
+    async fn run<T, F>(&self, call: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn Embedder) -> Result<T> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || call(inner.as_ref()))
+            .await
+            .map_err(|e| EngramError::Internal(format!("Embedding task panicked: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl AsyncEmbedder for BlockingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let text = text.to_string();
+        self.run(move |embedder| embedder.embed(&text)).await
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let text = text.to_string();
+        self.run(move |embedder| embedder.embed_query(&text)).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let texts: Vec<String> = texts.iter().map(|t| t.to_string()).collect();
+        self.run(move |embedder| {
+            let refs: Vec<&str> = texts.iter().map(String::as_str).collect();
+            embedder.embed_batch(&refs)
+        })
+        .await
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+}
+
+/// The async interface of an embedder: its native one if it has one,
+/// otherwise its sync methods run on the blocking pool
+pub fn to_async(embedder: Arc<dyn Embedder>) -> Arc<dyn AsyncEmbedder> {
+    embedder
+        .clone()
+        .native_async()
+        .unwrap_or_else(|| Arc::new(BlockingEmbedder::new(embedder)))
+}
+
+/// Embed a search query without blocking the runtime
+pub async fn embed_query(embedder: Arc<dyn Embedder>, query: &str) -> Result<Vec<f32>> {
+    to_async(embedder).embed_query(query).await
+}
+
+/// Drive an async embedding call to completion from sync code
+///
+/// On a multi-threaded runtime the current worker hands its tasks off while
+/// it blocks. A current-thread runtime can't do that (its one thread also
+/// drives the I/O the call waits on), so the call runs on a scoped thread
+/// with a runtime of its own, as it does when there is no runtime at all.
+pub fn block_on<T, F>(future: F) -> Result<T>
+where
+    T: Send,
+    F: Future<Output = Result<T>> + Send,
+{
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        Ok(_) => std::thread::scope(|scope| {
+            scope
+                .spawn(|| run_on_new_runtime(future))
+                .join()
+                .map_err(|_| EngramError::Internal("Embedding call panicked".to_string()))?
+        }),
+        Err(_) => run_on_new_runtime(future),
+    }
+}
+
+fn run_on_new_runtime<T, F: Future<Output = Result<T>>>(future: F) -> Result<T> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| EngramError::Embedding(format!("Failed to start runtime: {}", e)))?
+        .block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+
+    async fn answer() -> Result<u32> {
+        tokio::task::yield_now().await;
+        Ok(42)
+    }
+
+    #[test]
+    fn test_block_on_without_runtime() {
+        assert_eq!(block_on(answer()).unwrap(), 42);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_block_on_inside_current_thread_runtime() {
+        assert_eq!(block_on(answer()).unwrap(), 42);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_block_on_inside_multi_thread_runtime() {
+        assert_eq!(block_on(answer()).unwrap(), 42);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_sync_embedder_runs_off_the_runtime() {
+        let sync: Arc<dyn Embedder> = Arc::new(TfIdfEmbedder::new(64));
+        let embedder = to_async(sync.clone());
+        assert_eq!(embedder.dimensions(), 64);
+        assert_eq!(embedder.model_name(), sync.model_name());
+
+        let single = embedder.embed("rust async traits").await.unwrap();
+        assert_eq!(single, sync.embed("rust async traits").unwrap());
+
+        let batch = embedder.embed_batch(&["one", "two"]).await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[1], sync.embed("two").unwrap());
+
+        let query = embed_query(sync, "one").await.unwrap();
+        assert_eq!(query, batch[0]);
+    }
+}
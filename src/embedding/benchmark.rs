@@ -0,0 +1,111 @@
+//! Embedding throughput benchmark
+//!
+//! Times an embedder over the same texts at several batch sizes, to choose
+//! `batch_size` (and for candle models `inference_batch_size`) before a bulk
+//! ingestion. Each batch size is warmed up once first, so one-off costs such
+//! as GPU kernel compilation or connection setup don't count.
+
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::Embedder;
+use crate::error::{EngramError, Result};
+
+/// Throughput at one batch size
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSizeTiming {
+    pub batch_size: usize,
+    pub elapsed_ms: f64,
+    pub texts_per_second: f64,
+    pub chars_per_second: f64,
+}
+
+/// Throughput of an embedder across batch sizes
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingBenchmark {
+    pub model: String,
+    pub dimensions: usize,
+    pub texts: usize,
+    pub total_chars: usize,
+    pub timings: Vec<BatchSizeTiming>,
+    /// Batch size with the highest throughput
+    pub best_batch_size: usize,
+}
+
+/// Embed `texts` once per batch size in `batch_sizes`, calling
+/// [`Embedder::embed_batch`] on consecutive chunks of that size
+pub fn benchmark_embedder(
+    embedder: &dyn Embedder,
+    texts: &[String],
+    batch_sizes: &[usize],
+) -> Result<EmbeddingBenchmark> {
+    if texts.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "Embedding benchmark needs at least one text".to_string(),
+        ));
+    }
+    let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+    let total_chars: usize = texts.iter().map(|t| t.chars().count()).sum();
+
+    let mut timings = Vec::with_capacity(batch_sizes.len());
+    for &batch_size in batch_sizes.iter().filter(|&&size| size > 0) {
+        embedder.embed_batch(&texts[..batch_size.min(texts.len())])?;
+
+        let start = Instant::now();
+        for chunk in texts.chunks(batch_size) {
+            embedder.embed_batch(chunk)?;
+        }
+        let seconds = start.elapsed().as_secs_f64().max(f64::EPSILON);
+        timings.push(BatchSizeTiming {
+            batch_size,
+            elapsed_ms: seconds * 1000.0,
+            texts_per_second: texts.len() as f64 / seconds,
+            chars_per_second: total_chars as f64 / seconds,
+        });
+    }
+
+    let best_batch_size = timings
+        .iter()
+        .max_by(|a, b| a.texts_per_second.total_cmp(&b.texts_per_second))
+        .map(|t| t.batch_size)
+        .ok_or_else(|| {
+            EngramError::InvalidInput("Embedding benchmark needs a batch size above 0".to_string())
+        })?;
+
+    Ok(EmbeddingBenchmark {
+        model: embedder.model_name().to_string(),
+        dimensions: embedder.dimensions(),
+        texts: texts.len(),
+        total_chars,
+        timings,
+        best_batch_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+
+    #[test]
+    fn test_benchmark_embedder() {
+        let texts: Vec<String> = (0..10).map(|i| format!("memory number {}", i)).collect();
+        let report = benchmark_embedder(&TfIdfEmbedder::new(32), &texts, &[1, 4, 0, 16]).unwrap();
+        assert_eq!(report.texts, 10);
+        assert_eq!(report.dimensions, 32);
+        assert_eq!(
+            report
+                .timings
+                .iter()
+                .map(|t| t.batch_size)
+                .collect::<Vec<_>>(),
+            vec![1, 4, 16]
+        );
+        assert!(report.timings.iter().all(|t| t.texts_per_second > 0.0));
+        assert!([1, 4, 16].contains(&report.best_batch_size));
+
+        assert!(benchmark_embedder(&TfIdfEmbedder::new(32), &[], &[1]).is_err());
+        assert!(benchmark_embedder(&TfIdfEmbedder::new(32), &texts, &[0]).is_err());
+    }
+}
@@ -0,0 +1,505 @@
+//! Candle embedding provider
+//!
+//! Runs BERT-family sentence-transformer models (all-MiniLM-L6-v2,
+//! bge-small, e5-base, ...) with [candle], on the GPU when a backend is
+//! compiled in. Meant for bulk ingestion, where a forward pass over a padded
+//! batch on Metal or CUDA is far faster than one text at a time on the CPU.
+//!
+//! [`CandleEmbedder`] loads a model directory as downloaded from HuggingFace:
+//! `config.json`, `model.safetensors` (or `pytorch_model.bin`) and
+//! `tokenizer.json` (or `vocab.txt`). Token embeddings are mean-pooled over
+//! the attention mask and L2-normalized, like `sentence-transformers`.
+//!
+//! Texts in an [`Embedder::embed_batch`] call are sorted by length and cut
+//! into forward passes of at most `batch_size` texts and `max_batch_tokens`
+//! padded tokens, so similar lengths share a pass and long texts don't
+//! exhaust GPU memory. Results come back in input order.
+//!
+//! # Feature Flags
+//!
+//! - `candle-embed`: CPU inference (`ENGRAM_EMBEDDING_MODEL=candle`)
+//! - `candle-metal`: adds the Metal backend (Apple silicon)
+//! - `candle-cuda`: adds the CUDA backend (needs the CUDA toolkit to build)
+//!
+//! # Usage
+//!
+//! ```no_run
+//! # #[cfg(feature = "candle-embed")]
+//! # {
+//! use std::path::PathBuf;
+//! use engram::embedding::candle::{CandleConfig, CandleDevice, CandleEmbedder};
+//! use engram::embedding::Embedder;
+//!
+//! let embedder = CandleEmbedder::new(CandleConfig {
+//!     model_dir: PathBuf::from("models/all-MiniLM-L6-v2"),
+//!     device: CandleDevice::Auto,
+//!     batch_size: 64,
+//!     ..CandleConfig::default()
+//! })
+//! .unwrap();
+//! let embeddings = embedder.embed_batch(&["first memory", "second memory"]).unwrap();
+//! assert_eq!(embeddings[0].len(), 384);
+//! # }
+//! ```
+//!
+//! [candle]: https://github.com/huggingface/candle
+
+#[cfg(feature = "candle-embed")]
+mod inner {
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+
+    use candle_core::{DType, Device, Tensor};
+    use candle_nn::VarBuilder;
+    use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+
+    use crate::embedding::{Embedder, WordPieceTokenizer};
+    use crate::error::{EngramError, Result};
+
+    /// Texts per forward pass unless configured
+    pub const DEFAULT_INFERENCE_BATCH_SIZE: usize = 32;
+
+    /// Padded tokens per forward pass unless configured
+    pub const DEFAULT_MAX_BATCH_TOKENS: usize = 16_384;
+
+    /// Sequence length sentence-transformer models are usually trained with
+    const DEFAULT_MAX_LENGTH: usize = 256;
+
+    /// Where a candle model runs
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum CandleDevice {
+        /// CUDA or Metal if compiled in and present, otherwise the CPU
+        #[default]
+        Auto,
+        Cpu,
+        /// Metal device by ordinal (`candle-metal` feature)
+        Metal(usize),
+        /// CUDA device by ordinal (`candle-cuda` feature)
+        Cuda(usize),
+    }
+
+    impl FromStr for CandleDevice {
+        type Err = EngramError;
+
+        /// `auto`, `cpu`, `metal`, `metal:1`, `cuda`, `cuda:1`
+        fn from_str(s: &str) -> Result<Self> {
+            let s = s.trim().to_lowercase();
+            let (name, ordinal) = match s.split_once(':') {
+                Some((name, ordinal)) => {
+                    let ordinal = ordinal.parse().map_err(|_| {
+                        EngramError::Config(format!("Invalid device ordinal in '{}'", s))
+                    })?;
+                    (name, ordinal)
+                }
+                None => (s.as_str(), 0),
+            };
+            match name {
+                "auto" | "" => Ok(Self::Auto),
+                "cpu" => Ok(Self::Cpu),
+                "metal" => Ok(Self::Metal(ordinal)),
+                "cuda" | "gpu" => Ok(Self::Cuda(ordinal)),
+                _ => Err(EngramError::Config(format!(
+                    "Unknown device '{}': expected auto, cpu, metal[:N] or cuda[:N]",
+                    s
+                ))),
+            }
+        }
+    }
+
+    impl CandleDevice {
+        /// Open the device. An explicit GPU fails if its backend isn't
+        /// compiled in or the device can't be opened; `Auto` falls back to
+        /// the CPU instead.
+        pub fn open(self) -> Result<Device> {
+            let gpu_error = |backend: &str, e: candle_core::Error| {
+                EngramError::Config(format!(
+                    "Cannot open {} device (build with the candle-{} feature): {}",
+                    backend, backend, e
+                ))
+            };
+            match self {
+                Self::Cpu => Ok(Device::Cpu),
+                Self::Metal(ordinal) => {
+                    Device::new_metal(ordinal).map_err(|e| gpu_error("metal", e))
+                }
+                Self::Cuda(ordinal) => Device::new_cuda(ordinal).map_err(|e| gpu_error("cuda", e)),
+                Self::Auto => {
+                    if candle_core::utils::cuda_is_available() {
+                        if let Ok(device) = Device::new_cuda(0) {
+                            return Ok(device);
+                        }
+                    }
+                    if candle_core::utils::metal_is_available() {
+                        if let Ok(device) = Device::new_metal(0) {
+                            return Ok(device);
+                        }
+                    }
+                    Ok(Device::Cpu)
+                }
+            }
+        }
+    }
+
+    /// Configuration for [`CandleEmbedder`]
+    #[derive(Debug, Clone)]
+    pub struct CandleConfig {
+        /// HuggingFace model directory
+        pub model_dir: PathBuf,
+        pub device: CandleDevice,
+        /// Most texts per forward pass
+        pub batch_size: usize,
+        /// Most padded tokens (texts × longest text) per forward pass
+        pub max_batch_tokens: usize,
+        /// Texts are truncated to this many tokens (capped by the model's
+        /// position embeddings)
+        pub max_length: usize,
+        /// Expected embedding size; loading fails if the model's differs
+        pub dimensions: Option<usize>,
+    }
+
+    impl Default for CandleConfig {
+        fn default() -> Self {
+            Self {
+                model_dir: PathBuf::from("all-MiniLM-L6-v2"),
+                device: CandleDevice::Auto,
+                batch_size: DEFAULT_INFERENCE_BATCH_SIZE,
+                max_batch_tokens: DEFAULT_MAX_BATCH_TOKENS,
+                max_length: DEFAULT_MAX_LENGTH,
+                dimensions: None,
+            }
+        }
+    }
+
+    /// Default model directory for [`CandleEmbedder`], shared with the ONNX
+    /// backend: `~/.local/share/engram/models/all-MiniLM-L6-v2`
+    pub fn default_candle_model_dir() -> Result<PathBuf> {
+        let base = dirs::data_local_dir().ok_or_else(|| {
+            EngramError::Config("Cannot determine local data directory".to_string())
+        })?;
+        Ok(base.join("engram").join("models").join("all-MiniLM-L6-v2"))
+    }
+
+    /// Sentence-transformer embedder running on candle
+    pub struct CandleEmbedder {
+        model: BertModel,
+        tokenizer: WordPieceTokenizer,
+        device: Device,
+        pad_id: u32,
+        max_length: usize,
+        batch_size: usize,
+        max_batch_tokens: usize,
+        dimensions: usize,
+        model_name: String,
+    }
+
+    impl CandleEmbedder {
+        /// Load the model in `config.model_dir` onto `config.device`.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`EngramError::Config`] if a file is missing, the device
+        /// can't be opened or the model's size differs from
+        /// `config.dimensions`, and [`EngramError::Embedding`] if the files
+        /// can't be loaded.
+        pub fn new(config: CandleConfig) -> Result<Self> {
+            let dir = &config.model_dir;
+            let find = |candidates: &[&str]| {
+                candidates
+                    .iter()
+                    .map(|name| dir.join(name))
+                    .find(|path| path.is_file())
+            };
+            let missing =
+                |what: &str| EngramError::Config(format!("No {} found in {}", what, dir.display()));
+
+            let config_path = find(&["config.json"]).ok_or_else(|| missing("config.json"))?;
+            let bert_config: Config = serde_json::from_str(
+                &std::fs::read_to_string(&config_path).map_err(|e| load_error(&config_path, e))?,
+            )
+            .map_err(|e| load_error(&config_path, e))?;
+            let tokenizer_path = find(&["tokenizer.json", "vocab.txt"])
+                .ok_or_else(|| missing("tokenizer.json or vocab.txt"))?;
+            let tokenizer = WordPieceTokenizer::from_file(&tokenizer_path)?;
+
+            let device = config.device.open()?;
+            let vb = if let Some(path) = find(&["model.safetensors"]) {
+                let bytes = std::fs::read(&path).map_err(|e| load_error(&path, e))?;
+                VarBuilder::from_buffered_safetensors(bytes, DTYPE, &device)
+                    .map_err(|e| load_error(&path, e))?
+            } else if let Some(path) = find(&["pytorch_model.bin"]) {
+                VarBuilder::from_pth(&path, DTYPE, &device).map_err(|e| load_error(&path, e))?
+            } else {
+                return Err(missing("model.safetensors or pytorch_model.bin"));
+            };
+            let model = BertModel::load(vb, &bert_config)
+                .map_err(|e| EngramError::Embedding(format!("Failed to load BERT model: {e}")))?;
+
+            if let Some(dimensions) = config.dimensions {
+                if dimensions != bert_config.hidden_size {
+                    return Err(EngramError::Config(format!(
+                        "Model in {} produces {} dimensions, configured for {}",
+                        dir.display(),
+                        bert_config.hidden_size,
+                        dimensions
+                    )));
+                }
+            }
+            let model_name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "candle".to_string());
+
+            Ok(Self::with_model(
+                model,
+                &bert_config,
+                tokenizer,
+                device,
+                model_name,
+                &config,
+            ))
+        }
+
+        fn with_model(
+            model: BertModel,
+            bert_config: &Config,
+            tokenizer: WordPieceTokenizer,
+            device: Device,
+            model_name: String,
+            config: &CandleConfig,
+        ) -> Self {
+            Self {
+                model,
+                tokenizer,
+                device,
+                pad_id: bert_config.pad_token_id as u32,
+                max_length: config
+                    .max_length
+                    .clamp(2, bert_config.max_position_embeddings),
+                batch_size: config.batch_size.max(1),
+                max_batch_tokens: config.max_batch_tokens.max(1),
+                dimensions: bert_config.hidden_size,
+                model_name,
+            }
+        }
+
+        /// The device inference runs on, e.g. to report it
+        pub fn device(&self) -> &Device {
+            &self.device
+        }
+
+        /// Mean-pooled, L2-normalized embeddings of one padded batch
+        fn forward(&self, batch: &[&[i64]]) -> Result<Vec<Vec<f32>>> {
+            let seq_len = batch.iter().map(|ids| ids.len()).max().unwrap_or(0);
+            let mut ids = Vec::with_capacity(batch.len() * seq_len);
+            let mut mask = Vec::with_capacity(batch.len() * seq_len);
+            for sequence in batch {
+                ids.extend(sequence.iter().map(|&id| id as u32));
+                ids.resize(ids.len() + seq_len - sequence.len(), self.pad_id);
+                mask.extend(std::iter::repeat_n(1u32, sequence.len()));
+                mask.resize(mask.len() + seq_len - sequence.len(), 0);
+            }
+
+            let shape = (batch.len(), seq_len);
+            let ids = Tensor::from_vec(ids, shape, &self.device).map_err(inference_error)?;
+            let mask = Tensor::from_vec(mask, shape, &self.device).map_err(inference_error)?;
+            let token_types = ids.zeros_like().map_err(inference_error)?;
+            let hidden = self
+                .model
+                .forward(&ids, &token_types, Some(&mask))
+                .map_err(inference_error)?;
+
+            let pooled = (|| {
+                let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+                let summed = hidden.broadcast_mul(&mask)?.sum(1)?;
+                let counts = mask.sum(1)?.maximum(1e-9)?;
+                let mean = summed.broadcast_div(&counts)?;
+                let norms = mean.sqr()?.sum_keepdim(1)?.sqrt()?.maximum(1e-12)?;
+                mean.broadcast_div(&norms)?.to_vec2::<f32>()
+            })()
+            .map_err(inference_error)?;
+            Ok(pooled)
+        }
+    }
+
+    impl Embedder for CandleEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_batch(&[text])?
+                .pop()
+                .ok_or_else(|| EngramError::Embedding("Candle returned no embedding".to_string()))
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let encoded: Vec<Vec<i64>> = texts
+                .iter()
+                .map(|text| self.tokenizer.encode(text, self.max_length).0)
+                .collect();
+            let lengths: Vec<usize> = encoded.iter().map(Vec::len).collect();
+
+            let mut embeddings = vec![Vec::new(); texts.len()];
+            for batch in plan_batches(&lengths, self.batch_size, self.max_batch_tokens) {
+                let sequences: Vec<&[i64]> = batch.iter().map(|&i| encoded[i].as_slice()).collect();
+                for (i, embedding) in batch.into_iter().zip(self.forward(&sequences)?) {
+                    embeddings[i] = embedding;
+                }
+            }
+            Ok(embeddings)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.dimensions
+        }
+
+        fn model_name(&self) -> &str {
+            &self.model_name
+        }
+    }
+
+    /// Group sequences into forward passes, longest first: each pass holds
+    /// at most `batch_size` sequences, and at most `max_batch_tokens` tokens
+    /// once padded to its longest, except that a single sequence always fits.
+    /// Returns indices into `lengths`.
+    pub fn plan_batches(
+        lengths: &[usize],
+        batch_size: usize,
+        max_batch_tokens: usize,
+    ) -> Vec<Vec<usize>> {
+        let mut order: Vec<usize> = (0..lengths.len()).collect();
+        order.sort_by(|&a, &b| lengths[b].cmp(&lengths[a]));
+
+        let mut batches: Vec<Vec<usize>> = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+        for i in order {
+            // Sorted longest first, so the first sequence sets the padding
+            let longest = current.first().map_or(lengths[i], |&first| lengths[first]);
+            if !current.is_empty()
+                && (current.len() >= batch_size || (current.len() + 1) * longest > max_batch_tokens)
+            {
+                batches.push(std::mem::take(&mut current));
+            }
+            current.push(i);
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+        batches
+    }
+
+    fn load_error(path: &Path, e: impl std::fmt::Display) -> EngramError {
+        EngramError::Embedding(format!("Failed to load {}: {e}", path.display()))
+    }
+
+    fn inference_error(e: candle_core::Error) -> EngramError {
+        EngramError::Embedding(format!("Candle inference error: {e}"))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use candle_nn::VarMap;
+
+        fn tiny_embedder(batch_size: usize, max_batch_tokens: usize) -> CandleEmbedder {
+            let vocab = [
+                "[PAD]",
+                "[UNK]",
+                "[CLS]",
+                "[SEP]",
+                "deploy",
+                "the",
+                "api",
+                "on",
+                "friday",
+                "rollback",
+                "failed",
+                "after",
+                "database",
+                "migration",
+            ];
+            let tokenizer = WordPieceTokenizer::from_vocab(vocab).unwrap();
+            let bert_config = Config {
+                vocab_size: vocab.len(),
+                hidden_size: 16,
+                num_hidden_layers: 2,
+                num_attention_heads: 4,
+                intermediate_size: 32,
+                max_position_embeddings: 32,
+                ..Config::default()
+            };
+            // Randomly initialized weights: enough to check batching
+            let varmap = VarMap::new();
+            let vb = VarBuilder::from_varmap(&varmap, DTYPE, &Device::Cpu);
+            let model = BertModel::load(vb, &bert_config).unwrap();
+            CandleEmbedder::with_model(
+                model,
+                &bert_config,
+                tokenizer,
+                Device::Cpu,
+                "tiny-bert".to_string(),
+                &CandleConfig {
+                    batch_size,
+                    max_batch_tokens,
+                    ..CandleConfig::default()
+                },
+            )
+        }
+
+        #[test]
+        fn test_plan_batches() {
+            let lengths = [4, 30, 6, 28, 5, 3];
+            // Longest first, at most 2 per pass
+            assert_eq!(
+                plan_batches(&lengths, 2, 1000),
+                vec![vec![1, 3], vec![2, 4], vec![0, 5]]
+            );
+            // 2 × 30 padded tokens exceed 40, so the long ones run alone
+            assert_eq!(
+                plan_batches(&lengths, 8, 40),
+                vec![vec![1], vec![3], vec![2, 4, 0, 5]]
+            );
+            assert!(plan_batches(&[], 8, 40).is_empty());
+        }
+
+        #[test]
+        fn test_padded_batches_match_single_texts() {
+            let texts = [
+                "deploy the api on friday",
+                "rollback failed",
+                "the database migration failed after the deploy on friday",
+            ];
+            // `embed` runs each text alone; `embed_batch` pads them together
+            let embedder = tiny_embedder(2, 64);
+            let batched = embedder.embed_batch(&texts).unwrap();
+            assert_eq!(batched.len(), 3);
+            for (text, embedding) in texts.iter().zip(&batched) {
+                let alone = embedder.embed(text).unwrap();
+                assert_eq!(embedding.len(), 16);
+                let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+                assert!((norm - 1.0).abs() < 1e-4);
+                for (a, b) in alone.iter().zip(embedding) {
+                    assert!((a - b).abs() < 1e-4, "{} vs {} for '{}'", a, b, text);
+                }
+            }
+        }
+
+        #[test]
+        fn test_device_parsing() {
+            assert_eq!("auto".parse::<CandleDevice>().unwrap(), CandleDevice::Auto);
+            assert_eq!("CPU".parse::<CandleDevice>().unwrap(), CandleDevice::Cpu);
+            assert_eq!(
+                "cuda:1".parse::<CandleDevice>().unwrap(),
+                CandleDevice::Cuda(1)
+            );
+            assert_eq!(
+                "metal".parse::<CandleDevice>().unwrap(),
+                CandleDevice::Metal(0)
+            );
+            assert!("tpu".parse::<CandleDevice>().is_err());
+            assert!(matches!(CandleDevice::Auto.open(), Ok(_)));
+        }
+    }
+}
+
+#[cfg(feature = "candle-embed")]
+pub use inner::{
+    default_candle_model_dir, plan_batches, CandleConfig, CandleDevice, CandleEmbedder,
+    DEFAULT_INFERENCE_BATCH_SIZE, DEFAULT_MAX_BATCH_TOKENS,
+};
@@ -159,10 +159,7 @@ impl Embedder for ClipEmbedder {
 impl MultimodalEmbedder for ClipEmbedder {
     fn embed_image_sync(&self, image_bytes: &[u8], mime_type: &str) -> Result<Vec<f32>> {
         // Blocking wrapper around the async implementation
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current()
-                .block_on(self.embed_image_async(image_bytes, mime_type))
-        })
+        crate::embedding::block_on(self.embed_image_async(image_bytes, mime_type))
     }
 
     fn multimodal_provider_name(&self) -> &str {
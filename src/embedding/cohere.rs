@@ -3,6 +3,11 @@
 //! Sends texts to the Cohere `/embed` endpoint and returns dense float vectors.
 //! Default model: `embed-english-v3.0` (1024 dimensions).
 //!
+//! v3 models embed stored texts and search queries differently: memories are
+//! sent with `input_type: "search_document"` and queries (via
+//! [`Embedder::embed_query`](crate::embedding::Embedder::embed_query)) with
+//! `"search_query"`. Batches are split at 96 texts, the API's per-request limit.
+//!
 //! # Feature Flag
 //!
 //! Gated behind `#[cfg(feature = "cohere")]`. Requires the `cohere` feature to be
@@ -28,9 +33,19 @@
 
 #[cfg(feature = "cohere")]
 mod inner {
-    use crate::embedding::Embedder;
+    use std::sync::Arc;
+
+    use crate::embedding::{block_on, Embedder};
     use crate::error::{EngramError, Result};
 
+    /// Most texts the Cohere `/embed` endpoint accepts per request.
+    pub const COHERE_MAX_BATCH_SIZE: usize = 96;
+
+    /// `input_type` for texts stored in the memory index.
+    const SEARCH_DOCUMENT: &str = "search_document";
+    /// `input_type` for texts used to search the index.
+    const SEARCH_QUERY: &str = "search_query";
+
     /// Configuration for the Cohere embedding provider.
     #[derive(Debug, Clone)]
     pub struct CohereConfig {
@@ -42,6 +57,8 @@ mod inner {
         pub base_url: String,
         /// Expected number of dimensions in the output embedding vector.
         pub dimensions: usize,
+        /// Texts sent per request; clamped to [`COHERE_MAX_BATCH_SIZE`].
+        pub max_batch_size: usize,
     }
 
     impl Default for CohereConfig {
@@ -51,6 +68,7 @@ mod inner {
                 model: "embed-english-v3.0".to_string(),
                 base_url: "https://api.cohere.ai/v1".to_string(),
                 dimensions: 1024,
+                max_batch_size: COHERE_MAX_BATCH_SIZE,
             }
         }
     }
@@ -70,49 +88,52 @@ mod inner {
             }
         }
 
-        /// Async call to the Cohere `/embed` endpoint.
+        /// Async call to the Cohere `/embed` endpoint for a text to be stored.
         ///
-        /// The `input_type` is fixed to `"search_document"` which is suitable
-        /// for indexing content into a memory store.
+        /// Uses `input_type: "search_document"`; see [`Self::embed_query_async`]
+        /// for search queries.
         pub async fn embed_async(&self, text: &str) -> Result<Vec<f32>> {
-            let url = format!("{}/embed", self.config.base_url);
+            self.embed_one(text, SEARCH_DOCUMENT).await
+        }
 
-            let response = self
-                .client
-                .post(&url)
-                .header("Authorization", format!("Bearer {}", self.config.api_key))
-                .json(&serde_json::json!({
-                    "texts": [text],
-                    "model": self.config.model,
-                    "input_type": "search_document",
-                }))
-                .send()
-                .await?;
+        /// Async call to the Cohere `/embed` endpoint for a search query
+        /// (`input_type: "search_query"`).
+        pub async fn embed_query_async(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_one(text, SEARCH_QUERY).await
+        }
 
-            if !response.status().is_success() {
-                let status = response.status();
-                let body = response.text().await.unwrap_or_default();
-                return Err(EngramError::Embedding(format!(
-                    "Cohere API error {status}: {body}"
-                )));
+        /// Async batch call to the Cohere `/embed` endpoint for texts to be
+        /// stored.
+        ///
+        /// Texts are sent in chunks of at most `max_batch_size`.
+        pub async fn embed_batch_async(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for chunk in texts.chunks(self.batch_size()) {
+                embeddings.extend(self.embed_texts(chunk, SEARCH_DOCUMENT).await?);
             }
+            Ok(embeddings)
+        }
 
-            let data: serde_json::Value = response.json().await?;
-            let embeddings = data["embeddings"].as_array().ok_or_else(|| {
-                EngramError::Embedding("Cohere response missing 'embeddings' field".to_string())
-            })?;
+        fn batch_size(&self) -> usize {
+            self.config.max_batch_size.clamp(1, COHERE_MAX_BATCH_SIZE)
+        }
 
-            let embedding: Vec<f32> = embeddings
-                .first()
-                .and_then(|e| e.as_array())
-                .ok_or_else(|| {
-                    EngramError::Embedding(
-                        "Cohere response 'embeddings[0]' is missing or not an array".to_string(),
-                    )
-                })?
-                .iter()
-                .filter_map(|v| v.as_f64().map(|f| f as f32))
-                .collect();
+        fn request_body(&self, texts: &[&str], input_type: &str) -> serde_json::Value {
+            serde_json::json!({
+                "texts": texts,
+                "model": self.config.model,
+                "input_type": input_type,
+                "truncate": "END",
+            })
+        }
+
+        async fn embed_one(&self, text: &str, input_type: &str) -> Result<Vec<f32>> {
+            let embedding = self
+                .embed_texts(&[text], input_type)
+                .await?
+                .into_iter()
+                .next()
+                .unwrap_or_default();
 
             if embedding.is_empty() {
                 return Err(EngramError::Embedding(
@@ -123,10 +144,7 @@ mod inner {
             Ok(embedding)
         }
 
-        /// Async batch call to the Cohere `/embed` endpoint.
-        ///
-        /// Cohere natively supports batching; all texts are sent in a single request.
-        pub async fn embed_batch_async(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        async fn embed_texts(&self, texts: &[&str], input_type: &str) -> Result<Vec<Vec<f32>>> {
             if texts.is_empty() {
                 return Ok(vec![]);
             }
@@ -137,11 +155,7 @@ mod inner {
                 .client
                 .post(&url)
                 .header("Authorization", format!("Bearer {}", self.config.api_key))
-                .json(&serde_json::json!({
-                    "texts": texts,
-                    "model": self.config.model,
-                    "input_type": "search_document",
-                }))
+                .json(&self.request_body(texts, input_type))
                 .send()
                 .await?;
 
@@ -158,6 +172,14 @@ mod inner {
                 EngramError::Embedding("Cohere response missing 'embeddings' field".to_string())
             })?;
 
+            if raw.len() != texts.len() {
+                return Err(EngramError::Embedding(format!(
+                    "Cohere returned {} embeddings for {} texts",
+                    raw.len(),
+                    texts.len()
+                )));
+            }
+
             let embeddings: Vec<Vec<f32>> = raw
                 .iter()
                 .map(|e| {
@@ -177,15 +199,15 @@ mod inner {
 
     impl Embedder for CohereEmbedder {
         fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
-            tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(self.embed_async(text))
-            })
+            block_on(self.embed_async(text))
+        }
+
+        fn embed_query(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            block_on(self.embed_query_async(text))
         }
 
         fn embed_batch(&self, texts: &[&str]) -> crate::error::Result<Vec<Vec<f32>>> {
-            tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(self.embed_batch_async(texts))
-            })
+            block_on(self.embed_batch_async(texts))
         }
 
         fn dimensions(&self) -> usize {
@@ -195,11 +217,61 @@ mod inner {
         fn model_name(&self) -> &str {
             &self.config.model
         }
+
+        fn native_async(self: Arc<Self>) -> Option<Arc<dyn crate::embedding::AsyncEmbedder>> {
+            Some(self)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::embedding::AsyncEmbedder for CohereEmbedder {
+        async fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            self.embed_async(text).await
+        }
+
+        async fn embed_query(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            self.embed_query_async(text).await
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> crate::error::Result<Vec<Vec<f32>>> {
+            self.embed_batch_async(texts).await
+        }
+
+        fn dimensions(&self) -> usize {
+            self.config.dimensions
+        }
+
+        fn model_name(&self) -> &str {
+            &self.config.model
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_request_body_input_type_and_batch_size() {
+            let embedder = CohereEmbedder::new(CohereConfig {
+                max_batch_size: 500,
+                ..CohereConfig::default()
+            });
+            assert_eq!(embedder.batch_size(), COHERE_MAX_BATCH_SIZE);
+
+            let body = embedder.request_body(&["how do I deploy?"], SEARCH_QUERY);
+            assert_eq!(body["input_type"], "search_query");
+            assert_eq!(body["texts"][0], "how do I deploy?");
+            assert_eq!(body["model"], "embed-english-v3.0");
+
+            let body = embedder.request_body(&["a", "b"], SEARCH_DOCUMENT);
+            assert_eq!(body["input_type"], "search_document");
+            assert_eq!(body["texts"].as_array().unwrap().len(), 2);
+        }
     }
 }
 
 #[cfg(feature = "cohere")]
-pub use inner::{CohereConfig, CohereEmbedder};
+pub use inner::{CohereConfig, CohereEmbedder, COHERE_MAX_BATCH_SIZE};
 
 // ---------------------------------------------------------------------------
 // Tests
@@ -302,6 +374,7 @@ mod tests {
         assert_eq!(cfg.model, "embed-english-v3.0");
         assert_eq!(cfg.base_url, "https://api.cohere.ai/v1");
         assert_eq!(cfg.dimensions, 1024);
+        assert_eq!(cfg.max_batch_size, 96);
         assert!(cfg.api_key.is_empty(), "default api_key must be empty");
     }
 
@@ -314,6 +387,7 @@ mod tests {
             model: "embed-multilingual-v3.0".to_string(),
             base_url: "https://api.cohere.ai/v1".to_string(),
             dimensions: 1024,
+            max_batch_size: 32,
         };
         assert_eq!(cfg.api_key, "co-test-key");
         assert_eq!(cfg.model, "embed-multilingual-v3.0");
@@ -0,0 +1,332 @@
+//! Hugging Face Inference API and text-embeddings-inference provider
+//!
+//! Talks to either endpoint flavour Hugging Face serves embeddings from:
+//!
+//! - the hosted Inference API, when a model is configured:
+//!   `POST {base_url}/models/{model}/pipeline/feature-extraction`
+//! - a [text-embeddings-inference] (TEI) server, self-hosted or behind an
+//!   Inference Endpoint, when no model is (TEI serves a single model):
+//!   `POST {base_url}/embed`
+//!
+//! Both take `{"inputs": [...]}`. Models without a pooling layer return one
+//! vector per token; those are mean-pooled into a single embedding.
+//!
+//! # Feature Flag
+//!
+//! Gated behind `#[cfg(feature = "hf-inference")]`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "hf-inference")]
+//! # {
+//! use engram::embedding::hf_inference::{HfInferenceConfig, HfInferenceEmbedder};
+//! use engram::embedding::Embedder;
+//!
+//! // Self-hosted TEI server
+//! let config = HfInferenceConfig {
+//!     base_url: "http://localhost:8080".to_string(),
+//!     model: None,
+//!     ..HfInferenceConfig::default()
+//! };
+//! let embedder = HfInferenceEmbedder::new(config);
+//! let embedding = embedder.embed("Hello, world!").unwrap();
+//! assert_eq!(embedding.len(), 384);
+//! # }
+//! ```
+//!
+//! [text-embeddings-inference]: https://github.com/huggingface/text-embeddings-inference
+
+#[cfg(feature = "hf-inference")]
+mod inner {
+    use std::sync::Arc;
+
+    use crate::embedding::{block_on, Embedder};
+    use crate::error::{EngramError, Result};
+
+    /// Default base URL of the hosted Inference API.
+    pub const HF_INFERENCE_BASE_URL: &str = "https://router.huggingface.co/hf-inference";
+
+    /// Default model for the hosted Inference API.
+    pub const HF_DEFAULT_MODEL: &str = "sentence-transformers/all-MiniLM-L6-v2";
+
+    /// Texts per request; TEI's default `--max-client-batch-size`.
+    pub const HF_DEFAULT_BATCH_SIZE: usize = 32;
+
+    /// Configuration for the Hugging Face embedding provider.
+    #[derive(Debug, Clone)]
+    pub struct HfInferenceConfig {
+        /// Inference API or TEI server URL.
+        pub base_url: String,
+        /// Access token, sent as a bearer token (optional for local TEI).
+        pub token: Option<String>,
+        /// Hub model id for the hosted Inference API; `None` calls a TEI
+        /// server's `/embed` route.
+        pub model: Option<String>,
+        /// Expected number of dimensions in the output embedding vector.
+        pub dimensions: usize,
+        /// Texts sent per request.
+        pub max_batch_size: usize,
+    }
+
+    impl Default for HfInferenceConfig {
+        fn default() -> Self {
+            Self {
+                base_url: HF_INFERENCE_BASE_URL.to_string(),
+                token: None,
+                model: Some(HF_DEFAULT_MODEL.to_string()),
+                dimensions: 384,
+                max_batch_size: HF_DEFAULT_BATCH_SIZE,
+            }
+        }
+    }
+
+    /// Embedding client for the Hugging Face Inference API or a TEI server.
+    pub struct HfInferenceEmbedder {
+        config: HfInferenceConfig,
+        model_name: String,
+        client: reqwest::Client,
+    }
+
+    impl HfInferenceEmbedder {
+        /// Create a new embedder with the given configuration.
+        pub fn new(config: HfInferenceConfig) -> Self {
+            let model_name = config
+                .model
+                .clone()
+                .unwrap_or_else(|| format!("tei:{}", config.base_url));
+            Self {
+                config,
+                model_name,
+                client: reqwest::Client::new(),
+            }
+        }
+
+        /// Async embedding of a single text.
+        pub async fn embed_async(&self, text: &str) -> Result<Vec<f32>> {
+            let embedding = self
+                .embed_batch_async(&[text])
+                .await?
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+
+            if embedding.is_empty() {
+                return Err(EngramError::Embedding(
+                    "Hugging Face returned an empty embedding vector".to_string(),
+                ));
+            }
+
+            Ok(embedding)
+        }
+
+        /// Async batch embedding, in chunks of at most `max_batch_size`.
+        pub async fn embed_batch_async(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for chunk in texts.chunks(self.config.max_batch_size.max(1)) {
+                embeddings.extend(self.embed_chunk(chunk).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn url(&self) -> String {
+            let base = self.config.base_url.trim_end_matches('/');
+            match &self.config.model {
+                Some(model) => format!("{base}/models/{model}/pipeline/feature-extraction"),
+                None => format!("{base}/embed"),
+            }
+        }
+
+        fn request_body(&self, texts: &[&str]) -> serde_json::Value {
+            match self.config.model {
+                // Wait for a cold model to load instead of failing with 503
+                Some(_) => serde_json::json!({
+                    "inputs": texts,
+                    "options": {"wait_for_model": true},
+                }),
+                None => serde_json::json!({
+                    "inputs": texts,
+                    "truncate": true,
+                }),
+            }
+        }
+
+        async fn embed_chunk(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            if texts.is_empty() {
+                return Ok(vec![]);
+            }
+
+            let mut request = self.client.post(self.url()).json(&self.request_body(texts));
+            if let Some(token) = &self.config.token {
+                request = request.header("Authorization", format!("Bearer {token}"));
+            }
+            let response = request.send().await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(EngramError::Embedding(format!(
+                    "Hugging Face API error {status}: {body}"
+                )));
+            }
+
+            let data: serde_json::Value = response.json().await?;
+            parse_embeddings(&data, texts.len())
+        }
+    }
+
+    /// One embedding per input from a feature-extraction response: a list of
+    /// sentence vectors, or of per-token vectors to mean-pool
+    fn parse_embeddings(data: &serde_json::Value, expected: usize) -> Result<Vec<Vec<f32>>> {
+        let invalid =
+            || EngramError::Embedding("Invalid Hugging Face embedding response".to_string());
+        let items = data.as_array().ok_or_else(invalid)?;
+        // A single input may come back as a bare vector
+        if expected == 1 && items.first().is_some_and(|v| v.is_number()) {
+            return Ok(vec![floats(items)]);
+        }
+
+        let embeddings = items
+            .iter()
+            .map(|item| {
+                let rows = item.as_array().ok_or_else(invalid)?;
+                if rows.first().is_some_and(|v| v.is_array()) {
+                    let tokens: Vec<Vec<f32>> = rows
+                        .iter()
+                        .map(|token| token.as_array().map(|t| floats(t)).ok_or_else(invalid))
+                        .collect::<Result<_>>()?;
+                    Ok(mean_pool(&tokens))
+                } else {
+                    Ok(floats(rows))
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if embeddings.len() != expected {
+            return Err(EngramError::Embedding(format!(
+                "Hugging Face returned {} embeddings for {} texts",
+                embeddings.len(),
+                expected
+            )));
+        }
+        Ok(embeddings)
+    }
+
+    fn floats(values: &[serde_json::Value]) -> Vec<f32> {
+        values
+            .iter()
+            .filter_map(|v| v.as_f64().map(|f| f as f32))
+            .collect()
+    }
+
+    fn mean_pool(tokens: &[Vec<f32>]) -> Vec<f32> {
+        let Some(first) = tokens.first() else {
+            return Vec::new();
+        };
+        let mut pooled = vec![0.0; first.len()];
+        for token in tokens {
+            for (sum, x) in pooled.iter_mut().zip(token) {
+                *sum += x;
+            }
+        }
+        for sum in &mut pooled {
+            *sum /= tokens.len() as f32;
+        }
+        pooled
+    }
+
+    impl Embedder for HfInferenceEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            block_on(self.embed_async(text))
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            block_on(self.embed_batch_async(texts))
+        }
+
+        fn dimensions(&self) -> usize {
+            self.config.dimensions
+        }
+
+        fn model_name(&self) -> &str {
+            &self.model_name
+        }
+
+        fn native_async(self: Arc<Self>) -> Option<Arc<dyn crate::embedding::AsyncEmbedder>> {
+            Some(self)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::embedding::AsyncEmbedder for HfInferenceEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_async(text).await
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            self.embed_batch_async(texts).await
+        }
+
+        fn dimensions(&self) -> usize {
+            self.config.dimensions
+        }
+
+        fn model_name(&self) -> &str {
+            &self.model_name
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use serde_json::json;
+
+        #[test]
+        fn test_urls_for_hosted_api_and_tei() {
+            let hosted = HfInferenceEmbedder::new(HfInferenceConfig::default());
+            assert_eq!(
+                hosted.url(),
+                "https://router.huggingface.co/hf-inference/models/sentence-transformers/all-MiniLM-L6-v2/pipeline/feature-extraction"
+            );
+            assert_eq!(hosted.model_name(), HF_DEFAULT_MODEL);
+            assert_eq!(
+                hosted.request_body(&["a"])["options"]["wait_for_model"],
+                true
+            );
+
+            let tei = HfInferenceEmbedder::new(HfInferenceConfig {
+                base_url: "http://localhost:8080/".to_string(),
+                model: None,
+                ..HfInferenceConfig::default()
+            });
+            assert_eq!(tei.url(), "http://localhost:8080/embed");
+            assert_eq!(tei.model_name(), "tei:http://localhost:8080/");
+            assert_eq!(tei.request_body(&["a", "b"])["inputs"], json!(["a", "b"]));
+        }
+
+        #[test]
+        fn test_parse_sentence_and_token_embeddings() {
+            let sentences = json!([[1.0, 2.0], [3.0, 4.0]]);
+            assert_eq!(
+                parse_embeddings(&sentences, 2).unwrap(),
+                vec![vec![1.0, 2.0], vec![3.0, 4.0]]
+            );
+
+            let bare = json!([0.5, 0.25]);
+            assert_eq!(parse_embeddings(&bare, 1).unwrap(), vec![vec![0.5, 0.25]]);
+
+            // Token-level output is mean-pooled per input
+            let tokens = json!([[[1.0, 0.0], [3.0, 2.0]]]);
+            assert_eq!(parse_embeddings(&tokens, 1).unwrap(), vec![vec![2.0, 1.0]]);
+
+            assert!(parse_embeddings(&sentences, 3).is_err());
+            assert!(parse_embeddings(&json!({"error": "loading"}), 1).is_err());
+        }
+    }
+}
+
+#[cfg(feature = "hf-inference")]
+pub use inner::{
+    HfInferenceConfig, HfInferenceEmbedder, HF_DEFAULT_BATCH_SIZE, HF_DEFAULT_MODEL,
+    HF_INFERENCE_BASE_URL,
+};
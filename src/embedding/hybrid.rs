@@ -0,0 +1,187 @@
+//! Hybrid sparse + dense embeddings
+//!
+//! Semantic models are good at paraphrase but can rank an exact keyword
+//! match (an error code, a product name) below loosely related text.
+//! [`HybridEmbedder`] appends a TF-IDF vector to the dense one:
+//!
+//! ```text
+//! [ dense · √(1 − w) | tfidf · √w ]
+//! ```
+//!
+//! Each part is L2-normalized before scaling, so the cosine similarity of two
+//! hybrid vectors is `(1 − w) · cos(dense) + w · cos(tfidf)`: the vector
+//! index, duplicate detection and anything else comparing vectors get the
+//! blend without knowing about it. When [`SearchConfig::hybrid_dense_dims`]
+//! is set, [`hybrid_search`] also scores the two parts separately and fuses
+//! the sparse ranking into its reciprocal rank fusion.
+//!
+//! [`SearchConfig::hybrid_dense_dims`]: crate::search::SearchConfig::hybrid_dense_dims
+//! [`hybrid_search`]: crate::search::hybrid_search
+
+use std::sync::Arc;
+
+use super::{AsyncEmbedder, Embedder};
+use crate::error::Result;
+
+/// Share of the similarity given to the sparse part by default
+pub const DEFAULT_SPARSE_WEIGHT: f32 = 0.3;
+
+/// Embedder producing dense vectors with a TF-IDF vector appended
+pub struct HybridEmbedder {
+    dense: Arc<dyn Embedder>,
+    dense_async: Arc<dyn AsyncEmbedder>,
+    sparse: Arc<dyn Embedder>,
+    sparse_weight: f32,
+    model_name: String,
+}
+
+impl HybridEmbedder {
+    /// Combine `dense` with the sparse embedder `sparse` (normally a
+    /// [`TfIdfEmbedder`](super::TfIdfEmbedder))
+    pub fn new(dense: Arc<dyn Embedder>, sparse: Arc<dyn Embedder>) -> Self {
+        Self {
+            model_name: format!("hybrid:{}+{}", dense.model_name(), sparse.model_name()),
+            dense_async: super::to_async(dense.clone()),
+            dense,
+            sparse,
+            sparse_weight: DEFAULT_SPARSE_WEIGHT,
+        }
+    }
+
+    /// Share of the similarity given to the sparse part (clamped to 0..=1)
+    pub fn with_sparse_weight(mut self, weight: f32) -> Self {
+        self.sparse_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn sparse_weight(&self) -> f32 {
+        self.sparse_weight
+    }
+
+    fn combine(&self, dense: Vec<f32>, sparse: Vec<f32>) -> Vec<f32> {
+        let mut combined = Vec::with_capacity(dense.len() + sparse.len());
+        push_scaled(&mut combined, &dense, (1.0 - self.sparse_weight).sqrt());
+        push_scaled(&mut combined, &sparse, self.sparse_weight.sqrt());
+        combined
+    }
+
+    fn combine_batch(&self, dense: Vec<Vec<f32>>, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let sparse = self.sparse.embed_batch(texts)?;
+        Ok(dense
+            .into_iter()
+            .zip(sparse)
+            .map(|(dense, sparse)| self.combine(dense, sparse))
+            .collect())
+    }
+}
+
+/// Append `part` normalized to length `scale`
+fn push_scaled(out: &mut Vec<f32>, part: &[f32], scale: f32) {
+    let norm = part.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let factor = if norm > 0.0 { scale / norm } else { 0.0 };
+    out.extend(part.iter().map(|x| x * factor));
+}
+
+/// The dense and sparse parts of a hybrid vector whose first `dense_dims`
+/// components are dense; `None` when `vector` has no sparse part
+pub fn hybrid_parts(vector: &[f32], dense_dims: usize) -> Option<(&[f32], &[f32])> {
+    (dense_dims > 0 && vector.len() > dense_dims).then(|| vector.split_at(dense_dims))
+}
+
+impl Embedder for HybridEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.combine(self.dense.embed(text)?, self.sparse.embed(text)?))
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.combine(
+            self.dense.embed_query(text)?,
+            self.sparse.embed_query(text)?,
+        ))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.combine_batch(self.dense.embed_batch(texts)?, texts)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dense.dimensions() + self.sparse.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.dense.summarized_by(text)
+    }
+
+    fn hybrid_dense_dims(&self) -> Option<usize> {
+        Some(self.dense.dimensions())
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEmbedder for HybridEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let dense = self.dense_async.embed(text).await?;
+        Ok(self.combine(dense, self.sparse.embed(text)?))
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let dense = self.dense_async.embed_query(text).await?;
+        Ok(self.combine(dense, self.sparse.embed_query(text)?))
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let dense = self.dense_async.embed_batch(texts).await?;
+        self.combine_batch(dense, texts)
+    }
+
+    fn dimensions(&self) -> usize {
+        Embedder::dimensions(self)
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.dense_async.summarized_by(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::{cosine_similarity, TfIdfEmbedder};
+
+    #[test]
+    fn test_similarity_is_weighted_blend() {
+        let dense: Arc<dyn Embedder> = Arc::new(TfIdfEmbedder::new(64));
+        let sparse: Arc<dyn Embedder> = Arc::new(TfIdfEmbedder::new(128));
+        let hybrid: Arc<dyn Embedder> =
+            Arc::new(HybridEmbedder::new(dense.clone(), sparse.clone()).with_sparse_weight(0.25));
+        assert_eq!(hybrid.dimensions(), 192);
+        assert_eq!(hybrid.hybrid_dense_dims(), Some(64));
+        assert_eq!(hybrid.model_name(), "hybrid:tfidf+tfidf");
+
+        let (a, b) = ("error E1234 in checkout", "checkout failed with E1234");
+        let blended = cosine_similarity(&hybrid.embed(a).unwrap(), &hybrid.embed(b).unwrap());
+        let expected = 0.75 * cosine_similarity(&dense.embed(a).unwrap(), &dense.embed(b).unwrap())
+            + 0.25 * cosine_similarity(&sparse.embed(a).unwrap(), &sparse.embed(b).unwrap());
+        assert!((blended - expected).abs() < 1e-4, "{blended} vs {expected}");
+
+        let vector = hybrid.embed_query(a).unwrap();
+        let (dense_part, sparse_part) = hybrid_parts(&vector, 64).unwrap();
+        assert_eq!((dense_part.len(), sparse_part.len()), (64, 128));
+        assert!(hybrid_parts(&dense.embed(a).unwrap(), 64).is_none());
+
+        let batch = hybrid.embed_batch(&[a, b]).unwrap();
+        assert_eq!(batch[0], hybrid.embed(a).unwrap());
+    }
+}
@@ -0,0 +1,293 @@
+//! Summarize-then-embed for long memories
+//!
+//! Embedding APIs silently truncate inputs past their context window, so a
+//! long memory ends up embedded by its first few thousand tokens.
+//! [`SummarizingEmbedder`] detects text longer than a character budget and
+//! embeds a summary of it instead:
+//! - [`ExtractiveTextSummarizer`] (default): the sentences whose words recur
+//!   most across the text, in their original order, up to the budget
+//! - `LlmSummarizer` (`openai` feature): asks the chat model configured with
+//!   `ENGRAM_SESSION_SUMMARY_MODEL`
+//!
+//! Only the embedding input changes; the memory keeps its full content for
+//! full-text search. Which summarizer produced the embedded text is recorded
+//! in `embeddings.summarized_by` ([`Embedder::summarized_by`]).
+//!
+//! Configured with `EmbeddingConfig::summarize_over_chars` and
+//! `EmbeddingConfig::summarizer`.
+
+use std::sync::Arc;
+
+use super::{AsyncEmbedder, Embedder, ReductionHandle};
+use crate::error::{EngramError, Result};
+use crate::intelligence::session_summary::{score_sentences, split_sentences, truncate_words};
+use crate::types::EmbeddingConfig;
+
+/// Condenses a long text for embedding
+pub trait TextSummarizer: Send + Sync {
+    /// A summary of `text` of at most `max_chars` characters
+    fn summarize(&self, text: &str, max_chars: usize) -> Result<String>;
+
+    /// Name recorded as the embedding's `summarized_by`
+    fn name(&self) -> &str;
+}
+
+/// Frequency-based extractive summarizer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractiveTextSummarizer;
+
+impl TextSummarizer for ExtractiveTextSummarizer {
+    fn summarize(&self, text: &str, max_chars: usize) -> Result<String> {
+        let sentences = split_sentences(text);
+        let mut ranked: Vec<(usize, f64)> = score_sentences(&sentences)
+            .into_iter()
+            .enumerate()
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        // Best sentences first, skipping any that no longer fit
+        let mut chosen = Vec::new();
+        let mut used = 0;
+        for (i, _) in ranked {
+            let len = sentences[i].chars().count() + usize::from(!chosen.is_empty());
+            if used + len <= max_chars {
+                used += len;
+                chosen.push(i);
+            }
+        }
+        if chosen.is_empty() {
+            return Ok(truncate_words(text.trim(), max_chars));
+        }
+        chosen.sort_unstable();
+        Ok(chosen
+            .into_iter()
+            .map(|i| sentences[i])
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+
+    fn name(&self) -> &str {
+        "extractive"
+    }
+}
+
+#[cfg(feature = "openai")]
+impl TextSummarizer for crate::intelligence::session_summary::LlmSummarizer {
+    fn summarize(&self, text: &str, max_chars: usize) -> Result<String> {
+        super::block_on(self.summarize_text_async(text, max_chars))
+    }
+
+    fn name(&self) -> &str {
+        crate::intelligence::session_summary::SessionSummarizer::name(self)
+    }
+}
+
+/// The summarizer named by `EmbeddingConfig::summarizer`
+pub fn create_text_summarizer(name: &str) -> Result<Arc<dyn TextSummarizer>> {
+    match name {
+        "extractive" => Ok(Arc::new(ExtractiveTextSummarizer)),
+        #[cfg(feature = "openai")]
+        "llm" => crate::intelligence::session_summary::LlmSummarizer::from_env()
+            .map(|llm| Arc::new(llm) as Arc<dyn TextSummarizer>)
+            .ok_or_else(|| {
+                EngramError::Config(
+                    "The llm embedding summarizer requires ENGRAM_SESSION_SUMMARY_MODEL and an API key"
+                        .to_string(),
+                )
+            }),
+        #[cfg(not(feature = "openai"))]
+        "llm" => Err(EngramError::Config(
+            "The llm embedding summarizer requires the 'openai' feature to be enabled".to_string(),
+        )),
+        _ => Err(EngramError::Config(format!(
+            "Unknown embedding summarizer: '{}'. Use 'extractive' or 'llm'",
+            name
+        ))),
+    }
+}
+
+/// Embedder that embeds a summary of texts longer than `max_chars`
+pub struct SummarizingEmbedder {
+    inner: Arc<dyn Embedder>,
+    inner_async: Arc<dyn AsyncEmbedder>,
+    summarizer: Arc<dyn TextSummarizer>,
+    max_chars: usize,
+}
+
+impl SummarizingEmbedder {
+    pub fn new(
+        inner: Arc<dyn Embedder>,
+        summarizer: Arc<dyn TextSummarizer>,
+        max_chars: usize,
+    ) -> Self {
+        Self {
+            inner_async: super::to_async(inner.clone()),
+            inner,
+            summarizer,
+            max_chars: max_chars.max(1),
+        }
+    }
+
+    fn is_long(&self, text: &str) -> bool {
+        text.chars().count() > self.max_chars
+    }
+
+    /// The text to embed for `text`
+    fn input(&self, text: &str) -> Result<String> {
+        if self.is_long(text) {
+            self.summarizer.summarize(text, self.max_chars)
+        } else {
+            Ok(text.to_string())
+        }
+    }
+
+    /// Summarize on the blocking pool, as summarizers may call out to an LLM
+    async fn input_async(&self, text: &str) -> Result<String> {
+        if !self.is_long(text) {
+            return Ok(text.to_string());
+        }
+        let summarizer = self.summarizer.clone();
+        let (text, max_chars) = (text.to_string(), self.max_chars);
+        tokio::task::spawn_blocking(move || summarizer.summarize(&text, max_chars))
+            .await
+            .map_err(|e| EngramError::Embedding(format!("Summarizer task failed: {}", e)))?
+    }
+}
+
+/// Wrap `embedder` in a [`SummarizingEmbedder`] when
+/// `config.summarize_over_chars` is set
+pub fn summarize_long_texts(
+    embedder: Arc<dyn Embedder>,
+    config: &EmbeddingConfig,
+) -> Result<Arc<dyn Embedder>> {
+    match config.summarize_over_chars {
+        Some(max_chars) if max_chars > 0 => Ok(Arc::new(SummarizingEmbedder::new(
+            embedder,
+            create_text_summarizer(&config.summarizer)?,
+            max_chars,
+        ))),
+        _ => Ok(embedder),
+    }
+}
+
+impl Embedder for SummarizingEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.inner.embed(&self.input(text)?)
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.inner.embed_query(&self.input(text)?)
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let inputs = texts
+            .iter()
+            .map(|text| self.input(text))
+            .collect::<Result<Vec<_>>>()?;
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        self.inner.embed_batch(&inputs)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.is_long(text).then(|| self.summarizer.name())
+    }
+
+    fn reduction(&self) -> Option<&ReductionHandle> {
+        self.inner.reduction()
+    }
+
+    fn hybrid_dense_dims(&self) -> Option<usize> {
+        self.inner.hybrid_dense_dims()
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEmbedder for SummarizingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let input = self.input_async(text).await?;
+        self.inner_async.embed(&input).await
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let input = self.input_async(text).await?;
+        self.inner_async.embed_query(&input).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut inputs = Vec::with_capacity(texts.len());
+        for text in texts {
+            inputs.push(self.input_async(text).await?);
+        }
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        self.inner_async.embed_batch(&inputs).await
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        Embedder::summarized_by(self, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+
+    #[test]
+    fn test_extractive_summary_fits_budget() {
+        let text = "The billing service retries failed payments. \
+                    Lunch was good today. \
+                    Failed payments are retried three times by the billing service. \
+                    The retry interval for failed payments doubles each time.";
+        let summary = ExtractiveTextSummarizer.summarize(text, 120).unwrap();
+        assert!(summary.chars().count() <= 120, "{summary}");
+        assert!(summary.contains("payments"));
+        assert!(!summary.contains("Lunch"));
+
+        // One overlong sentence is cut instead
+        let summary = ExtractiveTextSummarizer
+            .summarize(&"word ".repeat(100), 20)
+            .unwrap();
+        assert!(summary.chars().count() <= 20);
+    }
+
+    #[test]
+    fn test_long_texts_embed_their_summary() {
+        let inner: Arc<dyn Embedder> = Arc::new(TfIdfEmbedder::new(64));
+        let embedder: Arc<dyn Embedder> = Arc::new(SummarizingEmbedder::new(
+            inner.clone(),
+            Arc::new(ExtractiveTextSummarizer),
+            80,
+        ));
+        let short = "Deploys go out on Tuesdays.";
+        assert_eq!(embedder.summarized_by(short), None);
+        assert_eq!(embedder.embed(short).unwrap(), inner.embed(short).unwrap());
+
+        let long = format!("{} {}", short, "Filler text that goes on. ".repeat(10));
+        assert_eq!(embedder.summarized_by(&long), Some("extractive"));
+        let summary = ExtractiveTextSummarizer.summarize(&long, 80).unwrap();
+        assert_eq!(
+            embedder.embed(&long).unwrap(),
+            inner.embed(&summary).unwrap()
+        );
+    }
+}
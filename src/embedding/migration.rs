@@ -0,0 +1,733 @@
+//! Resumable re-embedding for embedding model migrations
+//!
+//! Switching models (say tfidf to openai) means re-embedding every memory.
+//! [`run_embedding_rebuild`](super::run_embedding_rebuild) clears every
+//! `has_embedding` flag up front, so semantic search is empty until it
+//! finishes. A migration instead walks memories in id order and replaces
+//! vectors in place, so old vectors keep serving until their replacement
+//! lands (queries only match them once an
+//! [adapter](super::adapter) bridges the old model's space into the new
+//! one):
+//!
+//! - the `embedding_migrations` row is the checkpoint: its `cursor` (the last
+//!   memory id handled) advances in the same transaction that stores a
+//!   batch, so a restart resumes exactly after the last stored batch;
+//! - memories already embedded by the target model (e.g. created during the
+//!   migration) are skipped;
+//! - requests are spaced by `requests_per_minute`, failed batches are retried
+//!   with back-off, and a batch that keeps failing pauses the migration
+//!   instead of skipping memories.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::queue::store_embedding;
+use super::Embedder;
+use crate::error::{EngramError, Result};
+use crate::search::vector_index::VectorIndexHandle;
+use crate::storage::Storage;
+use crate::types::MemoryId;
+
+/// Attempts per batch before the migration pauses
+const MAX_BATCH_ATTEMPTS: u32 = 4;
+
+/// Longest pause between attempts at a failing batch
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// State of a migration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStatus {
+    Running,
+    /// Stopped by request or after a batch kept failing; resumable
+    Paused,
+    Completed,
+    /// Given up; memories past the cursor keep whatever vectors they had
+    Cancelled,
+}
+
+impl MigrationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationStatus::Running => "running",
+            MigrationStatus::Paused => "paused",
+            MigrationStatus::Completed => "completed",
+            MigrationStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::str::FromStr for MigrationStatus {
+    type Err = EngramError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "running" => Ok(MigrationStatus::Running),
+            "paused" => Ok(MigrationStatus::Paused),
+            "completed" => Ok(MigrationStatus::Completed),
+            "cancelled" => Ok(MigrationStatus::Cancelled),
+            _ => Err(EngramError::InvalidInput(format!(
+                "Unknown migration status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Settings for a new migration
+#[derive(Debug, Clone)]
+pub struct MigrationOptions {
+    /// Memories per `embed_batch` call
+    pub batch_size: usize,
+    /// Provider request budget; `None` means unlimited
+    pub requests_per_minute: Option<u32>,
+}
+
+impl Default for MigrationOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 64,
+            requests_per_minute: None,
+        }
+    }
+}
+
+/// A migration's persisted state and progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingMigration {
+    pub id: String,
+    pub target_model: String,
+    pub target_dimensions: usize,
+    pub status: MigrationStatus,
+    /// Last memory id handled; the next batch starts after it
+    pub cursor: MemoryId,
+    pub batch_size: usize,
+    pub requests_per_minute: Option<u32>,
+    /// Live memories when the migration started
+    pub items_total: i64,
+    /// Memories re-embedded by this migration
+    pub items_embedded: i64,
+    /// Memories already on the target model when reached
+    pub items_skipped: i64,
+    pub progress_percent: i32,
+    pub eta_seconds: Option<i64>,
+    pub last_error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+fn parse_time(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+const MIGRATION_COLUMNS: &str = "id, target_model, target_dimensions, status, cursor, batch_size,
+     requests_per_minute, items_total, items_embedded, items_skipped, eta_seconds,
+     last_error, started_at, updated_at, completed_at";
+
+fn migration_from_row(row: &rusqlite::Row) -> rusqlite::Result<EmbeddingMigration> {
+    let status: String = row.get(3)?;
+    let items_total: i64 = row.get(7)?;
+    let items_embedded: i64 = row.get(8)?;
+    let items_skipped: i64 = row.get(9)?;
+    let started_at: String = row.get(12)?;
+    let updated_at: String = row.get(13)?;
+    let completed_at: Option<String> = row.get(14)?;
+    let status = status.parse().unwrap_or(MigrationStatus::Paused);
+    Ok(EmbeddingMigration {
+        id: row.get(0)?,
+        target_model: row.get(1)?,
+        target_dimensions: row.get::<_, i64>(2)? as usize,
+        status,
+        cursor: row.get(4)?,
+        batch_size: row.get::<_, i64>(5)? as usize,
+        requests_per_minute: row.get::<_, Option<i64>>(6)?.map(|v| v as u32),
+        items_total,
+        items_embedded,
+        items_skipped,
+        progress_percent: if status == MigrationStatus::Completed || items_total <= 0 {
+            100
+        } else {
+            (((items_embedded + items_skipped) * 100) / items_total).clamp(0, 99) as i32
+        },
+        eta_seconds: row.get(10)?,
+        last_error: row.get(11)?,
+        started_at: parse_time(&started_at),
+        updated_at: parse_time(&updated_at),
+        completed_at: completed_at.as_deref().map(parse_time),
+    })
+}
+
+/// Fetch a migration by id
+pub fn get_embedding_migration(conn: &Connection, id: &str) -> Result<Option<EmbeddingMigration>> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM embedding_migrations WHERE id = ?",
+                MIGRATION_COLUMNS
+            ),
+            params![id],
+            migration_from_row,
+        )
+        .optional()?)
+}
+
+/// The migration that is running or paused, if any (at most one exists)
+pub fn active_embedding_migration(conn: &Connection) -> Result<Option<EmbeddingMigration>> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM embedding_migrations
+                 WHERE status IN ('running', 'paused')
+                 ORDER BY started_at DESC LIMIT 1",
+                MIGRATION_COLUMNS
+            ),
+            [],
+            migration_from_row,
+        )
+        .optional()?)
+}
+
+/// Record a new migration to `embedder`'s model, starting from the first memory.
+///
+/// Fails while another migration is running or paused; resume or cancel
+/// that one first.
+pub fn start_embedding_migration(
+    conn: &Connection,
+    embedder: &dyn Embedder,
+    options: &MigrationOptions,
+) -> Result<EmbeddingMigration> {
+    if options.batch_size == 0 {
+        return Err(EngramError::InvalidInput(
+            "batch_size must be greater than 0".to_string(),
+        ));
+    }
+    if let Some(active) = active_embedding_migration(conn)? {
+        return Err(EngramError::Conflict(format!(
+            "Embedding migration {} is {}; resume or cancel it first",
+            active.id,
+            active.status.as_str()
+        )));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    let total: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM memories WHERE valid_to IS NULL",
+        [],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "INSERT INTO embedding_migrations
+             (id, target_model, target_dimensions, status, cursor, batch_size,
+              requests_per_minute, items_total, started_at, updated_at)
+         VALUES (?, ?, ?, 'running', 0, ?, ?, ?, ?, ?)",
+        params![
+            id,
+            embedder.model_name(),
+            embedder.dimensions() as i64,
+            options.batch_size as i64,
+            options.requests_per_minute.map(|v| v as i64),
+            total,
+            now,
+            now
+        ],
+    )?;
+
+    get_embedding_migration(conn, &id)?
+        .ok_or_else(|| EngramError::Internal("migration row vanished".to_string()))
+}
+
+/// Mark a migration to stop after its current batch.
+pub fn pause_embedding_migration(conn: &Connection, id: &str) -> Result<EmbeddingMigration> {
+    set_status(
+        conn,
+        id,
+        &[MigrationStatus::Running],
+        MigrationStatus::Paused,
+    )
+}
+
+/// Abandon a running or paused migration so another can start.
+pub fn cancel_embedding_migration(conn: &Connection, id: &str) -> Result<EmbeddingMigration> {
+    set_status(
+        conn,
+        id,
+        &[MigrationStatus::Running, MigrationStatus::Paused],
+        MigrationStatus::Cancelled,
+    )
+}
+
+/// Mark a paused migration as running again; call
+/// [`run_embedding_migration`] to continue it.
+pub fn resume_embedding_migration(conn: &Connection, id: &str) -> Result<EmbeddingMigration> {
+    set_status(
+        conn,
+        id,
+        &[MigrationStatus::Paused],
+        MigrationStatus::Running,
+    )
+}
+
+fn set_status(
+    conn: &Connection,
+    id: &str,
+    from: &[MigrationStatus],
+    to: MigrationStatus,
+) -> Result<EmbeddingMigration> {
+    let migration = get_embedding_migration(conn, id)?.ok_or_else(|| {
+        EngramError::InvalidInput(format!("Embedding migration not found: {}", id))
+    })?;
+    if !from.contains(&migration.status) {
+        return Err(EngramError::Conflict(format!(
+            "Embedding migration {} is {}; cannot mark it {}",
+            id,
+            migration.status.as_str(),
+            to.as_str()
+        )));
+    }
+    conn.execute(
+        "UPDATE embedding_migrations SET status = ?, updated_at = ? WHERE id = ?",
+        params![to.as_str(), Utc::now().to_rfc3339(), id],
+    )?;
+    get_embedding_migration(conn, id)?
+        .ok_or_else(|| EngramError::Internal("migration row vanished".to_string()))
+}
+
+/// Next batch after `cursor`, split into memories still to embed and the
+/// count already on the target model. Returns `None` when nothing is left.
+fn next_batch(
+    conn: &Connection,
+    migration: &EmbeddingMigration,
+) -> Result<Option<(Vec<(MemoryId, String)>, i64, MemoryId)>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT m.id, m.content,
+                COALESCE(e.model = ?2 AND e.dimensions = ?3, 0)
+         FROM memories m
+         LEFT JOIN embeddings e ON e.memory_id = m.id
+         WHERE m.id > ?1 AND m.valid_to IS NULL
+         ORDER BY m.id
+         LIMIT ?4",
+    )?;
+    let rows: Vec<(MemoryId, String, bool)> = stmt
+        .query_map(
+            params![
+                migration.cursor,
+                migration.target_model,
+                migration.target_dimensions as i64,
+                migration.batch_size as i64
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let Some(last) = rows.last().map(|(id, _, _)| *id) else {
+        return Ok(None);
+    };
+    let mut pending = Vec::with_capacity(rows.len());
+    let mut skipped = 0;
+    for (id, content, current) in rows {
+        if current {
+            skipped += 1;
+        } else {
+            pending.push((id, content));
+        }
+    }
+    Ok(Some((pending, skipped, last)))
+}
+
+/// Walk the remaining memories of migration `id`, re-embedding them with
+/// `embedder`, until done, paused, or a batch keeps failing.
+///
+/// Returns the final state. The embedder must produce the migration's target
+/// model; restart with the right embedding configuration otherwise. New
+/// vectors are added to `vector_index` as each batch is stored.
+pub async fn run_embedding_migration(
+    storage: Storage,
+    embedder: Arc<dyn Embedder>,
+    id: String,
+    vector_index: Option<Arc<VectorIndexHandle>>,
+) -> Result<EmbeddingMigration> {
+    let mut migration = storage
+        .with_connection(|conn| get_embedding_migration(conn, &id))?
+        .ok_or_else(|| {
+            EngramError::InvalidInput(format!("Embedding migration not found: {}", id))
+        })?;
+    if migration.status != MigrationStatus::Running {
+        return Ok(migration);
+    }
+    if embedder.model_name() != migration.target_model
+        || embedder.dimensions() != migration.target_dimensions
+    {
+        return Err(EngramError::Config(format!(
+            "Embedding migration {} targets {} ({} dims) but the embedder is {} ({} dims)",
+            id,
+            migration.target_model,
+            migration.target_dimensions,
+            embedder.model_name(),
+            embedder.dimensions()
+        )));
+    }
+
+    let min_interval = migration
+        .requests_per_minute
+        .filter(|&rpm| rpm > 0)
+        .map(|rpm| Duration::from_secs_f64(60.0 / rpm as f64));
+    let mut next_dispatch = Instant::now();
+    let started = Instant::now();
+    let mut embedded_this_run = 0i64;
+
+    loop {
+        // Re-read each round so a pause requested elsewhere takes effect.
+        migration = storage
+            .with_connection(|conn| get_embedding_migration(conn, &id))?
+            .ok_or_else(|| EngramError::Internal("migration row vanished".to_string()))?;
+        if migration.status != MigrationStatus::Running {
+            break;
+        }
+
+        let Some((pending, skipped, last)) =
+            storage.with_connection(|conn| next_batch(conn, &migration))?
+        else {
+            storage.with_connection(|conn| {
+                let now = Utc::now().to_rfc3339();
+                conn.execute(
+                    "UPDATE embedding_migrations
+                     SET status = 'completed', eta_seconds = 0, updated_at = ?, completed_at = ?
+                     WHERE id = ?",
+                    params![now, now, id],
+                )?;
+                Ok(())
+            })?;
+            tracing::info!(
+                "Embedding migration {} to {} completed",
+                id,
+                migration.target_model
+            );
+            continue;
+        };
+
+        let mut attempt = 0;
+        let embeddings = loop {
+            if pending.is_empty() {
+                break Vec::new();
+            }
+            if let Some(interval) = min_interval {
+                tokio::time::sleep_until(next_dispatch.into()).await;
+                next_dispatch = Instant::now() + interval;
+            }
+            let texts: Vec<String> = pending.iter().map(|(_, c)| c.clone()).collect();
+            let worker = embedder.clone();
+            let outcome = tokio::task::spawn_blocking(move || {
+                let refs: Vec<&str> = texts.iter().map(String::as_str).collect();
+                worker.embed_batch(&refs)
+            })
+            .await
+            .map_err(|e| EngramError::Internal(format!("Embedding worker panicked: {}", e)))?;
+
+            let error = match outcome {
+                Ok(embeddings) if embeddings.len() == pending.len() => break embeddings,
+                Ok(embeddings) => format!(
+                    "Embedder returned {} embeddings for {} texts",
+                    embeddings.len(),
+                    pending.len()
+                ),
+                Err(e) => e.to_string(),
+            };
+
+            attempt += 1;
+            storage.with_connection(|conn| {
+                conn.execute(
+                    "UPDATE embedding_migrations SET last_error = ?, updated_at = ? WHERE id = ?",
+                    params![error, Utc::now().to_rfc3339(), id],
+                )?;
+                Ok(())
+            })?;
+            if attempt >= MAX_BATCH_ATTEMPTS {
+                tracing::warn!(
+                    "Embedding migration {} paused after {} failed attempts: {}",
+                    id,
+                    attempt,
+                    error
+                );
+                let paused = storage.with_connection(|conn| {
+                    conn.execute(
+                        "UPDATE embedding_migrations SET status = 'paused' WHERE id = ?",
+                        params![id],
+                    )?;
+                    get_embedding_migration(conn, &id)
+                })?;
+                return paused
+                    .ok_or_else(|| EngramError::Internal("migration row vanished".to_string()));
+            }
+            let backoff = (Duration::from_secs(1) * 2u32.pow(attempt - 1)).min(MAX_BACKOFF);
+            tracing::warn!(
+                "Embedding migration {} batch failed, retrying in {:?}: {}",
+                id,
+                backoff,
+                error
+            );
+            tokio::time::sleep(backoff).await;
+        };
+
+        embedded_this_run += pending.len() as i64;
+        let done = migration.items_embedded + migration.items_skipped;
+        let remaining = (migration.items_total - done - pending.len() as i64 - skipped).max(0);
+        let eta = if embedded_this_run > 0 {
+            let per_item = started.elapsed().as_secs_f64() / embedded_this_run as f64;
+            Some((per_item * remaining as f64).ceil() as i64)
+        } else {
+            None
+        };
+
+        let target_model = migration.target_model.clone();
+        let target_dimensions = migration.target_dimensions;
+        storage.with_transaction(|conn| {
+            let now = Utc::now().to_rfc3339();
+            for ((memory_id, content), embedding) in pending.iter().zip(&embeddings) {
+                store_embedding(
+                    conn,
+                    *memory_id,
+                    embedding,
+                    &target_model,
+                    target_dimensions,
+                    embedder.summarized_by(content),
+                    &now,
+                )?;
+            }
+            conn.execute(
+                "UPDATE embedding_migrations
+                 SET cursor = ?, items_embedded = items_embedded + ?,
+                     items_skipped = items_skipped + ?, eta_seconds = ?,
+                     last_error = NULL, updated_at = ?
+                 WHERE id = ?",
+                params![last, pending.len() as i64, skipped, eta, now, id],
+            )?;
+            Ok(())
+        })?;
+        if let Some(index) = &vector_index {
+            for ((memory_id, _), embedding) in pending.iter().zip(&embeddings) {
+                index.insert(*memory_id, embedding);
+            }
+        }
+    }
+
+    Ok(migration)
+}
+
+/// Run migration `id` on a dedicated thread and runtime.
+///
+/// Embedders block on async HTTP calls, which needs a multi-threaded runtime
+/// regardless of the caller's context.
+pub fn spawn_embedding_migration(
+    storage: Storage,
+    embedder: Arc<dyn Embedder>,
+    id: String,
+    vector_index: Option<Arc<VectorIndexHandle>>,
+) -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name("embedding-migration".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(e) => {
+                    tracing::error!("Failed to start embedding migration runtime: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = runtime.block_on(run_embedding_migration(
+                storage,
+                embedder,
+                id,
+                vector_index,
+            )) {
+                tracing::error!("Embedding migration failed: {}", e);
+            }
+        })
+        .map(|_| ())
+}
+
+/// Counts of stored embeddings per model, for status reporting
+pub fn embedding_model_counts(conn: &Connection) -> Result<Vec<(String, usize, i64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.model, e.dimensions, COUNT(*)
+         FROM embeddings e JOIN memories m ON m.id = e.memory_id
+         WHERE m.valid_to IS NULL
+         GROUP BY e.model, e.dimensions
+         ORDER BY COUNT(*) DESC",
+    )?;
+    let counts = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get::<_, i64>(1)? as usize, row.get(2)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+    use crate::storage::queries::create_memory;
+    use crate::types::CreateMemoryInput;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// TF-IDF under another model name that fails `embed_batch` calls
+    /// numbered in `fail_from..fail_to`.
+    struct TargetEmbedder {
+        inner: TfIdfEmbedder,
+        calls: AtomicUsize,
+        fail_from: usize,
+        fail_to: usize,
+    }
+
+    impl TargetEmbedder {
+        fn new(fail_from: usize, fail_to: usize) -> Self {
+            Self {
+                inner: TfIdfEmbedder::new(48),
+                calls: AtomicUsize::new(0),
+                fail_from,
+                fail_to,
+            }
+        }
+    }
+
+    impl Embedder for TargetEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.inner.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if (self.fail_from..self.fail_to).contains(&call) {
+                return Err(EngramError::Embedding(
+                    "503 Service Unavailable".to_string(),
+                ));
+            }
+            self.inner.embed_batch(texts)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn model_name(&self) -> &str {
+            "target-model"
+        }
+    }
+
+    fn seeded(count: usize) -> Storage {
+        let storage = Storage::open_in_memory().unwrap();
+        let old = TfIdfEmbedder::new(16);
+        storage
+            .with_transaction(|conn| {
+                let now = Utc::now().to_rfc3339();
+                for i in 0..count {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: format!("note {i} about the migration"),
+                            ..Default::default()
+                        },
+                    )?;
+                    let vector = old.embed(&memory.content)?;
+                    store_embedding(conn, memory.id, &vector, old.model_name(), 16, None, &now)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+        storage
+    }
+
+    fn counts(storage: &Storage) -> Vec<(String, usize, i64)> {
+        storage.with_connection(embedding_model_counts).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_migration_replaces_vectors_in_place() {
+        let storage = seeded(20);
+        let embedder: Arc<dyn Embedder> = Arc::new(TargetEmbedder::new(0, 0));
+        let options = MigrationOptions {
+            batch_size: 6,
+            requests_per_minute: None,
+        };
+        let started = storage
+            .with_connection(|conn| start_embedding_migration(conn, embedder.as_ref(), &options))
+            .unwrap();
+        assert_eq!(started.items_total, 20);
+
+        // A second migration cannot start while this one is active.
+        assert!(storage
+            .with_connection(|conn| start_embedding_migration(conn, embedder.as_ref(), &options))
+            .is_err());
+
+        let done = run_embedding_migration(storage.clone(), embedder, started.id.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(done.status, MigrationStatus::Completed);
+        assert_eq!(done.items_embedded, 20);
+        assert_eq!(done.progress_percent, 100);
+        assert_eq!(counts(&storage), vec![("target-model".to_string(), 48, 20)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pause_resume_and_batch_retry() {
+        let storage = seeded(10);
+        // The second batch fails once and succeeds on retry.
+        let embedder: Arc<dyn Embedder> = Arc::new(TargetEmbedder::new(1, 2));
+        let options = MigrationOptions {
+            batch_size: 4,
+            requests_per_minute: None,
+        };
+        let started = storage
+            .with_connection(|conn| start_embedding_migration(conn, embedder.as_ref(), &options))
+            .unwrap();
+
+        storage
+            .with_connection(|conn| pause_embedding_migration(conn, &started.id))
+            .unwrap();
+        let paused = run_embedding_migration(storage.clone(), embedder.clone(), started.id.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(paused.status, MigrationStatus::Paused);
+        assert_eq!(paused.items_embedded, 0);
+        // Old vectors keep serving while the migration waits.
+        assert_eq!(counts(&storage), vec![("tfidf".to_string(), 16, 10)]);
+
+        storage
+            .with_connection(|conn| resume_embedding_migration(conn, &started.id))
+            .unwrap();
+        let done = run_embedding_migration(storage.clone(), embedder, started.id, None)
+            .await
+            .unwrap();
+        assert_eq!(done.status, MigrationStatus::Completed);
+        assert_eq!(done.items_embedded, 10);
+        assert!(done.last_error.is_none());
+        assert_eq!(counts(&storage), vec![("target-model".to_string(), 48, 10)]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_rejects_mismatched_embedder() {
+        let storage = seeded(2);
+        let target: Arc<dyn Embedder> = Arc::new(TargetEmbedder::new(0, 0));
+        let migration = storage
+            .with_connection(|conn| {
+                start_embedding_migration(conn, target.as_ref(), &MigrationOptions::default())
+            })
+            .unwrap();
+
+        let other: Arc<dyn Embedder> = Arc::new(TfIdfEmbedder::new(16));
+        assert!(run_embedding_migration(storage, other, migration.id, None)
+            .await
+            .is_err());
+    }
+}
@@ -2,25 +2,66 @@
 //!
 //! Supports multiple embedding backends:
 //! - OpenAI API (text-embedding-3-small) - requires `openai` feature
+//! - Local ONNX model (all-MiniLM-L6-v2) - requires `onnx-embed` feature
+//! - Local sentence-transformers on candle, on Metal or CUDA GPUs -
+//!   requires `candle-embed` feature
+//! - Cohere API (embed-english-v3.0) - requires `cohere` feature
+//! - Voyage AI API (voyage-2) - requires `voyage` feature
+//! - Hugging Face Inference API or a text-embeddings-inference server -
+//!   requires `hf-inference` feature
 //! - TF-IDF fallback (no external dependencies)
 //!
 //! Features:
 //! - LRU embedding cache with zero-copy Arc<[f32]> sharing
 //! - Async queue processing for batch operations
+//! - Resumable re-embedding when switching embedding models
+//! - Optional Matryoshka truncation or PCA to shrink stored vectors
+//! - Summaries embedded in place of memories too long for the model
+//! - Query and document instruction prefixes (E5, BGE)
+//! - Configurable text normalization (markdown, URLs, case, length) before
+//!   embedding
+//! - Optional TF-IDF vectors appended to dense ones, so exact terms still
+//!   count in semantic search
+//! - Retries with back-off and request/token rate limits for hosted APIs
+//! - Throughput benchmark across batch sizes
 //!
 //! # Feature Flags
 //!
 //! - `openai`: Enables OpenAI embedding backend (requires API key)
-
+//! - `onnx-embed`: Enables the offline ONNX backend (`ENGRAM_EMBEDDING_MODEL=local`)
+//! - `candle-embed`: Enables the candle backend (`ENGRAM_EMBEDDING_MODEL=candle`);
+//!   `candle-metal` / `candle-cuda` add GPU support
+//! - `cohere`: Enables the Cohere backend (`ENGRAM_EMBEDDING_MODEL=cohere`)
+//! - `voyage`: Enables the Voyage AI backend (`ENGRAM_EMBEDDING_MODEL=voyage`)
+//! - `hf-inference`: Enables the Hugging Face backend (`ENGRAM_EMBEDDING_MODEL=hf`)
+
+pub mod adapter;
+mod async_embedder;
+pub mod benchmark;
 mod cache;
+mod hybrid;
+pub mod long_text;
+pub mod migration;
+pub mod normalize;
+mod prefix;
 mod provider;
 mod queue;
+pub mod rebuild;
+pub mod reduction;
+pub mod similarity_matrix;
 mod tfidf;
+pub mod throttle;
+mod token_limit;
+mod wordpiece;
 
+#[cfg(feature = "candle-embed")]
+pub mod candle;
 #[cfg(feature = "cohere")]
 pub mod cohere;
 #[cfg(feature = "multimodal")]
 pub mod clip;
+#[cfg(feature = "hf-inference")]
+pub mod hf_inference;
 #[cfg(feature = "ollama")]
 pub mod ollama;
 #[cfg(feature = "onnx-embed")]
@@ -28,12 +69,42 @@ pub mod onnx;
 #[cfg(feature = "voyage")]
 pub mod voyage;
 
+pub use adapter::{
+    fit_embedding_adapter, list_embedding_adapters, set_active_embedding_model, AdapterOptions,
+    LinearAdapter,
+};
+pub use async_embedder::{block_on, embed_query, to_async, AsyncEmbedder, BlockingEmbedder};
+pub use benchmark::{benchmark_embedder, BatchSizeTiming, EmbeddingBenchmark};
 pub use cache::{EmbeddingCache, EmbeddingCacheStats};
+pub use hybrid::{hybrid_parts, HybridEmbedder, DEFAULT_SPARSE_WEIGHT};
 #[cfg(feature = "multimodal")]
 pub use clip::{ClipEmbedder, MultimodalEmbedder, CLIP_PROVIDER_NAME};
+pub use migration::{
+    active_embedding_migration, cancel_embedding_migration, embedding_model_counts,
+    get_embedding_migration, pause_embedding_migration, resume_embedding_migration,
+    run_embedding_migration, spawn_embedding_migration, start_embedding_migration,
+    EmbeddingMigration, MigrationOptions, MigrationStatus,
+};
+pub use long_text::{
+    create_text_summarizer, summarize_long_texts, ExtractiveTextSummarizer, SummarizingEmbedder,
+    TextSummarizer,
+};
+pub use normalize::{normalize_embedder, NormalizingEmbedder};
+pub use prefix::{with_instruction_prefixes, PrefixingEmbedder};
 pub use provider::{EmbeddingProvider, EmbeddingProviderInfo, EmbeddingRegistry};
-pub use queue::{get_embedding, get_embedding_status, EmbeddingQueue, EmbeddingWorker};
-pub use tfidf::TfIdfEmbedder;
+pub use queue::{
+    get_embedding, get_embedding_status, list_embedding_dead_letters,
+    requeue_embedding_dead_letters, EmbeddingQueue, EmbeddingWorker, DEFAULT_MAX_ATTEMPTS,
+};
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use queue::store_embedding;
+pub use rebuild::{rebuild_status, run_embedding_rebuild, RebuildOptions};
+pub use reduction::{ReducingEmbedder, ReductionHandle, ReductionSpec};
+pub use similarity_matrix::{similarity_matrix, SimilarityMatrix, SimilarityMatrixOptions};
+pub use tfidf::{TfIdfEmbedder, TfIdfVocabulary};
+pub use throttle::{ApiThrottle, PartialBatch, RetryPolicy, ThrottleConfig};
+pub use token_limit::{with_token_limit, TokenLimitEmbedder};
+pub use wordpiece::WordPieceTokenizer;
 
 use std::sync::Arc;
 
@@ -45,6 +116,14 @@ pub trait Embedder: Send + Sync {
     /// Generate embedding for a single text
     fn embed(&self, text: &str) -> Result<Vec<f32>>;
 
+    /// Generate embedding for a search query
+    ///
+    /// Providers that embed queries and documents differently (Cohere,
+    /// Voyage) override this; everyone else embeds queries like documents.
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed(text)
+    }
+
     /// Generate embeddings for multiple texts (batch)
     fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
         texts.iter().map(|t| self.embed(t)).collect()
@@ -55,12 +134,39 @@ pub trait Embedder: Send + Sync {
 
     /// Get model name
     fn model_name(&self) -> &str;
+
+    /// Dimensionality reduction applied to this embedder's output, if any
+    fn reduction(&self) -> Option<&ReductionHandle> {
+        None
+    }
+
+    /// Name of the summarizer whose summary of `text` is embedded in its
+    /// place ([`SummarizingEmbedder`]); `None` when `text` is embedded as is
+    fn summarized_by(&self, _text: &str) -> Option<&str> {
+        None
+    }
+
+    /// For hybrid sparse + dense vectors ([`HybridEmbedder`]): how many
+    /// leading components are dense
+    fn hybrid_dense_dims(&self) -> Option<usize> {
+        None
+    }
+
+    /// This embedder's own [`AsyncEmbedder`] implementation, for backends
+    /// whose calls are async underneath. `None` (the default) makes
+    /// [`to_async`] run the sync methods on the blocking pool.
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        None
+    }
 }
 
 /// OpenAI embedding client
 ///
 /// Requires the `openai` feature to be enabled.
 /// Supports OpenAI, OpenRouter, Azure OpenAI, and other OpenAI-compatible APIs.
+/// Transient failures (429, 5xx) are retried with back-off, and requests can
+/// be throttled to a requests- and tokens-per-minute budget; see
+/// [`Self::with_throttle`].
 #[cfg(feature = "openai")]
 pub struct OpenAIEmbedder {
     client: reqwest::Client,
@@ -68,19 +174,19 @@ pub struct OpenAIEmbedder {
     base_url: String,
     model: String,
     dimensions: usize,
+    retry: RetryPolicy,
+    throttle: ApiThrottle,
 }
 
+/// Most inputs the OpenAI embeddings endpoint accepts per request
+#[cfg(feature = "openai")]
+const OPENAI_MAX_BATCH_SIZE: usize = 2048;
+
 #[cfg(feature = "openai")]
 impl OpenAIEmbedder {
     /// Create a new OpenAI embedder with default settings
     pub fn new(api_key: String) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            api_key,
-            base_url: "https://api.openai.com/v1".to_string(),
-            model: "text-embedding-3-small".to_string(),
-            dimensions: 1536,
-        }
+        Self::with_config(api_key, None, None, None)
     }
 
     /// Create a new OpenAI embedder with custom settings
@@ -102,23 +208,36 @@ impl OpenAIEmbedder {
             base_url: base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
             model: model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
             dimensions: dimensions.unwrap_or(1536),
+            retry: RetryPolicy::default(),
+            throttle: ApiThrottle::default(),
         }
     }
 
     /// Legacy constructor for backwards compatibility
     pub fn with_model(api_key: String, model: String, dimensions: usize) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            api_key,
-            base_url: "https://api.openai.com/v1".to_string(),
-            model,
-            dimensions,
-        }
+        Self::with_config(api_key, None, Some(model), Some(dimensions))
     }
 
-    /// Async embedding call to OpenAI-compatible API
-    pub async fn embed_async(&self, text: &str) -> Result<Vec<f32>> {
+    /// Use `config`'s retry policy and rate limits instead of the defaults
+    /// (5 retries, no rate limits)
+    pub fn with_throttle(mut self, config: ThrottleConfig) -> Self {
+        self.retry = config.retry;
+        self.throttle = ApiThrottle::new(config.requests_per_minute, config.tokens_per_minute);
+        self
+    }
+
+    /// One call to the embeddings endpoint, without retries
+    async fn request_embeddings(
+        &self,
+        input: &[&str],
+    ) -> std::result::Result<Vec<Vec<f32>>, throttle::ApiFailure> {
+        use throttle::ApiFailure;
+
         let url = format!("{}/embeddings", self.base_url);
+        let transient = |e: reqwest::Error| ApiFailure::Transient {
+            message: e.to_string(),
+            retry_after: None,
+        };
 
         let response = self
             .client
@@ -129,120 +248,139 @@ impl OpenAIEmbedder {
             // Optional: helps OpenRouter track usage
             .header("X-Title", "Engram Memory")
             .json(&serde_json::json!({
-                "input": text,
+                "input": input,
                 "model": self.model,
             }))
             .send()
-            .await?;
+            .await
+            .map_err(transient)?;
 
         if !response.status().is_success() {
             let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(throttle::parse_retry_after);
             let text = response.text().await.unwrap_or_default();
-            return Err(EngramError::Embedding(format!(
-                "Embedding API error {}: {}",
-                status, text
-            )));
+            return Err(ApiFailure::from_status(
+                status.as_u16(),
+                format!("Embedding API error {}: {}", status, text),
+                retry_after,
+            ));
         }
 
-        let data: serde_json::Value = response.json().await?;
-        let embedding: Vec<f32> = data["data"][0]["embedding"]
+        let data: serde_json::Value = response.json().await.map_err(transient)?;
+        let invalid = || ApiFailure::Permanent {
+            status: None,
+            message: "Invalid response format".to_string(),
+        };
+        let mut items: Vec<(u64, Vec<f32>)> = data["data"]
             .as_array()
-            .ok_or_else(|| EngramError::Embedding("Invalid response format".to_string()))?
+            .ok_or_else(invalid)?
             .iter()
-            .filter_map(|v| v.as_f64().map(|f| f as f32))
+            .enumerate()
+            .map(|(position, item)| {
+                let embedding = item["embedding"]
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_f64().map(|f| f as f32))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let index = item["index"].as_u64().unwrap_or(position as u64);
+                (index, embedding)
+            })
             .collect();
+        items.sort_by_key(|(index, _)| *index);
 
         // Validate dimensions match configuration
-        if embedding.len() != self.dimensions {
-            return Err(EngramError::Embedding(format!(
-                "Embedding dimensions mismatch: expected {}, got {}. Set OPENAI_EMBEDDING_DIMENSIONS={} to match your model.",
-                self.dimensions, embedding.len(), embedding.len()
-            )));
+        if let Some((_, embedding)) = items.iter().find(|(_, e)| e.len() != self.dimensions) {
+            return Err(ApiFailure::Permanent {
+                status: None,
+                message: format!(
+                    "Embedding dimensions mismatch: expected {}, got {}. Set OPENAI_EMBEDDING_DIMENSIONS={} to match your model.",
+                    self.dimensions, embedding.len(), embedding.len()
+                ),
+            });
         }
 
-        Ok(embedding)
+        Ok(items.into_iter().map(|(_, embedding)| embedding).collect())
+    }
+
+    /// Async embedding call to OpenAI-compatible API
+    pub async fn embed_async(&self, text: &str) -> Result<Vec<f32>> {
+        let tokens = throttle::estimate_tokens(text);
+        let embeddings = throttle::send_with_retry(&self.retry, &self.throttle, tokens, || {
+            self.request_embeddings(std::slice::from_ref(&text))
+        })
+        .await?;
+        embeddings
+            .into_iter()
+            .next()
+            .ok_or_else(|| EngramError::Embedding("Invalid response format".to_string()))
     }
 
     /// Async batch embedding (up to 2048 inputs per call)
+    ///
+    /// Fails if any text could not be embedded; see
+    /// [`Self::embed_batch_partial_async`] to keep the rest.
     pub async fn embed_batch_async(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
-        if texts.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let url = format!("{}/embeddings", self.base_url);
-
-        // OpenAI allows up to 2048 inputs per batch
-        let mut all_embeddings = Vec::with_capacity(texts.len());
-
-        for chunk in texts.chunks(2048) {
-            let response = self
-                .client
-                .post(&url)
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                // OpenRouter requires HTTP-Referer header
-                .header("HTTP-Referer", "https://github.com/engram")
-                .header("X-Title", "Engram Memory")
-                .json(&serde_json::json!({
-                    "input": chunk,
-                    "model": self.model,
-                }))
-                .send()
-                .await?;
-
-            if !response.status().is_success() {
-                let status = response.status();
-                let text = response.text().await.unwrap_or_default();
-                return Err(EngramError::Embedding(format!(
-                    "Embedding API error {}: {}",
-                    status, text
-                )));
-            }
-
-            let data: serde_json::Value = response.json().await?;
-            let embeddings: Vec<Vec<f32>> = data["data"]
-                .as_array()
-                .ok_or_else(|| EngramError::Embedding("Invalid response format".to_string()))?
-                .iter()
-                .map(|item| {
-                    item["embedding"]
-                        .as_array()
-                        .map(|arr| {
-                            arr.iter()
-                                .filter_map(|v| v.as_f64().map(|f| f as f32))
-                                .collect()
-                        })
-                        .unwrap_or_default()
-                })
-                .collect();
-
-            // Validate dimensions on first batch
-            if !embeddings.is_empty() && embeddings[0].len() != self.dimensions {
-                return Err(EngramError::Embedding(format!(
-                    "Embedding dimensions mismatch: expected {}, got {}. Set OPENAI_EMBEDDING_DIMENSIONS={} to match your model.",
-                    self.dimensions, embeddings[0].len(), embeddings[0].len()
-                )));
-            }
-
-            all_embeddings.extend(embeddings);
-        }
+        self.embed_batch_partial_async(texts).await.into_result()
+    }
 
-        Ok(all_embeddings)
+    /// Async batch embedding that keeps what succeeded
+    ///
+    /// Texts are sent in chunks of up to 2048 inputs, and under the
+    /// tokens-per-minute limit when one is set. Transient failures are
+    /// retried per chunk, a chunk rejected for its input is split to isolate
+    /// the offending texts, and chunks embedded before the API gave up are
+    /// kept.
+    pub async fn embed_batch_partial_async(&self, texts: &[&str]) -> PartialBatch {
+        throttle::embed_chunks(
+            texts,
+            OPENAI_MAX_BATCH_SIZE,
+            &self.retry,
+            &self.throttle,
+            |chunk| self.request_embeddings(chunk),
+        )
+        .await
     }
 }
 
 #[cfg(feature = "openai")]
 impl Embedder for OpenAIEmbedder {
     fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        // Blocking call for sync interface
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(self.embed_async(text))
-        })
+        block_on(self.embed_async(text))
     }
 
     fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(self.embed_batch_async(texts))
-        })
+        block_on(self.embed_batch_async(texts))
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[cfg(feature = "openai")]
+#[async_trait::async_trait]
+impl AsyncEmbedder for OpenAIEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embed_async(text).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.embed_batch_async(texts).await
     }
 
     fn dimensions(&self) -> usize {
@@ -258,13 +396,41 @@ impl Embedder for OpenAIEmbedder {
 ///
 /// Available models depend on enabled features:
 /// - `"tfidf"`: Always available, no external dependencies
-/// - `"openai"`: Requires `openai` feature and API key
+/// - `"openai"`: Requires `openai` feature and API key. Retries and rate
+///   limits come from `OPENAI_MAX_RETRIES`, `OPENAI_REQUESTS_PER_MINUTE` and
+///   `OPENAI_TOKENS_PER_MINUTE`
+/// - `"local"`: Requires `onnx-embed` feature; loads the ONNX model and
+///   tokenizer from `model_path` (default: `~/.local/share/engram/models/all-MiniLM-L6-v2`)
+/// - `"candle"`: Requires `candle-embed` feature; loads the sentence-transformer
+///   in `model_path` (same default as `"local"`) onto `device`, batching
+///   forward passes by `inference_batch_size` and `max_batch_tokens`
+/// - `"cohere"`: Requires `cohere` feature and API key
+/// - `"voyage"`: Requires `voyage` feature and API key
+/// - `"hf"`: Requires `hf-inference` feature. Without `base_url`, calls the
+///   hosted Inference API for `embedding_model` (default
+///   `sentence-transformers/all-MiniLM-L6-v2`); with a `base_url` and no
+///   `embedding_model`, calls that text-embeddings-inference server. The API
+///   key, if any, is sent as the access token
+///
+/// `embedding_model`, `base_url` and `dimensions` override the Cohere and
+/// Voyage defaults as well.
 ///
 /// For OpenAI-compatible APIs (OpenRouter, Azure, etc.), set:
 /// - `base_url`: API endpoint (e.g., "https://openrouter.ai/api/v1")
 /// - `embedding_model`: Model name (e.g., "openai/text-embedding-3-small")
 /// - `dimensions`: Expected output dimensions
 pub fn create_embedder(config: &EmbeddingConfig) -> Result<Arc<dyn Embedder>> {
+    summarize_long_texts(
+        normalize_embedder(
+            with_instruction_prefixes(with_token_limit(create_backend(config)?, config)?, config),
+            &config.normalization,
+        ),
+        config,
+    )
+}
+
+/// The embedder for `config.model`, without normalization
+fn create_backend(config: &EmbeddingConfig) -> Result<Arc<dyn Embedder>> {
     match config.model.as_str() {
         #[cfg(feature = "multimodal")]
         "clip" => {
@@ -279,20 +445,131 @@ pub fn create_embedder(config: &EmbeddingConfig) -> Result<Arc<dyn Embedder>> {
                 .ok_or_else(|| EngramError::Config(
                     "OPENAI_API_KEY required when ENGRAM_EMBEDDING_MODEL=openai".to_string()
                 ))?;
-            Ok(Arc::new(OpenAIEmbedder::with_config(
-                api_key,
-                config.base_url.clone(),
-                config.embedding_model.clone(),
-                Some(config.dimensions),
-            )))
+            Ok(Arc::new(
+                OpenAIEmbedder::with_config(
+                    api_key,
+                    config.base_url.clone(),
+                    config.embedding_model.clone(),
+                    Some(config.dimensions),
+                )
+                .with_throttle(ThrottleConfig::from_env("OPENAI")),
+            ))
         }
         #[cfg(not(feature = "openai"))]
         "openai" => Err(EngramError::Config(
             "OpenAI embeddings require the 'openai' feature to be enabled. Build with: cargo build --features openai".to_string(),
         )),
+        #[cfg(feature = "onnx-embed")]
+        "local" => {
+            let dir = match &config.model_path {
+                Some(path) => std::path::PathBuf::from(path),
+                None => onnx::default_local_model_dir()?,
+            };
+            Ok(Arc::new(onnx::LocalOnnxEmbedder::from_dir(
+                &dir,
+                config.dimensions,
+            )?))
+        }
+        #[cfg(not(feature = "onnx-embed"))]
+        "local" => Err(EngramError::Config(
+            "Local embeddings require the 'onnx-embed' feature to be enabled. Build with: cargo build --features onnx-embed".to_string(),
+        )),
+        #[cfg(feature = "candle-embed")]
+        "candle" => {
+            let model_dir = match &config.model_path {
+                Some(path) => std::path::PathBuf::from(path),
+                None => candle::default_candle_model_dir()?,
+            };
+            let device = match &config.device {
+                Some(device) => device.parse()?,
+                None => candle::CandleDevice::Auto,
+            };
+            Ok(Arc::new(candle::CandleEmbedder::new(candle::CandleConfig {
+                model_dir,
+                device,
+                batch_size: config
+                    .inference_batch_size
+                    .unwrap_or(candle::DEFAULT_INFERENCE_BATCH_SIZE),
+                max_batch_tokens: config
+                    .max_batch_tokens
+                    .unwrap_or(candle::DEFAULT_MAX_BATCH_TOKENS),
+                dimensions: Some(config.dimensions),
+                ..Default::default()
+            })?))
+        }
+        #[cfg(not(feature = "candle-embed"))]
+        "candle" => Err(EngramError::Config(
+            "Candle embeddings require the 'candle-embed' feature to be enabled. Build with: cargo build --features candle-embed (or candle-metal / candle-cuda)".to_string(),
+        )),
+        #[cfg(feature = "cohere")]
+        "cohere" => {
+            let api_key = config.api_key.clone().ok_or_else(|| {
+                EngramError::Config(
+                    "COHERE_API_KEY required when ENGRAM_EMBEDDING_MODEL=cohere".to_string(),
+                )
+            })?;
+            let defaults = cohere::CohereConfig::default();
+            Ok(Arc::new(cohere::CohereEmbedder::new(cohere::CohereConfig {
+                api_key,
+                model: config.embedding_model.clone().unwrap_or(defaults.model),
+                base_url: config.base_url.clone().unwrap_or(defaults.base_url),
+                dimensions: config.dimensions,
+                ..defaults
+            })))
+        }
+        #[cfg(not(feature = "cohere"))]
+        "cohere" => Err(EngramError::Config(
+            "Cohere embeddings require the 'cohere' feature to be enabled. Build with: cargo build --features cohere".to_string(),
+        )),
+        #[cfg(feature = "voyage")]
+        "voyage" => {
+            let api_key = config.api_key.clone().ok_or_else(|| {
+                EngramError::Config(
+                    "VOYAGE_API_KEY required when ENGRAM_EMBEDDING_MODEL=voyage".to_string(),
+                )
+            })?;
+            let defaults = voyage::VoyageConfig::default();
+            Ok(Arc::new(voyage::VoyageEmbedder::new(voyage::VoyageConfig {
+                api_key,
+                model: config.embedding_model.clone().unwrap_or(defaults.model),
+                base_url: config.base_url.clone().unwrap_or(defaults.base_url),
+                dimensions: config.dimensions,
+                ..defaults
+            })))
+        }
+        #[cfg(not(feature = "voyage"))]
+        "voyage" => Err(EngramError::Config(
+            "Voyage embeddings require the 'voyage' feature to be enabled. Build with: cargo build --features voyage".to_string(),
+        )),
+        #[cfg(feature = "hf-inference")]
+        "hf" => {
+            use hf_inference::{HfInferenceConfig, HfInferenceEmbedder, HF_INFERENCE_BASE_URL};
+
+            let (base_url, model) = match &config.base_url {
+                Some(url) => (url.clone(), config.embedding_model.clone()),
+                None => (
+                    HF_INFERENCE_BASE_URL.to_string(),
+                    config
+                        .embedding_model
+                        .clone()
+                        .or_else(|| HfInferenceConfig::default().model),
+                ),
+            };
+            Ok(Arc::new(HfInferenceEmbedder::new(HfInferenceConfig {
+                base_url,
+                token: config.api_key.clone(),
+                model,
+                dimensions: config.dimensions,
+                ..Default::default()
+            })))
+        }
+        #[cfg(not(feature = "hf-inference"))]
+        "hf" => Err(EngramError::Config(
+            "Hugging Face embeddings require the 'hf-inference' feature to be enabled. Build with: cargo build --features hf-inference".to_string(),
+        )),
         "tfidf" => Ok(Arc::new(TfIdfEmbedder::new(config.dimensions))),
         _ => Err(EngramError::Config(format!(
-            "Unknown embedding model: '{}'. Use 'openai' or 'tfidf'",
+            "Unknown embedding model: '{}'. Use 'openai', 'local', 'cohere', 'voyage', 'hf' or 'tfidf'",
             config.model
         ))),
     }
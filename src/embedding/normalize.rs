@@ -0,0 +1,370 @@
+//! Text normalization before embedding
+//!
+//! Two memories that say the same thing can still embed differently because
+//! one is markdown, one pastes a link and one shouts in capitals.
+//! [`TextNormalization`] (part of [`EmbeddingConfig`]) describes a
+//! preprocessing pipeline, run in this order:
+//!
+//! 1. fenced code blocks are kept, removed or replaced by a placeholder
+//! 2. markdown is reduced to its text
+//! 3. URLs are removed
+//! 4. whitespace is collapsed
+//! 5. the text is lowercased
+//! 6. overlong text is cut to `max_chars`, keeping the head or head and tail
+//!
+//! [`NormalizingEmbedder`] applies it to every document and query, so the
+//! embedding queue, duplicate detection and search all see the same text.
+//! [`create_embedder`](super::create_embedder) wraps its embedder in one
+//! whenever the configured pipeline changes anything.
+//!
+//! Configured with `ENGRAM_EMBEDDING_NORMALIZE`, e.g.
+//! `markdown,urls,whitespace,code:placeholder,max:8000:head_tail`.
+//!
+//! [`EmbeddingConfig`]: crate::types::EmbeddingConfig
+
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use pulldown_cmark::{Event, Parser, TagEnd};
+use regex::Regex;
+
+use super::{AsyncEmbedder, Embedder, ReductionHandle};
+use crate::error::{EngramError, Result};
+use crate::types::{CodeBlockPolicy, TextNormalization, Truncation};
+
+static URL_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"https?://[^\s<>\[\]()]+|www\.[^\s<>\[\]()]+").expect("valid regex"));
+
+impl TextNormalization {
+    /// Whether the pipeline leaves every text unchanged
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Run the pipeline over `text`
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.is_identity() {
+            return Cow::Borrowed(text);
+        }
+        let mut text = match self.code_blocks {
+            CodeBlockPolicy::Keep => text.to_string(),
+            policy => replace_code_blocks(text, policy),
+        };
+        if self.strip_markdown {
+            text = markdown_text(&text);
+        }
+        if self.strip_urls {
+            text = URL_PATTERN.replace_all(&text, "").into_owned();
+        }
+        if self.collapse_whitespace {
+            text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        if self.lowercase {
+            text = text.to_lowercase();
+        }
+        if let Some(max_chars) = self.max_chars {
+            text = truncate(&text, max_chars, self.truncation);
+        }
+        Cow::Owned(text)
+    }
+}
+
+impl FromStr for TextNormalization {
+    type Err = EngramError;
+
+    /// Parse a comma-separated list of steps: `lowercase`, `markdown`,
+    /// `urls`, `whitespace`, `code:<keep|remove|placeholder>` and
+    /// `max:<chars>[:<head|head_tail>]`. Empty or `none` changes nothing.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |step: &str| {
+            EngramError::Config(format!(
+                "Invalid text normalization step '{}': expected lowercase, markdown, urls, \
+                 whitespace, code:<keep|remove|placeholder> or max:<chars>[:head|head_tail]",
+                step
+            ))
+        };
+        let mut normalization = Self::default();
+        for step in s.split(',').map(str::trim).filter(|step| !step.is_empty()) {
+            let mut parts = step.split(':').map(str::trim);
+            match (parts.next().unwrap_or_default(), parts.next(), parts.next()) {
+                ("none", None, None) => {}
+                ("lowercase", None, None) => normalization.lowercase = true,
+                ("markdown", None, None) => normalization.strip_markdown = true,
+                ("urls", None, None) => normalization.strip_urls = true,
+                ("whitespace", None, None) => normalization.collapse_whitespace = true,
+                ("code", Some(policy), None) => {
+                    normalization.code_blocks = match policy {
+                        "keep" => CodeBlockPolicy::Keep,
+                        "remove" => CodeBlockPolicy::Remove,
+                        "placeholder" => CodeBlockPolicy::Placeholder,
+                        _ => return Err(invalid(step)),
+                    }
+                }
+                ("max", Some(chars), truncation) => {
+                    let chars = chars.parse::<usize>().map_err(|_| invalid(step))?;
+                    if chars == 0 {
+                        return Err(invalid(step));
+                    }
+                    normalization.max_chars = Some(chars);
+                    normalization.truncation = match truncation {
+                        None | Some("head") => Truncation::Head,
+                        Some("head_tail") => Truncation::HeadTail,
+                        Some(_) => return Err(invalid(step)),
+                    };
+                }
+                _ => return Err(invalid(step)),
+            }
+            if parts.next().is_some() {
+                return Err(invalid(step));
+            }
+        }
+        Ok(normalization)
+    }
+}
+
+/// Remove or replace fenced (```` ``` ```` or `~~~`) code blocks. An
+/// unclosed fence runs to the end of the text, as in markdown.
+fn replace_code_blocks(text: &str, policy: CodeBlockPolicy) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut fence: Option<char> = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            let close = trimmed.trim_end();
+            if close.len() >= 3 && close.chars().all(|c| c == marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if !(trimmed.starts_with("```") || trimmed.starts_with("~~~")) {
+            out.push_str(line);
+            continue;
+        }
+        let marker = trimmed.chars().next().unwrap_or('`');
+        fence = Some(marker);
+        if policy == CodeBlockPolicy::Placeholder {
+            match trimmed.trim_start_matches(marker).split_whitespace().next() {
+                Some(language) => out.push_str(&format!("[code: {}]\n", language)),
+                None => out.push_str("[code]\n"),
+            }
+        }
+    }
+    out
+}
+
+/// The text of a markdown document, one line per block
+fn markdown_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for event in Parser::new(text) {
+        match event {
+            Event::Text(text) | Event::Code(text) => out.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => out.push(' '),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableRow
+                | TagEnd::TableHead,
+            ) => out.push('\n'),
+            _ => {}
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// Cut `text` to `max_chars` characters; head-and-tail keeps both ends
+/// joined by a space
+fn truncate(text: &str, max_chars: usize, truncation: Truncation) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+    match truncation {
+        Truncation::Head => text.chars().take(max_chars).collect(),
+        Truncation::HeadTail => {
+            let head = max_chars / 2;
+            let tail = max_chars.saturating_sub(head + 1);
+            let mut out: String = text.chars().take(head).collect();
+            out.push(' ');
+            out.extend(text.chars().skip(total - tail));
+            out
+        }
+    }
+}
+
+/// Embedder that normalizes every text before passing it on
+pub struct NormalizingEmbedder {
+    inner: Arc<dyn Embedder>,
+    inner_async: Arc<dyn AsyncEmbedder>,
+    normalization: TextNormalization,
+}
+
+impl NormalizingEmbedder {
+    pub fn new(inner: Arc<dyn Embedder>, normalization: TextNormalization) -> Self {
+        Self {
+            inner_async: super::to_async(inner.clone()),
+            inner,
+            normalization,
+        }
+    }
+
+    fn normalize_all<'a>(&self, texts: &[&'a str]) -> Vec<Cow<'a, str>> {
+        texts
+            .iter()
+            .map(|text| self.normalization.apply(text))
+            .collect()
+    }
+}
+
+/// Wrap `embedder` in a [`NormalizingEmbedder`] unless `normalization`
+/// changes nothing
+pub fn normalize_embedder(
+    embedder: Arc<dyn Embedder>,
+    normalization: &TextNormalization,
+) -> Arc<dyn Embedder> {
+    if normalization.is_identity() {
+        embedder
+    } else {
+        Arc::new(NormalizingEmbedder::new(embedder, normalization.clone()))
+    }
+}
+
+impl Embedder for NormalizingEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.inner.embed(&self.normalization.apply(text))
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.inner.embed_query(&self.normalization.apply(text))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let normalized = self.normalize_all(texts);
+        let texts: Vec<&str> = normalized.iter().map(|text| text.as_ref()).collect();
+        self.inner.embed_batch(&texts)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+
+    fn reduction(&self) -> Option<&ReductionHandle> {
+        self.inner.reduction()
+    }
+
+    fn hybrid_dense_dims(&self) -> Option<usize> {
+        self.inner.hybrid_dense_dims()
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEmbedder for NormalizingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let text = self.normalization.apply(text).into_owned();
+        self.inner_async.embed(&text).await
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let text = self.normalization.apply(text).into_owned();
+        self.inner_async.embed_query(&text).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let normalized: Vec<String> = self
+            .normalize_all(texts)
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect();
+        let texts: Vec<&str> = normalized.iter().map(String::as_str).collect();
+        self.inner_async.embed_batch(&texts).await
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+
+    #[test]
+    fn test_pipeline() {
+        let normalization: TextNormalization =
+            "markdown,urls,whitespace,lowercase,code:placeholder"
+                .parse()
+                .unwrap();
+        let text = "# Deploy **Notes**\n\nSee [the runbook](https://example.com/runbook) or \
+                    https://example.com/x  now.\n\n```bash\nkubectl apply -f x.yaml\n```\n";
+        let normalized = normalization.apply(text);
+        assert_eq!(
+            normalized,
+            "deploy notes see the runbook or now. [code: bash]"
+        );
+        assert_eq!(normalization.apply(&normalized), normalized);
+
+        let removed = TextNormalization {
+            code_blocks: CodeBlockPolicy::Remove,
+            ..Default::default()
+        };
+        assert_eq!(removed.apply("a\n~~~\ncode\n~~~\nb\n"), "a\nb\n");
+        assert!(TextNormalization::default().apply("# As Is").eq("# As Is"));
+    }
+
+    #[test]
+    fn test_truncation() {
+        let head: TextNormalization = "max:5".parse().unwrap();
+        assert_eq!(head.apply("héllo world"), "héllo");
+        let head_tail: TextNormalization = "max:7:head_tail".parse().unwrap();
+        assert_eq!(head_tail.apply("abcdefghij"), "abc hij");
+        assert_eq!(head_tail.apply("abc hij"), "abc hij");
+        assert!("max:0".parse::<TextNormalization>().is_err());
+        assert!("code:fold".parse::<TextNormalization>().is_err());
+        assert!("none".parse::<TextNormalization>().unwrap().is_identity());
+    }
+
+    #[test]
+    fn test_embedder_ignores_formatting() {
+        let normalization: TextNormalization = "markdown,whitespace,lowercase".parse().unwrap();
+        let embedder = normalize_embedder(Arc::new(TfIdfEmbedder::new(64)), &normalization);
+        let plain = embedder.embed("retry the payment webhook").unwrap();
+        assert_eq!(
+            embedder.embed("Retry the **payment**\n  webhook").unwrap(),
+            plain
+        );
+        assert_eq!(
+            embedder.embed_query("RETRY the payment webhook").unwrap(),
+            plain
+        );
+        assert_eq!(
+            embedder
+                .embed_batch(&["_retry_ the payment webhook"])
+                .unwrap()[0],
+            plain
+        );
+    }
+}
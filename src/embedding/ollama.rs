@@ -25,7 +25,9 @@
 
 #[cfg(feature = "ollama")]
 mod inner {
-    use crate::embedding::Embedder;
+    use std::sync::Arc;
+
+    use crate::embedding::{block_on, Embedder};
     use crate::error::{EngramError, Result};
 
     /// Configuration for the Ollama embedding provider.
@@ -108,9 +110,7 @@ mod inner {
 
     impl Embedder for OllamaEmbedder {
         fn embed(&self, text: &str) -> Result<Vec<f32>> {
-            tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(self.embed_async(text))
-            })
+            block_on(self.embed_async(text))
         }
 
         fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
@@ -124,6 +124,25 @@ mod inner {
         fn model_name(&self) -> &str {
             &self.config.model
         }
+
+        fn native_async(self: Arc<Self>) -> Option<Arc<dyn crate::embedding::AsyncEmbedder>> {
+            Some(self)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::embedding::AsyncEmbedder for OllamaEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_async(text).await
+        }
+
+        fn dimensions(&self) -> usize {
+            self.config.dimensions
+        }
+
+        fn model_name(&self) -> &str {
+            &self.config.model
+        }
     }
 }
 
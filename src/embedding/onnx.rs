@@ -13,6 +13,10 @@
 //! onnx-embed = ["ort", "ndarray"]
 //! ```
 //!
+//! [`LocalOnnxEmbedder`] loads a model directory as downloaded from
+//! HuggingFace (`model.onnx` plus `tokenizer.json` or `vocab.txt`) and backs
+//! `ENGRAM_EMBEDDING_MODEL=local`, so semantic search runs fully offline.
+//!
 //! # Usage
 //!
 //! ```no_run
@@ -38,7 +42,7 @@
 #[cfg(feature = "onnx-embed")]
 mod inner {
     use std::collections::HashMap;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     use std::sync::Mutex;
 
@@ -46,16 +50,20 @@ mod inner {
     use ort::session::Session;
     use ort::value::Tensor;
 
-    use crate::embedding::Embedder;
+    use crate::embedding::{Embedder, WordPieceTokenizer};
     use crate::error::{EngramError, Result};
 
+    /// Sequence length all-MiniLM-L6-v2 was trained with
+    const LOCAL_MAX_LENGTH: usize = 256;
+
     /// Configuration for the ONNX embedding provider
     #[derive(Debug, Clone)]
     pub struct OnnxConfig {
         /// Path to the `.onnx` model file
         pub model_path: PathBuf,
 
-        /// Optional path to a tokenizer JSON file (HuggingFace tokenizer format).
+        /// Optional path to the model's WordPiece vocabulary, either a
+        /// HuggingFace `tokenizer.json` or a `vocab.txt`.
         /// When `None`, the built-in whitespace tokenizer is used.
         pub tokenizer_path: Option<PathBuf>,
 
@@ -90,6 +98,8 @@ mod inner {
         session: Mutex<Session>,
         /// Simple word-to-index vocabulary built from a whitespace split
         vocab: HashMap<String, i64>,
+        /// The model's own tokenizer, when `tokenizer_path` is set
+        tokenizer: Option<WordPieceTokenizer>,
     }
 
     impl OnnxEmbedder {
@@ -117,11 +127,17 @@ mod inner {
             // from the tokenizer JSON, but this deterministic fallback is
             // sufficient for the whitespace tokenizer used here.
             let vocab = Self::build_basic_vocab();
+            let tokenizer = config
+                .tokenizer_path
+                .as_deref()
+                .map(WordPieceTokenizer::from_file)
+                .transpose()?;
 
             Ok(Self {
                 config,
                 session: Mutex::new(session),
                 vocab,
+                tokenizer,
             })
         }
 
@@ -173,8 +189,12 @@ mod inner {
         /// ```
         ///
         /// Returns `(input_ids, attention_mask)` each of length `max_length`.
+        /// With a WordPiece tokenizer loaded the sequence is not padded.
         pub fn tokenize(&self, text: &str) -> (Vec<i64>, Vec<i64>) {
             let max_len = self.config.max_length;
+            if let Some(tokenizer) = &self.tokenizer {
+                return tokenizer.encode(text, max_len);
+            }
 
             // Reserve 2 positions for [CLS] and [SEP]
             let content_limit = max_len.saturating_sub(2);
@@ -350,6 +370,93 @@ mod inner {
         }
     }
 
+    /// Default model directory for [`LocalOnnxEmbedder`]:
+    /// `~/.local/share/engram/models/all-MiniLM-L6-v2`
+    pub fn default_local_model_dir() -> Result<PathBuf> {
+        let base = dirs::data_local_dir().ok_or_else(|| {
+            EngramError::Config("Cannot determine local data directory".to_string())
+        })?;
+        Ok(base.join("engram").join("models").join("all-MiniLM-L6-v2"))
+    }
+
+    /// Offline sentence-transformer embedder backing `ENGRAM_EMBEDDING_MODEL=local`.
+    ///
+    /// Unlike a bare [`OnnxEmbedder`] it always tokenizes with the model's own
+    /// WordPiece vocabulary, so the vectors match the reference model.
+    pub struct LocalOnnxEmbedder {
+        inner: OnnxEmbedder,
+    }
+
+    impl LocalOnnxEmbedder {
+        /// Load a model directory containing `model.onnx` (or `onnx/model.onnx`)
+        /// and `tokenizer.json` (or `vocab.txt`).
+        ///
+        /// # Errors
+        ///
+        /// Returns [`EngramError::Config`] if either file is missing and
+        /// [`EngramError::Embedding`] if they cannot be loaded.
+        pub fn from_dir(dir: &Path, dimensions: usize) -> Result<Self> {
+            let find = |candidates: &[&str]| {
+                candidates
+                    .iter()
+                    .map(|name| dir.join(name))
+                    .find(|path| path.is_file())
+            };
+            let model_path = find(&["model.onnx", "onnx/model.onnx"]).ok_or_else(|| {
+                EngramError::Config(format!("No model.onnx found in {}", dir.display()))
+            })?;
+            let tokenizer_path = find(&["tokenizer.json", "vocab.txt"]).ok_or_else(|| {
+                EngramError::Config(format!(
+                    "No tokenizer.json or vocab.txt found in {}",
+                    dir.display()
+                ))
+            })?;
+            let model_name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "all-MiniLM-L6-v2".to_string());
+
+            Self::new(OnnxConfig {
+                model_path,
+                tokenizer_path: Some(tokenizer_path),
+                dimensions,
+                max_length: LOCAL_MAX_LENGTH,
+                model_name,
+            })
+        }
+
+        /// Load a model from an explicit configuration; `tokenizer_path` is required.
+        pub fn new(config: OnnxConfig) -> Result<Self> {
+            if config.tokenizer_path.is_none() {
+                return Err(EngramError::Config(
+                    "Local ONNX embeddings need the model's tokenizer.json or vocab.txt"
+                        .to_string(),
+                ));
+            }
+            Ok(Self {
+                inner: OnnxEmbedder::new(config)?,
+            })
+        }
+    }
+
+    impl Embedder for LocalOnnxEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.inner.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            self.inner.embed_batch(texts)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn model_name(&self) -> &str {
+            self.inner.model_name()
+        }
+    }
+
     // ------------------------------------------------------------------
     // Internal utility
     // ------------------------------------------------------------------
@@ -726,4 +833,4 @@ mod inner {
 
 // Re-export public types when feature is enabled
 #[cfg(feature = "onnx-embed")]
-pub use inner::{OnnxConfig, OnnxEmbedder};
+pub use inner::{default_local_model_dir, LocalOnnxEmbedder, OnnxConfig, OnnxEmbedder};
@@ -0,0 +1,193 @@
+//! Instruction prefixes for query and document embeddings
+//!
+//! E5, BGE and similar models are trained with a marker in front of the
+//! input saying which side of retrieval it is on, e.g. `query: ` for search
+//! queries and `passage: ` for stored text, and they rank noticeably worse
+//! without it. [`PrefixingEmbedder`] prepends `EmbeddingConfig::query_prefix`
+//! in `embed_query` and `EmbeddingConfig::document_prefix` when embedding
+//! memories.
+//!
+//! Configured with `ENGRAM_EMBEDDING_QUERY_PREFIX` and
+//! `ENGRAM_EMBEDDING_DOCUMENT_PREFIX`.
+
+use std::sync::Arc;
+
+use super::{AsyncEmbedder, Embedder, ReductionHandle};
+use crate::error::Result;
+use crate::types::EmbeddingConfig;
+
+/// Embedder that prepends instruction prefixes to queries and documents
+pub struct PrefixingEmbedder {
+    inner: Arc<dyn Embedder>,
+    inner_async: Arc<dyn AsyncEmbedder>,
+    query_prefix: String,
+    document_prefix: String,
+}
+
+impl PrefixingEmbedder {
+    pub fn new(
+        inner: Arc<dyn Embedder>,
+        query_prefix: impl Into<String>,
+        document_prefix: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner_async: super::to_async(inner.clone()),
+            inner,
+            query_prefix: query_prefix.into(),
+            document_prefix: document_prefix.into(),
+        }
+    }
+
+    fn document(&self, text: &str) -> String {
+        format!("{}{}", self.document_prefix, text)
+    }
+
+    fn query(&self, text: &str) -> String {
+        format!("{}{}", self.query_prefix, text)
+    }
+
+    fn documents(&self, texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| self.document(text)).collect()
+    }
+}
+
+/// Wrap `embedder` in a [`PrefixingEmbedder`] when `config` sets a query or
+/// document prefix
+pub fn with_instruction_prefixes(
+    embedder: Arc<dyn Embedder>,
+    config: &EmbeddingConfig,
+) -> Arc<dyn Embedder> {
+    let query_prefix = config.query_prefix.as_deref().unwrap_or_default();
+    let document_prefix = config.document_prefix.as_deref().unwrap_or_default();
+    if query_prefix.is_empty() && document_prefix.is_empty() {
+        embedder
+    } else {
+        Arc::new(PrefixingEmbedder::new(
+            embedder,
+            query_prefix,
+            document_prefix,
+        ))
+    }
+}
+
+impl Embedder for PrefixingEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.inner.embed(&self.document(text))
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        self.inner.embed_query(&self.query(text))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let documents = self.documents(texts);
+        let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+        self.inner.embed_batch(&documents)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+
+    fn reduction(&self) -> Option<&ReductionHandle> {
+        self.inner.reduction()
+    }
+
+    fn hybrid_dense_dims(&self) -> Option<usize> {
+        self.inner.hybrid_dense_dims()
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEmbedder for PrefixingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let document = self.document(text);
+        self.inner_async.embed(&document).await
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let query = self.query(text);
+        self.inner_async.embed_query(&query).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let documents = self.documents(texts);
+        let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+        self.inner_async.embed_batch(&documents).await
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the texts it is asked to embed
+    #[derive(Default)]
+    struct RecordingEmbedder(Mutex<Vec<String>>);
+
+    impl Embedder for RecordingEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.0.lock().unwrap().push(text.to_string());
+            Ok(vec![1.0])
+        }
+
+        fn dimensions(&self) -> usize {
+            1
+        }
+
+        fn model_name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    #[test]
+    fn test_prefixes_queries_and_documents() {
+        let recorder = Arc::new(RecordingEmbedder::default());
+        let config = EmbeddingConfig {
+            query_prefix: Some("query: ".to_string()),
+            document_prefix: Some("passage: ".to_string()),
+            ..Default::default()
+        };
+        let embedder = with_instruction_prefixes(recorder.clone(), &config);
+        embedder.embed_query("deploy schedule").unwrap();
+        embedder.embed("Deploys go out on Tuesdays").unwrap();
+        embedder.embed_batch(&["a", "b"]).unwrap();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                "query: deploy schedule",
+                "passage: Deploys go out on Tuesdays",
+                "passage: a",
+                "passage: b"
+            ]
+        );
+
+        let plain = with_instruction_prefixes(recorder.clone(), &EmbeddingConfig::default());
+        assert!(Arc::ptr_eq(&plain, &(recorder as Arc<dyn Embedder>)));
+    }
+}
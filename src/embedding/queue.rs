@@ -2,48 +2,125 @@
 //!
 //! Embeddings are computed in the background to avoid blocking writes.
 //! The queue supports batching for efficient API usage.
+//!
+//! Requests go into one of three priority lanes and workers always drain
+//! the highest non-empty lane first, so an interactive `memory_create` is
+//! not stuck behind a bulk document ingest. A worker runs
+//! `worker_concurrency` batches at a time. Memories whose embedding fails
+//! permanently, or keeps failing, are moved to the `embedding_dead_letters`
+//! table instead of being retried forever; `get_embedding_status` reports
+//! them as `dead`.
+//!
+//! Requests with byte-identical text share one embedding: a batch embeds
+//! each distinct text once, and results are kept in an [`EmbeddingCache`]
+//! keyed by model and content hash so duplicates arriving in later batches
+//! don't call the embedder at all. With text normalization configured
+//! ([`EmbeddingConfig::normalization`]), texts are compared after
+//! normalizing, so ones differing only in formatting share an embedding too.
+//!
+//! The `embedding_queue` table is what gets persisted; the lanes only hold
+//! rows handed to a running worker. [`EmbeddingQueue::submit`] claims a row
+//! and hands it over (the server does this for interactive writes), and
+//! [`EmbeddingQueue::feed_pending`] hands over the remaining pending rows on
+//! the low lane, taking back claims abandoned by a stopped worker first.
 
 use async_channel::{bounded, Receiver, Sender};
 use chrono::Utc;
 use parking_lot::Mutex;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
 
-use super::{create_embedder, Embedder};
+use super::{create_embedder, to_async, AsyncEmbedder, Embedder, EmbeddingCache};
 use crate::error::{EngramError, Result};
-use crate::types::{EmbeddingConfig, EmbeddingState, EmbeddingStatus, MemoryId};
+use crate::search::vector_index::VectorIndexHandle;
+use crate::types::{
+    ContentHash, EmbeddingConfig, EmbeddingDeadLetter, EmbeddingPriority, EmbeddingState,
+    EmbeddingStatus, MemoryId, TextNormalization,
+};
+
+/// Attempts before a failing embedding is dead-lettered
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 3;
+
+/// Requests buffered per priority lane
+const LANE_CAPACITY: usize = 10000;
+
+/// Age after which a `processing` row is taken to belong to a stopped
+/// worker or an interrupted rebuild, and is claimable again
+pub const STALE_CLAIM: Duration = Duration::from_secs(15 * 60);
 
 /// Message for the embedding queue
 #[derive(Debug)]
 pub struct EmbeddingRequest {
     pub memory_id: MemoryId,
     pub content: String,
+    pub priority: EmbeddingPriority,
 }
 
-/// Embedding queue for async processing
-pub struct EmbeddingQueue {
+/// One bounded channel per priority
+#[derive(Clone)]
+struct Lane {
     sender: Sender<EmbeddingRequest>,
     receiver: Receiver<EmbeddingRequest>,
+}
+
+impl Lane {
+    fn new() -> Self {
+        let (sender, receiver) = bounded(LANE_CAPACITY);
+        Self { sender, receiver }
+    }
+}
+
+/// Embedding queue for async processing
+#[derive(Clone)]
+pub struct EmbeddingQueue {
+    high: Lane,
+    normal: Lane,
+    low: Lane,
     batch_size: usize,
 }
 
 impl EmbeddingQueue {
     /// Create a new embedding queue
     pub fn new(batch_size: usize) -> Self {
-        let (sender, receiver) = bounded(10000); // Buffer up to 10k requests
         Self {
-            sender,
-            receiver,
+            high: Lane::new(),
+            normal: Lane::new(),
+            low: Lane::new(),
             batch_size,
         }
     }
 
-    /// Queue a memory for embedding
+    fn lane(&self, priority: EmbeddingPriority) -> &Lane {
+        match priority {
+            EmbeddingPriority::High => &self.high,
+            EmbeddingPriority::Normal => &self.normal,
+            EmbeddingPriority::Low => &self.low,
+        }
+    }
+
+    /// Queue a memory for embedding at normal priority
     pub async fn queue(&self, memory_id: MemoryId, content: String) -> Result<()> {
-        self.sender
-            .send(EmbeddingRequest { memory_id, content })
+        self.queue_with_priority(memory_id, content, EmbeddingPriority::Normal)
+            .await
+    }
+
+    /// Queue a memory for embedding in the given lane
+    pub async fn queue_with_priority(
+        &self,
+        memory_id: MemoryId,
+        content: String,
+        priority: EmbeddingPriority,
+    ) -> Result<()> {
+        self.lane(priority)
+            .sender
+            .send(EmbeddingRequest {
+                memory_id,
+                content,
+                priority,
+            })
             .await
             .map_err(|e| EngramError::Embedding(format!("Queue send error: {}", e)))?;
         Ok(())
@@ -51,45 +128,168 @@ impl EmbeddingQueue {
 
     /// Queue a memory (blocking version for sync contexts)
     pub fn queue_blocking(&self, memory_id: MemoryId, content: String) -> Result<()> {
-        self.sender
-            .send_blocking(EmbeddingRequest { memory_id, content })
+        self.queue_blocking_with_priority(memory_id, content, EmbeddingPriority::Normal)
+    }
+
+    /// Queue a memory in the given lane (blocking version for sync contexts)
+    pub fn queue_blocking_with_priority(
+        &self,
+        memory_id: MemoryId,
+        content: String,
+        priority: EmbeddingPriority,
+    ) -> Result<()> {
+        self.lane(priority)
+            .sender
+            .send_blocking(EmbeddingRequest {
+                memory_id,
+                content,
+                priority,
+            })
             .map_err(|e| EngramError::Embedding(format!("Queue send error: {}", e)))?;
         Ok(())
     }
 
+    /// Claim a memory's pending `embedding_queue` row and hand it to the
+    /// lane for `priority`. Returns `false`, leaving the row alone, when it
+    /// isn't pending (deferred, or already claimed), and puts it back when
+    /// the lane is full so a later [`feed_pending`](Self::feed_pending)
+    /// picks it up.
+    pub fn submit(
+        &self,
+        conn: &Connection,
+        memory_id: MemoryId,
+        content: String,
+        priority: EmbeddingPriority,
+    ) -> Result<bool> {
+        let claimed = conn.execute(
+            "UPDATE embedding_queue SET status = 'processing', started_at = ?
+             WHERE memory_id = ? AND status = 'pending'",
+            params![Utc::now().to_rfc3339(), memory_id],
+        )?;
+        if claimed == 0 {
+            return Ok(false);
+        }
+        let request = EmbeddingRequest {
+            memory_id,
+            content,
+            priority,
+        };
+        if self.lane(priority).sender.try_send(request).is_err() {
+            conn.execute(
+                "UPDATE embedding_queue SET status = 'pending', started_at = NULL
+                 WHERE memory_id = ?",
+                params![memory_id],
+            )?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Hand pending `embedding_queue` rows to the low lane, oldest first, as
+    /// far as it has room, after returning claims older than
+    /// [`STALE_CLAIM`] to `pending`. Returns how many rows were handed over.
+    pub fn feed_pending(&self, conn: &Connection) -> Result<usize> {
+        let stale_before =
+            (Utc::now() - chrono::Duration::from_std(STALE_CLAIM).unwrap_or_default()).to_rfc3339();
+        conn.execute(
+            "UPDATE embedding_queue SET status = 'pending', started_at = NULL
+             WHERE status = 'processing' AND (started_at IS NULL OR started_at < ?)",
+            params![stale_before],
+        )?;
+
+        let room = LANE_CAPACITY.saturating_sub(self.len_for(EmbeddingPriority::Low));
+        if room == 0 {
+            return Ok(0);
+        }
+        let pending: Vec<(MemoryId, String)> = conn
+            .prepare(
+                "SELECT eq.memory_id, m.content FROM embedding_queue eq
+                 JOIN memories m ON m.id = eq.memory_id
+                 WHERE eq.status = 'pending'
+                 ORDER BY eq.queued_at, eq.memory_id
+                 LIMIT ?",
+            )?
+            .query_map(params![room as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut fed = 0;
+        for (memory_id, content) in pending {
+            if !self.submit(conn, memory_id, content, EmbeddingPriority::Low)? {
+                break;
+            }
+            fed += 1;
+        }
+        Ok(fed)
+    }
+
+    /// Batch size workers should use for this queue
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
     /// Get queue length
     pub fn len(&self) -> usize {
-        self.receiver.len()
+        self.high.receiver.len() + self.normal.receiver.len() + self.low.receiver.len()
+    }
+
+    /// Requests waiting in one lane
+    pub fn len_for(&self, priority: EmbeddingPriority) -> usize {
+        self.lane(priority).receiver.len()
     }
 
     /// Check if queue is empty
     pub fn is_empty(&self) -> bool {
-        self.receiver.is_empty()
+        self.len() == 0
     }
 
-    /// Get receiver for worker
-    pub fn receiver(&self) -> Receiver<EmbeddingRequest> {
-        self.receiver.clone()
+    /// Take the next request without waiting, highest priority first
+    pub fn try_recv(&self) -> Option<EmbeddingRequest> {
+        [&self.high, &self.normal, &self.low]
+            .into_iter()
+            .find_map(|lane| lane.receiver.try_recv().ok())
     }
-}
 
-impl Clone for EmbeddingQueue {
-    fn clone(&self) -> Self {
-        Self {
-            sender: self.sender.clone(),
-            receiver: self.receiver.clone(),
-            batch_size: self.batch_size,
+    /// Wait for the next request, highest priority first
+    pub async fn recv(&self) -> Option<EmbeddingRequest> {
+        if let Some(request) = self.try_recv() {
+            return Some(request);
         }
+        tokio::select! {
+            biased;
+            Ok(request) = self.high.receiver.recv() => Some(request),
+            Ok(request) = self.normal.receiver.recv() => Some(request),
+            Ok(request) = self.low.receiver.recv() => Some(request),
+            else => None,
+        }
+    }
+}
+
+/// Requests in one batch whose text is identical
+struct TextGroup {
+    key: String,
+    requests: Vec<EmbeddingRequest>,
+}
+
+impl TextGroup {
+    fn content(&self) -> &str {
+        &self.requests[0].content
     }
 }
 
 /// Background worker for processing embeddings
 pub struct EmbeddingWorker {
-    embedder: Arc<dyn Embedder>,
+    embedder: Arc<dyn AsyncEmbedder>,
+    cache: Arc<EmbeddingCache>,
     queue: EmbeddingQueue,
     conn: Arc<Mutex<Connection>>,
     batch_size: usize,
     batch_timeout: Duration,
+    concurrency: usize,
+    max_attempts: i32,
+    /// Normalization the embedder applies, used to spot equivalent texts
+    normalization: TextNormalization,
+    /// Index that stored vectors are added to
+    vector_index: Option<Arc<VectorIndexHandle>>,
 }
 
 impl EmbeddingWorker {
@@ -100,31 +300,111 @@ impl EmbeddingWorker {
         conn: Arc<Mutex<Connection>>,
     ) -> Result<Self> {
         let embedder = create_embedder(&config)?;
-        let batch_size = config.batch_size;
+        Ok(Self::with_embedder(embedder, &config, queue, conn))
+    }
 
-        Ok(Self {
+    /// Create a worker around an existing embedder
+    pub fn with_embedder(
+        embedder: Arc<dyn Embedder>,
+        config: &EmbeddingConfig,
+        queue: EmbeddingQueue,
+        conn: Arc<Mutex<Connection>>,
+    ) -> Self {
+        Self::with_async_embedder(to_async(embedder), config, queue, conn)
+    }
+
+    /// Create a worker around an async embedder
+    pub fn with_async_embedder(
+        embedder: Arc<dyn AsyncEmbedder>,
+        config: &EmbeddingConfig,
+        queue: EmbeddingQueue,
+        conn: Arc<Mutex<Connection>>,
+    ) -> Self {
+        Self {
             embedder,
+            cache: Arc::new(EmbeddingCache::default()),
             queue,
             conn,
-            batch_size,
+            batch_size: config.batch_size.max(1),
             batch_timeout: Duration::from_secs(5),
-        })
+            concurrency: config.worker_concurrency.max(1),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            normalization: config.normalization.clone(),
+            vector_index: None,
+        }
+    }
+
+    /// Attempts before a failing memory is dead-lettered
+    pub fn with_max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Share an embedding cache (e.g. the server's) instead of a private one
+    pub fn with_cache(mut self, cache: Arc<EmbeddingCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Add every stored embedding to `index`
+    pub fn with_vector_index(mut self, index: Arc<VectorIndexHandle>) -> Self {
+        self.vector_index = Some(index);
+        self
+    }
+
+    /// Cache key for a text: the model plus the hash of the normalized text
+    fn cache_key(&self, content: &str) -> String {
+        format!(
+            "{}:{}",
+            self.embedder.model_name(),
+            ContentHash::of_bytes(self.normalization.apply(content).as_ref()).as_str()
+        )
+    }
+
+    /// Group requests by text, keeping first-seen order
+    fn group(&self, requests: Vec<EmbeddingRequest>) -> Vec<TextGroup> {
+        let mut groups: Vec<TextGroup> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for request in requests {
+            let key = self.cache_key(&request.content);
+            match index.get(&key) {
+                Some(&i) => groups[i].requests.push(request),
+                None => {
+                    index.insert(key.clone(), groups.len());
+                    groups.push(TextGroup {
+                        key,
+                        requests: vec![request],
+                    });
+                }
+            }
+        }
+        groups
     }
 
     /// Run the worker (call in a spawned task)
     pub async fn run(&self) {
-        let receiver = self.queue.receiver();
+        let loops = (0..self.concurrency).map(|_| self.run_loop());
+        futures::future::join_all(loops).await;
+    }
+
+    /// One batching loop; `run` drives `concurrency` of them
+    async fn run_loop(&self) {
         let mut batch: Vec<EmbeddingRequest> = Vec::with_capacity(self.batch_size);
         let mut batch_timer = interval(self.batch_timeout);
 
         loop {
             tokio::select! {
                 // Receive new request
-                Ok(request) = receiver.recv() => {
+                request = self.queue.recv() => {
+                    let Some(request) = request else {
+                        break;
+                    };
+                    // Interactive requests don't wait for the batch to fill
+                    let urgent = request.priority == EmbeddingPriority::High;
                     batch.push(request);
 
                     // Process if batch is full
-                    if batch.len() >= self.batch_size {
+                    if urgent || batch.len() >= self.batch_size {
                         self.process_batch(&mut batch).await;
                     }
                 }
@@ -137,6 +417,7 @@ impl EmbeddingWorker {
                 }
             }
         }
+        self.process_batch(&mut batch).await;
     }
 
     /// Process a batch of embedding requests
@@ -144,76 +425,306 @@ impl EmbeddingWorker {
         if batch.is_empty() {
             return;
         }
-
-        let memory_ids: Vec<MemoryId> = batch.iter().map(|r| r.memory_id).collect();
-        let contents: Vec<&str> = batch.iter().map(|r| r.content.as_str()).collect();
+        let requests: Vec<EmbeddingRequest> = std::mem::take(batch);
 
         // Mark as processing
         {
             let conn = self.conn.lock();
             let now = Utc::now().to_rfc3339();
-            for &id in &memory_ids {
+            for request in &requests {
                 let _ = conn.execute(
                     "UPDATE embedding_queue SET status = 'processing', started_at = ? WHERE memory_id = ?",
-                    params![now, id],
+                    params![now, request.memory_id],
                 );
             }
         }
 
-        // Generate embeddings
-        match self.embedder.embed_batch(&contents) {
+        // Texts seen before are served from the cache; the rest are
+        // embedded once per distinct text
+        let mut groups = Vec::new();
+        for group in self.group(requests) {
+            match self.cache.get(&group.key) {
+                Some(embedding) => self.complete(&group.requests, &embedding),
+                None => groups.push(group),
+            }
+        }
+        if groups.is_empty() {
+            return;
+        }
+
+        let texts: Vec<String> = groups.iter().map(|g| g.content().to_string()).collect();
+        match self.embed(texts).await {
             Ok(embeddings) => {
-                let conn = self.conn.lock();
-                let now = Utc::now().to_rfc3339();
-                let model = self.embedder.model_name();
-                let dimensions = self.embedder.dimensions();
-
-                for (id, embedding) in memory_ids.iter().zip(embeddings.iter()) {
-                    // Serialize embedding to bytes
-                    let embedding_bytes: Vec<u8> =
-                        embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
-
-                    // Store embedding
-                    let _ = conn.execute(
-                        "INSERT OR REPLACE INTO embeddings (memory_id, embedding, model, dimensions, created_at)
-                         VALUES (?, ?, ?, ?, ?)",
-                        params![id, embedding_bytes, model, dimensions, now],
-                    );
-
-                    // Update memory
-                    let _ = conn.execute(
-                        "UPDATE memories SET has_embedding = 1 WHERE id = ?",
-                        params![id],
-                    );
-
-                    // Mark as complete
-                    let _ = conn.execute(
-                        "UPDATE embedding_queue SET status = 'complete', completed_at = ? WHERE memory_id = ?",
-                        params![now, id],
-                    );
+                for (group, embedding) in groups.iter().zip(embeddings) {
+                    self.complete(&group.requests, &embedding);
+                    self.cache.put(group.key.clone(), embedding);
                 }
-
-                tracing::info!("Processed {} embeddings", memory_ids.len());
             }
+            Err(e) if groups.len() == 1 => self.fail(groups.remove(0).requests, &e),
             Err(e) => {
-                let conn = self.conn.lock();
-                let error_time = Utc::now().to_rfc3339();
-                let error_msg = e.to_string();
-                let _ = error_time; // suppress unused warning
-
-                for &id in &memory_ids {
-                    let _ = conn.execute(
-                        "UPDATE embedding_queue SET status = 'failed', error = ?, retry_count = retry_count + 1 WHERE memory_id = ?",
-                        params![error_msg, id],
-                    );
+                // One bad text fails the whole request. The embedder has
+                // already retried transient errors, so embed the texts one
+                // at a time to find out which memory is at fault, stopping
+                // at the first error that isn't about the text itself.
+                tracing::warn!(
+                    "Embedding batch of {} texts failed, retrying one at a time: {}",
+                    groups.len(),
+                    e
+                );
+                let mut groups = groups.into_iter();
+                while let Some(group) = groups.next() {
+                    match self.embed(vec![group.content().to_string()]).await {
+                        Ok(mut embeddings) => {
+                            let embedding = embeddings.remove(0);
+                            self.complete(&group.requests, &embedding);
+                            self.cache.put(group.key, embedding);
+                        }
+                        Err(e) if is_permanent(&e) => self.fail(group.requests, &e),
+                        Err(e) => {
+                            let rest = groups.flat_map(|g| g.requests);
+                            self.fail(group.requests.into_iter().chain(rest).collect(), &e);
+                            break;
+                        }
+                    }
                 }
+            }
+        }
+    }
+
+    /// Embed through the async interface, so concurrent loops don't stall
+    /// each other or the runtime
+    async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        let expected = texts.len();
+        let embeddings = match texts.as_slice() {
+            [text] => vec![self.embedder.embed(text).await?],
+            _ => {
+                let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+                self.embedder.embed_batch(&texts).await?
+            }
+        };
+
+        if embeddings.len() != expected {
+            return Err(EngramError::Embedding(format!(
+                "Embedder returned {} embeddings for {} texts",
+                embeddings.len(),
+                expected
+            )));
+        }
+        Ok(embeddings)
+    }
 
-                tracing::error!("Embedding batch failed: {}", e);
+    /// Store one embedding for every request in a group
+    fn complete(&self, requests: &[EmbeddingRequest], embedding: &[f32]) {
+        let conn = self.conn.lock();
+        let now = Utc::now().to_rfc3339();
+        let model = self.embedder.model_name();
+        let dimensions = self.embedder.dimensions();
+
+        for request in requests {
+            let summarized_by = self.embedder.summarized_by(&request.content);
+            let stored = store_embedding(
+                &conn,
+                request.memory_id,
+                embedding,
+                model,
+                dimensions,
+                summarized_by,
+                &now,
+            );
+            if let (Ok(()), Some(index)) = (stored, &self.vector_index) {
+                index.insert(request.memory_id, embedding);
             }
         }
 
-        batch.clear();
+        tracing::info!("Processed {} embeddings", requests.len());
+    }
+
+    /// Record the failure and put the requests that may still succeed back
+    /// in their lane
+    fn fail(&self, requests: Vec<EmbeddingRequest>, error: &EngramError) {
+        let error_msg = error.to_string();
+        let permanent = is_permanent(error);
+        let mut retry = Vec::new();
+        {
+            let conn = self.conn.lock();
+            for request in requests {
+                let dead = record_embedding_failure(
+                    &conn,
+                    request.memory_id,
+                    request.priority,
+                    &error_msg,
+                    permanent,
+                    self.max_attempts,
+                )
+                .unwrap_or(true);
+                if !dead {
+                    retry.push(request);
+                }
+            }
+        }
+
+        tracing::error!("Embedding failed: {}", error_msg);
+        for request in retry {
+            let _ = self.queue.lane(request.priority).sender.try_send(request);
+        }
+    }
+}
+
+/// Errors about the text itself, which no retry will fix
+fn is_permanent(error: &EngramError) -> bool {
+    matches!(error, EngramError::InvalidInput(_))
+}
+
+/// Persist a computed embedding and mark its queue entry complete.
+/// `summarized_by` names the summarizer when a summary of the memory was
+/// embedded rather than its content.
+pub(crate) fn store_embedding(
+    conn: &Connection,
+    memory_id: MemoryId,
+    embedding: &[f32],
+    model: &str,
+    dimensions: usize,
+    summarized_by: Option<&str>,
+    now: &str,
+) -> Result<()> {
+    // Serialize embedding to bytes
+    let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+    conn.execute(
+        "INSERT OR REPLACE INTO embeddings
+            (memory_id, embedding, model, dimensions, summarized_by, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            memory_id,
+            embedding_bytes,
+            model,
+            dimensions,
+            summarized_by,
+            now
+        ],
+    )?;
+    conn.execute(
+        "UPDATE memories SET has_embedding = 1 WHERE id = ?",
+        params![memory_id],
+    )?;
+    conn.execute(
+        "UPDATE embedding_queue SET status = 'complete', completed_at = ? WHERE memory_id = ?",
+        params![now, memory_id],
+    )?;
+    Ok(())
+}
+
+/// Record a failed embedding attempt and return whether the memory was
+/// dead-lettered: always for a `permanent` error, otherwise once it has
+/// failed `max_attempts` times. A memory without a queue row (deleted since)
+/// counts as dead, as there is nothing left to retry.
+pub(crate) fn record_embedding_failure(
+    conn: &Connection,
+    memory_id: MemoryId,
+    priority: EmbeddingPriority,
+    error: &str,
+    permanent: bool,
+    max_attempts: i32,
+) -> Result<bool> {
+    let attempts: Option<i32> = conn
+        .query_row(
+            "UPDATE embedding_queue SET status = 'failed', error = ?, retry_count = retry_count + 1
+             WHERE memory_id = ? RETURNING retry_count",
+            params![error, memory_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(attempts) = attempts else {
+        return Ok(true);
+    };
+    if !permanent && attempts < max_attempts {
+        return Ok(false);
     }
+
+    conn.execute(
+        "UPDATE embedding_queue SET status = 'dead' WHERE memory_id = ?",
+        params![memory_id],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO embedding_dead_letters (memory_id, priority, error, attempts, dead_at)
+         VALUES (?, ?, ?, ?, ?)",
+        params![
+            memory_id,
+            priority.as_str(),
+            error,
+            attempts,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    tracing::warn!(
+        "Embedding for memory {} dead-lettered after {} attempt(s): {}",
+        memory_id,
+        attempts,
+        error
+    );
+    Ok(true)
+}
+
+fn dead_letter_from_row(row: &rusqlite::Row) -> rusqlite::Result<EmbeddingDeadLetter> {
+    let priority: String = row.get(1)?;
+    let dead_at: String = row.get(4)?;
+    Ok(EmbeddingDeadLetter {
+        memory_id: row.get(0)?,
+        priority: priority.parse().unwrap_or_default(),
+        error: row.get(2)?,
+        attempts: row.get(3)?,
+        dead_at: chrono::DateTime::parse_from_rfc3339(&dead_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Dead-lettered memories, most recent first. Entries for memories queued
+/// again since (e.g. after their content changed) are left out.
+pub fn list_embedding_dead_letters(
+    conn: &Connection,
+    limit: i64,
+) -> Result<Vec<EmbeddingDeadLetter>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.memory_id, d.priority, d.error, d.attempts, d.dead_at
+         FROM embedding_dead_letters d
+         JOIN embedding_queue eq ON eq.memory_id = d.memory_id AND eq.status = 'dead'
+         ORDER BY d.dead_at DESC, d.memory_id
+         LIMIT ?",
+    )?;
+    let letters = stmt
+        .query_map(params![limit], dead_letter_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(letters)
+}
+
+/// Put dead-lettered memories back in the queue as `pending` with a fresh
+/// retry budget; `None` requeues all of them. Returns how many were requeued.
+pub fn requeue_embedding_dead_letters(
+    conn: &Connection,
+    memory_ids: Option<&[MemoryId]>,
+) -> Result<usize> {
+    let ids: Vec<MemoryId> = match memory_ids {
+        Some(ids) => ids.to_vec(),
+        None => conn
+            .prepare("SELECT memory_id FROM embedding_dead_letters")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?,
+    };
+    let mut requeued = 0;
+    for id in ids {
+        requeued += conn.execute(
+            "UPDATE embedding_queue
+             SET status = 'pending', error = NULL, retry_count = 0, started_at = NULL
+             WHERE memory_id = ? AND status = 'dead'",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM embedding_dead_letters WHERE memory_id = ?",
+            params![id],
+        )?;
+    }
+    Ok(requeued)
 }
 
 /// Get embedding status for a memory
@@ -232,6 +743,7 @@ pub fn get_embedding_status(conn: &Connection, memory_id: MemoryId) -> Result<Em
                 "processing" => EmbeddingState::Processing,
                 "complete" => EmbeddingState::Complete,
                 "failed" => EmbeddingState::Failed,
+                "dead" => EmbeddingState::Dead,
                 _ => EmbeddingState::Pending,
             };
 
@@ -249,12 +761,26 @@ pub fn get_embedding_status(conn: &Connection, memory_id: MemoryId) -> Result<Em
                         .ok()
                 }),
                 error,
+                dead_letter: None,
+                summarized_by: None,
             })
         },
     );
 
-    match row {
-        Ok(status) => Ok(status),
+    let mut status = match row {
+        Ok(mut status) => {
+            if status.status == EmbeddingState::Dead {
+                status.dead_letter = conn
+                    .query_row(
+                        "SELECT memory_id, priority, error, attempts, dead_at
+                         FROM embedding_dead_letters WHERE memory_id = ?",
+                        params![memory_id],
+                        dead_letter_from_row,
+                    )
+                    .optional()?;
+            }
+            status
+        }
         Err(rusqlite::Error::QueryReturnedNoRows) => {
             // Check if memory has embedding
             let has_embedding: bool = conn
@@ -265,7 +791,7 @@ pub fn get_embedding_status(conn: &Connection, memory_id: MemoryId) -> Result<Em
                 )
                 .unwrap_or(false);
 
-            Ok(EmbeddingStatus {
+            EmbeddingStatus {
                 memory_id,
                 status: if has_embedding {
                     EmbeddingState::Complete
@@ -275,26 +801,38 @@ pub fn get_embedding_status(conn: &Connection, memory_id: MemoryId) -> Result<Em
                 queued_at: None,
                 completed_at: None,
                 error: None,
-            })
+                dead_letter: None,
+                summarized_by: None,
+            }
         }
-        Err(e) => Err(EngramError::Database(e)),
-    }
+        Err(e) => return Err(EngramError::Database(e)),
+    };
+    status.summarized_by = conn
+        .query_row(
+            "SELECT summarized_by FROM embeddings WHERE memory_id = ?",
+            params![memory_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    Ok(status)
 }
 
 /// Get embedding for a memory
 pub fn get_embedding(conn: &Connection, memory_id: MemoryId) -> Result<Option<Vec<f32>>> {
     let row = conn.query_row(
-        "SELECT embedding, dimensions FROM embeddings WHERE memory_id = ?",
+        "SELECT embedding, dimensions, model FROM embeddings WHERE memory_id = ?",
         params![memory_id],
         |row| {
             let bytes: Vec<u8> = row.get(0)?;
             let dimensions: usize = row.get(1)?;
-            Ok((bytes, dimensions))
+            let model: String = row.get(2)?;
+            Ok((bytes, dimensions, model))
         },
     );
 
     match row {
-        Ok((bytes, dimensions)) => {
+        Ok((bytes, dimensions, model)) => {
             let expected_len = dimensions.checked_mul(4).ok_or_else(|| {
                 EngramError::InvalidInput("Embedding dimensions too large".to_string())
             })?;
@@ -312,7 +850,10 @@ pub fn get_embedding(conn: &Connection, memory_id: MemoryId) -> Result<Option<Ve
                 let arr: [u8; 4] = chunk.try_into().unwrap();
                 embedding.push(f32::from_le_bytes(arr));
             }
-            Ok(Some(embedding))
+
+            // Only vectors comparable with the active model's
+            let active = super::adapter::active_embedding_model(conn)?;
+            super::adapter::compatible_vector(conn, active.as_deref(), &model, embedding)
         }
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(EngramError::Database(e)),
@@ -349,6 +890,7 @@ pub fn retry_failed_embeddings(conn: &Connection, max_retries: i32) -> Result<Ve
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::embedding::TfIdfEmbedder;
     use crate::storage::queries::create_memory;
     use crate::storage::Storage;
     use crate::types::{CreateMemoryInput, MemoryType};
@@ -363,6 +905,351 @@ mod tests {
         assert_eq!(queue.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_higher_lanes_drain_first() {
+        let queue = EmbeddingQueue::new(10);
+        queue
+            .queue_with_priority(1, "bulk".to_string(), EmbeddingPriority::Low)
+            .await
+            .unwrap();
+        queue.queue(2, "normal".to_string()).await.unwrap();
+        queue
+            .queue_with_priority(3, "interactive".to_string(), EmbeddingPriority::High)
+            .await
+            .unwrap();
+        assert_eq!(queue.len_for(EmbeddingPriority::Low), 1);
+
+        let order: Vec<MemoryId> = [queue.recv().await, queue.try_recv(), queue.recv().await]
+            .into_iter()
+            .map(|r| r.unwrap().memory_id)
+            .collect();
+        assert_eq!(order, vec![3, 2, 1]);
+        assert!(queue.is_empty());
+    }
+
+    fn queued_memory(conn: &Connection, content: &str) -> Result<MemoryId> {
+        Ok(create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                ..Default::default()
+            },
+        )?
+        .id)
+    }
+
+    #[test]
+    fn test_submit_and_feed_claim_queue_rows() {
+        let storage = Storage::open_in_memory().unwrap();
+        let queue = EmbeddingQueue::new(10);
+        storage
+            .with_connection(|conn| {
+                let interactive = queued_memory(conn, "typed by the user")?;
+                let bulk = queued_memory(conn, "ingested chunk")?;
+                let abandoned = queued_memory(conn, "claimed before a restart")?;
+                conn.execute(
+                    "UPDATE embedding_queue
+                     SET status = 'processing', started_at = '2020-01-01T00:00:00+00:00'
+                     WHERE memory_id = ?",
+                    params![abandoned],
+                )?;
+
+                assert!(queue.submit(
+                    conn,
+                    interactive,
+                    "typed by the user".to_string(),
+                    EmbeddingPriority::High
+                )?);
+                // Already claimed: not handed over twice
+                assert!(!queue.submit(
+                    conn,
+                    interactive,
+                    "typed by the user".to_string(),
+                    EmbeddingPriority::High
+                )?);
+                assert_eq!(
+                    get_embedding_status(conn, interactive)?.status,
+                    EmbeddingState::Processing
+                );
+
+                // The rest, including the stale claim, go to the low lane once
+                assert_eq!(queue.feed_pending(conn)?, 2);
+                assert_eq!(queue.feed_pending(conn)?, 0);
+                assert_eq!(queue.len_for(EmbeddingPriority::High), 1);
+                assert_eq!(queue.len_for(EmbeddingPriority::Low), 2);
+
+                let order: Vec<MemoryId> = std::iter::from_fn(|| queue.try_recv())
+                    .map(|r| r.memory_id)
+                    .collect();
+                assert_eq!(order, vec![interactive, bulk, abandoned]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_dead_letter_lifecycle() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let flaky = queued_memory(conn, "flaky")?;
+                let invalid = queued_memory(conn, "invalid")?;
+                let normal = EmbeddingPriority::Normal;
+
+                assert!(!record_embedding_failure(
+                    conn, flaky, normal, "503", false, 2
+                )?);
+                assert_eq!(
+                    get_embedding_status(conn, flaky)?.status,
+                    EmbeddingState::Failed
+                );
+                assert!(record_embedding_failure(
+                    conn, flaky, normal, "503", false, 2
+                )?);
+                assert!(record_embedding_failure(
+                    conn,
+                    invalid,
+                    EmbeddingPriority::Low,
+                    "400 input too long",
+                    true,
+                    2
+                )?);
+
+                let status = get_embedding_status(conn, flaky)?;
+                assert_eq!(status.status, EmbeddingState::Dead);
+                let letter = status.dead_letter.unwrap();
+                assert_eq!((letter.attempts, letter.error.as_str()), (2, "503"));
+                let letters = list_embedding_dead_letters(conn, 10)?;
+                assert_eq!(letters.len(), 2);
+                assert_eq!(
+                    letters
+                        .iter()
+                        .find(|l| l.memory_id == invalid)
+                        .unwrap()
+                        .priority,
+                    EmbeddingPriority::Low
+                );
+
+                assert_eq!(requeue_embedding_dead_letters(conn, Some(&[flaky]))?, 1);
+                assert_eq!(
+                    get_embedding_status(conn, flaky)?.status,
+                    EmbeddingState::Pending
+                );
+                assert_eq!(requeue_embedding_dead_letters(conn, None)?, 1);
+                assert!(list_embedding_dead_letters(conn, 10)?.is_empty());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    /// Rejects any text containing "poison", like an API refusing an input
+    struct PoisonEmbedder(TfIdfEmbedder);
+
+    impl Embedder for PoisonEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            if text.contains("poison") {
+                return Err(EngramError::InvalidInput("input rejected".to_string()));
+            }
+            self.0.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            if texts.iter().any(|t| t.contains("poison")) {
+                return Err(EngramError::Embedding("1 of 3 texts failed".to_string()));
+            }
+            self.0.embed_batch(texts)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.0.dimensions()
+        }
+
+        fn model_name(&self) -> &str {
+            self.0.model_name()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_worker_dead_letters_only_the_bad_input() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::migrations::run_migrations(&conn).unwrap();
+        let ids: Vec<MemoryId> = ["first", "poison pill", "third"]
+            .iter()
+            .map(|content| queued_memory(&conn, content).unwrap())
+            .collect();
+        let conn = Arc::new(Mutex::new(conn));
+
+        let queue = EmbeddingQueue::new(3);
+        for (&id, content) in ids.iter().zip(["first", "poison pill", "third"]) {
+            queue.queue(id, content.to_string()).await.unwrap();
+        }
+        let config = EmbeddingConfig {
+            batch_size: 3,
+            worker_concurrency: 1,
+            ..Default::default()
+        };
+        let worker = EmbeddingWorker::with_embedder(
+            Arc::new(PoisonEmbedder(TfIdfEmbedder::new(16))),
+            &config,
+            queue,
+            conn.clone(),
+        );
+
+        let settled = async {
+            loop {
+                let pending: i64 = conn
+                    .lock()
+                    .query_row(
+                        "SELECT COUNT(*) FROM embedding_queue WHERE status NOT IN ('complete', 'dead')",
+                        [],
+                        |row| row.get(0),
+                    )
+                    .unwrap();
+                if pending == 0 {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::select! {
+                _ = worker.run() => {}
+                _ = settled => {}
+            }
+        })
+        .await
+        .unwrap();
+
+        let conn = conn.lock();
+        let states: Vec<EmbeddingState> = ids
+            .iter()
+            .map(|&id| get_embedding_status(&conn, id).unwrap().status)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                EmbeddingState::Complete,
+                EmbeddingState::Dead,
+                EmbeddingState::Complete
+            ]
+        );
+    }
+
+    /// Counts the texts it is asked to embed
+    struct CountingEmbedder {
+        inner: TfIdfEmbedder,
+        texts: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Embedder for CountingEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.texts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            self.texts
+                .fetch_add(texts.len(), std::sync::atomic::Ordering::SeqCst);
+            self.inner.embed_batch(texts)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn model_name(&self) -> &str {
+            self.inner.model_name()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_worker_embeds_identical_texts_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::migrations::run_migrations(&conn).unwrap();
+        let ids: Vec<MemoryId> = (0..4)
+            .map(|i| queued_memory(&conn, &format!("chunk {}", i)).unwrap())
+            .collect();
+        let conn = Arc::new(Mutex::new(conn));
+        let embedder = Arc::new(CountingEmbedder {
+            inner: TfIdfEmbedder::new(16),
+            texts: Default::default(),
+        });
+        let worker = EmbeddingWorker::with_embedder(
+            embedder.clone(),
+            &EmbeddingConfig::default(),
+            EmbeddingQueue::new(10),
+            conn.clone(),
+        );
+        let request = |memory_id, content: &str| EmbeddingRequest {
+            memory_id,
+            content: content.to_string(),
+            priority: EmbeddingPriority::Normal,
+        };
+
+        let mut batch = vec![
+            request(ids[0], "same boilerplate"),
+            request(ids[1], "other text"),
+            request(ids[2], "same boilerplate"),
+        ];
+        worker.process_batch(&mut batch).await;
+        assert_eq!(embedder.texts.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // A later duplicate comes from the cache
+        let mut batch = vec![request(ids[3], "same boilerplate")];
+        worker.process_batch(&mut batch).await;
+        assert_eq!(embedder.texts.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        let conn = conn.lock();
+        let first = get_embedding(&conn, ids[0]).unwrap().unwrap();
+        assert_eq!(get_embedding(&conn, ids[2]).unwrap().unwrap(), first);
+        assert_eq!(get_embedding(&conn, ids[3]).unwrap().unwrap(), first);
+        assert_eq!(
+            get_embedding_status(&conn, ids[3]).unwrap().status,
+            EmbeddingState::Complete
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_worker_records_summarized_embeddings() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::storage::migrations::run_migrations(&conn).unwrap();
+        let long = "Payments are retried by the billing service. ".repeat(20);
+        let ids: Vec<MemoryId> = ["short note", long.as_str()]
+            .iter()
+            .map(|content| queued_memory(&conn, content).unwrap())
+            .collect();
+        let conn = Arc::new(Mutex::new(conn));
+        let embedder = Arc::new(crate::embedding::SummarizingEmbedder::new(
+            Arc::new(TfIdfEmbedder::new(16)),
+            Arc::new(crate::embedding::ExtractiveTextSummarizer),
+            200,
+        ));
+        let worker = EmbeddingWorker::with_embedder(
+            embedder,
+            &EmbeddingConfig::default(),
+            EmbeddingQueue::new(10),
+            conn.clone(),
+        );
+        let mut batch: Vec<EmbeddingRequest> = ids
+            .iter()
+            .zip(["short note", long.as_str()])
+            .map(|(&memory_id, content)| EmbeddingRequest {
+                memory_id,
+                content: content.to_string(),
+                priority: EmbeddingPriority::Normal,
+            })
+            .collect();
+        worker.process_batch(&mut batch).await;
+
+        let conn = conn.lock();
+        let short = get_embedding_status(&conn, ids[0]).unwrap();
+        let summarized = get_embedding_status(&conn, ids[1]).unwrap();
+        assert_eq!(short.status, EmbeddingState::Complete);
+        assert_eq!(short.summarized_by, None);
+        assert_eq!(summarized.status, EmbeddingState::Complete);
+        assert_eq!(summarized.summarized_by.as_deref(), Some("extractive"));
+    }
+
     #[test]
     fn test_get_embedding_length_mismatch() {
         let storage = Storage::open_in_memory().unwrap();
@@ -388,7 +1275,7 @@ mod tests {
                         event_duration_seconds: None,
                         trigger_pattern: None,
                         summary_of_id: None,
-                            media_url: None,
+                        media_url: None,
                     },
                 )?;
 
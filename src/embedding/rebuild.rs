@@ -0,0 +1,526 @@
+//! Parallel embedding rebuilds
+//!
+//! `rebuild_embeddings` only queues every memory; [`run_embedding_rebuild`]
+//! drains that queue with a bounded pool of blocking workers calling
+//! [`Embedder::embed_batch`]. The `embedding_queue` rows are the checkpoint:
+//! a batch is claimed by flipping its rows to `processing` and completed rows
+//! are marked `complete`, so a rebuild interrupted at any point resumes from
+//! the remaining rows. Claims are only taken back once they are
+//! [`STALE_CLAIM`] old, so a run never steals rows another run is embedding. Progress, including an ETA, is written to the
+//! `sync_tasks` table after every batch. Stored vectors are added to the
+//! vector index, when one is passed in, as each batch lands.
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+use super::queue::{record_embedding_failure, store_embedding, STALE_CLAIM};
+use super::Embedder;
+use crate::error::{EngramError, Result};
+use crate::search::vector_index::VectorIndexHandle;
+use crate::storage::queries::{get_sync_task, upsert_sync_task, SyncTask};
+use crate::storage::Storage;
+use crate::types::{EmbeddingPriority, MemoryId};
+
+/// `sync_tasks.task_type` for embedding rebuilds
+pub const REBUILD_TASK_TYPE: &str = "embedding_rebuild";
+
+/// Failed rows are retried on resume until they have failed this many times
+const MAX_RETRIES: i32 = 3;
+
+/// Longest pause after consecutive failed batches
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Options for [`run_embedding_rebuild`]
+#[derive(Debug, Clone)]
+pub struct RebuildOptions {
+    /// Batches embedded concurrently
+    pub concurrency: usize,
+    /// Memories per `embed_batch` call
+    pub batch_size: usize,
+    /// Provider request budget; `None` means unlimited
+    pub requests_per_minute: Option<u32>,
+}
+
+impl Default for RebuildOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            batch_size: 64,
+            requests_per_minute: None,
+        }
+    }
+}
+
+/// Queue counts used for progress reporting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct QueueCounts {
+    total: i64,
+    complete: i64,
+    failed: i64,
+}
+
+/// Drain the embedding queue in parallel, reporting progress under `task_id`.
+///
+/// Rows left `processing` by an interrupted run (claimed more than
+/// [`STALE_CLAIM`] ago), and failed rows that have not exhausted their
+/// retries, are put back to `pending` first. Failed
+/// batches are recorded and followed by an exponential back-off so a
+/// rate-limited provider is not hammered. Returns the final task record.
+pub async fn run_embedding_rebuild(
+    storage: Storage,
+    embedder: Arc<dyn Embedder>,
+    options: RebuildOptions,
+    task_id: String,
+    vector_index: Option<Arc<VectorIndexHandle>>,
+) -> Result<SyncTask> {
+    if options.concurrency == 0 || options.batch_size == 0 {
+        return Err(EngramError::InvalidInput(
+            "concurrency and batch_size must be greater than 0".to_string(),
+        ));
+    }
+
+    let previous = storage.with_connection(|conn| rebuild_status(conn, &task_id))?;
+    let initial = storage.with_transaction(|conn| {
+        reclaim_abandoned(conn)?;
+        queue_counts(conn)
+    })?;
+
+    let mut task = SyncTask {
+        task_id: task_id.clone(),
+        task_type: REBUILD_TASK_TYPE.to_string(),
+        status: "running".to_string(),
+        progress_percent: percent(initial.complete, initial.total),
+        traces_processed: 0,
+        memories_created: 0,
+        error_message: None,
+        // A resumed task keeps its original start time.
+        started_at: previous
+            .map(|t| t.started_at)
+            .unwrap_or_else(|| Utc::now().to_rfc3339()),
+        completed_at: None,
+        items_total: initial.total,
+        items_processed: initial.complete,
+        eta_seconds: None,
+    };
+    storage.with_connection(|conn| upsert_sync_task(conn, &task))?;
+
+    let min_interval = options
+        .requests_per_minute
+        .filter(|&rpm| rpm > 0)
+        .map(|rpm| Duration::from_secs_f64(60.0 / rpm as f64));
+    let mut next_dispatch = Instant::now();
+    let mut backoff = Duration::ZERO;
+
+    let started = Instant::now();
+    let mut embedded_this_run = 0i64;
+    let mut last_error: Option<String> = None;
+    let mut exhausted = false;
+    let mut in_flight = JoinSet::new();
+
+    loop {
+        while !exhausted && in_flight.len() < options.concurrency {
+            let batch = storage.with_transaction(|conn| claim_batch(conn, options.batch_size))?;
+            if batch.is_empty() {
+                exhausted = true;
+                break;
+            }
+
+            // Rate limit: space dispatches evenly across the minute.
+            if let Some(interval) = min_interval {
+                tokio::time::sleep_until(next_dispatch.into()).await;
+                next_dispatch = Instant::now() + interval;
+            }
+
+            let storage = storage.clone();
+            let embedder = embedder.clone();
+            let vector_index = vector_index.clone();
+            in_flight.spawn_blocking(move || {
+                embed_batch(&storage, embedder.as_ref(), batch, vector_index.as_deref())
+            });
+        }
+
+        let Some(joined) = in_flight.join_next().await else {
+            break;
+        };
+        let outcome = joined
+            .map_err(|e| EngramError::Internal(format!("Embedding worker panicked: {}", e)))?;
+        match outcome {
+            Ok(embedded) => {
+                embedded_this_run += embedded;
+                backoff = Duration::ZERO;
+            }
+            Err(e) => {
+                last_error = Some(e.to_string());
+                backoff = (backoff * 2).clamp(Duration::from_secs(1), MAX_BACKOFF);
+                tracing::warn!(
+                    "Embedding rebuild batch failed, backing off {:?}: {}",
+                    backoff,
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+            }
+        }
+
+        let counts = storage.with_connection(queue_counts)?;
+        let remaining = (counts.total - counts.complete - counts.failed).max(0);
+        task.items_total = counts.total;
+        task.items_processed = counts.complete;
+        task.progress_percent = percent(counts.complete + counts.failed, counts.total);
+        task.eta_seconds = eta_seconds(started.elapsed(), embedded_this_run, remaining);
+        task.error_message = last_error.clone();
+        storage.with_connection(|conn| upsert_sync_task(conn, &task))?;
+    }
+
+    let counts = storage.with_connection(queue_counts)?;
+    task.status = if counts.failed == 0 {
+        "completed".to_string()
+    } else {
+        "completed_with_errors".to_string()
+    };
+    task.items_total = counts.total;
+    task.items_processed = counts.complete;
+    task.progress_percent = 100;
+    task.eta_seconds = Some(0);
+    task.completed_at = Some(Utc::now().to_rfc3339());
+    storage.with_connection(|conn| upsert_sync_task(conn, &task))?;
+
+    tracing::info!(
+        "Embedding rebuild {} finished: {} embedded, {} failed in {:?}",
+        task_id,
+        embedded_this_run,
+        counts.failed,
+        started.elapsed()
+    );
+    Ok(task)
+}
+
+/// Current record of a rebuild task, if any
+pub fn rebuild_status(conn: &Connection, task_id: &str) -> Result<Option<SyncTask>> {
+    Ok(get_sync_task(conn, task_id)?.filter(|t| t.task_type == REBUILD_TASK_TYPE))
+}
+
+/// Put stale claims and retryable failures back to `pending`, returning how
+/// many rows were reclaimed. Fresh claims may belong to a concurrent run (or
+/// the embedding worker) and are left alone.
+fn reclaim_abandoned(conn: &Connection) -> Result<usize> {
+    let stale_before =
+        (Utc::now() - chrono::Duration::from_std(STALE_CLAIM).unwrap_or_default()).to_rfc3339();
+    Ok(conn.execute(
+        "UPDATE embedding_queue SET status = 'pending', started_at = NULL
+         WHERE (status = 'processing' AND (started_at IS NULL OR started_at < ?))
+            OR (status = 'failed' AND retry_count < ?)",
+        params![stale_before, MAX_RETRIES],
+    )?)
+}
+
+/// Claim up to `limit` pending rows by marking them `processing`.
+fn claim_batch(conn: &Connection, limit: usize) -> Result<Vec<(MemoryId, String)>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT eq.memory_id, m.content FROM embedding_queue eq
+         JOIN memories m ON m.id = eq.memory_id
+         WHERE eq.status = 'pending'
+         ORDER BY eq.memory_id
+         LIMIT ?",
+    )?;
+    let batch: Vec<(MemoryId, String)> = stmt
+        .query_map(params![limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let now = Utc::now().to_rfc3339();
+    let mut claim = conn.prepare_cached(
+        "UPDATE embedding_queue SET status = 'processing', started_at = ? WHERE memory_id = ?",
+    )?;
+    for (id, _) in &batch {
+        claim.execute(params![now, id])?;
+    }
+    Ok(batch)
+}
+
+/// Embed one claimed batch and store the results, returning how many were
+/// embedded.
+///
+/// Provider errors mark the whole batch failed and are returned so the caller
+/// can back off; the rows stay claimable by a later resume.
+fn embed_batch(
+    storage: &Storage,
+    embedder: &dyn Embedder,
+    batch: Vec<(MemoryId, String)>,
+    vector_index: Option<&VectorIndexHandle>,
+) -> Result<i64> {
+    let texts: Vec<&str> = batch.iter().map(|(_, content)| content.as_str()).collect();
+    let now = Utc::now().to_rfc3339();
+
+    match embedder.embed_batch(&texts) {
+        Ok(embeddings) if embeddings.len() == batch.len() => {
+            storage.with_transaction(|conn| {
+                for ((id, content), embedding) in batch.iter().zip(&embeddings) {
+                    store_embedding(
+                        conn,
+                        *id,
+                        embedding,
+                        embedder.model_name(),
+                        embedder.dimensions(),
+                        embedder.summarized_by(content),
+                        &now,
+                    )?;
+                }
+                Ok(())
+            })?;
+            if let Some(index) = vector_index {
+                for ((id, _), embedding) in batch.iter().zip(&embeddings) {
+                    index.insert(*id, embedding);
+                }
+            }
+            Ok(batch.len() as i64)
+        }
+        Ok(embeddings) => {
+            let error = format!(
+                "Embedder returned {} embeddings for {} texts",
+                embeddings.len(),
+                batch.len()
+            );
+            mark_failed(storage, &batch, &error)?;
+            Err(EngramError::Embedding(error))
+        }
+        Err(e) => {
+            mark_failed(storage, &batch, &e.to_string())?;
+            Err(e)
+        }
+    }
+}
+
+/// Rows that have failed `MAX_RETRIES` times are dead-lettered.
+fn mark_failed(storage: &Storage, batch: &[(MemoryId, String)], error: &str) -> Result<()> {
+    storage.with_transaction(|conn| {
+        for (id, _) in batch {
+            record_embedding_failure(conn, *id, EmbeddingPriority::Low, error, false, MAX_RETRIES)?;
+        }
+        Ok(())
+    })
+}
+
+fn queue_counts(conn: &Connection) -> Result<QueueCounts> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(status = 'complete'), 0),
+                COALESCE(SUM(status IN ('failed', 'dead')), 0)
+         FROM embedding_queue",
+        [],
+        |row| {
+            Ok(QueueCounts {
+                total: row.get(0)?,
+                complete: row.get(1)?,
+                failed: row.get(2)?,
+            })
+        },
+    )?)
+}
+
+fn percent(done: i64, total: i64) -> i32 {
+    if total <= 0 {
+        100
+    } else {
+        ((done * 100) / total).clamp(0, 100) as i32
+    }
+}
+
+/// Remaining time at this run's throughput so far
+fn eta_seconds(elapsed: Duration, done: i64, remaining: i64) -> Option<i64> {
+    if remaining == 0 {
+        return Some(0);
+    }
+    if done == 0 {
+        return None;
+    }
+    let per_item = elapsed.as_secs_f64() / done as f64;
+    Some((per_item * remaining as f64).ceil() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+    use crate::storage::queries::{create_memory, rebuild_embeddings};
+    use crate::types::CreateMemoryInput;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn seeded_storage(count: usize) -> Storage {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                for i in 0..count {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: format!("memory number {i} about embeddings"),
+                            ..Default::default()
+                        },
+                    )?;
+                }
+                rebuild_embeddings(conn)?;
+                Ok(())
+            })
+            .unwrap();
+        storage
+    }
+
+    /// Fails the first `fail_calls` batches, then delegates to TF-IDF.
+    struct FlakyEmbedder {
+        inner: TfIdfEmbedder,
+        fail_calls: usize,
+        calls: AtomicUsize,
+    }
+
+    impl Embedder for FlakyEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.inner.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.fail_calls {
+                return Err(EngramError::Embedding("429 Too Many Requests".to_string()));
+            }
+            self.inner.embed_batch(texts)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn model_name(&self) -> &str {
+            self.inner.model_name()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_parallel_rebuild_embeds_everything() {
+        let storage = seeded_storage(50);
+        let options = RebuildOptions {
+            concurrency: 3,
+            batch_size: 7,
+            requests_per_minute: None,
+        };
+        // Built before any embedding exists, so it only holds what the
+        // rebuild inserts
+        let index = Arc::new(VectorIndexHandle::default());
+        storage
+            .with_connection(|conn| index.rebuild(conn, None))
+            .unwrap();
+        let task = run_embedding_rebuild(
+            storage.clone(),
+            Arc::new(TfIdfEmbedder::new(64)),
+            options,
+            "rebuild-1".to_string(),
+            Some(index.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(task.status, "completed");
+        assert_eq!(task.items_total, 50);
+        assert_eq!(task.items_processed, 50);
+        assert_eq!(task.progress_percent, 100);
+        assert_eq!(index.stats().unwrap().vectors, 50);
+
+        storage
+            .with_connection(|conn| {
+                let stored: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM embeddings", [], |r| r.get(0))?;
+                assert_eq!(stored, 50);
+                let persisted = rebuild_status(conn, "rebuild-1")?.unwrap();
+                assert_eq!(persisted.status, "completed");
+                assert_eq!(persisted.eta_seconds, Some(0));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_rebuild_resumes_after_failures() {
+        let storage = seeded_storage(10);
+        let flaky = Arc::new(FlakyEmbedder {
+            inner: TfIdfEmbedder::new(32),
+            fail_calls: 1,
+            calls: AtomicUsize::new(0),
+        });
+        let options = RebuildOptions {
+            concurrency: 1,
+            batch_size: 4,
+            requests_per_minute: None,
+        };
+
+        let first = run_embedding_rebuild(
+            storage.clone(),
+            flaky.clone(),
+            options.clone(),
+            "rebuild-2".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.status, "completed_with_errors");
+        assert_eq!(first.items_processed, 6);
+        assert!(first.error_message.unwrap().contains("429"));
+
+        // Simulate a crash mid-batch, long ago, on top of the failed rows.
+        storage
+            .with_connection(|conn| {
+                conn.execute(
+                    "UPDATE embedding_queue
+                     SET status = 'processing', started_at = '2020-01-01T00:00:00+00:00'
+                     WHERE memory_id = (SELECT MAX(memory_id) FROM embedding_queue)",
+                    [],
+                )?;
+                Ok(())
+            })
+            .unwrap();
+
+        let resumed = run_embedding_rebuild(storage.clone(), flaky, options, "rebuild-2".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(resumed.status, "completed");
+        assert_eq!(resumed.items_processed, 10);
+    }
+
+    #[test]
+    fn test_reclaim_leaves_fresh_claims() {
+        let storage = seeded_storage(3);
+        storage
+            .with_transaction(|conn| {
+                let claimed = claim_batch(conn, 2)?;
+                assert_eq!(claimed.len(), 2);
+                conn.execute(
+                    "UPDATE embedding_queue SET started_at = '2020-01-01T00:00:00+00:00'
+                     WHERE memory_id = ?",
+                    params![claimed[0].0],
+                )?;
+
+                // Only the abandoned claim goes back to pending; the other
+                // is still being embedded by its run
+                assert_eq!(reclaim_abandoned(conn)?, 1);
+                let status = |id: MemoryId| -> Result<String> {
+                    Ok(conn.query_row(
+                        "SELECT status FROM embedding_queue WHERE memory_id = ?",
+                        params![id],
+                        |row| row.get(0),
+                    )?)
+                };
+                assert_eq!(status(claimed[0].0)?, "pending");
+                assert_eq!(status(claimed[1].0)?, "processing");
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_eta_and_percent() {
+        assert_eq!(eta_seconds(Duration::from_secs(10), 100, 50), Some(5));
+        assert_eq!(eta_seconds(Duration::from_secs(10), 0, 50), None);
+        assert_eq!(eta_seconds(Duration::from_secs(10), 5, 0), Some(0));
+        assert_eq!(percent(1, 3), 33);
+        assert_eq!(percent(0, 0), 100);
+    }
+}
@@ -0,0 +1,889 @@
+//! Embedding dimensionality reduction
+//!
+//! Shrinks stored vectors to a target size, either by truncating models
+//! trained with Matryoshka representation learning (their leading dimensions
+//! are a usable embedding on their own) or by projecting onto principal
+//! components fitted over the corpus. [`ReducingEmbedder`] applies the
+//! reduction to every document and query embedding so both sides stay
+//! comparable.
+//!
+//! [`reduce_stored_embeddings`] converts vectors already in the `embeddings`
+//! table, keeping the full-size originals in `embedding_originals` so that
+//! [`restore_original_embeddings`] can undo the reduction.
+//!
+//! Configured with `ENGRAM_EMBEDDING_REDUCTION`, e.g. `truncate:256` or
+//! `pca:256`.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::Utc;
+use parking_lot::RwLock;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::Embedder;
+use crate::error::{EngramError, Result};
+use crate::types::MemoryId;
+
+/// Orthogonal iterations used when fitting PCA.
+const PCA_ITERATIONS: usize = 12;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// How vectors are reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReductionMethod {
+    /// Keep the leading dimensions (Matryoshka models) and renormalize.
+    Truncate,
+    /// Project onto the top principal components of the stored corpus.
+    Pca,
+}
+
+impl ReductionMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReductionMethod::Truncate => "truncate",
+            ReductionMethod::Pca => "pca",
+        }
+    }
+}
+
+/// A reduction method and its target dimensionality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReductionSpec {
+    pub method: ReductionMethod,
+    pub dimensions: usize,
+}
+
+impl FromStr for ReductionSpec {
+    type Err = EngramError;
+
+    /// Parse `method:dims`, e.g. `truncate:256`, `matryoshka:512`, `pca:256`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            EngramError::Config(format!(
+                "Invalid embedding reduction '{}': expected truncate:<dims> or pca:<dims>",
+                s
+            ))
+        };
+        let (method, dims) = s.trim().split_once(':').ok_or_else(invalid)?;
+        let method = match method.trim().to_lowercase().as_str() {
+            "truncate" | "matryoshka" => ReductionMethod::Truncate,
+            "pca" => ReductionMethod::Pca,
+            _ => return Err(invalid()),
+        };
+        let dimensions = dims.trim().parse::<usize>().map_err(|_| invalid())?;
+        if dimensions == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { method, dimensions })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reductions
+// ---------------------------------------------------------------------------
+
+/// Principal components fitted over a set of embeddings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PcaModel {
+    pub source_dims: usize,
+    pub mean: Vec<f32>,
+    /// One unit-length component per output dimension, strongest first.
+    pub components: Vec<Vec<f32>>,
+    /// Share of the sample's variance kept by the components.
+    pub explained_variance: f32,
+}
+
+impl PcaModel {
+    /// Fit `dims` components by orthogonal iteration over `vectors`, which
+    /// must share one dimensionality.
+    pub fn fit(vectors: &[&[f32]], dims: usize, seed: u64) -> Result<Self> {
+        let Some(source_dims) = vectors.first().map(|v| v.len()) else {
+            return Err(EngramError::InvalidInput(
+                "PCA needs at least one embedding to fit".to_string(),
+            ));
+        };
+        if vectors.iter().any(|v| v.len() != source_dims) {
+            return Err(EngramError::InvalidInput(
+                "PCA input embeddings have mixed dimensions".to_string(),
+            ));
+        }
+        let n = vectors.len() as f32;
+        let k = dims.min(source_dims);
+
+        let mut mean = vec![0.0f32; source_dims];
+        for v in vectors {
+            for (m, x) in mean.iter_mut().zip(v.iter()) {
+                *m += x / n;
+            }
+        }
+        let centered: Vec<Vec<f32>> = vectors
+            .iter()
+            .map(|v| v.iter().zip(&mean).map(|(x, m)| x - m).collect())
+            .collect();
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut basis: Vec<Vec<f32>> = (0..k)
+            .map(|_| (0..source_dims).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect();
+        orthonormalize(&mut basis, &mut rng);
+
+        for _ in 0..PCA_ITERATIONS {
+            // basis ← orth(C · basis) with C the sample covariance, computed
+            // without materializing C.
+            let mut next = vec![vec![0.0f32; source_dims]; k];
+            for x in &centered {
+                for (row, b) in next.iter_mut().zip(&basis) {
+                    let p = dot(x, b);
+                    for (r, xi) in row.iter_mut().zip(x) {
+                        *r += p * xi;
+                    }
+                }
+            }
+            basis = next;
+            orthonormalize(&mut basis, &mut rng);
+        }
+
+        let variance_along = |c: &[f32]| centered.iter().map(|x| dot(x, c).powi(2)).sum::<f32>();
+        let mut ranked: Vec<(f32, Vec<f32>)> =
+            basis.into_iter().map(|c| (variance_along(&c), c)).collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let total: f32 = centered.iter().map(|x| dot(x, x)).sum();
+        let kept: f32 = ranked.iter().map(|(v, _)| v).sum();
+        Ok(Self {
+            source_dims,
+            mean,
+            components: ranked.into_iter().map(|(_, c)| c).collect(),
+            explained_variance: if total > 0.0 { kept / total } else { 1.0 },
+        })
+    }
+
+    pub fn project(&self, vector: &[f32]) -> Vec<f32> {
+        let centered: Vec<f32> = vector.iter().zip(&self.mean).map(|(x, m)| x - m).collect();
+        self.components.iter().map(|c| dot(&centered, c)).collect()
+    }
+}
+
+/// Modified Gram-Schmidt; rows that collapse are replaced by fresh random
+/// directions so the basis keeps full rank.
+fn orthonormalize(rows: &mut [Vec<f32>], rng: &mut StdRng) {
+    for i in 0..rows.len() {
+        for _attempt in 0..3 {
+            for j in 0..i {
+                let (done, rest) = rows.split_at_mut(i);
+                let p = dot(&rest[0], &done[j]);
+                for (x, y) in rest[0].iter_mut().zip(&done[j]) {
+                    *x -= p * y;
+                }
+            }
+            let norm = dot(&rows[i], &rows[i]).sqrt();
+            if norm > 1e-6 {
+                rows[i].iter_mut().for_each(|x| *x /= norm);
+                break;
+            }
+            rows[i]
+                .iter_mut()
+                .for_each(|x| *x = rng.gen_range(-1.0..1.0));
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// A reduction ready to apply.
+#[derive(Debug, Clone)]
+pub enum Reduction {
+    Truncate { dimensions: usize },
+    Pca(PcaModel),
+}
+
+impl Reduction {
+    pub fn method(&self) -> ReductionMethod {
+        match self {
+            Reduction::Truncate { .. } => ReductionMethod::Truncate,
+            Reduction::Pca(_) => ReductionMethod::Pca,
+        }
+    }
+
+    pub fn output_dims(&self) -> usize {
+        match self {
+            Reduction::Truncate { dimensions } => *dimensions,
+            Reduction::Pca(model) => model.components.len(),
+        }
+    }
+
+    /// Whether `vector` is full-size input this reduction applies to.
+    pub fn accepts(&self, vector: &[f32]) -> bool {
+        match self {
+            Reduction::Truncate { dimensions } => vector.len() > *dimensions,
+            Reduction::Pca(model) => vector.len() == model.source_dims,
+        }
+    }
+
+    /// Reduce `vector`; vectors the reduction does not accept pass through.
+    pub fn apply(&self, vector: &[f32]) -> Vec<f32> {
+        if !self.accepts(vector) {
+            return vector.to_vec();
+        }
+        match self {
+            Reduction::Truncate { dimensions } => {
+                let head = &vector[..*dimensions];
+                let norm = dot(head, head).sqrt();
+                if norm == 0.0 {
+                    head.to_vec()
+                } else {
+                    head.iter().map(|x| x / norm).collect()
+                }
+            }
+            Reduction::Pca(model) => model.project(vector),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Handle and embedder wrapper
+// ---------------------------------------------------------------------------
+
+/// Configured reduction and, once available, the active one. Truncation is
+/// active immediately; PCA becomes active when a model is fitted or loaded.
+pub struct ReductionHandle {
+    spec: ReductionSpec,
+    active: RwLock<Option<Reduction>>,
+}
+
+impl ReductionHandle {
+    pub fn new(spec: ReductionSpec) -> Self {
+        let active = match spec.method {
+            ReductionMethod::Truncate => Some(Reduction::Truncate {
+                dimensions: spec.dimensions,
+            }),
+            ReductionMethod::Pca => None,
+        };
+        Self {
+            spec,
+            active: RwLock::new(active),
+        }
+    }
+
+    pub fn spec(&self) -> ReductionSpec {
+        self.spec
+    }
+
+    pub fn active(&self) -> Option<Reduction> {
+        self.active.read().clone()
+    }
+
+    pub fn set_active(&self, reduction: Option<Reduction>) {
+        *self.active.write() = reduction;
+    }
+
+    /// Activate the reduction persisted in the database when it matches the
+    /// configured spec.
+    pub fn load(&self, conn: &Connection) -> Result<bool> {
+        match load_reduction(conn)? {
+            Some(reduction)
+                if reduction.method() == self.spec.method
+                    && reduction.output_dims() == self.spec.dimensions =>
+            {
+                self.set_active(Some(reduction));
+                Ok(true)
+            }
+            Some(reduction) => {
+                tracing::warn!(
+                    "Stored embedding reduction {}:{} does not match configured {}:{}; ignoring it",
+                    reduction.method().as_str(),
+                    reduction.output_dims(),
+                    self.spec.method.as_str(),
+                    self.spec.dimensions
+                );
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn reduce(&self, vector: Vec<f32>) -> Vec<f32> {
+        match self.active.read().as_ref() {
+            Some(reduction) if reduction.accepts(&vector) => reduction.apply(&vector),
+            _ => vector,
+        }
+    }
+}
+
+/// Embedder whose output passes through a [`ReductionHandle`].
+pub struct ReducingEmbedder {
+    inner: Arc<dyn Embedder>,
+    inner_async: Arc<dyn super::AsyncEmbedder>,
+    handle: Arc<ReductionHandle>,
+}
+
+impl ReducingEmbedder {
+    pub fn new(inner: Arc<dyn Embedder>, handle: Arc<ReductionHandle>) -> Self {
+        Self {
+            inner_async: super::to_async(inner.clone()),
+            inner,
+            handle,
+        }
+    }
+}
+
+impl Embedder for ReducingEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.handle.reduce(self.inner.embed(text)?))
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.handle.reduce(self.inner.embed_query(text)?))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(self
+            .inner
+            .embed_batch(texts)?
+            .into_iter()
+            .map(|v| self.handle.reduce(v))
+            .collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        match self.handle.active.read().as_ref() {
+            Some(reduction) if reduction.output_dims() < self.inner.dimensions() => {
+                reduction.output_dims()
+            }
+            _ => self.inner.dimensions(),
+        }
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+
+    fn reduction(&self) -> Option<&ReductionHandle> {
+        Some(&self.handle)
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn super::AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl super::AsyncEmbedder for ReducingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.handle.reduce(self.inner_async.embed(text).await?))
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self
+            .handle
+            .reduce(self.inner_async.embed_query(text).await?))
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(self
+            .inner_async
+            .embed_batch(texts)
+            .await?
+            .into_iter()
+            .map(|v| self.handle.reduce(v))
+            .collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        Embedder::dimensions(self)
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner_async.summarized_by(text)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Storage functions
+// ---------------------------------------------------------------------------
+
+/// Outcome of [`reduce_stored_embeddings`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReductionReport {
+    pub method: ReductionMethod,
+    pub source_dims: Option<usize>,
+    pub target_dims: usize,
+    pub reduced: usize,
+    /// Embeddings left alone: already reduced or of another dimensionality.
+    pub skipped: usize,
+    pub originals_kept: bool,
+    /// PCA only: variance share kept over the fitting sample.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explained_variance: Option<f32>,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+/// Outcome of [`restore_original_embeddings`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub restored: usize,
+    /// Reduced embeddings without a stored original, queued for re-embedding.
+    pub requeued: usize,
+}
+
+struct StoredEmbedding {
+    memory_id: MemoryId,
+    /// Full-size vector: the kept original if any, else the stored vector.
+    full: Vec<f32>,
+    model: String,
+    has_original: bool,
+    /// Byte length of the vector currently in `embeddings`.
+    stored_bytes: usize,
+}
+
+fn decode(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+fn encode(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn load_stored(conn: &Connection) -> Result<Vec<StoredEmbedding>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.memory_id, COALESCE(o.embedding, e.embedding), e.model, o.memory_id IS NOT NULL,
+                length(e.embedding)
+         FROM embeddings e
+         LEFT JOIN embedding_originals o ON o.memory_id = e.memory_id
+         ORDER BY e.memory_id",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok(StoredEmbedding {
+            memory_id: r.get(0)?,
+            full: decode(&r.get::<_, Vec<u8>>(1)?),
+            model: r.get(2)?,
+            has_original: r.get(3)?,
+            stored_bytes: r.get::<_, i64>(4)?.max(0) as usize,
+        })
+    })?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(Into::into)
+}
+
+/// The most common dimensionality among full-size vectors longer than
+/// `target`, i.e. what the model produces.
+fn source_dimensions(stored: &[StoredEmbedding], target: usize) -> Option<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for s in stored.iter().filter(|s| s.full.len() > target) {
+        *counts.entry(s.full.len()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(dims, count)| (count, dims))
+        .map(|(dims, _)| dims)
+}
+
+/// Reduce every stored embedding to the handle's target size, fitting PCA
+/// on up to `fit_sample` vectors first when that is the configured method
+/// and no model is active yet (restore first to refit).
+/// With `keep_originals`, full-size vectors are saved for
+/// [`restore_original_embeddings`]. The fitted reduction is persisted and
+/// activated on the handle.
+pub fn reduce_stored_embeddings(
+    conn: &Connection,
+    handle: &ReductionHandle,
+    fit_sample: usize,
+    keep_originals: bool,
+    seed: u64,
+) -> Result<ReductionReport> {
+    let spec = handle.spec();
+    let stored = load_stored(conn)?;
+    let source_dims = source_dimensions(&stored, spec.dimensions);
+
+    // An active PCA model is reused: vectors reduced without a kept
+    // original could not be re-projected onto a refitted basis.
+    let reduction = match (handle.active(), spec.method, source_dims) {
+        (Some(active), _, _) => active,
+        (None, ReductionMethod::Truncate, _) => Reduction::Truncate {
+            dimensions: spec.dimensions,
+        },
+        (None, ReductionMethod::Pca, Some(dims)) => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let candidates: Vec<&[f32]> = stored
+                .iter()
+                .filter(|s| s.full.len() == dims)
+                .map(|s| s.full.as_slice())
+                .collect();
+            let sample: Vec<&[f32]> = candidates
+                .choose_multiple(&mut rng, fit_sample.max(1))
+                .copied()
+                .collect();
+            Reduction::Pca(PcaModel::fit(&sample, spec.dimensions, seed)?)
+        }
+        (None, ReductionMethod::Pca, None) => {
+            return Err(EngramError::InvalidInput(format!(
+                "No stored embeddings larger than {} dimensions to fit PCA on",
+                spec.dimensions
+            )))
+        }
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let mut report = ReductionReport {
+        method: spec.method,
+        source_dims,
+        target_dims: reduction.output_dims(),
+        reduced: 0,
+        skipped: 0,
+        originals_kept: keep_originals,
+        explained_variance: match &reduction {
+            Reduction::Pca(model) => Some(model.explained_variance),
+            Reduction::Truncate { .. } => None,
+        },
+        bytes_before: 0,
+        bytes_after: 0,
+    };
+
+    for s in &stored {
+        report.bytes_before += s.stored_bytes;
+        let already_reduced = s.stored_bytes < s.full.len() * 4;
+        if already_reduced || Some(s.full.len()) != source_dims || !reduction.accepts(&s.full) {
+            report.skipped += 1;
+            report.bytes_after += s.stored_bytes;
+            continue;
+        }
+
+        if keep_originals && !s.has_original {
+            conn.execute(
+                "INSERT INTO embedding_originals (memory_id, embedding, model, dimensions, created_at)
+                 SELECT memory_id, embedding, model, dimensions, ? FROM embeddings WHERE memory_id = ?",
+                params![now, s.memory_id],
+            )?;
+        }
+        let reduced = reduction.apply(&s.full);
+        let bytes = encode(&reduced);
+        report.bytes_after += bytes.len();
+        conn.execute(
+            "UPDATE embeddings SET embedding = ?, dimensions = ?, model = ? WHERE memory_id = ?",
+            params![bytes, reduced.len(), s.model, s.memory_id],
+        )?;
+        report.reduced += 1;
+    }
+
+    save_reduction(conn, &reduction, source_dims)?;
+    handle.set_active(Some(reduction));
+    Ok(report)
+}
+
+/// Put kept originals back in place of reduced vectors and deactivate the
+/// reduction. Reduced vectors without an original (embedded after the
+/// reduction was enabled) are dropped and queued for re-embedding.
+///
+/// The configured reduction still applies to new embeddings; unset
+/// `ENGRAM_EMBEDDING_REDUCTION` before restarting to keep full-size vectors.
+pub fn restore_original_embeddings(
+    conn: &Connection,
+    handle: Option<&ReductionHandle>,
+) -> Result<RestoreReport> {
+    let restored = conn.execute(
+        "UPDATE embeddings SET
+            embedding = (SELECT o.embedding FROM embedding_originals o WHERE o.memory_id = embeddings.memory_id),
+            dimensions = (SELECT o.dimensions FROM embedding_originals o WHERE o.memory_id = embeddings.memory_id),
+            model = (SELECT o.model FROM embedding_originals o WHERE o.memory_id = embeddings.memory_id)
+         WHERE memory_id IN (SELECT memory_id FROM embedding_originals)",
+        [],
+    )?;
+    conn.execute("DELETE FROM embedding_originals", [])?;
+
+    let mut requeued = 0;
+    if let Some(source_dims) = stored_source_dims(conn)? {
+        let now = Utc::now().to_rfc3339();
+        let stale: Vec<MemoryId> = {
+            let mut stmt = conn.prepare("SELECT memory_id FROM embeddings WHERE dimensions < ?")?;
+            let ids = stmt
+                .query_map(params![source_dims as i64], |r| r.get(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            ids
+        };
+        for id in stale {
+            conn.execute("DELETE FROM embeddings WHERE memory_id = ?", params![id])?;
+            conn.execute(
+                "UPDATE memories SET has_embedding = 0 WHERE id = ?",
+                params![id],
+            )?;
+            conn.execute(
+                "INSERT OR REPLACE INTO embedding_queue (memory_id, status, queued_at)
+                 VALUES (?, 'pending', ?)",
+                params![id, now],
+            )?;
+            requeued += 1;
+        }
+    }
+
+    conn.execute("DELETE FROM embedding_reduction", [])?;
+    if let Some(handle) = handle {
+        handle.set_active(None);
+    }
+    Ok(RestoreReport { restored, requeued })
+}
+
+/// Source dimensionality recorded with the persisted reduction.
+fn stored_source_dims(conn: &Connection) -> Result<Option<usize>> {
+    let dims: Option<Option<i64>> = conn
+        .query_row(
+            "SELECT source_dims FROM embedding_reduction WHERE id = 1",
+            [],
+            |r| r.get(0),
+        )
+        .optional()?;
+    Ok(dims.flatten().map(|d| d as usize))
+}
+
+/// The full-size embedding of a memory: its kept original, or the stored
+/// vector when it was never reduced.
+pub fn get_original_embedding(conn: &Connection, memory_id: MemoryId) -> Result<Option<Vec<f32>>> {
+    let bytes: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT COALESCE(
+                (SELECT embedding FROM embedding_originals WHERE memory_id = ?1),
+                (SELECT embedding FROM embeddings WHERE memory_id = ?1))",
+            params![memory_id],
+            |r| r.get(0),
+        )
+        .optional()?
+        .flatten();
+    Ok(bytes.map(|b| decode(&b)))
+}
+
+fn save_reduction(
+    conn: &Connection,
+    reduction: &Reduction,
+    source_dims: Option<usize>,
+) -> Result<()> {
+    let model = match reduction {
+        Reduction::Truncate { .. } => None,
+        Reduction::Pca(model) => Some(serde_json::to_string(model)?),
+    };
+    conn.execute(
+        "INSERT INTO embedding_reduction (id, method, target_dims, source_dims, model, fitted_at)
+         VALUES (1, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            method = excluded.method,
+            target_dims = excluded.target_dims,
+            source_dims = COALESCE(excluded.source_dims, embedding_reduction.source_dims),
+            model = excluded.model,
+            fitted_at = excluded.fitted_at",
+        params![
+            reduction.method().as_str(),
+            reduction.output_dims() as i64,
+            source_dims.map(|d| d as i64),
+            model,
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+/// The persisted reduction, if any.
+pub fn load_reduction(conn: &Connection) -> Result<Option<Reduction>> {
+    let row: Option<(String, i64, Option<String>)> = conn
+        .query_row(
+            "SELECT method, target_dims, model FROM embedding_reduction WHERE id = 1",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .optional()?;
+    match row {
+        None => Ok(None),
+        Some((method, dims, _)) if method == "truncate" => Ok(Some(Reduction::Truncate {
+            dimensions: dims.max(0) as usize,
+        })),
+        Some((_, _, Some(model))) => Ok(Some(Reduction::Pca(serde_json::from_str(&model)?))),
+        Some((method, _, None)) => Err(EngramError::Storage(format!(
+            "Stored embedding reduction '{}' has no model",
+            method
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::TfIdfEmbedder;
+
+    fn with_db(f: impl FnOnce(&Connection)) {
+        let storage = crate::storage::Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                f(conn);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    /// Vectors that vary mostly along two directions.
+    fn low_rank_vectors(n: usize, dims: usize) -> Vec<Vec<f32>> {
+        let mut rng = StdRng::seed_from_u64(9);
+        (0..n)
+            .map(|_| {
+                let a: f32 = rng.gen_range(-1.0..1.0);
+                let b: f32 = rng.gen_range(-1.0..1.0);
+                (0..dims)
+                    .map(|d| {
+                        let noise: f32 = rng.gen_range(-0.01..0.01);
+                        a * (d as f32).sin() + b * (d as f32 * 0.5).cos() + noise
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn insert_embedding(conn: &Connection, content: &str, vector: &[f32]) -> MemoryId {
+        conn.execute(
+            "INSERT INTO memories (content, memory_type, importance, visibility, metadata, valid_from)
+             VALUES (?, 'note', 0.5, 'private', '{}', CURRENT_TIMESTAMP)",
+            params![content],
+        )
+        .unwrap();
+        let id = conn.last_insert_rowid();
+        crate::embedding::queue::store_embedding(
+            conn,
+            id,
+            vector,
+            "test",
+            vector.len(),
+            None,
+            "now",
+        )
+        .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let spec: ReductionSpec = "pca:256".parse().unwrap();
+        assert_eq!(spec.method, ReductionMethod::Pca);
+        assert_eq!(spec.dimensions, 256);
+        let spec: ReductionSpec = "matryoshka:64".parse().unwrap();
+        assert_eq!(spec.method, ReductionMethod::Truncate);
+        assert!("pca".parse::<ReductionSpec>().is_err());
+        assert!("svd:10".parse::<ReductionSpec>().is_err());
+        assert!("truncate:0".parse::<ReductionSpec>().is_err());
+    }
+
+    #[test]
+    fn test_pca_keeps_dominant_variance() {
+        let vectors = low_rank_vectors(200, 32);
+        let refs: Vec<&[f32]> = vectors.iter().map(|v| v.as_slice()).collect();
+        let model = PcaModel::fit(&refs, 4, 1).unwrap();
+        assert_eq!(model.components.len(), 4);
+        assert!(
+            model.explained_variance > 0.99,
+            "{}",
+            model.explained_variance
+        );
+
+        // Components are orthonormal.
+        for (i, a) in model.components.iter().enumerate() {
+            for (j, b) in model.components.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot(a, b) - expected).abs() < 1e-3);
+            }
+        }
+        assert_eq!(model.project(&vectors[0]).len(), 4);
+    }
+
+    #[test]
+    fn test_truncating_embedder_and_round_trip() {
+        with_db(|conn| {
+            let vectors = low_rank_vectors(20, 48);
+            let ids: Vec<MemoryId> = vectors
+                .iter()
+                .enumerate()
+                .map(|(i, v)| insert_embedding(conn, &format!("memory {}", i), v))
+                .collect();
+
+            let handle = Arc::new(ReductionHandle::new("truncate:16".parse().unwrap()));
+            let embedder = ReducingEmbedder::new(Arc::new(TfIdfEmbedder::new(48)), handle.clone());
+            assert_eq!(embedder.dimensions(), 16);
+            let query = embedder.embed_query("some query").unwrap();
+            assert_eq!(query.len(), 16);
+            assert!(embedder.reduction().is_some());
+
+            let report = reduce_stored_embeddings(conn, &handle, 100, true, 7).unwrap();
+            assert_eq!(report.reduced, 20);
+            assert_eq!(report.source_dims, Some(48));
+            assert_eq!(report.bytes_after * 3, report.bytes_before);
+            let reduced = crate::embedding::get_embedding(conn, ids[0])
+                .unwrap()
+                .unwrap();
+            assert_eq!(reduced.len(), 16);
+            assert_eq!(
+                get_original_embedding(conn, ids[0]).unwrap().unwrap(),
+                vectors[0]
+            );
+
+            // Reducing again is a no-op.
+            let again = reduce_stored_embeddings(conn, &handle, 100, true, 7).unwrap();
+            assert_eq!(again.reduced, 0);
+
+            let restore = restore_original_embeddings(conn, Some(&handle)).unwrap();
+            assert_eq!(restore.restored, 20);
+            assert_eq!(
+                crate::embedding::get_embedding(conn, ids[0])
+                    .unwrap()
+                    .unwrap(),
+                vectors[0]
+            );
+            assert!(handle.active().is_none());
+        });
+    }
+
+    #[test]
+    fn test_pca_reduction_is_persisted() {
+        with_db(|conn| {
+            for (i, v) in low_rank_vectors(30, 24).iter().enumerate() {
+                insert_embedding(conn, &format!("memory {}", i), v);
+            }
+            let handle = ReductionHandle::new("pca:4".parse().unwrap());
+            assert!(handle.active().is_none());
+            assert!(!handle.load(conn).unwrap());
+
+            // Without a fitted model vectors pass through unchanged.
+            assert_eq!(handle.reduce(vec![1.0; 24]).len(), 24);
+
+            let report = reduce_stored_embeddings(conn, &handle, 100, false, 7).unwrap();
+            assert_eq!(report.reduced, 30);
+            assert!(report.explained_variance.unwrap() > 0.9);
+            assert_eq!(handle.reduce(vec![1.0; 24]).len(), 4);
+
+            let fresh = ReductionHandle::new("pca:4".parse().unwrap());
+            assert!(fresh.load(conn).unwrap());
+            assert_eq!(fresh.reduce(vec![1.0; 24]), handle.reduce(vec![1.0; 24]));
+
+            // No originals were kept, so restoring re-queues everything.
+            let restore = restore_original_embeddings(conn, Some(&handle)).unwrap();
+            assert_eq!(restore.restored, 0);
+            assert_eq!(restore.requeued, 30);
+        });
+    }
+}
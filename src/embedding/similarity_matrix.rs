@@ -0,0 +1,234 @@
+//! Pairwise similarity matrices
+//!
+//! Computes the cosine similarity between the stored embeddings of every
+//! pair in a filtered set of memories and exports it as JSON or CSV, for
+//! clustering and duplicate analysis outside Engram. Vectors are read like
+//! search reads them: another model's embeddings are projected through an
+//! adapter (see [`super::adapter`]) or, without one, reported as missing
+//! rather than compared.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::adapter::active_embedding_model;
+use super::{cosine_similarity, get_embedding};
+use crate::error::{EngramError, Result};
+use crate::storage::queries::list_memories;
+use crate::types::{ListOptions, MemoryId};
+
+/// Memories selected when neither `ids` nor a `limit` is given
+pub const DEFAULT_MATRIX_MEMORIES: usize = 500;
+
+/// Largest matrix computed, in memories per side
+pub const MAX_MATRIX_MEMORIES: usize = 2000;
+
+/// Which memories to compare
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SimilarityMatrixOptions {
+    /// Exactly these memories, in this order; otherwise those `list` selects
+    #[serde(default)]
+    pub ids: Option<Vec<MemoryId>>,
+    /// Filters for selecting memories when `ids` is not given
+    #[serde(flatten)]
+    pub list: ListOptions,
+    /// Round similarities to this many decimal places
+    #[serde(default)]
+    pub precision: Option<u32>,
+}
+
+/// Cosine similarities between memories' embeddings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityMatrix {
+    /// Row and column order of `matrix`
+    pub ids: Vec<MemoryId>,
+    /// Embedding model the vectors were compared in, if one is recorded
+    pub model: Option<String>,
+    pub dimensions: usize,
+    /// `matrix[i][j]` is the similarity of `ids[i]` and `ids[j]`
+    pub matrix: Vec<Vec<f32>>,
+    /// Selected memories left out for lack of a comparable embedding
+    pub missing_embeddings: Vec<MemoryId>,
+}
+
+impl SimilarityMatrix {
+    /// The matrix as CSV: a header of memory IDs, then one row per memory
+    /// starting with its ID
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("id");
+        for id in &self.ids {
+            csv.push_str(&format!(",{}", id));
+        }
+        csv.push('\n');
+        for (id, row) in self.ids.iter().zip(&self.matrix) {
+            csv.push_str(&id.to_string());
+            for value in row {
+                csv.push_str(&format!(",{}", value));
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Compute the similarity matrix of the memories `options` selects
+pub fn similarity_matrix(
+    conn: &Connection,
+    options: &SimilarityMatrixOptions,
+) -> Result<SimilarityMatrix> {
+    let too_many = |n: usize| {
+        EngramError::InvalidInput(format!(
+            "Similarity matrix limited to {} memories, got {}",
+            MAX_MATRIX_MEMORIES, n
+        ))
+    };
+
+    let ids: Vec<MemoryId> = match &options.ids {
+        Some(ids) => {
+            let mut unique = Vec::with_capacity(ids.len());
+            for id in ids {
+                if !unique.contains(id) {
+                    unique.push(*id);
+                }
+            }
+            unique
+        }
+        None => {
+            let limit = options.list.limit.unwrap_or(DEFAULT_MATRIX_MEMORIES as i64);
+            if limit < 1 || limit as usize > MAX_MATRIX_MEMORIES {
+                return Err(too_many(limit.max(0) as usize));
+            }
+            let list = ListOptions {
+                limit: Some(limit),
+                ..options.list.clone()
+            };
+            list_memories(conn, &list)?.iter().map(|m| m.id).collect()
+        }
+    };
+    if ids.len() > MAX_MATRIX_MEMORIES {
+        return Err(too_many(ids.len()));
+    }
+
+    let mut vectors: Vec<(MemoryId, Vec<f32>)> = Vec::with_capacity(ids.len());
+    let mut missing_embeddings = Vec::new();
+    for id in ids {
+        match get_embedding(conn, id)? {
+            // Reduced and full-size vectors can't be compared
+            Some(vector) if vectors.first().is_none_or(|(_, v)| v.len() == vector.len()) => {
+                vectors.push((id, vector))
+            }
+            _ => missing_embeddings.push(id),
+        }
+    }
+
+    let round = |value: f32| match options.precision {
+        Some(places) => {
+            let scale = 10f32.powi(places.min(9) as i32);
+            (value * scale).round() / scale
+        }
+        None => value,
+    };
+    let n = vectors.len();
+    let mut matrix = vec![vec![0.0f32; n]; n];
+    for i in 0..n {
+        matrix[i][i] = round(1.0);
+        for j in (i + 1)..n {
+            let similarity = round(cosine_similarity(&vectors[i].1, &vectors[j].1));
+            matrix[i][j] = similarity;
+            matrix[j][i] = similarity;
+        }
+    }
+
+    Ok(SimilarityMatrix {
+        dimensions: vectors.first().map_or(0, |(_, v)| v.len()),
+        ids: vectors.into_iter().map(|(id, _)| id).collect(),
+        model: active_embedding_model(conn)?,
+        matrix,
+        missing_embeddings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::store_embedding;
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+
+    #[test]
+    fn test_similarity_matrix_and_csv() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let vectors: [&[f32]; 3] = [&[1.0, 0.0], &[1.0, 1.0], &[0.0, 2.0]];
+                let mut ids = Vec::new();
+                for (i, vector) in vectors.iter().enumerate() {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: format!("memory {}", i),
+                            workspace: Some("matrix".to_string()),
+                            ..Default::default()
+                        },
+                    )?;
+                    store_embedding(conn, memory.id, vector, "test", 2, None, "now")?;
+                    ids.push(memory.id);
+                }
+                let unembedded = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "no vector".to_string(),
+                        workspace: Some("matrix".to_string()),
+                        ..Default::default()
+                    },
+                )?;
+
+                let matrix = similarity_matrix(
+                    conn,
+                    &SimilarityMatrixOptions {
+                        ids: Some(vec![ids[0], ids[1], ids[2], unembedded.id, ids[0]]),
+                        precision: Some(3),
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(matrix.ids, ids);
+                assert_eq!(matrix.missing_embeddings, vec![unembedded.id]);
+                assert_eq!(matrix.dimensions, 2);
+                assert_eq!(matrix.matrix[0], vec![1.0, 0.707, 0.0]);
+                assert_eq!(matrix.matrix[2][1], 0.707);
+
+                let csv = matrix.to_csv();
+                let lines: Vec<&str> = csv.lines().collect();
+                assert_eq!(lines[0], format!("id,{},{},{}", ids[0], ids[1], ids[2]));
+                assert_eq!(lines[1], format!("{},1,0.707,0", ids[0]));
+
+                // Filtered selection
+                let by_workspace = similarity_matrix(
+                    conn,
+                    &SimilarityMatrixOptions {
+                        list: ListOptions {
+                            workspace: Some("matrix".to_string()),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(by_workspace.ids.len(), 3);
+                assert_eq!(by_workspace.missing_embeddings.len(), 1);
+
+                let too_large = similarity_matrix(
+                    conn,
+                    &SimilarityMatrixOptions {
+                        list: ListOptions {
+                            limit: Some(MAX_MATRIX_MEMORIES as i64 + 1),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                );
+                assert!(matches!(too_large, Err(EngramError::InvalidInput(_))));
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -2,25 +2,131 @@
 //!
 //! Simple, fast, no external dependencies. Good for testing and
 //! environments where API calls aren't possible.
+//!
+//! Without a vocabulary, IDF is approximated from term length. With a
+//! [`TfIdfVocabulary`] loaded from storage (see
+//! [`crate::storage::tfidf_vocabulary`]), terms are weighted by their real
+//! document frequency, which is persisted so vectors stay comparable across
+//! restarts.
 
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use rusqlite::Connection;
 
 use crate::embedding::Embedder;
 use crate::error::Result;
+use crate::storage::tfidf_vocabulary::{rebuild_tfidf_vocabulary, tfidf_corpus, tfidf_terms_since};
+
+/// In-memory copy of the stored document frequencies
+#[derive(Debug, Default)]
+pub struct TfIdfVocabulary {
+    state: RwLock<VocabularyState>,
+}
+
+#[derive(Debug, Default)]
+struct VocabularyState {
+    documents: u64,
+    generation: u64,
+    doc_freq: HashMap<String, u64>,
+}
+
+impl TfIdfVocabulary {
+    /// Load the stored vocabulary; empty (length-based IDF) when storage
+    /// has none
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let vocabulary = Self::default();
+        vocabulary.refresh(conn)?;
+        Ok(vocabulary)
+    }
+
+    /// Load the stored vocabulary, building it from the stored memories
+    /// first when storage has none
+    pub fn open(conn: &Connection) -> Result<Self> {
+        if tfidf_corpus(conn)?.is_none() {
+            rebuild_tfidf_vocabulary(conn)?;
+        }
+        Self::load(conn)
+    }
+
+    /// Pick up stored changes: terms counted since the last refresh, or
+    /// everything after a rebuild. Returns whether anything changed.
+    pub fn refresh(&self, conn: &Connection) -> Result<bool> {
+        let Some((documents, generation)) = tfidf_corpus(conn)? else {
+            return Ok(false);
+        };
+        let (since, rebuilt) = {
+            let state = self.state.read();
+            if state.generation == generation && state.documents == documents {
+                return Ok(false);
+            }
+            if state.generation == generation {
+                (state.documents, false)
+            } else {
+                (0, true)
+            }
+        };
+        let terms = tfidf_terms_since(conn, since)?;
+        let mut state = self.state.write();
+        if rebuilt {
+            state.doc_freq.clear();
+        }
+        state.doc_freq.extend(terms);
+        state.documents = documents;
+        state.generation = generation;
+        Ok(true)
+    }
+
+    /// Documents the frequencies were counted over
+    pub fn documents(&self) -> u64 {
+        self.state.read().documents
+    }
+
+    /// Distinct terms known
+    pub fn len(&self) -> usize {
+        self.state.read().doc_freq.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl VocabularyState {
+    /// Smoothed IDF, `None` before any document was counted
+    fn idf(&self, term: &str) -> Option<f32> {
+        if self.documents == 0 {
+            return None;
+        }
+        let df = self.doc_freq.get(term).copied().unwrap_or(0);
+        Some(((1.0 + self.documents as f32) / (1.0 + df as f32)).ln() + 1.0)
+    }
+}
 
 /// TF-IDF based embedder using hashing trick
 pub struct TfIdfEmbedder {
     dimensions: usize,
+    vocabulary: Option<Arc<TfIdfVocabulary>>,
 }
 
 impl TfIdfEmbedder {
     pub fn new(dimensions: usize) -> Self {
-        Self { dimensions }
+        Self {
+            dimensions,
+            vocabulary: None,
+        }
+    }
+
+    /// Weight terms by the vocabulary's document frequencies
+    pub fn with_vocabulary(mut self, vocabulary: Arc<TfIdfVocabulary>) -> Self {
+        self.vocabulary = Some(vocabulary);
+        self
     }
 
     /// Tokenize text into lowercase words
-    fn tokenize(text: &str) -> Vec<String> {
+    pub(crate) fn tokenize(text: &str) -> Vec<String> {
         text.to_lowercase()
             .split(|c: char| !c.is_alphanumeric())
             .filter(|s| s.len() > 1)
@@ -98,12 +204,17 @@ impl Embedder for TfIdfEmbedder {
         }
 
         // Apply TF-IDF-like weighting with feature hashing
+        let vocabulary = self.vocabulary.as_ref().map(|v| v.state.read());
         for (token, count) in tf {
             // TF: log(1 + count/doc_len)
             let tf_score = (1.0 + count / doc_len).ln();
 
-            // IDF approximation based on token length (longer = rarer)
-            let idf_score = 1.0 + (token.len() as f32 * 0.1);
+            // IDF from the vocabulary, or approximated from token length
+            // (longer = rarer)
+            let idf_score = vocabulary
+                .as_ref()
+                .and_then(|state| state.idf(&token))
+                .unwrap_or(1.0 + (token.len() as f32 * 0.1));
 
             let weight = tf_score * idf_score;
             let idx = Self::hash_token(&token, self.dimensions);
@@ -180,6 +291,60 @@ mod tests {
         assert!(e.iter().all(|&x| x == 0.0));
     }
 
+    #[test]
+    fn test_vocabulary_weights_rare_terms() {
+        use crate::storage::queries::create_memory;
+        use crate::storage::Storage;
+        use crate::types::CreateMemoryInput;
+
+        let storage = Storage::open_in_memory().unwrap();
+        for content in ["deploy the service", "deploy the database", "kubernetes"] {
+            storage
+                .with_transaction(|conn| {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .unwrap();
+        }
+        let vocabulary = Arc::new(storage.with_transaction(TfIdfVocabulary::open).unwrap());
+        assert_eq!(vocabulary.documents(), 3);
+        let embedder = TfIdfEmbedder::new(384).with_vocabulary(vocabulary.clone());
+
+        // "the" is in two of three documents, "kubernetes" in one
+        let common = embedder.embed("the the").unwrap();
+        let rare = embedder.embed("kubernetes kubernetes").unwrap();
+        let mixed = embedder.embed("the kubernetes").unwrap();
+        assert!(cosine_similarity(&mixed, &rare) > cosine_similarity(&mixed, &common));
+
+        // New memories are counted and picked up on refresh; a fresh load
+        // sees the same vocabulary, so vectors match across restarts
+        storage
+            .with_transaction(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "kubernetes upgrade".to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+        assert!(storage.with_connection(|c| vocabulary.refresh(c)).unwrap());
+        assert_eq!(vocabulary.documents(), 4);
+        let reloaded = Arc::new(storage.with_connection(TfIdfVocabulary::load).unwrap());
+        assert_eq!(reloaded.len(), vocabulary.len());
+        let restarted = TfIdfEmbedder::new(384).with_vocabulary(reloaded);
+        assert_eq!(
+            restarted.embed("kubernetes upgrade").unwrap(),
+            embedder.embed("kubernetes upgrade").unwrap()
+        );
+    }
+
     #[test]
     fn test_tfidf_normalized() {
         let embedder = TfIdfEmbedder::new(384);
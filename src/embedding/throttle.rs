@@ -0,0 +1,604 @@
+//! Retries and rate limiting for hosted embedding APIs
+//!
+//! Long batch runs against a hosted API hit transient `429` and `5xx`
+//! responses. This module keeps them alive:
+//!
+//! - [`RetryPolicy`] retries transient failures with jittered exponential
+//!   back-off, waiting at least as long as the server's `Retry-After`
+//! - [`ApiThrottle`] keeps requests under a requests-per-minute and
+//!   tokens-per-minute budget over a sliding one-minute window
+//! - [`embed_chunks`] sends a batch chunk by chunk, and when a chunk is
+//!   rejected for its input, splits it to isolate the offending texts instead
+//!   of losing the whole batch
+//!
+//! [`ThrottleConfig::from_env`] reads the settings; the OpenAI embedder uses
+//! the `OPENAI_` prefix.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::error::EngramError;
+
+/// Retries per request after the first attempt, unless configured
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Length of the rate-limit window
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Back-off for transient API failures
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub max_retries: u32,
+    /// Base delay before the first retry, doubled for each further one
+    pub initial_backoff: Duration,
+    /// Upper bound on any single delay, including `Retry-After`
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (1-based): the exponential delay
+    /// with its upper half jittered, or the server's `retry_after` when that
+    /// is longer. Never more than `max_backoff`.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        let half = exponential / 2;
+        let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        let delay = half + Duration::from_millis(jitter);
+        match retry_after {
+            Some(wait) => wait.min(self.max_backoff).max(delay),
+            None => delay,
+        }
+    }
+}
+
+/// Retry policy and rate limits for one API
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThrottleConfig {
+    pub retry: RetryPolicy,
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl ThrottleConfig {
+    /// Defaults overridden by `<PREFIX>_MAX_RETRIES`,
+    /// `<PREFIX>_REQUESTS_PER_MINUTE` and `<PREFIX>_TOKENS_PER_MINUTE`.
+    /// Rate limits of 0 or unparseable values mean no limit.
+    pub fn from_env(prefix: &str) -> Self {
+        let var = |name: &str| -> Option<u32> {
+            std::env::var(format!("{}_{}", prefix, name))
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+        };
+        Self {
+            retry: RetryPolicy {
+                max_retries: var("MAX_RETRIES").unwrap_or(DEFAULT_MAX_RETRIES),
+                ..Default::default()
+            },
+            requests_per_minute: var("REQUESTS_PER_MINUTE").filter(|&n| n > 0),
+            tokens_per_minute: var("TOKENS_PER_MINUTE").filter(|&n| n > 0),
+        }
+    }
+}
+
+/// Why an API request failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiFailure {
+    /// Worth retrying: 408, 429, 5xx or a connection problem
+    Transient {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Sending the same request again won't help
+    Permanent {
+        status: Option<u16>,
+        message: String,
+    },
+}
+
+impl ApiFailure {
+    /// Classify an HTTP error response
+    pub fn from_status(status: u16, message: String, retry_after: Option<Duration>) -> Self {
+        if status == 408 || status == 429 || status >= 500 {
+            ApiFailure::Transient {
+                message,
+                retry_after,
+            }
+        } else {
+            ApiFailure::Permanent {
+                status: Some(status),
+                message,
+            }
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiFailure::Transient { message, .. } | ApiFailure::Permanent { message, .. } => {
+                message
+            }
+        }
+    }
+
+    /// The request was rejected for its content (400, 413, 422), so a
+    /// smaller batch without the offending text may succeed
+    pub fn is_input_error(&self) -> bool {
+        matches!(
+            self,
+            ApiFailure::Permanent {
+                status: Some(400 | 413 | 422),
+                ..
+            }
+        )
+    }
+}
+
+impl From<ApiFailure> for EngramError {
+    fn from(failure: ApiFailure) -> Self {
+        if failure.is_input_error() {
+            EngramError::InvalidInput(failure.message().to_string())
+        } else {
+            EngramError::Embedding(failure.message().to_string())
+        }
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// Rough token count of a text for rate limiting (about four characters
+/// per token for English)
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(4).max(1)
+}
+
+/// Requests-per-minute and tokens-per-minute limiter over a sliding window
+#[derive(Debug, Default)]
+pub struct ApiThrottle {
+    requests_per_minute: Option<u32>,
+    tokens_per_minute: Option<u32>,
+    /// Start time and token count of each request in the last minute
+    window: parking_lot::Mutex<VecDeque<(Instant, u32)>>,
+}
+
+impl ApiThrottle {
+    pub fn new(requests_per_minute: Option<u32>, tokens_per_minute: Option<u32>) -> Self {
+        Self {
+            requests_per_minute,
+            tokens_per_minute,
+            window: Default::default(),
+        }
+    }
+
+    pub fn tokens_per_minute(&self) -> Option<u32> {
+        self.tokens_per_minute
+    }
+
+    /// Record a request of `tokens` if it fits the budget at `now`, or
+    /// return how long to wait before it will. A request larger than the
+    /// whole token budget is let through once the window is empty.
+    fn try_acquire(&self, tokens: u32, now: Instant) -> Option<Duration> {
+        if self.requests_per_minute.is_none() && self.tokens_per_minute.is_none() {
+            return None;
+        }
+        let mut window = self.window.lock();
+        while window
+            .front()
+            .is_some_and(|(start, _)| now.duration_since(*start) >= WINDOW)
+        {
+            window.pop_front();
+        }
+
+        // When the entry at `index` leaves the window
+        let expiry = |index: usize| {
+            let (start, _) = window[index];
+            (start + WINDOW).saturating_duration_since(now)
+        };
+        let mut wait = Duration::ZERO;
+        if let Some(limit) = self.requests_per_minute {
+            let limit = limit.max(1) as usize;
+            if window.len() >= limit {
+                wait = wait.max(expiry(window.len() - limit));
+            }
+        }
+        if let Some(limit) = self.tokens_per_minute {
+            let mut used: u32 = window.iter().map(|(_, t)| t).sum();
+            let mut index = 0;
+            while index < window.len() && used.saturating_add(tokens) > limit {
+                used -= window[index].1;
+                index += 1;
+            }
+            if index > 0 {
+                wait = wait.max(expiry(index - 1));
+            }
+        }
+
+        if wait.is_zero() {
+            window.push_back((now, tokens));
+            None
+        } else {
+            Some(wait)
+        }
+    }
+
+    /// Wait until a request of `tokens` fits the budget, and record it
+    pub async fn acquire(&self, tokens: u32) {
+        while let Some(wait) = self.try_acquire(tokens, Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Run `request` under `throttle`, retrying transient failures as `policy`
+/// allows. `tokens` is the request's estimated size.
+pub async fn send_with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    throttle: &ApiThrottle,
+    tokens: u32,
+    mut request: F,
+) -> Result<T, ApiFailure>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiFailure>>,
+{
+    let mut attempt = 0;
+    loop {
+        throttle.acquire(tokens).await;
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(ApiFailure::Transient {
+                message,
+                retry_after,
+            }) if attempt < policy.max_retries => {
+                attempt += 1;
+                let delay = policy.delay(attempt, retry_after);
+                tracing::warn!(
+                    "Embedding request failed (attempt {}/{}), retrying in {:?}: {}",
+                    attempt,
+                    policy.max_retries + 1,
+                    delay,
+                    message
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(failure) => return Err(failure),
+        }
+    }
+}
+
+/// Embeddings of a batch, one slot per input text
+#[derive(Debug, Clone, Default)]
+pub struct PartialBatch {
+    /// `None` where the text could not be embedded
+    pub embeddings: Vec<Option<Vec<f32>>>,
+    /// Index and error of each failed text
+    pub failures: Vec<(usize, String)>,
+}
+
+impl PartialBatch {
+    /// All embeddings, or an error if any text failed
+    pub fn into_result(self) -> crate::error::Result<Vec<Vec<f32>>> {
+        let total = self.embeddings.len();
+        match self.failures.first() {
+            None => Ok(self.embeddings.into_iter().flatten().collect()),
+            Some((_, message)) if self.failures.len() == total => {
+                Err(EngramError::Embedding(message.clone()))
+            }
+            Some((index, message)) => Err(EngramError::Embedding(format!(
+                "{} of {} texts failed to embed; text {}: {}",
+                self.failures.len(),
+                total,
+                index,
+                message
+            ))),
+        }
+    }
+}
+
+/// Split `texts` into consecutive chunks of at most `max_inputs` texts and,
+/// when set, `max_tokens` estimated tokens (a single larger text gets a
+/// chunk of its own)
+pub fn plan_chunks(
+    texts: &[&str],
+    max_inputs: usize,
+    max_tokens: Option<u32>,
+) -> Vec<Range<usize>> {
+    let max_inputs = max_inputs.max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0u32;
+    for (i, text) in texts.iter().enumerate() {
+        let text_tokens = estimate_tokens(text);
+        let over_tokens = max_tokens.is_some_and(|max| tokens.saturating_add(text_tokens) > max);
+        if i > start && (i - start >= max_inputs || over_tokens) {
+            chunks.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens = tokens.saturating_add(text_tokens);
+    }
+    if start < texts.len() {
+        chunks.push(start..texts.len());
+    }
+    chunks
+}
+
+/// Embed `texts` in chunks through `request`, retrying each chunk as
+/// `policy` allows.
+///
+/// Chunks that succeed are kept even when later ones fail. A chunk rejected
+/// for its input is split in halves until the offending texts stand alone;
+/// any other failure that outlasts the retries marks that chunk and every
+/// remaining one as failed, since the API is unavailable.
+pub async fn embed_chunks<'a, F, Fut>(
+    texts: &'a [&'a str],
+    max_inputs: usize,
+    policy: &RetryPolicy,
+    throttle: &ApiThrottle,
+    mut request: F,
+) -> PartialBatch
+where
+    F: FnMut(&'a [&'a str]) -> Fut,
+    Fut: Future<Output = Result<Vec<Vec<f32>>, ApiFailure>>,
+{
+    let mut batch = PartialBatch {
+        embeddings: vec![None; texts.len()],
+        failures: Vec::new(),
+    };
+    let mut pending = plan_chunks(texts, max_inputs, throttle.tokens_per_minute());
+    pending.reverse();
+
+    while let Some(range) = pending.pop() {
+        let chunk = &texts[range.clone()];
+        let tokens = chunk.iter().map(|t| estimate_tokens(t)).sum();
+        let result = send_with_retry(policy, throttle, tokens, || request(chunk)).await;
+        let failure = match result {
+            Ok(embeddings) if embeddings.len() == chunk.len() => {
+                for (slot, embedding) in batch.embeddings[range].iter_mut().zip(embeddings) {
+                    *slot = Some(embedding);
+                }
+                continue;
+            }
+            Ok(embeddings) => ApiFailure::Permanent {
+                status: None,
+                message: format!(
+                    "Embedding API returned {} embeddings for {} inputs",
+                    embeddings.len(),
+                    chunk.len()
+                ),
+            },
+            Err(failure) => failure,
+        };
+
+        if failure.is_input_error() && range.len() > 1 {
+            let mid = range.start + range.len() / 2;
+            pending.push(mid..range.end);
+            pending.push(range.start..mid);
+            continue;
+        }
+        let message = failure.message().to_string();
+        let abort = !failure.is_input_error();
+        batch
+            .failures
+            .extend(range.map(|index| (index, message.clone())));
+        if abort {
+            while let Some(rest) = pending.pop() {
+                batch
+                    .failures
+                    .extend(rest.map(|index| (index, message.clone())));
+            }
+        }
+    }
+    batch.failures.sort_by_key(|(index, _)| *index);
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    fn transient() -> ApiFailure {
+        ApiFailure::from_status(429, "Embedding API error 429".to_string(), None)
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_honours_retry_after() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        for _ in 0..20 {
+            let first = policy.delay(1, None);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.delay(3, None);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            assert!(policy.delay(30, None) <= Duration::from_secs(1));
+        }
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_millis(700))),
+            Duration::from_millis(700)
+        );
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(90))),
+            Duration::from_secs(1)
+        );
+        assert_eq!(parse_retry_after("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_throttle_limits_requests_and_tokens() {
+        let start = Instant::now();
+        let requests = ApiThrottle::new(Some(2), None);
+        assert!(requests.try_acquire(1, start).is_none());
+        assert!(requests
+            .try_acquire(1, start + Duration::from_secs(10))
+            .is_none());
+        assert_eq!(
+            requests.try_acquire(1, start + Duration::from_secs(20)),
+            Some(Duration::from_secs(40))
+        );
+        assert!(requests.try_acquire(1, start + WINDOW).is_none());
+
+        let tokens = ApiThrottle::new(None, Some(100));
+        assert!(tokens.try_acquire(60, start).is_none());
+        assert!(tokens
+            .try_acquire(30, start + Duration::from_secs(5))
+            .is_none());
+        // 60 + 30 + 50 > 100: wait for the first request to age out
+        assert_eq!(
+            tokens.try_acquire(50, start + Duration::from_secs(30)),
+            Some(Duration::from_secs(30))
+        );
+        // A request over the whole budget goes through on an empty window
+        let big = ApiThrottle::new(None, Some(100));
+        assert!(big.try_acquire(500, start).is_none());
+
+        assert!(ApiThrottle::default()
+            .try_acquire(u32::MAX, start)
+            .is_none());
+    }
+
+    #[test]
+    fn test_plan_chunks_by_count_and_tokens() {
+        let texts = ["aaaa"; 5];
+        assert_eq!(plan_chunks(&texts, 2, None), vec![0..2, 2..4, 4..5]);
+        // Each text is one token
+        assert_eq!(plan_chunks(&texts, 10, Some(3)), vec![0..3, 3..5]);
+        let long = "x".repeat(100);
+        assert_eq!(
+            plan_chunks(&["a", &long, "b"], 10, Some(10)),
+            vec![0..1, 1..2, 2..3]
+        );
+        assert!(plan_chunks(&[], 10, None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_recovers_from_transient_failures() {
+        let calls = Cell::new(0);
+        let throttle = ApiThrottle::default();
+        let result = send_with_retry(&fast_policy(3), &throttle, 1, || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt < 3 {
+                    Err(transient())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+
+        calls.set(0);
+        let exhausted: Result<(), _> = send_with_retry(&fast_policy(2), &throttle, 1, || {
+            calls.set(calls.get() + 1);
+            async { Err(transient()) }
+        })
+        .await;
+        assert!(matches!(exhausted, Err(ApiFailure::Transient { .. })));
+        assert_eq!(calls.get(), 3);
+
+        calls.set(0);
+        let permanent: Result<(), _> = send_with_retry(&fast_policy(2), &throttle, 1, || {
+            calls.set(calls.get() + 1);
+            async {
+                Err(ApiFailure::from_status(
+                    401,
+                    "unauthorized".to_string(),
+                    None,
+                ))
+            }
+        })
+        .await;
+        assert!(matches!(permanent, Err(ApiFailure::Permanent { .. })));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_embed_chunks_isolates_bad_inputs() {
+        let texts = ["a", "b", "bad", "c", "d"];
+        let throttle = ApiThrottle::default();
+        let batch = embed_chunks(&texts, 4, &fast_policy(1), &throttle, |chunk| async move {
+            if chunk.contains(&"bad") {
+                return Err(ApiFailure::from_status(
+                    400,
+                    "input too long".to_string(),
+                    None,
+                ));
+            }
+            Ok(chunk.iter().map(|t| vec![t.len() as f32]).collect())
+        })
+        .await;
+
+        assert_eq!(batch.failures, vec![(2, "input too long".to_string())]);
+        assert_eq!(batch.embeddings[3], Some(vec![1.0]));
+        assert!(batch.embeddings[2].is_none());
+        let err = batch.into_result().unwrap_err().to_string();
+        assert!(err.contains("1 of 5"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_embed_chunks_keeps_finished_chunks_when_api_fails() {
+        let texts = ["a", "b", "c", "d", "e"];
+        let throttle = ApiThrottle::default();
+        let calls = Cell::new(0);
+        let batch = embed_chunks(&texts, 2, &fast_policy(1), &throttle, |chunk| {
+            calls.set(calls.get() + 1);
+            let fail = calls.get() > 1;
+            async move {
+                if fail {
+                    Err(ApiFailure::from_status(
+                        503,
+                        "unavailable".to_string(),
+                        None,
+                    ))
+                } else {
+                    Ok(chunk.iter().map(|_| vec![1.0]).collect())
+                }
+            }
+        })
+        .await;
+
+        // First chunk kept; the second failed twice and the rest were abandoned
+        assert_eq!(calls.get(), 3);
+        assert!(batch.embeddings[0].is_some() && batch.embeddings[1].is_some());
+        let failed: Vec<usize> = batch.failures.iter().map(|(i, _)| *i).collect();
+        assert_eq!(failed, vec![2, 3, 4]);
+    }
+}
@@ -0,0 +1,316 @@
+//! Token limits for embedding inputs
+//!
+//! Embedding APIs reject inputs over the model's context window; OpenAI
+//! answers 400 for anything past 8191 tokens, which fails the whole batch it
+//! was sent in. [`TokenLimitEmbedder`] counts tokens with the model's
+//! tiktoken encoding before calling the API and, for inputs over the limit,
+//! either embeds the first `max_input_tokens` tokens
+//! ([`LongTextStrategy::Truncate`]) or embeds every chunk of that size and
+//! averages the vectors weighted by chunk length
+//! ([`LongTextStrategy::ChunkAverage`]).
+//!
+//! Configured with `EmbeddingConfig::max_input_tokens` and
+//! `EmbeddingConfig::long_text`.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::{AsyncEmbedder, Embedder, ReductionHandle};
+use crate::error::{EngramError, Result};
+use crate::intelligence::compression::{detect_encoding, TiktokenCounter, TokenEncoding};
+use crate::types::{EmbeddingConfig, LongTextStrategy};
+
+/// OpenAI embedding models' input limit
+const OPENAI_MAX_INPUT_TOKENS: usize = 8191;
+
+impl FromStr for LongTextStrategy {
+    type Err = EngramError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "truncate" => Ok(LongTextStrategy::Truncate),
+            "chunk_average" | "chunk" => Ok(LongTextStrategy::ChunkAverage),
+            other => Err(EngramError::Config(format!(
+                "Unknown long text strategy: '{}'. Use 'truncate' or 'chunk_average'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Embedder that keeps inputs within a token limit
+pub struct TokenLimitEmbedder {
+    inner: Arc<dyn Embedder>,
+    inner_async: Arc<dyn AsyncEmbedder>,
+    counter: TiktokenCounter,
+    max_tokens: usize,
+    strategy: LongTextStrategy,
+}
+
+impl TokenLimitEmbedder {
+    pub fn new(
+        inner: Arc<dyn Embedder>,
+        counter: TiktokenCounter,
+        max_tokens: usize,
+        strategy: LongTextStrategy,
+    ) -> Self {
+        Self {
+            inner_async: super::to_async(inner.clone()),
+            inner,
+            counter,
+            max_tokens: max_tokens.max(1),
+            strategy,
+        }
+    }
+
+    /// The inputs sent for `text`: itself, its first chunk, or every chunk
+    fn pieces<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        let mut pieces = self.counter.split(text, self.max_tokens);
+        if self.strategy == LongTextStrategy::Truncate {
+            pieces.truncate(1);
+        }
+        pieces
+    }
+
+    /// Pieces of all `texts` in one list, with how many belong to each text
+    fn flatten<'a>(&self, texts: &[&'a str]) -> (Vec<&'a str>, Vec<usize>) {
+        let mut pieces = Vec::new();
+        let mut counts = Vec::with_capacity(texts.len());
+        for text in texts {
+            let before = pieces.len();
+            pieces.extend(self.pieces(text));
+            counts.push(pieces.len() - before);
+        }
+        (pieces, counts)
+    }
+}
+
+/// Length-weighted mean of the vectors embedded for one text's pieces,
+/// renormalized; a single piece's vector is returned as is
+fn average(pieces: &[&str], mut vectors: Vec<Vec<f32>>) -> Vec<f32> {
+    if vectors.len() == 1 {
+        return vectors.pop().unwrap_or_default();
+    }
+    let dims = vectors.first().map_or(0, Vec::len);
+    let mut mean = vec![0.0f32; dims];
+    for (piece, vector) in pieces.iter().zip(&vectors) {
+        let weight = piece.len() as f32;
+        for (m, v) in mean.iter_mut().zip(vector) {
+            *m += weight * v;
+        }
+    }
+    let norm = mean.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        mean.iter_mut().for_each(|v| *v /= norm);
+    }
+    mean
+}
+
+/// Regroup vectors embedded for [`TokenLimitEmbedder::flatten`]'s pieces
+fn regroup(pieces: &[&str], counts: &[usize], vectors: Vec<Vec<f32>>) -> Vec<Vec<f32>> {
+    let mut vectors = vectors.into_iter();
+    let mut start = 0;
+    counts
+        .iter()
+        .map(|&count| {
+            let group: Vec<Vec<f32>> = vectors.by_ref().take(count).collect();
+            let vector = average(&pieces[start..start + count], group);
+            start += count;
+            vector
+        })
+        .collect()
+}
+
+/// Wrap `embedder` in a [`TokenLimitEmbedder`] when `config` sets
+/// `max_input_tokens` or the model has a known limit
+pub fn with_token_limit(
+    embedder: Arc<dyn Embedder>,
+    config: &EmbeddingConfig,
+) -> Result<Arc<dyn Embedder>> {
+    let default = match config.model.as_str() {
+        "openai" => Some(OPENAI_MAX_INPUT_TOKENS),
+        _ => None,
+    };
+    match config.max_input_tokens.or(default) {
+        Some(max_tokens) if max_tokens > 0 => {
+            // Other providers' tokenizers differ; cl100k is close enough
+            // to stay under their limits with some headroom
+            let encoding = config
+                .embedding_model
+                .as_deref()
+                .and_then(detect_encoding)
+                .unwrap_or(TokenEncoding::Cl100kBase);
+            Ok(Arc::new(TokenLimitEmbedder::new(
+                embedder,
+                TiktokenCounter::new(encoding)?,
+                max_tokens,
+                config.long_text,
+            )))
+        }
+        _ => Ok(embedder),
+    }
+}
+
+impl Embedder for TokenLimitEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let pieces = self.pieces(text);
+        if let [piece] = pieces.as_slice() {
+            return self.inner.embed(piece);
+        }
+        let vectors = self.inner.embed_batch(&pieces)?;
+        Ok(average(&pieces, vectors))
+    }
+
+    fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let pieces = self.pieces(text);
+        let vectors = pieces
+            .iter()
+            .map(|piece| self.inner.embed_query(piece))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(average(&pieces, vectors))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let (pieces, counts) = self.flatten(texts);
+        let vectors = self.inner.embed_batch(&pieces)?;
+        Ok(regroup(&pieces, &counts, vectors))
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+
+    fn reduction(&self) -> Option<&ReductionHandle> {
+        self.inner.reduction()
+    }
+
+    fn hybrid_dense_dims(&self) -> Option<usize> {
+        self.inner.hybrid_dense_dims()
+    }
+
+    fn native_async(self: Arc<Self>) -> Option<Arc<dyn AsyncEmbedder>> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncEmbedder for TokenLimitEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let pieces = self.pieces(text);
+        if let [piece] = pieces.as_slice() {
+            return self.inner_async.embed(piece).await;
+        }
+        let vectors = self.inner_async.embed_batch(&pieces).await?;
+        Ok(average(&pieces, vectors))
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let pieces = self.pieces(text);
+        let mut vectors = Vec::with_capacity(pieces.len());
+        for piece in &pieces {
+            vectors.push(self.inner_async.embed_query(piece).await?);
+        }
+        Ok(average(&pieces, vectors))
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let (pieces, counts) = self.flatten(texts);
+        let vectors = self.inner_async.embed_batch(&pieces).await?;
+        Ok(regroup(&pieces, &counts, vectors))
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn summarized_by(&self, text: &str) -> Option<&str> {
+        self.inner.summarized_by(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records its inputs and embeds each as `[tokens, 1]`
+    struct RecordingEmbedder {
+        counter: TiktokenCounter,
+        inputs: Mutex<Vec<String>>,
+    }
+
+    impl Embedder for RecordingEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.inputs.lock().unwrap().push(text.to_string());
+            Ok(vec![self.counter.count(text) as f32, 1.0])
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+
+        fn model_name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    fn limited(strategy: LongTextStrategy) -> (Arc<RecordingEmbedder>, Arc<dyn Embedder>) {
+        let counter = TiktokenCounter::new(TokenEncoding::Cl100kBase).unwrap();
+        let recorder = Arc::new(RecordingEmbedder {
+            counter,
+            inputs: Mutex::new(Vec::new()),
+        });
+        let embedder = Arc::new(TokenLimitEmbedder::new(
+            recorder.clone(),
+            counter,
+            20,
+            strategy,
+        ));
+        (recorder, embedder)
+    }
+
+    #[test]
+    fn test_truncates_long_inputs() {
+        let (recorder, embedder) = limited(LongTextStrategy::Truncate);
+        let long = "The deploy pipeline runs every hour. ".repeat(20);
+        let vectors = embedder.embed_batch(&["short text", &long]).unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert!(vectors[1][0] <= 20.0);
+        let inputs = recorder.inputs.lock().unwrap();
+        assert_eq!(inputs[0], "short text");
+        assert!(long.starts_with(&inputs[1]) && inputs[1].len() < long.len());
+    }
+
+    #[test]
+    fn test_chunk_average_covers_whole_text() {
+        let (recorder, embedder) = limited(LongTextStrategy::ChunkAverage);
+        let long = "The deploy pipeline runs every hour. ".repeat(20);
+        let vectors = embedder.embed_batch(&[&long, "short text"]).unwrap();
+        assert_eq!(vectors.len(), 2);
+        let inputs = recorder.inputs.lock().unwrap();
+        assert!(inputs.len() > 2);
+        assert_eq!(inputs[..inputs.len() - 1].concat(), long);
+        // Averaged and renormalized
+        let norm = vectors[0].iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert_eq!(vectors[1], vec![2.0, 1.0]);
+
+        assert_eq!(
+            "chunk_average".parse::<LongTextStrategy>().unwrap(),
+            LongTextStrategy::ChunkAverage
+        );
+        assert!("middle".parse::<LongTextStrategy>().is_err());
+    }
+}
@@ -3,6 +3,11 @@
 //! Sends texts to the Voyage AI `/embeddings` endpoint and returns dense float
 //! vectors. Default model: `voyage-2` (1024 dimensions).
 //!
+//! Stored memories are sent with `input_type: "document"` and search queries
+//! (via [`Embedder::embed_query`](crate::embedding::Embedder::embed_query))
+//! with `"query"`, so Voyage prepends the matching retrieval prompt. Batches
+//! are split at 128 texts, the API's per-request limit.
+//!
 //! # Feature Flag
 //!
 //! Gated behind `#[cfg(feature = "voyage")]`. Requires the `voyage` feature to be
@@ -28,9 +33,19 @@
 
 #[cfg(feature = "voyage")]
 mod inner {
-    use crate::embedding::Embedder;
+    use std::sync::Arc;
+
+    use crate::embedding::{block_on, Embedder};
     use crate::error::{EngramError, Result};
 
+    /// Most texts the Voyage AI `/embeddings` endpoint accepts per request.
+    pub const VOYAGE_MAX_BATCH_SIZE: usize = 128;
+
+    /// `input_type` for texts stored in the memory index.
+    const DOCUMENT: &str = "document";
+    /// `input_type` for texts used to search the index.
+    const QUERY: &str = "query";
+
     /// Configuration for the Voyage AI embedding provider.
     #[derive(Debug, Clone)]
     pub struct VoyageConfig {
@@ -42,6 +57,8 @@ mod inner {
         pub base_url: String,
         /// Expected number of dimensions in the output embedding vector.
         pub dimensions: usize,
+        /// Texts sent per request; clamped to [`VOYAGE_MAX_BATCH_SIZE`].
+        pub max_batch_size: usize,
     }
 
     impl Default for VoyageConfig {
@@ -51,6 +68,7 @@ mod inner {
                 model: "voyage-2".to_string(),
                 base_url: "https://api.voyageai.com/v1".to_string(),
                 dimensions: 1024,
+                max_batch_size: VOYAGE_MAX_BATCH_SIZE,
             }
         }
     }
@@ -70,44 +88,49 @@ mod inner {
             }
         }
 
-        /// Async call to the Voyage AI `/embeddings` endpoint for a single text.
+        /// Async call to the Voyage AI `/embeddings` endpoint for a text to be
+        /// stored (`input_type: "document"`).
         pub async fn embed_async(&self, text: &str) -> Result<Vec<f32>> {
-            let url = format!("{}/embeddings", self.config.base_url);
+            self.embed_one(text, DOCUMENT).await
+        }
 
-            let response = self
-                .client
-                .post(&url)
-                .header("Authorization", format!("Bearer {}", self.config.api_key))
-                .json(&serde_json::json!({
-                    "input": [text],
-                    "model": self.config.model,
-                }))
-                .send()
-                .await?;
+        /// Async call to the Voyage AI `/embeddings` endpoint for a search
+        /// query (`input_type: "query"`).
+        pub async fn embed_query_async(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_one(text, QUERY).await
+        }
 
-            if !response.status().is_success() {
-                let status = response.status();
-                let body = response.text().await.unwrap_or_default();
-                return Err(EngramError::Embedding(format!(
-                    "Voyage API error {status}: {body}"
-                )));
+        /// Async batch call to the Voyage AI `/embeddings` endpoint for texts
+        /// to be stored.
+        ///
+        /// Texts are sent in chunks of at most `max_batch_size`.
+        pub async fn embed_batch_async(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::with_capacity(texts.len());
+            for chunk in texts.chunks(self.batch_size()) {
+                embeddings.extend(self.embed_texts(chunk, DOCUMENT).await?);
             }
+            Ok(embeddings)
+        }
 
-            let data: serde_json::Value = response.json().await?;
+        fn batch_size(&self) -> usize {
+            self.config.max_batch_size.clamp(1, VOYAGE_MAX_BATCH_SIZE)
+        }
 
-            // Response shape: {"data": [{"embedding": [f32...], "index": 0}], ...}
-            let embedding: Vec<f32> = data["data"]
-                .as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|item| item["embedding"].as_array())
-                .ok_or_else(|| {
-                    EngramError::Embedding(
-                        "Voyage response missing 'data[0].embedding' field".to_string(),
-                    )
-                })?
-                .iter()
-                .filter_map(|v| v.as_f64().map(|f| f as f32))
-                .collect();
+        fn request_body(&self, texts: &[&str], input_type: &str) -> serde_json::Value {
+            serde_json::json!({
+                "input": texts,
+                "model": self.config.model,
+                "input_type": input_type,
+            })
+        }
+
+        async fn embed_one(&self, text: &str, input_type: &str) -> Result<Vec<f32>> {
+            let embedding = self
+                .embed_texts(&[text], input_type)
+                .await?
+                .into_iter()
+                .next()
+                .unwrap_or_default();
 
             if embedding.is_empty() {
                 return Err(EngramError::Embedding(
@@ -118,11 +141,7 @@ mod inner {
             Ok(embedding)
         }
 
-        /// Async batch call to the Voyage AI `/embeddings` endpoint.
-        ///
-        /// Voyage AI supports batching natively — all texts are sent in a single
-        /// request.
-        pub async fn embed_batch_async(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        async fn embed_texts(&self, texts: &[&str], input_type: &str) -> Result<Vec<Vec<f32>>> {
             if texts.is_empty() {
                 return Ok(vec![]);
             }
@@ -133,10 +152,7 @@ mod inner {
                 .client
                 .post(&url)
                 .header("Authorization", format!("Bearer {}", self.config.api_key))
-                .json(&serde_json::json!({
-                    "input": texts,
-                    "model": self.config.model,
-                }))
+                .json(&self.request_body(texts, input_type))
                 .send()
                 .await?;
 
@@ -150,12 +166,21 @@ mod inner {
 
             let data: serde_json::Value = response.json().await?;
 
-            // Sort by index to maintain input order.
+            // Response shape: {"data": [{"embedding": [f32...], "index": 0}], ...}
             let raw = data["data"].as_array().ok_or_else(|| {
                 EngramError::Embedding("Voyage response missing 'data' field".to_string())
             })?;
 
-            // Collect (index, embedding) pairs then sort by index.
+            if raw.len() != texts.len() {
+                return Err(EngramError::Embedding(format!(
+                    "Voyage returned {} embeddings for {} texts",
+                    raw.len(),
+                    texts.len()
+                )));
+            }
+
+            // Collect (index, embedding) pairs then sort by index to maintain
+            // input order.
             let mut indexed: Vec<(usize, Vec<f32>)> = raw
                 .iter()
                 .map(|item| {
@@ -179,15 +204,42 @@ mod inner {
 
     impl Embedder for VoyageEmbedder {
         fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
-            tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(self.embed_async(text))
-            })
+            block_on(self.embed_async(text))
+        }
+
+        fn embed_query(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            block_on(self.embed_query_async(text))
         }
 
         fn embed_batch(&self, texts: &[&str]) -> crate::error::Result<Vec<Vec<f32>>> {
-            tokio::task::block_in_place(|| {
-                tokio::runtime::Handle::current().block_on(self.embed_batch_async(texts))
-            })
+            block_on(self.embed_batch_async(texts))
+        }
+
+        fn dimensions(&self) -> usize {
+            self.config.dimensions
+        }
+
+        fn model_name(&self) -> &str {
+            &self.config.model
+        }
+
+        fn native_async(self: Arc<Self>) -> Option<Arc<dyn crate::embedding::AsyncEmbedder>> {
+            Some(self)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::embedding::AsyncEmbedder for VoyageEmbedder {
+        async fn embed(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            self.embed_async(text).await
+        }
+
+        async fn embed_query(&self, text: &str) -> crate::error::Result<Vec<f32>> {
+            self.embed_query_async(text).await
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> crate::error::Result<Vec<Vec<f32>>> {
+            self.embed_batch_async(texts).await
         }
 
         fn dimensions(&self) -> usize {
@@ -198,10 +250,33 @@ mod inner {
             &self.config.model
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_request_body_input_type_and_batch_size() {
+            let embedder = VoyageEmbedder::new(VoyageConfig {
+                max_batch_size: 0,
+                ..VoyageConfig::default()
+            });
+            assert_eq!(embedder.batch_size(), 1);
+
+            let body = embedder.request_body(&["how do I deploy?"], QUERY);
+            assert_eq!(body["input_type"], "query");
+            assert_eq!(body["input"][0], "how do I deploy?");
+            assert_eq!(body["model"], "voyage-2");
+
+            let body = embedder.request_body(&["a", "b"], DOCUMENT);
+            assert_eq!(body["input_type"], "document");
+            assert_eq!(body["input"].as_array().unwrap().len(), 2);
+        }
+    }
 }
 
 #[cfg(feature = "voyage")]
-pub use inner::{VoyageConfig, VoyageEmbedder};
+pub use inner::{VoyageConfig, VoyageEmbedder, VOYAGE_MAX_BATCH_SIZE};
 
 // ---------------------------------------------------------------------------
 // Tests
@@ -306,6 +381,7 @@ mod tests {
         assert_eq!(cfg.model, "voyage-2");
         assert_eq!(cfg.base_url, "https://api.voyageai.com/v1");
         assert_eq!(cfg.dimensions, 1024);
+        assert_eq!(cfg.max_batch_size, 128);
         assert!(cfg.api_key.is_empty(), "default api_key must be empty");
     }
 
@@ -318,6 +394,7 @@ mod tests {
             model: "voyage-large-2".to_string(),
             base_url: "https://api.voyageai.com/v1".to_string(),
             dimensions: 1536,
+            max_batch_size: 64,
         };
         assert_eq!(cfg.api_key, "pa-test-key");
         assert_eq!(cfg.model, "voyage-large-2");
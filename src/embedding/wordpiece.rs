@@ -0,0 +1,382 @@
+//! BERT-style WordPiece tokenizer
+//!
+//! Loads the vocabulary shipped with sentence-transformer models, either a
+//! HuggingFace `tokenizer.json` (WordPiece model) or a plain `vocab.txt`, so
+//! local ONNX embedders feed the model the token ids it was trained on.
+//!
+//! Pre-tokenization follows BERT's basic tokenizer: control characters are
+//! dropped, CJK ideographs become single-character words, text is optionally
+//! lowercased and accent-stripped, and words are split on whitespace and
+//! punctuation. Each word is then split greedily into the longest matching
+//! vocabulary pieces.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{EngramError, Result};
+
+const DEFAULT_CONTINUING_PREFIX: &str = "##";
+const DEFAULT_MAX_INPUT_CHARS_PER_WORD: usize = 100;
+
+/// WordPiece tokenizer with BERT pre-tokenization
+#[derive(Debug, Clone)]
+pub struct WordPieceTokenizer {
+    vocab: HashMap<String, i64>,
+    unk_token: String,
+    continuing_prefix: String,
+    max_input_chars_per_word: usize,
+    lowercase: bool,
+    strip_accents: bool,
+    cls_id: i64,
+    sep_id: i64,
+    unk_id: i64,
+}
+
+impl WordPieceTokenizer {
+    /// Build an uncased tokenizer from vocabulary tokens in id order
+    pub fn from_vocab<I, S>(tokens: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let vocab = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(id, token)| (token.into(), id as i64))
+            .collect();
+        Self::build(vocab, "[UNK]".to_string(), None, None, true, None)
+    }
+
+    /// Load a tokenizer from a `tokenizer.json` or `vocab.txt` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            EngramError::Embedding(format!(
+                "Failed to read tokenizer from {}: {e}",
+                path.display()
+            ))
+        })?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_tokenizer_json(&contents)
+        } else {
+            Self::from_vocab(contents.lines().map(str::trim_end))
+        }
+    }
+
+    /// Parse a HuggingFace `tokenizer.json` whose model is WordPiece
+    pub fn from_tokenizer_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| EngramError::Embedding(format!("Invalid tokenizer.json: {e}")))?;
+        let model = &value["model"];
+        if let Some(kind) = model["type"].as_str() {
+            if kind != "WordPiece" {
+                return Err(EngramError::Embedding(format!(
+                    "Unsupported tokenizer model '{kind}', expected WordPiece"
+                )));
+            }
+        }
+        let vocab: HashMap<String, i64> = model["vocab"]
+            .as_object()
+            .ok_or_else(|| EngramError::Embedding("tokenizer.json has no vocab".to_string()))?
+            .iter()
+            .filter_map(|(token, id)| id.as_i64().map(|id| (token.clone(), id)))
+            .collect();
+
+        let normalizer = &value["normalizer"];
+        let lowercase = normalizer["lowercase"].as_bool().unwrap_or(true);
+        Self::build(
+            vocab,
+            model["unk_token"].as_str().unwrap_or("[UNK]").to_string(),
+            model["continuing_subword_prefix"]
+                .as_str()
+                .map(str::to_string),
+            model["max_input_chars_per_word"]
+                .as_u64()
+                .map(|n| n as usize),
+            lowercase,
+            normalizer["strip_accents"].as_bool(),
+        )
+    }
+
+    fn build(
+        vocab: HashMap<String, i64>,
+        unk_token: String,
+        continuing_prefix: Option<String>,
+        max_input_chars_per_word: Option<usize>,
+        lowercase: bool,
+        strip_accents: Option<bool>,
+    ) -> Result<Self> {
+        let id = |token: &str| {
+            vocab.get(token).copied().ok_or_else(|| {
+                EngramError::Embedding(format!("Tokenizer vocabulary has no {token} token"))
+            })
+        };
+        let cls_id = id("[CLS]")?;
+        let sep_id = id("[SEP]")?;
+        let unk_id = id(&unk_token)?;
+
+        Ok(Self {
+            cls_id,
+            sep_id,
+            unk_id,
+            vocab,
+            unk_token,
+            continuing_prefix: continuing_prefix
+                .unwrap_or_else(|| DEFAULT_CONTINUING_PREFIX.to_string()),
+            max_input_chars_per_word: max_input_chars_per_word
+                .unwrap_or(DEFAULT_MAX_INPUT_CHARS_PER_WORD),
+            lowercase,
+            // BERT strips accents whenever it lowercases unless told otherwise
+            strip_accents: strip_accents.unwrap_or(lowercase),
+        })
+    }
+
+    /// Number of tokens in the vocabulary
+    pub fn vocab_size(&self) -> usize {
+        self.vocab.len()
+    }
+
+    /// Split text into vocabulary pieces, without special tokens
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        let mut pieces = Vec::new();
+        for word in self.pre_tokenize(text) {
+            self.push_word_pieces(&word, &mut pieces);
+        }
+        pieces
+    }
+
+    /// Encode text as `[CLS] pieces [SEP]`, truncated to `max_length` ids
+    ///
+    /// Returns `(input_ids, attention_mask)`; the sequence is not padded.
+    pub fn encode(&self, text: &str, max_length: usize) -> (Vec<i64>, Vec<i64>) {
+        let content_limit = max_length.saturating_sub(2);
+        let mut input_ids = Vec::with_capacity(content_limit.min(text.len()) + 2);
+        input_ids.push(self.cls_id);
+        input_ids.extend(
+            self.tokenize(text)
+                .iter()
+                .take(content_limit)
+                .map(|piece| self.vocab.get(piece).copied().unwrap_or(self.unk_id)),
+        );
+        input_ids.push(self.sep_id);
+        let attention_mask = vec![1; input_ids.len()];
+        (input_ids, attention_mask)
+    }
+
+    /// Encode a pair as `[CLS] first [SEP] second [SEP]`, as cross-encoders
+    /// read a query and a document, truncated to `max_length` ids
+    ///
+    /// The longer side loses pieces first. Returns `(input_ids,
+    /// token_type_ids)`: 0 for `[CLS]`, `first` and its `[SEP]`, 1 after.
+    pub fn encode_pair(
+        &self,
+        first: &str,
+        second: &str,
+        max_length: usize,
+    ) -> (Vec<i64>, Vec<i64>) {
+        let mut first = self.tokenize(first);
+        let mut second = self.tokenize(second);
+        let content_limit = max_length.saturating_sub(3);
+        while first.len() + second.len() > content_limit {
+            if first.len() > second.len() {
+                first.pop();
+            } else {
+                second.pop();
+            }
+        }
+
+        let id = |piece: &String| self.vocab.get(piece).copied().unwrap_or(self.unk_id);
+        let mut input_ids = Vec::with_capacity(first.len() + second.len() + 3);
+        input_ids.push(self.cls_id);
+        input_ids.extend(first.iter().map(id));
+        input_ids.push(self.sep_id);
+        let first_len = input_ids.len();
+        input_ids.extend(second.iter().map(id));
+        input_ids.push(self.sep_id);
+        let mut token_type_ids = vec![0; first_len];
+        token_type_ids.resize(input_ids.len(), 1);
+        (input_ids, token_type_ids)
+    }
+
+    /// BERT basic tokenization: clean, split CJK, normalize case and
+    /// accents, split on whitespace and punctuation
+    fn pre_tokenize(&self, text: &str) -> Vec<String> {
+        let mut cleaned = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c == '\0' || c == '\u{fffd}' || is_control(c) {
+                continue;
+            }
+            if is_cjk(c) {
+                cleaned.push(' ');
+                cleaned.push(c);
+                cleaned.push(' ');
+            } else if c.is_whitespace() {
+                cleaned.push(' ');
+            } else {
+                cleaned.push(c);
+            }
+        }
+
+        let mut words = Vec::new();
+        for token in cleaned.split_whitespace() {
+            let token = if self.lowercase {
+                token.to_lowercase()
+            } else {
+                token.to_string()
+            };
+            let token: String = if self.strip_accents {
+                token.nfd().filter(|c| !is_combining_mark(*c)).collect()
+            } else {
+                token
+            };
+
+            let mut current = String::new();
+            for c in token.chars() {
+                if is_punctuation(c) {
+                    if !current.is_empty() {
+                        words.push(std::mem::take(&mut current));
+                    }
+                    words.push(c.to_string());
+                } else {
+                    current.push(c);
+                }
+            }
+            if !current.is_empty() {
+                words.push(current);
+            }
+        }
+        words
+    }
+
+    /// Greedy longest-match-first split of one word into vocabulary pieces
+    fn push_word_pieces(&self, word: &str, pieces: &mut Vec<String>) {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() > self.max_input_chars_per_word {
+            pieces.push(self.unk_token.clone());
+            return;
+        }
+
+        let mut word_pieces = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let mut end = chars.len();
+            let mut matched = None;
+            while start < end {
+                let mut candidate: String = chars[start..end].iter().collect();
+                if start > 0 {
+                    candidate.insert_str(0, &self.continuing_prefix);
+                }
+                if self.vocab.contains_key(&candidate) {
+                    matched = Some(candidate);
+                    break;
+                }
+                end -= 1;
+            }
+            match matched {
+                Some(piece) => {
+                    word_pieces.push(piece);
+                    start = end;
+                }
+                None => {
+                    pieces.push(self.unk_token.clone());
+                    return;
+                }
+            }
+        }
+        pieces.extend(word_pieces);
+    }
+}
+
+fn is_control(c: char) -> bool {
+    !matches!(c, '\t' | '\n' | '\r') && c.is_control()
+}
+
+/// ASCII symbols count as punctuation, as in BERT; other non-alphanumeric
+/// characters approximate the Unicode `P*` categories
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || (!c.is_ascii() && !c.is_alphanumeric() && !is_combining_mark(c))
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x4E00..=0x9FFF
+            | 0x3400..=0x4DBF
+            | 0x20000..=0x2A6DF
+            | 0x2A700..=0x2B73F
+            | 0x2B740..=0x2B81F
+            | 0x2B820..=0x2CEAF
+            | 0xF900..=0xFAFF
+            | 0x2F800..=0x2FA1F
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenizer() -> WordPieceTokenizer {
+        WordPieceTokenizer::from_vocab([
+            "[PAD]", "[UNK]", "[CLS]", "[SEP]", "un", "##aff", "##able", "hello", "world", "!",
+            "cafe", "東",
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_splits_words_into_pieces() {
+        let t = tokenizer();
+        assert_eq!(t.tokenize("unaffable"), vec!["un", "##aff", "##able"]);
+        assert_eq!(
+            t.tokenize("Hello, World!"),
+            vec!["hello", "[UNK]", "world", "!"]
+        );
+        assert_eq!(t.tokenize("Café 東京"), vec!["cafe", "東", "[UNK]"]);
+        assert_eq!(t.tokenize("unknownword"), vec!["[UNK]"]);
+    }
+
+    #[test]
+    fn test_encode_adds_special_tokens_and_truncates() {
+        let t = tokenizer();
+        let (ids, mask) = t.encode("hello world", 16);
+        assert_eq!(ids, vec![2, 7, 8, 3]);
+        assert_eq!(mask, vec![1, 1, 1, 1]);
+
+        let (ids, _) = t.encode("hello world hello world", 4);
+        assert_eq!(ids, vec![2, 7, 8, 3]);
+    }
+
+    #[test]
+    fn test_encode_pair_truncates_longer_side() {
+        let t = tokenizer();
+        let (ids, types) = t.encode_pair("hello", "world !", 16);
+        assert_eq!(ids, vec![2, 7, 3, 8, 9, 3]);
+        assert_eq!(types, vec![0, 0, 0, 1, 1, 1]);
+
+        let (ids, types) = t.encode_pair("hello", "world world world world", 6);
+        assert_eq!(ids, vec![2, 7, 3, 8, 8, 3]);
+        assert_eq!(types, vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_reads_tokenizer_json() {
+        let json = r###"{
+            "normalizer": {"type": "BertNormalizer", "lowercase": false, "strip_accents": null},
+            "model": {
+                "type": "WordPiece",
+                "unk_token": "[UNK]",
+                "continuing_subword_prefix": "##",
+                "max_input_chars_per_word": 100,
+                "vocab": {"[UNK]": 0, "[CLS]": 1, "[SEP]": 2, "Hello": 3, "hello": 4}
+            }
+        }"###;
+        let t = WordPieceTokenizer::from_tokenizer_json(json).unwrap();
+        assert_eq!(t.vocab_size(), 5);
+        assert_eq!(t.encode("Hello hello", 8).0, vec![1, 3, 4, 2]);
+
+        let bpe = r#"{"model": {"type": "BPE", "vocab": {}}}"#;
+        assert!(WordPieceTokenizer::from_tokenizer_json(bpe).is_err());
+    }
+}
@@ -42,11 +42,25 @@ pub enum EngramError {
     Io(#[from] std::io::Error),
 
     #[error("HTTP request error: {0}")]
-    #[cfg(any(feature = "openai", feature = "multimodal"))]
+    #[cfg(any(
+        feature = "openai",
+        feature = "multimodal",
+        feature = "cohere",
+        feature = "voyage",
+        feature = "hf-inference",
+        feature = "api-rerank"
+    ))]
     Http(#[from] reqwest::Error),
 
     #[error("HTTP request error: {0}")]
-    #[cfg(not(any(feature = "openai", feature = "multimodal")))]
+    #[cfg(not(any(
+        feature = "openai",
+        feature = "multimodal",
+        feature = "cohere",
+        feature = "voyage",
+        feature = "hf-inference",
+        feature = "api-rerank"
+    )))]
     Http(String),
 
     #[error("Configuration error: {0}")]
@@ -67,6 +81,9 @@ pub enum EngramError {
     #[error("Rate limited: retry after {0} seconds")]
     RateLimited(u64),
 
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -93,6 +110,7 @@ impl EngramError {
             EngramError::RateLimited(_) => -32004,
             EngramError::Conflict(_) => -32005,
             EngramError::Duplicate { .. } => -32006,
+            EngramError::LimitExceeded(_) => -32007,
             _ => -32000,
         }
     }
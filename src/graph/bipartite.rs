@@ -0,0 +1,349 @@
+//! Entity and identity nodes alongside memories
+//!
+//! The memory graph only shows crossrefs, so two notes about the same person
+//! look unrelated until someone links them. [`KnowledgeGraph::add_entity_nodes`]
+//! turns the entities extracted from memories (`memory_entities`) and the
+//! identities linked to them (`memory_identity_links`) into nodes of their
+//! own, joined to each memory by its recorded relation (`mentions`, `about`,
+//! ...). An extracted entity whose name is an alias of a linked identity also
+//! gets a `same_as` edge to it.
+//!
+//! Entity and identity nodes are not memories, so they get negative ids; the
+//! [`EntityNodes`] returned alongside maps them back to what they stand for.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{GraphEdge, GraphNode, KnowledgeGraph};
+use crate::error::Result;
+use crate::storage::identity_links::get_memory_identities_with_mentions;
+use crate::storage::{get_entities_for_memory, resolve_alias};
+use crate::types::MemoryId;
+
+/// `memory_type` prefix of entity nodes, followed by the entity type
+pub const ENTITY_TYPE_PREFIX: &str = "entity:";
+
+/// `memory_type` prefix of identity nodes, followed by the identity type
+pub const IDENTITY_TYPE_PREFIX: &str = "identity:";
+
+/// Edge type joining an extracted entity to the identity it is an alias of
+pub const SAME_AS_EDGE: &str = "same_as";
+
+/// Which non-memory nodes [`KnowledgeGraph::add_entity_nodes`] adds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntityNodeOptions {
+    pub entities: bool,
+    pub identities: bool,
+    /// Leave out entities and identities linked to fewer memories of the graph
+    pub min_links: usize,
+}
+
+impl Default for EntityNodeOptions {
+    fn default() -> Self {
+        Self {
+            entities: true,
+            identities: true,
+            min_links: 1,
+        }
+    }
+}
+
+/// What a synthetic node stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EntityNodeRef {
+    /// Row of the `entities` table
+    Entity { entity_id: i64 },
+    /// Identity by canonical id
+    Identity { canonical_id: String },
+}
+
+/// Synthetic node ids added by [`KnowledgeGraph::add_entity_nodes`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityNodes {
+    pub nodes: BTreeMap<MemoryId, EntityNodeRef>,
+}
+
+impl EntityNodes {
+    /// What the node with this id stands for, if it is not a memory
+    pub fn get(&self, id: MemoryId) -> Option<&EntityNodeRef> {
+        self.nodes.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// One entity or identity and the memories linking to it
+struct Pending {
+    node: GraphNode,
+    reference: EntityNodeRef,
+    /// `(memory, relation, confidence)`
+    links: Vec<(MemoryId, String, f32)>,
+}
+
+impl KnowledgeGraph {
+    /// Add the entities and identities linked to this graph's memories as
+    /// nodes, with an edge from each memory to each of them
+    pub fn add_entity_nodes(
+        &mut self,
+        conn: &Connection,
+        options: &EntityNodeOptions,
+    ) -> Result<EntityNodes> {
+        let memory_ids: Vec<MemoryId> = {
+            let mut seen = HashSet::new();
+            self.nodes
+                .iter()
+                .map(|n| n.id)
+                .filter(|id| seen.insert(*id))
+                .collect()
+        };
+
+        let mut entities: BTreeMap<i64, Pending> = BTreeMap::new();
+        let mut identities: BTreeMap<String, Pending> = BTreeMap::new();
+        for &memory_id in &memory_ids {
+            if options.entities {
+                for (entity, relation, confidence) in get_entities_for_memory(conn, memory_id)? {
+                    entities
+                        .entry(entity.id)
+                        .or_insert_with(|| Pending {
+                            node: GraphNode {
+                                id: 0,
+                                label: entity.name.clone(),
+                                memory_type: format!(
+                                    "{}{}",
+                                    ENTITY_TYPE_PREFIX,
+                                    entity.entity_type.as_str()
+                                ),
+                                importance: 0.0,
+                                tags: entity.aliases.clone(),
+                            },
+                            reference: EntityNodeRef::Entity {
+                                entity_id: entity.id,
+                            },
+                            links: Vec::new(),
+                        })
+                        .links
+                        .push((memory_id, relation.as_str().to_string(), confidence));
+                }
+            }
+            if options.identities {
+                for linked in get_memory_identities_with_mentions(conn, memory_id)? {
+                    let identity = linked.identity;
+                    identities
+                        .entry(identity.canonical_id.clone())
+                        .or_insert_with(|| Pending {
+                            node: GraphNode {
+                                id: 0,
+                                label: identity.display_name.clone(),
+                                memory_type: format!(
+                                    "{}{}",
+                                    IDENTITY_TYPE_PREFIX,
+                                    identity.entity_type.as_str()
+                                ),
+                                importance: 0.0,
+                                tags: vec![],
+                            },
+                            reference: EntityNodeRef::Identity {
+                                canonical_id: identity.canonical_id.clone(),
+                            },
+                            links: Vec::new(),
+                        })
+                        .links
+                        .push((memory_id, "mentions".to_string(), 1.0));
+                }
+            }
+        }
+
+        let min_links = options.min_links.max(1);
+        let kept: Vec<Pending> = entities
+            .into_values()
+            .chain(identities.into_values())
+            .filter(|p| p.links.len() >= min_links)
+            .collect();
+        let most_links = kept.iter().map(|p| p.links.len()).max().unwrap_or(1);
+
+        // Synthetic ids count down from below every existing id
+        let mut next_id = self.nodes.iter().map(|n| n.id).min().unwrap_or(0).min(0) - 1;
+        let mut added = EntityNodes::default();
+        let mut identity_ids: HashMap<String, MemoryId> = HashMap::new();
+        let mut entity_names: Vec<(String, MemoryId)> = Vec::new();
+        for mut pending in kept {
+            let id = next_id;
+            next_id -= 1;
+            pending.node.id = id;
+            // Size by how many memories of the graph link to it
+            pending.node.importance = pending.links.len() as f32 / most_links as f32;
+            match &pending.reference {
+                EntityNodeRef::Entity { .. } => entity_names.push((pending.node.label.clone(), id)),
+                EntityNodeRef::Identity { canonical_id } => {
+                    identity_ids.insert(canonical_id.clone(), id);
+                }
+            }
+            for (memory_id, relation, confidence) in pending.links {
+                self.edges.push(GraphEdge {
+                    from: memory_id,
+                    to: id,
+                    edge_type: relation,
+                    score: 1.0,
+                    confidence,
+                });
+            }
+            self.nodes.push(pending.node);
+            added.nodes.insert(id, pending.reference);
+        }
+
+        // Extracted entities that are aliases of an identity in the graph
+        if !identity_ids.is_empty() {
+            for (name, id) in entity_names {
+                let Some(identity) = resolve_alias(conn, &name)? else {
+                    continue;
+                };
+                if let Some(&identity_id) = identity_ids.get(&identity.canonical_id) {
+                    self.edges.push(GraphEdge {
+                        from: id,
+                        to: identity_id,
+                        edge_type: SAME_AS_EDGE.to_string(),
+                        score: 1.0,
+                        confidence: 1.0,
+                    });
+                }
+            }
+        }
+
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::{EntityExtractionConfig, EntityExtractor};
+    use crate::storage::queries::create_memory;
+    use crate::storage::{
+        add_alias, create_identity, link_entity_to_memory, link_identity_to_memory, upsert_entity,
+        CreateIdentityInput, IdentityType, Storage,
+    };
+    use crate::types::CreateMemoryInput;
+
+    fn memory(conn: &Connection, content: &str) -> MemoryId {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_entities_and_identities_become_nodes() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let a = memory(conn, "Alice Johnson reviewed the release plan");
+                let b = memory(conn, "Alice Johnson owns the billing service");
+                let c = memory(conn, "Unrelated grocery list");
+
+                let extractor = EntityExtractor::new(EntityExtractionConfig::default());
+                let alice = extractor
+                    .extract("Alice Johnson")
+                    .entities
+                    .into_iter()
+                    .next()
+                    .unwrap();
+                let entity_id = upsert_entity(conn, &alice)?;
+                for id in [a, b] {
+                    link_entity_to_memory(
+                        conn,
+                        id,
+                        entity_id,
+                        crate::intelligence::EntityRelation::Mentions,
+                        0.8,
+                        None,
+                    )?;
+                }
+                create_identity(
+                    conn,
+                    &CreateIdentityInput {
+                        canonical_id: "user:alice".to_string(),
+                        display_name: "Alice".to_string(),
+                        entity_type: IdentityType::Person,
+                        description: None,
+                        metadata: Default::default(),
+                        aliases: vec![],
+                    },
+                )?;
+                add_alias(conn, "user:alice", "Alice Johnson", None)?;
+                link_identity_to_memory(conn, b, "user:alice", Some("Alice"))?;
+
+                let mut graph = KnowledgeGraph {
+                    nodes: [a, b, c]
+                        .into_iter()
+                        .map(|id| GraphNode {
+                            id,
+                            label: format!("Memory {}", id),
+                            memory_type: "note".to_string(),
+                            importance: 0.5,
+                            tags: vec![],
+                        })
+                        .collect(),
+                    edges: vec![],
+                };
+                let added = graph.add_entity_nodes(conn, &EntityNodeOptions::default())?;
+
+                assert_eq!(added.len(), 2);
+                assert_eq!(graph.nodes.len(), 5);
+                let entity_node = graph
+                    .nodes
+                    .iter()
+                    .find(|n| n.memory_type == "entity:person")
+                    .unwrap();
+                assert!(entity_node.id < 0);
+                assert_eq!(
+                    added.get(entity_node.id),
+                    Some(&EntityNodeRef::Entity { entity_id })
+                );
+                assert_eq!(entity_node.importance, 1.0);
+                let identity_node = graph
+                    .nodes
+                    .iter()
+                    .find(|n| n.memory_type == "identity:person")
+                    .unwrap();
+
+                let edges_to = |id: MemoryId| graph.edges.iter().filter(move |e| e.to == id);
+                assert_eq!(edges_to(entity_node.id).count(), 2);
+                assert!(edges_to(entity_node.id).all(|e| e.edge_type == "mentions"));
+                assert_eq!(
+                    edges_to(identity_node.id)
+                        .map(|e| (e.from, e.edge_type.as_str()))
+                        .collect::<Vec<_>>(),
+                    vec![(b, "mentions"), (entity_node.id, SAME_AS_EDGE)]
+                );
+
+                // Entities linked to a single memory can be left out
+                let mut sparse = graph.clone();
+                sparse.nodes.truncate(3);
+                sparse.edges.clear();
+                let added = sparse.add_entity_nodes(
+                    conn,
+                    &EntityNodeOptions {
+                        min_links: 2,
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(added.len(), 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
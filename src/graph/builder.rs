@@ -0,0 +1,879 @@
+//! Incremental knowledge graph builder
+//!
+//! [`KnowledgeGraph::from_data`] rebuilds the whole graph, one `get_related`
+//! query per memory, on every export. [`GraphBuilder`] loads the graph once
+//! and then keeps its adjacency current by applying deltas as memories and
+//! cross-references change, either directly via [`GraphBuilder::apply`] or
+//! from the realtime event stream via [`GraphBuilder::apply_event`] and
+//! [`follow_events`].
+//!
+//! Edges are only kept while both endpoints are in the graph; a memory that
+//! joins later picks up its edges from storage when it is added.
+//!
+//! With the mutation log enabled the builder also records each effective
+//! change as a [`GraphMutation`], and [`publish_mutations`] streams them on
+//! the realtime graph channel so views can update without re-exporting.
+//! Community changes are batched: Louvain reruns at most once per
+//! [`RECLUSTER_DELAY`] however many mutations arrive in between.
+
+use parking_lot::RwLock;
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::Instant;
+
+use super::{CommunityAlgorithm, GraphEdge, GraphNode, KnowledgeGraph, LabelOptions};
+use crate::error::{EngramError, Result};
+use crate::realtime::{EventType, GraphMutation, RealtimeEvent, RealtimeManager};
+use crate::storage::queries::{get_related, list_memories, peek_memory};
+use crate::storage::Storage;
+use crate::types::{CrossReference, ListOptions, Memory, MemoryId};
+
+/// `(from, to, edge_type)` — a cross-reference's identity
+type EdgeKey = (MemoryId, MemoryId, String);
+
+/// How long [`publish_mutations`] collects graph changes before recomputing
+/// communities, so a burst of writes costs one Louvain run instead of one
+/// per mutation.
+pub const RECLUSTER_DELAY: Duration = Duration::from_secs(2);
+
+/// A single change to the graph
+#[derive(Debug, Clone)]
+pub enum GraphDelta {
+    /// Insert a node or replace the existing one with the same id
+    UpsertNode(GraphNode),
+    /// Remove a node and every edge touching it
+    RemoveNode(MemoryId),
+    /// Insert an edge or replace the existing one with the same key
+    UpsertEdge(GraphEdge),
+    /// Remove one edge
+    RemoveEdge {
+        from: MemoryId,
+        to: MemoryId,
+        edge_type: String,
+    },
+}
+
+/// Knowledge graph kept up to date by deltas
+#[derive(Debug, Clone)]
+pub struct GraphBuilder {
+    labels: LabelOptions,
+    /// Memories loaded on a full (re)load
+    max_nodes: i64,
+    nodes: BTreeMap<MemoryId, GraphNode>,
+    edges: HashMap<EdgeKey, GraphEdge>,
+    /// Keys of the edges touching each node
+    incident: HashMap<MemoryId, HashSet<EdgeKey>>,
+    /// Sequence id of the last realtime event applied
+    last_seq: Option<u64>,
+    /// Community of each node as of the last [`GraphBuilder::recluster`]
+    clusters: HashMap<MemoryId, MemoryId>,
+    /// Whether changes are recorded in `mutations`
+    log_mutations: bool,
+    mutations: Vec<GraphMutation>,
+}
+
+impl GraphBuilder {
+    /// Load the `max_nodes` most recent memories and their cross-references
+    pub fn load(conn: &Connection, max_nodes: i64, labels: LabelOptions) -> Result<Self> {
+        let mut builder = Self::from_data(&[], &[], &labels);
+        builder.max_nodes = max_nodes;
+        builder.reload(conn)?;
+        Ok(builder)
+    }
+
+    /// Build from already-fetched data, like [`KnowledgeGraph::from_data_with_labels`]
+    pub fn from_data(
+        memories: &[Memory],
+        crossrefs: &[CrossReference],
+        labels: &LabelOptions,
+    ) -> Self {
+        let mut builder = Self {
+            labels: *labels,
+            max_nodes: memories.len() as i64,
+            nodes: BTreeMap::new(),
+            edges: HashMap::new(),
+            incident: HashMap::new(),
+            last_seq: None,
+            clusters: HashMap::new(),
+            log_mutations: false,
+            mutations: Vec::new(),
+        };
+        for memory in memories {
+            builder.upsert_memory(memory);
+        }
+        for crossref in crossrefs {
+            builder.upsert_crossref(crossref);
+        }
+        builder
+    }
+
+    /// Discard everything and load afresh from storage
+    pub fn reload(&mut self, conn: &Connection) -> Result<()> {
+        let options = ListOptions {
+            limit: Some(self.max_nodes),
+            ..Default::default()
+        };
+        let memories = list_memories(conn, &options)?;
+        let mut crossrefs = Vec::new();
+        for memory in &memories {
+            crossrefs.extend(get_related(conn, memory.id)?);
+        }
+
+        // Everything may have changed: replace pending mutations with a
+        // single reset and forget cluster assignments so that the next
+        // recluster reports every node.
+        let log_mutations = std::mem::replace(&mut self.log_mutations, false);
+        self.nodes.clear();
+        self.edges.clear();
+        self.incident.clear();
+        self.clusters.clear();
+        for memory in &memories {
+            self.upsert_memory(memory);
+        }
+        for crossref in &crossrefs {
+            self.upsert_crossref(crossref);
+        }
+        self.log_mutations = log_mutations;
+        self.mutations.clear();
+        self.record(GraphMutation::Reset);
+        Ok(())
+    }
+
+    /// Start or stop recording changes for [`GraphBuilder::take_mutations`]
+    pub fn log_mutations(&mut self, enabled: bool) {
+        self.log_mutations = enabled;
+        if !enabled {
+            self.mutations.clear();
+        }
+    }
+
+    /// Changes recorded since the last call, oldest first
+    pub fn take_mutations(&mut self) -> Vec<GraphMutation> {
+        std::mem::take(&mut self.mutations)
+    }
+
+    /// Recompute Louvain communities, recording a `ClusterChanged` for every
+    /// node whose community differs from the previous run.
+    ///
+    /// Communities are labelled by their smallest member id so that labels
+    /// stay put while the community itself does.
+    pub fn recluster(&mut self) {
+        let communities = self.snapshot().communities(CommunityAlgorithm::default());
+        for cluster in communities {
+            let Some(&label) = cluster.members.iter().min() else {
+                continue;
+            };
+            for id in cluster.members {
+                if self.clusters.insert(id, label) != Some(label) {
+                    self.record(GraphMutation::ClusterChanged { id, cluster: label });
+                }
+            }
+        }
+    }
+
+    /// Community of a node as of the last [`GraphBuilder::recluster`]
+    pub fn cluster_of(&self, id: MemoryId) -> Option<MemoryId> {
+        self.clusters.get(&id).copied()
+    }
+
+    fn record(&mut self, mutation: GraphMutation) {
+        if self.log_mutations {
+            self.mutations.push(mutation);
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn contains(&self, id: MemoryId) -> bool {
+        self.nodes.contains_key(&id)
+    }
+
+    /// Distinct neighbours of a node, in either direction, sorted by id
+    pub fn neighbors(&self, id: MemoryId) -> Vec<MemoryId> {
+        let mut neighbors: Vec<MemoryId> = self
+            .incident
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|(from, to, _)| if *from == id { *to } else { *from })
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// Sequence id of the last realtime event applied, if any
+    pub fn last_seq(&self) -> Option<u64> {
+        self.last_seq
+    }
+
+    /// Current graph, nodes ordered by id and edges by key
+    pub fn snapshot(&self) -> KnowledgeGraph {
+        let mut keys: Vec<&EdgeKey> = self.edges.keys().collect();
+        keys.sort_unstable();
+        KnowledgeGraph {
+            nodes: self.nodes.values().cloned().collect(),
+            edges: keys.into_iter().map(|k| self.edges[k].clone()).collect(),
+        }
+    }
+
+    /// Apply one delta, returning whether the graph changed
+    pub fn apply(&mut self, delta: GraphDelta) -> bool {
+        match delta {
+            GraphDelta::UpsertNode(node) => {
+                self.incident.entry(node.id).or_default();
+                match self.nodes.insert(node.id, node.clone()) {
+                    None => self.record(GraphMutation::NodeAdded { node }),
+                    Some(previous) if previous != node => {
+                        self.record(GraphMutation::NodeUpdated { node })
+                    }
+                    Some(_) => {}
+                }
+                true
+            }
+            GraphDelta::RemoveNode(id) => {
+                if self.nodes.remove(&id).is_none() {
+                    return false;
+                }
+                for key in self.incident.remove(&id).unwrap_or_default() {
+                    self.unlink(&key);
+                }
+                self.clusters.remove(&id);
+                self.record(GraphMutation::NodeRemoved { id });
+                true
+            }
+            GraphDelta::UpsertEdge(edge) => {
+                if !self.contains(edge.from) || !self.contains(edge.to) {
+                    return false;
+                }
+                let key = (edge.from, edge.to, edge.edge_type.clone());
+                for end in [edge.from, edge.to] {
+                    self.incident.entry(end).or_default().insert(key.clone());
+                }
+                if self.edges.insert(key, edge.clone()).as_ref() != Some(&edge) {
+                    self.record(GraphMutation::EdgeAdded { edge });
+                }
+                true
+            }
+            GraphDelta::RemoveEdge {
+                from,
+                to,
+                edge_type,
+            } => self.unlink(&(from, to, edge_type)),
+        }
+    }
+
+    /// Insert or refresh the node for `memory`
+    pub fn upsert_memory(&mut self, memory: &Memory) -> bool {
+        self.apply(GraphDelta::UpsertNode(GraphNode {
+            id: memory.id,
+            label: self.labels.label_for(memory),
+            memory_type: memory.memory_type.as_str().to_string(),
+            importance: memory.importance,
+            tags: memory.tags.clone(),
+        }))
+    }
+
+    pub fn remove_memory(&mut self, id: MemoryId) -> bool {
+        self.apply(GraphDelta::RemoveNode(id))
+    }
+
+    /// Insert or refresh an edge; ignored unless both endpoints are present
+    pub fn upsert_crossref(&mut self, crossref: &CrossReference) -> bool {
+        self.apply(GraphDelta::UpsertEdge(GraphEdge {
+            from: crossref.from_id,
+            to: crossref.to_id,
+            edge_type: crossref.edge_type.as_str().to_string(),
+            score: crossref.score,
+            confidence: crossref.confidence,
+        }))
+    }
+
+    pub fn remove_crossref(&mut self, from: MemoryId, to: MemoryId, edge_type: &str) -> bool {
+        self.apply(GraphDelta::RemoveEdge {
+            from,
+            to,
+            edge_type: edge_type.to_string(),
+        })
+    }
+
+    /// Bring the graph up to date with one realtime event.
+    ///
+    /// Memory events re-read the memory and its cross-references, so updates
+    /// that arrive out of order still converge on the stored state. Sync
+    /// completion can touch anything and triggers a full reload. Returns
+    /// whether the graph changed.
+    pub fn apply_event(&mut self, conn: &Connection, event: &RealtimeEvent) -> Result<bool> {
+        if let Some(seq) = event.seq_id {
+            self.last_seq = Some(self.last_seq.map_or(seq, |last| last.max(seq)));
+        }
+
+        if event.is_bulk() {
+            let mut changed = false;
+            for single in event.expand() {
+                changed |= self.apply_event(conn, &single)?;
+            }
+            return Ok(changed);
+        }
+
+        match event.event_type {
+            EventType::MemoryCreated | EventType::MemoryUpdated => {
+                let Some(id) = event.memory_id else {
+                    return Ok(false);
+                };
+                match peek_memory(conn, id) {
+                    Ok(memory) => {
+                        self.upsert_memory(&memory);
+                        self.refresh_edges(conn, id)?;
+                        Ok(true)
+                    }
+                    Err(EngramError::NotFound(_)) => Ok(self.remove_memory(id)),
+                    Err(e) => Err(e),
+                }
+            }
+            EventType::MemoryDeleted => Ok(event
+                .memory_id
+                .map(|id| self.remove_memory(id))
+                .unwrap_or(false)),
+            EventType::CrossrefCreated | EventType::CrossrefDeleted => {
+                let endpoint = |key: &str| {
+                    event
+                        .data
+                        .as_ref()
+                        .and_then(|d| d.get(key))
+                        .and_then(|v| v.as_i64())
+                };
+                let (Some(from), Some(to)) = (endpoint("from_id"), endpoint("to_id")) else {
+                    // Coalesced crossref events only name the source memory
+                    return match event.memory_id {
+                        Some(id) if self.contains(id) => {
+                            self.refresh_edges(conn, id)?;
+                            Ok(true)
+                        }
+                        _ => Ok(false),
+                    };
+                };
+                // Edges only exist between loaded nodes, so one end suffices.
+                if !self.contains(from) || !self.contains(to) {
+                    return Ok(false);
+                }
+                self.refresh_edges(conn, from)?;
+                Ok(true)
+            }
+            EventType::SyncCompleted => {
+                self.reload(conn)?;
+                Ok(true)
+            }
+            EventType::SyncStarted | EventType::SyncFailed | EventType::MemorySuggested => {
+                Ok(false)
+            }
+        }
+    }
+
+    /// Replace the edges touching `id` with the active ones in storage
+    fn refresh_edges(&mut self, conn: &Connection, id: MemoryId) -> Result<()> {
+        let related = get_related(conn, id)?;
+        let current: HashSet<EdgeKey> = related
+            .iter()
+            .map(|c| (c.from_id, c.to_id, c.edge_type.as_str().to_string()))
+            .collect();
+        for key in self.incident.get(&id).cloned().unwrap_or_default() {
+            if !current.contains(&key) {
+                self.unlink(&key);
+            }
+        }
+        for crossref in &related {
+            self.upsert_crossref(crossref);
+        }
+        Ok(())
+    }
+
+    fn unlink(&mut self, key: &EdgeKey) -> bool {
+        if self.edges.remove(key).is_none() {
+            return false;
+        }
+        for end in [key.0, key.1] {
+            if let Some(keys) = self.incident.get_mut(&end) {
+                keys.remove(key);
+            }
+        }
+        self.record(GraphMutation::EdgeRemoved {
+            from: key.0,
+            to: key.1,
+            edge_type: key.2.clone(),
+        });
+        true
+    }
+}
+
+/// Keep `builder` current until the event channel closes.
+///
+/// If the receiver falls behind and events are dropped, the graph is
+/// reloaded from storage instead of guessing what was missed.
+pub async fn follow_events(
+    builder: Arc<RwLock<GraphBuilder>>,
+    storage: Storage,
+    events: broadcast::Receiver<RealtimeEvent>,
+) {
+    follow(builder, storage, events, None).await
+}
+
+/// Keep `builder` current from `manager`'s events and broadcast the
+/// resulting graph mutations on its graph channel.
+///
+/// Node and edge changes go out as they are applied; community changes
+/// follow [`RECLUSTER_DELAY`] after the first change of a batch, covering
+/// every change made in the meantime.
+pub async fn publish_mutations(
+    builder: Arc<RwLock<GraphBuilder>>,
+    storage: Storage,
+    manager: RealtimeManager,
+) {
+    {
+        let mut builder = builder.write();
+        builder.log_mutations(true);
+        builder.recluster();
+        builder.take_mutations();
+    }
+    let events = manager.subscribe();
+    follow(builder, storage, events, Some((&manager, RECLUSTER_DELAY))).await
+}
+
+async fn follow(
+    builder: Arc<RwLock<GraphBuilder>>,
+    storage: Storage,
+    mut events: broadcast::Receiver<RealtimeEvent>,
+    publish: Option<(&RealtimeManager, Duration)>,
+) {
+    // Set while the graph has changed since communities were last computed
+    let mut recluster_at: Option<Instant> = None;
+    loop {
+        let received = match recluster_at {
+            Some(deadline) => tokio::select! {
+                received = events.recv() => Some(received),
+                _ = tokio::time::sleep_until(deadline) => None,
+            },
+            None => Some(events.recv().await),
+        };
+        let result = match received {
+            Some(Ok(event)) => {
+                storage.with_connection(|conn| builder.write().apply_event(conn, &event))
+            }
+            Some(Err(RecvError::Lagged(skipped))) => {
+                tracing::warn!("Graph builder missed {} events, reloading", skipped);
+                storage
+                    .with_connection(|conn| builder.write().reload(conn))
+                    .map(|_| true)
+            }
+            Some(Err(RecvError::Closed)) => break,
+            None => {
+                recluster_at = None;
+                if let Some((manager, _)) = publish {
+                    publish_clusters(&builder, manager);
+                }
+                continue;
+            }
+        };
+        match result {
+            Ok(changed) => {
+                if let Some((manager, delay)) = publish {
+                    if changed && recluster_at.is_none() {
+                        recluster_at = Some(Instant::now() + delay);
+                    }
+                    let mutations = builder.write().take_mutations();
+                    for mutation in mutations {
+                        manager.broadcast_graph(mutation);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Graph builder failed to apply event: {}", e),
+        }
+    }
+    if let (Some((manager, _)), Some(_)) = (publish, recluster_at) {
+        publish_clusters(&builder, manager);
+    }
+}
+
+/// Recompute communities and broadcast the nodes that moved.
+fn publish_clusters(builder: &RwLock<GraphBuilder>, manager: &RealtimeManager) {
+    let mutations = {
+        let mut builder = builder.write();
+        builder.recluster();
+        builder.take_mutations()
+    };
+    for mutation in mutations {
+        manager.broadcast_graph(mutation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::GraphEvent;
+    use crate::storage::queries::{create_crossref, create_memory, delete_crossref, delete_memory};
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+
+    fn memory(storage: &Storage, content: &str) -> MemoryId {
+        storage
+            .with_transaction(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap()
+            .id
+    }
+
+    fn link(storage: &Storage, from: MemoryId, to: MemoryId) {
+        storage
+            .with_transaction(|conn| {
+                create_crossref(
+                    conn,
+                    &CreateCrossRefInput {
+                        from_id: from,
+                        to_id: to,
+                        edge_type: EdgeType::RelatedTo,
+                        strength: None,
+                        source_context: None,
+                        pinned: false,
+                    },
+                )
+            })
+            .unwrap();
+    }
+
+    /// What a full rebuild would produce right now
+    fn rebuilt(storage: &Storage) -> KnowledgeGraph {
+        storage
+            .with_connection(|conn| {
+                Ok(GraphBuilder::load(conn, 100, LabelOptions::default())?.snapshot())
+            })
+            .unwrap()
+    }
+
+    fn assert_same(a: &KnowledgeGraph, b: &KnowledgeGraph) {
+        let nodes = |g: &KnowledgeGraph| {
+            g.nodes
+                .iter()
+                .map(|n| (n.id, n.label.clone()))
+                .collect::<Vec<_>>()
+        };
+        let edges = |g: &KnowledgeGraph| {
+            g.edges
+                .iter()
+                .map(|e| (e.from, e.to, e.edge_type.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(nodes(a), nodes(b));
+        assert_eq!(edges(a), edges(b));
+    }
+
+    #[test]
+    fn test_events_match_full_rebuild() {
+        let storage = Storage::open_in_memory().unwrap();
+        let a = memory(&storage, "alpha");
+        let b = memory(&storage, "beta");
+        link(&storage, a, b);
+
+        let mut builder = storage
+            .with_connection(|conn| GraphBuilder::load(conn, 100, LabelOptions::default()))
+            .unwrap();
+        assert_eq!((builder.node_count(), builder.edge_count()), (2, 1));
+
+        let apply = |builder: &mut GraphBuilder, event: RealtimeEvent| {
+            storage
+                .with_connection(|conn| builder.apply_event(conn, &event))
+                .unwrap()
+        };
+
+        let c = memory(&storage, "gamma");
+        link(&storage, c, a);
+        assert!(apply(
+            &mut builder,
+            RealtimeEvent::memory_created(c, "gamma".into())
+        ));
+        assert_eq!(builder.neighbors(a), vec![b, c]);
+
+        link(&storage, b, c);
+        apply(
+            &mut builder,
+            RealtimeEvent::crossref_created(b, c, "related_to"),
+        );
+        assert_same(&builder.snapshot(), &rebuilt(&storage));
+
+        storage
+            .with_transaction(|conn| delete_crossref(conn, a, b, EdgeType::RelatedTo))
+            .unwrap();
+        apply(
+            &mut builder,
+            RealtimeEvent::crossref_deleted(a, b, "related_to"),
+        );
+        assert_eq!(builder.neighbors(a), vec![c]);
+
+        storage
+            .with_transaction(|conn| delete_memory(conn, c))
+            .unwrap();
+        assert!(apply(&mut builder, RealtimeEvent::memory_deleted(c)));
+        assert_eq!(builder.edge_count(), 0);
+        assert_same(&builder.snapshot(), &rebuilt(&storage));
+    }
+
+    #[test]
+    fn test_bulk_events_apply_per_memory() {
+        let storage = Storage::open_in_memory().unwrap();
+        let a = memory(&storage, "alpha");
+        let mut builder = storage
+            .with_connection(|conn| GraphBuilder::load(conn, 100, LabelOptions::default()))
+            .unwrap();
+
+        let b = memory(&storage, "beta");
+        let c = memory(&storage, "gamma");
+        link(&storage, b, a);
+        let bulk = RealtimeEvent::bulk(EventType::MemoryCreated, None, vec![b, c]);
+        assert!(storage
+            .with_connection(|conn| builder.apply_event(conn, &bulk))
+            .unwrap());
+        assert_same(&builder.snapshot(), &rebuilt(&storage));
+    }
+
+    fn test_node(id: MemoryId) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("n{id}"),
+            memory_type: "note".into(),
+            importance: 0.5,
+            tags: vec![],
+        }
+    }
+
+    fn test_edge(from: MemoryId, to: MemoryId) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: "related_to".into(),
+            score: 1.0,
+            confidence: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_deltas_keep_edges_between_present_nodes() {
+        let (node, edge) = (test_node, test_edge);
+
+        let mut builder = GraphBuilder::from_data(&[], &[], &LabelOptions::default());
+        builder.apply(GraphDelta::UpsertNode(node(1)));
+        assert!(!builder.apply(GraphDelta::UpsertEdge(edge(1, 2))));
+        builder.apply(GraphDelta::UpsertNode(node(2)));
+        assert!(builder.apply(GraphDelta::UpsertEdge(edge(1, 2))));
+        // Re-upserting an edge replaces it rather than duplicating it.
+        assert!(builder.apply(GraphDelta::UpsertEdge(edge(1, 2))));
+        assert_eq!(builder.edge_count(), 1);
+
+        assert!(builder.remove_memory(2));
+        assert_eq!(builder.edge_count(), 0);
+        assert!(builder.neighbors(1).is_empty());
+        assert!(!builder.remove_crossref(1, 2, "related_to"));
+    }
+
+    #[test]
+    fn test_mutation_log_records_effective_changes() {
+        let mut builder = GraphBuilder::from_data(&[], &[], &LabelOptions::default());
+        builder.apply(GraphDelta::UpsertNode(test_node(1)));
+        builder.log_mutations(true);
+
+        // Re-upserting an identical node is not a change.
+        builder.apply(GraphDelta::UpsertNode(test_node(1)));
+        builder.apply(GraphDelta::UpsertNode(test_node(2)));
+        builder.apply(GraphDelta::UpsertEdge(test_edge(1, 2)));
+        builder.apply(GraphDelta::UpsertEdge(test_edge(1, 2)));
+        builder.remove_memory(2);
+        assert_eq!(
+            builder.take_mutations(),
+            vec![
+                GraphMutation::NodeAdded { node: test_node(2) },
+                GraphMutation::EdgeAdded {
+                    edge: test_edge(1, 2)
+                },
+                GraphMutation::EdgeRemoved {
+                    from: 1,
+                    to: 2,
+                    edge_type: "related_to".into()
+                },
+                GraphMutation::NodeRemoved { id: 2 },
+            ]
+        );
+        assert!(builder.take_mutations().is_empty());
+    }
+
+    #[test]
+    fn test_recluster_reports_moved_nodes() {
+        let mut builder = GraphBuilder::from_data(&[], &[], &LabelOptions::default());
+        builder.log_mutations(true);
+        for id in 1..=4 {
+            builder.apply(GraphDelta::UpsertNode(test_node(id)));
+        }
+        builder.apply(GraphDelta::UpsertEdge(test_edge(1, 2)));
+        builder.apply(GraphDelta::UpsertEdge(test_edge(3, 4)));
+        builder.recluster();
+        builder.take_mutations();
+        assert_eq!(builder.cluster_of(2), Some(1));
+        assert_eq!(builder.cluster_of(4), Some(3));
+
+        // Only the node that moved is reported.
+        builder.remove_crossref(3, 4, "related_to");
+        builder.recluster();
+        let changes: Vec<_> = builder
+            .take_mutations()
+            .into_iter()
+            .filter(|m| matches!(m, GraphMutation::ClusterChanged { .. }))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![GraphMutation::ClusterChanged { id: 4, cluster: 4 }]
+        );
+    }
+
+    fn drain(graph: &mut broadcast::Receiver<GraphEvent>) -> Vec<GraphMutation> {
+        let mut received = Vec::new();
+        while let Ok(event) = graph.try_recv() {
+            received.push(event.mutation);
+        }
+        received
+    }
+
+    /// `(id, cluster)` of every `ClusterChanged`, sorted by id
+    fn clusters_of(mutations: &[GraphMutation]) -> Vec<(MemoryId, MemoryId)> {
+        let mut clusters: Vec<_> = mutations
+            .iter()
+            .filter_map(|m| match m {
+                GraphMutation::ClusterChanged { id, cluster } => Some((*id, *cluster)),
+                _ => None,
+            })
+            .collect();
+        clusters.sort_unstable();
+        clusters
+    }
+
+    #[tokio::test]
+    async fn test_follow_publishes_mutations() {
+        let storage = Storage::open_in_memory().unwrap();
+        let a = memory(&storage, "alpha");
+        let builder = Arc::new(RwLock::new(
+            storage
+                .with_connection(|conn| GraphBuilder::load(conn, 100, LabelOptions::default()))
+                .unwrap(),
+        ));
+        {
+            let mut builder = builder.write();
+            builder.log_mutations(true);
+            builder.recluster();
+            builder.take_mutations();
+        }
+
+        let manager = RealtimeManager::new();
+        let mut graph = manager.subscribe_graph();
+        let (tx, rx) = broadcast::channel(16);
+
+        let b = memory(&storage, "beta");
+        link(&storage, a, b);
+        tx.send(RealtimeEvent::memory_created(b, "beta".into()))
+            .unwrap();
+        tx.send(RealtimeEvent::sync_completed("pull", 1)).unwrap();
+        drop(tx);
+        follow(builder, storage, rx, Some((&manager, RECLUSTER_DELAY))).await;
+
+        let received = drain(&mut graph);
+        assert!(matches!(&received[0], GraphMutation::NodeAdded { node } if node.id == b));
+        assert!(matches!(&received[1], GraphMutation::EdgeAdded { edge } if edge.to == b));
+        // A sync reloads the graph: clients are told to start over and then
+        // get every node's community afresh, flushed as the stream closes.
+        assert_eq!(received[2], GraphMutation::Reset);
+        assert_eq!(received.len(), 5);
+        assert_eq!(clusters_of(&received[3..]), vec![(a, a), (b, a)]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_batches_reclustering() {
+        let storage = Storage::open_in_memory().unwrap();
+        let a = memory(&storage, "alpha");
+        let builder = Arc::new(RwLock::new(
+            storage
+                .with_connection(|conn| GraphBuilder::load(conn, 100, LabelOptions::default()))
+                .unwrap(),
+        ));
+        {
+            let mut builder = builder.write();
+            builder.log_mutations(true);
+            builder.recluster();
+            builder.take_mutations();
+        }
+
+        let manager = RealtimeManager::new();
+        let mut graph = manager.subscribe_graph();
+        let (tx, rx) = broadcast::channel(16);
+        let delay = Duration::from_millis(200);
+
+        let writer = async {
+            let mut ids = Vec::new();
+            for content in ["beta", "gamma", "delta"] {
+                let id = memory(&storage, content);
+                link(&storage, a, id);
+                tx.send(RealtimeEvent::memory_created(id, content.into()))
+                    .unwrap();
+                ids.push(id);
+            }
+            tokio::time::sleep(delay / 4).await;
+            let early = drain(&mut graph);
+            assert_eq!(early.len(), 6, "{:?}", early);
+            assert!(early
+                .iter()
+                .all(|m| !matches!(m, GraphMutation::ClusterChanged { .. })));
+
+            tokio::time::sleep(delay * 2).await;
+            let clusters = clusters_of(&drain(&mut graph));
+            assert_eq!(clusters, ids.iter().map(|&id| (id, a)).collect::<Vec<_>>());
+            drop(tx);
+        };
+        let follower = follow(builder, storage.clone(), rx, Some((&manager, delay)));
+        tokio::join!(follower, writer);
+    }
+
+    #[tokio::test]
+    async fn test_follow_events_applies_stream() {
+        let storage = Storage::open_in_memory().unwrap();
+        let a = memory(&storage, "alpha");
+        let builder = Arc::new(RwLock::new(
+            storage
+                .with_connection(|conn| GraphBuilder::load(conn, 100, LabelOptions::default()))
+                .unwrap(),
+        ));
+
+        let (tx, rx) = broadcast::channel(16);
+        let follower = tokio::spawn(follow_events(builder.clone(), storage.clone(), rx));
+
+        let b = memory(&storage, "beta");
+        link(&storage, a, b);
+        let mut created = RealtimeEvent::memory_created(b, "beta".into());
+        created.seq_id = Some(7);
+        tx.send(created).unwrap();
+        drop(tx);
+        follower.await.unwrap();
+
+        let builder = builder.read();
+        assert_eq!(builder.neighbors(a), vec![b]);
+        assert_eq!(builder.last_seq(), Some(7));
+    }
+}
@@ -0,0 +1,931 @@
+//! Compact, index-based view of a [`KnowledgeGraph`]
+//!
+//! `KnowledgeGraph` keeps its nodes and edges as plain serializable vectors,
+//! which is what the exporters and the MCP layer want. Graph algorithms want
+//! something else: dense `u32` node indices and a contiguous adjacency list.
+//! [`CompactGraph`] is built once from a borrowed graph — no node or edge is
+//! cloned — and stores the undirected adjacency in CSR form (an `offsets`
+//! array into flat `targets`/`weights` arrays), so the traversals below touch
+//! only flat vectors instead of rebuilding `HashMap` adjacency per call.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::louvain::{louvain, WeightedCsr};
+use super::{
+    CentralityScores, CommunityAlgorithm, GraphCluster, GraphEdge, GraphNode, GraphStats, HubNode,
+    KnowledgeGraph,
+};
+use crate::types::MemoryId;
+
+/// Number of hub nodes reported by [`CompactGraph::stats`]
+const HUB_NODE_COUNT: usize = 10;
+
+/// PageRank damping used by [`CompactGraph::stats`]
+pub const DEFAULT_PAGERANK_DAMPING: f64 = 0.85;
+
+/// PageRank iteration cap used by [`CompactGraph::stats`]
+pub const DEFAULT_PAGERANK_ITERATIONS: usize = 100;
+
+/// Power-iteration steps for the spectral centralities
+const SPECTRAL_MAX_ITERATIONS: usize = 200;
+
+/// Convergence threshold (L1 change between iterations)
+const SPECTRAL_TOLERANCE: f64 = 1e-9;
+
+/// Katz attenuation as a fraction of `1 / λ_max`
+const KATZ_ALPHA_RATIO: f64 = 0.85;
+
+/// Arena/CSR representation of a [`KnowledgeGraph`].
+///
+/// Node `i` is `graph.nodes[i]`; if an id appears more than once, the first
+/// occurrence owns the edges. The adjacency is undirected with parallel edges
+/// merged (their `score * confidence` weights summed) and self-loops kept out
+/// of it; per-node in/out degrees still count every incident edge.
+#[derive(Debug)]
+pub struct CompactGraph<'a> {
+    graph: &'a KnowledgeGraph,
+    index: HashMap<MemoryId, u32>,
+    /// `targets[offsets[i]..offsets[i + 1]]` are the neighbours of node `i`,
+    /// sorted by index
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+    weights: Vec<f32>,
+    /// Summed weight of self-loops per node (counted from both ends)
+    loop_weight: Vec<f32>,
+    in_degree: Vec<u32>,
+    out_degree: Vec<u32>,
+    /// Endpoint indices per edge, parallel to `graph.edges`; `None` when an
+    /// endpoint is not a node of the graph
+    edge_ends: Vec<Option<(u32, u32)>>,
+}
+
+impl<'a> CompactGraph<'a> {
+    /// Build the compact view in O(V + E log d)
+    pub fn new(graph: &'a KnowledgeGraph) -> Self {
+        let n = graph.nodes.len();
+        let mut index = HashMap::with_capacity(n);
+        for (i, node) in graph.nodes.iter().enumerate() {
+            index.entry(node.id).or_insert(i as u32);
+        }
+
+        let mut in_degree = vec![0u32; n];
+        let mut out_degree = vec![0u32; n];
+        let mut loop_weight = vec![0.0f32; n];
+        let mut counts = vec![0usize; n + 1];
+        let edge_ends: Vec<Option<(u32, u32)>> = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                let from = index.get(&edge.from).copied();
+                let to = index.get(&edge.to).copied();
+                if let Some(f) = from {
+                    out_degree[f as usize] += 1;
+                }
+                if let Some(t) = to {
+                    in_degree[t as usize] += 1;
+                }
+                let (f, t) = (from?, to?);
+                if f == t {
+                    loop_weight[f as usize] += 2.0 * edge_weight(edge);
+                } else {
+                    counts[f as usize + 1] += 1;
+                    counts[t as usize + 1] += 1;
+                }
+                Some((f, t))
+            })
+            .collect();
+
+        // Bucket both directions of every edge by source...
+        for i in 0..n {
+            counts[i + 1] += counts[i];
+        }
+        let mut cursor = counts.clone();
+        let mut slots = vec![(0u32, 0.0f32); counts[n]];
+        for (edge, ends) in graph.edges.iter().zip(&edge_ends) {
+            if let Some((f, t)) = *ends {
+                if f != t {
+                    let weight = edge_weight(edge);
+                    slots[cursor[f as usize]] = (t, weight);
+                    cursor[f as usize] += 1;
+                    slots[cursor[t as usize]] = (f, weight);
+                    cursor[t as usize] += 1;
+                }
+            }
+        }
+
+        // ...then sort each bucket and merge parallel edges.
+        let mut offsets = Vec::with_capacity(n + 1);
+        let mut targets = Vec::with_capacity(slots.len());
+        let mut weights = Vec::with_capacity(slots.len());
+        offsets.push(0);
+        for i in 0..n {
+            let bucket = &mut slots[counts[i]..counts[i + 1]];
+            bucket.sort_unstable_by_key(|&(target, _)| target);
+            let start = targets.len();
+            for &(target, weight) in bucket.iter() {
+                if targets.len() > start && targets[targets.len() - 1] == target {
+                    *weights.last_mut().unwrap() += weight;
+                } else {
+                    targets.push(target);
+                    weights.push(weight);
+                }
+            }
+            offsets.push(targets.len());
+        }
+
+        Self {
+            graph,
+            index,
+            offsets,
+            targets,
+            weights,
+            loop_weight,
+            in_degree,
+            out_degree,
+            edge_ends,
+        }
+    }
+
+    /// Number of nodes
+    pub fn node_count(&self) -> usize {
+        self.graph.nodes.len()
+    }
+
+    /// Memory at index `i`
+    pub fn node_id(&self, i: usize) -> MemoryId {
+        self.graph.nodes[i].id
+    }
+
+    /// Index of a memory in this view
+    pub fn index_of(&self, id: MemoryId) -> Option<usize> {
+        self.index.get(&id).map(|&i| i as usize)
+    }
+
+    /// Distinct neighbours of node `i`, ignoring direction
+    pub fn neighbors(&self, i: usize) -> &[u32] {
+        &self.targets[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    /// Merged edge weights, parallel to [`neighbors`](Self::neighbors)
+    pub(super) fn neighbor_weights(&self, i: usize) -> &[f32] {
+        &self.weights[self.offsets[i]..self.offsets[i + 1]]
+    }
+
+    /// Incident edges of node `i`, counting parallel edges and both ends of a
+    /// self-loop
+    pub fn degree(&self, i: usize) -> usize {
+        (self.in_degree[i] + self.out_degree[i]) as usize
+    }
+
+    /// Graph statistics (see [`KnowledgeGraph::stats`])
+    pub fn stats(&self) -> GraphStats {
+        let n = self.node_count();
+        let nodes = &self.graph.nodes;
+        let edges = &self.graph.edges;
+
+        let total_degree: usize = (0..n).map(|i| self.degree(i)).sum();
+        let avg_degree = if n > 0 {
+            total_degree as f32 / n as f32
+        } else {
+            0.0
+        };
+        let density = if n > 1 {
+            edges.len() as f32 / (n * (n - 1)) as f32
+        } else {
+            0.0
+        };
+
+        let mut nodes_by_type: HashMap<String, usize> = HashMap::new();
+        for node in nodes {
+            *nodes_by_type.entry(node.memory_type.clone()).or_insert(0) += 1;
+        }
+        let mut edges_by_type: HashMap<String, usize> = HashMap::new();
+        for edge in edges {
+            *edges_by_type.entry(edge.edge_type.clone()).or_insert(0) += 1;
+        }
+
+        // Rank hubs by PageRank so a node with many weak links does not
+        // outrank one that strongly connected memories point to.
+        let pagerank = self.pagerank_scores(DEFAULT_PAGERANK_DAMPING, DEFAULT_PAGERANK_ITERATIONS);
+        let mut ranked: Vec<usize> = (0..n).collect();
+        ranked.sort_by(|&a, &b| {
+            pagerank[b]
+                .total_cmp(&pagerank[a])
+                .then(self.degree(b).cmp(&self.degree(a)))
+        });
+        let hub_nodes = ranked
+            .into_iter()
+            .take(HUB_NODE_COUNT)
+            .map(|i| HubNode {
+                id: nodes[i].id,
+                degree: self.degree(i),
+                pagerank: pagerank[i],
+            })
+            .collect();
+
+        let isolated_count = (0..n).filter(|&i| self.degree(i) == 0).count();
+        let component_sizes = self.component_sizes();
+
+        GraphStats {
+            node_count: n,
+            edge_count: edges.len(),
+            avg_degree,
+            density,
+            component_count: component_sizes.len(),
+            largest_component_size: component_sizes.iter().copied().max().unwrap_or(0),
+            nodes_by_type,
+            edges_by_type,
+            hub_nodes,
+            isolated_count,
+        }
+    }
+
+    /// PageRank per memory (see [`KnowledgeGraph::pagerank`])
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> HashMap<MemoryId, f64> {
+        let scores = self.pagerank_scores(damping, iterations);
+        self.graph
+            .nodes
+            .iter()
+            .zip(scores)
+            .map(|(node, score)| (node.id, score))
+            .collect()
+    }
+
+    /// Weighted, directed PageRank by node index; scores sum to 1.
+    ///
+    /// Each node splits its rank across its outgoing edges in proportion to
+    /// `score * confidence`. Rank held by nodes without outgoing weight is
+    /// spread evenly over all nodes, as is the `1 - damping` teleport share.
+    fn pagerank_scores(&self, damping: f64, iterations: usize) -> Vec<f64> {
+        let n = self.node_count();
+        if n == 0 {
+            return Vec::new();
+        }
+        let damping = damping.clamp(0.0, 1.0);
+
+        let mut out_weight = vec![0.0f64; n];
+        for (edge, ends) in self.graph.edges.iter().zip(&self.edge_ends) {
+            if let Some((f, _)) = ends {
+                out_weight[*f as usize] += edge_weight(edge).max(0.0) as f64;
+            }
+        }
+
+        let uniform = 1.0 / n as f64;
+        let mut rank = vec![uniform; n];
+        let mut next = vec![0.0f64; n];
+        for _ in 0..iterations {
+            let dangling: f64 = (0..n)
+                .filter(|&v| out_weight[v] == 0.0)
+                .map(|v| rank[v])
+                .sum();
+            let base = (1.0 - damping) * uniform + damping * dangling * uniform;
+            next.iter_mut().for_each(|x| *x = base);
+            for (edge, ends) in self.graph.edges.iter().zip(&self.edge_ends) {
+                if let Some((f, t)) = *ends {
+                    let (f, t) = (f as usize, t as usize);
+                    if out_weight[f] > 0.0 {
+                        let share = edge_weight(edge).max(0.0) as f64 / out_weight[f];
+                        next[t] += damping * rank[f] * share;
+                    }
+                }
+            }
+            let change: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+            std::mem::swap(&mut rank, &mut next);
+            if change < SPECTRAL_TOLERANCE {
+                break;
+            }
+        }
+        rank
+    }
+
+    /// Component id per node, numbered in order of each component's first node
+    pub fn components(&self) -> Vec<u32> {
+        let n = self.node_count();
+        let mut component = vec![u32::MAX; n];
+        let mut queue = VecDeque::new();
+        let mut next_id = 0;
+        for start in 0..n {
+            if component[start] != u32::MAX {
+                continue;
+            }
+            component[start] = next_id;
+            queue.push_back(start);
+            while let Some(v) = queue.pop_front() {
+                for &w in self.neighbors(v) {
+                    if component[w as usize] == u32::MAX {
+                        component[w as usize] = next_id;
+                        queue.push_back(w as usize);
+                    }
+                }
+            }
+            next_id += 1;
+        }
+        component
+    }
+
+    /// Size of each connected component
+    fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = Vec::new();
+        for c in self.components() {
+            let c = c as usize;
+            if c >= sizes.len() {
+                sizes.resize(c + 1, 0);
+            }
+            sizes[c] += 1;
+        }
+        sizes
+    }
+
+    /// Centrality scores (see [`KnowledgeGraph::centrality`])
+    pub fn centrality(&self) -> HashMap<MemoryId, CentralityScores> {
+        let n = self.node_count();
+        let max_degree = n.saturating_sub(1).max(1) as f32;
+        let (closeness, betweenness) = self.brandes();
+        let (eigenvector, katz) = self.spectral_centrality();
+
+        let mut results = HashMap::with_capacity(n);
+        for (i, node) in self.graph.nodes.iter().enumerate() {
+            let in_d = self.in_degree[i] as f32;
+            let out_d = self.out_degree[i] as f32;
+            results.insert(
+                node.id,
+                CentralityScores {
+                    in_degree: in_d / max_degree,
+                    out_degree: out_d / max_degree,
+                    degree: (in_d + out_d) / (2.0 * max_degree),
+                    closeness: closeness[i] as f32,
+                    betweenness: betweenness[i] as f32,
+                    eigenvector: eigenvector[i] as f32,
+                    katz: katz[i] as f32,
+                },
+            );
+        }
+        results
+    }
+
+    /// The `limit` most central nodes (see [`KnowledgeGraph::top_central_nodes`])
+    pub fn top_central_nodes(&self, limit: usize) -> Vec<(MemoryId, CentralityScores)> {
+        let mut ranked: Vec<(MemoryId, CentralityScores)> = self.centrality().into_iter().collect();
+        ranked.sort_by(|a, b| {
+            b.1.betweenness
+                .total_cmp(&a.1.betweenness)
+                .then(b.1.eigenvector.total_cmp(&a.1.eigenvector))
+                .then(a.0.cmp(&b.0))
+        });
+        ranked.truncate(limit);
+        ranked
+    }
+
+    /// Closeness and normalized betweenness for every node (Brandes, 2001).
+    ///
+    /// One BFS per source gives both: shortest-path distances for closeness and
+    /// the dependency accumulation for betweenness, in O(V·E) overall. Closeness
+    /// uses the Wasserman–Faust correction so nodes in small components are not
+    /// over-rated.
+    fn brandes(&self) -> (Vec<f64>, Vec<f64>) {
+        let n = self.node_count();
+        let mut closeness = vec![0.0; n];
+        let mut betweenness = vec![0.0; n];
+
+        let mut stack: Vec<usize> = Vec::with_capacity(n);
+        let mut queue = VecDeque::with_capacity(n);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut sigma = vec![0.0f64; n];
+        let mut distance = vec![-1i64; n];
+        let mut delta = vec![0.0f64; n];
+
+        for source in 0..n {
+            // Only the nodes reached by the previous BFS need resetting.
+            for &v in &stack {
+                predecessors[v].clear();
+                sigma[v] = 0.0;
+                distance[v] = -1;
+                delta[v] = 0.0;
+            }
+            stack.clear();
+            sigma[source] = 1.0;
+            distance[source] = 0;
+            queue.push_back(source);
+
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+                for &w in self.neighbors(v) {
+                    let w = w as usize;
+                    if distance[w] < 0 {
+                        distance[w] = distance[v] + 1;
+                        queue.push_back(w);
+                    }
+                    if distance[w] == distance[v] + 1 {
+                        sigma[w] += sigma[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            let reached = stack.len() - 1;
+            let total_distance: i64 = stack.iter().map(|&v| distance[v]).sum();
+            if total_distance > 0 && n > 1 {
+                let reached = reached as f64;
+                closeness[source] = (reached / total_distance as f64) * (reached / (n - 1) as f64);
+            }
+
+            // Walk the BFS order backwards without consuming it, so the next
+            // iteration knows which entries to reset.
+            for &w in stack.iter().rev() {
+                for &v in &predecessors[w] {
+                    delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+                }
+                if w != source {
+                    betweenness[w] += delta[w];
+                }
+            }
+        }
+
+        // Each unordered pair was visited from both ends, so the raw sum is
+        // twice the undirected betweenness; (n-1)(n-2)/2 pairs can pass
+        // through a node.
+        if n > 2 {
+            let scale = 1.0 / ((n - 1) * (n - 2)) as f64;
+            for b in &mut betweenness {
+                *b *= scale;
+            }
+        } else {
+            betweenness.iter_mut().for_each(|b| *b = 0.0);
+        }
+
+        (closeness, betweenness)
+    }
+
+    /// Eigenvector and Katz centrality, each scaled so the top node scores 1.0.
+    ///
+    /// Eigenvector centrality iterates on `A + I`, which has the same leading
+    /// eigenvector as `A` but also converges on bipartite graphs such as stars.
+    /// Katz uses `α = 0.85 / λ_max` and a unit baseline, so nodes outside the
+    /// dominant component still get a meaningful score.
+    fn spectral_centrality(&self) -> (Vec<f64>, Vec<f64>) {
+        let n = self.node_count();
+        if n == 0 {
+            return (Vec::new(), Vec::new());
+        }
+        let sum_neighbors = |scores: &[f64], v: usize| -> f64 {
+            self.neighbors(v).iter().map(|&w| scores[w as usize]).sum()
+        };
+
+        let mut eigenvector = vec![1.0 / n as f64; n];
+        let mut next = vec![0.0; n];
+        let mut lambda = 0.0;
+        for _ in 0..SPECTRAL_MAX_ITERATIONS {
+            for (v, slot) in next.iter_mut().enumerate() {
+                *slot = eigenvector[v] + sum_neighbors(&eigenvector, v);
+            }
+            let norm: f64 = next.iter().sum();
+            // The shifted matrix's eigenvalue is λ + 1.
+            lambda = norm - 1.0;
+            next.iter_mut().for_each(|x| *x /= norm);
+            let change: f64 = next
+                .iter()
+                .zip(&eigenvector)
+                .map(|(a, b)| (a - b).abs())
+                .sum();
+            std::mem::swap(&mut eigenvector, &mut next);
+            if change < SPECTRAL_TOLERANCE {
+                break;
+            }
+        }
+        if lambda <= SPECTRAL_TOLERANCE {
+            // No edges: every node is equally (un)important.
+            eigenvector.iter_mut().for_each(|x| *x = 0.0);
+        }
+
+        let mut katz = vec![1.0; n];
+        if lambda > SPECTRAL_TOLERANCE {
+            let alpha = KATZ_ALPHA_RATIO / lambda;
+            for _ in 0..SPECTRAL_MAX_ITERATIONS {
+                for (v, slot) in next.iter_mut().enumerate() {
+                    *slot = 1.0 + alpha * sum_neighbors(&katz, v);
+                }
+                let change: f64 = next.iter().zip(&katz).map(|(a, b)| (a - b).abs()).sum();
+                std::mem::swap(&mut katz, &mut next);
+                if change < SPECTRAL_TOLERANCE * n as f64 {
+                    break;
+                }
+            }
+        }
+
+        (scale_to_max(eigenvector), scale_to_max(katz))
+    }
+
+    /// Subgraph within `depth` hops of `center` (see
+    /// [`KnowledgeGraph::neighborhood`])
+    pub fn neighborhood(&self, center: MemoryId, depth: usize) -> KnowledgeGraph {
+        let Some(start) = self.index_of(center) else {
+            return KnowledgeGraph {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            };
+        };
+
+        let mut keep = vec![false; self.node_count()];
+        for v in self.within(start, depth) {
+            keep[v] = true;
+        }
+        self.induced_subgraph(&keep)
+    }
+
+    /// Nodes at most `depth` hops from `start`, nearest first
+    pub(super) fn within(&self, start: usize, depth: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.node_count()];
+        visited[start] = true;
+        let mut order = vec![start];
+        let mut frontier = 0;
+        for _ in 0..depth {
+            let reached = order.len();
+            for i in frontier..reached {
+                for &w in self.neighbors(order[i]) {
+                    if !visited[w as usize] {
+                        visited[w as usize] = true;
+                        order.push(w as usize);
+                    }
+                }
+            }
+            if order.len() == reached {
+                break;
+            }
+            frontier = reached;
+        }
+        order
+    }
+
+    /// Clone the nodes flagged in `keep` and the edges between them
+    pub(super) fn induced_subgraph(&self, keep: &[bool]) -> KnowledgeGraph {
+        let nodes: Vec<GraphNode> = self
+            .graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| keep[*i])
+            .map(|(_, node)| node.clone())
+            .collect();
+        let edges: Vec<GraphEdge> = self
+            .graph
+            .edges
+            .iter()
+            .zip(&self.edge_ends)
+            .filter_map(|(edge, ends)| {
+                let (f, t) = (*ends)?;
+                (keep[f as usize] && keep[t as usize]).then(|| edge.clone())
+            })
+            .collect();
+        KnowledgeGraph { nodes, edges }
+    }
+
+    /// Label-propagation communities (see
+    /// [`KnowledgeGraph::detect_communities`])
+    pub fn detect_communities(&self, max_iterations: usize) -> Vec<GraphCluster> {
+        self.communities(CommunityAlgorithm::LabelPropagation { max_iterations })
+    }
+
+    /// Communities found by `algorithm`, largest first, each with its share
+    /// of the partition's modularity
+    pub fn communities(&self, algorithm: CommunityAlgorithm) -> Vec<GraphCluster> {
+        if self.node_count() == 0 {
+            return Vec::new();
+        }
+        match algorithm {
+            CommunityAlgorithm::LabelPropagation { max_iterations } => {
+                let labels = self.label_propagation(max_iterations);
+                self.clusters_from_labels(&labels, 1.0)
+            }
+            CommunityAlgorithm::Louvain { resolution, seed } => {
+                let labels = louvain(&self.weighted_csr(), resolution, seed);
+                self.clusters_from_labels(&labels, resolution)
+            }
+        }
+    }
+
+    /// Weighted label propagation; returns a label (a node index) per node
+    fn label_propagation(&self, max_iterations: usize) -> Vec<u32> {
+        let n = self.node_count();
+
+        // Labels are node indices; votes are tallied in a dense buffer that is
+        // cleared through the list of labels actually touched.
+        let mut labels: Vec<u32> = (0..n as u32).collect();
+        let mut votes = vec![0.0f32; n];
+        let mut touched: Vec<u32> = Vec::new();
+
+        for _ in 0..max_iterations {
+            let mut changed = false;
+
+            for v in 0..n {
+                if self.neighbors(v).is_empty() {
+                    continue;
+                }
+                if self.loop_weight[v] > 0.0 {
+                    touched.push(labels[v]);
+                    votes[labels[v] as usize] += self.loop_weight[v];
+                }
+                for (&w, &weight) in self.neighbors(v).iter().zip(self.neighbor_weights(v)) {
+                    let label = labels[w as usize];
+                    if votes[label as usize] == 0.0 {
+                        touched.push(label);
+                    }
+                    votes[label as usize] += weight;
+                }
+
+                // Heaviest label wins; ties go to the lowest label so runs are
+                // reproducible.
+                let mut best = labels[v];
+                let mut best_votes = f32::NEG_INFINITY;
+                for &label in &touched {
+                    let score = votes[label as usize];
+                    if score > best_votes || (score == best_votes && label < best) {
+                        best = label;
+                        best_votes = score;
+                    }
+                }
+                for label in touched.drain(..) {
+                    votes[label as usize] = 0.0;
+                }
+
+                if best != labels[v] {
+                    labels[v] = best;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        labels
+    }
+
+    /// The adjacency in the form the Louvain implementation works on
+    fn weighted_csr(&self) -> WeightedCsr {
+        WeightedCsr {
+            offsets: self.offsets.clone(),
+            targets: self.targets.clone(),
+            weights: self.weights.iter().map(|&w| w as f64).collect(),
+            self_loops: self.loop_weight.iter().map(|&w| w as f64).collect(),
+        }
+    }
+
+    /// Group nodes by label and summarize each group.
+    ///
+    /// Each cluster's `modularity` is its term of
+    /// `Q = Σ_c [ in_c / 2m − γ·(tot_c / 2m)² ]`, so the values sum to the
+    /// modularity of the whole partition at resolution `γ`.
+    fn clusters_from_labels(&self, labels: &[u32], resolution: f64) -> Vec<GraphCluster> {
+        let nodes = &self.graph.nodes;
+        let mut slot_of = vec![usize::MAX; nodes.len()];
+        let mut members: Vec<Vec<usize>> = Vec::new();
+        for (i, &label) in labels.iter().enumerate() {
+            let label = label as usize;
+            if slot_of[label] == usize::MAX {
+                slot_of[label] = members.len();
+                members.push(Vec::new());
+            }
+            members[slot_of[label]].push(i);
+        }
+
+        let mut internal_edges = vec![0usize; members.len()];
+        for (f, t) in self.edge_ends.iter().flatten() {
+            let (a, b) = (labels[*f as usize], labels[*t as usize]);
+            if a == b {
+                internal_edges[slot_of[a as usize]] += 1;
+            }
+        }
+
+        let weighted = self.weighted_csr();
+        let strength = weighted.strengths();
+        let two_m: f64 = strength.iter().sum();
+        let mut internal_weight = vec![0.0f64; members.len()];
+        let mut total_weight = vec![0.0f64; members.len()];
+        for v in 0..nodes.len() {
+            let slot = slot_of[labels[v] as usize];
+            total_weight[slot] += strength[v];
+            internal_weight[slot] += weighted.self_loops[v];
+            for (&w, &weight) in self.neighbors(v).iter().zip(self.neighbor_weights(v)) {
+                if labels[w as usize] == labels[v] {
+                    internal_weight[slot] += weight as f64;
+                }
+            }
+        }
+
+        let mut clusters: Vec<GraphCluster> = members
+            .into_iter()
+            .zip(internal_edges)
+            .enumerate()
+            .map(|(slot, (member_indices, internal_edges))| {
+                let mut type_counts: HashMap<&str, usize> = HashMap::new();
+                let mut all_tags: HashMap<&str, usize> = HashMap::new();
+                for &i in &member_indices {
+                    *type_counts
+                        .entry(nodes[i].memory_type.as_str())
+                        .or_insert(0) += 1;
+                    for tag in &nodes[i].tags {
+                        *all_tags.entry(tag.as_str()).or_insert(0) += 1;
+                    }
+                }
+
+                let dominant_type = type_counts
+                    .into_iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+                    .map(|(t, _)| t.to_string());
+
+                // Common tags (present in > 50% of members)
+                let threshold = member_indices.len() / 2;
+                let mut common_tags: Vec<String> = all_tags
+                    .into_iter()
+                    .filter(|(_, count)| *count > threshold)
+                    .map(|(tag, _)| tag.to_string())
+                    .collect();
+                common_tags.sort();
+
+                // Cohesion: internal edges / possible internal edges
+                let size = member_indices.len();
+                let possible = if size > 1 { size * (size - 1) } else { 1 };
+
+                GraphCluster {
+                    id: 0,
+                    members: member_indices.iter().map(|&i| nodes[i].id).collect(),
+                    dominant_type,
+                    common_tags,
+                    internal_edges,
+                    cohesion: internal_edges as f32 / possible as f32,
+                    modularity: if two_m > 0.0 {
+                        (internal_weight[slot] / two_m
+                            - resolution * (total_weight[slot] / two_m).powi(2))
+                            as f32
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+
+        // Largest first, then renumber
+        clusters.sort_by_key(|c| std::cmp::Reverse(c.members.len()));
+        for (i, cluster) in clusters.iter_mut().enumerate() {
+            cluster.id = i;
+        }
+        clusters
+    }
+}
+
+/// Label-propagation weight of an edge
+fn edge_weight(edge: &GraphEdge) -> f32 {
+    edge.score * edge.confidence
+}
+
+/// Divide every score by the largest one (no-op when all are zero)
+fn scale_to_max(mut scores: Vec<f64>) -> Vec<f64> {
+    let max = scores.iter().cloned().fold(0.0, f64::max);
+    if max > 0.0 {
+        scores.iter_mut().for_each(|x| *x /= max);
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: MemoryId) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance: 0.5,
+            tags: vec![],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId, score: f32) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: "related_to".to_string(),
+            score,
+            confidence: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_csr_merges_parallel_edges_and_skips_dangling() {
+        let graph = KnowledgeGraph {
+            nodes: (1..=4).map(node).collect(),
+            edges: vec![
+                edge(1, 2, 0.5),
+                edge(2, 1, 0.25),
+                edge(1, 3, 1.0),
+                edge(3, 3, 1.0),
+                edge(4, 99, 1.0),
+            ],
+        };
+        let compact = CompactGraph::new(&graph);
+
+        assert_eq!(compact.neighbors(0), &[1, 2]);
+        assert_eq!(compact.neighbor_weights(0), &[0.75, 1.0]);
+        assert_eq!(compact.neighbors(1), &[0]);
+        // The self-loop stays out of the adjacency but counts towards degree.
+        assert_eq!(compact.neighbors(2), &[0]);
+        assert_eq!(compact.degree(2), 3);
+        // Node 4's only edge points outside the graph.
+        assert!(compact.neighbors(3).is_empty());
+        assert_eq!(compact.degree(3), 1);
+        assert_eq!(compact.index_of(99), None);
+        assert_eq!(compact.edge_ends[4], None);
+    }
+
+    #[test]
+    fn test_components_and_stats() {
+        let graph = KnowledgeGraph {
+            nodes: (1..=6).map(node).collect(),
+            edges: vec![edge(1, 2, 1.0), edge(2, 3, 1.0), edge(4, 5, 1.0)],
+        };
+        let compact = CompactGraph::new(&graph);
+
+        assert_eq!(compact.components(), vec![0, 0, 0, 1, 1, 2]);
+        let stats = compact.stats();
+        assert_eq!(stats.component_count, 3);
+        assert_eq!(stats.largest_component_size, 3);
+        assert_eq!(stats.isolated_count, 1);
+        // Rank flows down the 1 → 2 → 3 chain, so its sink leads.
+        assert_eq!(stats.hub_nodes[0].id, 3);
+        assert_eq!(stats.hub_nodes[1].id, 2);
+        assert_eq!(stats.hub_nodes[1].degree, 2);
+        assert!((stats.avg_degree - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_pagerank_discounts_weak_edges() {
+        // Node 1 has four weak in-links, node 2 two strong ones
+        let graph = KnowledgeGraph {
+            nodes: (1..=8).map(node).collect(),
+            edges: vec![
+                edge(3, 1, 0.05),
+                edge(4, 1, 0.05),
+                edge(5, 1, 0.05),
+                edge(6, 1, 0.05),
+                edge(3, 2, 1.0),
+                edge(4, 2, 1.0),
+                edge(7, 2, 1.0),
+                edge(8, 2, 1.0),
+            ],
+        };
+        let compact = CompactGraph::new(&graph);
+        let ranks = compact.pagerank(0.85, 100);
+
+        let total: f64 = ranks.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(ranks[&2] > ranks[&1]);
+        // Sources without in-links only keep the teleport share.
+        assert!(ranks[&1] > ranks[&5]);
+        assert_eq!(compact.stats().hub_nodes[0].id, 2);
+    }
+
+    #[test]
+    fn test_communities_are_deterministic() {
+        // Two 4-cliques joined by one weak edge
+        let mut edges = Vec::new();
+        for group in [[1, 2, 3, 4], [5, 6, 7, 8]] {
+            for (i, &a) in group.iter().enumerate() {
+                for &b in &group[i + 1..] {
+                    edges.push(edge(a, b, 1.0));
+                }
+            }
+        }
+        edges.push(edge(4, 5, 0.1));
+        let graph = KnowledgeGraph {
+            nodes: (1..=8).map(node).collect(),
+            edges,
+        };
+
+        let first = CompactGraph::new(&graph).detect_communities(20);
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].members, vec![1, 2, 3, 4]);
+        assert_eq!(first[1].members, vec![5, 6, 7, 8]);
+        assert_eq!(first[0].internal_edges, 6);
+        assert!((first[0].cohesion - 0.5).abs() < f32::EPSILON);
+
+        let second = CompactGraph::new(&graph).detect_communities(20);
+        let members = |c: &[GraphCluster]| c.iter().map(|c| c.members.clone()).collect::<Vec<_>>();
+        assert_eq!(members(&first), members(&second));
+    }
+
+    #[test]
+    fn test_neighborhood_of_unknown_center_is_empty() {
+        let graph = KnowledgeGraph {
+            nodes: (1..=2).map(node).collect(),
+            edges: vec![edge(1, 2, 1.0)],
+        };
+        let compact = CompactGraph::new(&graph);
+        assert!(compact.neighborhood(42, 3).nodes.is_empty());
+
+        let around = compact.neighborhood(1, 5);
+        assert_eq!(around.nodes.len(), 2);
+        assert_eq!(around.edges.len(), 1);
+    }
+}
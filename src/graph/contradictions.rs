@@ -0,0 +1,347 @@
+//! Contradiction chains over `supports` / `contradicts` links
+//!
+//! Read `supports` as "these two memories take the same stance" and
+//! `contradicts` as "they take opposite stances". A cycle of such links is
+//! consistent only if it crosses an even number of `contradicts` edges: in
+//! A supports B, B contradicts C, C supports A, following the chain from A
+//! back to A says A disagrees with itself.
+//!
+//! Detection is a two-colouring of each connected component by stance. A
+//! breadth-first spanning tree fixes every memory's stance relative to the
+//! component's root; each remaining link that disagrees with those stances
+//! closes one inconsistent cycle through the tree. Reversing any single link
+//! in such a cycle makes it consistent, so the suggested resolutions point at
+//! the weakest link and the least important memory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use super::{GraphEdge, KnowledgeGraph};
+use crate::types::MemoryId;
+
+const SUPPORTS: &str = "supports";
+const CONTRADICTS: &str = "contradicts";
+
+/// A closed chain of `supports` / `contradicts` links that cannot all hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContradictionCycle {
+    /// Memories in cycle order; the last one links back to the first
+    pub memories: Vec<MemoryId>,
+    /// `edges[i]` joins `memories[i]` and `memories[i + 1]` (wrapping), in
+    /// either direction
+    pub edges: Vec<GraphEdge>,
+    /// Number of `contradicts` links in the cycle, always odd
+    pub contradictions: usize,
+    /// Ways to make the cycle consistent, most likely first
+    pub suggestions: Vec<CycleResolution>,
+}
+
+/// A suggested way to break a [`ContradictionCycle`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum CycleResolution {
+    /// Re-check one link; removing or reversing it makes the cycle consistent
+    ReviewEdge {
+        from: MemoryId,
+        to: MemoryId,
+        edge_type: String,
+        /// `score * confidence` of the link
+        weight: f32,
+        reason: String,
+    },
+    /// Re-check one memory; if it is outdated, superseding it breaks the cycle
+    ReviewMemory {
+        id: MemoryId,
+        importance: f32,
+        reason: String,
+    },
+}
+
+impl KnowledgeGraph {
+    /// Find inconsistent cycles of `supports` and `contradicts` links,
+    /// shortest first
+    ///
+    /// Each returned cycle is closed by a different link, so every link that
+    /// takes part in some inconsistency appears in at least one cycle. Links
+    /// to memories outside the graph and self-links are ignored.
+    pub fn contradiction_cycles(&self) -> Vec<ContradictionCycle> {
+        let mut index: HashMap<MemoryId, usize> = HashMap::new();
+        for node in &self.nodes {
+            let next = index.len();
+            index.entry(node.id).or_insert(next);
+        }
+        let n = index.len();
+
+        // (a, b, edge index) for every stance link, with `true` for contradicts
+        let mut links: Vec<(usize, usize, usize, bool)> = Vec::new();
+        let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (e, edge) in self.edges.iter().enumerate() {
+            let opposed = match edge.edge_type.as_str() {
+                SUPPORTS => false,
+                CONTRADICTS => true,
+                _ => continue,
+            };
+            let (Some(&a), Some(&b)) = (index.get(&edge.from), index.get(&edge.to)) else {
+                continue;
+            };
+            if a == b {
+                continue;
+            }
+            adjacency[a].push(links.len());
+            adjacency[b].push(links.len());
+            links.push((a, b, e, opposed));
+        }
+
+        // Breadth-first spanning forest; `stance` is parity relative to the root
+        const UNSEEN: usize = usize::MAX;
+        let mut parent_link = vec![UNSEEN; n];
+        let mut depth = vec![UNSEEN; n];
+        let mut stance = vec![false; n];
+        let mut in_tree = vec![false; links.len()];
+        for root in 0..n {
+            if depth[root] != UNSEEN || adjacency[root].is_empty() {
+                continue;
+            }
+            depth[root] = 0;
+            let mut queue = VecDeque::from([root]);
+            while let Some(v) = queue.pop_front() {
+                for &l in &adjacency[v] {
+                    let (a, b, _, opposed) = links[l];
+                    let w = if a == v { b } else { a };
+                    if depth[w] != UNSEEN {
+                        continue;
+                    }
+                    depth[w] = depth[v] + 1;
+                    stance[w] = stance[v] ^ opposed;
+                    parent_link[w] = l;
+                    in_tree[l] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+
+        let parent = |v: usize| {
+            let (a, b, _, _) = links[parent_link[v]];
+            if a == v {
+                b
+            } else {
+                a
+            }
+        };
+        let node_ids: Vec<MemoryId> = {
+            let mut ids = vec![0; n];
+            for (&id, &i) in &index {
+                ids[i] = id;
+            }
+            ids
+        };
+
+        let mut cycles = Vec::new();
+        for (l, &(a, b, _, opposed)) in links.iter().enumerate() {
+            if in_tree[l] || stance[a] ^ stance[b] == opposed {
+                continue;
+            }
+            // Walk both ends up to their lowest common ancestor
+            let (mut up_a, mut up_b) = (vec![a], vec![b]);
+            let (mut x, mut y) = (a, b);
+            while x != y {
+                if depth[x] >= depth[y] {
+                    x = parent(x);
+                    up_a.push(x);
+                } else {
+                    y = parent(y);
+                    up_b.push(y);
+                }
+            }
+            up_b.pop();
+            let mut path_links: Vec<usize> = up_a[..up_a.len() - 1]
+                .iter()
+                .map(|&v| parent_link[v])
+                .collect();
+            path_links.extend(up_b.iter().rev().map(|&v| parent_link[v]));
+            path_links.push(l);
+
+            let members: Vec<usize> = up_a.into_iter().chain(up_b.into_iter().rev()).collect();
+            let edges: Vec<GraphEdge> = path_links
+                .iter()
+                .map(|&pl| self.edges[links[pl].2].clone())
+                .collect();
+            debug_assert_eq!(members.len(), edges.len());
+            let contradictions = edges
+                .iter()
+                .filter(|edge| edge.edge_type == CONTRADICTS)
+                .count();
+            let memories: Vec<MemoryId> = members.iter().map(|&i| node_ids[i]).collect();
+            let suggestions = self.suggest_resolutions(&memories, &edges);
+            cycles.push(ContradictionCycle {
+                memories,
+                edges,
+                contradictions,
+                suggestions,
+            });
+        }
+
+        cycles.sort_by_key(|c| c.memories.len());
+        cycles
+    }
+
+    /// The weakest link and the least important memory of a cycle
+    fn suggest_resolutions(
+        &self,
+        memories: &[MemoryId],
+        edges: &[GraphEdge],
+    ) -> Vec<CycleResolution> {
+        let mut suggestions = Vec::new();
+        let weakest = edges.iter().min_by(|a, b| {
+            (a.score * a.confidence)
+                .partial_cmp(&(b.score * b.confidence))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        if let Some(edge) = weakest {
+            let implied = if edge.edge_type == CONTRADICTS {
+                "agree"
+            } else {
+                "disagree"
+            };
+            suggestions.push(CycleResolution::ReviewEdge {
+                from: edge.from,
+                to: edge.to,
+                edge_type: edge.edge_type.clone(),
+                weight: edge.score * edge.confidence,
+                reason: format!(
+                    "Weakest link in the cycle: memory {} {} memory {}, but the rest of the cycle implies they {}",
+                    edge.from, edge.edge_type, edge.to, implied
+                ),
+            });
+        }
+
+        let least = self
+            .nodes
+            .iter()
+            .filter(|n| memories.contains(&n.id))
+            .min_by(|a, b| {
+                a.importance
+                    .partial_cmp(&b.importance)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        if let Some(node) = least {
+            suggestions.push(CycleResolution::ReviewMemory {
+                id: node.id,
+                importance: node.importance,
+                reason: "Least important memory in the cycle; if it is outdated, supersede it to break the cycle".to_string(),
+            });
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    fn node(id: MemoryId, importance: f32) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance,
+            tags: vec![],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId, edge_type: &str, score: f32) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: edge_type.to_string(),
+            score,
+            confidence: 1.0,
+        }
+    }
+
+    fn graph(ids: &[MemoryId], edges: Vec<GraphEdge>) -> KnowledgeGraph {
+        KnowledgeGraph {
+            nodes: ids.iter().map(|&id| node(id, 0.5)).collect(),
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_odd_cycle_is_flagged() {
+        // A supports B, B contradicts C, C supports A
+        let mut g = graph(
+            &[1, 2, 3],
+            vec![
+                edge(1, 2, SUPPORTS, 0.9),
+                edge(2, 3, CONTRADICTS, 0.8),
+                edge(3, 1, SUPPORTS, 0.4),
+            ],
+        );
+        g.nodes[1].importance = 0.1;
+        let cycles = g.contradiction_cycles();
+
+        assert_eq!(cycles.len(), 1);
+        let cycle = &cycles[0];
+        let mut members = cycle.memories.clone();
+        members.sort();
+        assert_eq!(members, vec![1, 2, 3]);
+        assert_eq!(cycle.edges.len(), 3);
+        assert_eq!(cycle.contradictions, 1);
+
+        // Consecutive memories are joined by the edge at the same position
+        for (i, e) in cycle.edges.iter().enumerate() {
+            let (a, b) = (cycle.memories[i], cycle.memories[(i + 1) % 3]);
+            assert!((e.from, e.to) == (a, b) || (e.from, e.to) == (b, a));
+        }
+
+        match &cycle.suggestions[0] {
+            CycleResolution::ReviewEdge { from, to, .. } => assert_eq!((*from, *to), (3, 1)),
+            other => panic!("unexpected {:?}", other),
+        }
+        match &cycle.suggestions[1] {
+            CycleResolution::ReviewMemory { id, .. } => assert_eq!(*id, 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_consistent_chains_are_not_flagged() {
+        // Two contradictions cancel out: A contradicts B, B contradicts C,
+        // A supports C. Unrelated link types are ignored.
+        let g = graph(
+            &[1, 2, 3, 4],
+            vec![
+                edge(1, 2, CONTRADICTS, 1.0),
+                edge(2, 3, CONTRADICTS, 1.0),
+                edge(1, 3, SUPPORTS, 1.0),
+                edge(3, 4, "related_to", 1.0),
+                edge(4, 1, CONTRADICTS, 1.0),
+            ],
+        );
+        assert!(g.contradiction_cycles().is_empty());
+    }
+
+    #[test]
+    fn test_parallel_opposite_links_and_shortest_first() {
+        // 1 both supports and contradicts 2; separately a 4-cycle 3-4-5-6
+        // with one contradiction
+        let g = graph(
+            &[1, 2, 3, 4, 5, 6],
+            vec![
+                edge(3, 4, SUPPORTS, 1.0),
+                edge(4, 5, SUPPORTS, 1.0),
+                edge(5, 6, SUPPORTS, 1.0),
+                edge(6, 3, CONTRADICTS, 1.0),
+                edge(1, 2, SUPPORTS, 1.0),
+                edge(1, 2, CONTRADICTS, 1.0),
+            ],
+        );
+        let cycles = g.contradiction_cycles();
+
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].memories.len(), 2);
+        assert_eq!(cycles[1].memories.len(), 4);
+        assert!(cycles.iter().all(|c| c.contradictions % 2 == 1));
+    }
+}
@@ -0,0 +1,410 @@
+//! Structural node embeddings (node2vec / DeepWalk)
+//!
+//! Text embeddings say what a memory is about; these say where it sits in the
+//! crossref graph. Each node starts a number of second-order random walks
+//! (Grover & Leskovec, 2016) over the weighted adjacency of a
+//! [`CompactGraph`], and a skip-gram model with negative sampling learns one
+//! vector per node from the co-occurrences within a sliding window. With
+//! `p = q = 1` the walks are first-order and this is DeepWalk.
+//!
+//! `q < 1` pushes walks outward, so nodes with similar roles (hubs, bridges,
+//! leaves) end up close; `q > 1` keeps walks local, so members of the same
+//! community end up close. Everything is driven by one seeded RNG, so a given
+//! graph and config always produce the same vectors.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::CompactGraph;
+use crate::types::MemoryId;
+
+/// Smoothing exponent for the negative-sampling distribution (word2vec)
+const UNIGRAM_POWER: f64 = 0.75;
+
+/// Floor for the decaying learning rate, as a fraction of the initial rate
+const MIN_LEARNING_RATE_RATIO: f32 = 1e-4;
+
+/// Logits are clamped to this range before the sigmoid
+const MAX_LOGIT: f32 = 6.0;
+
+/// Parameters for [`CompactGraph::node2vec`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Node2VecConfig {
+    /// Vector length
+    pub dimensions: usize,
+    /// Nodes per walk, the start node included
+    pub walk_length: usize,
+    /// Walks started from every node
+    pub walks_per_node: usize,
+    /// Skip-gram context size on each side of a node
+    pub window: usize,
+    /// Return parameter: higher values make stepping back less likely
+    pub p: f64,
+    /// In-out parameter: below 1 explores outward, above 1 stays local
+    pub q: f64,
+    /// Negative samples per positive pair
+    pub negative_samples: usize,
+    /// Passes over the walk corpus
+    pub epochs: usize,
+    /// Initial SGD learning rate, decayed linearly over training
+    pub learning_rate: f32,
+    pub seed: u64,
+}
+
+impl Default for Node2VecConfig {
+    fn default() -> Self {
+        Self {
+            dimensions: 64,
+            walk_length: 20,
+            walks_per_node: 10,
+            window: 4,
+            p: 1.0,
+            q: 1.0,
+            negative_samples: 5,
+            epochs: 1,
+            learning_rate: 0.025,
+            seed: 0,
+        }
+    }
+}
+
+/// Unit-length structural vectors keyed by memory.
+///
+/// Nodes without neighbours never appear in a walk with anything else, so
+/// they have no embedding.
+#[derive(Debug, Clone, Default)]
+pub struct NodeEmbeddings {
+    pub dimensions: usize,
+    vectors: HashMap<MemoryId, Vec<f32>>,
+}
+
+impl NodeEmbeddings {
+    /// Vector of a memory
+    pub fn get(&self, id: MemoryId) -> Option<&[f32]> {
+        self.vectors.get(&id).map(Vec::as_slice)
+    }
+
+    /// Number of embedded nodes
+    pub fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// Cosine similarity of two memories' vectors
+    pub fn similarity(&self, a: MemoryId, b: MemoryId) -> Option<f32> {
+        Some(dot(self.get(a)?, self.get(b)?))
+    }
+
+    /// The `limit` memories most similar to `id`, best first; ties go to the
+    /// lower id. Empty if `id` has no embedding.
+    pub fn most_similar(&self, id: MemoryId, limit: usize) -> Vec<(MemoryId, f32)> {
+        let Some(target) = self.get(id) else {
+            return Vec::new();
+        };
+        let mut ranked: Vec<(MemoryId, f32)> = self
+            .vectors
+            .iter()
+            .filter(|(&other, _)| other != id)
+            .map(|(&other, vector)| (other, dot(target, vector)))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
+impl CompactGraph<'_> {
+    /// Learn node2vec embeddings for every node that has a neighbour
+    pub fn node2vec(&self, config: &Node2VecConfig) -> NodeEmbeddings {
+        let dimensions = config.dimensions.max(1);
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let walks = self.random_walks(config, &mut rng);
+        let (input, _) = skip_gram(&walks, self.node_count(), dimensions, config, &mut rng);
+
+        let vectors = (0..self.node_count())
+            .filter(|&v| !self.neighbors(v).is_empty())
+            .map(|v| {
+                let mut vector = input[v * dimensions..(v + 1) * dimensions].to_vec();
+                normalize(&mut vector);
+                (self.node_id(v), vector)
+            })
+            .collect();
+        NodeEmbeddings {
+            dimensions,
+            vectors,
+        }
+    }
+
+    /// `walks_per_node` biased walks from every non-isolated node, in rounds
+    /// so that no start node is trained on all of its walks first
+    fn random_walks(&self, config: &Node2VecConfig, rng: &mut StdRng) -> Vec<Vec<u32>> {
+        let n = self.node_count();
+        let walk_length = config.walk_length.max(2);
+        let (inv_p, inv_q) = (1.0 / config.p.max(1e-6), 1.0 / config.q.max(1e-6));
+        let mut bias = Vec::new();
+        let mut walks = Vec::with_capacity(n * config.walks_per_node);
+
+        for _ in 0..config.walks_per_node {
+            for start in 0..n {
+                if self.neighbors(start).is_empty() {
+                    continue;
+                }
+                let mut walk = Vec::with_capacity(walk_length);
+                walk.push(start as u32);
+                while walk.len() < walk_length {
+                    let current = walk[walk.len() - 1] as usize;
+                    let neighbors = self.neighbors(current);
+                    let weights = self.neighbor_weights(current);
+
+                    bias.clear();
+                    match walk.len().checked_sub(2).map(|i| walk[i]) {
+                        None => bias.extend(weights.iter().map(|&w| w.max(0.0) as f64)),
+                        Some(previous) => {
+                            let previous_neighbors = self.neighbors(previous as usize);
+                            bias.extend(neighbors.iter().zip(weights).map(|(&x, &w)| {
+                                let alpha = if x == previous {
+                                    inv_p
+                                } else if previous_neighbors.binary_search(&x).is_ok() {
+                                    1.0
+                                } else {
+                                    inv_q
+                                };
+                                w.max(0.0) as f64 * alpha
+                            }));
+                        }
+                    }
+                    match sample(&bias, rng) {
+                        Some(next) => walk.push(neighbors[next]),
+                        None => break,
+                    }
+                }
+                walks.push(walk);
+            }
+        }
+        walks
+    }
+}
+
+/// Index drawn with probability proportional to `weights`; uniform if they
+/// are all zero, `None` if there are none
+fn sample(weights: &[f64], rng: &mut StdRng) -> Option<usize> {
+    if weights.is_empty() {
+        return None;
+    }
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return Some(rng.gen_range(0..weights.len()));
+    }
+    let mut remaining = rng.gen::<f64>() * total;
+    for (i, &w) in weights.iter().enumerate() {
+        if remaining < w {
+            return Some(i);
+        }
+        remaining -= w;
+    }
+    Some(weights.len() - 1)
+}
+
+/// Skip-gram with negative sampling over the walk corpus.
+///
+/// Returns the input and output matrices, each `n × dimensions` row-major;
+/// the input rows are the embeddings.
+fn skip_gram(
+    walks: &[Vec<u32>],
+    n: usize,
+    dimensions: usize,
+    config: &Node2VecConfig,
+    rng: &mut StdRng,
+) -> (Vec<f32>, Vec<f32>) {
+    let scale = 0.5 / dimensions as f32;
+    let mut input: Vec<f32> = (0..n * dimensions)
+        .map(|_| (rng.gen::<f32>() - 0.5) * 2.0 * scale)
+        .collect();
+    let mut output = vec![0.0f32; n * dimensions];
+
+    // Negatives are drawn from node frequency in the corpus, smoothed.
+    let mut frequency = vec![0.0f64; n];
+    for &v in walks.iter().flatten() {
+        frequency[v as usize] += 1.0;
+    }
+    let mut cumulative = Vec::with_capacity(n);
+    let mut total = 0.0;
+    for f in frequency {
+        total += f.powf(UNIGRAM_POWER);
+        cumulative.push(total);
+    }
+    if total == 0.0 {
+        return (input, output);
+    }
+
+    let window = config.window.max(1);
+    let steps = (config.epochs.max(1) * walks.len()).max(1);
+    let min_rate = config.learning_rate * MIN_LEARNING_RATE_RATIO;
+    let mut gradient = vec![0.0f32; dimensions];
+    let mut step = 0;
+
+    for _ in 0..config.epochs.max(1) {
+        for walk in walks {
+            let progress = step as f32 / steps as f32;
+            let rate = (config.learning_rate * (1.0 - progress)).max(min_rate);
+            step += 1;
+
+            for (i, &center) in walk.iter().enumerate() {
+                let lo = i.saturating_sub(window);
+                let hi = (i + window + 1).min(walk.len());
+                for (j, &context) in walk.iter().enumerate().take(hi).skip(lo) {
+                    if j == i {
+                        continue;
+                    }
+                    let center_row = center as usize * dimensions;
+                    gradient.iter_mut().for_each(|g| *g = 0.0);
+
+                    for k in 0..=config.negative_samples {
+                        let (target, label) = if k == 0 {
+                            (context as usize, 1.0)
+                        } else {
+                            let draw = rng.gen::<f64>() * total;
+                            let target = cumulative.partition_point(|&c| c <= draw).min(n - 1);
+                            if target == context as usize {
+                                continue;
+                            }
+                            (target, 0.0)
+                        };
+                        let target_row = target * dimensions;
+                        let logit = dot(
+                            &input[center_row..center_row + dimensions],
+                            &output[target_row..target_row + dimensions],
+                        )
+                        .clamp(-MAX_LOGIT, MAX_LOGIT);
+                        let g = rate * (label - sigmoid(logit));
+                        for d in 0..dimensions {
+                            gradient[d] += g * output[target_row + d];
+                            output[target_row + d] += g * input[center_row + d];
+                        }
+                    }
+                    for (value, g) in input[center_row..center_row + dimensions]
+                        .iter_mut()
+                        .zip(&gradient)
+                    {
+                        *value += g;
+                    }
+                }
+            }
+        }
+    }
+
+    (input, output)
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = dot(vector, vector).sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphEdge, GraphNode, KnowledgeGraph};
+
+    fn node(id: MemoryId) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance: 0.5,
+            tags: vec![],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: "related_to".to_string(),
+            score: 1.0,
+            confidence: 1.0,
+        }
+    }
+
+    /// Two 5-cliques (1–5 and 6–10) joined by a single 5–6 bridge, plus an
+    /// isolated node 11
+    fn barbell() -> KnowledgeGraph {
+        let mut edges = Vec::new();
+        for clique in [1..=5, 6..=10] {
+            let ids: Vec<MemoryId> = clique.collect();
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    edges.push(edge(a, b));
+                }
+            }
+        }
+        edges.push(edge(5, 6));
+        KnowledgeGraph {
+            nodes: (1..=11).map(node).collect(),
+            edges,
+        }
+    }
+
+    fn config() -> Node2VecConfig {
+        Node2VecConfig {
+            dimensions: 16,
+            walks_per_node: 20,
+            q: 2.0,
+            epochs: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clique_members_are_most_similar() {
+        let graph = barbell();
+        let embeddings = graph.compact().node2vec(&config());
+
+        assert_eq!(embeddings.len(), 10, "isolated node has no vector");
+        assert!(embeddings.get(11).is_none());
+        assert!(embeddings.most_similar(11, 5).is_empty());
+
+        let similar: Vec<MemoryId> = embeddings
+            .most_similar(1, 4)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let mut sorted = similar.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![2, 3, 4, 5], "got {:?}", similar);
+
+        let same = embeddings.similarity(1, 2).unwrap();
+        let across = embeddings.similarity(1, 9).unwrap();
+        assert!(same > across, "{} <= {}", same, across);
+    }
+
+    #[test]
+    fn test_same_seed_same_vectors() {
+        let graph = barbell();
+        let compact = graph.compact();
+        let a = compact.node2vec(&config());
+        let b = compact.node2vec(&config());
+        assert_eq!(a.get(3), b.get(3));
+
+        let vector = a.get(3).unwrap();
+        assert_eq!(vector.len(), 16);
+        assert!((dot(vector, vector) - 1.0).abs() < 1e-5);
+    }
+}
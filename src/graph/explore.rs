@@ -0,0 +1,557 @@
+//! On-demand graph exploration
+//!
+//! Graph views over large corpora can't load the whole graph up front.
+//! Instead they start from one memory and ask for more as the user explores:
+//! a node's neighbours, a page at a time and strongest links first
+//! ([`ExploreRequest::Expand`]), its details ([`ExploreRequest::Details`]),
+//! and the other members of its community ([`ExploreRequest::Cluster`]).
+//! Everything is read from storage per request, so the size of the corpus
+//! only matters for the parts actually viewed.
+//!
+//! Expansions take the ids the client already shows (`known`): those aren't
+//! sent again, but links from the new nodes to them are, so the view stays
+//! connected. Communities come from the last stored clustering run
+//! (`memory_clusters`) when the memory is part of one, and are otherwise
+//! detected on the fly in its two-hop neighbourhood.
+//!
+//! The same requests are served by the `memory_graph_explore` tool, the HTTP
+//! transport's `/v1/graph/nodes/:id` routes and the graph WebSockets.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::{CommunityAlgorithm, GraphEdge, GraphNode, KnowledgeGraph, LabelOptions};
+use crate::error::{EngramError, Result};
+use crate::storage::queries::{get_related, peek_memory};
+use crate::storage::Storage;
+use crate::types::{Memory, MemoryId};
+
+/// Neighbours per expansion when the request doesn't say
+pub const DEFAULT_EXPAND_LIMIT: usize = 50;
+/// Most neighbours one expansion returns
+pub const MAX_EXPAND_LIMIT: usize = 500;
+/// Cluster members per page when the request doesn't say
+pub const DEFAULT_CLUSTER_LIMIT: usize = 100;
+/// Most cluster members one page returns
+pub const MAX_CLUSTER_LIMIT: usize = 1000;
+/// Memories loaded to detect a community on the fly
+const LOCAL_CLUSTER_NODES: usize = 300;
+
+/// One exploration step
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ExploreRequest {
+    /// A page of a node's neighbours and the links that connect them
+    Expand {
+        id: MemoryId,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        offset: usize,
+        /// Only follow these link types (all when empty)
+        #[serde(default)]
+        edge_types: Vec<String>,
+        /// Nodes the client already has
+        #[serde(default)]
+        known: Vec<MemoryId>,
+    },
+    /// A node with its content and link counts
+    Details { id: MemoryId },
+    /// A page of the members of a node's community
+    Cluster {
+        id: MemoryId,
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        offset: usize,
+    },
+}
+
+impl ExploreRequest {
+    /// The node the request is about
+    pub fn id(&self) -> MemoryId {
+        match self {
+            ExploreRequest::Expand { id, .. }
+            | ExploreRequest::Details { id }
+            | ExploreRequest::Cluster { id, .. } => *id,
+        }
+    }
+}
+
+/// Neighbours of a node, a page at a time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expansion {
+    pub center: MemoryId,
+    /// New nodes: the page's neighbours (and the center) the client didn't
+    /// list as known
+    pub nodes: Vec<GraphNode>,
+    /// Links from the center to the page's neighbours, and from the new
+    /// nodes to each other and to known nodes
+    pub edges: Vec<GraphEdge>,
+    /// Neighbours over all pages
+    pub total_neighbors: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// A node's community
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterMembership {
+    /// Stored cluster id, or the lowest member id for communities detected
+    /// on the fly
+    pub cluster_id: MemoryId,
+    /// Clustering algorithm of a stored cluster, or `local`
+    pub source: String,
+    pub size: usize,
+    /// This page of members, by id
+    pub members: Vec<GraphNode>,
+    pub next_offset: Option<usize>,
+}
+
+/// Everything a view shows for a selected node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeDetails {
+    pub node: GraphNode,
+    pub content: String,
+    pub workspace: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Live links touching the node
+    pub degree: usize,
+    /// Links per type
+    pub edge_types: BTreeMap<String, usize>,
+    /// The node's community, without its members
+    pub cluster: Option<ClusterMembership>,
+}
+
+/// Answer an exploration request
+pub fn explore(
+    conn: &Connection,
+    request: &ExploreRequest,
+    labels: &LabelOptions,
+) -> Result<serde_json::Value> {
+    Ok(match request {
+        ExploreRequest::Expand {
+            id,
+            limit,
+            offset,
+            edge_types,
+            known,
+        } => {
+            let limit = limit
+                .unwrap_or(DEFAULT_EXPAND_LIMIT)
+                .clamp(1, MAX_EXPAND_LIMIT);
+            serde_json::to_value(expand(
+                conn, *id, limit, *offset, edge_types, known, labels,
+            )?)?
+        }
+        ExploreRequest::Details { id } => serde_json::to_value(node_details(conn, *id, labels)?)?,
+        ExploreRequest::Cluster { id, limit, offset } => {
+            let limit = limit
+                .unwrap_or(DEFAULT_CLUSTER_LIMIT)
+                .clamp(1, MAX_CLUSTER_LIMIT);
+            serde_json::to_value(cluster_of(conn, *id, limit, *offset, labels)?)?
+        }
+    })
+}
+
+/// `limit` neighbours of `center` from `offset`, strongest link first
+pub fn expand(
+    conn: &Connection,
+    center: MemoryId,
+    limit: usize,
+    offset: usize,
+    edge_types: &[String],
+    known: &[MemoryId],
+    labels: &LabelOptions,
+) -> Result<Expansion> {
+    let center_memory = peek_memory(conn, center)?;
+    let follows = |edge: &GraphEdge| edge_types.is_empty() || edge_types.contains(&edge.edge_type);
+
+    // Strongest link to each neighbour
+    let mut strength: HashMap<MemoryId, f32> = HashMap::new();
+    let mut center_edges: Vec<GraphEdge> = Vec::new();
+    for crossref in get_related(conn, center)? {
+        let edge = GraphEdge::for_crossref(&crossref);
+        if !follows(&edge) {
+            continue;
+        }
+        let other = other_end(&edge, center);
+        if other == center {
+            continue;
+        }
+        let weight = crossref.score * crossref.confidence * crossref.strength;
+        let best = strength.entry(other).or_insert(weight);
+        *best = best.max(weight);
+        center_edges.push(edge);
+    }
+    let mut neighbors: Vec<(MemoryId, f32)> = strength.into_iter().collect();
+    neighbors.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let total_neighbors = neighbors.len();
+    let end = offset.saturating_add(limit).min(total_neighbors);
+    let page: Vec<MemoryId> = neighbors
+        .get(offset..end)
+        .unwrap_or_default()
+        .iter()
+        .map(|(id, _)| *id)
+        .collect();
+
+    let known: HashSet<MemoryId> = known.iter().copied().collect();
+    let mut nodes = Vec::new();
+    if !known.contains(&center) {
+        nodes.push(GraphNode::for_memory(&center_memory, labels));
+    }
+    let mut shown: HashSet<MemoryId> = HashSet::from([center]);
+    let mut new_ids = Vec::new();
+    for &id in &page {
+        if known.contains(&id) {
+            shown.insert(id);
+            continue;
+        }
+        match peek_memory(conn, id) {
+            Ok(memory) => {
+                nodes.push(GraphNode::for_memory(&memory, labels));
+                shown.insert(id);
+                new_ids.push(id);
+            }
+            Err(EngramError::NotFound(_)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut edges = Vec::new();
+    let mut seen: HashSet<(MemoryId, MemoryId, String)> = HashSet::new();
+    let mut add = |edge: GraphEdge, edges: &mut Vec<GraphEdge>| {
+        if seen.insert((edge.from, edge.to, edge.edge_type.clone())) {
+            edges.push(edge);
+        }
+    };
+    for edge in center_edges {
+        if shown.contains(&other_end(&edge, center)) {
+            add(edge, &mut edges);
+        }
+    }
+    for &id in &new_ids {
+        for crossref in get_related(conn, id)? {
+            let edge = GraphEdge::for_crossref(&crossref);
+            let other = other_end(&edge, id);
+            if follows(&edge) && (shown.contains(&other) || known.contains(&other)) {
+                add(edge, &mut edges);
+            }
+        }
+    }
+
+    Ok(Expansion {
+        center,
+        nodes,
+        edges,
+        total_neighbors,
+        next_offset: (end < total_neighbors).then_some(end),
+    })
+}
+
+/// A node with its content, link counts and community
+pub fn node_details(conn: &Connection, id: MemoryId, labels: &LabelOptions) -> Result<NodeDetails> {
+    let memory = peek_memory(conn, id)?;
+    let related = get_related(conn, id)?;
+    let mut edge_types: BTreeMap<String, usize> = BTreeMap::new();
+    for crossref in &related {
+        *edge_types
+            .entry(crossref.edge_type.as_str().to_string())
+            .or_insert(0) += 1;
+    }
+    let cluster = cluster_of(conn, id, 0, 0, labels)?;
+    let Memory {
+        content,
+        workspace,
+        created_at,
+        updated_at,
+        metadata,
+        ..
+    } = memory.clone();
+    Ok(NodeDetails {
+        node: GraphNode::for_memory(&memory, labels),
+        content,
+        workspace,
+        created_at,
+        updated_at,
+        metadata,
+        degree: related.len(),
+        edge_types,
+        cluster,
+    })
+}
+
+/// `limit` members of `id`'s community from `offset`; `None` when the
+/// memory has no links
+pub fn cluster_of(
+    conn: &Connection,
+    id: MemoryId,
+    limit: usize,
+    offset: usize,
+    labels: &LabelOptions,
+) -> Result<Option<ClusterMembership>> {
+    if let Some((cluster_id, algorithm, members)) = stored_cluster(conn, id)? {
+        let page = page_of(&members, limit, offset);
+        let mut nodes = Vec::with_capacity(page.len());
+        for &member in page {
+            match peek_memory(conn, member) {
+                Ok(memory) => nodes.push(GraphNode::for_memory(&memory, labels)),
+                Err(EngramError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        return Ok(Some(ClusterMembership {
+            cluster_id,
+            source: algorithm,
+            size: members.len(),
+            next_offset: next_offset(members.len(), limit, offset),
+            members: nodes,
+        }));
+    }
+
+    // Detect the community within the memory's neighbourhood
+    peek_memory(conn, id)?;
+    let graph = KnowledgeGraph::around(conn, id, 2, LOCAL_CLUSTER_NODES, labels)?;
+    if graph.edges.is_empty() {
+        return Ok(None);
+    }
+    let Some(cluster) = graph
+        .communities(CommunityAlgorithm::default())
+        .into_iter()
+        .find(|cluster| cluster.members.contains(&id))
+    else {
+        return Ok(None);
+    };
+    let mut members = cluster.members;
+    members.sort_unstable();
+    let nodes: HashMap<MemoryId, &GraphNode> = graph.nodes.iter().map(|n| (n.id, n)).collect();
+    Ok(Some(ClusterMembership {
+        cluster_id: members[0],
+        source: "local".to_string(),
+        size: members.len(),
+        next_offset: next_offset(members.len(), limit, offset),
+        members: page_of(&members, limit, offset)
+            .iter()
+            .filter_map(|member| nodes.get(member).map(|node| (*node).clone()))
+            .collect(),
+    }))
+}
+
+/// Latest stored cluster containing `id`: its id, algorithm and members
+fn stored_cluster(conn: &Connection, id: MemoryId) -> Result<Option<(i64, String, Vec<i64>)>> {
+    let found: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT cluster_id, algorithm FROM memory_clusters
+             WHERE memory_id = ?
+             ORDER BY created_at DESC, id DESC
+             LIMIT 1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((cluster_id, algorithm)) = found else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare_cached(
+        "SELECT DISTINCT memory_id FROM memory_clusters
+         WHERE cluster_id = ? AND algorithm = ?
+         ORDER BY memory_id",
+    )?;
+    let members = stmt
+        .query_map(params![cluster_id, algorithm], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<i64>, _>>()?;
+    Ok(Some((cluster_id, algorithm, members)))
+}
+
+fn other_end(edge: &GraphEdge, id: MemoryId) -> MemoryId {
+    if edge.from == id {
+        edge.to
+    } else {
+        edge.from
+    }
+}
+
+fn page_of<T>(items: &[T], limit: usize, offset: usize) -> &[T] {
+    let end = offset.saturating_add(limit).min(items.len());
+    items.get(offset..end).unwrap_or_default()
+}
+
+fn next_offset(total: usize, limit: usize, offset: usize) -> Option<usize> {
+    let end = offset.saturating_add(limit);
+    (limit > 0 && end < total).then_some(end)
+}
+
+/// Serves [`ExploreRequest`]s from storage, for the graph WebSockets
+pub struct GraphExplorer {
+    storage: Storage,
+    labels: LabelOptions,
+}
+
+impl GraphExplorer {
+    pub fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            labels: LabelOptions::default(),
+        }
+    }
+
+    pub fn explore(&self, request: &ExploreRequest) -> Result<serde_json::Value> {
+        self.storage
+            .with_connection(|conn| explore(conn, request, &self.labels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_crossref, create_memory};
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+
+    fn memory(storage: &Storage, content: &str) -> MemoryId {
+        storage
+            .with_transaction(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap()
+            .id
+    }
+
+    fn link(storage: &Storage, from: MemoryId, to: MemoryId, strength: f32) {
+        storage
+            .with_transaction(|conn| {
+                create_crossref(
+                    conn,
+                    &CreateCrossRefInput {
+                        from_id: from,
+                        to_id: to,
+                        edge_type: EdgeType::RelatedTo,
+                        strength: Some(strength),
+                        source_context: None,
+                        pinned: false,
+                    },
+                )
+            })
+            .unwrap();
+    }
+
+    fn explore_json(storage: &Storage, request: serde_json::Value) -> serde_json::Value {
+        let request: ExploreRequest = serde_json::from_value(request).unwrap();
+        GraphExplorer::new(storage.clone())
+            .explore(&request)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_expand_pages_neighbours_and_links_known_nodes() {
+        let storage = Storage::open_in_memory().unwrap();
+        let hub = memory(&storage, "hub");
+        let a = memory(&storage, "a");
+        let b = memory(&storage, "b");
+        let c = memory(&storage, "c");
+        link(&storage, hub, a, 0.5);
+        link(&storage, hub, b, 0.9);
+        link(&storage, c, hub, 0.1);
+        link(&storage, b, c, 0.7);
+
+        let first = explore_json(
+            &storage,
+            serde_json::json!({"action": "expand", "id": hub, "limit": 2}),
+        );
+        let first: Expansion = serde_json::from_value(first).unwrap();
+        let ids: Vec<MemoryId> = first.nodes.iter().map(|n| n.id).collect();
+        // Strongest link first
+        assert_eq!(ids, vec![hub, b, a]);
+        assert_eq!(first.total_neighbors, 3);
+        assert_eq!(first.next_offset, Some(2));
+        assert_eq!(first.edges.len(), 2);
+
+        // The next page only sends `c`, with its links to shown nodes
+        let second: Expansion = serde_json::from_value(explore_json(
+            &storage,
+            serde_json::json!({"action": "expand", "id": hub, "limit": 2, "offset": 2, "known": [hub, a, b]}),
+        ))
+        .unwrap();
+        assert_eq!(second.nodes.len(), 1);
+        assert_eq!(second.nodes[0].id, c);
+        assert_eq!(second.next_offset, None);
+        let mut pairs: Vec<(MemoryId, MemoryId)> =
+            second.edges.iter().map(|e| (e.from, e.to)).collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![(b, c), (c, hub)]);
+    }
+
+    #[test]
+    fn test_details_and_cluster() {
+        let storage = Storage::open_in_memory().unwrap();
+        let ids: Vec<MemoryId> = (0..6)
+            .map(|i| memory(&storage, &format!("memory {i}")))
+            .collect();
+        // Two triangles joined by one weak link
+        for (from, to) in [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)] {
+            link(&storage, ids[from], ids[to], 0.9);
+        }
+        link(&storage, ids[2], ids[3], 0.1);
+        let lonely = memory(&storage, "no links");
+
+        let details: NodeDetails = serde_json::from_value(explore_json(
+            &storage,
+            serde_json::json!({"action": "details", "id": ids[2]}),
+        ))
+        .unwrap();
+        assert_eq!(details.content, "memory 2");
+        assert_eq!(details.degree, 3);
+        assert_eq!(details.edge_types["related_to"], 3);
+        let summary = details.cluster.unwrap();
+        assert_eq!(summary.source, "local");
+        assert_eq!(summary.size, 3);
+        assert!(summary.members.is_empty());
+
+        let cluster: ClusterMembership = serde_json::from_value(explore_json(
+            &storage,
+            serde_json::json!({"action": "cluster", "id": ids[3], "limit": 2}),
+        ))
+        .unwrap();
+        assert_eq!(cluster.cluster_id, ids[3]);
+        let members: Vec<MemoryId> = cluster.members.iter().map(|n| n.id).collect();
+        assert_eq!(members, vec![ids[3], ids[4]]);
+        assert_eq!(cluster.next_offset, Some(2));
+
+        // A stored clustering run takes precedence
+        storage
+            .with_connection(|conn| {
+                for id in [ids[0], ids[5]] {
+                    conn.execute(
+                        "INSERT INTO memory_clusters (cluster_id, memory_id, algorithm) VALUES (7, ?, 'louvain')",
+                        params![id],
+                    )?;
+                }
+                Ok(())
+            })
+            .unwrap();
+        let stored: ClusterMembership = serde_json::from_value(explore_json(
+            &storage,
+            serde_json::json!({"action": "cluster", "id": ids[5]}),
+        ))
+        .unwrap();
+        assert_eq!((stored.cluster_id, stored.source.as_str()), (7, "louvain"));
+        assert_eq!(stored.size, 2);
+
+        assert!(explore_json(
+            &storage,
+            serde_json::json!({"action": "cluster", "id": lonely})
+        )
+        .is_null());
+    }
+}
@@ -0,0 +1,184 @@
+//! Node labels for graph exports and compact listings
+//!
+//! Labels are cut on grapheme cluster boundaries, so multi-byte characters,
+//! combining accents and emoji sequences are never split.
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::types::Memory;
+
+/// Default label length, in grapheme clusters
+pub const DEFAULT_LABEL_LENGTH: usize = 50;
+
+/// Appended when a label is cut short
+const ELLIPSIS: &str = "...";
+
+/// Where a memory's label text comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelSource {
+    /// First non-empty line of the content
+    #[default]
+    FirstLine,
+    /// `title` metadata, falling back to the first line
+    Title,
+    /// `summary` metadata, falling back to the first sentence
+    Summary,
+}
+
+impl std::str::FromStr for LabelSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first_line" => Ok(LabelSource::FirstLine),
+            "title" => Ok(LabelSource::Title),
+            "summary" => Ok(LabelSource::Summary),
+            _ => Err(format!("Unknown label source: {}", s)),
+        }
+    }
+}
+
+/// How node labels are built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelOptions {
+    /// Maximum label length in grapheme clusters, ellipsis included
+    pub max_length: usize,
+    pub source: LabelSource,
+}
+
+impl Default for LabelOptions {
+    fn default() -> Self {
+        Self {
+            max_length: DEFAULT_LABEL_LENGTH,
+            source: LabelSource::FirstLine,
+        }
+    }
+}
+
+impl LabelOptions {
+    /// Label for a memory
+    pub fn label_for(&self, memory: &Memory) -> String {
+        let metadata = |key: &str| {
+            memory
+                .metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        let text = match self.source {
+            LabelSource::FirstLine => first_line(&memory.content),
+            LabelSource::Title => metadata("title")
+                .map(first_line)
+                .unwrap_or_else(|| first_line(&memory.content)),
+            LabelSource::Summary => metadata("summary")
+                .map(first_line)
+                .unwrap_or_else(|| first_sentence(&memory.content)),
+        };
+        truncate_graphemes(text, self.max_length)
+    }
+}
+
+/// First non-empty line, trimmed
+pub fn first_line(text: &str) -> &str {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+}
+
+/// First sentence of the first paragraph
+fn first_sentence(text: &str) -> &str {
+    let line = first_line(text);
+    line.unicode_sentences()
+        .next()
+        .map(str::trim)
+        .unwrap_or(line)
+}
+
+/// First line of `content`, cut to `max_len` graphemes
+pub fn truncate_label(content: &str, max_len: usize) -> String {
+    truncate_graphemes(first_line(content), max_len)
+}
+
+/// Cut `text` to at most `max` grapheme clusters, ending in `...` when
+/// shortened. Limits too small for an ellipsis cut without one.
+pub fn truncate_graphemes(text: &str, max: usize) -> String {
+    let offset = |n: usize| text.grapheme_indices(true).nth(n).map(|(i, _)| i);
+    if offset(max).is_none() {
+        return text.to_string();
+    }
+    if max <= ELLIPSIS.len() {
+        return text[..offset(max).unwrap_or(text.len())].to_string();
+    }
+    let cut = offset(max - ELLIPSIS.len()).unwrap_or(text.len());
+    format!("{}{}", text[..cut].trim_end(), ELLIPSIS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn memory(content: &str) -> Memory {
+        serde_json::from_value(json!({
+            "id": 1,
+            "content": content,
+            "type": "note",
+            "importance": 0.5,
+            "access_count": 0,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_truncate_graphemes_never_splits_characters() {
+        assert_eq!(truncate_graphemes("short", 10), "short");
+        assert_eq!(truncate_graphemes("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_graphemes("Ünïcödé téxt hère", 10), "Ünïcödé...");
+        // Family emoji is one grapheme made of several code points.
+        let family = "👨‍👩‍👧‍👦";
+        let text = format!("{family}{family}{family}{family}{family}");
+        assert_eq!(truncate_graphemes(&text, 4), format!("{family}..."));
+        // e + combining acute stays together.
+        assert_eq!(
+            truncate_graphemes("e\u{301}e\u{301}e\u{301}", 2),
+            "e\u{301}e\u{301}"
+        );
+        assert_eq!(truncate_graphemes("日本語のテキストです", 6), "日本語...");
+    }
+
+    #[test]
+    fn test_label_sources() {
+        let mut m = memory("\n  Deploy plan for v2. Roll out gradually.\nSecond line");
+        let opts = |source| LabelOptions {
+            max_length: 50,
+            source,
+        };
+
+        assert_eq!(
+            opts(LabelSource::FirstLine).label_for(&m),
+            "Deploy plan for v2. Roll out gradually."
+        );
+        assert_eq!(
+            opts(LabelSource::Summary).label_for(&m),
+            "Deploy plan for v2."
+        );
+        assert_eq!(
+            opts(LabelSource::Title).label_for(&m),
+            "Deploy plan for v2. Roll out gradually."
+        );
+
+        m.metadata.insert("title".into(), json!("Release v2"));
+        m.metadata.insert("summary".into(), json!("Staged rollout"));
+        assert_eq!(opts(LabelSource::Title).label_for(&m), "Release v2");
+        assert_eq!(opts(LabelSource::Summary).label_for(&m), "Staged rollout");
+
+        assert_eq!("summary".parse::<LabelSource>(), Ok(LabelSource::Summary));
+        assert!("heading".parse::<LabelSource>().is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Server-side force-directed layout (Fruchterman–Reingold)
+//!
+//! vis.js runs its physics simulation in the browser, which stops being
+//! usable somewhere past a few thousand nodes. This computes positions in
+//! Rust instead, so exports can hand vis.js fixed `x`/`y` coordinates and
+//! switch physics off.
+//!
+//! Neighbours attract with `d² / k` and every pair repels with `k² / d`,
+//! where `k` is the ideal edge length (Fruchterman & Reingold, 1991).
+//! Repulsion uses the paper's grid variant — only nodes within `2k` push on
+//! each other — so an iteration is roughly linear in the graph size rather
+//! than quadratic. A weak pull towards the origin keeps disconnected parts
+//! from drifting apart. Starting positions come from a seeded RNG, so a given
+//! graph and config always produce the same layout.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::CompactGraph;
+use crate::types::MemoryId;
+
+/// Nodes closer than this are nudged apart before dividing by the distance
+const MIN_DISTANCE: f64 = 0.01;
+
+/// Parameters for [`CompactGraph::force_layout`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Simulation steps; the step size cools linearly to zero over them
+    pub iterations: usize,
+    /// Ideal distance between neighbours, in vis.js canvas units
+    pub edge_length: f64,
+    /// Strength of the pull towards the origin
+    pub gravity: f64,
+    pub seed: u64,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            edge_length: 100.0,
+            gravity: 0.02,
+            seed: 0,
+        }
+    }
+}
+
+/// Node positions keyed by memory, centred on the origin
+#[derive(Debug, Clone, Default)]
+pub struct GraphLayout {
+    positions: HashMap<MemoryId, (f64, f64)>,
+}
+
+impl GraphLayout {
+    /// Position of a memory
+    pub fn get(&self, id: MemoryId) -> Option<(f64, f64)> {
+        self.positions.get(&id).copied()
+    }
+
+    /// Number of positioned nodes
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl CompactGraph<'_> {
+    /// Lay the graph out with Fruchterman–Reingold
+    pub fn force_layout(&self, config: &LayoutConfig) -> GraphLayout {
+        let n = self.node_count();
+        if n == 0 {
+            return GraphLayout::default();
+        }
+
+        let k = config.edge_length.max(1.0);
+        let side = k * (n as f64).sqrt();
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut pos: Vec<(f64, f64)> = (0..n)
+            .map(|_| {
+                (
+                    rng.gen_range(-0.5..0.5) * side,
+                    rng.gen_range(-0.5..0.5) * side,
+                )
+            })
+            .collect();
+        let mut disp = vec![(0.0f64, 0.0f64); n];
+        let cell = 2.0 * k;
+        let initial_temperature = side / 10.0;
+        let iterations = config.iterations.max(1);
+
+        for step in 0..iterations {
+            disp.iter_mut().for_each(|d| *d = (0.0, 0.0));
+
+            // Repulsion between nodes sharing or bordering a grid cell
+            let mut grid: HashMap<(i64, i64), Vec<u32>> = HashMap::new();
+            for (i, &(x, y)) in pos.iter().enumerate() {
+                grid.entry(cell_of(x, y, cell)).or_default().push(i as u32);
+            }
+            for (i, &(x, y)) in pos.iter().enumerate() {
+                let (cx, cy) = cell_of(x, y, cell);
+                for gx in cx - 1..=cx + 1 {
+                    for gy in cy - 1..=cy + 1 {
+                        let Some(members) = grid.get(&(gx, gy)) else {
+                            continue;
+                        };
+                        for &j in members {
+                            let j = j as usize;
+                            if j == i {
+                                continue;
+                            }
+                            let (dx, dy, d) = offset(pos[i], pos[j], i, j);
+                            if d < cell {
+                                let force = k * k / d;
+                                disp[i].0 += dx / d * force;
+                                disp[i].1 += dy / d * force;
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Attraction along edges, each undirected pair once
+            for i in 0..n {
+                for &j in self.neighbors(i) {
+                    let j = j as usize;
+                    if j <= i {
+                        continue;
+                    }
+                    let (dx, dy, d) = offset(pos[i], pos[j], i, j);
+                    let force = d * d / k;
+                    disp[i].0 -= dx / d * force;
+                    disp[i].1 -= dy / d * force;
+                    disp[j].0 += dx / d * force;
+                    disp[j].1 += dy / d * force;
+                }
+            }
+
+            // Gravity, then move each node at most `temperature`
+            let temperature = initial_temperature * (1.0 - step as f64 / iterations as f64);
+            for (p, d) in pos.iter_mut().zip(&mut disp) {
+                d.0 -= config.gravity * p.0;
+                d.1 -= config.gravity * p.1;
+                let length = (d.0 * d.0 + d.1 * d.1).sqrt();
+                if length > 0.0 {
+                    let moved = length.min(temperature);
+                    p.0 += d.0 / length * moved;
+                    p.1 += d.1 / length * moved;
+                }
+            }
+        }
+
+        let (cx, cy) = pos
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+        let (cx, cy) = (cx / n as f64, cy / n as f64);
+        let mut positions = HashMap::with_capacity(n);
+        for (i, &(x, y)) in pos.iter().enumerate() {
+            positions.entry(self.node_id(i)).or_insert((x - cx, y - cy));
+        }
+        GraphLayout { positions }
+    }
+}
+
+fn cell_of(x: f64, y: f64, cell: f64) -> (i64, i64) {
+    ((x / cell).floor() as i64, (y / cell).floor() as i64)
+}
+
+/// Vector from `b` to `a` and its length. Coincident nodes get a small
+/// index-derived offset so they separate deterministically.
+fn offset(a: (f64, f64), b: (f64, f64), i: usize, j: usize) -> (f64, f64, f64) {
+    let (mut dx, mut dy) = (a.0 - b.0, a.1 - b.1);
+    let mut d = (dx * dx + dy * dy).sqrt();
+    if d < MIN_DISTANCE {
+        let angle = (i.min(j) * 31 + i.max(j) * 17) as f64;
+        dx = angle.cos() * MIN_DISTANCE;
+        dy = angle.sin() * MIN_DISTANCE;
+        if i < j {
+            dx = -dx;
+            dy = -dy;
+        }
+        d = MIN_DISTANCE;
+    }
+    (dx, dy, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphEdge, GraphNode, KnowledgeGraph};
+
+    fn node(id: MemoryId) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance: 0.5,
+            tags: vec![],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: "related_to".to_string(),
+            score: 1.0,
+            confidence: 1.0,
+        }
+    }
+
+    fn distance(layout: &GraphLayout, a: MemoryId, b: MemoryId) -> f64 {
+        let (ax, ay) = layout.get(a).unwrap();
+        let (bx, by) = layout.get(b).unwrap();
+        ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt()
+    }
+
+    /// Two 5-cliques (1–5 and 6–10) joined by a single 5–6 bridge, plus an
+    /// isolated node 11
+    fn barbell() -> KnowledgeGraph {
+        let mut edges = Vec::new();
+        for clique in [1..=5, 6..=10] {
+            let ids: Vec<MemoryId> = clique.collect();
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    edges.push(edge(a, b));
+                }
+            }
+        }
+        edges.push(edge(5, 6));
+        KnowledgeGraph {
+            nodes: (1..=11).map(node).collect(),
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_cliques_are_laid_out_apart() {
+        let layout = barbell().compact().force_layout(&LayoutConfig::default());
+
+        assert_eq!(layout.len(), 11);
+        let within = distance(&layout, 1, 2);
+        let across = distance(&layout, 1, 9);
+        assert!(
+            within < across,
+            "clique members {within:.1} apart, other clique {across:.1}"
+        );
+        // Neighbours settle near the ideal edge length
+        assert!(within > 10.0 && within < 300.0, "{within}");
+    }
+
+    #[test]
+    fn test_same_seed_same_layout() {
+        let graph = barbell();
+        let compact = graph.compact();
+        let a = compact.force_layout(&LayoutConfig::default());
+        let b = compact.force_layout(&LayoutConfig::default());
+        for id in 1..=11 {
+            assert_eq!(a.get(id), b.get(id));
+        }
+    }
+
+    #[test]
+    fn test_layout_of_empty_and_coincident_graphs() {
+        let empty = KnowledgeGraph {
+            nodes: vec![],
+            edges: vec![],
+        };
+        assert!(empty
+            .compact()
+            .force_layout(&LayoutConfig::default())
+            .is_empty());
+
+        // Duplicate ids share the first occurrence's position
+        let graph = KnowledgeGraph {
+            nodes: vec![node(1), node(1), node(2)],
+            edges: vec![edge(1, 2)],
+        };
+        let layout = graph.compact().force_layout(&LayoutConfig::default());
+        assert_eq!(layout.len(), 2);
+        assert!(distance(&layout, 1, 2) > 0.0);
+    }
+}
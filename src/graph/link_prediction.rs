@@ -0,0 +1,259 @@
+//! Link prediction from graph topology
+//!
+//! Two memories that are not linked but share neighbours in the crossref
+//! graph are candidates for a link of their own. Each candidate pair is
+//! scored by the overlap of its neighbourhoods (Liben-Nowell & Kleinberg,
+//! 2003):
+//!
+//! - common neighbours: `|N(u) ∩ N(v)|`
+//! - Adamic-Adar: `Σ 1 / ln |N(w)|` over the shared neighbours `w`, so a
+//!   shared hub counts for less than a shared niche memory
+//! - Jaccard: `|N(u) ∩ N(v)| / |N(u) ∪ N(v)|`
+//!
+//! Neighbourhoods ignore edge direction, type and weight. Only pairs two
+//! hops apart can share a neighbour, so candidates are found by walking
+//! neighbours of neighbours instead of scoring every pair.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::CompactGraph;
+use crate::types::MemoryId;
+
+/// Score used to rank suggested links
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkScore {
+    /// Number of shared neighbours
+    CommonNeighbors,
+    /// Shared neighbours weighted by inverse log degree
+    #[default]
+    AdamicAdar,
+    /// Shared neighbours over the union of both neighbourhoods
+    Jaccard,
+}
+
+impl LinkScore {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkScore::CommonNeighbors => "common_neighbors",
+            LinkScore::AdamicAdar => "adamic_adar",
+            LinkScore::Jaccard => "jaccard",
+        }
+    }
+}
+
+impl std::str::FromStr for LinkScore {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "common_neighbors" => Ok(LinkScore::CommonNeighbors),
+            "adamic_adar" => Ok(LinkScore::AdamicAdar),
+            "jaccard" => Ok(LinkScore::Jaccard),
+            _ => Err(format!("Unknown link score: {}", s)),
+        }
+    }
+}
+
+/// A pair of unlinked memories and how strongly their neighbourhoods overlap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkSuggestion {
+    pub from: MemoryId,
+    pub to: MemoryId,
+    /// Value of the score the suggestions were ranked by
+    pub score: f64,
+    pub common_neighbors: usize,
+    pub adamic_adar: f64,
+    pub jaccard: f64,
+}
+
+/// Shared-neighbour tallies of one candidate pair
+#[derive(Default)]
+struct Overlap {
+    common: usize,
+    adamic_adar: f64,
+}
+
+impl CompactGraph<'_> {
+    /// The `limit` best-scoring links missing from the graph, best first.
+    ///
+    /// With a `source` node only links from it are considered, and `from` is
+    /// always the source; otherwise every unlinked pair sharing a neighbour
+    /// is, with `from` the lower memory ID. Ties go to the lower IDs.
+    pub fn suggest_links(
+        &self,
+        score: LinkScore,
+        source: Option<usize>,
+        limit: usize,
+    ) -> Vec<LinkSuggestion> {
+        let sources: Vec<usize> = match source {
+            Some(u) => vec![u],
+            None => (0..self.node_count()).collect(),
+        };
+        let mut suggestions = Vec::new();
+        for u in sources {
+            for (v, overlap) in self.two_hop_overlaps(u) {
+                // Each unordered pair once when scanning every node
+                if source.is_none() && self.node_id(v) < self.node_id(u) {
+                    continue;
+                }
+                suggestions.push(self.suggestion(u, v, &overlap, score));
+            }
+        }
+        suggestions.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.from.cmp(&b.from))
+                .then(a.to.cmp(&b.to))
+        });
+        suggestions.truncate(limit);
+        suggestions
+    }
+
+    /// Scores of a single pair, linked or not
+    pub fn link_scores(&self, u: usize, v: usize, score: LinkScore) -> LinkSuggestion {
+        let mut overlap = Overlap::default();
+        let neighbors_v = self.neighbors(v);
+        for &w in self.neighbors(u) {
+            if neighbors_v.binary_search(&w).is_ok() {
+                overlap.common += 1;
+                overlap.adamic_adar += self.adamic_adar_weight(w as usize);
+            }
+        }
+        self.suggestion(u, v, &overlap, score)
+    }
+
+    /// Unlinked nodes sharing at least one neighbour with `u`
+    fn two_hop_overlaps(&self, u: usize) -> HashMap<usize, Overlap> {
+        let neighbors_u = self.neighbors(u);
+        let mut overlaps: HashMap<usize, Overlap> = HashMap::new();
+        for &w in neighbors_u {
+            let weight = self.adamic_adar_weight(w as usize);
+            for &v in self.neighbors(w as usize) {
+                if v as usize == u || neighbors_u.binary_search(&v).is_ok() {
+                    continue;
+                }
+                let overlap = overlaps.entry(v as usize).or_default();
+                overlap.common += 1;
+                overlap.adamic_adar += weight;
+            }
+        }
+        overlaps
+    }
+
+    /// A shared neighbour has at least the two nodes of the pair as
+    /// neighbours, so the logarithm is never zero
+    fn adamic_adar_weight(&self, w: usize) -> f64 {
+        1.0 / (self.neighbors(w).len().max(2) as f64).ln()
+    }
+
+    fn suggestion(
+        &self,
+        u: usize,
+        v: usize,
+        overlap: &Overlap,
+        score: LinkScore,
+    ) -> LinkSuggestion {
+        let union = self.neighbors(u).len() + self.neighbors(v).len() - overlap.common;
+        let jaccard = if union > 0 {
+            overlap.common as f64 / union as f64
+        } else {
+            0.0
+        };
+        let (from, to) = (self.node_id(u), self.node_id(v));
+        LinkSuggestion {
+            from,
+            to,
+            score: match score {
+                LinkScore::CommonNeighbors => overlap.common as f64,
+                LinkScore::AdamicAdar => overlap.adamic_adar,
+                LinkScore::Jaccard => jaccard,
+            },
+            common_neighbors: overlap.common,
+            adamic_adar: overlap.adamic_adar,
+            jaccard,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphEdge, GraphNode, KnowledgeGraph};
+
+    fn node(id: MemoryId) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance: 0.5,
+            tags: vec![],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: "related_to".to_string(),
+            score: 1.0,
+            confidence: 1.0,
+        }
+    }
+
+    /// 1 and 2 share neighbours 3 and 4; 4 is also a hub linked to 5–8, and
+    /// 1 shares only the hub with 5
+    fn graph() -> KnowledgeGraph {
+        let mut edges = vec![edge(1, 3), edge(1, 4), edge(3, 2), edge(4, 2)];
+        edges.extend((5..=8).map(|id| edge(4, id)));
+        KnowledgeGraph {
+            nodes: (1..=8).map(node).collect(),
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_scores() {
+        let graph = graph();
+        let compact = graph.compact();
+        let (one, two) = (compact.index_of(1).unwrap(), compact.index_of(2).unwrap());
+
+        let pair = compact.link_scores(one, two, LinkScore::CommonNeighbors);
+        assert_eq!(pair.common_neighbors, 2);
+        assert_eq!(pair.score, 2.0);
+        assert!((pair.jaccard - 1.0).abs() < 1e-9);
+        // 3 has 2 neighbours, the hub 4 has 6
+        let expected = 1.0 / 2f64.ln() + 1.0 / 6f64.ln();
+        assert!((pair.adamic_adar - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_suggestions_skip_existing_links() {
+        let graph = graph();
+        let compact = graph.compact();
+        let suggestions = compact.suggest_links(LinkScore::AdamicAdar, None, 100);
+
+        // 3 and 4 share two low-degree neighbours; 1 and 2 share the hub
+        assert_eq!((suggestions[0].from, suggestions[0].to), (3, 4));
+        assert_eq!((suggestions[1].from, suggestions[1].to), (1, 2));
+        for s in &suggestions {
+            assert!(s.from < s.to);
+            let linked = graph
+                .edges
+                .iter()
+                .any(|e| (e.from, e.to) == (s.from, s.to) || (e.from, e.to) == (s.to, s.from));
+            assert!(!linked, "{:?} is already linked", s);
+        }
+        // Besides those two, each spoke pairs with 1, 2 and the other spokes
+        assert_eq!(suggestions.len(), 2 + 8 + 6);
+
+        let one = compact.index_of(1).unwrap();
+        let from_one = compact.suggest_links(LinkScore::Jaccard, Some(one), 2);
+        assert_eq!(from_one.len(), 2);
+        assert_eq!(from_one[0].to, 2);
+        assert_eq!((from_one[1].from, from_one[1].to), (1, 5));
+        assert!(from_one[1].score < from_one[0].score);
+    }
+}
@@ -0,0 +1,288 @@
+//! Multi-level Louvain community detection (Blondel et al., 2008)
+//!
+//! Runs on the weighted CSR adjacency produced by
+//! [`CompactGraph`](super::CompactGraph). Each level moves nodes greedily to
+//! the neighbouring community with the best modularity gain, then collapses
+//! the communities into super-nodes and repeats until nothing moves. The
+//! visiting order is shuffled with a seeded RNG, so a given seed always
+//! produces the same partition.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Gains smaller than this are treated as ties (the node stays put)
+const GAIN_EPSILON: f64 = 1e-12;
+
+/// Safety cap on local-moving sweeps per level
+const MAX_SWEEPS: usize = 100;
+
+/// Undirected weighted graph in CSR form
+#[derive(Debug, Clone)]
+pub(super) struct WeightedCsr {
+    /// `targets[offsets[i]..offsets[i + 1]]` are the neighbours of node `i`
+    pub offsets: Vec<usize>,
+    pub targets: Vec<u32>,
+    pub weights: Vec<f64>,
+    /// Self-loop weight per node, counted from both ends
+    pub self_loops: Vec<f64>,
+}
+
+impl WeightedCsr {
+    fn node_count(&self) -> usize {
+        self.self_loops.len()
+    }
+
+    fn edges(&self, v: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.offsets[v]..self.offsets[v + 1];
+        self.targets[range.clone()]
+            .iter()
+            .map(|&t| t as usize)
+            .zip(self.weights[range].iter().copied())
+    }
+
+    /// Weighted degree of every node, self-loops included
+    pub fn strengths(&self) -> Vec<f64> {
+        (0..self.node_count())
+            .map(|v| self.edges(v).map(|(_, w)| w).sum::<f64>() + self.self_loops[v])
+            .collect()
+    }
+
+    /// Collapse each community into a single node
+    fn aggregate(&self, community: &[u32], community_count: usize) -> WeightedCsr {
+        let mut buckets: Vec<Vec<(u32, f64)>> = vec![Vec::new(); community_count];
+        let mut self_loops = vec![0.0; community_count];
+        for v in 0..self.node_count() {
+            let cv = community[v] as usize;
+            self_loops[cv] += self.self_loops[v];
+            for (w, weight) in self.edges(v) {
+                let cw = community[w];
+                if cw as usize == cv {
+                    // Seen once from each end, matching the self-loop convention.
+                    self_loops[cv] += weight;
+                } else {
+                    buckets[cv].push((cw, weight));
+                }
+            }
+        }
+
+        let mut offsets = Vec::with_capacity(community_count + 1);
+        let mut targets = Vec::new();
+        let mut weights = Vec::new();
+        offsets.push(0);
+        for mut bucket in buckets {
+            bucket.sort_unstable_by_key(|&(target, _)| target);
+            let start = targets.len();
+            for (target, weight) in bucket {
+                if targets.len() > start && targets[targets.len() - 1] == target {
+                    *weights.last_mut().unwrap() += weight;
+                } else {
+                    targets.push(target);
+                    weights.push(weight);
+                }
+            }
+            offsets.push(targets.len());
+        }
+
+        WeightedCsr {
+            offsets,
+            targets,
+            weights,
+            self_loops,
+        }
+    }
+}
+
+/// Community label for every node of `graph`, numbered densely from 0 in
+/// order of each community's first node
+pub(super) fn louvain(graph: &WeightedCsr, resolution: f64, seed: u64) -> Vec<u32> {
+    let n = graph.node_count();
+    let mut membership: Vec<u32> = (0..n as u32).collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut level = graph.clone();
+    loop {
+        let (community, moved) = local_moving(&level, resolution, &mut rng);
+        if !moved {
+            break;
+        }
+        let (community, count) = renumber(&community);
+        for label in &mut membership {
+            *label = community[*label as usize];
+        }
+        if count == level.node_count() {
+            break;
+        }
+        level = level.aggregate(&community, count);
+    }
+
+    renumber(&membership).0
+}
+
+/// One Louvain phase: greedily move nodes between neighbouring communities
+/// until no move improves modularity. Returns the assignment and whether any
+/// node changed community.
+fn local_moving(graph: &WeightedCsr, resolution: f64, rng: &mut StdRng) -> (Vec<u32>, bool) {
+    let n = graph.node_count();
+    let mut community: Vec<u32> = (0..n as u32).collect();
+    let strength = graph.strengths();
+    let two_m: f64 = strength.iter().sum();
+    if two_m <= 0.0 {
+        return (community, false);
+    }
+
+    // Σ_tot: total strength of the nodes in each community
+    let mut total = strength.clone();
+    let mut link_weight = vec![0.0f64; n];
+    let mut touched: Vec<u32> = Vec::new();
+    let mut is_touched = vec![false; n];
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+
+    let mut moved_any = false;
+    for _ in 0..MAX_SWEEPS {
+        let mut moved = false;
+        for &v in &order {
+            let current = community[v];
+            let k = strength[v];
+
+            for (w, weight) in graph.edges(v) {
+                let c = community[w];
+                if !is_touched[c as usize] {
+                    is_touched[c as usize] = true;
+                    touched.push(c);
+                }
+                link_weight[c as usize] += weight;
+            }
+
+            // Take v out of its community, then put it back wherever the gain
+            // ΔQ ∝ k_i,in − γ·Σ_tot·k_i / 2m is largest.
+            total[current as usize] -= k;
+            let gain =
+                |c: u32| link_weight[c as usize] - resolution * total[c as usize] * k / two_m;
+            let mut best = current;
+            let mut best_gain = gain(current);
+            for &c in &touched {
+                let g = gain(c);
+                if g > best_gain + GAIN_EPSILON {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+            total[best as usize] += k;
+
+            for c in touched.drain(..) {
+                is_touched[c as usize] = false;
+                link_weight[c as usize] = 0.0;
+            }
+
+            if best != current {
+                community[v] = best;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+        moved_any = true;
+    }
+
+    (community, moved_any)
+}
+
+/// Map arbitrary labels onto `0..count` in order of first appearance
+fn renumber(labels: &[u32]) -> (Vec<u32>, usize) {
+    let mut mapping = vec![u32::MAX; labels.len()];
+    let mut next = 0u32;
+    let renumbered = labels
+        .iter()
+        .map(|&label| {
+            let slot = &mut mapping[label as usize];
+            if *slot == u32::MAX {
+                *slot = next;
+                next += 1;
+            }
+            *slot
+        })
+        .collect();
+    (renumbered, next as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a CSR graph from unit-weight undirected edges
+    fn csr(n: usize, edges: &[(u32, u32)]) -> WeightedCsr {
+        let mut buckets: Vec<Vec<u32>> = vec![Vec::new(); n];
+        for &(a, b) in edges {
+            buckets[a as usize].push(b);
+            buckets[b as usize].push(a);
+        }
+        let mut offsets = vec![0];
+        let mut targets = Vec::new();
+        for mut bucket in buckets {
+            bucket.sort_unstable();
+            targets.extend(bucket);
+            offsets.push(targets.len());
+        }
+        WeightedCsr {
+            weights: vec![1.0; targets.len()],
+            offsets,
+            targets,
+            self_loops: vec![0.0; n],
+        }
+    }
+
+    #[test]
+    fn test_ring_of_cliques() {
+        // Six 4-cliques joined in a ring by single edges
+        let mut edges = Vec::new();
+        for clique in 0..6u32 {
+            let base = clique * 4;
+            for a in 0..4 {
+                for b in a + 1..4 {
+                    edges.push((base + a, base + b));
+                }
+            }
+            edges.push((base + 3, (base + 4) % 24));
+        }
+        let graph = csr(24, &edges);
+
+        let labels = louvain(&graph, 1.0, 7);
+        for clique in 0..6 {
+            let members = &labels[clique * 4..clique * 4 + 4];
+            assert!(members.iter().all(|&l| l == members[0]));
+        }
+        let distinct: std::collections::HashSet<u32> = labels.iter().copied().collect();
+        assert_eq!(distinct.len(), 6);
+    }
+
+    #[test]
+    fn test_same_seed_same_partition() {
+        let edges: Vec<(u32, u32)> = (0..40u32)
+            .flat_map(|i| [(i, (i + 1) % 40), (i, (i * 7 + 3) % 40)])
+            .filter(|(a, b)| a != b)
+            .collect();
+        let graph = csr(40, &edges);
+        assert_eq!(louvain(&graph, 1.0, 42), louvain(&graph, 1.0, 42));
+    }
+
+    #[test]
+    fn test_aggregate_preserves_total_strength() {
+        let graph = csr(4, &[(0, 1), (1, 2), (2, 3)]);
+        let merged = graph.aggregate(&[0, 0, 1, 1], 2);
+        assert_eq!(merged.self_loops, vec![2.0, 2.0]);
+        assert_eq!(merged.targets, vec![1, 0]);
+        let before: f64 = graph.strengths().iter().sum();
+        let after: f64 = merged.strengths().iter().sum();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_edgeless_graph_keeps_singletons() {
+        let graph = csr(3, &[]);
+        assert_eq!(louvain(&graph, 1.0, 0), vec![0, 1, 2]);
+    }
+}
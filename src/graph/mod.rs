@@ -4,25 +4,69 @@
 //! - Interactive graph visualization with vis.js
 //! - Graph clustering and community detection
 //! - Graph statistics and metrics
-//! - Export to multiple formats (HTML, DOT, JSON)
+//! - Export to multiple formats (HTML, DOT, GEXF, GraphML, JSON)
 //! - Filtering and traversal utilities
 //! - Temporal knowledge graph with validity periods (RML-1235)
-
+//! - Structural node embeddings (node2vec)
+//! - Server-side force-directed layout for large exports
+//! - Entity and identity nodes alongside memories (bipartite exports)
+//! - Merging parallel edges for readable exports
+//! - Static SVG/PNG rendering without JavaScript
+//! - Streaming JSON/GraphML export straight to a file
+//! - Tag taxonomy trees
+//! - Link suggestions from shared neighbours
+
+pub mod bipartite;
+pub mod builder;
 pub mod coactivation;
+pub mod compact;
 pub mod conflicts;
+pub mod contradictions;
 #[cfg(feature = "duckdb-graph")]
 pub mod duckdb_graph;
+pub mod embeddings;
+pub mod explore;
+pub mod label;
+pub mod layout;
+pub mod link_prediction;
+mod louvain;
+pub mod parallel;
+pub mod query;
+pub mod render;
+pub mod stream;
+pub mod style;
+pub mod summary;
+pub mod tag_tree;
 pub mod temporal;
+pub mod timeline;
 pub mod triplets;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
+
+pub use bipartite::{EntityNodeOptions, EntityNodeRef, EntityNodes};
+pub use builder::{GraphBuilder, GraphDelta};
+pub use compact::CompactGraph;
+pub use contradictions::{ContradictionCycle, CycleResolution};
+pub use embeddings::{Node2VecConfig, NodeEmbeddings};
+pub use explore::{ExploreRequest, GraphExplorer};
+pub use label::{LabelOptions, LabelSource};
+pub use layout::{GraphLayout, LayoutConfig};
+pub use link_prediction::{LinkScore, LinkSuggestion};
+pub use parallel::AggregatedGraph;
+pub use query::{GraphQuery, QueryResult};
+pub use render::RenderOptions;
+pub use stream::{stream_graph, StreamFormat, StreamedGraph};
+pub use style::{NodeShape, NodeStyle, StyleRegistry};
+pub use summary::{GraphSummary, Supernode};
+pub use tag_tree::TagTreeOptions;
+pub use timeline::{GraphTimeline, TimelineFrame};
 
 use crate::types::{CrossReference, Memory, MemoryId};
 
 /// Graph node
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphNode {
     pub id: MemoryId,
     pub label: String,
@@ -32,7 +76,7 @@ pub struct GraphNode {
 }
 
 /// Graph edge
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphEdge {
     pub from: MemoryId,
     pub to: MemoryId,
@@ -41,6 +85,32 @@ pub struct GraphEdge {
     pub confidence: f32,
 }
 
+impl GraphNode {
+    /// Node for `memory`, labelled as described by `labels`
+    pub fn for_memory(memory: &Memory, labels: &LabelOptions) -> Self {
+        Self {
+            id: memory.id,
+            label: labels.label_for(memory),
+            memory_type: memory.memory_type.as_str().to_string(),
+            importance: memory.importance,
+            tags: memory.tags.clone(),
+        }
+    }
+}
+
+impl GraphEdge {
+    /// Edge for a cross-reference
+    pub fn for_crossref(crossref: &CrossReference) -> Self {
+        Self {
+            from: crossref.from_id,
+            to: crossref.to_id,
+            edge_type: crossref.edge_type.as_str().to_string(),
+            score: crossref.score,
+            confidence: crossref.confidence,
+        }
+    }
+}
+
 /// Knowledge graph structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeGraph {
@@ -51,15 +121,19 @@ pub struct KnowledgeGraph {
 impl KnowledgeGraph {
     /// Create graph from memories and cross-references
     pub fn from_data(memories: &[Memory], crossrefs: &[CrossReference]) -> Self {
+        Self::from_data_with_labels(memories, crossrefs, &LabelOptions::default())
+    }
+
+    /// Create graph from memories and cross-references, building node labels
+    /// as described by `labels`
+    pub fn from_data_with_labels(
+        memories: &[Memory],
+        crossrefs: &[CrossReference],
+        labels: &LabelOptions,
+    ) -> Self {
         let nodes: Vec<GraphNode> = memories
             .iter()
-            .map(|m| GraphNode {
-                id: m.id,
-                label: truncate_label(&m.content, 50),
-                memory_type: m.memory_type.as_str().to_string(),
-                importance: m.importance,
-                tags: m.tags.clone(),
-            })
+            .map(|m| GraphNode::for_memory(m, labels))
             .collect();
 
         let memory_ids: std::collections::HashSet<MemoryId> =
@@ -68,31 +142,69 @@ impl KnowledgeGraph {
         let edges: Vec<GraphEdge> = crossrefs
             .iter()
             .filter(|cr| memory_ids.contains(&cr.from_id) && memory_ids.contains(&cr.to_id))
-            .map(|cr| GraphEdge {
-                from: cr.from_id,
-                to: cr.to_id,
-                edge_type: cr.edge_type.as_str().to_string(),
-                score: cr.score,
-                confidence: cr.confidence,
-            })
+            .map(GraphEdge::for_crossref)
             .collect();
 
         Self { nodes, edges }
     }
 
+    /// The graph as it stood at `timestamp`: the `max_nodes` most recently
+    /// created memories alive then, rewound to their version at that time,
+    /// and the cross-references valid at that moment.
+    pub fn at(
+        conn: &rusqlite::Connection,
+        timestamp: DateTime<Utc>,
+        max_nodes: i64,
+        labels: &LabelOptions,
+    ) -> crate::error::Result<Self> {
+        let options = crate::types::ListOptions {
+            limit: Some(max_nodes),
+            as_of: Some(timestamp),
+            ..Default::default()
+        };
+        let memories = crate::storage::queries::list_memories(conn, &options)?;
+        let crossrefs =
+            crate::storage::temporal::TemporalQueryEngine::new(conn).crossrefs_at(timestamp)?;
+        Ok(Self::from_data_with_labels(&memories, &crossrefs, labels))
+    }
+
     /// Export as vis.js compatible JSON
     pub fn to_visjs_json(&self) -> serde_json::Value {
+        self.to_visjs_json_with(StyleRegistry::global())
+    }
+
+    /// Export as vis.js compatible JSON, styling nodes from `styles`
+    pub fn to_visjs_json_with(&self, styles: &StyleRegistry) -> serde_json::Value {
+        self.to_visjs_json_with_layout(styles, None)
+    }
+
+    /// Export as vis.js compatible JSON. Nodes placed by `layout` get fixed
+    /// `x`/`y` coordinates and are excluded from client-side physics.
+    pub fn to_visjs_json_with_layout(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+    ) -> serde_json::Value {
         let nodes: Vec<serde_json::Value> = self
             .nodes
             .iter()
             .map(|n| {
-                serde_json::json!({
+                let style = styles.style_for(&n.memory_type);
+                let mut node = serde_json::json!({
                     "id": n.id,
                     "label": n.label,
                     "group": n.memory_type,
+                    "color": style.color,
+                    "shape": style.shape.visjs(),
                     "value": (n.importance * 10.0) as i32 + 5,
                     "title": format!("Type: {}\nTags: {}", n.memory_type, n.tags.join(", "))
-                })
+                });
+                if let Some((x, y)) = layout.and_then(|l| l.get(n.id)) {
+                    node["x"] = serde_json::json!(x.round());
+                    node["y"] = serde_json::json!(y.round());
+                    node["physics"] = serde_json::json!(false);
+                }
+                node
             })
             .collect();
 
@@ -118,7 +230,45 @@ impl KnowledgeGraph {
 
     /// Export as standalone HTML with vis.js
     pub fn to_html(&self) -> String {
-        let graph_data = self.to_visjs_json();
+        self.to_html_with(StyleRegistry::global())
+    }
+
+    /// Export as standalone HTML, with groups and legend taken from `styles`
+    pub fn to_html_with(&self, styles: &StyleRegistry) -> String {
+        self.to_html_with_layout(styles, None)
+    }
+
+    /// Export as standalone HTML. With a precomputed `layout`, nodes are
+    /// drawn at its positions and the browser runs no physics simulation.
+    pub fn to_html_with_layout(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+    ) -> String {
+        let graph_data = self.to_visjs_json_with_layout(styles, layout);
+        self.html_page(&graph_data, styles, layout.is_some())
+    }
+
+    /// Standalone HTML page around already exported vis.js data; `fixed`
+    /// turns client-side physics off for precomputed positions
+    fn html_page(
+        &self,
+        graph_data: &serde_json::Value,
+        styles: &StyleRegistry,
+        fixed: bool,
+    ) -> String {
+        let (legend, groups) = self.html_legend_and_groups(styles);
+        let physics = if fixed {
+            "{ enabled: false }"
+        } else {
+            r#"{
+                stabilization: { iterations: 100 },
+                barnesHut: {
+                    gravitationalConstant: -2000,
+                    springLength: 100
+                }
+            }"#
+        };
 
         format!(
             r#"<!DOCTYPE html>
@@ -147,13 +297,7 @@ impl KnowledgeGraph {
 <body>
     <div id="controls">
         <input type="text" id="search" placeholder="Search nodes...">
-        <div class="legend">
-            <div class="legend-item"><span class="legend-dot" style="background: #97C2FC;"></span> note</div>
-            <div class="legend-item"><span class="legend-dot" style="background: #FFFF00;"></span> todo</div>
-            <div class="legend-item"><span class="legend-dot" style="background: #FB7E81;"></span> issue</div>
-            <div class="legend-item"><span class="legend-dot" style="background: #7BE141;"></span> decision</div>
-            <div class="legend-item"><span class="legend-dot" style="background: #FFA807;"></span> preference</div>
-            <div class="legend-item"><span class="legend-dot" style="background: #6E6EFD;"></span> learning</div>
+        <div class="legend">{legend}
         </div>
     </div>
     <div id="graph"></div>
@@ -171,23 +315,8 @@ impl KnowledgeGraph {
                 scaling: {{ min: 1, max: 5 }},
                 font: {{ size: 10, align: 'middle' }}
             }},
-            groups: {{
-                note: {{ color: '#97C2FC' }},
-                todo: {{ color: '#FFFF00' }},
-                issue: {{ color: '#FB7E81' }},
-                decision: {{ color: '#7BE141' }},
-                preference: {{ color: '#FFA807' }},
-                learning: {{ color: '#6E6EFD' }},
-                context: {{ color: '#C2FABC' }},
-                credential: {{ color: '#FD6A6A' }}
-            }},
-            physics: {{
-                stabilization: {{ iterations: 100 }},
-                barnesHut: {{
-                    gravitationalConstant: -2000,
-                    springLength: 100
-                }}
-            }},
+            groups: {groups},
+            physics: {physics},
             interaction: {{
                 hover: true,
                 tooltipDelay: 100
@@ -223,21 +352,54 @@ impl KnowledgeGraph {
     </script>
 </body>
 </html>"#,
-            graph_data = serde_json::to_string(&graph_data).unwrap_or_default()
+            graph_data = serde_json::to_string(graph_data).unwrap_or_default(),
+            groups = groups,
+            legend = legend,
+            physics = physics,
         )
     }
-}
 
-/// Truncate content for display as node label
-fn truncate_label(content: &str, max_len: usize) -> String {
-    let first_line = content.lines().next().unwrap_or(content);
-    if first_line.len() <= max_len {
-        first_line.to_string()
-    } else {
-        format!("{}...", &first_line[..max_len - 3])
+    /// Legend entries for the types present and vis.js `groups` options for
+    /// the standalone HTML exports
+    fn html_legend_and_groups(&self, styles: &StyleRegistry) -> (String, serde_json::Value) {
+        // Legend lists the types actually present; groups also cover every
+        // registered type so nodes added client-side are styled too.
+        let present: std::collections::BTreeSet<&str> =
+            self.nodes.iter().map(|n| n.memory_type.as_str()).collect();
+        let legend: String = present
+            .iter()
+            .map(|memory_type| {
+                let style = styles.style_for(memory_type);
+                format!(
+                    "\n            <div class=\"legend-item\"><span class=\"legend-dot\" style=\"background: {};\"></span> {}{}</div>",
+                    style.color,
+                    style.icon.map(|i| format!("{} ", html_escape(&i))).unwrap_or_default(),
+                    html_escape(memory_type)
+                )
+            })
+            .collect();
+        let mut groups = serde_json::Map::new();
+        let registered = styles.entries().map(|(name, _)| name);
+        for memory_type in registered.chain(present.iter().copied()) {
+            let style = styles.style_for(memory_type);
+            groups.insert(
+                memory_type.to_string(),
+                serde_json::json!({"color": style.color, "shape": style.shape.visjs()}),
+            );
+        }
+
+        (legend, serde_json::Value::Object(groups))
     }
 }
 
+/// Escape text for HTML and XML attribute values
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 // =============================================================================
 // Graph Statistics (RML-894)
 // =============================================================================
@@ -261,166 +423,88 @@ pub struct GraphStats {
     pub nodes_by_type: HashMap<String, usize>,
     /// Edges by type
     pub edges_by_type: HashMap<String, usize>,
-    /// Most connected nodes (top 10 by degree)
-    pub hub_nodes: Vec<(MemoryId, usize)>,
+    /// Most important nodes (top 10 by PageRank)
+    pub hub_nodes: Vec<HubNode>,
     /// Isolated nodes (degree 0)
     pub isolated_count: usize,
 }
 
+/// A hub entry in [`GraphStats`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HubNode {
+    pub id: MemoryId,
+    /// Incident edges, including parallel ones
+    pub degree: usize,
+    /// Weighted PageRank score (all nodes sum to 1.0)
+    pub pagerank: f64,
+}
+
 impl KnowledgeGraph {
+    /// Index-based view for running several algorithms over one graph
+    ///
+    /// Each analysis method below builds one of these on the fly; callers that
+    /// need more than one result (say `stats` and `centrality`) should build
+    /// it once and call the methods on [`CompactGraph`] directly.
+    pub fn compact(&self) -> CompactGraph<'_> {
+        CompactGraph::new(self)
+    }
+
     /// Calculate graph statistics
     pub fn stats(&self) -> GraphStats {
-        let node_count = self.nodes.len();
-        let edge_count = self.edges.len();
-
-        // Build adjacency for degree calculation
-        let mut degree: HashMap<MemoryId, usize> = HashMap::new();
-        for node in &self.nodes {
-            degree.insert(node.id, 0);
-        }
-        for edge in &self.edges {
-            *degree.entry(edge.from).or_insert(0) += 1;
-            *degree.entry(edge.to).or_insert(0) += 1;
-        }
-
-        let avg_degree = if node_count > 0 {
-            degree.values().sum::<usize>() as f32 / node_count as f32
-        } else {
-            0.0
-        };
-
-        // Density: edges / (n * (n-1) / 2) for undirected, edges / (n * (n-1)) for directed
-        let density = if node_count > 1 {
-            edge_count as f32 / (node_count * (node_count - 1)) as f32
-        } else {
-            0.0
-        };
-
-        // Count by type
-        let mut nodes_by_type: HashMap<String, usize> = HashMap::new();
-        for node in &self.nodes {
-            *nodes_by_type.entry(node.memory_type.clone()).or_insert(0) += 1;
-        }
-
-        let mut edges_by_type: HashMap<String, usize> = HashMap::new();
-        for edge in &self.edges {
-            *edges_by_type.entry(edge.edge_type.clone()).or_insert(0) += 1;
-        }
-
-        // Find hub nodes (top 10 by degree)
-        let mut degree_list: Vec<(MemoryId, usize)> =
-            degree.iter().map(|(&k, &v)| (k, v)).collect();
-        degree_list.sort_by(|a, b| b.1.cmp(&a.1));
-        let hub_nodes: Vec<(MemoryId, usize)> = degree_list.into_iter().take(10).collect();
-
-        // Count isolated nodes
-        let isolated_count = degree.values().filter(|&&d| d == 0).count();
-
-        // Find connected components using BFS
-        let components = self.find_connected_components();
-        let component_count = components.len();
-        let largest_component_size = components.iter().map(|c| c.len()).max().unwrap_or(0);
-
-        GraphStats {
-            node_count,
-            edge_count,
-            avg_degree,
-            density,
-            component_count,
-            largest_component_size,
-            nodes_by_type,
-            edges_by_type,
-            hub_nodes,
-            isolated_count,
-        }
+        self.compact().stats()
     }
 
-    /// Find connected components using BFS
-    fn find_connected_components(&self) -> Vec<Vec<MemoryId>> {
-        let node_ids: HashSet<MemoryId> = self.nodes.iter().map(|n| n.id).collect();
+    /// Weighted PageRank for every node, summing to 1.0
+    ///
+    /// Rank flows along edge direction, split by `score * confidence`, so a
+    /// memory with many weak links scores below one with a few strong ones.
+    /// `damping` is the usual follow-a-link probability (0.85 is standard);
+    /// iteration stops early once scores converge.
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> HashMap<MemoryId, f64> {
+        self.compact().pagerank(damping, iterations)
+    }
 
-        // Build adjacency list (undirected)
-        let mut adj: HashMap<MemoryId, Vec<MemoryId>> = HashMap::new();
-        for id in &node_ids {
-            adj.insert(*id, Vec::new());
+    /// Blend PageRank into node `importance`
+    ///
+    /// Scores are scaled so the top node maps to 1.0, then mixed in as
+    /// `importance = (1 - weight) * importance + weight * scaled`.
+    pub fn apply_pagerank_importance(&mut self, scores: &HashMap<MemoryId, f64>, weight: f32) {
+        let max = scores.values().cloned().fold(0.0, f64::max);
+        if max <= 0.0 {
+            return;
         }
-        for edge in &self.edges {
-            if let Some(list) = adj.get_mut(&edge.from) {
-                list.push(edge.to);
-            }
-            if let Some(list) = adj.get_mut(&edge.to) {
-                list.push(edge.from);
+        let weight = weight.clamp(0.0, 1.0);
+        for node in &mut self.nodes {
+            if let Some(score) = scores.get(&node.id) {
+                let scaled = (score / max) as f32;
+                node.importance = (1.0 - weight) * node.importance + weight * scaled;
             }
         }
-
-        let mut visited: HashSet<MemoryId> = HashSet::new();
-        let mut components = Vec::new();
-
-        for &start in &node_ids {
-            if visited.contains(&start) {
-                continue;
-            }
-
-            let mut component = Vec::new();
-            let mut queue = VecDeque::new();
-            queue.push_back(start);
-            visited.insert(start);
-
-            while let Some(node) = queue.pop_front() {
-                component.push(node);
-                if let Some(neighbors) = adj.get(&node) {
-                    for &neighbor in neighbors {
-                        if !visited.contains(&neighbor) {
-                            visited.insert(neighbor);
-                            queue.push_back(neighbor);
-                        }
-                    }
-                }
-            }
-
-            components.push(component);
-        }
-
-        components
     }
 
     /// Calculate centrality scores for nodes
+    ///
+    /// Degree scores follow edge direction. Closeness, betweenness and the
+    /// spectral scores treat the graph as undirected and unweighted, with
+    /// parallel edges between the same pair of memories collapsed into one.
     pub fn centrality(&self) -> HashMap<MemoryId, CentralityScores> {
-        let mut results: HashMap<MemoryId, CentralityScores> = HashMap::new();
-
-        // Build adjacency
-        let mut in_degree: HashMap<MemoryId, usize> = HashMap::new();
-        let mut out_degree: HashMap<MemoryId, usize> = HashMap::new();
-
-        for node in &self.nodes {
-            in_degree.insert(node.id, 0);
-            out_degree.insert(node.id, 0);
-        }
-
-        for edge in &self.edges {
-            *out_degree.entry(edge.from).or_insert(0) += 1;
-            *in_degree.entry(edge.to).or_insert(0) += 1;
-        }
-
-        let max_degree = self.nodes.len().saturating_sub(1).max(1) as f32;
+        self.compact().centrality()
+    }
 
-        for node in &self.nodes {
-            let in_d = *in_degree.get(&node.id).unwrap_or(&0) as f32;
-            let out_d = *out_degree.get(&node.id).unwrap_or(&0) as f32;
+    /// The `limit` most central nodes by betweenness, ties broken by
+    /// eigenvector score — the memories that bridge otherwise separate areas
+    pub fn top_central_nodes(&self, limit: usize) -> Vec<(MemoryId, CentralityScores)> {
+        self.compact().top_central_nodes(limit)
+    }
 
-            results.insert(
-                node.id,
-                CentralityScores {
-                    in_degree: in_d / max_degree,
-                    out_degree: out_d / max_degree,
-                    degree: (in_d + out_d) / (2.0 * max_degree),
-                    // Simplified closeness based on direct connections
-                    closeness: (in_d + out_d) / (2.0 * max_degree),
-                },
-            );
-        }
+    /// Structural node2vec embeddings (see [`CompactGraph::node2vec`])
+    pub fn node2vec(&self, config: &Node2VecConfig) -> NodeEmbeddings {
+        self.compact().node2vec(config)
+    }
 
-        results
+    /// Force-directed node positions (see [`CompactGraph::force_layout`])
+    pub fn force_layout(&self, config: &LayoutConfig) -> GraphLayout {
+        self.compact().force_layout(config)
     }
 }
 
@@ -433,8 +517,15 @@ pub struct CentralityScores {
     pub out_degree: f32,
     /// Combined degree centrality
     pub degree: f32,
-    /// Closeness centrality (simplified)
+    /// Closeness centrality (Wasserman–Faust, 0.0–1.0)
     pub closeness: f32,
+    /// Fraction of shortest paths between other nodes that pass through this
+    /// one (0.0–1.0); high values mark bridge memories
+    pub betweenness: f32,
+    /// Eigenvector centrality scaled to the most central node (0.0–1.0)
+    pub eigenvector: f32,
+    /// Katz centrality scaled to the most central node (0.0–1.0)
+    pub katz: f32,
 }
 
 // =============================================================================
@@ -442,7 +533,8 @@ pub struct CentralityScores {
 // =============================================================================
 
 /// Filter options for graph queries
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GraphFilter {
     /// Filter by memory types
     pub memory_types: Option<Vec<String>>,
@@ -471,6 +563,63 @@ impl GraphFilter {
         Self::default()
     }
 
+    /// Whether a node passes the type, tag and importance filters
+    pub fn matches_node(&self, node: &GraphNode) -> bool {
+        // Type filter
+        if let Some(ref types) = self.memory_types {
+            if !types.contains(&node.memory_type) {
+                return false;
+            }
+        }
+
+        // Tag filter (any match)
+        if let Some(ref tags) = self.tags {
+            if !node.tags.iter().any(|t| tags.contains(t)) {
+                return false;
+            }
+        }
+
+        // Importance filter
+        if let Some(min) = self.min_importance {
+            if node.importance < min {
+                return false;
+            }
+        }
+        if let Some(max) = self.max_importance {
+            if node.importance > max {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Whether an edge passes the type, confidence and score filters
+    pub fn matches_edge(&self, edge: &GraphEdge) -> bool {
+        // Edge type filter
+        if let Some(ref types) = self.edge_types {
+            if !types.contains(&edge.edge_type) {
+                return false;
+            }
+        }
+
+        // Confidence filter
+        if let Some(min) = self.min_confidence {
+            if edge.confidence < min {
+                return false;
+            }
+        }
+
+        // Score filter
+        if let Some(min) = self.min_score {
+            if edge.score < min {
+                return false;
+            }
+        }
+
+        true
+    }
+
     pub fn with_types(mut self, types: Vec<String>) -> Self {
         self.memory_types = Some(types);
         self
@@ -500,39 +649,10 @@ impl GraphFilter {
 impl KnowledgeGraph {
     /// Apply filter to create a subgraph
     pub fn filter(&self, filter: &GraphFilter) -> KnowledgeGraph {
-        // Filter nodes
         let mut filtered_nodes: Vec<GraphNode> = self
             .nodes
             .iter()
-            .filter(|n| {
-                // Type filter
-                if let Some(ref types) = filter.memory_types {
-                    if !types.contains(&n.memory_type) {
-                        return false;
-                    }
-                }
-
-                // Tag filter (any match)
-                if let Some(ref tags) = filter.tags {
-                    if !n.tags.iter().any(|t| tags.contains(t)) {
-                        return false;
-                    }
-                }
-
-                // Importance filter
-                if let Some(min) = filter.min_importance {
-                    if n.importance < min {
-                        return false;
-                    }
-                }
-                if let Some(max) = filter.max_importance {
-                    if n.importance > max {
-                        return false;
-                    }
-                }
-
-                true
-            })
+            .filter(|n| filter.matches_node(n))
             .cloned()
             .collect();
 
@@ -544,38 +664,12 @@ impl KnowledgeGraph {
         // Get set of valid node IDs
         let valid_ids: HashSet<MemoryId> = filtered_nodes.iter().map(|n| n.id).collect();
 
-        // Filter edges
+        // Both endpoints must be in filtered nodes
         let filtered_edges: Vec<GraphEdge> = self
             .edges
             .iter()
             .filter(|e| {
-                // Both endpoints must be in filtered nodes
-                if !valid_ids.contains(&e.from) || !valid_ids.contains(&e.to) {
-                    return false;
-                }
-
-                // Edge type filter
-                if let Some(ref types) = filter.edge_types {
-                    if !types.contains(&e.edge_type) {
-                        return false;
-                    }
-                }
-
-                // Confidence filter
-                if let Some(min) = filter.min_confidence {
-                    if e.confidence < min {
-                        return false;
-                    }
-                }
-
-                // Score filter
-                if let Some(min) = filter.min_score {
-                    if e.score < min {
-                        return false;
-                    }
-                }
-
-                true
+                valid_ids.contains(&e.from) && valid_ids.contains(&e.to) && filter.matches_edge(e)
             })
             .cloned()
             .collect();
@@ -588,50 +682,101 @@ impl KnowledgeGraph {
 
     /// Get subgraph centered on a node with given depth
     pub fn neighborhood(&self, center: MemoryId, depth: usize) -> KnowledgeGraph {
-        let mut visited: HashSet<MemoryId> = HashSet::new();
-        let mut current_level: HashSet<MemoryId> = HashSet::new();
-        current_level.insert(center);
-        visited.insert(center);
+        self.compact().neighborhood(center, depth)
+    }
 
-        // Build adjacency
-        let mut adj: HashMap<MemoryId, Vec<MemoryId>> = HashMap::new();
-        for edge in &self.edges {
-            adj.entry(edge.from).or_default().push(edge.to);
-            adj.entry(edge.to).or_default().push(edge.from);
+    /// Ego graph of `center`: the memories within `depth` hops, reached only
+    /// through nodes and edges `filter` keeps. The center is kept whether or
+    /// not it matches, and `filter.limit` keeps the nodes nearest to it.
+    pub fn ego_graph(
+        &self,
+        center: MemoryId,
+        depth: usize,
+        filter: &GraphFilter,
+    ) -> KnowledgeGraph {
+        let kept = KnowledgeGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|n| n.id == center || filter.matches_node(n))
+                .cloned()
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|e| filter.matches_edge(e))
+                .cloned()
+                .collect(),
+        };
+        let compact = kept.compact();
+        let Some(start) = compact.index_of(center) else {
+            return KnowledgeGraph {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            };
+        };
+
+        let mut reached = compact.within(start, depth);
+        if let Some(limit) = filter.limit {
+            reached.truncate(limit.max(1));
+        }
+        let mut keep = vec![false; compact.node_count()];
+        for v in reached {
+            keep[v] = true;
         }
+        compact.induced_subgraph(&keep)
+    }
 
-        // BFS to depth
-        for _ in 0..depth {
-            let mut next_level: HashSet<MemoryId> = HashSet::new();
-            for &node in &current_level {
-                if let Some(neighbors) = adj.get(&node) {
-                    for &neighbor in neighbors {
-                        if !visited.contains(&neighbor) {
-                            visited.insert(neighbor);
-                            next_level.insert(neighbor);
+    /// The memories within `depth` hops of `center` and the links between
+    /// them, read from storage by following links outward. Stops adding
+    /// memories once `max_nodes` are loaded.
+    pub fn around(
+        conn: &rusqlite::Connection,
+        center: MemoryId,
+        depth: usize,
+        max_nodes: usize,
+        labels: &LabelOptions,
+    ) -> crate::error::Result<Self> {
+        use crate::storage::queries::{get_memory, get_related};
+
+        let mut memories = vec![get_memory(conn, center)?];
+        let mut seen: HashSet<MemoryId> = HashSet::from([center]);
+        let mut crossrefs: Vec<CrossReference> = Vec::new();
+        let mut linked: HashSet<(MemoryId, MemoryId, String)> = HashSet::new();
+        let mut frontier = 0;
+        'hops: for hop in 0..=depth {
+            let reached = memories.len();
+            for i in frontier..reached {
+                if crate::budget::checkpoint() {
+                    break 'hops;
+                }
+                for crossref in get_related(conn, memories[i].id)? {
+                    let other = if crossref.from_id == memories[i].id {
+                        crossref.to_id
+                    } else {
+                        crossref.from_id
+                    };
+                    // The last hop only adds links between loaded memories
+                    if hop < depth && memories.len() < max_nodes && seen.insert(other) {
+                        match get_memory(conn, other) {
+                            Ok(memory) => memories.push(memory),
+                            Err(crate::error::EngramError::NotFound(_)) => continue,
+                            Err(e) => return Err(e),
                         }
                     }
+                    let key = (
+                        crossref.from_id,
+                        crossref.to_id,
+                        crossref.edge_type.as_str().to_string(),
+                    );
+                    if linked.insert(key) {
+                        crossrefs.push(crossref);
+                    }
                 }
             }
-            current_level = next_level;
+            frontier = reached;
         }
-
-        // Filter to visited nodes
-        let nodes: Vec<GraphNode> = self
-            .nodes
-            .iter()
-            .filter(|n| visited.contains(&n.id))
-            .cloned()
-            .collect();
-
-        let edges: Vec<GraphEdge> = self
-            .edges
-            .iter()
-            .filter(|e| visited.contains(&e.from) && visited.contains(&e.to))
-            .cloned()
-            .collect();
-
-        KnowledgeGraph { nodes, edges }
+        Ok(Self::from_data_with_labels(&memories, &crossrefs, labels))
     }
 }
 
@@ -642,31 +787,25 @@ impl KnowledgeGraph {
 impl KnowledgeGraph {
     /// Export as DOT format for Graphviz
     pub fn to_dot(&self) -> String {
+        self.to_dot_with(StyleRegistry::global())
+    }
+
+    /// Export as DOT, with node colors and shapes taken from `styles`
+    pub fn to_dot_with(&self, styles: &StyleRegistry) -> String {
         let mut dot = String::from("digraph knowledge_graph {\n");
         dot.push_str("    rankdir=LR;\n");
         dot.push_str("    node [shape=box, style=rounded];\n\n");
 
-        // Color mapping for memory types
-        let colors: HashMap<&str, &str> = [
-            ("note", "#97C2FC"),
-            ("todo", "#FFFF00"),
-            ("issue", "#FB7E81"),
-            ("decision", "#7BE141"),
-            ("preference", "#FFA807"),
-            ("learning", "#6E6EFD"),
-            ("context", "#C2FABC"),
-            ("credential", "#FD6A6A"),
-        ]
-        .into_iter()
-        .collect();
-
         // Write nodes
         for node in &self.nodes {
-            let color = colors.get(node.memory_type.as_str()).unwrap_or(&"#CCCCCC");
+            let style = styles.style_for(&node.memory_type);
             let label = node.label.replace('"', "\\\"");
             dot.push_str(&format!(
-                "    \"{}\" [label=\"{}\", fillcolor=\"{}\", style=\"filled,rounded\"];\n",
-                node.id, label, color
+                "    \"{}\" [label=\"{}\", shape={}, fillcolor=\"{}\", style=\"filled,rounded\"];\n",
+                node.id,
+                label,
+                style.shape.graphviz(),
+                style.color
             ));
         }
 
@@ -699,9 +838,14 @@ impl KnowledgeGraph {
 
     /// Export as GEXF format for Gephi
     pub fn to_gexf(&self) -> String {
+        self.to_gexf_with(StyleRegistry::global())
+    }
+
+    /// Export as GEXF, with `viz:color`/`viz:shape` taken from `styles`
+    pub fn to_gexf_with(&self, styles: &StyleRegistry) -> String {
         let mut gexf = String::from(
             r#"<?xml version="1.0" encoding="UTF-8"?>
-<gexf xmlns="http://gexf.net/1.3" version="1.3">
+<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">
   <meta>
     <creator>Engram</creator>
     <description>Knowledge Graph Export</description>
@@ -720,21 +864,27 @@ impl KnowledgeGraph {
         );
 
         for node in &self.nodes {
-            let label = node
-                .label
-                .replace('&', "&amp;")
-                .replace('<', "&lt;")
-                .replace('>', "&gt;")
-                .replace('"', "&quot;");
+            let label = html_escape(&node.label);
+            let style = styles.style_for(&node.memory_type);
+            let (r, g, b) = style.rgb();
             gexf.push_str(&format!(
                 r#"      <node id="{}" label="{}">
         <attvalues>
           <attvalue for="0" value="{}"/>
           <attvalue for="1" value="{}"/>
         </attvalues>
+        <viz:color r="{}" g="{}" b="{}"/>
+        <viz:shape value="{}"/>
       </node>
 "#,
-                node.id, label, node.memory_type, node.importance
+                node.id,
+                label,
+                node.memory_type,
+                node.importance,
+                r,
+                g,
+                b,
+                style.shape.gexf()
             ));
         }
 
@@ -756,6 +906,81 @@ impl KnowledgeGraph {
         gexf.push_str("    </edges>\n  </graph>\n</gexf>\n");
         gexf
     }
+
+    /// Export as GraphML for yEd, Cytoscape and other GraphML readers
+    pub fn to_graphml(&self) -> String {
+        self.to_graphml_with(StyleRegistry::global())
+    }
+
+    /// Export as GraphML, with `color`/`shape` node attributes from `styles`
+    pub fn to_graphml_with(&self, styles: &StyleRegistry) -> String {
+        let mut xml = String::from(GRAPHML_HEADER);
+        for node in &self.nodes {
+            xml.push_str(&graphml_node(node, styles));
+        }
+        for (i, edge) in self.edges.iter().enumerate() {
+            xml.push_str(&graphml_edge(i, edge));
+        }
+        xml.push_str(GRAPHML_FOOTER);
+        xml
+    }
+}
+
+const GRAPHML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+    xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">
+  <key id="label" for="node" attr.name="label" attr.type="string"/>
+  <key id="type" for="node" attr.name="type" attr.type="string"/>
+  <key id="importance" for="node" attr.name="importance" attr.type="double"/>
+  <key id="tags" for="node" attr.name="tags" attr.type="string"/>
+  <key id="color" for="node" attr.name="color" attr.type="string"/>
+  <key id="shape" for="node" attr.name="shape" attr.type="string"/>
+  <key id="edge_type" for="edge" attr.name="type" attr.type="string"/>
+  <key id="score" for="edge" attr.name="score" attr.type="double"/>
+  <key id="confidence" for="edge" attr.name="confidence" attr.type="double"/>
+  <graph id="engram" edgedefault="directed">
+"#;
+
+const GRAPHML_FOOTER: &str = "  </graph>\n</graphml>\n";
+
+fn graphml_node(node: &GraphNode, styles: &StyleRegistry) -> String {
+    let style = styles.style_for(&node.memory_type);
+    format!(
+        r#"    <node id="n{}">
+      <data key="label">{}</data>
+      <data key="type">{}</data>
+      <data key="importance">{}</data>
+      <data key="tags">{}</data>
+      <data key="color">{}</data>
+      <data key="shape">{}</data>
+    </node>
+"#,
+        node.id,
+        html_escape(&node.label),
+        html_escape(&node.memory_type),
+        node.importance,
+        html_escape(&node.tags.join(",")),
+        style.color,
+        style.shape.visjs()
+    )
+}
+
+fn graphml_edge(index: usize, edge: &GraphEdge) -> String {
+    format!(
+        r#"    <edge id="e{}" source="n{}" target="n{}">
+      <data key="edge_type">{}</data>
+      <data key="score">{}</data>
+      <data key="confidence">{}</data>
+    </edge>
+"#,
+        index,
+        edge.from,
+        edge.to,
+        html_escape(&edge.edge_type),
+        edge.score,
+        edge.confidence
+    )
 }
 
 // =============================================================================
@@ -777,148 +1002,51 @@ pub struct GraphCluster {
     pub internal_edges: usize,
     /// Cluster cohesion score
     pub cohesion: f32,
+    /// This cluster's contribution to the partition's modularity; the values
+    /// of all clusters sum to the overall modularity Q
+    #[serde(default)]
+    pub modularity: f32,
 }
 
-impl KnowledgeGraph {
-    /// Detect communities using label propagation algorithm
-    pub fn detect_communities(&self, max_iterations: usize) -> Vec<GraphCluster> {
-        if self.nodes.is_empty() {
-            return Vec::new();
-        }
-
-        // Initialize: each node in its own community
-        let mut labels: HashMap<MemoryId, usize> = self
-            .nodes
-            .iter()
-            .enumerate()
-            .map(|(i, n)| (n.id, i))
-            .collect();
-
-        // Build adjacency
-        let mut adj: HashMap<MemoryId, Vec<(MemoryId, f32)>> = HashMap::new();
-        for node in &self.nodes {
-            adj.insert(node.id, Vec::new());
-        }
-        for edge in &self.edges {
-            let weight = edge.score * edge.confidence;
-            adj.entry(edge.from).or_default().push((edge.to, weight));
-            adj.entry(edge.to).or_default().push((edge.from, weight));
-        }
-
-        // Label propagation
-        let node_ids: Vec<MemoryId> = self.nodes.iter().map(|n| n.id).collect();
-
-        for _ in 0..max_iterations {
-            let mut changed = false;
-
-            for &node_id in &node_ids {
-                if let Some(neighbors) = adj.get(&node_id) {
-                    if neighbors.is_empty() {
-                        continue;
-                    }
-
-                    // Count weighted votes for each label
-                    let mut votes: HashMap<usize, f32> = HashMap::new();
-                    for &(neighbor, weight) in neighbors {
-                        if let Some(&label) = labels.get(&neighbor) {
-                            *votes.entry(label).or_insert(0.0) += weight;
-                        }
-                    }
-
-                    // Pick label with most votes
-                    if let Some((&best_label, _)) = votes.iter().max_by(|a, b| a.1.total_cmp(b.1)) {
-                        let current = labels.get(&node_id).copied().unwrap_or(0);
-                        if best_label != current {
-                            labels.insert(node_id, best_label);
-                            changed = true;
-                        }
-                    }
-                }
-            }
-
-            if !changed {
-                break;
-            }
-        }
+/// Community detection algorithm for [`KnowledgeGraph::communities`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum CommunityAlgorithm {
+    /// Weighted label propagation: fast, but prone to merging loosely
+    /// connected groups on large graphs
+    LabelPropagation { max_iterations: usize },
+    /// Multi-level Louvain modularity optimisation. `resolution` above 1.0
+    /// favours smaller communities; `seed` fixes the node visiting order.
+    Louvain { resolution: f64, seed: u64 },
+}
 
-        // Group nodes by label
-        let mut clusters_map: HashMap<usize, Vec<MemoryId>> = HashMap::new();
-        for (node_id, label) in &labels {
-            clusters_map.entry(*label).or_default().push(*node_id);
+impl Default for CommunityAlgorithm {
+    fn default() -> Self {
+        CommunityAlgorithm::Louvain {
+            resolution: 1.0,
+            seed: 0,
         }
+    }
+}
 
-        // Build cluster objects
-        let node_map: HashMap<MemoryId, &GraphNode> =
-            self.nodes.iter().map(|n| (n.id, n)).collect();
-
-        let mut clusters: Vec<GraphCluster> = clusters_map
-            .into_iter()
-            .enumerate()
-            .map(|(new_id, (_, members))| {
-                // Find dominant type
-                let mut type_counts: HashMap<&str, usize> = HashMap::new();
-                let mut all_tags: HashMap<&str, usize> = HashMap::new();
-
-                for &member_id in &members {
-                    if let Some(node) = node_map.get(&member_id) {
-                        *type_counts.entry(node.memory_type.as_str()).or_insert(0) += 1;
-                        for tag in &node.tags {
-                            *all_tags.entry(tag.as_str()).or_insert(0) += 1;
-                        }
-                    }
-                }
-
-                let dominant_type = type_counts
-                    .into_iter()
-                    .max_by_key(|(_, count)| *count)
-                    .map(|(t, _)| t.to_string());
-
-                // Common tags (present in > 50% of members)
-                let threshold = members.len() / 2;
-                let common_tags: Vec<String> = all_tags
-                    .into_iter()
-                    .filter(|(_, count)| *count > threshold)
-                    .map(|(tag, _)| tag.to_string())
-                    .collect();
-
-                // Count internal edges
-                let member_set: HashSet<MemoryId> = members.iter().copied().collect();
-                let internal_edges = self
-                    .edges
-                    .iter()
-                    .filter(|e| member_set.contains(&e.from) && member_set.contains(&e.to))
-                    .count();
-
-                // Cohesion: internal edges / possible internal edges
-                let n = members.len();
-                let possible = if n > 1 { n * (n - 1) } else { 1 };
-                let cohesion = internal_edges as f32 / possible as f32;
-
-                GraphCluster {
-                    id: new_id,
-                    members,
-                    dominant_type,
-                    common_tags,
-                    internal_edges,
-                    cohesion,
-                }
-            })
-            .collect();
-
-        // Sort by size (largest first)
-        clusters.sort_by(|a, b| b.members.len().cmp(&a.members.len()));
-
-        // Renumber IDs
-        for (i, cluster) in clusters.iter_mut().enumerate() {
-            cluster.id = i;
-        }
+impl KnowledgeGraph {
+    /// Detect communities using label propagation algorithm
+    ///
+    /// Edges vote with weight `score * confidence`; ties go to the lowest
+    /// label, so the same graph always yields the same clusters.
+    pub fn detect_communities(&self, max_iterations: usize) -> Vec<GraphCluster> {
+        self.compact().detect_communities(max_iterations)
+    }
 
-        clusters
+    /// Detect communities with the given algorithm, largest first
+    pub fn communities(&self, algorithm: CommunityAlgorithm) -> Vec<GraphCluster> {
+        self.compact().communities(algorithm)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::label::truncate_label;
     use super::*;
 
     fn make_node(id: MemoryId, memory_type: &str, tags: Vec<&str>) -> GraphNode {
@@ -948,6 +1076,8 @@ mod tests {
             truncate_label("this is a very long label that should be truncated", 20),
             "this is a very lo..."
         );
+        // Multi-byte characters straddling the cut used to panic.
+        assert_eq!(truncate_label("ééééééééééééééééééééééé", 10), "ééééééé...");
     }
 
     #[test]
@@ -977,6 +1107,95 @@ mod tests {
         assert_eq!(stats.component_count, 1);
     }
 
+    #[test]
+    fn test_betweenness_finds_bridge() {
+        // Two triangles joined through node 4: 1-2-3 and 5-6-7
+        let edges = [
+            (1, 2),
+            (2, 3),
+            (3, 1),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 5),
+        ];
+        let graph = KnowledgeGraph {
+            nodes: (1..=7).map(|id| make_node(id, "note", vec![])).collect(),
+            edges: edges
+                .iter()
+                .map(|&(a, b)| make_edge(a, b, "related_to"))
+                .collect(),
+        };
+
+        let scores = graph.centrality();
+        // Node 4 lies on every path between the triangles: 3 * 3 pairs of the
+        // 15 pairs not involving it.
+        assert!((scores[&4].betweenness - 9.0 / 15.0).abs() < 1e-5);
+        assert!(scores[&4].betweenness > scores[&3].betweenness);
+        assert!(scores[&3].betweenness > scores[&1].betweenness);
+        assert_eq!(scores[&1].betweenness, 0.0);
+        assert!(scores[&4].closeness > scores[&1].closeness);
+
+        let top = graph.top_central_nodes(3);
+        assert_eq!(top[0].0, 4);
+        assert_eq!(top.len(), 3);
+    }
+
+    #[test]
+    fn test_spectral_centrality_on_star() {
+        // Star: hub 1 with four leaves; bipartite, so plain power iteration
+        // on A would oscillate.
+        let graph = KnowledgeGraph {
+            nodes: (1..=6).map(|id| make_node(id, "note", vec![])).collect(),
+            edges: (2..=5)
+                .map(|leaf| make_edge(1, leaf, "related_to"))
+                .collect(),
+        };
+
+        let scores = graph.centrality();
+        assert!((scores[&1].eigenvector - 1.0).abs() < 1e-4);
+        // Leaves of a star have eigenvector score 1/sqrt(k) relative to the hub.
+        assert!((scores[&2].eigenvector - 0.5).abs() < 1e-3);
+        assert_eq!(scores[&1].katz, 1.0);
+        assert!(scores[&2].katz < 1.0);
+        // Isolated node 6: no eigenvector mass, but a Katz baseline.
+        assert!(scores[&6].eigenvector.abs() < 1e-4);
+        assert!(scores[&6].katz > 0.0);
+        // 6 leaf pairs route through the hub, out of 10 pairs of other nodes.
+        assert!((scores[&1].betweenness - 0.6).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_pagerank_reweights_importance() {
+        let mut graph = KnowledgeGraph {
+            nodes: (1..=3).map(|id| make_node(id, "note", vec![])).collect(),
+            edges: vec![make_edge(2, 1, "related_to"), make_edge(3, 1, "related_to")],
+        };
+        let ranks = graph.pagerank(0.85, 50);
+        assert!(ranks[&1] > ranks[&2]);
+
+        graph.apply_pagerank_importance(&ranks, 0.5);
+        // Top node: 0.5 * 0.5 + 0.5 * 1.0
+        assert!((graph.nodes[0].importance - 0.75).abs() < 1e-6);
+        assert!(graph.nodes[1].importance < graph.nodes[0].importance);
+    }
+
+    #[test]
+    fn test_centrality_without_edges() {
+        let graph = KnowledgeGraph {
+            nodes: (1..=3).map(|id| make_node(id, "note", vec![])).collect(),
+            edges: vec![],
+        };
+        let scores = graph.centrality();
+        for s in scores.values() {
+            assert_eq!(s.betweenness, 0.0);
+            assert_eq!(s.closeness, 0.0);
+            assert_eq!(s.eigenvector, 0.0);
+            assert_eq!(s.katz, 1.0);
+        }
+    }
+
     #[test]
     fn test_graph_filter() {
         let id1: MemoryId = 1;
@@ -1037,6 +1256,47 @@ mod tests {
         assert_eq!(subgraph.nodes.len(), 3);
     }
 
+    #[test]
+    fn test_ego_graph_walks_filtered_graph() {
+        // 1 (issue) - 2 (note) - 3 (decision), and 1 - 4 (decision) - 5 (decision)
+        let graph = KnowledgeGraph {
+            nodes: vec![
+                make_node(1, "issue", vec![]),
+                make_node(2, "note", vec![]),
+                make_node(3, "decision", vec![]),
+                make_node(4, "decision", vec![]),
+                make_node(5, "decision", vec![]),
+            ],
+            edges: vec![
+                make_edge(1, 2, "related_to"),
+                make_edge(2, 3, "related_to"),
+                make_edge(1, 4, "related_to"),
+                make_edge(4, 5, "supports"),
+            ],
+        };
+
+        // The center stays even though it is not a decision; 3 is only
+        // reachable through the filtered-out note
+        let filter = GraphFilter::new().with_types(vec!["decision".to_string()]);
+        let ego = graph.ego_graph(1, 3, &filter);
+        let mut ids: Vec<MemoryId> = ego.nodes.iter().map(|n| n.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 4, 5]);
+        assert_eq!(ego.edges.len(), 2);
+
+        let filter = GraphFilter {
+            edge_types: Some(vec!["related_to".to_string()]),
+            limit: Some(3),
+            ..Default::default()
+        };
+        let ego = graph.ego_graph(1, 3, &filter);
+        let mut ids: Vec<MemoryId> = ego.nodes.iter().map(|n| n.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2, 4], "nearest nodes are kept");
+
+        assert!(graph.ego_graph(42, 2, &GraphFilter::new()).nodes.is_empty());
+    }
+
     #[test]
     fn test_to_dot() {
         let id1: MemoryId = 1;
@@ -1057,6 +1317,89 @@ mod tests {
         assert!(dot.contains("related_to"));
     }
 
+    #[test]
+    fn test_exports_share_style_registry() {
+        let mut styles = StyleRegistry::default();
+        styles
+            .set(
+                "runbook",
+                NodeStyle {
+                    color: "#123456".to_string(),
+                    shape: NodeShape::Hexagon,
+                    icon: Some("R".to_string()),
+                },
+            )
+            .unwrap();
+        let graph = KnowledgeGraph {
+            nodes: vec![
+                make_node(1, "runbook", vec![]),
+                make_node(2, "note", vec![]),
+            ],
+            edges: vec![make_edge(1, 2, "related_to")],
+        };
+
+        let dot = graph.to_dot_with(&styles);
+        assert!(dot.contains("shape=hexagon, fillcolor=\"#123456\""));
+        assert!(dot.contains("fillcolor=\"#97C2FC\""));
+
+        let json = graph.to_visjs_json_with(&styles);
+        assert_eq!(json["nodes"][0]["color"], "#123456");
+        assert_eq!(json["nodes"][0]["shape"], "hexagon");
+
+        let html = graph.to_html_with(&styles);
+        assert!(html.contains("R runbook"));
+        assert!(html.contains(r##""runbook":{"color":"#123456","shape":"hexagon"}"##));
+
+        let gexf = graph.to_gexf_with(&styles);
+        assert!(gexf.contains(r#"<viz:color r="18" g="52" b="86"/>"#));
+
+        let graphml = graph.to_graphml_with(&styles);
+        assert!(graphml.contains(r##"<data key="color">#123456</data>"##));
+    }
+
+    #[test]
+    fn test_precomputed_layout_fixes_positions() {
+        let graph = KnowledgeGraph {
+            nodes: vec![make_node(1, "note", vec![]), make_node(2, "todo", vec![])],
+            edges: vec![make_edge(1, 2, "related_to")],
+        };
+        let layout = graph.force_layout(&LayoutConfig::default());
+        let styles = StyleRegistry::default();
+
+        let json = graph.to_visjs_json_with_layout(&styles, Some(&layout));
+        for node in json["nodes"].as_array().unwrap() {
+            assert!(node["x"].is_number() && node["y"].is_number());
+            assert_eq!(node["physics"], false);
+        }
+        assert!(graph.to_visjs_json()["nodes"][0].get("x").is_none());
+
+        let html = graph.to_html_with_layout(&styles, Some(&layout));
+        assert!(html.contains("physics: { enabled: false }"));
+        assert!(graph.to_html().contains("barnesHut"));
+    }
+
+    #[test]
+    fn test_to_graphml() {
+        let mut node = make_node(1, "note", vec!["rust", "a&b"]);
+        node.label = "<Tom & \"Jerry\">".to_string();
+        let graph = KnowledgeGraph {
+            nodes: vec![node, make_node(2, "todo", vec![])],
+            edges: vec![make_edge(1, 2, "depends_on")],
+        };
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.starts_with("<?xml"));
+        assert!(graphml.contains(r#"<graph id="engram" edgedefault="directed">"#));
+        assert!(graphml.contains(r#"<node id="n1">"#));
+        assert!(graphml.contains("&lt;Tom &amp; &quot;Jerry&quot;&gt;"));
+        assert!(graphml.contains(r#"<data key="tags">rust,a&amp;b</data>"#));
+        assert!(graphml.contains(r#"<edge id="e0" source="n1" target="n2">"#));
+        assert!(graphml.contains(r#"<data key="edge_type">depends_on</data>"#));
+        assert!(graphml.contains(r#"<data key="confidence">0.9</data>"#));
+        assert_eq!(graphml.matches("<node ").count(), 2);
+        assert!(graphml.trim_end().ends_with("</graphml>"));
+    }
+
     #[test]
     fn test_community_detection() {
         // Create two clusters
@@ -1098,4 +1441,39 @@ mod tests {
         // Largest community should have at least 2 members
         assert!(communities[0].members.len() >= 2);
     }
+
+    #[test]
+    fn test_louvain_communities_report_modularity() {
+        // Two triangles joined by one edge
+        let edges = [(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 6), (6, 4)];
+        let graph = KnowledgeGraph {
+            nodes: (1..=6).map(|id| make_node(id, "note", vec![])).collect(),
+            edges: edges
+                .iter()
+                .map(|&(a, b)| make_edge(a, b, "related_to"))
+                .collect(),
+        };
+
+        let clusters = graph.communities(CommunityAlgorithm::default());
+        assert_eq!(clusters.len(), 2);
+        let mut groups: Vec<Vec<MemoryId>> = clusters
+            .iter()
+            .map(|c| {
+                let mut members = c.members.clone();
+                members.sort();
+                members
+            })
+            .collect();
+        groups.sort();
+        assert_eq!(groups, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        // Q = 2 * (6/14 - (7/14)^2) = 5/14 for equal edge weights
+        let q: f32 = clusters.iter().map(|c| c.modularity).sum();
+        assert!((q - 5.0 / 14.0).abs() < 1e-5);
+
+        // Label propagation reports modularity for its partition too
+        let lp = graph.communities(CommunityAlgorithm::LabelPropagation { max_iterations: 10 });
+        let lp_q: f32 = lp.iter().map(|c| c.modularity).sum();
+        assert!(lp_q <= q + 1e-5);
+    }
 }
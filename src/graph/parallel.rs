@@ -0,0 +1,204 @@
+//! Parallel edge aggregation
+//!
+//! Two memories can be linked several times — `related_to` and `supports`,
+//! or a link in each direction. vis.js draws every one of those edges on the
+//! same line, so their labels pile up on top of each other.
+//! [`KnowledgeGraph::aggregate_parallel_edges`] merges all edges between the
+//! same pair of memories, in either direction, into one edge whose weight is
+//! the sum of theirs and whose tooltip lists what it stands for.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::summary::MIXED_EDGE_TYPE;
+use super::{GraphEdge, GraphLayout, KnowledgeGraph, StyleRegistry};
+use crate::types::MemoryId;
+
+/// A graph whose parallel edges were merged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregatedGraph {
+    /// Same nodes, one edge per linked pair of memories. A merged edge keeps
+    /// the direction of the first original, has `score` set to the summed
+    /// `score * confidence` of the originals and `confidence` 1.0, and is
+    /// typed [`MIXED_EDGE_TYPE`] when the originals' types differ.
+    pub graph: KnowledgeGraph,
+    /// `parallel[i]` holds the original edges behind `graph.edges[i]`
+    pub parallel: Vec<Vec<GraphEdge>>,
+}
+
+impl KnowledgeGraph {
+    /// Merge edges that join the same two memories, in either direction.
+    /// Edges without a parallel are kept unchanged.
+    pub fn aggregate_parallel_edges(&self) -> AggregatedGraph {
+        let mut index: HashMap<(MemoryId, MemoryId), usize> = HashMap::new();
+        let mut parallel: Vec<Vec<GraphEdge>> = Vec::new();
+        for edge in &self.edges {
+            let key = (edge.from.min(edge.to), edge.from.max(edge.to));
+            let i = *index.entry(key).or_insert_with(|| {
+                parallel.push(Vec::new());
+                parallel.len() - 1
+            });
+            parallel[i].push(edge.clone());
+        }
+
+        let edges = parallel
+            .iter()
+            .map(|group| {
+                let first = &group[0];
+                if group.len() == 1 {
+                    return first.clone();
+                }
+                let edge_type = if group.iter().all(|e| e.edge_type == first.edge_type) {
+                    first.edge_type.clone()
+                } else {
+                    MIXED_EDGE_TYPE.to_string()
+                };
+                GraphEdge {
+                    from: first.from,
+                    to: first.to,
+                    edge_type,
+                    score: group.iter().map(|e| e.score * e.confidence).sum(),
+                    confidence: 1.0,
+                }
+            })
+            .collect();
+
+        AggregatedGraph {
+            graph: KnowledgeGraph {
+                nodes: self.nodes.clone(),
+                edges,
+            },
+            parallel,
+        }
+    }
+}
+
+impl AggregatedGraph {
+    /// Export as vis.js compatible JSON. Merged edges are labelled with the
+    /// distinct types they combine, report their `count`, and list every
+    /// original link with its direction and weight in the tooltip.
+    pub fn to_visjs_json_with_layout(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+    ) -> serde_json::Value {
+        let mut data = self.graph.to_visjs_json_with_layout(styles, layout);
+        if let Some(edges) = data["edges"].as_array_mut() {
+            for ((json, edge), group) in edges.iter_mut().zip(&self.graph.edges).zip(&self.parallel)
+            {
+                if group.len() < 2 {
+                    continue;
+                }
+                let mut types: Vec<&str> = Vec::new();
+                for original in group {
+                    if !types.contains(&original.edge_type.as_str()) {
+                        types.push(&original.edge_type);
+                    }
+                }
+                let lines: Vec<String> = group
+                    .iter()
+                    .map(|e| {
+                        let arrow = if e.from == edge.from { "→" } else { "←" };
+                        format!(
+                            "{} {} ({:.2} × {:.2})",
+                            arrow, e.edge_type, e.score, e.confidence
+                        )
+                    })
+                    .collect();
+                json["label"] = serde_json::json!(types.join(", "));
+                json["value"] = serde_json::json!((edge.score * 5.0) as i32 + 1);
+                json["count"] = serde_json::json!(group.len());
+                json["title"] = serde_json::json!(format!(
+                    "{} links, total weight {:.2}\n{}",
+                    group.len(),
+                    edge.score,
+                    lines.join("\n")
+                ));
+            }
+        }
+        data
+    }
+
+    /// Standalone HTML of the aggregated graph; see
+    /// [`KnowledgeGraph::to_html_with_layout`]
+    pub fn to_html_with_layout(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+    ) -> String {
+        let graph_data = self.to_visjs_json_with_layout(styles, layout);
+        self.graph.html_page(&graph_data, styles, layout.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphNode;
+
+    fn node(id: MemoryId) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance: 0.5,
+            tags: vec![],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId, edge_type: &str, score: f32) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: edge_type.to_string(),
+            score,
+            confidence: 0.5,
+        }
+    }
+
+    fn graph() -> KnowledgeGraph {
+        KnowledgeGraph {
+            nodes: vec![node(1), node(2), node(3)],
+            edges: vec![
+                edge(1, 2, "related_to", 0.8),
+                edge(2, 3, "related_to", 0.4),
+                edge(2, 1, "supports", 0.6),
+                edge(1, 2, "related_to", 1.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parallel_edges_are_merged() {
+        let aggregated = graph().aggregate_parallel_edges();
+
+        assert_eq!(aggregated.graph.nodes.len(), 3);
+        assert_eq!(aggregated.graph.edges.len(), 2);
+        let merged = &aggregated.graph.edges[0];
+        assert_eq!((merged.from, merged.to), (1, 2));
+        assert_eq!(merged.edge_type, MIXED_EDGE_TYPE);
+        assert!((merged.score - 1.2).abs() < 1e-6);
+        assert_eq!(merged.confidence, 1.0);
+        assert_eq!(aggregated.parallel[0].len(), 3);
+
+        // A lone edge is kept as it was
+        assert_eq!(aggregated.graph.edges[1], edge(2, 3, "related_to", 0.4));
+    }
+
+    #[test]
+    fn test_visjs_breakdown() {
+        let data = graph()
+            .aggregate_parallel_edges()
+            .to_visjs_json_with_layout(&StyleRegistry::default(), None);
+        let edges = data["edges"].as_array().unwrap();
+
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0]["label"], "related_to, supports");
+        assert_eq!(edges[0]["count"], 3);
+        let title = edges[0]["title"].as_str().unwrap();
+        assert!(title.starts_with("3 links, total weight 1.20"));
+        assert!(title.contains("← supports (0.60 × 0.50)"));
+        assert_eq!(edges[1]["label"], "related_to");
+        assert!(edges[1].get("count").is_none());
+    }
+}
@@ -0,0 +1,1563 @@
+//! Cypher-like pattern queries over the knowledge graph
+//!
+//! [`GraphFilter`](super::GraphFilter) selects nodes and edges one at a time;
+//! this module matches multi-hop patterns against the `memories` and
+//! `crossrefs` tables:
+//!
+//! ```text
+//! MATCH (a:decision)-[:depends_on*1..3]->(b:issue)
+//! WHERE a.tag = 'auth' AND b.importance >= 0.5
+//! RETURN a, b
+//! LIMIT 20
+//! ```
+//!
+//! ## Syntax
+//!
+//! - Nodes: `(var:type1|type2 {prop: value, ...})`; every part is optional.
+//!   Labels are memory types.
+//! - Relationships: `-[var:edge_type1|edge_type2*min..max]->`, `<-[...]-` or
+//!   the undirected `-[...]-`; `-->`, `<--` and `--` match any edge. `*` alone
+//!   means 1 to [`MAX_VAR_HOPS`] hops, `*n` exactly n, and either bound of
+//!   `*min..max` may be left out.
+//! - `WHERE` combines `var.prop op literal` comparisons with `AND`, `OR`,
+//!   `NOT` and parentheses. Operators: `=`, `!=`/`<>`, `<`, `<=`, `>`, `>=`,
+//!   `CONTAINS`, `STARTS WITH`.
+//! - Node properties: `id`, `type`, `content`, `importance`, `tag` (true if
+//!   the memory has that tag), `workspace`, `tier`, `created_at`.
+//!   Relationship properties (single-hop only): `type`, `score`, `confidence`.
+//! - `RETURN` lists variables (default: all named ones); `LIMIT` caps rows.
+//!
+//! Keywords are case-insensitive. `type`, `tag` and edge types compare
+//! case-insensitively, as do `CONTAINS` and `STARTS WITH`.
+//!
+//! ## Semantics
+//!
+//! Only current memories and cross-references are matched (deleted, expired
+//! and invalidated rows are skipped). A variable-length segment never visits
+//! the same memory twice. Repeating a node variable, as in
+//! `(a)-->(b)-->(a)`, requires both positions to bind the same memory.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use rusqlite::{params_from_iter, Connection};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::label::truncate_graphemes;
+use crate::error::{EngramError, Result};
+use crate::storage::queries::load_tags;
+use crate::types::MemoryId;
+
+/// Upper bound for variable-length relationships
+pub const MAX_VAR_HOPS: usize = 6;
+
+/// Default row limit when the query has no `LIMIT`
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Partial matches kept while expanding a pattern before the query is
+/// rejected as too broad
+const MAX_INTERMEDIATE_MATCHES: usize = 100_000;
+
+/// Characters of content included with each returned node
+const CONTENT_PREVIEW_CHARS: usize = 200;
+
+// =============================================================================
+// AST
+// =============================================================================
+
+/// A parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphQuery {
+    pub pattern: Pattern,
+    pub condition: Option<Condition>,
+    /// Variables to return; empty means every named variable
+    pub returns: Vec<String>,
+    pub limit: Option<usize>,
+}
+
+/// `(node) (-[rel]-> (node))*`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub start: NodePattern,
+    pub steps: Vec<(RelPattern, NodePattern)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodePattern {
+    pub var: Option<String>,
+    /// Memory types, any of which matches
+    pub labels: Vec<String>,
+    /// Inline `{prop: value}` equalities
+    pub props: Vec<(String, Literal)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelPattern {
+    pub var: Option<String>,
+    /// Edge types, any of which matches
+    pub types: Vec<String>,
+    pub direction: RelDirection,
+    /// `(min, max)` hops for variable-length relationships
+    pub hops: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelDirection {
+    /// `-[]->`
+    Outgoing,
+    /// `<-[]-`
+    Incoming,
+    /// `-[]-`
+    Either,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    StartsWith,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare {
+        var: String,
+        prop: String,
+        op: CompareOp,
+        value: Literal,
+    },
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    fn collect_vars<'a>(&'a self, vars: &mut HashSet<&'a str>) {
+        match self {
+            Condition::Compare { var, .. } => {
+                vars.insert(var);
+            }
+            Condition::And(a, b) | Condition::Or(a, b) => {
+                a.collect_vars(vars);
+                b.collect_vars(vars);
+            }
+            Condition::Not(c) => c.collect_vars(vars),
+        }
+    }
+
+    /// Split a top-level `AND` chain into its conjuncts
+    fn conjuncts(self, out: &mut Vec<Condition>) {
+        match self {
+            Condition::And(a, b) => {
+                a.conjuncts(out);
+                b.conjuncts(out);
+            }
+            other => out.push(other),
+        }
+    }
+}
+
+// =============================================================================
+// Lexer
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Colon,
+    Comma,
+    Dot,
+    DotDot,
+    Pipe,
+    Star,
+    Dash,
+    /// `->`
+    ArrowRight,
+    /// `<-`
+    ArrowLeft,
+    Op(CompareOp),
+    Ident(String),
+    Str(String),
+    Number(f64),
+}
+
+fn syntax_error(message: impl std::fmt::Display, pos: usize) -> EngramError {
+    EngramError::InvalidInput(format!("graph query: {} at position {}", message, pos))
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            ':' => Token::Colon,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '*' => Token::Star,
+            '.' if next == Some('.') => {
+                i += 1;
+                Token::DotDot
+            }
+            '.' => Token::Dot,
+            '-' if next == Some('>') => {
+                i += 1;
+                Token::ArrowRight
+            }
+            '-' => Token::Dash,
+            '<' if next == Some('-') => {
+                i += 1;
+                Token::ArrowLeft
+            }
+            '<' if next == Some('=') => {
+                i += 1;
+                Token::Op(CompareOp::Le)
+            }
+            '<' if next == Some('>') => {
+                i += 1;
+                Token::Op(CompareOp::Ne)
+            }
+            '<' => Token::Op(CompareOp::Lt),
+            '>' if next == Some('=') => {
+                i += 1;
+                Token::Op(CompareOp::Ge)
+            }
+            '>' => Token::Op(CompareOp::Gt),
+            '=' => Token::Op(CompareOp::Eq),
+            '!' if next == Some('=') => {
+                i += 1;
+                Token::Op(CompareOp::Ne)
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error("unterminated string", start)),
+                        Some('\\') if i + 1 < chars.len() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&ch) if ch == quote => break,
+                        Some(&ch) => {
+                            value.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::Str(value)
+            }
+            c if c.is_ascii_digit() => {
+                let mut end = i;
+                while end < chars.len() && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                // A single '.' followed by a digit is a decimal point; '..' is a range
+                if end + 1 < chars.len() && chars[end] == '.' && chars[end + 1].is_ascii_digit() {
+                    end += 1;
+                    while end < chars.len() && chars[end].is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+                let text: String = chars[i..end].iter().collect();
+                i = end - 1;
+                Token::Number(
+                    text.parse()
+                        .map_err(|_| syntax_error(format!("invalid number '{}'", text), start))?,
+                )
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = i;
+                while end < chars.len() && (chars[end].is_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                let text: String = chars[i..end].iter().collect();
+                i = end - 1;
+                Token::Ident(text)
+            }
+            other => return Err(syntax_error(format!("unexpected '{}'", other), start)),
+        };
+        tokens.push((token, start));
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|(t, _)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(_, p)| *p)
+            .unwrap_or(self.end)
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> EngramError {
+        syntax_error(message, self.position())
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(t, _)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {}", what)))
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<String> {
+        match self.peek() {
+            Some(Token::Ident(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => Err(self.error(format!("expected {}", what))),
+        }
+    }
+
+    fn usize_literal(&mut self) -> Result<usize> {
+        match self.peek() {
+            Some(Token::Number(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                let n = *n as usize;
+                self.pos += 1;
+                Ok(n)
+            }
+            _ => Err(self.error("expected a whole number")),
+        }
+    }
+
+    fn query(&mut self) -> Result<GraphQuery> {
+        if !self.eat_keyword("MATCH") {
+            return Err(self.error("expected MATCH"));
+        }
+        let pattern = self.pattern()?;
+
+        let condition = if self.eat_keyword("WHERE") {
+            Some(self.or_condition()?)
+        } else {
+            None
+        };
+
+        let mut returns = Vec::new();
+        if self.eat_keyword("RETURN") {
+            loop {
+                returns.push(self.ident("a variable to return")?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+
+        let limit = if self.eat_keyword("LIMIT") {
+            Some(self.usize_literal()?)
+        } else {
+            None
+        };
+
+        if self.peek().is_some() {
+            return Err(self.error("unexpected trailing input"));
+        }
+
+        Ok(GraphQuery {
+            pattern,
+            condition,
+            returns,
+            limit,
+        })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        let start = self.node()?;
+        let mut steps = Vec::new();
+        while matches!(self.peek(), Some(Token::Dash | Token::ArrowLeft)) {
+            let rel = self.relationship()?;
+            let node = self.node()?;
+            steps.push((rel, node));
+        }
+        Ok(Pattern { start, steps })
+    }
+
+    fn labels(&mut self, what: &str) -> Result<Vec<String>> {
+        let mut labels = Vec::new();
+        if self.eat(&Token::Colon) {
+            loop {
+                labels.push(self.ident(what)?.to_lowercase());
+                if !self.eat(&Token::Pipe) {
+                    break;
+                }
+                // Cypher also accepts `:a|:b`
+                self.eat(&Token::Colon);
+            }
+        }
+        Ok(labels)
+    }
+
+    fn node(&mut self) -> Result<NodePattern> {
+        self.expect(&Token::LParen, "'(' to start a node")?;
+        let var = match self.peek() {
+            Some(Token::Ident(_)) => Some(self.ident("a variable")?),
+            _ => None,
+        };
+        let labels = self.labels("a memory type")?;
+
+        let mut props = Vec::new();
+        if self.eat(&Token::LBrace) && !self.eat(&Token::RBrace) {
+            loop {
+                let prop = self.ident("a property name")?;
+                self.expect(&Token::Colon, "':' after property name")?;
+                props.push((prop.to_lowercase(), self.literal()?));
+                if self.eat(&Token::RBrace) {
+                    break;
+                }
+                self.expect(&Token::Comma, "',' or '}'")?;
+            }
+        }
+
+        self.expect(&Token::RParen, "')' to close the node")?;
+        Ok(NodePattern { var, labels, props })
+    }
+
+    fn relationship(&mut self) -> Result<RelPattern> {
+        let incoming = self.eat(&Token::ArrowLeft);
+        if !incoming {
+            self.expect(&Token::Dash, "'-' or '<-'")?;
+        }
+
+        let mut var = None;
+        let mut types = Vec::new();
+        let mut hops = None;
+
+        if self.eat(&Token::LBracket) {
+            if let Some(Token::Ident(_)) = self.peek() {
+                var = Some(self.ident("a variable")?);
+            }
+            types = self.labels("an edge type")?;
+            if self.eat(&Token::Star) {
+                hops = Some(self.hop_range()?);
+            }
+            self.expect(&Token::RBracket, "']' to close the relationship")?;
+        }
+
+        let outgoing = if self.eat(&Token::ArrowRight) {
+            true
+        } else {
+            self.expect(&Token::Dash, "'-' or '->'")?;
+            false
+        };
+
+        let direction = match (incoming, outgoing) {
+            (false, true) => RelDirection::Outgoing,
+            (true, false) => RelDirection::Incoming,
+            (false, false) => RelDirection::Either,
+            (true, true) => return Err(self.error("a relationship can't point both ways")),
+        };
+
+        Ok(RelPattern {
+            var,
+            types,
+            direction,
+            hops,
+        })
+    }
+
+    fn hop_range(&mut self) -> Result<(usize, usize)> {
+        let min = match self.peek() {
+            Some(Token::Number(_)) => Some(self.usize_literal()?),
+            _ => None,
+        };
+        let (min, max) = if self.eat(&Token::DotDot) {
+            let max = match self.peek() {
+                Some(Token::Number(_)) => self.usize_literal()?,
+                _ => MAX_VAR_HOPS,
+            };
+            (min.unwrap_or(1), max)
+        } else {
+            match min {
+                Some(n) => (n, n),
+                None => (1, MAX_VAR_HOPS),
+            }
+        };
+
+        if max > MAX_VAR_HOPS {
+            return Err(self.error(format!(
+                "variable-length relationships are limited to {} hops",
+                MAX_VAR_HOPS
+            )));
+        }
+        if min == 0 || min > max {
+            return Err(self.error(format!("invalid hop range {}..{}", min, max)));
+        }
+        Ok((min, max))
+    }
+
+    fn or_condition(&mut self) -> Result<Condition> {
+        let mut left = self.and_condition()?;
+        while self.eat_keyword("OR") {
+            let right = self.and_condition()?;
+            left = Condition::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and_condition(&mut self) -> Result<Condition> {
+        let mut left = self.not_condition()?;
+        while self.eat_keyword("AND") {
+            let right = self.not_condition()?;
+            left = Condition::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn not_condition(&mut self) -> Result<Condition> {
+        if self.eat_keyword("NOT") {
+            return Ok(Condition::Not(Box::new(self.not_condition()?)));
+        }
+        if self.eat(&Token::LParen) {
+            let inner = self.or_condition()?;
+            self.expect(&Token::RParen, "')'")?;
+            return Ok(inner);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Condition> {
+        let var = self.ident("a variable")?;
+        self.expect(&Token::Dot, "'.' after variable")?;
+        let prop = self.ident("a property name")?.to_lowercase();
+
+        let op = match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.pos += 1;
+                op
+            }
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case("CONTAINS") => {
+                self.pos += 1;
+                CompareOp::Contains
+            }
+            Some(Token::Ident(s))
+                if s.eq_ignore_ascii_case("STARTS")
+                    && matches!(self.peek_at(1), Some(Token::Ident(w)) if w.eq_ignore_ascii_case("WITH")) =>
+            {
+                self.pos += 2;
+                CompareOp::StartsWith
+            }
+            _ => return Err(self.error("expected a comparison operator")),
+        };
+
+        let value = self.literal()?;
+        Ok(Condition::Compare {
+            var,
+            prop,
+            op,
+            value,
+        })
+    }
+
+    fn literal(&mut self) -> Result<Literal> {
+        let negative = self.eat(&Token::Dash);
+        match self.advance() {
+            Some(Token::Number(n)) => Ok(Literal::Number(if negative { -n } else { n })),
+            Some(Token::Str(s)) if !negative => Ok(Literal::String(s)),
+            Some(Token::Ident(s)) if !negative && s.eq_ignore_ascii_case("true") => {
+                Ok(Literal::Bool(true))
+            }
+            Some(Token::Ident(s)) if !negative && s.eq_ignore_ascii_case("false") => {
+                Ok(Literal::Bool(false))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a string, number or boolean"))
+            }
+        }
+    }
+}
+
+const NODE_PROPS: &[&str] = &[
+    "id",
+    "type",
+    "content",
+    "importance",
+    "tag",
+    "workspace",
+    "tier",
+    "created_at",
+];
+
+const REL_PROPS: &[&str] = &["type", "score", "confidence"];
+
+impl GraphQuery {
+    /// Parse and validate a query
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: input.chars().count(),
+        };
+        let query = parser.query()?;
+        query.validate()?;
+        Ok(query)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| EngramError::InvalidInput(format!("graph query: {}", msg));
+
+        let mut node_vars = HashSet::new();
+        let mut rel_vars: HashMap<&str, bool> = HashMap::new();
+        for node in self.nodes() {
+            if let Some(ref var) = node.var {
+                node_vars.insert(var.as_str());
+            }
+            for (prop, _) in &node.props {
+                if !NODE_PROPS.contains(&prop.as_str()) {
+                    return Err(invalid(format!("unknown node property '{}'", prop)));
+                }
+            }
+        }
+        for (rel, _) in &self.pattern.steps {
+            if let Some(ref var) = rel.var {
+                if node_vars.contains(var.as_str()) || rel_vars.contains_key(var.as_str()) {
+                    return Err(invalid(format!("variable '{}' is bound twice", var)));
+                }
+                rel_vars.insert(var, rel.hops.is_some());
+            }
+        }
+
+        if let Some(ref condition) = self.condition {
+            self.validate_condition(condition, &node_vars, &rel_vars)?;
+        }
+        for var in &self.returns {
+            if !node_vars.contains(var.as_str()) && !rel_vars.contains_key(var.as_str()) {
+                return Err(invalid(format!("unknown variable '{}' in RETURN", var)));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_condition(
+        &self,
+        condition: &Condition,
+        node_vars: &HashSet<&str>,
+        rel_vars: &HashMap<&str, bool>,
+    ) -> Result<()> {
+        let invalid = |msg: String| EngramError::InvalidInput(format!("graph query: {}", msg));
+        match condition {
+            Condition::Compare { var, prop, .. } => {
+                if node_vars.contains(var.as_str()) {
+                    if !NODE_PROPS.contains(&prop.as_str()) {
+                        return Err(invalid(format!("unknown node property '{}'", prop)));
+                    }
+                } else if let Some(&variable_length) = rel_vars.get(var.as_str()) {
+                    if variable_length {
+                        return Err(invalid(format!(
+                            "'{}' is a variable-length relationship and has no properties",
+                            var
+                        )));
+                    }
+                    if !REL_PROPS.contains(&prop.as_str()) {
+                        return Err(invalid(format!("unknown relationship property '{}'", prop)));
+                    }
+                } else {
+                    return Err(invalid(format!("unknown variable '{}' in WHERE", var)));
+                }
+                Ok(())
+            }
+            Condition::And(a, b) | Condition::Or(a, b) => {
+                self.validate_condition(a, node_vars, rel_vars)?;
+                self.validate_condition(b, node_vars, rel_vars)
+            }
+            Condition::Not(c) => self.validate_condition(c, node_vars, rel_vars),
+        }
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = &NodePattern> {
+        std::iter::once(&self.pattern.start).chain(self.pattern.steps.iter().map(|(_, n)| n))
+    }
+
+    /// Variables returned by `execute`, in column order
+    pub fn columns(&self) -> Vec<String> {
+        if !self.returns.is_empty() {
+            return self.returns.clone();
+        }
+        let mut columns: Vec<String> = Vec::new();
+        let mut push = |var: &Option<String>| {
+            if let Some(var) = var {
+                if !columns.contains(var) {
+                    columns.push(var.clone());
+                }
+            }
+        };
+        push(&self.pattern.start.var);
+        for (rel, node) in &self.pattern.steps {
+            push(&rel.var);
+            push(&node.var);
+        }
+        columns
+    }
+}
+
+// =============================================================================
+// Executor
+// =============================================================================
+
+/// A memory as seen by the query engine
+#[derive(Debug, Clone)]
+struct NodeRow {
+    id: MemoryId,
+    memory_type: String,
+    content: String,
+    importance: f64,
+    workspace: String,
+    tier: String,
+    created_at: String,
+    tags: Vec<String>,
+}
+
+/// A current cross-reference
+#[derive(Debug, Clone, PartialEq)]
+struct EdgeRow {
+    from: MemoryId,
+    to: MemoryId,
+    edge_type: String,
+    score: f64,
+    confidence: f64,
+}
+
+impl EdgeRow {
+    fn to_json(&self) -> Value {
+        json!({
+            "from": self.from,
+            "to": self.to,
+            "type": self.edge_type,
+            "score": self.score,
+            "confidence": self.confidence,
+        })
+    }
+}
+
+/// A partial or complete match: one memory per pattern node, and the edges
+/// walked for each relationship
+#[derive(Debug, Clone)]
+struct Binding {
+    nodes: Vec<MemoryId>,
+    rels: Vec<Vec<EdgeRow>>,
+}
+
+/// Query results
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    /// One object per match, keyed by variable. Nodes are
+    /// `{id, type, content, importance, tags, workspace}`; relationships are
+    /// an edge `{from, to, type, score, confidence}`, or a list of edges for
+    /// variable-length ones.
+    pub rows: Vec<Value>,
+    /// More matches exist beyond the limit
+    pub truncated: bool,
+}
+
+enum Field<'a> {
+    Str(&'a str),
+    Num(f64),
+}
+
+fn compare(actual: Field, op: CompareOp, expected: &Literal, case_insensitive: bool) -> bool {
+    use std::cmp::Ordering;
+
+    let ordering = match (&actual, expected) {
+        (Field::Num(a), Literal::Number(b)) => a.partial_cmp(b),
+        (Field::Str(a), Literal::String(b)) => {
+            let (a, b) =
+                if case_insensitive || matches!(op, CompareOp::Contains | CompareOp::StartsWith) {
+                    (a.to_lowercase(), b.to_lowercase())
+                } else {
+                    (a.to_string(), b.clone())
+                };
+            match op {
+                CompareOp::Contains => return a.contains(&b),
+                CompareOp::StartsWith => return a.starts_with(&b),
+                _ => Some(a.cmp(&b)),
+            }
+        }
+        _ => None,
+    };
+
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        CompareOp::Eq => ordering == Ordering::Equal,
+        CompareOp::Ne => ordering != Ordering::Equal,
+        CompareOp::Lt => ordering == Ordering::Less,
+        CompareOp::Le => ordering != Ordering::Greater,
+        CompareOp::Gt => ordering == Ordering::Greater,
+        CompareOp::Ge => ordering != Ordering::Less,
+        CompareOp::Contains | CompareOp::StartsWith => false,
+    }
+}
+
+fn node_matches_prop(node: &NodeRow, prop: &str, op: CompareOp, value: &Literal) -> bool {
+    match prop {
+        "id" => compare(Field::Num(node.id as f64), op, value, false),
+        "type" => compare(Field::Str(&node.memory_type), op, value, true),
+        "content" => compare(Field::Str(&node.content), op, value, false),
+        "importance" => compare(Field::Num(node.importance), op, value, false),
+        "workspace" => compare(Field::Str(&node.workspace), op, value, false),
+        "tier" => compare(Field::Str(&node.tier), op, value, true),
+        "created_at" => compare(Field::Str(&node.created_at), op, value, false),
+        "tag" => {
+            // `tag = x` asks whether any tag equals x; `tag != x` whether none does
+            let any = |op| {
+                node.tags
+                    .iter()
+                    .any(|t| compare(Field::Str(t), op, value, true))
+            };
+            match op {
+                CompareOp::Ne => !any(CompareOp::Eq),
+                op => any(op),
+            }
+        }
+        _ => false,
+    }
+}
+
+fn edge_matches_prop(edge: &EdgeRow, prop: &str, op: CompareOp, value: &Literal) -> bool {
+    match prop {
+        "type" => compare(Field::Str(&edge.edge_type), op, value, true),
+        "score" => compare(Field::Num(edge.score), op, value, false),
+        "confidence" => compare(Field::Num(edge.confidence), op, value, false),
+        _ => false,
+    }
+}
+
+struct Executor<'a> {
+    conn: &'a Connection,
+    query: &'a GraphQuery,
+    workspace: Option<&'a str>,
+    now: String,
+    nodes: HashMap<MemoryId, Option<NodeRow>>,
+    adjacency: HashMap<MemoryId, Vec<EdgeRow>>,
+    /// First pattern position of each node variable
+    node_positions: HashMap<&'a str, usize>,
+    /// Step index of each relationship variable
+    rel_positions: HashMap<&'a str, usize>,
+    /// WHERE conjuncts that only mention one node position
+    node_filters: HashMap<usize, Vec<Condition>>,
+    /// WHERE conjuncts that only mention one single-hop relationship
+    rel_filters: HashMap<usize, Vec<Condition>>,
+    /// Everything else, checked on complete matches
+    residual: Vec<Condition>,
+}
+
+const NODE_COLUMNS: &str = "id, memory_type, content, importance, workspace, tier, created_at";
+
+impl<'a> Executor<'a> {
+    fn new(conn: &'a Connection, query: &'a GraphQuery, workspace: Option<&'a str>) -> Self {
+        let mut node_positions = HashMap::new();
+        for (i, node) in query.nodes().enumerate() {
+            if let Some(ref var) = node.var {
+                node_positions.entry(var.as_str()).or_insert(i);
+            }
+        }
+        let mut rel_positions = HashMap::new();
+        for (i, (rel, _)) in query.pattern.steps.iter().enumerate() {
+            if let Some(ref var) = rel.var {
+                rel_positions.insert(var.as_str(), i);
+            }
+        }
+
+        let mut executor = Self {
+            conn,
+            query,
+            workspace,
+            now: Utc::now().to_rfc3339(),
+            nodes: HashMap::new(),
+            adjacency: HashMap::new(),
+            node_positions,
+            rel_positions,
+            node_filters: HashMap::new(),
+            rel_filters: HashMap::new(),
+            residual: Vec::new(),
+        };
+
+        let mut conjuncts = Vec::new();
+        if let Some(condition) = query.condition.clone() {
+            condition.conjuncts(&mut conjuncts);
+        }
+        for conjunct in conjuncts {
+            let mut vars = HashSet::new();
+            conjunct.collect_vars(&mut vars);
+            let single = if vars.len() == 1 {
+                vars.into_iter().next().map(str::to_string)
+            } else {
+                None
+            };
+            match single {
+                Some(var) if executor.node_positions.contains_key(var.as_str()) => {
+                    let pos = executor.node_positions[var.as_str()];
+                    executor.node_filters.entry(pos).or_default().push(conjunct);
+                }
+                Some(var) if executor.rel_positions.contains_key(var.as_str()) => {
+                    let pos = executor.rel_positions[var.as_str()];
+                    executor.rel_filters.entry(pos).or_default().push(conjunct);
+                }
+                _ => executor.residual.push(conjunct),
+            }
+        }
+        executor
+    }
+
+    fn node_from_row(&self, row: &rusqlite::Row) -> rusqlite::Result<NodeRow> {
+        Ok(NodeRow {
+            id: row.get(0)?,
+            memory_type: row.get(1)?,
+            content: row.get(2)?,
+            importance: row.get(3)?,
+            workspace: row.get(4)?,
+            tier: row.get(5)?,
+            created_at: row.get(6)?,
+            tags: Vec::new(),
+        })
+    }
+
+    fn node(&mut self, id: MemoryId) -> Result<Option<&NodeRow>> {
+        if !self.nodes.contains_key(&id) {
+            let mut stmt = self.conn.prepare_cached(&format!(
+                "SELECT {} FROM memories
+                 WHERE id = ? AND valid_to IS NULL
+                   AND (expires_at IS NULL OR expires_at > ?)",
+                NODE_COLUMNS
+            ))?;
+            let mut rows = stmt.query(rusqlite::params![id, self.now])?;
+            let node = match rows.next()? {
+                Some(row) => {
+                    let mut node = self.node_from_row(row)?;
+                    node.tags = load_tags(self.conn, id)?;
+                    Some(node)
+                }
+                None => None,
+            };
+            self.nodes.insert(id, node);
+        }
+        Ok(self.nodes[&id].as_ref())
+    }
+
+    /// Memories that can bind the first pattern node
+    fn start_candidates(&mut self) -> Result<Vec<MemoryId>> {
+        let labels = &self.query.pattern.start.labels;
+        let mut sql = format!(
+            "SELECT {} FROM memories
+             WHERE valid_to IS NULL AND (expires_at IS NULL OR expires_at > ?)",
+            NODE_COLUMNS
+        );
+        let mut params: Vec<String> = vec![self.now.clone()];
+        if !labels.is_empty() {
+            let placeholders = vec!["?"; labels.len()].join(", ");
+            sql.push_str(&format!(" AND lower(memory_type) IN ({})", placeholders));
+            params.extend(labels.iter().cloned());
+        }
+        if let Some(workspace) = self.workspace {
+            sql.push_str(" AND workspace = ?");
+            params.push(workspace.to_string());
+        }
+        sql.push_str(" ORDER BY id");
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows: Vec<NodeRow> = stmt
+            .query_map(params_from_iter(params.iter()), |row| {
+                self.node_from_row(row)
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        for mut node in rows {
+            node.tags = load_tags(self.conn, node.id)?;
+            ids.push(node.id);
+            self.nodes.insert(node.id, Some(node));
+        }
+        Ok(ids)
+    }
+
+    fn edges(&mut self, id: MemoryId) -> Result<&[EdgeRow]> {
+        if !self.adjacency.contains_key(&id) {
+            let mut stmt = self.conn.prepare_cached(
+                "SELECT from_id, to_id, edge_type, score, confidence FROM crossrefs
+                 WHERE (from_id = ?1 OR to_id = ?1) AND valid_to IS NULL
+                 ORDER BY from_id, to_id, edge_type",
+            )?;
+            let edges = stmt
+                .query_map([id], |row| {
+                    Ok(EdgeRow {
+                        from: row.get(0)?,
+                        to: row.get(1)?,
+                        edge_type: row.get(2)?,
+                        score: row.get(3)?,
+                        confidence: row.get(4)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            self.adjacency.insert(id, edges);
+        }
+        Ok(&self.adjacency[&id])
+    }
+
+    /// Edges leaving `id` that `rel` may follow, with the memory on the far side
+    fn steps_from(&mut self, id: MemoryId, rel: &RelPattern) -> Result<Vec<(EdgeRow, MemoryId)>> {
+        let edges = self.edges(id)?;
+        Ok(edges
+            .iter()
+            .filter(|e| rel.types.is_empty() || rel.types.contains(&e.edge_type.to_lowercase()))
+            .filter_map(|e| {
+                let next = match rel.direction {
+                    RelDirection::Outgoing if e.from == id => e.to,
+                    RelDirection::Incoming if e.to == id => e.from,
+                    RelDirection::Either if e.from == id => e.to,
+                    RelDirection::Either => e.from,
+                    _ => return None,
+                };
+                Some((e.clone(), next))
+            })
+            .collect())
+    }
+
+    fn eval_node_condition(&self, condition: &Condition, node: &NodeRow) -> bool {
+        match condition {
+            Condition::Compare {
+                prop, op, value, ..
+            } => node_matches_prop(node, prop, *op, value),
+            Condition::And(a, b) => {
+                self.eval_node_condition(a, node) && self.eval_node_condition(b, node)
+            }
+            Condition::Or(a, b) => {
+                self.eval_node_condition(a, node) || self.eval_node_condition(b, node)
+            }
+            Condition::Not(c) => !self.eval_node_condition(c, node),
+        }
+    }
+
+    fn eval_edge_condition(&self, condition: &Condition, edge: &EdgeRow) -> bool {
+        match condition {
+            Condition::Compare {
+                prop, op, value, ..
+            } => edge_matches_prop(edge, prop, *op, value),
+            Condition::And(a, b) => {
+                self.eval_edge_condition(a, edge) && self.eval_edge_condition(b, edge)
+            }
+            Condition::Or(a, b) => {
+                self.eval_edge_condition(a, edge) || self.eval_edge_condition(b, edge)
+            }
+            Condition::Not(c) => !self.eval_edge_condition(c, edge),
+        }
+    }
+
+    /// Whether memory `id` can bind pattern node `pos`
+    fn node_passes(&mut self, pos: usize, id: MemoryId) -> Result<bool> {
+        let query = self.query;
+        let pattern = query.nodes().nth(pos).expect("pattern position in range");
+        let workspace = self.workspace;
+        let Some(node) = self.node(id)?.cloned() else {
+            return Ok(false);
+        };
+
+        if !pattern.labels.is_empty() && !pattern.labels.contains(&node.memory_type.to_lowercase())
+        {
+            return Ok(false);
+        }
+        if workspace.is_some_and(|ws| ws != node.workspace) {
+            return Ok(false);
+        }
+        if !pattern
+            .props
+            .iter()
+            .all(|(prop, value)| node_matches_prop(&node, prop, CompareOp::Eq, value))
+        {
+            return Ok(false);
+        }
+        // A repeated variable binds wherever it first appears; its filters
+        // apply there
+        let filters = self.node_filters.get(&pos);
+        Ok(filters.is_none_or(|conds| conds.iter().all(|c| self.eval_node_condition(c, &node))))
+    }
+
+    fn eval(&self, condition: &Condition, binding: &Binding) -> bool {
+        match condition {
+            Condition::Compare {
+                var,
+                prop,
+                op,
+                value,
+            } => {
+                if let Some(&pos) = self.node_positions.get(var.as_str()) {
+                    self.nodes
+                        .get(&binding.nodes[pos])
+                        .and_then(|n| n.as_ref())
+                        .is_some_and(|n| node_matches_prop(n, prop, *op, value))
+                } else if let Some(&pos) = self.rel_positions.get(var.as_str()) {
+                    binding.rels[pos]
+                        .first()
+                        .is_some_and(|e| edge_matches_prop(e, prop, *op, value))
+                } else {
+                    false
+                }
+            }
+            Condition::And(a, b) => self.eval(a, binding) && self.eval(b, binding),
+            Condition::Or(a, b) => self.eval(a, binding) || self.eval(b, binding),
+            Condition::Not(c) => !self.eval(c, binding),
+        }
+    }
+
+    /// Whether `id` at position `pos` is consistent with earlier bindings of
+    /// the same variable
+    fn consistent(&self, binding: &Binding, pos: usize, id: MemoryId) -> bool {
+        let var = self.query.nodes().nth(pos).and_then(|n| n.var.as_deref());
+        match var.and_then(|v| self.node_positions.get(v)) {
+            Some(&first) if first < pos => binding.nodes[first] == id,
+            _ => true,
+        }
+    }
+
+    fn extend(&mut self, binding: &Binding, step: usize) -> Result<Vec<Binding>> {
+        let query = self.query;
+        let (rel, _) = &query.pattern.steps[step];
+        let pos = step + 1;
+        let from = *binding.nodes.last().expect("bindings start with a node");
+        let mut out = Vec::new();
+
+        match rel.hops {
+            None => {
+                for (edge, next) in self.steps_from(from, rel)? {
+                    if let Some(filters) = self.rel_filters.get(&step) {
+                        if !filters.iter().all(|c| self.eval_edge_condition(c, &edge)) {
+                            continue;
+                        }
+                    }
+                    if !self.consistent(binding, pos, next) || !self.node_passes(pos, next)? {
+                        continue;
+                    }
+                    let mut extended = binding.clone();
+                    extended.nodes.push(next);
+                    extended.rels.push(vec![edge]);
+                    out.push(extended);
+                }
+            }
+            Some((min, max)) => {
+                // Depth-first over simple paths of up to `max` edges
+                let mut stack: Vec<(MemoryId, Vec<EdgeRow>, Vec<MemoryId>)> =
+                    vec![(from, Vec::new(), vec![from])];
+                while let Some((at, edges, visited)) = stack.pop() {
+                    if edges.len() >= min
+                        && self.consistent(binding, pos, at)
+                        && self.node_passes(pos, at)?
+                    {
+                        let mut extended = binding.clone();
+                        extended.nodes.push(at);
+                        extended.rels.push(edges.clone());
+                        out.push(extended);
+                    }
+                    if edges.len() == max {
+                        continue;
+                    }
+                    for (edge, next) in self.steps_from(at, rel)?.into_iter().rev() {
+                        if visited.contains(&next) {
+                            continue;
+                        }
+                        let mut edges = edges.clone();
+                        edges.push(edge);
+                        let mut visited = visited.clone();
+                        visited.push(next);
+                        stack.push((next, edges, visited));
+                    }
+                    if out.len() > MAX_INTERMEDIATE_MATCHES {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn run(&mut self, limit: usize) -> Result<QueryResult> {
+        let mut bindings = Vec::new();
+        for id in self.start_candidates()? {
+            if self.node_passes(0, id)? {
+                bindings.push(Binding {
+                    nodes: vec![id],
+                    rels: Vec::new(),
+                });
+            }
+        }
+
+        for step in 0..self.query.pattern.steps.len() {
+            let mut next = Vec::new();
+            for binding in &bindings {
+                next.extend(self.extend(binding, step)?);
+                if next.len() > MAX_INTERMEDIATE_MATCHES {
+                    return Err(EngramError::InvalidInput(format!(
+                        "graph query: pattern matches more than {} partial paths; add labels, \
+                         edge types or WHERE conditions to narrow it",
+                        MAX_INTERMEDIATE_MATCHES
+                    )));
+                }
+            }
+            bindings = next;
+        }
+
+        let columns = self.query.columns();
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        for binding in &bindings {
+            if !self.residual.iter().all(|c| self.eval(c, binding)) {
+                continue;
+            }
+            if rows.len() == limit {
+                truncated = true;
+                break;
+            }
+            rows.push(self.row_json(&columns, binding));
+        }
+
+        Ok(QueryResult {
+            columns,
+            rows,
+            truncated,
+        })
+    }
+
+    fn row_json(&self, columns: &[String], binding: &Binding) -> Value {
+        let mut row = serde_json::Map::new();
+        for column in columns {
+            let value = if let Some(&pos) = self.node_positions.get(column.as_str()) {
+                match self.nodes.get(&binding.nodes[pos]).and_then(|n| n.as_ref()) {
+                    Some(node) => json!({
+                        "id": node.id,
+                        "type": node.memory_type,
+                        "content": truncate_graphemes(&node.content, CONTENT_PREVIEW_CHARS),
+                        "importance": node.importance,
+                        "tags": node.tags,
+                        "workspace": node.workspace,
+                    }),
+                    None => Value::Null,
+                }
+            } else if let Some(&pos) = self.rel_positions.get(column.as_str()) {
+                let edges = &binding.rels[pos];
+                if self.query.pattern.steps[pos].0.hops.is_some() {
+                    Value::Array(edges.iter().map(EdgeRow::to_json).collect())
+                } else {
+                    edges.first().map(EdgeRow::to_json).unwrap_or(Value::Null)
+                }
+            } else {
+                Value::Null
+            };
+            row.insert(column.clone(), value);
+        }
+        Value::Object(row)
+    }
+}
+
+impl GraphQuery {
+    /// Run the query against current memories and cross-references.
+    ///
+    /// `workspace` restricts every node to that workspace. Returns at most
+    /// the query's `LIMIT` rows, or `default_limit` without one.
+    pub fn execute(
+        &self,
+        conn: &Connection,
+        workspace: Option<&str>,
+        default_limit: usize,
+    ) -> Result<QueryResult> {
+        let limit = self.limit.unwrap_or(default_limit);
+        Executor::new(conn, self, workspace).run(limit)
+    }
+}
+
+/// Parse and run `query` in one step
+pub fn run_query(
+    conn: &Connection,
+    query: &str,
+    workspace: Option<&str>,
+    default_limit: usize,
+) -> Result<QueryResult> {
+    GraphQuery::parse(query)?.execute(conn, workspace, default_limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_crossref, create_memory};
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType, MemoryType};
+
+    fn memory(conn: &Connection, content: &str, memory_type: MemoryType, tags: &[&str]) -> i64 {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                memory_type,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    fn link(conn: &Connection, from_id: i64, to_id: i64, edge_type: EdgeType) {
+        create_crossref(
+            conn,
+            &CreateCrossRefInput {
+                from_id,
+                to_id,
+                edge_type,
+                strength: None,
+                source_context: None,
+                pinned: false,
+            },
+        )
+        .unwrap();
+    }
+
+    fn ids(result: &QueryResult, var: &str) -> Vec<i64> {
+        result
+            .rows
+            .iter()
+            .map(|r| r[var]["id"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        let query = GraphQuery::parse(
+            "match (a:decision)-[r:depends_on|related_to*1..3]->(b:issue {importance: 0.5})<--(c) \
+             WHERE a.tag = 'auth' AND NOT (b.content CONTAINS \"wip\" OR c.id <> -1) \
+             RETURN a, b LIMIT 5",
+        )
+        .unwrap();
+
+        assert_eq!(query.pattern.start.var.as_deref(), Some("a"));
+        assert_eq!(query.pattern.start.labels, vec!["decision"]);
+        let (rel, b) = &query.pattern.steps[0];
+        assert_eq!(rel.types, vec!["depends_on", "related_to"]);
+        assert_eq!(rel.direction, RelDirection::Outgoing);
+        assert_eq!(rel.hops, Some((1, 3)));
+        assert_eq!(
+            b.props,
+            vec![("importance".to_string(), Literal::Number(0.5))]
+        );
+        let (rel, c) = &query.pattern.steps[1];
+        assert_eq!(rel.direction, RelDirection::Incoming);
+        assert!(rel.types.is_empty());
+        assert_eq!(c.var.as_deref(), Some("c"));
+        assert_eq!(query.returns, vec!["a", "b"]);
+        assert_eq!(query.limit, Some(5));
+        assert!(matches!(query.condition, Some(Condition::And(_, _))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for bad in [
+            "(a)-->(b)",
+            "MATCH (a",
+            "MATCH (a)-[*0..2]->(b)",
+            "MATCH (a)-[*1..99]->(b)",
+            "MATCH (a)<-[]->(b)",
+            "MATCH (a) WHERE b.id = 1",
+            "MATCH (a) WHERE a.colour = 'red'",
+            "MATCH (a)-[r*2]->(b) WHERE r.score > 0.5",
+            "MATCH (a) WHERE a.content = 'unterminated",
+            "MATCH (a) RETURN z",
+        ] {
+            let err = GraphQuery::parse(bad).unwrap_err();
+            assert!(
+                matches!(err, EngramError::InvalidInput(_)),
+                "{} -> {:?}",
+                bad,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_hop_match_with_where() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let auth = memory(conn, "Use JWT", MemoryType::Decision, &["auth"]);
+                let other = memory(conn, "Use Postgres", MemoryType::Decision, &["db"]);
+                let middle = memory(conn, "Token service", MemoryType::Note, &[]);
+                let issue = memory(conn, "Refresh tokens expire", MemoryType::Issue, &[]);
+                let db_issue = memory(conn, "Slow queries", MemoryType::Issue, &[]);
+
+                link(conn, auth, middle, EdgeType::DependsOn);
+                link(conn, middle, issue, EdgeType::DependsOn);
+                link(conn, other, db_issue, EdgeType::DependsOn);
+                // Wrong edge type, must not be followed
+                link(conn, auth, db_issue, EdgeType::RelatedTo);
+
+                let result = run_query(
+                    conn,
+                    "MATCH (a:decision)-[p:depends_on*1..3]->(b:issue) WHERE a.tag='auth'",
+                    None,
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                assert_eq!(result.columns, vec!["a", "p", "b"]);
+                assert_eq!(ids(&result, "a"), vec![auth]);
+                assert_eq!(ids(&result, "b"), vec![issue]);
+                assert_eq!(result.rows[0]["p"].as_array().unwrap().len(), 2);
+
+                // Exactly one hop finds only the direct dependency
+                let result = run_query(
+                    conn,
+                    "MATCH (a:decision)-[:depends_on]->(b:issue) RETURN a, b",
+                    None,
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                assert_eq!(ids(&result, "a"), vec![other]);
+
+                // Undirected and incoming relationships
+                let result = run_query(
+                    conn,
+                    "MATCH (i:issue)<-[r]-(d:decision) WHERE r.type = 'related_to' RETURN d",
+                    None,
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                assert_eq!(ids(&result, "d"), vec![auth]);
+                let result = run_query(
+                    conn,
+                    "MATCH (n:note)--(x) RETURN x",
+                    None,
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                let mut neighbors = ids(&result, "x");
+                neighbors.sort();
+                assert_eq!(neighbors, vec![auth, issue]);
+
+                // Conditions across variables and LIMIT
+                let result = run_query(
+                    conn,
+                    "MATCH (a)-->(b) WHERE a.type = 'decision' OR b.type = 'note' LIMIT 2",
+                    None,
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                assert_eq!(result.rows.len(), 2);
+                assert!(result.truncated);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_repeated_variable_and_workspace() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let a = memory(conn, "a", MemoryType::Note, &[]);
+                let b = memory(conn, "b", MemoryType::Note, &[]);
+                let c = memory(conn, "c", MemoryType::Note, &[]);
+                link(conn, a, b, EdgeType::RelatedTo);
+                link(conn, b, a, EdgeType::RelatedTo);
+                link(conn, b, c, EdgeType::RelatedTo);
+
+                let result = run_query(
+                    conn,
+                    "MATCH (x)-->(y)-->(x) RETURN x, y",
+                    None,
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                let mut cycles = ids(&result, "x");
+                cycles.sort();
+                assert_eq!(cycles, vec![a, b]);
+
+                let result = run_query(
+                    conn,
+                    "MATCH (x)-->(y)",
+                    Some("elsewhere"),
+                    DEFAULT_QUERY_LIMIT,
+                )?;
+                assert!(result.rows.is_empty());
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,484 @@
+//! Static SVG/PNG rendering
+//!
+//! The HTML export loads vis.js from a CDN, which sandboxed viewers, CI
+//! artifacts and air-gapped machines often refuse. This draws the graph
+//! without JavaScript: nodes are placed by the server-side force layout (or a
+//! precomputed one), scaled into a fixed canvas and written out as SVG with
+//! the same colors and shapes as the other exports. With the `graph-png`
+//! feature the SVG is rasterized by resvg.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::style::NodeShape;
+use super::{html_escape, GraphLayout, KnowledgeGraph, LayoutConfig, StyleRegistry};
+use crate::types::MemoryId;
+
+const EDGE_COLOR: &str = "#94a3b8";
+const EDGE_LABEL_COLOR: &str = "#64748b";
+const TEXT_COLOR: &str = "#1e293b";
+const FONT_FAMILY: &str = "system-ui, sans-serif";
+
+/// Canvas and content options for [`KnowledgeGraph::to_svg`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    /// Canvas width in pixels
+    pub width: u32,
+    /// Canvas height in pixels
+    pub height: u32,
+    /// Margin kept free around the drawing
+    pub padding: f64,
+    /// Draw node labels
+    pub labels: bool,
+    /// Draw edge types along the edges
+    pub edge_labels: bool,
+    /// Draw a legend of the memory types present
+    pub legend: bool,
+    /// CSS color of the canvas
+    pub background: String,
+    /// Layout used when no precomputed one is given
+    pub layout: LayoutConfig,
+    /// Hyperlink targets by node, e.g. one page per memory in a static site
+    pub node_links: HashMap<MemoryId, String>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 900,
+            padding: 40.0,
+            labels: true,
+            edge_labels: false,
+            legend: true,
+            background: "#ffffff".to_string(),
+            layout: LayoutConfig::default(),
+            node_links: HashMap::new(),
+        }
+    }
+}
+
+impl KnowledgeGraph {
+    /// Render as a standalone SVG image. Without a `layout`, one is computed
+    /// with `options.layout`; either way it is scaled to fit the canvas.
+    pub fn to_svg(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+        options: &RenderOptions,
+    ) -> String {
+        let computed;
+        let layout = match layout {
+            Some(layout) => layout,
+            None => {
+                computed = self.force_layout(&options.layout);
+                &computed
+            }
+        };
+        let positions = fit_to_canvas(self, layout, options);
+        let radii: HashMap<MemoryId, f64> = self
+            .nodes
+            .iter()
+            .map(|n| (n.id, node_radius(n.importance)))
+            .collect();
+
+        let (width, height) = (options.width, options.height);
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="{FONT_FAMILY}">
+  <defs>
+    <marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="9" markerHeight="9" markerUnits="userSpaceOnUse" orient="auto-start-reverse">
+      <path d="M 0 0 L 10 5 L 0 10 z" fill="{EDGE_COLOR}"/>
+    </marker>
+  </defs>
+  <rect width="100%" height="100%" fill="{}"/>
+  <g class="edges">
+"#,
+            html_escape(&options.background)
+        );
+
+        for edge in &self.edges {
+            let (Some(&from), Some(&to)) = (positions.get(&edge.from), positions.get(&edge.to))
+            else {
+                continue;
+            };
+            let stroke = 1.0 + (edge.score * edge.confidence).clamp(0.0, 1.0) as f64 * 3.0;
+            let title = format!(
+                "{} (score {:.2}, confidence {:.2})",
+                edge.edge_type, edge.score, edge.confidence
+            );
+            let r = radii.get(&edge.to).copied().unwrap_or(0.0);
+            let label_at = if edge.from == edge.to {
+                // Self-loop: a small circle touching the top of the node
+                svg.push_str(&format!(
+                    "    <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"none\" stroke=\"{EDGE_COLOR}\" stroke-width=\"{:.1}\"><title>{}</title></circle>\n",
+                    from.0,
+                    from.1 - r,
+                    r * 0.6,
+                    stroke,
+                    html_escape(&title)
+                ));
+                (from.0, from.1 - r * 1.8)
+            } else {
+                // Stop at the target's rim so the arrowhead stays visible
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                let length = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+                let shorten = (r + 2.0).min(length);
+                let end = (to.0 - dx / length * shorten, to.1 - dy / length * shorten);
+                svg.push_str(&format!(
+                    "    <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{EDGE_COLOR}\" stroke-width=\"{:.1}\" marker-end=\"url(#arrow)\"><title>{}</title></line>\n",
+                    from.0,
+                    from.1,
+                    end.0,
+                    end.1,
+                    stroke,
+                    html_escape(&title)
+                ));
+                ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0)
+            };
+            if options.edge_labels {
+                svg.push_str(&format!(
+                    "    <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"9\" text-anchor=\"middle\" fill=\"{EDGE_LABEL_COLOR}\">{}</text>\n",
+                    label_at.0,
+                    label_at.1 - 3.0,
+                    html_escape(&edge.edge_type)
+                ));
+            }
+        }
+
+        svg.push_str("  </g>\n  <g class=\"nodes\">\n");
+        for node in &self.nodes {
+            let Some(&(x, y)) = positions.get(&node.id) else {
+                continue;
+            };
+            let style = styles.style_for(&node.memory_type);
+            let r = radii[&node.id];
+            let title = if node.tags.is_empty() {
+                format!("{}\nType: {}", node.label, node.memory_type)
+            } else {
+                format!(
+                    "{}\nType: {}\nTags: {}",
+                    node.label,
+                    node.memory_type,
+                    node.tags.join(", ")
+                )
+            };
+            let shape = format!(
+                "<g>{}<title>{}</title></g>",
+                shape_svg(style.shape, x, y, r, &style.color),
+                html_escape(&title)
+            );
+            match options.node_links.get(&node.id) {
+                Some(href) => svg.push_str(&format!(
+                    "    <a href=\"{}\">{}</a>\n",
+                    html_escape(href),
+                    shape
+                )),
+                None => svg.push_str(&format!("    {}\n", shape)),
+            }
+            if options.labels && !node.label.is_empty() {
+                svg.push_str(&format!(
+                    "    <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"middle\" fill=\"{TEXT_COLOR}\">{}</text>\n",
+                    x,
+                    y + r + 13.0,
+                    html_escape(&node.label)
+                ));
+            }
+        }
+        svg.push_str("  </g>\n");
+
+        if options.legend {
+            svg.push_str(&self.svg_legend(styles));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Render as a PNG image; see [`KnowledgeGraph::to_svg`]. Labels use the
+    /// system's fonts and are left out when none are installed.
+    #[cfg(feature = "graph-png")]
+    pub fn to_png(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+        options: &RenderOptions,
+    ) -> crate::error::Result<Vec<u8>> {
+        use crate::error::EngramError;
+        use resvg::{tiny_skia, usvg};
+
+        let svg = self.to_svg(styles, layout, options);
+        let mut usvg_options = usvg::Options::default();
+        let fonts = usvg_options.fontdb_mut();
+        fonts.load_system_fonts();
+        // fontdb resolves `sans-serif` to Arial, which many Linux hosts lack
+        let families: Vec<String> = fonts
+            .faces()
+            .filter_map(|face| face.families.first().map(|(name, _)| name.clone()))
+            .collect();
+        let sans = [
+            "Arial",
+            "Helvetica",
+            "DejaVu Sans",
+            "Liberation Sans",
+            "Noto Sans",
+        ]
+        .iter()
+        .find(|name| families.iter().any(|f| f == *name))
+        .map(|name| name.to_string())
+        .or_else(|| families.first().cloned());
+        if let Some(sans) = sans {
+            fonts.set_sans_serif_family(sans);
+        }
+        let tree = usvg::Tree::from_str(&svg, &usvg_options)
+            .map_err(|e| EngramError::Internal(format!("Failed to parse rendered SVG: {}", e)))?;
+
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).ok_or_else(|| {
+            EngramError::InvalidInput(format!(
+                "Cannot render a {}x{} image",
+                size.width(),
+                size.height()
+            ))
+        })?;
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+        pixmap
+            .encode_png()
+            .map_err(|e| EngramError::Internal(format!("Failed to encode PNG: {}", e)))
+    }
+
+    fn svg_legend(&self, styles: &StyleRegistry) -> String {
+        let present: BTreeSet<&str> = self.nodes.iter().map(|n| n.memory_type.as_str()).collect();
+        if present.is_empty() {
+            return String::new();
+        }
+        let width =
+            24.0 + 7.0 * present.iter().map(|t| t.chars().count()).max().unwrap_or(0) as f64;
+        let mut legend = format!(
+            "  <g class=\"legend\">\n    <rect x=\"10\" y=\"10\" width=\"{:.0}\" height=\"{}\" rx=\"4\" fill=\"#ffffff\" fill-opacity=\"0.9\" stroke=\"#e2e8f0\"/>\n",
+            width + 16.0,
+            present.len() * 18 + 10
+        );
+        for (i, memory_type) in present.iter().enumerate() {
+            let style = styles.style_for(memory_type);
+            let y = 28.0 + i as f64 * 18.0;
+            legend.push_str(&format!(
+                "    {}<text x=\"36\" y=\"{:.1}\" font-size=\"11\" fill=\"{TEXT_COLOR}\">{}</text>\n",
+                shape_svg(style.shape, 24.0, y - 4.0, 5.0, &style.color),
+                y,
+                html_escape(memory_type)
+            ));
+        }
+        legend.push_str("  </g>\n");
+        legend
+    }
+}
+
+/// Radius of a node, growing with importance like the vis.js `value`
+fn node_radius(importance: f32) -> f64 {
+    6.0 + importance.clamp(0.0, 1.0) as f64 * 10.0
+}
+
+/// Layout positions scaled and centered into the canvas, keeping the aspect
+/// ratio. A single node, or nodes the layout left out, end up in the middle.
+fn fit_to_canvas(
+    graph: &KnowledgeGraph,
+    layout: &GraphLayout,
+    options: &RenderOptions,
+) -> HashMap<MemoryId, (f64, f64)> {
+    let raw: Vec<(MemoryId, (f64, f64))> = graph
+        .nodes
+        .iter()
+        .map(|n| (n.id, layout.get(n.id).unwrap_or((0.0, 0.0))))
+        .collect();
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for (_, (x, y)) in &raw {
+        min_x = min_x.min(*x);
+        min_y = min_y.min(*y);
+        max_x = max_x.max(*x);
+        max_y = max_y.max(*y);
+    }
+
+    // Leave room for the largest node and its label
+    let margin = options.padding + node_radius(1.0);
+    let available_w = (options.width as f64 - 2.0 * margin).max(1.0);
+    let available_h = (options.height as f64 - 2.0 * margin).max(1.0);
+    let span_x = max_x - min_x;
+    let span_y = max_y - min_y;
+    let scale = match (span_x > f64::EPSILON, span_y > f64::EPSILON) {
+        (true, true) => (available_w / span_x).min(available_h / span_y),
+        (true, false) => available_w / span_x,
+        (false, true) => available_h / span_y,
+        (false, false) => 1.0,
+    };
+    let center = (options.width as f64 / 2.0, options.height as f64 / 2.0);
+    let mid = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+
+    raw.into_iter()
+        .map(|(id, (x, y))| {
+            (
+                id,
+                (
+                    center.0 + (x - mid.0) * scale,
+                    center.1 + (y - mid.1) * scale,
+                ),
+            )
+        })
+        .collect()
+}
+
+/// SVG element for a node of `shape` centered on `(x, y)`
+fn shape_svg(shape: NodeShape, x: f64, y: f64, r: f64, color: &str) -> String {
+    let fill = format!(
+        "fill=\"{}\" stroke=\"#ffffff\" stroke-width=\"1.5\"",
+        html_escape(color)
+    );
+    let polygon = |points: Vec<(f64, f64)>| {
+        let points: Vec<String> = points
+            .iter()
+            .map(|(px, py)| format!("{:.1},{:.1}", x + px, y + py))
+            .collect();
+        format!("<polygon points=\"{}\" {}/>", points.join(" "), fill)
+    };
+    let regular = |corners: usize, radius: f64, offset: f64| -> Vec<(f64, f64)> {
+        (0..corners)
+            .map(|i| {
+                let angle = offset + i as f64 * std::f64::consts::TAU / corners as f64;
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    };
+    let up = -std::f64::consts::FRAC_PI_2;
+
+    match shape {
+        NodeShape::Dot => format!("<circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{r:.1}\" {fill}/>"),
+        NodeShape::Ellipse => format!(
+            "<ellipse cx=\"{x:.1}\" cy=\"{y:.1}\" rx=\"{:.1}\" ry=\"{:.1}\" {fill}/>",
+            r * 1.4,
+            r * 0.9
+        ),
+        NodeShape::Box => format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"3\" {fill}/>",
+            x - r * 1.4,
+            y - r * 0.9,
+            r * 2.8,
+            r * 1.8
+        ),
+        NodeShape::Square => format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" {fill}/>",
+            x - r,
+            y - r,
+            r * 2.0,
+            r * 2.0
+        ),
+        NodeShape::Diamond => polygon(regular(4, r * 1.2, up)),
+        NodeShape::Triangle => polygon(regular(3, r * 1.2, up)),
+        NodeShape::Hexagon => polygon(regular(6, r, 0.0)),
+        NodeShape::Star => {
+            let outer = regular(5, r * 1.3, up);
+            let inner = regular(5, r * 0.55, up + std::f64::consts::PI / 5.0);
+            polygon(
+                outer
+                    .into_iter()
+                    .zip(inner)
+                    .flat_map(|(o, i)| [o, i])
+                    .collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphEdge, GraphNode};
+
+    fn graph() -> KnowledgeGraph {
+        let node = |id: MemoryId, memory_type: &str| GraphNode {
+            id,
+            label: format!("Node <{}>", id),
+            memory_type: memory_type.to_string(),
+            importance: 0.5,
+            tags: vec!["t".to_string()],
+        };
+        let edge = |from: MemoryId, to: MemoryId| GraphEdge {
+            from,
+            to,
+            edge_type: "related_to".to_string(),
+            score: 0.8,
+            confidence: 1.0,
+        };
+        KnowledgeGraph {
+            nodes: vec![node(1, "note"), node(2, "decision"), node(3, "note")],
+            edges: vec![edge(1, 2), edge(2, 3), edge(3, 3)],
+        }
+    }
+
+    #[test]
+    fn test_svg_draws_every_node_and_edge_inside_the_canvas() {
+        let options = RenderOptions {
+            width: 400,
+            height: 300,
+            edge_labels: true,
+            ..Default::default()
+        };
+        let svg = graph().to_svg(&StyleRegistry::default(), None, &options);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"400\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(!svg.contains("<script"));
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(svg.contains("Node &lt;2&gt;"));
+        assert_eq!(svg.matches(">related_to</text>").count(), 3);
+        // Legend lists each type once
+        assert_eq!(svg.matches(">decision</text>").count(), 1);
+
+        let positions = fit_to_canvas(&graph(), &graph().force_layout(&options.layout), &options);
+        for (x, y) in positions.values() {
+            assert!((0.0..=400.0).contains(x) && (0.0..=300.0).contains(y));
+        }
+    }
+
+    #[test]
+    fn test_svg_uses_a_given_layout() {
+        let graph = graph();
+        let layout = graph.force_layout(&LayoutConfig {
+            seed: 7,
+            ..Default::default()
+        });
+        let styles = StyleRegistry::default();
+        let options = RenderOptions::default();
+
+        assert_eq!(
+            graph.to_svg(&styles, Some(&layout), &options),
+            graph.to_svg(&styles, Some(&layout), &options)
+        );
+        let single = KnowledgeGraph {
+            nodes: graph.nodes[..1].to_vec(),
+            edges: vec![],
+        };
+        let svg = single.to_svg(&styles, None, &options);
+        assert!(svg.contains("cx=\"600.0\" cy=\"450.0\""));
+    }
+
+    #[test]
+    fn test_svg_links_nodes() {
+        let options = RenderOptions {
+            node_links: HashMap::from([(2, "memories/2.html?a&b".to_string())]),
+            ..Default::default()
+        };
+        let svg = graph().to_svg(&StyleRegistry::default(), None, &options);
+        assert_eq!(svg.matches("<a href=").count(), 1);
+        assert!(svg.contains("<a href=\"memories/2.html?a&amp;b\"><g>"));
+    }
+
+    #[cfg(feature = "graph-png")]
+    #[test]
+    fn test_png() {
+        let png = graph()
+            .to_png(&StyleRegistry::default(), None, &RenderOptions::default())
+            .unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Streaming graph export
+//!
+//! Writes the knowledge graph to a writer page by page instead of building a
+//! [`KnowledgeGraph`] first, so exports larger than the in-memory graph
+//! limits can go straight to a file. Only the ids of exported memories are
+//! kept, to decide which links belong in the graph.
+//!
+//! The JSON output has the shape of a serialized [`KnowledgeGraph`]
+//! (`{"nodes": [...], "edges": [...]}`); GraphML matches
+//! [`KnowledgeGraph::to_graphml_with`]. Both stay well-formed when the call's
+//! time budget stops the export early.
+
+use std::collections::HashSet;
+use std::io::Write;
+
+use rusqlite::Connection;
+
+use super::{
+    graphml_edge, graphml_node, GraphEdge, GraphNode, LabelOptions, StyleRegistry, GRAPHML_FOOTER,
+    GRAPHML_HEADER,
+};
+use crate::error::Result;
+use crate::storage::queries::{get_related, list_memories};
+use crate::types::{ListOptions, MemoryId};
+
+/// Memories read per page
+const PAGE_SIZE: i64 = 500;
+
+/// Output format of [`stream_graph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    Json,
+    GraphMl,
+}
+
+impl std::str::FromStr for StreamFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(StreamFormat::Json),
+            "graphml" => Ok(StreamFormat::GraphMl),
+            other => Err(format!(
+                "Format '{}' can't be streamed: expected json or graphml",
+                other
+            )),
+        }
+    }
+}
+
+/// What [`stream_graph`] wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamedGraph {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// The time budget ran out before every memory or link was written
+    pub truncated: bool,
+}
+
+/// Write up to `max_nodes` memories (all when `None`) and the links between
+/// them to `writer`
+pub fn stream_graph<W: Write>(
+    conn: &Connection,
+    mut writer: W,
+    format: StreamFormat,
+    max_nodes: Option<usize>,
+    labels: &LabelOptions,
+) -> Result<StreamedGraph> {
+    let styles = StyleRegistry::global();
+    let mut streamed = StreamedGraph::default();
+    let mut ids: Vec<MemoryId> = Vec::new();
+
+    match format {
+        StreamFormat::Json => writer.write_all(b"{\"nodes\":[")?,
+        StreamFormat::GraphMl => writer.write_all(GRAPHML_HEADER.as_bytes())?,
+    }
+
+    let limit = max_nodes.unwrap_or(usize::MAX);
+    let mut offset = 0;
+    'pages: while ids.len() < limit {
+        if crate::budget::checkpoint() {
+            streamed.truncated = true;
+            break;
+        }
+        let options = ListOptions {
+            limit: Some(PAGE_SIZE),
+            offset: Some(offset),
+            ..Default::default()
+        };
+        let page = list_memories(conn, &options)?;
+        if page.is_empty() {
+            break;
+        }
+        offset += page.len() as i64;
+        for memory in &page {
+            if ids.len() >= limit {
+                break 'pages;
+            }
+            let node = GraphNode::for_memory(memory, labels);
+            match format {
+                StreamFormat::Json => {
+                    if !ids.is_empty() {
+                        writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut writer, &node)?;
+                }
+                StreamFormat::GraphMl => {
+                    writer.write_all(graphml_node(&node, styles).as_bytes())?
+                }
+            }
+            ids.push(memory.id);
+        }
+    }
+    streamed.node_count = ids.len();
+
+    if format == StreamFormat::Json {
+        writer.write_all(b"],\"edges\":[")?;
+    }
+    let exported: HashSet<MemoryId> = ids.iter().copied().collect();
+    for (i, id) in ids.iter().enumerate() {
+        if i % PAGE_SIZE as usize == 0 && crate::budget::checkpoint() {
+            streamed.truncated = true;
+            break;
+        }
+        // Each link is written once, from the memory it starts at
+        for crossref in get_related(conn, *id)? {
+            if crossref.from_id != *id || !exported.contains(&crossref.to_id) {
+                continue;
+            }
+            let edge = GraphEdge::for_crossref(&crossref);
+            match format {
+                StreamFormat::Json => {
+                    if streamed.edge_count > 0 {
+                        writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut writer, &edge)?;
+                }
+                StreamFormat::GraphMl => {
+                    writer.write_all(graphml_edge(streamed.edge_count, &edge).as_bytes())?
+                }
+            }
+            streamed.edge_count += 1;
+        }
+    }
+
+    match format {
+        StreamFormat::Json => writer.write_all(b"]}\n")?,
+        StreamFormat::GraphMl => writer.write_all(GRAPHML_FOOTER.as_bytes())?,
+    }
+    writer.flush()?;
+    Ok(streamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::KnowledgeGraph;
+    use crate::storage::queries::{create_crossref, create_memory};
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+
+    #[test]
+    fn test_stream_graph_matches_in_memory_export() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let mut ids = Vec::new();
+                for content in ["alpha", "beta", "gamma"] {
+                    let input = CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    };
+                    ids.push(create_memory(conn, &input)?.id);
+                }
+                for (from_id, to_id) in [(ids[0], ids[1]), (ids[1], ids[2])] {
+                    create_crossref(
+                        conn,
+                        &CreateCrossRefInput {
+                            from_id,
+                            to_id,
+                            edge_type: EdgeType::RelatedTo,
+                            strength: None,
+                            source_context: None,
+                            pinned: false,
+                        },
+                    )?;
+                }
+
+                let mut json = Vec::new();
+                let streamed = stream_graph(
+                    conn,
+                    &mut json,
+                    StreamFormat::Json,
+                    None,
+                    &LabelOptions::default(),
+                )?;
+                assert_eq!(streamed.node_count, 3);
+                assert_eq!(streamed.edge_count, 2);
+                assert!(!streamed.truncated);
+                let graph: KnowledgeGraph = serde_json::from_slice(&json)?;
+                assert_eq!(graph.nodes.len(), 3);
+                assert_eq!(graph.edges.len(), 2);
+
+                // Links to memories past max_nodes are left out
+                let mut xml = Vec::new();
+                let streamed = stream_graph(
+                    conn,
+                    &mut xml,
+                    StreamFormat::GraphMl,
+                    Some(2),
+                    &LabelOptions::default(),
+                )?;
+                assert_eq!(streamed.node_count, 2);
+                assert_eq!(streamed.edge_count, 1);
+                let xml = String::from_utf8(xml).unwrap();
+                assert!(xml.starts_with("<?xml"));
+                assert!(xml.ends_with("</graphml>\n"));
+                assert_eq!(xml.matches("<node ").count(), 2);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,347 @@
+//! Memory type styles shared by every graph export
+//!
+//! [`StyleRegistry`] maps a memory type to a color, a shape and an optional
+//! icon. The HTML, vis.js, DOT and GEXF exporters all read from it, so a type
+//! looks the same whichever format it ends up in. Types without an entry —
+//! custom types in particular — get a stable color derived from their name
+//! rather than a shared grey.
+//!
+//! Overrides are read from a JSON file, `$ENGRAM_GRAPH_STYLES` or
+//! `~/.config/engram/graph_styles.json`:
+//!
+//! ```json
+//! { "types": { "decision": { "color": "#2E7D32", "shape": "diamond", "icon": "⚖" } } }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+
+/// Environment variable pointing at a style override file
+pub const GRAPH_STYLES_ENV: &str = "ENGRAM_GRAPH_STYLES";
+
+/// Node shape, translated to the closest equivalent in each export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeShape {
+    #[default]
+    Dot,
+    Box,
+    Ellipse,
+    Diamond,
+    Triangle,
+    Square,
+    Star,
+    Hexagon,
+}
+
+impl NodeShape {
+    /// vis.js `shape` option
+    pub fn visjs(&self) -> &'static str {
+        match self {
+            NodeShape::Dot => "dot",
+            NodeShape::Box => "box",
+            NodeShape::Ellipse => "ellipse",
+            NodeShape::Diamond => "diamond",
+            NodeShape::Triangle => "triangle",
+            NodeShape::Square => "square",
+            NodeShape::Star => "star",
+            NodeShape::Hexagon => "hexagon",
+        }
+    }
+
+    /// Graphviz node `shape` attribute
+    pub fn graphviz(&self) -> &'static str {
+        match self {
+            NodeShape::Dot => "circle",
+            NodeShape::Box => "box",
+            NodeShape::Ellipse => "ellipse",
+            NodeShape::Diamond => "diamond",
+            NodeShape::Triangle => "triangle",
+            NodeShape::Square => "square",
+            NodeShape::Star => "star",
+            NodeShape::Hexagon => "hexagon",
+        }
+    }
+
+    /// GEXF `viz:shape` value; the format only knows four shapes
+    pub fn gexf(&self) -> &'static str {
+        match self {
+            NodeShape::Box | NodeShape::Square => "square",
+            NodeShape::Diamond => "diamond",
+            NodeShape::Triangle => "triangle",
+            _ => "disc",
+        }
+    }
+}
+
+/// How nodes of one memory type are drawn
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeStyle {
+    /// `#RRGGBB` fill color
+    pub color: String,
+    #[serde(default)]
+    pub shape: NodeShape,
+    /// Short text or emoji shown in legends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+impl NodeStyle {
+    fn new(color: &str, shape: NodeShape, icon: &str) -> Self {
+        Self {
+            color: color.to_string(),
+            shape,
+            icon: Some(icon.to_string()),
+        }
+    }
+
+    /// The color as 8-bit RGB components
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        parse_hex_color(&self.color).unwrap_or((0xCC, 0xCC, 0xCC))
+    }
+}
+
+/// Partial style from the override file; unset fields keep the built-in value
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeStyleOverride {
+    pub color: Option<String>,
+    pub shape: Option<NodeShape>,
+    pub icon: Option<String>,
+}
+
+/// Contents of the style override file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StyleConfig {
+    #[serde(default)]
+    pub types: BTreeMap<String, NodeStyleOverride>,
+}
+
+/// Memory type → [`NodeStyle`] lookup used by all graph exporters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleRegistry {
+    styles: BTreeMap<String, NodeStyle>,
+}
+
+impl Default for StyleRegistry {
+    fn default() -> Self {
+        use NodeShape::*;
+        let builtin = [
+            ("note", NodeStyle::new("#97C2FC", Dot, "📝")),
+            ("todo", NodeStyle::new("#FFFF00", Square, "☐")),
+            ("issue", NodeStyle::new("#FB7E81", Triangle, "⚠")),
+            ("decision", NodeStyle::new("#7BE141", Diamond, "⚖")),
+            ("preference", NodeStyle::new("#FFA807", Dot, "★")),
+            ("learning", NodeStyle::new("#6E6EFD", Dot, "💡")),
+            ("context", NodeStyle::new("#C2FABC", Ellipse, "🧭")),
+            ("credential", NodeStyle::new("#FD6A6A", Hexagon, "🔑")),
+            ("custom", NodeStyle::new("#B0B0B0", Dot, "◆")),
+            ("transcript_chunk", NodeStyle::new("#D9D9D9", Box, "💬")),
+            ("episodic", NodeStyle::new("#F4A3C8", Dot, "📅")),
+            ("procedural", NodeStyle::new("#8DD3C7", Box, "⚙")),
+            ("summary", NodeStyle::new("#BEBADA", Ellipse, "Σ")),
+            ("checkpoint", NodeStyle::new("#80B1D3", Star, "⚑")),
+            ("image", NodeStyle::new("#FDB462", Square, "🖼")),
+            ("audio", NodeStyle::new("#B3DE69", Square, "🔊")),
+            ("video", NodeStyle::new("#FCCDE5", Square, "🎬")),
+        ];
+        Self {
+            styles: builtin
+                .into_iter()
+                .map(|(name, style)| (name.to_string(), style))
+                .collect(),
+        }
+    }
+}
+
+impl StyleRegistry {
+    /// Process-wide registry: the built-in styles plus the override file, if
+    /// any, loaded on first use
+    pub fn global() -> &'static StyleRegistry {
+        static GLOBAL: OnceLock<StyleRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(Self::load_or_default)
+    }
+
+    /// Style for a memory type; unknown types get a color hashed from the name
+    pub fn style_for(&self, memory_type: &str) -> NodeStyle {
+        self.styles
+            .get(memory_type)
+            .cloned()
+            .unwrap_or_else(|| NodeStyle {
+                color: derived_color(memory_type),
+                shape: NodeShape::Dot,
+                icon: None,
+            })
+    }
+
+    /// Every explicitly styled type, in name order
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &NodeStyle)> {
+        self.styles
+            .iter()
+            .map(|(name, style)| (name.as_str(), style))
+    }
+
+    /// Set or replace the style of one type
+    pub fn set(&mut self, memory_type: impl Into<String>, style: NodeStyle) -> Result<()> {
+        validate_color(&style.color)?;
+        self.styles.insert(memory_type.into(), style);
+        Ok(())
+    }
+
+    /// Apply overrides on top of the current styles
+    pub fn apply(&mut self, config: StyleConfig) -> Result<()> {
+        for (name, patch) in config.types {
+            let mut style = self.style_for(&name);
+            if let Some(color) = patch.color {
+                style.color = color;
+            }
+            if let Some(shape) = patch.shape {
+                style.shape = shape;
+            }
+            if patch.icon.is_some() {
+                style.icon = patch.icon;
+            }
+            self.set(name, style)?;
+        }
+        Ok(())
+    }
+
+    /// Built-in styles with the overrides from a JSON file applied
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            EngramError::Config(format!("Cannot read graph styles {:?}: {}", path, e))
+        })?;
+        let config: StyleConfig = serde_json::from_str(&contents)
+            .map_err(|e| EngramError::Config(format!("Invalid graph styles {:?}: {}", path, e)))?;
+        let mut registry = Self::default();
+        registry.apply(config)?;
+        Ok(registry)
+    }
+
+    /// Load from `$ENGRAM_GRAPH_STYLES` or `~/.config/engram/graph_styles.json`,
+    /// falling back to the built-in styles when neither exists or parses
+    pub fn load_or_default() -> Self {
+        let path = std::env::var_os(GRAPH_STYLES_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                dirs::config_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("engram")
+                    .join("graph_styles.json")
+            });
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(&path).unwrap_or_else(|e| {
+            tracing::warn!(path = ?path, error = %e, "Failed to load graph styles, using defaults");
+            Self::default()
+        })
+    }
+}
+
+fn validate_color(color: &str) -> Result<()> {
+    parse_hex_color(color)
+        .map(|_| ())
+        .ok_or_else(|| EngramError::Config(format!("color must be #RRGGBB, got {:?}", color)))
+}
+
+fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Pastel color picked from a hash of the type name, so each custom type is
+/// distinguishable and keeps its color across exports and runs
+fn derived_color(memory_type: &str) -> String {
+    // FNV-1a: stable across platforms and Rust versions, unlike DefaultHasher.
+    let hash = memory_type.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    let hue = (hash % 360) as f64;
+    let (r, g, b) = hsl_to_rgb(hue, 0.65, 0.70);
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = lightness - c / 2.0;
+    let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_and_derived_styles() {
+        let registry = StyleRegistry::default();
+        assert_eq!(registry.style_for("note").color, "#97C2FC");
+        assert_eq!(registry.style_for("decision").shape, NodeShape::Diamond);
+
+        let custom = registry.style_for("meeting-notes");
+        assert_eq!(custom, registry.style_for("meeting-notes"));
+        assert_ne!(custom.color, "#CCCCCC");
+        assert!(parse_hex_color(&custom.color).is_some());
+        assert_ne!(custom.color, registry.style_for("runbook").color);
+    }
+
+    #[test]
+    fn test_overrides_merge_with_builtins() {
+        let mut registry = StyleRegistry::default();
+        let config: StyleConfig = serde_json::from_str(
+            r##"{"types": {
+                "note": {"shape": "box"},
+                "runbook": {"color": "#123456", "icon": "R"}
+            }}"##,
+        )
+        .unwrap();
+        registry.apply(config).unwrap();
+
+        let note = registry.style_for("note");
+        assert_eq!(note.color, "#97C2FC");
+        assert_eq!(note.shape, NodeShape::Box);
+        let runbook = registry.style_for("runbook");
+        assert_eq!(runbook.rgb(), (0x12, 0x34, 0x56));
+        assert_eq!(runbook.icon.as_deref(), Some("R"));
+    }
+
+    #[test]
+    fn test_invalid_color_is_rejected() {
+        let mut registry = StyleRegistry::default();
+        let config: StyleConfig =
+            serde_json::from_str(r#"{"types": {"note": {"color": "blue"}}}"#).unwrap();
+        assert!(registry.apply(config).is_err());
+        assert!(serde_json::from_str::<StyleConfig>(r#"{"colours": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("styles.json");
+        std::fs::write(&path, r##"{"types": {"todo": {"color": "#000000"}}}"##).unwrap();
+        let registry = StyleRegistry::load(&path).unwrap();
+        assert_eq!(registry.style_for("todo").color, "#000000");
+        assert!(StyleRegistry::load(&dir.path().join("missing.json")).is_err());
+    }
+}
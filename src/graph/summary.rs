@@ -0,0 +1,562 @@
+//! Cluster-collapsed graph summaries
+//!
+//! A few thousand memories render as an unreadable hairball. [`GraphSummary`]
+//! replaces whole communities with one "supernode" each, so an export shows at
+//! most a requested number of nodes. Edges into a collapsed community are
+//! merged per pair of endpoints, summing their weights and counting how many
+//! original edges each merged edge stands for.
+//!
+//! The summary keeps the graph it was built from, so
+//! [`GraphSummary::to_html`] can ship every memory and let the viewer expand
+//! a supernode in place by clicking it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{CommunityAlgorithm, GraphEdge, GraphLayout, GraphNode, KnowledgeGraph, StyleRegistry};
+use crate::types::MemoryId;
+
+/// `memory_type` given to supernodes in the collapsed graph
+pub const SUPERNODE_TYPE: &str = "cluster";
+
+/// `edge_type` of merged edges whose originals have different types
+pub const MIXED_EDGE_TYPE: &str = "mixed";
+
+/// One collapsed community
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Supernode {
+    /// Node id in the collapsed graph; always negative and below every id of
+    /// the original graph, so it never clashes with a memory or entity node
+    pub id: MemoryId,
+    pub label: String,
+    /// Memories folded into this node
+    pub members: Vec<MemoryId>,
+    pub dominant_type: Option<String>,
+    pub common_tags: Vec<String>,
+    /// Edges between two members, hidden while collapsed
+    pub internal_edges: usize,
+}
+
+/// A graph with its largest communities collapsed into supernodes
+#[derive(Debug, Clone)]
+pub struct GraphSummary {
+    /// Uncollapsed memories plus one node of type [`SUPERNODE_TYPE`] per
+    /// supernode. Merged edges carry the summed `score * confidence` of
+    /// their originals as `score`, with `confidence` 1.0.
+    pub graph: KnowledgeGraph,
+    pub supernodes: Vec<Supernode>,
+    /// Original edges behind each edge of `graph`, parallel to `graph.edges`
+    pub edge_counts: Vec<usize>,
+    /// The graph before collapsing
+    pub original: KnowledgeGraph,
+}
+
+impl KnowledgeGraph {
+    /// Collapse Louvain communities until at most `max_nodes` nodes remain
+    /// (see [`KnowledgeGraph::summarize_with`])
+    pub fn summarize(&self, max_nodes: usize) -> GraphSummary {
+        self.summarize_with(max_nodes, CommunityAlgorithm::default())
+    }
+
+    /// Collapse communities found by `algorithm`, largest first, until at
+    /// most `max_nodes` nodes remain
+    ///
+    /// Graphs already within the limit come back unchanged. Single-memory
+    /// communities are never collapsed, so a graph with many isolated
+    /// memories can stay above `max_nodes`.
+    pub fn summarize_with(&self, max_nodes: usize, algorithm: CommunityAlgorithm) -> GraphSummary {
+        let compact = self.compact();
+        let mut remaining = compact.node_count();
+        let mut collapsed = Vec::new();
+        if remaining > max_nodes {
+            for cluster in compact.communities(algorithm) {
+                if remaining <= max_nodes {
+                    break;
+                }
+                if cluster.members.len() < 2 {
+                    continue;
+                }
+                remaining -= cluster.members.len() - 1;
+                collapsed.push(cluster);
+            }
+        }
+
+        let by_id: HashMap<MemoryId, &GraphNode> =
+            self.nodes.iter().rev().map(|n| (n.id, n)).collect();
+        let mut supernode_of: HashMap<MemoryId, MemoryId> = HashMap::new();
+        let mut supernodes = Vec::with_capacity(collapsed.len());
+        let mut super_graph_nodes = Vec::with_capacity(collapsed.len());
+        // Entity nodes already use negative ids; start below them
+        let first_id = self.nodes.iter().map(|n| n.id).min().unwrap_or(0).min(0) - 1;
+        for (i, cluster) in collapsed.into_iter().enumerate() {
+            let id = first_id - i as MemoryId;
+            for &member in &cluster.members {
+                supernode_of.insert(member, id);
+            }
+            // Name the supernode after its most important member
+            let lead = cluster.members.iter().filter_map(|m| by_id.get(m)).fold(
+                None::<&GraphNode>,
+                |best, n| match best {
+                    Some(b) if b.importance >= n.importance => Some(b),
+                    _ => Some(n),
+                },
+            );
+            let label = match lead {
+                Some(lead) => format!("{} (+{})", lead.label, cluster.members.len() - 1),
+                None => format!("Cluster {}", i + 1),
+            };
+            super_graph_nodes.push(GraphNode {
+                id,
+                label: label.clone(),
+                memory_type: SUPERNODE_TYPE.to_string(),
+                importance: lead.map(|n| n.importance).unwrap_or(0.0),
+                tags: cluster.common_tags.clone(),
+            });
+            supernodes.push(Supernode {
+                id,
+                label,
+                members: cluster.members,
+                dominant_type: cluster.dominant_type,
+                common_tags: cluster.common_tags,
+                internal_edges: cluster.internal_edges,
+            });
+        }
+
+        let mut nodes: Vec<GraphNode> = self
+            .nodes
+            .iter()
+            .filter(|n| !supernode_of.contains_key(&n.id))
+            .cloned()
+            .collect();
+        nodes.extend(super_graph_nodes);
+
+        // Edges between two uncollapsed memories pass through; the rest are
+        // merged per (from, to) pair in the collapsed graph
+        let mut edges = Vec::new();
+        let mut edge_counts = Vec::new();
+        let mut merged: BTreeMap<(MemoryId, MemoryId), (f32, usize, HashSet<&str>)> =
+            BTreeMap::new();
+        for edge in &self.edges {
+            let from = supernode_of.get(&edge.from).copied();
+            let to = supernode_of.get(&edge.to).copied();
+            if from.is_none() && to.is_none() {
+                edges.push(edge.clone());
+                edge_counts.push(1);
+                continue;
+            }
+            let key = (from.unwrap_or(edge.from), to.unwrap_or(edge.to));
+            if key.0 == key.1 {
+                continue;
+            }
+            let entry = merged.entry(key).or_default();
+            entry.0 += edge.score * edge.confidence;
+            entry.1 += 1;
+            entry.2.insert(edge.edge_type.as_str());
+        }
+        for ((from, to), (weight, count, types)) in merged {
+            let edge_type = match types.into_iter().collect::<Vec<_>>().as_slice() {
+                [only] => only.to_string(),
+                _ => MIXED_EDGE_TYPE.to_string(),
+            };
+            edges.push(GraphEdge {
+                from,
+                to,
+                edge_type,
+                score: weight,
+                confidence: 1.0,
+            });
+            edge_counts.push(count);
+        }
+
+        GraphSummary {
+            graph: KnowledgeGraph { nodes, edges },
+            supernodes,
+            edge_counts,
+            original: self.clone(),
+        }
+    }
+}
+
+impl GraphSummary {
+    /// Whether any community was collapsed
+    pub fn is_collapsed(&self) -> bool {
+        !self.supernodes.is_empty()
+    }
+
+    /// Export the collapsed graph as vis.js compatible JSON
+    pub fn to_visjs_json(&self) -> serde_json::Value {
+        self.to_visjs_json_with_layout(StyleRegistry::global(), None)
+    }
+
+    /// Export the collapsed graph as vis.js compatible JSON. Supernodes are
+    /// sized by member count and list their `members`; merged edges report
+    /// how many originals they stand for.
+    pub fn to_visjs_json_with_layout(
+        &self,
+        styles: &StyleRegistry,
+        layout: Option<&GraphLayout>,
+    ) -> serde_json::Value {
+        let mut data = self.graph.to_visjs_json_with_layout(styles, layout);
+        let supernodes: HashMap<MemoryId, &Supernode> =
+            self.supernodes.iter().map(|s| (s.id, s)).collect();
+        if let Some(nodes) = data["nodes"].as_array_mut() {
+            for node in nodes {
+                let Some(supernode) = node["id"].as_i64().and_then(|id| supernodes.get(&id)) else {
+                    continue;
+                };
+                node["value"] = serde_json::json!(supernode.members.len() + 5);
+                node["members"] = serde_json::json!(supernode.members);
+                node["title"] = serde_json::json!(format!(
+                    "{} memories, {} internal links\nMostly: {}\nTags: {}",
+                    supernode.members.len(),
+                    supernode.internal_edges,
+                    supernode.dominant_type.as_deref().unwrap_or("-"),
+                    supernode.common_tags.join(", ")
+                ));
+            }
+        }
+        if let Some(edges) = data["edges"].as_array_mut() {
+            for ((json, edge), &count) in edges
+                .iter_mut()
+                .zip(&self.graph.edges)
+                .zip(&self.edge_counts)
+            {
+                if count > 1 {
+                    json["label"] = serde_json::json!(format!("{} ×{}", edge.edge_type, count));
+                    json["value"] = serde_json::json!((edge.score * 5.0) as i32 + 1);
+                    json["title"] = serde_json::json!(format!(
+                        "{} links, total weight {:.2}",
+                        count, edge.score
+                    ));
+                }
+            }
+        }
+        data
+    }
+
+    /// Standalone HTML showing the collapsed graph
+    pub fn to_html(&self) -> String {
+        self.to_html_with(StyleRegistry::global())
+    }
+
+    /// Standalone HTML showing the collapsed graph, styled from `styles`.
+    /// Clicking a supernode expands it into its members; double-clicking a
+    /// member collapses it again.
+    pub fn to_html_with(&self, styles: &StyleRegistry) -> String {
+        let full = self.original.to_visjs_json_with(styles);
+        let collapsed = self.to_visjs_json_with_layout(styles, None);
+        let supernodes: Vec<&serde_json::Value> = collapsed["nodes"]
+            .as_array()
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .filter(|n| n.get("members").is_some())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let (legend, groups) = self.graph.html_legend_and_groups(styles);
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Engram Knowledge Graph Summary</title>
+    <script type="text/javascript" src="https://unpkg.com/vis-network/standalone/umd/vis-network.min.js"></script>
+    <style>
+        body {{ margin: 0; padding: 0; font-family: system-ui, sans-serif; }}
+        #graph {{ width: 100vw; height: 100vh; }}
+        #controls {{
+            position: absolute;
+            top: 10px;
+            left: 10px;
+            z-index: 1;
+            background: white;
+            padding: 10px;
+            border-radius: 8px;
+            box-shadow: 0 2px 8px rgba(0,0,0,0.1);
+        }}
+        #search {{ padding: 8px; width: 200px; border: 1px solid #ddd; border-radius: 4px; }}
+        #hint {{ font-size: 12px; margin-top: 6px; color: #666; }}
+        .legend {{ display: flex; gap: 10px; margin-top: 10px; flex-wrap: wrap; }}
+        .legend-item {{ display: flex; align-items: center; gap: 5px; font-size: 12px; }}
+        .legend-dot {{ width: 12px; height: 12px; border-radius: 50%; }}
+    </style>
+</head>
+<body>
+    <div id="controls">
+        <input type="text" id="search" placeholder="Search nodes...">
+        <button id="collapse-all">Collapse all</button>
+        <div id="hint">Click a cluster to expand it, double-click a member to collapse it</div>
+        <div class="legend">{legend}
+        </div>
+    </div>
+    <div id="graph"></div>
+    <script>
+        const full = {full};
+        const supernodes = {supernodes};
+
+        const memberOf = {{}};
+        supernodes.forEach(s => s.members.forEach(m => {{ memberOf[m] = s.id; }}));
+        const expanded = new Set();
+
+        // Where a memory is drawn: its own node, or its collapsed supernode
+        function rep(id) {{
+            const s = memberOf[id];
+            return s !== undefined && !expanded.has(s) ? s : id;
+        }}
+
+        const nodes = new vis.DataSet(full.nodes.concat(supernodes));
+        const edges = new vis.DataSet();
+        const nodeView = new vis.DataView(nodes, {{ filter: n => rep(n.id) === n.id && !expanded.has(n.id) }});
+
+        function render() {{
+            const direct = [];
+            const merged = new Map();
+            full.edges.forEach(e => {{
+                const from = rep(e.from);
+                const to = rep(e.to);
+                if (from === e.from && to === e.to) {{
+                    direct.push(e);
+                    return;
+                }}
+                if (from === to) return;
+                const key = from + '>' + to;
+                const m = merged.get(key) || {{ from, to, value: 0, count: 0 }};
+                m.value += e.value;
+                m.count += 1;
+                merged.set(key, m);
+            }});
+            const aggregated = Array.from(merged.values()).map(m => ({{
+                from: m.from,
+                to: m.to,
+                value: m.value,
+                label: m.count > 1 ? '×' + m.count : '',
+                title: m.count + ' links'
+            }}));
+            edges.clear();
+            edges.add(direct.concat(aggregated));
+            nodeView.refresh();
+        }}
+
+        const options = {{
+            nodes: {{
+                shape: 'dot',
+                scaling: {{ min: 10, max: 30 }},
+                font: {{ size: 12, face: 'system-ui' }}
+            }},
+            edges: {{
+                arrows: 'to',
+                scaling: {{ min: 1, max: 5 }},
+                font: {{ size: 10, align: 'middle' }}
+            }},
+            groups: {groups},
+            physics: {{
+                stabilization: {{ iterations: 100 }},
+                barnesHut: {{
+                    gravitationalConstant: -2000,
+                    springLength: 100
+                }}
+            }},
+            interaction: {{
+                hover: true,
+                tooltipDelay: 100
+            }}
+        }};
+
+        const network = new vis.Network(
+            document.getElementById('graph'),
+            {{ nodes: nodeView, edges }},
+            options
+        );
+
+        function expand(id) {{
+            const at = network.getPosition(id);
+            expanded.add(id);
+            const s = nodes.get(id);
+            nodes.update(s.members.map(m => ({{
+                id: m,
+                x: at.x + (Math.random() - 0.5) * 100,
+                y: at.y + (Math.random() - 0.5) * 100
+            }})));
+            render();
+        }}
+
+        function collapse(id) {{
+            expanded.delete(id);
+            render();
+        }}
+
+        network.on('click', params => {{
+            const id = params.nodes[0];
+            if (id !== undefined && nodes.get(id).members) expand(id);
+        }});
+        network.on('doubleClick', params => {{
+            const id = params.nodes[0];
+            if (id !== undefined && memberOf[id] !== undefined) collapse(memberOf[id]);
+        }});
+        document.getElementById('collapse-all').addEventListener('click', () => {{
+            expanded.clear();
+            render();
+        }});
+
+        // Search expands the supernodes hiding a match
+        document.getElementById('search').addEventListener('input', function() {{
+            const query = this.value.toLowerCase();
+            if (!query) {{
+                network.unselectAll();
+                return;
+            }}
+            const matches = full.nodes.filter(n => n.label.toLowerCase().includes(query)).map(n => n.id);
+            matches.forEach(id => {{
+                const s = memberOf[id];
+                if (s !== undefined && !expanded.has(s)) expand(s);
+            }});
+            network.selectNodes(matches);
+            if (matches.length > 0) {{
+                network.focus(matches[0], {{ scale: 1.5, animation: true }});
+            }}
+        }});
+
+        render();
+    </script>
+</body>
+</html>"#,
+            full = serde_json::to_string(&full).unwrap_or_default(),
+            supernodes = serde_json::to_string(&supernodes).unwrap_or_default(),
+            groups = groups,
+            legend = legend,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: MemoryId, importance: f32) -> GraphNode {
+        GraphNode {
+            id,
+            label: format!("Node {}", id),
+            memory_type: "note".to_string(),
+            importance,
+            tags: vec!["shared".to_string()],
+        }
+    }
+
+    fn edge(from: MemoryId, to: MemoryId, edge_type: &str) -> GraphEdge {
+        GraphEdge {
+            from,
+            to,
+            edge_type: edge_type.to_string(),
+            score: 1.0,
+            confidence: 0.5,
+        }
+    }
+
+    /// Two 5-cliques (1–5 and 6–10) joined by bridges 5–6 and 4–7, plus an
+    /// isolated node 11. Node 3 is the most important.
+    fn barbell() -> KnowledgeGraph {
+        let mut edges = Vec::new();
+        for clique in [1..=5, 6..=10] {
+            let ids: Vec<MemoryId> = clique.collect();
+            for (i, &a) in ids.iter().enumerate() {
+                for &b in &ids[i + 1..] {
+                    edges.push(edge(a, b, "related_to"));
+                }
+            }
+        }
+        edges.push(edge(5, 6, "related_to"));
+        edges.push(edge(4, 7, "supports"));
+        KnowledgeGraph {
+            nodes: (1..=11)
+                .map(|id| node(id, if id == 3 { 0.9 } else { 0.5 }))
+                .collect(),
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_small_graph_is_left_alone() {
+        let graph = barbell();
+        let summary = graph.summarize(11);
+
+        assert!(!summary.is_collapsed());
+        assert_eq!(summary.graph.nodes.len(), 11);
+        assert_eq!(summary.graph.edges.len(), graph.edges.len());
+        assert!(summary.edge_counts.iter().all(|&c| c == 1));
+    }
+
+    #[test]
+    fn test_collapses_largest_communities_until_within_limit() {
+        let summary = barbell().summarize(7);
+
+        // One clique folds into a supernode: 11 - 4 = 7 nodes
+        assert_eq!(summary.supernodes.len(), 1);
+        assert_eq!(summary.graph.nodes.len(), 7);
+        let supernode = &summary.supernodes[0];
+        assert!(supernode.id < 0);
+        assert_eq!(supernode.members.len(), 5);
+        assert_eq!(supernode.internal_edges, 10);
+        assert_eq!(supernode.common_tags, vec!["shared".to_string()]);
+        // Each bridge now leaves the supernode on its own
+        assert_eq!(summary.graph.edges.len(), 10 + 2);
+    }
+
+    #[test]
+    fn test_parallel_edges_into_supernodes_are_merged() {
+        let summary = barbell().summarize(3);
+        let side = |id: MemoryId| {
+            summary
+                .supernodes
+                .iter()
+                .find(|s| s.members.contains(&id))
+                .unwrap()
+                .id
+        };
+
+        // Both bridges merge into one edge worth two originals
+        assert_eq!(summary.graph.edges.len(), 1);
+        assert_eq!(summary.edge_counts, vec![2]);
+        let bridge = &summary.graph.edges[0];
+        assert_eq!((bridge.from, bridge.to), (side(5), side(6)));
+        assert_eq!(bridge.edge_type, MIXED_EDGE_TYPE);
+        assert!((bridge.score - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_best_effort_when_limit_is_unreachable() {
+        let summary = barbell().summarize(1);
+
+        // Both cliques collapse; the isolated node can't be merged away
+        assert_eq!(summary.supernodes.len(), 2);
+        assert_eq!(summary.graph.nodes.len(), 3);
+        let lead = summary
+            .supernodes
+            .iter()
+            .find(|s| s.members.contains(&3))
+            .unwrap();
+        assert_eq!(lead.label, "Node 3 (+4)");
+    }
+
+    #[test]
+    fn test_exports_carry_member_counts() {
+        let summary = barbell().summarize(3);
+        let json = summary.to_visjs_json();
+        let supernode = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|n| n["group"] == SUPERNODE_TYPE)
+            .unwrap();
+        assert_eq!(supernode["members"].as_array().unwrap().len(), 5);
+        assert!(json["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["title"] == "2 links, total weight 1.00"));
+
+        let html = summary.to_html();
+        assert!(html.contains("\"members\""));
+        assert!(html.contains("network.on('click'"));
+    }
+}
@@ -0,0 +1,408 @@
+//! Tag taxonomy as a graph
+//!
+//! Slash-separated tags (`project/engram/core`) form a tree, built by
+//! [`get_tag_hierarchy`](crate::storage::queries::get_tag_hierarchy).
+//! [`KnowledgeGraph::from_tag_hierarchy`] turns that tree into a graph with
+//! one node per tag path, sized and labelled by how many memories sit below
+//! it, so the taxonomy can be audited with the usual exports or with the
+//! tree-shaped HTML and DOT renderings here. Both can link every tag to a
+//! drill-down URL built from a template with a `{tag}` placeholder.
+
+use serde::{Deserialize, Serialize};
+
+use super::{GraphEdge, GraphNode, KnowledgeGraph, StyleRegistry};
+use crate::storage::queries::TagHierarchyNode;
+use crate::types::MemoryId;
+
+/// `memory_type` of the nodes of a tag tree
+pub const TAG_NODE_TYPE: &str = "tag";
+
+/// Edge type from a tag to a tag nested under it
+pub const SUBTAG_EDGE: &str = "subtag";
+
+/// Which tags [`KnowledgeGraph::from_tag_hierarchy_with`] keeps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagTreeOptions {
+    /// Deepest level kept, roots being level 1
+    pub max_depth: Option<usize>,
+    /// Leave out tags with fewer memories (their subtrees too)
+    pub min_count: i64,
+}
+
+impl KnowledgeGraph {
+    /// Graph of a tag hierarchy: one node per tag path, with edges from each
+    /// tag to the tags nested under it
+    pub fn from_tag_hierarchy(roots: &[TagHierarchyNode]) -> Self {
+        Self::from_tag_hierarchy_with(roots, &TagTreeOptions::default())
+    }
+
+    /// Graph of a tag hierarchy, pruned by `options`
+    ///
+    /// Nodes are numbered from 1 in depth-first order, labelled
+    /// `name (count)`, typed [`TAG_NODE_TYPE`] and carry their full path as
+    /// their only tag. Importance is the count relative to the largest one.
+    /// Edges are typed [`SUBTAG_EDGE`] and scored with the child's share of
+    /// its parent's count.
+    pub fn from_tag_hierarchy_with(roots: &[TagHierarchyNode], options: &TagTreeOptions) -> Self {
+        fn visit(
+            graph: &mut KnowledgeGraph,
+            node: &TagHierarchyNode,
+            parent: Option<(MemoryId, i64)>,
+            depth: usize,
+            options: &TagTreeOptions,
+        ) {
+            if node.count < options.min_count || options.max_depth.is_some_and(|max| depth > max) {
+                return;
+            }
+            let id = graph.nodes.len() as MemoryId + 1;
+            graph.nodes.push(GraphNode {
+                id,
+                label: format!("{} ({})", node.name, node.count),
+                memory_type: TAG_NODE_TYPE.to_string(),
+                importance: node.count as f32,
+                tags: vec![node.full_path.clone()],
+            });
+            if let Some((parent_id, parent_count)) = parent {
+                graph.edges.push(GraphEdge {
+                    from: parent_id,
+                    to: id,
+                    edge_type: SUBTAG_EDGE.to_string(),
+                    score: if parent_count > 0 {
+                        node.count as f32 / parent_count as f32
+                    } else {
+                        0.0
+                    },
+                    confidence: 1.0,
+                });
+            }
+            for child in &node.children {
+                visit(graph, child, Some((id, node.count)), depth + 1, options);
+            }
+        }
+
+        let mut graph = KnowledgeGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        for root in roots {
+            visit(&mut graph, root, None, 1, options);
+        }
+        let max = graph
+            .nodes
+            .iter()
+            .map(|n| n.importance)
+            .fold(0.0f32, f32::max);
+        if max > 0.0 {
+            for node in &mut graph.nodes {
+                node.importance /= max;
+            }
+        }
+        graph
+    }
+
+    /// Standalone HTML of a tag tree, laid out top-down. Clicking a tag
+    /// folds or unfolds the tags below it; with a `link_template`
+    /// (e.g. `https://notes.example/tags/{tag}`) double-clicking opens it
+    /// with the tag's URL-encoded path.
+    pub fn to_tag_tree_html(&self, styles: &StyleRegistry, link_template: Option<&str>) -> String {
+        let mut data = self.to_visjs_json_with(styles);
+        if let Some(nodes) = data["nodes"].as_array_mut() {
+            for (json, node) in nodes.iter_mut().zip(&self.nodes) {
+                let path = tag_path(node);
+                json["path"] = serde_json::json!(path);
+                json["title"] = serde_json::json!(path);
+            }
+        }
+        if let Some(edges) = data["edges"].as_array_mut() {
+            for edge in edges {
+                edge["label"] = serde_json::json!("");
+            }
+        }
+        let hint = if link_template.is_some() {
+            "Click a tag to fold its subtags, double-click to open it"
+        } else {
+            "Click a tag to fold its subtags"
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Engram Tag Tree</title>
+    <script type="text/javascript" src="https://unpkg.com/vis-network/standalone/umd/vis-network.min.js"></script>
+    <style>
+        body {{ margin: 0; padding: 0; font-family: system-ui, sans-serif; }}
+        #graph {{ width: 100vw; height: 100vh; }}
+        #controls {{
+            position: absolute;
+            top: 10px;
+            left: 10px;
+            z-index: 1;
+            background: white;
+            padding: 10px;
+            border-radius: 8px;
+            box-shadow: 0 2px 8px rgba(0,0,0,0.1);
+        }}
+        #search {{ padding: 8px; width: 200px; border: 1px solid #ddd; border-radius: 4px; }}
+        #hint {{ font-size: 12px; margin-top: 6px; color: #666; }}
+    </style>
+</head>
+<body>
+    <div id="controls">
+        <input type="text" id="search" placeholder="Search tags...">
+        <div id="hint">{hint}</div>
+    </div>
+    <div id="graph"></div>
+    <script>
+        const data = {data};
+        const linkTemplate = {link_template};
+
+        const children = {{}};
+        data.edges.forEach(e => {{ (children[e.from] = children[e.from] || []).push(e.to); }});
+        const folded = new Set();
+
+        function hidden(id) {{
+            const parent = data.edges.find(e => e.to === id);
+            return parent !== undefined && (folded.has(parent.from) || hidden(parent.from));
+        }}
+
+        const nodes = new vis.DataSet(data.nodes);
+        const nodeView = new vis.DataView(nodes, {{ filter: n => !hidden(n.id) }});
+
+        const options = {{
+            nodes: {{
+                shape: 'dot',
+                scaling: {{ min: 10, max: 30 }},
+                font: {{ size: 12, face: 'system-ui' }}
+            }},
+            edges: {{
+                arrows: 'to',
+                scaling: {{ min: 1, max: 5 }}
+            }},
+            layout: {{
+                hierarchical: {{
+                    direction: 'UD',
+                    sortMethod: 'directed',
+                    levelSeparation: 120,
+                    nodeSpacing: 140
+                }}
+            }},
+            physics: {{ enabled: false }},
+            interaction: {{
+                hover: true,
+                tooltipDelay: 100
+            }}
+        }};
+
+        const network = new vis.Network(
+            document.getElementById('graph'),
+            {{ nodes: nodeView, edges: data.edges }},
+            options
+        );
+
+        network.on('click', params => {{
+            const id = params.nodes[0];
+            if (id === undefined || !children[id]) return;
+            if (folded.has(id)) {{
+                folded.delete(id);
+                nodes.update({{ id, borderWidth: 1 }});
+            }} else {{
+                folded.add(id);
+                nodes.update({{ id, borderWidth: 4 }});
+            }}
+            nodeView.refresh();
+        }});
+        network.on('doubleClick', params => {{
+            const id = params.nodes[0];
+            if (id === undefined || !linkTemplate) return;
+            window.open(linkTemplate.replace('{{tag}}', encodeURIComponent(nodes.get(id).path)), '_blank');
+        }});
+
+        // Search unfolds the tags hiding a match
+        document.getElementById('search').addEventListener('input', function() {{
+            const query = this.value.toLowerCase();
+            if (!query) {{
+                network.unselectAll();
+                return;
+            }}
+            const matches = data.nodes.filter(n => n.path.toLowerCase().includes(query)).map(n => n.id);
+            if (matches.some(hidden)) {{
+                folded.clear();
+                nodes.update(data.nodes.map(n => ({{ id: n.id, borderWidth: 1 }})));
+                nodeView.refresh();
+            }}
+            network.selectNodes(matches);
+            if (matches.length > 0) {{
+                network.focus(matches[0], {{ scale: 1.2, animation: true }});
+            }}
+        }});
+    </script>
+</body>
+</html>"#,
+            hint = hint,
+            data = serde_json::to_string(&data).unwrap_or_default(),
+            link_template = serde_json::to_string(&link_template).unwrap_or_default(),
+        )
+    }
+
+    /// Export a tag tree as DOT, ranked top-down. With a `link_template`
+    /// every tag gets a `URL`, which Graphviz's SVG output turns into a link.
+    pub fn to_tag_tree_dot(&self, styles: &StyleRegistry, link_template: Option<&str>) -> String {
+        let style = styles.style_for(TAG_NODE_TYPE);
+        let mut dot = String::from("digraph tag_tree {\n");
+        dot.push_str("    rankdir=TB;\n");
+        dot.push_str(&format!(
+            "    node [shape=box, style=\"filled,rounded\", fillcolor=\"{}\"];\n\n",
+            style.color
+        ));
+
+        for node in &self.nodes {
+            let path = tag_path(node);
+            let mut attributes = format!(
+                "label=\"{}\", tooltip=\"{}\"",
+                dot_escape(&node.label),
+                dot_escape(path)
+            );
+            if let Some(template) = link_template {
+                attributes.push_str(&format!(
+                    ", URL=\"{}\"",
+                    dot_escape(&template.replace("{tag}", &percent_encode(path)))
+                ));
+            }
+            dot.push_str(&format!("    \"{}\" [{}];\n", node.id, attributes));
+        }
+
+        dot.push('\n');
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [penwidth={:.1}];\n",
+                edge.from,
+                edge.to,
+                0.5 + edge.score * 2.5
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Full path of a tag tree node, falling back to its label
+fn tag_path(node: &GraphNode) -> &str {
+    node.tags.first().map_or(&node.label, |path| path.as_str())
+}
+
+/// Escape text for a quoted DOT attribute value
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(
+        name: &str,
+        full_path: &str,
+        count: i64,
+        children: Vec<TagHierarchyNode>,
+    ) -> TagHierarchyNode {
+        TagHierarchyNode {
+            name: name.to_string(),
+            full_path: full_path.to_string(),
+            count,
+            children,
+        }
+    }
+
+    fn hierarchy() -> Vec<TagHierarchyNode> {
+        vec![
+            tag(
+                "project",
+                "project",
+                10,
+                vec![
+                    tag(
+                        "engram",
+                        "project/engram",
+                        8,
+                        vec![tag("core", "project/engram/core", 2, vec![])],
+                    ),
+                    tag("web", "project/web", 2, vec![]),
+                ],
+            ),
+            tag("todo", "todo", 5, vec![]),
+        ]
+    }
+
+    #[test]
+    fn test_from_tag_hierarchy() {
+        let graph = KnowledgeGraph::from_tag_hierarchy(&hierarchy());
+
+        let labels: Vec<&str> = graph.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "project (10)",
+                "engram (8)",
+                "core (2)",
+                "web (2)",
+                "todo (5)"
+            ]
+        );
+        assert_eq!(graph.nodes[2].tags, ["project/engram/core"]);
+        assert_eq!(graph.nodes[0].importance, 1.0);
+        assert_eq!(graph.nodes[4].importance, 0.5);
+        let edges: Vec<(MemoryId, MemoryId)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(edges, [(1, 2), (2, 3), (1, 4)]);
+        assert!((graph.edges[0].score - 0.8).abs() < 1e-6);
+        assert!(graph.edges.iter().all(|e| e.edge_type == SUBTAG_EDGE));
+
+        let pruned = KnowledgeGraph::from_tag_hierarchy_with(
+            &hierarchy(),
+            &TagTreeOptions {
+                max_depth: Some(2),
+                min_count: 3,
+            },
+        );
+        let labels: Vec<&str> = pruned.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, ["project (10)", "engram (8)", "todo (5)"]);
+        assert_eq!(pruned.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_tag_tree_exports_link_tags() {
+        let graph = KnowledgeGraph::from_tag_hierarchy(&hierarchy());
+        let styles = StyleRegistry::default();
+        let template = Some("https://notes.example/tags/{tag}");
+
+        let dot = graph.to_tag_tree_dot(&styles, template);
+        assert!(dot.contains("rankdir=TB"));
+        assert!(dot.contains(
+            "\"3\" [label=\"core (2)\", tooltip=\"project/engram/core\", URL=\"https://notes.example/tags/project%2Fengram%2Fcore\"];"
+        ));
+        assert!(dot.contains("\"1\" -> \"2\""));
+        assert!(!graph.to_tag_tree_dot(&styles, None).contains("URL="));
+
+        let html = graph.to_tag_tree_html(&styles, template);
+        assert!(html.contains("hierarchical"));
+        assert!(html.contains("\"path\":\"project/engram/core\""));
+        assert!(html.contains("const linkTemplate = \"https://notes.example/tags/{tag}\";"));
+        assert!(graph
+            .to_tag_tree_html(&styles, None)
+            .contains("const linkTemplate = null;"));
+    }
+}
@@ -0,0 +1,426 @@
+//! Knowledge graph evolution over time
+//!
+//! [`GraphTimeline`] samples [`KnowledgeGraph::at`] at a series of timestamps
+//! and keeps, per frame, which nodes and edges existed. The frames share one
+//! set of node and edge definitions, so [`GraphTimeline::to_html`] can render
+//! a single vis.js network and move through time with a slider by filtering
+//! it instead of rebuilding it.
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{GraphEdge, GraphNode, KnowledgeGraph, LabelOptions, StyleRegistry};
+use crate::error::Result;
+use crate::types::MemoryId;
+
+/// `(from, to, edge_type)` — identifies an edge across frames
+pub type EdgeKey = (MemoryId, MemoryId, String);
+
+/// Which nodes and edges existed at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineFrame {
+    pub timestamp: DateTime<Utc>,
+    pub nodes: Vec<MemoryId>,
+    pub edges: Vec<EdgeKey>,
+}
+
+/// A sequence of graph snapshots over one shared graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphTimeline {
+    /// Frames in the order given, normally oldest first
+    pub frames: Vec<TimelineFrame>,
+    /// Every node and edge that appears in some frame, as of the last frame
+    /// it appears in
+    pub graph: KnowledgeGraph,
+}
+
+impl GraphTimeline {
+    /// Snapshot the graph at each of `timestamps`
+    pub fn build(
+        conn: &Connection,
+        timestamps: &[DateTime<Utc>],
+        max_nodes: i64,
+        labels: &LabelOptions,
+    ) -> Result<Self> {
+        let snapshots = timestamps
+            .iter()
+            .map(|&t| Ok((t, KnowledgeGraph::at(conn, t, max_nodes, labels)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::from_snapshots(snapshots))
+    }
+
+    /// Combine already-built snapshots into a timeline
+    pub fn from_snapshots(snapshots: Vec<(DateTime<Utc>, KnowledgeGraph)>) -> Self {
+        let mut nodes: BTreeMap<MemoryId, GraphNode> = BTreeMap::new();
+        let mut edges: BTreeMap<EdgeKey, GraphEdge> = BTreeMap::new();
+        let mut frames = Vec::with_capacity(snapshots.len());
+
+        for (timestamp, graph) in snapshots {
+            let mut frame = TimelineFrame {
+                timestamp,
+                nodes: Vec::with_capacity(graph.nodes.len()),
+                edges: Vec::with_capacity(graph.edges.len()),
+            };
+            for node in graph.nodes {
+                frame.nodes.push(node.id);
+                nodes.insert(node.id, node);
+            }
+            for edge in graph.edges {
+                let key = (edge.from, edge.to, edge.edge_type.clone());
+                frame.edges.push(key.clone());
+                edges.insert(key, edge);
+            }
+            frames.push(frame);
+        }
+
+        Self {
+            frames,
+            graph: KnowledgeGraph {
+                nodes: nodes.into_values().collect(),
+                edges: edges.into_values().collect(),
+            },
+        }
+    }
+
+    /// Standalone HTML with a time slider
+    pub fn to_html(&self) -> String {
+        self.to_html_with(StyleRegistry::global())
+    }
+
+    /// Standalone HTML with a time slider, styled from `styles`
+    pub fn to_html_with(&self, styles: &StyleRegistry) -> String {
+        let mut graph_data = self.graph.to_visjs_json_with(styles);
+        if let Some(edges) = graph_data["edges"].as_array_mut() {
+            for (json, edge) in edges.iter_mut().zip(&self.graph.edges) {
+                json["id"] = edge_id(&(edge.from, edge.to, edge.edge_type.clone())).into();
+            }
+        }
+        let frames: Vec<serde_json::Value> = self
+            .frames
+            .iter()
+            .map(|frame| {
+                serde_json::json!({
+                    "timestamp": frame.timestamp.to_rfc3339(),
+                    "nodes": frame.nodes,
+                    "edges": frame.edges.iter().map(edge_id).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let (legend, groups) = self.graph.html_legend_and_groups(styles);
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Engram Knowledge Graph Timeline</title>
+    <script type="text/javascript" src="https://unpkg.com/vis-network/standalone/umd/vis-network.min.js"></script>
+    <style>
+        body {{ margin: 0; padding: 0; font-family: system-ui, sans-serif; }}
+        #graph {{ width: 100vw; height: 100vh; }}
+        #controls {{
+            position: absolute;
+            top: 10px;
+            left: 10px;
+            z-index: 1;
+            background: white;
+            padding: 10px;
+            border-radius: 8px;
+            box-shadow: 0 2px 8px rgba(0,0,0,0.1);
+        }}
+        #slider {{ width: 320px; vertical-align: middle; }}
+        #when {{ font-size: 13px; margin-top: 6px; }}
+        .legend {{ display: flex; gap: 10px; margin-top: 10px; flex-wrap: wrap; }}
+        .legend-item {{ display: flex; align-items: center; gap: 5px; font-size: 12px; }}
+        .legend-dot {{ width: 12px; height: 12px; border-radius: 50%; }}
+    </style>
+</head>
+<body>
+    <div id="controls">
+        <button id="play">Play</button>
+        <input type="range" id="slider" min="0" max="{last}" value="{last}" step="1">
+        <div id="when"></div>
+        <div class="legend">{legend}
+        </div>
+    </div>
+    <div id="graph"></div>
+    <script>
+        const data = {graph_data};
+        const frames = {frames};
+
+        const nodes = new vis.DataSet(data.nodes);
+        const edges = new vis.DataSet(data.edges);
+        let visibleNodes = new Set();
+        let visibleEdges = new Set();
+        const nodeView = new vis.DataView(nodes, {{ filter: n => visibleNodes.has(n.id) }});
+        const edgeView = new vis.DataView(edges, {{ filter: e => visibleEdges.has(e.id) }});
+
+        const options = {{
+            nodes: {{
+                shape: 'dot',
+                scaling: {{ min: 10, max: 30 }},
+                font: {{ size: 12, face: 'system-ui' }}
+            }},
+            edges: {{
+                arrows: 'to',
+                scaling: {{ min: 1, max: 5 }},
+                font: {{ size: 10, align: 'middle' }}
+            }},
+            groups: {groups},
+            physics: {{
+                stabilization: {{ iterations: 100 }},
+                barnesHut: {{
+                    gravitationalConstant: -2000,
+                    springLength: 100
+                }}
+            }},
+            interaction: {{
+                hover: true,
+                tooltipDelay: 100
+            }}
+        }};
+
+        const network = new vis.Network(
+            document.getElementById('graph'),
+            {{ nodes: nodeView, edges: edgeView }},
+            options
+        );
+
+        const slider = document.getElementById('slider');
+        const when = document.getElementById('when');
+        const play = document.getElementById('play');
+
+        function show(index) {{
+            const frame = frames[index];
+            if (!frame) return;
+            visibleNodes = new Set(frame.nodes);
+            visibleEdges = new Set(frame.edges);
+            nodeView.refresh();
+            edgeView.refresh();
+            when.textContent = new Date(frame.timestamp).toLocaleString() +
+                ' — ' + frame.nodes.length + ' nodes, ' + frame.edges.length + ' edges';
+        }}
+
+        slider.addEventListener('input', () => show(Number(slider.value)));
+
+        let timer = null;
+        play.addEventListener('click', () => {{
+            if (timer) {{
+                clearInterval(timer);
+                timer = null;
+                play.textContent = 'Play';
+                return;
+            }}
+            if (Number(slider.value) >= frames.length - 1) slider.value = 0;
+            play.textContent = 'Pause';
+            show(Number(slider.value));
+            timer = setInterval(() => {{
+                if (Number(slider.value) >= frames.length - 1) {{
+                    play.click();
+                    return;
+                }}
+                slider.value = Number(slider.value) + 1;
+                show(Number(slider.value));
+            }}, 1000);
+        }});
+
+        show(frames.length - 1);
+    </script>
+</body>
+</html>"#,
+            graph_data = serde_json::to_string(&graph_data).unwrap_or_default(),
+            frames = serde_json::Value::Array(frames),
+            groups = groups,
+            legend = legend,
+            last = self.frames.len().saturating_sub(1),
+        )
+    }
+}
+
+fn edge_id((from, to, edge_type): &EdgeKey) -> String {
+    format!("{from}:{to}:{edge_type}")
+}
+
+/// `steps` timestamps from `from` to `to` inclusive, evenly spaced.
+/// A single step yields just `to`.
+pub fn evenly_spaced(from: DateTime<Utc>, to: DateTime<Utc>, steps: usize) -> Vec<DateTime<Utc>> {
+    if steps <= 1 || to <= from {
+        return vec![to];
+    }
+    let span = (to - from).num_milliseconds();
+    (0..steps)
+        .map(|i| from + Duration::milliseconds(span * i as i64 / (steps - 1) as i64))
+        .collect()
+}
+
+/// Creation time of the oldest memory, where a timeline naturally starts
+pub fn earliest_memory_time(conn: &Connection) -> Result<Option<DateTime<Utc>>> {
+    let earliest: Option<String> =
+        conn.query_row("SELECT MIN(created_at) FROM memories", [], |row| row.get(0))?;
+    Ok(earliest
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_crossref, create_memory, delete_crossref};
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+    use rusqlite::params;
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    /// Memory created at `created` (its first version too)
+    fn memory_at(conn: &Connection, content: &str, created: &str) -> MemoryId {
+        let memory = create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                defer_embedding: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1 WHERE id = ?2",
+            params![ts(created).to_rfc3339(), memory.id],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE memory_versions SET created_at = ?1 WHERE memory_id = ?2",
+            params![ts(created).to_rfc3339(), memory.id],
+        )
+        .unwrap();
+        memory.id
+    }
+
+    /// Link valid from `from` until `to` (open-ended when `None`)
+    fn link_during(conn: &Connection, a: MemoryId, b: MemoryId, from: &str, to: Option<&str>) {
+        create_crossref(
+            conn,
+            &CreateCrossRefInput {
+                from_id: a,
+                to_id: b,
+                edge_type: EdgeType::RelatedTo,
+                strength: None,
+                source_context: None,
+                pinned: false,
+            },
+        )
+        .unwrap();
+        if to.is_some() {
+            delete_crossref(conn, a, b, EdgeType::RelatedTo).unwrap();
+        }
+        conn.execute(
+            "UPDATE crossrefs SET valid_from = ?1, valid_to = ?2 WHERE from_id = ?3 AND to_id = ?4",
+            params![ts(from).to_rfc3339(), to.map(|t| ts(t).to_rfc3339()), a, b],
+        )
+        .unwrap();
+    }
+
+    fn seed(conn: &Connection) -> (MemoryId, MemoryId, MemoryId) {
+        let a = memory_at(conn, "alpha", "2026-01-01T00:00:00Z");
+        let b = memory_at(conn, "beta", "2026-02-01T00:00:00Z");
+        let c = memory_at(conn, "gamma", "2026-03-01T00:00:00Z");
+        link_during(
+            conn,
+            a,
+            b,
+            "2026-02-01T00:00:00Z",
+            Some("2026-03-15T00:00:00Z"),
+        );
+        link_during(conn, b, c, "2026-03-01T00:00:00Z", None);
+        (a, b, c)
+    }
+
+    #[test]
+    fn test_graph_at_follows_validity() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let (a, b, c) = seed(conn);
+                let labels = LabelOptions::default();
+                let ids = |g: &KnowledgeGraph| {
+                    let mut ids: Vec<_> = g.nodes.iter().map(|n| n.id).collect();
+                    ids.sort_unstable();
+                    ids
+                };
+                let ends =
+                    |g: &KnowledgeGraph| g.edges.iter().map(|e| (e.from, e.to)).collect::<Vec<_>>();
+
+                let january = KnowledgeGraph::at(conn, ts("2026-01-15T00:00:00Z"), 100, &labels)?;
+                assert_eq!(ids(&january), vec![a]);
+                assert!(january.edges.is_empty());
+
+                let february = KnowledgeGraph::at(conn, ts("2026-02-15T00:00:00Z"), 100, &labels)?;
+                assert_eq!(ids(&february), vec![a, b]);
+                assert_eq!(ends(&february), vec![(a, b)]);
+
+                let march = KnowledgeGraph::at(conn, ts("2026-03-10T00:00:00Z"), 100, &labels)?;
+                assert_eq!(ends(&march), vec![(a, b), (b, c)]);
+
+                // The a→b link was removed in mid-March.
+                let april = KnowledgeGraph::at(conn, ts("2026-04-01T00:00:00Z"), 100, &labels)?;
+                assert_eq!(ids(&april), vec![a, b, c]);
+                assert_eq!(ends(&april), vec![(b, c)]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_timeline_frames_share_one_graph() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let (a, b, c) = seed(conn);
+                assert_eq!(
+                    earliest_memory_time(conn)?,
+                    Some(ts("2026-01-01T00:00:00Z"))
+                );
+
+                let times = [
+                    ts("2026-01-15T00:00:00Z"),
+                    ts("2026-03-10T00:00:00Z"),
+                    ts("2026-04-01T00:00:00Z"),
+                ];
+                let timeline = GraphTimeline::build(conn, &times, 100, &LabelOptions::default())?;
+                assert_eq!(timeline.frames.len(), 3);
+                assert_eq!(timeline.frames[0].nodes, vec![a]);
+                assert_eq!(timeline.frames[1].edges.len(), 2);
+                assert_eq!(
+                    timeline.frames[2].edges,
+                    vec![(b, c, "related_to".to_string())]
+                );
+                // Edges that later disappeared still exist in the shared graph.
+                assert_eq!(timeline.graph.nodes.len(), 3);
+                assert_eq!(timeline.graph.edges.len(), 2);
+
+                let html = timeline.to_html();
+                assert!(html.contains(r#"id="slider" min="0" max="2""#));
+                assert!(html.contains(&format!("\"{a}:{b}:related_to\"")));
+                assert!(html.contains("new vis.DataView"));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_evenly_spaced() {
+        let from = ts("2026-01-01T00:00:00Z");
+        let to = ts("2026-01-11T00:00:00Z");
+        let times = evenly_spaced(from, to, 6);
+        assert_eq!(times.len(), 6);
+        assert_eq!(times[0], from);
+        assert_eq!(times[1], ts("2026-01-03T00:00:00Z"));
+        assert_eq!(times[5], to);
+
+        assert_eq!(evenly_spaced(from, to, 1), vec![to]);
+        assert_eq!(evenly_spaced(to, from, 5), vec![from]);
+    }
+}
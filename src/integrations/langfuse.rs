@@ -4,10 +4,13 @@
 //! - Fetching traces and generations
 //! - Extracting patterns from successful/failed prompts
 //! - Converting traces to memories automatically
+//! - Prioritizing trace memories by their scores
 //!
 //! All code is feature-gated behind `#[cfg(feature = "langfuse")]`
 
 use crate::error::{EngramError, Result};
+use crate::intelligence::importance::{ImportancePolicy, ScoreBand};
+use crate::types::{Memory, MemoryType};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -462,6 +465,122 @@ pub fn trace_to_memory_content(trace: &Trace, generations: &[TraceGeneration]) -
     content
 }
 
+/// Metadata key holding the Langfuse trace ID of an imported trace memory
+pub const TRACE_ID_KEY: &str = "langfuse_trace_id";
+
+/// Metadata key holding the mean score of a trace memory
+pub const SCORE_KEY: &str = "langfuse_score";
+
+/// Metadata key holding every score of a trace memory, by name
+pub const SCORES_KEY: &str = "langfuse_scores";
+
+/// Metadata key holding the importance added for the score, so a later
+/// backfill can replace it instead of stacking on top of it
+pub const SCORE_ADJUSTMENT_KEY: &str = "langfuse_score_adjustment";
+
+/// Tag of trace memories whose mean score is low
+pub const LOW_SCORE_TAG: &str = "langfuse:low-score";
+
+/// Tag of trace memories whose mean score is high
+pub const HIGH_SCORE_TAG: &str = "langfuse:high-score";
+
+/// Tag of low-scored traces kept as learnings about what went wrong
+pub const FAILURE_MODE_TAG: &str = "failure-mode";
+
+/// How a trace's scores shape the memory made from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceScoring {
+    /// Mean of the trace's scores, clamped to `[0, 1]`
+    pub score: f32,
+    pub band: ScoreBand,
+    /// Importance added on top of the memory's base importance
+    pub adjustment: f32,
+    /// `Some(Learning)` when a low score should turn the memory into a
+    /// learning about the failure
+    pub memory_type: Option<MemoryType>,
+    /// Tags to add to the memory
+    pub tags: Vec<String>,
+    /// Metadata entries to set on the memory
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Mean of a trace's scores, or `None` when it has none
+///
+/// Scores are expected in `[0, 1]`, as Langfuse numeric and boolean scores
+/// usually are; values outside are clamped.
+pub fn mean_score(scores: &[TraceScore]) -> Option<f32> {
+    let values: Vec<f64> = scores
+        .iter()
+        .map(|s| s.value)
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    let mean = values.iter().map(|v| v.clamp(0.0, 1.0)).sum::<f64>() / values.len() as f64;
+    Some(mean as f32)
+}
+
+/// Score-derived importance, type and tags for a trace memory, or `None` for
+/// an unscored trace
+pub fn score_trace(trace: &Trace, policy: &ImportancePolicy) -> Option<TraceScoring> {
+    let score = mean_score(&trace.scores)?;
+    let band = policy.score.band(score);
+    let (memory_type, tags) = match band {
+        ScoreBand::Low => (
+            Some(MemoryType::Learning),
+            vec![LOW_SCORE_TAG.to_string(), FAILURE_MODE_TAG.to_string()],
+        ),
+        ScoreBand::Neutral => (None, vec![]),
+        ScoreBand::High => (None, vec![HIGH_SCORE_TAG.to_string()]),
+    };
+    let adjustment = policy.score.adjustment(score);
+
+    let scores: serde_json::Map<String, serde_json::Value> = trace
+        .scores
+        .iter()
+        .map(|s| (s.name.clone(), serde_json::json!(s.value)))
+        .collect();
+    let metadata = HashMap::from([
+        (TRACE_ID_KEY.to_string(), serde_json::json!(trace.id)),
+        (SCORE_KEY.to_string(), serde_json::json!(score)),
+        (SCORES_KEY.to_string(), serde_json::Value::Object(scores)),
+        (
+            SCORE_ADJUSTMENT_KEY.to_string(),
+            serde_json::json!(adjustment),
+        ),
+    ]);
+
+    Some(TraceScoring {
+        score,
+        band,
+        adjustment,
+        memory_type,
+        tags,
+        metadata,
+    })
+}
+
+/// Langfuse trace ID of a memory imported from a trace
+///
+/// Looks at the metadata written by current imports, the `trace:<id>` tag of
+/// `memory_from_trace`, and finally the `**Trace ID:**` line every trace
+/// memory's content starts with.
+pub fn trace_id_of(memory: &Memory) -> Option<String> {
+    if let Some(id) = memory.metadata.get(TRACE_ID_KEY).and_then(|v| v.as_str()) {
+        return Some(id.to_string());
+    }
+    if let Some(id) = memory.tags.iter().find_map(|t| t.strip_prefix("trace:")) {
+        return Some(id.to_string());
+    }
+    memory
+        .content
+        .lines()
+        .find_map(|line| line.strip_prefix("**Trace ID:** "))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -505,4 +624,75 @@ mod tests {
         assert!(content.contains("test-123"));
         assert!(content.contains("quality"));
     }
+
+    fn scored_trace(values: &[f64]) -> Trace {
+        Trace {
+            id: "trace-7".to_string(),
+            name: None,
+            user_id: None,
+            session_id: None,
+            input: None,
+            output: None,
+            metadata: None,
+            tags: vec![],
+            timestamp: Utc::now(),
+            level: None,
+            status_message: None,
+            scores: values
+                .iter()
+                .enumerate()
+                .map(|(i, &value)| TraceScore {
+                    name: format!("eval-{}", i),
+                    value,
+                    comment: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_score_trace_bands() {
+        let policy = ImportancePolicy::default();
+        assert!(score_trace(&scored_trace(&[]), &policy).is_none());
+
+        let low = score_trace(&scored_trace(&[0.1, 0.3]), &policy).unwrap();
+        assert_eq!(low.band, ScoreBand::Low);
+        assert_eq!(low.memory_type, Some(MemoryType::Learning));
+        assert!(low.tags.contains(&FAILURE_MODE_TAG.to_string()));
+        assert_eq!(low.metadata[TRACE_ID_KEY], "trace-7");
+        assert_eq!(low.metadata[SCORES_KEY]["eval-1"], 0.3);
+
+        // Out-of-range values are clamped before averaging
+        let high = score_trace(&scored_trace(&[1.0, 5.0]), &policy).unwrap();
+        assert_eq!(high.score, 1.0);
+        assert_eq!(high.band, ScoreBand::High);
+        assert_eq!(high.memory_type, None);
+        assert_eq!(high.tags, vec![HIGH_SCORE_TAG.to_string()]);
+    }
+
+    #[test]
+    fn test_trace_id_of() {
+        use crate::storage::{queries::create_memory, Storage};
+        use crate::types::CreateMemoryInput;
+
+        let storage = Storage::open_in_memory().unwrap();
+        let mut memory = storage
+            .with_connection(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: trace_to_memory_content(&scored_trace(&[]), &[]),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+        assert_eq!(trace_id_of(&memory).as_deref(), Some("trace-7"));
+        memory.tags.push("trace:from-tag".to_string());
+        assert_eq!(trace_id_of(&memory).as_deref(), Some("from-tag"));
+        memory
+            .metadata
+            .insert(TRACE_ID_KEY.to_string(), serde_json::json!("from-metadata"));
+        assert_eq!(trace_id_of(&memory).as_deref(), Some("from-metadata"));
+    }
 }
@@ -4,6 +4,7 @@
 //!
 //! Currently supported:
 //! - Langfuse (feature-gated behind `langfuse` feature)
+//! - OpenTelemetry GenAI spans over OTLP (feature-gated behind `otel` feature)
 
 #[cfg(feature = "langfuse")]
 pub mod langfuse;
@@ -13,3 +14,9 @@ pub use langfuse::{
     LangfuseClient, LangfuseConfig, LangfuseError, PatternExtraction, SyncProgress, SyncTask,
     Trace, TraceGeneration,
 };
+
+#[cfg(feature = "otel")]
+pub mod otel;
+
+#[cfg(feature = "otel")]
+pub use otel::{ExportTraceServiceRequest, GenAiSpan, SpanPattern};
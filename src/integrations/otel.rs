@@ -0,0 +1,763 @@
+//! OpenTelemetry GenAI trace ingestion
+//!
+//! Vendor-neutral counterpart of the Langfuse integration: instead of pulling
+//! traces from an API, engram receives OTLP/HTTP JSON trace exports (the
+//! `ExportTraceServiceRequest` body sent to `POST /v1/traces`) and turns the
+//! spans that follow the GenAI semantic conventions into memories.
+//!
+//! Recognized attributes:
+//! - OpenTelemetry GenAI: `gen_ai.operation.name`, `gen_ai.system` /
+//!   `gen_ai.provider.name`, `gen_ai.request.model` / `gen_ai.response.model`,
+//!   `gen_ai.usage.*`, `gen_ai.prompt` / `gen_ai.completion` (also indexed as
+//!   `gen_ai.prompt.0.content`), `gen_ai.input.messages` /
+//!   `gen_ai.output.messages`, and the `gen_ai.*.message` / `gen_ai.choice`
+//!   events
+//! - OpenInference (Phoenix): `llm.model_name`, `llm.token_count.*`,
+//!   `llm.input_messages.N.message.*`, `input.value` / `output.value`
+//!
+//! All code is feature-gated behind `#[cfg(feature = "otel")]`
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::graph::label::truncate_graphemes;
+
+/// Metadata key holding the trace ID of a span memory
+pub const OTEL_TRACE_ID_KEY: &str = "otel_trace_id";
+
+/// Metadata key holding the span ID of a span memory
+pub const OTEL_SPAN_ID_KEY: &str = "otel_span_id";
+
+/// Metadata key identifying a pattern memory, e.g. `error:gpt-4o`
+pub const OTEL_PATTERN_KEY: &str = "otel_pattern";
+
+/// Tag of every memory created from OTLP spans
+pub const OTEL_TAG: &str = "otel";
+
+/// Tag of span memories whose span failed
+pub const OTEL_ERROR_TAG: &str = "otel:error";
+
+/// Failed spans of one model needed before they become an error pattern
+pub const MIN_PATTERN_FAILURES: usize = 3;
+
+/// Longest prompt or completion kept in a span memory, in characters
+const MAX_TEXT_CHARS: usize = 4000;
+
+/// OTLP `ExportTraceServiceRequest`, as sent by OTLP/HTTP JSON exporters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportTraceServiceRequest {
+    pub resource_spans: Vec<ResourceSpans>,
+}
+
+/// Spans of one resource (usually one service)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceSpans {
+    pub resource: Resource,
+    pub scope_spans: Vec<ScopeSpans>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Resource {
+    pub attributes: Vec<KeyValue>,
+}
+
+/// Spans of one instrumentation scope
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeSpans {
+    pub spans: Vec<Span>,
+}
+
+/// An OTLP span; IDs are hex strings and times nanoseconds since the epoch,
+/// sent as strings or numbers depending on the exporter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Span {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: String,
+    pub name: String,
+    pub start_time_unix_nano: Value,
+    pub end_time_unix_nano: Value,
+    pub attributes: Vec<KeyValue>,
+    pub events: Vec<SpanEvent>,
+    pub status: SpanStatus,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpanEvent {
+    pub name: String,
+    pub time_unix_nano: Value,
+    pub attributes: Vec<KeyValue>,
+}
+
+/// Span status; `code` is `2` (or `"STATUS_CODE_ERROR"`) for failed spans
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpanStatus {
+    pub code: Value,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: AnyValue,
+}
+
+/// OTLP attribute value; exactly one field is set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnyValue {
+    pub string_value: Option<String>,
+    /// 64-bit integers are sent as strings in OTLP JSON
+    pub int_value: Option<Value>,
+    pub double_value: Option<f64>,
+    pub bool_value: Option<bool>,
+    pub array_value: Option<ArrayValue>,
+    pub kvlist_value: Option<KeyValueList>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArrayValue {
+    pub values: Vec<AnyValue>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyValueList {
+    pub values: Vec<KeyValue>,
+}
+
+impl AnyValue {
+    /// The value as plain JSON
+    pub fn to_json(&self) -> Value {
+        if let Some(s) = &self.string_value {
+            Value::String(s.clone())
+        } else if let Some(i) = &self.int_value {
+            as_i64(i).map(Value::from).unwrap_or(Value::Null)
+        } else if let Some(d) = self.double_value {
+            Value::from(d)
+        } else if let Some(b) = self.bool_value {
+            Value::Bool(b)
+        } else if let Some(array) = &self.array_value {
+            Value::Array(array.values.iter().map(AnyValue::to_json).collect())
+        } else if let Some(list) = &self.kvlist_value {
+            Value::Object(
+                list.values
+                    .iter()
+                    .map(|kv| (kv.key.clone(), kv.value.to_json()))
+                    .collect(),
+            )
+        } else {
+            Value::Null
+        }
+    }
+}
+
+impl ExportTraceServiceRequest {
+    /// Number of spans in the request, GenAI or not
+    pub fn span_count(&self) -> usize {
+        self.resource_spans
+            .iter()
+            .flat_map(|r| &r.scope_spans)
+            .map(|s| s.spans.len())
+            .sum()
+    }
+}
+
+/// A GenAI span, reduced to what a memory needs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenAiSpan {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    /// `service.name` of the resource that sent the span
+    pub service: Option<String>,
+    /// `gen_ai.operation.name`, e.g. `chat`
+    pub operation: Option<String>,
+    /// `gen_ai.system` / `gen_ai.provider.name`, e.g. `openai`
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    /// Prompt text, truncated
+    pub input: Option<String>,
+    /// Completion text, truncated
+    pub output: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub duration_ms: Option<f64>,
+    /// Status message (or `error.type`) of a failed span
+    pub error: Option<String>,
+    pub session_id: Option<String>,
+    pub user_id: Option<String>,
+}
+
+/// A recurring failure across spans, to be stored as an issue memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpanPattern {
+    /// Stable key of the pattern, stored under [`OTEL_PATTERN_KEY`]
+    pub key: String,
+    pub description: String,
+    pub occurrences: usize,
+    pub source_span_ids: Vec<String>,
+    pub suggested_content: String,
+    pub suggested_tags: Vec<String>,
+}
+
+/// The GenAI spans of an OTLP request, in the order they were sent
+///
+/// A span counts as GenAI when it has a `gen_ai.*` or `llm.*` attribute or
+/// is an OpenInference `LLM` span; other spans (HTTP, database, chains) are
+/// ignored.
+pub fn genai_spans(request: &ExportTraceServiceRequest) -> Vec<GenAiSpan> {
+    let mut spans = Vec::new();
+    for resource in &request.resource_spans {
+        let service = attribute_map(&resource.resource.attributes)
+            .get("service.name")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        for span in resource.scope_spans.iter().flat_map(|s| &s.spans) {
+            if let Some(genai) = to_genai_span(span, service.clone()) {
+                spans.push(genai);
+            }
+        }
+    }
+    spans
+}
+
+fn to_genai_span(span: &Span, service: Option<String>) -> Option<GenAiSpan> {
+    let attrs = attribute_map(&span.attributes);
+    let is_genai = attrs
+        .keys()
+        .any(|k| k.starts_with("gen_ai.") || k.starts_with("llm."))
+        || attrs.get("openinference.span.kind").and_then(Value::as_str) == Some("LLM");
+    if !is_genai {
+        return None;
+    }
+
+    let text = |keys: &[&str]| keys.iter().find_map(|k| attrs.get(*k).and_then(value_text));
+    let int = |keys: &[&str]| keys.iter().find_map(|k| attrs.get(*k).and_then(as_i64));
+
+    let input = text(&["gen_ai.prompt"])
+        .or_else(|| indexed_messages(&attrs, "gen_ai.prompt.", ".role", ".content"))
+        .or_else(|| attrs.get("gen_ai.input.messages").and_then(render_messages))
+        .or_else(|| event_messages(&span.events, false))
+        .or_else(|| {
+            indexed_messages(
+                &attrs,
+                "llm.input_messages.",
+                ".message.role",
+                ".message.content",
+            )
+        })
+        .or_else(|| text(&["input.value"]));
+    let output = text(&["gen_ai.completion"])
+        .or_else(|| indexed_messages(&attrs, "gen_ai.completion.", ".role", ".content"))
+        .or_else(|| {
+            attrs
+                .get("gen_ai.output.messages")
+                .and_then(render_messages)
+        })
+        .or_else(|| event_messages(&span.events, true))
+        .or_else(|| {
+            indexed_messages(
+                &attrs,
+                "llm.output_messages.",
+                ".message.role",
+                ".message.content",
+            )
+        })
+        .or_else(|| text(&["output.value"]));
+
+    let start = nanos(&span.start_time_unix_nano);
+    let end = nanos(&span.end_time_unix_nano);
+    let failed = match &span.status.code {
+        Value::Number(n) => n.as_i64() == Some(2),
+        Value::String(s) => s == "STATUS_CODE_ERROR" || s == "2",
+        _ => false,
+    };
+    let error = failed.then(|| {
+        Some(span.status.message.trim())
+            .filter(|m| !m.is_empty())
+            .map(str::to_string)
+            .or_else(|| text(&["error.type"]))
+            .unwrap_or_else(|| "error".to_string())
+    });
+
+    Some(GenAiSpan {
+        trace_id: span.trace_id.clone(),
+        span_id: span.span_id.clone(),
+        parent_span_id: Some(span.parent_span_id.clone()).filter(|p| !p.is_empty()),
+        name: span.name.clone(),
+        service,
+        operation: text(&["gen_ai.operation.name"]),
+        provider: text(&["gen_ai.provider.name", "gen_ai.system", "llm.provider"]),
+        model: text(&[
+            "gen_ai.response.model",
+            "gen_ai.request.model",
+            "llm.model_name",
+        ]),
+        input_tokens: int(&[
+            "gen_ai.usage.input_tokens",
+            "gen_ai.usage.prompt_tokens",
+            "llm.token_count.prompt",
+        ]),
+        output_tokens: int(&[
+            "gen_ai.usage.output_tokens",
+            "gen_ai.usage.completion_tokens",
+            "llm.token_count.completion",
+        ]),
+        input: input.map(|t| truncate_graphemes(&t, MAX_TEXT_CHARS)),
+        output: output.map(|t| truncate_graphemes(&t, MAX_TEXT_CHARS)),
+        start_time: start.and_then(to_datetime),
+        duration_ms: start
+            .zip(end)
+            .filter(|(s, e)| e >= s)
+            .map(|(s, e)| (e - s) as f64 / 1_000_000.0),
+        error,
+        session_id: text(&["session.id", "gen_ai.conversation.id"]),
+        user_id: text(&["user.id", "enduser.id"]),
+    })
+}
+
+/// Convert a GenAI span to memory content
+pub fn span_to_memory_content(span: &GenAiSpan) -> String {
+    let mut content = String::new();
+
+    let title = if span.name.is_empty() {
+        span.operation.as_deref().unwrap_or("GenAI span")
+    } else {
+        &span.name
+    };
+    content.push_str(&format!("# {}\n\n", title));
+
+    content.push_str(&format!("**Trace ID:** {}\n", span.trace_id));
+    content.push_str(&format!("**Span ID:** {}\n", span.span_id));
+    if let Some(start) = span.start_time {
+        content.push_str(&format!("**Timestamp:** {}\n", start));
+    }
+    if let Some(service) = &span.service {
+        content.push_str(&format!("**Service:** {}\n", service));
+    }
+    match (&span.provider, &span.model) {
+        (Some(provider), Some(model)) => {
+            content.push_str(&format!("**Model:** {} ({})\n", model, provider))
+        }
+        (None, Some(model)) => content.push_str(&format!("**Model:** {}\n", model)),
+        (Some(provider), None) => content.push_str(&format!("**Provider:** {}\n", provider)),
+        (None, None) => {}
+    }
+    if span.input_tokens.is_some() || span.output_tokens.is_some() {
+        content.push_str(&format!(
+            "**Tokens:** {} in / {} out\n",
+            span.input_tokens.unwrap_or(0),
+            span.output_tokens.unwrap_or(0)
+        ));
+    }
+    if let Some(duration) = span.duration_ms {
+        content.push_str(&format!("**Duration:** {:.0} ms\n", duration));
+    }
+    if let Some(user_id) = &span.user_id {
+        content.push_str(&format!("**User:** {}\n", user_id));
+    }
+    if let Some(session_id) = &span.session_id {
+        content.push_str(&format!("**Session:** {}\n", session_id));
+    }
+    if let Some(error) = &span.error {
+        content.push_str(&format!("**Error:** {}\n", error));
+    }
+
+    if let Some(input) = &span.input {
+        content.push_str(&format!("\n## Input\n{}\n", input));
+    }
+    if let Some(output) = &span.output {
+        content.push_str(&format!("\n## Output\n{}\n", output));
+    }
+
+    content
+}
+
+/// Recurring failures: every model with at least [`MIN_PATTERN_FAILURES`]
+/// failed spans becomes one pattern, most frequent first
+pub fn extract_error_patterns(spans: &[GenAiSpan]) -> Vec<SpanPattern> {
+    let mut failures: BTreeMap<&str, Vec<&GenAiSpan>> = BTreeMap::new();
+    for span in spans.iter().filter(|s| s.error.is_some()) {
+        let model = span.model.as_deref().unwrap_or("unknown");
+        failures.entry(model).or_default().push(span);
+    }
+
+    let mut patterns: Vec<SpanPattern> = failures
+        .into_iter()
+        .filter(|(_, failed)| failed.len() >= MIN_PATTERN_FAILURES)
+        .map(|(model, failed)| {
+            let mut samples: Vec<&str> = Vec::new();
+            for error in failed.iter().filter_map(|s| s.error.as_deref()) {
+                if !samples.contains(&error) && samples.len() < 3 {
+                    samples.push(error);
+                }
+            }
+            let mut content = format!(
+                "# Recurring GenAI failure: {}\n\n{} spans calling `{}` failed.\n\n## Sample errors\n",
+                model,
+                failed.len(),
+                model
+            );
+            for error in &samples {
+                content.push_str(&format!("- {}\n", error));
+            }
+            SpanPattern {
+                key: format!("error:{}", model),
+                description: format!("{} failed spans calling {}", failed.len(), model),
+                occurrences: failed.len(),
+                source_span_ids: failed.iter().take(5).map(|s| s.span_id.clone()).collect(),
+                suggested_content: content,
+                suggested_tags: vec![
+                    OTEL_TAG.to_string(),
+                    "pattern".to_string(),
+                    "error".to_string(),
+                    format!("model:{}", model),
+                ],
+            }
+        })
+        .collect();
+    patterns.sort_by_key(|p| std::cmp::Reverse(p.occurrences));
+    patterns
+}
+
+/// Metadata stored on the memory of a span
+pub fn span_metadata(span: &GenAiSpan) -> HashMap<String, Value> {
+    let mut metadata = HashMap::from([
+        (
+            OTEL_TRACE_ID_KEY.to_string(),
+            Value::from(span.trace_id.clone()),
+        ),
+        (
+            OTEL_SPAN_ID_KEY.to_string(),
+            Value::from(span.span_id.clone()),
+        ),
+    ]);
+    let optional = [
+        ("otel_service", span.service.clone().map(Value::from)),
+        ("gen_ai_operation", span.operation.clone().map(Value::from)),
+        ("gen_ai_provider", span.provider.clone().map(Value::from)),
+        ("gen_ai_model", span.model.clone().map(Value::from)),
+        ("gen_ai_input_tokens", span.input_tokens.map(Value::from)),
+        ("gen_ai_output_tokens", span.output_tokens.map(Value::from)),
+        ("duration_ms", span.duration_ms.map(Value::from)),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            metadata.insert(key.to_string(), value);
+        }
+    }
+    metadata
+}
+
+/// Attributes by key, as plain JSON
+fn attribute_map(attributes: &[KeyValue]) -> BTreeMap<&str, Value> {
+    attributes
+        .iter()
+        .map(|kv| (kv.key.as_str(), kv.value.to_json()))
+        .collect()
+}
+
+fn as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn nanos(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .filter(|n| *n > 0)
+}
+
+fn to_datetime(nanos: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(
+        (nanos / 1_000_000_000) as i64,
+        (nanos % 1_000_000_000) as u32,
+    )
+}
+
+/// Non-empty text of a value: strings as they are, anything else as JSON
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// `role: content` lines from indexed attributes such as
+/// `gen_ai.prompt.0.role` / `gen_ai.prompt.0.content`
+fn indexed_messages(
+    attrs: &BTreeMap<&str, Value>,
+    prefix: &str,
+    role_suffix: &str,
+    content_suffix: &str,
+) -> Option<String> {
+    let mut messages: BTreeMap<usize, (Option<String>, Option<String>)> = BTreeMap::new();
+    for (key, value) in attrs {
+        let Some(rest) = key.strip_prefix(prefix) else {
+            continue;
+        };
+        let Some((index, field)) = rest.split_once('.') else {
+            continue;
+        };
+        let Ok(index) = index.parse::<usize>() else {
+            continue;
+        };
+        let field = format!(".{}", field);
+        let entry = messages.entry(index).or_default();
+        if field == role_suffix {
+            entry.0 = value_text(value);
+        } else if field == content_suffix {
+            entry.1 = value_text(value);
+        }
+    }
+    join_messages(messages.into_values())
+}
+
+/// Text of a `gen_ai.input.messages` / `gen_ai.output.messages` value: a
+/// JSON array (or a string holding one) of `{role, content}` or
+/// `{role, parts: [{type: "text", content}]}` messages
+fn render_messages(value: &Value) -> Option<String> {
+    let parsed;
+    let value = match value {
+        Value::String(s) => match serde_json::from_str::<Value>(s) {
+            Ok(v) if v.is_array() => {
+                parsed = v;
+                &parsed
+            }
+            _ => return value_text(value),
+        },
+        other => other,
+    };
+    let Some(messages) = value.as_array() else {
+        return value_text(value);
+    };
+    join_messages(messages.iter().map(|message| {
+        let role = message.get("role").and_then(value_text);
+        (role, message_text(message))
+    }))
+}
+
+/// Text content of one message object
+fn message_text(message: &Value) -> Option<String> {
+    if let Some(content) = message.get("content").and_then(value_text) {
+        return Some(content);
+    }
+    let parts: Vec<String> = message
+        .get("parts")?
+        .as_array()?
+        .iter()
+        .filter_map(|part| part.get("content").and_then(value_text))
+        .collect();
+    Some(parts.join("\n")).filter(|t| !t.is_empty())
+}
+
+/// Prompt (or, with `completion`, completion) text carried in span events
+fn event_messages(events: &[SpanEvent], completion: bool) -> Option<String> {
+    let messages = events.iter().filter_map(|event| {
+        let attrs = attribute_map(&event.attributes);
+        let text = |key: &str| attrs.get(key).and_then(value_text);
+        match (event.name.as_str(), completion) {
+            ("gen_ai.content.prompt", false) => Some((None, text("gen_ai.prompt"))),
+            ("gen_ai.content.completion", true) => Some((None, text("gen_ai.completion"))),
+            ("gen_ai.choice", true) => {
+                let message = attrs.get("message");
+                let content = text("content").or_else(|| message.and_then(message_text));
+                Some((Some("assistant".to_string()), content))
+            }
+            (name, false) => {
+                let role = name.strip_prefix("gen_ai.")?.strip_suffix(".message")?;
+                let content = text("content")
+                    .or_else(|| attrs.get("message")?.get("content").and_then(value_text));
+                Some((Some(role.to_string()), content))
+            }
+            _ => None,
+        }
+    });
+    join_messages(messages)
+}
+
+fn join_messages(
+    messages: impl IntoIterator<Item = (Option<String>, Option<String>)>,
+) -> Option<String> {
+    let lines: Vec<String> = messages
+        .into_iter()
+        .filter_map(|(role, content)| {
+            let content = content?;
+            Some(match role {
+                Some(role) => format!("{}: {}", role, content),
+                None => content,
+            })
+        })
+        .collect();
+    Some(lines.join("\n\n")).filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(spans: Value) -> ExportTraceServiceRequest {
+        serde_json::from_value(json!({
+            "resourceSpans": [{
+                "resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": "chatbot"}}
+                ]},
+                "scopeSpans": [{"scope": {"name": "test"}, "spans": spans}]
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn attr(key: &str, value: Value) -> Value {
+        json!({"key": key, "value": value})
+    }
+
+    #[test]
+    fn test_genai_spans_reads_semantic_conventions() {
+        let req = request(json!([
+            {
+                "traceId": "5b8efff798038103d269b633813fc60c",
+                "spanId": "eee19b7ec3c1b174",
+                "name": "chat gpt-4o",
+                "startTimeUnixNano": "1700000000000000000",
+                "endTimeUnixNano": "1700000001500000000",
+                "attributes": [
+                    attr("gen_ai.operation.name", json!({"stringValue": "chat"})),
+                    attr("gen_ai.system", json!({"stringValue": "openai"})),
+                    attr("gen_ai.request.model", json!({"stringValue": "gpt-4o"})),
+                    attr("gen_ai.usage.input_tokens", json!({"intValue": "12"})),
+                    attr("gen_ai.usage.output_tokens", json!({"intValue": 30})),
+                    attr("gen_ai.prompt.0.role", json!({"stringValue": "user"})),
+                    attr("gen_ai.prompt.0.content", json!({"stringValue": "Hello?"}))
+                ],
+                "events": [{
+                    "name": "gen_ai.choice",
+                    "attributes": [attr("message", json!({"kvlistValue": {"values": [
+                        attr("content", json!({"stringValue": "Hi there"}))
+                    ]}}))]
+                }]
+            },
+            {"traceId": "5b8e", "spanId": "aaaa", "name": "GET /health", "attributes": [
+                attr("http.method", json!({"stringValue": "GET"}))
+            ]}
+        ]));
+        assert_eq!(req.span_count(), 2);
+
+        let spans = genai_spans(&req);
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.service.as_deref(), Some("chatbot"));
+        assert_eq!(span.operation.as_deref(), Some("chat"));
+        assert_eq!(span.provider.as_deref(), Some("openai"));
+        assert_eq!(span.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(span.input_tokens, Some(12));
+        assert_eq!(span.output_tokens, Some(30));
+        assert_eq!(span.input.as_deref(), Some("user: Hello?"));
+        assert_eq!(span.output.as_deref(), Some("assistant: Hi there"));
+        assert_eq!(span.duration_ms, Some(1500.0));
+        assert_eq!(span.start_time.unwrap().timestamp(), 1_700_000_000);
+        assert!(span.error.is_none());
+
+        let content = span_to_memory_content(span);
+        assert!(content.starts_with("# chat gpt-4o\n"));
+        assert!(content.contains("**Span ID:** eee19b7ec3c1b174"));
+        assert!(content.contains("**Model:** gpt-4o (openai)"));
+        assert!(content.contains("**Tokens:** 12 in / 30 out"));
+        assert!(content.contains("## Input\nuser: Hello?"));
+    }
+
+    #[test]
+    fn test_genai_spans_reads_messages_and_openinference() {
+        let req = request(json!([
+            {"traceId": "t1", "spanId": "s1", "name": "chat", "attributes": [
+                attr("gen_ai.input.messages", json!({"stringValue":
+                    r#"[{"role":"user","parts":[{"type":"text","content":"Weather?"}]}]"#})),
+                attr("gen_ai.output.messages", json!({"stringValue":
+                    r#"[{"role":"assistant","parts":[{"type":"text","content":"Sunny"}]}]"#}))
+            ]},
+            {"traceId": "t2", "spanId": "s2", "name": "llm", "attributes": [
+                attr("openinference.span.kind", json!({"stringValue": "LLM"})),
+                attr("llm.model_name", json!({"stringValue": "llama-3"})),
+                attr("input.value", json!({"stringValue": "Summarize"})),
+                attr("output.value", json!({"stringValue": "Done"}))
+            ]}
+        ]));
+        let spans = genai_spans(&req);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].input.as_deref(), Some("user: Weather?"));
+        assert_eq!(spans[0].output.as_deref(), Some("assistant: Sunny"));
+        assert_eq!(spans[1].model.as_deref(), Some("llama-3"));
+        assert_eq!(spans[1].input.as_deref(), Some("Summarize"));
+        assert_eq!(spans[1].output.as_deref(), Some("Done"));
+    }
+
+    #[test]
+    fn test_extract_error_patterns_groups_by_model() {
+        let failed = |id: &str, model: &str, error: &str| GenAiSpan {
+            span_id: id.to_string(),
+            model: Some(model.to_string()),
+            error: Some(error.to_string()),
+            ..Default::default()
+        };
+        let spans = vec![
+            failed("1", "gpt-4o", "rate limited"),
+            failed("2", "gpt-4o", "rate limited"),
+            failed("3", "gpt-4o", "context length exceeded"),
+            failed("4", "llama-3", "timeout"),
+            GenAiSpan {
+                span_id: "5".to_string(),
+                model: Some("gpt-4o".to_string()),
+                ..Default::default()
+            },
+        ];
+        let patterns = extract_error_patterns(&spans);
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].key, "error:gpt-4o");
+        assert_eq!(patterns[0].occurrences, 3);
+        assert_eq!(patterns[0].source_span_ids, vec!["1", "2", "3"]);
+        assert!(patterns[0]
+            .suggested_content
+            .contains("- rate limited\n- context length"));
+    }
+
+    #[test]
+    fn test_failed_span_status() {
+        let req = request(json!([
+            {"traceId": "t", "spanId": "s", "name": "chat", "status": {"code": 2},
+             "attributes": [
+                attr("gen_ai.request.model", json!({"stringValue": "gpt-4o"})),
+                attr("error.type", json!({"stringValue": "RateLimitError"}))
+            ]},
+            {"traceId": "t", "spanId": "s2", "name": "chat",
+             "status": {"code": "STATUS_CODE_ERROR", "message": "boom"},
+             "attributes": [attr("gen_ai.request.model", json!({"stringValue": "gpt-4o"}))]}
+        ]));
+        let spans = genai_spans(&req);
+        assert_eq!(spans[0].error.as_deref(), Some("RateLimitError"));
+        assert_eq!(spans[1].error.as_deref(), Some("boom"));
+        assert!(span_to_memory_content(&spans[1]).contains("**Error:** boom"));
+    }
+}
@@ -3,8 +3,12 @@
 //! Provides token counting and context budget management for LLM interactions.
 //! Uses tiktoken-rs for accurate token counting with explicit error handling.
 
+use std::sync::OnceLock;
+
 use crate::error::{EngramError, Result};
+use crate::intelligence::context_builder::TokenCounter;
 use serde::{Deserialize, Serialize};
+use tiktoken_rs::CoreBPE;
 
 /// Compression strategy for memory content
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -82,54 +86,136 @@ pub fn parse_encoding(encoding: &str) -> Option<TokenEncoding> {
     }
 }
 
-/// Count tokens in text using the specified model or encoding.
-///
-/// # Arguments
-/// * `text` - The text to count tokens for
-/// * `model` - Model name (e.g., "gpt-4", "gpt-4o", "claude-3-opus")
-/// * `encoding` - Optional encoding override (e.g., "cl100k_base", "o200k_base")
-///
-/// # Returns
-/// * `Ok(usize)` - Number of tokens
-/// * `Err` - If model is unknown AND no encoding provided
-///
-/// # Errors
-/// This function will NOT silently fall back to chars/4. If the model is unknown
-/// and no encoding is provided, it returns an error with a helpful message.
-pub fn count_tokens(text: &str, model: &str, encoding: Option<&str>) -> Result<usize> {
-    // First try explicit encoding override
-    let token_encoding = if let Some(enc) = encoding {
+/// Resolve the encoding for a model, or the explicit `encoding` override
+fn resolve_encoding(model: &str, encoding: Option<&str>) -> Result<TokenEncoding> {
+    if let Some(enc) = encoding {
         parse_encoding(enc).ok_or_else(|| {
             EngramError::InvalidInput(format!(
                 "Unknown encoding '{}'. Supported: cl100k_base, o200k_base",
                 enc
             ))
-        })?
+        })
     } else {
-        // Try to detect from model name
         detect_encoding(model).ok_or_else(|| {
             EngramError::InvalidInput(format!(
                 "Unknown model '{}'. Provide 'encoding' parameter (cl100k_base or o200k_base) or use a known model (gpt-4, gpt-4o, claude-*, text-embedding-*).",
                 model
             ))
-        })?
-    };
+        })
+    }
+}
 
-    // Use tiktoken-rs to count tokens
-    let bpe = match token_encoding {
-        TokenEncoding::Cl100kBase => tiktoken_rs::cl100k_base(),
-        TokenEncoding::O200kBase => tiktoken_rs::o200k_base(),
+/// Shared tokenizer for an encoding, built on first use
+fn encoder(encoding: TokenEncoding) -> Result<&'static CoreBPE> {
+    static CL100K: OnceLock<CoreBPE> = OnceLock::new();
+    static O200K: OnceLock<CoreBPE> = OnceLock::new();
+
+    let (cell, init): (_, fn() -> anyhow::Result<CoreBPE>) = match encoding {
+        TokenEncoding::Cl100kBase => (&CL100K, tiktoken_rs::cl100k_base),
+        TokenEncoding::O200kBase => (&O200K, tiktoken_rs::o200k_base),
     };
+    if let Some(bpe) = cell.get() {
+        return Ok(bpe);
+    }
+    let bpe = init()
+        .map_err(|e| EngramError::Internal(format!("Failed to initialize tokenizer: {}", e)))?;
+    Ok(cell.get_or_init(|| bpe))
+}
 
-    match bpe {
-        Ok(encoder) => Ok(encoder.encode_with_special_tokens(text).len()),
-        Err(e) => Err(EngramError::Internal(format!(
-            "Failed to initialize tokenizer: {}",
-            e
-        ))),
+/// Exact token counter for one encoding.
+///
+/// The tokenizer is loaded once per process and shared, so counters are
+/// cheap to create and copy, unlike [`count_tokens`] calls in a loop.
+#[derive(Clone, Copy)]
+pub struct TiktokenCounter {
+    encoding: TokenEncoding,
+    bpe: &'static CoreBPE,
+}
+
+impl TiktokenCounter {
+    pub fn new(encoding: TokenEncoding) -> Result<Self> {
+        Ok(Self {
+            encoding,
+            bpe: encoder(encoding)?,
+        })
+    }
+
+    /// Counter for a model name or explicit encoding, resolved like
+    /// [`count_tokens`]
+    pub fn for_model(model: &str, encoding: Option<&str>) -> Result<Self> {
+        Self::new(resolve_encoding(model, encoding)?)
+    }
+
+    pub fn encoding(&self) -> TokenEncoding {
+        self.encoding
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+
+    /// Split `text` into consecutive pieces of at most `max_tokens` tokens.
+    ///
+    /// Pieces are cut at token boundaries, moved back to the nearest
+    /// character boundary where a token ends inside a multi-byte character.
+    pub fn split<'a>(&self, text: &'a str, max_tokens: usize) -> Vec<&'a str> {
+        let tokens = self.bpe.encode_with_special_tokens(text);
+        if tokens.len() <= max_tokens.max(1) {
+            return vec![text];
+        }
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        let mut end = 0;
+        for window in tokens.chunks(max_tokens.max(1)) {
+            end += self.bpe._decode_native(window).len();
+            let mut cut = end.min(text.len());
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            if cut > start {
+                pieces.push(&text[start..cut]);
+                start = cut;
+            }
+        }
+        if start < text.len() {
+            pieces.push(&text[start..]);
+        }
+        pieces
+    }
+}
+
+impl std::fmt::Debug for TiktokenCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TiktokenCounter")
+            .field("encoding", &self.encoding)
+            .finish()
+    }
+}
+
+impl TokenCounter for TiktokenCounter {
+    fn count_tokens(&self, text: &str) -> usize {
+        self.count(text)
     }
 }
 
+/// Count tokens in text using the specified model or encoding.
+///
+/// # Arguments
+/// * `text` - The text to count tokens for
+/// * `model` - Model name (e.g., "gpt-4", "gpt-4o", "claude-3-opus")
+/// * `encoding` - Optional encoding override (e.g., "cl100k_base", "o200k_base")
+///
+/// # Returns
+/// * `Ok(usize)` - Number of tokens
+/// * `Err` - If model is unknown AND no encoding provided
+///
+/// # Errors
+/// This function will NOT silently fall back to chars/4. If the model is unknown
+/// and no encoding is provided, it returns an error with a helpful message.
+pub fn count_tokens(text: &str, model: &str, encoding: Option<&str>) -> Result<usize> {
+    Ok(TiktokenCounter::for_model(model, encoding)?.count(text))
+}
+
 /// Input for context budget checking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextBudgetInput {
@@ -335,6 +421,21 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_split_by_tokens() {
+        let counter = TiktokenCounter::new(TokenEncoding::Cl100kBase).unwrap();
+        let text = "Ünïcödé text with emoji 🚀 and plenty of words. ".repeat(20);
+        let pieces = counter.split(&text, 16);
+        assert!(pieces.len() > 1);
+        assert_eq!(pieces.concat(), text);
+        assert!(
+            pieces.iter().all(|p| counter.count(p) <= 18),
+            "{:?}",
+            pieces
+        );
+        assert_eq!(counter.split("short", 16), vec!["short"]);
+    }
+
     #[test]
     fn test_context_budget_under() {
         let contents = vec![
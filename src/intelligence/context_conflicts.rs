@@ -0,0 +1,399 @@
+//! Conflict warnings for assembled context
+//!
+//! Before memories are handed to an agent as prompt context, checks whether
+//! any of them are known to disagree, with each other or with a pinned
+//! decision:
+//! - unresolved `contradiction` and `staleness` conflicts recorded by the
+//!   conflict detector (`memory_conflicts`)
+//! - active `contradicts` links between the memories
+//! - selected memories that were superseded
+//!
+//! Each warning names the memory to trust when that is known: the one that
+//! supersedes the other, the pinned decision, or for staleness the newer one.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::context_quality::{ConflictSeverity, ConflictType};
+use crate::error::Result;
+use crate::storage::queries::peek_memory;
+use crate::storage::supersession::get_superseded;
+use crate::types::MemoryId;
+
+/// Characters of each memory quoted in warnings
+const PREVIEW_CHARS: usize = 80;
+
+/// Where a conflict is known from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictSource {
+    /// An unresolved `memory_conflicts` record
+    Detector,
+    /// A `contradicts` link
+    ContradictsLink,
+    /// The memory was superseded
+    Superseded,
+}
+
+/// Why `prefer` is the memory to trust
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreferReason {
+    /// The other memory was superseded
+    Supersedes,
+    /// It is a pinned decision
+    Pinned,
+    /// Staleness conflict: it is the more recently updated
+    Newer,
+}
+
+/// A conflict involving a memory selected for context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextConflict {
+    /// Selected memory
+    pub memory_id: MemoryId,
+    /// Memory it conflicts with; `None` for a superseded memory whose
+    /// replacement is unknown
+    pub conflicts_with: Option<MemoryId>,
+    /// Whether `conflicts_with` is a pinned decision
+    #[serde(default)]
+    pub with_pinned: bool,
+    pub source: ConflictSource,
+    pub conflict_type: ConflictType,
+    pub severity: ConflictSeverity,
+    pub description: Option<String>,
+    /// Memory to trust, when known
+    pub prefer: Option<MemoryId>,
+    pub prefer_reason: Option<PreferReason>,
+    pub memory_preview: String,
+    pub conflicts_with_preview: Option<String>,
+}
+
+fn preview(conn: &Connection, id: MemoryId) -> Option<String> {
+    let memory = peek_memory(conn, id).ok()?;
+    let mut text: String = memory.content.chars().take(PREVIEW_CHARS).collect();
+    if memory.content.chars().count() > PREVIEW_CHARS {
+        text.push('…');
+    }
+    Some(text.replace('\n', " "))
+}
+
+fn raise(severity: ConflictSeverity, floor: ConflictSeverity) -> ConflictSeverity {
+    let rank = |s: ConflictSeverity| match s {
+        ConflictSeverity::Low => 0,
+        ConflictSeverity::Medium => 1,
+        ConflictSeverity::High => 2,
+        ConflictSeverity::Critical => 3,
+    };
+    if rank(severity) >= rank(floor) {
+        severity
+    } else {
+        floor
+    }
+}
+
+/// Known conflicts among `selected` memories, and between them and `pinned`
+/// ones. A pair is reported once, detector records first.
+pub fn find_context_conflicts(
+    conn: &Connection,
+    selected: &[MemoryId],
+    pinned: &[MemoryId],
+) -> Result<Vec<ContextConflict>> {
+    let selected_set: HashSet<MemoryId> = selected.iter().copied().collect();
+    let pinned_set: HashSet<MemoryId> = pinned.iter().copied().collect();
+    // (selected memory, other memory, source, type, severity, description)
+    let mut pairs: Vec<(
+        MemoryId,
+        MemoryId,
+        ConflictSource,
+        ConflictType,
+        ConflictSeverity,
+        Option<String>,
+    )> = Vec::new();
+    let mut seen: HashSet<(MemoryId, MemoryId)> = HashSet::new();
+    let mut add_pair = |a: MemoryId, b: MemoryId| -> Option<(MemoryId, MemoryId)> {
+        let (first, second) = match (selected_set.contains(&a), selected_set.contains(&b)) {
+            // A pinned side goes second
+            (true, true) if pinned_set.contains(&a) && !pinned_set.contains(&b) => (b, a),
+            (true, true) if pinned_set.contains(&b) && !pinned_set.contains(&a) => (a, b),
+            (true, true) => (a.min(b), a.max(b)),
+            (true, false) if pinned_set.contains(&b) => (a, b),
+            (false, true) if pinned_set.contains(&a) => (b, a),
+            _ => return None,
+        };
+        seen.insert((first.min(second), first.max(second)))
+            .then_some((first, second))
+    };
+
+    let involved: Vec<MemoryId> = selected_set.union(&pinned_set).copied().collect();
+    if involved.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ids_json = serde_json::to_string(&involved)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT memory_a_id, memory_b_id, conflict_type, severity, description
+         FROM memory_conflicts
+         WHERE resolved_at IS NULL
+           AND conflict_type IN ('contradiction', 'staleness')
+           AND memory_a_id IN (SELECT value FROM json_each(?1))
+           AND memory_b_id IN (SELECT value FROM json_each(?1))
+         ORDER BY id",
+    )?;
+    let detected = stmt
+        .query_map(params![ids_json], |row| {
+            Ok((
+                row.get::<_, MemoryId>(0)?,
+                row.get::<_, MemoryId>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (a, b, conflict_type, severity, description) in detected {
+        if let Some((first, second)) = add_pair(a, b) {
+            pairs.push((
+                first,
+                second,
+                ConflictSource::Detector,
+                conflict_type.parse().unwrap_or(ConflictType::Contradiction),
+                severity.parse().unwrap_or(ConflictSeverity::Medium),
+                description,
+            ));
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT from_id, to_id, source_context FROM crossrefs
+         WHERE edge_type = 'contradicts' AND valid_to IS NULL
+           AND from_id IN (SELECT value FROM json_each(?1))
+           AND to_id IN (SELECT value FROM json_each(?1))
+         ORDER BY from_id, to_id",
+    )?;
+    let links = stmt
+        .query_map(params![ids_json], |row| {
+            Ok((
+                row.get::<_, MemoryId>(0)?,
+                row.get::<_, MemoryId>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (a, b, context) in links {
+        if let Some((first, second)) = add_pair(a, b) {
+            pairs.push((
+                first,
+                second,
+                ConflictSource::ContradictsLink,
+                ConflictType::Contradiction,
+                ConflictSeverity::High,
+                context,
+            ));
+        }
+    }
+
+    let mut superseded: HashMap<MemoryId, Option<MemoryId>> = HashMap::new();
+    for id in &involved {
+        if let Some(record) = get_superseded(conn, *id)? {
+            superseded.insert(*id, record.superseded_by);
+        }
+    }
+    let updated_at = |id: MemoryId| peek_memory(conn, id).ok().map(|m| m.updated_at);
+
+    let mut conflicts = Vec::new();
+    for (memory_id, other, source, conflict_type, severity, description) in pairs {
+        let with_pinned = pinned_set.contains(&other) && !pinned_set.contains(&memory_id);
+        let (prefer, prefer_reason) =
+            if superseded.contains_key(&memory_id) && !superseded.contains_key(&other) {
+                (Some(other), Some(PreferReason::Supersedes))
+            } else if superseded.contains_key(&other) && !superseded.contains_key(&memory_id) {
+                (Some(memory_id), Some(PreferReason::Supersedes))
+            } else if with_pinned {
+                (Some(other), Some(PreferReason::Pinned))
+            } else if conflict_type == ConflictType::Staleness {
+                match (updated_at(memory_id), updated_at(other)) {
+                    (Some(a), Some(b)) if a >= b => (Some(memory_id), Some(PreferReason::Newer)),
+                    (Some(_), Some(_)) => (Some(other), Some(PreferReason::Newer)),
+                    _ => (None, None),
+                }
+            } else {
+                (None, None)
+            };
+        let severity = if with_pinned {
+            raise(severity, ConflictSeverity::High)
+        } else {
+            severity
+        };
+        conflicts.push(ContextConflict {
+            memory_id,
+            conflicts_with: Some(other),
+            with_pinned,
+            source,
+            conflict_type,
+            severity,
+            description,
+            prefer,
+            prefer_reason,
+            memory_preview: preview(conn, memory_id).unwrap_or_default(),
+            conflicts_with_preview: preview(conn, other),
+        });
+    }
+
+    // Superseded memories that made it into the selection anyway
+    for id in selected {
+        let Some(superseded_by) = superseded.get(id) else {
+            continue;
+        };
+        if superseded_by.is_some_and(|by| {
+            conflicts.iter().any(|c| {
+                c.prefer == Some(by) && (c.memory_id == *id || c.conflicts_with == Some(*id))
+            })
+        }) {
+            continue;
+        }
+        conflicts.push(ContextConflict {
+            memory_id: *id,
+            conflicts_with: *superseded_by,
+            with_pinned: superseded_by.is_some_and(|by| pinned_set.contains(&by)),
+            source: ConflictSource::Superseded,
+            conflict_type: ConflictType::Staleness,
+            severity: ConflictSeverity::Medium,
+            description: Some("memory was superseded".to_string()),
+            prefer: *superseded_by,
+            prefer_reason: superseded_by.map(|_| PreferReason::Supersedes),
+            memory_preview: preview(conn, *id).unwrap_or_default(),
+            conflicts_with_preview: superseded_by.and_then(|by| preview(conn, by)),
+        });
+    }
+
+    Ok(conflicts)
+}
+
+/// Plain-text warnings to append to a prompt, one line per conflict
+pub fn render_conflict_warnings(conflicts: &[ContextConflict]) -> String {
+    if conflicts.is_empty() {
+        return String::new();
+    }
+    let mut text = String::from("## Conflicting memories\n");
+    for conflict in conflicts {
+        let line = match (&conflict.conflicts_with_preview, conflict.source) {
+            (None, _) => format!(
+                "- \"{}\" is superseded; treat it as outdated.",
+                conflict.memory_preview
+            ),
+            (Some(other), ConflictSource::Superseded) => format!(
+                "- \"{}\" was superseded by \"{}\".",
+                conflict.memory_preview, other
+            ),
+            (Some(other), _) => {
+                let verb = match conflict.conflict_type {
+                    ConflictType::Staleness => "may be outdated by",
+                    _ => "contradicts",
+                };
+                let pinned = if conflict.with_pinned {
+                    "the pinned decision "
+                } else {
+                    ""
+                };
+                let advice = match conflict.prefer {
+                    Some(id) if id == conflict.memory_id => {
+                        format!(" Prefer \"{}\".", conflict.memory_preview)
+                    }
+                    Some(_) => format!(" Prefer \"{}\".", other),
+                    None => " Verify before relying on either.".to_string(),
+                };
+                format!(
+                    "- \"{}\" {} {}\"{}\".{}",
+                    conflict.memory_preview, verb, pinned, other, advice
+                )
+            }
+        };
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_crossref, create_memory};
+    use crate::storage::supersession::mark_superseded;
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+
+    #[test]
+    fn test_context_conflicts_and_warnings() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let mut ids = Vec::new();
+                for content in [
+                    "The API rate limit is 100 requests per minute",
+                    "The API rate limit is 500 requests per minute",
+                    "Decision: we deploy on Tuesdays",
+                    "We deploy on Fridays",
+                    "Old deploy checklist",
+                    "New deploy checklist",
+                ] {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            ..Default::default()
+                        },
+                    )?;
+                    ids.push(memory.id);
+                }
+                let link = |from, to| CreateCrossRefInput {
+                    from_id: from,
+                    to_id: to,
+                    edge_type: EdgeType::Contradicts,
+                    strength: None,
+                    source_context: None,
+                    pinned: false,
+                };
+                create_crossref(conn, &link(ids[0], ids[1]))?;
+                create_crossref(conn, &link(ids[3], ids[2]))?;
+                mark_superseded(conn, ids[4], Some(ids[5]), None)?;
+
+                // The pinned decision (ids[2]) is not selected itself
+                let selected = [ids[0], ids[1], ids[3], ids[4]];
+                let conflicts = find_context_conflicts(conn, &selected, &[ids[2]])?;
+                assert_eq!(conflicts.len(), 3);
+
+                let rate = &conflicts[0];
+                assert_eq!(rate.source, ConflictSource::ContradictsLink);
+                assert_eq!(rate.prefer, None);
+
+                let deploy = conflicts
+                    .iter()
+                    .find(|c| c.memory_id == ids[3])
+                    .unwrap();
+                assert!(deploy.with_pinned);
+                assert_eq!(deploy.prefer, Some(ids[2]));
+                assert_eq!(deploy.prefer_reason, Some(PreferReason::Pinned));
+                assert_eq!(deploy.severity, ConflictSeverity::High);
+
+                let old = conflicts
+                    .iter()
+                    .find(|c| c.memory_id == ids[4])
+                    .unwrap();
+                assert_eq!(old.source, ConflictSource::Superseded);
+                assert_eq!(old.prefer, Some(ids[5]));
+
+                let warnings = render_conflict_warnings(&conflicts);
+                assert!(warnings.contains(
+                    "\"We deploy on Fridays\" contradicts the pinned decision \
+                     \"Decision: we deploy on Tuesdays\". Prefer \"Decision: we deploy on Tuesdays\"."
+                ));
+                assert!(warnings.contains("Verify before relying on either."));
+                assert!(warnings.contains("was superseded by \"New deploy checklist\""));
+                Ok(())
+            })
+            .unwrap();
+    }
+}
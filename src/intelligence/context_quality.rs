@@ -16,6 +16,7 @@ use std::collections::{HashMap, HashSet};
 
 use crate::error::{EngramError, Result};
 use crate::storage::queries::get_memory;
+use crate::storage::supersession::mark_superseded;
 use crate::types::{Memory, MemoryId};
 
 // ============================================================================
@@ -134,26 +135,9 @@ impl ResolutionType {
     }
 }
 
-/// Validation status for memories
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ValidationStatus {
-    Unverified,
-    Verified,
-    Disputed,
-    Stale,
-}
-
-impl ValidationStatus {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            ValidationStatus::Unverified => "unverified",
-            ValidationStatus::Verified => "verified",
-            ValidationStatus::Disputed => "disputed",
-            ValidationStatus::Stale => "stale",
-        }
-    }
-}
+/// Validation status for memories (defined in `types` so search options can
+/// filter on it)
+pub use crate::types::ValidationStatus;
 
 // ============================================================================
 // Data Structures
@@ -339,7 +323,7 @@ pub fn find_near_duplicates(
     let mut stmt = conn.prepare(
         r#"
         SELECT id, content FROM memories
-        WHERE deleted_at IS NULL
+        WHERE valid_to IS NULL
         ORDER BY created_at DESC
         LIMIT ?
         "#,
@@ -379,7 +363,7 @@ pub fn find_near_duplicates(
                     )?;
 
                     duplicates.push(DuplicateCandidate {
-                        id: 0,
+                        id: conn.last_insert_rowid(),
                         memory_a_id: *id_a,
                         memory_b_id: *id_b,
                         similarity_score: similarity,
@@ -428,6 +412,84 @@ pub fn get_pending_duplicates(conn: &Connection, limit: i64) -> Result<Vec<Dupli
     Ok(duplicates)
 }
 
+/// A reviewer's verdict on a duplicate candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateVerdict {
+    /// The pair really is a duplicate
+    Confirmed,
+    /// The pair only looks alike
+    Rejected,
+}
+
+impl DuplicateVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateVerdict::Confirmed => "confirmed",
+            DuplicateVerdict::Rejected => "rejected",
+        }
+    }
+}
+
+impl std::str::FromStr for DuplicateVerdict {
+    type Err = EngramError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "confirmed" => Ok(DuplicateVerdict::Confirmed),
+            "rejected" => Ok(DuplicateVerdict::Rejected),
+            _ => Err(EngramError::InvalidInput(format!(
+                "Unknown duplicate verdict: {} (expected confirmed or rejected)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Record a reviewer's verdict on a duplicate candidate.
+///
+/// Resolved candidates leave the pending queue and become the labelled pairs
+/// dedup threshold calibration learns from.
+pub fn resolve_duplicate_candidate(
+    conn: &Connection,
+    candidate_id: i64,
+    verdict: DuplicateVerdict,
+) -> Result<DuplicateCandidate> {
+    let now = Utc::now().to_rfc3339();
+    let updated = conn.execute(
+        "UPDATE duplicate_candidates SET status = ?, resolved_at = ?, resolution_type = ?
+         WHERE id = ?",
+        params![verdict.as_str(), now, verdict.as_str(), candidate_id],
+    )?;
+    if updated == 0 {
+        return Err(EngramError::InvalidInput(format!(
+            "Duplicate candidate not found: {}",
+            candidate_id
+        )));
+    }
+
+    conn.query_row(
+        "SELECT id, memory_a_id, memory_b_id, similarity_score, similarity_type, detected_at, status
+         FROM duplicate_candidates WHERE id = ?",
+        params![candidate_id],
+        |row| {
+            Ok(DuplicateCandidate {
+                id: row.get(0)?,
+                memory_a_id: row.get(1)?,
+                memory_b_id: row.get(2)?,
+                similarity_score: row.get(3)?,
+                similarity_type: row.get(4)?,
+                detected_at: row
+                    .get::<_, String>(5)?
+                    .parse()
+                    .unwrap_or_else(|_| Utc::now()),
+                status: row.get(6)?,
+            })
+        },
+    )
+    .map_err(EngramError::from)
+}
+
 // ============================================================================
 // Semantic Deduplication (ENG-49)
 // ============================================================================
@@ -725,18 +787,20 @@ pub fn resolve_conflict(
 
     match resolution_type {
         ResolutionType::KeepA => {
-            // Archive memory B
+            // Archive memory B and mark it superseded by A
             conn.execute(
                 "UPDATE memories SET lifecycle_state = 'archived' WHERE id = ?",
                 params![memory_b_id],
             )?;
+            supersede_loser(conn, conflict_id, resolution_type, memory_b_id, memory_a_id)?;
         }
         ResolutionType::KeepB => {
-            // Archive memory A
+            // Archive memory A and mark it superseded by B
             conn.execute(
                 "UPDATE memories SET lifecycle_state = 'archived' WHERE id = ?",
                 params![memory_a_id],
             )?;
+            supersede_loser(conn, conflict_id, resolution_type, memory_a_id, memory_b_id)?;
         }
         ResolutionType::DeleteBoth => {
             let now = Utc::now().to_rfc3339();
@@ -753,6 +817,27 @@ pub fn resolve_conflict(
     Ok(())
 }
 
+/// Record the losing side of a resolved conflict as superseded by the winner,
+/// so it drops out of retrieval even if it is later un-archived.
+fn supersede_loser(
+    conn: &Connection,
+    conflict_id: i64,
+    resolution_type: ResolutionType,
+    loser: MemoryId,
+    winner: MemoryId,
+) -> Result<()> {
+    let reason = format!(
+        "conflict {} resolved as {}",
+        conflict_id,
+        resolution_type.as_str()
+    );
+    match mark_superseded(conn, loser, Some(winner), Some(&reason)) {
+        // Either side may already be deleted; nothing left to suppress.
+        Ok(_) | Err(EngramError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
 // ============================================================================
 // Enhanced Quality Scoring (ENG-52)
 // ============================================================================
@@ -1202,6 +1287,43 @@ mod tests {
         assert!(cosine_similarity(&a, &c).abs() < 0.001);
     }
 
+    #[test]
+    fn test_resolving_conflict_supersedes_loser() {
+        use crate::storage::queries::create_memory;
+        use crate::storage::supersession::get_superseded;
+        use crate::storage::Storage;
+        use crate::types::CreateMemoryInput;
+
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let mut ids = Vec::new();
+                for content in ["Deploys run on Fridays", "Deploys never run on Fridays"] {
+                    let input = CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    };
+                    ids.push(create_memory(conn, &input)?.id);
+                }
+                let conflict = create_conflict(
+                    conn,
+                    ids[0],
+                    ids[1],
+                    ConflictType::Contradiction,
+                    ConflictSeverity::High,
+                    None,
+                )?;
+
+                resolve_conflict(conn, conflict.id, ResolutionType::KeepB, None)?;
+
+                let record = get_superseded(conn, ids[0])?.expect("loser superseded");
+                assert_eq!(record.superseded_by, Some(ids[1]));
+                assert!(get_superseded(conn, ids[1])?.is_none());
+                Ok(())
+            })
+            .unwrap();
+    }
+
     #[test]
     fn test_conflict_type_parsing() {
         assert_eq!(
@@ -0,0 +1,346 @@
+//! Relevance and redundancy scoring for externally assembled context
+//!
+//! Agents that build prompts themselves still pick which memories (or raw
+//! snippets) go in. [`score_context`] rates each candidate against the task:
+//!
+//! - **relevance** — cosine similarity to the task embedding when both sides
+//!   have one, otherwise the share of task terms the candidate mentions
+//! - **quality** — the memory's quality score (snippets count as neutral)
+//! - **redundancy** — highest similarity to a candidate already kept
+//!
+//! Candidates are considered in utility order and kept while they are
+//! relevant, not redundant, and fit in the remaining token budget.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::embedding::cosine_similarity;
+use crate::intelligence::context_builder::TokenCounter;
+use crate::intelligence::context_quality::calculate_text_similarity;
+use crate::types::MemoryId;
+
+/// Quality assumed for raw snippets, which have no stored history to score
+pub const SNIPPET_QUALITY: f32 = 0.5;
+
+/// A memory or snippet offered for inclusion in a prompt
+#[derive(Debug, Clone)]
+pub struct ContextCandidate {
+    /// Set for stored memories, `None` for raw snippets
+    pub memory_id: Option<MemoryId>,
+    pub content: String,
+    /// Quality in 0.0–1.0; `None` uses [`SNIPPET_QUALITY`]
+    pub quality: Option<f32>,
+    pub embedding: Option<Vec<f32>>,
+}
+
+impl ContextCandidate {
+    pub fn snippet(content: impl Into<String>) -> Self {
+        Self {
+            memory_id: None,
+            content: content.into(),
+            quality: None,
+            embedding: None,
+        }
+    }
+}
+
+/// Weights and cut-offs for [`score_context`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextScoreConfig {
+    /// Token budget for kept items; `None` keeps everything that qualifies
+    pub budget: Option<usize>,
+    pub relevance_weight: f32,
+    pub quality_weight: f32,
+    /// Items at least this similar to an already kept item are dropped
+    pub redundancy_threshold: f32,
+    /// Items less relevant than this are dropped
+    pub min_relevance: f32,
+}
+
+impl Default for ContextScoreConfig {
+    fn default() -> Self {
+        Self {
+            budget: None,
+            relevance_weight: 0.8,
+            quality_weight: 0.2,
+            redundancy_threshold: 0.85,
+            min_relevance: 0.1,
+        }
+    }
+}
+
+/// Why an item was kept or dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextDecision {
+    Keep,
+    Irrelevant,
+    Redundant,
+    OverBudget,
+}
+
+impl ContextDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContextDecision::Keep => "keep",
+            ContextDecision::Irrelevant => "irrelevant",
+            ContextDecision::Redundant => "redundant",
+            ContextDecision::OverBudget => "over_budget",
+        }
+    }
+}
+
+/// Scores for one candidate, reported in input order
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoredContextItem {
+    /// Position in the input list
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<MemoryId>,
+    pub tokens: usize,
+    pub relevance: f32,
+    pub quality: f32,
+    /// Highest similarity to an item kept before this one was considered
+    pub redundancy: f32,
+    /// Index of the kept item it is most similar to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redundant_with: Option<usize>,
+    /// `relevance_weight * relevance + quality_weight * quality`,
+    /// discounted by redundancy
+    pub score: f32,
+    pub keep: bool,
+    pub decision: ContextDecision,
+    /// "embedding" or "lexical"
+    pub relevance_method: &'static str,
+}
+
+/// Result of [`score_context`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextScoreReport {
+    pub items: Vec<ScoredContextItem>,
+    /// Indices of kept items, best first
+    pub keep: Vec<usize>,
+    /// Indices of dropped items, in input order
+    pub drop: Vec<usize>,
+    pub tokens_kept: usize,
+    pub tokens_total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<usize>,
+}
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "are", "was", "were", "will",
+    "have", "has", "not", "but", "you", "your", "our", "how", "what", "when", "where", "which",
+    "who", "why", "can", "should", "would", "could", "about", "use", "using",
+];
+
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| w.len() > 2)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+fn comparable<'a>(a: Option<&'a [f32]>, b: Option<&'a [f32]>) -> Option<(&'a [f32], &'a [f32])> {
+    match (a, b) {
+        (Some(a), Some(b)) if a.len() == b.len() && !a.is_empty() => Some((a, b)),
+        _ => None,
+    }
+}
+
+/// Score candidates against a task and choose what to keep within the budget
+pub fn score_context(
+    task: &str,
+    task_embedding: Option<&[f32]>,
+    candidates: &[ContextCandidate],
+    counter: &dyn TokenCounter,
+    config: &ContextScoreConfig,
+) -> ContextScoreReport {
+    let task_terms = terms(task);
+
+    let mut items: Vec<ScoredContextItem> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let (relevance, method) =
+                match comparable(task_embedding, candidate.embedding.as_deref()) {
+                    Some((t, c)) => (cosine_similarity(t, c).clamp(0.0, 1.0), "embedding"),
+                    None => {
+                        let relevance = if task_terms.is_empty() {
+                            0.0
+                        } else {
+                            let item_terms = terms(&candidate.content);
+                            task_terms.intersection(&item_terms).count() as f32
+                                / task_terms.len() as f32
+                        };
+                        (relevance, "lexical")
+                    }
+                };
+            let quality = candidate.quality.unwrap_or(SNIPPET_QUALITY).clamp(0.0, 1.0);
+            ScoredContextItem {
+                index,
+                memory_id: candidate.memory_id,
+                tokens: counter.count_tokens(&candidate.content),
+                relevance,
+                quality,
+                redundancy: 0.0,
+                redundant_with: None,
+                score: config.relevance_weight * relevance + config.quality_weight * quality,
+                keep: false,
+                decision: ContextDecision::Irrelevant,
+                relevance_method: method,
+            }
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| {
+        items[b]
+            .score
+            .partial_cmp(&items[a].score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.cmp(&b))
+    });
+
+    let mut kept: Vec<usize> = Vec::new();
+    let mut tokens_kept = 0usize;
+
+    for i in order {
+        let (redundancy, redundant_with) = kept
+            .iter()
+            .map(|&k| {
+                let similarity = match comparable(
+                    candidates[i].embedding.as_deref(),
+                    candidates[k].embedding.as_deref(),
+                ) {
+                    Some((a, b)) => cosine_similarity(a, b).clamp(0.0, 1.0),
+                    None => {
+                        calculate_text_similarity(&candidates[i].content, &candidates[k].content)
+                    }
+                };
+                (similarity, k)
+            })
+            .fold((0.0f32, None), |best, (similarity, k)| {
+                if similarity > best.0 {
+                    (similarity, Some(k))
+                } else {
+                    best
+                }
+            });
+
+        let item = &mut items[i];
+        item.redundancy = redundancy;
+        item.redundant_with = redundant_with;
+        item.score *= 1.0 - redundancy;
+
+        item.decision = if item.relevance < config.min_relevance {
+            ContextDecision::Irrelevant
+        } else if redundancy >= config.redundancy_threshold {
+            ContextDecision::Redundant
+        } else if config
+            .budget
+            .is_some_and(|budget| tokens_kept + item.tokens > budget)
+        {
+            ContextDecision::OverBudget
+        } else {
+            ContextDecision::Keep
+        };
+
+        if item.decision == ContextDecision::Keep {
+            item.keep = true;
+            tokens_kept += item.tokens;
+            kept.push(i);
+        }
+    }
+
+    let drop = items
+        .iter()
+        .filter(|item| !item.keep)
+        .map(|item| item.index)
+        .collect();
+    let tokens_total = items.iter().map(|item| item.tokens).sum();
+
+    ContextScoreReport {
+        items,
+        keep: kept,
+        drop,
+        tokens_kept,
+        tokens_total,
+        budget: config.budget,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::context_builder::SimpleTokenCounter;
+
+    #[test]
+    fn test_lexical_relevance_and_redundancy() {
+        let candidates = vec![
+            ContextCandidate::snippet("The billing service retries failed invoices nightly"),
+            ContextCandidate::snippet("The billing service retries failed invoices nightly."),
+            ContextCandidate::snippet("Team lunch is on Fridays"),
+        ];
+        let report = score_context(
+            "Why do billing invoices get retried?",
+            None,
+            &candidates,
+            &SimpleTokenCounter,
+            &ContextScoreConfig::default(),
+        );
+
+        assert_eq!(report.keep, vec![0]);
+        assert_eq!(report.items[1].decision, ContextDecision::Redundant);
+        assert_eq!(report.items[1].redundant_with, Some(0));
+        assert_eq!(report.items[2].decision, ContextDecision::Irrelevant);
+        assert_eq!(report.items[0].relevance_method, "lexical");
+    }
+
+    #[test]
+    fn test_budget_skips_items_that_do_not_fit() {
+        let long = "deploy pipeline ".repeat(40);
+        let mut big = ContextCandidate::snippet(long);
+        big.quality = Some(1.0);
+        let candidates = vec![
+            big,
+            ContextCandidate::snippet("deploy pipeline runs on merge"),
+        ];
+        let config = ContextScoreConfig {
+            budget: Some(20),
+            ..Default::default()
+        };
+        let report = score_context(
+            "deploy pipeline",
+            None,
+            &candidates,
+            &SimpleTokenCounter,
+            &config,
+        );
+
+        assert_eq!(report.items[0].decision, ContextDecision::OverBudget);
+        assert_eq!(report.keep, vec![1]);
+        assert!(report.tokens_kept <= 20);
+    }
+
+    #[test]
+    fn test_embedding_relevance_preferred() {
+        let mut close = ContextCandidate::snippet("unrelated words");
+        close.embedding = Some(vec![1.0, 0.0]);
+        let mut far = ContextCandidate::snippet("unrelated words too");
+        far.embedding = Some(vec![0.0, 1.0]);
+        let report = score_context(
+            "task",
+            Some(&[1.0, 0.0]),
+            &[close, far],
+            &SimpleTokenCounter,
+            &ContextScoreConfig::default(),
+        );
+
+        assert_eq!(report.items[0].relevance_method, "embedding");
+        assert!(report.items[0].relevance > 0.99);
+        assert_eq!(report.keep, vec![0]);
+        assert_eq!(report.drop, vec![1]);
+    }
+}
@@ -0,0 +1,477 @@
+//! Dedup threshold calibration
+//!
+//! `dedup_threshold` on `memory_create` is a cosine similarity cut-off
+//! between embeddings, and picking it by hand is guesswork. Calibration
+//! derives one per workspace from two samples:
+//! - labelled pairs: duplicate candidates a reviewer confirmed or rejected
+//!   (see [`resolve_duplicate_candidate`](super::resolve_duplicate_candidate))
+//! - random pairs of memories in the workspace, which are nearly always
+//!   distinct and stand in for extra negatives
+//!
+//! With confirmed duplicates available, the recommendation is the cut-off
+//! with the best F1 score separating them from rejected and random pairs.
+//! Without any, it sits just above the tail of the random-pair distribution
+//! and is reported as [`CalibrationBasis::RandomPairsOnly`]. Recommendations
+//! can be written back as the workspace's default `dedup_threshold`.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::{cosine_similarity, get_embedding};
+use crate::error::{EngramError, Result};
+use crate::storage::queries::list_workspaces;
+use crate::storage::workspace_config::{get_workspace_config, set_workspace_dedup_threshold};
+use crate::types::{normalize_workspace, MemoryId};
+
+/// Lowest threshold calibration will ever recommend
+pub const MIN_RECOMMENDED_THRESHOLD: f32 = 0.5;
+
+/// Highest threshold calibration will ever recommend
+pub const MAX_RECOMMENDED_THRESHOLD: f32 = 0.99;
+
+/// Floor for recommendations made without confirmed duplicates, so an
+/// embedder whose random pairs all score low doesn't yield an eager threshold
+pub const UNLABELLED_THRESHOLD_FLOOR: f32 = 0.85;
+
+/// Options for [`calibrate_dedup_thresholds`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    /// Calibrate only this workspace (default: every workspace)
+    pub workspace: Option<String>,
+    /// Random pairs sampled per workspace
+    pub sample_pairs: usize,
+    /// Workspaces with fewer embedded memories get no recommendation
+    pub min_memories: usize,
+    /// Gap kept above the random-pair p99 when no duplicates are confirmed
+    pub margin: f32,
+    /// Seed for random pair sampling, so reruns see the same pairs
+    pub seed: u64,
+    /// Store recommendations as workspace `dedup_threshold` defaults
+    pub apply: bool,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            sample_pairs: 500,
+            min_memories: 10,
+            margin: 0.02,
+            seed: 42,
+            apply: false,
+        }
+    }
+}
+
+/// Summary of a sample of pair similarities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarityDistribution {
+    pub count: usize,
+    pub min: f32,
+    pub mean: f32,
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl SimilarityDistribution {
+    /// Summarize `values`, or `None` when there are none
+    pub fn from_values(values: &[f32]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f32| {
+            let rank = (p * (sorted.len() - 1) as f32).round() as usize;
+            sorted[rank.min(sorted.len() - 1)]
+        };
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// What a recommendation was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationBasis {
+    /// Confirmed duplicates against rejected and random pairs
+    Labelled,
+    /// Random pairs only; no duplicates confirmed in this workspace yet
+    RandomPairsOnly,
+    /// Too few embedded memories to say anything
+    InsufficientData,
+}
+
+/// Calibration result for one workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceCalibration {
+    pub workspace: String,
+    /// Memories with an embedding, the population random pairs come from
+    pub embedded_memories: usize,
+    pub confirmed: Option<SimilarityDistribution>,
+    pub rejected: Option<SimilarityDistribution>,
+    pub random: Option<SimilarityDistribution>,
+    pub basis: CalibrationBasis,
+    pub recommended_threshold: Option<f32>,
+    /// Share of pairs at or above the threshold that are confirmed duplicates
+    pub precision: Option<f32>,
+    /// Share of confirmed duplicates at or above the threshold
+    pub recall: Option<f32>,
+    /// The workspace's `dedup_threshold` default before this run
+    pub current_threshold: Option<f32>,
+    /// Whether the recommendation was stored as the workspace default
+    pub applied: bool,
+}
+
+/// Calibrate the semantic dedup threshold of one or every workspace
+pub fn calibrate_dedup_thresholds(
+    conn: &Connection,
+    config: &CalibrationConfig,
+) -> Result<Vec<WorkspaceCalibration>> {
+    let workspaces = match &config.workspace {
+        Some(ws) => vec![normalize_workspace(ws)
+            .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))?],
+        None => list_workspaces(conn)?
+            .into_iter()
+            .map(|stats| stats.workspace)
+            .collect(),
+    };
+
+    workspaces
+        .into_iter()
+        .map(|workspace| calibrate_workspace(conn, &workspace, config))
+        .collect()
+}
+
+fn calibrate_workspace(
+    conn: &Connection,
+    workspace: &str,
+    config: &CalibrationConfig,
+) -> Result<WorkspaceCalibration> {
+    let mut embeddings = EmbeddingCache::default();
+
+    let (confirmed, rejected) = labelled_similarities(conn, workspace, &mut embeddings)?;
+
+    let ids = embedded_memory_ids(conn, workspace)?;
+    let mut random = Vec::new();
+    if ids.len() >= 2 {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        for _ in 0..config.sample_pairs {
+            let a = ids[rng.gen_range(0..ids.len())];
+            let b = ids[rng.gen_range(0..ids.len())];
+            if a == b {
+                continue;
+            }
+            if let Some(similarity) = embeddings.similarity(conn, a, b)? {
+                random.push(similarity);
+            }
+        }
+    }
+
+    let current_threshold = get_workspace_config(conn, workspace)?.and_then(|c| c.dedup_threshold);
+    let mut calibration = WorkspaceCalibration {
+        workspace: workspace.to_string(),
+        embedded_memories: ids.len(),
+        confirmed: SimilarityDistribution::from_values(&confirmed),
+        rejected: SimilarityDistribution::from_values(&rejected),
+        random: SimilarityDistribution::from_values(&random),
+        basis: CalibrationBasis::InsufficientData,
+        recommended_threshold: None,
+        precision: None,
+        recall: None,
+        current_threshold,
+        applied: false,
+    };
+
+    if ids.len() < config.min_memories.max(2) {
+        return Ok(calibration);
+    }
+
+    let mut negatives = rejected;
+    negatives.extend_from_slice(&random);
+
+    if !confirmed.is_empty() {
+        let (threshold, precision, recall) = best_f1_threshold(&confirmed, &negatives);
+        calibration.basis = CalibrationBasis::Labelled;
+        calibration.recommended_threshold = Some(threshold);
+        calibration.precision = Some(precision);
+        calibration.recall = Some(recall);
+    } else if let Some(random) = &calibration.random {
+        let threshold = round_up(random.p99 + config.margin)
+            .clamp(UNLABELLED_THRESHOLD_FLOOR, MAX_RECOMMENDED_THRESHOLD);
+        calibration.basis = CalibrationBasis::RandomPairsOnly;
+        calibration.recommended_threshold = Some(threshold);
+    }
+
+    if config.apply {
+        if let Some(threshold) = calibration.recommended_threshold {
+            set_workspace_dedup_threshold(conn, workspace, Some(threshold))?;
+            calibration.applied = true;
+        }
+    }
+
+    Ok(calibration)
+}
+
+/// Pick the cut-off with the best F1 score, preferring the higher threshold
+/// on ties. Returns `(threshold, precision, recall)`.
+///
+/// Candidate cut-offs are the confirmed similarities themselves, rounded
+/// down to two decimals so the pair that set them still clears them.
+fn best_f1_threshold(positives: &[f32], negatives: &[f32]) -> (f32, f32, f32) {
+    let mut best = (MAX_RECOMMENDED_THRESHOLD, 0.0, 0.0);
+    let mut best_f1 = -1.0;
+
+    for &candidate in positives {
+        let threshold =
+            round_down(candidate).clamp(MIN_RECOMMENDED_THRESHOLD, MAX_RECOMMENDED_THRESHOLD);
+        let true_positives = positives.iter().filter(|&&s| s >= threshold).count() as f32;
+        let false_positives = negatives.iter().filter(|&&s| s >= threshold).count() as f32;
+        let precision = if true_positives + false_positives > 0.0 {
+            true_positives / (true_positives + false_positives)
+        } else {
+            0.0
+        };
+        let recall = true_positives / positives.len() as f32;
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+        if f1 > best_f1 || (f1 == best_f1 && threshold > best.0) {
+            best_f1 = f1;
+            best = (threshold, precision, recall);
+        }
+    }
+
+    best
+}
+
+fn round_down(value: f32) -> f32 {
+    (value * 100.0).floor() / 100.0
+}
+
+fn round_up(value: f32) -> f32 {
+    (value * 100.0).ceil() / 100.0
+}
+
+/// Similarities of confirmed and rejected duplicate candidates in a
+/// workspace. Memories merged away after confirmation still count.
+fn labelled_similarities(
+    conn: &Connection,
+    workspace: &str,
+    embeddings: &mut EmbeddingCache,
+) -> Result<(Vec<f32>, Vec<f32>)> {
+    let mut stmt = conn.prepare(
+        "SELECT dc.memory_a_id, dc.memory_b_id, dc.status
+         FROM duplicate_candidates dc
+         JOIN memories a ON a.id = dc.memory_a_id
+         JOIN memories b ON b.id = dc.memory_b_id
+         WHERE dc.status IN ('confirmed', 'rejected')
+           AND a.workspace = ?1 AND b.workspace = ?1",
+    )?;
+    let pairs: Vec<(MemoryId, MemoryId, String)> = stmt
+        .query_map(params![workspace], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut confirmed = Vec::new();
+    let mut rejected = Vec::new();
+    for (a, b, status) in pairs {
+        if let Some(similarity) = embeddings.similarity(conn, a, b)? {
+            if status == "confirmed" {
+                confirmed.push(similarity);
+            } else {
+                rejected.push(similarity);
+            }
+        }
+    }
+    Ok((confirmed, rejected))
+}
+
+fn embedded_memory_ids(conn: &Connection, workspace: &str) -> Result<Vec<MemoryId>> {
+    let mut stmt = conn.prepare(
+        "SELECT m.id FROM memories m
+         JOIN embeddings e ON e.memory_id = m.id
+         WHERE m.workspace = ? AND m.valid_to IS NULL
+         ORDER BY m.id",
+    )?;
+    let ids = stmt
+        .query_map(params![workspace], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<MemoryId>>>()?;
+    Ok(ids)
+}
+
+/// Embeddings loaded so far, so sampled pairs sharing a memory read it once
+#[derive(Default)]
+struct EmbeddingCache {
+    loaded: HashMap<MemoryId, Option<Vec<f32>>>,
+}
+
+impl EmbeddingCache {
+    fn get(&mut self, conn: &Connection, id: MemoryId) -> Result<Option<&Vec<f32>>> {
+        if let std::collections::hash_map::Entry::Vacant(entry) = self.loaded.entry(id) {
+            entry.insert(get_embedding(conn, id)?);
+        }
+        Ok(self.loaded[&id].as_ref())
+    }
+
+    fn similarity(&mut self, conn: &Connection, a: MemoryId, b: MemoryId) -> Result<Option<f32>> {
+        let Some(first) = self.get(conn, a)?.cloned() else {
+            return Ok(None);
+        };
+        Ok(self
+            .get(conn, b)?
+            .map(|second| cosine_similarity(&first, second)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::{resolve_duplicate_candidate, DuplicateVerdict};
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+
+    fn store_embedding(conn: &Connection, id: MemoryId, embedding: &[f32]) {
+        let bytes: Vec<u8> = embedding.iter().flat_map(|v| v.to_le_bytes()).collect();
+        conn.execute(
+            "INSERT INTO embeddings (memory_id, embedding, model, dimensions) VALUES (?, ?, 'test', ?)",
+            params![id, bytes, embedding.len()],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE memories SET has_embedding = 1 WHERE id = ?",
+            params![id],
+        )
+        .unwrap();
+    }
+
+    fn create(conn: &Connection, content: &str, embedding: &[f32]) -> MemoryId {
+        let memory = create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                workspace: Some("calib".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        store_embedding(conn, memory.id, embedding);
+        memory.id
+    }
+
+    fn candidate(conn: &Connection, a: MemoryId, b: MemoryId, verdict: DuplicateVerdict) {
+        conn.execute(
+            "INSERT INTO duplicate_candidates (memory_a_id, memory_b_id, similarity_score)
+             VALUES (?, ?, 0.9)",
+            params![a, b],
+        )
+        .unwrap();
+        resolve_duplicate_candidate(conn, conn.last_insert_rowid(), verdict).unwrap();
+    }
+
+    /// Unit vector at `angle` radians in the plane of the first two axes
+    fn at(angle: f32) -> Vec<f32> {
+        vec![angle.cos(), angle.sin(), 0.0]
+    }
+
+    #[test]
+    fn test_best_f1_prefers_separating_threshold() {
+        let (threshold, precision, recall) = best_f1_threshold(&[0.97, 0.95], &[0.2, 0.5, 0.93]);
+        assert_eq!(threshold, 0.95);
+        assert_eq!((precision, recall), (1.0, 1.0));
+    }
+
+    #[test]
+    fn test_labelled_calibration_and_apply() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                // Spread distinct memories around the circle
+                let mut ids = Vec::new();
+                for i in 0..12 {
+                    ids.push(create(conn, &format!("memory {}", i), &at(i as f32 * 0.5)));
+                }
+                // Near-identical pairs a reviewer confirmed
+                let dup_a = create(conn, "dup a", &at(0.02));
+                let dup_b = create(conn, "dup b", &at(1.02));
+                candidate(conn, ids[0], dup_a, DuplicateVerdict::Confirmed);
+                candidate(conn, ids[2], dup_b, DuplicateVerdict::Confirmed);
+                // Close, but not a duplicate
+                let near = create(conn, "near", &at(2.3));
+                candidate(conn, ids[4], near, DuplicateVerdict::Rejected);
+
+                let config = CalibrationConfig {
+                    workspace: Some("calib".to_string()),
+                    apply: true,
+                    ..Default::default()
+                };
+                let report = calibrate_dedup_thresholds(conn, &config)?;
+                assert_eq!(report.len(), 1);
+                let calibration = &report[0];
+                assert_eq!(calibration.basis, CalibrationBasis::Labelled);
+                assert_eq!(calibration.confirmed.as_ref().unwrap().count, 2);
+                assert_eq!(calibration.rejected.as_ref().unwrap().count, 1);
+
+                // cos(0.02) ≈ 0.9998 for duplicates, cos(0.3) ≈ 0.955 for the
+                // rejected pair: the cut-off lands between them
+                let threshold = calibration.recommended_threshold.unwrap();
+                assert!(threshold > 0.96 && threshold <= MAX_RECOMMENDED_THRESHOLD);
+                assert_eq!(calibration.recall, Some(1.0));
+                assert!(calibration.applied);
+
+                let stored = get_workspace_config(conn, "calib")?.unwrap();
+                assert_eq!(stored.dedup_threshold, Some(threshold));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_unlabelled_and_sparse_workspaces() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                for i in 0..3 {
+                    create(conn, &format!("memory {}", i), &at(i as f32 * 0.5));
+                }
+                let config = CalibrationConfig {
+                    workspace: Some("calib".to_string()),
+                    ..Default::default()
+                };
+                let sparse = &calibrate_dedup_thresholds(conn, &config)?[0];
+                assert_eq!(sparse.basis, CalibrationBasis::InsufficientData);
+                assert!(sparse.recommended_threshold.is_none());
+
+                for i in 3..12 {
+                    create(conn, &format!("memory {}", i), &at(i as f32 * 0.5));
+                }
+                let unlabelled = &calibrate_dedup_thresholds(conn, &config)?[0];
+                assert_eq!(unlabelled.basis, CalibrationBasis::RandomPairsOnly);
+                let threshold = unlabelled.recommended_threshold.unwrap();
+                assert!(threshold >= UNLABELLED_THRESHOLD_FLOOR);
+                assert!(threshold >= unlabelled.random.as_ref().unwrap().p99);
+                assert!(!unlabelled.applied);
+                assert!(get_workspace_config(conn, "calib")?.is_none());
+                Ok(())
+            })
+            .unwrap();
+    }
+}
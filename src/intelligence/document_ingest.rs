@@ -24,12 +24,13 @@ use std::path::Path;
 use std::time::Instant;
 
 use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
-use sha2::{Digest, Sha256};
 
 use crate::error::{EngramError, Result};
-use crate::storage::queries::{create_memory, list_memories};
+use crate::intelligence::importance::{auto_importance, ImportanceSignals, ImportanceSource};
+use crate::storage::bulk::{bulk_create_memories, BulkWriteOptions};
+use crate::storage::queries::list_memories;
 use crate::storage::Storage;
-use crate::types::{CreateMemoryInput, ListOptions, MemoryType};
+use crate::types::{ContentHash, CreateMemoryInput, ListOptions, MemoryType};
 
 /// Maximum file size in bytes (10 MB default)
 pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
@@ -237,20 +238,29 @@ impl<'a> DocumentIngestor<'a> {
         let source_path = path.to_string_lossy().to_string();
         let chunks = create_chunks(sections, &source_path, &doc_id, &config);
 
-        // Ingest chunks
+        // Ingest new chunks through the batched bulk-write path
         let existing_hashes = self.existing_chunk_hashes(&doc_id)?;
-        let mut chunks_created = 0;
-        let mut chunks_skipped = 0;
-
-        for chunk in &chunks {
-            if existing_hashes.contains(&chunk.chunk_hash) {
-                chunks_skipped += 1;
-                continue;
-            }
-
-            // Create memory for chunk
-            self.create_chunk_memory(chunk, &config.extra_tags)?;
-            chunks_created += 1;
+        let inputs: Vec<CreateMemoryInput> = chunks
+            .iter()
+            .filter(|chunk| !existing_hashes.contains(&chunk.chunk_hash))
+            .map(|chunk| chunk_memory_input(chunk, &config.extra_tags))
+            .collect();
+        let chunks_skipped = chunks.len() - inputs.len();
+
+        let written = bulk_create_memories(self.storage, &inputs, &BulkWriteOptions::default())?;
+        let chunks_created = written.ids.len();
+        if written.truncated {
+            warnings.push(format!(
+                "Stopped after {} of {} new chunks at the time budget; ingest again to resume",
+                chunks_created,
+                inputs.len()
+            ));
+        }
+        for failure in written.failed {
+            warnings.push(format!(
+                "Failed to store chunk {}: {}",
+                failure.index, failure.error
+            ));
         }
 
         let duration_ms = start.elapsed().as_millis() as u64;
@@ -290,6 +300,11 @@ impl<'a> DocumentIngestor<'a> {
                     metadata_filter: Some(filter),
                     filter: None,
                     include_archived: false,
+                    event_after: None,
+                    event_before: None,
+                    as_of: None,
+                    show_superseded: false,
+                    validation_status: None,
                 };
 
                 let results = list_memories(conn, &options)?;
@@ -309,79 +324,76 @@ impl<'a> DocumentIngestor<'a> {
             Ok(hashes)
         })
     }
+}
 
-    /// Create a memory entry for a chunk
-    fn create_chunk_memory(&self, chunk: &DocumentChunk, extra_tags: &[String]) -> Result<()> {
-        let mut tags = vec!["document-chunk".to_string()];
-        tags.extend(extra_tags.iter().cloned());
-
-        let mut metadata = HashMap::new();
-        metadata.insert(
-            "source_file".to_string(),
-            serde_json::Value::String(
-                Path::new(&chunk.source_path)
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default(),
-            ),
-        );
-        metadata.insert(
-            "source_path".to_string(),
-            serde_json::Value::String(chunk.source_path.clone()),
-        );
-        metadata.insert(
-            "doc_id".to_string(),
-            serde_json::Value::String(chunk.doc_id.clone()),
-        );
-        metadata.insert(
-            "chunk_index".to_string(),
-            serde_json::Value::Number(chunk.chunk_index.into()),
-        );
-        metadata.insert(
-            "section_path".to_string(),
-            serde_json::Value::String(chunk.section_path.clone()),
-        );
-        metadata.insert(
-            "chunk_hash".to_string(),
-            serde_json::Value::String(chunk.chunk_hash.clone()),
-        );
-
-        if let Some(page) = chunk.page {
-            metadata.insert("page".to_string(), serde_json::Value::Number(page.into()));
-        }
-
-        let input = CreateMemoryInput {
-            content: chunk.content.clone(),
-            memory_type: MemoryType::Context,
-            tags,
-            metadata,
-            importance: Some(0.5),
-            scope: crate::types::MemoryScope::Global,
-            workspace: None,
-            tier: crate::types::MemoryTier::Permanent,
-            defer_embedding: false,
-            ttl_seconds: None,
-            dedup_mode: Default::default(),
-            dedup_threshold: None,
-            event_time: None,
-            event_duration_seconds: None,
-            trigger_pattern: None,
-            summary_of_id: None,
-            media_url: None,
-        };
+/// Build the memory input for a chunk
+fn chunk_memory_input(chunk: &DocumentChunk, extra_tags: &[String]) -> CreateMemoryInput {
+    let mut tags = vec!["document-chunk".to_string()];
+    tags.extend(extra_tags.iter().cloned());
+
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "source_file".to_string(),
+        serde_json::Value::String(
+            Path::new(&chunk.source_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        ),
+    );
+    metadata.insert(
+        "source_path".to_string(),
+        serde_json::Value::String(chunk.source_path.clone()),
+    );
+    metadata.insert(
+        "doc_id".to_string(),
+        serde_json::Value::String(chunk.doc_id.clone()),
+    );
+    metadata.insert(
+        "chunk_index".to_string(),
+        serde_json::Value::Number(chunk.chunk_index.into()),
+    );
+    metadata.insert(
+        "section_path".to_string(),
+        serde_json::Value::String(chunk.section_path.clone()),
+    );
+    metadata.insert(
+        "chunk_hash".to_string(),
+        serde_json::Value::String(chunk.chunk_hash.clone()),
+    );
+
+    if let Some(page) = chunk.page {
+        metadata.insert("page".to_string(), serde_json::Value::Number(page.into()));
+    }
 
-        self.storage.with_connection(|conn| {
-            create_memory(conn, &input)?;
-            Ok(())
-        })
+    CreateMemoryInput {
+        content: chunk.content.clone(),
+        memory_type: MemoryType::Context,
+        tags,
+        metadata,
+        importance: Some(auto_importance(&ImportanceSignals::new(
+            ImportanceSource::DocumentIngest,
+            MemoryType::Context,
+            &chunk.content,
+        ))),
+        scope: crate::types::MemoryScope::Global,
+        workspace: None,
+        tier: crate::types::MemoryTier::Permanent,
+        defer_embedding: false,
+        ttl_seconds: None,
+        dedup_mode: Default::default(),
+        dedup_threshold: None,
+        event_time: None,
+        event_duration_seconds: None,
+        trigger_pattern: None,
+        summary_of_id: None,
+        media_url: None,
     }
 }
 
-/// Compute SHA-256 hash of content
+/// Exact hash of a document or chunk
 fn compute_hash(content: &[u8]) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content);
-    format!("sha256:{}", hex::encode(hasher.finalize()))
+    ContentHash::of_bytes(content).into_string()
 }
 
 /// Extract sections from Markdown content
@@ -0,0 +1,486 @@
+//! Near-duplicate clustering across the whole corpus
+//!
+//! Pairwise duplicate reports grow quadratically: ten copies of one note are
+//! 45 pairs. This pass groups them instead:
+//! 1. each live memory gets a MinHash signature over word shingles of its
+//!    normalized content (see [`crate::storage::text_signatures`], which
+//!    keeps the same signatures indexed for lookups on create)
+//! 2. locality-sensitive hashing over signature bands picks candidate pairs,
+//!    so only memories sharing a band are ever compared
+//! 3. candidates whose exact shingle Jaccard similarity reaches the threshold
+//!    are joined with union-find, so transitive matches form one group
+//!
+//! Memories are only grouped within the same workspace and scope, like
+//! [`find_duplicates`](crate::storage::queries::find_duplicates). Each group
+//! names a canonical member to keep and carries `memory_merge` arguments;
+//! with `enqueue` its pairs also go to the duplicate review queue
+//! (`quality_get_duplicates` / `quality_resolve_duplicate`).
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::graph::label::truncate_label;
+use crate::storage::text_signatures::{band_hashes, jaccard, minhash, shingles};
+use crate::types::{normalize_workspace, MemoryId};
+
+/// `similarity_type` of review queue entries created by clustering
+pub const CLUSTER_SIMILARITY_TYPE: &str = "minhash";
+
+/// Characters of content kept in member previews
+const PREVIEW_LENGTH: usize = 80;
+
+/// Options for [`cluster_duplicates`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuplicateClusterConfig {
+    /// Cluster only this workspace (default: every workspace)
+    pub workspace: Option<String>,
+    /// Minimum shingle Jaccard similarity for two memories to be joined
+    pub threshold: f32,
+    /// Words per shingle
+    pub shingle_size: usize,
+    /// MinHash signature length
+    pub num_hashes: usize,
+    /// LSH bands; must divide `num_hashes`. More bands catch less similar
+    /// pairs at the cost of more comparisons.
+    pub bands: usize,
+    /// Buckets larger than this compare each member with the first one only
+    /// instead of all pairs, which keeps boilerplate-heavy corpora linear
+    pub max_bucket_size: usize,
+    /// Smallest group reported
+    pub min_group_size: usize,
+    /// Groups returned, largest first
+    pub limit: usize,
+    /// Add each member/canonical pair to the duplicate review queue
+    pub enqueue: bool,
+}
+
+impl Default for DuplicateClusterConfig {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            threshold: 0.7,
+            shingle_size: 3,
+            num_hashes: 128,
+            bands: 32,
+            max_bucket_size: 64,
+            min_group_size: 2,
+            limit: 100,
+            enqueue: false,
+        }
+    }
+}
+
+/// One memory in a [`DuplicateGroup`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroupMember {
+    pub id: MemoryId,
+    /// Shingle Jaccard similarity to the canonical member
+    pub similarity: f32,
+    pub importance: f32,
+    pub access_count: i64,
+    pub created_at: String,
+    pub preview: String,
+}
+
+/// Arguments for `memory_merge` that fold a group into its canonical member
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeSuggestion {
+    pub ids: Vec<MemoryId>,
+    pub keep_id: MemoryId,
+}
+
+/// A set of memories that are near-duplicates of each other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub workspace: String,
+    /// Suggested memory to keep: highest importance, then most accessed,
+    /// then oldest
+    pub canonical_id: MemoryId,
+    /// Members with the canonical one first, then by similarity to it
+    pub members: Vec<DuplicateGroupMember>,
+    /// Lowest similarity of a member to the canonical one; members can fall
+    /// below the threshold when they joined through another member
+    pub min_similarity: f32,
+    pub merge: MergeSuggestion,
+}
+
+/// Result of [`cluster_duplicates`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicateClusterReport {
+    pub memories_scanned: usize,
+    /// Pairs compared after the LSH pre-filter
+    pub candidate_pairs: usize,
+    /// Compared pairs at or above the threshold
+    pub similar_pairs: usize,
+    pub total_groups: usize,
+    /// Memories that would go away if every group were merged
+    pub duplicate_memories: usize,
+    pub groups: Vec<DuplicateGroup>,
+    /// Review queue entries added (with `enqueue`)
+    pub queued: usize,
+}
+
+struct Candidate {
+    id: MemoryId,
+    partition: usize,
+    workspace: String,
+    content: String,
+    importance: f32,
+    access_count: i64,
+    created_at: String,
+    shingles: Vec<u64>,
+}
+
+/// Group near-duplicate memories and suggest a canonical member per group
+pub fn cluster_duplicates(
+    conn: &Connection,
+    config: &DuplicateClusterConfig,
+) -> Result<DuplicateClusterReport> {
+    if config.bands == 0 || !config.num_hashes.is_multiple_of(config.bands) {
+        return Err(EngramError::InvalidInput(format!(
+            "bands ({}) must be positive and divide num_hashes ({})",
+            config.bands, config.num_hashes
+        )));
+    }
+    if !(config.threshold > 0.0 && config.threshold <= 1.0) {
+        return Err(EngramError::InvalidInput(format!(
+            "threshold must be in (0, 1], got {}",
+            config.threshold
+        )));
+    }
+    let workspace = config
+        .workspace
+        .as_deref()
+        .map(normalize_workspace)
+        .transpose()
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))?;
+
+    let candidates = load_candidates(conn, workspace.as_deref(), config.shingle_size.max(1))?;
+    let mut report = DuplicateClusterReport {
+        memories_scanned: candidates.len(),
+        ..Default::default()
+    };
+
+    // LSH: memories sharing any band of their signature land in one bucket
+    let rows = config.num_hashes / config.bands;
+    let mut buckets: HashMap<(usize, usize, u64), Vec<usize>> = HashMap::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let signature = minhash(&candidate.shingles, config.num_hashes);
+        for (band, bucket) in band_hashes(&signature, rows).into_iter().enumerate() {
+            buckets
+                .entry((candidate.partition, band, bucket))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut sets = UnionFind::new(candidates.len());
+    for members in buckets.values().filter(|m| m.len() > 1) {
+        if crate::budget::checkpoint() {
+            break;
+        }
+        // Small buckets compare all pairs, large ones each member with the first
+        let all_pairs = members.len() <= config.max_bucket_size;
+        for j in 1..members.len() {
+            for i in 0..if all_pairs { j } else { 1 } {
+                let (a, b) = (members[i], members[j]);
+                if sets.find(a) == sets.find(b) {
+                    continue;
+                }
+                report.candidate_pairs += 1;
+                if jaccard(&candidates[a].shingles, &candidates[b].shingles) >= config.threshold {
+                    report.similar_pairs += 1;
+                    sets.union(a, b);
+                }
+            }
+        }
+    }
+
+    let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..candidates.len() {
+        components.entry(sets.find(index)).or_default().push(index);
+    }
+
+    let mut groups: Vec<DuplicateGroup> = components
+        .into_values()
+        .filter(|members| members.len() >= config.min_group_size.max(2))
+        .map(|members| build_group(&candidates, members))
+        .collect();
+    groups.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(a.canonical_id.cmp(&b.canonical_id))
+    });
+
+    report.total_groups = groups.len();
+    report.duplicate_memories = groups.iter().map(|g| g.members.len() - 1).sum();
+    if config.enqueue {
+        for group in &groups {
+            report.queued += enqueue_group(conn, group)?;
+        }
+    }
+    groups.truncate(config.limit);
+    report.groups = groups;
+
+    Ok(report)
+}
+
+fn build_group(candidates: &[Candidate], mut members: Vec<usize>) -> DuplicateGroup {
+    // Highest importance, then most accessed, then oldest, then lowest id
+    members.sort_by(|&a, &b| {
+        let (a, b) = (&candidates[a], &candidates[b]);
+        b.importance
+            .total_cmp(&a.importance)
+            .then(b.access_count.cmp(&a.access_count))
+            .then(a.created_at.cmp(&b.created_at))
+            .then(a.id.cmp(&b.id))
+    });
+    let canonical = &candidates[members[0]];
+
+    let mut group_members: Vec<DuplicateGroupMember> = members
+        .iter()
+        .map(|&index| {
+            let candidate = &candidates[index];
+            DuplicateGroupMember {
+                id: candidate.id,
+                similarity: jaccard(&canonical.shingles, &candidate.shingles),
+                importance: candidate.importance,
+                access_count: candidate.access_count,
+                created_at: candidate.created_at.clone(),
+                preview: truncate_label(&candidate.content, PREVIEW_LENGTH),
+            }
+        })
+        .collect();
+    group_members[1..].sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then(a.id.cmp(&b.id)));
+
+    let min_similarity = group_members
+        .iter()
+        .map(|m| m.similarity)
+        .fold(1.0, f32::min);
+    DuplicateGroup {
+        workspace: canonical.workspace.clone(),
+        canonical_id: canonical.id,
+        merge: MergeSuggestion {
+            ids: group_members.iter().map(|m| m.id).collect(),
+            keep_id: canonical.id,
+        },
+        members: group_members,
+        min_similarity,
+    }
+}
+
+/// Queue each member against the canonical member for review, skipping
+/// pairs the queue already holds under any similarity type
+fn enqueue_group(conn: &Connection, group: &DuplicateGroup) -> Result<usize> {
+    let now = Utc::now().to_rfc3339();
+    let mut queued = 0;
+    for member in &group.members[1..] {
+        let (a, b) = if group.canonical_id < member.id {
+            (group.canonical_id, member.id)
+        } else {
+            (member.id, group.canonical_id)
+        };
+        queued += conn.execute(
+            "INSERT INTO duplicate_candidates
+                 (memory_a_id, memory_b_id, similarity_score, similarity_type, detected_at)
+             SELECT ?1, ?2, ?3, ?4, ?5
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM duplicate_candidates
+                 WHERE (memory_a_id = ?1 AND memory_b_id = ?2)
+                    OR (memory_a_id = ?2 AND memory_b_id = ?1)
+             )",
+            params![a, b, member.similarity, CLUSTER_SIMILARITY_TYPE, now],
+        )?;
+    }
+    Ok(queued)
+}
+
+fn load_candidates(
+    conn: &Connection,
+    workspace: Option<&str>,
+    shingle_size: usize,
+) -> Result<Vec<Candidate>> {
+    let now = Utc::now().to_rfc3339();
+    let mut stmt = conn.prepare(
+        "SELECT id, content, workspace, scope_type, scope_id, importance, access_count, created_at
+         FROM memories
+         WHERE valid_to IS NULL
+           AND superseded_by IS NULL
+           AND (expires_at IS NULL OR expires_at > ?1)
+           AND (?2 IS NULL OR workspace = ?2)
+         ORDER BY id",
+    )?;
+    let mut rows = stmt.query(params![now, workspace])?;
+
+    let mut partitions: HashMap<(String, String, Option<String>), usize> = HashMap::new();
+    let mut candidates = Vec::new();
+    while let Some(row) = rows.next()? {
+        let content: String = row.get(1)?;
+        let shingles = shingles(&content, shingle_size);
+        if shingles.is_empty() {
+            continue;
+        }
+        let workspace: String = row.get(2)?;
+        let key = (workspace.clone(), row.get(3)?, row.get(4)?);
+        let next = partitions.len();
+        let partition = *partitions.entry(key).or_insert(next);
+        candidates.push(Candidate {
+            id: row.get(0)?,
+            partition,
+            workspace,
+            content,
+            importance: row.get(5)?,
+            access_count: row.get(6)?,
+            created_at: row.get(7)?,
+            shingles,
+        });
+    }
+    Ok(candidates)
+}
+
+/// Disjoint sets with path halving and union by size
+struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::{CreateMemoryInput, DedupMode};
+
+    fn create(conn: &Connection, content: &str, workspace: &str, importance: f32) -> MemoryId {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                workspace: Some(workspace.to_string()),
+                importance: Some(importance),
+                dedup_mode: DedupMode::Allow,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    const DEPLOY: &str = "deploy the billing service with the blue green script after the \
+                          database migration has finished and the smoke tests pass";
+
+    #[test]
+    fn test_groups_transitive_duplicates_with_canonical() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let a = create(conn, DEPLOY, "ops", 0.5);
+                let b = create(conn, &format!("{} today", DEPLOY), "ops", 0.9);
+                let c = create(conn, &format!("Please {}", DEPLOY), "ops", 0.5);
+                create(
+                    conn,
+                    "an unrelated note about lunch plans for friday",
+                    "ops",
+                    0.5,
+                );
+                // Same text in another workspace is not grouped with these
+                create(conn, DEPLOY, "other", 0.5);
+
+                let report = cluster_duplicates(conn, &DuplicateClusterConfig::default())?;
+                assert_eq!(report.memories_scanned, 5);
+                assert_eq!(report.total_groups, 1);
+                assert_eq!(report.duplicate_memories, 2);
+
+                let group = &report.groups[0];
+                assert_eq!(group.workspace, "ops");
+                assert_eq!(group.canonical_id, b);
+                assert_eq!(group.members[0].id, b);
+                let mut ids: Vec<_> = group.members.iter().map(|m| m.id).collect();
+                ids.sort();
+                assert_eq!(ids, vec![a, b, c]);
+                assert!(group.min_similarity >= 0.7);
+                assert_eq!(group.merge.keep_id, b);
+                assert_eq!(group.merge.ids.len(), 3);
+                assert_eq!(report.queued, 0);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_enqueue_adds_review_entries_once() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                create(conn, DEPLOY, "ops", 0.5);
+                create(conn, &format!("{} today", DEPLOY), "ops", 0.5);
+                create(conn, &format!("Please {}", DEPLOY), "ops", 0.5);
+
+                let config = DuplicateClusterConfig {
+                    workspace: Some("ops".to_string()),
+                    enqueue: true,
+                    ..Default::default()
+                };
+                assert_eq!(cluster_duplicates(conn, &config)?.queued, 2);
+                assert_eq!(cluster_duplicates(conn, &config)?.queued, 0);
+
+                let pending = crate::intelligence::get_pending_duplicates(conn, 10)?;
+                assert_eq!(pending.len(), 2);
+                assert!(pending
+                    .iter()
+                    .all(|p| p.similarity_type == CLUSTER_SIMILARITY_TYPE));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bands_not_dividing_hashes() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let config = DuplicateClusterConfig {
+                    bands: 30,
+                    ..Default::default()
+                };
+                assert!(cluster_duplicates(conn, &config).is_err());
+                Ok(())
+            })
+            .unwrap();
+    }
+}
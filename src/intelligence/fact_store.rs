@@ -0,0 +1,565 @@
+//! Structured fact store — current value per (subject, predicate).
+//!
+//! Unlike the extraction table in [`super::fact_extraction`], which keeps every
+//! distinct SPO triple it has ever seen, this store holds exactly one *current*
+//! object per `(workspace, subject, predicate)`. Writing a different object
+//! supersedes the previous value instead of adding a competing one, so lookups
+//! such as "what port does the API listen on?" return a single answer while
+//! the older values remain available through [`fact_history`].
+//!
+//! ## Invariants
+//!
+//! - Subject and predicate are matched case-insensitively; predicates are
+//!   normalised to `snake_case` (`"Works At"` → `"works_at"`)
+//! - At most one current fact exists per `(workspace, subject, predicate)`
+//! - Re-asserting the current object is a no-op that keeps the higher confidence
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::intelligence::fact_extraction::{
+    ConversationProcessor, ExtractedFact, RuleBasedExtractor,
+};
+
+// =============================================================================
+// DDL
+// =============================================================================
+
+/// DDL for the structured fact store — call once during schema setup.
+pub const CREATE_FACT_STORE_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS fact_store (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        workspace TEXT NOT NULL DEFAULT 'default',
+        subject TEXT NOT NULL,
+        predicate TEXT NOT NULL,
+        object TEXT NOT NULL,
+        subject_key TEXT NOT NULL,
+        confidence REAL NOT NULL DEFAULT 1.0,
+        source_memory_id INTEGER REFERENCES memories(id) ON DELETE SET NULL,
+        created_at TEXT NOT NULL,
+        superseded_by INTEGER,
+        superseded_at TEXT
+    );
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_fact_store_current
+        ON fact_store(workspace, subject_key, predicate)
+        WHERE superseded_at IS NULL;
+    CREATE INDEX IF NOT EXISTS idx_fact_store_lookup
+        ON fact_store(workspace, subject_key, predicate, id);
+    CREATE INDEX IF NOT EXISTS idx_fact_store_source ON fact_store(source_memory_id);
+"#;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// A fact in the structured store.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructuredFact {
+    pub id: i64,
+    pub workspace: String,
+    /// Subject as first written (e.g., "API server")
+    pub subject: String,
+    /// Normalised predicate (e.g., "listens_on")
+    pub predicate: String,
+    pub object: String,
+    pub confidence: f32,
+    /// Memory the fact was stated in, if any
+    pub source_memory_id: Option<i64>,
+    /// RFC3339 UTC timestamp
+    pub created_at: String,
+    /// Fact that replaced this one, if superseded
+    pub superseded_by: Option<i64>,
+    pub superseded_at: Option<String>,
+}
+
+impl StructuredFact {
+    /// Whether this is the current value for its subject and predicate.
+    pub fn is_current(&self) -> bool {
+        self.superseded_at.is_none()
+    }
+}
+
+/// Input for [`put_fact`].
+#[derive(Debug, Clone)]
+pub struct FactInput {
+    pub workspace: String,
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    pub confidence: f32,
+    pub source_memory_id: Option<i64>,
+}
+
+impl FactInput {
+    pub fn new(
+        subject: impl Into<String>,
+        predicate: impl Into<String>,
+        object: impl Into<String>,
+    ) -> Self {
+        Self {
+            workspace: "default".to_string(),
+            subject: subject.into(),
+            predicate: predicate.into(),
+            object: object.into(),
+            confidence: 1.0,
+            source_memory_id: None,
+        }
+    }
+}
+
+/// What [`put_fact`] did with the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FactPutAction {
+    /// No current value existed
+    Inserted,
+    /// The current value already had this object
+    Unchanged,
+    /// A different current value was superseded
+    Superseded,
+}
+
+/// Result of [`put_fact`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactPutOutcome {
+    pub action: FactPutAction,
+    /// The current fact after the write
+    pub fact: StructuredFact,
+    /// The value that was replaced, for [`FactPutAction::Superseded`]
+    pub previous: Option<StructuredFact>,
+}
+
+// =============================================================================
+// Storage functions
+// =============================================================================
+
+/// Assert `subject predicate object`, superseding any different current value.
+pub fn put_fact(conn: &Connection, input: &FactInput) -> Result<FactPutOutcome> {
+    let subject = input.subject.trim();
+    let predicate = normalize_predicate(&input.predicate);
+    let object = input.object.trim();
+    if subject.is_empty() || predicate.is_empty() || object.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "subject, predicate and object must be non-empty".to_string(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&input.confidence) {
+        return Err(EngramError::InvalidInput(format!(
+            "confidence must be between 0.0 and 1.0, got {}",
+            input.confidence
+        )));
+    }
+
+    let current = get_fact(conn, &input.workspace, subject, &predicate)?;
+    if let Some(existing) = &current {
+        if existing.object.eq_ignore_ascii_case(object) {
+            conn.execute(
+                "UPDATE fact_store
+                 SET confidence = MAX(confidence, ?1),
+                     source_memory_id = COALESCE(source_memory_id, ?2)
+                 WHERE id = ?3",
+                params![input.confidence, input.source_memory_id, existing.id],
+            )?;
+            let fact = get_fact_by_id(conn, existing.id)?;
+            return Ok(FactPutOutcome {
+                action: FactPutAction::Unchanged,
+                fact,
+                previous: None,
+            });
+        }
+    }
+
+    let now = Utc::now().to_rfc3339();
+    // Retire the old value first so the partial unique index admits the new one.
+    if let Some(existing) = &current {
+        conn.execute(
+            "UPDATE fact_store SET superseded_at = ?1 WHERE id = ?2",
+            params![now, existing.id],
+        )?;
+    }
+
+    conn.execute(
+        "INSERT INTO fact_store
+            (workspace, subject, predicate, object, subject_key, confidence,
+             source_memory_id, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            input.workspace,
+            subject,
+            predicate,
+            object,
+            subject_key(subject),
+            input.confidence,
+            input.source_memory_id,
+            now,
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    let previous = match current {
+        Some(existing) => {
+            conn.execute(
+                "UPDATE fact_store SET superseded_by = ?1 WHERE id = ?2",
+                params![id, existing.id],
+            )?;
+            Some(get_fact_by_id(conn, existing.id)?)
+        }
+        None => None,
+    };
+
+    Ok(FactPutOutcome {
+        action: if previous.is_some() {
+            FactPutAction::Superseded
+        } else {
+            FactPutAction::Inserted
+        },
+        fact: get_fact_by_id(conn, id)?,
+        previous,
+    })
+}
+
+/// Current value of `subject predicate` in `workspace`, if any.
+pub fn get_fact(
+    conn: &Connection,
+    workspace: &str,
+    subject: &str,
+    predicate: &str,
+) -> Result<Option<StructuredFact>> {
+    let fact = conn
+        .query_row(
+            &format!(
+                "SELECT {FACT_COLUMNS} FROM fact_store
+                 WHERE workspace = ?1 AND subject_key = ?2 AND predicate = ?3
+                   AND superseded_at IS NULL"
+            ),
+            params![
+                workspace,
+                subject_key(subject),
+                normalize_predicate(predicate)
+            ],
+            map_row,
+        )
+        .optional()?;
+    Ok(fact)
+}
+
+/// Every value `subject predicate` has had in `workspace`, newest first.
+pub fn fact_history(
+    conn: &Connection,
+    workspace: &str,
+    subject: &str,
+    predicate: &str,
+) -> Result<Vec<StructuredFact>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {FACT_COLUMNS} FROM fact_store
+         WHERE workspace = ?1 AND subject_key = ?2 AND predicate = ?3
+         ORDER BY id DESC"
+    ))?;
+    let facts = stmt
+        .query_map(
+            params![
+                workspace,
+                subject_key(subject),
+                normalize_predicate(predicate)
+            ],
+            map_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(facts)
+}
+
+/// Current facts in `workspace`, optionally restricted to one subject.
+pub fn list_current_facts(
+    conn: &Connection,
+    workspace: &str,
+    subject: Option<&str>,
+    limit: i64,
+) -> Result<Vec<StructuredFact>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {FACT_COLUMNS} FROM fact_store
+         WHERE workspace = ?1 AND superseded_at IS NULL
+           AND (?2 IS NULL OR subject_key = ?2)
+         ORDER BY subject_key, predicate
+         LIMIT ?3"
+    ))?;
+    let facts = stmt
+        .query_map(params![workspace, subject.map(subject_key), limit], map_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(facts)
+}
+
+/// Retract the current value of `subject predicate` without replacing it.
+///
+/// Returns `false` if there was no current value.
+pub fn retract_fact(
+    conn: &Connection,
+    workspace: &str,
+    subject: &str,
+    predicate: &str,
+) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE fact_store SET superseded_at = ?1
+         WHERE workspace = ?2 AND subject_key = ?3 AND predicate = ?4
+           AND superseded_at IS NULL",
+        params![
+            Utc::now().to_rfc3339(),
+            workspace,
+            subject_key(subject),
+            normalize_predicate(predicate)
+        ],
+    )?;
+    Ok(updated > 0)
+}
+
+/// Extract facts from a memory's content and write them to the store.
+///
+/// Facts are attributed to the memory and its workspace. When the content
+/// yields several objects for the same subject and predicate, the one with
+/// the highest confidence is kept.
+pub fn store_facts_from_memory(conn: &Connection, memory_id: i64) -> Result<Vec<FactPutOutcome>> {
+    let (content, workspace): (String, String) = conn
+        .query_row(
+            "SELECT content, workspace FROM memories WHERE id = ?1 AND valid_to IS NULL",
+            params![memory_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or(EngramError::NotFound(memory_id))?;
+
+    let processor = ConversationProcessor::new(Box::new(RuleBasedExtractor::new()));
+    let mut extracted = processor.process_text(&content, Some(memory_id));
+    extracted.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut seen = std::collections::HashSet::new();
+    let mut outcomes = Vec::new();
+    for fact in extracted {
+        let key = (
+            subject_key(&fact.subject),
+            normalize_predicate(&fact.predicate),
+        );
+        if !seen.insert(key) {
+            continue;
+        }
+        outcomes.push(put_fact(
+            conn,
+            &from_extracted(&fact, &workspace, memory_id),
+        )?);
+    }
+    Ok(outcomes)
+}
+
+// =============================================================================
+// Internal helpers
+// =============================================================================
+
+const FACT_COLUMNS: &str = "id, workspace, subject, predicate, object, confidence, \
+     source_memory_id, created_at, superseded_by, superseded_at";
+
+fn get_fact_by_id(conn: &Connection, id: i64) -> Result<StructuredFact> {
+    conn.query_row(
+        &format!("SELECT {FACT_COLUMNS} FROM fact_store WHERE id = ?1"),
+        params![id],
+        map_row,
+    )
+    .optional()?
+    .ok_or(EngramError::NotFound(id))
+}
+
+fn from_extracted(fact: &ExtractedFact, workspace: &str, memory_id: i64) -> FactInput {
+    FactInput {
+        workspace: workspace.to_string(),
+        subject: fact.subject.clone(),
+        predicate: fact.predicate.clone(),
+        object: fact.object.clone(),
+        confidence: fact.confidence,
+        source_memory_id: Some(memory_id),
+    }
+}
+
+fn subject_key(subject: &str) -> String {
+    subject.trim().to_lowercase()
+}
+
+/// Lowercase and join words with underscores: `"Works At"` → `"works_at"`.
+fn normalize_predicate(predicate: &str) -> String {
+    predicate
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn map_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<StructuredFact> {
+    Ok(StructuredFact {
+        id: row.get(0)?,
+        workspace: row.get(1)?,
+        subject: row.get(2)?,
+        predicate: row.get(3)?,
+        object: row.get(4)?,
+        confidence: row.get(5)?,
+        source_memory_id: row.get(6)?,
+        created_at: row.get(7)?,
+        superseded_by: row.get(8)?,
+        superseded_at: row.get(9)?,
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+
+    fn in_memory_conn() -> Connection {
+        let conn = Connection::open_in_memory().expect("open in-memory db");
+        conn.execute_batch("CREATE TABLE memories (id INTEGER PRIMARY KEY);")
+            .expect("create memories");
+        conn.execute_batch(CREATE_FACT_STORE_TABLE)
+            .expect("create fact_store");
+        conn
+    }
+
+    #[test]
+    fn test_put_and_get_fact() {
+        let conn = in_memory_conn();
+        let outcome =
+            put_fact(&conn, &FactInput::new("API server", "Listens On", "8080")).expect("put");
+        assert_eq!(outcome.action, FactPutAction::Inserted);
+        assert_eq!(outcome.fact.predicate, "listens_on");
+
+        let fact = get_fact(&conn, "default", "api server", "listens_on")
+            .expect("get")
+            .expect("fact exists");
+        assert_eq!(fact.object, "8080");
+        assert!(get_fact(&conn, "other", "api server", "listens_on")
+            .expect("get")
+            .is_none());
+    }
+
+    #[test]
+    fn test_changed_value_supersedes_previous() {
+        let conn = in_memory_conn();
+        let first = put_fact(&conn, &FactInput::new("User", "prefers_editor", "vim"))
+            .expect("put first")
+            .fact;
+
+        let outcome = put_fact(&conn, &FactInput::new("user", "prefers editor", "helix"))
+            .expect("put second");
+        assert_eq!(outcome.action, FactPutAction::Superseded);
+        let previous = outcome.previous.expect("previous value");
+        assert_eq!(previous.id, first.id);
+        assert_eq!(previous.superseded_by, Some(outcome.fact.id));
+        assert!(!previous.is_current());
+
+        let current = get_fact(&conn, "default", "USER", "prefers_editor")
+            .expect("get")
+            .expect("current");
+        assert_eq!(current.object, "helix");
+
+        let history = fact_history(&conn, "default", "user", "prefers_editor").expect("history");
+        let objects: Vec<&str> = history.iter().map(|f| f.object.as_str()).collect();
+        assert_eq!(objects, vec!["helix", "vim"]);
+    }
+
+    #[test]
+    fn test_same_value_is_unchanged() {
+        let conn = in_memory_conn();
+        let mut input = FactInput::new("Alice", "works_at", "Acme");
+        input.confidence = 0.6;
+        put_fact(&conn, &input).expect("put");
+        input.confidence = 0.9;
+        input.object = "acme".to_string();
+        let outcome = put_fact(&conn, &input).expect("put again");
+        assert_eq!(outcome.action, FactPutAction::Unchanged);
+        assert!((outcome.fact.confidence - 0.9).abs() < f32::EPSILON);
+        assert_eq!(
+            fact_history(&conn, "default", "alice", "works_at")
+                .expect("history")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_retract_and_list_current() {
+        let conn = in_memory_conn();
+        put_fact(&conn, &FactInput::new("Bob", "lives_in", "Paris")).expect("put");
+        put_fact(&conn, &FactInput::new("Bob", "works_at", "Initech")).expect("put");
+        put_fact(&conn, &FactInput::new("Carol", "lives_in", "Rome")).expect("put");
+
+        assert_eq!(
+            list_current_facts(&conn, "default", None, 10)
+                .expect("list")
+                .len(),
+            3
+        );
+        assert!(retract_fact(&conn, "default", "bob", "lives_in").expect("retract"));
+        assert!(!retract_fact(&conn, "default", "bob", "lives_in").expect("retract again"));
+
+        let bob = list_current_facts(&conn, "default", Some("BOB"), 10).expect("list bob");
+        assert_eq!(bob.len(), 1);
+        assert_eq!(bob[0].predicate, "works_at");
+    }
+
+    #[test]
+    fn test_put_fact_rejects_invalid_input() {
+        let conn = in_memory_conn();
+        assert!(put_fact(&conn, &FactInput::new(" ", "is", "x")).is_err());
+        let mut input = FactInput::new("a", "is", "x");
+        input.confidence = 1.5;
+        assert!(put_fact(&conn, &input).is_err());
+    }
+
+    #[test]
+    fn test_store_facts_from_memory() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                let old = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "Dana works at Globex.".to_string(),
+                        ..Default::default()
+                    },
+                )?;
+                let new = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "Dana works at Initech.".to_string(),
+                        ..Default::default()
+                    },
+                )?;
+
+                let first = store_facts_from_memory(conn, old.id)?;
+                assert!(first
+                    .iter()
+                    .any(|o| o.fact.predicate == "works_at" && o.fact.object == "Globex"));
+
+                let second = store_facts_from_memory(conn, new.id)?;
+                let changed = second
+                    .iter()
+                    .find(|o| o.fact.predicate == "works_at")
+                    .expect("works_at fact");
+                assert_eq!(changed.action, FactPutAction::Superseded);
+                assert_eq!(changed.fact.source_memory_id, Some(new.id));
+
+                let current = get_fact(conn, "default", "dana", "works_at")?.expect("current");
+                assert_eq!(current.object, "Initech");
+
+                assert!(matches!(
+                    store_facts_from_memory(conn, 9999),
+                    Err(EngramError::NotFound(9999))
+                ));
+                Ok(())
+            })
+            .expect("transaction");
+    }
+}
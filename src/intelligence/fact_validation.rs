@@ -0,0 +1,564 @@
+//! Verification workflow for unverified facts.
+//!
+//! `context_seed` stores assumptions tagged `status:unverified`. This module
+//! closes the loop:
+//!
+//! - [`verify_fact`] records a verdict, adjusting validation status,
+//!   confidence, tags and TTL, and links the supporting or contradicting
+//!   evidence memory.
+//! - [`escalate_unverified_facts`] queues facts that stayed unverified for a
+//!   long time but keep being retrieved, so a reviewer looks at them before
+//!   they silently expire or mislead.
+//! - [`list_review_queue`] reads that queue.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::storage::queries::{create_crossref, get_memory, promote_to_permanent, update_memory};
+use crate::storage::supersession::mark_superseded;
+use crate::types::{
+    CreateCrossRefInput, EdgeType, MemoryId, MemoryTier, UpdateMemoryInput, ValidationStatus,
+};
+
+// ---------------------------------------------------------------------------
+// DDL
+// ---------------------------------------------------------------------------
+
+/// Review queue for facts escalated by [`escalate_unverified_facts`].
+pub const CREATE_FACT_REVIEW_QUEUE_TABLE: &str = r#"
+    CREATE TABLE IF NOT EXISTS fact_review_queue (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        memory_id INTEGER NOT NULL UNIQUE REFERENCES memories(id) ON DELETE CASCADE,
+        reason TEXT NOT NULL,
+        access_count INTEGER NOT NULL DEFAULT 0,
+        queued_at TEXT NOT NULL,
+        resolved_at TEXT,
+        verdict TEXT
+    );
+"#;
+
+/// Tag marking a memory as an unverified fact.
+pub const UNVERIFIED_TAG: &str = "status:unverified";
+
+/// Remaining lifetime of a disputed or refuted Daily-tier fact.
+pub const DISPUTED_FACT_TTL_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Confidence assumed when a fact carries none in its metadata.
+const DEFAULT_CONFIDENCE: f64 = 0.7;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Outcome of checking a fact against evidence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FactVerdict {
+    /// Evidence confirms the fact
+    Verified,
+    /// Evidence casts doubt on the fact
+    Disputed,
+    /// Evidence shows the fact is wrong; it is superseded
+    Refuted,
+}
+
+impl FactVerdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FactVerdict::Verified => "verified",
+            FactVerdict::Disputed => "disputed",
+            FactVerdict::Refuted => "refuted",
+        }
+    }
+
+    /// Validation status stored on the memory for this verdict.
+    pub fn validation_status(&self) -> ValidationStatus {
+        match self {
+            FactVerdict::Verified => ValidationStatus::Verified,
+            FactVerdict::Disputed | FactVerdict::Refuted => ValidationStatus::Disputed,
+        }
+    }
+
+    fn adjust_confidence(&self, current: f64) -> f64 {
+        match self {
+            FactVerdict::Verified => current.max(0.9),
+            FactVerdict::Disputed => current.min(0.3),
+            FactVerdict::Refuted => 0.0,
+        }
+    }
+
+    fn evidence_edge(&self) -> EdgeType {
+        match self {
+            FactVerdict::Verified => EdgeType::References,
+            FactVerdict::Disputed => EdgeType::Contradicts,
+            FactVerdict::Refuted => EdgeType::Supersedes,
+        }
+    }
+}
+
+impl std::str::FromStr for FactVerdict {
+    type Err = EngramError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "verified" | "confirmed" => Ok(FactVerdict::Verified),
+            "disputed" => Ok(FactVerdict::Disputed),
+            "refuted" | "false" => Ok(FactVerdict::Refuted),
+            _ => Err(EngramError::InvalidInput(format!(
+                "Unknown verdict: {} (expected verified, disputed or refuted)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Result of [`verify_fact`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactVerification {
+    pub memory_id: MemoryId,
+    pub verdict: FactVerdict,
+    pub validation_status: ValidationStatus,
+    pub confidence: f64,
+    pub evidence_memory_id: Option<MemoryId>,
+    /// Expiry after the verdict (`None` = never expires)
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether the fact was superseded (refuted verdicts only)
+    pub superseded: bool,
+}
+
+/// Thresholds for [`escalate_unverified_facts`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// Minimum age of an unverified fact before it is escalated
+    pub min_age_days: i64,
+    /// Minimum number of retrievals that make the fact worth reviewing
+    pub min_access_count: i64,
+    /// Maximum facts queued per run
+    pub limit: i64,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            min_age_days: 14,
+            min_access_count: 3,
+            limit: 100,
+        }
+    }
+}
+
+/// Entry of the fact review queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub id: i64,
+    pub memory_id: MemoryId,
+    pub reason: String,
+    /// Access count when the fact was queued
+    pub access_count: i64,
+    pub queued_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub verdict: Option<FactVerdict>,
+}
+
+// ---------------------------------------------------------------------------
+// Storage functions
+// ---------------------------------------------------------------------------
+
+/// Record a verdict for the fact stored in `memory_id`.
+///
+/// - status and `confidence:` tags and metadata are rewritten;
+/// - verified Daily-tier facts are promoted to Permanent (no expiry);
+/// - disputed or refuted Daily-tier facts expire within
+///   [`DISPUTED_FACT_TTL_SECONDS`];
+/// - refuted facts are superseded by the evidence memory;
+/// - the evidence memory (if any) is linked to the fact;
+/// - an open review-queue entry for the fact is resolved;
+/// - a verified fact's freshness-contract flag is lifted.
+pub fn verify_fact(
+    conn: &Connection,
+    memory_id: MemoryId,
+    verdict: FactVerdict,
+    evidence_memory_id: Option<MemoryId>,
+) -> Result<FactVerification> {
+    let memory = get_memory(conn, memory_id)?;
+    if let Some(evidence_id) = evidence_memory_id {
+        if evidence_id == memory_id {
+            return Err(EngramError::InvalidInput(
+                "A fact cannot be its own evidence".to_string(),
+            ));
+        }
+        get_memory(conn, evidence_id)?;
+    }
+
+    let now = Utc::now();
+    let status = verdict.validation_status();
+    let current_confidence = memory
+        .metadata
+        .get("confidence")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(DEFAULT_CONFIDENCE);
+    let confidence = verdict.adjust_confidence(current_confidence);
+
+    let mut tags: Vec<String> = memory
+        .tags
+        .iter()
+        .filter(|t| !t.starts_with("status:") && !t.starts_with("confidence:"))
+        .cloned()
+        .collect();
+    tags.push(format!("status:{}", verdict.as_str()));
+    tags.push(format!("confidence:{:.2}", confidence));
+    tags.sort();
+    tags.dedup();
+
+    let mut metadata: HashMap<String, serde_json::Value> = memory.metadata.clone();
+    metadata.insert("status".to_string(), verdict.as_str().into());
+    metadata.insert("confidence".to_string(), confidence.into());
+    metadata.insert("verified_at".to_string(), now.to_rfc3339().into());
+    match evidence_memory_id {
+        Some(id) => metadata.insert("evidence_memory_id".to_string(), id.into()),
+        None => metadata.remove("evidence_memory_id"),
+    };
+
+    // Doubtful Daily facts expire soon; never extend an earlier expiry.
+    let ttl_seconds = match (verdict, memory.tier, memory.expires_at) {
+        (FactVerdict::Verified, _, _) | (_, MemoryTier::Permanent, _) => None,
+        (_, MemoryTier::Daily, Some(expires_at))
+            if expires_at <= now + Duration::seconds(DISPUTED_FACT_TTL_SECONDS) =>
+        {
+            None
+        }
+        (_, MemoryTier::Daily, _) => Some(DISPUTED_FACT_TTL_SECONDS),
+    };
+
+    update_memory(
+        conn,
+        memory_id,
+        &UpdateMemoryInput {
+            content: None,
+            memory_type: None,
+            tags: Some(tags),
+            metadata: Some(metadata),
+            importance: None,
+            scope: None,
+            ttl_seconds,
+            event_time: None,
+            trigger_pattern: None,
+            media_url: None,
+        },
+    )?;
+
+    if verdict == FactVerdict::Verified && memory.tier == MemoryTier::Daily {
+        promote_to_permanent(conn, memory_id)?;
+    }
+
+    conn.execute(
+        "UPDATE memories SET validation_status = ? WHERE id = ?",
+        params![status.as_str(), memory_id],
+    )?;
+
+    if let Some(evidence_id) = evidence_memory_id {
+        create_crossref(
+            conn,
+            &CreateCrossRefInput {
+                from_id: evidence_id,
+                to_id: memory_id,
+                edge_type: verdict.evidence_edge(),
+                strength: None,
+                source_context: Some(format!("fact verification: {}", verdict.as_str())),
+                pinned: false,
+            },
+        )?;
+    }
+
+    let superseded = verdict == FactVerdict::Refuted;
+    if superseded {
+        mark_superseded(
+            conn,
+            memory_id,
+            evidence_memory_id,
+            Some("refuted by fact verification"),
+        )?;
+    }
+
+    conn.execute(
+        "UPDATE fact_review_queue SET resolved_at = ?, verdict = ?
+         WHERE memory_id = ? AND resolved_at IS NULL",
+        params![now.to_rfc3339(), verdict.as_str(), memory_id],
+    )?;
+
+    if verdict == FactVerdict::Verified {
+        conn.execute(
+            "DELETE FROM freshness_violations WHERE memory_id = ?",
+            params![memory_id],
+        )?;
+    }
+
+    Ok(FactVerification {
+        memory_id,
+        verdict,
+        validation_status: status,
+        confidence,
+        evidence_memory_id,
+        expires_at: get_memory(conn, memory_id)?.expires_at,
+        superseded,
+    })
+}
+
+/// Queue unverified facts that are old and frequently retrieved.
+///
+/// Returns only the entries added by this run; facts already in the queue
+/// (open or resolved) are skipped.
+pub fn escalate_unverified_facts(
+    conn: &Connection,
+    config: &EscalationConfig,
+) -> Result<Vec<ReviewItem>> {
+    let now = Utc::now();
+    let cutoff = (now - Duration::days(config.min_age_days)).to_rfc3339();
+
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.access_count, m.created_at
+         FROM memories m
+         WHERE m.valid_to IS NULL
+           AND m.superseded_at IS NULL
+           AND COALESCE(m.validation_status, 'unverified') = 'unverified'
+           AND m.created_at <= ?1
+           AND m.access_count >= ?2
+           AND EXISTS (
+               SELECT 1 FROM memory_tags mt JOIN tags t ON mt.tag_id = t.id
+               WHERE mt.memory_id = m.id AND t.name = ?3
+           )
+           AND NOT EXISTS (SELECT 1 FROM fact_review_queue q WHERE q.memory_id = m.id)
+         ORDER BY m.access_count DESC, m.created_at ASC
+         LIMIT ?4",
+    )?;
+    let candidates: Vec<(i64, i64, String)> = stmt
+        .query_map(
+            params![
+                cutoff,
+                config.min_access_count,
+                UNVERIFIED_TAG,
+                config.limit
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut queued = Vec::with_capacity(candidates.len());
+    for (memory_id, access_count, created_at) in candidates {
+        let age_days = DateTime::parse_from_rfc3339(&created_at)
+            .map(|dt| (now - dt.with_timezone(&Utc)).num_days())
+            .unwrap_or(config.min_age_days);
+        let reason = format!(
+            "unverified for {} days, retrieved {} times",
+            age_days, access_count
+        );
+        conn.execute(
+            "INSERT INTO fact_review_queue (memory_id, reason, access_count, queued_at)
+             VALUES (?, ?, ?, ?)",
+            params![memory_id, reason, access_count, now.to_rfc3339()],
+        )?;
+        queued.push(ReviewItem {
+            id: conn.last_insert_rowid(),
+            memory_id,
+            reason,
+            access_count,
+            queued_at: now,
+            resolved_at: None,
+            verdict: None,
+        });
+    }
+
+    Ok(queued)
+}
+
+/// Review queue entries, oldest first. Resolved entries are included only
+/// when `include_resolved` is set.
+pub fn list_review_queue(
+    conn: &Connection,
+    include_resolved: bool,
+    limit: i64,
+) -> Result<Vec<ReviewItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, memory_id, reason, access_count, queued_at, resolved_at, verdict
+         FROM fact_review_queue
+         WHERE ?1 OR resolved_at IS NULL
+         ORDER BY queued_at ASC, id ASC
+         LIMIT ?2",
+    )?;
+    let items = stmt
+        .query_map(params![include_resolved, limit], row_to_review_item)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(items)
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+fn parse_ts(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn row_to_review_item(row: &rusqlite::Row) -> rusqlite::Result<ReviewItem> {
+    let queued_at: String = row.get(4)?;
+    let resolved_at: Option<String> = row.get(5)?;
+    let verdict: Option<String> = row.get(6)?;
+    Ok(ReviewItem {
+        id: row.get(0)?,
+        memory_id: row.get(1)?,
+        reason: row.get(2)?,
+        access_count: row.get(3)?,
+        queued_at: parse_ts(&queued_at),
+        resolved_at: resolved_at.as_deref().map(parse_ts),
+        verdict: verdict.and_then(|v| v.parse().ok()),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::storage::supersession::is_superseded;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+
+    fn seed_fact(conn: &Connection, content: &str) -> MemoryId {
+        let mut metadata = HashMap::new();
+        metadata.insert("status".to_string(), serde_json::json!("unverified"));
+        metadata.insert("confidence".to_string(), serde_json::json!(0.6));
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                tags: vec![UNVERIFIED_TAG.to_string(), "confidence:0.60".to_string()],
+                metadata,
+                tier: MemoryTier::Daily,
+                ttl_seconds: Some(90 * 24 * 60 * 60),
+                ..Default::default()
+            },
+        )
+        .expect("create fact")
+        .id
+    }
+
+    fn plain(conn: &Connection, content: &str) -> MemoryId {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                ..Default::default()
+            },
+        )
+        .expect("create")
+        .id
+    }
+
+    #[test]
+    fn test_verified_fact_becomes_permanent() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let fact = seed_fact(conn, "The user prefers dark mode");
+                let evidence = plain(conn, "User said: please keep dark mode on");
+
+                let result = verify_fact(conn, fact, FactVerdict::Verified, Some(evidence))?;
+                assert_eq!(result.validation_status, ValidationStatus::Verified);
+                assert!((result.confidence - 0.9).abs() < 1e-9);
+                assert!(result.expires_at.is_none());
+
+                let memory = get_memory(conn, fact)?;
+                assert_eq!(memory.tier, MemoryTier::Permanent);
+                assert!(memory.tags.contains(&"status:verified".to_string()));
+                assert!(!memory.tags.contains(&UNVERIFIED_TAG.to_string()));
+                assert!(memory.tags.contains(&"confidence:0.90".to_string()));
+                assert_eq!(memory.metadata["evidence_memory_id"], evidence);
+
+                let edge: String = conn.query_row(
+                    "SELECT edge_type FROM crossrefs WHERE from_id = ? AND to_id = ?",
+                    params![evidence, fact],
+                    |row| row.get(0),
+                )?;
+                assert_eq!(edge, "references");
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_refuted_fact_is_superseded_and_expires_soon() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let fact = seed_fact(conn, "The office is in Berlin");
+                let evidence = plain(conn, "The office moved to Lisbon");
+
+                let result = verify_fact(conn, fact, FactVerdict::Refuted, Some(evidence))?;
+                assert_eq!(result.validation_status, ValidationStatus::Disputed);
+                assert_eq!(result.confidence, 0.0);
+                assert!(result.superseded);
+                assert!(is_superseded(conn, fact)?);
+
+                let expires_at = result.expires_at.expect("daily fact keeps an expiry");
+                assert!(expires_at <= Utc::now() + Duration::seconds(DISPUTED_FACT_TTL_SECONDS));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_escalation_queues_old_frequently_used_facts_once() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let hot = seed_fact(conn, "Deploys happen on Tuesdays");
+                let cold = seed_fact(conn, "The team uses tabs");
+                let untagged = plain(conn, "Regular note");
+                let old = (Utc::now() - Duration::days(30)).to_rfc3339();
+                conn.execute(
+                    "UPDATE memories SET created_at = ?, access_count = 10 WHERE id IN (?, ?)",
+                    params![old, hot, untagged],
+                )?;
+                conn.execute(
+                    "UPDATE memories SET created_at = ? WHERE id = ?",
+                    params![old, cold],
+                )?;
+
+                let config = EscalationConfig::default();
+                let queued = escalate_unverified_facts(conn, &config)?;
+                assert_eq!(queued.len(), 1);
+                assert_eq!(queued[0].memory_id, hot);
+                assert!(escalate_unverified_facts(conn, &config)?.is_empty());
+
+                verify_fact(conn, hot, FactVerdict::Disputed, None)?;
+                assert!(list_review_queue(conn, false, 10)?.is_empty());
+                let all = list_review_queue(conn, true, 10)?;
+                assert_eq!(all[0].verdict, Some(FactVerdict::Disputed));
+                assert!(all[0].resolved_at.is_some());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verdict_parsing() {
+        assert_eq!(
+            "Verified".parse::<FactVerdict>().unwrap(),
+            FactVerdict::Verified
+        );
+        assert_eq!(
+            "refuted".parse::<FactVerdict>().unwrap(),
+            FactVerdict::Refuted
+        );
+        assert!("maybe".parse::<FactVerdict>().is_err());
+    }
+}
@@ -0,0 +1,615 @@
+//! Freshness contracts per fact category.
+//!
+//! A contract says memories carrying a tag (e.g. `category:pricing`) must be
+//! re-verified every N days, which separates old-but-fine knowledge from
+//! knowledge that has gone unchecked for too long:
+//!
+//! - [`check_freshness_contracts`] flags memories whose last verification
+//!   (the `verified_at` stamp left by `memory_verify_fact`, else creation)
+//!   is older than their contract allows, and clears flags once they are
+//!   re-verified.
+//! - [`apply_freshness_penalties`] demotes flagged memories in search results.
+//! - [`stale_report`] lists flagged memories and, per contract, how many old
+//!   memories are still within their contract.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::types::{MemoryId, SearchResult};
+
+/// Score subtracted from a stale memory when a contract sets none.
+pub const DEFAULT_STALE_PENALTY: f32 = 0.2;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Re-verification requirement for memories carrying `tag`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreshnessContract {
+    pub id: i64,
+    pub tag: String,
+    /// Limits the contract to one workspace (`None` = all workspaces)
+    pub workspace: Option<String>,
+    pub max_age_days: i64,
+    /// Score subtracted from violating memories in search results
+    pub penalty: f32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A memory past its contract's re-verification deadline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreshnessViolation {
+    pub memory_id: MemoryId,
+    pub contract_id: i64,
+    pub tag: String,
+    pub last_verified_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub days_overdue: i64,
+    pub flagged_at: DateTime<Utc>,
+}
+
+/// Result of [`check_freshness_contracts`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FreshnessCheckReport {
+    pub contracts: usize,
+    /// Memories covered by at least one contract
+    pub checked: usize,
+    /// Memories currently violating a contract
+    pub violating: usize,
+    /// Memories flagged by this run
+    pub newly_flagged: usize,
+    /// Flags removed because the memory was re-verified, deleted or is no
+    /// longer covered
+    pub cleared: usize,
+}
+
+/// Per-contract counts in a [`StaleReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractSummary {
+    pub contract_id: i64,
+    pub tag: String,
+    pub workspace: Option<String>,
+    pub max_age_days: i64,
+    pub violating: usize,
+    /// Created longer ago than `max_age_days` but re-verified since
+    pub old_but_fine: usize,
+}
+
+/// Result of [`stale_report`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleReport {
+    /// Most overdue first
+    pub stale: Vec<FreshnessViolation>,
+    pub contracts: Vec<ContractSummary>,
+}
+
+/// Penalty applied to one search hit by [`apply_freshness_penalties`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreshnessPenalty {
+    pub memory_id: MemoryId,
+    pub contract_id: i64,
+    pub tag: String,
+    pub penalty: f32,
+    pub due_at: DateTime<Utc>,
+}
+
+// ---------------------------------------------------------------------------
+// Contracts
+// ---------------------------------------------------------------------------
+
+fn parse_time(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+fn contract_from_row(row: &rusqlite::Row) -> rusqlite::Result<FreshnessContract> {
+    let created_at: String = row.get(5)?;
+    let updated_at: String = row.get(6)?;
+    Ok(FreshnessContract {
+        id: row.get(0)?,
+        tag: row.get(1)?,
+        workspace: row.get(2)?,
+        max_age_days: row.get(3)?,
+        penalty: row.get(4)?,
+        created_at: parse_time(&created_at),
+        updated_at: parse_time(&updated_at),
+    })
+}
+
+const CONTRACT_COLUMNS: &str = "id, tag, workspace, max_age_days, penalty, created_at, updated_at";
+
+/// Create the contract for `tag` (and `workspace`), or replace its terms.
+pub fn set_freshness_contract(
+    conn: &Connection,
+    tag: &str,
+    workspace: Option<&str>,
+    max_age_days: i64,
+    penalty: Option<f32>,
+) -> Result<FreshnessContract> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "tag must not be empty".to_string(),
+        ));
+    }
+    if max_age_days < 1 {
+        return Err(EngramError::InvalidInput(
+            "max_age_days must be at least 1".to_string(),
+        ));
+    }
+    let penalty = penalty.unwrap_or(DEFAULT_STALE_PENALTY);
+    if !(0.0..=1.0).contains(&penalty) {
+        return Err(EngramError::InvalidInput(
+            "penalty must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM freshness_contracts WHERE tag = ? AND workspace IS ?",
+            params![tag, workspace],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let id = match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE freshness_contracts SET max_age_days = ?, penalty = ?, updated_at = ?
+                 WHERE id = ?",
+                params![max_age_days, penalty, now, id],
+            )?;
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO freshness_contracts
+                     (tag, workspace, max_age_days, penalty, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                params![tag, workspace, max_age_days, penalty, now, now],
+            )?;
+            conn.last_insert_rowid()
+        }
+    };
+
+    get_freshness_contract(conn, id)
+}
+
+pub fn get_freshness_contract(conn: &Connection, id: i64) -> Result<FreshnessContract> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM freshness_contracts WHERE id = ?",
+            CONTRACT_COLUMNS
+        ),
+        params![id],
+        contract_from_row,
+    )
+    .optional()?
+    .ok_or_else(|| EngramError::NotFound(id))
+}
+
+pub fn list_freshness_contracts(conn: &Connection) -> Result<Vec<FreshnessContract>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM freshness_contracts ORDER BY tag, workspace",
+        CONTRACT_COLUMNS
+    ))?;
+    let contracts = stmt
+        .query_map([], contract_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(contracts)
+}
+
+/// Delete a contract and the flags it raised. Returns false if it did not exist.
+pub fn delete_freshness_contract(conn: &Connection, id: i64) -> Result<bool> {
+    conn.execute(
+        "DELETE FROM freshness_violations WHERE contract_id = ?",
+        params![id],
+    )?;
+    let deleted = conn.execute("DELETE FROM freshness_contracts WHERE id = ?", params![id])?;
+    Ok(deleted > 0)
+}
+
+// ---------------------------------------------------------------------------
+// Checking
+// ---------------------------------------------------------------------------
+
+/// A covered memory with the verification time that counts for it.
+struct Covered {
+    memory_id: MemoryId,
+    created_at: DateTime<Utc>,
+    last_verified_at: DateTime<Utc>,
+}
+
+/// Memories under `contract`, narrowed to `workspace` for contracts that
+/// apply everywhere.
+fn covered_memories(
+    conn: &Connection,
+    contract: &FreshnessContract,
+    workspace: Option<&str>,
+) -> Result<Vec<Covered>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT m.id, m.created_at, json_extract(m.metadata, '$.verified_at')
+         FROM memories m
+         WHERE m.valid_to IS NULL
+           AND m.superseded_at IS NULL
+           AND (?2 IS NULL OR m.workspace = ?2)
+           AND EXISTS (
+               SELECT 1 FROM memory_tags mt JOIN tags t ON mt.tag_id = t.id
+               WHERE mt.memory_id = m.id AND t.name = ?1
+           )",
+    )?;
+    let rows = stmt
+        .query_map(
+            params![contract.tag, contract.workspace.as_deref().or(workspace)],
+            |row| {
+                Ok((
+                    row.get::<_, MemoryId>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(memory_id, created_at, verified_at)| {
+            let created_at = parse_time(&created_at);
+            let last_verified_at = verified_at
+                .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_or(created_at, |v| v.max(created_at));
+            Covered {
+                memory_id,
+                created_at,
+                last_verified_at,
+            }
+        })
+        .collect())
+}
+
+/// Re-evaluate every contract as of `now` and rewrite the violation flags.
+///
+/// A memory covered by several contracts is held to the one it is most
+/// overdue on.
+pub fn check_freshness_contracts(
+    conn: &Connection,
+    now: DateTime<Utc>,
+) -> Result<FreshnessCheckReport> {
+    let contracts = list_freshness_contracts(conn)?;
+
+    let mut covered = std::collections::HashSet::new();
+    // memory -> (contract, last verified, due)
+    let mut due: HashMap<MemoryId, (i64, DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
+    for contract in &contracts {
+        for memory in covered_memories(conn, contract, None)? {
+            covered.insert(memory.memory_id);
+            let due_at = memory.last_verified_at + Duration::days(contract.max_age_days);
+            if due_at >= now {
+                continue;
+            }
+            let entry = due.entry(memory.memory_id).or_insert((
+                contract.id,
+                memory.last_verified_at,
+                due_at,
+            ));
+            if due_at < entry.2 {
+                *entry = (contract.id, memory.last_verified_at, due_at);
+            }
+        }
+    }
+
+    let flagged: HashMap<MemoryId, i64> = {
+        let mut stmt = conn.prepare("SELECT memory_id, contract_id FROM freshness_violations")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter().collect()
+    };
+
+    let mut cleared = 0;
+    for memory_id in flagged.keys() {
+        if !due.contains_key(memory_id) {
+            conn.execute(
+                "DELETE FROM freshness_violations WHERE memory_id = ?",
+                params![memory_id],
+            )?;
+            cleared += 1;
+        }
+    }
+
+    let mut newly_flagged = 0;
+    let flagged_at = now.to_rfc3339();
+    for (memory_id, (contract_id, last_verified_at, due_at)) in &due {
+        if !flagged.contains_key(memory_id) {
+            newly_flagged += 1;
+        }
+        conn.execute(
+            "INSERT INTO freshness_violations
+                 (memory_id, contract_id, last_verified_at, due_at, flagged_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(memory_id) DO UPDATE SET
+                 contract_id = excluded.contract_id,
+                 last_verified_at = excluded.last_verified_at,
+                 due_at = excluded.due_at",
+            params![
+                memory_id,
+                contract_id,
+                last_verified_at.to_rfc3339(),
+                due_at.to_rfc3339(),
+                flagged_at
+            ],
+        )?;
+    }
+
+    Ok(FreshnessCheckReport {
+        contracts: contracts.len(),
+        checked: covered.len(),
+        violating: due.len(),
+        newly_flagged,
+        cleared,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Reporting and ranking
+// ---------------------------------------------------------------------------
+
+fn violation_from_row(
+    row: &rusqlite::Row,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<FreshnessViolation> {
+    let last_verified_at: String = row.get(3)?;
+    let due_at = parse_time(&row.get::<_, String>(4)?);
+    let flagged_at: String = row.get(5)?;
+    Ok(FreshnessViolation {
+        memory_id: row.get(0)?,
+        contract_id: row.get(1)?,
+        tag: row.get(2)?,
+        last_verified_at: parse_time(&last_verified_at),
+        due_at,
+        days_overdue: (now - due_at).num_days().max(0),
+        flagged_at: parse_time(&flagged_at),
+    })
+}
+
+/// Flagged memories (optionally for one workspace) and per-contract counts
+/// separating stale memories from old ones that are still within contract.
+pub fn stale_report(conn: &Connection, workspace: Option<&str>, limit: i64) -> Result<StaleReport> {
+    let now = Utc::now();
+    let mut stmt = conn.prepare(
+        "SELECT v.memory_id, v.contract_id, c.tag, v.last_verified_at, v.due_at, v.flagged_at
+         FROM freshness_violations v
+         JOIN freshness_contracts c ON c.id = v.contract_id
+         JOIN memories m ON m.id = v.memory_id
+         WHERE ?1 IS NULL OR m.workspace = ?1
+         ORDER BY v.due_at ASC
+         LIMIT ?2",
+    )?;
+    let stale = stmt
+        .query_map(params![workspace, limit], |row| {
+            violation_from_row(row, now)
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut contracts = Vec::new();
+    for contract in list_freshness_contracts(conn)? {
+        if let (Some(filter), Some(ws)) = (workspace, contract.workspace.as_deref()) {
+            if filter != ws {
+                continue;
+            }
+        }
+        let max_age = Duration::days(contract.max_age_days);
+        let mut violating = 0;
+        let mut old_but_fine = 0;
+        for memory in covered_memories(conn, &contract, workspace)? {
+            if memory.last_verified_at + max_age < now {
+                violating += 1;
+            } else if memory.created_at + max_age < now {
+                old_but_fine += 1;
+            }
+        }
+        contracts.push(ContractSummary {
+            contract_id: contract.id,
+            tag: contract.tag,
+            workspace: contract.workspace,
+            max_age_days: contract.max_age_days,
+            violating,
+            old_but_fine,
+        });
+    }
+
+    Ok(StaleReport { stale, contracts })
+}
+
+/// Subtract the contract penalty from flagged hits and re-sort.
+pub fn apply_freshness_penalties(
+    conn: &Connection,
+    results: &mut [SearchResult],
+) -> Result<Vec<FreshnessPenalty>> {
+    if results.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT v.contract_id, c.tag, c.penalty, v.due_at
+         FROM freshness_violations v
+         JOIN freshness_contracts c ON c.id = v.contract_id
+         WHERE v.memory_id = ?",
+    )?;
+    let mut applied = Vec::new();
+    for result in results.iter_mut() {
+        let flag = stmt
+            .query_row(params![result.memory.id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, f32>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .optional()?;
+        if let Some((contract_id, tag, penalty, due_at)) = flag {
+            result.score -= penalty;
+            applied.push(FreshnessPenalty {
+                memory_id: result.memory.id,
+                contract_id,
+                tag,
+                penalty,
+                due_at: parse_time(&due_at),
+            });
+        }
+    }
+
+    if !applied.is_empty() {
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::fact_validation::{verify_fact, FactVerdict};
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+
+    fn tagged(conn: &Connection, content: &str, tag: &str) -> MemoryId {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                tags: vec![tag.to_string()],
+                ..Default::default()
+            },
+        )
+        .expect("create")
+        .id
+    }
+
+    #[test]
+    fn test_check_flags_and_clears_on_verification() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let pricing = tagged(conn, "Pro plan costs $20/month", "category:pricing");
+                let other = tagged(conn, "Office is in Lisbon", "category:office");
+                set_freshness_contract(conn, "category:pricing", None, 30, None)?;
+
+                let later = Utc::now() + Duration::days(31);
+                let report = check_freshness_contracts(conn, later)?;
+                assert_eq!(report.checked, 1);
+                assert_eq!(report.violating, 1);
+                assert_eq!(report.newly_flagged, 1);
+
+                let flagged: Vec<MemoryId> = conn
+                    .prepare("SELECT memory_id FROM freshness_violations")?
+                    .query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()?;
+                assert_eq!(flagged, vec![pricing]);
+                assert!(!flagged.contains(&other));
+
+                // Re-running does not count the same memory again.
+                let again = check_freshness_contracts(conn, later)?;
+                assert_eq!(again.newly_flagged, 0);
+                assert_eq!(again.violating, 1);
+
+                // Verification lifts the flag right away.
+                verify_fact(conn, pricing, FactVerdict::Verified, None)?;
+                let remaining: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM freshness_violations", [], |row| {
+                        row.get(0)
+                    })?;
+                assert_eq!(remaining, 0);
+
+                // The contract clock restarts from the verification.
+                let after = check_freshness_contracts(conn, Utc::now() + Duration::days(29))?;
+                assert_eq!(after.violating, 0);
+                let overdue = check_freshness_contracts(conn, Utc::now() + Duration::days(31))?;
+                assert_eq!(overdue.violating, 1);
+
+                // Deleting the contract clears its flags.
+                let contract = list_freshness_contracts(conn)?.remove(0);
+                delete_freshness_contract(conn, contract.id)?;
+                let cleared = check_freshness_contracts(conn, Utc::now() + Duration::days(31))?;
+                assert_eq!(cleared.violating, 0);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_set_contract_upserts_and_validates() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let first = set_freshness_contract(conn, "category:pricing", None, 30, None)?;
+                let second = set_freshness_contract(conn, "category:pricing", None, 14, Some(0.5))?;
+                assert_eq!(first.id, second.id);
+                assert_eq!(second.max_age_days, 14);
+                assert!((second.penalty - 0.5).abs() < f32::EPSILON);
+
+                let scoped =
+                    set_freshness_contract(conn, "category:pricing", Some("sales"), 7, None)?;
+                assert_ne!(scoped.id, first.id);
+                assert_eq!(list_freshness_contracts(conn)?.len(), 2);
+
+                assert!(set_freshness_contract(conn, "category:pricing", None, 0, None).is_err());
+                assert!(
+                    set_freshness_contract(conn, "category:pricing", None, 7, Some(2.0)).is_err()
+                );
+
+                assert!(delete_freshness_contract(conn, scoped.id)?);
+                assert!(!delete_freshness_contract(conn, scoped.id)?);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_penalty_demotes_stale_hit() {
+        use crate::storage::queries::get_memory;
+        use crate::types::{MatchInfo, SearchStrategy};
+
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let stale = tagged(conn, "Pro plan costs $20/month", "category:pricing");
+                let fresh = tagged(conn, "Pro plan includes SSO", "category:features");
+                set_freshness_contract(conn, "category:pricing", None, 30, Some(0.3))?;
+                check_freshness_contracts(conn, Utc::now() + Duration::days(60))?;
+
+                let hit = |id: MemoryId, score: f32| -> Result<SearchResult> {
+                    Ok(SearchResult {
+                        memory: get_memory(conn, id)?,
+                        score,
+                        match_info: MatchInfo {
+                            strategy: SearchStrategy::KeywordOnly,
+                            matched_terms: Vec::new(),
+                            highlights: Vec::new(),
+                            semantic_score: None,
+                            keyword_score: None,
+                            boosts: Vec::new(),
+                        },
+                    })
+                };
+                let mut results = vec![hit(stale, 0.9)?, hit(fresh, 0.7)?];
+                let applied = apply_freshness_penalties(conn, &mut results)?;
+
+                assert_eq!(applied.len(), 1);
+                assert_eq!(applied[0].memory_id, stale);
+                assert_eq!(results[0].memory.id, fresh);
+                assert!((results[1].score - 0.6).abs() < 1e-6);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
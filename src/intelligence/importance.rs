@@ -0,0 +1,468 @@
+//! Importance assigned to memories that engram creates on its own
+//!
+//! Todos, issues, ingested documents, Langfuse and OpenTelemetry traces,
+//! transcript chunks and session summaries are all stored without the caller
+//! picking an importance.
+//! [`ImportancePolicy`] decides it in one place: a base value per source, a
+//! priority or severity mapping that replaces the base when one is given, a
+//! per-type adjustment, an adjustment for evaluation scores (Langfuse), and
+//! optional adjustments for content length and the number of entities
+//! mentioned. The built-in policy reproduces the values each creator used
+//! before; the length and entity signals are off until configured.
+//!
+//! Overrides are read from a JSON file, `$ENGRAM_IMPORTANCE_POLICY` or
+//! `~/.config/engram/importance_policy.json`, and merged over the built-in
+//! policy key by key:
+//!
+//! ```json
+//! {
+//!   "sources": { "langfuse_sync": 0.4 },
+//!   "types": { "decision": 0.1 },
+//!   "entities": { "per_entity": 0.02, "max_bonus": 0.1 }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::{EntityExtractionConfig, EntityExtractor};
+use crate::error::{EngramError, Result};
+use crate::types::MemoryType;
+
+/// Environment variable pointing at an importance policy override file
+pub const IMPORTANCE_POLICY_ENV: &str = "ENGRAM_IMPORTANCE_POLICY";
+
+/// Where an automatically created memory comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportanceSource {
+    /// `memory_create_todo`
+    Todo,
+    /// `memory_create_issue`
+    Issue,
+    /// Chunk of an ingested document
+    DocumentIngest,
+    /// Section memory of a structured document
+    DocumentSection,
+    /// Trace stored by a Langfuse sync
+    LangfuseSync,
+    /// Single trace imported with `memory_from_trace`
+    LangfuseImport,
+    /// GenAI span received over OTLP
+    OtelSpan,
+    /// Recurring failure found across OTLP spans
+    OtelPattern,
+    /// Chunk of an indexed conversation
+    TranscriptChunk,
+    /// Generated summary of an indexed conversation
+    SessionSummary,
+    /// Topic shared by several sessions
+    SessionTopic,
+    /// Session checkpoint
+    Checkpoint,
+    /// Session handoff snapshot
+    Handoff,
+}
+
+impl ImportanceSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportanceSource::Todo => "todo",
+            ImportanceSource::Issue => "issue",
+            ImportanceSource::DocumentIngest => "document_ingest",
+            ImportanceSource::DocumentSection => "document_section",
+            ImportanceSource::LangfuseSync => "langfuse_sync",
+            ImportanceSource::LangfuseImport => "langfuse_import",
+            ImportanceSource::OtelSpan => "otel_span",
+            ImportanceSource::OtelPattern => "otel_pattern",
+            ImportanceSource::TranscriptChunk => "transcript_chunk",
+            ImportanceSource::SessionSummary => "session_summary",
+            ImportanceSource::SessionTopic => "session_topic",
+            ImportanceSource::Checkpoint => "checkpoint",
+            ImportanceSource::Handoff => "handoff",
+        }
+    }
+}
+
+/// Adjustment for very short or very long content
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LengthSignal {
+    /// Content with fewer characters than this loses `adjustment`
+    pub short_chars: usize,
+    /// Content with more characters than this gains `adjustment`
+    pub long_chars: usize,
+    pub adjustment: f32,
+}
+
+impl Default for LengthSignal {
+    fn default() -> Self {
+        Self {
+            short_chars: 80,
+            long_chars: 2000,
+            adjustment: 0.0,
+        }
+    }
+}
+
+/// Bonus for each entity mentioned in the content
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntitySignal {
+    pub per_entity: f32,
+    /// Cap on the total entity bonus
+    pub max_bonus: f32,
+}
+
+impl Default for EntitySignal {
+    fn default() -> Self {
+        Self {
+            per_entity: 0.0,
+            max_bonus: 0.2,
+        }
+    }
+}
+
+/// Where an evaluation score falls relative to [`ScoreSignal`] thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreBand {
+    Low,
+    Neutral,
+    High,
+}
+
+/// Adjustment for an evaluation score in `[0, 1]`
+///
+/// Low scores raise importance as well: a failed generation is worth
+/// remembering as a failure mode.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreSignal {
+    /// Scores at or below this are [`ScoreBand::Low`]
+    pub low_threshold: f32,
+    /// Scores at or above this are [`ScoreBand::High`]
+    pub high_threshold: f32,
+    pub low_adjustment: f32,
+    pub high_adjustment: f32,
+}
+
+impl Default for ScoreSignal {
+    fn default() -> Self {
+        Self {
+            low_threshold: 0.4,
+            high_threshold: 0.8,
+            low_adjustment: 0.2,
+            high_adjustment: 0.1,
+        }
+    }
+}
+
+impl ScoreSignal {
+    pub fn band(&self, score: f32) -> ScoreBand {
+        if score <= self.low_threshold {
+            ScoreBand::Low
+        } else if score >= self.high_threshold {
+            ScoreBand::High
+        } else {
+            ScoreBand::Neutral
+        }
+    }
+
+    /// Importance added for a memory with this score
+    pub fn adjustment(&self, score: f32) -> f32 {
+        match self.band(score) {
+            ScoreBand::Low => self.low_adjustment,
+            ScoreBand::Neutral => 0.0,
+            ScoreBand::High => self.high_adjustment,
+        }
+    }
+}
+
+/// How importance is assigned to automatically created memories
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportancePolicy {
+    /// Base importance of sources missing from `sources`
+    pub default: f32,
+    /// Base importance per source
+    pub sources: BTreeMap<ImportanceSource, f32>,
+    /// Priority or severity → importance; replaces the source base when the
+    /// creator passes a known priority
+    pub priorities: BTreeMap<String, f32>,
+    /// Added per memory type (`MemoryType::as_str`)
+    pub types: BTreeMap<String, f32>,
+    pub score: ScoreSignal,
+    pub length: LengthSignal,
+    pub entities: EntitySignal,
+}
+
+impl Default for ImportancePolicy {
+    fn default() -> Self {
+        use ImportanceSource::*;
+        let sources = [
+            (Todo, 0.5),
+            (Issue, 0.5),
+            (DocumentIngest, 0.5),
+            (DocumentSection, 0.6),
+            (LangfuseSync, 0.5),
+            (LangfuseImport, 0.6),
+            (OtelSpan, 0.5),
+            (OtelPattern, 0.7),
+            (TranscriptChunk, 0.3),
+            (SessionSummary, 0.5),
+            (SessionTopic, 0.6),
+            (Checkpoint, 0.7),
+            (Handoff, 0.9),
+        ];
+        let priorities = [
+            ("critical", 1.0),
+            ("high", 0.8),
+            ("medium", 0.5),
+            ("low", 0.3),
+        ];
+        Self {
+            default: 0.5,
+            sources: sources.into_iter().collect(),
+            priorities: priorities
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            types: BTreeMap::new(),
+            score: ScoreSignal::default(),
+            length: LengthSignal::default(),
+            entities: EntitySignal::default(),
+        }
+    }
+}
+
+/// What is known about a memory when its importance is assigned
+#[derive(Debug, Clone)]
+pub struct ImportanceSignals<'a> {
+    pub source: ImportanceSource,
+    pub memory_type: MemoryType,
+    pub content: &'a str,
+    /// Priority or severity given by the caller, e.g. `high`
+    pub priority: Option<&'a str>,
+    /// Entities already extracted; counted from the content when the entity
+    /// signal is enabled and this is `None`
+    pub entity_count: Option<usize>,
+    /// Evaluation score in `[0, 1]`, e.g. the mean Langfuse score of a trace
+    pub score: Option<f32>,
+}
+
+impl<'a> ImportanceSignals<'a> {
+    pub fn new(source: ImportanceSource, memory_type: MemoryType, content: &'a str) -> Self {
+        Self {
+            source,
+            memory_type,
+            content,
+            priority: None,
+            entity_count: None,
+            score: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: &'a str) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn with_entity_count(mut self, count: usize) -> Self {
+        self.entity_count = Some(count);
+        self
+    }
+
+    pub fn with_score(mut self, score: f32) -> Self {
+        self.score = Some(score);
+        self
+    }
+}
+
+impl ImportancePolicy {
+    /// Process-wide policy: the built-in policy plus the override file, if
+    /// any, loaded on first use
+    pub fn global() -> &'static ImportancePolicy {
+        static GLOBAL: OnceLock<ImportancePolicy> = OnceLock::new();
+        GLOBAL.get_or_init(Self::load_or_default)
+    }
+
+    /// Importance for a new memory, in `[0, 1]`
+    pub fn assess(&self, signals: &ImportanceSignals) -> f32 {
+        let base = signals
+            .priority
+            .and_then(|p| self.priorities.get(&p.to_lowercase()))
+            .or_else(|| self.sources.get(&signals.source))
+            .copied()
+            .unwrap_or(self.default);
+        let mut importance = base
+            + self
+                .types
+                .get(signals.memory_type.as_str())
+                .copied()
+                .unwrap_or(0.0);
+
+        if let Some(score) = signals.score {
+            importance += self.score.adjustment(score);
+        }
+
+        if self.length.adjustment != 0.0 {
+            let chars = signals.content.chars().count();
+            if chars < self.length.short_chars {
+                importance -= self.length.adjustment;
+            } else if chars > self.length.long_chars {
+                importance += self.length.adjustment;
+            }
+        }
+
+        if self.entities.per_entity != 0.0 {
+            let count = signals.entity_count.unwrap_or_else(|| {
+                EntityExtractor::new(EntityExtractionConfig::default())
+                    .extract(signals.content)
+                    .entities
+                    .len()
+            });
+            importance += (count as f32 * self.entities.per_entity).min(self.entities.max_bonus);
+        }
+
+        importance.clamp(0.0, 1.0)
+    }
+
+    /// Built-in policy with the overrides from a JSON file merged in
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            EngramError::Config(format!("Cannot read importance policy {:?}: {}", path, e))
+        })?;
+        let overrides: serde_json::Value = serde_json::from_str(&contents).map_err(|e| {
+            EngramError::Config(format!("Invalid importance policy {:?}: {}", path, e))
+        })?;
+        Self::default().merged(overrides)
+    }
+
+    /// This policy with the keys present in `overrides` replaced
+    pub fn merged(&self, overrides: serde_json::Value) -> Result<Self> {
+        fn merge(base: &mut serde_json::Value, patch: serde_json::Value) {
+            match (base, patch) {
+                (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+                    for (key, value) in patch {
+                        merge(base.entry(key).or_insert(serde_json::Value::Null), value);
+                    }
+                }
+                (base, patch) => *base = patch,
+            }
+        }
+
+        let mut value = serde_json::to_value(self)?;
+        merge(&mut value, overrides);
+        serde_json::from_value(value)
+            .map_err(|e| EngramError::Config(format!("Invalid importance policy: {}", e)))
+    }
+
+    /// Load from `$ENGRAM_IMPORTANCE_POLICY` or
+    /// `~/.config/engram/importance_policy.json`, falling back to the built-in
+    /// policy when neither exists or parses
+    pub fn load_or_default() -> Self {
+        let path = std::env::var_os(IMPORTANCE_POLICY_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                dirs::config_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join("engram")
+                    .join("importance_policy.json")
+            });
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(&path).unwrap_or_else(|e| {
+            tracing::warn!(path = ?path, error = %e, "Failed to load importance policy, using defaults");
+            Self::default()
+        })
+    }
+}
+
+/// Importance for a new memory under the process-wide policy
+pub fn auto_importance(signals: &ImportanceSignals) -> f32 {
+    ImportancePolicy::global().assess(signals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_defaults_match_previous_constants() {
+        let policy = ImportancePolicy::default();
+        let todo = |priority: &'static str| {
+            policy.assess(
+                &ImportanceSignals::new(ImportanceSource::Todo, MemoryType::Todo, "Ship it")
+                    .with_priority(priority),
+            )
+        };
+        assert_eq!(todo("critical"), 1.0);
+        assert_eq!(todo("High"), 0.8);
+        assert_eq!(todo("low"), 0.3);
+        assert_eq!(todo("someday"), 0.5);
+
+        let chunk = ImportanceSignals::new(
+            ImportanceSource::TranscriptChunk,
+            MemoryType::TranscriptChunk,
+            "user: hi",
+        );
+        assert_eq!(policy.assess(&chunk), 0.3);
+        let trace =
+            ImportanceSignals::new(ImportanceSource::LangfuseImport, MemoryType::Episodic, "");
+        assert_eq!(policy.assess(&trace), 0.6);
+    }
+
+    #[test]
+    fn test_signals_and_overrides() {
+        let policy = ImportancePolicy::default()
+            .merged(json!({
+                "sources": {"document_ingest": 0.4},
+                "types": {"context": 0.1},
+                "length": {"adjustment": 0.05},
+                "entities": {"per_entity": 0.1, "max_bonus": 0.15}
+            }))
+            .unwrap();
+        // Untouched keys keep their built-in values
+        assert_eq!(policy.sources[&ImportanceSource::Handoff], 0.9);
+        assert_eq!(policy.length.short_chars, 80);
+
+        let short = ImportanceSignals::new(
+            ImportanceSource::DocumentIngest,
+            MemoryType::Context,
+            "Tiny chunk",
+        )
+        .with_entity_count(0);
+        assert!((policy.assess(&short) - 0.45).abs() < 1e-6);
+
+        let long = "word ".repeat(500);
+        let rich =
+            ImportanceSignals::new(ImportanceSource::DocumentIngest, MemoryType::Context, &long)
+                .with_entity_count(5);
+        assert!((policy.assess(&rich) - 0.7).abs() < 1e-6);
+
+        assert!(ImportancePolicy::default()
+            .merged(json!({"sources": {"nowhere": 1.0}}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_score_signal() {
+        let policy = ImportancePolicy::default();
+        let trace = |score: f32| {
+            policy.assess(
+                &ImportanceSignals::new(ImportanceSource::LangfuseSync, MemoryType::Episodic, "")
+                    .with_score(score),
+            )
+        };
+        assert_eq!(policy.score.band(0.1), ScoreBand::Low);
+        assert!((trace(0.1) - 0.7).abs() < 1e-6);
+        assert_eq!(trace(0.6), 0.5);
+        assert!((trace(0.95) - 0.6).abs() < 1e-6);
+    }
+}
@@ -30,6 +30,7 @@ use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{EngramError, Result};
+use crate::types::ContentHash;
 
 // =============================================================================
 // Public types
@@ -613,7 +614,7 @@ pub fn apply_update(
         |row| Ok((row.get(0)?, row.get(1).unwrap_or_else(|_| "[]".to_string()))),
     )?;
 
-    let old_hash = sha256_hex(&old_content);
+    let old_hash = ContentHash::of_bytes(&old_content).into_string();
 
     let new_stored_content = match action {
         UpdateAction::Replace => new_content.to_string(),
@@ -622,7 +623,7 @@ pub fn apply_update(
         UpdateAction::Flag => old_content.clone(),
     };
 
-    let new_hash = sha256_hex(&new_stored_content);
+    let new_hash = ContentHash::of_bytes(&new_stored_content).into_string();
 
     match action {
         UpdateAction::Replace => {
@@ -783,21 +784,6 @@ fn contains_old_year(text: &str) -> bool {
     false
 }
 
-/// Compute a SHA-256 hex digest of a string without pulling in a heavy dep.
-///
-/// We use a simple FNV-1a inspired hash here because the spec only asks for
-/// a "content hash" string — not cryptographic security. This keeps the module
-/// dependency-free.
-fn sha256_hex(content: &str) -> String {
-    // Use a deterministic 64-bit FNV-1a hash formatted as 16-char hex.
-    let mut hash: u64 = 14695981039346656037u64; // FNV offset basis
-    for byte in content.as_bytes() {
-        hash ^= *byte as u64;
-        hash = hash.wrapping_mul(1099511628211u64); // FNV prime
-    }
-    format!("{:016x}", hash)
-}
-
 /// Append a tag to a JSON array string (e.g., `["existing"]` → `["existing","needs-review"]`).
 fn add_tag_to_json(tags_json: &str, tag: &str) -> String {
     let mut tags: Vec<String> = serde_json::from_str(tags_json).unwrap_or_default();
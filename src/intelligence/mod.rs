@@ -10,14 +10,26 @@
 //! - Entity extraction / NER (RML-925)
 //! - Document ingestion (RML-928)
 //! - Session transcript indexing with dual-limiter chunking
+//! - Cross-session linking of recurring topics
 //! - AI auto-tagging for memories
 //! - Context compression and token counting (ENG-34)
 //! - Salience scoring and temporal decay (Phase 8 - ENG-66 to ENG-68)
 //! - Session context tracking (Phase 8 - ENG-70, ENG-71)
 //! - Context quality and deduplication (Phase 9 - ENG-48 to ENG-66)
+//! - Relevance/redundancy scoring for externally assembled prompts
+//! - Per-workspace dedup threshold calibration
+//! - Near-duplicate clustering with MinHash/LSH
 //! - Semantic structured compression (RML-1208)
 //! - Emotional analysis and reflective memory (RML-1215)
 //! - Autonomous memory garden maintenance (RML-1222)
+//! - Verification workflow and review queue for unverified facts
+//! - Freshness contracts that flag and demote memories overdue for re-verification
+//! - Structured fact store with one current value per subject and predicate
+//! - Configurable importance policy for automatically created memories
+//! - Heuristic workspace assignment for memories created without one
+//! - Temporal expression parsing ("last Tuesday", "two weeks ago") with per-workspace timezone and date order
+//! - Conflict warnings for memories assembled into prompt context
+//! - Usage-based recommendations of memories a session probably needs
 
 pub mod agent_loop;
 pub mod auto_capture;
@@ -28,24 +40,39 @@ pub mod consolidation;
 pub mod consolidation_offline;
 pub mod content_utils;
 pub mod context_builder;
+pub mod context_conflicts;
 pub mod context_compression;
 pub mod context_quality;
+pub mod context_scoring;
+pub mod dedup_calibration;
 pub mod document_ingest;
+pub mod duplicate_clusters;
 pub mod emotional;
 pub mod entities;
 pub mod entity_extraction;
 pub mod fact_extraction;
+pub mod fact_store;
+pub mod fact_validation;
+pub mod freshness;
 pub mod gardening;
+pub mod importance;
 pub mod memory_update;
 pub mod natural_language;
+pub mod policy_simulation;
 pub mod proactive;
 pub mod project_context;
 pub mod quality;
+pub mod recommendations;
 pub mod salience;
 pub mod session_context;
 pub mod session_indexing;
+pub mod session_summary;
+pub mod session_topics;
 pub mod suggestions;
 pub mod synthesis;
+pub mod temporal_expr;
+pub mod transcript_denoise;
+pub mod workspace_assignment;
 
 pub use auto_capture::{
     AutoCaptureConfig, AutoCaptureEngine, CaptureCandidate, CaptureType, ConversationTracker,
@@ -68,12 +95,21 @@ pub use entity_extraction::{
     auto_link_memory, extract_entities, ExtractedEntity as NerExtractedEntity, ExtractedEntityType,
     ExtractionConfig, ExtractionResult as NerExtractionResult,
 };
+pub use importance::{
+    auto_importance, EntitySignal, ImportancePolicy, ImportanceSignals, ImportanceSource,
+    LengthSignal, ScoreBand, ScoreSignal, IMPORTANCE_POLICY_ENV,
+};
 pub use natural_language::{CommandType, NaturalLanguageParser, ParsedCommand};
+pub use policy_simulation::{simulate_policies, PolicySimulation, PolicySimulationOptions};
 pub use project_context::{
     DiscoveredFile, InstructionFileParser, InstructionFileType, ParsedInstructions, ParsedSection,
     ProjectContextConfig, ProjectContextEngine, ScanResult, CORE_INSTRUCTION_FILES,
 };
 pub use quality::{QualityMetrics, QualityScore, QualityScorer};
+pub use recommendations::{
+    active_sessions, recommend_memories, Recommendation, RecommendationOptions,
+    RecommendationSet, RecommendationTracker,
+};
 pub use salience::{
     boost_memory_salience, demote_memory_salience, get_memory_salience,
     get_memory_salience_with_feedback, get_salience_history, get_salience_stats,
@@ -90,14 +126,26 @@ pub use session_context::{
 };
 pub use session_indexing::{
     chunk_conversation, delete_session, get_session, index_conversation, index_conversation_delta,
-    list_sessions, ChunkingConfig, ConversationChunk, Message, Session,
+    list_sessions, ChunkingConfig, ConversationChunk, Message, MessageKind, Session, TokenLimit,
+    TranscriptFilter,
+};
+pub use session_summary::{
+    default_summarizer, ExtractiveSummarizer, SessionSummarizer, SessionSummary,
+};
+pub use session_topics::{
+    find_session_topics, link_session_topics, RelatedSession, SessionLink, SessionTopic,
+    TopicLinkConfig, TopicLinkReport,
 };
 pub use suggestions::{Suggestion, SuggestionEngine, SuggestionType};
+pub use temporal_expr::{
+    parse_time_range, parse_utc_offset, DateOrder, TemporalContext, TimeRange,
+};
+pub use transcript_denoise::{denoise_transcript, DenoiseConfig, DenoiseStats, Denoised};
 
 // Phase 2: Context Compression Engine (ENG-34)
 pub use compression::{
     check_context_budget, count_tokens, detect_encoding, parse_encoding, CompressionStrategy,
-    ContextBudgetInput, ContextBudgetResult, MemoryTokenCount, TokenEncoding,
+    ContextBudgetInput, ContextBudgetResult, MemoryTokenCount, TiktokenCounter, TokenEncoding,
 };
 
 // RML-1232: Automatic fact extraction (SPO triples)
@@ -106,14 +154,45 @@ pub use fact_extraction::{
     ExtractedFact, Fact, FactExtractor, RuleBasedExtractor, CREATE_FACTS_TABLE,
 };
 
+// Structured fact store
+pub use fact_store::{
+    fact_history, get_fact, list_current_facts, put_fact, retract_fact, store_facts_from_memory,
+    FactInput, FactPutAction, FactPutOutcome, StructuredFact, CREATE_FACT_STORE_TABLE,
+};
+
+// Fact verification workflow
+pub use fact_validation::{
+    escalate_unverified_facts, list_review_queue, verify_fact, EscalationConfig, FactVerdict,
+    FactVerification, ReviewItem,
+};
+
+// Freshness contracts
+pub use freshness::{
+    apply_freshness_penalties, check_freshness_contracts, delete_freshness_contract,
+    list_freshness_contracts, set_freshness_contract, stale_report, FreshnessCheckReport,
+    FreshnessContract, FreshnessPenalty, FreshnessViolation, StaleReport,
+};
+
 // Phase 9: Context Quality (ENG-48 to ENG-66)
 pub use context_quality::{
     calculate_quality_score, calculate_text_similarity, detect_conflicts, find_near_duplicates,
     find_semantic_duplicates, generate_quality_report, get_pending_duplicates, get_source_trust,
-    get_unresolved_conflicts, resolve_conflict, update_source_trust, ConflictSeverity,
-    ConflictType, ContextQualityConfig, DuplicateCandidate, EnhancedQualityScore, MemoryConflict,
-    QualityIssue, QualityReport, QualitySuggestion, ResolutionType, SourceTrustScore,
-    ValidationStatus,
+    get_unresolved_conflicts, resolve_conflict, resolve_duplicate_candidate, update_source_trust,
+    ConflictSeverity, ConflictType, ContextQualityConfig, DuplicateCandidate, DuplicateVerdict,
+    EnhancedQualityScore, MemoryConflict, QualityIssue, QualityReport, QualitySuggestion,
+    ResolutionType, SourceTrustScore, ValidationStatus,
+};
+
+// Dedup threshold calibration
+pub use dedup_calibration::{
+    calibrate_dedup_thresholds, CalibrationBasis, CalibrationConfig, SimilarityDistribution,
+    WorkspaceCalibration,
+};
+
+// Near-duplicate clustering
+pub use duplicate_clusters::{
+    cluster_duplicates, DuplicateClusterConfig, DuplicateClusterReport, DuplicateGroup,
+    DuplicateGroupMember, MergeSuggestion,
 };
 
 // RML-1208: Semantic Structured Compression
@@ -2,8 +2,9 @@
 //!
 //! Parses natural language input into structured commands.
 
+use crate::intelligence::temporal_expr::{parse_time_range, DateOrder, TemporalContext, TimeRange};
 use crate::types::{EdgeType, MemoryType};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -79,6 +80,10 @@ pub struct NaturalLanguageParser {
     link_keywords: Vec<&'static str>,
     /// Keywords that indicate list intent
     list_keywords: Vec<&'static str>,
+    /// Timezone and date order for date expressions
+    temporal: TemporalContext,
+    /// Resolve relative dates against this instead of the current time
+    now: Option<DateTime<Utc>>,
 }
 
 impl Default for NaturalLanguageParser {
@@ -112,9 +117,29 @@ impl NaturalLanguageParser {
             delete_keywords: vec!["delete", "remove", "forget", "erase", "discard", "drop"],
             link_keywords: vec!["link", "connect", "relate", "associate", "reference"],
             list_keywords: vec!["list", "show all", "display", "enumerate", "browse"],
+            temporal: TemporalContext::default(),
+            now: None,
         }
     }
 
+    /// Take calendar days in `offset` rather than UTC ("today", "last Tuesday")
+    pub fn with_timezone(mut self, offset: FixedOffset) -> Self {
+        self.temporal = self.temporal.with_offset(offset);
+        self
+    }
+
+    /// Read all-numeric dates ("03/04/2024") in this order
+    pub fn with_date_order(mut self, date_order: DateOrder) -> Self {
+        self.temporal = self.temporal.with_date_order(date_order);
+        self
+    }
+
+    /// Resolve relative dates against a fixed time
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = Some(now);
+        self
+    }
+
     /// Parse a natural language input into a command
     pub fn parse(&self, input: &str) -> ParsedCommand {
         let input_lower = input.to_lowercase();
@@ -123,8 +148,19 @@ impl NaturalLanguageParser {
         // Detect command type
         let (command_type, confidence) = self.detect_command_type(&input_lower);
 
-        // Extract content
-        let content = self.extract_content(input_trimmed, &command_type);
+        // Extract date filter
+        let time_range = self.extract_time_range(input_trimmed);
+        let date_filter = time_range.as_ref().map(|range| DateFilter {
+            after: range.after,
+            before: range.before,
+        });
+
+        // Extract content, without the date expression for searches
+        let mut content = self.extract_content(input_trimmed, &command_type);
+        if let (Some(range), CommandType::Search | CommandType::List) = (&time_range, &command_type)
+        {
+            content = content.and_then(|c| strip_expression(&c, &range.expression));
+        }
 
         // Extract tags
         let tags = self.extract_tags(&input_lower);
@@ -138,9 +174,6 @@ impl NaturalLanguageParser {
         // Extract edge type
         let edge_type = self.extract_edge_type(&input_lower);
 
-        // Extract date filter
-        let date_filter = self.extract_date_filter(&input_lower);
-
         // Extract limit
         let limit = self.extract_limit(&input_lower);
 
@@ -366,6 +399,8 @@ impl NaturalLanguageParser {
             Some(EdgeType::Supersedes)
         } else if input.contains("contradict") || input.contains("conflict") {
             Some(EdgeType::Contradicts)
+        } else if input.contains("support") || input.contains("confirm") {
+            Some(EdgeType::Supports)
         } else if input.contains("implement") {
             Some(EdgeType::Implements)
         } else if input.contains("extend") {
@@ -385,70 +420,11 @@ impl NaturalLanguageParser {
         }
     }
 
-    /// Extract date filter from input
-    fn extract_date_filter(&self, input: &str) -> Option<DateFilter> {
-        let mut after = None;
-        let mut before = None;
-
-        // Look for "last X days/weeks"
-        if input.contains("last") {
-            if let Some(days) = self.extract_duration_days(input) {
-                after = Some(Utc::now() - chrono::Duration::days(days));
-            }
-        }
-
-        // Look for "today", "yesterday", "this week"
-        if input.contains("today") {
-            let today = Utc::now().date_naive();
-            after = Some(today.and_hms_opt(0, 0, 0).unwrap().and_utc());
-        } else if input.contains("yesterday") {
-            let yesterday = Utc::now().date_naive() - chrono::Duration::days(1);
-            after = Some(yesterday.and_hms_opt(0, 0, 0).unwrap().and_utc());
-            before = Some(
-                Utc::now()
-                    .date_naive()
-                    .and_hms_opt(0, 0, 0)
-                    .unwrap()
-                    .and_utc(),
-            );
-        } else if input.contains("this week") {
-            after = Some(Utc::now() - chrono::Duration::days(7));
-        } else if input.contains("this month") {
-            after = Some(Utc::now() - chrono::Duration::days(30));
-        }
-
-        if after.is_some() || before.is_some() {
-            Some(DateFilter { after, before })
-        } else {
-            None
-        }
-    }
-
-    /// Extract duration in days from phrases like "last 7 days"
-    fn extract_duration_days(&self, input: &str) -> Option<i64> {
-        // Look for patterns like "last 7 days", "last week", "last month"
-        for word in input.split_whitespace() {
-            if let Ok(num) = word.parse::<i64>() {
-                if input.contains("day") {
-                    return Some(num);
-                } else if input.contains("week") {
-                    return Some(num * 7);
-                } else if input.contains("month") {
-                    return Some(num * 30);
-                }
-            }
-        }
-
-        // Handle special cases
-        if input.contains("last week") {
-            Some(7)
-        } else if input.contains("last month") {
-            Some(30)
-        } else if input.contains("last year") {
-            Some(365)
-        } else {
-            None
-        }
+    /// Extract the first date or time range expression from input
+    fn extract_time_range(&self, input: &str) -> Option<TimeRange> {
+        let mut ctx = self.temporal;
+        ctx.now = self.now.unwrap_or_else(Utc::now);
+        parse_time_range(input, &ctx)
     }
 
     /// Extract result limit from input
@@ -470,6 +446,22 @@ impl NaturalLanguageParser {
     }
 }
 
+/// `content` without `expression` (matched case-insensitively), or `None`
+/// if nothing else is left
+fn strip_expression(content: &str, expression: &str) -> Option<String> {
+    let stripped = match content.to_ascii_lowercase().find(expression) {
+        Some(pos) => format!("{} {}", &content[..pos], &content[pos + expression.len()..]),
+        None => content.to_string(),
+    };
+    let stripped = stripped.split_whitespace().collect::<Vec<_>>().join(" ");
+    let stripped = stripped.trim_end_matches(['?', '.', '!']).trim();
+    if stripped.is_empty() {
+        None
+    } else {
+        Some(stripped.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,6 +523,38 @@ mod tests {
         assert!(cmd.date_filter.unwrap().after.is_some());
     }
 
+    #[test]
+    fn test_relative_and_locale_dates() {
+        // Wednesday 2024-03-13 23:30 UTC, already Thursday in Berlin
+        let now: DateTime<Utc> = "2024-03-13T23:30:00Z".parse().unwrap();
+        let parser = NaturalLanguageParser::new()
+            .with_now(now)
+            .with_timezone(FixedOffset::east_opt(3600).unwrap())
+            .with_date_order(DateOrder::Dmy);
+
+        let cmd = parser.parse("Find outage reports from last Tuesday");
+        let filter = cmd.date_filter.unwrap();
+        assert_eq!(
+            filter.after.unwrap().to_rfc3339(),
+            "2024-03-11T23:00:00+00:00"
+        );
+        assert_eq!(
+            filter.before.unwrap().to_rfc3339(),
+            "2024-03-12T23:00:00+00:00"
+        );
+        assert_eq!(cmd.content.as_deref(), Some("outage reports"));
+
+        let cmd = parser.parse("Search for outages two weeks ago");
+        assert!(cmd.date_filter.unwrap().before.is_some());
+
+        let cmd = parser.parse("Find incidents on 01/03/2024");
+        let filter = cmd.date_filter.unwrap();
+        assert_eq!(
+            filter.after.unwrap().to_rfc3339(),
+            "2024-02-29T23:00:00+00:00"
+        );
+    }
+
     #[test]
     fn test_extract_limit() {
         let parser = NaturalLanguageParser::new();
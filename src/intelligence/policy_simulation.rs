@@ -0,0 +1,611 @@
+//! Policy simulation
+//!
+//! Runs the lifecycle, TTL, retention and salience decay rules hypothetically
+//! over the live corpus and reports what each workspace would lose — without
+//! touching a single row. Each rule mirrors the job that enforces it:
+//!
+//! - Lifecycle: `lifecycle_run` (mark stale, archive stale)
+//! - TTL: the expired-memory cleanup job (purge after the grace period)
+//! - Retention: `retention_policy_apply` (compress, cap, auto-delete)
+//! - Salience decay: `salience_decay_run`
+//!
+//! A memory hit by several rules is counted once under its most severe
+//! outcome (delete > archive > demote); `by_rule` keeps the per-rule counts.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::intelligence::salience::{evaluate_decay, SalienceConfig};
+use crate::storage::queries::{list_retention_policies, DEFAULT_EXPIRED_GRACE_DAYS};
+use crate::types::MemoryId;
+
+/// Age buckets (days since creation) used for the age histogram
+const AGE_BUCKETS: &[(&str, i64)] = &[
+    ("<7d", 7),
+    ("7-30d", 30),
+    ("30-90d", 90),
+    ("90-365d", 365),
+    (">=365d", i64::MAX),
+];
+
+/// Thresholds for the lifecycle rule (same defaults as `lifecycle_run`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleRules {
+    /// Active memories created before this many days ago may be marked stale
+    pub stale_days: i64,
+    /// Stale memories created before this many days ago are archived
+    pub archive_days: i64,
+    /// Only memories below this importance are marked stale
+    pub min_importance: f32,
+    /// Only memories accessed fewer times than this are marked stale
+    pub min_access_count: i64,
+}
+
+impl Default for LifecycleRules {
+    fn default() -> Self {
+        Self {
+            stale_days: 30,
+            archive_days: 90,
+            min_importance: 0.5,
+            min_access_count: 5,
+        }
+    }
+}
+
+/// Options for [`simulate_policies`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicySimulationOptions {
+    /// Limit the simulation to one workspace
+    pub workspace: Option<String>,
+    /// Evaluate the lifecycle rule
+    pub lifecycle: bool,
+    /// Evaluate the TTL purge rule
+    pub ttl: bool,
+    /// Evaluate stored per-workspace retention policies
+    pub retention: bool,
+    /// Evaluate salience decay
+    pub salience: bool,
+    /// Lifecycle thresholds
+    pub lifecycle_rules: LifecycleRules,
+    /// Days an expired memory stays recoverable before it is purged
+    pub expired_grace_days: i64,
+    /// Salience decay configuration
+    pub salience_config: SalienceConfig,
+    /// Sample changes to include per workspace
+    pub sample_size: usize,
+}
+
+impl Default for PolicySimulationOptions {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            lifecycle: true,
+            ttl: true,
+            retention: true,
+            salience: true,
+            lifecycle_rules: LifecycleRules::default(),
+            expired_grace_days: DEFAULT_EXPIRED_GRACE_DAYS,
+            salience_config: SalienceConfig::default(),
+            sample_size: 10,
+        }
+    }
+}
+
+/// Outcome a rule would apply to a memory, in increasing severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulatedAction {
+    /// Lifecycle state would move to `stale`
+    Demote,
+    /// Lifecycle state would move to `archived`
+    Archive,
+    /// Memory would be soft-deleted
+    Delete,
+}
+
+/// Rule responsible for a simulated action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRule {
+    Lifecycle,
+    Ttl,
+    Retention,
+    SalienceDecay,
+}
+
+impl PolicyRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyRule::Lifecycle => "lifecycle",
+            PolicyRule::Ttl => "ttl",
+            PolicyRule::Retention => "retention",
+            PolicyRule::SalienceDecay => "salience_decay",
+        }
+    }
+}
+
+/// Demote/archive/delete counts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionCounts {
+    pub demote: usize,
+    pub archive: usize,
+    pub delete: usize,
+}
+
+impl ActionCounts {
+    fn add(&mut self, action: SimulatedAction) {
+        match action {
+            SimulatedAction::Demote => self.demote += 1,
+            SimulatedAction::Archive => self.archive += 1,
+            SimulatedAction::Delete => self.delete += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &ActionCounts) {
+        self.demote += other.demote;
+        self.archive += other.archive;
+        self.delete += other.delete;
+    }
+
+    pub fn total(&self) -> usize {
+        self.demote + self.archive + self.delete
+    }
+}
+
+/// One histogram bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub bucket: String,
+    /// Live memories in this bucket
+    pub memories: usize,
+    #[serde(flatten)]
+    pub actions: ActionCounts,
+}
+
+/// A memory that would change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedChange {
+    pub id: MemoryId,
+    pub action: SimulatedAction,
+    pub rules: Vec<PolicyRule>,
+    pub memory_type: String,
+    pub lifecycle_state: String,
+    pub age_days: i64,
+    pub preview: String,
+}
+
+/// Simulated impact on one workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceImpact {
+    pub workspace: String,
+    /// Live memories evaluated
+    pub memories: usize,
+    /// Memories affected, counted once under their most severe action
+    pub affected: ActionCounts,
+    /// Per-rule counts (a memory may appear under several rules)
+    pub by_rule: BTreeMap<String, ActionCounts>,
+    pub by_type: Vec<HistogramBucket>,
+    pub by_age: Vec<HistogramBucket>,
+    pub samples: Vec<SimulatedChange>,
+}
+
+/// Result of [`simulate_policies`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicySimulation {
+    pub simulated_at: String,
+    pub memories: usize,
+    pub affected: ActionCounts,
+    pub workspaces: Vec<WorkspaceImpact>,
+}
+
+struct Row {
+    id: MemoryId,
+    workspace: String,
+    memory_type: String,
+    importance: f32,
+    access_count: i64,
+    created_at: DateTime<Utc>,
+    last_accessed_at: Option<DateTime<Utc>>,
+    lifecycle_state: String,
+    expires_at: Option<DateTime<Utc>>,
+    preview: String,
+}
+
+fn parse_ts(value: Option<String>) -> Option<DateTime<Utc>> {
+    value.and_then(|s| {
+        DateTime::parse_from_rfc3339(&s)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+    })
+}
+
+fn age_bucket(age_days: i64) -> usize {
+    AGE_BUCKETS
+        .iter()
+        .position(|(_, upper)| age_days < *upper)
+        .unwrap_or(AGE_BUCKETS.len() - 1)
+}
+
+fn load_rows(conn: &Connection, workspace: Option<&str>, now: DateTime<Utc>) -> Result<Vec<Row>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, COALESCE(workspace, 'default'), memory_type, COALESCE(importance, 0.5),
+                access_count, created_at, last_accessed_at,
+                COALESCE(lifecycle_state, 'active'), expires_at, substr(content, 1, 120)
+         FROM memories
+         WHERE valid_to IS NULL AND (?1 IS NULL OR workspace = ?1)
+         ORDER BY id",
+    )?;
+
+    let rows = stmt
+        .query_map(params![workspace], |row| {
+            let content: String = row.get(9)?;
+            Ok(Row {
+                id: row.get(0)?,
+                workspace: row.get(1)?,
+                memory_type: row.get(2)?,
+                importance: row.get(3)?,
+                access_count: row.get(4)?,
+                created_at: parse_ts(row.get(5)?).unwrap_or(now),
+                last_accessed_at: parse_ts(row.get(6)?),
+                lifecycle_state: row.get(7)?,
+                expires_at: parse_ts(row.get(8)?),
+                preview: content.chars().take(50).collect(),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(rows)
+}
+
+/// Simulate every enabled policy and report the impact per workspace.
+///
+/// Read-only: nothing is written, so it is safe to run against production
+/// data before tightening thresholds.
+pub fn simulate_policies(
+    conn: &Connection,
+    options: &PolicySimulationOptions,
+) -> Result<PolicySimulation> {
+    let now = Utc::now();
+    let rows = load_rows(conn, options.workspace.as_deref(), now)?;
+
+    let mut hits: HashMap<MemoryId, Vec<(PolicyRule, SimulatedAction)>> = HashMap::new();
+    let mut hit = |id: MemoryId, rule: PolicyRule, action: SimulatedAction| {
+        hits.entry(id).or_default().push((rule, action));
+    };
+
+    let is_expired = |row: &Row| row.expires_at.is_some_and(|at| at <= now);
+
+    if options.lifecycle {
+        let rules = &options.lifecycle_rules;
+        let stale_cutoff = now - Duration::days(rules.stale_days);
+        let archive_cutoff = now - Duration::days(rules.archive_days);
+        for row in &rows {
+            if row.lifecycle_state == "active"
+                && row.created_at < stale_cutoff
+                && row.importance < rules.min_importance
+                && row.access_count < rules.min_access_count
+            {
+                hit(row.id, PolicyRule::Lifecycle, SimulatedAction::Demote);
+            } else if row.lifecycle_state == "stale" && row.created_at < archive_cutoff {
+                hit(row.id, PolicyRule::Lifecycle, SimulatedAction::Archive);
+            }
+        }
+    }
+
+    if options.ttl {
+        let purge_cutoff = now - Duration::days(options.expired_grace_days.max(0));
+        for row in &rows {
+            if row.expires_at.is_some_and(|at| at <= purge_cutoff) {
+                hit(row.id, PolicyRule::Ttl, SimulatedAction::Delete);
+            }
+        }
+    }
+
+    if options.retention {
+        for policy in list_retention_policies(conn)? {
+            let in_workspace: Vec<&Row> = rows
+                .iter()
+                .filter(|r| r.workspace == policy.workspace)
+                .collect();
+            let protected = |r: &Row| r.memory_type == "summary" || r.memory_type == "checkpoint";
+            let mut archived_now: HashSet<MemoryId> = HashSet::new();
+
+            // 1. Compression archives old, low-value originals
+            if let Some(days) = policy.compress_after_days {
+                let cutoff = now - Duration::days(days);
+                for row in &in_workspace {
+                    if row.lifecycle_state == "active"
+                        && !protected(row)
+                        && !is_expired(row)
+                        && row.created_at < cutoff
+                        && row.importance <= policy.compress_max_importance
+                        && row.access_count < policy.compress_min_access as i64
+                    {
+                        archived_now.insert(row.id);
+                        hit(row.id, PolicyRule::Retention, SimulatedAction::Archive);
+                    }
+                }
+            }
+
+            // 2. The cap archives the excess, least valuable first
+            if let Some(max_memories) = policy.max_memories {
+                let still_active = in_workspace
+                    .iter()
+                    .filter(|r| r.lifecycle_state == "active" && !archived_now.contains(&r.id))
+                    .count() as i64;
+                if still_active > max_memories {
+                    let mut eligible: Vec<&&Row> = in_workspace
+                        .iter()
+                        .filter(|r| {
+                            r.lifecycle_state == "active"
+                                && !protected(r)
+                                && !archived_now.contains(&r.id)
+                        })
+                        .collect();
+                    eligible.sort_by(|a, b| {
+                        a.importance
+                            .partial_cmp(&b.importance)
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                            .then(a.access_count.cmp(&b.access_count))
+                            .then(a.created_at.cmp(&b.created_at))
+                    });
+                    let excess = (still_active - max_memories) as usize;
+                    for row in eligible.into_iter().take(excess) {
+                        archived_now.insert(row.id);
+                        hit(row.id, PolicyRule::Retention, SimulatedAction::Archive);
+                    }
+                }
+            }
+
+            // 3. Auto-delete sees archives made earlier in the same pass
+            if let Some(days) = policy.auto_delete_after_days {
+                let cutoff = now - Duration::days(days);
+                for row in &in_workspace {
+                    if (row.lifecycle_state == "archived" || archived_now.contains(&row.id))
+                        && row.created_at < cutoff
+                    {
+                        hit(row.id, PolicyRule::Retention, SimulatedAction::Delete);
+                    }
+                }
+            }
+        }
+    }
+
+    if options.salience {
+        for row in &rows {
+            if row.lifecycle_state == "archived" || is_expired(row) {
+                continue;
+            }
+            let last_access = row.last_accessed_at.unwrap_or(row.created_at);
+            let outcome = evaluate_decay(
+                &options.salience_config,
+                row.importance,
+                row.access_count.clamp(0, i32::MAX as i64) as i32,
+                last_access,
+                now,
+            );
+            if outcome.state == row.lifecycle_state {
+                continue;
+            }
+            match outcome.state {
+                "stale" => hit(row.id, PolicyRule::SalienceDecay, SimulatedAction::Demote),
+                "archived" => hit(row.id, PolicyRule::SalienceDecay, SimulatedAction::Archive),
+                _ => {}
+            }
+        }
+    }
+
+    // Aggregate per workspace
+    struct Acc {
+        memories: usize,
+        affected: ActionCounts,
+        by_rule: BTreeMap<String, ActionCounts>,
+        by_type: BTreeMap<String, (usize, ActionCounts)>,
+        by_age: Vec<(usize, ActionCounts)>,
+        samples: Vec<SimulatedChange>,
+    }
+
+    let mut workspaces: BTreeMap<String, Acc> = BTreeMap::new();
+    for row in &rows {
+        let acc = workspaces
+            .entry(row.workspace.clone())
+            .or_insert_with(|| Acc {
+                memories: 0,
+                affected: ActionCounts::default(),
+                by_rule: BTreeMap::new(),
+                by_type: BTreeMap::new(),
+                by_age: vec![(0, ActionCounts::default()); AGE_BUCKETS.len()],
+                samples: Vec::new(),
+            });
+
+        let age_days = (now - row.created_at).num_days().max(0);
+        let age_idx = age_bucket(age_days);
+
+        acc.memories += 1;
+        acc.by_age[age_idx].0 += 1;
+        let type_entry = acc.by_type.entry(row.memory_type.clone()).or_default();
+        type_entry.0 += 1;
+
+        let Some(row_hits) = hits.get(&row.id) else {
+            continue;
+        };
+        for (rule, action) in row_hits {
+            acc.by_rule
+                .entry(rule.as_str().to_string())
+                .or_default()
+                .add(*action);
+        }
+
+        let action = row_hits
+            .iter()
+            .map(|(_, action)| *action)
+            .max()
+            .expect("hits are never empty");
+        acc.affected.add(action);
+        type_entry.1.add(action);
+        acc.by_age[age_idx].1.add(action);
+
+        if acc.samples.len() < options.sample_size {
+            let mut rules: Vec<PolicyRule> = Vec::new();
+            for (rule, _) in row_hits {
+                if !rules.contains(rule) {
+                    rules.push(*rule);
+                }
+            }
+            acc.samples.push(SimulatedChange {
+                id: row.id,
+                action,
+                rules,
+                memory_type: row.memory_type.clone(),
+                lifecycle_state: row.lifecycle_state.clone(),
+                age_days,
+                preview: row.preview.clone(),
+            });
+        }
+    }
+
+    let mut affected = ActionCounts::default();
+    let workspaces: Vec<WorkspaceImpact> = workspaces
+        .into_iter()
+        .map(|(workspace, acc)| {
+            affected.merge(&acc.affected);
+            WorkspaceImpact {
+                workspace,
+                memories: acc.memories,
+                affected: acc.affected,
+                by_rule: acc.by_rule,
+                by_type: acc
+                    .by_type
+                    .into_iter()
+                    .map(|(bucket, (memories, actions))| HistogramBucket {
+                        bucket,
+                        memories,
+                        actions,
+                    })
+                    .collect(),
+                by_age: AGE_BUCKETS
+                    .iter()
+                    .zip(acc.by_age)
+                    .map(|((label, _), (memories, actions))| HistogramBucket {
+                        bucket: label.to_string(),
+                        memories,
+                        actions,
+                    })
+                    .collect(),
+                samples: acc.samples,
+            }
+        })
+        .collect();
+
+    Ok(PolicySimulation {
+        simulated_at: now.to_rfc3339(),
+        memories: rows.len(),
+        affected,
+        workspaces,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_memory, set_retention_policy};
+    use crate::storage::Storage;
+    use crate::types::{CreateMemoryInput, MemoryType};
+
+    fn backdate(conn: &Connection, id: MemoryId, days: i64) {
+        let ts = (Utc::now() - Duration::days(days)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET created_at = ?1, last_accessed_at = ?1 WHERE id = ?2",
+            params![ts, id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_simulation_reports_without_changing_state() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let create = |content: &str, workspace: &str, importance: f32| {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            memory_type: MemoryType::Note,
+                            importance: Some(importance),
+                            workspace: Some(workspace.to_string()),
+                            ..Default::default()
+                        },
+                    )
+                    .unwrap()
+                    .id
+                };
+
+                let fresh = create("fresh and important", "alpha", 0.9);
+                let old = create("old low value note", "alpha", 0.1);
+                backdate(conn, old, 200);
+                let stale = create("already stale note", "beta", 0.8);
+                backdate(conn, stale, 120);
+                conn.execute(
+                    "UPDATE memories SET lifecycle_state = 'stale' WHERE id = ?",
+                    params![stale],
+                )?;
+                let expired = create("expired scratch", "beta", 0.5);
+                conn.execute(
+                    "UPDATE memories SET expires_at = ? WHERE id = ?",
+                    params![(Utc::now() - Duration::days(30)).to_rfc3339(), expired],
+                )?;
+                set_retention_policy(conn, "alpha", None, Some(1), None, None, None, None, None)?;
+
+                let report = simulate_policies(conn, &PolicySimulationOptions::default())?;
+                assert_eq!(report.memories, 4);
+                assert_eq!(report.workspaces.len(), 2);
+
+                let alpha = &report.workspaces[0];
+                assert_eq!(alpha.workspace, "alpha");
+                // Lifecycle demotes and salience decay archives the old note
+                assert_eq!(alpha.affected.archive, 1);
+                assert_eq!(alpha.by_rule["lifecycle"].demote, 1);
+                assert_eq!(alpha.by_rule["retention"].archive, 1);
+                assert!(alpha.samples.iter().all(|c| c.id != fresh));
+                let old_age = alpha.by_age.iter().find(|b| b.bucket == "90-365d").unwrap();
+                assert_eq!(old_age.memories, 1);
+                assert_eq!(old_age.actions.archive, 1);
+
+                let beta = &report.workspaces[1];
+                assert_eq!(beta.affected.delete, 1);
+                assert_eq!(beta.affected.archive, 1);
+                assert_eq!(beta.by_type[0].bucket, "note");
+                assert_eq!(beta.by_type[0].memories, 2);
+
+                // Nothing was written
+                let state: String = conn.query_row(
+                    "SELECT lifecycle_state FROM memories WHERE id = ?",
+                    params![old],
+                    |row| row.get(0),
+                )?;
+                assert_eq!(state, "active");
+
+                let lifecycle_only = simulate_policies(
+                    conn,
+                    &PolicySimulationOptions {
+                        workspace: Some("beta".to_string()),
+                        ttl: false,
+                        salience: false,
+                        retention: false,
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(lifecycle_only.memories, 2);
+                assert_eq!(lifecycle_only.affected.total(), 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -18,14 +18,13 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use crate::error::Result;
-use crate::types::{Memory, MemoryScope, MemoryTier, MemoryType, Visibility};
+use crate::types::{ContentHash, Memory, MemoryScope, MemoryTier, MemoryType, Visibility};
 
 // =============================================================================
 // Configuration
@@ -680,11 +679,9 @@ impl Default for ProjectContextEngine {
 // Helper Functions
 // =============================================================================
 
-/// Compute SHA-256 hash of content
+/// Exact hash of file or section content, so any edit registers as a change
 fn hash_content(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    format!("sha256:{}", hex::encode(hasher.finalize()))
+    ContentHash::of_bytes(content).into_string()
 }
 
 /// Parse a markdown heading line
@@ -0,0 +1,556 @@
+//! Usage-based memory recommendations ("you might need this")
+//!
+//! Ranks memories an agent has not retrieved yet by how likely it is to need
+//! them, given what the session is doing:
+//! - embedding similarity to the session's context (recent transcript
+//!   messages, active context, project) and to the memories it recently used
+//! - graph adjacency: cross-references from the recently used memories
+//!
+//! The server recomputes recommendations for active sessions periodically and
+//! announces new ones as `memory_suggested` realtime events;
+//! [`RecommendationTracker`] remembers what each session has been sent.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::{cosine_similarity, get_embedding, Embedder};
+use crate::error::{EngramError, Result};
+use crate::storage::queries::{get_related, peek_memory};
+use crate::types::{Memory, MemoryId};
+
+/// Transcript chunks read from an indexed session as "recent messages"
+const RECENT_CHUNKS: usize = 2;
+
+/// Longest context text embedded, in characters
+const MAX_CONTEXT_CHARS: usize = 4000;
+
+fn default_limit() -> usize {
+    5
+}
+
+fn default_recent_memories() -> usize {
+    20
+}
+
+fn default_min_score() -> f32 {
+    0.3
+}
+
+fn default_similarity_weight() -> f32 {
+    0.6
+}
+
+fn default_graph_weight() -> f32 {
+    0.4
+}
+
+fn default_max_candidates() -> usize {
+    5000
+}
+
+/// What to recommend for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationOptions {
+    /// Session whose context and used memories drive the recommendations
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Current context text (recent messages); defaults to the session's
+    /// active context and latest transcript chunks
+    #[serde(default)]
+    pub context: Option<String>,
+    /// Active project, added to the context
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Memories recently used outside a session
+    #[serde(default)]
+    pub used_ids: Vec<MemoryId>,
+    /// Memories never to recommend
+    #[serde(default)]
+    pub exclude_ids: Vec<MemoryId>,
+    /// Workspace to recommend from; defaults to the session's
+    #[serde(default)]
+    pub workspace: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Most recently used session memories taken as seeds
+    #[serde(default = "default_recent_memories")]
+    pub recent_memories: usize,
+    #[serde(default = "default_min_score")]
+    pub min_score: f32,
+    #[serde(default = "default_similarity_weight")]
+    pub similarity_weight: f32,
+    #[serde(default = "default_graph_weight")]
+    pub graph_weight: f32,
+    /// Most recently updated embedded memories compared
+    #[serde(default = "default_max_candidates")]
+    pub max_candidates: usize,
+}
+
+impl Default for RecommendationOptions {
+    fn default() -> Self {
+        Self {
+            session_id: None,
+            context: None,
+            project: None,
+            used_ids: Vec::new(),
+            exclude_ids: Vec::new(),
+            workspace: None,
+            limit: default_limit(),
+            recent_memories: default_recent_memories(),
+            min_score: default_min_score(),
+            similarity_weight: default_similarity_weight(),
+            graph_weight: default_graph_weight(),
+            max_candidates: default_max_candidates(),
+        }
+    }
+}
+
+/// A memory the agent probably needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub memory: Memory,
+    /// Weighted combination of `similarity` and `graph_score`, in [0, 1]
+    pub score: f32,
+    /// Mean similarity to the context and the closest used memory
+    pub similarity: f32,
+    /// Noisy-or of cross-reference weights from used memories
+    pub graph_score: f32,
+    /// Used memories this one is most similar or linked to
+    pub via: Vec<MemoryId>,
+    pub reasons: Vec<String>,
+}
+
+/// Recommendations with the inputs they were computed from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationSet {
+    pub session_id: Option<String>,
+    pub workspace: String,
+    /// Recently used memories the recommendations were seeded from
+    pub seeds: Vec<MemoryId>,
+    /// Whether a context text was embedded
+    pub used_context: bool,
+    pub recommendations: Vec<Recommendation>,
+}
+
+struct Candidate {
+    context_similarity: Option<f32>,
+    seed_similarity: Option<(f32, MemoryId)>,
+    /// Product of (1 - weight) over links from used memories
+    graph_miss: f32,
+    links: Vec<(MemoryId, String)>,
+}
+
+impl Default for Candidate {
+    fn default() -> Self {
+        Self {
+            context_similarity: None,
+            seed_similarity: None,
+            graph_miss: 1.0,
+            links: Vec::new(),
+        }
+    }
+}
+
+/// Session inputs: workspace, memories used (newest first), and text
+struct SessionInputs {
+    workspace: Option<String>,
+    used: Vec<MemoryId>,
+    transcript: Vec<MemoryId>,
+    context: Vec<String>,
+}
+
+fn session_inputs(conn: &Connection, session_id: &str) -> Result<SessionInputs> {
+    let session: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT workspace, context FROM sessions WHERE session_id = ?",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (workspace, active_context) = session
+        .ok_or_else(|| EngramError::InvalidInput(format!("Session not found: {}", session_id)))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT memory_id FROM session_memories WHERE session_id = ?
+         ORDER BY added_at DESC",
+    )?;
+    let used = stmt
+        .query_map(params![session_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<MemoryId>>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT memory_id FROM session_chunks WHERE session_id = ?
+         ORDER BY chunk_index DESC",
+    )?;
+    let transcript = stmt
+        .query_map(params![session_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<MemoryId>>>()?;
+
+    let mut context: Vec<String> = active_context.into_iter().collect();
+    for id in transcript.iter().take(RECENT_CHUNKS) {
+        if let Ok(chunk) = peek_memory(conn, *id) {
+            context.push(chunk.content);
+        }
+    }
+
+    Ok(SessionInputs {
+        workspace: Some(workspace),
+        used,
+        transcript,
+        context,
+    })
+}
+
+/// Recommend memories for a session or explicit context.
+///
+/// `embedder` embeds the context text; without it (or without a context)
+/// only used memories' embeddings and cross-references are used.
+pub fn recommend_memories(
+    conn: &Connection,
+    embedder: Option<&dyn Embedder>,
+    options: &RecommendationOptions,
+) -> Result<RecommendationSet> {
+    let mut inputs = match &options.session_id {
+        Some(session_id) => session_inputs(conn, session_id)?,
+        None => SessionInputs {
+            workspace: None,
+            used: Vec::new(),
+            transcript: Vec::new(),
+            context: Vec::new(),
+        },
+    };
+    if let Some(context) = &options.context {
+        // An explicit context replaces the stored one
+        inputs.context = vec![context.clone()];
+    }
+    inputs.context.extend(options.project.iter().cloned());
+    let workspace = options
+        .workspace
+        .clone()
+        .or(inputs.workspace)
+        .unwrap_or_else(|| "default".to_string());
+
+    let mut seeds: Vec<MemoryId> = Vec::new();
+    for id in options
+        .used_ids
+        .iter()
+        .chain(inputs.used.iter().take(options.recent_memories))
+    {
+        if !seeds.contains(id) {
+            seeds.push(*id);
+        }
+    }
+    let excluded: HashSet<MemoryId> = seeds
+        .iter()
+        .chain(&inputs.used)
+        .chain(&inputs.transcript)
+        .chain(&options.exclude_ids)
+        .copied()
+        .collect();
+
+    let context_text: String = inputs
+        .context
+        .join("\n")
+        .chars()
+        .take(MAX_CONTEXT_CHARS)
+        .collect();
+    let context_embedding = match embedder {
+        Some(embedder) if !context_text.trim().is_empty() => {
+            Some(embedder.embed_query(&context_text)?)
+        }
+        _ => None,
+    };
+    let seed_embeddings: Vec<(MemoryId, Vec<f32>)> = seeds
+        .iter()
+        .filter_map(|id| get_embedding(conn, *id).ok().flatten().map(|v| (*id, v)))
+        .collect();
+
+    let mut candidates: HashMap<MemoryId, Candidate> = HashMap::new();
+
+    if context_embedding.is_some() || !seed_embeddings.is_empty() {
+        let now = Utc::now().to_rfc3339();
+        let mut stmt = conn.prepare(
+            "SELECT id FROM memories
+             WHERE has_embedding = 1 AND valid_to IS NULL
+               AND (expires_at IS NULL OR expires_at > ?)
+               AND workspace = ? AND memory_type != 'transcript_chunk'
+             ORDER BY updated_at DESC LIMIT ?",
+        )?;
+        let ids = stmt
+            .query_map(
+                params![now, workspace, options.max_candidates as i64],
+                |row| row.get(0),
+            )?
+            .collect::<rusqlite::Result<Vec<MemoryId>>>()?;
+
+        for id in ids {
+            if excluded.contains(&id) {
+                continue;
+            }
+            let Some(vector) = get_embedding(conn, id)? else {
+                continue;
+            };
+            let candidate = candidates.entry(id).or_default();
+            if let Some(context) = &context_embedding {
+                candidate.context_similarity = Some(cosine_similarity(context, &vector).max(0.0));
+            }
+            for (seed, seed_vector) in &seed_embeddings {
+                let similarity = cosine_similarity(seed_vector, &vector).max(0.0);
+                if candidate
+                    .seed_similarity
+                    .is_none_or(|(best, _)| similarity > best)
+                {
+                    candidate.seed_similarity = Some((similarity, *seed));
+                }
+            }
+        }
+    }
+
+    for seed in &seeds {
+        for crossref in get_related(conn, *seed)? {
+            let neighbor = if crossref.from_id == *seed {
+                crossref.to_id
+            } else {
+                crossref.from_id
+            };
+            if excluded.contains(&neighbor) {
+                continue;
+            }
+            let weight = (crossref.score * crossref.strength * crossref.confidence).clamp(0.0, 1.0);
+            let candidate = candidates.entry(neighbor).or_default();
+            candidate.graph_miss *= 1.0 - weight;
+            candidate
+                .links
+                .push((*seed, crossref.edge_type.as_str().to_string()));
+        }
+    }
+
+    let total_weight = (options.similarity_weight + options.graph_weight).max(f32::EPSILON);
+    let mut scored: Vec<(MemoryId, f32, f32, f32)> = candidates
+        .iter()
+        .map(|(id, candidate)| {
+            let signals: Vec<f32> = candidate
+                .context_similarity
+                .into_iter()
+                .chain(candidate.seed_similarity.map(|(s, _)| s))
+                .collect();
+            let similarity = if signals.is_empty() {
+                0.0
+            } else {
+                signals.iter().sum::<f32>() / signals.len() as f32
+            };
+            let graph_score = 1.0 - candidate.graph_miss;
+            let score = (options.similarity_weight * similarity
+                + options.graph_weight * graph_score)
+                / total_weight;
+            (*id, score, similarity, graph_score)
+        })
+        .filter(|(_, score, _, _)| *score >= options.min_score)
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut recommendations = Vec::new();
+    for (id, score, similarity, graph_score) in scored {
+        if recommendations.len() >= options.limit {
+            break;
+        }
+        // Linked memories can sit in other workspaces or be gone
+        let Ok(memory) = peek_memory(conn, id) else {
+            continue;
+        };
+        if memory.workspace != workspace {
+            continue;
+        }
+        let candidate = &candidates[&id];
+        let mut via = Vec::new();
+        let mut reasons = Vec::new();
+        if let Some(similarity) = candidate.context_similarity {
+            reasons.push(format!(
+                "similar to the current context ({:.2})",
+                similarity
+            ));
+        }
+        if let Some((similarity, seed)) = candidate.seed_similarity {
+            reasons.push(format!("similar to memory {} ({:.2})", seed, similarity));
+            via.push(seed);
+        }
+        for (seed, edge_type) in &candidate.links {
+            reasons.push(format!("{} memory {}", edge_type, seed));
+            if !via.contains(seed) {
+                via.push(*seed);
+            }
+        }
+        recommendations.push(Recommendation {
+            memory,
+            score,
+            similarity,
+            graph_score,
+            via,
+            reasons,
+        });
+    }
+
+    Ok(RecommendationSet {
+        session_id: options.session_id.clone(),
+        workspace,
+        seeds,
+        used_context: context_embedding.is_some(),
+        recommendations,
+    })
+}
+
+/// Sessions not ended that were indexed or used memories since `since`
+pub fn active_sessions(conn: &Connection, since: DateTime<Utc>) -> Result<Vec<String>> {
+    let since = since.to_rfc3339();
+    let mut stmt = conn.prepare(
+        "SELECT s.session_id FROM sessions s
+         WHERE s.ended_at IS NULL
+           AND (s.last_indexed_at >= ?1
+                OR EXISTS (SELECT 1 FROM session_memories sm
+                           WHERE sm.session_id = s.session_id AND sm.added_at >= ?1))
+         ORDER BY s.session_id",
+    )?;
+    let sessions = stmt
+        .query_map(params![since], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(sessions)
+}
+
+/// Memories already suggested to each session, so periodic runs only
+/// announce new recommendations
+#[derive(Debug, Default)]
+pub struct RecommendationTracker {
+    sent: HashMap<String, HashSet<MemoryId>>,
+}
+
+impl RecommendationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the recommendations not yet sent to `session_id` and mark them
+    /// sent
+    pub fn take_new(
+        &mut self,
+        session_id: &str,
+        recommendations: Vec<Recommendation>,
+    ) -> Vec<Recommendation> {
+        let sent = self.sent.entry(session_id.to_string()).or_default();
+        recommendations
+            .into_iter()
+            .filter(|r| sent.insert(r.memory.id))
+            .collect()
+    }
+
+    /// Forget sessions no longer active
+    pub fn retain_sessions(&mut self, active: &[String]) {
+        self.sent
+            .retain(|session_id, _| active.contains(session_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::{store_embedding, TfIdfEmbedder};
+    use crate::intelligence::session_context::{
+        add_memory_to_session, create_session, ContextRole, CreateSessionInput,
+    };
+    use crate::storage::queries::{create_crossref, create_memory};
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+
+    #[test]
+    fn test_recommends_similar_and_linked_memories() {
+        let storage = Storage::open_in_memory().unwrap();
+        let embedder = TfIdfEmbedder::new(256);
+        storage
+            .with_transaction(|conn| {
+                let mut ids = Vec::new();
+                for content in [
+                    "Postgres connection pool is sized to 20",
+                    "Postgres connection pool exhaustion caused the outage",
+                    "Deploy runbook for the billing service",
+                    "Team lunch is on Fridays",
+                ] {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            ..Default::default()
+                        },
+                    )?;
+                    store_embedding(
+                        conn,
+                        memory.id,
+                        &embedder.embed(content)?,
+                        "tfidf",
+                        256,
+                        None,
+                        "now",
+                    )?;
+                    ids.push(memory.id);
+                }
+                create_crossref(
+                    conn,
+                    &CreateCrossRefInput {
+                        from_id: ids[0],
+                        to_id: ids[2],
+                        edge_type: EdgeType::RelatedTo,
+                        strength: None,
+                        source_context: None,
+                        pinned: false,
+                    },
+                )?;
+
+                create_session(
+                    conn,
+                    CreateSessionInput {
+                        session_id: Some("s1".to_string()),
+                        title: None,
+                        initial_context: None,
+                        workspace: None,
+                        metadata: Default::default(),
+                    },
+                )?;
+                add_memory_to_session(conn, "s1", ids[0], 1.0, ContextRole::Referenced)?;
+
+                let options = RecommendationOptions {
+                    session_id: Some("s1".to_string()),
+                    context: Some("why did the postgres pool run out".to_string()),
+                    min_score: 0.05,
+                    ..Default::default()
+                };
+                let set = recommend_memories(conn, Some(&embedder), &options)?;
+                assert_eq!(set.seeds, vec![ids[0]]);
+                assert!(set.used_context);
+                let recommended: Vec<MemoryId> =
+                    set.recommendations.iter().map(|r| r.memory.id).collect();
+                // The used memory itself is never recommended
+                assert!(!recommended.contains(&ids[0]));
+                assert!(recommended.contains(&ids[1]));
+                let linked = set
+                    .recommendations
+                    .iter()
+                    .find(|r| r.memory.id == ids[2])
+                    .unwrap();
+                assert!(linked.graph_score > 0.5);
+                assert_eq!(linked.via, vec![ids[0]]);
+
+                let mut tracker = RecommendationTracker::new();
+                let first = tracker.take_new("s1", set.recommendations.clone());
+                assert_eq!(first.len(), set.recommendations.len());
+                assert!(tracker.take_new("s1", set.recommendations).is_empty());
+
+                assert_eq!(
+                    active_sessions(conn, Utc::now() - chrono::Duration::hours(1))?,
+                    vec!["s1".to_string()]
+                );
+                Ok(())
+            })
+            .unwrap();
+    }
+}
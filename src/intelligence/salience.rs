@@ -21,6 +21,7 @@ use crate::types::{LifecycleState, Memory, MemoryId};
 
 /// Configuration for salience scoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SalienceConfig {
     /// Weight for recency component (0.0 - 1.0)
     pub recency_weight: f32,
@@ -314,6 +315,66 @@ impl SalienceCalculator {
 
 use std::collections::HashMap;
 
+/// Component scores and suggested lifecycle state from one decay pass
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DecayOutcome {
+    pub recency: f32,
+    pub frequency: f32,
+    pub score: f32,
+    pub state: &'static str,
+}
+
+/// Apply the decay rules to a single memory (feedback fixed at 0.5).
+///
+/// Shared by [`run_salience_decay_in_workspace`] and the policy simulator so
+/// both always agree on which memories would be demoted or archived.
+pub(crate) fn evaluate_decay(
+    config: &SalienceConfig,
+    importance: f32,
+    access_count: i32,
+    last_access: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> DecayOutcome {
+    // Calculate recency
+    let days_since_access = (now - last_access).num_hours() as f32 / 24.0;
+    let recency = 0.5_f32.powf(days_since_access / config.recency_half_life_days);
+
+    // Calculate frequency
+    let count = access_count.max(0) as f32;
+    let frequency = if count <= 0.0 {
+        0.1
+    } else {
+        let log_count = (count + 1.0).log(config.frequency_log_base);
+        let log_max = (config.frequency_max_count as f32 + 1.0).log(config.frequency_log_base);
+        (log_count / log_max).min(1.0)
+    };
+
+    // Calculate overall score (using 0.5 as default feedback)
+    let score = (recency * config.recency_weight
+        + frequency * config.frequency_weight
+        + importance * config.importance_weight
+        + 0.5 * config.feedback_weight)
+        .max(config.min_salience)
+        .min(1.0);
+
+    // Determine suggested state
+    let days_inactive = (now - last_access).num_days();
+    let state = if score < 0.2 && days_inactive >= config.archive_threshold_days {
+        "archived"
+    } else if score < 0.4 || days_inactive >= config.stale_threshold_days {
+        "stale"
+    } else {
+        "active"
+    };
+
+    DecayOutcome {
+        recency,
+        frequency,
+        score,
+        state,
+    }
+}
+
 /// Run decay on all memories and update lifecycle states
 pub fn run_salience_decay(
     conn: &Connection,
@@ -411,38 +472,13 @@ pub fn run_salience_decay_in_workspace(
                 .ok()
         });
 
-        // Calculate recency
         let last_access = last_accessed_at.unwrap_or(created_at);
-        let days_since_access = (now - last_access).num_hours() as f32 / 24.0;
-        let recency = 0.5_f32.powf(days_since_access / config.recency_half_life_days);
-
-        // Calculate frequency
-        let count = access_count.max(0) as f32;
-        let frequency = if count <= 0.0 {
-            0.1
-        } else {
-            let log_count = (count + 1.0).log(config.frequency_log_base);
-            let log_max = (config.frequency_max_count as f32 + 1.0).log(config.frequency_log_base);
-            (log_count / log_max).min(1.0)
-        };
-
-        // Calculate overall score (using 0.5 as default feedback)
-        let score = (recency * config.recency_weight
-            + frequency * config.frequency_weight
-            + importance * config.importance_weight
-            + 0.5 * config.feedback_weight)
-            .max(config.min_salience)
-            .min(1.0);
-
-        // Determine suggested state
-        let days_inactive = (now - last_access).num_days();
-        let new_state = if score < 0.2 && days_inactive >= config.archive_threshold_days {
-            "archived"
-        } else if score < 0.4 || days_inactive >= config.stale_threshold_days {
-            "stale"
-        } else {
-            "active"
-        };
+        let DecayOutcome {
+            recency,
+            frequency,
+            state: new_state,
+            score,
+        } = evaluate_decay(config, importance, access_count, last_access, now);
 
         // Update state if changed
         if new_state != current_state {
@@ -603,15 +639,12 @@ pub fn boost_memory_salience(
     let now = Utc::now().to_rfc3339();
     let boost = boost_amount.clamp(0.0, 0.5); // Max boost of 0.5
 
-    // Update and return new importance
-    conn.execute(
-        "UPDATE memories SET importance = MIN(1.0, importance + ?), updated_at = ? WHERE id = ?",
-        params![boost, now, memory_id],
-    )?;
-
+    // Update and read back in one statement so a concurrent write can't land
+    // between the two
     let new_importance: f32 = conn.query_row(
-        "SELECT importance FROM memories WHERE id = ?",
-        params![memory_id],
+        "UPDATE memories SET importance = MIN(1.0, MAX(0.0, importance + ?)), updated_at = ?
+         WHERE id = ? RETURNING importance",
+        params![boost, now, memory_id],
         |row| row.get(0),
     )?;
 
@@ -627,14 +660,10 @@ pub fn demote_memory_salience(
     let now = Utc::now().to_rfc3339();
     let demote = demote_amount.clamp(0.0, 0.5); // Max demote of 0.5
 
-    conn.execute(
-        "UPDATE memories SET importance = MAX(0.0, importance - ?), updated_at = ? WHERE id = ?",
-        params![demote, now, memory_id],
-    )?;
-
     let new_importance: f32 = conn.query_row(
-        "SELECT importance FROM memories WHERE id = ?",
-        params![memory_id],
+        "UPDATE memories SET importance = MAX(0.0, MIN(1.0, importance - ?)), updated_at = ?
+         WHERE id = ? RETURNING importance",
+        params![demote, now, memory_id],
         |row| row.get(0),
     )?;
 
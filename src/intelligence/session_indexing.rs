@@ -1,22 +1,36 @@
 //! Session transcript indexing with dual-limiter chunking
 //!
 //! Implements conversation indexing with:
-//! - Dual-limiter chunking (messages + characters)
+//! - Dual-limiter chunking (messages + characters), plus an optional token
+//!   limit counted with the session's tokenizer
 //! - Overlap preservation for context continuity
 //! - Delta updates for incremental indexing
 //! - TranscriptChunk memory type with 7-day default TTL
+//! - Soft message schema: system prompts, tool calls and tool results are
+//!   rendered and budgeted separately from conversational text
+//! - Optional de-noise pass before chunking, with removed-content counts
+//!   kept on the session record
+//! - Generated session title and summary, stored on the session row and as
+//!   a searchable summary memory
 //!
 //! Based on Fix 6 from the design plan:
 //! > Dual-limiter chunking algorithm with max_messages AND max_chars
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{EngramError, Result};
-use crate::storage::queries::create_memory;
+use crate::intelligence::compression::{parse_encoding, TiktokenCounter};
+use crate::intelligence::importance::{auto_importance, ImportanceSignals, ImportanceSource};
+use crate::intelligence::session_summary::{
+    ExtractiveSummarizer, SessionSummarizer, SessionSummary,
+};
+use crate::intelligence::transcript_denoise::{denoise_transcript, DenoiseConfig, DenoiseStats};
+use crate::storage::queries::{create_memory, delete_memory};
 use crate::types::{CreateMemoryInput, MemoryTier, MemoryType};
 
 /// Configuration for conversation chunking
@@ -30,6 +44,61 @@ pub struct ChunkingConfig {
     pub max_chars: usize,
     /// Default TTL for transcript chunks in seconds (default: 7 days)
     pub default_ttl_seconds: i64,
+    /// Maximum characters kept from a system prompt (default: 1000)
+    pub max_system_chars: usize,
+    /// Maximum characters of a tool call's argument summary (default: 300)
+    pub max_argument_chars: usize,
+    /// Maximum characters kept from a tool result (default: 2000)
+    pub max_tool_result_chars: usize,
+    /// Optional token limit per chunk, on top of the character limit
+    pub token_limit: Option<TokenLimit>,
+    /// Optional de-noise pass run on messages before chunking
+    pub denoise: Option<DenoiseConfig>,
+    /// Generates the session title and summary (default: extractive)
+    pub summarizer: Option<Arc<dyn SessionSummarizer>>,
+}
+
+/// Token budget for a chunk and the tokenizer that measures it
+#[derive(Debug, Clone, Copy)]
+pub struct TokenLimit {
+    pub max_tokens: usize,
+    pub counter: TiktokenCounter,
+}
+
+impl TokenLimit {
+    /// Session metadata key recording the encoding chunks were measured with
+    pub const ENCODING_KEY: &'static str = "token_encoding";
+    /// Session metadata key recording the per-chunk token limit
+    pub const MAX_TOKENS_KEY: &'static str = "max_tokens";
+
+    /// The limit recorded in a session's metadata by an earlier indexing run
+    pub fn from_session_metadata(
+        metadata: &HashMap<String, serde_json::Value>,
+    ) -> Result<Option<Self>> {
+        let max_tokens = metadata.get(Self::MAX_TOKENS_KEY).and_then(|v| v.as_u64());
+        let encoding = metadata
+            .get(Self::ENCODING_KEY)
+            .and_then(|v| v.as_str())
+            .and_then(parse_encoding);
+        match (max_tokens, encoding) {
+            (Some(max_tokens), Some(encoding)) => Ok(Some(Self {
+                max_tokens: max_tokens as usize,
+                counter: TiktokenCounter::new(encoding)?,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    fn record(&self, metadata: &mut HashMap<String, serde_json::Value>) {
+        metadata.insert(
+            Self::ENCODING_KEY.to_string(),
+            serde_json::json!(self.counter.encoding().as_str()),
+        );
+        metadata.insert(
+            Self::MAX_TOKENS_KEY.to_string(),
+            serde_json::json!(self.max_tokens),
+        );
+    }
 }
 
 impl Default for ChunkingConfig {
@@ -39,6 +108,67 @@ impl Default for ChunkingConfig {
             overlap_messages: 2,
             max_chars: 8000,
             default_ttl_seconds: 7 * 24 * 60 * 60, // 7 days
+            max_system_chars: 1000,
+            max_argument_chars: 300,
+            max_tool_result_chars: 2000,
+            token_limit: None,
+            denoise: None,
+            summarizer: None,
+        }
+    }
+}
+
+/// What a message carries, independent of who sent it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    /// Conversational text
+    #[default]
+    Text,
+    /// System or developer prompt
+    System,
+    /// A request to call a tool, with its arguments
+    ToolCall,
+    /// Output returned by a tool
+    ToolResult,
+}
+
+impl MessageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageKind::Text => "text",
+            MessageKind::System => "system",
+            MessageKind::ToolCall => "tool_call",
+            MessageKind::ToolResult => "tool_result",
+        }
+    }
+
+    /// Best guess for a message that doesn't state its kind: `system` and
+    /// `developer` roles are prompts, `tool` and `function` roles are
+    /// results, and anything else with arguments is a tool call.
+    pub fn infer(role: &str, has_arguments: bool) -> Self {
+        match role.to_ascii_lowercase().as_str() {
+            "system" | "developer" => MessageKind::System,
+            "tool" | "function" => MessageKind::ToolResult,
+            _ if has_arguments => MessageKind::ToolCall,
+            _ => MessageKind::Text,
+        }
+    }
+}
+
+impl std::str::FromStr for MessageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(MessageKind::Text),
+            "system" => Ok(MessageKind::System),
+            "tool_call" => Ok(MessageKind::ToolCall),
+            "tool_result" => Ok(MessageKind::ToolResult),
+            _ => Err(format!(
+                "Invalid message kind '{}': expected text, system, tool_call or tool_result",
+                s
+            )),
         }
     }
 }
@@ -46,15 +176,54 @@ impl Default for ChunkingConfig {
 /// A message in a conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
-    /// Message role (user, assistant, system)
+    /// Message role (user, assistant, system, tool)
     pub role: String,
     /// Message content
+    #[serde(default)]
     pub content: String,
     /// Message timestamp
     #[serde(default = "Utc::now")]
     pub timestamp: DateTime<Utc>,
     /// Optional message ID
     pub id: Option<String>,
+    /// What the message carries
+    #[serde(default)]
+    pub kind: MessageKind,
+    /// Tool called, or whose result this is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// Structured tool-call arguments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+}
+
+impl Default for Message {
+    fn default() -> Self {
+        Self {
+            role: String::new(),
+            content: String::new(),
+            timestamp: Utc::now(),
+            id: None,
+            kind: MessageKind::default(),
+            tool_name: None,
+            arguments: None,
+        }
+    }
+}
+
+/// Role-aware filters for transcript search. A chunk matches when it holds at
+/// least one message satisfying every filter that is set.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptFilter {
+    pub role: Option<String>,
+    pub kind: Option<MessageKind>,
+    pub tool_name: Option<String>,
+}
+
+impl TranscriptFilter {
+    pub fn is_empty(&self) -> bool {
+        self.role.is_none() && self.kind.is_none() && self.tool_name.is_none()
+    }
 }
 
 /// A chunk of conversation messages
@@ -72,6 +241,8 @@ pub struct ConversationChunk {
     pub content: String,
     /// Character count
     pub char_count: usize,
+    /// Token count, when chunking with a token limit
+    pub token_count: Option<usize>,
 }
 
 /// Session information
@@ -93,10 +264,20 @@ pub struct Session {
     pub chunk_count: i64,
     /// Workspace for the session
     pub workspace: String,
+    /// Summary of the session (generated at index time unless set manually)
+    #[serde(default)]
+    pub summary: Option<String>,
     /// Additional metadata
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl Session {
+    /// Counts of content removed by de-noising, if the session was de-noised
+    pub fn denoise_stats(&self) -> Option<DenoiseStats> {
+        DenoiseStats::from_session_metadata(&self.metadata)
+    }
+}
+
 /// Chunk a conversation using the dual-limiter algorithm.
 ///
 /// The algorithm ensures chunks respect both message count AND character limits:
@@ -105,6 +286,14 @@ pub struct Session {
 /// 3. If a single message exceeds max_chars, truncate it with marker
 /// 4. Close the chunk and start a new one with overlap
 ///
+/// With a [`TokenLimit`], tokens are a third limit: messages are measured
+/// with the configured tokenizer, a message over the limit on its own is
+/// truncated to fit, and every chunk's content stays within `max_tokens`.
+///
+/// Before the limits are checked, system prompts and tool results are cut to
+/// their own budgets and tool-call arguments are summarized. A tool call that
+/// would end a chunk moves to the next one so it stays with its result.
+///
 /// # Arguments
 /// - `messages`: The conversation messages to chunk
 /// - `config`: Chunking configuration
@@ -120,46 +309,78 @@ pub fn chunk_conversation(messages: &[Message], config: &ChunkingConfig) -> Vec<
     let mut chunk_start = 0;
 
     while chunk_start < messages.len() {
-        let mut current_messages = Vec::new();
+        let mut current_messages: Vec<Message> = Vec::new();
         let mut current_chars = 0;
+        let mut current_tokens = 0;
         let mut i = chunk_start;
 
         // Build chunk until we hit a limit
         while i < messages.len() {
             let msg = &messages[i];
-            let msg_chars = msg.content.len();
+            let body = message_body(msg, config);
 
             // Handle very long messages - truncate with marker
-            let (content, chars) = if msg_chars > config.max_chars {
-                let truncated = truncate_with_marker(&msg.content, config.max_chars);
-                (truncated.clone(), truncated.len())
+            let mut content = if body.len() > config.max_chars {
+                truncate_with_marker(&body, config.max_chars)
             } else {
-                (msg.content.clone(), msg_chars)
+                body
+            };
+            let tokens = match &config.token_limit {
+                Some(limit) => {
+                    let label = format!("[{}]: ", message_label(msg));
+                    content = truncate_to_tokens(&content, &label, limit);
+                    limit.counter.count(&format!("{}{}", label, content)) + SEPARATOR_TOKENS
+                }
+                None => 0,
             };
+            let chars = content.len();
 
             // Check if adding this message would exceed limits
             let would_exceed_chars =
                 current_chars + chars > config.max_chars && !current_messages.is_empty();
             let would_exceed_messages = current_messages.len() >= config.max_messages;
+            let would_exceed_tokens = config.token_limit.as_ref().is_some_and(|limit| {
+                current_tokens + tokens > limit.max_tokens && !current_messages.is_empty()
+            });
 
-            if would_exceed_chars || would_exceed_messages {
+            if would_exceed_chars || would_exceed_messages || would_exceed_tokens {
+                // Don't separate a tool call from the result that follows it
+                let splits_tool_call = msg.kind == MessageKind::ToolResult
+                    && current_messages.len() > 1
+                    && current_messages.last().map(|m| m.kind) == Some(MessageKind::ToolCall);
+                if splits_tool_call {
+                    current_messages.pop();
+                    i -= 1;
+                }
                 break;
             }
 
             // Add message to chunk
             current_messages.push(Message {
-                role: msg.role.clone(),
                 content,
-                timestamp: msg.timestamp,
-                id: msg.id.clone(),
+                ..msg.clone()
             });
             current_chars += chars;
+            current_tokens += tokens;
             i += 1;
         }
 
         // Create chunk if we have messages
         if !current_messages.is_empty() {
-            let chunk_content = format_chunk_content(&current_messages);
+            let mut chunk_content = format_chunk_content(&current_messages);
+            let mut token_count = None;
+            if let Some(limit) = &config.token_limit {
+                // Per-message counts only approximate the joined text, so
+                // measure it and give back messages until it fits.
+                let mut count = limit.counter.count(&chunk_content);
+                while count > limit.max_tokens && current_messages.len() > 1 {
+                    current_messages.pop();
+                    i -= 1;
+                    chunk_content = format_chunk_content(&current_messages);
+                    count = limit.counter.count(&chunk_content);
+                }
+                token_count = Some(count);
+            }
             chunks.push(ConversationChunk {
                 chunk_index: chunks.len(),
                 start_index: chunk_start,
@@ -167,6 +388,7 @@ pub fn chunk_conversation(messages: &[Message], config: &ChunkingConfig) -> Vec<
                 messages: current_messages,
                 content: chunk_content.clone(),
                 char_count: chunk_content.len(),
+                token_count,
             });
         }
 
@@ -184,6 +406,67 @@ pub fn chunk_conversation(messages: &[Message], config: &ChunkingConfig) -> Vec<
     chunks
 }
 
+/// The text indexed for a message, cut to the budget for its kind
+fn message_body(msg: &Message, config: &ChunkingConfig) -> String {
+    match msg.kind {
+        MessageKind::Text => msg.content.clone(),
+        MessageKind::System => truncate_with_marker(&msg.content, config.max_system_chars),
+        MessageKind::ToolResult => truncate_with_marker(&msg.content, config.max_tool_result_chars),
+        MessageKind::ToolCall => {
+            let arguments = msg
+                .arguments
+                .as_ref()
+                .map(|args| summarize_arguments(args, config.max_argument_chars))
+                .unwrap_or_default();
+            match (msg.content.trim(), arguments.is_empty()) {
+                ("", _) => arguments,
+                (content, true) => content.to_string(),
+                (content, false) => format!("{}\n{}", content, arguments),
+            }
+        }
+    }
+}
+
+/// One-line summary of tool-call arguments: `key=value` pairs for objects,
+/// with long strings shortened and nested values reduced to their shape
+fn summarize_arguments(arguments: &serde_json::Value, max_chars: usize) -> String {
+    use serde_json::Value;
+
+    fn shorten(s: &str, max: usize) -> String {
+        if s.chars().count() <= max {
+            s.to_string()
+        } else {
+            let mut out: String = s.chars().take(max.saturating_sub(1)).collect();
+            out.push('…');
+            out
+        }
+    }
+
+    fn value_summary(value: &Value) -> String {
+        match value {
+            Value::String(s) => format!("{:?}", shorten(s, 60)),
+            Value::Array(items) => format!("[{} items]", items.len()),
+            Value::Object(map) => {
+                let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+                format!("{{{}}}", keys.join(", "))
+            }
+            other => other.to_string(),
+        }
+    }
+
+    let summary = match arguments {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value_summary(value)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    shorten(&summary, max_chars)
+}
+
 /// Truncate content with a marker preserving head and tail
 fn truncate_with_marker(content: &str, max_chars: usize) -> String {
     if content.len() <= max_chars {
@@ -192,7 +475,7 @@ fn truncate_with_marker(content: &str, max_chars: usize) -> String {
 
     // Preserve 60% head, 30% tail, 10% for marker
     let marker = "\n[...truncated...]\n";
-    let available = max_chars - marker.len();
+    let available = max_chars.saturating_sub(marker.len());
     let head_len = (available * 60) / 100;
     let tail_len = available - head_len;
 
@@ -209,15 +492,68 @@ fn truncate_with_marker(content: &str, max_chars: usize) -> String {
     format!("{}{}{}", head, marker, tail)
 }
 
+/// Tokens assumed for the blank line joining two messages in a chunk
+const SEPARATOR_TOKENS: usize = 1;
+
+/// Shorten `content` (head and tail kept) until `label` plus `content` fits
+/// in the token limit
+fn truncate_to_tokens(content: &str, label: &str, limit: &TokenLimit) -> String {
+    let budget = limit.max_tokens.saturating_sub(SEPARATOR_TOKENS);
+    let mut tokens = limit.counter.count(&format!("{}{}", label, content));
+    if tokens <= budget {
+        return content.to_string();
+    }
+
+    let mut max_chars = content.len();
+    let mut truncated = content.to_string();
+    while tokens > budget && max_chars > 0 {
+        // Scale by the overshoot, and always shrink by at least a tenth
+        let scaled = max_chars * budget / tokens.max(1);
+        max_chars = scaled.min(max_chars * 9 / 10);
+        truncated = truncate_with_marker(content, max_chars);
+        tokens = limit.counter.count(&format!("{}{}", label, truncated));
+    }
+    truncated
+}
+
+/// Label a message by role, and for tool traffic by kind and tool
+fn message_label(msg: &Message) -> String {
+    let tool = msg.tool_name.as_deref().unwrap_or("unknown");
+    match msg.kind {
+        MessageKind::Text | MessageKind::System => msg.role.clone(),
+        MessageKind::ToolCall => format!("{} tool_call {}", msg.role, tool),
+        MessageKind::ToolResult => format!("tool_result {}", tool),
+    }
+}
+
 /// Format chunk messages into a single content string
 fn format_chunk_content(messages: &[Message]) -> String {
     messages
         .iter()
-        .map(|m| format!("[{}]: {}", m.role, m.content))
+        .map(|m| format!("[{}]: {}", message_label(m), m.content))
         .collect::<Vec<_>>()
         .join("\n\n")
 }
 
+/// Distinct (role, kind, tool) combinations in a chunk, stored in the chunk's
+/// metadata as `message_facets` for [`TranscriptFilter`] matching
+fn message_facets(messages: &[Message]) -> serde_json::Value {
+    let mut facets: Vec<(String, MessageKind, Option<&str>)> = messages
+        .iter()
+        .map(|m| (m.role.to_lowercase(), m.kind, m.tool_name.as_deref()))
+        .collect();
+    facets.sort_by(|a, b| (&a.0, a.1.as_str(), a.2).cmp(&(&b.0, b.1.as_str(), b.2)));
+    facets.dedup();
+    serde_json::Value::Array(
+        facets
+            .into_iter()
+            .map(|(role, kind, tool_name)| {
+                serde_json::json!({"role": role, "kind": kind, "tool_name": tool_name})
+            })
+            .collect(),
+    )
+}
+
 /// Index a full conversation into memory chunks.
 ///
 /// Creates TranscriptChunk memories with 7-day TTL by default.
@@ -229,7 +565,8 @@ fn format_chunk_content(messages: &[Message]) -> String {
 /// - `messages`: The conversation messages
 /// - `config`: Chunking configuration
 /// - `workspace`: Optional workspace (default: "default")
-/// - `title`: Optional session title
+/// - `title`: Optional session title; generated by the config's summarizer
+///   when neither given nor set earlier
 /// - `agent_id`: Optional agent identifier
 ///
 /// # Returns
@@ -246,17 +583,25 @@ pub fn index_conversation(
     let now = Utc::now();
     let workspace = workspace.unwrap_or("default");
 
-    // Chunk the conversation
-    let chunks = chunk_conversation(messages, config);
+    // Drop noise, then chunk what's left
+    let denoised = match &config.denoise {
+        Some(denoise) => Some(denoise_transcript(messages, denoise)?),
+        None => None,
+    };
+    let kept_messages = denoised.as_ref().map_or(messages, |d| &d.messages[..]);
+    let chunks = chunk_conversation(kept_messages, config);
 
     if chunks.is_empty() {
-        return Err(EngramError::InvalidInput(
-            "No messages to index".to_string(),
-        ));
+        let reason = if messages.is_empty() {
+            "No messages to index"
+        } else {
+            "All messages were removed as noise"
+        };
+        return Err(EngramError::InvalidInput(reason.to_string()));
     }
 
     // Store the last N messages as overlap for future delta updates
-    let overlap_messages: Vec<&Message> = messages
+    let overlap_messages: Vec<&Message> = kept_messages
         .iter()
         .rev()
         .take(config.overlap_messages)
@@ -270,6 +615,119 @@ pub fn index_conversation(
         "overlap_messages".to_string(),
         serde_json::to_value(&overlap_messages).unwrap_or_default(),
     );
+    if let Some(limit) = &config.token_limit {
+        limit.record(&mut session_metadata);
+    }
+    if let (Some(denoise), Some(denoised)) = (&config.denoise, &denoised) {
+        session_metadata.insert(
+            DenoiseConfig::METADATA_KEY.to_string(),
+            serde_json::to_value(denoise)?,
+        );
+        session_metadata.insert(
+            DenoiseStats::METADATA_KEY.to_string(),
+            serde_json::to_value(&denoised.stats)?,
+        );
+    }
+
+    // Title and summary: a caller-provided title and manually set values
+    // win over generated ones
+    let previous: Option<(Option<String>, Option<String>, String)> = conn
+        .query_row(
+            "SELECT title, summary, metadata FROM sessions WHERE session_id = ?",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let (previous_title, previous_summary, previous_metadata) = match previous {
+        Some((title, summary, metadata)) => (
+            title,
+            summary,
+            serde_json::from_str::<HashMap<String, serde_json::Value>>(&metadata)
+                .unwrap_or_default(),
+        ),
+        None => (None, None, HashMap::new()),
+    };
+    let was_generated = |field: &str| {
+        previous_metadata
+            .get(GENERATED_KEY)
+            .and_then(|g| g.get(field))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    };
+    let manual_title = previous_title.clone().filter(|_| !was_generated("title"));
+    let manual_summary = previous_summary
+        .clone()
+        .filter(|_| !was_generated("summary"));
+    let generated = generate_summary(kept_messages, config);
+    let title_generated = title.is_none() && manual_title.is_none() && generated.is_some();
+    let summary_generated = manual_summary.is_none() && generated.is_some();
+    let title = title
+        .map(String::from)
+        .or(manual_title)
+        .or_else(|| generated.as_ref().map(|(g, _)| g.title.clone()))
+        .or(previous_title);
+    let summary = manual_summary
+        .or_else(|| generated.as_ref().map(|(g, _)| g.summary.clone()))
+        .or(previous_summary);
+    if let Some((_, source)) = &generated {
+        session_metadata.insert(
+            GENERATED_KEY.to_string(),
+            serde_json::json!({
+                "title": title_generated,
+                "summary": summary_generated,
+                "source": source,
+            }),
+        );
+    }
+
+    // Keep one summary memory per session so title and summary are searchable
+    if let Some(old_id) = previous_metadata
+        .get(SUMMARY_MEMORY_KEY)
+        .and_then(|v| v.as_i64())
+    {
+        // Already gone if someone deleted it by hand
+        let _ = delete_memory(conn, old_id);
+    }
+    if let Some(summary) = &summary {
+        let content = match &title {
+            Some(title) => format!("{}\n\n{}", title, summary),
+            None => summary.clone(),
+        };
+        let importance = auto_importance(&ImportanceSignals::new(
+            ImportanceSource::SessionSummary,
+            MemoryType::Summary,
+            &content,
+        ));
+        let memory = create_memory(
+            conn,
+            &CreateMemoryInput {
+                content,
+                memory_type: MemoryType::Summary,
+                tags: vec![
+                    "session_summary".to_string(),
+                    format!("session:{}", session_id),
+                ],
+                metadata: HashMap::from([(
+                    "session_id".to_string(),
+                    serde_json::json!(session_id),
+                )]),
+                importance: Some(importance),
+                scope: Default::default(),
+                workspace: Some(workspace.to_string()),
+                tier: MemoryTier::Permanent,
+                defer_embedding: false,
+                ttl_seconds: None,
+                dedup_mode: Default::default(),
+                dedup_threshold: None,
+                event_time: None,
+                event_duration_seconds: None,
+                trigger_pattern: None,
+                summary_of_id: None,
+                media_url: None,
+            },
+        )?;
+        session_metadata.insert(SUMMARY_MEMORY_KEY.to_string(), serde_json::json!(memory.id));
+    }
     let metadata_json = serde_json::to_string(&session_metadata)?;
 
     // Create or update session record
@@ -278,14 +736,15 @@ pub fn index_conversation(
     conn.execute(
         r#"
         INSERT INTO sessions (session_id, title, agent_id, started_at, last_indexed_at,
-                             message_count, chunk_count, workspace, metadata)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                             message_count, chunk_count, workspace, metadata, summary)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(session_id) DO UPDATE SET
-            title = COALESCE(excluded.title, sessions.title),
+            title = excluded.title,
             last_indexed_at = excluded.last_indexed_at,
             message_count = excluded.message_count,
             chunk_count = excluded.chunk_count,
-            metadata = excluded.metadata
+            metadata = excluded.metadata,
+            summary = excluded.summary
         "#,
         params![
             session_id,
@@ -297,6 +756,7 @@ pub fn index_conversation(
             chunks.len() as i64,
             workspace,
             metadata_json,
+            summary,
         ],
     )?;
 
@@ -308,31 +768,43 @@ pub fn index_conversation(
 
     // Create memory for each chunk
     for chunk in &chunks {
+        // Message positions refer to the transcript as given, not what
+        // survived de-noising
+        let (start_index, end_index) = match &denoised {
+            Some(d) => (d.kept[chunk.start_index], d.kept[chunk.end_index - 1] + 1),
+            None => (chunk.start_index, chunk.end_index),
+        };
+
         let mut metadata = HashMap::new();
         metadata.insert("session_id".to_string(), serde_json::json!(session_id));
         metadata.insert(
             "chunk_index".to_string(),
             serde_json::json!(chunk.chunk_index),
         );
-        metadata.insert(
-            "start_message".to_string(),
-            serde_json::json!(chunk.start_index),
-        );
-        metadata.insert(
-            "end_message".to_string(),
-            serde_json::json!(chunk.end_index),
-        );
+        metadata.insert("start_message".to_string(), serde_json::json!(start_index));
+        metadata.insert("end_message".to_string(), serde_json::json!(end_index));
         metadata.insert(
             "message_count".to_string(),
             serde_json::json!(chunk.messages.len()),
         );
+        metadata.insert(
+            "message_facets".to_string(),
+            message_facets(&chunk.messages),
+        );
+        if let Some(tokens) = chunk.token_count {
+            metadata.insert("token_count".to_string(), serde_json::json!(tokens));
+        }
 
         let input = CreateMemoryInput {
             content: chunk.content.clone(),
             memory_type: MemoryType::TranscriptChunk,
             tags: vec!["transcript".to_string(), format!("session:{}", session_id)],
             metadata,
-            importance: Some(0.3), // Lower importance for transcript chunks
+            importance: Some(auto_importance(&ImportanceSignals::new(
+                ImportanceSource::TranscriptChunk,
+                MemoryType::TranscriptChunk,
+                &chunk.content,
+            ))),
             scope: Default::default(),
             workspace: Some(workspace.to_string()),
             tier: MemoryTier::Daily, // Transcript chunks are ephemeral by default
@@ -360,8 +832,8 @@ pub fn index_conversation(
                 session_id,
                 memory.id,
                 chunk.chunk_index as i64,
-                chunk.start_index as i64,
-                chunk.end_index as i64,
+                start_index as i64,
+                end_index as i64,
             ],
         )?;
     }
@@ -375,17 +847,53 @@ pub fn index_conversation(
 
     Ok(Session {
         session_id: session_id.to_string(),
-        title: title.map(String::from),
+        title,
         agent_id: agent_id.map(String::from),
         started_at,
         last_indexed_at: Some(now),
         message_count: messages.len() as i64,
         chunk_count: chunks.len() as i64,
         workspace: workspace.to_string(),
-        metadata: HashMap::new(),
+        summary,
+        metadata: session_metadata,
     })
 }
 
+/// Session metadata key recording which of the title and summary were
+/// generated, and by which summarizer
+const GENERATED_KEY: &str = "generated";
+
+/// Session metadata key holding the id of the session's summary memory
+pub(crate) const SUMMARY_MEMORY_KEY: &str = "summary_memory_id";
+
+/// Run the configured summarizer, falling back to the extractive one if it
+/// fails. Returns the summary and the name of the summarizer that wrote it.
+fn generate_summary(
+    messages: &[Message],
+    config: &ChunkingConfig,
+) -> Option<(SessionSummary, String)> {
+    let extractive = ExtractiveSummarizer::default();
+    let summarizer: &dyn SessionSummarizer = match &config.summarizer {
+        Some(summarizer) => summarizer.as_ref(),
+        None => &extractive,
+    };
+    match summarizer.summarize(messages) {
+        Ok(summary) => summary.map(|s| (s, summarizer.name().to_string())),
+        Err(e) => {
+            tracing::warn!(
+                summarizer = summarizer.name(),
+                error = %e,
+                "Session summarizer failed, using extractive summary"
+            );
+            extractive
+                .summarize(messages)
+                .ok()
+                .flatten()
+                .map(|s| (s, extractive.name().to_string()))
+        }
+    }
+}
+
 /// Index new messages incrementally (delta update).
 ///
 /// Only indexes messages that haven't been indexed yet.
@@ -408,7 +916,7 @@ pub fn index_conversation_delta(
     let session: Option<Session> = conn
         .query_row(
             "SELECT session_id, title, agent_id, started_at, last_indexed_at,
-                    message_count, chunk_count, workspace, metadata
+                    message_count, chunk_count, workspace, metadata, summary
              FROM sessions WHERE session_id = ?",
             params![session_id],
             |row| {
@@ -430,6 +938,7 @@ pub fn index_conversation_delta(
                     message_count: row.get(5)?,
                     chunk_count: row.get(6)?,
                     workspace: row.get(7)?,
+                    summary: row.get(9)?,
                     metadata: serde_json::from_str(&metadata_str).unwrap_or_default(),
                 })
             },
@@ -457,14 +966,44 @@ pub fn index_conversation_delta(
             // Track overlap count before moving for offset calculation later
             let overlap_count = overlap_messages.len();
 
+            // Keep measuring chunks with the tokenizer, and de-noising with
+            // the config, the session was indexed with unless the caller
+            // picks its own
+            let session_token_limit = TokenLimit::from_session_metadata(&existing.metadata)?
+                .filter(|_| config.token_limit.is_none());
+            let session_denoise = DenoiseConfig::from_session_metadata(&existing.metadata)
+                .filter(|_| config.denoise.is_none());
+            let session_config;
+            let config = if session_token_limit.is_some() || session_denoise.is_some() {
+                session_config = ChunkingConfig {
+                    token_limit: config.token_limit.or(session_token_limit),
+                    denoise: config.denoise.clone().or(session_denoise),
+                    ..config.clone()
+                };
+                &session_config
+            } else {
+                config
+            };
+
+            let denoised = match &config.denoise {
+                Some(denoise) => Some(denoise_transcript(new_messages, denoise)?),
+                None => None,
+            };
+            let kept_messages = denoised.as_ref().map_or(new_messages, |d| &d.messages[..]);
+
             // Combine overlap messages with new messages for proper context continuity
             let mut all_messages = overlap_messages;
-            all_messages.extend(new_messages.iter().cloned());
+            all_messages.extend(kept_messages.iter().cloned());
 
-            // Chunk the combined messages (overlap + new)
-            let chunks = chunk_conversation(&all_messages, config);
+            // Chunk the combined messages (overlap + new), unless every new
+            // message was noise
+            let chunks = if denoised.is_some() && kept_messages.is_empty() {
+                Vec::new()
+            } else {
+                chunk_conversation(&all_messages, config)
+            };
 
-            if chunks.is_empty() {
+            if chunks.is_empty() && denoised.is_none() {
                 return Ok(existing);
             }
 
@@ -489,6 +1028,22 @@ pub fn index_conversation_delta(
                 "overlap_messages".to_string(),
                 serde_json::to_value(&new_overlap).unwrap_or_default(),
             );
+            if let Some(limit) = &config.token_limit {
+                limit.record(&mut updated_metadata);
+            }
+            if let (Some(denoise), Some(denoised)) = (&config.denoise, &denoised) {
+                let mut stats =
+                    DenoiseStats::from_session_metadata(&existing.metadata).unwrap_or_default();
+                stats.merge(&denoised.stats);
+                updated_metadata.insert(
+                    DenoiseConfig::METADATA_KEY.to_string(),
+                    serde_json::to_value(denoise)?,
+                );
+                updated_metadata.insert(
+                    DenoiseStats::METADATA_KEY.to_string(),
+                    serde_json::to_value(&stats)?,
+                );
+            }
             let metadata_json = serde_json::to_string(&updated_metadata)?;
 
             // Update session
@@ -508,15 +1063,22 @@ pub fn index_conversation_delta(
             // Since overlap messages were already indexed in previous chunks,
             // we need to subtract the overlap count to avoid double-counting.
             // global_index = chunk_local_index + last_chunk_end - overlap_count
+            // New messages dropped as noise still count towards positions.
             let base_offset = (last_chunk_end as usize).saturating_sub(overlap_count);
+            let global_index = |local: usize| match &denoised {
+                Some(d) if local >= overlap_count => {
+                    existing.message_count as usize + d.kept[local - overlap_count]
+                }
+                _ => local + base_offset,
+            };
 
             // Create memory for each new chunk
             for (i, chunk) in chunks.iter().enumerate() {
                 let chunk_index = starting_chunk_index as usize + i;
 
                 // Calculate global message indices
-                let global_start = chunk.start_index + base_offset;
-                let global_end = chunk.end_index + base_offset;
+                let global_start = global_index(chunk.start_index);
+                let global_end = global_index(chunk.end_index - 1) + 1;
 
                 let mut metadata = HashMap::new();
                 metadata.insert("session_id".to_string(), serde_json::json!(session_id));
@@ -527,13 +1089,24 @@ pub fn index_conversation_delta(
                     "message_count".to_string(),
                     serde_json::json!(chunk.messages.len()),
                 );
+                metadata.insert(
+                    "message_facets".to_string(),
+                    message_facets(&chunk.messages),
+                );
+                if let Some(tokens) = chunk.token_count {
+                    metadata.insert("token_count".to_string(), serde_json::json!(tokens));
+                }
 
                 let input = CreateMemoryInput {
                     content: chunk.content.clone(),
                     memory_type: MemoryType::TranscriptChunk,
                     tags: vec!["transcript".to_string(), format!("session:{}", session_id)],
                     metadata,
-                    importance: Some(0.3),
+                    importance: Some(auto_importance(&ImportanceSignals::new(
+                        ImportanceSource::TranscriptChunk,
+                        MemoryType::TranscriptChunk,
+                        &chunk.content,
+                    ))),
                     scope: Default::default(),
                     workspace: Some(existing.workspace.clone()),
                     tier: MemoryTier::Daily,
@@ -570,6 +1143,7 @@ pub fn index_conversation_delta(
                 message_count: new_message_count,
                 chunk_count: existing.chunk_count + chunks.len() as i64,
                 last_indexed_at: Some(now),
+                metadata: updated_metadata,
                 ..existing
             })
         }
@@ -584,7 +1158,7 @@ pub fn index_conversation_delta(
 pub fn get_session(conn: &Connection, session_id: &str) -> Result<Session> {
     conn.query_row(
         "SELECT session_id, title, agent_id, started_at, last_indexed_at,
-                message_count, chunk_count, workspace, metadata
+                message_count, chunk_count, workspace, metadata, summary
          FROM sessions WHERE session_id = ?",
         params![session_id],
         |row| {
@@ -606,6 +1180,7 @@ pub fn get_session(conn: &Connection, session_id: &str) -> Result<Session> {
                 message_count: row.get(5)?,
                 chunk_count: row.get(6)?,
                 workspace: row.get(7)?,
+                summary: row.get(9)?,
                 metadata: serde_json::from_str(&metadata_str).unwrap_or_default(),
             })
         },
@@ -621,7 +1196,7 @@ pub fn list_sessions(
 ) -> Result<Vec<Session>> {
     let mut sql = String::from(
         "SELECT session_id, title, agent_id, started_at, last_indexed_at,
-                message_count, chunk_count, workspace, metadata
+                message_count, chunk_count, workspace, metadata, summary
          FROM sessions",
     );
 
@@ -658,6 +1233,7 @@ pub fn list_sessions(
                 message_count: row.get(5)?,
                 chunk_count: row.get(6)?,
                 workspace: row.get(7)?,
+                summary: row.get(9)?,
                 metadata: serde_json::from_str(&metadata_str).unwrap_or_default(),
             })
         })?
@@ -704,6 +1280,7 @@ mod tests {
                 content: format!("Message {} {}", i, "x".repeat(char_len)),
                 timestamp: Utc::now(),
                 id: Some(format!("msg-{}", i)),
+                ..Default::default()
             })
             .collect()
     }
@@ -765,6 +1342,7 @@ mod tests {
             content: long_content,
             timestamp: Utc::now(),
             id: None,
+            ..Default::default()
         }];
 
         let chunks = chunk_conversation(&messages, &config);
@@ -791,12 +1369,14 @@ mod tests {
                 content: "Hello".to_string(),
                 timestamp: Utc::now(),
                 id: None,
+                ..Default::default()
             },
             Message {
                 role: "assistant".to_string(),
                 content: "Hi there!".to_string(),
                 timestamp: Utc::now(),
                 id: None,
+                ..Default::default()
             },
         ];
 
@@ -804,4 +1384,173 @@ mod tests {
         assert!(content.contains("[user]: Hello"));
         assert!(content.contains("[assistant]: Hi there!"));
     }
+
+    fn tool_call(name: &str, arguments: serde_json::Value) -> Message {
+        Message {
+            role: "assistant".to_string(),
+            kind: MessageKind::ToolCall,
+            tool_name: Some(name.to_string()),
+            arguments: Some(arguments),
+            ..Default::default()
+        }
+    }
+
+    fn tool_result(name: &str, content: &str) -> Message {
+        Message {
+            role: "tool".to_string(),
+            content: content.to_string(),
+            kind: MessageKind::ToolResult,
+            tool_name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tool_messages_are_summarized_and_budgeted() {
+        let config = ChunkingConfig {
+            max_tool_result_chars: 100,
+            ..Default::default()
+        };
+        let messages = vec![
+            Message {
+                role: "system".to_string(),
+                content: "s".repeat(5000),
+                kind: MessageKind::System,
+                ..Default::default()
+            },
+            tool_call(
+                "grep",
+                serde_json::json!({"pattern": "fn main", "paths": ["a", "b"], "opts": {"i": true}}),
+            ),
+            tool_result("grep", &"line\n".repeat(200)),
+        ];
+
+        let chunks = chunk_conversation(&messages, &config);
+        assert_eq!(chunks.len(), 1);
+        let content = &chunks[0].content;
+        assert!(content.contains(
+            "[assistant tool_call grep]: opts={i}, paths=[2 items], pattern=\"fn main\""
+        ));
+        assert!(content.contains("[tool_result grep]: line"));
+        assert!(chunks[0].messages[0].content.len() <= config.max_system_chars);
+        assert!(chunks[0].messages[2].content.len() <= 100);
+    }
+
+    #[test]
+    fn test_tool_call_stays_with_its_result() {
+        let config = ChunkingConfig {
+            max_messages: 3,
+            overlap_messages: 0,
+            ..Default::default()
+        };
+        let mut messages = make_messages(2, 5);
+        messages.push(tool_call("search", serde_json::json!({"query": "auth"})));
+        messages.push(tool_result("search", "3 hits"));
+        messages.extend(make_messages(1, 5));
+
+        let chunks = chunk_conversation(&messages, &config);
+        let ends: Vec<(usize, usize)> = chunks
+            .iter()
+            .map(|c| (c.start_index, c.end_index))
+            .collect();
+        assert_eq!(ends, vec![(0, 2), (2, 5)]);
+    }
+
+    #[test]
+    fn test_message_kind_inference_and_facets() {
+        assert_eq!(MessageKind::infer("System", false), MessageKind::System);
+        assert_eq!(MessageKind::infer("tool", false), MessageKind::ToolResult);
+        assert_eq!(MessageKind::infer("assistant", true), MessageKind::ToolCall);
+        assert_eq!(MessageKind::infer("user", false), MessageKind::Text);
+        assert!("bogus".parse::<MessageKind>().is_err());
+
+        let mut messages = make_messages(2, 1);
+        messages.push(tool_result("search", "ok"));
+        messages.push(tool_result("search", "again"));
+        let facets = message_facets(&messages);
+        assert_eq!(
+            facets,
+            serde_json::json!([
+                {"role": "assistant", "kind": "text", "tool_name": null},
+                {"role": "tool", "kind": "tool_result", "tool_name": "search"},
+                {"role": "user", "kind": "text", "tool_name": null},
+            ])
+        );
+    }
+
+    fn token_limit(max_tokens: usize) -> TokenLimit {
+        TokenLimit {
+            max_tokens,
+            counter: TiktokenCounter::new(crate::intelligence::TokenEncoding::Cl100kBase).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_chunk_by_token_count() {
+        let config = ChunkingConfig {
+            max_messages: 100,
+            overlap_messages: 0,
+            token_limit: Some(token_limit(60)),
+            ..Default::default()
+        };
+        let messages: Vec<Message> = (0..12)
+            .map(|i| Message {
+                role: "user".to_string(),
+                content: format!("Deploying service number {} to the staging cluster", i),
+                ..Default::default()
+            })
+            .collect();
+
+        let chunks = chunk_conversation(&messages, &config);
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.last().unwrap().end_index, 12);
+        for chunk in &chunks {
+            let tokens = chunk.token_count.unwrap();
+            assert!(tokens <= 60, "chunk has {} tokens", tokens);
+            assert_eq!(
+                tokens,
+                config.token_limit.unwrap().counter.count(&chunk.content)
+            );
+        }
+    }
+
+    #[test]
+    fn test_oversized_message_truncated_to_token_limit() {
+        let config = ChunkingConfig {
+            token_limit: Some(token_limit(50)),
+            ..Default::default()
+        };
+        let messages = vec![Message {
+            role: "user".to_string(),
+            content: "lorem ipsum dolor sit amet ".repeat(100),
+            ..Default::default()
+        }];
+
+        let chunks = chunk_conversation(&messages, &config);
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].token_count.unwrap() <= 50);
+        assert!(chunks[0].content.contains("[...truncated...]"));
+    }
+
+    #[test]
+    fn test_token_limit_round_trips_through_session_metadata() {
+        let mut metadata = HashMap::new();
+        assert!(TokenLimit::from_session_metadata(&metadata)
+            .unwrap()
+            .is_none());
+
+        let limit = TokenLimit {
+            max_tokens: 512,
+            counter: TiktokenCounter::new(crate::intelligence::TokenEncoding::O200kBase).unwrap(),
+        };
+        limit.record(&mut metadata);
+        let restored = TokenLimit::from_session_metadata(&metadata)
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.max_tokens, 512);
+        assert_eq!(
+            restored.counter.encoding(),
+            crate::intelligence::TokenEncoding::O200kBase
+        );
+    }
 }
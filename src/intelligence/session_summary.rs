@@ -0,0 +1,450 @@
+//! Automatic session titles and summaries
+//!
+//! Session indexing asks a [`SessionSummarizer`] for a short title and a
+//! 2–3 sentence summary of the transcript:
+//! - [`ExtractiveSummarizer`] (default): the title comes from the opening
+//!   user request, the summary from the conversational sentences whose words
+//!   recur most across the session, kept in transcript order
+//! - `LlmSummarizer` (`openai` feature): asks an OpenAI-compatible chat model,
+//!   enabled by setting `ENGRAM_SESSION_SUMMARY_MODEL`
+//!
+//! [`default_summarizer`] picks the LLM when it is configured and the
+//! extractive summarizer otherwise.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::intelligence::session_indexing::{Message, MessageKind};
+
+/// Words ignored when scoring sentences
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by",
+    "from", "is", "are", "was", "were", "be", "been", "being", "have", "has", "had", "do", "does",
+    "did", "will", "would", "can", "could", "should", "it", "its", "this", "that", "these",
+    "those", "i", "you", "we", "me", "my", "your", "our", "let", "just", "so", "not", "what",
+    "how", "why", "when", "there", "here", "then", "than", "also", "if", "as", "into", "about",
+];
+
+/// Sentences shorter than this many words are not summary candidates
+const MIN_SENTENCE_WORDS: usize = 4;
+
+/// Sentences longer than this many characters are not summary candidates
+const MAX_SENTENCE_CHARS: usize = 300;
+
+/// A generated session title and summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub title: String,
+    pub summary: String,
+}
+
+/// Produces a title and summary for a session transcript
+pub trait SessionSummarizer: Send + Sync + std::fmt::Debug {
+    /// Summarize the transcript. `None` when there is nothing to summarize.
+    fn summarize(&self, messages: &[Message]) -> Result<Option<SessionSummary>>;
+
+    /// Name recorded on the session as the summary's source
+    fn name(&self) -> &str;
+}
+
+/// Frequency-based extractive summarizer
+#[derive(Debug, Clone)]
+pub struct ExtractiveSummarizer {
+    /// Longest title, in characters (default: 60)
+    pub max_title_chars: usize,
+    /// Sentences in the summary (default: 3)
+    pub max_sentences: usize,
+}
+
+impl Default for ExtractiveSummarizer {
+    fn default() -> Self {
+        Self {
+            max_title_chars: 60,
+            max_sentences: 3,
+        }
+    }
+}
+
+impl SessionSummarizer for ExtractiveSummarizer {
+    fn summarize(&self, messages: &[Message]) -> Result<Option<SessionSummary>> {
+        // Only the conversation itself; system prompts and tool traffic are
+        // mostly boilerplate
+        let conversation: Vec<&Message> = messages
+            .iter()
+            .filter(|m| m.kind == MessageKind::Text && m.role != "system")
+            .filter(|m| !m.content.trim().is_empty())
+            .collect();
+        if conversation.is_empty() {
+            return Ok(None);
+        }
+
+        let sentences: Vec<&str> = conversation
+            .iter()
+            .flat_map(|m| split_sentences(&m.content))
+            .collect();
+        let mut scored: Vec<(usize, f64)> = score_sentences(&sentences)
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, _)| {
+                let s = sentences[i];
+                s.len() <= MAX_SENTENCE_CHARS && s.split_whitespace().count() >= MIN_SENTENCE_WORDS
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored.truncate(self.max_sentences.max(1));
+        scored.sort_by_key(|&(i, _)| i);
+
+        let summary = if scored.is_empty() {
+            truncate_words(conversation[0].content.trim(), MAX_SENTENCE_CHARS)
+        } else {
+            scored
+                .iter()
+                .map(|&(i, _)| sentences[i])
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        // The opening request usually says what the session is about
+        let opening = conversation
+            .iter()
+            .find(|m| m.role == "user")
+            .unwrap_or(&conversation[0]);
+        let first_sentence = split_sentences(&opening.content)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let title = truncate_words(
+            first_sentence.trim_end_matches(['.', '!', ':', ';']),
+            self.max_title_chars.max(1),
+        );
+
+        Ok(Some(SessionSummary { title, summary }))
+    }
+
+    fn name(&self) -> &str {
+        "extractive"
+    }
+}
+
+/// Summarizer backed by an OpenAI-compatible chat completions API
+#[cfg(feature = "openai")]
+#[derive(Debug)]
+pub struct LlmSummarizer {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    /// Transcript characters sent to the model; the rest is cut
+    pub max_transcript_chars: usize,
+}
+
+#[cfg(feature = "openai")]
+impl LlmSummarizer {
+    pub fn new(api_key: String, base_url: Option<String>, model: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key,
+            base_url: base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            model,
+            max_transcript_chars: 24_000,
+        }
+    }
+
+    /// Configured from `ENGRAM_SESSION_SUMMARY_MODEL`, with the key from
+    /// `ENGRAM_SESSION_SUMMARY_API_KEY` (or `OPENAI_API_KEY`) and an optional
+    /// `ENGRAM_SESSION_SUMMARY_BASE_URL`. `None` unless a model and key are set.
+    pub fn from_env() -> Option<Self> {
+        let model = std::env::var("ENGRAM_SESSION_SUMMARY_MODEL").ok()?;
+        let api_key = std::env::var("ENGRAM_SESSION_SUMMARY_API_KEY")
+            .or_else(|_| std::env::var("OPENAI_API_KEY"))
+            .ok()?;
+        let base_url = std::env::var("ENGRAM_SESSION_SUMMARY_BASE_URL").ok();
+        Some(Self::new(api_key, base_url, model))
+    }
+
+    async fn summarize_async(&self, transcript: String) -> Result<Option<SessionSummary>> {
+        use crate::error::EngramError;
+
+        let content = self
+            .chat(serde_json::json!({
+                "model": self.model,
+                "temperature": 0.2,
+                "response_format": {"type": "json_object"},
+                "messages": [
+                    {
+                        "role": "system",
+                        "content": "Summarize the conversation transcript. Reply with a JSON object \
+                                    {\"title\": \"...\", \"summary\": \"...\"}: a title of at most 8 \
+                                    words and a summary of 2-3 sentences covering the goal, what was \
+                                    done and the outcome."
+                    },
+                    {"role": "user", "content": transcript}
+                ]
+            }))
+            .await?;
+        let summary: SessionSummary = serde_json::from_str(&content)
+            .map_err(|e| EngramError::Internal(format!("Invalid summary JSON: {}", e)))?;
+        Ok(Some(summary))
+    }
+
+    /// Condense a long document into at most `max_chars` of plain text
+    pub async fn summarize_text_async(&self, text: &str, max_chars: usize) -> Result<String> {
+        let text = truncate_words(text, self.max_transcript_chars);
+        let summary = self
+            .chat(serde_json::json!({
+                "model": self.model,
+                "temperature": 0.2,
+                "messages": [
+                    {
+                        "role": "system",
+                        "content": format!(
+                            "Summarize the document in at most {} characters of plain text. \
+                             Keep names, identifiers, numbers and the key facts; reply with \
+                             the summary only.",
+                            max_chars
+                        )
+                    },
+                    {"role": "user", "content": text}
+                ]
+            }))
+            .await?;
+        Ok(truncate_words(summary.trim(), max_chars))
+    }
+
+    /// Send a chat completion request and return the reply's text
+    async fn chat(&self, body: serde_json::Value) -> Result<String> {
+        use crate::error::EngramError;
+
+        let response = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| EngramError::Internal(format!("Summary request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(EngramError::Internal(format!(
+                "Summary request failed with status {}",
+                response.status()
+            )));
+        }
+        let reply: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| EngramError::Internal(format!("Invalid summary response: {}", e)))?;
+        Ok(reply["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+}
+
+#[cfg(feature = "openai")]
+impl SessionSummarizer for LlmSummarizer {
+    fn summarize(&self, messages: &[Message]) -> Result<Option<SessionSummary>> {
+        let mut transcript = String::new();
+        for m in messages.iter().filter(|m| !m.content.trim().is_empty()) {
+            transcript.push_str(&format!("[{}]: {}\n", m.role, m.content.trim()));
+            if transcript.len() >= self.max_transcript_chars {
+                break;
+            }
+        }
+        if transcript.is_empty() {
+            return Ok(None);
+        }
+        let transcript = truncate_words(&transcript, self.max_transcript_chars);
+
+        // Blocking call for the sync indexing path, as the embedders do
+        crate::embedding::block_on(self.summarize_async(transcript))
+    }
+
+    fn name(&self) -> &str {
+        &self.model
+    }
+}
+
+/// The process-wide summarizer: the LLM when configured, else extractive
+pub fn default_summarizer() -> Arc<dyn SessionSummarizer> {
+    static DEFAULT: OnceLock<Arc<dyn SessionSummarizer>> = OnceLock::new();
+    DEFAULT
+        .get_or_init(|| {
+            #[cfg(feature = "openai")]
+            if let Some(llm) = LlmSummarizer::from_env() {
+                return Arc::new(llm);
+            }
+            Arc::new(ExtractiveSummarizer::default())
+        })
+        .clone()
+}
+
+/// Score each sentence by how much its words recur across all of them,
+/// damped for sentence length
+pub(crate) fn score_sentences(sentences: &[&str]) -> Vec<f64> {
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for sentence in sentences {
+        for word in content_words(sentence) {
+            *frequency.entry(word).or_default() += 1;
+        }
+    }
+    sentences
+        .iter()
+        .map(|s| {
+            let words = content_words(s);
+            let weight: f64 = words.iter().map(|w| (frequency[w] as f64).ln_1p()).sum();
+            weight / (words.len().max(1) as f64).sqrt()
+        })
+        .collect()
+}
+
+/// Split on sentence-ending punctuation followed by whitespace, and on
+/// line breaks
+pub(crate) fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    for line in text.lines() {
+        let mut start = 0;
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at_boundary = matches!(c, '.' | '!' | '?')
+                && chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+            if at_boundary {
+                let end = i + c.len_utf8();
+                let sentence = line[start..end].trim();
+                if !sentence.is_empty() {
+                    sentences.push(sentence);
+                }
+                start = end;
+            }
+        }
+        let tail = line[start..].trim();
+        if !tail.is_empty() {
+            sentences.push(tail);
+        }
+    }
+    sentences
+}
+
+/// Lowercased words that carry meaning
+fn content_words(sentence: &str) -> Vec<String> {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 2)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Cut `text` to at most `max_chars`, at a word boundary when possible
+pub(crate) fn truncate_words(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(space) if space > 0 => &cut[..space],
+        _ => &cut[..],
+    };
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extractive_title_and_summary() {
+        let messages = vec![
+            message("system", "You are a helpful deployment assistant."),
+            message(
+                "user",
+                "Why does the staging deploy fail? It worked yesterday.",
+            ),
+            message(
+                "assistant",
+                "The staging deploy fails because the deploy user lost read access to the \
+                 config bucket. Hi again by the way.",
+            ),
+            Message {
+                role: "tool".to_string(),
+                content: "deploy: permission denied reading config bucket".to_string(),
+                kind: MessageKind::ToolResult,
+                ..Default::default()
+            },
+            message(
+                "user",
+                "Can you restore the deploy user's access to the config bucket?",
+            ),
+            message(
+                "assistant",
+                "I restored read access for the deploy user and the staging deploy now passes.",
+            ),
+            message("user", "Great, thanks!"),
+        ];
+
+        let summary = ExtractiveSummarizer::default()
+            .summarize(&messages)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(summary.title, "Why does the staging deploy fail?");
+        let sentences = split_sentences(&summary.summary);
+        assert!((2..=3).contains(&sentences.len()), "{}", summary.summary);
+        assert!(summary.summary.contains("lost read access"));
+        assert!(!summary.summary.contains("helpful deployment assistant"));
+        assert!(!summary.summary.contains("permission denied"));
+    }
+
+    #[test]
+    fn test_long_titles_are_cut_at_a_word() {
+        let summarizer = ExtractiveSummarizer {
+            max_title_chars: 20,
+            ..Default::default()
+        };
+        let summary = summarizer
+            .summarize(&[message(
+                "user",
+                "Please migrate the billing service to the new queue",
+            )])
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(summary.title, "Please migrate the…");
+        assert_eq!(
+            summary.summary,
+            "Please migrate the billing service to the new queue"
+        );
+    }
+
+    #[test]
+    fn test_nothing_to_summarize() {
+        let tool_only = vec![Message {
+            role: "assistant".to_string(),
+            kind: MessageKind::ToolCall,
+            tool_name: Some("ls".to_string()),
+            ..Default::default()
+        }];
+        assert!(ExtractiveSummarizer::default()
+            .summarize(&tool_only)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_split_sentences_keeps_versions_together() {
+        assert_eq!(
+            split_sentences("Upgrade to v2.0 today. Then restart!\nDone"),
+            vec!["Upgrade to v2.0 today.", "Then restart!", "Done"]
+        );
+    }
+}
@@ -0,0 +1,595 @@
+//! Cross-session linking of recurring topics
+//!
+//! Indexed sessions are searched one chunk at a time, so nothing tells an
+//! agent that last week's session already covered the migration it is about
+//! to discuss. This job collects the entities each session mentions — those
+//! already linked to its chunks plus a pattern-based extraction pass over the
+//! transcript, title and summary — and links sessions that share enough of
+//! them:
+//!
+//! - each linked pair gets a `related_to` crossref between the sessions'
+//!   summary memories (or first chunks), and both sessions list each other
+//!   under the `related_sessions` metadata key shown by `session_list`;
+//! - each group of linked sessions gets one topic memory, tagged
+//!   `session_topic` and `session:<id>` for every session, that names the
+//!   shared entities and summarizes where they came up.
+//!
+//! Entities that appear in most sessions ("rust", "github") say nothing about
+//! a topic and are ignored. Re-running the job replaces earlier topic
+//! memories and `related_sessions` lists for the sessions it analyzes.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::intelligence::entities::{EntityExtractionConfig, EntityExtractor, EntityType};
+use crate::intelligence::importance::{auto_importance, ImportanceSignals, ImportanceSource};
+use crate::intelligence::session_indexing::{list_sessions, Session, SUMMARY_MEMORY_KEY};
+use crate::storage::queries::{create_crossref, create_memory, delete_memory};
+use crate::types::{
+    CreateCrossRefInput, CreateMemoryInput, EdgeType, MemoryId, MemoryTier, MemoryType,
+};
+
+/// Session metadata key listing related sessions
+pub const RELATED_SESSIONS_KEY: &str = "related_sessions";
+
+/// Session metadata key holding the id of the session's topic memory
+pub const TOPIC_MEMORY_KEY: &str = "topic_memory_id";
+
+/// Settings for [`link_session_topics`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TopicLinkConfig {
+    /// Entities two sessions must share to be linked
+    pub min_shared_entities: usize,
+    /// Ignore entities mentioned in more than this fraction of the analyzed
+    /// sessions; only applied once there are at least four sessions
+    pub max_session_fraction: f32,
+    /// Most recent sessions to analyze
+    pub max_sessions: i64,
+}
+
+impl Default for TopicLinkConfig {
+    fn default() -> Self {
+        Self {
+            min_shared_entities: 2,
+            max_session_fraction: 0.5,
+            max_sessions: 200,
+        }
+    }
+}
+
+/// Two sessions that discuss the same entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLink {
+    /// The session that started first
+    pub session_a: String,
+    pub session_b: String,
+    pub shared_entities: Vec<String>,
+    /// Jaccard similarity of the two sessions' entity sets
+    pub score: f32,
+}
+
+/// A group of linked sessions and the entities they keep coming back to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTopic {
+    pub label: String,
+    /// Entities shared by at least two of the sessions, most widespread first
+    pub entities: Vec<String>,
+    /// Session ids, oldest first
+    pub sessions: Vec<String>,
+    pub workspace: String,
+    /// The topic memory, once written
+    pub memory_id: Option<MemoryId>,
+}
+
+/// Entry of a session's `related_sessions` metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedSession {
+    pub session_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub shared_entities: Vec<String>,
+    pub score: f32,
+}
+
+/// What [`link_session_topics`] found and wrote
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TopicLinkReport {
+    pub sessions_analyzed: usize,
+    pub links: Vec<SessionLink>,
+    pub topics: Vec<SessionTopic>,
+    /// Crossrefs written between sessions and to topic memories
+    pub crossrefs_created: usize,
+}
+
+impl Session {
+    /// Sessions linked to this one by [`link_session_topics`]
+    pub fn related_sessions(&self) -> Vec<RelatedSession> {
+        self.metadata
+            .get(RELATED_SESSIONS_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Find sessions that share entities, without writing anything
+pub fn find_session_topics(
+    conn: &Connection,
+    workspace: Option<&str>,
+    config: &TopicLinkConfig,
+) -> Result<TopicLinkReport> {
+    analyze(conn, workspace, config).map(|(_, report)| report)
+}
+
+/// Link sessions that share entities and write one topic memory per group
+/// of linked sessions
+pub fn link_session_topics(
+    conn: &Connection,
+    workspace: Option<&str>,
+    config: &TopicLinkConfig,
+) -> Result<TopicLinkReport> {
+    let (mut sessions, mut report) = analyze(conn, workspace, config)?;
+
+    // Forget the previous run's results for these sessions
+    let mut stale_topics = BTreeSet::new();
+    for session in &mut sessions {
+        session.metadata.remove(RELATED_SESSIONS_KEY);
+        if let Some(id) = session
+            .metadata
+            .remove(TOPIC_MEMORY_KEY)
+            .and_then(|v| v.as_i64())
+        {
+            stale_topics.insert(id);
+        }
+    }
+    for id in stale_topics {
+        // Already gone if someone deleted it by hand
+        let _ = delete_memory(conn, id);
+    }
+
+    let index: HashMap<String, usize> = sessions
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.session_id.clone(), i))
+        .collect();
+    let anchors: Vec<Option<MemoryId>> = sessions
+        .iter()
+        .map(|s| anchor_memory(conn, s))
+        .collect::<Result<_>>()?;
+
+    for link in &report.links {
+        let (a, b) = (index[&link.session_a], index[&link.session_b]);
+        if let (Some(from_id), Some(to_id)) = (anchors[a], anchors[b]) {
+            create_crossref(
+                conn,
+                &CreateCrossRefInput {
+                    from_id,
+                    to_id,
+                    edge_type: EdgeType::RelatedTo,
+                    strength: Some(link.score),
+                    source_context: Some(format!(
+                        "Sessions share: {}",
+                        link.shared_entities.join(", ")
+                    )),
+                    pinned: false,
+                },
+            )?;
+            report.crossrefs_created += 1;
+        }
+        for (this, other) in [(a, b), (b, a)] {
+            let related = RelatedSession {
+                session_id: sessions[other].session_id.clone(),
+                title: sessions[other].title.clone(),
+                shared_entities: link.shared_entities.clone(),
+                score: link.score,
+            };
+            let entry = sessions[this]
+                .metadata
+                .entry(RELATED_SESSIONS_KEY.to_string())
+                .or_insert_with(|| serde_json::json!([]));
+            if let Some(list) = entry.as_array_mut() {
+                list.push(serde_json::to_value(related)?);
+            }
+        }
+    }
+
+    for topic in &mut report.topics {
+        let members: Vec<usize> = topic.sessions.iter().map(|id| index[id]).collect();
+        let content = topic_content(topic, members.iter().map(|&i| &sessions[i]));
+        let importance = auto_importance(
+            &ImportanceSignals::new(
+                ImportanceSource::SessionTopic,
+                MemoryType::Summary,
+                &content,
+            )
+            .with_entity_count(topic.entities.len()),
+        );
+        let memory = create_memory(
+            conn,
+            &CreateMemoryInput {
+                content,
+                memory_type: MemoryType::Summary,
+                tags: std::iter::once("session_topic".to_string())
+                    .chain(topic.sessions.iter().map(|id| format!("session:{}", id)))
+                    .collect(),
+                metadata: HashMap::from([
+                    ("sessions".to_string(), serde_json::json!(topic.sessions)),
+                    ("entities".to_string(), serde_json::json!(topic.entities)),
+                ]),
+                importance: Some(importance),
+                scope: Default::default(),
+                workspace: Some(topic.workspace.clone()),
+                tier: MemoryTier::Permanent,
+                defer_embedding: false,
+                ttl_seconds: None,
+                dedup_mode: Default::default(),
+                dedup_threshold: None,
+                event_time: None,
+                event_duration_seconds: None,
+                trigger_pattern: None,
+                summary_of_id: None,
+                media_url: None,
+            },
+        )?;
+        topic.memory_id = Some(memory.id);
+
+        for &i in &members {
+            if let Some(to_id) = anchors[i] {
+                create_crossref(
+                    conn,
+                    &CreateCrossRefInput {
+                        from_id: memory.id,
+                        to_id,
+                        edge_type: EdgeType::References,
+                        strength: None,
+                        source_context: None,
+                        pinned: false,
+                    },
+                )?;
+                report.crossrefs_created += 1;
+            }
+            sessions[i]
+                .metadata
+                .insert(TOPIC_MEMORY_KEY.to_string(), serde_json::json!(memory.id));
+        }
+    }
+
+    for session in &sessions {
+        conn.execute(
+            "UPDATE sessions SET metadata = ? WHERE session_id = ?",
+            params![
+                serde_json::to_string(&session.metadata)?,
+                session.session_id
+            ],
+        )?;
+    }
+
+    tracing::info!(
+        sessions = report.sessions_analyzed,
+        links = report.links.len(),
+        topics = report.topics.len(),
+        "Linked recurring session topics"
+    );
+    Ok(report)
+}
+
+/// Load sessions, collect their entities and work out links and topics
+fn analyze(
+    conn: &Connection,
+    workspace: Option<&str>,
+    config: &TopicLinkConfig,
+) -> Result<(Vec<Session>, TopicLinkReport)> {
+    let mut sessions = list_sessions(conn, workspace, config.max_sessions)?;
+    sessions.sort_by(|a, b| {
+        a.started_at
+            .cmp(&b.started_at)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+
+    let extractor = EntityExtractor::new(EntityExtractionConfig {
+        extract_datetime: false,
+        ..Default::default()
+    });
+    let mut entities: Vec<BTreeSet<String>> = sessions
+        .iter()
+        .map(|s| session_entities(conn, s, &extractor))
+        .collect::<Result<_>>()?;
+
+    // Drop entities too common to identify a topic
+    if sessions.len() >= 4 {
+        let mut frequency: HashMap<String, usize> = HashMap::new();
+        for set in &entities {
+            for entity in set {
+                *frequency.entry(entity.clone()).or_insert(0) += 1;
+            }
+        }
+        let max = (sessions.len() as f32 * config.max_session_fraction).floor() as usize;
+        for set in &mut entities {
+            set.retain(|e| frequency[e] <= max.max(2));
+        }
+    }
+
+    let min_shared = config.min_shared_entities.max(1);
+    let mut links = Vec::new();
+    let mut linked: Vec<(usize, usize)> = Vec::new();
+    for a in 0..sessions.len() {
+        for b in a + 1..sessions.len() {
+            if sessions[a].workspace != sessions[b].workspace {
+                continue;
+            }
+            let shared: Vec<String> = entities[a].intersection(&entities[b]).cloned().collect();
+            if shared.len() < min_shared {
+                continue;
+            }
+            let union = entities[a].union(&entities[b]).count();
+            links.push(SessionLink {
+                session_a: sessions[a].session_id.clone(),
+                session_b: sessions[b].session_id.clone(),
+                score: shared.len() as f32 / union as f32,
+                shared_entities: shared,
+            });
+            linked.push((a, b));
+        }
+    }
+
+    // Topics are the connected groups of linked sessions
+    let mut group: Vec<usize> = (0..sessions.len()).collect();
+    fn root(group: &mut [usize], mut i: usize) -> usize {
+        while group[i] != i {
+            group[i] = group[group[i]];
+            i = group[i];
+        }
+        i
+    }
+    for &(a, b) in &linked {
+        let (ra, rb) = (root(&mut group, a), root(&mut group, b));
+        if ra != rb {
+            group[ra.max(rb)] = ra.min(rb);
+        }
+    }
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..sessions.len() {
+        let r = root(&mut group, i);
+        members.entry(r).or_default().push(i);
+    }
+
+    let topics = members
+        .into_values()
+        .filter(|m| m.len() >= 2)
+        .map(|m| {
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for &i in &m {
+                for entity in &entities[i] {
+                    *counts.entry(entity.as_str()).or_insert(0) += 1;
+                }
+            }
+            let mut shared: Vec<(&str, usize)> =
+                counts.into_iter().filter(|&(_, c)| c >= 2).collect();
+            shared.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let entities: Vec<String> = shared.iter().take(5).map(|(e, _)| e.to_string()).collect();
+            SessionTopic {
+                label: entities
+                    .iter()
+                    .take(3)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                entities,
+                sessions: m.iter().map(|&i| sessions[i].session_id.clone()).collect(),
+                workspace: sessions[m[0]].workspace.clone(),
+                memory_id: None,
+            }
+        })
+        .collect();
+
+    let report = TopicLinkReport {
+        sessions_analyzed: sessions.len(),
+        links,
+        topics,
+        crossrefs_created: 0,
+    };
+    Ok((sessions, report))
+}
+
+/// Normalized entity names linked to a session's chunks or extracted from
+/// its transcript, title and summary
+fn session_entities(
+    conn: &Connection,
+    session: &Session,
+    extractor: &EntityExtractor,
+) -> Result<BTreeSet<String>> {
+    let mut found = BTreeSet::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT e.normalized_name
+         FROM session_chunks sc
+         JOIN memory_entities me ON me.memory_id = sc.memory_id
+         JOIN entities e ON e.id = me.entity_id
+         WHERE sc.session_id = ? AND e.entity_type != 'datetime'",
+    )?;
+    for name in stmt.query_map(params![session.session_id], |row| row.get::<_, String>(0))? {
+        found.insert(name?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT m.content
+         FROM session_chunks sc
+         JOIN memories m ON m.id = sc.memory_id
+         WHERE sc.session_id = ? AND m.valid_to IS NULL
+         ORDER BY sc.chunk_index",
+    )?;
+    let mut texts: Vec<String> = stmt
+        .query_map(params![session.session_id], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    texts.extend(session.title.iter().cloned());
+    texts.extend(session.summary.iter().cloned());
+
+    for text in &texts {
+        for entity in extractor.extract(text).entities {
+            if entity.entity_type != EntityType::DateTime {
+                found.insert(entity.normalized);
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Memory that stands for a session in crossrefs: its summary memory, or
+/// its first chunk when it has no summary
+fn anchor_memory(conn: &Connection, session: &Session) -> Result<Option<MemoryId>> {
+    if let Some(id) = session
+        .metadata
+        .get(SUMMARY_MEMORY_KEY)
+        .and_then(|v| v.as_i64())
+    {
+        let live: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM memories WHERE id = ? AND valid_to IS NULL)",
+            params![id],
+            |row| row.get(0),
+        )?;
+        if live {
+            return Ok(Some(id));
+        }
+    }
+    let mut stmt = conn.prepare(
+        "SELECT sc.memory_id
+         FROM session_chunks sc
+         JOIN memories m ON m.id = sc.memory_id
+         WHERE sc.session_id = ? AND m.valid_to IS NULL
+         ORDER BY sc.chunk_index
+         LIMIT 1",
+    )?;
+    let first = stmt
+        .query_map(params![session.session_id], |row| row.get(0))?
+        .next()
+        .transpose()?;
+    Ok(first)
+}
+
+/// Text of a topic memory: the shared entities, then one line per session
+fn topic_content<'a>(topic: &SessionTopic, sessions: impl Iterator<Item = &'a Session>) -> String {
+    let mut content = format!(
+        "Recurring topic: {}\n\nDiscussed in {} sessions:",
+        topic.entities.join(", "),
+        topic.sessions.len()
+    );
+    for session in sessions {
+        let name = session.title.as_deref().unwrap_or(&session.session_id);
+        content.push_str(&format!(
+            "\n- {} ({})",
+            name,
+            session.started_at.format("%Y-%m-%d")
+        ));
+        if let Some(summary) = &session.summary {
+            content.push_str(": ");
+            content.push_str(summary);
+        }
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::session_indexing::{index_conversation, ChunkingConfig, Message};
+    use crate::storage::Storage;
+
+    fn index(conn: &Connection, session_id: &str, text: &str) {
+        let messages = vec![Message {
+            role: "user".to_string(),
+            content: text.to_string(),
+            ..Default::default()
+        }];
+        index_conversation(
+            conn,
+            session_id,
+            &messages,
+            &ChunkingConfig::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sessions_sharing_entities_are_linked() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                index(
+                    conn,
+                    "s1",
+                    "Planning the PostgreSQL migration with Alice Johnson on Kubernetes.",
+                );
+                index(
+                    conn,
+                    "s2",
+                    "Alice Johnson found that the PostgreSQL migration stalls on Kubernetes.",
+                );
+                index(conn, "s3", "Booked flights to Paris for the offsite.");
+
+                let config = TopicLinkConfig::default();
+                let dry = find_session_topics(conn, None, &config)?;
+                assert_eq!(dry.sessions_analyzed, 3);
+                assert_eq!(dry.links.len(), 1, "{:?}", dry.links);
+                assert_eq!(dry.topics.len(), 1);
+                assert!(dry.topics[0].memory_id.is_none());
+
+                let report = link_session_topics(conn, None, &config)?;
+                let link = &report.links[0];
+                assert_eq!(
+                    (link.session_a.as_str(), link.session_b.as_str()),
+                    ("s1", "s2")
+                );
+                assert!(link.shared_entities.len() >= 2);
+                assert_eq!(report.topics[0].sessions, vec!["s1", "s2"]);
+                let topic_id = report.topics[0].memory_id.unwrap();
+
+                let s1 = crate::intelligence::get_session(conn, "s1")?;
+                let related = s1.related_sessions();
+                assert_eq!(related.len(), 1);
+                assert_eq!(related[0].session_id, "s2");
+                assert_eq!(s1.metadata[TOPIC_MEMORY_KEY], topic_id);
+                assert!(crate::intelligence::get_session(conn, "s3")?
+                    .related_sessions()
+                    .is_empty());
+
+                // Re-running replaces the topic memory instead of piling up
+                let again = link_session_topics(conn, None, &config)?;
+                let new_id = again.topics[0].memory_id.unwrap();
+                assert_ne!(new_id, topic_id);
+                assert!(crate::storage::queries::get_memory(conn, topic_id).is_err());
+                let related = crate::intelligence::get_session(conn, "s2")?.related_sessions();
+                assert_eq!(related.len(), 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_min_shared_entities_threshold() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                index(conn, "a", "Alice Johnson reviewed the budget.");
+                index(conn, "b", "Alice Johnson booked the venue.");
+
+                let loose = TopicLinkConfig {
+                    min_shared_entities: 1,
+                    ..Default::default()
+                };
+                assert_eq!(find_session_topics(conn, None, &loose)?.links.len(), 1);
+                let strict = TopicLinkConfig {
+                    min_shared_entities: 5,
+                    ..Default::default()
+                };
+                assert!(find_session_topics(conn, None, &strict)?.links.is_empty());
+                Ok(())
+            })
+            .unwrap();
+    }
+}
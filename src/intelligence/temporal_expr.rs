@@ -0,0 +1,729 @@
+//! Temporal expressions in natural language
+//!
+//! Turns phrases like "last Tuesday", "two weeks ago", "past 3 days",
+//! "since 5 March", "between 01/02/2024 and 15/02/2024" or "in 2023" into a
+//! time range for search and timeline filters.
+//!
+//! Calendar boundaries (the start of a day, week or month) are taken in the
+//! caller's UTC offset, and all-numeric dates are read in its [`DateOrder`]:
+//! "03/04/2024" is 3 April for a `dmy` user and March 4 for an `mdy` one.
+//! ISO dates (`2024-04-03`) read the same everywhere. Workspaces store their
+//! offset and date order in `workspace_settings` (schema v57).
+
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+
+/// Order of day, month and year in all-numeric dates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateOrder {
+    /// 04/03/2024 is April 3 (US)
+    #[default]
+    Mdy,
+    /// 04/03/2024 is 4 March (most of Europe, Latin America, ...)
+    Dmy,
+    /// 2024/04/03 is April 3 (ISO, East Asia)
+    Ymd,
+}
+
+impl DateOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateOrder::Mdy => "mdy",
+            DateOrder::Dmy => "dmy",
+            DateOrder::Ymd => "ymd",
+        }
+    }
+}
+
+impl FromStr for DateOrder {
+    type Err = EngramError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mdy" | "us" => Ok(DateOrder::Mdy),
+            "dmy" | "eu" => Ok(DateOrder::Dmy),
+            "ymd" | "iso" => Ok(DateOrder::Ymd),
+            other => Err(EngramError::InvalidInput(format!(
+                "Unknown date order '{}'. Use mdy, dmy or ymd",
+                other
+            ))),
+        }
+    }
+}
+
+/// Parse a fixed UTC offset: `UTC`, `Z`, `+02:00`, `-0530`, `UTC+2` or
+/// `GMT-03:00`
+pub fn parse_utc_offset(s: &str) -> Result<FixedOffset> {
+    let invalid = || {
+        EngramError::InvalidInput(format!(
+            "Invalid timezone '{}'. Use a UTC offset such as +02:00, -0530 or UTC+1",
+            s
+        ))
+    };
+    let upper = s.trim().to_ascii_uppercase();
+    let rest = upper
+        .strip_prefix("UTC")
+        .or_else(|| upper.strip_prefix("GMT"))
+        .unwrap_or(&upper);
+    if rest.is_empty() || rest == "Z" {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset"));
+    }
+
+    let (sign, digits) = match rest.as_bytes()[0] {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if digits.len() > 2 => digits.split_at(digits.len() - 2),
+        None => (digits, "0"),
+    };
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 14 || minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// The moment and locale temporal expressions are resolved against
+#[derive(Debug, Clone, Copy)]
+pub struct TemporalContext {
+    pub now: DateTime<Utc>,
+    pub offset: FixedOffset,
+    pub date_order: DateOrder,
+}
+
+impl Default for TemporalContext {
+    fn default() -> Self {
+        Self::new(Utc::now())
+    }
+}
+
+impl TemporalContext {
+    /// UTC and month-first dates at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now,
+            offset: FixedOffset::east_opt(0).expect("zero offset"),
+            date_order: DateOrder::default(),
+        }
+    }
+
+    pub fn with_offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_date_order(mut self, date_order: DateOrder) -> Self {
+        self.date_order = date_order;
+        self
+    }
+
+    fn now_local(&self) -> NaiveDateTime {
+        self.now.with_timezone(&self.offset).naive_local()
+    }
+
+    fn utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        self.offset
+            .from_local_datetime(&local)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| local.and_utc())
+    }
+}
+
+/// A time range found in text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    /// Inclusive start
+    pub after: Option<DateTime<Utc>>,
+    /// Exclusive end
+    pub before: Option<DateTime<Utc>>,
+    /// The words of the expression, lowercased
+    pub expression: String,
+}
+
+/// The first temporal expression in `text`, if any
+pub fn parse_time_range(text: &str, ctx: &TemporalContext) -> Option<TimeRange> {
+    let lower = text.to_ascii_lowercase();
+    let words: Vec<&str> = lower
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| matches!(c, ',' | '?' | '!' | ';' | '(' | ')' | '"' | '\''))
+                .trim_end_matches('.')
+        })
+        .filter(|w| !w.is_empty())
+        .collect();
+    let parser = Parser {
+        words: &words,
+        ctx,
+        now: ctx.now_local(),
+        today: ctx.now_local().date(),
+    };
+
+    (0..words.len()).find_map(|i| {
+        let (after, before, consumed) = parser.range_at(i)?;
+        Some(TimeRange {
+            after: after.map(|t| ctx.utc(t)),
+            before: before.map(|t| ctx.utc(t)),
+            expression: words[i..i + consumed].join(" "),
+        })
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// A stretch of local time; `end` is exclusive, `None` meaning "until now"
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: NaiveDateTime,
+    end: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Local range bounds and the number of words they took
+type Parsed = (Option<NaiveDateTime>, Option<NaiveDateTime>, usize);
+
+struct Parser<'a> {
+    words: &'a [&'a str],
+    ctx: &'a TemporalContext,
+    now: NaiveDateTime,
+    today: NaiveDate,
+}
+
+impl Parser<'_> {
+    fn word(&self, i: usize) -> &str {
+        self.words.get(i).copied().unwrap_or("")
+    }
+
+    fn range_at(&self, i: usize) -> Option<Parsed> {
+        let keyword = match self.word(i) {
+            "between" => self.point(i + 1, true).and_then(|(from, n)| {
+                (self.word(i + 1 + n) == "and").then_some(())?;
+                let (to, m) = self.point(i + 2 + n, true)?;
+                Some((Some(from.start), to.end, 2 + n + m))
+            }),
+            "from" => self.point(i + 1, true).map(|(from, n)| {
+                match self.word(i + 1 + n) {
+                    "to" | "until" | "till" | "through" => {
+                        if let Some((to, m)) = self.point(i + 2 + n, true) {
+                            return (Some(from.start), to.end, 2 + n + m);
+                        }
+                    }
+                    _ => {}
+                }
+                (Some(from.start), from.end, 1 + n)
+            }),
+            "since" => self
+                .point(i + 1, true)
+                .map(|(span, n)| (Some(span.start), None, 1 + n)),
+            "after" => self
+                .point(i + 1, true)
+                .map(|(span, n)| (Some(span.end.unwrap_or(span.start)), None, 1 + n)),
+            "before" => self
+                .point(i + 1, true)
+                .map(|(span, n)| (None, Some(span.start), 1 + n)),
+            "until" | "till" => self
+                .point(i + 1, true)
+                .map(|(span, n)| (None, span.end, 1 + n)),
+            "on" | "in" | "during" => self
+                .point(i + 1, true)
+                .map(|(span, n)| (Some(span.start), span.end, 1 + n)),
+            // "last 3 days", "past week": a rolling window up to now
+            "last" | "past" => {
+                self.count_unit(i + 1, self.word(i) == "past")
+                    .and_then(|(count, unit, n)| {
+                        Some((Some(shift(self.now, unit, -count)?), None, 1 + n))
+                    })
+            }
+            _ => None,
+        };
+        keyword.or_else(|| {
+            self.point(i, false)
+                .map(|(span, n)| (Some(span.start), span.end, n))
+        })
+    }
+
+    /// A single point in time, day, week, month or year at word `i`.
+    /// `bare` also accepts a lone month name or year, which are only
+    /// temporal after a preposition ("in March", "since 2023").
+    fn point(&self, i: usize, bare: bool) -> Option<(Span, usize)> {
+        let word = self.word(i);
+        match word {
+            "today" => return Some((self.day(self.today), 1)),
+            "yesterday" => return Some((self.day(self.today - Duration::days(1)), 1)),
+            "tomorrow" => return Some((self.day(self.today + Duration::days(1)), 1)),
+            "last" | "this" | "next" => {
+                let direction = match word {
+                    "last" => -1,
+                    "this" => 0,
+                    _ => 1,
+                };
+                if let Some(span) = self.relative_period(self.word(i + 1), direction) {
+                    return Some((span, 2));
+                }
+            }
+            _ => {}
+        }
+
+        // "two weeks ago"
+        if let Some((count, unit, n)) = self.count_unit(i, false) {
+            if self.word(i + n) == "ago" {
+                return self.ago(count, unit).map(|span| (span, n + 1));
+            }
+        }
+
+        if let Some(weekday) = full_weekday(word) {
+            return Some((self.day(self.previous_weekday(weekday, true)), 1));
+        }
+
+        self.absolute(i, bare)
+    }
+
+    /// "week", "month", "year" or a weekday, relative to the current one
+    fn relative_period(&self, word: &str, direction: i64) -> Option<Span> {
+        if let Some(weekday) = weekday(word) {
+            let date = match direction {
+                -1 => self.previous_weekday(weekday, false),
+                0 => {
+                    self.week_start(self.today)
+                        + Duration::days(weekday.num_days_from_monday().into())
+                }
+                _ => {
+                    let back = self.previous_weekday(weekday, true);
+                    back + Duration::days(7)
+                }
+            };
+            return Some(self.day(date));
+        }
+        let (unit, start) = match unit(word)? {
+            Unit::Week => (Unit::Week, self.week_start(self.today)),
+            Unit::Month => (Unit::Month, self.today.with_day(1)?),
+            Unit::Year => (Unit::Year, self.today.with_ordinal(1)?),
+            Unit::Day => (Unit::Day, self.today),
+            Unit::Minute | Unit::Hour => return None,
+        };
+        let start = shift(start.and_hms_opt(0, 0, 0)?, unit, direction)?;
+        Some(Span {
+            start,
+            end: if direction == 0 {
+                None
+            } else {
+                Some(shift(start, unit, 1)?)
+            },
+        })
+    }
+
+    /// The window one `unit` wide that started `count` units ago
+    fn ago(&self, count: i64, unit: Unit) -> Option<Span> {
+        let start = match unit {
+            Unit::Minute | Unit::Hour => shift(self.now, unit, -count)?,
+            _ => shift(self.today.and_hms_opt(0, 0, 0)?, unit, -count)?,
+        };
+        let end = shift(start, unit, 1)?;
+        Some(Span {
+            start,
+            end: Some(end),
+        })
+    }
+
+    /// A count and unit at `i` ("3 days", "two weeks", "a month"); with
+    /// `implicit_one` a bare unit counts as one ("past week")
+    fn count_unit(&self, i: usize, implicit_one: bool) -> Option<(i64, Unit, usize)> {
+        if let Some(count) = number(self.word(i)) {
+            let mut n = 1;
+            if self.word(i) == "couple" && self.word(i + 1) == "of" {
+                n = 2;
+            }
+            return unit(self.word(i + n)).map(|unit| (count, unit, n + 1));
+        }
+        if implicit_one {
+            return unit(self.word(i)).map(|unit| (1, unit, 1));
+        }
+        None
+    }
+
+    /// ISO and numeric dates, and dates with month names
+    fn absolute(&self, i: usize, bare: bool) -> Option<(Span, usize)> {
+        let word = self.word(i);
+
+        if let Some(date) = self.numeric_date(word) {
+            return Some((self.day(date), 1));
+        }
+
+        // "March 5", "March 5th, 2024", "March 2024", "March"
+        if let Some(month) = month(word) {
+            if let Some(day) = day_of_month(self.word(i + 1)) {
+                if let Some(year) = year(self.word(i + 2)) {
+                    return Some((self.day(NaiveDate::from_ymd_opt(year, month, day)?), 3));
+                }
+                return Some((self.day(self.recent_date(month, day)?), 2));
+            }
+            if let Some(year) = year(self.word(i + 1)) {
+                return Some((month_span(year, month)?, 2));
+            }
+            if bare {
+                let year = if month <= self.today.month() {
+                    self.today.year()
+                } else {
+                    self.today.year() - 1
+                };
+                return Some((month_span(year, month)?, 1));
+            }
+            return None;
+        }
+
+        // "5 March", "5th of March 2024"
+        if let Some(day) = day_of_month(word) {
+            let of = usize::from(self.word(i + 1) == "of");
+            let month = month(self.word(i + 1 + of))?;
+            if let Some(year) = year(self.word(i + 2 + of)) {
+                return Some((self.day(NaiveDate::from_ymd_opt(year, month, day)?), 3 + of));
+            }
+            return Some((self.day(self.recent_date(month, day)?), 2 + of));
+        }
+
+        if bare {
+            if let Some(year) = year(word) {
+                let start = NaiveDate::from_ymd_opt(year, 1, 1)?.and_hms_opt(0, 0, 0)?;
+                return Some((
+                    Span {
+                        start,
+                        end: Some(shift(start, Unit::Year, 1)?),
+                    },
+                    1,
+                ));
+            }
+        }
+        None
+    }
+
+    /// `2024-04-03`, `03/04/2024`, `3.4.24` or `03/04`
+    fn numeric_date(&self, word: &str) -> Option<NaiveDate> {
+        let parts: Vec<&str> = word.split(['/', '-', '.']).collect();
+        if parts.len() < 2
+            || parts.len() > 3
+            || parts
+                .iter()
+                .any(|p| p.is_empty() || !p.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return None;
+        }
+        let numbers: Vec<u32> = parts
+            .iter()
+            .map(|p| p.parse().ok())
+            .collect::<Option<_>>()?;
+
+        if parts.len() == 2 {
+            let (month, day) = match self.ctx.date_order {
+                DateOrder::Dmy => (numbers[1], numbers[0]),
+                DateOrder::Mdy | DateOrder::Ymd => (numbers[0], numbers[1]),
+            };
+            return self.recent_date(month, day);
+        }
+
+        let full_year = |y: u32| if y < 100 { 2000 + y as i32 } else { y as i32 };
+        let (year, month, day) = if parts[0].len() == 4 {
+            (full_year(numbers[0]), numbers[1], numbers[2])
+        } else {
+            match self.ctx.date_order {
+                DateOrder::Mdy => (full_year(numbers[2]), numbers[0], numbers[1]),
+                DateOrder::Dmy => (full_year(numbers[2]), numbers[1], numbers[0]),
+                DateOrder::Ymd => (full_year(numbers[0]), numbers[1], numbers[2]),
+            }
+        };
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    /// `month`/`day` in the current year, or last year if that is still ahead
+    fn recent_date(&self, month: u32, day: u32) -> Option<NaiveDate> {
+        let date = NaiveDate::from_ymd_opt(self.today.year(), month, day)?;
+        if date > self.today {
+            NaiveDate::from_ymd_opt(self.today.year() - 1, month, day)
+        } else {
+            Some(date)
+        }
+    }
+
+    /// The latest `weekday` before today (or on it, with `include_today`)
+    fn previous_weekday(&self, weekday: Weekday, include_today: bool) -> NaiveDate {
+        let mut back =
+            (7 + self.today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        if back == 0 && !include_today {
+            back = 7;
+        }
+        self.today - Duration::days(back.into())
+    }
+
+    fn week_start(&self, date: NaiveDate) -> NaiveDate {
+        date - Duration::days(date.weekday().num_days_from_monday().into())
+    }
+
+    fn day(&self, date: NaiveDate) -> Span {
+        let start = date.and_hms_opt(0, 0, 0).expect("midnight");
+        Span {
+            start,
+            end: Some(start + Duration::days(1)),
+        }
+    }
+}
+
+fn month_span(year: i32, month: u32) -> Option<Span> {
+    let start = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+    Some(Span {
+        start,
+        end: Some(shift(start, Unit::Month, 1)?),
+    })
+}
+
+/// `time` moved by `count` units (backwards when negative)
+fn shift(time: NaiveDateTime, unit: Unit, count: i64) -> Option<NaiveDateTime> {
+    let months = |n: i64| Months::new(n.unsigned_abs().try_into().unwrap_or(u32::MAX));
+    match unit {
+        Unit::Minute => time.checked_add_signed(Duration::minutes(count)),
+        Unit::Hour => time.checked_add_signed(Duration::hours(count)),
+        Unit::Day => time.checked_add_signed(Duration::days(count)),
+        Unit::Week => time.checked_add_signed(Duration::weeks(count)),
+        Unit::Month | Unit::Year => {
+            let n = if unit == Unit::Year {
+                count * 12
+            } else {
+                count
+            };
+            if n >= 0 {
+                time.checked_add_months(months(n))
+            } else {
+                time.checked_sub_months(months(n))
+            }
+        }
+    }
+}
+
+fn number(word: &str) -> Option<i64> {
+    if let Ok(n) = word.parse::<i64>() {
+        return (0..=10_000).contains(&n).then_some(n);
+    }
+    Some(match word {
+        "a" | "an" | "one" => 1,
+        "two" | "couple" => 2,
+        "three" | "few" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        _ => return None,
+    })
+}
+
+fn unit(word: &str) -> Option<Unit> {
+    Some(match word {
+        "minute" | "minutes" | "min" | "mins" => Unit::Minute,
+        "hour" | "hours" | "hr" | "hrs" => Unit::Hour,
+        "day" | "days" => Unit::Day,
+        "week" | "weeks" => Unit::Week,
+        "month" | "months" => Unit::Month,
+        "year" | "years" => Unit::Year,
+        _ => return None,
+    })
+}
+
+/// Full weekday names, which are temporal on their own
+fn full_weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Weekday names and abbreviations, after "last", "this" or "next"
+fn weekday(word: &str) -> Option<Weekday> {
+    full_weekday(word).or(match word {
+        "mon" => Some(Weekday::Mon),
+        "tue" | "tues" => Some(Weekday::Tue),
+        "wed" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" => Some(Weekday::Thu),
+        "fri" => Some(Weekday::Fri),
+        "sat" => Some(Weekday::Sat),
+        "sun" => Some(Weekday::Sun),
+        _ => None,
+    })
+}
+
+fn month(word: &str) -> Option<u32> {
+    Some(match word {
+        "january" | "jan" => 1,
+        "february" | "feb" => 2,
+        "march" | "mar" => 3,
+        "april" | "apr" => 4,
+        "may" => 5,
+        "june" | "jun" => 6,
+        "july" | "jul" => 7,
+        "august" | "aug" => 8,
+        "september" | "sep" | "sept" => 9,
+        "october" | "oct" => 10,
+        "november" | "nov" => 11,
+        "december" | "dec" => 12,
+        _ => return None,
+    })
+}
+
+/// "5", "5th", "21st"
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word
+        .strip_suffix("st")
+        .or_else(|| word.strip_suffix("nd"))
+        .or_else(|| word.strip_suffix("rd"))
+        .or_else(|| word.strip_suffix("th"))
+        .unwrap_or(word);
+    let day: u32 = digits.parse().ok()?;
+    (1..=31).contains(&day).then_some(day)
+}
+
+fn year(word: &str) -> Option<i32> {
+    let year: i32 = word.parse().ok()?;
+    (word.len() == 4 && (1900..=2200).contains(&year)).then_some(year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wednesday 2024-03-13 15:30 UTC
+    fn ctx() -> TemporalContext {
+        TemporalContext::new("2024-03-13T15:30:00Z".parse().unwrap())
+    }
+
+    fn range(text: &str, ctx: &TemporalContext) -> (Option<String>, Option<String>) {
+        let range = parse_time_range(text, ctx).unwrap_or_else(|| panic!("no range in {text}"));
+        let fmt = |t: Option<DateTime<Utc>>| t.map(|t| t.format("%Y-%m-%d %H:%M").to_string());
+        (fmt(range.after), fmt(range.before))
+    }
+
+    fn day(date: &str) -> (Option<String>, Option<String>) {
+        let start: NaiveDate = date.parse().unwrap();
+        (
+            Some(format!("{} 00:00", start)),
+            Some(format!("{} 00:00", start + Duration::days(1))),
+        )
+    }
+
+    #[test]
+    fn test_relative_days() {
+        let ctx = ctx();
+        assert_eq!(range("what did I note yesterday", &ctx), day("2024-03-12"));
+        assert_eq!(range("notes from last Tuesday?", &ctx), day("2024-03-12"));
+        assert_eq!(range("on monday", &ctx), day("2024-03-11"));
+        assert_eq!(range("last wed", &ctx), day("2024-03-06"));
+        assert_eq!(range("three days ago", &ctx), day("2024-03-10"));
+        assert_eq!(
+            range("two weeks ago", &ctx),
+            (
+                Some("2024-02-28 00:00".to_string()),
+                Some("2024-03-06 00:00".to_string())
+            )
+        );
+        assert_eq!(
+            range("in the past 3 days", &ctx),
+            (Some("2024-03-10 15:30".to_string()), None)
+        );
+        assert_eq!(
+            range("last week", &ctx),
+            (
+                Some("2024-03-04 00:00".to_string()),
+                Some("2024-03-11 00:00".to_string())
+            )
+        );
+        assert_eq!(
+            range("this month", &ctx),
+            (Some("2024-03-01 00:00".to_string()), None)
+        );
+        assert!(parse_time_range("deploy the service", &ctx).is_none());
+        assert!(parse_time_range("may I ask about sun exposure", &ctx).is_none());
+    }
+
+    #[test]
+    fn test_absolute_dates_follow_date_order() {
+        let us = ctx();
+        let eu = ctx().with_date_order(DateOrder::Dmy);
+        assert_eq!(range("on 03/04/2024", &us), day("2024-03-04"));
+        assert_eq!(range("on 03/04/2024", &eu), day("2024-04-03"));
+        assert_eq!(range("am 5.3.2024", &eu), day("2024-03-05"));
+        assert_eq!(range("2024-02-29", &eu), day("2024-02-29"));
+        assert_eq!(range("March 5th", &us), day("2024-03-05"));
+        assert_eq!(range("5 March 2023", &us), day("2023-03-05"));
+        // Later this year means last year
+        assert_eq!(range("on 12 december", &us), day("2023-12-12"));
+        assert_eq!(
+            range("in May", &us),
+            (
+                Some("2023-05-01 00:00".to_string()),
+                Some("2023-06-01 00:00".to_string())
+            )
+        );
+        assert_eq!(
+            range("between 01/02/2024 and 15/02/2024", &eu),
+            (
+                Some("2024-02-01 00:00".to_string()),
+                Some("2024-02-16 00:00".to_string())
+            )
+        );
+        assert_eq!(
+            range("since 2023", &us),
+            (Some("2023-01-01 00:00".to_string()), None)
+        );
+        assert_eq!(
+            range("before March 1", &us),
+            (None, Some("2024-03-01 00:00".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_day_boundaries_use_offset() {
+        // 15:30 UTC is already Thursday in Tokyo
+        let tokyo = ctx().with_offset(parse_utc_offset("+09:00").unwrap());
+        let parsed = parse_time_range("today", &tokyo).unwrap();
+        assert_eq!(
+            parsed.after.unwrap().to_rfc3339(),
+            "2024-03-13T15:00:00+00:00"
+        );
+        assert_eq!(parsed.expression, "today");
+
+        assert_eq!(parse_utc_offset("UTC").unwrap().local_minus_utc(), 0);
+        assert_eq!(parse_utc_offset("-0530").unwrap().local_minus_utc(), -19800);
+        assert_eq!(parse_utc_offset("GMT+2").unwrap().local_minus_utc(), 7200);
+        assert!(parse_utc_offset("Europe/Paris").is_err());
+        assert_eq!("dmy".parse::<DateOrder>().unwrap(), DateOrder::Dmy);
+    }
+}
@@ -0,0 +1,460 @@
+//! Transcript de-noising before session indexing
+//!
+//! Drops low-information messages so they don't pollute transcript search:
+//! - **Greetings and acknowledgements**: short messages made only of filler
+//!   words ("thanks!", "ok, sounds good")
+//! - **Duplicates**: a message repeating the one before it verbatim
+//! - **Repeated errors**: an error identical to the last one (ignoring
+//!   numbers) is folded into it along with the retried tool call, and the
+//!   first occurrence is annotated with the repeat count
+//! - **Boilerplate**: empty or content-free tool output ("ok", "{}", "null")
+//!   and anything matching caller-supplied patterns
+//!
+//! Counts of what was removed are kept in [`DenoiseStats`], which session
+//! indexing stores on the session record.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::intelligence::session_indexing::{Message, MessageKind};
+
+/// Configuration for the de-noise pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DenoiseConfig {
+    /// Drop greetings and acknowledgements (default: true)
+    pub drop_greetings: bool,
+    /// Longest message still considered a greeting (default: 60)
+    pub max_greeting_chars: usize,
+    /// Drop a message that repeats the previous one (default: true)
+    pub drop_duplicates: bool,
+    /// Fold repeated identical errors into the first (default: true)
+    pub collapse_repeated_errors: bool,
+    /// Drop empty and content-free tool output (default: true)
+    pub drop_boilerplate: bool,
+    /// Extra regexes; any message matching one is dropped as boilerplate
+    pub boilerplate_patterns: Vec<String>,
+}
+
+impl Default for DenoiseConfig {
+    fn default() -> Self {
+        Self {
+            drop_greetings: true,
+            max_greeting_chars: 60,
+            drop_duplicates: true,
+            collapse_repeated_errors: true,
+            drop_boilerplate: true,
+            boilerplate_patterns: Vec::new(),
+        }
+    }
+}
+
+impl DenoiseConfig {
+    /// Session metadata key recording the config a session was indexed with
+    pub const METADATA_KEY: &'static str = "denoise_config";
+
+    /// The config recorded in a session's metadata by an earlier indexing run
+    pub fn from_session_metadata(metadata: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        metadata
+            .get(Self::METADATA_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+}
+
+/// Counts of content removed by the de-noise pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DenoiseStats {
+    /// Messages before de-noising
+    pub messages_in: usize,
+    /// Greetings and acknowledgements dropped
+    pub greetings: usize,
+    /// Verbatim repeats dropped
+    pub duplicates: usize,
+    /// Repeated errors and their retried tool calls folded away
+    pub repeated_errors: usize,
+    /// Boilerplate messages dropped
+    pub boilerplate: usize,
+    /// Characters of message content removed
+    pub chars_removed: usize,
+}
+
+impl DenoiseStats {
+    /// Session metadata key holding the accumulated counts
+    pub const METADATA_KEY: &'static str = "denoise";
+
+    /// Total messages removed
+    pub fn messages_removed(&self) -> usize {
+        self.greetings + self.duplicates + self.repeated_errors + self.boilerplate
+    }
+
+    /// Add another run's counts (e.g. from a delta update)
+    pub fn merge(&mut self, other: &Self) {
+        self.messages_in += other.messages_in;
+        self.greetings += other.greetings;
+        self.duplicates += other.duplicates;
+        self.repeated_errors += other.repeated_errors;
+        self.boilerplate += other.boilerplate;
+        self.chars_removed += other.chars_removed;
+    }
+
+    /// The counts recorded in a session's metadata
+    pub fn from_session_metadata(metadata: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        metadata
+            .get(Self::METADATA_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+}
+
+/// Messages that survived de-noising
+#[derive(Debug, Clone)]
+pub struct Denoised {
+    /// Kept messages, in order
+    pub messages: Vec<Message>,
+    /// Index in the input of each kept message
+    pub kept: Vec<usize>,
+    /// What was removed
+    pub stats: DenoiseStats,
+}
+
+/// Words that carry no information on their own
+const FILLER_WORDS: &str = "\
+    a afternoon all alright appreciate awesome bye cheers cool day do evening fine for \
+    gonna good goodbye got great hello help hey hi hmm it k kk lol morning much nice \
+    noted np ok okay perfect please problem see so sounds sure thank thanks that the \
+    there thx ty understood very welcome will works you your youre";
+
+/// Tool output that says nothing beyond "it ran"
+static BOILERPLATE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)^\s*(ok|okay|done|success(ful)?|true|null|none|\{\s*\}|\[\s*\]|""|'')\.?\s*$"#,
+    )
+    .expect("valid regex")
+});
+
+/// Markers of an error message
+const ERROR_MARKERS: &[&str] = &[
+    "error",
+    "exception",
+    "failed",
+    "failure",
+    "traceback",
+    "permission denied",
+    "not found",
+    "timed out",
+];
+
+/// Run the de-noise pass over a transcript
+pub fn denoise_transcript(messages: &[Message], config: &DenoiseConfig) -> Result<Denoised> {
+    let patterns = config
+        .boilerplate_patterns
+        .iter()
+        .map(|p| {
+            Regex::new(p).map_err(|e| {
+                EngramError::InvalidInput(format!("Invalid boilerplate pattern '{}': {}", p, e))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut stats = DenoiseStats {
+        messages_in: messages.len(),
+        ..Default::default()
+    };
+    let mut kept: Vec<usize> = Vec::with_capacity(messages.len());
+    // Signature of the last error kept, with its position in `kept`
+    let mut last_error: Option<(String, usize)> = None;
+    let mut repeats: HashMap<usize, usize> = HashMap::new();
+
+    for (idx, msg) in messages.iter().enumerate() {
+        let is_boilerplate = (config.drop_boilerplate
+            && msg.kind == MessageKind::ToolResult
+            && (msg.content.trim().is_empty() || BOILERPLATE_RE.is_match(&msg.content)))
+            || patterns.iter().any(|re| re.is_match(&msg.content));
+        if is_boilerplate {
+            stats.boilerplate += 1;
+            stats.chars_removed += msg.content.len();
+            continue;
+        }
+
+        if config.drop_greetings && is_greeting(msg, config.max_greeting_chars) {
+            stats.greetings += 1;
+            stats.chars_removed += msg.content.len();
+            continue;
+        }
+
+        if config.drop_duplicates
+            && kept
+                .last()
+                .is_some_and(|&prev| same_message(&messages[prev], msg))
+        {
+            stats.duplicates += 1;
+            stats.chars_removed += msg.content.len();
+            continue;
+        }
+
+        if config.collapse_repeated_errors {
+            if is_error(msg) {
+                let signature = error_signature(msg);
+                if let Some((_, first)) = last_error.as_ref().filter(|(s, _)| *s == signature) {
+                    let first = *first;
+                    // The call that was retried to produce this error goes too
+                    if let Some(&prev) = kept.last() {
+                        let call = &messages[prev];
+                        if kept.len() - 1 > first
+                            && call.kind == MessageKind::ToolCall
+                            && call.tool_name == msg.tool_name
+                        {
+                            kept.pop();
+                            stats.repeated_errors += 1;
+                            stats.chars_removed += call.content.len();
+                        }
+                    }
+                    *repeats.entry(first).or_default() += 1;
+                    stats.repeated_errors += 1;
+                    stats.chars_removed += msg.content.len();
+                    continue;
+                }
+                last_error = Some((signature, kept.len()));
+            } else if msg.kind == MessageKind::ToolResult || msg.role == "user" {
+                // A success or new user input ends the retry loop
+                last_error = None;
+            }
+        }
+
+        kept.push(idx);
+    }
+
+    let messages = kept
+        .iter()
+        .enumerate()
+        .map(|(pos, &idx)| {
+            let mut msg = messages[idx].clone();
+            if let Some(n) = repeats.get(&pos) {
+                msg.content = format!(
+                    "{}\n[repeated {} more time{}]",
+                    msg.content,
+                    n,
+                    if *n == 1 { "" } else { "s" }
+                );
+            }
+            msg
+        })
+        .collect();
+
+    Ok(Denoised {
+        messages,
+        kept,
+        stats,
+    })
+}
+
+/// Short conversational text made only of filler words
+fn is_greeting(msg: &Message, max_chars: usize) -> bool {
+    if msg.kind != MessageKind::Text || msg.role == "system" {
+        return false;
+    }
+    let content = msg.content.trim();
+    if content.is_empty() || content.chars().count() > max_chars {
+        return false;
+    }
+    content
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.replace('\'', "").to_lowercase())
+        .filter(|w| !w.is_empty())
+        .all(|w| FILLER_WORDS.split_whitespace().any(|f| f == w))
+}
+
+/// Same sender, kind, tool and (whitespace-normalized) content
+fn same_message(a: &Message, b: &Message) -> bool {
+    a.role == b.role
+        && a.kind == b.kind
+        && a.tool_name == b.tool_name
+        && a.arguments == b.arguments
+        && normalize_whitespace(&a.content) == normalize_whitespace(&b.content)
+}
+
+fn is_error(msg: &Message) -> bool {
+    if msg.kind == MessageKind::ToolCall {
+        return false;
+    }
+    let head: String = msg
+        .content
+        .chars()
+        .take(300)
+        .collect::<String>()
+        .to_lowercase();
+    ERROR_MARKERS.iter().any(|m| head.contains(m))
+}
+
+/// Errors match when they differ only in numbers (line numbers, timestamps,
+/// attempt counters) and whitespace
+fn error_signature(msg: &Message) -> String {
+    let digits_masked: String = msg
+        .content
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '#' } else { c })
+        .collect();
+    format!(
+        "{}|{}|{}",
+        msg.role,
+        msg.tool_name.as_deref().unwrap_or_default(),
+        normalize_whitespace(&digits_masked)
+    )
+}
+
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn tool_call(name: &str) -> Message {
+        Message {
+            role: "assistant".to_string(),
+            kind: MessageKind::ToolCall,
+            tool_name: Some(name.to_string()),
+            arguments: Some(serde_json::json!({"path": "/etc/app.toml"})),
+            ..Default::default()
+        }
+    }
+
+    fn tool_result(name: &str, content: &str) -> Message {
+        Message {
+            role: "tool".to_string(),
+            content: content.to_string(),
+            kind: MessageKind::ToolResult,
+            tool_name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_drops_greetings_and_boilerplate() {
+        let messages = vec![
+            text("user", "Hi there!"),
+            text("assistant", "Hello! How can I help?"),
+            text("user", "Why does the deploy fail on staging?"),
+            tool_call("read_config"),
+            tool_result("read_config", "{}"),
+            text(
+                "assistant",
+                "The staging config is empty, so the deploy has no target.",
+            ),
+            text("user", "Thanks, sounds good 👍"),
+        ];
+
+        let denoised = denoise_transcript(&messages, &DenoiseConfig::default()).unwrap();
+
+        assert_eq!(denoised.kept, vec![1, 2, 3, 5]);
+        assert_eq!(denoised.stats.greetings, 2);
+        assert_eq!(denoised.stats.boilerplate, 1);
+        assert_eq!(denoised.stats.messages_removed(), 3);
+        assert_eq!(denoised.stats.messages_in, 7);
+    }
+
+    #[test]
+    fn test_collapses_repeated_errors_with_retries() {
+        let messages = vec![
+            text("user", "Read the app config"),
+            tool_call("read_file"),
+            tool_result("read_file", "Error: permission denied (attempt 1)"),
+            tool_call("read_file"),
+            tool_result("read_file", "Error: permission denied (attempt 2)"),
+            tool_call("read_file"),
+            tool_result("read_file", "Error: permission denied (attempt 3)"),
+            text("assistant", "I can't read that file."),
+        ];
+
+        let denoised = denoise_transcript(&messages, &DenoiseConfig::default()).unwrap();
+
+        assert_eq!(denoised.kept, vec![0, 1, 2, 7]);
+        assert_eq!(denoised.stats.repeated_errors, 4);
+        assert!(denoised.messages[2]
+            .content
+            .ends_with("[repeated 2 more times]"));
+    }
+
+    #[test]
+    fn test_success_ends_retry_loop() {
+        let messages = vec![
+            tool_call("fetch"),
+            tool_result("fetch", "error: timed out"),
+            tool_call("fetch"),
+            tool_result("fetch", "200 rows"),
+            tool_call("fetch"),
+            tool_result("fetch", "error: timed out"),
+        ];
+
+        let denoised = denoise_transcript(&messages, &DenoiseConfig::default()).unwrap();
+
+        assert_eq!(denoised.kept.len(), 6);
+        assert_eq!(denoised.stats.repeated_errors, 0);
+    }
+
+    #[test]
+    fn test_duplicates_and_custom_patterns() {
+        let config = DenoiseConfig {
+            boilerplate_patterns: vec![r"^\[auto-saved\]".to_string()],
+            ..Default::default()
+        };
+        let messages = vec![
+            text("user", "Rebuild the search index"),
+            text("user", "Rebuild  the search index"),
+            text("assistant", "[auto-saved] draft 3"),
+            text("assistant", "Rebuilt; 1,204 documents indexed."),
+        ];
+
+        let denoised = denoise_transcript(&messages, &config).unwrap();
+
+        assert_eq!(denoised.kept, vec![0, 3]);
+        assert_eq!(denoised.stats.duplicates, 1);
+        assert_eq!(denoised.stats.boilerplate, 1);
+
+        let invalid = DenoiseConfig {
+            boilerplate_patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(denoise_transcript(&messages, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_disabled_rules_keep_everything() {
+        let config = DenoiseConfig {
+            drop_greetings: false,
+            drop_duplicates: false,
+            collapse_repeated_errors: false,
+            drop_boilerplate: false,
+            ..Default::default()
+        };
+        let messages = vec![
+            text("user", "hi"),
+            text("user", "hi"),
+            tool_result("ls", ""),
+        ];
+
+        let denoised = denoise_transcript(&messages, &config).unwrap();
+
+        assert_eq!(denoised.kept, vec![0, 1, 2]);
+        assert_eq!(
+            denoised.stats,
+            DenoiseStats {
+                messages_in: 3,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! Heuristic workspace assignment
+//!
+//! Memories created without a workspace land in `default`. When automatic
+//! assignment is on, a workspace is inferred instead, from the first of:
+//!
+//! 1. A `project_path` or `cwd` in the memory's metadata — the directory
+//!    names along the path are matched against existing workspaces, and
+//!    failing that the project directory becomes the workspace
+//! 2. The default workspace of the active persona
+//! 3. Content similarity — the workspace whose nearest memories are, on
+//!    average, at least `threshold` similar to the new one
+//!
+//! The chosen workspace and why it was chosen are recorded under
+//! [`WORKSPACE_ASSIGNMENT_KEY`] in the memory's metadata.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::embedding::{cosine_similarity, get_embedding};
+use crate::error::Result;
+use crate::storage::queries::list_workspaces;
+use crate::types::{normalize_workspace, MemoryId};
+
+/// Metadata key holding the [`WorkspaceAssignment`] of a memory
+pub const WORKSPACE_ASSIGNMENT_KEY: &str = "workspace_assignment";
+
+/// Environment variable that turns automatic assignment on for every
+/// `memory_create` call that doesn't opt out
+pub const AUTO_WORKSPACE_ENV: &str = "ENGRAM_AUTO_WORKSPACE";
+
+/// Environment variable overriding [`DEFAULT_SIMILARITY_THRESHOLD`]
+pub const AUTO_WORKSPACE_THRESHOLD_ENV: &str = "ENGRAM_AUTO_WORKSPACE_THRESHOLD";
+
+/// Minimum mean similarity to a workspace's nearest memories
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.75;
+
+/// Nearest memories per workspace averaged for the similarity score
+const NEAREST_PER_WORKSPACE: usize = 3;
+
+/// Metadata keys naming the directory a memory was created from, most
+/// specific first
+const PATH_KEYS: &[&str] = &["project_path", "cwd"];
+
+/// Directory names that say where in a project one is, not which project
+const GENERIC_DIRS: &[&str] = &[
+    "src", "lib", "bin", "app", "apps", "pkg", "packages", "crates", "cmd", "internal", "test",
+    "tests", "docs", "scripts", "build", "target", "dist", "out", "tmp",
+];
+
+/// Directories whose children are user homes rather than projects
+const HOME_ROOTS: &[&str] = &["home", "Users"];
+
+/// What a workspace was inferred from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignmentSource {
+    /// `project_path` or `cwd` metadata
+    Path,
+    /// The active persona's default workspace
+    Persona,
+    /// Memories similar to the new one
+    Similarity,
+}
+
+/// An inferred workspace and the reason for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceAssignment {
+    pub workspace: String,
+    pub source: AssignmentSource,
+    pub reason: String,
+    /// Mean similarity of the nearest memories, for similarity assignments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    /// The nearest memories, for similarity assignments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub similar_memories: Vec<MemoryId>,
+}
+
+/// Whether automatic assignment is on: the call's `auto_workspace` flag if
+/// given, else [`AUTO_WORKSPACE_ENV`]
+pub fn auto_workspace_enabled(flag: Option<bool>) -> bool {
+    flag.unwrap_or_else(|| {
+        std::env::var(AUTO_WORKSPACE_ENV)
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Similarity threshold from [`AUTO_WORKSPACE_THRESHOLD_ENV`], or the default
+pub fn similarity_threshold() -> f32 {
+    std::env::var(AUTO_WORKSPACE_THRESHOLD_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse::<f32>().ok())
+        .filter(|t| (0.0..=1.0).contains(t))
+        .unwrap_or(DEFAULT_SIMILARITY_THRESHOLD)
+}
+
+/// Infer a workspace, trying the path metadata, then `persona` (its name and
+/// default workspace), then the similarity of `embed()` to stored memories.
+/// `embed` is only called if the first two find nothing.
+pub fn assign_workspace(
+    conn: &Connection,
+    metadata: &HashMap<String, Value>,
+    persona: Option<(&str, &str)>,
+    embed: impl FnOnce() -> Option<Vec<f32>>,
+    threshold: f32,
+) -> Result<Option<WorkspaceAssignment>> {
+    if let Some(assignment) = from_path(conn, metadata)? {
+        return Ok(Some(assignment));
+    }
+    if let Some(assignment) = persona.and_then(|(name, ws)| from_persona(name, ws)) {
+        return Ok(Some(assignment));
+    }
+    match embed() {
+        Some(embedding) => from_similarity(conn, &embedding, threshold),
+        None => Ok(None),
+    }
+}
+
+/// Workspace named by the `project_path` or `cwd` metadata
+pub fn from_path(
+    conn: &Connection,
+    metadata: &HashMap<String, Value>,
+) -> Result<Option<WorkspaceAssignment>> {
+    let Some((key, path)) = PATH_KEYS
+        .iter()
+        .find_map(|key| Some((*key, metadata.get(*key)?.as_str()?)))
+    else {
+        return Ok(None);
+    };
+    let components: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty() && !c.ends_with(':'))
+        .collect();
+
+    let existing: HashSet<String> = list_workspaces(conn)?
+        .into_iter()
+        .map(|w| w.workspace)
+        .filter(|w| w != "default")
+        .collect();
+    for component in components.iter().rev() {
+        if let Some(workspace) = slug(component).filter(|w| existing.contains(w)) {
+            return Ok(Some(WorkspaceAssignment {
+                reason: format!(
+                    "{} {} is inside existing workspace '{}'",
+                    key, path, workspace
+                ),
+                workspace,
+                source: AssignmentSource::Path,
+                similarity: None,
+                similar_memories: Vec::new(),
+            }));
+        }
+    }
+
+    // No match: the innermost directory that names a project
+    let project = components.iter().enumerate().rev().find_map(|(i, c)| {
+        let under_home = i > 0 && HOME_ROOTS.contains(&components[i - 1]);
+        if i == 0 || under_home || GENERIC_DIRS.contains(&c.to_lowercase().as_str()) {
+            None
+        } else {
+            slug(c)
+        }
+    });
+    Ok(project.map(|workspace| WorkspaceAssignment {
+        reason: format!("{} {} is in project directory '{}'", key, path, workspace),
+        workspace,
+        source: AssignmentSource::Path,
+        similarity: None,
+        similar_memories: Vec::new(),
+    }))
+}
+
+/// The default workspace of the active persona
+pub fn from_persona(name: &str, workspace: &str) -> Option<WorkspaceAssignment> {
+    let workspace = normalize_workspace(workspace).ok()?;
+    Some(WorkspaceAssignment {
+        reason: format!("default workspace of active persona '{}'", name),
+        workspace,
+        source: AssignmentSource::Persona,
+        similarity: None,
+        similar_memories: Vec::new(),
+    })
+}
+
+/// The workspace, other than `default`, whose nearest memories are most
+/// similar to `embedding` on average, if that average reaches `threshold`
+pub fn from_similarity(
+    conn: &Connection,
+    embedding: &[f32],
+    threshold: f32,
+) -> Result<Option<WorkspaceAssignment>> {
+    let now = Utc::now().to_rfc3339();
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, workspace FROM memories
+         WHERE has_embedding = 1 AND valid_to IS NULL AND workspace != 'default'
+           AND (expires_at IS NULL OR expires_at > ?)",
+    )?;
+    let candidates: Vec<(MemoryId, String)> = stmt
+        .query_map(params![now], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut by_workspace: HashMap<String, Vec<(f32, MemoryId)>> = HashMap::new();
+    for (id, workspace) in candidates {
+        if let Some(stored) = get_embedding(conn, id)? {
+            let similarity = cosine_similarity(embedding, &stored);
+            by_workspace
+                .entry(workspace)
+                .or_default()
+                .push((similarity, id));
+        }
+    }
+
+    let best = by_workspace
+        .into_iter()
+        .map(|(workspace, mut scored)| {
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+            scored.truncate(NEAREST_PER_WORKSPACE);
+            let mean = scored.iter().map(|(s, _)| s).sum::<f32>() / scored.len() as f32;
+            (workspace, mean, scored)
+        })
+        .filter(|(_, mean, _)| *mean >= threshold)
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+
+    Ok(best.map(|(workspace, mean, scored)| WorkspaceAssignment {
+        reason: format!(
+            "content is {:.2} similar to its {} nearest memories in '{}' (threshold {:.2})",
+            mean,
+            scored.len(),
+            workspace,
+            threshold
+        ),
+        workspace,
+        source: AssignmentSource::Similarity,
+        similarity: Some(mean),
+        similar_memories: scored.into_iter().map(|(_, id)| id).collect(),
+    }))
+}
+
+/// Workspace name for a directory: lowercased, with runs of other
+/// characters turned into single hyphens
+fn slug(name: &str) -> Option<String> {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug
+        .trim_matches(|c| c == '-' || c == '_')
+        .chars()
+        .take(crate::types::MAX_WORKSPACE_LENGTH)
+        .collect();
+    normalize_workspace(&slug).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::{Embedder, TfIdfEmbedder};
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+    use serde_json::json;
+
+    fn create(conn: &Connection, content: &str, workspace: &str) -> MemoryId {
+        let input = CreateMemoryInput {
+            content: content.to_string(),
+            workspace: Some(workspace.to_string()),
+            ..Default::default()
+        };
+        create_memory(conn, &input).unwrap().id
+    }
+
+    fn embed(conn: &Connection, id: MemoryId, embedding: &[f32]) {
+        let bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+        conn.execute(
+            "INSERT OR REPLACE INTO embeddings (memory_id, embedding, model, dimensions)
+             VALUES (?1, ?2, 'tfidf', ?3)",
+            params![id, bytes, embedding.len() as i64],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE memories SET has_embedding = 1 WHERE id = ?",
+            params![id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_workspace_from_path() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                create(conn, "Engram release checklist", "engram");
+                let metadata =
+                    |key: &str, path: &str| HashMap::from([(key.to_string(), json!(path))]);
+
+                let found = from_path(conn, &metadata("cwd", "/home/ana/code/Engram/src/graph"))?;
+                let found = found.unwrap();
+                assert_eq!(found.workspace, "engram");
+                assert!(
+                    found.reason.contains("existing workspace"),
+                    "{}",
+                    found.reason
+                );
+
+                let found = from_path(conn, &metadata("project_path", "/srv/My Service/src"))?;
+                assert_eq!(found.unwrap().workspace, "my-service");
+
+                assert!(from_path(conn, &metadata("cwd", "/home/ana"))?.is_none());
+                assert!(from_path(conn, &HashMap::new())?.is_none());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_workspace_from_similarity() {
+        let storage = Storage::open_in_memory().unwrap();
+        let embedder = TfIdfEmbedder::new(384);
+        storage
+            .with_connection(|conn| {
+                for (content, workspace) in [
+                    (
+                        "Kubernetes deploy pipeline for the billing cluster",
+                        "infra",
+                    ),
+                    (
+                        "Kubernetes cluster autoscaling for the billing pipeline",
+                        "infra",
+                    ),
+                    ("Sourdough starter needs feeding twice a day", "baking"),
+                    ("Kubernetes deploy pipeline notes", "default"),
+                ] {
+                    let id = create(conn, content, workspace);
+                    embed(conn, id, &embedder.embed(content)?);
+                }
+
+                let query = embedder.embed_query("Kubernetes deploy pipeline for the billing cluster")?;
+                let found = from_similarity(conn, &query, 0.5)?.unwrap();
+                assert_eq!(found.workspace, "infra");
+                assert_eq!(found.source, AssignmentSource::Similarity);
+                assert_eq!(found.similar_memories.len(), 2);
+
+                assert!(from_similarity(conn, &query, 0.999)?.is_none());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_assignment_order() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let path = HashMap::from([("cwd".to_string(), json!("/work/payments"))]);
+                let found = assign_workspace(conn, &path, Some(("ops", "oncall")), || None, 0.5)?;
+                assert_eq!(found.unwrap().source, AssignmentSource::Path);
+
+                let found =
+                    assign_workspace(conn, &HashMap::new(), Some(("ops", "oncall")), || None, 0.5)?
+                        .unwrap();
+                assert_eq!(found.workspace, "oncall");
+                assert_eq!(found.source, AssignmentSource::Persona);
+
+                let none = assign_workspace(conn, &HashMap::new(), None, || None, 0.5)?;
+                assert!(none.is_none());
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+//! Anthropic log parser
+//!
+//! Reads, one record at a time:
+//! - Messages API request/response logs: `{"system", "messages": [...]}`
+//!   with the response as `response`, optionally wrapped as
+//!   `{"request", "response"}`
+//! - Bare messages (`{"role", "content": [...]}`) and response objects
+//! - Claude Code transcripts: JSONL of `{"type": "user" | "assistant",
+//!   "message": {...}, "sessionId", "timestamp"}`
+//!
+//! `tool_use` blocks become tool calls and `tool_result` blocks tool results;
+//! thinking blocks are dropped. Records are grouped into sessions by
+//! `sessionId` / `session_id`; unkeyed bare messages form one session per log.
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use super::{
+    merge_sessions, parse_timestamp, str_field, ImportedSession, LogParser, SessionBuilder,
+};
+use crate::error::Result;
+
+/// Where a record may name its session
+const SESSION_KEYS: &[&str] = &[
+    "/sessionId",
+    "/session_id",
+    "/conversation_id",
+    "/metadata/session_id",
+];
+
+/// Block types that mark a log as Anthropic's
+const ANTHROPIC_BLOCKS: &[&str] = &[
+    "tool_use",
+    "tool_result",
+    "thinking",
+    "redacted_thinking",
+    "server_tool_use",
+];
+
+/// Parser for Anthropic Messages API and Claude Code logs
+pub struct AnthropicParser;
+
+impl LogParser for AnthropicParser {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn detect(&self, records: &[Value]) -> usize {
+        records.iter().map(record_score).sum()
+    }
+
+    fn parse(&self, records: &[Value]) -> Result<Vec<ImportedSession>> {
+        let mut parts = Vec::new();
+        let mut loose = SessionBuilder::new(None);
+        for record in records {
+            let key = str_field(record, SESSION_KEYS).map(str::to_string);
+            let mut builder = SessionBuilder::new(key);
+            let timestamp = ["timestamp", "created_at"]
+                .iter()
+                .find_map(|k| record.get(*k).and_then(parse_timestamp));
+            let model = str_field(
+                record,
+                &[
+                    "/model",
+                    "/request/model",
+                    "/message/model",
+                    "/response/model",
+                ],
+            );
+            if let Some(model) = model {
+                builder.metadata.insert("model".to_string(), json!(model));
+            }
+            if let Some(cwd) = record["cwd"].as_str() {
+                builder.metadata.insert("cwd".to_string(), json!(cwd));
+            }
+
+            let request = record.get("request").unwrap_or(record);
+            let full = if let Some(messages) = request["messages"].as_array() {
+                read_content(&mut builder, "system", &request["system"], timestamp);
+                for message in messages {
+                    read_message(&mut builder, message, timestamp);
+                }
+                if let Some(response) = record.get("response") {
+                    read_message(&mut builder, response, timestamp);
+                }
+                true
+            } else if let Some(message) = record.get("message").filter(|m| m.is_object()) {
+                // Claude Code: skip summaries, system notices and the like
+                if !matches!(record["type"].as_str(), Some("user" | "assistant") | None) {
+                    continue;
+                }
+                read_message(&mut builder, message, timestamp);
+                false
+            } else if record.get("role").is_some() {
+                read_message(&mut builder, record, timestamp);
+                false
+            } else {
+                continue;
+            };
+
+            if full || builder.key.is_some() {
+                parts.push(builder);
+            } else {
+                loose.absorb(builder);
+            }
+        }
+        parts.push(loose);
+        Ok(merge_sessions(self.name(), parts))
+    }
+}
+
+fn record_score(record: &Value) -> usize {
+    if record.get("stop_reason").is_some()
+        || record.get("anthropic_version").is_some()
+        || (record.get("sessionId").is_some() && record["message"].is_object())
+    {
+        return 3;
+    }
+    let request = record.get("request").unwrap_or(record);
+    let mut messages: Vec<&Value> = request["messages"]
+        .as_array()
+        .map(|m| m.iter().collect())
+        .unwrap_or_default();
+    messages.extend(record.get("message"));
+    messages.extend(record.get("response"));
+    if messages.is_empty() {
+        messages.push(record);
+    }
+    let has_blocks = messages.iter().any(|m| {
+        m["content"].as_array().is_some_and(|blocks| {
+            blocks
+                .iter()
+                .any(|b| ANTHROPIC_BLOCKS.contains(&b["type"].as_str().unwrap_or("")))
+        })
+    });
+    if has_blocks {
+        3
+    } else if request.get("system").is_some() && request.get("messages").is_some() {
+        2
+    } else if messages.iter().any(|m| m["role"].is_string()) {
+        1
+    } else {
+        0
+    }
+}
+
+fn read_message(builder: &mut SessionBuilder, message: &Value, timestamp: Option<DateTime<Utc>>) {
+    let role = message["role"].as_str().unwrap_or("user");
+    read_content(builder, role, &message["content"], timestamp);
+}
+
+/// Content of one message: a string, or blocks whose text is gathered into
+/// messages between the tool calls and results
+fn read_content(
+    builder: &mut SessionBuilder,
+    role: &str,
+    content: &Value,
+    timestamp: Option<DateTime<Utc>>,
+) {
+    let blocks = match content {
+        Value::String(text) => return builder.text(role, text.clone(), timestamp),
+        Value::Array(blocks) => blocks,
+        _ => return,
+    };
+    let mut text: Vec<String> = Vec::new();
+    let flush = |builder: &mut SessionBuilder, text: &mut Vec<String>| {
+        if !text.is_empty() {
+            builder.text(role, text.join("\n"), timestamp);
+            text.clear();
+        }
+    };
+    for block in blocks {
+        match block["type"].as_str().unwrap_or("text") {
+            "text" => text.extend(block["text"].as_str().map(str::to_string)),
+            "image" | "document" => {
+                text.push(format!("[{}]", block["type"].as_str().unwrap_or("")))
+            }
+            "tool_use" | "server_tool_use" => {
+                flush(builder, &mut text);
+                builder.tool_call(
+                    block["id"].as_str().map(str::to_string),
+                    block["name"].as_str().unwrap_or("tool").to_string(),
+                    block["input"].clone(),
+                    timestamp,
+                );
+            }
+            kind if kind.ends_with("tool_result") => {
+                flush(builder, &mut text);
+                let mut output = result_text(&block["content"]);
+                if block["is_error"].as_bool() == Some(true) {
+                    output = format!("Error: {}", output);
+                }
+                builder.tool_result(block["tool_use_id"].as_str(), None, output, timestamp);
+            }
+            // thinking, redacted_thinking
+            _ => {}
+        }
+    }
+    flush(builder, &mut text);
+}
+
+/// Text of a tool result: a string, or its text blocks
+fn result_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b["text"].as_str().map(str::to_string))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::session_indexing::MessageKind;
+    use crate::interop::read_records;
+
+    #[test]
+    fn test_claude_code_transcript() {
+        let log = r#"
+{"type": "summary", "summary": "Fix the build", "leafUuid": "u3"}
+{"type": "user", "sessionId": "s-1", "timestamp": "2026-01-05T10:00:00Z", "message": {"role": "user", "content": "Why does the build fail?"}}
+{"type": "assistant", "sessionId": "s-1", "timestamp": "2026-01-05T10:00:05Z", "message": {"role": "assistant", "model": "claude-sonnet-4", "content": [{"type": "thinking", "thinking": "..."}, {"type": "text", "text": "Let me check."}, {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "cargo build"}}]}}
+{"type": "user", "sessionId": "s-1", "timestamp": "2026-01-05T10:00:09Z", "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "toolu_1", "content": "error[E0433]", "is_error": true}]}}
+"#;
+        let records = read_records(log).unwrap();
+        assert!(AnthropicParser.detect(&records) > 0);
+        let sessions = AnthropicParser.parse(&records).unwrap();
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.session_id, "anthropic:s-1");
+        assert_eq!(session.metadata["model"], "claude-sonnet-4");
+        let kinds: Vec<MessageKind> = session.messages.iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MessageKind::Text,
+                MessageKind::Text,
+                MessageKind::ToolCall,
+                MessageKind::ToolResult
+            ]
+        );
+        assert_eq!(
+            session.messages[2].arguments,
+            Some(json!({"command": "cargo build"}))
+        );
+        assert_eq!(session.messages[3].tool_name.as_deref(), Some("Bash"));
+        assert_eq!(session.messages[3].content, "Error: error[E0433]");
+    }
+
+    #[test]
+    fn test_request_response_log() {
+        let record = json!({
+            "request": {
+                "model": "claude-opus-4",
+                "system": "You are terse.",
+                "messages": [{"role": "user", "content": "Hi"}]
+            },
+            "response": {"type": "message", "role": "assistant", "stop_reason": "end_turn",
+                         "content": [{"type": "text", "text": "Hello."}]}
+        });
+        let sessions = AnthropicParser.parse(&[record]).unwrap();
+        let messages = &sessions[0].messages;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].kind, MessageKind::System);
+        assert_eq!(messages[2].content, "Hello.");
+    }
+}
@@ -0,0 +1,264 @@
+//! Storing imported sessions
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::ImportedSession;
+use crate::error::Result;
+use crate::intelligence::auto_capture::{AutoCaptureConfig, AutoCaptureEngine};
+use crate::intelligence::session_indexing::{
+    get_session, index_conversation, index_conversation_delta, ChunkingConfig, Message, MessageKind,
+};
+use crate::storage::queries::create_memory;
+use crate::types::{CreateMemoryInput, DedupMode};
+
+/// Session metadata key holding where an imported session came from
+pub const IMPORT_METADATA_KEY: &str = "import";
+
+/// Tag of memories captured from imported logs
+pub const LOG_IMPORT_TAG: &str = "log-import";
+
+/// How imported sessions are stored
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Workspace for new sessions (default: "default")
+    pub workspace: Option<String>,
+    /// Agent ID for sessions whose log doesn't name one
+    pub agent_id: Option<String>,
+    /// Run auto-capture over the conversation (default: true)
+    pub capture: bool,
+    /// Minimum confidence of captured memories (default: 0.6)
+    pub min_confidence: f32,
+    pub chunking: ChunkingConfig,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            agent_id: None,
+            capture: true,
+            min_confidence: AutoCaptureConfig::default().min_confidence,
+            chunking: ChunkingConfig::default(),
+        }
+    }
+}
+
+/// What importing did with a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// Indexed for the first time
+    Created,
+    /// Already indexed; the messages added since were indexed
+    Extended,
+    /// Already indexed with all its messages
+    Unchanged,
+}
+
+/// Result of importing one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionImport {
+    pub session_id: String,
+    pub status: ImportStatus,
+    pub messages_indexed: usize,
+    pub memories_captured: usize,
+}
+
+/// Totals over an import run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub files: usize,
+    pub sessions_created: usize,
+    pub sessions_extended: usize,
+    pub sessions_unchanged: usize,
+    pub messages_indexed: usize,
+    pub memories_captured: usize,
+    pub errors: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn add(&mut self, import: &SessionImport) {
+        match import.status {
+            ImportStatus::Created => self.sessions_created += 1,
+            ImportStatus::Extended => self.sessions_extended += 1,
+            ImportStatus::Unchanged => self.sessions_unchanged += 1,
+        }
+        self.messages_indexed += import.messages_indexed;
+        self.memories_captured += import.memories_captured;
+    }
+}
+
+/// Index an imported session and capture memories from its conversation
+///
+/// Importing is incremental: a session indexed earlier only gets the
+/// messages past its stored message count, so re-running an import over a
+/// growing log indexes what is new and leaves the rest alone.
+pub fn import_session(
+    conn: &Connection,
+    format: &str,
+    session: &ImportedSession,
+    options: &ImportOptions,
+) -> Result<SessionImport> {
+    let id = &session.session_id;
+    let (status, new_messages, workspace) = match get_session(conn, id).ok() {
+        Some(existing) if existing.message_count as usize >= session.messages.len() => {
+            return Ok(SessionImport {
+                session_id: id.clone(),
+                status: ImportStatus::Unchanged,
+                messages_indexed: 0,
+                memories_captured: 0,
+            });
+        }
+        Some(existing) => {
+            let new_messages = &session.messages[existing.message_count as usize..];
+            index_conversation_delta(conn, id, new_messages, &options.chunking)?;
+            (ImportStatus::Extended, new_messages, existing.workspace)
+        }
+        None => {
+            let agent_id = session.agent_id.as_deref().or(options.agent_id.as_deref());
+            let indexed = index_conversation(
+                conn,
+                id,
+                &session.messages,
+                &options.chunking,
+                options.workspace.as_deref(),
+                session.title.as_deref(),
+                agent_id,
+            )?;
+            let mut metadata = indexed.metadata;
+            let mut source = session.metadata.clone();
+            source.insert("format".to_string(), json!(format));
+            metadata.insert(IMPORT_METADATA_KEY.to_string(), json!(source));
+            conn.execute(
+                "UPDATE sessions SET metadata = ? WHERE session_id = ?",
+                params![serde_json::to_string(&metadata)?, id],
+            )?;
+            (
+                ImportStatus::Created,
+                &session.messages[..],
+                indexed.workspace,
+            )
+        }
+    };
+
+    let memories_captured = if options.capture {
+        capture_memories(conn, id, &workspace, new_messages, options)?
+    } else {
+        0
+    };
+    Ok(SessionImport {
+        session_id: id.clone(),
+        status,
+        messages_indexed: new_messages.len(),
+        memories_captured,
+    })
+}
+
+/// Store what auto-capture finds in the conversational messages; a capture
+/// identical to an existing memory is skipped
+fn capture_memories(
+    conn: &Connection,
+    session_id: &str,
+    workspace: &str,
+    messages: &[Message],
+    options: &ImportOptions,
+) -> Result<usize> {
+    let engine = AutoCaptureEngine::new(AutoCaptureConfig {
+        min_confidence: options.min_confidence,
+        require_confirmation: false,
+        ..Default::default()
+    });
+    let mut captured = 0;
+    let conversation = messages
+        .iter()
+        .filter(|m| m.kind == MessageKind::Text && matches!(m.role.as_str(), "user" | "assistant"));
+    for message in conversation {
+        for candidate in engine.analyze(&message.content, session_id) {
+            let mut tags = candidate.suggested_tags.clone();
+            tags.push(LOG_IMPORT_TAG.to_string());
+            tags.push(format!("session:{}", session_id));
+            let input = CreateMemoryInput {
+                content: candidate.content.clone(),
+                memory_type: candidate.capture_type.to_memory_type(),
+                tags,
+                metadata: HashMap::from([
+                    ("session_id".to_string(), json!(session_id)),
+                    ("capture_type".to_string(), json!(candidate.capture_type)),
+                    (
+                        "capture_confidence".to_string(),
+                        json!(candidate.confidence),
+                    ),
+                    ("message_role".to_string(), json!(message.role)),
+                ]),
+                importance: Some(candidate.suggested_importance),
+                workspace: Some(workspace.to_string()),
+                event_time: Some(message.timestamp),
+                dedup_mode: DedupMode::Skip,
+                ..Default::default()
+            };
+            create_memory(conn, &input)?;
+            captured += 1;
+        }
+    }
+    Ok(captured)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::ParserRegistry;
+    use crate::storage::Storage;
+
+    const LOG: &str = r#"
+{"role": "user", "content": "We decided to use Postgres for the billing service."}
+{"role": "assistant", "content": "Noted. I'll set up the schema migration next."}
+"#;
+
+    #[test]
+    fn test_import_is_incremental() {
+        let storage = Storage::open_in_memory().unwrap();
+        let registry = ParserRegistry::default();
+        let options = ImportOptions {
+            workspace: Some("imports".to_string()),
+            ..Default::default()
+        };
+
+        let parsed = registry.parse(LOG, None).unwrap();
+        assert_eq!(parsed.format, "openai");
+        let session = &parsed.sessions[0];
+        let first = storage
+            .with_transaction(|conn| import_session(conn, parsed.format, session, &options))
+            .unwrap();
+        assert_eq!(first.status, ImportStatus::Created);
+        assert_eq!(first.messages_indexed, 2);
+        assert!(first.memories_captured >= 1, "{:?}", first);
+
+        let again = storage
+            .with_transaction(|conn| import_session(conn, parsed.format, session, &options))
+            .unwrap();
+        assert_eq!(again.status, ImportStatus::Unchanged);
+
+        let mut longer = session.clone();
+        longer.messages.push(Message {
+            role: "user".to_string(),
+            content: "Thanks, go ahead.".to_string(),
+            ..Default::default()
+        });
+        let extended = storage
+            .with_transaction(|conn| import_session(conn, parsed.format, &longer, &options))
+            .unwrap();
+        assert_eq!(extended.status, ImportStatus::Extended);
+        assert_eq!(extended.messages_indexed, 1);
+
+        let stored = storage
+            .with_connection(|conn| get_session(conn, &session.session_id))
+            .unwrap();
+        assert_eq!(stored.workspace, "imports");
+        assert_eq!(stored.message_count, 3);
+        assert_eq!(stored.metadata[IMPORT_METADATA_KEY]["format"], "openai");
+    }
+}
@@ -0,0 +1,301 @@
+//! LangGraph checkpoint parser
+//!
+//! Reads checkpoint dumps — `{"config": {"configurable": {"thread_id"}},
+//! "checkpoint": {"ts", "channel_values": {"messages": [...]}},
+//! "metadata": {"step"}}`, where `checkpoint` may also be a JSON string as
+//! stored by the SQL savers — and thread state or history exports from the
+//! LangGraph API (`{"values": {"messages": [...]}, "checkpoint":
+//! {"thread_id"}, "created_at"}`).
+//!
+//! Every checkpoint holds the whole history of its thread, so a thread's
+//! session is the `messages` channel of its latest checkpoint (highest
+//! `step`). A message is timestamped with the first checkpoint it appears
+//! in. Messages may be plain dicts (`{"type": "human", "content"}`) or
+//! LangChain-serialized (`{"lc": 1, "id": [..., "HumanMessage"], "kwargs"}`).
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use super::{
+    parse_arguments, parse_timestamp, str_field, ImportedSession, LogParser, SessionBuilder,
+};
+use crate::error::Result;
+
+/// Where a record may name its thread
+const THREAD_KEYS: &[&str] = &[
+    "/config/configurable/thread_id",
+    "/configurable/thread_id",
+    "/checkpoint/thread_id",
+    "/thread_id",
+    "/metadata/thread_id",
+];
+
+/// Parser for LangGraph checkpoints and thread states
+pub struct LangGraphParser;
+
+/// One checkpoint of a thread
+struct Checkpoint {
+    step: i64,
+    position: usize,
+    id: Option<String>,
+    timestamp: Option<DateTime<Utc>>,
+    messages: Vec<Value>,
+}
+
+impl LogParser for LangGraphParser {
+    fn name(&self) -> &'static str {
+        "langgraph"
+    }
+
+    fn detect(&self, records: &[Value]) -> usize {
+        records
+            .iter()
+            .map(|record| {
+                if record.get("checkpoint").is_some()
+                    || record.pointer("/config/configurable").is_some()
+                    || record.pointer("/values/messages").is_some()
+                {
+                    3
+                } else if record["messages"]
+                    .as_array()
+                    .is_some_and(|m| m.iter().any(|m| m.get("lc").is_some()))
+                {
+                    2
+                } else {
+                    0
+                }
+            })
+            .sum()
+    }
+
+    fn parse(&self, records: &[Value]) -> Result<Vec<ImportedSession>> {
+        let mut threads: Vec<(Option<String>, Vec<Checkpoint>)> = Vec::new();
+        for (position, record) in records.iter().enumerate() {
+            let Some(checkpoint) = read_checkpoint(record, position) else {
+                continue;
+            };
+            let thread = str_field(record, THREAD_KEYS).map(str::to_string);
+            match threads.iter_mut().find(|(t, _)| *t == thread) {
+                Some((_, checkpoints)) => checkpoints.push(checkpoint),
+                None => threads.push((thread, vec![checkpoint])),
+            }
+        }
+
+        let mut sessions = Vec::new();
+        for (thread, mut checkpoints) in threads {
+            checkpoints.sort_by_key(|c| (c.step, c.position));
+            let mut first_seen: HashMap<String, DateTime<Utc>> = HashMap::new();
+            for checkpoint in &checkpoints {
+                let Some(ts) = checkpoint.timestamp else {
+                    continue;
+                };
+                for message in &checkpoint.messages {
+                    if let Some(id) = message_id(message) {
+                        first_seen.entry(id).or_insert(ts);
+                    }
+                }
+            }
+            let Some(latest) = checkpoints.last() else {
+                continue;
+            };
+
+            let mut builder = SessionBuilder::new(thread);
+            builder
+                .metadata
+                .insert("step".to_string(), json!(latest.step));
+            if let Some(id) = &latest.id {
+                builder
+                    .metadata
+                    .insert("checkpoint_id".to_string(), json!(id));
+            }
+            // Keep timestamps in transcript order
+            let mut previous = checkpoints[0].timestamp;
+            for message in &latest.messages {
+                let seen = message_id(message).and_then(|id| first_seen.get(&id).copied());
+                let timestamp = match (previous, seen) {
+                    (Some(p), Some(s)) => Some(p.max(s)),
+                    (p, s) => s.or(p).or(latest.timestamp),
+                };
+                previous = timestamp;
+                read_message(&mut builder, message, timestamp);
+            }
+            sessions.extend(builder.build(self.name()));
+        }
+        Ok(sessions)
+    }
+}
+
+fn read_checkpoint(record: &Value, position: usize) -> Option<Checkpoint> {
+    // SQL savers store the checkpoint as a JSON string
+    let checkpoint = match &record["checkpoint"] {
+        Value::String(s) => serde_json::from_str(s).unwrap_or(Value::Null),
+        other => other.clone(),
+    };
+    let messages = checkpoint
+        .pointer("/channel_values/messages")
+        .or_else(|| record.pointer("/values/messages"))
+        .or_else(|| record.get("messages"))
+        .and_then(Value::as_array)?
+        .clone();
+    let timestamp = [
+        checkpoint.get("ts"),
+        record.get("created_at"),
+        record.get("updated_at"),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(parse_timestamp);
+    Some(Checkpoint {
+        step: record
+            .pointer("/metadata/step")
+            .and_then(Value::as_i64)
+            .unwrap_or(-1),
+        position,
+        id: str_field(
+            record,
+            &[
+                "/config/configurable/checkpoint_id",
+                "/checkpoint/checkpoint_id",
+                "/checkpoint_id",
+            ],
+        )
+        .or_else(|| checkpoint["id"].as_str())
+        .map(str::to_string),
+        timestamp,
+        messages,
+    })
+}
+
+/// Message type and fields, unwrapping LangChain serialization
+fn message_parts(message: &Value) -> (String, &Value) {
+    if let Some(kwargs) = message
+        .get("kwargs")
+        .filter(|_| message.get("lc").is_some())
+    {
+        let class = message["id"]
+            .as_array()
+            .and_then(|id| id.last())
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let kind = match class.trim_end_matches("Chunk") {
+            "HumanMessage" => "human",
+            "AIMessage" => "ai",
+            "SystemMessage" => "system",
+            "ToolMessage" => "tool",
+            "FunctionMessage" => "function",
+            _ => "chat",
+        };
+        return (kind.to_string(), kwargs);
+    }
+    // messages_to_dict: {"type", "data": {...}}
+    let data = message
+        .get("data")
+        .filter(|d| d.is_object())
+        .unwrap_or(message);
+    let kind = message["type"]
+        .as_str()
+        .or_else(|| data["role"].as_str())
+        .unwrap_or("chat");
+    (kind.to_string(), data)
+}
+
+fn message_id(message: &Value) -> Option<String> {
+    let (_, data) = message_parts(message);
+    data["id"].as_str().map(str::to_string)
+}
+
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.as_str().or_else(|| part["text"].as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn read_message(builder: &mut SessionBuilder, message: &Value, timestamp: Option<DateTime<Utc>>) {
+    let (kind, data) = message_parts(message);
+    let content = content_text(&data["content"]);
+    let role = match kind.as_str() {
+        "human" | "user" => "user",
+        "ai" | "assistant" => "assistant",
+        "system" | "developer" => "system",
+        "tool" | "function" => {
+            let name = data["name"].as_str().map(str::to_string);
+            builder.tool_result(data["tool_call_id"].as_str(), name, content, timestamp);
+            return;
+        }
+        _ => data["role"].as_str().unwrap_or("user"),
+    };
+    builder.text(role, content, timestamp);
+
+    let calls = data["tool_calls"].as_array().into_iter().flatten();
+    for call in calls {
+        // LangChain's {"name", "args", "id"}, or OpenAI's {"function": {...}}
+        let (name, arguments) = match call.get("function") {
+            Some(function) => (&function["name"], parse_arguments(&function["arguments"])),
+            None => (&call["name"], call["args"].clone()),
+        };
+        builder.tool_call(
+            call["id"].as_str().map(str::to_string),
+            name.as_str().unwrap_or("tool").to_string(),
+            arguments,
+            timestamp,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::session_indexing::MessageKind;
+
+    fn checkpoint(step: i64, ts: &str, messages: Value) -> Value {
+        json!({
+            "config": {"configurable": {"thread_id": "t-42", "checkpoint_id": format!("cp-{step}")}},
+            "checkpoint": {"ts": ts, "channel_values": {"messages": messages}},
+            "metadata": {"step": step, "source": "loop"}
+        })
+    }
+
+    #[test]
+    fn test_latest_checkpoint_wins() {
+        let human = json!({"lc": 1, "type": "constructor",
+            "id": ["langchain", "schema", "messages", "HumanMessage"],
+            "kwargs": {"content": "Book a table", "id": "m1"}});
+        let ai = json!({"type": "ai", "content": "", "id": "m2",
+            "tool_calls": [{"name": "book", "args": {"size": 2}, "id": "call_9"}]});
+        let tool =
+            json!({"type": "tool", "content": "Booked", "tool_call_id": "call_9", "id": "m3"});
+        let records = vec![
+            checkpoint(2, "2026-02-01T12:00:05Z", json!([human, ai, tool])),
+            checkpoint(1, "2026-02-01T12:00:00Z", json!([human])),
+        ];
+        assert!(LangGraphParser.detect(&records) > 0);
+
+        let sessions = LangGraphParser.parse(&records).unwrap();
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.session_id, "langgraph:t-42");
+        assert_eq!(session.metadata["checkpoint_id"], "cp-2");
+        let messages = &session.messages;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].content, "Book a table");
+        assert_eq!(
+            messages[0].timestamp.to_rfc3339(),
+            "2026-02-01T12:00:00+00:00"
+        );
+        assert_eq!(messages[1].kind, MessageKind::ToolCall);
+        assert_eq!(messages[1].arguments, Some(json!({"size": 2})));
+        assert_eq!(messages[2].tool_name.as_deref(), Some("book"));
+        assert_eq!(
+            messages[2].timestamp.to_rfc3339(),
+            "2026-02-01T12:00:05+00:00"
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Agent framework log import
+//!
+//! Turns logs written by agent frameworks into indexed sessions (and the
+//! memories auto-capture finds in them), so months of existing logs can
+//! bootstrap memory. Each format is a [`LogParser`]; a [`ParserRegistry`]
+//! holds them and picks one by sniffing the first records of a log.
+//!
+//! Built-in formats:
+//! - `openai` — Chat Completions request/response logs and message JSONL,
+//!   Responses API items, Assistants thread messages and run steps
+//! - `anthropic` — Messages API messages with `tool_use` / `tool_result`
+//!   blocks, request/response logs and Claude Code transcript JSONL
+//! - `langgraph` — checkpoint dumps and thread state/history exports
+//!
+//! Logs are read as a JSON document (object or array) or as JSONL. Parsers
+//! only map records to [`Message`]s; [`import_session`] does the storing.
+
+pub mod anthropic;
+mod import;
+pub mod langgraph;
+pub mod openai;
+
+pub use import::{
+    import_session, ImportOptions, ImportReport, ImportStatus, SessionImport, IMPORT_METADATA_KEY,
+    LOG_IMPORT_TAG,
+};
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::{EngramError, Result};
+use crate::intelligence::session_indexing::{Message, MessageKind};
+
+/// Records looked at when detecting a log's format
+const DETECT_RECORDS: usize = 50;
+
+/// A conversation recovered from a log, ready to be indexed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedSession {
+    /// Session ID, prefixed with the format name (`openai:thread_abc`)
+    pub session_id: String,
+    pub title: Option<String>,
+    pub agent_id: Option<String>,
+    pub messages: Vec<Message>,
+    /// Format-specific details (model, run IDs, checkpoint), kept for reference
+    pub metadata: HashMap<String, Value>,
+}
+
+/// Parser for one log format
+///
+/// Implement this to teach the importer a new format and add it with
+/// [`ParserRegistry::register`].
+pub trait LogParser: Send + Sync {
+    /// Format name, used for `--format` and as the session ID prefix
+    fn name(&self) -> &'static str;
+
+    /// How strongly `records` look like this format; 0 means not at all
+    fn detect(&self, records: &[Value]) -> usize;
+
+    /// Sessions found in `records`
+    fn parse(&self, records: &[Value]) -> Result<Vec<ImportedSession>>;
+}
+
+/// A parsed log
+#[derive(Debug, Clone)]
+pub struct ParsedLog {
+    /// Name of the parser that read it
+    pub format: &'static str,
+    pub sessions: Vec<ImportedSession>,
+}
+
+/// The known log formats
+pub struct ParserRegistry {
+    parsers: Vec<Box<dyn LogParser>>,
+}
+
+impl Default for ParserRegistry {
+    /// Registry with the built-in `openai`, `anthropic` and `langgraph` parsers
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(openai::OpenAiParser));
+        registry.register(Box::new(anthropic::AnthropicParser));
+        registry.register(Box::new(langgraph::LangGraphParser));
+        registry
+    }
+}
+
+impl ParserRegistry {
+    /// Registry without any parser
+    pub fn empty() -> Self {
+        Self {
+            parsers: Vec::new(),
+        }
+    }
+
+    /// Add a parser; one with the same name is replaced
+    pub fn register(&mut self, parser: Box<dyn LogParser>) {
+        self.parsers.retain(|p| p.name() != parser.name());
+        self.parsers.push(parser);
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.parsers.iter().map(|p| p.name()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn LogParser> {
+        self.parsers
+            .iter()
+            .find(|p| p.name() == name)
+            .map(|p| p.as_ref())
+    }
+
+    /// Parser scoring highest on the first records, earlier parsers
+    /// winning ties; `None` when none recognizes them
+    pub fn detect(&self, records: &[Value]) -> Option<&dyn LogParser> {
+        let sample = &records[..records.len().min(DETECT_RECORDS)];
+        let mut best: Option<(&dyn LogParser, usize)> = None;
+        for parser in &self.parsers {
+            let score = parser.detect(sample);
+            if score > 0 && best.is_none_or(|(_, s)| score > s) {
+                best = Some((parser.as_ref(), score));
+            }
+        }
+        best.map(|(parser, _)| parser)
+    }
+
+    /// Read `input` with the named parser, or the detected one when
+    /// `format` is `None` or `"auto"`
+    pub fn parse(&self, input: &str, format: Option<&str>) -> Result<ParsedLog> {
+        let records = read_records(input)?;
+        let parser = match format.filter(|f| *f != "auto") {
+            Some(name) => self.get(name).ok_or_else(|| {
+                EngramError::InvalidInput(format!(
+                    "Unknown log format '{}': expected auto or one of {}",
+                    name,
+                    self.names().join(", ")
+                ))
+            })?,
+            None => self.detect(&records).ok_or_else(|| {
+                EngramError::InvalidInput("Could not detect the log format".to_string())
+            })?,
+        };
+        Ok(ParsedLog {
+            format: parser.name(),
+            sessions: parser.parse(&records)?,
+        })
+    }
+}
+
+/// Records of a log: the elements of a JSON array, a single JSON object, or
+/// one value per non-empty line of JSONL
+pub fn read_records(input: &str) -> Result<Vec<Value>> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(EngramError::InvalidInput("Empty log".to_string()));
+    }
+    if let Ok(value) = serde_json::from_str::<Value>(trimmed) {
+        return Ok(match value {
+            Value::Array(records) => records,
+            other => vec![other],
+        });
+    }
+    trimmed
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| EngramError::InvalidInput(format!("Line {}: not JSON: {}", n + 1, e)))
+        })
+        .collect()
+}
+
+/// Timestamp from an RFC 3339 string or a Unix time in seconds (or
+/// milliseconds, for values too large to be seconds)
+pub(crate) fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+            .or_else(|| s.parse::<f64>().ok().and_then(unix_timestamp)),
+        Value::Number(n) => n.as_f64().and_then(unix_timestamp),
+        _ => None,
+    }
+}
+
+fn unix_timestamp(value: f64) -> Option<DateTime<Utc>> {
+    let millis = if value > 1e11 { value } else { value * 1000.0 };
+    DateTime::from_timestamp_millis(millis as i64)
+}
+
+/// First non-empty string found under any of the JSON pointers `keys`
+pub(crate) fn str_field<'a>(record: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|k| {
+        record
+            .pointer(k)
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+    })
+}
+
+/// Tool arguments as JSON; OpenAI sends them as a JSON-encoded string
+pub(crate) fn parse_arguments(value: &Value) -> Value {
+    match value {
+        Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| value.clone()),
+        other => other.clone(),
+    }
+}
+
+/// Messages of one session as they are read, with the name of each tool
+/// call kept so its result can be labelled
+#[derive(Debug, Default)]
+pub(crate) struct SessionBuilder {
+    pub key: Option<String>,
+    pub title: Option<String>,
+    pub agent_id: Option<String>,
+    pub messages: Vec<Message>,
+    pub metadata: HashMap<String, Value>,
+    tool_names: HashMap<String, String>,
+    /// Whether every message had a timestamp of its own
+    timestamped: bool,
+}
+
+impl SessionBuilder {
+    pub fn new(key: Option<String>) -> Self {
+        Self {
+            key,
+            timestamped: true,
+            ..Default::default()
+        }
+    }
+
+    /// Append a message; `timestamp` falls back to the previous message's
+    pub fn push(&mut self, mut message: Message, timestamp: Option<DateTime<Utc>>) {
+        match timestamp {
+            Some(ts) => message.timestamp = ts,
+            None => {
+                self.timestamped = false;
+                if let Some(last) = self.messages.last() {
+                    message.timestamp = last.timestamp;
+                }
+            }
+        }
+        if message.kind == MessageKind::ToolCall {
+            if let (Some(id), Some(name)) = (&message.id, &message.tool_name) {
+                self.tool_names.insert(id.clone(), name.clone());
+            }
+        }
+        self.messages.push(message);
+    }
+
+    pub fn text(&mut self, role: &str, content: String, timestamp: Option<DateTime<Utc>>) {
+        if content.trim().is_empty() {
+            return;
+        }
+        let kind = MessageKind::infer(role, false);
+        self.push(
+            Message {
+                role: role.to_string(),
+                content,
+                kind,
+                ..Default::default()
+            },
+            timestamp,
+        );
+    }
+
+    pub fn tool_call(
+        &mut self,
+        id: Option<String>,
+        name: String,
+        arguments: Value,
+        timestamp: Option<DateTime<Utc>>,
+    ) {
+        self.push(
+            Message {
+                role: "assistant".to_string(),
+                id,
+                kind: MessageKind::ToolCall,
+                tool_name: Some(name),
+                arguments: Some(arguments),
+                ..Default::default()
+            },
+            timestamp,
+        );
+    }
+
+    /// Append a tool result, named after the call `call_id` refers to
+    pub fn tool_result(
+        &mut self,
+        call_id: Option<&str>,
+        name: Option<String>,
+        content: String,
+        timestamp: Option<DateTime<Utc>>,
+    ) {
+        let tool_name = name.or_else(|| call_id.and_then(|id| self.tool_names.get(id).cloned()));
+        self.push(
+            Message {
+                role: "tool".to_string(),
+                content,
+                id: call_id.map(str::to_string),
+                kind: MessageKind::ToolResult,
+                tool_name,
+                ..Default::default()
+            },
+            timestamp,
+        );
+    }
+
+    /// Whether `other` replays this session's messages and adds more, as
+    /// request logs do when every call resends the history
+    fn continued_by(&self, other: &SessionBuilder) -> bool {
+        other.messages.len() > self.messages.len()
+            && self
+                .messages
+                .iter()
+                .zip(&other.messages)
+                .all(|(a, b)| same_message(a, b))
+    }
+
+    /// Add the messages of `other` to this session, skipping the history it
+    /// replays
+    pub fn absorb(&mut self, other: SessionBuilder) {
+        let skip = if self.continued_by(&other) {
+            self.messages.len()
+        } else {
+            0
+        };
+        self.timestamped &= other.timestamped;
+        self.tool_names.extend(other.tool_names);
+        for mut message in other.messages.into_iter().skip(skip) {
+            // Results logged apart from their calls
+            if message.kind == MessageKind::ToolResult && message.tool_name.is_none() {
+                message.tool_name = message
+                    .id
+                    .as_ref()
+                    .and_then(|id| self.tool_names.get(id).cloned());
+            }
+            self.messages.push(message);
+        }
+        self.title = self.title.take().or(other.title);
+        self.agent_id = self.agent_id.take().or(other.agent_id);
+        for (key, value) in other.metadata {
+            self.metadata.entry(key).or_insert(value);
+        }
+    }
+
+    /// Finish the session; IDs missing from the log are derived from its
+    /// first messages so re-importing the same log finds the same session
+    pub fn build(mut self, format: &str) -> Option<ImportedSession> {
+        if self.messages.is_empty() {
+            return None;
+        }
+        if self.timestamped {
+            self.messages.sort_by_key(|m| m.timestamp);
+        }
+        let key = self.key.unwrap_or_else(|| {
+            let mut hasher = Sha256::new();
+            for message in self.messages.iter().take(4) {
+                hasher.update(message.role.as_bytes());
+                hasher.update(message.content.as_bytes());
+                // Messages without a timestamp of their own are stamped now
+                if self.timestamped {
+                    hasher.update(message.timestamp.to_rfc3339().as_bytes());
+                }
+            }
+            hex::encode(hasher.finalize())[..16].to_string()
+        });
+        Some(ImportedSession {
+            session_id: format!("{}:{}", format, key),
+            title: self.title,
+            agent_id: self.agent_id,
+            messages: self.messages,
+            metadata: self.metadata,
+        })
+    }
+}
+
+fn same_message(a: &Message, b: &Message) -> bool {
+    a.role == b.role && a.kind == b.kind && a.content == b.content && a.tool_name == b.tool_name
+}
+
+/// Group per-record sessions: records sharing a key form one session, and
+/// an unkeyed record that continues the previous unkeyed session (the same
+/// history plus new messages) is merged into it
+pub(crate) fn merge_sessions(format: &str, parts: Vec<SessionBuilder>) -> Vec<ImportedSession> {
+    let mut sessions: Vec<SessionBuilder> = Vec::new();
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    let mut last_unkeyed: Option<usize> = None;
+    for part in parts.into_iter().filter(|p| !p.messages.is_empty()) {
+        let target = match &part.key {
+            Some(key) => by_key.get(key).copied(),
+            None => last_unkeyed.filter(|&i| sessions[i].continued_by(&part)),
+        };
+        match target {
+            Some(i) => sessions[i].absorb(part),
+            None => {
+                match &part.key {
+                    Some(key) => {
+                        by_key.insert(key.clone(), sessions.len());
+                    }
+                    None => last_unkeyed = Some(sessions.len()),
+                }
+                sessions.push(part);
+            }
+        }
+    }
+    sessions
+        .into_iter()
+        .filter_map(|s| s.build(format))
+        .collect()
+}
@@ -0,0 +1,357 @@
+//! OpenAI log parser
+//!
+//! Reads, one record at a time:
+//! - Chat Completions logs: `{"messages": [...], "choices": [...]}`, with the
+//!   request and response optionally wrapped as `{"request", "response"}`
+//! - Bare chat messages (`{"role", "content", "tool_calls"}`), one per line
+//! - Responses API requests and responses (`input` / `output` item lists)
+//!   and bare items (`message`, `function_call`, `function_call_output`)
+//! - Assistants thread messages and run steps, also inside
+//!   `{"object": "list", "data": [...]}` pages
+//!
+//! Records are grouped into sessions by `thread_id`, `conversation_id`,
+//! `session_id` or the same keys under `metadata`. Unkeyed bare messages
+//! form one session per log.
+
+use serde_json::{json, Value};
+
+use super::{
+    merge_sessions, parse_arguments, parse_timestamp, str_field, ImportedSession, LogParser,
+    SessionBuilder,
+};
+use crate::error::Result;
+
+/// Where a record may name its session
+const SESSION_KEYS: &[&str] = &[
+    "/thread_id",
+    "/conversation_id",
+    "/session_id",
+    "/metadata/thread_id",
+    "/metadata/conversation_id",
+    "/metadata/session_id",
+    "/request/metadata/session_id",
+    "/conversation/id",
+];
+
+/// Parser for OpenAI Chat Completions, Responses and Assistants logs
+pub struct OpenAiParser;
+
+impl LogParser for OpenAiParser {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn detect(&self, records: &[Value]) -> usize {
+        records.iter().flat_map(expand).map(record_score).sum()
+    }
+
+    fn parse(&self, records: &[Value]) -> Result<Vec<ImportedSession>> {
+        let mut parts = Vec::new();
+        let mut loose = SessionBuilder::new(None);
+        for record in records.iter().flat_map(expand) {
+            let key = str_field(record, SESSION_KEYS).map(str::to_string);
+            let mut builder = SessionBuilder::new(key);
+            let timestamp = ["created_at", "created", "timestamp"]
+                .iter()
+                .find_map(|k| record.get(*k).and_then(parse_timestamp));
+            let model = str_field(record, &["/model", "/request/model", "/response/model"]);
+            if let Some(model) = model {
+                builder.metadata.insert("model".to_string(), json!(model));
+            }
+
+            let request = record.get("request").unwrap_or(record);
+            let response = record.get("response").unwrap_or(record);
+            let full = if let Some(messages) = request.get("messages").and_then(Value::as_array) {
+                for message in messages {
+                    read_message(&mut builder, message, timestamp);
+                }
+                if let Some(message) = response.pointer("/choices/0/message") {
+                    read_message(&mut builder, message, timestamp);
+                }
+                true
+            } else if request.get("input").is_some() || response.get("output").is_some() {
+                if let Some(instructions) = request.get("instructions").and_then(Value::as_str) {
+                    builder.text("system", instructions.to_string(), timestamp);
+                }
+                match request.get("input") {
+                    Some(Value::String(text)) => builder.text("user", text.clone(), timestamp),
+                    Some(Value::Array(items)) => {
+                        for item in items {
+                            read_item(&mut builder, item, timestamp);
+                        }
+                    }
+                    _ => {}
+                }
+                for item in response["output"].as_array().into_iter().flatten() {
+                    read_item(&mut builder, item, timestamp);
+                }
+                true
+            } else if record["object"] == "thread.run.step" {
+                read_run_step(&mut builder, record, timestamp);
+                false
+            } else if record.get("role").is_some() || record.get("type").is_some() {
+                read_item(&mut builder, record, timestamp);
+                false
+            } else {
+                continue;
+            };
+
+            if full || builder.key.is_some() {
+                parts.push(builder);
+            } else {
+                loose.absorb(builder);
+            }
+        }
+        parts.push(loose);
+        Ok(merge_sessions(self.name(), parts))
+    }
+}
+
+/// A record, or the items of an Assistants list page
+fn expand(record: &Value) -> Vec<&Value> {
+    match record.get("data").and_then(Value::as_array) {
+        Some(data) if record["object"] == "list" => data.iter().collect(),
+        _ => vec![record],
+    }
+}
+
+fn record_score(record: &Value) -> usize {
+    let object = record["object"].as_str().unwrap_or("");
+    if object.starts_with("chat.completion")
+        || object.starts_with("thread.")
+        || object == "response"
+        || record.pointer("/response/choices").is_some()
+        || record.get("choices").is_some()
+    {
+        return 3;
+    }
+    let request = record.get("request").unwrap_or(record);
+    let messages = request
+        .get("messages")
+        .or_else(|| request.get("input"))
+        .and_then(Value::as_array)
+        .map(|m| m.iter().collect())
+        .unwrap_or_else(|| vec![record]);
+    messages
+        .into_iter()
+        .map(|m| {
+            let role = m["role"].as_str().unwrap_or("");
+            let kind = m["type"].as_str().unwrap_or("");
+            if m.get("tool_calls").is_some()
+                || matches!(role, "tool" | "function" | "developer")
+                || matches!(kind, "function_call" | "function_call_output")
+            {
+                3
+            } else if !role.is_empty() && m["content"].is_string() {
+                1
+            } else {
+                0
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Text of a message's content: a string, or the text of its parts
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(text) => Some(text.clone()),
+                _ => part
+                    .get("text")
+                    .and_then(|t| t.as_str().or_else(|| t["value"].as_str()))
+                    .or_else(|| part["refusal"].as_str())
+                    .map(str::to_string)
+                    .or_else(|| {
+                        let kind = part["type"].as_str()?;
+                        kind.contains("image").then(|| "[image]".to_string())
+                    }),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// A chat message, its tool calls included
+fn read_message(
+    builder: &mut SessionBuilder,
+    message: &Value,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+) {
+    let role = message["role"].as_str().unwrap_or("user");
+    let content = content_text(&message["content"]);
+    let name = message["name"].as_str().map(str::to_string);
+    match role {
+        "tool" | "function" => {
+            builder.tool_result(message["tool_call_id"].as_str(), name, content, timestamp)
+        }
+        _ => {
+            builder.text(role, content, timestamp);
+            for call in message["tool_calls"].as_array().into_iter().flatten() {
+                let function = &call["function"];
+                builder.tool_call(
+                    call["id"].as_str().map(str::to_string),
+                    function["name"].as_str().unwrap_or("function").to_string(),
+                    parse_arguments(&function["arguments"]),
+                    timestamp,
+                );
+            }
+            if let Some(function) = message.get("function_call").filter(|f| f.is_object()) {
+                builder.tool_call(
+                    None,
+                    function["name"].as_str().unwrap_or("function").to_string(),
+                    parse_arguments(&function["arguments"]),
+                    timestamp,
+                );
+            }
+        }
+    }
+}
+
+/// A Responses API item, or a bare chat message
+fn read_item(
+    builder: &mut SessionBuilder,
+    item: &Value,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+) {
+    let call_id = item["call_id"].as_str().or_else(|| item["id"].as_str());
+    match item["type"].as_str() {
+        None | Some("message") => read_message(builder, item, timestamp),
+        Some("function_call") | Some("custom_tool_call") => builder.tool_call(
+            call_id.map(str::to_string),
+            item["name"].as_str().unwrap_or("function").to_string(),
+            parse_arguments(item.get("arguments").unwrap_or(&item["input"])),
+            timestamp,
+        ),
+        Some("function_call_output") | Some("custom_tool_call_output") => {
+            let output = match &item["output"] {
+                Value::String(text) => text.clone(),
+                other => content_text(other),
+            };
+            builder.tool_result(item["call_id"].as_str(), None, output, timestamp)
+        }
+        // Built-in tools: web_search_call, file_search_call, computer_call...
+        Some(kind) if kind.ends_with("_call") => builder.tool_call(
+            call_id.map(str::to_string),
+            kind.trim_end_matches("_call").to_string(),
+            item.get("action")
+                .or_else(|| item.get("queries"))
+                .cloned()
+                .unwrap_or_else(|| json!({})),
+            timestamp,
+        ),
+        _ => {}
+    }
+}
+
+/// Tool calls of an Assistants run step, with their outputs
+fn read_run_step(
+    builder: &mut SessionBuilder,
+    step: &Value,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+) {
+    for call in step["step_details"]["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let id = call["id"].as_str();
+        let kind = call["type"].as_str().unwrap_or("function");
+        let (name, arguments, output) = match kind {
+            "function" => {
+                let function = &call["function"];
+                (
+                    function["name"].as_str().unwrap_or("function").to_string(),
+                    parse_arguments(&function["arguments"]),
+                    function["output"].as_str().map(str::to_string),
+                )
+            }
+            "code_interpreter" => {
+                let interpreter = &call["code_interpreter"];
+                let logs: Vec<&str> = interpreter["outputs"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|o| o["logs"].as_str())
+                    .collect();
+                (
+                    kind.to_string(),
+                    json!({"input": interpreter["input"]}),
+                    Some(logs.join("\n")).filter(|l| !l.is_empty()),
+                )
+            }
+            other => (
+                other.to_string(),
+                call.get(other).cloned().unwrap_or_else(|| json!({})),
+                None,
+            ),
+        };
+        builder.tool_call(id.map(str::to_string), name, arguments, timestamp);
+        if let Some(output) = output {
+            builder.tool_result(id, None, output, timestamp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intelligence::session_indexing::MessageKind;
+    use crate::interop::read_records;
+
+    #[test]
+    fn test_chat_completion_logs_merge_into_one_session() {
+        let log = r#"
+{"model": "gpt-4o", "messages": [{"role": "system", "content": "Be brief."}, {"role": "user", "content": "Weather in Paris?"}], "choices": [{"message": {"role": "assistant", "content": null, "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"}}]}}]}
+{"model": "gpt-4o", "messages": [{"role": "system", "content": "Be brief."}, {"role": "user", "content": "Weather in Paris?"}, {"role": "assistant", "content": null, "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"}}]}, {"role": "tool", "tool_call_id": "call_1", "content": "18C, sunny"}], "choices": [{"message": {"role": "assistant", "content": "18C and sunny."}}]}
+"#;
+        let sessions = OpenAiParser.parse(&read_records(log).unwrap()).unwrap();
+        assert_eq!(sessions.len(), 1);
+        let messages = &sessions[0].messages;
+        let kinds: Vec<MessageKind> = messages.iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MessageKind::System,
+                MessageKind::Text,
+                MessageKind::ToolCall,
+                MessageKind::ToolResult,
+                MessageKind::Text
+            ]
+        );
+        assert_eq!(messages[2].arguments, Some(json!({"city": "Paris"})));
+        assert_eq!(messages[3].tool_name.as_deref(), Some("get_weather"));
+        assert_eq!(messages[4].content, "18C and sunny.");
+        assert!(sessions[0].session_id.starts_with("openai:"));
+        assert_eq!(sessions[0].metadata["model"], "gpt-4o");
+    }
+
+    #[test]
+    fn test_assistants_and_responses_records() {
+        let page = json!({"object": "list", "data": [
+            {"object": "thread.message", "thread_id": "thread_1", "created_at": 1700000060,
+             "role": "assistant", "content": [{"type": "text", "text": {"value": "Done", "annotations": []}}]},
+            {"object": "thread.message", "thread_id": "thread_1", "created_at": 1700000000,
+             "role": "user", "content": [{"type": "text", "text": {"value": "Summarize the doc", "annotations": []}}]}
+        ]});
+        let response = json!({
+            "object": "response",
+            "input": "Look up engram",
+            "output": [
+                {"type": "function_call", "call_id": "fc_1", "name": "search", "arguments": "{\"q\":\"engram\"}"},
+                {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "Found it"}]}
+            ]
+        });
+        let sessions = OpenAiParser.parse(&[page, response]).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "openai:thread_1");
+        // Assistants pages list the newest message first
+        assert_eq!(sessions[0].messages[0].content, "Summarize the doc");
+        assert_eq!(sessions[1].messages.len(), 3);
+        assert_eq!(sessions[1].messages[1].tool_name.as_deref(), Some("search"));
+    }
+}
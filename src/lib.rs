@@ -9,12 +9,15 @@
 
 pub mod auth;
 pub mod bench;
+pub mod budget;
 pub mod embedding;
 pub mod error;
 pub mod graph;
-#[cfg(feature = "langfuse")]
+#[cfg(any(feature = "langfuse", feature = "otel"))]
 pub mod integrations;
 pub mod intelligence;
+pub mod interop;
+pub mod limits;
 pub mod mcp;
 #[cfg(feature = "multimodal")]
 pub mod multimodal;
@@ -22,6 +25,8 @@ pub mod realtime;
 pub mod search;
 pub mod storage;
 pub mod sync;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
 #[cfg(feature = "watcher")]
 pub mod watcher;
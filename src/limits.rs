@@ -0,0 +1,221 @@
+//! Memory pressure guardrails for graph exports, traversals and results
+//!
+//! Hard caps on how many graph nodes and edges a call may load and how many
+//! bytes its response may take, so one oversized request can't exhaust the
+//! server's memory. Exceeding a cap is an [`EngramError::LimitExceeded`]
+//! naming the cap, the environment variable that raises it and the filters
+//! that would narrow the request.
+//!
+//! Tool dispatch checks every response against `max_result_bytes`; graph
+//! code checks node and edge counts as it loads them. Exports that
+//! legitimately need more can stream to a file instead (`output_path` on
+//! `memory_export_graph`).
+
+use std::io::Write;
+
+use serde_json::Value;
+
+use crate::error::{EngramError, Result};
+
+/// Default cap on graph nodes loaded by one call
+pub const DEFAULT_MAX_GRAPH_NODES: usize = 10_000;
+
+/// Default cap on graph edges loaded by one call
+pub const DEFAULT_MAX_GRAPH_EDGES: usize = 50_000;
+
+/// Default cap on the serialized size of one tool response
+pub const DEFAULT_MAX_RESULT_BYTES: usize = 32 * 1024 * 1024;
+
+/// Environment variable overriding [`DEFAULT_MAX_GRAPH_NODES`]
+pub const MAX_GRAPH_NODES_ENV: &str = "ENGRAM_MAX_GRAPH_NODES";
+
+/// Environment variable overriding [`DEFAULT_MAX_GRAPH_EDGES`]
+pub const MAX_GRAPH_EDGES_ENV: &str = "ENGRAM_MAX_GRAPH_EDGES";
+
+/// Environment variable overriding [`DEFAULT_MAX_RESULT_BYTES`]
+pub const MAX_RESULT_BYTES_ENV: &str = "ENGRAM_MAX_RESULT_BYTES";
+
+/// How to narrow a traversal that reached too many nodes
+pub const TRAVERSAL_HINT: &str =
+    "Lower depth or limit_per_hop, restrict edge_types, or raise min_score / min_confidence";
+
+/// How to narrow a graph export that is too large
+pub const EXPORT_GRAPH_HINT: &str =
+    "Lower max_nodes, use summarize or format=stats, or pass output_path to stream the export to a file";
+
+/// A resource with a hard cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    GraphNodes,
+    GraphEdges,
+    ResultBytes,
+}
+
+impl Resource {
+    fn describe(self) -> &'static str {
+        match self {
+            Resource::GraphNodes => "graph nodes",
+            Resource::GraphEdges => "graph edges",
+            Resource::ResultBytes => "response bytes",
+        }
+    }
+
+    fn env(self) -> &'static str {
+        match self {
+            Resource::GraphNodes => MAX_GRAPH_NODES_ENV,
+            Resource::GraphEdges => MAX_GRAPH_EDGES_ENV,
+            Resource::ResultBytes => MAX_RESULT_BYTES_ENV,
+        }
+    }
+}
+
+/// Hard caps applied to a call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_nodes: usize,
+    pub max_edges: usize,
+    pub max_result_bytes: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: DEFAULT_MAX_GRAPH_NODES,
+            max_edges: DEFAULT_MAX_GRAPH_EDGES,
+            max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Defaults overridden by the `ENGRAM_MAX_*` environment variables
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            max_nodes: var(MAX_GRAPH_NODES_ENV, DEFAULT_MAX_GRAPH_NODES),
+            max_edges: var(MAX_GRAPH_EDGES_ENV, DEFAULT_MAX_GRAPH_EDGES),
+            max_result_bytes: var(MAX_RESULT_BYTES_ENV, DEFAULT_MAX_RESULT_BYTES),
+        }
+    }
+
+    pub fn check_nodes(&self, count: usize, hint: &str) -> Result<()> {
+        check(Resource::GraphNodes, count, self.max_nodes, hint)
+    }
+
+    pub fn check_edges(&self, count: usize, hint: &str) -> Result<()> {
+        check(Resource::GraphEdges, count, self.max_edges, hint)
+    }
+
+    /// Fails when `value` serializes to more than `max_result_bytes`,
+    /// without building the serialized text
+    pub fn check_result_size(&self, value: &Value, hint: &str) -> Result<()> {
+        let mut counter = ByteCounter {
+            count: 0,
+            limit: self.max_result_bytes,
+        };
+        if serde_json::to_writer(&mut counter, value).is_ok() {
+            return Ok(());
+        }
+        Err(exceeded(
+            Resource::ResultBytes,
+            counter.count,
+            self.max_result_bytes,
+            hint,
+        ))
+    }
+}
+
+/// Fails when `count` is over `limit`
+pub fn check(resource: Resource, count: usize, limit: usize, hint: &str) -> Result<()> {
+    if count <= limit {
+        return Ok(());
+    }
+    Err(exceeded(resource, count, limit, hint))
+}
+
+fn exceeded(resource: Resource, count: usize, limit: usize, hint: &str) -> EngramError {
+    let count = match resource {
+        // Serialization stops at the first write past the limit
+        Resource::ResultBytes => format!("more than {limit}"),
+        _ => count.to_string(),
+    };
+    EngramError::LimitExceeded(format!(
+        "{count} {} exceeds the limit of {limit} ({}). {hint}",
+        resource.describe(),
+        resource.env(),
+    ))
+}
+
+/// How to narrow an oversized response of `tool_name`
+pub fn narrowing_hint(tool_name: &str) -> &'static str {
+    match tool_name {
+        "memory_traverse" | "memory_find_path" => TRAVERSAL_HINT,
+        "memory_export_graph" => EXPORT_GRAPH_HINT,
+        "memory_export_neighborhood" => "Lower depth or max_nodes, or add a filter",
+        "memory_export" => {
+            "Export less at once with memory_export_markdown for one workspace, or raise the limit"
+        }
+        _ => "Narrow the request with filters (workspace, tags, memory_type) or a smaller limit",
+    }
+}
+
+/// Counts bytes written and fails once they pass the limit
+struct ByteCounter {
+    count: usize,
+    limit: usize,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.count += buf.len();
+        if self.count > self.limit {
+            return Err(std::io::Error::other("result size limit exceeded"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_limits_name_the_cap_and_hint() {
+        let limits = ResourceLimits {
+            max_nodes: 10,
+            max_edges: 20,
+            max_result_bytes: 64,
+        };
+        assert!(limits.check_nodes(10, TRAVERSAL_HINT).is_ok());
+
+        let err = limits.check_nodes(11, TRAVERSAL_HINT).unwrap_err();
+        let message = err.to_string();
+        assert!(matches!(err, EngramError::LimitExceeded(_)));
+        assert!(message.contains("11 graph nodes exceeds the limit of 10"));
+        assert!(message.contains(MAX_GRAPH_NODES_ENV));
+        assert!(message.contains("limit_per_hop"));
+
+        assert!(limits.check_edges(21, EXPORT_GRAPH_HINT).is_err());
+
+        assert!(limits
+            .check_result_size(&json!({"ok": true}), "narrow it")
+            .is_ok());
+        let big = json!({"content": "x".repeat(100)});
+        let message = limits
+            .check_result_size(&big, "narrow it")
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("more than 64 response bytes"));
+        assert!(message.ends_with("narrow it"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Permission requirements for MCP tools
+//!
+//! Callers authenticated with a database-issued API key carry an
+//! [`AuthContext`]. Each tool needs one `(Permission, ResourceType)` pair:
+//! maintenance and server-administration tools need system admin, read-only
+//! tools need memory read, destructive tools need memory delete, and the rest
+//! need memory write. Tools that read or write files on the server need
+//! admin too, as do calls that pass a server path to a tool where the path
+//! is optional. `tools/list` hides tools the caller can't use and
+//! `tools/call` refuses them, so a read-only key only ever sees read tools.
+//!
+//! Requests without an auth context (stdio, or the server-wide API key) are
+//! not restricted.
+
+use crate::auth::{AuthContext, Permission, ResourceType};
+
+use super::protocol::ToolDefinition;
+use super::tools::TOOL_DEFINITIONS;
+use super::versioning::{route_tool, ToolRoute};
+
+/// Tools that rebuild indexes, rewrite stored data in bulk, change server
+/// configuration or touch the server's filesystem
+pub const ADMIN_TOOLS: &[&str] = &[
+    "embedding_cache_clear",
+    "langfuse_import_scores",
+    "langfuse_sync",
+    "lifecycle_config",
+    "meilisearch_config",
+    "meilisearch_reindex",
+    "memory_cleanup_expired",
+    "memory_events_clear",
+    "memory_export_markdown",
+    "memory_export_site",
+    "memory_fit_embedding_adapter",
+    "memory_import",
+    "memory_ingest_document",
+    "memory_list_instruction_files",
+    "memory_migrate_embeddings",
+    "memory_migrate_images",
+    "memory_rebuild_adjacency",
+    "memory_rebuild_crossrefs",
+    "memory_rebuild_embeddings",
+    "memory_rebuild_signatures",
+    "memory_rebuild_vocabulary",
+    "memory_reduce_embeddings",
+    "memory_restore_embeddings",
+    "memory_scan_project",
+    "memory_search_by_image",
+    "memory_upload_image",
+    "memory_vector_index_rebuild",
+    "persona_activate",
+    "persona_deactivate",
+    "persona_delete",
+    "persona_upsert",
+    "retention_policy_apply",
+    "retention_policy_delete",
+    "retention_policy_set",
+    "search_cache_clear",
+    "snapshot_create",
+    "snapshot_inspect",
+    "snapshot_load",
+    "sync_cleanup",
+    "tag_normalization_set",
+    "tag_policy_set",
+    "tag_synonym_add",
+    "workspace_config_delete",
+    "workspace_config_set",
+    "workspace_merge",
+    "workspace_share_create",
+    "workspace_share_revoke",
+    "workspace_split",
+];
+
+/// Optional arguments naming a server path; passing one needs admin
+pub const PATH_ARGUMENTS: &[(&str, &str)] = &[
+    ("memory_export_graph", "output_path"),
+    ("memory_get_project_context", "path"),
+];
+
+/// Permission a caller needs to use a tool. Deprecated names need what
+/// their replacement needs; unknown tools need admin.
+pub fn required_permission(name: &str) -> (Permission, ResourceType) {
+    let name = match route_tool(name) {
+        ToolRoute::Shim { replacement, .. } => replacement,
+        _ => name,
+    };
+    if name == "discover_tools" {
+        return (Permission::Read, ResourceType::Memory);
+    }
+    if ADMIN_TOOLS.contains(&name) {
+        return (Permission::Admin, ResourceType::System);
+    }
+    let Some(def) = TOOL_DEFINITIONS.iter().find(|def| def.name == name) else {
+        return (Permission::Admin, ResourceType::System);
+    };
+    if def.annotations.read_only_hint == Some(true) {
+        (Permission::Read, ResourceType::Memory)
+    } else if def.annotations.destructive_hint == Some(true) {
+        (Permission::Delete, ResourceType::Memory)
+    } else {
+        (Permission::Write, ResourceType::Memory)
+    }
+}
+
+/// Whether the caller may use a tool
+pub fn tool_allowed(auth: &AuthContext, name: &str) -> bool {
+    let (permission, resource) = required_permission(name);
+    auth.has_permission(permission, resource)
+}
+
+/// Permission a caller needs for one call, counting path arguments
+pub fn required_permission_for_call(
+    name: &str,
+    arguments: &serde_json::Value,
+) -> (Permission, ResourceType) {
+    let name = match route_tool(name) {
+        ToolRoute::Shim { replacement, .. } => replacement,
+        _ => name,
+    };
+    let passes_path = PATH_ARGUMENTS.iter().any(|(tool, argument)| {
+        *tool == name && arguments.get(*argument).is_some_and(|v| !v.is_null())
+    });
+    if passes_path {
+        return (Permission::Admin, ResourceType::System);
+    }
+    required_permission(name)
+}
+
+/// Whether the caller may make this call
+pub fn call_allowed(auth: &AuthContext, name: &str, arguments: &serde_json::Value) -> bool {
+    let (permission, resource) = required_permission_for_call(name, arguments);
+    auth.has_permission(permission, resource)
+}
+
+/// Drop the tools the caller may not use
+pub fn filter_tools(tools: &mut Vec<ToolDefinition>, auth: &AuthContext) {
+    tools.retain(|tool| tool_allowed(auth, &tool.name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{PermissionSet, UserId};
+    use crate::mcp::tools::get_tool_definitions;
+
+    #[test]
+    fn test_admin_tools_are_defined() {
+        let definitions = include_str!("tools.rs");
+        for name in ADMIN_TOOLS {
+            assert!(
+                definitions.contains(&format!("name: \"{}\",", name)),
+                "{} is not a tool",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_only_key_sees_read_tools() {
+        let auth = AuthContext::new(UserId::new(), PermissionSet::read_only());
+        let mut tools = get_tool_definitions();
+        let total = tools.len();
+        filter_tools(&mut tools, &auth);
+
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert!(names.contains(&"memory_search"));
+        assert!(names.contains(&"discover_tools"));
+        assert!(!names.contains(&"memory_create"));
+        assert!(!names.contains(&"memory_delete"));
+        assert!(!names.contains(&"memory_vector_index_rebuild"));
+        assert!(tools.len() < total);
+        assert!(tools
+            .iter()
+            .all(
+                |t| t.annotations.as_ref().and_then(|a| a.read_only_hint) == Some(true)
+                    || t.name == "discover_tools"
+            ));
+    }
+
+    #[test]
+    fn test_server_paths_need_admin() {
+        let reader = AuthContext::new(UserId::new(), PermissionSet::read_only());
+        let export = serde_json::json!({"format": "json"});
+        let export_to_file =
+            serde_json::json!({"format": "json", "output_path": "/tmp/graph.json"});
+        assert!(call_allowed(&reader, "memory_export_graph", &export));
+        assert!(!call_allowed(
+            &reader,
+            "memory_export_graph",
+            &export_to_file
+        ));
+        assert!(!call_allowed(
+            &reader,
+            "snapshot_inspect",
+            &serde_json::json!({"path": "/tmp/x.egm"})
+        ));
+
+        let user = AuthContext::new(UserId::new(), PermissionSet::standard_user());
+        assert!(!call_allowed(
+            &user,
+            "memory_ingest_document",
+            &serde_json::json!({"path": "/etc/passwd"})
+        ));
+        assert!(call_allowed(
+            &AuthContext::system(),
+            "memory_export_graph",
+            &export_to_file
+        ));
+    }
+
+    #[test]
+    fn test_standard_user_and_admin() {
+        let user = AuthContext::new(UserId::new(), PermissionSet::standard_user());
+        assert!(tool_allowed(&user, "memory_create"));
+        assert!(tool_allowed(&user, "memory_delete"));
+        assert!(!tool_allowed(&user, "workspace_merge"));
+        assert!(!tool_allowed(&user, "no_such_tool"));
+        // Deprecated names are checked against their replacement
+        assert_eq!(
+            required_permission("memory_seed"),
+            required_permission("context_seed")
+        );
+
+        let admin = AuthContext::system();
+        let mut tools = get_tool_definitions();
+        let total = tools.len();
+        filter_tools(&mut tools, &admin);
+        assert_eq!(tools.len(), total);
+    }
+}
@@ -0,0 +1,136 @@
+//! Embedded web dashboard
+//!
+//! A single-page UI for browsing memories, faceted search, the knowledge
+//! graph, conflict review and background job status. The assets are compiled
+//! into the binary and served under `/ui` by the HTTP transport when the
+//! server is started with `--dashboard`.
+//!
+//! The assets themselves are public; the page calls `POST /mcp` with the API
+//! key the user enters, so it is subject to the same authentication as every
+//! other client. The page loads nothing from other origins (the graph view is
+//! bundled too), and its content security policy keeps it that way, since a
+//! script injected into it could read the key.
+
+use axum::{
+    http::header,
+    response::{IntoResponse, Redirect},
+    routing::get,
+    Router,
+};
+
+const INDEX_HTML: &str = include_str!("dashboard/index.html");
+const APP_JS: &str = include_str!("dashboard/app.js");
+const GRAPH_JS: &str = include_str!("dashboard/graph.js");
+const STYLE_CSS: &str = include_str!("dashboard/style.css");
+
+/// Scripts, styles and connections limited to this server
+const CONTENT_SECURITY_POLICY: &str =
+    "default-src 'self'; connect-src 'self' ws: wss:; img-src 'self' data:; object-src 'none'; base-uri 'none'; frame-ancestors 'none'";
+
+/// Routes serving the dashboard under `/ui`
+pub fn routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/ui", get(|| async { Redirect::permanent("/ui/") }))
+        .route(
+            "/ui/",
+            get(|| asset("text/html; charset=utf-8", INDEX_HTML)),
+        )
+        .route(
+            "/ui/app.js",
+            get(|| asset("text/javascript; charset=utf-8", APP_JS)),
+        )
+        .route(
+            "/ui/graph.js",
+            get(|| asset("text/javascript; charset=utf-8", GRAPH_JS)),
+        )
+        .route(
+            "/ui/style.css",
+            get(|| asset("text/css; charset=utf-8", STYLE_CSS)),
+        )
+}
+
+async fn asset(content_type: &'static str, body: &'static str) -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "no-cache"),
+            (header::CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY),
+        ],
+        body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    async fn get(path: &str) -> axum::response::Response {
+        routes::<()>()
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serves_assets() {
+        let index = get("/ui/").await;
+        assert_eq!(index.status(), StatusCode::OK);
+        assert_eq!(
+            index.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+
+        let script = get("/ui/app.js").await;
+        assert_eq!(script.status(), StatusCode::OK);
+        assert!(script.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/javascript"));
+
+        assert!(index.headers()[header::CONTENT_SECURITY_POLICY]
+            .to_str()
+            .unwrap()
+            .starts_with("default-src 'self'"));
+        assert_eq!(get("/ui/graph.js").await.status(), StatusCode::OK);
+
+        assert_eq!(get("/ui").await.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(get("/ui/missing.js").await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_index_references_bundled_assets() {
+        assert!(INDEX_HTML.contains("/ui/app.js"));
+        assert!(INDEX_HTML.contains("/ui/graph.js"));
+        assert!(INDEX_HTML.contains("/ui/style.css"));
+        // Nothing is loaded from other origins
+        assert!(!INDEX_HTML.contains("https://"));
+        assert!(!APP_JS.contains("localStorage"));
+        assert!(APP_JS.contains("/v1/graph/ws"));
+        // Every tool the page calls must exist.
+        for tool in [
+            "memory_list",
+            "memory_get",
+            "memory_related",
+            "memory_search",
+            "memory_stats",
+            "memory_export_graph",
+            "quality_get_conflicts",
+            "quality_resolve_conflict",
+            "sync_task_list",
+        ] {
+            assert!(APP_JS.contains(&format!("\"{tool}\"")), "{tool}");
+            assert!(
+                super::super::TOOL_DEFINITIONS
+                    .iter()
+                    .any(|t| t.name == tool),
+                "{tool}"
+            );
+        }
+    }
+}
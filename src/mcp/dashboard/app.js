@@ -0,0 +1,482 @@
+// Engram dashboard. Every request is an MCP `tools/call` against POST /mcp,
+// so the dashboard sees exactly what any other client with the same key can.
+"use strict";
+
+const PAGE_SIZE = 25;
+const JOBS_REFRESH_MS = 5000;
+
+const state = {
+    // Per tab, and gone when it closes, so the key isn't left on disk
+    token: sessionStorage.getItem("engram.token") || "",
+    page: 0,
+    searchHits: [],
+    facetFilter: null,
+    graph: null,
+    graphSocket: null,
+    jobsTimer: null,
+};
+
+const $ = (selector) => document.querySelector(selector);
+
+function el(tag, props = {}, ...children) {
+    const node = document.createElement(tag);
+    Object.assign(node, props);
+    for (const child of children) {
+        node.append(child instanceof Node ? child : document.createTextNode(String(child ?? "")));
+    }
+    return node;
+}
+
+function setStatus(message, isError = false) {
+    const status = $("#status");
+    status.textContent = message;
+    status.className = isError ? "error" : "";
+}
+
+let nextRequestId = 1;
+
+async function callTool(name, args = {}) {
+    const headers = { "Content-Type": "application/json" };
+    if (state.token) {
+        headers.Authorization = `Bearer ${state.token}`;
+    }
+    const response = await fetch("/mcp", {
+        method: "POST",
+        headers,
+        body: JSON.stringify({
+            jsonrpc: "2.0",
+            id: nextRequestId++,
+            method: "tools/call",
+            params: { name, arguments: args },
+        }),
+    });
+    if (response.status === 401) {
+        throw new Error("Unauthorized: enter the server API key");
+    }
+    const body = await response.json();
+    if (body.error) {
+        throw new Error(body.error.message);
+    }
+    const text = body.result?.content?.[0]?.text ?? "null";
+    const value = JSON.parse(text);
+    if (value && value.error) {
+        throw new Error(value.error);
+    }
+    return value;
+}
+
+async function run(label, task) {
+    setStatus(`${label}…`);
+    try {
+        await task();
+        setStatus("");
+    } catch (e) {
+        setStatus(`${label} failed: ${e.message}`, true);
+    }
+}
+
+function memoryType(memory) {
+    return memory.type ?? memory.memory_type ?? "note";
+}
+
+function preview(content, length = 160) {
+    const text = (content ?? "").trim();
+    return text.length > length ? `${text.slice(0, length)}…` : text;
+}
+
+function formatDate(value) {
+    return value ? new Date(value).toLocaleString() : "";
+}
+
+function tagChips(tags) {
+    return el("span", { className: "tags" }, ...(tags ?? []).map((t) => el("span", { className: "tag" }, t)));
+}
+
+function memoryItem(memory, extra) {
+    const item = el(
+        "li",
+        {},
+        el("div", { className: "meta" }, `#${memory.id} · ${memoryType(memory)}`, extra ? ` · ${extra}` : ""),
+        el("div", {}, preview(memory.content)),
+        tagChips(memory.tags),
+    );
+    item.addEventListener("click", () => showMemory(memory.id));
+    return item;
+}
+
+// ── Memories ────────────────────────────────────────────────────────────
+
+async function loadMemories() {
+    const args = {
+        limit: PAGE_SIZE,
+        offset: state.page * PAGE_SIZE,
+        sort_by: $("#list-sort").value,
+    };
+    const workspace = $("#list-workspace").value.trim();
+    if (workspace) args.workspace = workspace;
+    if ($("#list-type").value) args.memory_type = $("#list-type").value;
+
+    const memories = await callTool("memory_list", args);
+    $("#memory-list").replaceChildren(...memories.map((m) => memoryItem(m)));
+    $("#list-page").textContent = `Page ${state.page + 1}`;
+    $("#list-prev").disabled = state.page === 0;
+    $("#list-next").disabled = memories.length < PAGE_SIZE;
+}
+
+async function loadTypes() {
+    const stats = await callTool("memory_stats");
+    const select = $("#list-type");
+    const types = Object.keys(stats.type_counts ?? {}).sort();
+    select.replaceChildren(
+        el("option", { value: "" }, "All types"),
+        ...types.map((t) => el("option", { value: t }, `${t} (${stats.type_counts[t]})`)),
+    );
+}
+
+async function showMemory(id) {
+    await run("Loading memory", async () => {
+        const memory = await callTool("memory_get", { id });
+        const related = await callTool("memory_related", { id }).catch(() => []);
+        const metadata = Object.keys(memory.metadata ?? {}).length
+            ? el("pre", {}, JSON.stringify(memory.metadata, null, 2))
+            : "";
+        $("#memory-detail").replaceChildren(
+            el("h2", {}, `#${memory.id} · ${memoryType(memory)}`),
+            el(
+                "div",
+                { className: "meta" },
+                `importance ${Number(memory.importance).toFixed(2)} · workspace ${memory.workspace ?? "default"} · created ${formatDate(memory.created_at)}`,
+            ),
+            tagChips(memory.tags),
+            el("pre", { className: "content" }, memory.content),
+            metadata,
+            el("h3", {}, "Related"),
+            el(
+                "ul",
+                { className: "related" },
+                ...(Array.isArray(related) ? related : []).map((r) => {
+                    const other = r.from_id === memory.id ? r.to_id : r.from_id;
+                    const link = el("a", { href: "#" }, `#${other}`);
+                    link.addEventListener("click", (e) => {
+                        e.preventDefault();
+                        showMemory(other);
+                    });
+                    return el("li", {}, link, ` ${r.edge_type} (${Number(r.score).toFixed(2)})`);
+                }),
+            ),
+        );
+        showView("memories");
+    });
+}
+
+// ── Search ──────────────────────────────────────────────────────────────
+
+const FACETS = {
+    type: (m) => [memoryType(m)],
+    workspace: (m) => [m.workspace ?? "default"],
+    tag: (m) => m.tags ?? [],
+};
+
+async function search(event) {
+    event.preventDefault();
+    await run("Searching", async () => {
+        const result = await callTool("memory_search", {
+            query: $("#search-query").value,
+            strategy: $("#search-strategy").value,
+            limit: 100,
+        });
+        state.searchHits = Array.isArray(result) ? result : result.results ?? [];
+        state.facetFilter = null;
+        renderSearch();
+    });
+}
+
+function renderSearch() {
+    const hits = state.searchHits.filter((hit) => {
+        if (!state.facetFilter) return true;
+        const [facet, value] = state.facetFilter;
+        return FACETS[facet](hit.memory).includes(value);
+    });
+
+    const facets = Object.entries(FACETS).map(([facet, values]) => {
+        const counts = new Map();
+        for (const hit of state.searchHits) {
+            for (const value of values(hit.memory)) {
+                counts.set(value, (counts.get(value) ?? 0) + 1);
+            }
+        }
+        const entries = [...counts].sort((a, b) => b[1] - a[1]).slice(0, 15);
+        return el(
+            "div",
+            {},
+            el("h3", {}, facet),
+            ...entries.map(([value, count]) => {
+                const active = state.facetFilter?.[0] === facet && state.facetFilter?.[1] === value;
+                const button = el("button", { className: active ? "facet active" : "facet" }, `${value} (${count})`);
+                button.addEventListener("click", () => {
+                    state.facetFilter = active ? null : [facet, value];
+                    renderSearch();
+                });
+                return button;
+            }),
+        );
+    });
+
+    $("#facets").replaceChildren(...facets);
+    $("#search-results").replaceChildren(
+        ...hits.map((hit) => memoryItem(hit.memory, `score ${Number(hit.score).toFixed(3)}`)),
+    );
+    if (hits.length === 0) {
+        $("#search-results").append(el("li", { className: "empty" }, "No results"));
+    }
+}
+
+// ── Graph ───────────────────────────────────────────────────────────────
+
+async function loadGraph() {
+    await run("Loading graph", async () => {
+        const args = { format: "json", max_nodes: Number($("#graph-max").value) || 200 };
+        const focus = Number($("#graph-focus").value);
+        if (focus) args.focus_id = focus;
+        const graph = await callTool("memory_export_graph", args);
+        if (state.graph) state.graph.destroy();
+        state.graph = new GraphView($("#graph-canvas"), showMemory);
+        state.graph.setGraph(
+            graph.nodes,
+            graph.edges.map((e) => ({ ...e, id: edgeId(e.from, e.to, e.label) })),
+        );
+        followGraph();
+    });
+}
+
+function edgeId(from, to, edgeType) {
+    return `${from}:${to}:${edgeType}`;
+}
+
+// Live updates: apply graph mutations from /v1/graph/ws to the loaded graph
+// instead of re-exporting it.
+function followGraph() {
+    if (state.graphSocket) state.graphSocket.close();
+    state.graphSocket = null;
+    if (!$("#graph-live").checked) return;
+
+    const scheme = location.protocol === "https:" ? "wss" : "ws";
+    const query = state.token ? `?token=${encodeURIComponent(state.token)}` : "";
+    const socket = new WebSocket(`${scheme}://${location.host}/v1/graph/ws${query}`);
+    socket.addEventListener("message", (message) => applyGraphMutation(JSON.parse(message.data)));
+    socket.addEventListener("close", () => {
+        if (state.graphSocket === socket) {
+            state.graphSocket = null;
+            setStatus("Live graph updates disconnected", true);
+        }
+    });
+    state.graphSocket = socket;
+}
+
+function graphNode(node) {
+    // Reuse the server's styling for the type when the graph already shows it.
+    const sameType = state.graph.nodeOfGroup(node.memory_type);
+    return {
+        id: node.id,
+        label: node.label,
+        group: node.memory_type,
+        color: sameType?.color,
+        shape: sameType?.shape,
+        value: Math.trunc(node.importance * 10) + 5,
+        title: `Type: ${node.memory_type}\nTags: ${node.tags.join(", ")}`,
+    };
+}
+
+function applyGraphMutation(event) {
+    const { graph } = state;
+    switch (event.type) {
+        case "node_added":
+        case "node_updated":
+            graph.updateNode(graphNode(event.node));
+            break;
+        case "node_removed":
+            graph.removeNode(event.id);
+            break;
+        case "edge_added": {
+            const e = event.edge;
+            if (!graph.node(e.from) || !graph.node(e.to)) break;
+            graph.updateEdge({
+                id: edgeId(e.from, e.to, e.edge_type),
+                from: e.from,
+                to: e.to,
+                label: e.edge_type,
+                value: Math.trunc(e.score * e.confidence * 5) + 1,
+                title: `Score: ${e.score.toFixed(2)}, Confidence: ${e.confidence.toFixed(2)}`,
+            });
+            break;
+        }
+        case "edge_removed":
+            graph.removeEdge(edgeId(event.from, event.to, event.edge_type));
+            break;
+        case "cluster_changed": {
+            const node = graph.node(event.id);
+            if (node) {
+                const title = node.title.replace(/\nCluster: .*$/, "");
+                graph.updateNode({ id: event.id, title: `${title}\nCluster: ${event.cluster}` });
+            }
+            break;
+        }
+        case "reset":
+            loadGraph();
+            break;
+    }
+}
+
+// ── Conflicts ───────────────────────────────────────────────────────────
+
+const RESOLUTIONS = ["keep_a", "keep_b", "merge", "keep_both", "delete_both", "false_positive"];
+
+async function loadConflicts() {
+    await run("Loading conflicts", async () => {
+        const conflicts = await callTool("quality_get_conflicts", { limit: 100 });
+        const memoryLink = (id) => {
+            const link = el("a", { href: "#" }, `#${id}`);
+            link.addEventListener("click", (e) => {
+                e.preventDefault();
+                showMemory(id);
+            });
+            return link;
+        };
+        const rows = conflicts.map((c) => {
+            const select = el("select", {}, ...RESOLUTIONS.map((r) => el("option", { value: r }, r)));
+            const apply = el("button", {}, "Apply");
+            apply.addEventListener("click", () =>
+                run("Resolving conflict", async () => {
+                    await callTool("quality_resolve_conflict", { conflict_id: c.id, resolution: select.value });
+                    await loadConflicts();
+                }),
+            );
+            return el(
+                "tr",
+                { title: c.description ?? "" },
+                el("td", {}, memoryLink(c.memory_a_id), " vs ", memoryLink(c.memory_b_id)),
+                el("td", {}, c.conflict_type),
+                el("td", { className: `severity ${c.severity}` }, c.severity),
+                el("td", {}, formatDate(c.detected_at)),
+                el("td", {}, select, apply),
+            );
+        });
+        $("#conflicts tbody").replaceChildren(...rows);
+        if (rows.length === 0) {
+            $("#conflicts tbody").append(el("tr", {}, el("td", { colSpan: 5, className: "empty" }, "No unresolved conflicts")));
+        }
+    });
+}
+
+// ── Jobs ────────────────────────────────────────────────────────────────
+
+async function loadJobs() {
+    await run("Loading jobs", async () => {
+        const [stats, jobs] = await Promise.all([callTool("memory_stats"), callTool("sync_task_list", { limit: 50 })]);
+        const card = (label, value) => el("div", { className: "card" }, el("strong", {}, value), el("span", {}, label));
+        $("#stats").replaceChildren(
+            card("memories", stats.total_memories),
+            card("cross-references", stats.total_crossrefs),
+            card("embedded", stats.memories_with_embeddings),
+            card("pending embedding", stats.memories_pending_embedding),
+            card("schema version", stats.schema_version),
+        );
+        const rows = jobs.tasks.map((t) =>
+            el(
+                "tr",
+                {},
+                el("td", {}, t.task_id),
+                el("td", {}, t.task_type),
+                el("td", { className: `status ${t.status}` }, t.status),
+                el("td", {}, el("progress", { max: 100, value: t.progress_percent }), ` ${t.progress_percent}%`),
+                el("td", {}, t.eta_seconds == null ? "" : `${t.eta_seconds}s`),
+                el("td", {}, formatDate(t.started_at)),
+                el("td", { className: "error" }, t.error_message ?? ""),
+            ),
+        );
+        $("#jobs tbody").replaceChildren(...rows);
+        if (rows.length === 0) {
+            $("#jobs tbody").append(el("tr", {}, el("td", { colSpan: 7, className: "empty" }, "No background tasks")));
+        }
+    });
+}
+
+function scheduleJobs(active) {
+    clearInterval(state.jobsTimer);
+    state.jobsTimer = active && $("#jobs-auto").checked ? setInterval(loadJobs, JOBS_REFRESH_MS) : null;
+}
+
+// ── Wiring ──────────────────────────────────────────────────────────────
+
+function showView(name) {
+    for (const button of document.querySelectorAll("nav button")) {
+        button.classList.toggle("active", button.dataset.view === name);
+    }
+    for (const view of document.querySelectorAll(".view")) {
+        view.classList.toggle("active", view.id === `view-${name}`);
+    }
+    scheduleJobs(name === "jobs");
+}
+
+const VIEW_LOADERS = {
+    memories: () => run("Loading memories", loadMemories),
+    search: () => {},
+    graph: () => {
+        if (!state.graph) loadGraph();
+    },
+    conflicts: loadConflicts,
+    jobs: loadJobs,
+};
+
+function init() {
+    $("#token").value = state.token;
+    $("#auth").addEventListener("submit", (e) => {
+        e.preventDefault();
+        state.token = $("#token").value.trim();
+        sessionStorage.setItem("engram.token", state.token);
+        refreshAll();
+    });
+
+    for (const button of document.querySelectorAll("nav button")) {
+        button.addEventListener("click", () => {
+            showView(button.dataset.view);
+            VIEW_LOADERS[button.dataset.view]();
+        });
+    }
+
+    const reloadList = () => {
+        state.page = 0;
+        run("Loading memories", loadMemories);
+    };
+    $("#list-refresh").addEventListener("click", reloadList);
+    $("#list-type").addEventListener("change", reloadList);
+    $("#list-sort").addEventListener("change", reloadList);
+    $("#list-prev").addEventListener("click", () => {
+        state.page = Math.max(0, state.page - 1);
+        run("Loading memories", loadMemories);
+    });
+    $("#list-next").addEventListener("click", () => {
+        state.page += 1;
+        run("Loading memories", loadMemories);
+    });
+
+    $("#search-form").addEventListener("submit", search);
+    $("#graph-load").addEventListener("click", loadGraph);
+    $("#graph-live").addEventListener("change", () => {
+        if (state.graph) followGraph();
+    });
+    $("#conflicts-refresh").addEventListener("click", loadConflicts);
+    $("#jobs-refresh").addEventListener("click", loadJobs);
+    $("#jobs-auto").addEventListener("change", () => scheduleJobs(true));
+
+    refreshAll();
+}
+
+function refreshAll() {
+    run("Loading memories", async () => {
+        await loadTypes();
+        await loadMemories();
+    });
+}
+
+document.addEventListener("DOMContentLoaded", init);
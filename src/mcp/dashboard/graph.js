@@ -0,0 +1,274 @@
+// Knowledge graph view for the dashboard: a small force-directed layout drawn
+// as SVG, bundled with the page so it loads no third-party scripts.
+"use strict";
+
+const SVG_NS = "http://www.w3.org/2000/svg";
+const LAYOUT_TICKS = 300;
+
+// Sides and rotation of the vis.js shape names the server sends; anything
+// else is drawn as a circle.
+const POLYGONS = {
+    box: [4, Math.PI / 4],
+    square: [4, Math.PI / 4],
+    diamond: [4, 0],
+    triangle: [3, -Math.PI / 2],
+    triangleDown: [3, Math.PI / 2],
+    hexagon: [6, 0],
+};
+
+function svg(tag, attrs = {}) {
+    const node = document.createElementNS(SVG_NS, tag);
+    for (const [name, value] of Object.entries(attrs)) node.setAttribute(name, value);
+    return node;
+}
+
+function polygonPoints(sides, rotation, radius) {
+    return Array.from({ length: sides }, (_, i) => {
+        const angle = rotation + (2 * Math.PI * i) / sides;
+        return `${(radius * Math.cos(angle)).toFixed(1)},${(radius * Math.sin(angle)).toFixed(1)}`;
+    }).join(" ");
+}
+
+class GraphView {
+    // `onOpen(id)` runs when a node is double-clicked.
+    constructor(container, onOpen) {
+        this.onOpen = onOpen;
+        this.nodes = new Map();
+        this.edges = new Map();
+        this.view = { x: 0, y: 0, scale: 1 };
+        this.ticks = 0;
+        this.frame = null;
+
+        this.root = svg("svg", { width: "100%", height: "100%" });
+        const marker = svg("marker", {
+            id: "graph-arrow",
+            viewBox: "0 0 10 10",
+            refX: 10,
+            refY: 5,
+            markerWidth: 6,
+            markerHeight: 6,
+            orient: "auto-start-reverse",
+        });
+        marker.append(svg("path", { d: "M 0 0 L 10 5 L 0 10 z", class: "graph-arrow" }));
+        const defs = svg("defs");
+        defs.append(marker);
+        this.scene = svg("g");
+        this.edgeLayer = svg("g");
+        this.nodeLayer = svg("g");
+        this.scene.append(this.edgeLayer, this.nodeLayer);
+        this.root.append(defs, this.scene);
+        container.replaceChildren(this.root);
+        this.bindPointer();
+    }
+
+    destroy() {
+        cancelAnimationFrame(this.frame);
+        window.removeEventListener("pointermove", this.onPointerMove);
+        window.removeEventListener("pointerup", this.onPointerUp);
+        this.root.remove();
+    }
+
+    node(id) {
+        return this.nodes.get(id)?.data;
+    }
+
+    // First node of a memory type, to style nodes added by live updates.
+    nodeOfGroup(group) {
+        for (const { data } of this.nodes.values()) {
+            if (data.group === group) return data;
+        }
+        return undefined;
+    }
+
+    updateNode(data) {
+        const existing = this.nodes.get(data.id);
+        if (existing) {
+            existing.data = { ...existing.data, ...data };
+            existing.element.remove();
+            existing.element = this.drawNode(existing);
+        } else {
+            const angle = Math.random() * 2 * Math.PI;
+            const spread = 40 * Math.sqrt(this.nodes.size + 1);
+            const node = { data, x: spread * Math.cos(angle), y: spread * Math.sin(angle), vx: 0, vy: 0 };
+            node.element = this.drawNode(node);
+            this.nodes.set(data.id, node);
+        }
+        this.relayout();
+    }
+
+    removeNode(id) {
+        const node = this.nodes.get(id);
+        if (!node) return;
+        node.element.remove();
+        this.nodes.delete(id);
+        for (const [edgeId, edge] of this.edges) {
+            if (edge.data.from === id || edge.data.to === id) this.removeEdge(edgeId);
+        }
+        this.relayout();
+    }
+
+    updateEdge(data) {
+        this.edges.get(data.id)?.element.remove();
+        const edge = { data };
+        edge.element = svg("g", { class: "graph-edge" });
+        const line = svg("line", {
+            "marker-end": "url(#graph-arrow)",
+            "stroke-width": Math.min(data.value || 1, 6),
+        });
+        const title = svg("title");
+        title.textContent = data.title || data.label || "";
+        line.append(title);
+        const label = svg("text", { "text-anchor": "middle" });
+        label.textContent = data.label || "";
+        edge.element.append(line, label);
+        this.edgeLayer.append(edge.element);
+        this.edges.set(data.id, edge);
+        this.relayout();
+    }
+
+    removeEdge(id) {
+        this.edges.get(id)?.element.remove();
+        this.edges.delete(id);
+    }
+
+    setGraph(nodes, edges) {
+        for (const node of nodes) this.updateNode(node);
+        for (const edge of edges) this.updateEdge(edge);
+    }
+
+    radius(data) {
+        return 6 + Math.min(data.value || 5, 15);
+    }
+
+    drawNode(node) {
+        const { data } = node;
+        const group = svg("g", { class: "graph-node" });
+        const radius = this.radius(data);
+        const fill = data.color || "#4a90d9";
+        const polygon = POLYGONS[data.shape];
+        const body = polygon
+            ? svg("polygon", { points: polygonPoints(polygon[0], polygon[1], radius), fill })
+            : svg("circle", { r: radius, fill });
+        const title = svg("title");
+        title.textContent = data.title || "";
+        body.append(title);
+        const label = svg("text", { y: radius + 12, "text-anchor": "middle" });
+        label.textContent = data.label || "";
+        group.append(body, label);
+        group.addEventListener("dblclick", () => this.onOpen(data.id));
+        group.addEventListener("pointerdown", (e) => {
+            e.stopPropagation();
+            this.drag = { node, x: e.clientX, y: e.clientY };
+        });
+        this.nodeLayer.append(group);
+        return group;
+    }
+
+    bindPointer() {
+        this.root.addEventListener("pointerdown", (e) => {
+            this.drag = { x: e.clientX, y: e.clientY };
+        });
+        this.onPointerMove = (e) => {
+            if (!this.drag) return;
+            const dx = e.clientX - this.drag.x;
+            const dy = e.clientY - this.drag.y;
+            this.drag.x = e.clientX;
+            this.drag.y = e.clientY;
+            if (this.drag.node) {
+                this.drag.node.x += dx / this.view.scale;
+                this.drag.node.y += dy / this.view.scale;
+            } else {
+                this.view.x += dx;
+                this.view.y += dy;
+            }
+            this.render();
+        };
+        this.onPointerUp = () => {
+            this.drag = null;
+        };
+        window.addEventListener("pointermove", this.onPointerMove);
+        window.addEventListener("pointerup", this.onPointerUp);
+        this.root.addEventListener("wheel", (e) => {
+            e.preventDefault();
+            this.view.scale = Math.min(4, Math.max(0.1, this.view.scale * Math.exp(-e.deltaY / 500)));
+            this.render();
+        });
+    }
+
+    // Run (or restart) the layout; it cools down and stops after LAYOUT_TICKS.
+    relayout() {
+        this.ticks = 0;
+        if (this.frame === null) this.frame = requestAnimationFrame(() => this.step());
+    }
+
+    step() {
+        const nodes = [...this.nodes.values()];
+        const cooling = 1 - this.ticks / LAYOUT_TICKS;
+        for (let i = 0; i < nodes.length; i++) {
+            const a = nodes[i];
+            for (let j = i + 1; j < nodes.length; j++) {
+                const b = nodes[j];
+                const dx = a.x - b.x || Math.random() - 0.5;
+                const dy = a.y - b.y || Math.random() - 0.5;
+                const dist2 = Math.max(dx * dx + dy * dy, 25);
+                const push = 2000 / (dist2 * Math.sqrt(dist2));
+                a.vx += dx * push;
+                a.vy += dy * push;
+                b.vx -= dx * push;
+                b.vy -= dy * push;
+            }
+            a.vx -= a.x * 0.002;
+            a.vy -= a.y * 0.002;
+        }
+        for (const { data } of this.edges.values()) {
+            const a = this.nodes.get(data.from);
+            const b = this.nodes.get(data.to);
+            if (!a || !b) continue;
+            const dx = b.x - a.x;
+            const dy = b.y - a.y;
+            const dist = Math.sqrt(dx * dx + dy * dy) || 1;
+            const pull = ((dist - 120) * 0.01) / dist;
+            a.vx += dx * pull;
+            a.vy += dy * pull;
+            b.vx -= dx * pull;
+            b.vy -= dy * pull;
+        }
+        for (const node of nodes) {
+            if (this.drag?.node !== node) {
+                node.x += node.vx * cooling;
+                node.y += node.vy * cooling;
+            }
+            node.vx *= 0.6;
+            node.vy *= 0.6;
+        }
+        this.render();
+        this.ticks += 1;
+        this.frame = this.ticks < LAYOUT_TICKS ? requestAnimationFrame(() => this.step()) : null;
+    }
+
+    render() {
+        const { width, height } = this.root.getBoundingClientRect();
+        const { x, y, scale } = this.view;
+        this.scene.setAttribute("transform", `translate(${width / 2 + x},${height / 2 + y}) scale(${scale})`);
+        for (const node of this.nodes.values()) {
+            node.element.setAttribute("transform", `translate(${node.x},${node.y})`);
+        }
+        for (const { data, element } of this.edges.values()) {
+            const a = this.nodes.get(data.from);
+            const b = this.nodes.get(data.to);
+            if (!a || !b) continue;
+            // Stop the arrow at the target's edge
+            const dx = b.x - a.x;
+            const dy = b.y - a.y;
+            const dist = Math.sqrt(dx * dx + dy * dy) || 1;
+            const end = (dist - this.radius(b.data)) / dist;
+            const [line, label] = element.children;
+            line.setAttribute("x1", a.x);
+            line.setAttribute("y1", a.y);
+            line.setAttribute("x2", a.x + dx * end);
+            line.setAttribute("y2", a.y + dy * end);
+            label.setAttribute("x", (a.x + b.x) / 2);
+            label.setAttribute("y", (a.y + b.y) / 2);
+        }
+    }
+}
@@ -24,6 +24,7 @@ use tokio_stream::{Stream, StreamExt};
 use tonic::{metadata::MetadataMap, transport::Server, Request, Response, Status};
 
 use super::protocol::{McpHandler, McpRequest, McpResponse};
+use crate::budget;
 use crate::realtime::{EventType, RealtimeManager};
 
 // Include generated tonic stubs.
@@ -93,6 +94,15 @@ fn check_auth(metadata: &MetadataMap, expected: &Option<String>) -> Result<(), S
     }
 }
 
+/// Scope a caller's JSON-RPC ids are registered and cancelled in, separated
+/// per client by its `mcp-session-id` metadata when it sends one.
+fn request_scope(metadata: &MetadataMap) -> String {
+    match metadata.get("mcp-session-id").and_then(|v| v.to_str().ok()) {
+        Some(session) => format!("grpc/session:{}", session),
+        None => "grpc".to_string(),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Conversion helpers
 // ---------------------------------------------------------------------------
@@ -165,6 +175,7 @@ fn parse_event_type(s: &str) -> Option<EventType> {
         "sync_started" => Some(EventType::SyncStarted),
         "sync_completed" => Some(EventType::SyncCompleted),
         "sync_failed" => Some(EventType::SyncFailed),
+        "memory_suggested" => Some(EventType::MemorySuggested),
         _ => None,
     }
 }
@@ -184,8 +195,15 @@ impl McpService for GrpcMcpService {
     ) -> Result<Response<ProtoResponse>, Status> {
         check_auth(request.metadata(), &self.api_key)?;
 
+        let scope = request_scope(request.metadata());
         let handler_req = proto_to_handler_request(request.into_inner());
-        let handler_resp = self.handler.handle_request(handler_req);
+        let id = handler_req.id.clone();
+        let handler = self.handler.clone();
+        let handler_resp = tokio::task::spawn_blocking(move || {
+            budget::with_request_scope(&scope, || handler.handle_request(handler_req))
+        })
+        .await
+            .unwrap_or_else(|e| McpResponse::error(id, -32603, format!("Internal error: {}", e)));
         let proto_resp = handler_to_proto_response(handler_resp);
         Ok(Response::new(proto_resp))
     }
@@ -287,6 +305,18 @@ pub async fn serve_grpc(
     port: u16,
     api_key: Option<String>,
     realtime: Option<RealtimeManager>,
+) -> crate::error::Result<()> {
+    serve_grpc_with_shutdown(handler, port, api_key, realtime, std::future::pending()).await
+}
+
+/// [`serve_grpc`] until `shutdown` completes, then return once in-flight
+/// calls have finished.
+pub async fn serve_grpc_with_shutdown(
+    handler: Arc<dyn McpHandler>,
+    port: u16,
+    api_key: Option<String>,
+    realtime: Option<RealtimeManager>,
+    shutdown: impl std::future::Future<Output = ()>,
 ) -> crate::error::Result<()> {
     let addr = format!("0.0.0.0:{port}")
         .parse::<std::net::SocketAddr>()
@@ -298,7 +328,7 @@ pub async fn serve_grpc(
 
     Server::builder()
         .add_service(McpServiceServer::new(service))
-        .serve(addr)
+        .serve_with_shutdown(addr, shutdown)
         .await
         .map_err(|e| crate::error::EngramError::Internal(e.to_string()))?;
 
@@ -449,6 +479,7 @@ mod tests {
             ("sync_started", EventType::SyncStarted),
             ("sync_completed", EventType::SyncCompleted),
             ("sync_failed", EventType::SyncFailed),
+            ("memory_suggested", EventType::MemorySuggested),
         ];
         for (input, expected) in cases {
             assert_eq!(parse_event_type(input), Some(expected), "failed for {input}");
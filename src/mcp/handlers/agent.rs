@@ -303,9 +303,16 @@ mod tests {
             search_config: crate::search::SearchConfig::default(),
             realtime: None,
             embedding_cache: Arc::new(crate::embedding::EmbeddingCache::default()),
+            embedding_queue: None,
             search_cache: Arc::new(crate::search::SearchResultCache::new(
                 crate::search::AdaptiveCacheConfig::default(),
             )),
+            memory_cache: Arc::new(crate::storage::MemoryCache::default()),
+            persona: Arc::new(crate::storage::ActivePersona::new()),
+            vector_index: Arc::new(crate::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: crate::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             #[cfg(feature = "meilisearch")]
             meili: None,
             #[cfg(feature = "meilisearch")]
@@ -153,6 +153,171 @@ pub fn memory_fact_graph(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+// ── Structured fact store ─────────────────────────────────────────────────────
+
+/// Read the required `subject` and `predicate` params.
+fn subject_predicate(params: &Value) -> Result<(String, String), Value> {
+    let subject = params.get("subject").and_then(|v| v.as_str());
+    let predicate = params.get("predicate").and_then(|v| v.as_str());
+    match (subject, predicate) {
+        (Some(s), Some(p)) => Ok((s.to_string(), p.to_string())),
+        _ => Err(json!({"error": "subject and predicate are required"})),
+    }
+}
+
+/// Read the optional `workspace` param, normalized like memory workspaces.
+fn fact_workspace(params: &Value) -> Result<String, Value> {
+    let workspace = params
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default");
+    crate::types::normalize_workspace(workspace)
+        .map_err(|e| json!({"error": format!("Invalid workspace: {}", e)}))
+}
+
+/// Assert a structured fact, superseding a different current value.
+///
+/// Params:
+/// - `subject`, `predicate`, `object` (string, required)
+/// - `confidence` (f64, optional) — 0.0-1.0 (default: 1.0)
+/// - `source_memory_id` (i64, optional) — memory the fact was stated in
+/// - `workspace` (string, optional) — default: "default"
+pub fn fact_put(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::fact_store::{put_fact, FactInput};
+
+    let (subject, predicate) = match subject_predicate(&params) {
+        Ok(sp) => sp,
+        Err(e) => return e,
+    };
+    let object = match params.get("object").and_then(|v| v.as_str()) {
+        Some(o) => o,
+        None => return json!({"error": "object is required"}),
+    };
+
+    let mut input = FactInput::new(subject, predicate, object);
+    input.workspace = match fact_workspace(&params) {
+        Ok(workspace) => workspace,
+        Err(e) => return e,
+    };
+    if let Some(confidence) = params.get("confidence").and_then(|v| v.as_f64()) {
+        input.confidence = confidence as f32;
+    }
+    input.source_memory_id = params.get("source_memory_id").and_then(|v| v.as_i64());
+
+    ctx.storage
+        .with_transaction(|conn| put_fact(conn, &input))
+        .map(|outcome| json!(outcome))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+/// Current value of a structured fact.
+///
+/// Params:
+/// - `subject`, `predicate` (string, required)
+/// - `workspace` (string, optional) — default: "default"
+/// - `include_history` (bool, optional) — also return superseded values (default: false)
+pub fn fact_get(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::fact_store::{fact_history, get_fact};
+
+    let (subject, predicate) = match subject_predicate(&params) {
+        Ok(sp) => sp,
+        Err(e) => return e,
+    };
+    let workspace = match fact_workspace(&params) {
+        Ok(workspace) => workspace,
+        Err(e) => return e,
+    };
+    let include_history = params
+        .get("include_history")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let fact = get_fact(conn, &workspace, &subject, &predicate)?;
+            let mut response = json!({
+                "found": fact.is_some(),
+                "fact": fact,
+            });
+            if include_history {
+                response["history"] = json!(fact_history(conn, &workspace, &subject, &predicate)?);
+            }
+            Ok(response)
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+/// List current structured facts.
+///
+/// Params:
+/// - `subject` (string, optional) — restrict to one subject
+/// - `workspace` (string, optional) — default: "default"
+/// - `limit` (i64, optional) — default: 100
+pub fn fact_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::fact_store::list_current_facts;
+
+    let subject = params.get("subject").and_then(|v| v.as_str());
+    let workspace = match fact_workspace(&params) {
+        Ok(workspace) => workspace,
+        Err(e) => return e,
+    };
+    let limit = params.get("limit").and_then(|v| v.as_i64()).unwrap_or(100);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let facts = list_current_facts(conn, &workspace, subject, limit)?;
+            Ok(json!({"facts": facts, "count": facts.len()}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+/// Retract the current value of a structured fact without replacing it.
+///
+/// Params:
+/// - `subject`, `predicate` (string, required)
+/// - `workspace` (string, optional) — default: "default"
+pub fn fact_retract(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::fact_store::retract_fact;
+
+    let (subject, predicate) = match subject_predicate(&params) {
+        Ok(sp) => sp,
+        Err(e) => return e,
+    };
+    let workspace = match fact_workspace(&params) {
+        Ok(workspace) => workspace,
+        Err(e) => return e,
+    };
+
+    ctx.storage
+        .with_transaction(|conn| retract_fact(conn, &workspace, &subject, &predicate))
+        .map(|retracted| json!({"retracted": retracted}))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+/// Extract facts from a memory into the structured store.
+///
+/// Params:
+/// - `memory_id` (i64, required) — source memory to extract from
+pub fn fact_extract(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::fact_store::store_facts_from_memory;
+
+    let memory_id = match params.get("memory_id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "memory_id is required"}),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| store_facts_from_memory(conn, memory_id))
+        .map(|outcomes| {
+            json!({
+                "memory_id": memory_id,
+                "count": outcomes.len(),
+                "facts": outcomes,
+            })
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 /// Build a structured prompt context from memories.
 ///
 /// Params:
@@ -165,11 +330,20 @@ pub fn memory_fact_graph(ctx: &HandlerContext, params: Value) -> Value {
 /// - `timeframe` (string, optional) — "1h"|"24h"|"7d"|"30d"|"all" (default: "all")
 /// - `include_types` (array of string, optional) — filter to these memory types
 /// - `include_graph` (bool, optional) — include relationship graph in response (default: false)
+/// - `show_superseded` (bool, optional) — include memories marked superseded (default: false)
+/// - `conflict_warnings` (bool, optional) — append warnings for memories known to
+///   contradict each other or a pinned decision (default: true)
+/// - `session_id` (string, optional) — session whose pinned memories count as decisions
+/// - `pinned_ids` (array of i64, optional) — further pinned decisions
 pub fn memory_build_context(ctx: &HandlerContext, params: Value) -> Value {
     use crate::intelligence::context_builder::{
         ContextBuilder, MemoryEntry, PromptTemplate, Section, SimpleTokenCounter, Strategy,
     };
-    use crate::search::hybrid_search;
+    use crate::intelligence::context_conflicts::{
+        find_context_conflicts, render_conflict_warnings,
+    };
+    use crate::intelligence::{get_session_memories, ContextRole};
+    use crate::search::hybrid_search_with_index;
     use crate::types::SearchOptions;
     use chrono::{Duration, Utc};
     use std::collections::HashSet;
@@ -219,6 +393,16 @@ pub fn memory_build_context(ctx: &HandlerContext, params: Value) -> Value {
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let show_superseded = params
+        .get("show_superseded")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let conflict_warnings = params
+        .get("conflict_warnings")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
     // Compute the timeframe cutoff
     let time_cutoff = match timeframe {
         "1h" => Some(Utc::now() - Duration::hours(1)),
@@ -234,19 +418,22 @@ pub fn memory_build_context(ctx: &HandlerContext, params: Value) -> Value {
             .and_then(|v| v.as_str())
             .map(str::to_string),
         limit: Some(limit as i64),
+        show_superseded,
         ..Default::default()
     };
 
-    let query_embedding = ctx.embedder.embed(&query).ok();
+    let query_embedding = ctx.embedder.embed_query(&query).ok();
     let embedding_ref = query_embedding.as_deref();
 
+    let search_config = ctx.effective_search_config();
     let search_result = ctx.storage.with_connection(|conn| {
-        hybrid_search(
+        hybrid_search_with_index(
             conn,
             &query,
             embedding_ref,
             &search_opts,
-            &ctx.search_config,
+            &search_config,
+            Some(&ctx.vector_index),
         )
     });
 
@@ -309,6 +496,17 @@ pub fn memory_build_context(ctx: &HandlerContext, params: Value) -> Value {
                 if let Ok(mem) = ctx.storage.with_connection(|conn| {
                     crate::storage::queries::get_memory(conn, *id)
                 }) {
+                    // Links must not pull superseded memories back in
+                    if !show_superseded
+                        && ctx
+                            .storage
+                            .with_connection(|conn| {
+                                crate::storage::supersession::is_superseded(conn, mem.id)
+                            })
+                            .unwrap_or(false)
+                    {
+                        continue;
+                    }
                     // Apply timeframe filter to expanded memories too
                     if let Some(cutoff) = time_cutoff {
                         if mem.created_at < cutoff {
@@ -348,7 +546,48 @@ pub fn memory_build_context(ctx: &HandlerContext, params: Value) -> Value {
     };
 
     let builder = ContextBuilder::new(Box::new(SimpleTokenCounter));
-    let prompt = builder.build(&template, &entries, strategy);
+    let mut prompt = builder.build(&template, &entries, strategy);
+
+    // Warn about memories in the prompt that are known to disagree, rather
+    // than handing the agent contradictory facts silently
+    let conflicts = if conflict_warnings {
+        let included: Vec<i64> = all_memory_ids
+            .iter()
+            .zip(&all_memory_contents)
+            .filter(|(_, (content, _))| prompt.contains(content.as_str()))
+            .map(|(id, _)| *id)
+            .collect();
+        let mut pinned: Vec<i64> = params
+            .get("pinned_ids")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_i64()).collect())
+            .unwrap_or_default();
+        let session_id = params.get("session_id").and_then(|v| v.as_str());
+        let found = ctx.storage.with_connection(|conn| {
+            if let Some(session_id) = session_id {
+                pinned.extend(
+                    get_session_memories(conn, session_id, Some(ContextRole::Pinned))?
+                        .iter()
+                        .map(|link| link.memory_id),
+                );
+            }
+            find_context_conflicts(conn, &included, &pinned)
+        });
+        match found {
+            Ok(conflicts) => conflicts,
+            Err(e) => return json!({"error": e.to_string()}),
+        }
+    } else {
+        Vec::new()
+    };
+    if !conflicts.is_empty() {
+        let warnings = render_conflict_warnings(&conflicts);
+        prompt = if prompt.is_empty() {
+            warnings
+        } else {
+            format!("{}{}{}", prompt, template.separator, warnings)
+        };
+    }
     let token_estimate = builder.estimate_tokens(&prompt);
 
     // Build graph data if requested
@@ -389,6 +628,13 @@ pub fn memory_build_context(ctx: &HandlerContext, params: Value) -> Value {
         "timeframe": timeframe
     });
 
+    if conflict_warnings {
+        response
+            .as_object_mut()
+            .expect("response is an object")
+            .insert("conflicts".to_string(), json!(conflicts));
+    }
+
     if include_graph {
         response
             .as_object_mut()
@@ -546,7 +792,7 @@ pub fn memory_block_archive(ctx: &HandlerContext, params: Value) -> Value {
 /// - `workspace` (string, optional) — workspace to search in
 /// - `include_types` (array of string, optional) — filter by memory type (e.g. ["note","episodic"])
 pub fn memory_get_injection_prompt(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::search::hybrid_search;
+    use crate::search::hybrid_search_with_index;
     use crate::types::SearchOptions;
 
     let query = match params.get("query").and_then(|v| v.as_str()) {
@@ -578,16 +824,18 @@ pub fn memory_get_injection_prompt(ctx: &HandlerContext, params: Value) -> Value
         ..Default::default()
     };
 
-    let query_embedding = ctx.embedder.embed(&query).ok();
+    let query_embedding = ctx.embedder.embed_query(&query).ok();
     let embedding_ref = query_embedding.as_deref();
 
+    let search_config = ctx.effective_search_config();
     let search_result = ctx.storage.with_connection(|conn| {
-        hybrid_search(
+        hybrid_search_with_index(
             conn,
             &query,
             embedding_ref,
             &search_opts,
-            &ctx.search_config,
+            &search_config,
+            Some(&ctx.vector_index),
         )
     });
 
@@ -1153,7 +1401,14 @@ mod context_tests {
             search_config: SearchConfig::default(),
             realtime: None,
             embedding_cache: Arc::new(EmbeddingCache::default()),
+            embedding_queue: None,
             search_cache: Arc::new(SearchResultCache::new(AdaptiveCacheConfig::default())),
+            memory_cache: Arc::new(crate::storage::MemoryCache::default()),
+            persona: Arc::new(crate::storage::ActivePersona::new()),
+            vector_index: Arc::new(crate::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: crate::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             #[cfg(feature = "meilisearch")]
             meili: None,
             #[cfg(feature = "meilisearch")]
@@ -1388,4 +1643,35 @@ mod context_tests {
     fn test_safe_truncate_zero() {
         assert_eq!(safe_truncate("hello", 0), "");
     }
+
+    #[test]
+    fn test_fact_tools_normalize_workspace() {
+        let ctx = test_ctx();
+        let put = super::fact_put(
+            &ctx,
+            serde_json::json!({
+                "subject": "api", "predicate": "uses", "object": "rest", "workspace": " Team-A "
+            }),
+        );
+        assert!(put.get("error").is_none(), "{}", put);
+
+        let got = super::fact_get(
+            &ctx,
+            serde_json::json!({"subject": "api", "predicate": "uses", "workspace": "team-a"}),
+        );
+        assert_eq!(got["found"], true, "{}", got);
+        assert_eq!(got["fact"]["workspace"], "team-a");
+
+        let listed = super::fact_list(&ctx, serde_json::json!({"workspace": "TEAM-A"}));
+        assert_eq!(listed["count"], 1, "{}", listed);
+
+        let retracted = super::fact_retract(
+            &ctx,
+            serde_json::json!({"subject": "api", "predicate": "uses", "workspace": "Team-A"}),
+        );
+        assert_eq!(retracted["retracted"], true, "{}", retracted);
+
+        let invalid = super::fact_list(&ctx, serde_json::json!({"workspace": "no spaces"}));
+        assert!(invalid["error"].is_string(), "{}", invalid);
+    }
 }
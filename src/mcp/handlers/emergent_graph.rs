@@ -263,7 +263,14 @@ mod tests {
             search_config: SearchConfig::default(),
             realtime: None,
             embedding_cache: Arc::new(EmbeddingCache::default()),
+            embedding_queue: None,
             search_cache: Arc::new(SearchResultCache::new(AdaptiveCacheConfig::default())),
+            memory_cache: Arc::new(crate::storage::MemoryCache::default()),
+            persona: Arc::new(crate::storage::ActivePersona::new()),
+            vector_index: Arc::new(crate::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: crate::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             #[cfg(feature = "meilisearch")]
             meili: None,
             #[cfg(feature = "meilisearch")]
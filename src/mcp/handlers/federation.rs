@@ -0,0 +1,82 @@
+//! Federated search tool handlers (search/list across attached databases).
+
+use serde_json::{json, Value};
+
+use super::HandlerContext;
+use crate::storage::Federation;
+use crate::types::{ListOptions, SearchOptions};
+
+fn federation(ctx: &HandlerContext) -> Result<&Federation, Value> {
+    ctx.federation.as_deref().ok_or_else(|| {
+        json!({"error": "Federation is not configured (set ENGRAM_FEDERATION_CONFIG or create ~/.config/engram/federation.json)"})
+    })
+}
+
+fn sources_param(params: &Value) -> Option<Vec<String>> {
+    params.get("sources").and_then(|v| v.as_array()).map(|arr| {
+        arr.iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect()
+    })
+}
+
+pub fn federated_search(ctx: &HandlerContext, params: Value) -> Value {
+    let federation = match federation(ctx) {
+        Ok(federation) => federation,
+        Err(e) => return e,
+    };
+    let query = match params.get("query").and_then(|v| v.as_str()) {
+        Some(q) if !q.trim().is_empty() => q.to_string(),
+        _ => return json!({"error": "query is required"}),
+    };
+    let options: SearchOptions = match serde_json::from_value(params.clone()) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+    let sources = sources_param(&params);
+
+    let query_embedding = ctx.embedder.embed_query(&query).ok();
+    let embedding = query_embedding
+        .as_deref()
+        .map(|vector| (vector, ctx.embedder.model_name()));
+
+    match federation.search(
+        &ctx.storage,
+        &query,
+        embedding,
+        &options,
+        &ctx.search_config,
+        sources.as_deref(),
+    ) {
+        Ok(results) => json!(results),
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn federated_list(ctx: &HandlerContext, params: Value) -> Value {
+    let federation = match federation(ctx) {
+        Ok(federation) => federation,
+        Err(e) => return e,
+    };
+    let options: ListOptions = match serde_json::from_value(params.clone()) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+    let sources = sources_param(&params);
+
+    match federation.list(&ctx.storage, &options, sources.as_deref()) {
+        Ok(results) => json!(results),
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn federation_sources(ctx: &HandlerContext, _params: Value) -> Value {
+    let federation = match federation(ctx) {
+        Ok(federation) => federation,
+        Err(e) => return e,
+    };
+    match federation.describe(&ctx.storage) {
+        Ok(sources) => json!({"sources": sources}),
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
@@ -1,8 +1,14 @@
 //! Graph and entity tool handlers.
 
+use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
 
-use crate::graph::KnowledgeGraph;
+use crate::graph::{
+    stream_graph, EntityNodeOptions, GraphLayout, GraphTimeline, KnowledgeGraph, LabelOptions,
+    LayoutConfig, RenderOptions, StreamFormat, StyleRegistry,
+};
+use crate::limits::{ResourceLimits, EXPORT_GRAPH_HINT};
+use crate::realtime::RealtimeEvent;
 use crate::storage::queries::*;
 use crate::types::*;
 
@@ -14,12 +20,22 @@ pub fn memory_link(ctx: &HandlerContext, params: Value) -> Value {
         Err(e) => return json!({"error": e.to_string()}),
     };
 
-    ctx.storage
-        .with_transaction(|conn| {
-            let crossref = create_crossref(conn, &input)?;
-            Ok(json!(crossref))
-        })
-        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+    match ctx
+        .storage
+        .with_transaction(|conn| create_crossref(conn, &input))
+    {
+        Ok(crossref) => {
+            if let Some(ref manager) = ctx.realtime {
+                manager.broadcast(RealtimeEvent::crossref_created(
+                    crossref.from_id,
+                    crossref.to_id,
+                    crossref.edge_type.as_str(),
+                ));
+            }
+            json!(crossref)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
 }
 
 pub fn memory_unlink(ctx: &HandlerContext, params: Value) -> Value {
@@ -31,12 +47,22 @@ pub fn memory_unlink(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or("related_to");
     let edge_type: EdgeType = edge_type_str.parse().unwrap_or_default();
 
-    ctx.storage
-        .with_transaction(|conn| {
-            delete_crossref(conn, from_id, to_id, edge_type)?;
-            Ok(json!({"unlinked": true}))
-        })
-        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+    match ctx
+        .storage
+        .with_transaction(|conn| delete_crossref(conn, from_id, to_id, edge_type))
+    {
+        Ok(()) => {
+            if let Some(ref manager) = ctx.realtime {
+                manager.broadcast(RealtimeEvent::crossref_deleted(
+                    from_id,
+                    to_id,
+                    edge_type.as_str(),
+                ));
+            }
+            json!({"unlinked": true})
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
 }
 
 pub fn memory_related(ctx: &HandlerContext, params: Value) -> Value {
@@ -145,6 +171,7 @@ pub fn memory_traverse(ctx: &HandlerContext, params: Value) -> Value {
         limit_per_hop,
         include_entities,
         direction,
+        max_nodes: ResourceLimits::from_env().max_nodes,
     };
 
     ctx.storage
@@ -156,7 +183,7 @@ pub fn memory_traverse(ctx: &HandlerContext, params: Value) -> Value {
 }
 
 pub fn find_path(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::storage::graph_queries::find_path;
+    use crate::storage::graph_queries::{find_path, find_weighted_path};
 
     let from_id = params.get("from_id").and_then(|v| v.as_i64()).unwrap_or(0);
     let to_id = params.get("to_id").and_then(|v| v.as_i64()).unwrap_or(0);
@@ -164,19 +191,36 @@ pub fn find_path(ctx: &HandlerContext, params: Value) -> Value {
         .get("max_depth")
         .and_then(|v| v.as_u64())
         .unwrap_or(5) as usize;
+    let weighted = params
+        .get("weighted")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
 
     ctx.storage
         .with_connection(|conn| {
-            let path = find_path(conn, from_id, to_id, max_depth)?;
+            let (path, cost) = if weighted {
+                match find_weighted_path(conn, from_id, to_id, max_depth)? {
+                    Some(found) => (Some(found.node), Some(found.cost)),
+                    None => (None, None),
+                }
+            } else {
+                (find_path(conn, from_id, to_id, max_depth)?, None)
+            };
             match path {
-                Some(node) => Ok(json!({
-                    "found": true,
-                    "path": node.path,
-                    "edge_path": node.edge_path,
-                    "depth": node.depth,
-                    "cumulative_score": node.cumulative_score,
-                    "connection_type": node.connection_type
-                })),
+                Some(node) => {
+                    let mut response = json!({
+                        "found": true,
+                        "path": node.path,
+                        "edge_path": node.edge_path,
+                        "depth": node.depth,
+                        "cumulative_score": node.cumulative_score,
+                        "connection_type": node.connection_type
+                    });
+                    if let Some(cost) = cost {
+                        response["cost"] = json!(cost);
+                    }
+                    Ok(response)
+                }
                 None => Ok(json!({
                     "found": false,
                     "from_id": from_id,
@@ -187,6 +231,241 @@ pub fn find_path(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn graph_query(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::query::{run_query, DEFAULT_QUERY_LIMIT};
+
+    let query = match params.get("query").and_then(|v| v.as_str()) {
+        Some(q) => q,
+        None => return json!({"error": "query is required"}),
+    };
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|l| (l as usize).clamp(1, 1000))
+        .unwrap_or(DEFAULT_QUERY_LIMIT);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let result = run_query(conn, query, workspace, limit)?;
+            Ok(json!({
+                "columns": result.columns,
+                "count": result.rows.len(),
+                "truncated": result.truncated,
+                "rows": result.rows,
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn similar_by_structure(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::{GraphBuilder, Node2VecConfig};
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(10)
+        .clamp(1, 100) as usize;
+    let max_nodes = params
+        .get("max_nodes")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1000);
+
+    let mut config = Node2VecConfig::default();
+    let count = |key: &str, default: usize| {
+        params
+            .get(key)
+            .and_then(|v| v.as_u64())
+            .map_or(default, |v| (v as usize).clamp(1, 256))
+    };
+    config.dimensions = count("dimensions", config.dimensions);
+    config.walk_length = count("walk_length", config.walk_length);
+    config.walks_per_node = count("walks_per_node", config.walks_per_node);
+    if let Some(p) = params.get("p").and_then(|v| v.as_f64()) {
+        config.p = p;
+    }
+    if let Some(q) = params.get("q").and_then(|v| v.as_f64()) {
+        config.q = q;
+    }
+    if let Some(seed) = params.get("seed").and_then(|v| v.as_u64()) {
+        config.seed = seed;
+    }
+
+    ctx.storage
+        .with_connection(|conn| {
+            let graph = GraphBuilder::load(conn, max_nodes, LabelOptions::default())?.snapshot();
+            let compact = graph.compact();
+            let Some(index) = compact.index_of(id) else {
+                return Ok(json!({
+                    "error": format!(
+                        "Memory {} is not among the {} most recent memories; raise max_nodes",
+                        id, max_nodes
+                    )
+                }));
+            };
+            if compact.neighbors(index).is_empty() {
+                return Ok(json!({
+                    "error": format!("Memory {} has no links, so it has no structural embedding", id)
+                }));
+            }
+
+            let embeddings = compact.node2vec(&config);
+            let similar: Vec<Value> = embeddings
+                .most_similar(id, limit)
+                .into_iter()
+                .filter_map(|(other, similarity)| {
+                    let node = &graph.nodes[compact.index_of(other)?];
+                    Some(json!({
+                        "id": other,
+                        "similarity": similarity,
+                        "label": node.label,
+                        "memory_type": node.memory_type,
+                    }))
+                })
+                .collect();
+            Ok(json!({
+                "id": id,
+                "similar": similar,
+                "node_count": compact.node_count(),
+                "embedded_count": embeddings.len(),
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn suggest_links(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::{GraphBuilder, LinkScore};
+
+    let id = params.get("id").and_then(|v| v.as_i64());
+    let method: LinkScore = match params.get("method").and_then(|v| v.as_str()) {
+        Some(method) => match method.parse() {
+            Ok(method) => method,
+            Err(e) => return json!({"error": e}),
+        },
+        None => LinkScore::default(),
+    };
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(10)
+        .clamp(1, 100) as usize;
+    let max_nodes = params
+        .get("max_nodes")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1000);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let graph = GraphBuilder::load(conn, max_nodes, LabelOptions::default())?.snapshot();
+            let compact = graph.compact();
+            let source = match id {
+                Some(id) => match compact.index_of(id) {
+                    Some(index) => Some(index),
+                    None => {
+                        return Ok(json!({
+                            "error": format!(
+                                "Memory {} is not among the {} most recent memories; raise max_nodes",
+                                id, max_nodes
+                            )
+                        }))
+                    }
+                },
+                None => None,
+            };
+
+            let label = |id: MemoryId| {
+                compact
+                    .index_of(id)
+                    .map(|i| graph.nodes[i].label.clone())
+                    .unwrap_or_default()
+            };
+            let suggestions: Vec<Value> = compact
+                .suggest_links(method, source, limit)
+                .into_iter()
+                .map(|s| {
+                    json!({
+                        "from_id": s.from,
+                        "to_id": s.to,
+                        "score": s.score,
+                        "common_neighbors": s.common_neighbors,
+                        "adamic_adar": s.adamic_adar,
+                        "jaccard": s.jaccard,
+                        "from_label": label(s.from),
+                        "to_label": label(s.to),
+                    })
+                })
+                .collect();
+            Ok(json!({
+                "method": method.as_str(),
+                "suggestions": suggestions,
+                "node_count": compact.node_count(),
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn detect_contradiction_cycles(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::GraphBuilder;
+
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(20)
+        .clamp(1, 200) as usize;
+    let max_nodes = params
+        .get("max_nodes")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1000);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let graph = GraphBuilder::load(conn, max_nodes, LabelOptions::default())?.snapshot();
+            let cycles = graph.contradiction_cycles();
+
+            let mut affected: Vec<i64> = cycles
+                .iter()
+                .flat_map(|c| c.memories.iter().copied())
+                .collect();
+            affected.sort_unstable();
+            affected.dedup();
+
+            let labels: std::collections::HashMap<i64, &str> = graph
+                .nodes
+                .iter()
+                .map(|n| (n.id, n.label.as_str()))
+                .collect();
+            let shown: Vec<Value> = cycles
+                .iter()
+                .take(limit)
+                .map(|c| {
+                    let memories: Vec<Value> = c
+                        .memories
+                        .iter()
+                        .map(|id| json!({"id": id, "label": labels.get(id)}))
+                        .collect();
+                    json!({
+                        "memories": memories,
+                        "edges": c.edges,
+                        "contradictions": c.contradictions,
+                        "suggestions": c.suggestions,
+                    })
+                })
+                .collect();
+
+            Ok(json!({
+                "cycles": shown,
+                "cycle_count": cycles.len(),
+                "affected_memories": affected,
+                "node_count": graph.nodes.len(),
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 pub fn export_graph(ctx: &HandlerContext, params: Value) -> Value {
     let format = params
         .get("format")
@@ -196,32 +475,325 @@ pub fn export_graph(ctx: &HandlerContext, params: Value) -> Value {
         .get("max_nodes")
         .and_then(|v| v.as_i64())
         .unwrap_or(500);
+    let mut labels = LabelOptions::default();
+    if let Some(length) = params.get("label_length").and_then(|v| v.as_u64()) {
+        labels.max_length = (length as usize).max(1);
+    }
+    if let Some(source) = params.get("label_source").and_then(|v| v.as_str()) {
+        match source.parse() {
+            Ok(source) => labels.source = source,
+            Err(e) => return json!({"error": e}),
+        }
+    }
+    let timestamp = |key: &str| -> std::result::Result<Option<DateTime<Utc>>, Value> {
+        match params.get(key).and_then(|v| v.as_str()) {
+            None => Ok(None),
+            Some(s) => DateTime::parse_from_rfc3339(s)
+                .map(|dt| Some(dt.with_timezone(&Utc)))
+                .map_err(|e| json!({"error": format!("Invalid {} format: {}", key, e)})),
+        }
+    };
+    let (as_of, from) = match (timestamp("as_of"), timestamp("from")) {
+        (Ok(as_of), Ok(from)) => (as_of, from),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    if let Some(path) = params.get("output_path").and_then(|v| v.as_str()) {
+        let bipartite = params.get("mode").and_then(|v| v.as_str()) == Some("bipartite");
+        if as_of.is_some() || bipartite || params.get("summarize").is_some() {
+            return json!({"error": "as_of, summarize and mode=bipartite can't be combined with output_path"});
+        }
+        let max_nodes = params
+            .get("max_nodes")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize);
+        return stream_export_graph(ctx, path, format, max_nodes, &labels);
+    }
+    let limits = ResourceLimits::from_env();
+    if let Err(e) = limits.check_nodes(max_nodes.max(0) as usize, EXPORT_GRAPH_HINT) {
+        return json!({"error": e.to_string()});
+    }
+    let entity_nodes = match params.get("mode").and_then(|v| v.as_str()) {
+        None | Some("memories") => None,
+        Some("bipartite") => Some(EntityNodeOptions {
+            identities: params
+                .get("include_identities")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            min_links: params
+                .get("min_entity_links")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1) as usize,
+            ..Default::default()
+        }),
+        Some(other) => {
+            return json!({"error": format!("Unknown mode '{}': expected memories or bipartite", other)})
+        }
+    };
 
     ctx.storage
         .with_connection(|conn| {
-            let options = ListOptions {
-                limit: Some(max_nodes),
-                ..Default::default()
-            };
-            let memories = list_memories(conn, &options)?;
+            if format == "timeline" {
+                use crate::graph::timeline::{earliest_memory_time, evenly_spaced};
+
+                let to = as_of.unwrap_or_else(Utc::now);
+                let from = match from {
+                    Some(from) => from,
+                    None => earliest_memory_time(conn)?.unwrap_or(to),
+                };
+                let steps = params
+                    .get("steps")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(10)
+                    .clamp(1, 100) as usize;
+                let timeline = GraphTimeline::build(
+                    conn,
+                    &evenly_spaced(from, to, steps),
+                    max_nodes,
+                    &labels,
+                )?;
+                let frames: Vec<Value> = timeline
+                    .frames
+                    .iter()
+                    .map(|f| {
+                        json!({
+                            "timestamp": f.timestamp,
+                            "node_count": f.nodes.len(),
+                            "edge_count": f.edges.len(),
+                        })
+                    })
+                    .collect();
+                return Ok(json!({"html": timeline.to_html(), "frames": frames}));
+            }
 
-            let mut all_crossrefs = Vec::new();
-            for memory in &memories {
-                if let Ok(refs) = get_related(conn, memory.id) {
-                    all_crossrefs.extend(refs);
+            let mut graph = match as_of {
+                Some(as_of) => KnowledgeGraph::at(conn, as_of, max_nodes, &labels)?,
+                None => {
+                    let options = ListOptions {
+                        limit: Some(max_nodes),
+                        ..Default::default()
+                    };
+                    let memories = list_memories(conn, &options)?;
+
+                    let mut all_crossrefs = Vec::new();
+                    for memory in &memories {
+                        if let Ok(refs) = get_related(conn, memory.id) {
+                            all_crossrefs.extend(refs);
+                        }
+                    }
+
+                    KnowledgeGraph::from_data_with_labels(&memories, &all_crossrefs, &labels)
+                }
+            };
+            if let Some(options) = &entity_nodes {
+                graph.add_entity_nodes(conn, options)?;
+            }
+            limits.check_nodes(graph.nodes.len(), EXPORT_GRAPH_HINT)?;
+            limits.check_edges(graph.edges.len(), EXPORT_GRAPH_HINT)?;
+
+            let styles = StyleRegistry::global();
+            if let Some(limit) = params.get("summarize").and_then(|v| v.as_u64()) {
+                if matches!(format, "html" | "json" | "svg" | "png") {
+                    let summary = graph.summarize(limit as usize);
+                    if format == "html" {
+                        return Ok(json!({"html": summary.to_html_with(styles)}));
+                    }
+                    if format != "json" {
+                        return render_static(&summary.graph, styles, None, format, &params);
+                    }
+                    let layout = params
+                        .get("precompute_layout")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false)
+                        .then(|| summary.graph.force_layout(&LayoutConfig::default()));
+                    return Ok(summary.to_visjs_json_with_layout(styles, layout.as_ref()));
                 }
             }
 
-            let graph = KnowledgeGraph::from_data(&memories, &all_crossrefs);
+            // Lay large graphs out here so the browser doesn't run physics
+            let layout = params
+                .get("precompute_layout")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+                .then(|| graph.force_layout(&LayoutConfig::default()));
+
+            let aggregate = params
+                .get("aggregate_parallel_edges")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            if matches!(format, "svg" | "png") {
+                let aggregated = aggregate.then(|| graph.aggregate_parallel_edges());
+                let shown = aggregated.as_ref().map_or(&graph, |a| &a.graph);
+                return render_static(shown, styles, layout.as_ref(), format, &params);
+            }
+            if aggregate && matches!(format, "html" | "json") {
+                let aggregated = graph.aggregate_parallel_edges();
+                if format == "json" {
+                    return Ok(aggregated.to_visjs_json_with_layout(styles, layout.as_ref()));
+                }
+                return Ok(
+                    json!({"html": aggregated.to_html_with_layout(styles, layout.as_ref())}),
+                );
+            }
 
             match format {
-                "json" => Ok(graph.to_visjs_json()),
-                _ => Ok(json!({"html": graph.to_html()})),
+                "json" => Ok(graph.to_visjs_json_with_layout(styles, layout.as_ref())),
+                "graphml" => Ok(json!({"graphml": graph.to_graphml()})),
+                "stats" => {
+                    let top = params.get("top").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+                    let compact = graph.compact();
+                    let central_nodes: Vec<Value> = compact
+                        .top_central_nodes(top)
+                        .into_iter()
+                        .map(|(id, scores)| json!({"id": id, "centrality": scores}))
+                        .collect();
+                    Ok(json!({"stats": compact.stats(), "central_nodes": central_nodes}))
+                }
+                _ => Ok(json!({"html": graph.to_html_with_layout(styles, layout.as_ref())})),
             }
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+/// `memory_export_graph` with `output_path`: stream the graph to a file
+/// without the in-memory graph limits
+fn stream_export_graph(
+    ctx: &HandlerContext,
+    path: &str,
+    format: &str,
+    max_nodes: Option<usize>,
+    labels: &LabelOptions,
+) -> Value {
+    let stream_format: StreamFormat = match format.parse() {
+        Ok(f) => f,
+        Err(e) => return json!({"error": e}),
+    };
+    let path = shellexpand::tilde(path).to_string();
+
+    ctx.storage
+        .with_connection(|conn| {
+            let file = std::fs::File::create(&path)?;
+            let streamed = stream_graph(
+                conn,
+                std::io::BufWriter::new(file),
+                stream_format,
+                max_nodes,
+                labels,
+            )?;
+            Ok(json!({
+                "output_path": path,
+                "format": format,
+                "node_count": streamed.node_count,
+                "edge_count": streamed.edge_count,
+                "bytes": std::fs::metadata(&path)?.len(),
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn export_neighborhood(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::GraphFilter;
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+    let depth = params
+        .get("depth")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(2)
+        .min(6) as usize;
+    let format = params
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("html");
+    if !matches!(format, "html" | "json" | "dot") {
+        return json!({"error": format!("Unknown format '{}': expected html, json or dot", format)});
+    }
+    let max_nodes = params
+        .get("max_nodes")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(500)
+        .max(1) as usize;
+    if let Err(e) = ResourceLimits::from_env().check_nodes(max_nodes, "Lower max_nodes or depth") {
+        return json!({"error": e.to_string()});
+    }
+    let filter: GraphFilter = match params.get("filter") {
+        Some(filter) => match serde_json::from_value(filter.clone()) {
+            Ok(filter) => filter,
+            Err(e) => return json!({"error": format!("Invalid filter: {}", e)}),
+        },
+        None => GraphFilter::default(),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let loaded =
+                KnowledgeGraph::around(conn, id, depth, max_nodes, &LabelOptions::default())?;
+            let graph = loaded.ego_graph(id, depth, &filter);
+            let styles = StyleRegistry::global();
+            Ok(match format {
+                "json" => graph.to_visjs_json_with(styles),
+                "dot" => json!({"dot": graph.to_dot_with(styles)}),
+                _ => json!({"html": graph.to_html_with(styles)}),
+            })
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn graph_explore(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::explore::{explore, ExploreRequest};
+
+    let request: ExploreRequest = match serde_json::from_value(params) {
+        Ok(request) => request,
+        Err(e) => return json!({"error": format!("Invalid explore request: {}", e)}),
+    };
+    ctx.storage
+        .with_connection(|conn| explore(conn, &request, &LabelOptions::default()))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+/// SVG or PNG (base64) image of `graph` for `memory_export_graph`
+fn render_static(
+    graph: &KnowledgeGraph,
+    styles: &StyleRegistry,
+    layout: Option<&GraphLayout>,
+    format: &str,
+    params: &Value,
+) -> crate::error::Result<Value> {
+    let mut options = RenderOptions::default();
+    if let Some(width) = params.get("width").and_then(|v| v.as_u64()) {
+        options.width = width.clamp(100, 8000) as u32;
+    }
+    if let Some(height) = params.get("height").and_then(|v| v.as_u64()) {
+        options.height = height.clamp(100, 8000) as u32;
+    }
+    if let Some(edge_labels) = params.get("edge_labels").and_then(|v| v.as_bool()) {
+        options.edge_labels = edge_labels;
+    }
+
+    if format == "svg" {
+        return Ok(json!({"svg": graph.to_svg(styles, layout, &options)}));
+    }
+    #[cfg(feature = "graph-png")]
+    {
+        use base64::Engine as _;
+
+        let png = graph.to_png(styles, layout, &options)?;
+        Ok(json!({
+            "png_base64": base64::engine::general_purpose::STANDARD.encode(png),
+            "mime_type": "image/png",
+            "width": options.width,
+            "height": options.height,
+        }))
+    }
+    #[cfg(not(feature = "graph-png"))]
+    {
+        Ok(json!({
+            "error": "PNG rendering requires the graph-png feature; use format 'svg' instead"
+        }))
+    }
+}
+
 pub fn extract_entities(ctx: &HandlerContext, params: Value) -> Value {
     use crate::intelligence::{EntityExtractionConfig, EntityExtractor};
     use crate::storage::{link_entity_to_memory, upsert_entity};
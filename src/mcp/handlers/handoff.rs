@@ -5,6 +5,8 @@
 
 use serde_json::{json, Value};
 
+use crate::intelligence::{auto_importance, ImportanceSignals, ImportanceSource};
+
 use super::HandlerContext;
 
 /// Land the plane: generate a structured session handoff.
@@ -141,6 +143,11 @@ pub fn session_land(ctx: &HandlerContext, params: Value) -> Value {
     let checkpoint_content =
         serde_json::to_string_pretty(&handoff).unwrap_or_else(|_| handoff.to_string());
 
+    let importance = auto_importance(&ImportanceSignals::new(
+        ImportanceSource::Handoff,
+        crate::types::MemoryType::Checkpoint,
+        &checkpoint_content,
+    ));
     let checkpoint_input = crate::types::CreateMemoryInput {
         content: checkpoint_content,
         memory_type: crate::types::MemoryType::Checkpoint,
@@ -149,7 +156,7 @@ pub fn session_land(ctx: &HandlerContext, params: Value) -> Value {
             format!("session:{}", session_id),
         ],
         workspace: Some(workspace.to_string()),
-        importance: Some(0.9),
+        importance: Some(importance),
         ..Default::default()
     };
 
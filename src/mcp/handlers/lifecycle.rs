@@ -370,3 +370,80 @@ pub fn retention_policy_apply(ctx: &HandlerContext, params: Value) -> Value {
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
+
+pub fn policy_simulate(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{simulate_policies, PolicySimulationOptions};
+
+    let options: PolicySimulationOptions = match serde_json::from_value(params) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let simulation = simulate_policies(conn, &options)?;
+            Ok(json!(simulation))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_supersede(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::supersession::mark_superseded;
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+    let superseded_by = params.get("superseded_by").and_then(|v| v.as_i64());
+    let reason = params.get("reason").and_then(|v| v.as_str());
+
+    let result = ctx
+        .storage
+        .with_transaction(|conn| mark_superseded(conn, id, superseded_by, reason));
+
+    match result {
+        Ok(record) => {
+            ctx.search_cache.invalidate_for_memory(id);
+            ctx.memory_cache.invalidate(id);
+            json!(record)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn memory_restore_superseded(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::supersession::restore_superseded;
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+
+    let result = ctx
+        .storage
+        .with_transaction(|conn| restore_superseded(conn, id));
+
+    match result {
+        Ok(restored) => {
+            // A restored memory may now belong in cached result sets.
+            ctx.search_cache.clear();
+            ctx.memory_cache.invalidate(id);
+            json!({"id": id, "restored": restored})
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn memory_list_superseded(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::supersession::list_superseded;
+
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let limit = params.get("limit").and_then(|v| v.as_i64()).unwrap_or(50);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let superseded = list_superseded(conn, workspace, limit)?;
+            Ok(json!({"superseded": superseded, "count": superseded.len()}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
@@ -357,7 +357,7 @@ fn parse_tags(tags_str: &str) -> Vec<String> {
 }
 
 /// Sanitize a string for use as a filename.
-fn sanitize_filename(s: &str) -> String {
+pub(super) fn sanitize_filename(s: &str) -> String {
     let cleaned: String = s
         .chars()
         .take(50)
@@ -378,7 +378,7 @@ fn sanitize_filename(s: &str) -> String {
 }
 
 /// Pluralize a memory type for directory naming.
-fn pluralize_type(mem_type: &str) -> String {
+pub(super) fn pluralize_type(mem_type: &str) -> String {
     match mem_type {
         "summary" => "summaries".to_string(),
         s if s.ends_with('s') => s.to_string(),
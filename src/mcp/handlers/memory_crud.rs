@@ -2,7 +2,8 @@
 
 use serde_json::{json, Value};
 
-use crate::realtime::RealtimeEvent;
+use crate::intelligence::{auto_importance, ImportanceSignals, ImportanceSource};
+use crate::realtime::{RealtimeEvent, RealtimeManager};
 use crate::storage::queries::*;
 use crate::types::*;
 
@@ -35,81 +36,143 @@ fn strip_private_content(content: &str) -> String {
     result
 }
 
+/// The error response for client-supplied tags breaking the tag policy
+fn tag_policy_error(ctx: &HandlerContext, tags: &[String]) -> Option<Value> {
+    ctx.storage
+        .with_connection(|conn| crate::storage::check_tag_policy(conn, tags))
+        .err()
+        .map(|e| json!({"error": e.to_string()}))
+}
+
 pub fn memory_create(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::storage::queries::find_similar_by_embedding;
+    use crate::intelligence::workspace_assignment::{
+        assign_workspace, auto_workspace_enabled, similarity_threshold, WORKSPACE_ASSIGNMENT_KEY,
+    };
+    use crate::storage::queries::{find_similar_by_embedding, find_similar_by_signature};
 
-    let input: CreateMemoryInput = match serde_json::from_value(params) {
+    let auto_workspace = params.get("auto_workspace").and_then(|v| v.as_bool());
+    let mut input: CreateMemoryInput = match serde_json::from_value(params) {
         Ok(i) => i,
         Err(e) => return json!({"error": e.to_string()}),
     };
+    if let Some(error) = tag_policy_error(ctx, &input.tags) {
+        return error;
+    }
+
+    // Heuristic workspace for memories created without one
+    if input.workspace.is_none() && auto_workspace_enabled(auto_workspace) {
+        let persona = ctx.persona.get();
+        let persona = persona
+            .as_ref()
+            .and_then(|p| Some((p.name.as_str(), p.default_workspace.as_deref()?)));
+        let assignment = ctx.storage.with_connection(|conn| {
+            assign_workspace(
+                conn,
+                &input.metadata,
+                persona,
+                || ctx.embedder.embed(&input.content).ok(),
+                similarity_threshold(),
+            )
+        });
+        match assignment {
+            Ok(Some(assignment)) => {
+                input.workspace = Some(assignment.workspace.clone());
+                input
+                    .metadata
+                    .insert(WORKSPACE_ASSIGNMENT_KEY.to_string(), json!(assignment));
+            }
+            Ok(None) => {}
+            Err(e) => return json!({"error": e.to_string()}),
+        }
+    }
+
+    // Workspace defaults may supply the dedup mode and threshold. create_memory
+    // applies them again, which is a no-op for defaults already filled in.
+    let config_workspace = input.workspace.as_deref().unwrap_or("default");
+    let workspace_config = ctx
+        .storage
+        .with_connection(|conn| crate::storage::get_workspace_config(conn, config_workspace));
+    if let Ok(Some(config)) = workspace_config {
+        config.apply(&mut input);
+    }
 
-    // Semantic deduplication
+    // Semantic deduplication. Near-identical text found through the signature
+    // index is a duplicate at any threshold, so embedding is only needed when
+    // that cheap check comes up empty.
     if input.dedup_mode != DedupMode::Allow {
         if let Some(threshold) = input.dedup_threshold {
-            if let Ok(query_embedding) = ctx.embedder.embed(&input.content) {
-                let workspace = input.workspace.as_deref();
-                let similar_result = ctx.storage.with_connection(|conn| {
-                    find_similar_by_embedding(
-                        conn,
-                        &query_embedding,
-                        &input.scope,
-                        workspace,
-                        threshold,
-                    )
-                });
-
-                if let Ok(Some((existing, similarity))) = similar_result {
-                    match input.dedup_mode {
-                        DedupMode::Reject => {
-                            return json!({
-                                "error": format!(
-                                    "Similar memory detected (id={}, similarity={:.3}). Use dedup_mode='allow' to create anyway.",
-                                    existing.id, similarity
-                                ),
-                                "existing_id": existing.id,
-                                "similarity": similarity
-                            });
-                        }
-                        DedupMode::Skip => {
-                            return json!(existing);
-                        }
-                        DedupMode::Merge => {
-                            let merge_result = ctx.storage.with_transaction(|conn| {
-                                let mut merged_tags = existing.tags.clone();
-                                for tag in &input.tags {
-                                    if !merged_tags.contains(tag) {
-                                        merged_tags.push(tag.clone());
-                                    }
+            let workspace = input.workspace.as_deref();
+            let similar_result = match ctx.storage.with_connection(|conn| {
+                find_similar_by_signature(conn, &input.content, &input.scope, workspace, threshold)
+            }) {
+                Ok(Some(found)) => Ok(Some(found)),
+                _ => match ctx.embedder.embed(&input.content) {
+                    Ok(query_embedding) => ctx.storage.with_connection(|conn| {
+                        find_similar_by_embedding(
+                            conn,
+                            &query_embedding,
+                            &input.scope,
+                            workspace,
+                            threshold,
+                        )
+                    }),
+                    Err(_) => Ok(None),
+                },
+            };
+            if let Ok(Some((existing, similarity))) = similar_result {
+                match input.dedup_mode {
+                    DedupMode::Reject => {
+                        return json!({
+                            "error": format!(
+                                "Similar memory detected (id={}, similarity={:.3}). Use dedup_mode='allow' to create anyway.",
+                                existing.id, similarity
+                            ),
+                            "existing_id": existing.id,
+                            "similarity": similarity
+                        });
+                    }
+                    DedupMode::Skip => {
+                        return json!(existing);
+                    }
+                    DedupMode::Merge => {
+                        let merge_result = ctx.storage.with_transaction(|conn| {
+                            let mut merged_tags = existing.tags.clone();
+                            for tag in &input.tags {
+                                if !merged_tags.contains(tag) {
+                                    merged_tags.push(tag.clone());
                                 }
+                            }
+
+                            let mut merged_metadata = existing.metadata.clone();
+                            for (key, value) in &input.metadata {
+                                merged_metadata.insert(key.clone(), value.clone());
+                            }
+
+                            let update_input = UpdateMemoryInput {
+                                content: None,
+                                memory_type: None,
+                                tags: Some(merged_tags),
+                                metadata: Some(merged_metadata),
+                                importance: input.importance,
+                                scope: None,
+                                ttl_seconds: input.ttl_seconds,
+                                event_time: None,
+                                trigger_pattern: None,
+                                media_url: input.media_url.map(Some),
+                            };
 
-                                let mut merged_metadata = existing.metadata.clone();
-                                for (key, value) in &input.metadata {
-                                    merged_metadata.insert(key.clone(), value.clone());
-                                }
+                            update_memory(conn, existing.id, &update_input)
+                        });
 
-                                let update_input = UpdateMemoryInput {
-                                    content: None,
-                                    memory_type: None,
-                                    tags: Some(merged_tags),
-                                    metadata: Some(merged_metadata),
-                                    importance: input.importance,
-                                    scope: None,
-                                    ttl_seconds: input.ttl_seconds,
-                                    event_time: None,
-                                    trigger_pattern: None,
-                                    media_url: input.media_url.map(Some),
-                                };
-
-                                update_memory(conn, existing.id, &update_input)
-                            });
-
-                            return match merge_result {
-                                Ok(memory) => json!(memory),
-                                Err(e) => json!({"error": e.to_string()}),
-                            };
-                        }
-                        DedupMode::Allow => {}
+                        return match merge_result {
+                            Ok(memory) => {
+                                ctx.memory_cache.refresh(&memory);
+                                json!(memory)
+                            }
+                            Err(e) => json!({"error": e.to_string()}),
+                        };
                     }
+                    DedupMode::Allow => {}
                 }
             }
         }
@@ -126,11 +189,12 @@ pub fn memory_create(ctx: &HandlerContext, params: Value) -> Value {
         Ok(memory) => {
             ctx.search_cache
                 .invalidate_for_workspace(Some(memory.workspace.as_str()));
+            ctx.submit_embedding(memory.id, &memory.content, EmbeddingPriority::High);
             if let Some(ref manager) = ctx.realtime {
-                manager.broadcast(RealtimeEvent::memory_created(
-                    memory.id,
-                    memory.content.clone(),
-                ));
+                manager.broadcast(
+                    RealtimeEvent::memory_created(memory.id, memory.content.clone())
+                        .in_workspace(memory.workspace.as_str()),
+                );
             }
             json!(memory)
         }
@@ -310,13 +374,8 @@ pub fn context_seed(ctx: &HandlerContext, params: Value) -> Value {
                 }
             }
 
-            for memory in &batch.created {
-                if let Some(ref manager) = ctx.realtime {
-                    manager.broadcast(RealtimeEvent::memory_created(
-                        memory.id,
-                        memory.content.clone(),
-                    ));
-                }
+            if let Some(ref manager) = ctx.realtime {
+                broadcast_created(manager, &batch.created);
             }
 
             json!({
@@ -337,9 +396,33 @@ pub fn memory_get(ctx: &HandlerContext, params: Value) -> Value {
         .get("strip_private")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let offset = params.get("offset").and_then(|v| v.as_u64());
+    let length = params.get("length").and_then(|v| v.as_u64());
+    if length == Some(0) {
+        // An empty page would never advance `next_offset`
+        return json!({"error": "length must be at least 1"});
+    }
+    if offset.is_some() || length.is_some() {
+        let offset = offset.unwrap_or(0) as usize;
+        let length = length.map(|l| l as usize);
+        return ctx
+            .storage
+            .with_connection(|conn| {
+                // Stripping shifts character positions, so ranges over the
+                // public view have to be cut from the stripped text.
+                if do_strip {
+                    let memory = ctx.memory_cache.get_or_load(conn, id)?;
+                    let public = strip_private_content(&memory.content);
+                    return Ok(ContentRange::from_content(id, &public, offset, length));
+                }
+                get_memory_content_range(conn, id, offset, length)
+            })
+            .map(|range| json!(range))
+            .unwrap_or_else(|e| json!({"error": e.to_string()}));
+    }
     ctx.storage
         .with_connection(|conn| {
-            let mut memory = get_memory(conn, id)?;
+            let mut memory = ctx.memory_cache.get_or_load(conn, id)?;
             if do_strip {
                 memory.content = strip_private_content(&memory.content);
             }
@@ -355,7 +438,7 @@ pub fn memory_get_public(ctx: &HandlerContext, params: Value) -> Value {
     let id = params.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
     ctx.storage
         .with_connection(|conn| {
-            let mut memory = get_memory(conn, id)?;
+            let mut memory = ctx.memory_cache.get_or_load(conn, id)?;
             memory.content = strip_private_content(&memory.content);
             Ok(json!(memory))
         })
@@ -368,6 +451,13 @@ pub fn memory_update(ctx: &HandlerContext, params: Value) -> Value {
         Ok(i) => i,
         Err(e) => return json!({"error": e.to_string()}),
     };
+    if let Some(error) = input
+        .tags
+        .as_deref()
+        .and_then(|tags| tag_policy_error(ctx, tags))
+    {
+        return error;
+    }
 
     let mut changes = Vec::new();
     if input.content.is_some() {
@@ -394,8 +484,15 @@ pub fn memory_update(ctx: &HandlerContext, params: Value) -> Value {
     match result {
         Ok(memory) => {
             ctx.search_cache.invalidate_for_memory(memory.id);
+            ctx.memory_cache.refresh(&memory);
+            if changes.iter().any(|c| c == "content") {
+                ctx.submit_embedding(memory.id, &memory.content, EmbeddingPriority::Normal);
+            }
             if let Some(ref manager) = ctx.realtime {
-                manager.broadcast(RealtimeEvent::memory_updated(memory.id, changes));
+                manager.broadcast(
+                    RealtimeEvent::memory_updated(memory.id, changes)
+                        .in_workspace(memory.workspace.as_str()),
+                );
             }
             json!(memory)
         }
@@ -414,6 +511,8 @@ pub fn memory_delete(ctx: &HandlerContext, params: Value) -> Value {
     match result {
         Ok(deleted_id) => {
             ctx.search_cache.invalidate_for_memory(deleted_id);
+            ctx.memory_cache.invalidate(deleted_id);
+            ctx.vector_index.remove(deleted_id);
             if let Some(ref manager) = ctx.realtime {
                 manager.broadcast(RealtimeEvent::memory_deleted(deleted_id));
             }
@@ -424,6 +523,19 @@ pub fn memory_delete(ctx: &HandlerContext, params: Value) -> Value {
 }
 
 pub fn memory_list(ctx: &HandlerContext, params: Value) -> Value {
+    // Reject malformed timestamps instead of silently dropping every filter.
+    for key in ["as_of", "event_after", "event_before"] {
+        if let Some(s) = params.get(key).and_then(|v| v.as_str()) {
+            if let Err(e) = chrono::DateTime::parse_from_rfc3339(s) {
+                return json!({"error": format!("Invalid {} format: {}", key, e)});
+            }
+        }
+    }
+    if let Some(s) = params.get("validation_status").and_then(|v| v.as_str()) {
+        if let Err(e) = s.parse::<ValidationStatus>() {
+            return json!({"error": e});
+        }
+    }
     let options: ListOptions = serde_json::from_value(params).unwrap_or_default();
     ctx.storage
         .with_connection(|conn| {
@@ -477,6 +589,10 @@ pub fn memory_create_daily(ctx: &HandlerContext, params: Value) -> Value {
         .and_then(|v| v.as_str())
         .map(String::from);
 
+    if let Some(error) = tag_policy_error(ctx, &tags) {
+        return error;
+    }
+
     let input = CreateMemoryInput {
         content,
         memory_type,
@@ -615,6 +731,10 @@ pub fn memory_create_episodic(ctx: &HandlerContext, params: Value) -> Value {
         .and_then(|v| v.as_str())
         .map(String::from);
 
+    if let Some(error) = tag_policy_error(ctx, &tags) {
+        return error;
+    }
+
     let input = CreateMemoryInput {
         content,
         memory_type: MemoryType::Episodic,
@@ -679,6 +799,10 @@ pub fn memory_create_procedural(ctx: &HandlerContext, params: Value) -> Value {
         .and_then(|v| v.as_str())
         .map(String::from);
 
+    if let Some(error) = tag_policy_error(ctx, &tags) {
+        return error;
+    }
+
     let input = CreateMemoryInput {
         content,
         memory_type: MemoryType::Procedural,
@@ -710,6 +834,11 @@ pub fn memory_create_procedural(ctx: &HandlerContext, params: Value) -> Value {
 pub fn memory_get_timeline(ctx: &HandlerContext, params: Value) -> Value {
     use crate::storage::queries::get_episodic_timeline;
 
+    // `when` fills in whichever of start_time and end_time are not given
+    let time_range = match super::resolve_when(ctx, &params) {
+        Ok(range) => range,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
     let start_time = params
         .get("start_time")
         .and_then(|v| v.as_str())
@@ -717,7 +846,8 @@ pub fn memory_get_timeline(ctx: &HandlerContext, params: Value) -> Value {
             chrono::DateTime::parse_from_rfc3339(s)
                 .ok()
                 .map(|dt| dt.with_timezone(&chrono::Utc))
-        });
+        })
+        .or_else(|| time_range.as_ref().and_then(|r| r.after));
     let end_time = params
         .get("end_time")
         .and_then(|v| v.as_str())
@@ -725,7 +855,8 @@ pub fn memory_get_timeline(ctx: &HandlerContext, params: Value) -> Value {
             chrono::DateTime::parse_from_rfc3339(s)
                 .ok()
                 .map(|dt| dt.with_timezone(&chrono::Utc))
-        });
+        })
+        .or_else(|| time_range.as_ref().and_then(|r| r.before));
     let workspace = params.get("workspace").and_then(|v| v.as_str());
     let tags: Option<Vec<String>> = params.get("tags").and_then(|v| v.as_array()).map(|arr| {
         arr.iter()
@@ -815,24 +946,113 @@ pub fn set_expiration(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn set_expiration_bulk(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::queries::{set_memory_expiration_bulk, ExpirationFilter};
+
+    let ttl_seconds = match params.get("ttl_seconds") {
+        Some(Value::Null) => None,
+        Some(v) => match v.as_i64() {
+            Some(ttl) => Some(ttl),
+            None => return json!({"error": "ttl_seconds must be an integer or null"}),
+        },
+        None => return json!({"error": "ttl_seconds is required (null removes expiration)"}),
+    };
+    let filter: ExpirationFilter = match params.get("filter").cloned() {
+        Some(filter) => match serde_json::from_value(filter) {
+            Ok(f) => f,
+            Err(e) => return json!({"error": format!("Invalid filter: {}", e)}),
+        },
+        None => return json!({"error": "filter is required"}),
+    };
+    let dry_run = params
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let result = ctx
+        .storage
+        .with_transaction(|conn| set_memory_expiration_bulk(conn, &filter, ttl_seconds, dry_run));
+    match result {
+        Ok(report) => {
+            if !report.dry_run {
+                for id in &report.memory_ids {
+                    ctx.memory_cache.invalidate(*id);
+                }
+            }
+            json!(report)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
 pub fn cleanup_expired(ctx: &HandlerContext, params: Value) -> Value {
     use crate::storage::queries::{cleanup_expired_memories, count_expired_memories};
 
-    let _ = params;
+    let grace_days = params
+        .get("grace_days")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(ctx.expired_grace_days)
+        .max(0);
 
     ctx.storage
         .with_transaction(|conn| {
-            let _count_before = count_expired_memories(conn)?;
-            let deleted = cleanup_expired_memories(conn)?;
+            let count_before = count_expired_memories(conn)?;
+            let deleted = cleanup_expired_memories(conn, grace_days)?;
             Ok(json!({
                 "success": true,
                 "deleted": deleted,
+                "in_grace": count_before.saturating_sub(deleted),
+                "grace_days": grace_days,
                 "message": format!("Cleaned up {} expired memories", deleted)
             }))
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn expired_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::queries::list_expired_memories;
+
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let limit = params.get("limit").and_then(|v| v.as_i64()).unwrap_or(50);
+    let grace_days = params
+        .get("grace_days")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(ctx.expired_grace_days)
+        .max(0);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let memories = list_expired_memories(conn, workspace, limit, grace_days)?;
+            Ok(json!({
+                "count": memories.len(),
+                "grace_days": grace_days,
+                "memories": memories
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn resurrect(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::queries::resurrect_memory;
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+    let ttl_seconds = params.get("ttl_seconds").and_then(|v| v.as_i64());
+
+    match ctx
+        .storage
+        .with_transaction(|conn| resurrect_memory(conn, id, ttl_seconds, ctx.expired_grace_days))
+    {
+        Ok(memory) => {
+            ctx.memory_cache.refresh(&memory);
+            json!(memory)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
 pub fn memory_create_batch(ctx: &HandlerContext, params: Value) -> Value {
     use crate::storage::create_memory_batch;
 
@@ -849,15 +1069,33 @@ pub fn memory_create_batch(ctx: &HandlerContext, params: Value) -> Value {
     if inputs.is_empty() {
         return json!({"error": "No valid memory inputs provided"});
     }
+    for (index, input) in inputs.iter().enumerate() {
+        if let Some(error) = tag_policy_error(ctx, &input.tags) {
+            return json!({"error": error["error"], "index": index});
+        }
+    }
 
     ctx.storage
         .with_connection(|conn| {
             let result = create_memory_batch(conn, &inputs)?;
+            if let Some(ref manager) = ctx.realtime {
+                broadcast_created(manager, &result.created);
+            }
             Ok(json!(result))
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+/// One created event per memory; the realtime manager coalesces bursts
+fn broadcast_created(manager: &RealtimeManager, memories: &[Memory]) {
+    for memory in memories {
+        manager.broadcast(
+            RealtimeEvent::memory_created(memory.id, memory.content.clone())
+                .in_workspace(memory.workspace.as_str()),
+        );
+    }
+}
+
 pub fn memory_delete_batch(ctx: &HandlerContext, params: Value) -> Value {
     use crate::storage::delete_memory_batch;
 
@@ -873,6 +1111,14 @@ pub fn memory_delete_batch(ctx: &HandlerContext, params: Value) -> Value {
     ctx.storage
         .with_connection(|conn| {
             let result = delete_memory_batch(conn, &ids)?;
+            for id in &ids {
+                ctx.memory_cache.invalidate(*id);
+            }
+            if let Some(ref manager) = ctx.realtime {
+                for &id in &result.deleted {
+                    manager.broadcast(RealtimeEvent::memory_deleted(id));
+                }
+            }
             Ok(json!(result))
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
@@ -921,13 +1167,10 @@ pub fn create_todo(ctx: &HandlerContext, params: Value) -> Value {
         metadata.insert("due_date".to_string(), due.clone());
     }
 
-    let importance: f32 = match priority {
-        "critical" => 1.0,
-        "high" => 0.8,
-        "medium" => 0.5,
-        "low" => 0.3,
-        _ => 0.5,
-    };
+    let importance = auto_importance(
+        &ImportanceSignals::new(ImportanceSource::Todo, MemoryType::Todo, content)
+            .with_priority(priority),
+    );
 
     let input = CreateMemoryInput {
         content: content.to_string(),
@@ -983,13 +1226,10 @@ pub fn create_issue(ctx: &HandlerContext, params: Value) -> Value {
     metadata.insert("severity".to_string(), json!(severity));
     metadata.insert("title".to_string(), json!(title));
 
-    let importance: f32 = match severity {
-        "critical" => 1.0,
-        "high" => 0.8,
-        "medium" => 0.5,
-        "low" => 0.3,
-        _ => 0.5,
-    };
+    let importance = auto_importance(
+        &ImportanceSignals::new(ImportanceSource::Issue, MemoryType::Issue, &content)
+            .with_priority(severity),
+    );
 
     let input = CreateMemoryInput {
         content,
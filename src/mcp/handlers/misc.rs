@@ -24,13 +24,41 @@ pub fn memory_tags(ctx: &HandlerContext, _params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
-pub fn memory_tag_hierarchy(ctx: &HandlerContext, _params: Value) -> Value {
+pub fn memory_tag_hierarchy(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::{KnowledgeGraph, StyleRegistry, TagTreeOptions};
     use crate::storage::get_tag_hierarchy;
 
+    let format = params
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("tree");
+    if !matches!(format, "tree" | "html" | "dot") {
+        return json!({"error": format!("Unknown format '{}': expected tree, html or dot", format)});
+    }
+    let options = TagTreeOptions {
+        max_depth: params
+            .get("max_depth")
+            .and_then(|v| v.as_u64())
+            .map(|d| d as usize),
+        min_count: params
+            .get("min_count")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0),
+    };
+    let link_template = params.get("link_template").and_then(|v| v.as_str());
+
     ctx.storage
         .with_connection(|conn| {
             let hierarchy = get_tag_hierarchy(conn)?;
-            Ok(json!({"hierarchy": hierarchy}))
+            if format == "tree" {
+                return Ok(json!({"hierarchy": hierarchy}));
+            }
+            let graph = KnowledgeGraph::from_tag_hierarchy_with(&hierarchy, &options);
+            let styles = StyleRegistry::global();
+            Ok(match format {
+                "dot" => json!({"dot": graph.to_tag_tree_dot(styles, link_template)}),
+                _ => json!({"html": graph.to_tag_tree_html(styles, link_template)}),
+            })
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
@@ -46,6 +74,168 @@ pub fn memory_validate_tags(ctx: &HandlerContext, _params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn tag_synonym_add(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::add_tag_synonyms;
+
+    let Some(canonical) = params.get("canonical").and_then(|v| v.as_str()) else {
+        return json!({"error": "canonical is required"});
+    };
+    let aliases: Vec<String> = params
+        .get("aliases")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    if aliases.is_empty() {
+        return json!({"error": "aliases must list at least one tag"});
+    }
+    let retag = params
+        .get("retag")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let result = ctx.storage.with_transaction(|conn| {
+        let (group, report) = add_tag_synonyms(conn, canonical, &aliases, retag)?;
+        Ok(json!({"group": group, "retagged": report}))
+    });
+    match result {
+        Ok(response) => {
+            // Tag filters now match the group, and memories may be retagged
+            ctx.search_cache.clear();
+            ctx.memory_cache.clear();
+            response
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn tag_synonym_remove(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::remove_tag_synonym;
+
+    let Some(alias) = params.get("alias").and_then(|v| v.as_str()) else {
+        return json!({"error": "alias is required"});
+    };
+
+    let result = ctx.storage.with_connection(|conn| {
+        let removed = remove_tag_synonym(conn, alias)?;
+        Ok(json!({"alias": alias, "removed": removed}))
+    });
+    match result {
+        Ok(response) => {
+            ctx.search_cache.clear();
+            response
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn tag_synonym_list(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::list_tag_synonyms;
+
+    ctx.storage
+        .with_connection(|conn| {
+            let groups = list_tag_synonyms(conn)?;
+            Ok(json!({"groups": groups}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn tag_synonym_suggest(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{suggest_tag_synonyms, TagSynonymSuggestOptions};
+
+    let options: TagSynonymSuggestOptions = match serde_json::from_value(params) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let suggestions = suggest_tag_synonyms(conn, &options)?;
+            Ok(json!({"count": suggestions.len(), "suggestions": suggestions}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn tag_normalization_get(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::get_tag_normalization;
+
+    ctx.storage
+        .with_connection(|conn| {
+            let rules = get_tag_normalization(conn)?;
+            Ok(json!({"rules": rules}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn tag_normalization_set(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{set_tag_normalization, TagNormalization};
+
+    let rules: TagNormalization = match serde_json::from_value(params.clone()) {
+        Ok(rules) => rules,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+    let apply_existing = params
+        .get("apply_existing")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let result = ctx.storage.with_transaction(|conn| {
+        let report = set_tag_normalization(conn, &rules, apply_existing)?;
+        Ok(json!({"rules": rules, "rewritten": report}))
+    });
+    match result {
+        Ok(response) => {
+            if apply_existing {
+                ctx.search_cache.clear();
+                ctx.memory_cache.clear();
+            }
+            response
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn tag_policy_get(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::get_tag_policy;
+
+    ctx.storage
+        .with_connection(|conn| {
+            let policy = get_tag_policy(conn)?;
+            Ok(json!({"policy": policy}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn tag_policy_set(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{set_tag_policy, TagPolicy};
+
+    let policy: TagPolicy = match serde_json::from_value(params) {
+        Ok(policy) => policy,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            set_tag_policy(conn, &policy)?;
+            Ok(json!({"policy": policy}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn tag_policy_report(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{tag_policy_report, TagPolicyReportOptions};
+
+    let options: TagPolicyReportOptions = match serde_json::from_value(params) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let report = tag_policy_report(conn, &options)?;
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 // ── Import / Export ───────────────────────────────────────────────────────────
 
 pub fn memory_export(ctx: &HandlerContext, _params: Value) -> Value {
@@ -85,17 +275,266 @@ pub fn memory_import(ctx: &HandlerContext, params: Value) -> Value {
 
 // ── Maintenance ───────────────────────────────────────────────────────────────
 
-pub fn memory_rebuild_embeddings(ctx: &HandlerContext, _params: Value) -> Value {
+pub fn memory_rebuild_embeddings(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::{run_embedding_rebuild, RebuildOptions};
     use crate::storage::rebuild_embeddings;
 
+    let parallel = params
+        .get("parallel")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let resume_task = params
+        .get("resume_task_id")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    // Resuming keeps the queue as the previous run left it.
+    let queued = if resume_task.is_some() {
+        None
+    } else {
+        match ctx.storage.with_connection(rebuild_embeddings) {
+            Ok(count) => Some(count),
+            Err(e) => return json!({"error": e.to_string()}),
+        }
+    };
+    if !parallel && resume_task.is_none() {
+        return json!({"rebuilt": queued});
+    }
+
+    let defaults = RebuildOptions::default();
+    let options = RebuildOptions {
+        concurrency: params
+            .get("concurrency")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(defaults.concurrency),
+        batch_size: params
+            .get("batch_size")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(defaults.batch_size),
+        requests_per_minute: params
+            .get("requests_per_minute")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as u32),
+    };
+    if options.concurrency == 0 || options.batch_size == 0 {
+        return json!({"error": "concurrency and batch_size must be greater than 0"});
+    }
+
+    let task_id = resume_task.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let storage = ctx.storage.clone();
+    let embedder = ctx.embedder.clone();
+    let vector_index = ctx.vector_index.clone();
+    // Batches run on the blocking pool; the async workers only coordinate
+    let worker_threads = options.concurrency.min(
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+    );
+    let background_task_id = task_id.clone();
+
+    // Run on a dedicated runtime: embedders block on async HTTP calls, which
+    // needs a multi-threaded runtime regardless of the caller's context.
+    let spawned = std::thread::Builder::new()
+        .name("embedding-rebuild".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(worker_threads)
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(e) => {
+                    tracing::error!("Failed to start embedding rebuild runtime: {}", e);
+                    return;
+                }
+            };
+            let result = runtime.block_on(run_embedding_rebuild(
+                storage,
+                embedder,
+                options,
+                background_task_id,
+                Some(vector_index),
+            ));
+            if let Err(e) = result {
+                tracing::error!("Embedding rebuild failed: {}", e);
+            }
+        });
+    if let Err(e) = spawned {
+        return json!({"error": format!("Failed to start embedding rebuild: {}", e)});
+    }
+
+    json!({
+        "task_id": task_id,
+        "status": "running",
+        "queued": queued,
+    })
+}
+
+pub fn memory_rebuild_embeddings_status(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::rebuild_status;
+
+    let task_id = match params.get("task_id").and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => return json!({"error": "task_id is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| rebuild_status(conn, &task_id))
+        .map(|task| match task {
+            Some(task) => json!(task),
+            None => json!({"error": format!("Rebuild task not found: {}", task_id)}),
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_migrate_embeddings(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::{
+        cancel_embedding_migration, pause_embedding_migration, resume_embedding_migration,
+        spawn_embedding_migration, start_embedding_migration, MigrationOptions,
+    };
+
+    let action = params
+        .get("action")
+        .and_then(|v| v.as_str())
+        .unwrap_or("start");
+    let id = params.get("id").and_then(|v| v.as_str());
+
+    let migration = match (action, id) {
+        ("start", _) => {
+            let defaults = MigrationOptions::default();
+            let options = MigrationOptions {
+                batch_size: params
+                    .get("batch_size")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(defaults.batch_size),
+                requests_per_minute: params
+                    .get("requests_per_minute")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32),
+            };
+            ctx.storage.with_transaction(|conn| {
+                start_embedding_migration(conn, ctx.embedder.as_ref(), &options)
+            })
+        }
+        ("resume", Some(id)) => ctx
+            .storage
+            .with_transaction(|conn| resume_embedding_migration(conn, id)),
+        ("pause", Some(id)) => ctx
+            .storage
+            .with_transaction(|conn| pause_embedding_migration(conn, id)),
+        ("cancel", Some(id)) => ctx
+            .storage
+            .with_transaction(|conn| cancel_embedding_migration(conn, id)),
+        ("resume" | "pause" | "cancel", None) => {
+            return json!({"error": format!("id is required to {} a migration", action)})
+        }
+        _ => {
+            return json!({"error": format!(
+                "Unknown action: {} (expected start, resume, pause or cancel)",
+                action
+            )})
+        }
+    };
+    let migration = match migration {
+        Ok(m) => m,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+
+    if matches!(action, "start" | "resume") {
+        if let Err(e) = spawn_embedding_migration(
+            ctx.storage.clone(),
+            ctx.embedder.clone(),
+            migration.id.clone(),
+            Some(ctx.vector_index.clone()),
+        ) {
+            return json!({"error": format!("Failed to start embedding migration: {}", e)});
+        }
+    }
+    json!(migration)
+}
+
+/// Most recent dead-lettered embeddings listed by `memory_embedding_status`
+const DEAD_LETTER_LIMIT: i64 = 50;
+
+pub fn memory_embedding_status(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::{
+        active_embedding_migration, embedding_model_counts, get_embedding_migration,
+        get_embedding_status, list_embedding_adapters, list_embedding_dead_letters,
+    };
+
+    if let Some(id) = params.get("id").and_then(|v| v.as_i64()) {
+        return ctx
+            .storage
+            .with_connection(|conn| {
+                let status = get_embedding_status(conn, id)?;
+                let stored: Option<(String, i64)> = conn
+                    .query_row(
+                        "SELECT model, dimensions FROM embeddings WHERE memory_id = ?",
+                        rusqlite::params![id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .ok();
+                let mut value = json!(status);
+                if let Some((model, dimensions)) = stored {
+                    value["model"] = json!(model);
+                    value["dimensions"] = json!(dimensions);
+                }
+                Ok(value)
+            })
+            .unwrap_or_else(|e| json!({"error": e.to_string()}));
+    }
+
+    let migration_id = params.get("migration_id").and_then(|v| v.as_str());
     ctx.storage
         .with_connection(|conn| {
-            let count = rebuild_embeddings(conn)?;
-            Ok(json!({"rebuilt": count}))
+            let models: Vec<Value> = embedding_model_counts(conn)?
+                .into_iter()
+                .map(|(model, dimensions, count)| {
+                    json!({"model": model, "dimensions": dimensions, "count": count})
+                })
+                .collect();
+            let queue: serde_json::Map<String, Value> = conn
+                .prepare("SELECT status, COUNT(*) FROM embedding_queue GROUP BY status")?
+                .query_map([], |row| Ok((row.get(0)?, json!(row.get::<_, i64>(1)?))))?
+                .collect::<rusqlite::Result<_>>()?;
+            let migration = match migration_id {
+                Some(id) => get_embedding_migration(conn, id)?,
+                None => active_embedding_migration(conn)?,
+            };
+            let dead_letters = list_embedding_dead_letters(conn, DEAD_LETTER_LIMIT)?;
+            let adapters = list_embedding_adapters(conn)?;
+            Ok(json!({
+                "model": ctx.embedder.model_name(),
+                "dimensions": ctx.embedder.dimensions(),
+                "stored": models,
+                "queue": queue,
+                "dead_letters": dead_letters,
+                "migration": migration,
+                "adapters": adapters,
+            }))
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn sync_task_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::queries::list_sync_tasks;
+
+    let task_type = params.get("task_type").and_then(|v| v.as_str());
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(20)
+        .clamp(1, 200);
+
+    ctx.storage
+        .with_connection(|conn| list_sync_tasks(conn, task_type, limit))
+        .map(|tasks| json!({"count": tasks.len(), "tasks": tasks}))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 pub fn memory_rebuild_crossrefs(ctx: &HandlerContext, _params: Value) -> Value {
     use crate::storage::rebuild_crossrefs;
 
@@ -107,6 +546,136 @@ pub fn memory_rebuild_crossrefs(ctx: &HandlerContext, _params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn memory_rebuild_adjacency(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::rebuild_adjacency;
+
+    ctx.storage
+        .with_transaction(|conn| Ok(json!(rebuild_adjacency(conn)?)))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_rebuild_signatures(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::rebuild_text_signatures;
+
+    ctx.storage
+        .with_transaction(|conn| Ok(json!(rebuild_text_signatures(conn)?)))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_rebuild_vocabulary(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::rebuild_tfidf_vocabulary;
+
+    ctx.storage
+        .with_transaction(|conn| Ok(json!(rebuild_tfidf_vocabulary(conn)?)))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_vector_index_rebuild(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::vector_index::VectorIndexKind;
+
+    let kind = match params.get("backend").and_then(|v| v.as_str()) {
+        Some(s) => match s.parse::<VectorIndexKind>() {
+            Ok(kind) => Some(kind),
+            Err(e) => return json!({"error": e}),
+        },
+        None => None,
+    };
+
+    ctx.storage
+        .with_connection(|conn| Ok(json!(ctx.vector_index.rebuild(conn, kind)?)))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_vector_index_stats(ctx: &HandlerContext, _params: Value) -> Value {
+    match ctx.vector_index.stats() {
+        Some(stats) => json!(stats),
+        None => json!({
+            "built": false,
+            "backend": ctx.vector_index.config().kind,
+            "hint": "Run memory_vector_index_rebuild to build the index",
+        }),
+    }
+}
+
+pub fn memory_reduce_embeddings(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::reduction::reduce_stored_embeddings;
+
+    let Some(handle) = ctx.embedder.reduction() else {
+        return json!({
+            "error": "Embedding reduction is not configured; set ENGRAM_EMBEDDING_REDUCTION (e.g. pca:256 or truncate:256)"
+        });
+    };
+    let fit_sample = params
+        .get("fit_sample")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(5000) as usize;
+    let keep_originals = params
+        .get("keep_originals")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let seed = params.get("seed").and_then(|v| v.as_u64()).unwrap_or(42);
+
+    let result = ctx.storage.with_transaction(|conn| {
+        reduce_stored_embeddings(conn, handle, fit_sample, keep_originals, seed)
+    });
+    match result {
+        Ok(report) => {
+            ctx.embedding_cache.clear();
+            ctx.search_cache.clear();
+            json!(report)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn memory_restore_embeddings(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::embedding::reduction::restore_original_embeddings;
+
+    let result = ctx
+        .storage
+        .with_transaction(|conn| restore_original_embeddings(conn, ctx.embedder.reduction()));
+    match result {
+        Ok(report) => {
+            ctx.embedding_cache.clear();
+            ctx.search_cache.clear();
+            json!(report)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn memory_fit_embedding_adapter(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::{fit_embedding_adapter, AdapterOptions};
+
+    let defaults = AdapterOptions::default();
+    let options = AdapterOptions {
+        source_model: params
+            .get("source_model")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        sample_size: params
+            .get("sample_size")
+            .and_then(|v| v.as_u64())
+            .map_or(defaults.sample_size, |v| v as usize),
+        ridge: params
+            .get("ridge")
+            .and_then(|v| v.as_f64())
+            .unwrap_or(defaults.ridge),
+        seed: params
+            .get("seed")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.seed),
+    };
+
+    match fit_embedding_adapter(&ctx.storage, ctx.embedder.as_ref(), &options) {
+        Ok(info) => {
+            ctx.search_cache.clear();
+            json!(info)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
 // ── Image Handling ────────────────────────────────────────────────────────────
 
 pub fn memory_upload_image(ctx: &HandlerContext, params: Value) -> Value {
@@ -346,6 +915,7 @@ pub fn memory_auto_tag(ctx: &HandlerContext, params: Value) -> Value {
         .with_transaction(|conn| update_memory(conn, id, &update_input))
     {
         Ok(updated_memory) => {
+            ctx.memory_cache.refresh(&updated_memory);
             json!({
                 "memory_id": id,
                 "suggestions": suggestions.suggestions,
@@ -424,7 +994,7 @@ pub fn langfuse_connect(ctx: &HandlerContext, params: Value) -> Value {
 #[cfg(feature = "langfuse")]
 pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
     use crate::integrations::langfuse::{LangfuseClient, LangfuseConfig};
-    use crate::storage::queries::{upsert_sync_task, SyncTask};
+    use crate::storage::queries::{get_trace_memory, upsert_sync_task, SyncTask};
     use chrono::{Duration, Utc};
 
     let config = match LangfuseConfig::from_env() {
@@ -445,10 +1015,25 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
 
     let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize;
 
-    let workspace = params
+    let workspace = match params
         .get("workspace")
         .and_then(|v| v.as_str())
-        .map(|s| s.to_string());
+        .map(crate::types::normalize_workspace)
+        .transpose()
+    {
+        Ok(workspace) => workspace,
+        Err(e) => return json!({"error": format!("Invalid workspace: {}", e)}),
+    };
+
+    let on_existing = params
+        .get("on_existing")
+        .and_then(|v| v.as_str())
+        .unwrap_or("update");
+    if !matches!(on_existing, "update" | "skip") {
+        return json!({
+            "error": format!("Unknown on_existing '{}', expected 'update' or 'skip'", on_existing)
+        });
+    }
 
     let dry_run = params
         .get("dry_run")
@@ -468,6 +1053,9 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
         error_message: None,
         started_at: started_at.clone(),
         completed_at: None,
+        items_total: 0,
+        items_processed: 0,
+        eta_seconds: None,
     };
 
     if let Err(e) = ctx
@@ -483,18 +1071,26 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
         .langfuse_runtime
         .block_on(async { client.fetch_traces(since, limit).await });
 
+    let workspace_key = workspace.as_deref().unwrap_or("default");
+
     match result {
         Ok(traces) => {
             if dry_run {
                 let trace_summaries: Vec<_> = traces
                     .iter()
                     .map(|t| {
+                        let imported = ctx
+                            .storage
+                            .with_connection(|conn| get_trace_memory(conn, &t.id, workspace_key))
+                            .ok()
+                            .flatten();
                         json!({
                             "id": t.id,
                             "name": t.name,
                             "timestamp": t.timestamp.to_rfc3339(),
                             "user_id": t.user_id,
-                            "tags": t.tags
+                            "tags": t.tags,
+                            "memory_id": imported.map(|link| link.memory_id)
                         })
                     })
                     .collect();
@@ -509,6 +1105,9 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
                     error_message: None,
                     started_at,
                     completed_at: Some(Utc::now().to_rfc3339()),
+                    items_total: 0,
+                    items_processed: 0,
+                    eta_seconds: None,
                 };
                 let _ = ctx
                     .storage
@@ -522,35 +1121,28 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
                 });
             }
 
-            use crate::integrations::langfuse::trace_to_memory_content;
-            use crate::storage::queries::create_memory;
-            use crate::types::{CreateMemoryInput, MemoryType};
+            use crate::intelligence::ImportanceSource;
 
             let mut memories_created = 0i64;
+            let mut memories_updated = 0i64;
+            let mut traces_skipped = 0i64;
             let mut errors: Vec<String> = Vec::new();
 
             for trace in &traces {
-                let content = trace_to_memory_content(trace, &[]);
-
-                let input = CreateMemoryInput {
-                    content,
-                    memory_type: MemoryType::Episodic,
-                    importance: Some(0.5),
-                    tags: {
-                        let mut tags = trace.tags.clone();
-                        tags.push("langfuse".to_string());
-                        tags
-                    },
-                    workspace: workspace.clone(),
-                    event_time: Some(trace.timestamp),
-                    ..Default::default()
-                };
-
-                match ctx
-                    .storage
-                    .with_connection(|conn| create_memory(conn, &input))
-                {
-                    Ok(_) => memories_created += 1,
+                let input = trace_memory_input(
+                    trace,
+                    ImportanceSource::LangfuseSync,
+                    None,
+                    workspace.clone(),
+                    Vec::new(),
+                );
+
+                match ctx.storage.with_transaction(|conn| {
+                    import_trace_memory(conn, &trace.id, workspace_key, &input, on_existing)
+                }) {
+                    Ok(TraceImport::Created) => memories_created += 1,
+                    Ok(TraceImport::Updated) => memories_updated += 1,
+                    Ok(TraceImport::Skipped) => traces_skipped += 1,
                     Err(e) => errors.push(format!("Trace {}: {}", trace.id, e)),
                 }
             }
@@ -573,6 +1165,9 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
                 },
                 started_at,
                 completed_at: Some(Utc::now().to_rfc3339()),
+                items_total: 0,
+                items_processed: 0,
+                eta_seconds: None,
             };
             let _ = ctx
                 .storage
@@ -583,6 +1178,8 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
                 "status": final_task.status,
                 "traces_processed": traces.len(),
                 "memories_created": memories_created,
+                "memories_updated": memories_updated,
+                "traces_skipped": traces_skipped,
                 "errors": errors
             })
         }
@@ -597,6 +1194,9 @@ pub fn langfuse_sync(ctx: &HandlerContext, params: Value) -> Value {
                 error_message: Some(e.to_string()),
                 started_at,
                 completed_at: Some(Utc::now().to_rfc3339()),
+                items_total: 0,
+                items_processed: 0,
+                eta_seconds: None,
             };
             let _ = ctx
                 .storage
@@ -682,27 +1282,26 @@ pub fn langfuse_extract_patterns(ctx: &HandlerContext, params: Value) -> Value {
 
 #[cfg(feature = "langfuse")]
 pub fn memory_from_trace(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::integrations::langfuse::{trace_to_memory_content, LangfuseClient, LangfuseConfig};
-    use crate::storage::queries::create_memory;
-    use crate::types::{CreateMemoryInput, MemoryType};
+    use crate::integrations::langfuse::{LangfuseClient, LangfuseConfig, SCORE_KEY};
+    use crate::intelligence::ImportanceSource;
+    use crate::storage::queries::{create_memory, get_trace_memory, upsert_trace_memory};
+    use crate::types::MemoryType;
 
     let trace_id = match params.get("trace_id").and_then(|v| v.as_str()) {
         Some(id) => id,
         None => return json!({"error": "trace_id is required"}),
     };
 
-    let memory_type_str = params
+    // Without an explicit type, a low-scored trace becomes a learning
+    let memory_type = params
         .get("memory_type")
         .and_then(|v| v.as_str())
-        .unwrap_or("episodic");
-
-    let memory_type = match memory_type_str {
-        "note" => MemoryType::Note,
-        "episodic" => MemoryType::Episodic,
-        "procedural" => MemoryType::Procedural,
-        "learning" => MemoryType::Learning,
-        _ => MemoryType::Episodic,
-    };
+        .map(|t| match t {
+            "note" => MemoryType::Note,
+            "procedural" => MemoryType::Procedural,
+            "learning" => MemoryType::Learning,
+            _ => MemoryType::Episodic,
+        });
 
     let workspace = params
         .get("workspace")
@@ -731,30 +1330,35 @@ pub fn memory_from_trace(ctx: &HandlerContext, params: Value) -> Value {
 
     match trace_result {
         Ok(Some(trace)) => {
-            let content = trace_to_memory_content(&trace, &[]);
-
-            let mut tags = trace.tags.clone();
-            tags.push("langfuse".to_string());
-            tags.push(format!("trace:{}", trace_id));
+            let mut tags = vec![format!("trace:{}", trace_id)];
             tags.extend(extra_tags);
-
-            let input = CreateMemoryInput {
-                content,
+            let input = trace_memory_input(
+                &trace,
+                ImportanceSource::LangfuseImport,
                 memory_type,
-                importance: Some(0.6),
-                tags,
                 workspace,
-                event_time: Some(trace.timestamp),
-                ..Default::default()
-            };
+                tags,
+            );
 
             ctx.storage
-                .with_connection(|conn| {
+                .with_transaction(|conn| {
                     let memory = create_memory(conn, &input)?;
+                    // Let later syncs of the trace find this memory
+                    if get_trace_memory(conn, trace_id, &memory.workspace)?.is_none() {
+                        upsert_trace_memory(
+                            conn,
+                            trace_id,
+                            &memory.workspace,
+                            memory.id,
+                            &trace_fingerprint(&input),
+                        )?;
+                    }
                     Ok(json!({
                         "id": memory.id,
                         "trace_id": trace_id,
-                        "memory_type": memory_type_str,
+                        "memory_type": memory.memory_type.as_str(),
+                        "importance": memory.importance,
+                        "score": memory.metadata.get(SCORE_KEY),
                         "content_length": memory.content.len()
                     }))
                 })
@@ -765,6 +1369,477 @@ pub fn memory_from_trace(ctx: &HandlerContext, params: Value) -> Value {
     }
 }
 
+/// Memory input for a trace, with importance, type and tags shaped by its
+/// scores; `memory_type` overrides the score-derived type
+#[cfg(feature = "langfuse")]
+fn trace_memory_input(
+    trace: &crate::integrations::langfuse::Trace,
+    source: crate::intelligence::ImportanceSource,
+    memory_type: Option<crate::types::MemoryType>,
+    workspace: Option<String>,
+    extra_tags: Vec<String>,
+) -> crate::types::CreateMemoryInput {
+    use crate::integrations::langfuse::{score_trace, trace_to_memory_content, TRACE_ID_KEY};
+    use crate::intelligence::{ImportancePolicy, ImportanceSignals};
+    use crate::types::{CreateMemoryInput, MemoryType};
+
+    let policy = ImportancePolicy::global();
+    let scoring = score_trace(trace, policy);
+    let memory_type = memory_type
+        .or_else(|| scoring.as_ref().and_then(|s| s.memory_type))
+        .unwrap_or(MemoryType::Episodic);
+    let content = trace_to_memory_content(trace, &[]);
+
+    let mut signals = ImportanceSignals::new(source, memory_type, &content);
+    if let Some(scoring) = &scoring {
+        signals = signals.with_score(scoring.score);
+    }
+    let importance = policy.assess(&signals);
+
+    let mut tags = trace.tags.clone();
+    tags.push("langfuse".to_string());
+    tags.extend(extra_tags);
+    let mut metadata =
+        std::collections::HashMap::from([(TRACE_ID_KEY.to_string(), json!(trace.id))]);
+    if let Some(scoring) = scoring {
+        tags.extend(scoring.tags);
+        metadata.extend(scoring.metadata);
+    }
+
+    CreateMemoryInput {
+        content,
+        memory_type,
+        importance: Some(importance),
+        tags,
+        metadata,
+        workspace,
+        event_time: Some(trace.timestamp),
+        ..Default::default()
+    }
+}
+
+/// What a sync did with a trace
+#[cfg(feature = "langfuse")]
+enum TraceImport {
+    Created,
+    Updated,
+    Skipped,
+}
+
+/// Create the memory of a trace, or refresh the one an earlier import made
+///
+/// A trace whose memory was deleted is imported again. An existing memory is
+/// left alone when `on_existing` is `"skip"` or the trace is unchanged since
+/// it was imported; otherwise its content, type, importance and score data are
+/// replaced while tags and metadata added since are kept.
+#[cfg(feature = "langfuse")]
+fn import_trace_memory(
+    conn: &rusqlite::Connection,
+    trace_id: &str,
+    workspace: &str,
+    input: &crate::types::CreateMemoryInput,
+    on_existing: &str,
+) -> crate::error::Result<TraceImport> {
+    use crate::integrations::langfuse::{FAILURE_MODE_TAG, HIGH_SCORE_TAG, LOW_SCORE_TAG};
+    use crate::storage::queries::{
+        create_memory, get_trace_memory, peek_memory, update_memory, upsert_trace_memory,
+    };
+    use crate::types::UpdateMemoryInput;
+
+    let fingerprint = trace_fingerprint(input);
+    let existing = match get_trace_memory(conn, trace_id, workspace)? {
+        Some(link) => peek_memory(conn, link.memory_id)
+            .ok()
+            .map(|memory| (link, memory)),
+        None => None,
+    };
+    let Some((link, memory)) = existing else {
+        let memory = create_memory(conn, input)?;
+        upsert_trace_memory(conn, trace_id, &memory.workspace, memory.id, &fingerprint)?;
+        return Ok(TraceImport::Created);
+    };
+    if on_existing == "skip" || link.fingerprint == fingerprint {
+        return Ok(TraceImport::Skipped);
+    }
+
+    // Score tags follow the trace's current scores
+    let score_tags = [LOW_SCORE_TAG, HIGH_SCORE_TAG, FAILURE_MODE_TAG];
+    let mut tags: Vec<String> = memory
+        .tags
+        .into_iter()
+        .filter(|t| !score_tags.contains(&t.as_str()))
+        .collect();
+    for tag in &input.tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    let mut metadata = memory.metadata;
+    metadata.extend(input.metadata.clone());
+
+    let update = UpdateMemoryInput {
+        content: Some(input.content.clone()),
+        memory_type: Some(input.memory_type),
+        tags: Some(tags),
+        metadata: Some(metadata),
+        importance: input.importance,
+        scope: None,
+        ttl_seconds: None,
+        event_time: Some(input.event_time),
+        trigger_pattern: None,
+        media_url: None,
+    };
+    update_memory(conn, memory.id, &update)?;
+    upsert_trace_memory(conn, trace_id, workspace, memory.id, &fingerprint)?;
+    Ok(TraceImport::Updated)
+}
+
+/// Hash of everything a trace import writes, to tell whether a trace changed
+/// since it was last imported
+#[cfg(feature = "langfuse")]
+fn trace_fingerprint(input: &crate::types::CreateMemoryInput) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut tags = input.tags.clone();
+    tags.sort();
+    let metadata: std::collections::BTreeMap<_, _> = input.metadata.iter().collect();
+    let imported = json!({
+        "content": input.content,
+        "memory_type": input.memory_type.as_str(),
+        "importance": input.importance,
+        "tags": tags,
+        "metadata": metadata,
+    });
+    hex::encode(Sha256::digest(imported.to_string().as_bytes()))
+}
+
+#[cfg(feature = "langfuse")]
+pub fn langfuse_import_scores(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::integrations::langfuse::{
+        score_trace, trace_id_of, LangfuseClient, LangfuseConfig, SCORE_ADJUSTMENT_KEY,
+    };
+    use crate::intelligence::ImportancePolicy;
+    use crate::storage::queries::{list_memories, update_memory};
+    use crate::types::{ListOptions, UpdateMemoryInput};
+
+    let config = match LangfuseConfig::from_env() {
+        Some(c) => c,
+        None => {
+            return json!({
+                "error": "Langfuse not configured. Set LANGFUSE_PUBLIC_KEY and LANGFUSE_SECRET_KEY environment variables."
+            });
+        }
+    };
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(100)
+        .clamp(1, 1000);
+    let workspace = params
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let retype = params
+        .get("retype")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let dry_run = params
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let options = ListOptions {
+        limit: Some(limit),
+        tags: Some(vec!["langfuse".to_string()]),
+        workspace,
+        ..Default::default()
+    };
+    let memories = match ctx
+        .storage
+        .with_connection(|conn| list_memories(conn, &options))
+    {
+        Ok(memories) => memories,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+
+    let client = LangfuseClient::new(config);
+    let policy = ImportancePolicy::global();
+    let mut updates = Vec::new();
+    let mut unscored = 0usize;
+    let mut not_found = Vec::new();
+    let mut errors = Vec::new();
+    for memory in &memories {
+        let Some(trace_id) = trace_id_of(memory) else {
+            continue;
+        };
+        let trace = match ctx
+            .langfuse_runtime
+            .block_on(async { client.fetch_trace(&trace_id).await })
+        {
+            Ok(Some(trace)) => trace,
+            Ok(None) => {
+                not_found.push(trace_id);
+                continue;
+            }
+            Err(e) => {
+                errors.push(format!("Trace {}: {}", trace_id, e));
+                continue;
+            }
+        };
+        let Some(scoring) = score_trace(&trace, policy) else {
+            unscored += 1;
+            continue;
+        };
+
+        // Replace the adjustment of an earlier backfill rather than adding to it
+        let previous = memory
+            .metadata
+            .get(SCORE_ADJUSTMENT_KEY)
+            .and_then(|v| v.as_f64())
+            .unwrap_or(0.0) as f32;
+        let importance = (memory.importance - previous + scoring.adjustment).clamp(0.0, 1.0);
+        let memory_type = scoring
+            .memory_type
+            .filter(|_| retype)
+            .unwrap_or(memory.memory_type);
+        let mut tags = memory.tags.clone();
+        for tag in &scoring.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        let mut metadata = memory.metadata.clone();
+        metadata.extend(scoring.metadata.clone());
+
+        updates.push(json!({
+            "id": memory.id,
+            "trace_id": trace_id,
+            "score": scoring.score,
+            "band": scoring.band,
+            "importance": importance,
+            "previous_importance": memory.importance,
+            "memory_type": memory_type.as_str(),
+        }));
+        if dry_run {
+            continue;
+        }
+        let input = UpdateMemoryInput {
+            content: None,
+            memory_type: Some(memory_type),
+            tags: Some(tags),
+            metadata: Some(metadata),
+            importance: Some(importance),
+            scope: None,
+            ttl_seconds: None,
+            event_time: None,
+            trigger_pattern: None,
+            media_url: None,
+        };
+        if let Err(e) = ctx
+            .storage
+            .with_transaction(|conn| update_memory(conn, memory.id, &input))
+        {
+            errors.push(format!("Memory {}: {}", memory.id, e));
+            updates.pop();
+        }
+    }
+
+    json!({
+        "memories_scanned": memories.len(),
+        "updated": updates.len(),
+        "unscored": unscored,
+        "not_found": not_found,
+        "dry_run": dry_run,
+        "updates": updates,
+        "errors": errors,
+    })
+}
+
+// ── OpenTelemetry Integration (feature-gated) ─────────────────────────────────
+
+#[cfg(feature = "otel")]
+pub fn otel_ingest_traces(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::integrations::otel::{
+        extract_error_patterns, genai_spans, span_metadata, span_to_memory_content,
+        ExportTraceServiceRequest, OTEL_ERROR_TAG, OTEL_PATTERN_KEY, OTEL_SPAN_ID_KEY, OTEL_TAG,
+    };
+    use crate::intelligence::{ImportancePolicy, ImportanceSignals, ImportanceSource};
+    use crate::storage::queries::create_memory;
+    use crate::types::{CreateMemoryInput, MemoryType};
+
+    let request: ExportTraceServiceRequest = match serde_json::from_value(params.clone()) {
+        Ok(request) => request,
+        Err(e) => return json!({"error": format!("Invalid OTLP trace request: {}", e)}),
+    };
+
+    let workspace = match params
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .map(crate::types::normalize_workspace)
+        .transpose()
+    {
+        Ok(workspace) => workspace,
+        Err(e) => return json!({"error": format!("Invalid workspace: {}", e)}),
+    };
+    let workspace_key = workspace.as_deref().unwrap_or("default");
+
+    let extract_patterns = params
+        .get("extract_patterns")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let dry_run = params
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let spans = genai_spans(&request);
+    let patterns = if extract_patterns {
+        extract_error_patterns(&spans)
+    } else {
+        Vec::new()
+    };
+
+    if dry_run {
+        let summaries: Vec<_> = spans
+            .iter()
+            .map(|span| {
+                let existing = ctx
+                    .storage
+                    .with_connection(|conn| {
+                        find_otel_memory(conn, OTEL_SPAN_ID_KEY, &span.span_id, workspace_key)
+                    })
+                    .ok()
+                    .flatten();
+                json!({
+                    "trace_id": span.trace_id,
+                    "span_id": span.span_id,
+                    "name": span.name,
+                    "model": span.model,
+                    "error": span.error,
+                    "memory_id": existing
+                })
+            })
+            .collect();
+        return json!({
+            "dry_run": true,
+            "spans_received": request.span_count(),
+            "genai_spans": spans.len(),
+            "spans": summaries,
+            "patterns": patterns
+        });
+    }
+
+    let policy = ImportancePolicy::global();
+    let mut memories_created = 0i64;
+    let mut spans_skipped = 0i64;
+    let mut patterns_created = 0i64;
+    let mut errors: Vec<String> = Vec::new();
+
+    for span in &spans {
+        let content = span_to_memory_content(span);
+        let importance = policy.assess(&ImportanceSignals::new(
+            ImportanceSource::OtelSpan,
+            MemoryType::Episodic,
+            &content,
+        ));
+        let mut tags = vec![OTEL_TAG.to_string()];
+        if let Some(model) = &span.model {
+            tags.push(format!("model:{}", model));
+        }
+        if span.error.is_some() {
+            tags.push(OTEL_ERROR_TAG.to_string());
+        }
+        let input = CreateMemoryInput {
+            content,
+            memory_type: MemoryType::Episodic,
+            importance: Some(importance),
+            tags,
+            metadata: span_metadata(span),
+            workspace: workspace.clone(),
+            event_time: span.start_time,
+            ..Default::default()
+        };
+
+        match ctx.storage.with_transaction(|conn| {
+            if find_otel_memory(conn, OTEL_SPAN_ID_KEY, &span.span_id, workspace_key)?.is_some() {
+                return Ok(false);
+            }
+            create_memory(conn, &input)?;
+            Ok(true)
+        }) {
+            Ok(true) => memories_created += 1,
+            Ok(false) => spans_skipped += 1,
+            Err(e) => errors.push(format!("Span {}: {}", span.span_id, e)),
+        }
+    }
+
+    // A pattern is stored once per workspace; later batches leave it alone
+    for pattern in &patterns {
+        let content = &pattern.suggested_content;
+        let importance = policy.assess(&ImportanceSignals::new(
+            ImportanceSource::OtelPattern,
+            MemoryType::Issue,
+            content,
+        ));
+        let input = CreateMemoryInput {
+            content: content.clone(),
+            memory_type: MemoryType::Issue,
+            importance: Some(importance),
+            tags: pattern.suggested_tags.clone(),
+            metadata: std::collections::HashMap::from([
+                (OTEL_PATTERN_KEY.to_string(), json!(pattern.key)),
+                ("otel_span_ids".to_string(), json!(pattern.source_span_ids)),
+            ]),
+            workspace: workspace.clone(),
+            ..Default::default()
+        };
+
+        match ctx.storage.with_transaction(|conn| {
+            if find_otel_memory(conn, OTEL_PATTERN_KEY, &pattern.key, workspace_key)?.is_some() {
+                return Ok(false);
+            }
+            create_memory(conn, &input)?;
+            Ok(true)
+        }) {
+            Ok(true) => patterns_created += 1,
+            Ok(false) => {}
+            Err(e) => errors.push(format!("Pattern {}: {}", pattern.key, e)),
+        }
+    }
+
+    json!({
+        "spans_received": request.span_count(),
+        "genai_spans": spans.len(),
+        "memories_created": memories_created,
+        "spans_skipped": spans_skipped,
+        "patterns_created": patterns_created,
+        "errors": errors
+    })
+}
+
+/// ID of the memory in `workspace` whose metadata has `key` set to `value`
+#[cfg(feature = "otel")]
+fn find_otel_memory(
+    conn: &rusqlite::Connection,
+    key: &str,
+    value: &str,
+    workspace: &str,
+) -> crate::error::Result<Option<i64>> {
+    use crate::storage::queries::list_memories;
+    use crate::types::ListOptions;
+
+    let options = ListOptions {
+        metadata_filter: Some(std::collections::HashMap::from([(
+            key.to_string(),
+            json!(value),
+        )])),
+        workspace: Some(workspace.to_string()),
+        limit: Some(1),
+        ..Default::default()
+    };
+    Ok(list_memories(conn, &options)?.first().map(|m| m.id))
+}
+
 // ── Meilisearch Tools (feature-gated) ─────────────────────────────────────────
 
 #[cfg(feature = "meilisearch")]
@@ -948,11 +2023,17 @@ pub fn discover_tools(_ctx: &HandlerContext, params: Value) -> Value {
                 ToolTier::Standard => "standard",
                 ToolTier::Advanced => "advanced",
             };
-            json!({
+            let version = crate::mcp::versioning::tool_version(def.name);
+            let mut entry = json!({
                 "name": def.name,
                 "description": def.description,
-                "tier": tier_str
-            })
+                "tier": tier_str,
+                "since": version.since
+            });
+            if let Some(message) = version.deprecation_message() {
+                entry["deprecated"] = json!(message);
+            }
+            entry
         })
         .collect();
 
@@ -8,10 +8,16 @@ use std::sync::Arc;
 use parking_lot::Mutex;
 use serde_json::{json, Value};
 
-use crate::embedding::EmbeddingCache;
+use crate::budget::{self, CancelHandle};
+use crate::embedding::{EmbeddingCache, EmbeddingQueue};
+use crate::error::EngramError;
+use crate::intelligence::temporal_expr::{parse_time_range, parse_utc_offset, TimeRange};
+use crate::limits::{self, ResourceLimits};
+use crate::mcp::versioning::{self, ToolRoute};
 use crate::realtime::RealtimeManager;
 use crate::search::{FuzzyEngine, SearchConfig, SearchResultCache};
-use crate::storage::Storage;
+use crate::storage::{workspace_temporal_context, Storage};
+use crate::types::{EmbeddingPriority, FieldSelection, ItemKind, MemoryId, OutputShape, Verbosity};
 
 pub mod agent;
 pub mod autonomous;
@@ -20,17 +26,21 @@ pub mod context;
 pub mod document_ingest;
 pub mod markdown_export;
 pub mod evolution;
+pub mod federation;
 pub mod graph;
 pub mod handoff;
 pub mod identity;
 pub mod lifecycle;
 pub mod memory_crud;
 pub mod misc;
+pub mod persona;
+pub mod preference;
 pub mod project_context;
 pub mod quality;
 pub mod retrieval;
 pub mod search;
 pub mod session;
+pub mod site_export;
 pub mod stats;
 pub mod summarize;
 pub mod sync;
@@ -60,7 +70,26 @@ pub struct HandlerContext {
     pub search_config: SearchConfig,
     pub realtime: Option<RealtimeManager>,
     pub embedding_cache: Arc<EmbeddingCache>,
+    /// Lanes of the background embedding worker; `None` when no worker
+    /// runs, leaving queued memories to `memory_rebuild_embeddings`.
+    pub embedding_queue: Option<EmbeddingQueue>,
     pub search_cache: Arc<SearchResultCache>,
+    /// Read-through cache for by-ID memory fetches.
+    pub memory_cache: Arc<crate::storage::MemoryCache>,
+    /// Persona activated by the connected agent, shaping retrieval defaults
+    /// and the tools it may call.
+    pub persona: Arc<crate::storage::ActivePersona>,
+    /// Nearest-neighbour index over embeddings, empty until rebuilt.
+    pub vector_index: Arc<crate::search::vector_index::VectorIndexHandle>,
+    /// Cross-encoder for `rerank_strategy: "cross_encoder"`, loaded once at
+    /// startup; searches use it by default when set.
+    pub cross_encoder: Option<Arc<dyn crate::search::CrossEncoder>>,
+    /// Read-only databases searched next to `storage` by the federated
+    /// tools; `None` when no federation config is present.
+    pub federation: Option<Arc<crate::storage::Federation>>,
+    /// Days expired memories stay recoverable before cleanup purges them
+    /// (`ENGRAM_EXPIRED_GRACE_DAYS` on the server).
+    pub expired_grace_days: i64,
     /// Meilisearch backend (feature-gated).
     #[cfg(feature = "meilisearch")]
     pub meili: Option<Arc<crate::storage::MeilisearchBackend>>,
@@ -75,25 +104,282 @@ pub struct HandlerContext {
     pub langfuse_runtime: Arc<tokio::runtime::Runtime>,
 }
 
+/// Tools whose responses carry memories, search hits or graph edges, and
+/// where: only these honor the `verbosity` and `fields` parameters.
+pub const PROJECTABLE_TOOLS: &[(&str, &[OutputShape])] = &[
+    ("memory_get", &[OutputShape::new("", ItemKind::MEMORY)]),
+    ("memory_list", &[OutputShape::new("[]", ItemKind::MEMORY)]),
+    (
+        "memory_search",
+        &[
+            OutputShape::new("[]", ItemKind::Hit),
+            OutputShape::new("results[]", ItemKind::Hit),
+        ],
+    ),
+    (
+        "memory_search_by_identity",
+        &[OutputShape::new("memories[]", ItemKind::MEMORY)],
+    ),
+    (
+        "memory_session_search",
+        &[OutputShape::new("memories[]", ItemKind::MEMORY)],
+    ),
+    (
+        "memory_expand",
+        &[OutputShape::new("memories[]", ItemKind::MEMORY)],
+    ),
+    (
+        "memory_related",
+        &[
+            OutputShape::new("[]", ItemKind::Edge),
+            OutputShape::new("discovery_edges[]", ItemKind::Edge),
+        ],
+    ),
+    (
+        "memory_traverse",
+        &[OutputShape::new("discovery_edges[]", ItemKind::Edge)],
+    ),
+    // Paths are id lists; accepted for symmetry with memory_traverse
+    ("memory_find_path", &[]),
+    (
+        "memory_graph_query",
+        &[OutputShape::new("rows[]", ItemKind::GraphRow)],
+    ),
+    (
+        "memory_expired_list",
+        &[OutputShape::new(
+            "memories[]",
+            ItemKind::Memory {
+                labels: &["purge_at"],
+            },
+        )],
+    ),
+    (
+        "federated_search",
+        &[OutputShape::new("results[]", ItemKind::Hit)],
+    ),
+    (
+        "federated_list",
+        &[OutputShape::new(
+            "results[]",
+            ItemKind::Memory {
+                labels: &["source"],
+            },
+        )],
+    ),
+];
+
+/// The declared output shape of a tool call, if its response is projectable.
+fn output_shape(tool_name: &str, params: &Value) -> Option<&'static [OutputShape]> {
+    // A paged memory_get returns a content range, not a memory
+    if tool_name == "memory_get"
+        && ["offset", "length"]
+            .iter()
+            .any(|key| params.get(key).is_some_and(|v| !v.is_null()))
+    {
+        return None;
+    }
+    PROJECTABLE_TOOLS
+        .iter()
+        .find(|(name, _)| *name == tool_name)
+        .map(|(_, shape)| *shape)
+}
+
+/// Retrieval tools that fall back to the active persona's default workspace
+/// when the call doesn't name a workspace.
+pub const PERSONA_WORKSPACE_TOOLS: &[&str] = &[
+    "memory_list",
+    "memory_list_compact",
+    "memory_search",
+    "memory_search_compact",
+    "memory_search_by_identity",
+    "memory_search_by_image",
+    "memory_session_search",
+    "memory_graph_query",
+    "memory_get_timeline",
+    "memory_get_procedures",
+    "memory_build_context",
+    "memory_get_injection_prompt",
+    "meilisearch_search",
+    "fact_get",
+    "fact_list",
+    "salience_top",
+    "recent_activity",
+];
+
+impl HandlerContext {
+    /// The server's search configuration with the active persona's ranking
+    /// profile applied.
+    pub fn effective_search_config(&self) -> SearchConfig {
+        match self.persona.get() {
+            Some(persona) => persona.ranking.apply(&self.search_config),
+            None => self.search_config.clone(),
+        }
+    }
+
+    /// Hand a memory queued for embedding to the background worker in the
+    /// given lane, so it doesn't wait for the next pass over pending rows.
+    pub fn submit_embedding(
+        &self,
+        memory_id: MemoryId,
+        content: &str,
+        priority: EmbeddingPriority,
+    ) {
+        let Some(queue) = &self.embedding_queue else {
+            return;
+        };
+        let submitted = self
+            .storage
+            .with_connection(|conn| queue.submit(conn, memory_id, content.to_string(), priority));
+        if let Err(e) = submitted {
+            tracing::warn!("Failed to submit memory {} for embedding: {}", memory_id, e);
+        }
+    }
+}
+
+/// Resolve the response verbosity for a tool call.
+///
+/// A per-call `verbosity` argument wins over the `ENGRAM_VERBOSITY`
+/// environment default; tools outside [`PROJECTABLE_TOOLS`] are never projected.
+fn resolve_verbosity(tool_name: &str, params: &Value) -> Result<Verbosity, String> {
+    if output_shape(tool_name, params).is_none() {
+        return Ok(Verbosity::Full);
+    }
+    match params.get("verbosity").and_then(|v| v.as_str()) {
+        Some(v) => v.parse(),
+        None => Ok(std::env::var("ENGRAM_VERBOSITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default()),
+    }
+}
+
+/// Resolve the natural-language `when` argument of a tool call ("last
+/// Tuesday", "since 03/04") to a time range.
+///
+/// Dates are read in the call's `timezone` and `date_order`, falling back to
+/// the `workspace`'s configured ones, then UTC and month-first.
+fn resolve_when(ctx: &HandlerContext, params: &Value) -> crate::error::Result<Option<TimeRange>> {
+    let Some(when) = params.get("when").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let workspace = params
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default");
+    let mut temporal = ctx
+        .storage
+        .with_connection(|conn| workspace_temporal_context(conn, workspace))?;
+    if let Some(tz) = params.get("timezone").and_then(|v| v.as_str()) {
+        temporal = temporal.with_offset(parse_utc_offset(tz)?);
+    }
+    if let Some(order) = params.get("date_order").and_then(|v| v.as_str()) {
+        temporal = temporal.with_date_order(order.parse()?);
+    }
+    parse_time_range(when, &temporal).map(Some).ok_or_else(|| {
+        EngramError::InvalidInput(format!("No date or time range found in when: '{}'", when))
+    })
+}
+
+/// Resolve an explicit `fields` selection for a tool call, if any.
+fn resolve_fields(tool_name: &str, params: &Value) -> Result<Option<FieldSelection>, String> {
+    if output_shape(tool_name, params).is_none() {
+        return Ok(None);
+    }
+    params
+        .get("fields")
+        .filter(|v| !v.is_null())
+        .map(FieldSelection::from_value)
+        .transpose()
+}
+
 /// Route a tool call to the appropriate domain handler.
 ///
-/// Returns the JSON value that should be placed in the MCP `ToolCallResult`.
+/// Returns the JSON value that should be placed in the MCP `ToolCallResult`,
+/// projected to the requested `fields` or, absent those, [`Verbosity`].
 pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
+    dispatch_cancellable(ctx, tool_name, params, CancelHandle::new())
+}
+
+/// [`dispatch`] under a time budget that `cancel` can cut short.
+///
+/// The budget comes from [`budget::tool_timeout`]; when it runs out, work
+/// stops at the next checkpoint and the response is marked `truncated: true`.
+/// Responses larger than the [`ResourceLimits`] byte cap are replaced by an
+/// error suggesting how to narrow the call.
+pub fn dispatch_cancellable(
+    ctx: &HandlerContext,
+    tool_name: &str,
+    mut params: Value,
+    cancel: CancelHandle,
+) -> Value {
+    if let Some(persona) = ctx.persona.get() {
+        if !persona.allows_tool(tool_name) {
+            return json!({
+                "error": format!(
+                    "Tool '{}' is not allowed for persona '{}'",
+                    tool_name, persona.name
+                )
+            });
+        }
+        if let (Some(workspace), Value::Object(map)) = (persona.default_workspace, &mut params) {
+            if PERSONA_WORKSPACE_TOOLS.contains(&tool_name)
+                && map.get("workspace").is_none_or(|v| v.is_null())
+            {
+                map.insert("workspace".to_string(), json!(workspace));
+            }
+        }
+    }
+    // Deprecated names are served by their replacement (or refused)
+    let (tool_name, deprecation) = match versioning::route_tool(tool_name) {
+        ToolRoute::Direct => (tool_name, None),
+        ToolRoute::Shim {
+            replacement,
+            message,
+        } => (replacement, Some((replacement, message))),
+        ToolRoute::Disabled { error } => return json!({"error": error, "deprecated": true}),
+    };
+    let verbosity = match resolve_verbosity(tool_name, &params) {
+        Ok(v) => v,
+        Err(e) => return json!({"error": e}),
+    };
+    let fields = match resolve_fields(tool_name, &params) {
+        Ok(f) => f,
+        Err(e) => return json!({"error": e}),
+    };
+    let shape = output_shape(tool_name, &params).unwrap_or_default();
+    let budget = budget::enter(budget::tool_timeout(tool_name, &params), cancel);
+    let mut result = route(ctx, tool_name, params);
+    if result.get("error").is_none() {
+        match fields {
+            Some(selection) => selection.project(&mut result, shape),
+            None => verbosity.project(&mut result, shape),
+        }
+    }
+    if budget.truncated() {
+        if let Value::Object(map) = &mut result {
+            map.insert("truncated".to_string(), json!(true));
+        }
+    }
+    if let (Some((replacement, message)), Value::Object(map)) = (deprecation, &mut result) {
+        map.insert("deprecated".to_string(), json!(true));
+        map.insert("deprecated_message".to_string(), json!(message));
+        map.insert("replacement".to_string(), json!(replacement));
+    }
+    if result.get("error").is_none() {
+        let limits = ResourceLimits::from_env();
+        if let Err(e) = limits.check_result_size(&result, limits::narrowing_hint(tool_name)) {
+            return json!({"error": e.to_string()});
+        }
+    }
+    result
+}
+
+fn route(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
     match tool_name {
         // ── Memory CRUD ──────────────────────────────────────────────────────
         "memory_create" => memory_crud::memory_create(ctx, params),
         "context_seed" => memory_crud::context_seed(ctx, params),
-        "memory_seed" => {
-            let mut result = memory_crud::context_seed(ctx, params);
-            if let Value::Object(ref mut map) = result {
-                map.insert("deprecated".to_string(), json!(true));
-                map.insert(
-                    "deprecated_message".to_string(),
-                    json!("Use context_seed instead."),
-                );
-            }
-            result
-        }
         "memory_get" => memory_crud::memory_get(ctx, params),
         "memory_get_public" => memory_crud::memory_get_public(ctx, params),
         "memory_update" => memory_crud::memory_update(ctx, params),
@@ -109,7 +395,10 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "memory_get_procedures" => memory_crud::memory_get_procedures(ctx, params),
         "memory_record_procedure_outcome" => memory_crud::record_procedure_outcome(ctx, params),
         "memory_set_expiration" => memory_crud::set_expiration(ctx, params),
+        "memory_set_ttl_bulk" => memory_crud::set_expiration_bulk(ctx, params),
         "memory_cleanup_expired" => memory_crud::cleanup_expired(ctx, params),
+        "memory_expired_list" => memory_crud::expired_list(ctx, params),
+        "memory_resurrect" => memory_crud::resurrect(ctx, params),
         "memory_create_batch" => memory_crud::memory_create_batch(ctx, params),
         "memory_delete_batch" => memory_crud::memory_delete_batch(ctx, params),
         "memory_create_section" => memory_crud::memory_create_section(ctx, params),
@@ -119,10 +408,16 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         // ── Search ───────────────────────────────────────────────────────────
         "memory_search" => search::memory_search(ctx, params),
         "memory_search_suggest" => search::search_suggest(ctx, params),
+        "federated_search" => federation::federated_search(ctx, params),
+        "federated_list" => federation::federated_list(ctx, params),
+        "federation_sources" => federation::federation_sources(ctx, params),
         "memory_search_by_identity" => search::memory_search_by_identity(ctx, params),
         "memory_session_search" => search::memory_session_search(ctx, params),
         "memory_find_duplicates" => search::find_duplicates(ctx, params),
+        "memory_cluster_duplicates" => search::cluster_duplicates(ctx, params),
         "memory_find_semantic_duplicates" => search::find_semantic_duplicates(ctx, params),
+        "memory_similarity_matrix" => search::similarity_matrix(ctx, params),
+        "memory_check_duplicate" => search::check_duplicate(ctx, params),
         "search_cache_feedback" => search::search_cache_feedback(ctx, params),
         "search_cache_stats" => search::search_cache_stats(ctx, params),
         "search_cache_clear" => search::search_cache_clear(ctx, params),
@@ -133,7 +428,13 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "memory_related" => graph::memory_related(ctx, params),
         "memory_traverse" => graph::memory_traverse(ctx, params),
         "memory_find_path" => graph::find_path(ctx, params),
+        "memory_graph_query" => graph::graph_query(ctx, params),
+        "memory_similar_by_structure" => graph::similar_by_structure(ctx, params),
+        "memory_suggest_links" => graph::suggest_links(ctx, params),
+        "memory_detect_contradiction_cycles" => graph::detect_contradiction_cycles(ctx, params),
         "memory_export_graph" => graph::export_graph(ctx, params),
+        "memory_export_neighborhood" => graph::export_neighborhood(ctx, params),
+        "memory_graph_explore" => graph::graph_explore(ctx, params),
         "memory_extract_entities" => graph::extract_entities(ctx, params),
         "memory_get_entities" => graph::get_entities(ctx, params),
         "memory_search_entities" => graph::search_entities(ctx, params),
@@ -144,6 +445,16 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "workspace_stats" => workspace::workspace_stats(ctx, params),
         "workspace_move" => workspace::workspace_move(ctx, params),
         "workspace_delete" => workspace::workspace_delete(ctx, params),
+        "workspace_merge" => workspace::workspace_merge(ctx, params),
+        "workspace_split" => workspace::workspace_split(ctx, params),
+        "workspace_config_set" => workspace::workspace_config_set(ctx, params),
+        "workspace_config_get" => workspace::workspace_config_get(ctx, params),
+        "workspace_config_list" => workspace::workspace_config_list(ctx, params),
+        "workspace_config_delete" => workspace::workspace_config_delete(ctx, params),
+        "workspace_share_create" => workspace::workspace_share_create(ctx, params),
+        "workspace_share_list" => workspace::workspace_share_list(ctx, params),
+        "workspace_share_revoke" => workspace::workspace_share_revoke(ctx, params),
+        "workspace_share_view" => workspace::workspace_share_view(ctx, params),
 
         // ── Identity ─────────────────────────────────────────────────────────
         "identity_create" => identity::identity_create(ctx, params),
@@ -159,11 +470,19 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "identity_unlink" => identity::identity_unlink(ctx, params),
         "memory_get_identities" => identity::memory_get_identities(ctx, params),
 
+        // ── Preferences ──────────────────────────────────────────────────────
+        "preference_set" => preference::preference_set(ctx, params),
+        "preference_get" => preference::preference_get(ctx, params),
+        "preference_list" => preference::preference_list(ctx, params),
+        "preference_history" => preference::preference_history(ctx, params),
+        "preference_unset" => preference::preference_unset(ctx, params),
+
         // ── Session ──────────────────────────────────────────────────────────
         "session_index" => session::session_index(ctx, params),
         "session_index_delta" => session::session_index_delta(ctx, params),
         "session_get" => session::session_get(ctx, params),
         "session_list" => session::session_list(ctx, params),
+        "session_link_topics" => session::session_link_topics(ctx, params),
         "session_delete" => session::session_delete(ctx, params),
         "session_context_create" => session::session_context_create(ctx, params),
         "session_context_add_memory" => session::session_context_add_memory(ctx, params),
@@ -174,6 +493,7 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "session_context_update_summary" => session::session_context_update_summary(ctx, params),
         "session_context_end" => session::session_context_end(ctx, params),
         "session_context_export" => session::session_context_export(ctx, params),
+        "recommendations_get" => session::recommendations_get(ctx, params),
         "session_land" => handoff::session_land(ctx, params),
 
         // ── Lifecycle ────────────────────────────────────────────────────────
@@ -186,17 +506,33 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "retention_policy_list" => lifecycle::retention_policy_list(ctx, params),
         "retention_policy_delete" => lifecycle::retention_policy_delete(ctx, params),
         "retention_policy_apply" => lifecycle::retention_policy_apply(ctx, params),
+        "policy_simulate" => lifecycle::policy_simulate(ctx, params),
+        "memory_supersede" => lifecycle::memory_supersede(ctx, params),
+        "memory_restore_superseded" => lifecycle::memory_restore_superseded(ctx, params),
+        "memory_list_superseded" => lifecycle::memory_list_superseded(ctx, params),
 
         // ── Quality ──────────────────────────────────────────────────────────
         "quality_score" => quality::quality_score(ctx, params),
         "quality_report" => quality::quality_report(ctx, params),
         "quality_find_duplicates" => quality::quality_find_duplicates(ctx, params),
         "quality_get_duplicates" => quality::quality_get_duplicates(ctx, params),
+        "quality_resolve_duplicate" => quality::quality_resolve_duplicate(ctx, params),
+        "memory_calibrate_dedup" => quality::memory_calibrate_dedup(ctx, params),
         "quality_find_conflicts" => quality::quality_find_conflicts(ctx, params),
         "quality_get_conflicts" => quality::quality_get_conflicts(ctx, params),
         "quality_resolve_conflict" => quality::quality_resolve_conflict(ctx, params),
         "quality_source_trust" => quality::quality_source_trust(ctx, params),
         "quality_improve" => quality::quality_improve(ctx, params),
+        "memory_verify_fact" => quality::memory_verify_fact(ctx, params),
+        "memory_escalate_unverified_facts" => {
+            quality::memory_escalate_unverified_facts(ctx, params)
+        }
+        "memory_fact_review_queue" => quality::memory_fact_review_queue(ctx, params),
+        "freshness_contract_set" => quality::freshness_contract_set(ctx, params),
+        "freshness_contract_list" => quality::freshness_contract_list(ctx, params),
+        "freshness_contract_delete" => quality::freshness_contract_delete(ctx, params),
+        "memory_freshness_check" => quality::memory_freshness_check(ctx, params),
+        "memory_stale_report" => quality::memory_stale_report(ctx, params),
         "salience_get" => quality::salience_get(ctx, params),
         "salience_set_importance" => quality::salience_set_importance(ctx, params),
         "salience_boost" => quality::salience_boost(ctx, params),
@@ -225,6 +561,7 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "memory_versions" => stats::memory_versions(ctx, params),
         "embedding_cache_stats" => stats::embedding_cache_stats(ctx, params),
         "embedding_cache_clear" => stats::embedding_cache_clear(ctx, params),
+        "memory_read_cache_stats" => stats::memory_read_cache_stats(ctx, params),
         "memory_soft_trim" => stats::memory_soft_trim(ctx, params),
         "memory_list_compact" => stats::memory_list_compact(ctx, params),
         "memory_content_stats" => stats::memory_content_stats(ctx, params),
@@ -244,17 +581,40 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "memory_summarize" => summarize::memory_summarize(ctx, params),
         "memory_get_full" => summarize::memory_get_full(ctx, params),
         "context_budget_check" => summarize::context_budget_check(ctx, params),
+        "context_score" => summarize::context_score(ctx, params),
         "memory_archive_old" => summarize::memory_archive_old(ctx, params),
 
         // ── Misc ─────────────────────────────────────────────────────────────
         "memory_tags" => misc::memory_tags(ctx, params),
         "memory_tag_hierarchy" => misc::memory_tag_hierarchy(ctx, params),
         "memory_validate_tags" => misc::memory_validate_tags(ctx, params),
+        "tag_synonym_add" => misc::tag_synonym_add(ctx, params),
+        "tag_synonym_remove" => misc::tag_synonym_remove(ctx, params),
+        "tag_synonym_list" => misc::tag_synonym_list(ctx, params),
+        "tag_synonym_suggest" => misc::tag_synonym_suggest(ctx, params),
+        "tag_normalization_get" => misc::tag_normalization_get(ctx, params),
+        "tag_normalization_set" => misc::tag_normalization_set(ctx, params),
+        "tag_policy_get" => misc::tag_policy_get(ctx, params),
+        "tag_policy_set" => misc::tag_policy_set(ctx, params),
+        "tag_policy_report" => misc::tag_policy_report(ctx, params),
         "memory_export" => misc::memory_export(ctx, params),
         "memory_export_markdown" => markdown_export::memory_export_markdown(ctx, params),
+        "memory_export_site" => site_export::memory_export_site(ctx, params),
         "memory_import" => misc::memory_import(ctx, params),
         "memory_rebuild_embeddings" => misc::memory_rebuild_embeddings(ctx, params),
+        "memory_rebuild_embeddings_status" => misc::memory_rebuild_embeddings_status(ctx, params),
+        "memory_migrate_embeddings" => misc::memory_migrate_embeddings(ctx, params),
+        "memory_embedding_status" => misc::memory_embedding_status(ctx, params),
+        "sync_task_list" => misc::sync_task_list(ctx, params),
         "memory_rebuild_crossrefs" => misc::memory_rebuild_crossrefs(ctx, params),
+        "memory_rebuild_adjacency" => misc::memory_rebuild_adjacency(ctx, params),
+        "memory_rebuild_signatures" => misc::memory_rebuild_signatures(ctx, params),
+        "memory_rebuild_vocabulary" => misc::memory_rebuild_vocabulary(ctx, params),
+        "memory_vector_index_rebuild" => misc::memory_vector_index_rebuild(ctx, params),
+        "memory_vector_index_stats" => misc::memory_vector_index_stats(ctx, params),
+        "memory_reduce_embeddings" => misc::memory_reduce_embeddings(ctx, params),
+        "memory_restore_embeddings" => misc::memory_restore_embeddings(ctx, params),
+        "memory_fit_embedding_adapter" => misc::memory_fit_embedding_adapter(ctx, params),
         "memory_upload_image" => misc::memory_upload_image(ctx, params),
         "memory_migrate_images" => misc::memory_migrate_images(ctx, params),
         "memory_suggest_tags" => misc::memory_suggest_tags(ctx, params),
@@ -270,8 +630,14 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         #[cfg(feature = "langfuse")]
         "langfuse_extract_patterns" => misc::langfuse_extract_patterns(ctx, params),
         #[cfg(feature = "langfuse")]
+        "langfuse_import_scores" => misc::langfuse_import_scores(ctx, params),
+        #[cfg(feature = "langfuse")]
         "memory_from_trace" => misc::memory_from_trace(ctx, params),
 
+        // ── OpenTelemetry (feature-gated) ─────────────────────────────────────
+        #[cfg(feature = "otel")]
+        "otel_ingest_traces" => misc::otel_ingest_traces(ctx, params),
+
         // ── Meilisearch (feature-gated) ───────────────────────────────────────
         #[cfg(feature = "meilisearch")]
         "meilisearch_search" => misc::meilisearch_search(ctx, params),
@@ -282,6 +648,14 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         #[cfg(feature = "meilisearch")]
         "meilisearch_config" => misc::meilisearch_config(ctx, params),
 
+        // ── Agent personas ──────────────────────────────────────────────────
+        "persona_upsert" => persona::persona_upsert(ctx, params),
+        "persona_get" => persona::persona_get(ctx, params),
+        "persona_list" => persona::persona_list(ctx, params),
+        "persona_delete" => persona::persona_delete(ctx, params),
+        "persona_activate" => persona::persona_activate(ctx, params),
+        "persona_deactivate" => persona::persona_deactivate(ctx, params),
+
         // ── Agent Registry ──────────────────────────────────────────────────
         "agent_register" => agent::agent_register(ctx, params),
         "agent_deregister" => agent::agent_deregister(ctx, params),
@@ -339,6 +713,11 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "memory_extract_facts" => context::memory_extract_facts(ctx, params),
         "memory_list_facts" => context::memory_list_facts(ctx, params),
         "memory_fact_graph" => context::memory_fact_graph(ctx, params),
+        "fact_put" => context::fact_put(ctx, params),
+        "fact_get" => context::fact_get(ctx, params),
+        "fact_list" => context::fact_list(ctx, params),
+        "fact_retract" => context::fact_retract(ctx, params),
+        "fact_extract" => context::fact_extract(ctx, params),
         "memory_build_context" => context::memory_build_context(ctx, params),
         "memory_block_get" => context::memory_block_get(ctx, params),
         "memory_block_edit" => context::memory_block_edit(ctx, params),
@@ -364,6 +743,19 @@ pub fn dispatch(ctx: &HandlerContext, tool_name: &str, params: Value) -> Value {
         "memory_feedback" => search::memory_feedback(ctx, params),
         "memory_feedback_stats" => search::memory_feedback_stats(ctx, params),
 
+        // ── Ranking experiments (A/B testing) ────────────────────────────────
+        "search_experiment_create" => search::search_experiment_create(ctx, params),
+        "search_experiment_list" => search::search_experiment_list(ctx, params),
+        "search_experiment_stop" => search::search_experiment_stop(ctx, params),
+        "search_experiment_outcome" => search::search_experiment_outcome(ctx, params),
+        "search_experiment_report" => search::search_experiment_report(ctx, params),
+        "search_strategy_outcome" => search::search_strategy_outcome(ctx, params),
+        "search_strategy_stats" => search::search_strategy_stats(ctx, params),
+        "boost_rule_create" => search::boost_rule_create(ctx, params),
+        "boost_rule_list" => search::boost_rule_list(ctx, params),
+        "boost_rule_update" => search::boost_rule_update(ctx, params),
+        "boost_rule_delete" => search::boost_rule_delete(ctx, params),
+
         // ── Compact search + expand ──────────────────────────────────────────
         "memory_search_compact" => search::memory_search_compact(ctx, params),
         "memory_expand" => search::memory_expand(ctx, params),
@@ -333,7 +333,7 @@ fn query_media_assets(
 /// Returns: `{ results: [...], query_description, strategy_used }`
 #[cfg(feature = "multimodal")]
 pub fn memory_search_by_image(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::search::{hybrid_search, Reranker};
+    use crate::search::{hybrid_search_with_index, Reranker};
     use crate::types::SearchOptions;
 
     let image_path = match params.get("image_path").and_then(|v| v.as_str()) {
@@ -423,22 +423,22 @@ pub fn memory_search_by_image(ctx: &HandlerContext, params: Value) -> Value {
                 Err(e) => {
                     tracing::warn!("CLIP embedding failed, falling back to description: {}", e);
                     strategy_used = "description";
-                    ctx.embedder.embed(&query_text).ok()
+                    ctx.embedder.embed_query(&query_text).ok()
                 }
             }
         } else {
             strategy_used = "description";
-            ctx.embedder.embed(&query_text).ok()
+            ctx.embedder.embed_query(&query_text).ok()
         }
     } else {
         strategy_used = "description";
-        ctx.embedder.embed(&query_text).ok()
+        ctx.embedder.embed_query(&query_text).ok()
     };
 
     #[cfg(not(feature = "multimodal"))]
     let query_embedding: Option<Vec<f32>> = {
         strategy_used = "description";
-        ctx.embedder.embed(&query_text).ok()
+        ctx.embedder.embed_query(&query_text).ok()
     };
 
     // Step 5: Run hybrid search with the generated embedding
@@ -449,12 +449,19 @@ pub fn memory_search_by_image(ctx: &HandlerContext, params: Value) -> Value {
         ..Default::default()
     };
 
-    let search_config = ctx.search_config.clone();
+    let search_config = ctx.effective_search_config();
     let embedding_ref = query_embedding.as_deref();
 
     ctx.storage
         .with_connection(|conn| {
-            let results = hybrid_search(conn, &query_text, embedding_ref, &options, &search_config)?;
+            let results = hybrid_search_with_index(
+                conn,
+                &query_text,
+                embedding_ref,
+                &options,
+                &search_config,
+                Some(&ctx.vector_index),
+            )?;
             Ok(results)
         })
         .map(|results| {
@@ -562,7 +569,14 @@ mod tests {
             search_config: SearchConfig::default(),
             realtime: None,
             embedding_cache: Arc::new(EmbeddingCache::default()),
+            embedding_queue: None,
             search_cache: Arc::new(SearchResultCache::new(AdaptiveCacheConfig::default())),
+            memory_cache: Arc::new(crate::storage::MemoryCache::default()),
+            persona: Arc::new(crate::storage::ActivePersona::new()),
+            vector_index: Arc::new(crate::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: crate::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             #[cfg(feature = "meilisearch")]
             meili: None,
             #[cfg(feature = "meilisearch")]
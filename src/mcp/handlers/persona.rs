@@ -0,0 +1,124 @@
+//! Agent persona MCP tool handlers.
+//!
+//! Personas bundle a default workspace, ranking profile, pinned memories and
+//! tool allowlist under a name. `persona_activate` makes one the active
+//! persona for this server and returns its context pack.
+
+use serde_json::{json, Value};
+
+use crate::error::EngramError;
+use crate::storage::personas::{
+    delete_persona, get_persona, list_personas, upsert_persona, UpsertPersonaInput,
+};
+
+use super::HandlerContext;
+
+pub fn persona_upsert(ctx: &HandlerContext, params: Value) -> Value {
+    let input: UpsertPersonaInput = match serde_json::from_value(params) {
+        Ok(i) => i,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+
+    match ctx
+        .storage
+        .with_connection(|conn| upsert_persona(conn, &input))
+    {
+        Ok(persona) => {
+            ctx.persona.reload(&persona.name, Some(persona.clone()));
+            json!(persona)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn persona_get(ctx: &HandlerContext, params: Value) -> Value {
+    let name = match params.get("name").and_then(|v| v.as_str()) {
+        Some(name) => name,
+        None => return json!({"active": ctx.persona.get()}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| match get_persona(conn, name)? {
+            Some(persona) => Ok(json!(persona)),
+            None => Ok(json!({"error": format!("Persona not found: {}", name)})),
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn persona_list(ctx: &HandlerContext, _params: Value) -> Value {
+    let active = ctx.persona.get().map(|p| p.name);
+    ctx.storage
+        .with_connection(|conn| {
+            let personas = list_personas(conn)?;
+            Ok(json!({
+                "personas": personas,
+                "count": personas.len(),
+                "active": active,
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn persona_delete(ctx: &HandlerContext, params: Value) -> Value {
+    let name = match params.get("name").and_then(|v| v.as_str()) {
+        Some(name) => name,
+        None => return json!({"error": "name is required"}),
+    };
+
+    match ctx
+        .storage
+        .with_connection(|conn| delete_persona(conn, name))
+    {
+        Ok(deleted) => {
+            if deleted {
+                ctx.persona.reload(name, None);
+            }
+            json!({"success": deleted, "name": name})
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+/// Activate a persona and return its context pack: the persona's settings
+/// plus its pinned memories, in pinned order.
+pub fn persona_activate(ctx: &HandlerContext, params: Value) -> Value {
+    let name = match params.get("name").and_then(|v| v.as_str()) {
+        Some(name) => name,
+        None => return json!({"error": "name is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let persona = match get_persona(conn, name)? {
+                Some(persona) => persona,
+                None => return Ok(json!({"error": format!("Persona not found: {}", name)})),
+            };
+
+            let mut pinned = Vec::new();
+            let mut missing = Vec::new();
+            for &id in &persona.pinned_memory_ids {
+                match ctx.memory_cache.get_or_load(conn, id) {
+                    Ok(memory) => pinned.push(memory),
+                    Err(EngramError::NotFound(_)) => missing.push(id),
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let previous = ctx.persona.activate(persona.clone());
+            Ok(json!({
+                "persona": persona,
+                "previous": previous.map(|p| p.name),
+                "pinned_memories": pinned,
+                "missing_pinned_ids": missing,
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn persona_deactivate(ctx: &HandlerContext, _params: Value) -> Value {
+    let previous = ctx.persona.deactivate();
+    json!({
+        "success": previous.is_some(),
+        "previous": previous.map(|p| p.name),
+    })
+}
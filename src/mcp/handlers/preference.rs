@@ -0,0 +1,125 @@
+//! Preference tool handlers.
+
+use serde_json::{json, Value};
+
+use super::HandlerContext;
+use crate::storage::preferences::{PreferenceContext, PreferenceScope};
+
+/// Read `scope` (default "global") and `scope_id` params.
+fn scope_param(params: &Value) -> Result<PreferenceScope, Value> {
+    let level = params
+        .get("scope")
+        .and_then(|v| v.as_str())
+        .unwrap_or("global");
+    let id = params.get("scope_id").and_then(|v| v.as_str());
+    PreferenceScope::from_parts(level, id).map_err(|e| json!({"error": e.to_string()}))
+}
+
+/// Read the optional `workspace`, `project` and `session` lookup params.
+fn context_param(params: &Value) -> PreferenceContext {
+    let field = |name: &str| params.get(name).and_then(|v| v.as_str()).map(String::from);
+    PreferenceContext {
+        workspace: field("workspace"),
+        project: field("project"),
+        session: field("session"),
+    }
+}
+
+pub fn preference_set(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::preferences::set_preference;
+
+    let key = match params.get("key").and_then(|v| v.as_str()) {
+        Some(k) => k.to_string(),
+        None => return json!({"error": "key is required"}),
+    };
+    let value = match params.get("value") {
+        Some(v) if !v.is_null() => v.clone(),
+        _ => return json!({"error": "value is required"}),
+    };
+    let scope = match scope_param(&params) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    ctx.storage
+        .with_transaction(|conn| set_preference(conn, &key, value, &scope))
+        .map(|change| json!(change))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn preference_get(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::preferences::get_preference;
+
+    let key = match params.get("key").and_then(|v| v.as_str()) {
+        Some(k) => k.to_string(),
+        None => return json!({"error": "key is required"}),
+    };
+    let context = context_param(&params);
+
+    ctx.storage
+        .with_connection(|conn| get_preference(conn, &key, &context))
+        .map(|resolved| match resolved {
+            Some(resolved) => json!({
+                "found": true,
+                "key": resolved.preference.key,
+                "value": resolved.preference.value,
+                "scope": resolved.preference.scope,
+                "memory_id": resolved.preference.memory_id,
+                "overridden": resolved.overridden,
+            }),
+            None => json!({"found": false, "key": key}),
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn preference_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::preferences::list_preferences;
+
+    let namespace = params.get("namespace").and_then(|v| v.as_str());
+    let effective = params
+        .get("effective")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let context = context_param(&params);
+
+    ctx.storage
+        .with_connection(|conn| list_preferences(conn, namespace, effective.then_some(&context)))
+        .map(|preferences| json!({"preferences": preferences, "count": preferences.len()}))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn preference_history(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::preferences::preference_history;
+
+    let key = match params.get("key").and_then(|v| v.as_str()) {
+        Some(k) => k.to_string(),
+        None => return json!({"error": "key is required"}),
+    };
+    let scope = match scope_param(&params) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    ctx.storage
+        .with_connection(|conn| preference_history(conn, &key, &scope))
+        .map(|history| json!({"key": key, "scope": scope, "history": history}))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn preference_unset(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::preferences::unset_preference;
+
+    let key = match params.get("key").and_then(|v| v.as_str()) {
+        Some(k) => k.to_string(),
+        None => return json!({"error": "key is required"}),
+    };
+    let scope = match scope_param(&params) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    ctx.storage
+        .with_transaction(|conn| unset_preference(conn, &key, &scope))
+        .map(|removed| json!({"removed": removed}))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
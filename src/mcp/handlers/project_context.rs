@@ -139,6 +139,7 @@ pub fn scan_project(ctx: &HandlerContext, params: Value) -> Value {
                         update_memory(conn, existing_memory.id, &update_input)
                     }) {
                         Ok(updated) => {
+                            ctx.memory_cache.refresh(&updated);
                             result.memories_updated += 1;
                             updated.id
                         }
@@ -287,7 +288,10 @@ pub fn scan_project(ctx: &HandlerContext, params: Value) -> Value {
                         .storage
                         .with_transaction(|conn| update_memory(conn, existing.id, &update_input))
                     {
-                        Ok(_) => result.memories_updated += 1,
+                        Ok(updated) => {
+                            ctx.memory_cache.refresh(&updated);
+                            result.memories_updated += 1;
+                        }
                         Err(e) => {
                             result.errors.push(format!(
                                 "Failed to update section '{}': {}",
@@ -349,6 +353,7 @@ pub fn scan_project(ctx: &HandlerContext, params: Value) -> Value {
                         .with_transaction(|conn| delete_memory(conn, existing.id))
                     {
                         Ok(_) => {
+                            ctx.memory_cache.invalidate(existing.id);
                             tracing::info!("Deleted stale section: {}", path);
                         }
                         Err(e) => {
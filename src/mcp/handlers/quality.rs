@@ -66,6 +66,63 @@ pub fn quality_get_duplicates(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn quality_resolve_duplicate(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{resolve_duplicate_candidate, DuplicateVerdict};
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+
+    let verdict = match params.get("verdict").and_then(|v| v.as_str()) {
+        Some(v) => match v.parse::<DuplicateVerdict>() {
+            Ok(verdict) => verdict,
+            Err(e) => return json!({"error": e.to_string()}),
+        },
+        None => return json!({"error": "verdict is required"}),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let candidate = resolve_duplicate_candidate(conn, id, verdict)?;
+            Ok(json!(candidate))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_calibrate_dedup(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{calibrate_dedup_thresholds, CalibrationConfig};
+
+    let defaults = CalibrationConfig::default();
+    let config = CalibrationConfig {
+        workspace: params
+            .get("workspace")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        sample_pairs: params
+            .get("sample_pairs")
+            .and_then(|v| v.as_u64())
+            .map(|n| n.min(10_000) as usize)
+            .unwrap_or(defaults.sample_pairs),
+        seed: params
+            .get("seed")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(defaults.seed),
+        apply: params
+            .get("apply")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        ..defaults
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let workspaces = calibrate_dedup_thresholds(conn, &config)?;
+            Ok(json!({"applied": config.apply, "workspaces": workspaces}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 pub fn quality_find_conflicts(ctx: &HandlerContext, params: Value) -> Value {
     use crate::intelligence::{detect_conflicts, ContextQualityConfig};
 
@@ -122,16 +179,24 @@ pub fn quality_resolve_conflict(ctx: &HandlerContext, params: Value) -> Value {
 
     let notes = params.get("notes").and_then(|v| v.as_str());
 
-    ctx.storage
-        .with_transaction(|conn| {
-            resolve_conflict(conn, conflict_id, resolution_type, notes)?;
-            Ok(json!({
-                "conflict_id": conflict_id,
-                "resolution": resolution_str,
-                "resolved": true
-            }))
-        })
-        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+    let result = ctx.storage.with_transaction(|conn| {
+        resolve_conflict(conn, conflict_id, resolution_type, notes)?;
+        Ok(json!({
+            "conflict_id": conflict_id,
+            "resolution": resolution_str,
+            "resolved": true
+        }))
+    });
+
+    match result {
+        Ok(response) => {
+            // keep_a / keep_b supersede the losing memory
+            ctx.search_cache.clear();
+            ctx.memory_cache.clear();
+            response
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
 }
 
 pub fn quality_source_trust(ctx: &HandlerContext, params: Value) -> Value {
@@ -212,6 +277,172 @@ pub fn quality_improve(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+// ── Fact Verification ─────────────────────────────────────────────────────────
+
+pub fn memory_verify_fact(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{verify_fact, FactVerdict};
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+    let verdict: FactVerdict = match params.get("verdict").and_then(|v| v.as_str()) {
+        Some(v) => match v.parse() {
+            Ok(verdict) => verdict,
+            Err(e) => return json!({"error": format!("{}", e)}),
+        },
+        None => return json!({"error": "verdict is required"}),
+    };
+    let evidence_memory_id = params.get("evidence_memory_id").and_then(|v| v.as_i64());
+
+    let result = ctx
+        .storage
+        .with_transaction(|conn| verify_fact(conn, id, verdict, evidence_memory_id));
+
+    match result {
+        Ok(verification) => {
+            ctx.search_cache.invalidate_for_memory(id);
+            ctx.memory_cache.invalidate(id);
+            json!(verification)
+        }
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
+pub fn memory_escalate_unverified_facts(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{escalate_unverified_facts, EscalationConfig};
+
+    let defaults = EscalationConfig::default();
+    let config = EscalationConfig {
+        min_age_days: params
+            .get("min_age_days")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(defaults.min_age_days),
+        min_access_count: params
+            .get("min_access_count")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(defaults.min_access_count),
+        limit: params
+            .get("limit")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(defaults.limit),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let queued = escalate_unverified_facts(conn, &config)?;
+            Ok(json!({"queued": queued, "count": queued.len()}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_fact_review_queue(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::list_review_queue;
+
+    let include_resolved = params
+        .get("include_resolved")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let limit = params.get("limit").and_then(|v| v.as_i64()).unwrap_or(50);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let items = list_review_queue(conn, include_resolved, limit)?;
+            Ok(json!({"items": items, "count": items.len()}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+// ── Freshness Contracts ───────────────────────────────────────────────────────
+
+pub fn freshness_contract_set(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::set_freshness_contract;
+
+    let tag = match params.get("tag").and_then(|v| v.as_str()) {
+        Some(t) => t,
+        None => return json!({"error": "tag is required"}),
+    };
+    let max_age_days = match params.get("max_age_days").and_then(|v| v.as_i64()) {
+        Some(d) => d,
+        None => return json!({"error": "max_age_days is required"}),
+    };
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let penalty = params
+        .get("penalty")
+        .and_then(|v| v.as_f64())
+        .map(|p| p as f32);
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let contract = set_freshness_contract(conn, tag, workspace, max_age_days, penalty)?;
+            Ok(json!(contract))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn freshness_contract_list(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::intelligence::list_freshness_contracts;
+
+    ctx.storage
+        .with_connection(|conn| {
+            let contracts = list_freshness_contracts(conn)?;
+            Ok(json!({"contracts": contracts, "count": contracts.len()}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn freshness_contract_delete(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::delete_freshness_contract;
+
+    let id = match params.get("id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "id is required"}),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let deleted = delete_freshness_contract(conn, id)?;
+            Ok(json!({"id": id, "deleted": deleted}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_freshness_check(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::intelligence::check_freshness_contracts;
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let report = check_freshness_contracts(conn, chrono::Utc::now())?;
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn memory_stale_report(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{check_freshness_contracts, stale_report};
+
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let limit = params.get("limit").and_then(|v| v.as_i64()).unwrap_or(50);
+    let refresh = params
+        .get("refresh")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    ctx.storage
+        .with_transaction(|conn| {
+            if refresh {
+                check_freshness_contracts(conn, chrono::Utc::now())?;
+            }
+            let report = stale_report(conn, workspace, limit)?;
+            Ok(json!({
+                "count": report.stale.len(),
+                "stale": report.stale,
+                "contracts": report.contracts,
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 // ── Salience Tools ────────────────────────────────────────────────────────────
 
 pub fn salience_get(ctx: &HandlerContext, params: Value) -> Value {
@@ -2,16 +2,59 @@
 
 use serde_json::{json, Value};
 
-use crate::search::{hybrid_search, RerankConfig, RerankStrategy, Reranker};
+use crate::search::{hybrid_search_with_index, RerankConfig, RerankStrategy, Reranker};
 use crate::types::*;
 
 use super::HandlerContext;
 
 pub fn memory_search(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::apply_freshness_penalties;
+    use crate::search::boost_rules::{apply_boost_rules, list_boost_rules, BoostContext};
     use crate::search::result_cache::CacheFilterParams;
 
     let query = params.get("query").and_then(|v| v.as_str()).unwrap_or("");
-    let options: SearchOptions = serde_json::from_value(params.clone()).unwrap_or_default();
+    if let Some(s) = params.get("validation_status").and_then(|v| v.as_str()) {
+        if let Err(e) = s.parse::<ValidationStatus>() {
+            return json!({"error": e});
+        }
+    }
+    let mut options: SearchOptions = serde_json::from_value(params.clone()).unwrap_or_default();
+
+    // A natural-language `when` narrows results to memories created in it.
+    let time_range = match super::resolve_when(ctx, &params) {
+        Ok(range) => range,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+    if let Some(range) = &time_range {
+        let mut conditions: Vec<Value> = options.filter.take().into_iter().collect();
+        if let Some(after) = range.after {
+            conditions.push(json!({"created_at": {"gte": after.to_rfc3339()}}));
+        }
+        if let Some(before) = range.before {
+            conditions.push(json!({"created_at": {"lt": before.to_rfc3339()}}));
+        }
+        options.filter = match conditions.len() {
+            0 => None,
+            1 => conditions.pop(),
+            _ => Some(json!({"AND": conditions})),
+        };
+    }
+
+    // Time-travel search runs against the memory versions valid at `as_of`.
+    if let Some(as_of) = params.get("as_of").and_then(|v| v.as_str()) {
+        let as_of = match chrono::DateTime::parse_from_rfc3339(as_of) {
+            Ok(dt) => dt.with_timezone(&chrono::Utc),
+            Err(e) => return json!({"error": format!("Invalid as_of format: {}", e)}),
+        };
+        return ctx
+            .storage
+            .with_connection(|conn| {
+                let results = crate::storage::TemporalQueryEngine::new(conn)
+                    .search_at(query, as_of, &options)?;
+                Ok(json!(results))
+            })
+            .unwrap_or_else(|e| json!({"error": e.to_string()}));
+    }
 
     let rerank_enabled = params
         .get("rerank")
@@ -20,10 +63,17 @@ pub fn memory_search(ctx: &HandlerContext, params: Value) -> Value {
     let rerank_strategy = match params.get("rerank_strategy").and_then(|v| v.as_str()) {
         Some("none") => RerankStrategy::None,
         Some("multi_signal") => RerankStrategy::MultiSignal,
-        _ => RerankStrategy::Heuristic,
+        Some("cross_encoder") => RerankStrategy::CrossEncoder,
+        Some(_) => RerankStrategy::Heuristic,
+        None if ctx.cross_encoder.is_some() => RerankStrategy::CrossEncoder,
+        None => RerankStrategy::Heuristic,
     };
+    let rerank_top_k = params
+        .get("rerank_top_k")
+        .and_then(|v| v.as_u64())
+        .map(|k| k as usize);
 
-    let query_embedding = ctx.embedder.embed(query).ok();
+    let query_embedding = ctx.embedder.embed_query(query).ok();
     let embedding_ref = query_embedding.as_deref();
 
     let cache_filters = CacheFilterParams {
@@ -33,29 +83,147 @@ pub fn memory_search(ctx: &HandlerContext, params: Value) -> Value {
         include_archived: options.include_archived,
         include_transcripts: options.include_transcripts,
         tags: options.tags.clone(),
+        show_superseded: options.show_superseded,
+        validation_status: options.validation_status,
     };
 
-    let skip_cache = params
-        .get("skip_cache")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
+    // Experiment traffic and persona ranking profiles bypass the result
+    // cache, whose key ignores ranking config, and so do relative time
+    // ranges, whose key ignores filters.
+    let experiment_id = params.get("experiment_id").and_then(|v| v.as_i64());
+    let persona_ranking = ctx
+        .persona
+        .get()
+        .is_some_and(|p| p.ranking != Default::default());
+    let skip_cache = experiment_id.is_some()
+        || persona_ranking
+        || time_range.is_some()
+        || params
+            .get("skip_cache")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+    let cwd = std::env::current_dir()
+        .and_then(|p| p.canonicalize())
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
+
+    // Boost rules depend on the caller's context, so they are applied after
+    // the cache and never stored in it.
+    let boost_ctx = BoostContext {
+        cwd: cwd.clone(),
+        agent_id: params
+            .get("agent_id")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        workspace: options.workspace.clone(),
+        session_tags: params
+            .get("session_tags")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        now: chrono::Utc::now(),
+    };
+    let boost_rules = match ctx
+        .storage
+        .with_connection(|conn| list_boost_rules(conn, true))
+    {
+        Ok(rules) => rules,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
 
     if !skip_cache && !rerank_enabled {
-        if let Some(cached_results) = ctx.search_cache.get(query, embedding_ref, &cache_filters) {
+        if let Some(mut cached_results) = ctx.search_cache.get(query, embedding_ref, &cache_filters)
+        {
+            apply_boost_rules(&mut cached_results, &boost_rules, &boost_ctx);
+            if let Err(e) = ctx
+                .storage
+                .with_connection(|conn| apply_freshness_penalties(conn, &mut cached_results))
+            {
+                return json!({"error": e.to_string()});
+            }
             return json!({"results": cached_results, "cached": true});
         }
     }
 
-    let mut search_config = ctx.search_config.clone();
-    if let Ok(cwd) = std::env::current_dir() {
-        if let Ok(canonical) = cwd.canonicalize() {
-            search_config.project_context_path = Some(canonical.to_string_lossy().to_string());
+    let mut search_config = ctx.effective_search_config();
+    if cwd.is_some() {
+        search_config.project_context_path = cwd;
+    }
+
+    // Ranking experiment: route the query to an arm and apply its overrides.
+    let mut experiment_arm = None;
+    if let Some(id) = experiment_id {
+        use crate::search::experiments::{get_experiment, record_exposure};
+
+        let assigned = ctx.storage.with_connection(|conn| {
+            let experiment = get_experiment(conn, id)?;
+            if !experiment.is_active() {
+                return Ok(None);
+            }
+            if let (Some(exp_ws), Some(ws)) = (&experiment.workspace, &options.workspace) {
+                if exp_ws != ws {
+                    return Ok(None);
+                }
+            }
+            let arm = experiment.assign(query);
+            record_exposure(conn, id, arm, query)?;
+            Ok(Some((arm, experiment.variant(arm).apply(&search_config))))
+        });
+        match assigned {
+            Ok(Some((arm, config))) => {
+                search_config = config;
+                experiment_arm = Some(arm);
+            }
+            Ok(None) => {}
+            Err(e) => return json!({"error": e.to_string()}),
         }
     }
 
-    ctx.storage
+    // Without an explicit strategy, pick one from the workspace's learned
+    // thresholds and log it so reported outcomes can refine them.
+    let mut strategy_decision = None;
+    if options.strategy.is_none() {
+        use crate::search::strategy_learning::{choose_strategy, exploration_rate, record_served};
+
+        let decision = ctx.storage.with_connection(|conn| {
+            let decision = choose_strategy(
+                conn,
+                query,
+                options.workspace.as_deref(),
+                &search_config,
+                exploration_rate(),
+            )?;
+            record_served(conn, query, &decision)?;
+            Ok(decision)
+        });
+        match decision {
+            Ok(decision) => {
+                options.strategy = Some(decision.strategy);
+                strategy_decision = Some(decision);
+            }
+            Err(e) => return json!({"error": e.to_string()}),
+        }
+    }
+
+    // Memories overdue on a freshness contract are demoted after the cache,
+    // since flags change when the freshness check runs.
+    let mut freshness_penalties = Vec::new();
+    let result = ctx
+        .storage
         .with_connection(|conn| {
-            let results = hybrid_search(conn, query, embedding_ref, &options, &search_config)?;
+            let mut results = hybrid_search_with_index(
+                conn,
+                query,
+                embedding_ref,
+                &options,
+                &search_config,
+                Some(&ctx.vector_index),
+            )?;
 
             if !rerank_enabled && !skip_cache {
                 ctx.search_cache.put(
@@ -65,14 +233,19 @@ pub fn memory_search(ctx: &HandlerContext, params: Value) -> Value {
                     results.clone(),
                 );
             }
+            apply_boost_rules(&mut results, &boost_rules, &boost_ctx);
+            freshness_penalties = apply_freshness_penalties(conn, &mut results)?;
 
             if rerank_enabled && rerank_strategy != RerankStrategy::None {
                 let config = RerankConfig {
                     enabled: true,
                     strategy: rerank_strategy,
+                    cross_encoder_top_k: rerank_top_k
+                        .unwrap_or(RerankConfig::default().cross_encoder_top_k),
                     ..Default::default()
                 };
-                let reranker = Reranker::with_config(config);
+                let reranker =
+                    Reranker::with_config(config).with_cross_encoder(ctx.cross_encoder.clone());
                 let reranked = reranker.rerank(results, query, None);
 
                 if options.explain {
@@ -104,7 +277,34 @@ pub fn memory_search(ctx: &HandlerContext, params: Value) -> Value {
                 Ok(json!(results))
             }
         })
-        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}));
+
+    let mut extras = Vec::new();
+    if let (Some(id), Some(arm)) = (experiment_id, experiment_arm) {
+        extras.push(("experiment", json!({"id": id, "arm": arm})));
+    }
+    if let (true, Some(decision)) = (options.explain, strategy_decision) {
+        extras.push(("strategy_selection", json!(decision)));
+    }
+    if options.explain && !freshness_penalties.is_empty() {
+        extras.push(("freshness_penalties", json!(freshness_penalties)));
+    }
+    if extras.is_empty() {
+        return result;
+    }
+    let mut map = match result {
+        Value::Object(map) if !map.contains_key("error") => map,
+        Value::Array(results) => {
+            let mut map = serde_json::Map::new();
+            map.insert("results".to_string(), Value::Array(results));
+            map
+        }
+        other => return other,
+    };
+    for (key, value) in extras {
+        map.insert(key.to_string(), value);
+    }
+    Value::Object(map)
 }
 
 pub fn search_suggest(ctx: &HandlerContext, params: Value) -> Value {
@@ -137,6 +337,7 @@ pub fn memory_search_by_identity(ctx: &HandlerContext, params: Value) -> Value {
 }
 
 pub fn memory_session_search(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::session_indexing::TranscriptFilter;
     use crate::storage::search_sessions;
 
     let query = match params.get("query").and_then(|v| v.as_str()) {
@@ -150,10 +351,28 @@ pub fn memory_session_search(ctx: &HandlerContext, params: Value) -> Value {
         .get("limit")
         .and_then(|v| v.as_u64())
         .map(|v| v as usize);
+    let kind = match params.get("kind").and_then(|v| v.as_str()) {
+        Some(kind) => match kind.parse() {
+            Ok(kind) => Some(kind),
+            Err(e) => return json!({"error": e}),
+        },
+        None => None,
+    };
+    let filter = TranscriptFilter {
+        role: params
+            .get("role")
+            .and_then(|v| v.as_str())
+            .map(str::to_lowercase),
+        kind,
+        tool_name: params
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+    };
 
     ctx.storage
         .with_connection(|conn| {
-            let memories = search_sessions(conn, query, session_id, workspace, limit)?;
+            let memories = search_sessions(conn, query, session_id, workspace, &filter, limit)?;
             Ok(json!({"memories": memories}))
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
@@ -179,6 +398,105 @@ pub fn find_duplicates(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn cluster_duplicates(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{cluster_duplicates, DuplicateClusterConfig};
+
+    let defaults = DuplicateClusterConfig::default();
+    let usize_param = |name: &str, default: usize| {
+        params
+            .get(name)
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(default)
+    };
+    let config = DuplicateClusterConfig {
+        workspace: params
+            .get("workspace")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        threshold: params
+            .get("threshold")
+            .and_then(|v| v.as_f64())
+            .map(|t| t as f32)
+            .unwrap_or(defaults.threshold),
+        shingle_size: usize_param("shingle_size", defaults.shingle_size),
+        num_hashes: usize_param("num_hashes", defaults.num_hashes).min(1024),
+        bands: usize_param("bands", defaults.bands),
+        max_bucket_size: defaults.max_bucket_size,
+        min_group_size: usize_param("min_group_size", defaults.min_group_size),
+        limit: usize_param("limit", defaults.limit),
+        enqueue: params
+            .get("enqueue")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let report = cluster_duplicates(conn, &config)?;
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn check_duplicate(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::queries::find_by_content_hash;
+    use crate::storage::text_signatures::find_signature_matches;
+    use crate::types::{normalize_workspace, ContentHash, MemoryScope};
+
+    let content = params.get("content").and_then(|v| v.as_str());
+    let near_threshold = params
+        .get("near_threshold")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.8) as f32;
+    let hash = match (content, params.get("content_hash").and_then(|v| v.as_str())) {
+        (Some(content), _) => ContentHash::of(content),
+        (None, Some(hash)) => match hash.parse::<ContentHash>() {
+            Ok(hash) => hash,
+            Err(e) => return json!({"error": e}),
+        },
+        (None, None) => return json!({"error": "content or content_hash is required"}),
+    };
+    let scope: MemoryScope = match params.get("scope") {
+        Some(scope) => match serde_json::from_value(scope.clone()) {
+            Ok(scope) => scope,
+            Err(e) => return json!({"error": format!("Invalid scope: {}", e)}),
+        },
+        None => MemoryScope::Global,
+    };
+    let workspace = match params.get("workspace").and_then(|v| v.as_str()) {
+        Some(ws) => match normalize_workspace(ws) {
+            Ok(ws) => Some(ws),
+            Err(e) => return json!({"error": e.to_string()}),
+        },
+        None => None,
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let existing = find_by_content_hash(conn, hash.as_str(), &scope, workspace.as_deref())?;
+            let mut response = json!({
+                "content_hash": hash,
+                "duplicate": existing.is_some(),
+                "memory_id": existing.map(|m| m.id),
+            });
+            // Textual near-duplicates need the content itself, not just its hash
+            if let Some(content) = content {
+                let near = find_signature_matches(
+                    conn,
+                    content,
+                    &scope,
+                    workspace.as_deref().unwrap_or("default"),
+                    near_threshold,
+                    10,
+                )?;
+                response["near_duplicates"] = json!(near);
+            }
+            Ok(response)
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 pub fn find_semantic_duplicates(ctx: &HandlerContext, params: Value) -> Value {
     use crate::storage::queries::find_duplicates_by_embedding;
 
@@ -202,6 +520,37 @@ pub fn find_semantic_duplicates(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn similarity_matrix(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::{similarity_matrix, SimilarityMatrixOptions};
+
+    let options: SimilarityMatrixOptions = match serde_json::from_value(params.clone()) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+    let csv = match params.get("format").and_then(|v| v.as_str()) {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => return json!({"error": format!("Unknown format: {}", other)}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let matrix = similarity_matrix(conn, &options)?;
+            if !csv {
+                return Ok(json!(matrix));
+            }
+            Ok(json!({
+                "format": "csv",
+                "ids": matrix.ids,
+                "model": matrix.model,
+                "dimensions": matrix.dimensions,
+                "missing_embeddings": matrix.missing_embeddings,
+                "csv": matrix.to_csv(),
+            }))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 pub fn search_cache_feedback(ctx: &HandlerContext, params: Value) -> Value {
     use crate::search::CacheFilterParams;
 
@@ -369,6 +718,249 @@ pub fn memory_feedback_stats(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+// ── Ranking Experiments (A/B testing) ───────────────────────────────────────
+
+pub fn search_experiment_create(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::experiments::{create_experiment, RankingVariant};
+
+    let name = match params.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return json!({"error": "name is required"}),
+    };
+
+    let parse_variant = |key: &str| -> Result<RankingVariant, String> {
+        match params.get(key) {
+            Some(v) => serde_json::from_value(v.clone()).map_err(|e| format!("invalid {key}: {e}")),
+            None => Ok(RankingVariant::default()),
+        }
+    };
+    let variant_a = match parse_variant("variant_a") {
+        Ok(v) => v,
+        Err(e) => return json!({"error": e}),
+    };
+    let variant_b = match parse_variant("variant_b") {
+        Ok(v) => v,
+        Err(e) => return json!({"error": e}),
+    };
+
+    let traffic_b = params
+        .get("traffic_b")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.5);
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+
+    ctx.storage
+        .with_connection(|conn| {
+            let experiment =
+                create_experiment(conn, name, workspace, &variant_a, &variant_b, traffic_b)?;
+            Ok(json!(experiment))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn search_experiment_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::experiments::list_experiments;
+
+    let active_only = params
+        .get("active_only")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let experiments = list_experiments(conn, active_only)?;
+            Ok(json!({"count": experiments.len(), "experiments": experiments}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn search_experiment_stop(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::experiments::stop_experiment;
+
+    let experiment_id = match params.get("experiment_id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "experiment_id is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let experiment = stop_experiment(conn, experiment_id)?;
+            Ok(json!(experiment))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn search_experiment_outcome(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::experiments::{record_outcome, ExperimentOutcome};
+
+    let experiment_id = match params.get("experiment_id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "experiment_id is required"}),
+    };
+    let query = match params.get("query").and_then(|v| v.as_str()) {
+        Some(q) => q,
+        None => return json!({"error": "query is required"}),
+    };
+    let outcome = match params
+        .get("outcome")
+        .and_then(|v| v.as_str())
+        .map(ExperimentOutcome::parse)
+    {
+        Some(Ok(o)) => o,
+        _ => return json!({"error": "outcome must be 'used', 'useful' or 'irrelevant'"}),
+    };
+    let memory_id = params.get("memory_id").and_then(|v| v.as_i64());
+
+    ctx.storage
+        .with_connection(|conn| {
+            let arm = record_outcome(conn, experiment_id, query, outcome, memory_id)?;
+            Ok(json!({"recorded": true, "experiment_id": experiment_id, "arm": arm}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn search_experiment_report(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::experiments::experiment_report;
+
+    let experiment_id = match params.get("experiment_id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "experiment_id is required"}),
+    };
+    let min_outcomes = params
+        .get("min_outcomes")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(30);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let report = experiment_report(conn, experiment_id, min_outcomes)?;
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn search_strategy_outcome(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::strategy_learning::record_used;
+
+    let query = match params.get("query").and_then(|v| v.as_str()) {
+        Some(q) => q,
+        None => return json!({"error": "query is required"}),
+    };
+    let workspace = params
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default");
+
+    ctx.storage
+        .with_connection(|conn| {
+            let strategy = record_used(conn, query, workspace)?;
+            Ok(json!({"recorded": strategy.is_some(), "strategy": strategy}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn search_strategy_stats(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::strategy_learning::strategy_stats;
+
+    let workspace = params
+        .get("workspace")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default");
+
+    ctx.storage
+        .with_connection(|conn| Ok(json!(strategy_stats(conn, workspace)?)))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+// ── Contextual Boost Rules ───────────────────────────────────────────────────
+
+pub fn boost_rule_create(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::boost_rules::{create_boost_rule, BoostCondition, BoostTarget};
+
+    let name = match params.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return json!({"error": "name is required"}),
+    };
+    let weight = match params.get("weight").and_then(|v| v.as_f64()) {
+        Some(w) => w as f32,
+        None => return json!({"error": "weight is required"}),
+    };
+    let condition: BoostCondition = match params.get("condition") {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(c) => c,
+            Err(e) => return json!({"error": format!("invalid condition: {e}")}),
+        },
+        None => BoostCondition::default(),
+    };
+    let target: BoostTarget = match params.get("target") {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(t) => t,
+            Err(e) => return json!({"error": format!("invalid target: {e}")}),
+        },
+        None => return json!({"error": "target is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let rule = create_boost_rule(conn, name, &condition, &target, weight)?;
+            Ok(json!(rule))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn boost_rule_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::boost_rules::list_boost_rules;
+
+    let enabled_only = params
+        .get("enabled_only")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let rules = list_boost_rules(conn, enabled_only)?;
+            Ok(json!({"count": rules.len(), "rules": rules}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn boost_rule_update(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::boost_rules::update_boost_rule;
+
+    let rule_id = match params.get("rule_id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "rule_id is required"}),
+    };
+    let weight = params
+        .get("weight")
+        .and_then(|v| v.as_f64())
+        .map(|w| w as f32);
+    let enabled = params.get("enabled").and_then(|v| v.as_bool());
+
+    ctx.storage
+        .with_connection(|conn| {
+            let rule = update_boost_rule(conn, rule_id, weight, enabled)?;
+            Ok(json!(rule))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn boost_rule_delete(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::search::boost_rules::delete_boost_rule;
+
+    let rule_id = match params.get("rule_id").and_then(|v| v.as_i64()) {
+        Some(id) => id,
+        None => return json!({"error": "rule_id is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let deleted = delete_boost_rule(conn, rule_id)?;
+            Ok(json!({"deleted": deleted, "rule_id": rule_id}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 // ── Compact Search + Expand ──────────────────────────────────────────────────
 
 /// Return a compact summary of search results (id, title, created_at, tags).
@@ -394,10 +986,10 @@ pub fn memory_search_compact(ctx: &HandlerContext, params: Value) -> Value {
         options.limit = Some(limit_from_param.unwrap_or(10));
     }
 
-    let query_embedding = ctx.embedder.embed(query).ok();
+    let query_embedding = ctx.embedder.embed_query(query).ok();
     let embedding_ref = query_embedding.as_deref();
 
-    let mut search_config = ctx.search_config.clone();
+    let mut search_config = ctx.effective_search_config();
     if let Ok(cwd) = std::env::current_dir() {
         if let Ok(canonical) = cwd.canonicalize() {
             search_config.project_context_path = Some(canonical.to_string_lossy().to_string());
@@ -406,7 +998,14 @@ pub fn memory_search_compact(ctx: &HandlerContext, params: Value) -> Value {
 
     ctx.storage
         .with_connection(|conn| {
-            let results = hybrid_search(conn, query, embedding_ref, &options, &search_config)?;
+            let results = hybrid_search_with_index(
+                conn,
+                query,
+                embedding_ref,
+                &options,
+                &search_config,
+                Some(&ctx.vector_index),
+            )?;
 
             let compact: Vec<Value> = results
                 .iter()
@@ -3,37 +3,106 @@
 use serde_json::{json, Value};
 
 use super::HandlerContext;
+use crate::intelligence::session_indexing::{Message, MessageKind, TokenLimit};
+use crate::intelligence::{default_summarizer, DenoiseConfig, TiktokenCounter};
+
+/// Token limit requested by `max_tokens`, measured with the tokenizer for
+/// `model` or `encoding` (cl100k_base when neither is given)
+fn parse_token_limit(params: &Value) -> Result<Option<TokenLimit>, String> {
+    let Some(max_tokens) = params.get("max_tokens").and_then(|v| v.as_u64()) else {
+        return Ok(None);
+    };
+    let model = params.get("model").and_then(|v| v.as_str());
+    let encoding = params.get("encoding").and_then(|v| v.as_str());
+    let counter = match (model, encoding) {
+        (None, None) => TiktokenCounter::for_model("", Some("cl100k_base")),
+        (model, encoding) => TiktokenCounter::for_model(model.unwrap_or_default(), encoding),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(Some(TokenLimit {
+        max_tokens: (max_tokens as usize).max(1),
+        counter,
+    }))
+}
+
+/// De-noise config requested by `denoise`: `true` for the defaults, or an
+/// object overriding individual rules
+fn parse_denoise(params: &Value) -> Result<Option<DenoiseConfig>, String> {
+    match params.get("denoise") {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => Ok(None),
+        Some(Value::Bool(true)) => Ok(Some(DenoiseConfig::default())),
+        Some(value @ Value::Object(_)) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| format!("Invalid denoise config: {}", e)),
+        Some(_) => Err("denoise must be a boolean or an object".to_string()),
+    }
+}
+
+/// Parse the `messages` array of a session indexing call.
+///
+/// Messages without `role` are skipped. `content` may be omitted for tool
+/// calls. `kind` is inferred from the role and arguments when not given, and
+/// `name` is accepted as an alias for `tool_name` (OpenAI function messages).
+fn parse_messages(params: &Value) -> Result<Vec<Message>, String> {
+    let arr = params
+        .get("messages")
+        .and_then(|v| v.as_array())
+        .ok_or("messages array is required")?;
+
+    let mut messages = Vec::with_capacity(arr.len());
+    for m in arr {
+        let Some(role) = m.get("role").and_then(|r| r.as_str()) else {
+            continue;
+        };
+        let content = m
+            .get("content")
+            .and_then(|c| c.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let timestamp = m
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .unwrap_or_else(chrono::Utc::now);
+        let id = m.get("id").and_then(|i| i.as_str()).map(String::from);
+        let tool_name = m
+            .get("tool_name")
+            .or_else(|| m.get("name"))
+            .and_then(|t| t.as_str())
+            .map(String::from);
+        let arguments = m.get("arguments").filter(|a| !a.is_null()).cloned();
+        let kind = match m.get("kind").and_then(|k| k.as_str()) {
+            Some(kind) => kind.parse::<MessageKind>()?,
+            None => MessageKind::infer(role, arguments.is_some()),
+        };
+        if content.is_empty() && kind != MessageKind::ToolCall {
+            continue;
+        }
+        messages.push(Message {
+            role: role.to_string(),
+            content,
+            timestamp,
+            id,
+            kind,
+            tool_name,
+            arguments,
+        });
+    }
+    Ok(messages)
+}
 
 pub fn session_index(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::intelligence::session_indexing::{index_conversation, ChunkingConfig, Message};
+    use crate::intelligence::session_indexing::{index_conversation, ChunkingConfig};
 
     let session_id = match params.get("session_id").and_then(|v| v.as_str()) {
         Some(id) => id,
         None => return json!({"error": "session_id is required"}),
     };
 
-    let messages: Vec<Message> = match params.get("messages").and_then(|v| v.as_array()) {
-        Some(arr) => arr
-            .iter()
-            .filter_map(|m| {
-                let role = m.get("role")?.as_str()?.to_string();
-                let content = m.get("content")?.as_str()?.to_string();
-                let timestamp = m
-                    .get("timestamp")
-                    .and_then(|t| t.as_str())
-                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .unwrap_or_else(chrono::Utc::now);
-                let id = m.get("id").and_then(|i| i.as_str()).map(String::from);
-                Some(Message {
-                    role,
-                    content,
-                    timestamp,
-                    id,
-                })
-            })
-            .collect(),
-        None => return json!({"error": "messages array is required"}),
+    let messages = match parse_messages(&params) {
+        Ok(messages) => messages,
+        Err(e) => return json!({"error": e}),
     };
 
     if messages.is_empty() {
@@ -43,6 +112,14 @@ pub fn session_index(ctx: &HandlerContext, params: Value) -> Value {
     let title = params.get("title").and_then(|v| v.as_str());
     let workspace = params.get("workspace").and_then(|v| v.as_str());
     let agent_id = params.get("agent_id").and_then(|v| v.as_str());
+    let token_limit = match parse_token_limit(&params) {
+        Ok(limit) => limit,
+        Err(e) => return json!({"error": e}),
+    };
+    let denoise = match parse_denoise(&params) {
+        Ok(denoise) => denoise,
+        Err(e) => return json!({"error": e}),
+    };
 
     let config = ChunkingConfig {
         max_messages: params
@@ -58,6 +135,14 @@ pub fn session_index(ctx: &HandlerContext, params: Value) -> Value {
             * 24
             * 60
             * 60,
+        max_tool_result_chars: params
+            .get("max_tool_result_chars")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(2000) as usize,
+        token_limit,
+        denoise,
+        summarizer: Some(default_summarizer()),
+        ..Default::default()
     };
 
     ctx.storage
@@ -71,40 +156,29 @@ pub fn session_index(ctx: &HandlerContext, params: Value) -> Value {
 }
 
 pub fn session_index_delta(ctx: &HandlerContext, params: Value) -> Value {
-    use crate::intelligence::session_indexing::{
-        index_conversation_delta, ChunkingConfig, Message,
-    };
+    use crate::intelligence::session_indexing::{index_conversation_delta, ChunkingConfig};
 
     let session_id = match params.get("session_id").and_then(|v| v.as_str()) {
         Some(id) => id,
         None => return json!({"error": "session_id is required"}),
     };
 
-    let messages: Vec<Message> = match params.get("messages").and_then(|v| v.as_array()) {
-        Some(arr) => arr
-            .iter()
-            .filter_map(|m| {
-                let role = m.get("role")?.as_str()?.to_string();
-                let content = m.get("content")?.as_str()?.to_string();
-                let timestamp = m
-                    .get("timestamp")
-                    .and_then(|t| t.as_str())
-                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .unwrap_or_else(chrono::Utc::now);
-                let id = m.get("id").and_then(|i| i.as_str()).map(String::from);
-                Some(Message {
-                    role,
-                    content,
-                    timestamp,
-                    id,
-                })
-            })
-            .collect(),
-        None => return json!({"error": "messages array is required"}),
+    let messages = match parse_messages(&params) {
+        Ok(messages) => messages,
+        Err(e) => return json!({"error": e}),
     };
 
-    let config = ChunkingConfig::default();
+    // Without max_tokens or denoise, the session's recorded token limit and
+    // de-noise config (if any) apply
+    let config = match (parse_token_limit(&params), parse_denoise(&params)) {
+        (Ok(token_limit), Ok(denoise)) => ChunkingConfig {
+            token_limit,
+            denoise,
+            summarizer: Some(default_summarizer()),
+            ..Default::default()
+        },
+        (Err(e), _) | (_, Err(e)) => return json!({"error": e}),
+    };
 
     ctx.storage
         .with_connection(|conn| {
@@ -144,6 +218,38 @@ pub fn session_list(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn session_link_topics(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{find_session_topics, link_session_topics, TopicLinkConfig};
+
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let dry_run = params
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let mut config = TopicLinkConfig::default();
+    if let Some(n) = params.get("min_shared_entities").and_then(|v| v.as_u64()) {
+        config.min_shared_entities = (n as usize).max(1);
+    }
+    if let Some(f) = params.get("max_session_fraction").and_then(|v| v.as_f64()) {
+        config.max_session_fraction = (f as f32).clamp(0.0, 1.0);
+    }
+    if let Some(n) = params.get("max_sessions").and_then(|v| v.as_i64()) {
+        config.max_sessions = n.max(1);
+    }
+
+    let result = if dry_run {
+        ctx.storage
+            .with_connection(|conn| find_session_topics(conn, workspace, &config))
+    } else {
+        ctx.storage
+            .with_transaction(|conn| link_session_topics(conn, workspace, &config))
+    };
+    match result {
+        Ok(report) => json!({"dry_run": dry_run, "report": report}),
+        Err(e) => json!({"error": e.to_string()}),
+    }
+}
+
 pub fn session_delete(ctx: &HandlerContext, params: Value) -> Value {
     use crate::intelligence::session_indexing::delete_session;
 
@@ -438,3 +544,22 @@ pub fn session_context_export(ctx: &HandlerContext, params: Value) -> Value {
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
+
+pub fn recommendations_get(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::intelligence::{recommend_memories, RecommendationOptions};
+
+    let options: RecommendationOptions = match serde_json::from_value(params) {
+        Ok(options) => options,
+        Err(e) => return json!({"error": format!("Invalid parameters: {}", e)}),
+    };
+    if options.session_id.is_none() && options.context.is_none() && options.used_ids.is_empty() {
+        return json!({"error": "session_id, context or used_ids is required"});
+    }
+
+    ctx.storage
+        .with_connection(|conn| {
+            let set = recommend_memories(conn, Some(ctx.embedder.as_ref()), &options)?;
+            Ok(json!(set))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
@@ -0,0 +1,811 @@
+//! Static site export handler — a workspace as a browsable website.
+//!
+//! Writes an index page with client-side search, one page per memory with
+//! its links and backlinks, and a graph page. Everything is a plain file:
+//! open `index.html` from disk or copy the directory to any static host.
+//! Search runs on a MiniSearch index prebuilt here (`search-index.json`);
+//! the same data also ships as `search-index.js` so it loads over
+//! `file://`, and a small built-in scorer takes over when the MiniSearch
+//! script can't be fetched from the CDN.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use serde_json::{json, Value};
+
+use super::markdown_export::{pluralize_type, sanitize_filename};
+use super::HandlerContext;
+use crate::graph::{KnowledgeGraph, RenderOptions, StyleRegistry};
+use crate::types::{CrossReference, Memory, MemoryId};
+
+/// Most memories written to one site
+const SITE_MAX_MEMORIES: i64 = 10_000;
+/// Nodes drawn on the graph page unless `graph_max_nodes` says otherwise
+const DEFAULT_GRAPH_MAX_NODES: usize = 300;
+const MAX_GRAPH_NODES: usize = 2_000;
+/// Longest title taken from a memory's first line
+const MAX_TITLE_CHARS: usize = 80;
+/// Fields of the search index, in MiniSearch field-id order
+const SEARCH_FIELDS: [&str; 3] = ["title", "content", "tags"];
+const MINISEARCH_URL: &str = "https://cdn.jsdelivr.net/npm/minisearch@6.3.0/dist/umd/index.min.js";
+
+/// Export a workspace as a static website.
+///
+/// Params:
+/// - `workspace` (string, required) — workspace to export
+/// - `output_dir` (string, optional) — output directory
+///   (default: `./engram-site/{workspace}/`)
+/// - `title` (string, optional) — site title (default: the workspace name)
+/// - `include_graph` (bool, optional, default true) — write `graph.html`
+/// - `graph_max_nodes` (integer, optional, default 300) — most important
+///   memories drawn on the graph page
+pub fn memory_export_site(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::queries::{get_related, list_memories};
+    use crate::types::ListOptions;
+
+    let workspace = match params.get("workspace").and_then(|v| v.as_str()) {
+        Some(w) => w.to_string(),
+        None => return json!({"error": "workspace is required"}),
+    };
+
+    let default_dir = format!("./engram-site/{}", workspace);
+    let output_dir = params
+        .get("output_dir")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&default_dir);
+    let output_path = PathBuf::from(output_dir);
+
+    let options = SiteOptions {
+        title: params
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .unwrap_or(&workspace)
+            .to_string(),
+        workspace: workspace.clone(),
+        include_graph: params
+            .get("include_graph")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+        graph_max_nodes: params
+            .get("graph_max_nodes")
+            .and_then(|v| v.as_u64())
+            .map(|n| (n as usize).clamp(1, MAX_GRAPH_NODES))
+            .unwrap_or(DEFAULT_GRAPH_MAX_NODES),
+    };
+
+    let loaded = ctx.storage.with_connection(|conn| {
+        let memories = list_memories(
+            conn,
+            &ListOptions {
+                limit: Some(SITE_MAX_MEMORIES),
+                workspace: Some(workspace.clone()),
+                ..Default::default()
+            },
+        )?;
+        let mut crossrefs = Vec::new();
+        let mut seen = HashSet::new();
+        for memory in &memories {
+            for crossref in get_related(conn, memory.id)? {
+                let key = (
+                    crossref.from_id,
+                    crossref.to_id,
+                    crossref.edge_type.as_str(),
+                );
+                if seen.insert(key) {
+                    crossrefs.push(crossref);
+                }
+            }
+        }
+        Ok((memories, crossrefs))
+    });
+    let (memories, crossrefs) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => return json!({"error": format!("Failed to query memories: {}", e)}),
+    };
+
+    if memories.is_empty() {
+        return json!({
+            "error": format!("No memories found in workspace '{}'", workspace),
+            "files_written": 0
+        });
+    }
+
+    let site = build_site(&memories, &crossrefs, &options);
+
+    if let Err(e) = fs::create_dir_all(output_path.join("memories")) {
+        return json!({"error": format!("Failed to create output directory: {}", e)});
+    }
+    for (relative, contents) in &site.files {
+        let path = output_path.join(relative);
+        if let Err(e) = fs::write(&path, contents) {
+            return json!({"error": format!("Failed to write {}: {}", path.display(), e)});
+        }
+    }
+
+    json!({
+        "files_written": site.files.len(),
+        "output_dir": output_path.to_string_lossy(),
+        "index_path": output_path.join("index.html").to_string_lossy(),
+        "memories_exported": memories.len(),
+        "links_exported": site.links,
+        "search_terms": site.search_terms,
+        "graph": options.include_graph,
+        "truncated": memories.len() as i64 >= SITE_MAX_MEMORIES
+    })
+}
+
+/// What to put in the site
+struct SiteOptions {
+    title: String,
+    workspace: String,
+    include_graph: bool,
+    graph_max_nodes: usize,
+}
+
+/// Rendered site: paths relative to the output directory and their contents
+struct Site {
+    files: Vec<(String, String)>,
+    links: usize,
+    search_terms: usize,
+}
+
+/// A memory's page in the site
+struct Page<'a> {
+    memory: &'a Memory,
+    title: String,
+    /// File name inside `memories/`
+    file: String,
+}
+
+/// Render every file of the site
+fn build_site(memories: &[Memory], crossrefs: &[CrossReference], options: &SiteOptions) -> Site {
+    let pages: Vec<Page> = memories
+        .iter()
+        .map(|memory| {
+            let title = memory_title(&memory.content);
+            let file = format!("{}-{}.html", memory.id, sanitize_filename(&title));
+            Page {
+                memory,
+                title,
+                file,
+            }
+        })
+        .collect();
+    let by_id: HashMap<MemoryId, &Page> = pages.iter().map(|p| (p.memory.id, p)).collect();
+
+    // Only links between exported memories; self-loops add nothing to a page
+    let links: Vec<&CrossReference> = crossrefs
+        .iter()
+        .filter(|c| c.from_id != c.to_id)
+        .filter(|c| by_id.contains_key(&c.from_id) && by_id.contains_key(&c.to_id))
+        .collect();
+
+    let mut files = Vec::with_capacity(pages.len() + 6);
+    for page in &pages {
+        let outgoing: Vec<(&str, &Page)> = links
+            .iter()
+            .filter(|c| c.from_id == page.memory.id)
+            .map(|c| (c.edge_type.as_str(), by_id[&c.to_id]))
+            .collect();
+        let incoming: Vec<(&str, &Page)> = links
+            .iter()
+            .filter(|c| c.to_id == page.memory.id)
+            .map(|c| (c.edge_type.as_str(), by_id[&c.from_id]))
+            .collect();
+        files.push((
+            format!("memories/{}", page.file),
+            memory_page(page, &outgoing, &incoming, options),
+        ));
+    }
+
+    let index = build_search_index(&pages);
+    let search_terms = index["index"].as_array().map_or(0, Vec::len);
+    let index_json = index.to_string();
+
+    files.push(("index.html".to_string(), index_page(&pages, options)));
+    if options.include_graph {
+        files.push((
+            "graph.html".to_string(),
+            graph_page(memories, crossrefs, &pages, options),
+        ));
+    }
+    files.push((
+        "search-index.js".to_string(),
+        format!("window.ENGRAM_SEARCH_INDEX = {};\n", index_json),
+    ));
+    files.push(("search-index.json".to_string(), index_json));
+    files.push(("search.js".to_string(), SEARCH_JS.to_string()));
+    files.push(("style.css".to_string(), STYLE_CSS.to_string()));
+
+    Site {
+        files,
+        links: links.len(),
+        search_terms,
+    }
+}
+
+/// Page title for a memory: its first non-empty line without heading marks
+fn memory_title(content: &str) -> String {
+    let line = content
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("Untitled");
+    let mut title: String = line.chars().take(MAX_TITLE_CHARS).collect();
+    if line.chars().count() > MAX_TITLE_CHARS {
+        title.push('…');
+    }
+    title
+}
+
+/// Split text into lowercase search terms. `search.js` tokenizes queries
+/// the same way, so both sides agree on what a term is.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Build a search index in MiniSearch's serialized form (version 2), so the
+/// browser loads it with `MiniSearch.loadJSON` instead of indexing the
+/// whole workspace on every page view.
+fn build_search_index(pages: &[Page]) -> Value {
+    // term -> field id -> short document id -> term frequency
+    let mut postings: BTreeMap<String, BTreeMap<usize, BTreeMap<usize, u32>>> = BTreeMap::new();
+    let mut document_ids = serde_json::Map::new();
+    let mut field_length = serde_json::Map::new();
+    let mut stored_fields = serde_json::Map::new();
+    let mut length_totals = [0usize; SEARCH_FIELDS.len()];
+
+    for (short_id, page) in pages.iter().enumerate() {
+        let fields = [
+            page.title.clone(),
+            page.memory.content.clone(),
+            page.memory.tags.join(" "),
+        ];
+        let mut lengths = Vec::with_capacity(fields.len());
+        for (field_id, text) in fields.iter().enumerate() {
+            let terms = tokenize(text);
+            let unique: BTreeSet<&String> = terms.iter().collect();
+            lengths.push(unique.len());
+            length_totals[field_id] += unique.len();
+            for term in terms {
+                *postings
+                    .entry(term)
+                    .or_default()
+                    .entry(field_id)
+                    .or_default()
+                    .entry(short_id)
+                    .or_default() += 1;
+            }
+        }
+        document_ids.insert(short_id.to_string(), json!(page.memory.id.to_string()));
+        field_length.insert(short_id.to_string(), json!(lengths));
+        stored_fields.insert(
+            short_id.to_string(),
+            json!({
+                "title": page.title,
+                "path": format!("memories/{}", page.file),
+                "memory_type": page.memory.memory_type.as_str(),
+                "tags": page.memory.tags,
+            }),
+        );
+    }
+
+    let document_count = pages.len();
+    let average_field_length: Vec<f64> = length_totals
+        .iter()
+        .map(|&total| total as f64 / document_count.max(1) as f64)
+        .collect();
+    let field_ids: serde_json::Map<String, Value> = SEARCH_FIELDS
+        .iter()
+        .enumerate()
+        .map(|(id, name)| (name.to_string(), json!(id)))
+        .collect();
+    let index: Vec<Value> = postings
+        .into_iter()
+        .map(|(term, fields)| {
+            let fields: serde_json::Map<String, Value> = fields
+                .into_iter()
+                .map(|(field_id, docs)| {
+                    let docs: serde_json::Map<String, Value> = docs
+                        .into_iter()
+                        .map(|(doc, freq)| (doc.to_string(), json!(freq)))
+                        .collect();
+                    (field_id.to_string(), Value::Object(docs))
+                })
+                .collect();
+            json!([term, fields])
+        })
+        .collect();
+
+    json!({
+        "documentCount": document_count,
+        "nextId": document_count,
+        "documentIds": document_ids,
+        "fieldIds": field_ids,
+        "fieldLength": field_length,
+        "averageFieldLength": average_field_length,
+        "storedFields": stored_fields,
+        "dirtCount": 0,
+        "index": index,
+        "serializationVersion": 2
+    })
+}
+
+/// Render memory content as HTML. Raw HTML in the content is shown as text
+/// and script-capable link targets are dropped, so a page can't run code.
+fn render_markdown(content: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+        let lower = url.trim_start().to_ascii_lowercase();
+        if ["javascript:", "vbscript:", "data:"]
+            .iter()
+            .any(|scheme| lower.starts_with(scheme))
+        {
+            CowStr::Borrowed("#")
+        } else {
+            url
+        }
+    }
+    let parser = Parser::new_ext(content, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        other => other,
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, parser);
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Wrap a page body in the shared layout. `root` is the relative path back
+/// to the site root ("" or "../").
+fn layout(title: &str, root: &str, body: &str, options: &SiteOptions) -> String {
+    let graph_link = if options.include_graph {
+        format!(" <a href=\"{root}graph.html\">Graph</a>")
+    } else {
+        String::new()
+    };
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<link rel=\"stylesheet\" href=\"{root}style.css\">
+</head>
+<body>
+<header><a class=\"site\" href=\"{root}index.html\">{site}</a><nav><a href=\"{root}index.html\">Index</a>{graph_link}</nav></header>
+<main>
+{body}</main>
+<footer>Exported from Engram workspace <code>{workspace}</code> on {date}</footer>
+</body>
+</html>
+",
+        title = escape(title),
+        site = escape(&options.title),
+        workspace = escape(&options.workspace),
+        date = Utc::now().format("%Y-%m-%d"),
+    )
+}
+
+fn tag_list(tags: &[String]) -> String {
+    tags.iter()
+        .map(|t| format!("<span class=\"tag\">{}</span>", escape(t)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Index page: search box plus every memory grouped by type
+fn index_page(pages: &[Page], options: &SiteOptions) -> String {
+    let mut by_type: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+    for page in pages {
+        by_type
+            .entry(page.memory.memory_type.as_str())
+            .or_default()
+            .push(page);
+    }
+
+    let mut body = format!(
+        "<h1>{}</h1>
+<input id=\"search\" type=\"search\" placeholder=\"Search {} memories\" autocomplete=\"off\">
+<ul id=\"results\"></ul>
+<div id=\"all\">
+",
+        escape(&options.title),
+        pages.len()
+    );
+    for (memory_type, mut group) in by_type {
+        group.sort_by_key(|p| p.title.to_lowercase());
+        body.push_str(&format!(
+            "<section>\n<h2>{} ({})</h2>\n<ul>\n",
+            escape(&pluralize_type(memory_type)),
+            group.len()
+        ));
+        for page in group {
+            body.push_str(&format!(
+                "<li><a href=\"memories/{}\">{}</a> {}</li>\n",
+                escape(&page.file),
+                escape(&page.title),
+                tag_list(&page.memory.tags)
+            ));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+    body.push_str(&format!(
+        "</div>
+<script src=\"search-index.js\"></script>
+<script src=\"{MINISEARCH_URL}\"></script>
+<script src=\"search.js\"></script>
+"
+    ));
+    layout(&options.title, "", &body, options)
+}
+
+/// One memory: rendered content, metadata, links and backlinks
+fn memory_page(
+    page: &Page,
+    outgoing: &[(&str, &Page)],
+    incoming: &[(&str, &Page)],
+    options: &SiteOptions,
+) -> String {
+    let memory = page.memory;
+    let mut body = format!(
+        "<article>
+<h1>{}</h1>
+<p class=\"meta\">{} · importance {:.2} · created {} · updated {}</p>
+<p class=\"tags\">{}</p>
+<div class=\"content\">
+{}</div>
+</article>
+",
+        escape(&page.title),
+        escape(memory.memory_type.as_str()),
+        memory.importance,
+        memory.created_at.format("%Y-%m-%d"),
+        memory.updated_at.format("%Y-%m-%d"),
+        tag_list(&memory.tags),
+        render_markdown(&memory.content)
+    );
+    for (heading, links) in [("Links", outgoing), ("Backlinks", incoming)] {
+        if links.is_empty() {
+            continue;
+        }
+        body.push_str(&format!(
+            "<section class=\"links\">\n<h2>{}</h2>\n<ul>\n",
+            heading
+        ));
+        for (edge_type, other) in links {
+            body.push_str(&format!(
+                "<li><span class=\"edge\">{}</span> <a href=\"{}\">{}</a></li>\n",
+                escape(edge_type),
+                escape(&other.file),
+                escape(&other.title)
+            ));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+    layout(&page.title, "../", &body, options)
+}
+
+/// Graph page: the most important memories drawn as an SVG whose nodes
+/// link to their pages
+fn graph_page(
+    memories: &[Memory],
+    crossrefs: &[CrossReference],
+    pages: &[Page],
+    options: &SiteOptions,
+) -> String {
+    let mut drawn: Vec<Memory> = memories.to_vec();
+    drawn.sort_by(|a, b| b.importance.total_cmp(&a.importance).then(a.id.cmp(&b.id)));
+    drawn.truncate(options.graph_max_nodes);
+    // Edges to memories left out are dropped by the graph builder
+    let graph = KnowledgeGraph::from_data(&drawn, crossrefs);
+    let render = RenderOptions {
+        node_links: pages
+            .iter()
+            .map(|p| (p.memory.id, format!("memories/{}", p.file)))
+            .collect(),
+        ..Default::default()
+    };
+    let svg = graph.to_svg(StyleRegistry::global(), None, &render);
+    let note = if drawn.len() < memories.len() {
+        format!(
+            "<p class=\"meta\">Showing the {} most important of {} memories.</p>\n",
+            drawn.len(),
+            memories.len()
+        )
+    } else {
+        String::new()
+    };
+    let body = format!("<h1>Graph</h1>\n{note}<div class=\"graph\">\n{svg}</div>\n");
+    layout(&format!("{} — Graph", options.title), "", &body, options)
+}
+
+const STYLE_CSS: &str = r#"body { margin: 0; font-family: system-ui, sans-serif; color: #1e293b; background: #f8fafc; line-height: 1.5; }
+header { display: flex; justify-content: space-between; align-items: center; padding: 0.75rem 1.5rem; background: #1e293b; }
+header a { color: #f8fafc; text-decoration: none; margin-left: 1rem; }
+header a.site { font-weight: 600; margin-left: 0; }
+main { max-width: 60rem; margin: 0 auto; padding: 1.5rem; }
+footer { max-width: 60rem; margin: 0 auto; padding: 1.5rem; color: #64748b; font-size: 0.85rem; }
+a { color: #2563eb; }
+#search { width: 100%; box-sizing: border-box; padding: 0.6rem 0.8rem; font-size: 1rem; border: 1px solid #cbd5e1; border-radius: 6px; }
+#results:empty { display: none; }
+.searching #all { display: none; }
+.meta, .edge { color: #64748b; font-size: 0.9rem; }
+.tag { display: inline-block; padding: 0 0.4rem; margin-right: 0.2rem; border-radius: 4px; background: #e2e8f0; font-size: 0.8rem; }
+.content { background: #ffffff; border: 1px solid #e2e8f0; border-radius: 6px; padding: 0.5rem 1.25rem; }
+.content pre { overflow-x: auto; background: #f1f5f9; padding: 0.75rem; }
+.graph { overflow: auto; background: #ffffff; border: 1px solid #e2e8f0; border-radius: 6px; }
+"#;
+
+const SEARCH_JS: &str = r#"(function () {
+  var FIELDS = ["title", "content", "tags"];
+  var STORE = ["title", "path", "memory_type", "tags"];
+  var BOOST = { title: 2, tags: 1.5 };
+  var tokenize = function (text) { return text.split(/[^\p{L}\p{N}]+/u); };
+  var data = window.ENGRAM_SEARCH_INDEX;
+  var input = document.getElementById("search");
+  var results = document.getElementById("results");
+  if (!data || !input) { return; }
+
+  var search = null;
+  if (window.MiniSearch) {
+    try {
+      var engine = window.MiniSearch.loadJSON(JSON.stringify(data), {
+        fields: FIELDS,
+        storeFields: STORE,
+        tokenize: tokenize,
+        searchOptions: { prefix: true, fuzzy: 0.2, boost: BOOST }
+      });
+      search = function (query) { return engine.search(query); };
+    } catch (e) {
+      search = null;
+    }
+  }
+  if (!search) {
+    // Offline fallback: prefix match over the same prebuilt index
+    var weights = FIELDS.map(function (field) { return BOOST[field] || 1; });
+    search = function (query) {
+      var scores = {};
+      tokenize(query.toLowerCase()).filter(Boolean).forEach(function (term) {
+        data.index.forEach(function (entry) {
+          if (entry[0].indexOf(term) !== 0) { return; }
+          Object.keys(entry[1]).forEach(function (field) {
+            var docs = entry[1][field];
+            Object.keys(docs).forEach(function (doc) {
+              scores[doc] = (scores[doc] || 0) + docs[doc] * weights[field];
+            });
+          });
+        });
+      });
+      return Object.keys(scores)
+        .sort(function (a, b) { return scores[b] - scores[a]; })
+        .map(function (doc) { return data.storedFields[doc]; });
+    };
+  }
+
+  function render(hits) {
+    results.textContent = "";
+    hits.slice(0, 50).forEach(function (hit) {
+      var item = document.createElement("li");
+      var link = document.createElement("a");
+      link.href = hit.path;
+      link.textContent = hit.title;
+      var meta = document.createElement("span");
+      meta.className = "meta";
+      meta.textContent = " " + hit.memory_type + (hit.tags.length ? " · " + hit.tags.join(", ") : "");
+      item.appendChild(link);
+      item.appendChild(meta);
+      results.appendChild(item);
+    });
+  }
+
+  input.addEventListener("input", function () {
+    var query = input.value.trim();
+    document.body.classList.toggle("searching", query.length > 0);
+    render(query ? search(query) : []);
+  });
+})();
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_crossref, create_memory, get_related, list_memories};
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType, ListOptions};
+
+    fn options() -> SiteOptions {
+        SiteOptions {
+            title: "Handbook".to_string(),
+            workspace: "handbook".to_string(),
+            include_graph: true,
+            graph_max_nodes: DEFAULT_GRAPH_MAX_NODES,
+        }
+    }
+
+    fn load() -> (Vec<Memory>, Vec<CrossReference>) {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let memory = |content: &str, tags: &[&str]| {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            tags: tags.iter().map(|t| t.to_string()).collect(),
+                            workspace: Some("handbook".to_string()),
+                            ..Default::default()
+                        },
+                    )
+                };
+                let a = memory("# Deploy process\nShip with `make release`.", &["ops"])?;
+                let b = memory("Rollback plan <script>x</script>", &["ops", "incident"])?;
+                create_crossref(
+                    conn,
+                    &CreateCrossRefInput {
+                        from_id: a.id,
+                        to_id: b.id,
+                        edge_type: EdgeType::DependsOn,
+                        strength: None,
+                        source_context: None,
+                        pinned: false,
+                    },
+                )?;
+                let memories = list_memories(
+                    conn,
+                    &ListOptions {
+                        workspace: Some("handbook".to_string()),
+                        ..Default::default()
+                    },
+                )?;
+                Ok((memories, get_related(conn, a.id)?))
+            })
+            .unwrap()
+    }
+
+    fn file<'a>(site: &'a Site, prefix: &str) -> &'a str {
+        &site
+            .files
+            .iter()
+            .find(|(path, _)| path.starts_with(prefix))
+            .unwrap_or_else(|| panic!("no file {}", prefix))
+            .1
+    }
+
+    #[test]
+    fn test_memory_title_and_tokenize() {
+        assert_eq!(memory_title("\n## Deploy process\nbody"), "Deploy process");
+        assert_eq!(memory_title("   "), "Untitled");
+        assert_eq!(
+            memory_title(&"x".repeat(100)).chars().count(),
+            MAX_TITLE_CHARS + 1
+        );
+        assert_eq!(
+            tokenize("Use `cargo-test`, NOT make!"),
+            vec!["use", "cargo", "test", "not", "make"]
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_neutralizes_html_and_scripts() {
+        let html =
+            render_markdown("**bold** <b>raw</b> [x](javascript:alert(1)) [ok](https://a.b)");
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("&lt;b&gt;raw&lt;/b&gt;"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("href=\"https://a.b\""));
+    }
+
+    #[test]
+    fn test_site_pages_links_and_backlinks() {
+        let (memories, crossrefs) = load();
+        let site = build_site(&memories, &crossrefs, &options());
+        assert_eq!(site.links, 1);
+
+        let deploy = memories
+            .iter()
+            .find(|m| m.content.contains("Deploy"))
+            .unwrap();
+        let rollback = memories
+            .iter()
+            .find(|m| m.content.contains("Rollback"))
+            .unwrap();
+        let deploy_page = file(&site, &format!("memories/{}-", deploy.id));
+        let rollback_page = file(&site, &format!("memories/{}-", rollback.id));
+
+        assert!(deploy_page.contains("<h1>Deploy process</h1>"));
+        assert!(deploy_page.contains("<h2>Links</h2>"));
+        assert!(deploy_page.contains(&format!("href=\"{}-rollback-plan", rollback.id)));
+        assert!(!deploy_page.contains("<h2>Backlinks</h2>"));
+        assert!(rollback_page.contains("<h2>Backlinks</h2>"));
+        assert!(!rollback_page.contains("<script>"));
+        assert!(rollback_page.contains("href=\"../style.css\""));
+
+        let index = file(&site, "index.html");
+        assert!(index.contains(&format!(
+            "href=\"memories/{}-deploy-process.html\"",
+            deploy.id
+        )));
+        assert!(index.contains("src=\"search-index.js\""));
+        assert!(file(&site, "graph.html").contains("<a href=\"memories/"));
+
+        let without_graph = build_site(
+            &memories,
+            &crossrefs,
+            &SiteOptions {
+                include_graph: false,
+                ..options()
+            },
+        );
+        assert!(without_graph
+            .files
+            .iter()
+            .all(|(path, _)| path != "graph.html"));
+    }
+
+    #[test]
+    fn test_search_index_is_minisearch_json() {
+        let (memories, crossrefs) = load();
+        let site = build_site(&memories, &crossrefs, &options());
+        let index: Value = serde_json::from_str(file(&site, "search-index.json")).unwrap();
+
+        assert_eq!(index["serializationVersion"], 2);
+        assert_eq!(index["documentCount"], 2);
+        assert_eq!(
+            index["fieldIds"],
+            json!({"title": 0, "content": 1, "tags": 2})
+        );
+        let ops = index["index"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry[0] == "ops")
+            .unwrap();
+        // Both memories carry the tag, once each
+        assert_eq!(ops[1]["2"], json!({"0": 1, "1": 1}));
+        assert_eq!(site.search_terms, index["index"].as_array().unwrap().len());
+
+        let stored = &index["storedFields"]["0"];
+        assert!(stored["path"].as_str().unwrap().starts_with("memories/"));
+        assert!(file(&site, "search-index.js").starts_with("window.ENGRAM_SEARCH_INDEX = {"));
+    }
+}
@@ -179,6 +179,8 @@ pub fn snapshot_load(ctx: &HandlerContext, params: Value) -> Value {
 
     match result {
         Ok(load_result) => {
+            // A merge or replace can rewrite any memory we have cached.
+            ctx.memory_cache.clear();
             // Phase L: log attestation for the loaded snapshot manifest (best-effort).
             // Use the raw snapshot archive bytes as document content so the hash
             // matches what an external verifier would compute over the .egm file.
@@ -59,6 +59,12 @@ pub fn embedding_cache_clear(ctx: &HandlerContext, _params: Value) -> Value {
     })
 }
 
+// ── Memory Cache ──────────────────────────────────────────────────────────────
+
+pub fn memory_read_cache_stats(ctx: &HandlerContext, _params: Value) -> Value {
+    json!(ctx.memory_cache.stats())
+}
+
 // ── Content Utilities ─────────────────────────────────────────────────────────
 
 pub fn memory_soft_trim(ctx: &HandlerContext, params: Value) -> Value {
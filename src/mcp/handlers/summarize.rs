@@ -191,6 +191,124 @@ pub fn context_budget_check(ctx: &HandlerContext, params: Value) -> Value {
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
 
+pub fn context_score(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::embedding::get_embedding;
+    use crate::intelligence::compression::TiktokenCounter;
+    use crate::intelligence::context_builder::{SimpleTokenCounter, TokenCounter};
+    use crate::intelligence::context_quality::{calculate_quality_score, ContextQualityConfig};
+    use crate::intelligence::context_scoring::{
+        score_context, ContextCandidate, ContextScoreConfig,
+    };
+    use crate::storage::queries::get_memory;
+
+    let task = match params.get("task").and_then(|v| v.as_str()) {
+        Some(t) if !t.trim().is_empty() => t,
+        _ => return json!({"error": "task is required"}),
+    };
+
+    let memory_ids: Vec<i64> = match params.get("memory_ids") {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(ids) => ids,
+            Err(e) => return json!({"error": format!("Invalid memory_ids: {}", e)}),
+        },
+        None => Vec::new(),
+    };
+    let snippets: Vec<String> = match params.get("snippets") {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(s) => s,
+            Err(e) => return json!({"error": format!("Invalid snippets: {}", e)}),
+        },
+        None => Vec::new(),
+    };
+    if memory_ids.is_empty() && snippets.is_empty() {
+        return json!({"error": "memory_ids or snippets is required"});
+    }
+
+    let counter: Box<dyn TokenCounter> = match params.get("model").and_then(|v| v.as_str()) {
+        Some(model) => {
+            let encoding = params.get("encoding").and_then(|v| v.as_str());
+            match TiktokenCounter::for_model(model, encoding) {
+                Ok(counter) => Box::new(counter),
+                Err(e) => return json!({"error": e.to_string()}),
+            }
+        }
+        None => Box::new(SimpleTokenCounter),
+    };
+
+    let defaults = ContextScoreConfig::default();
+    let config = ContextScoreConfig {
+        budget: params
+            .get("budget")
+            .and_then(|v| v.as_u64())
+            .map(|b| b as usize),
+        relevance_weight: params
+            .get("relevance_weight")
+            .and_then(|v| v.as_f64())
+            .map(|w| w as f32)
+            .unwrap_or(defaults.relevance_weight),
+        quality_weight: params
+            .get("quality_weight")
+            .and_then(|v| v.as_f64())
+            .map(|w| w as f32)
+            .unwrap_or(defaults.quality_weight),
+        redundancy_threshold: params
+            .get("redundancy_threshold")
+            .and_then(|v| v.as_f64())
+            .map(|t| t as f32)
+            .unwrap_or(defaults.redundancy_threshold),
+        min_relevance: params
+            .get("min_relevance")
+            .and_then(|v| v.as_f64())
+            .map(|t| t as f32)
+            .unwrap_or(defaults.min_relevance),
+    };
+
+    // Without a usable embedding, relevance falls back to term overlap
+    let task_embedding = ctx.embedder.embed_query(task).ok();
+    let snippet_refs: Vec<&str> = snippets.iter().map(String::as_str).collect();
+    let snippet_embeddings = if snippet_refs.is_empty() {
+        Vec::new()
+    } else {
+        ctx.embedder.embed_batch(&snippet_refs).unwrap_or_default()
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let quality_config = ContextQualityConfig::default();
+            let mut candidates = Vec::with_capacity(memory_ids.len() + snippets.len());
+
+            for id in &memory_ids {
+                let memory = match get_memory(conn, *id) {
+                    Ok(memory) => memory,
+                    Err(_) => return Ok(json!({"error": format!("Memory {} not found", id)})),
+                };
+                candidates.push(ContextCandidate {
+                    memory_id: Some(*id),
+                    content: memory.content,
+                    quality: calculate_quality_score(conn, *id, &quality_config)
+                        .ok()
+                        .map(|q| q.overall),
+                    embedding: get_embedding(conn, *id).ok().flatten(),
+                });
+            }
+            for (i, snippet) in snippets.iter().enumerate() {
+                let mut candidate = ContextCandidate::snippet(snippet.clone());
+                candidate.embedding = snippet_embeddings.get(i).cloned();
+                candidates.push(candidate);
+            }
+
+            let report = score_context(
+                task,
+                task_embedding.as_deref(),
+                &candidates,
+                counter.as_ref(),
+                &config,
+            );
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
 pub fn memory_archive_old(ctx: &HandlerContext, params: Value) -> Value {
     use crate::storage::queries::{create_memory, list_memories};
     use crate::types::{CreateMemoryInput, ListOptions, MemoryTier, MemoryType};
@@ -70,3 +70,309 @@ pub fn workspace_delete(ctx: &HandlerContext, params: Value) -> Value {
         })
         .unwrap_or_else(|e| json!({"error": e.to_string()}))
 }
+
+pub fn workspace_merge(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{merge_workspaces, MergeOptions};
+
+    let from = match params.get("from").and_then(|v| v.as_str()) {
+        Some(ws) => ws,
+        None => return json!({"error": "from is required"}),
+    };
+    let into = match params.get("into").and_then(|v| v.as_str()) {
+        Some(ws) => ws,
+        None => return json!({"error": "into is required"}),
+    };
+    let options = MergeOptions {
+        origin_tag: params
+            .get("origin_tag")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        dedup: params
+            .get("dedup")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+        dry_run: params
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let report = merge_workspaces(conn, from, into, &options)?;
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_split(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::split_workspace;
+    use crate::types::ListOptions;
+
+    let source = match params.get("workspace").and_then(|v| v.as_str()) {
+        Some(ws) => ws.to_string(),
+        None => return json!({"error": "workspace is required"}),
+    };
+    let new_workspace = match params.get("new_workspace").and_then(|v| v.as_str()) {
+        Some(ws) => ws.to_string(),
+        None => return json!({"error": "new_workspace is required"}),
+    };
+    let dry_run = params
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let filter: ListOptions = match serde_json::from_value(params) {
+        Ok(f) => f,
+        Err(e) => return json!({"error": format!("Invalid filter: {}", e)}),
+    };
+
+    ctx.storage
+        .with_transaction(|conn| {
+            let report = split_workspace(conn, &source, &new_workspace, &filter, dry_run)?;
+            Ok(json!(report))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_config_set(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{set_workspace_config, SetWorkspaceConfigInput};
+
+    let input: SetWorkspaceConfigInput = match serde_json::from_value(params) {
+        Ok(i) => i,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| Ok(json!(set_workspace_config(conn, &input)?)))
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_config_get(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::get_workspace_config;
+
+    let workspace = match params.get("workspace").and_then(|v| v.as_str()) {
+        Some(ws) => ws,
+        None => return json!({"error": "workspace is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let config = get_workspace_config(conn, workspace)?;
+            Ok(json!({"workspace": workspace, "config": config}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_config_list(ctx: &HandlerContext, _params: Value) -> Value {
+    use crate::storage::list_workspace_configs;
+
+    ctx.storage
+        .with_connection(|conn| {
+            let configs = list_workspace_configs(conn)?;
+            Ok(json!({"count": configs.len(), "configs": configs}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_config_delete(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::delete_workspace_config;
+
+    let workspace = match params.get("workspace").and_then(|v| v.as_str()) {
+        Some(ws) => ws,
+        None => return json!({"error": "workspace is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let deleted = delete_workspace_config(conn, workspace)?;
+            Ok(json!({"success": deleted, "workspace": workspace}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_share_create(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::{create_workspace_share, CreateWorkspaceShareInput};
+
+    let input: CreateWorkspaceShareInput = match serde_json::from_value(params) {
+        Ok(i) => i,
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let share = create_workspace_share(conn, &input)?;
+            Ok(json!({"path": share.path(), "share": share}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_share_list(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::list_workspace_shares;
+
+    let workspace = params.get("workspace").and_then(|v| v.as_str());
+    let include_inactive = params
+        .get("include_inactive")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    ctx.storage
+        .with_connection(|conn| {
+            let shares = list_workspace_shares(conn, workspace, include_inactive)?;
+            Ok(json!({"count": shares.len(), "shares": shares}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+pub fn workspace_share_revoke(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::storage::revoke_workspace_share;
+
+    let token = match params.get("token").and_then(|v| v.as_str()) {
+        Some(t) => t,
+        None => return json!({"error": "token is required"}),
+    };
+
+    ctx.storage
+        .with_connection(|conn| {
+            let revoked = revoke_workspace_share(conn, token)?;
+            Ok(json!({"success": revoked, "token": token}))
+        })
+        .unwrap_or_else(|e| json!({"error": e.to_string()}))
+}
+
+/// Most search results a share link returns per request
+const SHARE_MAX_RESULTS: i64 = 50;
+
+/// Most graph nodes a share link returns per request
+const SHARE_MAX_NODES: i64 = 500;
+
+/// The fields of a memory a share link exposes; metadata, scope and access
+/// history stay private.
+fn shared_memory(memory: &crate::types::Memory) -> Value {
+    json!({
+        "id": memory.id,
+        "content": memory.content,
+        "memory_type": memory.memory_type,
+        "tags": memory.tags,
+        "created_at": memory.created_at,
+        "updated_at": memory.updated_at,
+    })
+}
+
+/// Read-only view of a shared workspace, as served on `/public/:token`.
+/// Unknown, revoked and expired tokens all answer "Share not found".
+pub fn workspace_share_view(ctx: &HandlerContext, params: Value) -> Value {
+    use crate::graph::KnowledgeGraph;
+    use crate::search::hybrid_search_with_index;
+    use crate::storage::queries::{get_related, get_workspace_stats, list_memories};
+    use crate::storage::resolve_workspace_share;
+    use crate::types::{ListOptions, SearchOptions};
+
+    let token = match params.get("token").and_then(|v| v.as_str()) {
+        Some(t) => t,
+        None => return json!({"error": "token is required"}),
+    };
+    let view = params
+        .get("view")
+        .and_then(|v| v.as_str())
+        .unwrap_or("info");
+    let limit = |key: &str, default: i64, max: i64| {
+        params
+            .get(key)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(default)
+            .clamp(1, max)
+    };
+
+    let share = match ctx
+        .storage
+        .with_connection(|conn| resolve_workspace_share(conn, token, chrono::Utc::now()))
+    {
+        Ok(Some(share)) => share,
+        Ok(None) => return json!({"error": "Share not found"}),
+        Err(e) => return json!({"error": e.to_string()}),
+    };
+
+    match view {
+        "info" => ctx
+            .storage
+            .with_connection(|conn| {
+                let memory_count = match get_workspace_stats(conn, &share.workspace) {
+                    Ok(stats) => stats.memory_count,
+                    Err(crate::error::EngramError::NotFound(_)) => 0,
+                    Err(e) => return Err(e),
+                };
+                Ok(json!({
+                    "workspace": share.workspace,
+                    "label": share.label,
+                    "memory_count": memory_count,
+                    "requests_per_minute": share.requests_per_minute,
+                    "expires_at": share.expires_at,
+                }))
+            })
+            .unwrap_or_else(|e| json!({"error": e.to_string()})),
+        "search" => {
+            let query = match params.get("query").and_then(|v| v.as_str()) {
+                Some(q) if !q.trim().is_empty() => q,
+                _ => return json!({"error": "query is required"}),
+            };
+            let options = SearchOptions {
+                limit: Some(limit("limit", 20, SHARE_MAX_RESULTS)),
+                workspace: Some(share.workspace.clone()),
+                ..Default::default()
+            };
+            let query_embedding = ctx.embedder.embed_query(query).ok();
+            let config = ctx.effective_search_config();
+            ctx.storage
+                .with_connection(|conn| {
+                    let results = hybrid_search_with_index(
+                        conn,
+                        query,
+                        query_embedding.as_deref(),
+                        &options,
+                        &config,
+                        Some(&ctx.vector_index),
+                    )?;
+                    let results: Vec<Value> = results
+                        .iter()
+                        .filter(|r| r.memory.workspace == share.workspace)
+                        .map(|r| {
+                            let mut memory = shared_memory(&r.memory);
+                            memory["score"] = json!(r.score);
+                            memory
+                        })
+                        .collect();
+                    Ok(json!({
+                        "workspace": share.workspace,
+                        "query": query,
+                        "count": results.len(),
+                        "results": results,
+                    }))
+                })
+                .unwrap_or_else(|e| json!({"error": e.to_string()}))
+        }
+        "graph" => {
+            let options = ListOptions {
+                limit: Some(limit("max_nodes", 200, SHARE_MAX_NODES)),
+                workspace: Some(share.workspace.clone()),
+                ..Default::default()
+            };
+            ctx.storage
+                .with_connection(|conn| {
+                    let memories = list_memories(conn, &options)?;
+                    let mut crossrefs = Vec::new();
+                    for memory in &memories {
+                        crossrefs.extend(get_related(conn, memory.id)?);
+                    }
+                    // Edges to memories outside the share are dropped here
+                    let graph = KnowledgeGraph::from_data(&memories, &crossrefs);
+                    let mut value = graph.to_visjs_json();
+                    value["workspace"] = json!(share.workspace);
+                    Ok(value)
+                })
+                .unwrap_or_else(|e| json!({"error": e.to_string()}))
+        }
+        other => {
+            json!({"error": format!("Unknown view '{}': expected info, search or graph", other)})
+        }
+    }
+}
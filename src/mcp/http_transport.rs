@@ -3,17 +3,33 @@
 //! Provides an axum-based HTTP server that accepts JSON-RPC requests at `POST /mcp`
 //! and forwards them to the same `McpHandler` used by the stdio transport.
 //!
-//! Also provides a `GET /v1/events` SSE endpoint for real-time event streaming.
+//! Also provides a `GET /v1/events` SSE endpoint for real-time event streaming,
+//! and `GET /v1/memories/:id/content` for streaming large memory content as a
+//! chunked response. `GET /v1/graph/ws` streams knowledge graph mutations
+//! over a WebSocket, and `GET /v1/graph/nodes/:id` (with `/neighbors` and
+//! `/cluster`) lets graph views load large graphs a node at a time. With the `otel` feature, `POST /v1/traces` receives
+//! OTLP/HTTP JSON trace exports. The optional web dashboard is mounted under
+//! `/ui`.
+//!
+//! Workspaces published with `workspace_share_create` are readable without
+//! the API key under `GET /public/:token`, rate limited per share link.
+//!
+//! Besides the server-wide API key, `POST /mcp` accepts per-user API keys
+//! (see [`crate::auth`]); those calls only see and run the tools their
+//! permissions allow.
 
+use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::{
-    extract::{Query, State},
-    http::{HeaderMap, StatusCode},
+    body::Body,
+    extract::{ws::WebSocketUpgrade, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{
         sse::{Event, KeepAlive, Sse},
-        IntoResponse,
+        IntoResponse, Response,
     },
     routing::{get, post},
     Json, Router,
@@ -25,7 +41,10 @@ use tokio_stream::{Stream, StreamExt};
 use tower_http::cors::{Any, CorsLayer};
 
 use super::protocol::{McpHandler, McpRequest, McpResponse};
+use crate::auth::AuthContext;
+use crate::budget;
 use crate::realtime::{EventType, RealtimeEvent, RealtimeManager};
+use crate::types::ContentRange;
 
 /// Shared application state for all axum handlers.
 #[derive(Clone)]
@@ -33,6 +52,7 @@ struct AppState {
     handler: Arc<dyn McpHandler>,
     api_key: Option<String>,
     realtime: Option<RealtimeManager>,
+    share_limiter: Arc<ShareRateLimiter>,
 }
 
 // ---------------------------------------------------------------------------
@@ -46,20 +66,38 @@ async fn handle_mcp(
     headers: HeaderMap,
     Json(request): Json<McpRequest>,
 ) -> impl IntoResponse {
-    // Auth check
-    if let Some(ref expected) = state.api_key {
-        if !check_bearer(&headers, expected) {
-            let err = McpResponse::error(request.id, -32000, "Unauthorized".to_string());
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::to_value(err).unwrap_or_default()),
-            );
-        }
-    }
+    // Auth check: the server-wide key has full access; API keys issued
+    // per user are limited to their permissions
+    let caller = match state.api_key {
+        Some(ref expected) if check_bearer(&headers, expected) => Caller::Unrestricted,
+        ref api_key => match bearer_token(&headers).and_then(|t| state.handler.authenticate(t)) {
+            Some(auth) => Caller::Authenticated(auth),
+            None if api_key.is_none() => Caller::Unrestricted,
+            None => {
+                let err = McpResponse::error(request.id, -32000, "Unauthorized".to_string());
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::to_value(err).unwrap_or_default()),
+                );
+            }
+        },
+    };
 
     // Notifications have no id — process for side effects, return 202 Accepted
-    let is_notification = request.id.is_none();
-    let response = state.handler.handle_request(request);
+    let id = request.id.clone();
+    let is_notification = id.is_none();
+    // Tools block on storage and embedding calls, so run them off the
+    // async workers
+    let handler = state.handler.clone();
+    let scope = request_scope(&headers, &caller);
+    let response = tokio::task::spawn_blocking(move || {
+        budget::with_request_scope(&scope, || match caller {
+            Caller::Unrestricted => handler.handle_request(request),
+            Caller::Authenticated(auth) => handler.handle_request_as(request, &auth),
+        })
+    })
+    .await
+    .unwrap_or_else(|e| McpResponse::error(id, -32603, format!("Internal error: {}", e)));
     if is_notification {
         return (StatusCode::ACCEPTED, Json(serde_json::Value::Null));
     }
@@ -125,6 +163,7 @@ fn parse_event_type(s: &str) -> Option<EventType> {
         "sync_started" => Some(EventType::SyncStarted),
         "sync_completed" => Some(EventType::SyncCompleted),
         "sync_failed" => Some(EventType::SyncFailed),
+        "memory_suggested" => Some(EventType::MemorySuggested),
         _ => None,
     }
 }
@@ -140,6 +179,7 @@ fn event_type_to_str(et: EventType) -> &'static str {
         EventType::SyncStarted => "sync_started",
         EventType::SyncCompleted => "sync_completed",
         EventType::SyncFailed => "sync_failed",
+        EventType::MemorySuggested => "memory_suggested",
     }
 }
 
@@ -282,21 +322,669 @@ async fn handle_events(
         .keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(30))))
 }
 
+// ---------------------------------------------------------------------------
+// Graph mutation WebSocket
+// ---------------------------------------------------------------------------
+
+/// Query parameters for the `GET /v1/graph/ws` endpoint.
+#[derive(Debug, Clone, Deserialize)]
+struct GraphWsQuery {
+    /// API key, for browsers that cannot set headers on a WebSocket upgrade.
+    token: Option<String>,
+}
+
+/// `GET /v1/graph/ws` — WebSocket stream of graph mutations.
+///
+/// Each message is a JSON `GraphEvent` (`node_added`, `node_updated`,
+/// `node_removed`, `edge_added`, `edge_removed`, `cluster_changed` or
+/// `reset`). There is no replay: after a `reset`, or on reconnecting,
+/// clients re-fetch the graph with `memory_export_graph`. Clients may also
+/// send `explore` messages (see [`crate::realtime::stream_graph`]) to load
+/// parts of the graph on demand.
+///
+/// Requires `Authorization: Bearer <token>` or `?token=<token>` when the
+/// server was started with an API key.
+async fn handle_graph_ws(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<GraphWsQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, StatusCode> {
+    if let Some(ref expected) = state.api_key {
+        if !check_bearer(&headers, expected) && query.token.as_deref() != Some(expected.as_str()) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    let manager = state.realtime.ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    Ok(ws.on_upgrade(move |socket| crate::realtime::stream_graph(socket, manager)))
+}
+
+// ---------------------------------------------------------------------------
+// Graph exploration
+// ---------------------------------------------------------------------------
+
+/// Query parameters for the `GET /v1/graph/nodes/:id` endpoints.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ExploreQuery {
+    /// Neighbours or cluster members per page.
+    limit: Option<usize>,
+    /// Start of the page (the previous answer's `next_offset`).
+    offset: Option<usize>,
+    /// Comma-separated link types to follow (neighbors only).
+    edge_types: Option<String>,
+    /// Comma-separated ids of the nodes the view already shows (neighbors
+    /// only).
+    known: Option<String>,
+}
+
+impl ExploreQuery {
+    /// `memory_graph_explore` arguments for `action` on memory `id`.
+    fn arguments(&self, action: &str, id: i64) -> Result<serde_json::Value, String> {
+        let list = |value: &Option<String>| -> Vec<String> {
+            value
+                .iter()
+                .flat_map(|v| v.split(','))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let known = list(&self.known)
+            .iter()
+            .map(|id| id.parse::<i64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| "known must be comma-separated memory ids".to_string())?;
+        Ok(json!({
+            "action": action,
+            "id": id,
+            "limit": self.limit,
+            "offset": self.offset.unwrap_or(0),
+            "edge_types": list(&self.edge_types),
+            "known": known,
+        }))
+    }
+}
+
+/// Run the `memory_graph_explore` tool, returning its result or error text.
+fn explore_graph(
+    handler: &dyn McpHandler,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let request = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(0)),
+        method: "tools/call".to_string(),
+        params: json!({"name": "memory_graph_explore", "arguments": arguments}),
+    };
+    let response = handler.handle_request(request);
+    if let Some(err) = response.error {
+        return Err(err.message);
+    }
+    let text = response
+        .result
+        .as_ref()
+        .and_then(|r| r.pointer("/content/0/text"))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "memory_graph_explore returned no content".to_string())?;
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(err) = value.get("error") {
+        return Err(err
+            .as_str()
+            .unwrap_or("memory_graph_explore failed")
+            .to_string());
+    }
+    Ok(value)
+}
+
+/// Answer one graph exploration request: `404` for unknown memories, `400`
+/// for bad parameters.
+async fn serve_explore(
+    state: AppState,
+    headers: HeaderMap,
+    action: &'static str,
+    id: i64,
+    query: ExploreQuery,
+) -> Response {
+    if let Some(ref expected) = state.api_key {
+        if !check_bearer(&headers, expected) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+    let arguments = match query.arguments(action, id) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": message}))).into_response()
+        }
+    };
+    let handler = state.handler.clone();
+    let result = tokio::task::spawn_blocking(move || explore_graph(handler.as_ref(), arguments))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    match result {
+        Ok(value) => (StatusCode::OK, Json(value)).into_response(),
+        Err(message) => {
+            let status = if message.starts_with("Memory not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            (status, Json(json!({"error": message}))).into_response()
+        }
+    }
+}
+
+/// `GET /v1/graph/nodes/:id` -- a memory's content, link counts per type and
+/// community (without members), for the graph view's detail panel.
+///
+/// Requires `Authorization: Bearer <token>` when the server was started with an API key.
+async fn handle_graph_node(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Response {
+    serve_explore(state, headers, "details", id, ExploreQuery::default()).await
+}
+
+/// `GET /v1/graph/nodes/:id/neighbors` -- a page of a memory's neighbours,
+/// strongest links first, with the links among them and to known nodes.
+///
+/// Query parameters:
+/// - `limit` / `offset` — page (default 50 neighbours, max 500)
+/// - `edge_types` — comma-separated link types to follow
+/// - `known` — comma-separated ids the view already shows; they aren't sent
+///   again, but links to them are
+///
+/// Same auth as `GET /v1/graph/nodes/:id`.
+async fn handle_graph_neighbors(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Query(query): Query<ExploreQuery>,
+) -> Response {
+    serve_explore(state, headers, "expand", id, query).await
+}
+
+/// `GET /v1/graph/nodes/:id/cluster` -- a page of the members of a memory's
+/// community (`limit` default 100, max 1000; `offset`). `null` when the
+/// memory has no links. Same auth as `GET /v1/graph/nodes/:id`.
+async fn handle_graph_cluster(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Query(query): Query<ExploreQuery>,
+) -> Response {
+    serve_explore(state, headers, "cluster", id, query).await
+}
+
+// ---------------------------------------------------------------------------
+// Memory content streaming
+// ---------------------------------------------------------------------------
+
+/// Default number of characters per streamed chunk.
+const DEFAULT_CONTENT_CHUNK_CHARS: usize = 64 * 1024;
+
+/// Upper bound on the `chunk_size` query parameter.
+const MAX_CONTENT_CHUNK_CHARS: usize = 1024 * 1024;
+
+/// Query parameters for the `GET /v1/memories/:id/content` endpoint.
+#[derive(Debug, Clone, Deserialize)]
+struct ContentQuery {
+    /// Characters fetched from storage per chunk (default 64 Ki, max 1 Mi).
+    chunk_size: Option<usize>,
+    /// Character offset to start streaming from (default 0).
+    offset: Option<usize>,
+}
+
+/// Fetch one content range through the `memory_get` tool so the stream goes
+/// through the same handler (and storage) as every other request.
+fn fetch_content_range(
+    handler: &dyn McpHandler,
+    id: i64,
+    offset: usize,
+    length: usize,
+) -> Result<ContentRange, String> {
+    let request = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(0)),
+        method: "tools/call".to_string(),
+        params: json!({
+            "name": "memory_get",
+            "arguments": {"id": id, "offset": offset, "length": length}
+        }),
+    };
+    let response = handler.handle_request(request);
+    if let Some(err) = response.error {
+        return Err(err.message);
+    }
+    let text = response
+        .result
+        .as_ref()
+        .and_then(|r| r.pointer("/content/0/text"))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "memory_get returned no content".to_string())?;
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(err) = value.get("error") {
+        return Err(err.as_str().unwrap_or("memory_get failed").to_string());
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// [`fetch_content_range`] on the blocking pool, as tools block on storage
+async fn fetch_content_range_blocking(
+    handler: Arc<dyn McpHandler>,
+    id: i64,
+    offset: usize,
+    length: usize,
+) -> Result<ContentRange, String> {
+    tokio::task::spawn_blocking(move || fetch_content_range(handler.as_ref(), id, offset, length))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()))
+}
+
+/// Status for a failed content read: only a missing memory is a 404
+fn content_error_status(message: &str) -> StatusCode {
+    if message.starts_with("Memory not found") {
+        StatusCode::NOT_FOUND
+    } else if message.starts_with("Invalid input") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// `GET /v1/memories/:id/content` -- stream a memory's content as a chunked
+/// `text/plain` body.
+///
+/// The total size is reported up front in the `X-Engram-Content-Chars` and
+/// `X-Engram-Content-Bytes` headers; `X-Engram-Content-Offset` echoes the
+/// starting offset so interrupted downloads can resume with `?offset=`.
+///
+/// Query parameters:
+/// - `chunk_size` — characters read from storage per chunk
+/// - `offset` — character offset to start from
+///
+/// Requires `Authorization: Bearer <token>` when the server was started with an API key.
+async fn handle_memory_content(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Query(query): Query<ContentQuery>,
+) -> Result<Response, StatusCode> {
+    if let Some(ref expected) = state.api_key {
+        if !check_bearer(&headers, expected) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    let chunk_size = query
+        .chunk_size
+        .unwrap_or(DEFAULT_CONTENT_CHUNK_CHARS)
+        .clamp(1, MAX_CONTENT_CHUNK_CHARS);
+    let offset = query.offset.unwrap_or(0);
+
+    // Read the first chunk eagerly so a missing memory is a 404 rather than
+    // an empty 200, and so the size headers are known before the body starts.
+    let first = fetch_content_range_blocking(state.handler.clone(), id, offset, chunk_size)
+        .await
+        .map_err(|e| content_error_status(&e))?;
+
+    let handler = state.handler.clone();
+    let rest = futures::stream::unfold(first.next_offset, move |next| {
+        let handler = handler.clone();
+        async move {
+            let offset = next?;
+            match fetch_content_range_blocking(handler, id, offset, chunk_size).await {
+                Ok(range) => Some((Ok(range.content), range.next_offset)),
+                Err(e) => Some((Err(std::io::Error::other(e)), None)),
+            }
+        }
+    });
+    let body = Body::from_stream(tokio_stream::once(Ok(first.content)).chain(rest));
+
+    let mut response = Response::new(body);
+    let out = response.headers_mut();
+    out.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    out.insert(
+        "x-engram-content-chars",
+        HeaderValue::from(first.total_chars),
+    );
+    out.insert(
+        "x-engram-content-bytes",
+        HeaderValue::from(first.total_bytes),
+    );
+    out.insert("x-engram-content-offset", HeaderValue::from(offset));
+    Ok(response)
+}
+
+/// Query parameters for `POST /v1/traces`.
+#[cfg(feature = "otel")]
+#[derive(Debug, Deserialize, Default)]
+struct TracesQuery {
+    /// Workspace to create memories in.
+    workspace: Option<String>,
+}
+
+/// Ingest an OTLP trace export through the `otel_ingest_traces` tool and
+/// build the OTLP response: `{}` when every span was handled, a
+/// `partialSuccess` naming the failures otherwise.
+#[cfg(feature = "otel")]
+fn ingest_traces(
+    handler: &dyn McpHandler,
+    mut body: serde_json::Value,
+    workspace: Option<String>,
+) -> Result<serde_json::Value, String> {
+    if let (Some(object), Some(workspace)) = (body.as_object_mut(), workspace) {
+        object.insert("workspace".to_string(), json!(workspace));
+    }
+    let request = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(0)),
+        method: "tools/call".to_string(),
+        params: json!({"name": "otel_ingest_traces", "arguments": body}),
+    };
+    let response = handler.handle_request(request);
+    if let Some(err) = response.error {
+        return Err(err.message);
+    }
+    let text = response
+        .result
+        .as_ref()
+        .and_then(|r| r.pointer("/content/0/text"))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "otel_ingest_traces returned no content".to_string())?;
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(err) = value.get("error") {
+        return Err(err
+            .as_str()
+            .unwrap_or("otel_ingest_traces failed")
+            .to_string());
+    }
+    let errors: Vec<&str> = value["errors"]
+        .as_array()
+        .map(|errors| errors.iter().filter_map(|e| e.as_str()).collect())
+        .unwrap_or_default();
+    if errors.is_empty() {
+        return Ok(json!({}));
+    }
+    Ok(json!({
+        "partialSuccess": {
+            "rejectedSpans": errors.iter().filter(|e| e.starts_with("Span ")).count(),
+            "errorMessage": errors.join("; ")
+        }
+    }))
+}
+
+/// `POST /v1/traces` -- OTLP/HTTP trace receiver.
+///
+/// Accepts `application/json` `ExportTraceServiceRequest` bodies (set
+/// `OTEL_EXPORTER_OTLP_PROTOCOL=http/json` on the exporter); protobuf bodies
+/// are answered with `415 Unsupported Media Type`. GenAI spans become memories,
+/// see the `otel_ingest_traces` tool.
+///
+/// Query parameters:
+/// - `workspace` — workspace to create memories in
+///
+/// Requires `Authorization: Bearer <token>` when the server was started with an API key.
+#[cfg(feature = "otel")]
+async fn handle_otlp_traces(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TracesQuery>,
+    body: axum::body::Bytes,
+) -> Response {
+    if let Some(ref expected) = state.api_key {
+        if !check_bearer(&headers, expected) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json");
+    if !content_type.starts_with("application/json") {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(json!({"message": "Only OTLP/HTTP JSON is supported; set OTEL_EXPORTER_OTLP_PROTOCOL=http/json"})),
+        )
+            .into_response();
+    }
+
+    let body: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"message": format!("Invalid JSON: {e}")})),
+            )
+                .into_response()
+        }
+    };
+
+    match ingest_traces(state.handler.as_ref(), body, query.workspace) {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(message) => {
+            (StatusCode::BAD_REQUEST, Json(json!({"message": message}))).into_response()
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Public workspace shares
+// ---------------------------------------------------------------------------
+
+/// Length of a share link's rate-limit window.
+const SHARE_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Fixed-window request counter per share token.
+///
+/// Only tokens that resolved to an active share are counted, so the map is
+/// bounded by the number of share links.
+#[derive(Default)]
+struct ShareRateLimiter {
+    windows: parking_lot::Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl ShareRateLimiter {
+    /// Count a request on `token`. Once `per_minute` requests were made in the
+    /// current window, returns the seconds until it resets instead.
+    fn check(&self, token: &str, per_minute: u32, now: Instant) -> Result<(), u64> {
+        let mut windows = self.windows.lock();
+        let (start, count) = windows.entry(token.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= SHARE_RATE_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= per_minute {
+            let reset = SHARE_RATE_WINDOW.saturating_sub(now.duration_since(*start));
+            return Err(reset.as_secs().max(1));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+/// Query parameters for `GET /public/:token/search`.
+#[derive(Debug, Clone, Deserialize)]
+struct PublicSearchQuery {
+    /// Search query.
+    q: Option<String>,
+    /// Maximum results (default 20, max 50).
+    limit: Option<i64>,
+}
+
+/// Query parameters for `GET /public/:token/graph`.
+#[derive(Debug, Clone, Deserialize)]
+struct PublicGraphQuery {
+    /// Maximum nodes (default 200, max 500).
+    max_nodes: Option<i64>,
+}
+
+/// Run the `workspace_share_view` tool, returning its result or error text.
+fn share_view(
+    handler: &dyn McpHandler,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let request = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(0)),
+        method: "tools/call".to_string(),
+        params: json!({"name": "workspace_share_view", "arguments": arguments}),
+    };
+    let response = handler.handle_request(request);
+    if let Some(err) = response.error {
+        return Err(err.message);
+    }
+    let text = response
+        .result
+        .as_ref()
+        .and_then(|r| r.pointer("/content/0/text"))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| "workspace_share_view returned no content".to_string())?;
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(err) = value.get("error") {
+        return Err(err
+            .as_str()
+            .unwrap_or("workspace_share_view failed")
+            .to_string());
+    }
+    Ok(value)
+}
+
+/// Resolve a share token, count the request against its rate limit and
+/// serve `view` (`info`, `search` or `graph`) with the extra `arguments`.
+fn serve_share_view(
+    state: &AppState,
+    token: &str,
+    view: &str,
+    mut arguments: serde_json::Value,
+) -> Response {
+    let info = match share_view(
+        state.handler.as_ref(),
+        json!({"token": token, "view": "info"}),
+    ) {
+        Ok(info) => info,
+        Err(message) if message == "Share not found" => {
+            return (StatusCode::NOT_FOUND, Json(json!({"error": message}))).into_response()
+        }
+        Err(message) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": message})),
+            )
+                .into_response()
+        }
+    };
+
+    let per_minute = info["requests_per_minute"].as_u64().unwrap_or(60) as u32;
+    if let Err(retry_after) = state.share_limiter.check(token, per_minute, Instant::now()) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, HeaderValue::from(retry_after))],
+            Json(json!({"error": "Rate limit exceeded", "retry_after": retry_after})),
+        )
+            .into_response();
+    }
+    if view == "info" {
+        return (StatusCode::OK, Json(info)).into_response();
+    }
+
+    arguments["token"] = json!(token);
+    arguments["view"] = json!(view);
+    match share_view(state.handler.as_ref(), arguments) {
+        Ok(value) => (StatusCode::OK, Json(value)).into_response(),
+        Err(message) => (StatusCode::BAD_REQUEST, Json(json!({"error": message}))).into_response(),
+    }
+}
+
+/// `GET /public/:token` -- name, size and rate limit of a shared workspace.
+///
+/// Needs no API key. Unknown, revoked and expired tokens are `404`; requests
+/// over the link's limit are `429` with `Retry-After`.
+async fn handle_public_info(State(state): State<AppState>, Path(token): Path<String>) -> Response {
+    serve_share_view(&state, &token, "info", json!({}))
+}
+
+/// `GET /public/:token/search?q=` -- search a shared workspace.
+///
+/// Results carry only id, content, type, tags, timestamps and score. Same
+/// auth and limits as `GET /public/:token`.
+async fn handle_public_search(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<PublicSearchQuery>,
+) -> Response {
+    serve_share_view(
+        &state,
+        &token,
+        "search",
+        json!({"query": query.q, "limit": query.limit}),
+    )
+}
+
+/// `GET /public/:token/graph` -- vis.js graph of a shared workspace; links
+/// to memories outside it are left out. Same auth and limits as
+/// `GET /public/:token`.
+async fn handle_public_graph(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(query): Query<PublicGraphQuery>,
+) -> Response {
+    serve_share_view(
+        &state,
+        &token,
+        "graph",
+        json!({"max_nodes": query.max_nodes}),
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Auth helpers
 // ---------------------------------------------------------------------------
 
-/// Return `true` when the `Authorization: Bearer <token>` header matches the
-/// expected key.
-fn check_bearer(headers: &HeaderMap, expected: &str) -> bool {
+/// Who a `POST /mcp` request runs as
+enum Caller {
+    /// No API key configured, or the server-wide key
+    Unrestricted,
+    /// A per-user API key
+    Authenticated(AuthContext),
+}
+
+/// Scope a caller's JSON-RPC ids are registered and cancelled in: its
+/// API key's user (or the server-wide key), narrowed to the client's
+/// `Mcp-Session-Id` when it sends one. A session id alone isn't trusted, so
+/// one user can't cancel another's requests by naming their session.
+fn request_scope(headers: &HeaderMap, caller: &Caller) -> String {
+    let principal = match caller {
+        Caller::Unrestricted => "server".to_string(),
+        Caller::Authenticated(auth) => format!("user:{}", auth.user_id.as_str()),
+    };
+    match headers
+        .get("mcp-session-id")
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(session) => format!("{}/session:{}", principal, session),
+        None => principal,
+    }
+}
+
+/// The token from an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
     headers
         .get("authorization")
         .and_then(|v| v.to_str().ok())
-        .map(|v| {
-            v.strip_prefix("Bearer ")
-                .map(|token| token == expected)
-                .unwrap_or(false)
-        })
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Return `true` when the `Authorization: Bearer <token>` header matches the
+/// expected key.
+fn check_bearer(headers: &HeaderMap, expected: &str) -> bool {
+    bearer_token(headers)
+        .map(|token| token == expected)
         .unwrap_or(false)
 }
 
@@ -306,20 +994,55 @@ fn check_bearer(headers: &HeaderMap, expected: &str) -> bool {
 
 /// Start the axum HTTP server on `0.0.0.0:{port}`.
 ///
-/// The server will run until the process is terminated.
+/// The server will run until the process is terminated; see
+/// [`serve_http_with_shutdown`] to stop it gracefully.
 ///
 /// - `realtime` — optional `RealtimeManager` for SSE streaming (`GET /v1/events`).
 ///   When `None`, the `/v1/events` endpoint returns `503 Service Unavailable`.
+///
+/// Large memories can be streamed from `GET /v1/memories/:id/content`, and
+/// graph mutations from the `GET /v1/graph/ws` WebSocket. Graph views fetch
+/// a node's details, neighbours and community from `GET /v1/graph/nodes/:id`,
+/// `/neighbors` and `/cluster`. With the `otel`
+/// feature, OTLP trace exporters can post to `POST /v1/traces`.
+///
+/// Workspaces shared with `workspace_share_create` are served read-only,
+/// without the API key, under `GET /public/:token`.
+///
+/// - `dashboard` — also serve the embedded web UI under `/ui`.
 pub async fn serve_http(
     handler: Arc<dyn McpHandler>,
     port: u16,
     api_key: Option<String>,
     realtime: Option<RealtimeManager>,
+    dashboard: bool,
+) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    serve_http_with_shutdown(
+        handler,
+        port,
+        api_key,
+        realtime,
+        dashboard,
+        std::future::pending(),
+    )
+    .await
+}
+
+/// [`serve_http`] until `shutdown` completes, then stop accepting
+/// connections and return once in-flight requests have finished.
+pub async fn serve_http_with_shutdown(
+    handler: Arc<dyn McpHandler>,
+    port: u16,
+    api_key: Option<String>,
+    realtime: Option<RealtimeManager>,
+    dashboard: bool,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
 ) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let state = AppState {
         handler,
         api_key,
         realtime,
+        share_limiter: Arc::new(ShareRateLimiter::default()),
     };
 
     let cors = CorsLayer::new()
@@ -327,17 +1050,34 @@ pub async fn serve_http(
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/mcp", post(handle_mcp))
         .route("/health", get(handle_health))
         .route("/v1/events", get(handle_events))
-        .layer(cors)
-        .with_state(state);
+        .route("/v1/graph/ws", get(handle_graph_ws))
+        .route("/v1/graph/nodes/:id", get(handle_graph_node))
+        .route("/v1/graph/nodes/:id/neighbors", get(handle_graph_neighbors))
+        .route("/v1/graph/nodes/:id/cluster", get(handle_graph_cluster))
+        .route("/v1/memories/:id/content", get(handle_memory_content))
+        .route("/public/:token", get(handle_public_info))
+        .route("/public/:token/search", get(handle_public_search))
+        .route("/public/:token/graph", get(handle_public_graph));
+    #[cfg(feature = "otel")]
+    {
+        app = app.route("/v1/traces", post(handle_otlp_traces));
+    }
+    if dashboard {
+        app = app.merge(super::dashboard::routes());
+        tracing::info!("Web dashboard enabled at /ui/");
+    }
+    let app = app.layer(cors).with_state(state);
 
     let addr = format!("0.0.0.0:{port}");
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("HTTP transport listening on {}", addr);
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await?;
     Ok(())
 }
 
@@ -380,6 +1120,24 @@ mod tests {
         assert!(!check_bearer(&headers, "abc123"));
     }
 
+    #[test]
+    fn test_request_scope_separates_users_and_sessions() {
+        let alice = Caller::Authenticated(AuthContext::new(
+            crate::auth::UserId::from_string("alice"),
+            Default::default(),
+        ));
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_scope(&headers, &Caller::Unrestricted), "server");
+        assert_eq!(request_scope(&headers, &alice), "user:alice");
+
+        headers.insert("mcp-session-id", "s1".parse().unwrap());
+        assert_eq!(request_scope(&headers, &alice), "user:alice/session:s1");
+        assert_ne!(
+            request_scope(&headers, &alice),
+            request_scope(&headers, &Caller::Unrestricted)
+        );
+    }
+
     // ---- SSE event serialization tests ------------------------------------
 
     /// Verify that a `RealtimeEvent` can be round-tripped through JSON
@@ -421,6 +1179,10 @@ mod tests {
             "sync_completed"
         );
         assert_eq!(event_type_to_str(EventType::SyncFailed), "sync_failed");
+        assert_eq!(
+            event_type_to_str(EventType::MemorySuggested),
+            "memory_suggested"
+        );
     }
 
     // ---- parse_event_type tests -------------------------------------------
@@ -653,4 +1415,329 @@ mod tests {
     fn test_retry_constant_is_3000ms() {
         assert_eq!(SSE_RETRY_MS, 3000);
     }
+
+    // ---- memory content streaming ------------------------------------------
+
+    /// Serves `memory_get` ranges over a fixed content string.
+    struct FixedContentHandler(&'static str);
+
+    impl McpHandler for FixedContentHandler {
+        fn handle_request(&self, request: McpRequest) -> McpResponse {
+            let args = &request.params["arguments"];
+            let id = args["id"].as_i64().unwrap();
+            let value = if id == 1 {
+                let offset = args["offset"].as_u64().unwrap() as usize;
+                let length = args["length"].as_u64().map(|l| l as usize);
+                json!(ContentRange::from_content(id, self.0, offset, length))
+            } else {
+                json!({"error": format!("Memory not found: {id}")})
+            };
+            let text = serde_json::to_string(&value).unwrap();
+            McpResponse::success(
+                request.id,
+                json!({"content": [{"type": "text", "text": text}]}),
+            )
+        }
+    }
+
+    #[test]
+    fn test_fetch_content_range_pages_to_end() {
+        let handler = FixedContentHandler("ünïcode content");
+        let mut offset = Some(0);
+        let mut collected = String::new();
+        let mut chunks = 0;
+        while let Some(o) = offset {
+            let range = fetch_content_range(&handler, 1, o, 4).unwrap();
+            assert_eq!(range.total_chars, 15);
+            collected.push_str(&range.content);
+            offset = range.next_offset;
+            chunks += 1;
+        }
+        assert_eq!(collected, "ünïcode content");
+        assert_eq!(chunks, 4);
+    }
+
+    #[test]
+    fn test_fetch_content_range_missing_memory_is_error() {
+        let handler = FixedContentHandler("x");
+        let err = fetch_content_range(&handler, 2, 0, 4).unwrap_err();
+        assert!(err.contains("not found"), "{err}");
+        assert_eq!(content_error_status(&err), StatusCode::NOT_FOUND);
+        assert_eq!(
+            content_error_status("Invalid input: offset out of range"),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            content_error_status("Database error: disk I/O error"),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // ---- OTLP trace receiver -----------------------------------------------
+
+    /// Answers `otel_ingest_traces` with a fixed result, echoing the workspace.
+    #[cfg(feature = "otel")]
+    struct IngestHandler(serde_json::Value);
+
+    #[cfg(feature = "otel")]
+    impl McpHandler for IngestHandler {
+        fn handle_request(&self, request: McpRequest) -> McpResponse {
+            assert_eq!(request.params["name"], "otel_ingest_traces");
+            let mut value = self.0.clone();
+            value["workspace"] = request.params["arguments"]["workspace"].clone();
+            let text = serde_json::to_string(&value).unwrap();
+            McpResponse::success(
+                request.id,
+                json!({"content": [{"type": "text", "text": text}]}),
+            )
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_ingest_traces_responses() {
+        let ok = IngestHandler(json!({"memories_created": 1, "errors": []}));
+        let body = json!({"resourceSpans": []});
+        assert_eq!(
+            ingest_traces(&ok, body.clone(), Some("ws".to_string())).unwrap(),
+            json!({})
+        );
+
+        let partial = IngestHandler(json!({"errors": ["Span ab: disk full"]}));
+        let response = ingest_traces(&partial, body.clone(), None).unwrap();
+        assert_eq!(response["partialSuccess"]["rejectedSpans"], 1);
+        assert_eq!(
+            response["partialSuccess"]["errorMessage"],
+            "Span ab: disk full"
+        );
+
+        let failed = IngestHandler(json!({"error": "Invalid workspace"}));
+        assert_eq!(
+            ingest_traces(&failed, body, None).unwrap_err(),
+            "Invalid workspace"
+        );
+    }
+
+    // ---- public workspace shares -------------------------------------------
+
+    /// Serves one share, `shr_ok`, limited to two requests per minute.
+    struct ShareHandler;
+
+    impl McpHandler for ShareHandler {
+        fn handle_request(&self, request: McpRequest) -> McpResponse {
+            assert_eq!(request.params["name"], "workspace_share_view");
+            let args = &request.params["arguments"];
+            let value = match (args["token"].as_str(), args["view"].as_str()) {
+                (Some("shr_ok"), Some("info")) => {
+                    json!({"workspace": "docs", "requests_per_minute": 2})
+                }
+                (Some("shr_ok"), Some("search")) if args["query"].is_string() => {
+                    json!({"workspace": "docs", "results": []})
+                }
+                (Some("shr_ok"), _) => json!({"error": "query is required"}),
+                _ => json!({"error": "Share not found"}),
+            };
+            let text = serde_json::to_string(&value).unwrap();
+            McpResponse::success(
+                request.id,
+                json!({"content": [{"type": "text", "text": text}]}),
+            )
+        }
+    }
+
+    /// Full access for the server key, limited access for "user-key"
+    struct KeyedHandler;
+
+    impl McpHandler for KeyedHandler {
+        fn handle_request(&self, request: McpRequest) -> McpResponse {
+            McpResponse::success(request.id, json!("unrestricted"))
+        }
+
+        fn handle_request_as(&self, request: McpRequest, auth: &AuthContext) -> McpResponse {
+            McpResponse::success(request.id, json!(auth.user_id.to_string()))
+        }
+
+        fn authenticate(&self, token: &str) -> Option<AuthContext> {
+            (token == "user-key").then(AuthContext::anonymous)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mcp_runs_per_user_keys_with_their_permissions() {
+        let state = AppState {
+            handler: Arc::new(KeyedHandler),
+            ..share_state()
+        };
+        let call = |token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("authorization", format!("Bearer {token}").parse().unwrap());
+            let request: McpRequest =
+                serde_json::from_value(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+                    .unwrap();
+            handle_mcp(State(state.clone()), headers, Json(request))
+        };
+
+        let read = |response: Response| async move {
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            (status, body["result"].clone())
+        };
+        let (status, result) = read(call("secret").await.into_response()).await;
+        assert_eq!((status, result), (StatusCode::OK, json!("unrestricted")));
+        let (status, result) = read(call("user-key").await.into_response()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result, json!(AuthContext::anonymous().user_id.to_string()));
+        let (status, _) = read(call("wrong").await.into_response()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    fn share_state() -> AppState {
+        AppState {
+            handler: Arc::new(ShareHandler),
+            api_key: Some("secret".to_string()),
+            realtime: None,
+            share_limiter: Arc::new(ShareRateLimiter::default()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_http_stops_on_shutdown() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_http_with_shutdown(
+            Arc::new(ShareHandler),
+            port,
+            None,
+            None,
+            false,
+            async {
+                let _ = stopped.await;
+            },
+        ));
+        // Serving until told to stop
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!server.is_finished());
+
+        stop.send(()).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server should stop after the shutdown signal")
+            .unwrap();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_share_rate_limiter_window() {
+        let limiter = ShareRateLimiter::default();
+        let start = Instant::now();
+        assert!(limiter.check("a", 2, start).is_ok());
+        assert!(limiter.check("a", 2, start).is_ok());
+        let retry = limiter
+            .check("a", 2, start + Duration::from_secs(15))
+            .unwrap_err();
+        assert_eq!(retry, 45);
+        // Other links have their own window
+        assert!(limiter.check("b", 2, start).is_ok());
+        // The window resets after a minute
+        assert!(limiter.check("a", 2, start + SHARE_RATE_WINDOW).is_ok());
+    }
+
+    #[test]
+    fn test_serve_share_view_statuses() {
+        let state = share_state();
+        let search = json!({"query": "deploy"});
+        assert_eq!(
+            serve_share_view(&state, "shr_ok", "search", search.clone()).status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            serve_share_view(&state, "shr_ok", "search", json!({"query": null})).status(),
+            StatusCode::BAD_REQUEST
+        );
+        let limited = serve_share_view(&state, "shr_ok", "info", json!({}));
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key(header::RETRY_AFTER));
+        assert_eq!(
+            serve_share_view(&state, "shr_gone", "search", search).status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    // ---- graph exploration -------------------------------------------------
+
+    /// Echoes `memory_graph_explore` arguments; memory 2 doesn't exist.
+    struct ExploreHandler;
+
+    impl McpHandler for ExploreHandler {
+        fn handle_request(&self, request: McpRequest) -> McpResponse {
+            assert_eq!(request.params["name"], "memory_graph_explore");
+            let args = request.params["arguments"].clone();
+            let value = if args["id"] == 2 {
+                json!({"error": "Memory not found: 2"})
+            } else {
+                args
+            };
+            let text = serde_json::to_string(&value).unwrap();
+            McpResponse::success(
+                request.id,
+                json!({"content": [{"type": "text", "text": text}]}),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_explore_statuses() {
+        let state = AppState {
+            handler: Arc::new(ExploreHandler),
+            ..share_state()
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        let query = ExploreQuery {
+            limit: Some(20),
+            edge_types: Some("related_to, depends_on".to_string()),
+            known: Some("1,3".to_string()),
+            ..Default::default()
+        };
+
+        let response =
+            serve_explore(state.clone(), headers.clone(), "expand", 1, query.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["action"], "expand");
+        assert_eq!(body["limit"], 20);
+        assert_eq!(body["offset"], 0);
+        assert_eq!(body["edge_types"], json!(["related_to", "depends_on"]));
+        assert_eq!(body["known"], json!([1, 3]));
+
+        let missing = serve_explore(state.clone(), headers.clone(), "details", 2, query).await;
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+
+        let bad = ExploreQuery {
+            known: Some("1,x".to_string()),
+            ..Default::default()
+        };
+        let response = serve_explore(state.clone(), headers, "expand", 1, bad).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = serve_explore(
+            state,
+            HeaderMap::new(),
+            "details",
+            1,
+            ExploreQuery::default(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
 }
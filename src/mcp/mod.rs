@@ -2,6 +2,8 @@
 //!
 //! JSON-RPC over stdio for AI tool integration.
 
+pub mod access;
+pub mod dashboard;
 pub mod handlers;
 pub mod http_transport;
 #[cfg(feature = "grpc")]
@@ -10,6 +12,7 @@ pub mod prompts;
 pub mod protocol;
 pub mod resources;
 pub mod tools;
+pub mod versioning;
 
 pub use prompts::{get_prompt, list_prompts};
 pub use protocol::{
@@ -20,3 +23,6 @@ pub use protocol::{
 };
 pub use resources::{list_resources, read_resource};
 pub use tools::{get_tool_definitions, get_tool_definitions_tiered, TOOL_DEFINITIONS};
+pub use versioning::{
+    set_deprecated_tools_disabled, tool_version, ToolVersion, BASELINE_VERSION, TOOL_VERSIONS,
+};
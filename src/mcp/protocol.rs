@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io::{BufRead, BufReader, Write};
 
+use crate::auth::AuthContext;
 use crate::error::{EngramError, Result};
 
 /// MCP JSON-RPC request
@@ -79,12 +80,33 @@ where
 /// Trait for handling MCP requests
 pub trait McpHandler: Send + Sync {
     fn handle_request(&self, request: McpRequest) -> McpResponse;
+
+    /// Handle a request on behalf of an authenticated caller, limited to
+    /// what its permissions allow. Handlers that don't check permissions
+    /// treat it like any other request.
+    fn handle_request_as(&self, request: McpRequest, _auth: &AuthContext) -> McpResponse {
+        self.handle_request(request)
+    }
+
+    /// Resolve a bearer token issued as an API key to the caller's
+    /// permissions. `None` when the token is unknown, expired or revoked.
+    fn authenticate(&self, _token: &str) -> Option<AuthContext> {
+        None
+    }
 }
 
 impl<T: McpHandler> McpHandler for std::sync::Arc<T> {
     fn handle_request(&self, request: McpRequest) -> McpResponse {
         (**self).handle_request(request)
     }
+
+    fn handle_request_as(&self, request: McpRequest, auth: &AuthContext) -> McpResponse {
+        (**self).handle_request_as(request, auth)
+    }
+
+    fn authenticate(&self, token: &str) -> Option<AuthContext> {
+        (**self).authenticate(token)
+    }
 }
 
 impl<H: McpHandler> McpServer<H> {
@@ -94,61 +116,97 @@ impl<H: McpHandler> McpServer<H> {
     }
 
     /// Run the server, reading from stdin and writing to stdout
+    ///
+    /// Requests run one at a time, in order, on a worker thread while this
+    /// thread keeps reading. Notifications are handled as soon as they are
+    /// read, so `notifications/cancelled` reaches a request while it runs.
     pub fn run(&self) -> Result<()> {
         let stdin = std::io::stdin();
-        let stdout = std::io::stdout();
-        let mut reader = BufReader::new(stdin.lock());
-        let mut writer = stdout.lock();
-
-        let mut line = String::new();
-
-        loop {
-            line.clear();
-            match reader.read_line(&mut line) {
-                Ok(0) => break, // EOF
-                Ok(_) => {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
+        self.serve(BufReader::new(stdin.lock()), std::io::stdout())
+    }
 
-                    match serde_json::from_str::<McpRequest>(trimmed) {
-                        Ok(request) => {
+    fn serve(&self, mut reader: impl BufRead, writer: impl Write + Send) -> Result<()> {
+        let (requests, pending) = std::sync::mpsc::channel::<McpResponseSource>();
+
+        std::thread::scope(|scope| {
+            let worker = scope.spawn(move || self.respond(pending, writer));
+
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line) {
+                    Ok(0) => break, // EOF
+                    Ok(_) => {
+                        let trimmed = line.trim();
+                        if trimmed.is_empty() {
+                            continue;
+                        }
+
+                        let next = match serde_json::from_str::<McpRequest>(trimmed) {
                             // Per JSON-RPC 2.0: notifications have no id and MUST NOT
                             // produce a response. Process for side effects only.
-                            let is_notification = request.id.is_none();
-                            let response = self.handler.handle_request(request);
-                            if is_notification {
+                            Ok(request) if request.id.is_none() => {
+                                self.handler.handle_request(request);
                                 continue;
                             }
-                            let response_json = serde_json::to_string(&response)?;
-                            writeln!(writer, "{}", response_json)?;
-                            writer.flush()?;
-                        }
-                        Err(e) => {
-                            let response =
-                                McpResponse::error(None, -32700, format!("Parse error: {}", e));
-                            let response_json = serde_json::to_string(&response)?;
-                            writeln!(writer, "{}", response_json)?;
-                            writer.flush()?;
+                            Ok(request) => McpResponseSource::Request(request),
+                            Err(e) => McpResponseSource::Ready(McpResponse::error(
+                                None,
+                                -32700,
+                                format!("Parse error: {}", e),
+                            )),
+                        };
+                        // The worker only hangs up after failing to write
+                        if requests.send(next).is_err() {
+                            break;
                         }
                     }
-                }
-                Err(e) => {
-                    tracing::error!("Error reading stdin: {}", e);
-                    break;
+                    Err(e) => {
+                        tracing::error!("Error reading stdin: {}", e);
+                        break;
+                    }
                 }
             }
-        }
 
+            drop(requests);
+            worker
+                .join()
+                .unwrap_or_else(|_| Err(EngramError::Internal("stdio worker panicked".into())))
+        })
+    }
+
+    /// Answer requests from the reader in order until it hangs up
+    fn respond(
+        &self,
+        pending: std::sync::mpsc::Receiver<McpResponseSource>,
+        mut writer: impl Write,
+    ) -> Result<()> {
+        for next in pending {
+            let response = match next {
+                McpResponseSource::Request(request) => self.handler.handle_request(request),
+                McpResponseSource::Ready(response) => response,
+            };
+            let response_json = serde_json::to_string(&response)?;
+            writeln!(writer, "{}", response_json)?;
+            writer.flush()?;
+        }
         Ok(())
     }
 }
 
+/// A stdio message waiting for its response to be written
+enum McpResponseSource {
+    /// A request to run
+    Request(McpRequest),
+    /// A response known when the message was read (parse errors)
+    Ready(McpResponse),
+}
+
 /// Standard MCP methods
 pub mod methods {
     pub const INITIALIZE: &str = "initialize";
     pub const INITIALIZED: &str = "notifications/initialized";
+    pub const CANCELLED: &str = "notifications/cancelled";
     pub const LIST_TOOLS: &str = "tools/list";
     pub const CALL_TOOL: &str = "tools/call";
     pub const LIST_RESOURCES: &str = "resources/list";
@@ -237,6 +295,10 @@ pub struct ToolDefinition {
     pub input_schema: Value,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotations: Option<ToolAnnotations>,
+    /// Version metadata: `since`, plus `deprecated` details when the tool is
+    /// on its way out
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 /// MCP initialize result
@@ -424,3 +486,84 @@ pub struct PromptContent {
     pub content_type: String,
     pub text: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget;
+    use serde_json::json;
+    use std::sync::mpsc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Runs `tools/call` until cancelled; signals when it has started
+    struct BlockingHandler {
+        started: Mutex<mpsc::Sender<()>>,
+    }
+
+    impl McpHandler for BlockingHandler {
+        fn handle_request(&self, request: McpRequest) -> McpResponse {
+            if request.method == methods::CANCELLED {
+                budget::request_cancel(&request.params["requestId"]);
+                return McpResponse::success(None, Value::Null);
+            }
+            let registration = request.id.as_ref().map(budget::register_request);
+            let cancel = registration
+                .as_ref()
+                .map(|r| r.handle())
+                .unwrap_or_default();
+            self.started.lock().unwrap().send(()).unwrap();
+            for _ in 0..500 {
+                if cancel.is_cancelled() {
+                    return McpResponse::success(request.id, json!("cancelled"));
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            McpResponse::success(request.id, json!("finished"))
+        }
+    }
+
+    /// Reader that hands out lines as the test releases them
+    struct LineFeed(mpsc::Receiver<String>);
+
+    impl std::io::Read for LineFeed {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.recv() {
+                Ok(line) => {
+                    buf[..line.len()].copy_from_slice(line.as_bytes());
+                    Ok(line.len())
+                }
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_stdio_cancels_a_running_request() {
+        let (started_tx, started) = mpsc::channel();
+        let server = McpServer::new(BlockingHandler {
+            started: Mutex::new(started_tx),
+        });
+        let (lines, feed) = mpsc::channel();
+        let mut output = Vec::new();
+
+        std::thread::scope(|scope| {
+            let run = scope.spawn(|| server.serve(BufReader::new(LineFeed(feed)), &mut output));
+            lines
+                .send("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\"}\n".into())
+                .unwrap();
+            started.recv().unwrap();
+            lines
+                .send(
+                    "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/cancelled\",\"params\":{\"requestId\":1}}\n"
+                        .into(),
+                )
+                .unwrap();
+            drop(lines);
+            run.join().unwrap().unwrap();
+        });
+
+        let response: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(response["result"], "cancelled");
+    }
+}
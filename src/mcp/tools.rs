@@ -3,6 +3,7 @@
 use serde_json::json;
 
 use super::protocol::{ToolAnnotations, ToolDefinition};
+use super::versioning::{deprecated_tools_disabled, tool_version};
 
 /// Tool exposure tier for progressive discovery.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,6 +41,7 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "metadata": {"type": "object", "description": "Additional metadata as key-value pairs"},
                 "importance": {"type": "number", "minimum": 0, "maximum": 1, "description": "Importance score (0-1)"},
                 "workspace": {"type": "string", "description": "Workspace to store the memory in (default: 'default')"},
+                "auto_workspace": {"type": "boolean", "description": "Without a workspace, infer one from project_path/cwd metadata, the active persona's default workspace, or similar memories, recording the reason under metadata.workspace_assignment. Defaults to the ENGRAM_AUTO_WORKSPACE setting; false opts out"},
                 "tier": {"type": "string", "enum": ["permanent", "daily"], "default": "permanent", "description": "Memory tier: permanent (never expires) or daily (auto-expires)"},
                 "defer_embedding": {"type": "boolean", "default": false, "description": "Defer embedding to background queue"},
                 "ttl_seconds": {"type": "integer", "description": "Time-to-live in seconds. Memory will auto-expire after this duration. Omit for permanent storage. Setting this implies tier='daily'."},
@@ -171,7 +173,11 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
             "type": "object",
             "properties": {
                 "id": {"type": "integer", "description": "Memory ID"},
-                "strip_private": {"type": "boolean", "description": "When true, removes all <private>...</private> tagged sections from the content before returning (default: false)"}
+                "strip_private": {"type": "boolean", "description": "When true, removes all <private>...</private> tagged sections from the content before returning (default: false)"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"},
+                "offset": {"type": "integer", "minimum": 0, "description": "Ranged read: character offset into the content. When offset or length is set, returns a content range (offset, length, total_chars, total_bytes, next_offset, content) instead of the full memory"},
+                "length": {"type": "integer", "minimum": 1, "description": "Ranged read: maximum number of characters to return (default: to the end). Page through large memories by following next_offset"}
             },
             "required": ["id"]
         }"#,
@@ -237,7 +243,14 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "metadata_filter": {
                     "type": "object",
                     "description": "Legacy simple key-value filter (deprecated, use 'filter' instead)"
-                }
+                },
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"},
+                "as_of": {"type": "string", "description": "RFC3339 timestamp; evaluate against memory versions valid at that time (time-travel)"},
+                "event_after": {"type": "string", "description": "RFC3339; only memories whose event_time is at or after this (event time)"},
+                "event_before": {"type": "string", "description": "RFC3339; only memories whose event_time is at or before this (event time)"},
+                "show_superseded": {"type": "boolean", "default": false, "description": "Include memories marked as superseded (hidden by default)"},
+                "validation_status": {"type": "string", "enum": ["unverified", "verified", "disputed", "stale"], "description": "Only memories with this validation status"}
             }
         }"#,
         annotations: ToolAnnotations::read_only(),
@@ -261,13 +274,25 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "tier": {"type": "string", "enum": ["permanent", "daily"], "description": "Filter by memory tier"},
                 "include_transcripts": {"type": "boolean", "default": false, "description": "Include transcript chunk memories (excluded by default)"},
                 "strategy": {"type": "string", "enum": ["auto", "keyword", "keyword_only", "semantic", "semantic_only", "hybrid"], "description": "Force specific strategy (auto selects based on query; keyword/semantic are aliases for keyword_only/semantic_only)"},
-                "explain": {"type": "boolean", "default": false, "description": "Include match explanations"},
+                "explain": {"type": "boolean", "default": false, "description": "Include match explanations and, when no strategy is given, why one was selected (strategy_selection)"},
                 "rerank": {"type": "boolean", "default": true, "description": "Apply reranking to improve result quality"},
-                "rerank_strategy": {"type": "string", "enum": ["none", "heuristic", "multi_signal"], "default": "heuristic", "description": "Reranking strategy to use"},
+                "rerank_strategy": {"type": "string", "enum": ["none", "heuristic", "multi_signal", "cross_encoder"], "default": "heuristic", "description": "Reranking strategy to use. cross_encoder scores query/memory pairs with the server's configured reranker model (the default when one is configured) and falls back to heuristic otherwise"},
+                "rerank_top_k": {"type": "integer", "default": 20, "description": "Number of top results scored by the cross-encoder"},
+                "experiment_id": {"type": "integer", "description": "Route this search through an active ranking experiment (see search_experiment_create). The response includes the serving arm."},
                 "filter": {
                     "type": "object",
                     "description": "Advanced filter with AND/OR logic. Supports workspace, tier, and metadata fields. Example: {\"AND\": [{\"workspace\": {\"eq\": \"my-project\"}}, {\"importance\": {\"gte\": 0.5}}]}"
-                }
+                },
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"},
+                "as_of": {"type": "string", "description": "RFC3339 timestamp; evaluate against memory versions valid at that time (time-travel)"},
+                "when": {"type": "string", "description": "Only memories created in this natural-language time range, e.g. \"last Tuesday\", \"two weeks ago\", \"past 3 days\", \"since 5 March\", \"between 01/02/2024 and 15/02/2024\""},
+                "timezone": {"type": "string", "description": "UTC offset for reading when, e.g. +02:00 or UTC-5 (default: the workspace's timezone, then UTC)"},
+                "date_order": {"type": "string", "enum": ["mdy", "dmy", "ymd"], "description": "How to read numeric dates in when, e.g. 03/04 (default: the workspace's date order, then mdy)"},
+                "agent_id": {"type": "string", "description": "Calling agent, used to evaluate contextual boost rules"},
+                "session_tags": {"type": "array", "items": {"type": "string"}, "description": "Tags of the active session, used to evaluate contextual boost rules"},
+                "show_superseded": {"type": "boolean", "default": false, "description": "Include memories marked as superseded (hidden by default)"},
+                "validation_status": {"type": "string", "enum": ["unverified", "verified", "disputed", "stale"], "description": "Only memories with this validation status"}
             },
             "required": ["query"]
         }"#,
@@ -287,6 +312,62 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Standard,
     },
+    // Federation
+    ToolDef {
+        name: "federated_search",
+        description: "Search the primary database and every database attached read-only through the federation config. Each result carries the label of its source; rankings are merged with weighted reciprocal rank fusion. Failing sources are reported under errors without failing the search.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "Search query"},
+                "sources": {"type": "array", "items": {"type": "string"}, "description": "Only search these source labels (default: all)"},
+                "limit": {"type": "integer", "default": 20, "description": "Maximum merged results"},
+                "min_score": {"type": "number"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "memory_type": {"type": "string", "description": "Filter by memory type (alias: type)"},
+                "workspace": {"type": "string", "description": "Filter by workspace name in every source"},
+                "strategy": {"type": "string", "enum": ["auto", "keyword", "keyword_only", "semantic", "semantic_only", "hybrid"], "description": "Semantic matching is only used on sources whose embeddings come from the same model"},
+                "include_archived": {"type": "boolean", "default": false},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
+            },
+            "required": ["query"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "federated_list",
+        description: "List memories across the primary and attached databases as one page sorted by sort_by, each labelled with its source.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "sources": {"type": "array", "items": {"type": "string"}, "description": "Only list these source labels (default: all)"},
+                "limit": {"type": "integer", "default": 100},
+                "offset": {"type": "integer", "default": 0},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "memory_type": {"type": "string", "description": "Filter by memory type (alias: type)"},
+                "workspace": {"type": "string"},
+                "sort_by": {"type": "string", "enum": ["created_at", "updated_at", "last_accessed_at", "importance", "access_count"]},
+                "sort_order": {"type": "string", "enum": ["asc", "desc"], "default": "desc"},
+                "include_archived": {"type": "boolean", "default": false},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "federation_sources",
+        description: "List the databases taking part in federated search with their labels, paths, weights, memory counts and schema versions.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     // Cross-references
     ToolDef {
         name: "memory_link",
@@ -296,7 +377,7 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
             "properties": {
                 "from_id": {"type": "integer"},
                 "to_id": {"type": "integer"},
-                "edge_type": {"type": "string", "enum": ["related_to", "supersedes", "contradicts", "implements", "extends", "references", "depends_on", "blocks", "follows_up"], "default": "related_to"},
+                "edge_type": {"type": "string", "enum": ["related_to", "supersedes", "contradicts", "supports", "implements", "extends", "references", "depends_on", "blocks", "follows_up"], "default": "related_to"},
                 "strength": {"type": "number", "minimum": 0, "maximum": 1, "description": "Relationship strength"},
                 "source_context": {"type": "string", "description": "Why this link exists"},
                 "pinned": {"type": "boolean", "default": false, "description": "Exempt from confidence decay"}
@@ -331,7 +412,9 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "depth": {"type": "integer", "default": 1, "description": "Traversal depth (1 = direct relations only)"},
                 "include_entities": {"type": "boolean", "default": false, "description": "Include connections through shared entities"},
                 "edge_type": {"type": "string", "description": "Filter by edge type"},
-                "include_decayed": {"type": "boolean", "default": false}
+                "include_decayed": {"type": "boolean", "default": false},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
             },
             "required": ["id"]
         }"#,
@@ -416,13 +499,13 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     // Embedding status
     ToolDef {
         name: "memory_embedding_status",
-        description: "Check embedding computation status",
+        description: "Check embedding status. With id: that memory's queue state and stored model, plus the dead-letter entry if its embedding failed permanently. Without: the current model, stored vectors per model, queue counts, the most recent dead-lettered memories and the active (or given) embedding migration's progress.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "id": {"type": "integer"}
-            },
-            "required": ["id"]
+                "id": {"type": "integer", "description": "Memory ID"},
+                "migration_id": {"type": "string", "description": "Report this migration instead of the active one"}
+            }
         }"#,
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
@@ -442,16 +525,75 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::mutating(),
         tier: ToolTier::Standard,
     },
+    ToolDef {
+        name: "memory_set_ttl_bulk",
+        description: "Set or remove the expiration of every memory matching a filter in a single transaction. A positive ttl_seconds expires them that long from now and moves them to the daily tier; null or 0 removes the expiration and moves them to the permanent tier. Use dry_run to see the matched and retiered counts first.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "filter": {
+                    "type": "object",
+                    "description": "Memories to change; at least one criterion is required",
+                    "properties": {
+                        "tags": {"type": "array", "items": {"type": "string"}, "description": "Match memories with any of these tags"},
+                        "memory_type": {"type": "string", "description": "Match memories of this type"},
+                        "workspace": {"type": "string", "description": "Match memories in this workspace"},
+                        "tier": {"type": "string", "enum": ["permanent", "daily"], "description": "Match memories in this tier"},
+                        "older_than_days": {"type": "number", "description": "Match memories created at least this many days ago"},
+                        "newer_than_days": {"type": "number", "description": "Match memories created at most this many days ago"},
+                        "filter": {"type": "object", "description": "Advanced filter expression (same syntax as memory_list)"}
+                    }
+                },
+                "ttl_seconds": {"type": ["integer", "null"], "description": "Time-to-live in seconds from now; null or 0 removes the expiration"},
+                "dry_run": {"type": "boolean", "default": false, "description": "Report what would change without writing"}
+            },
+            "required": ["filter", "ttl_seconds"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "memory_cleanup_expired",
-        description: "Delete all expired memories. Typically called by a background job, but can be invoked manually.",
+        description: "Purge memories that expired more than grace_days ago. Expired memories are hidden immediately but stay recoverable with memory_resurrect until then. Typically called by a background job, but can be invoked manually.",
         schema: r#"{
             "type": "object",
-            "properties": {}
+            "properties": {
+                "grace_days": {"type": "integer", "minimum": 0, "description": "Days expired memories stay recoverable (default: ENGRAM_EXPIRED_GRACE_DAYS or 7; 0 purges all expired memories)"}
+            }
         }"#,
         annotations: ToolAnnotations::destructive(),
         tier: ToolTier::Standard,
     },
+    ToolDef {
+        name: "memory_expired_list",
+        description: "List expired memories that are still in their grace period and can be resurrected, most recently expired first, with the time each will be purged.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Only list memories in this workspace"},
+                "limit": {"type": "integer", "default": 50},
+                "grace_days": {"type": "integer", "minimum": 0, "description": "Grace period used to compute purge_at (default: ENGRAM_EXPIRED_GRACE_DAYS or 7)"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_resurrect",
+        description: "Bring back an expired memory that is still within its grace period. Without ttl_seconds it becomes permanent; with ttl_seconds it expires again that long from now.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "ID of the expired memory"},
+                "ttl_seconds": {"type": "integer", "description": "New time-to-live in seconds from now; omit or 0 to keep it permanently"}
+            },
+            "required": ["id"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
     // Sync
     ToolDef {
         name: "memory_sync_status",
@@ -518,14 +660,78 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         schema: r#"{
             "type": "object",
             "properties": {
-                "format": {"type": "string", "enum": ["html", "json"], "default": "html"},
-                "max_nodes": {"type": "integer", "default": 500},
-                "focus_id": {"type": "integer", "description": "Center graph on this memory"}
+                "format": {"type": "string", "enum": ["html", "json", "graphml", "stats", "timeline", "svg", "png"], "default": "html", "description": "html/json render the graph; graphml exports node/edge attributes for yEd or Cytoscape; stats returns graph metrics plus the most central nodes (betweenness, closeness, eigenvector, Katz); timeline returns HTML with a time slider over snapshots from `from` to `as_of`; svg/png draw a static image server-side, for viewers that can't load vis.js from its CDN (png is base64 and needs the graph-png feature)"},
+                "max_nodes": {"type": "integer", "default": 500, "description": "Memories to export; over ENGRAM_MAX_GRAPH_NODES (default 10000) is an error unless output_path is set. With output_path, defaults to all memories"},
+                "output_path": {"type": "string", "description": "Stream the graph (json or graphml) to this file instead of returning it, for exports beyond the in-memory limits. Returns the path, node/edge counts and file size; can't be combined with as_of, summarize or mode=bipartite"},
+                "focus_id": {"type": "integer", "description": "Center graph on this memory"},
+                "top": {"type": "integer", "default": 10, "description": "Number of central nodes returned by the stats format, ranked by betweenness"},
+                "label_length": {"type": "integer", "default": 50, "minimum": 1, "description": "Maximum node label length in characters (grapheme clusters)"},
+                "label_source": {"type": "string", "enum": ["first_line", "title", "summary"], "default": "first_line", "description": "Node label text: first content line, title metadata, or summary metadata / first sentence"},
+                "as_of": {"type": "string", "description": "RFC3339 timestamp; export the graph as it stood then (memory versions and links valid at that time). For timeline, the last snapshot (default now)"},
+                "from": {"type": "string", "description": "RFC3339 timestamp of the first timeline snapshot (default: oldest memory)"},
+                "steps": {"type": "integer", "default": 10, "minimum": 1, "maximum": 100, "description": "Number of evenly spaced timeline snapshots"},
+                "precompute_layout": {"type": "boolean", "default": false, "description": "For html/json: compute a force-directed layout server-side and emit fixed x/y node positions with client-side physics disabled (recommended above a few thousand nodes)"},
+                "summarize": {"type": "integer", "minimum": 1, "description": "For html/json: collapse the largest communities into supernodes until at most this many nodes remain. Merged edges report how many links they stand for; in html, click a supernode to expand it (precompute_layout is ignored for html)"},
+                "mode": {"type": "string", "enum": ["memories", "bipartite"], "default": "memories", "description": "bipartite adds extracted entities and linked identities as nodes (memory_type entity:<type> / identity:<type>, negative ids) with mentions/about edges from memories and same_as edges from an entity to the identity it is an alias of. Not applied to timeline"},
+                "include_identities": {"type": "boolean", "default": true, "description": "For bipartite mode: include identities as well as extracted entities"},
+                "min_entity_links": {"type": "integer", "default": 1, "minimum": 1, "description": "For bipartite mode: leave out entities and identities linked to fewer exported memories"},
+                "width": {"type": "integer", "default": 1200, "minimum": 100, "maximum": 8000, "description": "For svg/png: image width in pixels"},
+                "height": {"type": "integer", "default": 900, "minimum": 100, "maximum": 8000, "description": "For svg/png: image height in pixels"},
+                "edge_labels": {"type": "boolean", "default": false, "description": "For svg/png: write each edge's type along it"},
+                "aggregate_parallel_edges": {"type": "boolean", "default": false, "description": "For html/json/svg/png: merge all links between the same two memories (either direction) into one edge with their summed weight, labelled with the combined types and listing each link in its tooltip. Ignored with summarize, which merges edges itself"}
             }
         }"#,
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Standard,
     },
+    ToolDef {
+        name: "memory_export_neighborhood",
+        description: "Export the ego graph of one memory: the memories within `depth` links of it and the links between them, as HTML, vis.js JSON or Graphviz DOT. A filter restricts which memories and links the walk may pass through",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Memory at the center of the graph"},
+                "depth": {"type": "integer", "default": 2, "minimum": 0, "maximum": 6, "description": "Maximum number of links from the center"},
+                "format": {"type": "string", "enum": ["html", "json", "dot"], "default": "html"},
+                "max_nodes": {"type": "integer", "default": 500, "description": "Stop loading memories once this many are reached"},
+                "filter": {
+                    "type": "object",
+                    "description": "Memories and links the walk may use; the center is always kept",
+                    "properties": {
+                        "memory_types": {"type": "array", "items": {"type": "string"}},
+                        "tags": {"type": "array", "items": {"type": "string"}, "description": "Keep memories with any of these tags"},
+                        "edge_types": {"type": "array", "items": {"type": "string"}},
+                        "min_importance": {"type": "number"},
+                        "max_importance": {"type": "number"},
+                        "min_confidence": {"type": "number", "description": "Minimum link confidence"},
+                        "min_score": {"type": "number", "description": "Minimum link score"},
+                        "limit": {"type": "integer", "minimum": 1, "description": "Keep at most this many memories, nearest to the center first"}
+                    }
+                }
+            },
+            "required": ["id"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_graph_explore",
+        description: "Explore the knowledge graph one step at a time, for graph views too large to export whole. expand returns a page of a memory's neighbours (strongest links first) with the links among them and to the nodes passed as known; details returns a memory's content, link counts per type and community; cluster returns a page of its community's members (from the last stored clustering run, or detected in its two-hop neighbourhood)",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "action": {"type": "string", "enum": ["expand", "details", "cluster"]},
+                "id": {"type": "integer", "description": "Memory to explore from"},
+                "limit": {"type": "integer", "minimum": 1, "description": "Neighbours (default 50, max 500) or cluster members (default 100, max 1000) per page"},
+                "offset": {"type": "integer", "default": 0, "minimum": 0, "description": "Start of the page; pass the previous answer's next_offset"},
+                "edge_types": {"type": "array", "items": {"type": "string"}, "description": "For expand: only follow these link types"},
+                "known": {"type": "array", "items": {"type": "integer"}, "description": "For expand: memories the view already shows; they aren't returned again, but links to them are"}
+            },
+            "required": ["action", "id"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     // Quality
     ToolDef {
         name: "memory_quality_report",
@@ -556,7 +762,7 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     },
     ToolDef {
         name: "memory_find_duplicates",
-        description: "Find potential duplicate memories",
+        description: "Find potential duplicate memories as pairs. For large corpora use memory_cluster_duplicates, which returns one group per set of duplicates.",
         schema: r#"{
             "type": "object",
             "properties": {
@@ -566,6 +772,41 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Standard,
     },
+    ToolDef {
+        name: "memory_cluster_duplicates",
+        description: "Group near-duplicate memories across the corpus instead of listing pairs. MinHash/LSH picks candidates, word-shingle Jaccard similarity confirms them, and transitive matches are merged into one group (within a workspace and scope). Each group names a canonical memory to keep (highest importance, then most accessed, then oldest) and memory_merge arguments. enqueue=true adds member/canonical pairs to the duplicate review queue (quality_get_duplicates).",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Cluster only this workspace (default: all)"},
+                "threshold": {"type": "number", "default": 0.7, "description": "Minimum word-shingle Jaccard similarity to join two memories"},
+                "shingle_size": {"type": "integer", "default": 3, "description": "Words per shingle"},
+                "num_hashes": {"type": "integer", "default": 128, "description": "MinHash signature length"},
+                "bands": {"type": "integer", "default": 32, "description": "LSH bands; must divide num_hashes. More bands find less similar pairs but compare more"},
+                "min_group_size": {"type": "integer", "default": 2, "description": "Smallest group to report"},
+                "limit": {"type": "integer", "default": 100, "description": "Maximum groups to return, largest first"},
+                "enqueue": {"type": "boolean", "default": false, "description": "Add each member/canonical pair to the duplicate review queue"}
+            }
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_check_duplicate",
+        description: "Check whether content already exists before creating it. Pass the content, or its content_hash computed client-side (sha256 of the lowercased, whitespace-collapsed text, formatted 'sha256:<hex>'). Matches the exact-duplicate check memory_create applies, within one scope and workspace. When content is given, near_duplicates lists memories with nearly the same wording, found through the MinHash signature index without embeddings.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "content": {"type": "string", "description": "Content to check"},
+                "content_hash": {"type": "string", "description": "Precomputed hash ('sha256:<hex>'), used when content is omitted"},
+                "scope": {"description": "Scope to check within, e.g. \"global\" or {\"user\": {\"user_id\": \"u1\"}} (default: global)"},
+                "workspace": {"type": "string", "description": "Workspace to check within (default: 'default')"},
+                "near_threshold": {"type": "number", "default": 0.8, "description": "With content: also list textual near-duplicates at or above this estimated word-shingle similarity"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "memory_find_semantic_duplicates",
         description: "Find semantically similar memories using embedding cosine similarity (LLM-powered dedup). Goes beyond hash/n-gram to detect paraphrased content.",
@@ -580,6 +821,25 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Standard,
     },
+    ToolDef {
+        name: "memory_similarity_matrix",
+        description: "Export the pairwise cosine similarity matrix of a set of memories' embeddings as JSON or CSV, for offline clustering and duplicate analysis. Select memories by ids, or by workspace, tags, type and filter (up to 2000).",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "ids": {"type": "array", "items": {"type": "integer"}, "description": "Exactly these memories, in this order (overrides the filters)"},
+                "workspace": {"type": "string", "description": "Filter by workspace"},
+                "tags": {"type": "array", "items": {"type": "string"}, "description": "Filter by tags"},
+                "memory_type": {"type": "string", "description": "Filter by memory type"},
+                "filter": {"type": "object", "description": "Advanced filter with AND/OR logic, as in memory_list"},
+                "limit": {"type": "integer", "default": 500, "maximum": 2000, "description": "Maximum memories to select with the filters"},
+                "format": {"type": "string", "enum": ["json", "csv"], "default": "json", "description": "json returns the matrix as nested arrays; csv returns it as a string with a header row of memory IDs"},
+                "precision": {"type": "integer", "minimum": 0, "maximum": 9, "description": "Round similarities to this many decimal places"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "memory_merge",
         description: "Merge duplicate memories",
@@ -702,7 +962,9 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "min_score": {"type": "number", "default": 0, "description": "Minimum edge score threshold"},
                 "min_confidence": {"type": "number", "default": 0, "description": "Minimum confidence threshold"},
                 "limit_per_hop": {"type": "integer", "default": 50, "description": "Max results per hop"},
-                "include_entities": {"type": "boolean", "default": true, "description": "Include entity-based connections"}
+                "include_entities": {"type": "boolean", "default": true, "description": "Include entity-based connections"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
             },
             "required": ["id"]
         }"#,
@@ -711,19 +973,88 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     },
     ToolDef {
         name: "memory_find_path",
-        description: "Find the shortest path between two memories in the knowledge graph",
+        description: "Find the shortest path between two memories in the knowledge graph. With weighted=true, finds the path through the strongest relationships instead of the fewest hops",
         schema: r#"{
             "type": "object",
             "properties": {
                 "from_id": {"type": "integer", "description": "Starting memory ID"},
                 "to_id": {"type": "integer", "description": "Target memory ID"},
-                "max_depth": {"type": "integer", "default": 5, "description": "Maximum path length to search"}
+                "max_depth": {"type": "integer", "default": 5, "description": "Maximum path length to search"},
+                "weighted": {"type": "boolean", "default": false, "description": "Minimize total edge cost (1 - score * confidence) instead of hop count; the response includes the path's cost"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
             },
             "required": ["from_id", "to_id"]
         }"#,
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Standard,
     },
+    ToolDef {
+        name: "memory_graph_query",
+        description: "Match a Cypher-like pattern against the knowledge graph, e.g. MATCH (a:decision)-[:depends_on*1..3]->(b:issue) WHERE a.tag = 'auth' RETURN a, b. Node labels are memory types; node properties: id, type, content, importance, tag, workspace, tier, created_at; single-hop relationship properties: type, score, confidence",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "MATCH pattern [WHERE condition] [RETURN vars] [LIMIT n]. Relationships: -[r:type1|type2]->, <-[...]-, -[...]-, variable length -[:type*1..3]-> (max 6 hops). WHERE supports =, !=, <, <=, >, >=, CONTAINS, STARTS WITH, AND, OR, NOT"},
+                "workspace": {"type": "string", "description": "Only match memories in this workspace"},
+                "limit": {"type": "integer", "default": 100, "maximum": 1000, "description": "Maximum rows when the query has no LIMIT"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
+            },
+            "required": ["query"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_similar_by_structure",
+        description: "Find memories that sit in similar places in the knowledge graph, using node2vec embeddings learned from random walks over cross-references. Complements text similarity: memories can be structurally alike without sharing words",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Memory to compare against; must have at least one link"},
+                "limit": {"type": "integer", "default": 10, "maximum": 100, "description": "Maximum results"},
+                "max_nodes": {"type": "integer", "default": 1000, "description": "Embed the graph of the N most recent memories"},
+                "dimensions": {"type": "integer", "default": 64, "description": "Embedding size"},
+                "walk_length": {"type": "integer", "default": 20, "description": "Nodes per random walk"},
+                "walks_per_node": {"type": "integer", "default": 10, "description": "Random walks started from each memory"},
+                "p": {"type": "number", "default": 1.0, "description": "Return parameter; higher values make walks less likely to step back"},
+                "q": {"type": "number", "default": 1.0, "description": "In-out parameter; below 1 favors memories with similar roles (hubs, bridges), above 1 favors memories in the same cluster"},
+                "seed": {"type": "integer", "default": 0, "description": "Random seed; the same seed and graph give the same results"}
+            },
+            "required": ["id"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_suggest_links",
+        description: "Suggest links between memories that are not linked but share neighbors in the knowledge graph, ranked by common neighbors, Adamic-Adar or Jaccard score. Each suggestion carries from_id and to_id, ready to pass to memory_link",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Only suggest links from this memory; omit to rank all unlinked pairs"},
+                "method": {"type": "string", "enum": ["common_neighbors", "adamic_adar", "jaccard"], "default": "adamic_adar", "description": "Score to rank by: shared neighbors, shared neighbors weighted against hubs, or shared over combined neighbors"},
+                "limit": {"type": "integer", "default": 10, "maximum": 100, "description": "Maximum suggestions"},
+                "max_nodes": {"type": "integer", "default": 1000, "description": "Analyze the graph of the N most recent memories"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_detect_contradiction_cycles",
+        description: "Find chains of 'supports' and 'contradicts' links that cannot all hold, such as A supports B, B contradicts C, C supports A. Returns each inconsistent cycle with its memories and links, the affected memory IDs, and suggested resolutions (the weakest link and the least important memory to review)",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "limit": {"type": "integer", "default": 20, "maximum": 200, "description": "Maximum cycles to return, shortest first"},
+                "max_nodes": {"type": "integer", "default": 1000, "description": "Analyze the graph of the N most recent memories"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     // Document Ingestion (RML-928)
     ToolDef {
         name: "memory_ingest_document",
@@ -795,124 +1126,312 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::destructive(),
         tier: ToolTier::Advanced,
     },
-    // Memory Tiering
     ToolDef {
-        name: "memory_create_daily",
-        description: "Create a daily (ephemeral) memory that auto-expires after the specified TTL. Useful for session context and scratch notes.",
+        name: "workspace_merge",
+        description: "Merge all memories of one workspace into another in a single transaction. Exact duplicates already in the target are collapsed (tags and cross-references move to the kept memory). Use dry_run to preview.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "content": {"type": "string", "description": "The content to remember"},
-                "type": {"type": "string", "enum": ["note", "todo", "issue", "decision", "preference", "learning", "context", "credential"], "default": "note"},
-                "tags": {"type": "array", "items": {"type": "string"}, "description": "Tags for categorization"},
-                "metadata": {"type": "object", "description": "Additional metadata as key-value pairs"},
-                "importance": {"type": "number", "minimum": 0, "maximum": 1, "description": "Importance score (0-1)"},
-                "ttl_seconds": {"type": "integer", "default": 86400, "description": "Time-to-live in seconds (default: 24 hours)"},
-                "workspace": {"type": "string", "description": "Workspace to store the memory in (default: 'default')"}
+                "from": {"type": "string", "description": "Workspace to merge (emptied afterwards)"},
+                "into": {"type": "string", "description": "Target workspace"},
+                "dedup": {"type": "boolean", "default": true, "description": "Collapse memories whose content already exists in the target"},
+                "origin_tag": {"type": "string", "description": "Tag added to moved memories to record where they came from"},
+                "dry_run": {"type": "boolean", "default": false, "description": "Report what would happen without writing"}
             },
-            "required": ["content"]
+            "required": ["from", "into"]
         }"#,
-        annotations: ToolAnnotations::mutating(),
-        tier: ToolTier::Standard,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "memory_promote_to_permanent",
-        description: "Promote a daily memory to permanent tier. Clears the expiration and makes the memory permanent.",
+        name: "workspace_split",
+        description: "Move the memories of a workspace that match a filter (tags, type, tier, metadata or advanced filter) into a new workspace in a single transaction. Use dry_run to preview.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "id": {"type": "integer", "description": "Memory ID to promote"}
+                "workspace": {"type": "string", "description": "Source workspace"},
+                "new_workspace": {"type": "string", "description": "Workspace receiving the matching memories"},
+                "tags": {"type": "array", "items": {"type": "string"}, "description": "Match memories with any of these tags"},
+                "memory_type": {"type": "string", "description": "Match memories of this type"},
+                "tier": {"type": "string", "enum": ["permanent", "daily"], "description": "Match memories in this tier"},
+                "filter": {"type": "object", "description": "Advanced filter expression (same syntax as memory_list)"},
+                "dry_run": {"type": "boolean", "default": false, "description": "Report what would happen without writing"}
             },
-            "required": ["id"]
+            "required": ["workspace", "new_workspace"]
         }"#,
         annotations: ToolAnnotations::mutating(),
-        tier: ToolTier::Standard,
+        tier: ToolTier::Advanced,
     },
-    // Embedding Cache
     ToolDef {
-        name: "embedding_cache_stats",
-        description: "Get statistics about the embedding cache (hits, misses, entries, bytes used, hit rate)",
+        name: "workspace_config_set",
+        description: "Set the defaults applied to memories created in a workspace, replacing any previous ones: base tags always added, and the tier, TTL, dedup mode and dedup threshold used when memory_create leaves them unset (tier permanent without ttl_seconds 0, no ttl_seconds on a daily memory, dedup_mode allow, no dedup_threshold), and the timezone and date order used to read date expressions.",
         schema: r#"{
             "type": "object",
-            "properties": {}
+            "properties": {
+                "workspace": {"type": "string", "description": "Workspace name"},
+                "base_tags": {"type": "array", "items": {"type": "string"}, "description": "Tags added to every new memory"},
+                "default_tier": {"type": "string", "enum": ["permanent", "daily"], "description": "Tier for memories that don't choose one"},
+                "default_ttl_seconds": {"type": "integer", "minimum": 1, "description": "TTL for daily memories that don't set one"},
+                "dedup_mode": {"type": "string", "enum": ["reject", "merge", "skip", "allow"], "description": "Dedup mode for memories that don't choose one"},
+                "dedup_threshold": {"type": "number", "minimum": 0, "maximum": 1, "description": "Semantic dedup threshold for memories that don't set one (see memory_calibrate_dedup)"},
+                "timezone": {"type": "string", "description": "UTC offset for reading when in memory_search and memory_get_timeline, e.g. +02:00"},
+                "date_order": {"type": "string", "enum": ["mdy", "dmy", "ymd"], "description": "How to read numeric dates such as 03/04 in when"}
+            },
+            "required": ["workspace"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "workspace_config_get",
+        description: "Get the memory defaults of a workspace (config is null if none are set)",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Workspace name"}
+            },
+            "required": ["workspace"]
         }"#,
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "embedding_cache_clear",
-        description: "Clear all entries from the embedding cache",
+        name: "workspace_config_list",
+        description: "List the memory defaults of every configured workspace",
         schema: r#"{
             "type": "object",
             "properties": {}
         }"#,
-        annotations: ToolAnnotations::destructive(),
+        annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
-    // Session Transcript Indexing
     ToolDef {
-        name: "session_index",
-        description: "Index a conversation into searchable memory chunks. Uses dual-limiter chunking (messages + characters) with overlap.",
+        name: "workspace_config_delete",
+        description: "Remove the memory defaults of a workspace. Existing memories are unchanged.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "session_id": {"type": "string", "description": "Unique session identifier"},
-                "messages": {
-                    "type": "array",
-                    "description": "Array of conversation messages",
-                    "items": {
-                        "type": "object",
-                        "properties": {
-                            "role": {"type": "string", "description": "Message role (user, assistant, system)"},
-                            "content": {"type": "string", "description": "Message content"},
-                            "timestamp": {"type": "string", "description": "ISO 8601 timestamp"},
-                            "id": {"type": "string", "description": "Optional message ID"}
-                        },
-                        "required": ["role", "content"]
-                    }
-                },
-                "title": {"type": "string", "description": "Optional session title"},
-                "workspace": {"type": "string", "description": "Workspace to store chunks in (default: 'default')"},
-                "agent_id": {"type": "string", "description": "Optional agent identifier"},
-                "max_messages": {"type": "integer", "default": 10, "description": "Max messages per chunk"},
-                "max_chars": {"type": "integer", "default": 8000, "description": "Max characters per chunk"},
-                "overlap": {"type": "integer", "default": 2, "description": "Overlap messages between chunks"},
-                "ttl_days": {"type": "integer", "default": 7, "description": "TTL for transcript chunks in days"}
+                "workspace": {"type": "string", "description": "Workspace name"}
             },
-            "required": ["session_id", "messages"]
+            "required": ["workspace"]
         }"#,
-        annotations: ToolAnnotations::mutating(),
-        tier: ToolTier::Essential,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "session_index_delta",
-        description: "Incrementally index new messages to an existing session. More efficient than full reindex.",
+        name: "workspace_share_create",
+        description: "Publish a workspace read-only through a share link. Anyone with the returned /public/<token> path on the HTTP transport can search the workspace and browse its graph without an API key; nothing else is reachable and nothing can be written. Requests per link are rate limited.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "session_id": {"type": "string", "description": "Session to update"},
-                "messages": {
-                    "type": "array",
-                    "description": "New messages to add",
-                    "items": {
-                        "type": "object",
-                        "properties": {
-                            "role": {"type": "string"},
-                            "content": {"type": "string"},
-                            "timestamp": {"type": "string"},
-                            "id": {"type": "string"}
-                        },
-                        "required": ["role", "content"]
-                    }
-                }
+                "workspace": {"type": "string", "description": "Workspace to publish"},
+                "label": {"type": "string", "description": "Name shown to visitors of the link"},
+                "requests_per_minute": {"type": "integer", "default": 60, "minimum": 1, "description": "Requests allowed per minute on this link; further requests get 429 Too Many Requests"},
+                "expires_in_days": {"type": "integer", "minimum": 1, "description": "Days until the link stops working (default: never)"}
             },
-            "required": ["session_id", "messages"]
+            "required": ["workspace"]
         }"#,
         annotations: ToolAnnotations::mutating(),
-        tier: ToolTier::Standard,
+        tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "session_get",
+        name: "workspace_share_list",
+        description: "List workspace share links, newest first",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Only links for this workspace"},
+                "include_inactive": {"type": "boolean", "default": false, "description": "Also list revoked and expired links"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "workspace_share_revoke",
+        description: "Revoke a workspace share link; it stops working immediately",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "token": {"type": "string", "description": "Share token (shr_...)"}
+            },
+            "required": ["token"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "workspace_share_view",
+        description: "What a share link serves: info (workspace, label, memory count, rate limit), search results or the vis.js graph, limited to the shared workspace and to public fields (id, content, type, tags, timestamps). Revoked or expired links answer 'Share not found'.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "token": {"type": "string", "description": "Share token (shr_...)"},
+                "view": {"type": "string", "enum": ["info", "search", "graph"], "default": "info"},
+                "query": {"type": "string", "description": "Search query (view=search)"},
+                "limit": {"type": "integer", "default": 20, "minimum": 1, "maximum": 50, "description": "Search results (view=search)"},
+                "max_nodes": {"type": "integer", "default": 200, "minimum": 1, "maximum": 500, "description": "Graph nodes (view=graph)"}
+            },
+            "required": ["token"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    // Memory Tiering
+    ToolDef {
+        name: "memory_create_daily",
+        description: "Create a daily (ephemeral) memory that auto-expires after the specified TTL. Useful for session context and scratch notes.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "content": {"type": "string", "description": "The content to remember"},
+                "type": {"type": "string", "enum": ["note", "todo", "issue", "decision", "preference", "learning", "context", "credential"], "default": "note"},
+                "tags": {"type": "array", "items": {"type": "string"}, "description": "Tags for categorization"},
+                "metadata": {"type": "object", "description": "Additional metadata as key-value pairs"},
+                "importance": {"type": "number", "minimum": 0, "maximum": 1, "description": "Importance score (0-1)"},
+                "ttl_seconds": {"type": "integer", "default": 86400, "description": "Time-to-live in seconds (default: 24 hours)"},
+                "workspace": {"type": "string", "description": "Workspace to store the memory in (default: 'default')"}
+            },
+            "required": ["content"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "memory_promote_to_permanent",
+        description: "Promote a daily memory to permanent tier. Clears the expiration and makes the memory permanent.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Memory ID to promote"}
+            },
+            "required": ["id"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Standard,
+    },
+    // Embedding Cache
+    ToolDef {
+        name: "embedding_cache_stats",
+        description: "Get statistics about the embedding cache (hits, misses, entries, bytes used, hit rate)",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "embedding_cache_clear",
+        description: "Clear all entries from the embedding cache",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
+    // Memory Cache
+    ToolDef {
+        name: "memory_read_cache_stats",
+        description: "Get statistics about the read-through memory cache used by memory_get (hits, misses, invalidations, evictions, entries, hit rate)",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    // Session Transcript Indexing
+    ToolDef {
+        name: "session_index",
+        description: "Index a conversation into searchable memory chunks. Uses dual-limiter chunking (messages + characters, optionally tokens) with overlap.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "session_id": {"type": "string", "description": "Unique session identifier"},
+                "messages": {
+                    "type": "array",
+                    "description": "Array of conversation messages",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "role": {"type": "string", "description": "Message role (user, assistant, system, tool)"},
+                            "content": {"type": "string", "description": "Message content (optional for tool calls)"},
+                            "timestamp": {"type": "string", "description": "ISO 8601 timestamp"},
+                            "id": {"type": "string", "description": "Optional message ID"},
+                            "kind": {"type": "string", "enum": ["text", "system", "tool_call", "tool_result"], "description": "Message kind; inferred from role and arguments when omitted"},
+                            "tool_name": {"type": "string", "description": "Tool called, or whose result this is"},
+                            "arguments": {"type": "object", "description": "Tool-call arguments; indexed as a short key=value summary"}
+                        },
+                        "required": ["role"]
+                    }
+                },
+                "title": {"type": "string", "description": "Optional session title; generated from the transcript (with a 2-3 sentence summary) when omitted"},
+                "workspace": {"type": "string", "description": "Workspace to store chunks in (default: 'default')"},
+                "agent_id": {"type": "string", "description": "Optional agent identifier"},
+                "max_messages": {"type": "integer", "default": 10, "description": "Max messages per chunk"},
+                "max_chars": {"type": "integer", "default": 8000, "description": "Max characters per chunk"},
+                "overlap": {"type": "integer", "default": 2, "description": "Overlap messages between chunks"},
+                "ttl_days": {"type": "integer", "default": 7, "description": "TTL for transcript chunks in days"},
+                "max_tool_result_chars": {"type": "integer", "default": 2000, "description": "Max characters kept from each tool result"},
+                "max_tokens": {"type": "integer", "description": "Optional max tokens per chunk, in addition to max_chars; recorded on the session and reused by session_index_delta"},
+                "model": {"type": "string", "description": "Model whose tokenizer measures max_tokens (e.g. gpt-4o, claude-3-opus)"},
+                "encoding": {"type": "string", "enum": ["cl100k_base", "o200k_base"], "description": "Tokenizer override for max_tokens (default: cl100k_base)"},
+                "denoise": {
+                    "type": ["boolean", "object"],
+                    "description": "Drop low-information messages before chunking: true for the defaults, or an object overriding rules. Removed-content counts are stored in the session metadata under 'denoise'",
+                    "properties": {
+                        "drop_greetings": {"type": "boolean", "default": true, "description": "Drop greetings and acknowledgements"},
+                        "max_greeting_chars": {"type": "integer", "default": 60, "description": "Longest message still treated as a greeting"},
+                        "drop_duplicates": {"type": "boolean", "default": true, "description": "Drop a message repeating the previous one"},
+                        "collapse_repeated_errors": {"type": "boolean", "default": true, "description": "Fold repeated identical errors (and their retried tool calls) into the first"},
+                        "drop_boilerplate": {"type": "boolean", "default": true, "description": "Drop empty and content-free tool output"},
+                        "boilerplate_patterns": {"type": "array", "items": {"type": "string"}, "description": "Extra regexes; matching messages are dropped"}
+                    }
+                }
+            },
+            "required": ["session_id", "messages"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Essential,
+    },
+    ToolDef {
+        name: "session_index_delta",
+        description: "Incrementally index new messages to an existing session. More efficient than full reindex.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "session_id": {"type": "string", "description": "Session to update"},
+                "messages": {
+                    "type": "array",
+                    "description": "New messages to add",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "role": {"type": "string"},
+                            "content": {"type": "string"},
+                            "timestamp": {"type": "string"},
+                            "id": {"type": "string"},
+                            "kind": {"type": "string", "enum": ["text", "system", "tool_call", "tool_result"]},
+                            "tool_name": {"type": "string"},
+                            "arguments": {"type": "object"}
+                        },
+                        "required": ["role"]
+                    }
+                },
+                "max_tokens": {"type": "integer", "description": "Max tokens per chunk; defaults to the limit recorded when the session was indexed"},
+                "model": {"type": "string", "description": "Model whose tokenizer measures max_tokens"},
+                "encoding": {"type": "string", "enum": ["cl100k_base", "o200k_base"], "description": "Tokenizer override for max_tokens"},
+                "denoise": {"type": ["boolean", "object"], "description": "De-noise config as for session_index; defaults to the config recorded when the session was indexed"}
+            },
+            "required": ["session_id", "messages"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "session_get",
         description: "Get information about an indexed session",
         schema: r#"{
             "type": "object",
@@ -926,7 +1445,7 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     },
     ToolDef {
         name: "session_list",
-        description: "List indexed sessions with optional workspace filter",
+        description: "List indexed sessions, with their titles and summaries, with optional workspace filter. Sessions linked by session_link_topics list each other under metadata.related_sessions",
         schema: r#"{
             "type": "object",
             "properties": {
@@ -937,6 +1456,22 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Essential,
     },
+    ToolDef {
+        name: "session_link_topics",
+        description: "Find indexed sessions that discuss the same entities and link them: a related_to crossref between their summary memories, a related_sessions list in each session's metadata, and one session_topic memory per group summarizing where the topic came up. Re-running replaces earlier results",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Only analyze sessions in this workspace (sessions are only linked within a workspace either way)"},
+                "min_shared_entities": {"type": "integer", "default": 2, "minimum": 1, "description": "Entities two sessions must share to be linked"},
+                "max_session_fraction": {"type": "number", "default": 0.5, "description": "Ignore entities mentioned in more than this fraction of sessions (applied from 4 sessions up)"},
+                "max_sessions": {"type": "integer", "default": 200, "description": "Most recent sessions to analyze"},
+                "dry_run": {"type": "boolean", "default": false, "description": "Report links and topics without writing anything"}
+            }
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "session_delete",
         description: "Delete a session and all its indexed chunks",
@@ -1121,6 +1656,85 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
+    // Preferences
+    ToolDef {
+        name: "preference_set",
+        description: "Set a namespaced user preference (e.g. 'editor.indent_width') at a scope. Returns the previous value and any disagreeing values of the same key at other scopes.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "key": {"type": "string", "description": "Dot-separated key, e.g. 'style.quotes'"},
+                "value": {"description": "Preference value (any JSON value)"},
+                "scope": {"type": "string", "enum": ["global", "workspace", "project", "session"], "default": "global"},
+                "scope_id": {"type": "string", "description": "Workspace, project or session ID (required unless scope is global)"}
+            },
+            "required": ["key", "value"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "preference_get",
+        description: "Resolve a preference in a lookup context. The most specific applicable scope wins: global < workspace < project < session. Overridden values are returned alongside.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "key": {"type": "string"},
+                "workspace": {"type": "string", "description": "Workspace the lookup happens in"},
+                "project": {"type": "string", "description": "Project the lookup happens in"},
+                "session": {"type": "string", "description": "Session the lookup happens in"}
+            },
+            "required": ["key"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "preference_list",
+        description: "List preferences, optionally under a key namespace. With effective=true, returns only the winning value of each key for the given workspace/project/session.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "namespace": {"type": "string", "description": "Key prefix, e.g. 'editor' matches 'editor.theme'"},
+                "effective": {"type": "boolean", "default": false},
+                "workspace": {"type": "string", "description": "Workspace the lookup happens in"},
+                "project": {"type": "string", "description": "Project the lookup happens in"},
+                "session": {"type": "string", "description": "Session the lookup happens in"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "preference_history",
+        description: "Show the values a preference has had at one scope, newest first.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "key": {"type": "string"},
+                "scope": {"type": "string", "enum": ["global", "workspace", "project", "session"], "default": "global"},
+                "scope_id": {"type": "string", "description": "Workspace, project or session ID (required unless scope is global)"}
+            },
+            "required": ["key"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "preference_unset",
+        description: "Remove a preference at one scope so less specific scopes apply again.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "key": {"type": "string"},
+                "scope": {"type": "string", "enum": ["global", "workspace", "project", "session"], "default": "global"},
+                "scope_id": {"type": "string", "description": "Workspace, project or session ID (required unless scope is global)"}
+            },
+            "required": ["key"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
     // Content Utilities
     ToolDef {
         name: "memory_soft_trim",
@@ -1200,104 +1814,362 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
             },
             "required": ["memories"]
         }"#,
-        annotations: ToolAnnotations::mutating(),
-        tier: ToolTier::Standard,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "memory_delete_batch",
+        description: "Delete multiple memories in a single operation.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "ids": {
+                    "type": "array",
+                    "items": {"type": "integer"},
+                    "description": "Array of memory IDs to delete"
+                }
+            },
+            "required": ["ids"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Standard,
+    },
+    // Tag Utilities
+    ToolDef {
+        name: "memory_tags",
+        description: "List all tags with usage counts and most recent usage timestamps.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "memory_tag_hierarchy",
+        description: "Get tags organized in a hierarchical tree structure. Tags with slashes are treated as paths (e.g., 'project/engram/core'); each node counts the memories tagged with its path or below it. Use format html or dot to draw the tree for auditing the tag taxonomy.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "format": {"type": "string", "enum": ["tree", "html", "dot"], "default": "tree", "description": "tree returns the nested hierarchy as JSON; html a top-down vis.js tree whose tags fold on click; dot a Graphviz digraph"},
+                "max_depth": {"type": "integer", "minimum": 1, "description": "For html/dot: deepest tag level drawn, roots being level 1"},
+                "min_count": {"type": "integer", "default": 0, "description": "For html/dot: leave out tags (and their subtags) with fewer memories"},
+                "link_template": {"type": "string", "description": "For html/dot: drill-down URL for each tag, with {tag} replaced by its URL-encoded path (e.g. 'https://notes.example/tags/{tag}'). Opened on double-click in html; a node URL in dot"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_validate_tags",
+        description: "Validate tag consistency across memories. Reports orphaned tags, unused tags, and suggested normalizations.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_synonym_add",
+        description: "Make alias tags resolve to a canonical tag. Memories created or updated with an alias get the canonical tag, and tag filters in search and list match every tag in the group. With retag (default), memories already tagged with an alias are retagged with the canonical tag. Adding to an alias adds to its group; a canonical tag added as an alias brings its aliases along.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "canonical": {"type": "string", "description": "Tag the aliases resolve to"},
+                "aliases": {"type": "array", "items": {"type": "string"}, "description": "Tags that should mean the canonical tag"},
+                "retag": {"type": "boolean", "default": true, "description": "Move memories tagged with an alias to the canonical tag"}
+            },
+            "required": ["canonical", "aliases"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_synonym_remove",
+        description: "Stop an alias tag resolving to its canonical tag. Memories already retagged keep the canonical tag.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "alias": {"type": "string", "description": "Alias to remove from its synonym group"}
+            },
+            "required": ["alias"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_synonym_list",
+        description: "List tag synonym groups: each canonical tag with the aliases resolving to it.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_synonym_suggest",
+        description: "Suggest tags to merge into synonym groups: pairs that normalize to the same tag (case, separators, plurals), or whose names are within a small edit distance and which co-occur with the same other tags. The more used tag of each pair is proposed as canonical; apply a suggestion with tag_synonym_add.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "min_score": {"type": "number", "minimum": 0, "maximum": 1, "default": 0.75, "description": "Minimum score: 1 for the same normalized tag, otherwise 0.6 x name similarity + 0.4 x co-occurrence similarity"},
+                "limit": {"type": "integer", "minimum": 1, "default": 50, "description": "Maximum suggestions"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_normalization_get",
+        description: "Get the normalization rules applied to tags when memories are created or updated.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_normalization_set",
+        description: "Set the normalization rules applied to tags when memories are created or updated, replacing the previous ones: lowercase ('Bug' -> 'bug'), hyphenate spaces and underscores ('bug_fix' -> 'bug-fix') and singularize plurals ('bugs' -> 'bug'). With apply_existing, existing tags are renamed or merged into their normalized forms too.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "lowercase": {"type": "boolean", "default": false},
+                "hyphenate": {"type": "boolean", "default": false},
+                "singularize": {"type": "boolean", "default": false, "description": "Singularize the last word of each '/' segment"},
+                "apply_existing": {"type": "boolean", "default": false, "description": "Rewrite existing tags with the new rules"}
+            }
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_policy_get",
+        description: "Get the tag policy enforced when memories are created or updated.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_policy_set",
+        description: "Set the tag policy enforced when memories are created or updated, replacing the previous one. Writes breaking it fail with an error naming each offending tag. Tags are checked after normalization and synonym resolution. An empty policy allows any tag; use tag_policy_report to find existing memories that break a policy.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "allowed_prefixes": {"type": "array", "items": {"type": "string"}, "description": "Prefixes every tag must start with, e.g. ['category:', 'entity:']; empty allows any"},
+                "allow_plain": {"type": "boolean", "default": true, "description": "Allow tags without a 'namespace:' even when allowed_prefixes is set"},
+                "patterns": {"type": "array", "items": {"type": "string"}, "description": "Regexes; every tag must match at least one"},
+                "max_tags": {"type": "integer", "minimum": 0, "description": "Most tags per memory"},
+                "reserved_namespaces": {"type": "array", "items": {"type": "string"}, "description": "Namespaces only engram writes, e.g. ['status', 'session']; clients can't set tags in them"}
+            }
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "tag_policy_report",
+        description: "Audit stored memories against the tag policy: memories in violation with the rules they break, counts per rule, and offending tags by how many memories carry them. Tags in reserved namespaces are engram's own and only count towards max_tags.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Only memories in this workspace"},
+                "limit": {"type": "integer", "minimum": 0, "default": 100, "description": "Most memories listed; counts cover all"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    // Import/Export
+    ToolDef {
+        name: "memory_export",
+        description: "Export all memories to a JSON-serializable format for backup or migration.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Optional: export only from specific workspace"},
+                "include_embeddings": {"type": "boolean", "default": false, "description": "Include embedding vectors in export (larger file size)"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_import",
+        description: "Import memories from a previously exported JSON format.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "data": {"type": "object", "description": "The exported data object"},
+                "skip_duplicates": {"type": "boolean", "default": true, "description": "Skip memories with matching content hash"}
+            },
+            "required": ["data"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    // Maintenance
+    ToolDef {
+        name: "memory_rebuild_embeddings",
+        description: "Rebuild embeddings for all memories that are missing them. Useful after model changes or data recovery. By default only queues memories for re-embedding; with parallel=true a background rebuild with bounded concurrency starts and a task_id is returned for memory_rebuild_embeddings_status.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "parallel": {"type": "boolean", "default": false, "description": "Embed the queue now in a background worker pool and return a task_id"},
+                "resume_task_id": {"type": "string", "description": "Resume an interrupted parallel rebuild from its checkpoint instead of re-queuing everything"},
+                "concurrency": {"type": "integer", "minimum": 1, "default": 4, "description": "Batches embedded concurrently"},
+                "batch_size": {"type": "integer", "minimum": 1, "default": 64, "description": "Memories per embedding request"},
+                "requests_per_minute": {"type": "integer", "minimum": 1, "description": "Provider rate limit; embedding requests are spaced to stay under it"}
+            }
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_rebuild_embeddings_status",
+        description: "Progress of a parallel embedding rebuild: items processed, percent complete, ETA in seconds and last error",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "task_id": {"type": "string", "description": "Task ID returned by memory_rebuild_embeddings"}
+            },
+            "required": ["task_id"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_migrate_embeddings",
+        description: "Re-embed every memory with the server's current embedding model (e.g. after switching tfidf to openai) as a resumable background migration. Vectors are replaced in place, so search keeps using old ones until each is re-embedded; progress is checkpointed per batch and an interrupted migration resumes on the next server start. Track it with memory_embedding_status.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "action": {"type": "string", "enum": ["start", "resume", "pause", "cancel"], "default": "start"},
+                "id": {"type": "string", "description": "Migration ID (required for resume, pause, cancel)"},
+                "batch_size": {"type": "integer", "minimum": 1, "default": 64, "description": "Memories per embedding request (start only)"},
+                "requests_per_minute": {"type": "integer", "minimum": 1, "description": "Provider rate limit (start only)"}
+            }
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "sync_task_list",
+        description: "List recent background tasks (embedding rebuilds, Langfuse syncs) with status and progress, newest first",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "task_type": {"type": "string", "description": "Only tasks of this type, e.g. embedding_rebuild"},
+                "limit": {"type": "integer", "default": 20, "minimum": 1, "maximum": 200}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "memory_delete_batch",
-        description: "Delete multiple memories in a single operation.",
+        name: "memory_rebuild_crossrefs",
+        description: "Rebuild cross-reference links between memories. Re-analyzes all memories to find and create links.",
         schema: r#"{
             "type": "object",
-            "properties": {
-                "ids": {
-                    "type": "array",
-                    "items": {"type": "integer"},
-                    "description": "Array of memory IDs to delete"
-                }
-            },
-            "required": ["ids"]
+            "properties": {}
         }"#,
-        annotations: ToolAnnotations::destructive(),
-        tier: ToolTier::Standard,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
     },
-    // Tag Utilities
     ToolDef {
-        name: "memory_tags",
-        description: "List all tags with usage counts and most recent usage timestamps.",
+        name: "memory_rebuild_adjacency",
+        description: "Rebuild the materialized adjacency table that memory_traverse and memory_find_path read from. It is kept up to date on every cross-reference write, so this is only needed to repair drift; returns the number of live edges and of rows repaired.",
         schema: r#"{
             "type": "object",
             "properties": {}
         }"#,
-        annotations: ToolAnnotations::read_only(),
-        tier: ToolTier::Standard,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "memory_tag_hierarchy",
-        description: "Get tags organized in a hierarchical tree structure. Tags with slashes are treated as paths (e.g., 'project/engram/core').",
+        name: "memory_rebuild_signatures",
+        description: "Recompute the MinHash text signatures used to find near-duplicates on create and in memory_check_duplicate. Creates and updates keep them current, so this is only needed after content was changed outside the normal write paths; returns the number of memories scanned and indexed.",
         schema: r#"{
             "type": "object",
             "properties": {}
         }"#,
-        annotations: ToolAnnotations::read_only(),
+        annotations: ToolAnnotations::idempotent(),
         tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "memory_validate_tags",
-        description: "Validate tag consistency across memories. Reports orphaned tags, unused tags, and suggested normalizations.",
+        name: "memory_rebuild_vocabulary",
+        description: "Recount the persisted TF-IDF vocabulary (document frequencies used by the tfidf embedding model) over all live memories. New memories are counted as they are created, but updates and deletes are not subtracted, so rebuild after large edits or cleanups; returns the document and term counts and the new generation.",
         schema: r#"{
             "type": "object",
             "properties": {}
         }"#,
-        annotations: ToolAnnotations::read_only(),
+        annotations: ToolAnnotations::idempotent(),
         tier: ToolTier::Advanced,
     },
-    // Import/Export
     ToolDef {
-        name: "memory_export",
-        description: "Export all memories to a JSON-serializable format for backup or migration.",
+        name: "memory_vector_index_rebuild",
+        description: "Rebuild the approximate nearest-neighbour index from stored embeddings. The backend defaults to ENGRAM_VECTOR_INDEX (hnsw unless set) and can be switched per rebuild: flat scans exactly, hnsw is a navigable graph, ivf_pq quantizes vectors to a fraction of their size. Returns the index stats, including a recall@k estimate against exact search and memory usage.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "workspace": {"type": "string", "description": "Optional: export only from specific workspace"},
-                "include_embeddings": {"type": "boolean", "default": false, "description": "Include embedding vectors in export (larger file size)"}
+                "backend": {"type": "string", "enum": ["flat", "hnsw", "ivf_pq"], "description": "Index backend to build (default: configured backend)"}
             }
         }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_vector_index_stats",
+        description: "Report the active vector index: backend, vector count, dimensions, memory usage in bytes, and the recall estimate measured at the last rebuild.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "memory_import",
-        description: "Import memories from a previously exported JSON format.",
+        name: "memory_reduce_embeddings",
+        description: "Shrink stored embeddings to the size configured by ENGRAM_EMBEDDING_REDUCTION: truncate:<dims> keeps the leading dimensions of Matryoshka models, pca:<dims> fits principal components over a sample of the corpus first (restore to refit). New and query embeddings are reduced the same way from then on. Full-size originals are kept for memory_restore_embeddings unless keep_originals is false. Returns counts, bytes before and after, and for PCA the variance kept.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "data": {"type": "object", "description": "The exported data object"},
-                "skip_duplicates": {"type": "boolean", "default": true, "description": "Skip memories with matching content hash"}
-            },
-            "required": ["data"]
+                "fit_sample": {"type": "integer", "default": 5000, "description": "Embeddings sampled to fit PCA"},
+                "keep_originals": {"type": "boolean", "default": true, "description": "Keep full-size vectors so the reduction can be undone"},
+                "seed": {"type": "integer", "default": 42}
+            }
         }"#,
         annotations: ToolAnnotations::mutating(),
         tier: ToolTier::Advanced,
     },
-    // Maintenance
     ToolDef {
-        name: "memory_rebuild_embeddings",
-        description: "Rebuild embeddings for all memories that are missing them. Useful after model changes or data recovery.",
+        name: "memory_fit_embedding_adapter",
+        description: "Bridge vectors of an older embedding model into the current model's space while a migration is running. Re-embeds a sample of memories still on the old model, fits a linear projection on the pairs and stores it; similarity search then projects the old vectors instead of ignoring them. Returns the sample size and the mean cosine between projected and re-embedded vectors on a held-out fifth of the sample.",
         schema: r#"{
             "type": "object",
-            "properties": {}
+            "properties": {
+                "source_model": {"type": "string", "description": "Model to bridge from (default: the most common model other than the current one)"},
+                "sample_size": {"type": "integer", "default": 256, "description": "Memories re-embedded as training pairs"},
+                "ridge": {"type": "number", "default": 0.01, "description": "Regularization of the fit"},
+                "seed": {"type": "integer", "default": 42}
+            }
         }"#,
-        annotations: ToolAnnotations::idempotent(),
+        annotations: ToolAnnotations::mutating(),
         tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "memory_rebuild_crossrefs",
-        description: "Rebuild cross-reference links between memories. Re-analyzes all memories to find and create links.",
+        name: "memory_restore_embeddings",
+        description: "Undo memory_reduce_embeddings: put kept full-size vectors back and stop reducing for this session. Reduced embeddings without a kept original are queued for re-embedding. Unset ENGRAM_EMBEDDING_REDUCTION before restarting to stay at full size.",
         schema: r#"{
             "type": "object",
             "properties": {}
         }"#,
-        annotations: ToolAnnotations::idempotent(),
+        annotations: ToolAnnotations::mutating(),
         tier: ToolTier::Advanced,
     },
     // Special Memory Types
@@ -1380,6 +2252,9 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
             "properties": {
                 "start_time": {"type": "string", "format": "date-time", "description": "Start of time range (ISO8601)"},
                 "end_time": {"type": "string", "format": "date-time", "description": "End of time range (ISO8601)"},
+                "when": {"type": "string", "description": "Natural-language time range, e.g. \"yesterday\", \"last week\", \"in March\"; fills in start_time and end_time when they are not given"},
+                "timezone": {"type": "string", "description": "UTC offset for reading when, e.g. +02:00 or UTC-5 (default: the workspace's timezone, then UTC)"},
+                "date_order": {"type": "string", "enum": ["mdy", "dmy", "ymd"], "description": "How to read numeric dates in when, e.g. 03/04 (default: the workspace's date order, then mdy)"},
                 "workspace": {"type": "string", "description": "Filter by workspace"},
                 "tags": {"type": "array", "items": {"type": "string"}, "description": "Filter by tags"},
                 "limit": {"type": "integer", "default": 50, "description": "Maximum results to return"}
@@ -1493,6 +2368,36 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
+    ToolDef {
+        name: "context_score",
+        description: "Score candidate memories and/or raw snippets for a prompt you are assembling yourself. Returns per-item relevance to the task, quality, redundancy with higher-ranked items, and a suggested keep/drop set that fits the token budget.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "task": {"type": "string", "description": "What the prompt is for; relevance is measured against this"},
+                "memory_ids": {
+                    "type": "array",
+                    "items": {"type": "integer"},
+                    "description": "Candidate memories"
+                },
+                "snippets": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Candidate raw text, scored after memory_ids (item index continues from there)"
+                },
+                "budget": {"type": "integer", "description": "Token budget for kept items; omit to keep everything relevant and non-redundant"},
+                "model": {"type": "string", "description": "Model name for exact token counts (gpt-4, gpt-4o, ...). Defaults to a ~4 chars/token estimate."},
+                "encoding": {"type": "string", "description": "Override encoding (cl100k_base, o200k_base)"},
+                "relevance_weight": {"type": "number", "default": 0.8, "description": "Weight of relevance in the ranking score"},
+                "quality_weight": {"type": "number", "default": 0.2, "description": "Weight of memory quality in the ranking score (snippets count as 0.5)"},
+                "redundancy_threshold": {"type": "number", "default": 0.85, "description": "Drop items at least this similar to an item already kept"},
+                "min_relevance": {"type": "number", "default": 0.1, "description": "Drop items less relevant than this"}
+            },
+            "required": ["task"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "memory_archive_old",
         description: "Archive old, low-importance memories by creating summaries. Moves originals to archived state.",
@@ -1528,13 +2433,14 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     #[cfg(feature = "langfuse")]
     ToolDef {
         name: "langfuse_sync",
-        description: "Start background sync from Langfuse traces to memories. Returns task_id for status checking.",
+        description: "Start background sync from Langfuse traces to memories. Returns task_id for status checking. Trace scores set importance and tags; low-scored traces become learnings tagged failure-mode. Each trace maps to one memory per workspace, so re-syncing an overlapping window updates changed traces and skips the rest; the result reports memories_created, memories_updated and traces_skipped.",
         schema: r#"{
             "type": "object",
             "properties": {
                 "since": {"type": "string", "format": "date-time", "description": "Sync traces since this timestamp (default: 24h ago)"},
                 "limit": {"type": "integer", "default": 100, "description": "Maximum traces to sync"},
                 "workspace": {"type": "string", "description": "Workspace to create memories in"},
+                "on_existing": {"type": "string", "enum": ["update", "skip"], "default": "update", "description": "What to do with a trace that already has a memory: refresh it if the trace changed since, or always leave it alone"},
                 "dry_run": {"type": "boolean", "default": false, "description": "Preview what would be synced without creating memories"}
             }
         }"#,
@@ -1573,12 +2479,12 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     #[cfg(feature = "langfuse")]
     ToolDef {
         name: "memory_from_trace",
-        description: "Create a memory from a specific Langfuse trace ID.",
+        description: "Create a memory from a specific Langfuse trace ID. Trace scores set importance and tags; low-scored traces become learnings tagged failure-mode.",
         schema: r#"{
             "type": "object",
             "properties": {
                 "trace_id": {"type": "string", "description": "Langfuse trace ID"},
-                "memory_type": {"type": "string", "enum": ["note", "episodic", "procedural", "learning"], "default": "episodic", "description": "Type of memory to create"},
+                "memory_type": {"type": "string", "enum": ["note", "episodic", "procedural", "learning"], "description": "Type of memory to create (default: episodic, or learning for a low-scored trace)"},
                 "workspace": {"type": "string", "description": "Workspace for the memory"},
                 "tags": {"type": "array", "items": {"type": "string"}, "description": "Additional tags"}
             },
@@ -1587,6 +2493,40 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::mutating(),
         tier: ToolTier::Advanced,
     },
+    #[cfg(feature = "langfuse")]
+    ToolDef {
+        name: "langfuse_import_scores",
+        description: "Backfill Langfuse scores onto already-imported trace memories: refetch each trace, store its scores in metadata, adjust importance and add score tags. Low-scored traces become learnings tagged failure-mode.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "limit": {"type": "integer", "default": 100, "minimum": 1, "maximum": 1000, "description": "Maximum langfuse-tagged memories to check, most recent first"},
+                "workspace": {"type": "string", "description": "Only memories in this workspace"},
+                "retype": {"type": "boolean", "default": true, "description": "Turn low-scored trace memories into learning memories"},
+                "dry_run": {"type": "boolean", "default": false, "description": "Report the changes without applying them"}
+            }
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    // OpenTelemetry GenAI span ingestion - feature-gated
+    #[cfg(feature = "otel")]
+    ToolDef {
+        name: "otel_ingest_traces",
+        description: "Ingest an OTLP/HTTP JSON trace export (ExportTraceServiceRequest). Spans following the GenAI semantic conventions (gen_ai.*, or OpenInference llm.*) become episodic memories with model, tokens, prompt and completion; other spans are ignored. Spans already ingested into the workspace are skipped, and models with 3 or more failed spans in the batch become an issue memory. The HTTP transport accepts the same body at POST /v1/traces.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "resourceSpans": {"type": "array", "items": {"type": "object"}, "description": "OTLP resourceSpans, as in the JSON body an OTLP exporter sends"},
+                "workspace": {"type": "string", "description": "Workspace to create memories in"},
+                "extract_patterns": {"type": "boolean", "default": true, "description": "Store recurring failures of a model as an issue memory"},
+                "dry_run": {"type": "boolean", "default": false, "description": "Report the GenAI spans found without creating memories"}
+            },
+            "required": ["resourceSpans"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
     // Phase 4: Search Result Caching (ENG-36)
     ToolDef {
         name: "search_cache_feedback",
@@ -1625,6 +2565,181 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::destructive(),
         tier: ToolTier::Advanced,
     },
+    // Ranking experiments (A/B testing)
+    ToolDef {
+        name: "search_experiment_create",
+        description: "Create a ranking A/B experiment. Searches passing experiment_id are routed to arm a or b deterministically by query hash; each arm overrides SearchConfig fields (keyword_weight, semantic_weight, rrf_k, min_score, project_context_boost).",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Unique experiment name"},
+                "workspace": {"type": "string", "description": "Only apply to searches in this workspace (optional)"},
+                "variant_a": {"type": "object", "description": "Ranking overrides for arm a (empty = current config)"},
+                "variant_b": {"type": "object", "description": "Ranking overrides for arm b"},
+                "traffic_b": {"type": "number", "minimum": 0, "maximum": 1, "default": 0.5, "description": "Fraction of queries routed to arm b"}
+            },
+            "required": ["name"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "search_experiment_list",
+        description: "List ranking experiments.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "active_only": {"type": "boolean", "default": false}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "search_experiment_stop",
+        description: "Stop a ranking experiment. Recorded events are kept for reporting.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "experiment_id": {"type": "integer"}
+            },
+            "required": ["experiment_id"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "search_experiment_outcome",
+        description: "Record an outcome signal (used, useful, irrelevant) for a query served by an experiment. The arm is derived from the query.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "experiment_id": {"type": "integer"},
+                "query": {"type": "string", "description": "The original search query"},
+                "outcome": {"type": "string", "enum": ["used", "useful", "irrelevant"]},
+                "memory_id": {"type": "integer", "description": "Result the signal refers to (optional)"}
+            },
+            "required": ["experiment_id", "query", "outcome"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "search_experiment_report",
+        description: "Compare both arms of a ranking experiment and report the winner when the difference in positive outcome rate is significant (95%).",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "experiment_id": {"type": "integer"},
+                "min_outcomes": {"type": "integer", "default": 30, "description": "Judged outcomes required per arm before declaring a winner"}
+            },
+            "required": ["experiment_id"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    // Adaptive strategy selection
+    ToolDef {
+        name: "search_strategy_outcome",
+        description: "Report that a result of a memory_search was actually used. Credits the strategy (keyword, hybrid, semantic) that served the latest search for this query and refits the workspace's word-count thresholds once enough searches are logged. Searches with explain=true show the selection in strategy_selection.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "The original search query"},
+                "workspace": {"type": "string", "default": "default", "description": "Workspace the search ran in"}
+            },
+            "required": ["query"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "search_strategy_stats",
+        description: "Show how search strategies perform in a workspace: searches served and used per word count, entity class and strategy, plus the thresholds learned from them.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "default": "default"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    // Contextual boost rules
+    ToolDef {
+        name: "boost_rule_create",
+        description: "Create a contextual ranking rule: when the condition holds (cwd prefix, agent, workspace, UTC hour window, active session tags), add weight to the score of memories matching the target tags or types. Negative weights penalize. Applied rules appear in each hit's match_info.boosts.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Unique rule name"},
+                "weight": {"type": "number", "minimum": -1.0, "maximum": 1.0, "description": "Score adjustment for matching memories"},
+                "condition": {
+                    "type": "object",
+                    "description": "All populated fields must match; omit for an unconditional rule",
+                    "properties": {
+                        "cwd_prefix": {"type": "string"},
+                        "agent_id": {"type": "string"},
+                        "workspace": {"type": "string"},
+                        "hour_start": {"type": "integer", "minimum": 0, "maximum": 23},
+                        "hour_end": {"type": "integer", "minimum": 0, "maximum": 23},
+                        "session_tags": {"type": "array", "items": {"type": "string"}}
+                    }
+                },
+                "target": {
+                    "type": "object",
+                    "description": "Memories with any of these tags or types",
+                    "properties": {
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "memory_types": {"type": "array", "items": {"type": "string"}}
+                    }
+                }
+            },
+            "required": ["name", "weight", "target"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "boost_rule_list",
+        description: "List contextual boost rules.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "enabled_only": {"type": "boolean", "default": false}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "boost_rule_update",
+        description: "Change a boost rule's weight or enable/disable it.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "rule_id": {"type": "integer"},
+                "weight": {"type": "number", "minimum": -1.0, "maximum": 1.0},
+                "enabled": {"type": "boolean"}
+            },
+            "required": ["rule_id"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "boost_rule_delete",
+        description: "Delete a boost rule.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "rule_id": {"type": "integer"}
+            },
+            "required": ["rule_id"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
     // Phase 5: Memory Lifecycle Management (ENG-37)
     ToolDef {
         name: "lifecycle_status",
@@ -1710,46 +2825,117 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         schema: r#"{
             "type": "object",
             "properties": {
-                "workspace": {"type": "string", "description": "Workspace name"}
-            },
-            "required": ["workspace"]
+                "workspace": {"type": "string", "description": "Workspace name"}
+            },
+            "required": ["workspace"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "retention_policy_list",
+        description: "List all retention policies across all workspaces.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "retention_policy_delete",
+        description: "Delete a retention policy for a workspace.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Workspace name"}
+            },
+            "required": ["workspace"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "retention_policy_apply",
+        description: "Apply all retention policies now. Compresses, caps, and deletes per workspace rules.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "dry_run": {"type": "boolean", "default": false, "description": "Preview what would happen without making changes"}
+            }
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "policy_simulate",
+        description: "Simulate lifecycle, TTL, retention and salience decay rules over the current corpus without changing anything. Reports per workspace what would be demoted, archived or deleted, with histograms by memory type and age.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Limit to specific workspace"},
+                "lifecycle": {"type": "boolean", "default": true, "description": "Simulate lifecycle_run"},
+                "ttl": {"type": "boolean", "default": true, "description": "Simulate purging expired memories"},
+                "retention": {"type": "boolean", "default": true, "description": "Simulate stored retention policies"},
+                "salience": {"type": "boolean", "default": true, "description": "Simulate salience decay"},
+                "lifecycle_rules": {
+                    "type": "object",
+                    "description": "Hypothetical lifecycle thresholds",
+                    "properties": {
+                        "stale_days": {"type": "integer", "default": 30},
+                        "archive_days": {"type": "integer", "default": 90},
+                        "min_importance": {"type": "number", "default": 0.5},
+                        "min_access_count": {"type": "integer", "default": 5}
+                    }
+                },
+                "expired_grace_days": {"type": "integer", "default": 7, "description": "Days an expired memory is kept before purge"},
+                "salience_config": {"type": "object", "description": "Hypothetical salience settings (e.g. stale_threshold_days, archive_threshold_days, recency_half_life_days)"},
+                "sample_size": {"type": "integer", "default": 10, "description": "Example changes listed per workspace"}
+            }
         }"#,
         annotations: ToolAnnotations::read_only(),
-        tier: ToolTier::Standard,
+        tier: ToolTier::Advanced,
     },
+    // Superseded memories
     ToolDef {
-        name: "retention_policy_list",
-        description: "List all retention policies across all workspaces.",
+        name: "memory_supersede",
+        description: "Mark a memory as superseded/incorrect. It is kept for audit but excluded from search, list and context assembly unless show_superseded is set. Resolving a quality conflict with keep_a/keep_b does this automatically.",
         schema: r#"{
             "type": "object",
-            "properties": {}
+            "properties": {
+                "id": {"type": "integer", "description": "Memory to suppress"},
+                "superseded_by": {"type": "integer", "description": "Memory that replaces it"},
+                "reason": {"type": "string"}
+            },
+            "required": ["id"]
         }"#,
-        annotations: ToolAnnotations::read_only(),
-        tier: ToolTier::Standard,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "retention_policy_delete",
-        description: "Delete a retention policy for a workspace.",
+        name: "memory_restore_superseded",
+        description: "Clear a memory's superseded marker so it is retrieved again.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "workspace": {"type": "string", "description": "Workspace name"}
+                "id": {"type": "integer"}
             },
-            "required": ["workspace"]
+            "required": ["id"]
         }"#,
-        annotations: ToolAnnotations::destructive(),
+        annotations: ToolAnnotations::idempotent(),
         tier: ToolTier::Advanced,
     },
     ToolDef {
-        name: "retention_policy_apply",
-        description: "Apply all retention policies now. Compresses, caps, and deletes per workspace rules.",
+        name: "memory_list_superseded",
+        description: "List superseded memories with what replaced them and why, most recent first.",
         schema: r#"{
             "type": "object",
             "properties": {
-                "dry_run": {"type": "boolean", "default": false, "description": "Preview what would happen without making changes"}
+                "workspace": {"type": "string"},
+                "limit": {"type": "integer", "default": 50}
             }
         }"#,
-        annotations: ToolAnnotations::idempotent(),
+        annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
     // Event System
@@ -1945,7 +3131,9 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
             "properties": {
                 "identity": {"type": "string", "description": "Identity name or alias to search for"},
                 "workspace": {"type": "string", "description": "Optional: limit search to specific workspace"},
-                "limit": {"type": "integer", "default": 50, "description": "Maximum results to return"}
+                "limit": {"type": "integer", "default": 50, "description": "Maximum results to return"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
             },
             "required": ["identity"]
         }"#,
@@ -1961,7 +3149,12 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "query": {"type": "string", "description": "Search query"},
                 "session_id": {"type": "string", "description": "Optional: limit to specific session"},
                 "workspace": {"type": "string", "description": "Optional: limit to specific workspace"},
-                "limit": {"type": "integer", "default": 20, "description": "Maximum results to return"}
+                "role": {"type": "string", "description": "Optional: only chunks with a message from this role"},
+                "kind": {"type": "string", "enum": ["text", "system", "tool_call", "tool_result"], "description": "Optional: only chunks with a message of this kind"},
+                "tool_name": {"type": "string", "description": "Optional: only chunks with a call to or result from this tool"},
+                "limit": {"type": "integer", "default": 20, "description": "Maximum results to return"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
             },
             "required": ["query"]
         }"#,
@@ -2289,6 +3482,28 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
+    ToolDef {
+        name: "recommendations_get",
+        description: "Recommend memories the agent has not retrieved yet but probably needs, ranked by embedding similarity to the session's context and recently used memories and by cross-references from those memories. Memories already linked to the session are never recommended.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "session_id": {"type": "string", "description": "Session whose active context, latest transcript chunks and linked memories drive the recommendations"},
+                "context": {"type": "string", "description": "Current context (recent messages); replaces the session's stored context"},
+                "project": {"type": "string", "description": "Active project, added to the context"},
+                "used_ids": {"type": "array", "items": {"type": "integer"}, "description": "Memories recently used outside a session"},
+                "exclude_ids": {"type": "array", "items": {"type": "integer"}, "description": "Memories never to recommend"},
+                "workspace": {"type": "string", "description": "Workspace to recommend from (default: the session's)"},
+                "limit": {"type": "integer", "default": 5},
+                "min_score": {"type": "number", "default": 0.3, "description": "Minimum combined score"},
+                "similarity_weight": {"type": "number", "default": 0.6},
+                "graph_weight": {"type": "number", "default": 0.4},
+                "recent_memories": {"type": "integer", "default": 20, "description": "Most recently used session memories taken as seeds"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     // Phase 9: Context Quality (ENG-48 to ENG-66)
     ToolDef {
         name: "quality_score",
@@ -2340,6 +3555,35 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
+    ToolDef {
+        name: "quality_resolve_duplicate",
+        description: "Confirm or reject a duplicate candidate from quality_find_duplicates. Verdicts are the labelled pairs memory_calibrate_dedup learns from.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Duplicate candidate ID"},
+                "verdict": {"type": "string", "enum": ["confirmed", "rejected"], "description": "Whether the pair really is a duplicate"}
+            },
+            "required": ["id", "verdict"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_calibrate_dedup",
+        description: "Recommend a semantic dedup_threshold per workspace. Compares embedding similarity of confirmed duplicates against rejected candidates and random memory pairs; falls back to the random-pair tail when nothing is confirmed yet. Set apply=true to store recommendations as workspace defaults (see workspace_config_set).",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Calibrate only this workspace (default: all workspaces)"},
+                "sample_pairs": {"type": "integer", "default": 500, "maximum": 10000, "description": "Random memory pairs sampled per workspace"},
+                "seed": {"type": "integer", "default": 42, "description": "Seed for pair sampling; reruns with the same seed see the same pairs"},
+                "apply": {"type": "boolean", "default": false, "description": "Store each recommendation as the workspace's default dedup_threshold"}
+            }
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "quality_find_conflicts",
         description: "Detect conflicts for a memory against existing memories. Finds contradictions, staleness, and semantic overlaps.",
@@ -2409,6 +3653,190 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::mutating(),
         tier: ToolTier::Advanced,
     },
+    // Fact verification workflow
+    ToolDef {
+        name: "memory_verify_fact",
+        description: "Record a verdict on a fact (e.g. a seeded status:unverified memory). verified raises confidence and makes it permanent; disputed lowers confidence and shortens its TTL; refuted also supersedes it. The optional evidence memory is linked to the fact.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Memory holding the fact"},
+                "verdict": {"type": "string", "enum": ["verified", "disputed", "refuted"]},
+                "evidence_memory_id": {"type": "integer", "description": "Memory that supports or contradicts the fact"}
+            },
+            "required": ["id", "verdict"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_escalate_unverified_facts",
+        description: "Queue unverified facts that are old and frequently retrieved for human review. Facts already queued are skipped.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "min_age_days": {"type": "integer", "default": 14},
+                "min_access_count": {"type": "integer", "default": 3},
+                "limit": {"type": "integer", "default": 100}
+            }
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_fact_review_queue",
+        description: "List facts waiting for review, oldest first. Entries resolve when memory_verify_fact records a verdict.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "include_resolved": {"type": "boolean", "default": false},
+                "limit": {"type": "integer", "default": 50}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    // Freshness contracts
+    ToolDef {
+        name: "freshness_contract_set",
+        description: "Require memories with a tag (e.g. category:pricing) to be re-verified every max_age_days. Memories past the deadline are flagged by the freshness check, demoted in memory_search and listed by memory_stale_report. Setting the same tag and workspace again replaces the terms.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "tag": {"type": "string", "description": "Tag the contract applies to"},
+                "max_age_days": {"type": "integer", "minimum": 1, "description": "Days a verification stays valid"},
+                "workspace": {"type": "string", "description": "Limit to one workspace (default: all)"},
+                "penalty": {"type": "number", "default": 0.2, "minimum": 0, "maximum": 1, "description": "Score subtracted from violating memories in search"}
+            },
+            "required": ["tag", "max_age_days"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "freshness_contract_list",
+        description: "List freshness contracts.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "freshness_contract_delete",
+        description: "Delete a freshness contract and lift the flags it raised.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "description": "Contract ID"}
+            },
+            "required": ["id"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_freshness_check",
+        description: "Re-evaluate all freshness contracts now: flag memories whose last verification (memory_verify_fact, else creation) is older than their contract allows and clear flags that no longer apply.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "memory_stale_report",
+        description: "List memories violating their freshness contract, most overdue first, plus per-contract counts of violating vs. old-but-re-verified memories. Re-verify with memory_verify_fact.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Only report memories in this workspace"},
+                "limit": {"type": "integer", "default": 50},
+                "refresh": {"type": "boolean", "default": true, "description": "Run the freshness check first"}
+            }
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    // Structured fact store
+    ToolDef {
+        name: "fact_put",
+        description: "Assert a structured fact (subject, predicate, object). Each subject and predicate has one current value: writing a different object supersedes the previous one, writing the same object is a no-op.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "subject": {"type": "string", "description": "Entity the fact is about (case-insensitive)"},
+                "predicate": {"type": "string", "description": "Attribute or relation, normalized to snake_case"},
+                "object": {"type": "string", "description": "Value of the fact"},
+                "confidence": {"type": "number", "minimum": 0, "maximum": 1, "default": 1.0},
+                "source_memory_id": {"type": "integer", "description": "Memory the fact was stated in"},
+                "workspace": {"type": "string", "default": "default"}
+            },
+            "required": ["subject", "predicate", "object"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "fact_get",
+        description: "Get the current value of a structured fact by subject and predicate, optionally with the values it superseded.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "subject": {"type": "string"},
+                "predicate": {"type": "string"},
+                "workspace": {"type": "string", "default": "default"},
+                "include_history": {"type": "boolean", "default": false}
+            },
+            "required": ["subject", "predicate"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "fact_list",
+        description: "List current structured facts in a workspace, optionally for a single subject.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "subject": {"type": "string"},
+                "workspace": {"type": "string", "default": "default"},
+                "limit": {"type": "integer", "default": 100}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "fact_retract",
+        description: "Retract the current value of a structured fact without replacing it. The value stays in fact_get history.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "subject": {"type": "string"},
+                "predicate": {"type": "string"},
+                "workspace": {"type": "string", "default": "default"}
+            },
+            "required": ["subject", "predicate"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "fact_extract",
+        description: "Extract subject-predicate-object facts from a memory's content into the structured fact store, superseding changed values.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "memory_id": {"type": "integer", "description": "Memory to extract facts from"}
+            },
+            "required": ["memory_id"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Advanced,
+    },
     // Phase 7: Meilisearch Integration (ENG-58) - feature-gated
     #[cfg(feature = "meilisearch")]
     ToolDef {
@@ -2462,6 +3890,93 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
+    // ── Agent personas ────────────────────────────────────────────────────
+    ToolDef {
+        name: "persona_upsert",
+        description: "Create or replace an agent persona: a named bundle of default workspace, ranking profile, pinned memories and tool allowlist. Omitted settings are cleared on replace.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Persona name, e.g. 'coder' or 'assistant'"},
+                "description": {"type": "string", "description": "What the persona is for"},
+                "default_workspace": {"type": "string", "description": "Workspace used by retrieval tools when a call doesn't specify one"},
+                "ranking": {
+                    "type": "object",
+                    "description": "Ranking overrides applied to searches while active; unset fields keep the server defaults",
+                    "properties": {
+                        "keyword_weight": {"type": "number"},
+                        "semantic_weight": {"type": "number"},
+                        "rrf_k": {"type": "number"},
+                        "min_score": {"type": "number"},
+                        "project_context_boost": {"type": "number"}
+                    }
+                },
+                "pinned_memory_ids": {"type": "array", "items": {"type": "integer"}, "description": "Memories returned as the context pack on activation, in order"},
+                "tool_allowlist": {"type": "array", "items": {"type": "string"}, "description": "Tools the persona may call (empty = all). persona_* control tools are always allowed"}
+            },
+            "required": ["name"]
+        }"#,
+        annotations: ToolAnnotations::idempotent(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "persona_get",
+        description: "Get a persona by name, or the active persona when no name is given.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Persona name (omit for the active persona)"}
+            }
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "persona_list",
+        description: "List all personas and the name of the active one.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "persona_delete",
+        description: "Delete a persona. Deactivates it if it is active.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Persona name"}
+            },
+            "required": ["name"]
+        }"#,
+        annotations: ToolAnnotations::destructive(),
+        tier: ToolTier::Advanced,
+    },
+    ToolDef {
+        name: "persona_activate",
+        description: "Activate a persona at session start. Until deactivated, retrieval tools default to its workspace, searches use its ranking profile and only its allowlisted tools can be called. Returns the persona and its pinned memories as a context pack.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "description": "Persona name"}
+            },
+            "required": ["name"]
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Standard,
+    },
+    ToolDef {
+        name: "persona_deactivate",
+        description: "Deactivate the active persona, restoring server-default retrieval and the full tool set.",
+        schema: r#"{
+            "type": "object",
+            "properties": {}
+        }"#,
+        annotations: ToolAnnotations::mutating(),
+        tier: ToolTier::Standard,
+    },
     // ── Agent Registry ────────────────────────────────────────────────────
     ToolDef {
         name: "agent_register",
@@ -2764,7 +4279,9 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         schema: r#"{
             "type": "object",
             "properties": {
-                "ids": {"type": "array", "items": {"type": "integer"}, "description": "Memory IDs to expand"}
+                "ids": {"type": "array", "items": {"type": "integer"}, "description": "Memory IDs to expand"},
+                "verbosity": {"type": "string", "enum": ["ids_only", "compact", "full"], "description": "Response verbosity: ids_only, compact (no metadata/timestamps), or full (default; server default via ENGRAM_VERBOSITY)"},
+                "fields": {"type": "array", "items": {"type": "string"}, "description": "Return only these fields per memory/hit, e.g. [\"id\", \"content:200\", \"tags\", \"score\"]; name:N truncates to N chars. Overrides verbosity"}
             },
             "required": ["ids"]
         }"#,
@@ -2870,7 +4387,7 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
     },
     ToolDef {
         name: "memory_build_context",
-        description: "Build a structured prompt context from relevant memories using hybrid search, with optional graph traversal depth, timeframe filtering, type filtering, and relationship graph inclusion. Inspired by Basic Memory's build_context.",
+        description: "Build a structured prompt context from relevant memories using hybrid search, with optional graph traversal depth, timeframe filtering, type filtering, and relationship graph inclusion. Memories known to contradict each other or a pinned decision are flagged with conflict warnings. Inspired by Basic Memory's build_context.",
         schema: r#"{
             "type": "object",
             "properties": {
@@ -2882,7 +4399,11 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
                 "depth": {"type": "integer", "minimum": 1, "maximum": 3, "default": 1, "description": "Graph traversal depth: 1=search only, 2=search+1 hop of related memories, 3=search+2 hops"},
                 "timeframe": {"type": "string", "enum": ["1h", "24h", "7d", "30d", "all"], "default": "all", "description": "Time window for memory filtering"},
                 "include_types": {"type": "array", "items": {"type": "string"}, "description": "Only include these memory types (e.g., ['note', 'decision'])"},
-                "include_graph": {"type": "boolean", "default": false, "description": "Include entity relationship graph in response"}
+                "include_graph": {"type": "boolean", "default": false, "description": "Include entity relationship graph in response"},
+                "show_superseded": {"type": "boolean", "default": false, "description": "Include memories marked as superseded (hidden by default)"},
+                "conflict_warnings": {"type": "boolean", "default": true, "description": "Append warnings for memories in the prompt that are known to contradict each other or a pinned decision, naming the memory to trust, and return them as conflicts"},
+                "session_id": {"type": "string", "description": "Session whose pinned memories count as decisions to check against"},
+                "pinned_ids": {"type": "array", "items": {"type": "integer"}, "description": "Further pinned decisions to check against"}
             },
             "required": ["query"]
         }"#,
@@ -2904,6 +4425,23 @@ pub const TOOL_DEFINITIONS: &[ToolDef] = &[
         annotations: ToolAnnotations::read_only(),
         tier: ToolTier::Advanced,
     },
+    ToolDef {
+        name: "memory_export_site",
+        description: "Export a workspace as a static website: an index page with client-side search over a prebuilt MiniSearch index, one page per memory with links and backlinks, and a graph page. Open index.html from disk or publish the directory to any static host.",
+        schema: r#"{
+            "type": "object",
+            "properties": {
+                "workspace": {"type": "string", "description": "Workspace to export"},
+                "output_dir": {"type": "string", "description": "Output directory path (default: ./engram-site/{workspace}/)"},
+                "title": {"type": "string", "description": "Site title (default: the workspace name)"},
+                "include_graph": {"type": "boolean", "default": true, "description": "Write graph.html with an SVG graph linking to memory pages"},
+                "graph_max_nodes": {"type": "integer", "minimum": 1, "maximum": 2000, "default": 300, "description": "Most important memories drawn on the graph page"}
+            },
+            "required": ["workspace"]
+        }"#,
+        annotations: ToolAnnotations::read_only(),
+        tier: ToolTier::Advanced,
+    },
     ToolDef {
         name: "recent_activity",
         description: "Discover recently created or updated memories. Returns compact previews sorted by most recent activity. Useful for understanding what has changed recently.",
@@ -2953,12 +4491,8 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
             }
             true
         })
-        .map(|def| ToolDefinition {
-            name: def.name.to_string(),
-            description: def.description.to_string(),
-            input_schema: serde_json::from_str(def.schema).unwrap_or(json!({})),
-            annotations: Some(def.annotations.clone()),
-        })
+        .filter(|def| listed_version(def))
+        .map(to_definition)
         .collect()
 }
 
@@ -2996,15 +4530,27 @@ pub fn get_tool_definitions_tiered(max_tier: Option<&str>) -> Vec<ToolDefinition
                     | (ToolTier::Advanced, _)
             )
         })
-        .map(|def| ToolDefinition {
-            name: def.name.to_string(),
-            description: def.description.to_string(),
-            input_schema: serde_json::from_str(def.schema).unwrap_or(json!({})),
-            annotations: Some(def.annotations.clone()),
-        })
+        .filter(|def| listed_version(def))
+        .map(to_definition)
         .collect()
 }
 
+/// Deprecated tools stay listed (flagged in `_meta`) unless the server was
+/// started with deprecated tools disabled.
+fn listed_version(def: &ToolDef) -> bool {
+    !(deprecated_tools_disabled() && tool_version(def.name).is_deprecated())
+}
+
+fn to_definition(def: &ToolDef) -> ToolDefinition {
+    ToolDefinition {
+        name: def.name.to_string(),
+        description: def.description.to_string(),
+        input_schema: serde_json::from_str(def.schema).unwrap_or(json!({})),
+        annotations: Some(def.annotations.clone()),
+        meta: Some(tool_version(def.name).meta()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,307 @@
+//! Tool API versions and deprecations
+//!
+//! [`TOOL_DEFINITIONS`](super::tools::TOOL_DEFINITIONS) describes what each
+//! tool does; this registry records when it appeared and whether it is on
+//! its way out. Tools without an entry date from [`BASELINE_VERSION`] or
+//! earlier. Both show up under `_meta` in `tools/list`.
+//!
+//! A deprecated tool with a replacement keeps working through a
+//! compatibility shim: the call is served by the replacement and the result
+//! is flagged `deprecated`. Starting the server with
+//! `--disable-deprecated-tools` hides deprecated tools from `tools/list`
+//! and rejects calls to them, so clients still using old names show up.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::{json, Value};
+
+/// Release the registry starts from
+pub const BASELINE_VERSION: &str = "0.19.0";
+
+/// Version history of one tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolVersion {
+    pub name: &'static str,
+    /// Release that introduced the tool
+    pub since: &'static str,
+    /// Release that deprecated the tool
+    pub deprecated: Option<&'static str>,
+    /// Tool to call instead; calls to a deprecated tool are routed to it
+    pub replacement: Option<&'static str>,
+}
+
+const fn added(name: &'static str, since: &'static str) -> ToolVersion {
+    ToolVersion {
+        name,
+        since,
+        deprecated: None,
+        replacement: None,
+    }
+}
+
+const fn renamed(
+    name: &'static str,
+    since: &'static str,
+    deprecated: &'static str,
+    replacement: &'static str,
+) -> ToolVersion {
+    ToolVersion {
+        name,
+        since,
+        deprecated: Some(deprecated),
+        replacement: Some(replacement),
+    }
+}
+
+/// Tools added or deprecated after [`BASELINE_VERSION`]
+pub const TOOL_VERSIONS: &[ToolVersion] = &[
+    renamed(
+        "memory_seed",
+        BASELINE_VERSION,
+        BASELINE_VERSION,
+        "context_seed",
+    ),
+    added("boost_rule_create", "0.20.0"),
+    added("boost_rule_delete", "0.20.0"),
+    added("boost_rule_list", "0.20.0"),
+    added("boost_rule_update", "0.20.0"),
+    added("context_score", "0.20.0"),
+    added("fact_extract", "0.20.0"),
+    added("fact_get", "0.20.0"),
+    added("fact_list", "0.20.0"),
+    added("fact_put", "0.20.0"),
+    added("fact_retract", "0.20.0"),
+    added("federated_list", "0.20.0"),
+    added("federated_search", "0.20.0"),
+    added("federation_sources", "0.20.0"),
+    added("freshness_contract_delete", "0.20.0"),
+    added("freshness_contract_list", "0.20.0"),
+    added("freshness_contract_set", "0.20.0"),
+    added("langfuse_import_scores", "0.20.0"),
+    added("memory_calibrate_dedup", "0.20.0"),
+    added("memory_check_duplicate", "0.20.0"),
+    added("memory_cluster_duplicates", "0.20.0"),
+    added("memory_detect_contradiction_cycles", "0.20.0"),
+    added("memory_escalate_unverified_facts", "0.20.0"),
+    added("memory_expired_list", "0.20.0"),
+    added("memory_export_neighborhood", "0.20.0"),
+    added("memory_export_site", "0.20.0"),
+    added("memory_fact_review_queue", "0.20.0"),
+    added("memory_fit_embedding_adapter", "0.20.0"),
+    added("memory_freshness_check", "0.20.0"),
+    added("memory_graph_explore", "0.20.0"),
+    added("memory_graph_query", "0.20.0"),
+    added("memory_list_superseded", "0.20.0"),
+    added("memory_migrate_embeddings", "0.20.0"),
+    added("memory_read_cache_stats", "0.20.0"),
+    added("memory_rebuild_adjacency", "0.20.0"),
+    added("memory_rebuild_embeddings_status", "0.20.0"),
+    added("memory_rebuild_signatures", "0.20.0"),
+    added("memory_rebuild_vocabulary", "0.20.0"),
+    added("memory_reduce_embeddings", "0.20.0"),
+    added("memory_restore_embeddings", "0.20.0"),
+    added("memory_restore_superseded", "0.20.0"),
+    added("memory_resurrect", "0.20.0"),
+    added("memory_set_ttl_bulk", "0.20.0"),
+    added("memory_similar_by_structure", "0.20.0"),
+    added("memory_similarity_matrix", "0.20.0"),
+    added("memory_stale_report", "0.20.0"),
+    added("memory_suggest_links", "0.20.0"),
+    added("memory_supersede", "0.20.0"),
+    added("memory_vector_index_rebuild", "0.20.0"),
+    added("memory_vector_index_stats", "0.20.0"),
+    added("memory_verify_fact", "0.20.0"),
+    added("otel_ingest_traces", "0.20.0"),
+    added("persona_activate", "0.20.0"),
+    added("persona_deactivate", "0.20.0"),
+    added("persona_delete", "0.20.0"),
+    added("persona_get", "0.20.0"),
+    added("persona_list", "0.20.0"),
+    added("persona_upsert", "0.20.0"),
+    added("policy_simulate", "0.20.0"),
+    added("preference_get", "0.20.0"),
+    added("preference_history", "0.20.0"),
+    added("preference_list", "0.20.0"),
+    added("preference_set", "0.20.0"),
+    added("preference_unset", "0.20.0"),
+    added("quality_resolve_duplicate", "0.20.0"),
+    added("recommendations_get", "0.20.0"),
+    added("search_experiment_create", "0.20.0"),
+    added("search_experiment_list", "0.20.0"),
+    added("search_experiment_outcome", "0.20.0"),
+    added("search_experiment_report", "0.20.0"),
+    added("search_experiment_stop", "0.20.0"),
+    added("search_strategy_outcome", "0.20.0"),
+    added("search_strategy_stats", "0.20.0"),
+    added("session_link_topics", "0.20.0"),
+    added("sync_task_list", "0.20.0"),
+    added("tag_normalization_get", "0.20.0"),
+    added("tag_normalization_set", "0.20.0"),
+    added("tag_policy_get", "0.20.0"),
+    added("tag_policy_report", "0.20.0"),
+    added("tag_policy_set", "0.20.0"),
+    added("tag_synonym_add", "0.20.0"),
+    added("tag_synonym_list", "0.20.0"),
+    added("tag_synonym_remove", "0.20.0"),
+    added("tag_synonym_suggest", "0.20.0"),
+    added("workspace_config_delete", "0.20.0"),
+    added("workspace_config_get", "0.20.0"),
+    added("workspace_config_list", "0.20.0"),
+    added("workspace_config_set", "0.20.0"),
+    added("workspace_merge", "0.20.0"),
+    added("workspace_share_create", "0.20.0"),
+    added("workspace_share_list", "0.20.0"),
+    added("workspace_share_revoke", "0.20.0"),
+    added("workspace_share_view", "0.20.0"),
+    added("workspace_split", "0.20.0"),
+];
+
+static DEPRECATED_TOOLS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Hide deprecated tools and reject calls to them (`--disable-deprecated-tools`)
+pub fn set_deprecated_tools_disabled(disabled: bool) {
+    DEPRECATED_TOOLS_DISABLED.store(disabled, Ordering::Relaxed);
+}
+
+pub fn deprecated_tools_disabled() -> bool {
+    DEPRECATED_TOOLS_DISABLED.load(Ordering::Relaxed)
+}
+
+/// Version history of `name`; unregistered tools date from the baseline
+pub fn tool_version(name: &str) -> ToolVersion {
+    TOOL_VERSIONS
+        .iter()
+        .find(|v| v.name == name)
+        .copied()
+        .unwrap_or(ToolVersion {
+            name: "",
+            since: BASELINE_VERSION,
+            deprecated: None,
+            replacement: None,
+        })
+}
+
+impl ToolVersion {
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated.is_some()
+    }
+
+    /// What callers of a deprecated tool should do instead
+    pub fn deprecation_message(&self) -> Option<String> {
+        let since = self.deprecated?;
+        Some(match self.replacement {
+            Some(replacement) => format!("Use {} instead.", replacement),
+            None => format!("Deprecated since {}.", since),
+        })
+    }
+
+    /// `_meta` of the tool's definition in `tools/list`
+    pub fn meta(&self) -> Value {
+        let mut meta = json!({"since": self.since});
+        if let Some(since) = self.deprecated {
+            meta["deprecated"] = json!({
+                "since": since,
+                "replacement": self.replacement,
+                "message": self.deprecation_message(),
+            });
+        }
+        meta
+    }
+}
+
+/// How a call to a tool name is served
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolRoute {
+    /// A current tool, or a deprecated one without a replacement
+    Direct,
+    /// A deprecated name served by its replacement
+    Shim {
+        replacement: &'static str,
+        message: String,
+    },
+    /// A deprecated tool while deprecated tools are disabled
+    Disabled { error: String },
+}
+
+/// Decide how to serve a call to `name`
+pub fn route_tool(name: &str) -> ToolRoute {
+    route_tool_with(name, deprecated_tools_disabled())
+}
+
+fn route_tool_with(name: &str, deprecated_disabled: bool) -> ToolRoute {
+    let version = tool_version(name);
+    let Some(message) = version.deprecation_message() else {
+        return ToolRoute::Direct;
+    };
+    if deprecated_disabled {
+        return ToolRoute::Disabled {
+            error: format!(
+                "Tool '{}' is deprecated and disabled on this server. {}",
+                name, message
+            ),
+        };
+    }
+    match version.replacement {
+        Some(replacement) => ToolRoute::Shim {
+            replacement,
+            message,
+        },
+        None => ToolRoute::Direct,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_names_defined_tools() {
+        // Checked against the source so feature-gated tools count too
+        let definitions = include_str!("tools.rs");
+        for version in TOOL_VERSIONS {
+            assert!(
+                definitions.contains(&format!("name: \"{}\",", version.name)),
+                "{} has a version entry but no definition",
+                version.name
+            );
+            if let Some(replacement) = version.replacement {
+                assert!(!tool_version(replacement).is_deprecated());
+            }
+        }
+        let mut names: Vec<&str> = TOOL_VERSIONS.iter().map(|v| v.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(
+            names.len(),
+            TOOL_VERSIONS.len(),
+            "duplicate version entries"
+        );
+    }
+
+    #[test]
+    fn test_meta_and_routes() {
+        assert_eq!(
+            tool_version("memory_create").meta(),
+            json!({"since": BASELINE_VERSION})
+        );
+        assert_eq!(tool_version("memory_export_site").since, "0.20.0");
+
+        let seed = tool_version("memory_seed").meta();
+        assert_eq!(seed["deprecated"]["replacement"], "context_seed");
+        assert_eq!(seed["deprecated"]["message"], "Use context_seed instead.");
+
+        assert_eq!(route_tool("context_seed"), ToolRoute::Direct);
+        assert!(matches!(
+            route_tool_with("memory_seed", true),
+            ToolRoute::Disabled { error } if error.contains("Use context_seed instead.")
+        ));
+        assert_eq!(route_tool_with("context_seed", true), ToolRoute::Direct);
+        assert!(matches!(
+            route_tool("memory_seed"),
+            ToolRoute::Shim {
+                replacement: "context_seed",
+                ..
+            }
+        ));
+    }
+}
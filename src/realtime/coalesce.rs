@@ -0,0 +1,255 @@
+//! Coalescing of high-frequency events
+//!
+//! Bulk operations (batch create, ingest, import) would otherwise emit one
+//! event per memory. With a rule for an event type, the first event of that
+//! type in a workspace goes out immediately and opens a window; events of
+//! the same type and workspace arriving during the window are held and,
+//! when it closes, sent as one bulk event (`count`, `memory_ids`,
+//! `workspace`), or one by one if fewer than the rule's `min_batch` arrived.
+//! A window that collected events is followed by another, so a long-running
+//! import produces one bulk event per window.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+
+use super::events::{EventType, RealtimeEvent};
+
+/// How long the flusher sleeps when no window is open
+const IDLE_WAIT: Duration = Duration::from_secs(1);
+
+/// Coalescing settings for one event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoalesceRule {
+    /// How long events are collected before being flushed
+    pub window: Duration,
+    /// Fewest held events sent as a bulk event; smaller groups are sent
+    /// individually
+    pub min_batch: usize,
+}
+
+/// Per-event-type coalescing rules. Event types without a rule are always
+/// delivered immediately.
+#[derive(Debug, Clone, Default)]
+pub struct CoalescingConfig {
+    rules: HashMap<EventType, CoalesceRule>,
+}
+
+impl CoalescingConfig {
+    /// No coalescing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Coalesce memory created/updated/deleted events in `window`, the
+    /// events bulk operations produce one per memory
+    pub fn memory_writes(window: Duration) -> Self {
+        [
+            EventType::MemoryCreated,
+            EventType::MemoryUpdated,
+            EventType::MemoryDeleted,
+        ]
+        .into_iter()
+        .fold(Self::new(), |config, event_type| {
+            config.rule(event_type, window, 3)
+        })
+    }
+
+    /// Coalesce `event_type` in `window`, batching groups of at least
+    /// `min_batch` events
+    pub fn rule(mut self, event_type: EventType, window: Duration, min_batch: usize) -> Self {
+        self.rules.insert(
+            event_type,
+            CoalesceRule {
+                window,
+                min_batch: min_batch.max(2),
+            },
+        );
+        self
+    }
+
+    /// The rule for an event type, if it is coalesced
+    pub fn rule_for(&self, event_type: EventType) -> Option<&CoalesceRule> {
+        self.rules.get(&event_type)
+    }
+
+    /// Whether no event type is coalesced
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+type BucketKey = (EventType, Option<String>);
+
+/// An open window for one event type and workspace
+struct Bucket {
+    deadline: Instant,
+    held: Vec<RealtimeEvent>,
+}
+
+/// Open windows, shared by a `RealtimeManager` and its flusher thread
+pub(crate) struct Coalescer {
+    config: CoalescingConfig,
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+    wake: Condvar,
+}
+
+impl Coalescer {
+    pub(crate) fn new(config: CoalescingConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            wake: Condvar::new(),
+        }
+    }
+
+    /// Hold the event if its window is open, otherwise hand it back to be
+    /// sent now (opening a window when its type is coalesced)
+    pub(crate) fn offer(&self, event: RealtimeEvent, now: Instant) -> Option<RealtimeEvent> {
+        let Some(rule) = self.config.rule_for(event.event_type) else {
+            return Some(event);
+        };
+        let key = (event.event_type, event.workspace.clone());
+        let mut buckets = self.buckets.lock();
+        if let Some(bucket) = buckets.get_mut(&key) {
+            bucket.held.push(event);
+            return None;
+        }
+        buckets.insert(
+            key,
+            Bucket {
+                deadline: now + rule.window,
+                held: Vec::new(),
+            },
+        );
+        self.wake.notify_one();
+        Some(event)
+    }
+
+    /// Close the windows that ended by `now` and return what to send. A
+    /// window that held events stays open for another period.
+    pub(crate) fn due(&self, now: Instant) -> Vec<RealtimeEvent> {
+        let mut buckets = self.buckets.lock();
+        self.take(&mut buckets, |bucket| bucket.deadline <= now, Some(now))
+    }
+
+    /// Close every window and return what to send
+    pub(crate) fn drain(&self) -> Vec<RealtimeEvent> {
+        let mut buckets = self.buckets.lock();
+        self.take(&mut buckets, |_| true, None)
+    }
+
+    /// Wait until a window may have ended (or `IDLE_WAIT`), then return
+    /// what is due
+    pub(crate) fn wait_due(&self) -> Vec<RealtimeEvent> {
+        {
+            let mut buckets = self.buckets.lock();
+            let deadline = buckets
+                .values()
+                .map(|bucket| bucket.deadline)
+                .min()
+                .unwrap_or_else(|| Instant::now() + IDLE_WAIT);
+            if deadline > Instant::now() {
+                self.wake.wait_until(&mut buckets, deadline);
+            }
+        }
+        self.due(Instant::now())
+    }
+
+    /// Flush the selected buckets; with `reopen_at`, those that held events
+    /// get a new window starting then
+    fn take(
+        &self,
+        buckets: &mut HashMap<BucketKey, Bucket>,
+        select: impl Fn(&Bucket) -> bool,
+        reopen_at: Option<Instant>,
+    ) -> Vec<RealtimeEvent> {
+        let keys: Vec<BucketKey> = buckets
+            .iter()
+            .filter(|(_, bucket)| select(bucket))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut out = Vec::new();
+        for key in keys {
+            let rule = self.config.rule_for(key.0).copied();
+            let bucket = buckets.get_mut(&key).expect("selected bucket");
+            let held = std::mem::take(&mut bucket.held);
+            match (reopen_at, rule) {
+                (Some(now), Some(rule)) if !held.is_empty() => bucket.deadline = now + rule.window,
+                _ => {
+                    buckets.remove(&key);
+                }
+            }
+            let min_batch = rule.map_or(usize::MAX, |rule| rule.min_batch);
+            if held.len() < min_batch {
+                out.extend(held);
+            } else {
+                let ids = held.iter().flat_map(RealtimeEvent::affected_ids).collect();
+                out.push(RealtimeEvent::bulk(key.0, key.1, ids));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created(id: i64, workspace: &str) -> RealtimeEvent {
+        RealtimeEvent::memory_created(id, format!("memory {id}")).in_workspace(workspace)
+    }
+
+    #[test]
+    fn test_first_event_passes_and_burst_is_summarized() {
+        let coalescer = Coalescer::new(CoalescingConfig::memory_writes(Duration::from_millis(50)));
+        let start = Instant::now();
+
+        assert!(coalescer.offer(created(1, "a"), start).is_some());
+        for id in 2..=5 {
+            assert!(coalescer.offer(created(id, "a"), start).is_none());
+        }
+        // Another workspace has its own window
+        assert!(coalescer.offer(created(9, "b"), start).is_some());
+        // Sync events are never held
+        assert!(coalescer
+            .offer(RealtimeEvent::sync_failed("x"), start)
+            .is_some());
+
+        assert!(coalescer.due(start).is_empty());
+        let flushed = coalescer.due(start + Duration::from_millis(50));
+        assert_eq!(flushed.len(), 1);
+        let bulk = &flushed[0];
+        assert_eq!(bulk.event_type, EventType::MemoryCreated);
+        assert_eq!(bulk.workspace.as_deref(), Some("a"));
+        assert_eq!(bulk.count, Some(4));
+        assert_eq!(bulk.memory_ids, Some(vec![2, 3, 4, 5]));
+
+        // The window stayed open, so a trickle is held and sent one by one
+        assert!(coalescer.offer(created(6, "a"), start).is_none());
+        let flushed = coalescer.due(start + Duration::from_millis(100));
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].memory_id, Some(6));
+        assert!(!flushed[0].is_bulk());
+
+        // An empty window closes, and the next event goes out immediately
+        assert!(coalescer.due(start + Duration::from_millis(150)).is_empty());
+        assert!(coalescer.offer(created(7, "a"), start).is_some());
+    }
+
+    #[test]
+    fn test_drain_flushes_everything() {
+        let config =
+            CoalescingConfig::new().rule(EventType::MemoryDeleted, Duration::from_secs(60), 2);
+        let coalescer = Coalescer::new(config);
+        let now = Instant::now();
+        for id in 1..=3 {
+            coalescer.offer(RealtimeEvent::memory_deleted(id), now);
+        }
+        let drained = coalescer.drain();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].affected_ids(), vec![2, 3]);
+        assert!(coalescer.drain().is_empty());
+    }
+}
@@ -3,10 +3,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::graph::{GraphEdge, GraphNode};
 use crate::types::MemoryId;
 
 /// Types of real-time events
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventType {
     MemoryCreated,
@@ -17,6 +18,8 @@ pub enum EventType {
     SyncStarted,
     SyncCompleted,
     SyncFailed,
+    /// Memories recommended to an active session
+    MemorySuggested,
 }
 
 /// A real-time event
@@ -39,6 +42,15 @@ pub struct RealtimeEvent {
     pub changes: Option<Vec<String>>,
     /// Additional data
     pub data: Option<serde_json::Value>,
+    /// Workspace of the affected memory, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// Number of events summarized by a bulk event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+    /// Memories affected by a bulk event, in event order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_ids: Option<Vec<MemoryId>>,
 }
 
 impl RealtimeEvent {
@@ -52,6 +64,9 @@ impl RealtimeEvent {
             preview: Some(truncate(&preview, 100)),
             changes: None,
             data: None,
+            workspace: None,
+            count: None,
+            memory_ids: None,
         }
     }
 
@@ -65,6 +80,9 @@ impl RealtimeEvent {
             preview: None,
             changes: Some(changes),
             data: None,
+            workspace: None,
+            count: None,
+            memory_ids: None,
         }
     }
 
@@ -78,6 +96,38 @@ impl RealtimeEvent {
             preview: None,
             changes: None,
             data: None,
+            workspace: None,
+            count: None,
+            memory_ids: None,
+        }
+    }
+
+    /// Create a cross-reference created event
+    pub fn crossref_created(from: MemoryId, to: MemoryId, edge_type: &str) -> Self {
+        Self::crossref(EventType::CrossrefCreated, from, to, edge_type)
+    }
+
+    /// Create a cross-reference deleted event
+    pub fn crossref_deleted(from: MemoryId, to: MemoryId, edge_type: &str) -> Self {
+        Self::crossref(EventType::CrossrefDeleted, from, to, edge_type)
+    }
+
+    fn crossref(event_type: EventType, from: MemoryId, to: MemoryId, edge_type: &str) -> Self {
+        Self {
+            seq_id: None,
+            event_type,
+            timestamp: Utc::now(),
+            memory_id: Some(from),
+            preview: None,
+            changes: None,
+            data: Some(serde_json::json!({
+                "from_id": from,
+                "to_id": to,
+                "edge_type": edge_type,
+            })),
+            workspace: None,
+            count: None,
+            memory_ids: None,
         }
     }
 
@@ -94,9 +144,96 @@ impl RealtimeEvent {
                 "direction": direction,
                 "changes": changes,
             })),
+            workspace: None,
+            count: None,
+            memory_ids: None,
         }
     }
 
+    /// Create a memory suggested event for a session's new recommendations
+    pub fn memory_suggested(
+        session_id: &str,
+        workspace: &str,
+        recommendations: &[crate::intelligence::Recommendation],
+    ) -> Self {
+        Self {
+            seq_id: None,
+            event_type: EventType::MemorySuggested,
+            timestamp: Utc::now(),
+            memory_id: recommendations.first().map(|r| r.memory.id),
+            preview: None,
+            changes: None,
+            data: Some(serde_json::json!({
+                "session_id": session_id,
+                "recommendations": recommendations.iter().map(|r| serde_json::json!({
+                    "memory_id": r.memory.id,
+                    "preview": truncate(&r.memory.content, 100),
+                    "score": r.score,
+                    "reasons": r.reasons,
+                })).collect::<Vec<_>>(),
+            })),
+            workspace: Some(workspace.to_string()),
+            count: None,
+            memory_ids: None,
+        }
+    }
+
+    /// Summarize events of one type in one workspace as a single bulk event
+    pub fn bulk(
+        event_type: EventType,
+        workspace: Option<String>,
+        memory_ids: Vec<MemoryId>,
+    ) -> Self {
+        Self {
+            seq_id: None,
+            event_type,
+            timestamp: Utc::now(),
+            memory_id: None,
+            preview: None,
+            changes: None,
+            data: None,
+            workspace,
+            count: Some(memory_ids.len()),
+            memory_ids: Some(memory_ids),
+        }
+    }
+
+    /// Tag the event with the affected memory's workspace
+    pub fn in_workspace(mut self, workspace: impl Into<String>) -> Self {
+        self.workspace = Some(workspace.into());
+        self
+    }
+
+    /// Whether this event summarizes several coalesced events
+    pub fn is_bulk(&self) -> bool {
+        self.memory_ids.is_some()
+    }
+
+    /// Memories the event is about: every id of a bulk event, otherwise
+    /// `memory_id`
+    pub fn affected_ids(&self) -> Vec<MemoryId> {
+        match &self.memory_ids {
+            Some(ids) => ids.clone(),
+            None => self.memory_id.into_iter().collect(),
+        }
+    }
+
+    /// Split a bulk event back into one event per memory (without previews,
+    /// changes or crossref details); other events are returned as is
+    pub fn expand(&self) -> Vec<RealtimeEvent> {
+        let Some(ids) = &self.memory_ids else {
+            return vec![self.clone()];
+        };
+        ids.iter()
+            .map(|&id| Self {
+                memory_id: Some(id),
+                count: None,
+                memory_ids: None,
+                ..self.clone()
+            })
+            .collect()
+    }
+
     /// Create a sync failed event
     pub fn sync_failed(error: &str) -> Self {
         Self {
@@ -109,21 +246,74 @@ impl RealtimeEvent {
             data: Some(serde_json::json!({
                 "error": error,
             })),
+            workspace: None,
+            count: None,
+            memory_ids: None,
         }
     }
 }
 
-/// Truncate string for preview (UTF-8 safe)
-fn truncate(s: &str, max: usize) -> String {
-    if s.chars().count() <= max {
-        s.to_string()
-    } else {
-        // Take max - 3 chars safely, then append "..."
-        let truncated: String = s.chars().take(max.saturating_sub(3)).collect();
-        format!("{}...", truncated)
+/// A change to the live knowledge graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GraphMutation {
+    NodeAdded {
+        node: GraphNode,
+    },
+    /// Label, type, importance or tags of an existing node changed
+    NodeUpdated {
+        node: GraphNode,
+    },
+    /// Preceded by `edge_removed` for each of its edges
+    NodeRemoved {
+        id: MemoryId,
+    },
+    /// A new edge, or new score/confidence for an existing one
+    EdgeAdded {
+        edge: GraphEdge,
+    },
+    EdgeRemoved {
+        from: MemoryId,
+        to: MemoryId,
+        edge_type: String,
+    },
+    /// A node joined or moved to another Louvain community. Communities are
+    /// identified by their smallest member id.
+    ClusterChanged {
+        id: MemoryId,
+        cluster: MemoryId,
+    },
+    /// The graph was reloaded from storage; clients should re-fetch it
+    Reset,
+}
+
+/// A graph mutation as delivered on the graph channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEvent {
+    /// Sequential id within the graph channel, stamped by
+    /// `RealtimeManager::broadcast_graph`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq_id: Option<u64>,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub mutation: GraphMutation,
+}
+
+impl GraphEvent {
+    pub fn new(mutation: GraphMutation) -> Self {
+        Self {
+            seq_id: None,
+            timestamp: Utc::now(),
+            mutation,
+        }
     }
 }
 
+/// Truncate string for preview without splitting graphemes
+fn truncate(s: &str, max: usize) -> String {
+    crate::graph::label::truncate_graphemes(s, max)
+}
+
 /// Subscription filter for events
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SubscriptionFilter {
@@ -145,12 +335,12 @@ impl SubscriptionFilter {
             }
         }
 
-        // Check memory ID filter
+        // Check memory ID filter (a bulk event matches if any of its
+        // memories does)
         if let Some(ref ids) = self.memory_ids {
-            if let Some(event_id) = event.memory_id {
-                if !ids.contains(&event_id) {
-                    return false;
-                }
+            let affected = event.affected_ids();
+            if !affected.is_empty() && !affected.iter().any(|id| ids.contains(id)) {
+                return false;
             }
         }
 
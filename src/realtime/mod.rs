@@ -2,8 +2,12 @@
 //!
 //! Provides push notifications for memory changes to connected clients.
 
+mod coalesce;
 pub(crate) mod events;
+mod resume;
 mod server;
 
-pub use events::{EventType, RealtimeEvent, SubscriptionFilter};
-pub use server::{RealtimeManager, RealtimeServer};
+pub use coalesce::{CoalesceRule, CoalescingConfig};
+pub use events::{EventType, GraphEvent, GraphMutation, RealtimeEvent, SubscriptionFilter};
+pub use resume::{Backfill, EventLog, ResumeToken, StorageEventLog};
+pub use server::{stream_graph, RealtimeManager, RealtimeServer};
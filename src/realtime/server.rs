@@ -4,22 +4,29 @@ use std::collections::{HashMap, VecDeque};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, State,
     },
     response::IntoResponse,
     routing::get,
     Router,
 };
+use futures::stream::SplitSink;
 use futures::{SinkExt, StreamExt};
 use parking_lot::RwLock;
+use serde::Deserialize;
+use serde_json::json;
 use tokio::sync::broadcast;
 use uuid::Uuid;
 
-use super::events::{RealtimeEvent, SubscriptionFilter};
+use super::coalesce::{Coalescer, CoalescingConfig};
+use super::events::{GraphEvent, GraphMutation, RealtimeEvent, SubscriptionFilter};
+use super::resume::{Backfill, EventLog, ResumeToken};
+use crate::graph::{ExploreRequest, GraphExplorer};
 
 /// Connection ID
 pub type ConnectionId = String;
@@ -27,6 +34,15 @@ pub type ConnectionId = String;
 /// Default maximum number of events retained in the replay ring buffer.
 const DEFAULT_MAX_BUFFERED_EVENTS: usize = 500;
 
+/// Default interval between heartbeats on `/ws`
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Heartbeat intervals a `/ws` client may stay silent before it is dropped
+const MISSED_HEARTBEATS: u32 = 3;
+
+/// Default maximum number of events backfilled for a resuming client
+const DEFAULT_BACKFILL_LIMIT: usize = 1000;
+
 /// Manages WebSocket connections and SSE subscriptions.
 ///
 /// Each event broadcast through [`RealtimeManager::broadcast`] is:
@@ -36,11 +52,52 @@ const DEFAULT_MAX_BUFFERED_EVENTS: usize = 500;
 ///
 /// Clients that reconnect with a `Last-Event-Id` header can call
 /// [`RealtimeManager::get_events_after`] to retrieve buffered events they missed.
+///
+/// With [`RealtimeManager::with_coalescing`], bursts of events of the
+/// configured types are held for a short window and delivered as bulk
+/// events (see [`CoalescingConfig`]); held events may then arrive after
+/// later events of other types.
+///
+/// WebSocket clients get heartbeats and a resume token with every event;
+/// reconnecting with it backfills what they missed from the buffer or, with
+/// [`RealtimeManager::with_event_log`], the durable event log.
+///
+/// Graph mutations travel on a separate channel
+/// ([`RealtimeManager::broadcast_graph`]) with its own sequence and no replay
+/// buffer: a client that misses some re-fetches the graph instead. With
+/// [`RealtimeManager::with_graph_explorer`], graph clients can also fetch
+/// parts of the graph on the same socket as they explore it.
 pub struct RealtimeManager {
-    /// Broadcast channel for live delivery
-    tx: broadcast::Sender<RealtimeEvent>,
+    /// Sequencing, replay buffer and live delivery
+    sink: EventSink,
+    /// Held events, when coalescing is enabled
+    coalescer: Option<Arc<Coalescer>>,
+    /// Broadcast channel for graph mutations
+    graph_tx: broadcast::Sender<GraphEvent>,
+    /// Sequence counter for graph events (starts at 1)
+    next_graph_seq: Arc<AtomicU64>,
     /// Connected clients with their filters
     clients: Arc<RwLock<HashMap<ConnectionId, SubscriptionFilter>>>,
+    /// Random id of this manager, so resume tokens from another process are
+    /// recognized
+    epoch: u64,
+    /// Key resume tokens are signed with
+    resume_key: Arc<[u8]>,
+    /// Durable log for backfilling beyond the buffer
+    event_log: Option<Arc<dyn EventLog>>,
+    /// Interval between heartbeats on `/ws`
+    heartbeat_interval: Duration,
+    /// Most events backfilled for a resuming client
+    backfill_limit: usize,
+    /// Answers `explore` requests on the graph WebSockets
+    graph_explorer: Option<Arc<GraphExplorer>>,
+}
+
+/// Stamps, buffers and delivers events; shared with the coalescing flusher
+#[derive(Clone)]
+struct EventSink {
+    /// Broadcast channel for live delivery
+    tx: broadcast::Sender<RealtimeEvent>,
     /// Monotonically-increasing sequence counter (starts at 1)
     next_seq_id: Arc<AtomicU64>,
     /// In-memory ring buffer for replay
@@ -49,6 +106,28 @@ pub struct RealtimeManager {
     max_buffered_events: usize,
 }
 
+impl EventSink {
+    fn publish(&self, mut event: RealtimeEvent) {
+        // Stamp with sequential ID (fetch-and-increment, wraps at u64::MAX which
+        // is effectively never for any real-world workload).
+        let seq = self.next_seq_id.fetch_add(1, Ordering::Relaxed);
+        event.seq_id = Some(seq);
+
+        // Push into ring buffer, evicting the oldest entry when full.
+        {
+            let mut buf = self.buffer.write();
+            if buf.len() >= self.max_buffered_events {
+                buf.pop_front();
+            }
+            buf.push_back(event.clone());
+        }
+
+        // Deliver to live subscribers (errors are expected when no subscriber
+        // is registered yet — ignore them).
+        let _ = self.tx.send(event);
+    }
+}
+
 impl RealtimeManager {
     /// Create a new realtime manager with the default buffer size (500 events).
     pub fn new() -> Self {
@@ -58,46 +137,123 @@ impl RealtimeManager {
     /// Create a realtime manager with a custom ring-buffer size.
     pub fn with_buffer_size(max_buffered_events: usize) -> Self {
         let (tx, _) = broadcast::channel(1000);
+        let (graph_tx, _) = broadcast::channel(1000);
         Self {
-            tx,
+            sink: EventSink {
+                tx,
+                next_seq_id: Arc::new(AtomicU64::new(1)),
+                buffer: Arc::new(RwLock::new(VecDeque::with_capacity(
+                    max_buffered_events.min(4096),
+                ))),
+                max_buffered_events,
+            },
+            coalescer: None,
+            graph_tx,
+            next_graph_seq: Arc::new(AtomicU64::new(1)),
             clients: Arc::new(RwLock::new(HashMap::new())),
-            next_seq_id: Arc::new(AtomicU64::new(1)),
-            buffer: Arc::new(RwLock::new(VecDeque::with_capacity(
-                max_buffered_events.min(4096),
-            ))),
-            max_buffered_events,
+            epoch: rand::random::<u64>() | 1,
+            resume_key: Arc::from(rand::random::<[u8; 32]>().as_slice()),
+            event_log: None,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+            backfill_limit: DEFAULT_BACKFILL_LIMIT,
+            graph_explorer: None,
+        }
+    }
+
+    /// Backfill resuming clients from a durable log when the buffer no
+    /// longer covers what they missed (or the server restarted)
+    pub fn with_event_log(mut self, event_log: Arc<dyn EventLog>) -> Self {
+        self.event_log = Some(event_log);
+        self
+    }
+
+    /// Sign resume tokens with `secret` instead of a random per-process key,
+    /// so tokens stay valid across restarts (and between servers sharing
+    /// the secret and the event log)
+    pub fn with_resume_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.resume_key = Arc::from(secret.as_ref());
+        self
+    }
+
+    /// Interval between heartbeats on `/ws`; clients silent for three
+    /// intervals are disconnected
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval.max(Duration::from_millis(10));
+        self
+    }
+
+    /// Most events backfilled for a resuming client before it is told to
+    /// resync instead
+    pub fn with_backfill_limit(mut self, limit: usize) -> Self {
+        self.backfill_limit = limit;
+        self
+    }
+
+    /// Answer `explore` messages from graph WebSocket clients
+    pub fn with_graph_explorer(mut self, explorer: Arc<GraphExplorer>) -> Self {
+        self.graph_explorer = Some(explorer);
+        self
+    }
+
+    /// Coalesce bursts of events as configured. Held events are flushed by
+    /// a background thread that exits once every clone of the manager is
+    /// dropped.
+    pub fn with_coalescing(mut self, config: CoalescingConfig) -> Self {
+        if config.is_empty() {
+            self.coalescer = None;
+            return self;
         }
+        let coalescer = Arc::new(Coalescer::new(config));
+        let weak = Arc::downgrade(&coalescer);
+        let sink = self.sink.clone();
+        let spawned = std::thread::Builder::new()
+            .name("engram-event-coalescer".to_string())
+            .spawn(move || {
+                while let Some(coalescer) = weak.upgrade() {
+                    let due = coalescer.wait_due();
+                    drop(coalescer);
+                    for event in due {
+                        sink.publish(event);
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => self.coalescer = Some(coalescer),
+            Err(e) => tracing::warn!("Event coalescing disabled: {}", e),
+        }
+        self
     }
 
     /// Broadcast an event to all matching clients.
     ///
     /// The event is stamped with a sequential `seq_id`, pushed into the ring
-    /// buffer, and sent over the broadcast channel.
-    pub fn broadcast(&self, mut event: RealtimeEvent) {
-        // Stamp with sequential ID (fetch-and-increment, wraps at u64::MAX which
-        // is effectively never for any real-world workload).
-        let seq = self.next_seq_id.fetch_add(1, Ordering::Relaxed);
-        event.seq_id = Some(seq);
+    /// buffer, and sent over the broadcast channel — unless coalescing holds
+    /// it for a bulk event.
+    pub fn broadcast(&self, event: RealtimeEvent) {
+        let event = match &self.coalescer {
+            Some(coalescer) => coalescer.offer(event, Instant::now()),
+            None => Some(event),
+        };
+        if let Some(event) = event {
+            self.sink.publish(event);
+        }
+    }
 
-        // Push into ring buffer, evicting the oldest entry when full.
-        {
-            let mut buf = self.buffer.write();
-            if buf.len() >= self.max_buffered_events {
-                buf.pop_front();
+    /// Send every held event now instead of waiting for its window to end
+    pub fn flush_coalesced(&self) {
+        if let Some(coalescer) = &self.coalescer {
+            for event in coalescer.drain() {
+                self.sink.publish(event);
             }
-            buf.push_back(event.clone());
         }
-
-        // Deliver to live subscribers (errors are expected when no subscriber
-        // is registered yet — ignore them).
-        let _ = self.tx.send(event);
     }
 
     /// Return all buffered events whose `seq_id` is strictly greater than
     /// `last_seq_id`, in ascending order. Used to replay missed events for
     /// reconnecting clients.
     pub fn get_events_after(&self, last_seq_id: u64) -> Vec<RealtimeEvent> {
-        self.buffer
+        self.sink
+            .buffer
             .read()
             .iter()
             .filter(|e| e.seq_id.is_some_and(|id| id > last_seq_id))
@@ -105,10 +261,92 @@ impl RealtimeManager {
             .collect()
     }
 
+    /// What a client resuming from `token` missed, bounded by the backfill
+    /// limit
+    pub fn backfill(&self, token: &ResumeToken) -> Backfill {
+        if token.epoch == self.epoch {
+            let next_seq = self.current_seq();
+            if token.seq_id + 1 >= next_seq {
+                return Backfill::Buffered(Vec::new());
+            }
+            let buffer = self.sink.buffer.read();
+            let covered = buffer
+                .front()
+                .and_then(|event| event.seq_id)
+                .is_some_and(|oldest| oldest <= token.seq_id + 1);
+            if covered {
+                let missed: Vec<RealtimeEvent> = buffer
+                    .iter()
+                    .filter(|e| e.seq_id.is_some_and(|id| id > token.seq_id))
+                    .cloned()
+                    .collect();
+                return if missed.len() > self.backfill_limit {
+                    Backfill::ResyncRequired("too_many_missed")
+                } else {
+                    Backfill::Buffered(missed)
+                };
+            }
+        }
+        let Some(event_log) = &self.event_log else {
+            return Backfill::ResyncRequired("history_unavailable");
+        };
+        // A token that predates the log covers nothing in it; replaying the
+        // whole log is never a fallback
+        if token.log_id <= 0 {
+            return Backfill::ResyncRequired("history_unavailable");
+        }
+        match event_log.events_after(token.log_id, self.backfill_limit + 1) {
+            Ok(missed) if missed.len() > self.backfill_limit => {
+                Backfill::ResyncRequired("too_many_missed")
+            }
+            Ok(missed) => Backfill::Logged(missed),
+            Err(e) => {
+                tracing::warn!("Event log backfill failed: {}", e);
+                Backfill::ResyncRequired("history_unavailable")
+            }
+        }
+    }
+
+    /// Token for a client that has everything up to `seq_id` and, in the
+    /// event log, up to `log_id`
+    pub fn resume_token(&self, seq_id: u64, log_id: i64) -> ResumeToken {
+        ResumeToken {
+            epoch: self.epoch,
+            seq_id,
+            log_id,
+        }
+    }
+
+    /// Signed, encoded form of `token`
+    pub fn encode_token(&self, token: &ResumeToken) -> String {
+        token.encode(&self.resume_key)
+    }
+
+    /// The token a client sent back, if this server (or one sharing its
+    /// resume secret) signed it
+    pub fn parse_token(&self, token: &str) -> Option<ResumeToken> {
+        ResumeToken::parse(token, &self.resume_key)
+    }
+
+    /// Newest event log id, read on the blocking pool (`0` without a log)
+    async fn log_head(&self) -> i64 {
+        let Some(event_log) = self.event_log.clone() else {
+            return 0;
+        };
+        match tokio::task::spawn_blocking(move || event_log.head()).await {
+            Ok(Ok(head)) => head,
+            Ok(Err(e)) => {
+                tracing::warn!("Reading the event log head failed: {}", e);
+                0
+            }
+            Err(_) => 0,
+        }
+    }
+
     /// Return the current value of the sequence counter (next ID to be issued).
     /// Mainly useful for tests.
     pub fn current_seq(&self) -> u64 {
-        self.next_seq_id.load(Ordering::Relaxed)
+        self.sink.next_seq_id.load(Ordering::Relaxed)
     }
 
     /// Get number of connected clients
@@ -118,7 +356,19 @@ impl RealtimeManager {
 
     /// Subscribe to live events
     pub fn subscribe(&self) -> broadcast::Receiver<RealtimeEvent> {
-        self.tx.subscribe()
+        self.sink.tx.subscribe()
+    }
+
+    /// Broadcast a graph mutation to graph subscribers, stamping its `seq_id`
+    pub fn broadcast_graph(&self, mutation: GraphMutation) {
+        let mut event = GraphEvent::new(mutation);
+        event.seq_id = Some(self.next_graph_seq.fetch_add(1, Ordering::Relaxed));
+        let _ = self.graph_tx.send(event);
+    }
+
+    /// Subscribe to live graph mutations
+    pub fn subscribe_graph(&self) -> broadcast::Receiver<GraphEvent> {
+        self.graph_tx.subscribe()
     }
 
     /// Register a new client
@@ -146,11 +396,17 @@ impl Default for RealtimeManager {
 impl Clone for RealtimeManager {
     fn clone(&self) -> Self {
         Self {
-            tx: self.tx.clone(),
+            sink: self.sink.clone(),
+            coalescer: self.coalescer.clone(),
+            graph_tx: self.graph_tx.clone(),
+            next_graph_seq: self.next_graph_seq.clone(),
             clients: self.clients.clone(),
-            next_seq_id: self.next_seq_id.clone(),
-            buffer: self.buffer.clone(),
-            max_buffered_events: self.max_buffered_events,
+            epoch: self.epoch,
+            resume_key: self.resume_key.clone(),
+            event_log: self.event_log.clone(),
+            heartbeat_interval: self.heartbeat_interval,
+            backfill_limit: self.backfill_limit,
+            graph_explorer: self.graph_explorer.clone(),
         }
     }
 }
@@ -172,6 +428,7 @@ impl RealtimeServer {
     pub fn router(manager: RealtimeManager) -> Router {
         Router::new()
             .route("/ws", get(ws_handler))
+            .route("/ws/graph", get(graph_ws_handler))
             .route("/health", get(health_handler))
             .with_state(manager)
     }
@@ -198,16 +455,285 @@ async fn health_handler(State(manager): State<RealtimeManager>) -> impl IntoResp
     .to_string()
 }
 
+/// Query parameters of `/ws`
+#[derive(Debug, Default, Deserialize)]
+struct SocketQuery {
+    /// Resume token from the last event received before disconnecting
+    resume: Option<String>,
+}
+
 /// WebSocket upgrade handler
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(manager): State<RealtimeManager>,
+    Query(query): Query<SocketQuery>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, manager))
+    ws.on_upgrade(move |socket| handle_socket(socket, manager, query.resume))
+}
+
+/// Graph mutation WebSocket upgrade handler
+async fn graph_ws_handler(
+    ws: WebSocketUpgrade,
+    State(manager): State<RealtimeManager>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| stream_graph(socket, manager))
+}
+
+/// Forward graph mutations to a WebSocket client until either side closes.
+///
+/// If the client falls behind and mutations are dropped, it is sent a
+/// `reset` so it re-fetches the graph rather than drifting out of sync.
+///
+/// Clients can send `{"type": "explore", "action": ..., "id": ...}` with the
+/// fields of an [`ExploreRequest`] (and an optional `request_id`) to fetch a
+/// node's neighbours (`expand`), details (`details`) or community
+/// (`cluster`). The answer comes back on the socket as `explore_result`, or
+/// `explore_error`, echoing `request_id` and `action`.
+pub async fn stream_graph(socket: WebSocket, manager: RealtimeManager) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut rx = manager.subscribe_graph();
+
+    let mut open = true;
+    while open {
+        open = tokio::select! {
+            received = rx.recv() => {
+                let event = match received {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        GraphEvent::new(GraphMutation::Reset)
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let json = serde_json::to_string(&event).unwrap_or_default();
+                sender.send(Message::Text(json)).await.is_ok()
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => false,
+                Some(Ok(Message::Text(text))) => match explore_reply(&manager, &text).await {
+                    Some(reply) => send_json(&mut sender, reply).await,
+                    None => true,
+                },
+                Some(Ok(_)) => true,
+            },
+        };
+    }
+}
+
+/// The answer to an `explore` message from a graph client; `None` for
+/// other messages
+async fn explore_reply(manager: &RealtimeManager, text: &str) -> Option<serde_json::Value> {
+    let message: serde_json::Value = serde_json::from_str(text).ok()?;
+    if message.get("type").and_then(|t| t.as_str()) != Some("explore") {
+        return None;
+    }
+    let request_id = message.get("request_id").cloned().unwrap_or_default();
+    let action = message.get("action").cloned().unwrap_or_default();
+    let result = match (
+        manager.graph_explorer.clone(),
+        serde_json::from_value(message),
+    ) {
+        (None, _) => Err("Graph exploration is not available".to_string()),
+        (_, Err(e)) => Err(format!("Invalid explore request: {}", e)),
+        (Some(explorer), Ok(request)) => {
+            let request: ExploreRequest = request;
+            tokio::task::spawn_blocking(move || explorer.explore(&request))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|result| result.map_err(|e| e.to_string()))
+        }
+    };
+    Some(match result {
+        Ok(result) => json!({
+            "type": "explore_result",
+            "request_id": request_id,
+            "action": action,
+            "result": result,
+        }),
+        Err(error) => json!({
+            "type": "explore_error",
+            "request_id": request_id,
+            "action": action,
+            "error": error,
+        }),
+    })
+}
+
+type SocketSender = SplitSink<WebSocket, Message>;
+
+/// Log position used in a connection's resume tokens. A newly read head
+/// becomes active one heartbeat later, once the client has been sent
+/// everything broadcast before it was read.
+struct LogCursor {
+    active: i64,
+    /// Head read at the last heartbeat, with the `seq_id` issued next then
+    pending: Option<(i64, u64)>,
+}
+
+impl LogCursor {
+    fn advance(&mut self, last_seq: u64) {
+        if let Some((head, next_seq)) = self.pending {
+            if last_seq + 1 >= next_seq {
+                self.active = self.active.max(head);
+                self.pending = None;
+            }
+        }
+    }
+}
+
+/// Per-connection delivery state
+struct Delivery {
+    manager: RealtimeManager,
+    filter: SubscriptionFilter,
+    /// Last `seq_id` delivered (or skipped by the filter)
+    last_seq: u64,
+    cursor: LogCursor,
+}
+
+impl Delivery {
+    fn token(&self) -> String {
+        self.manager
+            .encode_token(&self.manager.resume_token(self.last_seq, self.cursor.active))
+    }
+
+    /// Send a live or buffered event, advancing the resume position
+    async fn send_event(&mut self, sender: &mut SocketSender, event: &RealtimeEvent) -> bool {
+        if let Some(seq) = event.seq_id {
+            if seq <= self.last_seq {
+                return true;
+            }
+            self.last_seq = seq;
+        }
+        if !self.filter.matches(event) {
+            return true;
+        }
+        let token = self.token();
+        send_json(sender, event_message(event, &token, false)).await
+    }
+
+    /// Replay what a resuming client missed
+    async fn backfill(&mut self, sender: &mut SocketSender, token: ResumeToken) -> bool {
+        let manager = self.manager.clone();
+        let backfill = tokio::task::spawn_blocking(move || manager.backfill(&token))
+            .await
+            .unwrap_or(Backfill::ResyncRequired("history_unavailable"));
+        let count = match backfill {
+            Backfill::Buffered(events) => {
+                self.last_seq = token.seq_id;
+                let count = events.len();
+                for event in &events {
+                    if !self.send_event(sender, event).await {
+                        return false;
+                    }
+                }
+                count
+            }
+            Backfill::Logged(events) => {
+                let count = events.len();
+                for (log_id, event) in &events {
+                    if !self.filter.matches(event) {
+                        continue;
+                    }
+                    // Log events can only be resumed from the log
+                    let token = ResumeToken {
+                        epoch: 0,
+                        seq_id: 0,
+                        log_id: *log_id,
+                    };
+                    let token = self.manager.encode_token(&token);
+                    if !send_json(sender, event_message(event, &token, true)).await {
+                        return false;
+                    }
+                }
+                count
+            }
+            Backfill::ResyncRequired(reason) => {
+                return send_json(sender, json!({"type": "resync_required", "reason": reason}))
+                    .await;
+            }
+        };
+        send_json(
+            sender,
+            json!({"type": "backfill_complete", "count": count, "resume_token": self.token()}),
+        )
+        .await
+    }
+
+    /// Catch up from the buffer after the live channel dropped events
+    async fn recover_lag(&mut self, sender: &mut SocketSender) -> bool {
+        let missed = self.manager.get_events_after(self.last_seq);
+        let gap = missed
+            .first()
+            .and_then(|event| event.seq_id)
+            .is_some_and(|first| first > self.last_seq + 1);
+        if gap {
+            self.last_seq = missed
+                .last()
+                .and_then(|e| e.seq_id)
+                .unwrap_or(self.last_seq);
+            return send_json(
+                sender,
+                json!({"type": "resync_required", "reason": "lagged"}),
+            )
+            .await;
+        }
+        for event in &missed {
+            if !self.send_event(sender, event).await {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Heartbeat: ping, send the current resume token, and read a new log
+    /// head to move the token's log position forward
+    async fn heartbeat(&mut self, sender: &mut SocketSender) -> bool {
+        self.cursor.advance(self.last_seq);
+        if self.cursor.pending.is_none() {
+            let next_seq = self.manager.current_seq();
+            let head = self.manager.log_head().await;
+            self.cursor.pending = Some((head, next_seq));
+        }
+        if sender.send(Message::Ping(Vec::new())).await.is_err() {
+            return false;
+        }
+        let token = self.token();
+        send_json(
+            sender,
+            json!({"type": "heartbeat", "resume_token": token, "seq_id": self.last_seq}),
+        )
+        .await
+    }
+}
+
+/// An event as sent on `/ws`: its JSON plus the token to resume after it
+fn event_message(event: &RealtimeEvent, token: &str, backfilled: bool) -> serde_json::Value {
+    let mut message = serde_json::to_value(event).unwrap_or_default();
+    if let Some(object) = message.as_object_mut() {
+        object.insert("resume_token".to_string(), json!(token));
+        if backfilled {
+            object.insert("backfill".to_string(), json!(true));
+        }
+    }
+    message
+}
+
+async fn send_json(sender: &mut SocketSender, message: serde_json::Value) -> bool {
+    sender
+        .send(Message::Text(message.to_string()))
+        .await
+        .is_ok()
 }
 
 /// Handle an individual WebSocket connection
-async fn handle_socket(socket: WebSocket, manager: RealtimeManager) {
+///
+/// The client gets a `welcome` with its first resume token, then (when
+/// resuming) the events it missed followed by `backfill_complete`, or
+/// `resync_required` when they can't be replayed. Afterwards events arrive
+/// live, each with a `resume_token`, and a ping plus a `heartbeat` message
+/// go out every heartbeat interval. Text messages from the client update its
+/// [`SubscriptionFilter`], except `{"type": "ping"}`, which is answered with
+/// a `pong`. Clients silent for [`MISSED_HEARTBEATS`] intervals are dropped.
+async fn handle_socket(socket: WebSocket, manager: RealtimeManager, resume: Option<String>) {
     let connection_id = Uuid::new_v4().to_string();
     let filter = SubscriptionFilter::default();
 
@@ -215,50 +741,95 @@ async fn handle_socket(socket: WebSocket, manager: RealtimeManager) {
     tracing::info!("Client connected: {}", connection_id);
 
     let (mut sender, mut receiver) = socket.split();
+
+    // Positions are read before subscribing, so anything published in
+    // between is replayed on resume rather than lost
+    let start_seq = manager.current_seq().saturating_sub(1);
+    let start_log = manager.log_head().await;
     let mut rx = manager.subscribe();
 
-    // Task to forward events to client
-    let conn_id = connection_id.clone();
-    let mgr = manager.clone();
-    let send_task = tokio::spawn(async move {
-        while let Ok(event) = rx.recv().await {
-            // Check if event matches client's filter
-            if let Some(filter) = mgr.get_client_filter(&conn_id) {
-                if filter.matches(&event) {
-                    let json = serde_json::to_string(&event).unwrap_or_default();
-                    if sender.send(Message::Text(json)).await.is_err() {
-                        break;
-                    }
-                }
-            }
-        }
+    let heartbeat_interval = manager.heartbeat_interval;
+    let mut delivery = Delivery {
+        manager: manager.clone(),
+        filter,
+        last_seq: start_seq,
+        cursor: LogCursor {
+            active: start_log,
+            pending: None,
+        },
+    };
+
+    let welcome = json!({
+        "type": "welcome",
+        "connection_id": connection_id,
+        "resume_token": delivery.token(),
+        "heartbeat_interval_ms": heartbeat_interval.as_millis() as u64,
     });
+    let mut open = send_json(&mut sender, welcome).await;
+    if open {
+        open = match resume.as_deref().map(|token| manager.parse_token(token)) {
+            None => true,
+            Some(Some(token)) => delivery.backfill(&mut sender, token).await,
+            Some(None) => {
+                send_json(
+                    &mut sender,
+                    json!({"type": "resync_required", "reason": "invalid_token"}),
+                )
+                .await
+            }
+        };
+    }
 
-    // Task to handle incoming messages from client
-    let conn_id = connection_id.clone();
-    let mgr = manager.clone();
-    let recv_task = tokio::spawn(async move {
-        while let Some(Ok(msg)) = receiver.next().await {
-            match msg {
-                Message::Text(text) => {
-                    // Try to parse as filter update
-                    if let Ok(new_filter) = serde_json::from_str::<SubscriptionFilter>(&text) {
-                        mgr.register_client(conn_id.clone(), new_filter);
-                        tracing::debug!("Updated filter for client {}", conn_id);
+    let mut heartbeat = tokio::time::interval_at(
+        tokio::time::Instant::now() + heartbeat_interval,
+        heartbeat_interval,
+    );
+    let mut last_heard = Instant::now();
+    while open {
+        open = tokio::select! {
+            received = rx.recv() => match received {
+                Ok(event) => delivery.send_event(&mut sender, &event).await,
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    delivery.recover_lag(&mut sender).await
+                }
+                Err(broadcast::error::RecvError::Closed) => false,
+            },
+            message = receiver.next() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => false,
+                Some(Ok(message)) => {
+                    last_heard = Instant::now();
+                    match message {
+                        Message::Text(text) => {
+                            let request: serde_json::Value =
+                                serde_json::from_str(&text).unwrap_or_default();
+                            if request.get("type").and_then(|t| t.as_str()) == Some("ping") {
+                                send_json(&mut sender, json!({"type": "pong"})).await
+                            } else {
+                                // Try to parse as filter update
+                                if let Ok(new_filter) = serde_json::from_value(request) {
+                                    delivery.filter = new_filter;
+                                    manager.register_client(
+                                        connection_id.clone(),
+                                        delivery.filter.clone(),
+                                    );
+                                    tracing::debug!("Updated filter for client {}", connection_id);
+                                }
+                                true
+                            }
+                        }
+                        _ => true,
                     }
                 }
-                Message::Close(_) => {
-                    break;
+            },
+            _ = heartbeat.tick() => {
+                if last_heard.elapsed() > heartbeat_interval * MISSED_HEARTBEATS {
+                    tracing::info!("Client {} missed its heartbeats", connection_id);
+                    false
+                } else {
+                    delivery.heartbeat(&mut sender).await
                 }
-                _ => {}
             }
-        }
-    });
-
-    // Wait for either task to finish
-    tokio::select! {
-        _ = send_task => {}
-        _ = recv_task => {}
+        };
     }
 
     manager.unregister_client(&connection_id);
@@ -308,7 +879,7 @@ mod tests {
         manager.broadcast(RealtimeEvent::memory_deleted(3));
 
         // IDs should be 1, 2, 3 (counter starts at 1)
-        let buf = manager.buffer.read();
+        let buf = manager.sink.buffer.read();
         let ids: Vec<u64> = buf.iter().filter_map(|e| e.seq_id).collect();
         assert_eq!(ids, vec![1, 2, 3]);
     }
@@ -335,7 +906,7 @@ mod tests {
             manager.broadcast(RealtimeEvent::memory_created(i as i64, format!("m{i}")));
         }
 
-        let buf = manager.buffer.read();
+        let buf = manager.sink.buffer.read();
         assert_eq!(buf.len(), max, "buffer should be at capacity");
         // The first two events (seq 1, 2) should have been evicted
         let ids: Vec<u64> = buf.iter().filter_map(|e| e.seq_id).collect();
@@ -352,7 +923,7 @@ mod tests {
             manager.broadcast(RealtimeEvent::memory_deleted(i as i64));
         }
 
-        assert_eq!(manager.buffer.read().len(), max);
+        assert_eq!(manager.sink.buffer.read().len(), max);
     }
 
     // --- Replay / get_events_after tests ------------------------------------
@@ -407,6 +978,45 @@ mod tests {
         assert!(replayed.is_empty());
     }
 
+    // --- Graph channel -------------------------------------------------------
+
+    #[test]
+    fn test_graph_channel_is_separate_from_events() {
+        let manager = RealtimeManager::new();
+        let mut events = manager.subscribe();
+        let mut graph = manager.subscribe_graph();
+
+        manager.broadcast_graph(GraphMutation::NodeRemoved { id: 4 });
+        manager.broadcast_graph(GraphMutation::Reset);
+
+        let first = graph.try_recv().unwrap();
+        assert_eq!(first.seq_id, Some(1));
+        assert_eq!(first.mutation, GraphMutation::NodeRemoved { id: 4 });
+        assert_eq!(graph.try_recv().unwrap().seq_id, Some(2));
+
+        // Graph mutations neither reach event subscribers nor the replay buffer.
+        assert!(events.try_recv().is_err());
+        assert_eq!(manager.current_seq(), 1);
+        assert!(manager.get_events_after(0).is_empty());
+    }
+
+    #[test]
+    fn test_graph_event_json_shape() {
+        let event = GraphEvent::new(GraphMutation::EdgeRemoved {
+            from: 1,
+            to: 2,
+            edge_type: "related_to".to_string(),
+        });
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], "edge_removed");
+        assert_eq!(value["from"], 1);
+        assert_eq!(value["edge_type"], "related_to");
+        assert!(value.get("seq_id").is_none());
+
+        let back: GraphEvent = serde_json::from_value(value).unwrap();
+        assert_eq!(back.mutation, event.mutation);
+    }
+
     // --- Clone shares same state --------------------------------------------
 
     #[test]
@@ -418,8 +1028,234 @@ mod tests {
         manager.broadcast(RealtimeEvent::memory_created(1, "shared".to_string()));
 
         // cloned should see the same buffer
-        assert_eq!(cloned.buffer.read().len(), 1);
+        assert_eq!(cloned.sink.buffer.read().len(), 1);
         let replayed = cloned.get_events_after(0);
         assert_eq!(replayed.len(), 1);
     }
+
+    // --- Coalescing ---------------------------------------------------------
+
+    #[test]
+    fn test_coalescing_summarizes_bursts() {
+        let manager = RealtimeManager::new().with_coalescing(CoalescingConfig::memory_writes(
+            std::time::Duration::from_secs(60),
+        ));
+        let mut rx = manager.subscribe();
+
+        for id in 1..=5 {
+            manager.broadcast(
+                RealtimeEvent::memory_created(id, format!("bulk {id}")).in_workspace("import"),
+            );
+        }
+        manager.broadcast(RealtimeEvent::sync_failed("offline"));
+        // Only the first created event and the uncoalesced sync event are out
+        assert_eq!(rx.try_recv().unwrap().memory_id, Some(1));
+        assert_eq!(
+            rx.try_recv().unwrap().event_type,
+            super::super::events::EventType::SyncFailed
+        );
+        assert!(rx.try_recv().is_err());
+
+        manager.flush_coalesced();
+        let bulk = rx.try_recv().unwrap();
+        assert_eq!(bulk.count, Some(4));
+        assert_eq!(bulk.memory_ids, Some(vec![2, 3, 4, 5]));
+        assert_eq!(bulk.workspace.as_deref(), Some("import"));
+        assert_eq!(bulk.seq_id, Some(3));
+        assert_eq!(manager.get_events_after(2).len(), 1);
+    }
+
+    #[test]
+    fn test_coalesced_events_flush_after_window() {
+        let manager = RealtimeManager::new().with_coalescing(CoalescingConfig::memory_writes(
+            std::time::Duration::from_millis(20),
+        ));
+        let _rx = manager.subscribe();
+        for id in 1..=4 {
+            manager.broadcast(RealtimeEvent::memory_deleted(id));
+        }
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        while manager.current_seq() < 3 && Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let events = manager.get_events_after(0);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].affected_ids(), vec![2, 3, 4]);
+    }
+
+    // --- Resume / backfill --------------------------------------------------
+
+    /// Event log holding memory deleted events with log ids 1..=n
+    struct FixedLog(i64);
+
+    impl EventLog for FixedLog {
+        fn head(&self) -> crate::error::Result<i64> {
+            Ok(self.0)
+        }
+
+        fn events_after(
+            &self,
+            log_id: i64,
+            limit: usize,
+        ) -> crate::error::Result<Vec<(i64, RealtimeEvent)>> {
+            Ok((log_id + 1..=self.0)
+                .take(limit)
+                .map(|id| (id, RealtimeEvent::memory_deleted(id)))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_backfill_from_buffer() {
+        let manager = RealtimeManager::new();
+        for id in 1..=5 {
+            manager.broadcast(RealtimeEvent::memory_deleted(id));
+        }
+        match manager.backfill(&manager.resume_token(2, 0)) {
+            Backfill::Buffered(events) => {
+                let ids: Vec<u64> = events.iter().filter_map(|e| e.seq_id).collect();
+                assert_eq!(ids, vec![3, 4, 5]);
+            }
+            other => panic!("unexpected backfill {:?}", other),
+        }
+        assert!(matches!(
+            manager.backfill(&manager.resume_token(5, 0)),
+            Backfill::Buffered(events) if events.is_empty()
+        ));
+
+        let limited = RealtimeManager::new().with_backfill_limit(2);
+        for id in 1..=5 {
+            limited.broadcast(RealtimeEvent::memory_deleted(id));
+        }
+        assert!(matches!(
+            limited.backfill(&limited.resume_token(1, 0)),
+            Backfill::ResyncRequired("too_many_missed")
+        ));
+    }
+
+    #[test]
+    fn test_backfill_falls_back_to_event_log() {
+        let manager = RealtimeManager::with_buffer_size(2);
+        for id in 1..=5 {
+            manager.broadcast(RealtimeEvent::memory_deleted(id));
+        }
+        // Evicted from the buffer and no log to fall back on
+        assert!(matches!(
+            manager.backfill(&manager.resume_token(1, 0)),
+            Backfill::ResyncRequired("history_unavailable")
+        ));
+
+        // A token from before a restart resumes from the log
+        let restarted = RealtimeManager::new().with_event_log(Arc::new(FixedLog(4)));
+        let old_token = manager.resume_token(3, 2);
+        match restarted.backfill(&old_token) {
+            Backfill::Logged(events) => {
+                let log_ids: Vec<i64> = events.iter().map(|(id, _)| *id).collect();
+                assert_eq!(log_ids, vec![3, 4]);
+            }
+            other => panic!("unexpected backfill {:?}", other),
+        }
+        // A position before the log never replays all of it
+        assert!(matches!(
+            restarted.backfill(&manager.resume_token(3, 0)),
+            Backfill::ResyncRequired("history_unavailable")
+        ));
+        let restarted = restarted.with_backfill_limit(1);
+        assert!(matches!(
+            restarted.backfill(&old_token),
+            Backfill::ResyncRequired("too_many_missed")
+        ));
+    }
+
+    #[test]
+    fn test_log_cursor_waits_for_delivery() {
+        let mut cursor = LogCursor {
+            active: 10,
+            pending: Some((20, 8)),
+        };
+        // Events up to seq 6 delivered; seq 7 was broadcast before the head
+        // was read, so the new head can't be trusted yet
+        cursor.advance(6);
+        assert_eq!(cursor.active, 10);
+        cursor.advance(7);
+        assert_eq!(cursor.active, 20);
+        assert!(cursor.pending.is_none());
+    }
+
+    #[test]
+    fn test_event_message_carries_token() {
+        let manager = RealtimeManager::new();
+        manager.broadcast(RealtimeEvent::memory_deleted(9));
+        let event = manager.get_events_after(0).remove(0);
+        let token = manager.encode_token(&manager.resume_token(1, 0));
+        let message = event_message(&event, &token, false);
+        assert_eq!(message["resume_token"], json!(token));
+        assert_eq!(message["memory_id"], json!(9));
+        assert!(message.get("backfill").is_none());
+        assert_eq!(
+            manager.parse_token(message["resume_token"].as_str().unwrap()),
+            Some(manager.resume_token(1, 0))
+        );
+        // Another server's tokens are rejected unless it shares the secret
+        assert_eq!(RealtimeManager::new().parse_token(&token), None);
+        let shared = RealtimeManager::new().with_resume_secret("s3cret");
+        let token = shared.encode_token(&shared.resume_token(1, 4));
+        let restarted = RealtimeManager::new().with_resume_secret("s3cret");
+        assert_eq!(
+            restarted.parse_token(&token),
+            Some(shared.resume_token(1, 4))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explore_messages_are_answered() {
+        use crate::storage::Storage;
+
+        assert!(
+            explore_reply(&RealtimeManager::new(), r#"{"type": "filter"}"#)
+                .await
+                .is_none()
+        );
+        let reply = explore_reply(
+            &RealtimeManager::new(),
+            r#"{"type": "explore", "action": "details", "id": 1}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply["type"], "explore_error");
+
+        let storage = Storage::open_in_memory().unwrap();
+        let memory = storage
+            .with_transaction(|conn| {
+                crate::storage::queries::create_memory(
+                    conn,
+                    &crate::types::CreateMemoryInput {
+                        content: "explored".to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+        let manager =
+            RealtimeManager::new().with_graph_explorer(Arc::new(GraphExplorer::new(storage)));
+        let message = json!({
+            "type": "explore",
+            "request_id": "r1",
+            "action": "expand",
+            "id": memory.id,
+        });
+        let reply = explore_reply(&manager, &message.to_string()).await.unwrap();
+        assert_eq!(reply["type"], "explore_result");
+        assert_eq!(reply["request_id"], "r1");
+        assert_eq!(reply["action"], "expand");
+        assert_eq!(reply["result"]["nodes"][0]["id"], json!(memory.id));
+
+        let reply = explore_reply(
+            &manager,
+            r#"{"type": "explore", "action": "zoom", "id": 1}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply["type"], "explore_error");
+    }
 }
@@ -104,6 +104,8 @@ pub fn bm25_search_complete(
         workspaces,
         tier,
         None,
+        false,
+        None,
     )
 }
 
@@ -125,6 +127,8 @@ pub fn bm25_search_complete_with_scope_path(
     workspaces: Option<&[String]>,
     tier: Option<&crate::types::MemoryTier>,
     scope_path: Option<&str>,
+    show_superseded: bool,
+    validation_status: Option<crate::types::ValidationStatus>,
 ) -> Result<Vec<Bm25Result>> {
     // Escape special FTS5 characters
     let escaped_query = escape_fts5_query(query);
@@ -159,6 +163,16 @@ pub fn bm25_search_complete_with_scope_path(
         sql.push_str(" AND (m.lifecycle_state IS NULL OR m.lifecycle_state != 'archived')");
     }
 
+    // Exclude superseded memories unless show_superseded is true
+    if !show_superseded {
+        sql.push_str(" AND m.superseded_at IS NULL");
+    }
+
+    if let Some(status) = validation_status {
+        sql.push_str(" AND COALESCE(m.validation_status, 'unverified') = ?");
+        params.push(Box::new(status.as_str().to_string()));
+    }
+
     // Add advanced filter (RML-932)
     if let Some(filter_json) = filter {
         let filter_expr = parse_filter(filter_json)?;
@@ -447,6 +461,7 @@ impl Bm25Result {
             highlights: self.highlights.clone(),
             semantic_score: None,
             keyword_score: Some(self.score),
+            boosts: Vec::new(),
         }
     }
 }
@@ -0,0 +1,542 @@
+//! Contextual boost rules
+//!
+//! Generalizes the fixed project-context boost into operator-defined rules of
+//! the form "when the search happens in context X, boost (or penalize)
+//! memories matching Y". Conditions look at the caller's context — working
+//! directory prefix, agent id, workspace, hour of day and the active session's
+//! tags — and targets match memories by tag or type. Every rule that fires is
+//! recorded on the hit's [`MatchInfo`](crate::types::MatchInfo) so search
+//! explanations show why a result moved.
+
+use chrono::{DateTime, Timelike, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::types::{AppliedBoost, Memory, MemoryType, SearchResult};
+
+// ---------------------------------------------------------------------------
+// DDL
+// ---------------------------------------------------------------------------
+
+/// SQL for creating the boost rule table.
+/// Safe to call on an existing database — uses `CREATE TABLE IF NOT EXISTS`.
+pub const CREATE_BOOST_RULES_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS boost_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    condition TEXT NOT NULL DEFAULT '{}',
+    target TEXT NOT NULL DEFAULT '{}',
+    weight REAL NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+"#;
+
+/// Largest absolute weight a rule may carry. Fused search scores sit roughly
+/// in `[0, 1]`, so anything larger would swamp relevance entirely.
+pub const MAX_BOOST_WEIGHT: f32 = 1.0;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// When a rule fires. Every populated field must match; an empty condition
+/// always fires.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoostCondition {
+    /// Working directory must start with this path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd_prefix: Option<String>,
+    /// Calling agent must be this agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Search must be scoped to this workspace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// First UTC hour (0-23) of the active window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hour_start: Option<u32>,
+    /// Last UTC hour (0-23) of the active window; may wrap past midnight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hour_end: Option<u32>,
+    /// Active session must carry at least one of these tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub session_tags: Vec<String>,
+}
+
+/// Which memories a rule adjusts. A memory matches if it has any listed tag
+/// or any listed type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoostTarget {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_types: Vec<MemoryType>,
+}
+
+/// A stored boost rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoostRule {
+    pub id: i64,
+    pub name: String,
+    pub condition: BoostCondition,
+    pub target: BoostTarget,
+    /// Added to the score of matching hits; negative values penalize
+    pub weight: f32,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+/// The caller context rules are evaluated against.
+#[derive(Debug, Clone)]
+pub struct BoostContext {
+    pub cwd: Option<String>,
+    pub agent_id: Option<String>,
+    pub workspace: Option<String>,
+    pub session_tags: Vec<String>,
+    pub now: DateTime<Utc>,
+}
+
+impl Default for BoostContext {
+    fn default() -> Self {
+        Self {
+            cwd: None,
+            agent_id: None,
+            workspace: None,
+            session_tags: Vec::new(),
+            now: Utc::now(),
+        }
+    }
+}
+
+impl BoostCondition {
+    /// Whether the condition holds for `ctx`.
+    pub fn holds(&self, ctx: &BoostContext) -> bool {
+        if let Some(ref prefix) = self.cwd_prefix {
+            if !ctx
+                .cwd
+                .as_deref()
+                .is_some_and(|cwd| cwd.starts_with(prefix))
+            {
+                return false;
+            }
+        }
+        if self.agent_id.is_some() && self.agent_id != ctx.agent_id {
+            return false;
+        }
+        if self.workspace.is_some() && self.workspace != ctx.workspace {
+            return false;
+        }
+        if !self.session_tags.is_empty()
+            && !self
+                .session_tags
+                .iter()
+                .any(|t| ctx.session_tags.contains(t))
+        {
+            return false;
+        }
+        let hour = ctx.now.hour();
+        match (self.hour_start, self.hour_end) {
+            (Some(start), Some(end)) if start <= end => (start..=end).contains(&hour),
+            // Window wraps midnight, e.g. 22 -> 6
+            (Some(start), Some(end)) => hour >= start || hour <= end,
+            (Some(start), None) => hour >= start,
+            (None, Some(end)) => hour <= end,
+            (None, None) => true,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        for hour in [self.hour_start, self.hour_end].into_iter().flatten() {
+            if hour > 23 {
+                return Err(EngramError::InvalidInput(format!(
+                    "hours must be within 0-23, got {hour}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BoostTarget {
+    /// Whether `memory` is adjusted by this target.
+    pub fn matches(&self, memory: &Memory) -> bool {
+        self.memory_types.contains(&memory.memory_type)
+            || self.tags.iter().any(|t| memory.tags.contains(t))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.memory_types.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ranking
+// ---------------------------------------------------------------------------
+
+/// Apply every enabled rule whose condition holds to `results`, recording the
+/// applied boosts on each hit and re-sorting by the adjusted score.
+///
+/// Returns the number of hits whose score changed.
+pub fn apply_boost_rules(
+    results: &mut [SearchResult],
+    rules: &[BoostRule],
+    ctx: &BoostContext,
+) -> usize {
+    let active: Vec<&BoostRule> = rules
+        .iter()
+        .filter(|r| r.enabled && r.condition.holds(ctx))
+        .collect();
+    if active.is_empty() {
+        return 0;
+    }
+
+    let mut adjusted = 0;
+    for result in results.iter_mut() {
+        let mut touched = false;
+        for rule in active.iter().filter(|r| r.target.matches(&result.memory)) {
+            result.score += rule.weight;
+            result.match_info.boosts.push(AppliedBoost {
+                rule_id: rule.id,
+                rule: rule.name.clone(),
+                weight: rule.weight,
+            });
+            touched = true;
+        }
+        if touched {
+            adjusted += 1;
+        }
+    }
+
+    if adjusted > 0 {
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+    adjusted
+}
+
+// ---------------------------------------------------------------------------
+// Storage functions
+// ---------------------------------------------------------------------------
+
+/// Create a new enabled rule.
+pub fn create_boost_rule(
+    conn: &Connection,
+    name: &str,
+    condition: &BoostCondition,
+    target: &BoostTarget,
+    weight: f32,
+) -> Result<BoostRule> {
+    if name.trim().is_empty() {
+        return Err(EngramError::InvalidInput(
+            "rule name must not be empty".to_string(),
+        ));
+    }
+    validate_weight(weight)?;
+    condition.validate()?;
+    if target.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "rule target needs at least one tag or memory type".to_string(),
+        ));
+    }
+
+    conn.execute(
+        "INSERT INTO boost_rules (name, condition, target, weight) VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![
+            name,
+            serde_json::to_string(condition)?,
+            serde_json::to_string(target)?,
+            weight,
+        ],
+    )?;
+
+    get_boost_rule(conn, conn.last_insert_rowid())
+}
+
+/// Fetch a rule by id.
+pub fn get_boost_rule(conn: &Connection, rule_id: i64) -> Result<BoostRule> {
+    conn.query_row(
+        "SELECT id, name, condition, target, weight, enabled, created_at
+         FROM boost_rules WHERE id = ?1",
+        rusqlite::params![rule_id],
+        row_to_rule,
+    )
+    .optional()?
+    .ok_or(EngramError::NotFound(rule_id))
+}
+
+/// List rules in creation order. `enabled_only` hides disabled rules.
+pub fn list_boost_rules(conn: &Connection, enabled_only: bool) -> Result<Vec<BoostRule>> {
+    let sql = if enabled_only {
+        "SELECT id, name, condition, target, weight, enabled, created_at
+         FROM boost_rules WHERE enabled = 1 ORDER BY id"
+    } else {
+        "SELECT id, name, condition, target, weight, enabled, created_at
+         FROM boost_rules ORDER BY id"
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt
+        .query_map([], row_to_rule)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Change a rule's weight and/or enabled flag.
+pub fn update_boost_rule(
+    conn: &Connection,
+    rule_id: i64,
+    weight: Option<f32>,
+    enabled: Option<bool>,
+) -> Result<BoostRule> {
+    let current = get_boost_rule(conn, rule_id)?;
+    let weight = weight.unwrap_or(current.weight);
+    validate_weight(weight)?;
+    conn.execute(
+        "UPDATE boost_rules SET weight = ?1, enabled = ?2 WHERE id = ?3",
+        rusqlite::params![weight, enabled.unwrap_or(current.enabled), rule_id],
+    )?;
+    get_boost_rule(conn, rule_id)
+}
+
+/// Delete a rule. Returns whether it existed.
+pub fn delete_boost_rule(conn: &Connection, rule_id: i64) -> Result<bool> {
+    let affected = conn.execute(
+        "DELETE FROM boost_rules WHERE id = ?1",
+        rusqlite::params![rule_id],
+    )?;
+    Ok(affected > 0)
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+fn validate_weight(weight: f32) -> Result<()> {
+    if !weight.is_finite() || weight.abs() > MAX_BOOST_WEIGHT {
+        return Err(EngramError::InvalidInput(format!(
+            "weight must be within [-{MAX_BOOST_WEIGHT}, {MAX_BOOST_WEIGHT}], got {weight}"
+        )));
+    }
+    Ok(())
+}
+
+/// Map a rusqlite row to [`BoostRule`].
+fn row_to_rule(r: &rusqlite::Row<'_>) -> rusqlite::Result<BoostRule> {
+    fn parse_json<T: serde::de::DeserializeOwned>(idx: usize, raw: String) -> rusqlite::Result<T> {
+        serde_json::from_str(&raw).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+    }
+
+    Ok(BoostRule {
+        id: r.get(0)?,
+        name: r.get(1)?,
+        condition: parse_json(2, r.get(2)?)?,
+        target: parse_json(3, r.get(3)?)?,
+        weight: r.get(4)?,
+        enabled: r.get(5)?,
+        created_at: r.get(6)?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        LifecycleState, MatchInfo, MemoryScope, MemoryTier, MemoryType, SearchStrategy, Visibility,
+    };
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().expect("open in-memory db");
+        conn.execute_batch(CREATE_BOOST_RULES_TABLE)
+            .expect("create tables");
+        conn
+    }
+
+    fn hit(id: i64, score: f32, tags: &[&str]) -> SearchResult {
+        let now = Utc::now();
+        let memory = Memory {
+            id,
+            content: format!("memory {id}"),
+            memory_type: MemoryType::Note,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            metadata: HashMap::new(),
+            importance: 0.5,
+            access_count: 0,
+            created_at: now,
+            updated_at: now,
+            last_accessed_at: None,
+            owner_id: None,
+            visibility: Visibility::Private,
+            scope: MemoryScope::Global,
+            workspace: "default".to_string(),
+            tier: MemoryTier::Permanent,
+            version: 1,
+            has_embedding: false,
+            expires_at: None,
+            content_hash: None,
+            event_time: None,
+            event_duration_seconds: None,
+            trigger_pattern: None,
+            procedure_success_count: 0,
+            procedure_failure_count: 0,
+            summary_of_id: None,
+            lifecycle_state: LifecycleState::Active,
+            media_url: None,
+        };
+        SearchResult {
+            memory,
+            score,
+            match_info: MatchInfo {
+                strategy: SearchStrategy::Hybrid,
+                matched_terms: vec![],
+                highlights: vec![],
+                semantic_score: None,
+                keyword_score: None,
+                boosts: Vec::new(),
+            },
+        }
+    }
+
+    fn rule(condition: BoostCondition, tag: &str, weight: f32) -> BoostRule {
+        BoostRule {
+            id: 1,
+            name: format!("boost-{tag}"),
+            condition,
+            target: BoostTarget {
+                tags: vec![tag.to_string()],
+                memory_types: vec![],
+            },
+            weight,
+            enabled: true,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_condition_matching() {
+        let ctx = BoostContext {
+            cwd: Some("/home/dev/engram/src".to_string()),
+            agent_id: Some("claude".to_string()),
+            session_tags: vec!["debugging".to_string()],
+            now: Utc.with_ymd_and_hms(2026, 1, 1, 23, 0, 0).unwrap(),
+            ..Default::default()
+        };
+
+        assert!(BoostCondition::default().holds(&ctx));
+        let in_repo = BoostCondition {
+            cwd_prefix: Some("/home/dev/engram".to_string()),
+            ..Default::default()
+        };
+        assert!(in_repo.holds(&ctx));
+        let other_repo = BoostCondition {
+            cwd_prefix: Some("/home/dev/other".to_string()),
+            ..Default::default()
+        };
+        assert!(!other_repo.holds(&ctx));
+
+        let night = BoostCondition {
+            hour_start: Some(22),
+            hour_end: Some(6),
+            ..Default::default()
+        };
+        assert!(night.holds(&ctx));
+        let office = BoostCondition {
+            hour_start: Some(9),
+            hour_end: Some(17),
+            ..Default::default()
+        };
+        assert!(!office.holds(&ctx));
+
+        let session = BoostCondition {
+            session_tags: vec!["debugging".to_string(), "review".to_string()],
+            agent_id: Some("claude".to_string()),
+            ..Default::default()
+        };
+        assert!(session.holds(&ctx));
+    }
+
+    #[test]
+    fn test_apply_boosts_reorders_and_records() {
+        let mut results = vec![hit(1, 0.8, &["docs"]), hit(2, 0.6, &["runbook"])];
+        let rules = vec![
+            rule(BoostCondition::default(), "runbook", 0.5),
+            rule(BoostCondition::default(), "docs", -0.1),
+        ];
+
+        let adjusted = apply_boost_rules(&mut results, &rules, &BoostContext::default());
+        assert_eq!(adjusted, 2);
+        assert_eq!(results[0].memory.id, 2);
+        assert!((results[0].score - 1.1).abs() < 1e-6);
+        assert_eq!(results[0].match_info.boosts[0].rule, "boost-runbook");
+        assert_eq!(results[1].match_info.boosts[0].weight, -0.1);
+    }
+
+    #[test]
+    fn test_disabled_or_unmet_rules_do_nothing() {
+        let mut results = vec![hit(1, 0.8, &["docs"])];
+        let mut disabled = rule(BoostCondition::default(), "docs", 0.5);
+        disabled.enabled = false;
+        let elsewhere = rule(
+            BoostCondition {
+                agent_id: Some("other".to_string()),
+                ..Default::default()
+            },
+            "docs",
+            0.5,
+        );
+        let ctx = BoostContext::default();
+        assert_eq!(
+            apply_boost_rules(&mut results, &[disabled, elsewhere], &ctx),
+            0
+        );
+        assert!(results[0].match_info.boosts.is_empty());
+    }
+
+    #[test]
+    fn test_rule_crud_and_validation() {
+        let conn = setup();
+        let target = BoostTarget {
+            tags: vec!["runbook".to_string()],
+            memory_types: vec![MemoryType::Decision],
+        };
+        let created =
+            create_boost_rule(&conn, "runbooks", &BoostCondition::default(), &target, 0.3).unwrap();
+        assert!(created.enabled);
+        assert_eq!(created.target, target);
+
+        assert!(create_boost_rule(&conn, "big", &BoostCondition::default(), &target, 2.0).is_err());
+        assert!(create_boost_rule(
+            &conn,
+            "no-target",
+            &BoostCondition::default(),
+            &BoostTarget::default(),
+            0.1
+        )
+        .is_err());
+        let bad_hours = BoostCondition {
+            hour_start: Some(25),
+            ..Default::default()
+        };
+        assert!(create_boost_rule(&conn, "hours", &bad_hours, &target, 0.1).is_err());
+
+        let updated = update_boost_rule(&conn, created.id, Some(-0.2), Some(false)).unwrap();
+        assert_eq!(updated.weight, -0.2);
+        assert!(!updated.enabled);
+        assert!(list_boost_rules(&conn, true).unwrap().is_empty());
+        assert_eq!(list_boost_rules(&conn, false).unwrap().len(), 1);
+
+        assert!(delete_boost_rule(&conn, created.id).unwrap());
+        assert!(!delete_boost_rule(&conn, created.id).unwrap());
+        assert!(matches!(
+            get_boost_rule(&conn, created.id),
+            Err(EngramError::NotFound(_))
+        ));
+    }
+}
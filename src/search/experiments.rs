@@ -0,0 +1,689 @@
+//! Search ranking experiments (A/B testing)
+//!
+//! Lets operators compare two ranking configurations against live traffic.
+//! Each search that opts into an experiment is routed to arm `A` or `B`
+//! deterministically from a hash of the normalized query, so repeated queries
+//! always land on the same arm and outcome signals can be attributed without
+//! carrying a session token.  Exposures and outcome signals (result used,
+//! useful, irrelevant) are persisted and summarized into a report that names
+//! the winning configuration once the difference is statistically meaningful.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::SearchConfig;
+use crate::error::{EngramError, Result};
+
+// ---------------------------------------------------------------------------
+// DDL
+// ---------------------------------------------------------------------------
+
+/// SQL for creating the experiment tables and their indexes.
+/// Safe to call on an existing database — uses `CREATE TABLE IF NOT EXISTS`.
+pub const CREATE_SEARCH_EXPERIMENT_TABLES: &str = r#"
+CREATE TABLE IF NOT EXISTS search_experiments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    workspace TEXT,
+    variant_a TEXT NOT NULL DEFAULT '{}',
+    variant_b TEXT NOT NULL DEFAULT '{}',
+    traffic_b REAL NOT NULL DEFAULT 0.5,
+    status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active', 'stopped')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    stopped_at TEXT
+);
+CREATE TABLE IF NOT EXISTS search_experiment_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    experiment_id INTEGER NOT NULL,
+    arm TEXT NOT NULL CHECK(arm IN ('a', 'b')),
+    event TEXT NOT NULL CHECK(event IN ('exposure', 'used', 'useful', 'irrelevant')),
+    query_hash TEXT NOT NULL,
+    memory_id INTEGER,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+CREATE INDEX IF NOT EXISTS idx_experiment_events_exp ON search_experiment_events(experiment_id, arm, event);
+"#;
+
+/// Critical value for a two-sided test at 95% confidence.
+const Z_CRITICAL_95: f64 = 1.96;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One arm of an experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExperimentArm {
+    A,
+    B,
+}
+
+impl ExperimentArm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExperimentArm::A => "a",
+            ExperimentArm::B => "b",
+        }
+    }
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "a" => Ok(ExperimentArm::A),
+            "b" => Ok(ExperimentArm::B),
+            other => Err(EngramError::InvalidInput(format!(
+                "unknown experiment arm: {other}"
+            ))),
+        }
+    }
+}
+
+/// Outcome signal attributed to the arm that served a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentOutcome {
+    /// A result was opened / expanded / cited by the agent.
+    Used,
+    /// Explicit positive feedback on a result.
+    Useful,
+    /// Explicit negative feedback on a result.
+    Irrelevant,
+}
+
+impl ExperimentOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            ExperimentOutcome::Used => "used",
+            ExperimentOutcome::Useful => "useful",
+            ExperimentOutcome::Irrelevant => "irrelevant",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "used" => Ok(ExperimentOutcome::Used),
+            "useful" => Ok(ExperimentOutcome::Useful),
+            "irrelevant" => Ok(ExperimentOutcome::Irrelevant),
+            other => Err(EngramError::InvalidInput(format!(
+                "unknown experiment outcome: {other}"
+            ))),
+        }
+    }
+}
+
+/// Ranking overrides applied on top of the server's [`SearchConfig`].
+///
+/// Unset fields inherit the base configuration, so an arm only needs to
+/// specify the knobs under test.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RankingVariant {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyword_weight: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semantic_weight: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rrf_k: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_context_boost: Option<f32>,
+}
+
+impl RankingVariant {
+    /// Produce a [`SearchConfig`] with this variant's overrides applied.
+    pub fn apply(&self, base: &SearchConfig) -> SearchConfig {
+        let mut config = base.clone();
+        if let Some(v) = self.keyword_weight {
+            config.keyword_weight = v;
+        }
+        if let Some(v) = self.semantic_weight {
+            config.semantic_weight = v;
+        }
+        if let Some(v) = self.rrf_k {
+            config.rrf_k = v;
+        }
+        if let Some(v) = self.min_score {
+            config.min_score = v;
+        }
+        if let Some(v) = self.project_context_boost {
+            config.project_context_boost = v;
+        }
+        config
+    }
+}
+
+/// A persisted experiment definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchExperiment {
+    pub id: i64,
+    pub name: String,
+    /// Restrict the experiment to one workspace (`None` = all workspaces).
+    pub workspace: Option<String>,
+    pub variant_a: RankingVariant,
+    pub variant_b: RankingVariant,
+    /// Fraction of queries (0.0 – 1.0) routed to arm `B`.
+    pub traffic_b: f64,
+    pub status: String,
+    pub created_at: String,
+    pub stopped_at: Option<String>,
+}
+
+impl SearchExperiment {
+    pub fn is_active(&self) -> bool {
+        self.status == "active"
+    }
+
+    /// Arm that serves `query` in this experiment.
+    pub fn assign(&self, query: &str) -> ExperimentArm {
+        assign_arm(self.id, query, self.traffic_b)
+    }
+
+    /// Ranking overrides for `arm`.
+    pub fn variant(&self, arm: ExperimentArm) -> &RankingVariant {
+        match arm {
+            ExperimentArm::A => &self.variant_a,
+            ExperimentArm::B => &self.variant_b,
+        }
+    }
+}
+
+/// Aggregated counters for one arm.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArmReport {
+    pub exposures: i64,
+    pub used: i64,
+    pub useful: i64,
+    pub irrelevant: i64,
+    /// `(used + useful) / (used + useful + irrelevant)`; 0.0 with no outcomes.
+    pub positive_rate: f64,
+    /// Outcome signals per exposure — a proxy for engagement.
+    pub outcomes_per_search: f64,
+}
+
+impl ArmReport {
+    fn positives(&self) -> i64 {
+        self.used + self.useful
+    }
+
+    fn judged(&self) -> i64 {
+        self.positives() + self.irrelevant
+    }
+}
+
+/// Comparison of both arms of an experiment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub experiment: SearchExperiment,
+    pub arm_a: ArmReport,
+    pub arm_b: ArmReport,
+    /// Two-proportion z statistic for `positive_rate` (B minus A).
+    pub z_score: Option<f64>,
+    /// Winning arm, set only when both arms reached `min_outcomes` judged
+    /// signals and `|z| >= 1.96`.
+    pub winner: Option<ExperimentArm>,
+    /// Human-readable verdict.
+    pub verdict: String,
+}
+
+// ---------------------------------------------------------------------------
+// Assignment
+// ---------------------------------------------------------------------------
+
+/// Deterministically assign a query to an arm.
+///
+/// The bucket is derived from SHA-256 of the experiment id and the normalized
+/// (trimmed, lowercased) query, so the same query always hits the same arm
+/// within an experiment while different experiments bucket independently.
+pub fn assign_arm(experiment_id: i64, query: &str, traffic_b: f64) -> ExperimentArm {
+    let bucket = query_bucket(experiment_id, query);
+    if bucket < traffic_b.clamp(0.0, 1.0) {
+        ExperimentArm::B
+    } else {
+        ExperimentArm::A
+    }
+}
+
+/// Map a query to a stable value in `[0.0, 1.0)`.
+pub(crate) fn query_bucket(experiment_id: i64, query: &str) -> f64 {
+    let digest = Sha256::digest(format!("{}:{}", experiment_id, normalize_query(query)));
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+pub(crate) fn query_hash(query: &str) -> String {
+    hex::encode(Sha256::digest(normalize_query(query)))
+}
+
+// ---------------------------------------------------------------------------
+// Storage functions
+// ---------------------------------------------------------------------------
+
+/// Create a new active experiment.
+pub fn create_experiment(
+    conn: &Connection,
+    name: &str,
+    workspace: Option<&str>,
+    variant_a: &RankingVariant,
+    variant_b: &RankingVariant,
+    traffic_b: f64,
+) -> Result<SearchExperiment> {
+    if name.trim().is_empty() {
+        return Err(EngramError::InvalidInput(
+            "experiment name must not be empty".to_string(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&traffic_b) {
+        return Err(EngramError::InvalidInput(format!(
+            "traffic_b must be within [0, 1], got {traffic_b}"
+        )));
+    }
+
+    conn.execute(
+        "INSERT INTO search_experiments (name, workspace, variant_a, variant_b, traffic_b)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            name,
+            workspace,
+            serde_json::to_string(variant_a)?,
+            serde_json::to_string(variant_b)?,
+            traffic_b,
+        ],
+    )?;
+
+    get_experiment(conn, conn.last_insert_rowid())
+}
+
+/// Fetch an experiment by id.
+pub fn get_experiment(conn: &Connection, experiment_id: i64) -> Result<SearchExperiment> {
+    conn.query_row(
+        "SELECT id, name, workspace, variant_a, variant_b, traffic_b, status, created_at, stopped_at
+         FROM search_experiments WHERE id = ?1",
+        rusqlite::params![experiment_id],
+        row_to_experiment,
+    )
+    .optional()?
+    .ok_or(EngramError::NotFound(experiment_id))
+}
+
+/// List experiments, newest first. `active_only` hides stopped experiments.
+pub fn list_experiments(conn: &Connection, active_only: bool) -> Result<Vec<SearchExperiment>> {
+    let sql = if active_only {
+        "SELECT id, name, workspace, variant_a, variant_b, traffic_b, status, created_at, stopped_at
+         FROM search_experiments WHERE status = 'active' ORDER BY id DESC"
+    } else {
+        "SELECT id, name, workspace, variant_a, variant_b, traffic_b, status, created_at, stopped_at
+         FROM search_experiments ORDER BY id DESC"
+    };
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt
+        .query_map([], row_to_experiment)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Stop an experiment. Recorded events are kept for reporting.
+pub fn stop_experiment(conn: &Connection, experiment_id: i64) -> Result<SearchExperiment> {
+    let affected = conn.execute(
+        "UPDATE search_experiments
+         SET status = 'stopped', stopped_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+         WHERE id = ?1 AND status = 'active'",
+        rusqlite::params![experiment_id],
+    )?;
+    if affected == 0 {
+        // Distinguish "missing" from "already stopped".
+        get_experiment(conn, experiment_id)?;
+    }
+    get_experiment(conn, experiment_id)
+}
+
+/// Record that `query` was served by `arm`.
+pub fn record_exposure(
+    conn: &Connection,
+    experiment_id: i64,
+    arm: ExperimentArm,
+    query: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO search_experiment_events (experiment_id, arm, event, query_hash)
+         VALUES (?1, ?2, 'exposure', ?3)",
+        rusqlite::params![experiment_id, arm.as_str(), query_hash(query)],
+    )?;
+    Ok(())
+}
+
+/// Record an outcome signal for `query`.
+///
+/// The arm is recomputed from the query, so callers only need the experiment
+/// id and the original query text. Returns the arm the signal was credited to.
+pub fn record_outcome(
+    conn: &Connection,
+    experiment_id: i64,
+    query: &str,
+    outcome: ExperimentOutcome,
+    memory_id: Option<i64>,
+) -> Result<ExperimentArm> {
+    let experiment = get_experiment(conn, experiment_id)?;
+    let arm = experiment.assign(query);
+    conn.execute(
+        "INSERT INTO search_experiment_events (experiment_id, arm, event, query_hash, memory_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            experiment_id,
+            arm.as_str(),
+            outcome.as_str(),
+            query_hash(query),
+            memory_id,
+        ],
+    )?;
+    Ok(arm)
+}
+
+/// Summarize an experiment and pick a winner when the evidence supports one.
+///
+/// A winner is declared only when both arms have at least `min_outcomes`
+/// judged signals and the two-proportion z-test on `positive_rate` clears the
+/// 95% threshold.
+pub fn experiment_report(
+    conn: &Connection,
+    experiment_id: i64,
+    min_outcomes: i64,
+) -> Result<ExperimentReport> {
+    let experiment = get_experiment(conn, experiment_id)?;
+
+    let mut arm_a = ArmReport::default();
+    let mut arm_b = ArmReport::default();
+
+    let mut stmt = conn.prepare(
+        "SELECT arm, event, COUNT(*) FROM search_experiment_events
+         WHERE experiment_id = ?1 GROUP BY arm, event",
+    )?;
+    let rows = stmt
+        .query_map(rusqlite::params![experiment_id], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, i64>(2)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    for (arm, event, count) in rows {
+        let report = match ExperimentArm::from_str(&arm)? {
+            ExperimentArm::A => &mut arm_a,
+            ExperimentArm::B => &mut arm_b,
+        };
+        match event.as_str() {
+            "exposure" => report.exposures = count,
+            "used" => report.used = count,
+            "useful" => report.useful = count,
+            "irrelevant" => report.irrelevant = count,
+            _ => {}
+        }
+    }
+
+    for report in [&mut arm_a, &mut arm_b] {
+        let judged = report.judged();
+        report.positive_rate = if judged == 0 {
+            0.0
+        } else {
+            report.positives() as f64 / judged as f64
+        };
+        report.outcomes_per_search = if report.exposures == 0 {
+            0.0
+        } else {
+            judged as f64 / report.exposures as f64
+        };
+    }
+
+    let z_score = two_proportion_z(&arm_a, &arm_b);
+    let enough_data = arm_a.judged() >= min_outcomes && arm_b.judged() >= min_outcomes;
+
+    let (winner, verdict) = match z_score {
+        _ if !enough_data => (
+            None,
+            format!(
+                "insufficient data: need {} judged outcomes per arm (a: {}, b: {})",
+                min_outcomes,
+                arm_a.judged(),
+                arm_b.judged()
+            ),
+        ),
+        Some(z) if z >= Z_CRITICAL_95 => {
+            (Some(ExperimentArm::B), format!("arm b wins (z = {z:.2})"))
+        }
+        Some(z) if z <= -Z_CRITICAL_95 => {
+            (Some(ExperimentArm::A), format!("arm a wins (z = {z:.2})"))
+        }
+        Some(z) => (None, format!("no significant difference (z = {z:.2})")),
+        None => (None, "no significant difference".to_string()),
+    };
+
+    Ok(ExperimentReport {
+        experiment,
+        arm_a,
+        arm_b,
+        z_score,
+        winner,
+        verdict,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Two-proportion z statistic for `b.positive_rate - a.positive_rate`.
+fn two_proportion_z(a: &ArmReport, b: &ArmReport) -> Option<f64> {
+    let (na, nb) = (a.judged() as f64, b.judged() as f64);
+    if na == 0.0 || nb == 0.0 {
+        return None;
+    }
+    let pooled = (a.positives() + b.positives()) as f64 / (na + nb);
+    let se = (pooled * (1.0 - pooled) * (1.0 / na + 1.0 / nb)).sqrt();
+    if se == 0.0 {
+        return None;
+    }
+    Some((b.positive_rate - a.positive_rate) / se)
+}
+
+/// Map a rusqlite row to [`SearchExperiment`].
+fn row_to_experiment(r: &rusqlite::Row<'_>) -> rusqlite::Result<SearchExperiment> {
+    let parse_variant = |idx: usize, raw: String| {
+        serde_json::from_str::<RankingVariant>(&raw).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+    };
+
+    Ok(SearchExperiment {
+        id: r.get(0)?,
+        name: r.get(1)?,
+        workspace: r.get(2)?,
+        variant_a: parse_variant(3, r.get(3)?)?,
+        variant_b: parse_variant(4, r.get(4)?)?,
+        traffic_b: r.get(5)?,
+        status: r.get(6)?,
+        created_at: r.get(7)?,
+        stopped_at: r.get(8)?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().expect("open in-memory db");
+        conn.execute_batch(CREATE_SEARCH_EXPERIMENT_TABLES)
+            .expect("create tables");
+        conn
+    }
+
+    fn keyword_heavy() -> RankingVariant {
+        RankingVariant {
+            keyword_weight: Some(0.8),
+            semantic_weight: Some(0.2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_assignment_is_deterministic() {
+        for q in ["rust async", "how do we deploy", "jwt"] {
+            assert_eq!(assign_arm(1, q, 0.5), assign_arm(1, q, 0.5));
+        }
+        // Whitespace and case do not change the bucket.
+        assert_eq!(
+            assign_arm(7, "Rust   Async", 0.5),
+            assign_arm(7, "rust async", 0.5)
+        );
+    }
+
+    #[test]
+    fn test_assignment_respects_traffic_split() {
+        let queries: Vec<String> = (0..2000).map(|i| format!("query {i}")).collect();
+
+        assert!(queries
+            .iter()
+            .all(|q| assign_arm(1, q, 0.0) == ExperimentArm::A));
+        assert!(queries
+            .iter()
+            .all(|q| assign_arm(1, q, 1.0) == ExperimentArm::B));
+
+        let b = queries
+            .iter()
+            .filter(|q| assign_arm(1, q, 0.2) == ExperimentArm::B)
+            .count() as f64;
+        let frac = b / queries.len() as f64;
+        assert!((frac - 0.2).abs() < 0.04, "expected ~20% on b, got {frac}");
+    }
+
+    #[test]
+    fn test_variant_apply_overrides_only_set_fields() {
+        let base = SearchConfig::default();
+        let config = keyword_heavy().apply(&base);
+        assert_eq!(config.keyword_weight, 0.8);
+        assert_eq!(config.semantic_weight, 0.2);
+        assert_eq!(config.rrf_k, base.rrf_k);
+        assert_eq!(config.min_score, base.min_score);
+    }
+
+    #[test]
+    fn test_create_get_and_stop() {
+        let conn = setup();
+        let exp = create_experiment(
+            &conn,
+            "kw-vs-sem",
+            Some("ws"),
+            &RankingVariant::default(),
+            &keyword_heavy(),
+            0.3,
+        )
+        .unwrap();
+        assert!(exp.is_active());
+        assert_eq!(exp.variant_b, keyword_heavy());
+        assert_eq!(exp.workspace.as_deref(), Some("ws"));
+
+        let stopped = stop_experiment(&conn, exp.id).unwrap();
+        assert!(!stopped.is_active());
+        assert!(stopped.stopped_at.is_some());
+        assert!(list_experiments(&conn, true).unwrap().is_empty());
+        assert_eq!(list_experiments(&conn, false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_create_rejects_bad_split() {
+        let conn = setup();
+        let v = RankingVariant::default();
+        assert!(create_experiment(&conn, "x", None, &v, &v, 1.5).is_err());
+        assert!(create_experiment(&conn, " ", None, &v, &v, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_missing_experiment_is_not_found() {
+        let conn = setup();
+        assert!(matches!(
+            get_experiment(&conn, 42),
+            Err(EngramError::NotFound(42))
+        ));
+        assert!(matches!(
+            stop_experiment(&conn, 42),
+            Err(EngramError::NotFound(42))
+        ));
+    }
+
+    #[test]
+    fn test_outcome_credited_to_assigned_arm() {
+        let conn = setup();
+        let v = RankingVariant::default();
+        let exp = create_experiment(&conn, "e", None, &v, &v, 0.5).unwrap();
+
+        let arm = exp.assign("deploy pipeline");
+        record_exposure(&conn, exp.id, arm, "deploy pipeline").unwrap();
+        let credited = record_outcome(
+            &conn,
+            exp.id,
+            "Deploy Pipeline",
+            ExperimentOutcome::Useful,
+            Some(3),
+        )
+        .unwrap();
+        assert_eq!(credited, arm);
+
+        let report = experiment_report(&conn, exp.id, 1).unwrap();
+        let arm_report = match arm {
+            ExperimentArm::A => &report.arm_a,
+            ExperimentArm::B => &report.arm_b,
+        };
+        assert_eq!(arm_report.exposures, 1);
+        assert_eq!(arm_report.useful, 1);
+        assert_eq!(arm_report.positive_rate, 1.0);
+    }
+
+    #[test]
+    fn test_report_declares_winner_with_enough_evidence() {
+        let conn = setup();
+        let v = RankingVariant::default();
+        let exp = create_experiment(&conn, "e", None, &v, &keyword_heavy(), 0.5).unwrap();
+
+        let insert = |arm: &str, event: &str, n: usize| {
+            for _ in 0..n {
+                conn.execute(
+                    "INSERT INTO search_experiment_events (experiment_id, arm, event, query_hash)
+                     VALUES (?1, ?2, ?3, 'h')",
+                    rusqlite::params![exp.id, arm, event],
+                )
+                .unwrap();
+            }
+        };
+        insert("a", "exposure", 100);
+        insert("b", "exposure", 100);
+        insert("a", "useful", 20);
+        insert("a", "irrelevant", 30);
+        insert("b", "useful", 40);
+        insert("b", "irrelevant", 10);
+
+        let report = experiment_report(&conn, exp.id, 30).unwrap();
+        assert_eq!(report.winner, Some(ExperimentArm::B));
+        assert!(report.z_score.unwrap() > Z_CRITICAL_95);
+        assert!((report.arm_b.positive_rate - 0.8).abs() < 1e-9);
+
+        // Raising the bar above the available evidence withholds a verdict.
+        let cautious = experiment_report(&conn, exp.id, 100).unwrap();
+        assert_eq!(cautious.winner, None);
+        assert!(cautious.verdict.starts_with("insufficient data"));
+    }
+}
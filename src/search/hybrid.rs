@@ -1,7 +1,13 @@
 //! Hybrid search combining BM25 and semantic search
 //!
 //! Uses Reciprocal Rank Fusion (RRF) to combine results from
-//! keyword and vector search.
+//! keyword and vector search. With hybrid sparse + dense embeddings
+//! ([`SearchConfig::hybrid_dense_dims`]), the dense and TF-IDF parts of the
+//! vectors are ranked separately and fused as well.
+//!
+//! Once the [vector index](super::vector_index) has been built, semantic
+//! search takes its nearest-neighbour candidates from the index instead of
+//! comparing the query with every stored embedding.
 
 use std::collections::HashMap;
 
@@ -9,13 +15,21 @@ use chrono::Utc;
 use rusqlite::Connection;
 
 use super::bm25::bm25_search_complete_with_scope_path;
-use super::{select_search_strategy, SearchConfig};
-use crate::embedding::{cosine_similarity, get_embedding};
+use super::vector_index::VectorIndexHandle;
+use super::{select_search_strategy_with, SearchConfig};
+use crate::embedding::{cosine_similarity, get_embedding, hybrid_parts};
 use crate::error::Result;
 use crate::storage::filter::{parse_filter, SqlBuilder};
 use crate::storage::queries::{load_tags, memory_from_row};
 use crate::types::{MatchInfo, Memory, MemoryId, SearchOptions, SearchResult, SearchStrategy};
 
+/// Index candidates fetched per requested result, leaving room for the
+/// filters applied afterwards
+const ANN_OVERSAMPLE: usize = 10;
+
+/// Fewest index candidates fetched for a semantic search
+const ANN_MIN_CANDIDATES: usize = 100;
+
 /// Apply project context boost to a memory's score if it matches the current project path
 fn apply_project_context_boost(memory: &Memory, score: f32, config: &SearchConfig) -> f32 {
     if let Some(ref project_path) = config.project_context_path {
@@ -31,6 +45,36 @@ fn apply_project_context_boost(memory: &Memory, score: f32, config: &SearchConfi
     score
 }
 
+/// Which part of the stored vectors semantic search compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VectorPart {
+    Whole,
+    /// The dense part of hybrid vectors
+    Dense,
+    /// The sparse (TF-IDF) part of hybrid vectors
+    Sparse,
+}
+
+impl VectorPart {
+    /// Similarity of the compared parts; `None` when a split part is asked
+    /// for but the vectors aren't hybrid vectors of the same layout
+    fn similarity(self, query: &[f32], stored: &[f32], config: &SearchConfig) -> Option<f32> {
+        if self == VectorPart::Whole {
+            return Some(cosine_similarity(query, stored));
+        }
+        let dense_dims = config.hybrid_dense_dims?;
+        if query.len() != stored.len() {
+            return None;
+        }
+        let (query_dense, query_sparse) = hybrid_parts(query, dense_dims)?;
+        let (stored_dense, stored_sparse) = hybrid_parts(stored, dense_dims)?;
+        Some(match self {
+            VectorPart::Dense => cosine_similarity(query_dense, stored_dense),
+            _ => cosine_similarity(query_sparse, stored_sparse),
+        })
+    }
+}
+
 /// Perform hybrid search with automatic strategy selection
 pub fn hybrid_search(
     conn: &Connection,
@@ -39,9 +83,22 @@ pub fn hybrid_search(
     options: &SearchOptions,
     config: &SearchConfig,
 ) -> Result<Vec<SearchResult>> {
-    let strategy = options
-        .strategy
-        .unwrap_or_else(|| select_search_strategy(query));
+    hybrid_search_with_index(conn, query, query_embedding, options, config, None)
+}
+
+/// [`hybrid_search`] taking semantic candidates from `index` once it has
+/// been built
+pub fn hybrid_search_with_index(
+    conn: &Connection,
+    query: &str,
+    query_embedding: Option<&[f32]>,
+    options: &SearchOptions,
+    config: &SearchConfig,
+    index: Option<&VectorIndexHandle>,
+) -> Result<Vec<SearchResult>> {
+    let strategy = options.strategy.unwrap_or_else(|| {
+        select_search_strategy_with(query, config.short_threshold, config.long_threshold)
+    });
     let limit = options.limit.unwrap_or(20);
     let min_score = options.min_score.unwrap_or(config.min_score);
 
@@ -51,7 +108,16 @@ pub fn hybrid_search(
         }
         SearchStrategy::SemanticOnly => {
             if let Some(embedding) = query_embedding {
-                semantic_only_search(conn, embedding, limit, min_score, options, config)
+                semantic_only_search(
+                    conn,
+                    embedding,
+                    VectorPart::Whole,
+                    limit,
+                    min_score,
+                    options,
+                    config,
+                    index,
+                )
             } else {
                 // Fallback to keyword if no embedding
                 keyword_only_search(conn, query, limit, min_score, options, config)
@@ -59,7 +125,9 @@ pub fn hybrid_search(
         }
         SearchStrategy::Hybrid => {
             if let Some(embedding) = query_embedding {
-                rrf_hybrid_search(conn, query, embedding, limit, min_score, options, config)
+                rrf_hybrid_search(
+                    conn, query, embedding, limit, min_score, options, config, index,
+                )
             } else {
                 keyword_only_search(conn, query, limit, min_score, options, config)
             }
@@ -89,6 +157,8 @@ fn keyword_only_search(
         options.workspaces.as_deref(),
         options.tier.as_ref(),
         options.scope_path.as_deref(),
+        options.show_superseded,
+        options.validation_status,
     )?;
 
     let mut results: Vec<SearchResult> = bm25_results
@@ -105,6 +175,7 @@ fn keyword_only_search(
                     highlights: r.highlights,
                     semantic_score: None,
                     keyword_score: Some(r.score),
+                    boosts: Vec::new(),
                 },
             }
         })
@@ -122,13 +193,16 @@ fn keyword_only_search(
 }
 
 /// Semantic-only search using vector similarity
+#[allow(clippy::too_many_arguments)]
 fn semantic_only_search(
     conn: &Connection,
     query_embedding: &[f32],
+    part: VectorPart,
     limit: i64,
     min_score: f32,
     options: &SearchOptions,
     config: &SearchConfig,
+    index: Option<&VectorIndexHandle>,
 ) -> Result<Vec<SearchResult>> {
     let now = Utc::now().to_rfc3339();
 
@@ -155,6 +229,16 @@ fn semantic_only_search(
         sql.push_str(" AND (m.lifecycle_state IS NULL OR m.lifecycle_state != 'archived')");
     }
 
+    // Exclude superseded memories unless show_superseded is true
+    if !options.show_superseded {
+        sql.push_str(" AND m.superseded_at IS NULL");
+    }
+
+    if let Some(status) = options.validation_status {
+        sql.push_str(" AND COALESCE(m.validation_status, 'unverified') = ?");
+        params.push(Box::new(status.as_str().to_string()));
+    }
+
     // Advanced filter (RML-932) - takes precedence over legacy tags/memory_type
     if let Some(ref filter_json) = options.filter {
         let filter_expr = parse_filter(filter_json)?;
@@ -169,6 +253,7 @@ fn semantic_only_search(
         // Legacy filters (deprecated, use `filter` instead)
         // Add tag filter if specified
         if let Some(ref tags) = options.tags {
+            let tags = &crate::storage::expand_tag_filter(conn, tags)?;
             if !tags.is_empty() {
                 sql.push_str(
                     " AND m.id IN (
@@ -234,30 +319,62 @@ fn semantic_only_search(
         params.push(Box::new(format!("{}/", escaped) + "%"));
     }
 
-    let mut stmt = conn.prepare(&sql)?;
-
     let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|b| b.as_ref()).collect();
 
-    let memories: Vec<Memory> = stmt
-        .query_map(param_refs.as_slice(), memory_from_row)?
-        .filter_map(|r| r.ok())
-        .map(|mut m| {
-            m.tags = load_tags(conn, m.id).unwrap_or_default();
-            m
-        })
-        .collect();
-
     // Calculate similarity scores with project context boost
-    let mut scored: Vec<(Memory, f32, f32)> = Vec::new(); // (memory, boosted_score, original_score)
-    for memory in memories {
-        if let Ok(Some(embedding)) = get_embedding(conn, memory.id) {
-            let similarity = cosine_similarity(query_embedding, &embedding);
-            if similarity >= min_score {
-                let boosted_score = apply_project_context_boost(&memory, similarity, config);
-                scored.push((memory, boosted_score, similarity));
+    // (memory, boosted_score, original_score)
+    let score_matches = |sql: &str| -> Result<Vec<(Memory, f32, f32)>> {
+        let mut stmt = conn.prepare(sql)?;
+        let memories: Vec<Memory> = stmt
+            .query_map(param_refs.as_slice(), memory_from_row)?
+            .filter_map(|r| r.ok())
+            .map(|mut m| {
+                m.tags = load_tags(conn, m.id).unwrap_or_default();
+                m
+            })
+            .collect();
+
+        let mut scored = Vec::new();
+        for memory in memories {
+            if let Ok(Some(embedding)) = get_embedding(conn, memory.id) {
+                let Some(similarity) = part.similarity(query_embedding, &embedding, config) else {
+                    continue;
+                };
+                if similarity >= min_score {
+                    let boosted_score = apply_project_context_boost(&memory, similarity, config);
+                    scored.push((memory, boosted_score, similarity));
+                }
             }
         }
-    }
+        Ok(scored)
+    };
+
+    // The index compares whole vectors, so split parts are always scanned.
+    // Candidates are re-scored against their stored embeddings; when the
+    // filters leave too few of them, every match is scanned instead.
+    let wanted = (limit.max(0) as usize)
+        .saturating_mul(ANN_OVERSAMPLE)
+        .max(ANN_MIN_CANDIDATES);
+    let candidates = index
+        .filter(|_| part == VectorPart::Whole)
+        .and_then(|index| index.search(query_embedding, wanted));
+    let mut scored = match candidates {
+        Some(hits) => {
+            let exhaustive = hits.len() < wanted;
+            let ids: Vec<String> = hits.iter().map(|h| h.memory_id.to_string()).collect();
+            let scored = if ids.is_empty() {
+                Vec::new()
+            } else {
+                score_matches(&format!("{} AND m.id IN ({})", sql, ids.join(", ")))?
+            };
+            if exhaustive || scored.len() >= limit.max(0) as usize {
+                scored
+            } else {
+                score_matches(&sql)?
+            }
+        }
+        None => score_matches(&sql)?,
+    };
 
     // Sort by boosted score descending
     scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
@@ -274,6 +391,7 @@ fn semantic_only_search(
                 highlights: vec![],
                 semantic_score: Some(original_score),
                 keyword_score: None,
+                boosts: Vec::new(),
             },
         })
         .collect();
@@ -282,6 +400,7 @@ fn semantic_only_search(
 }
 
 /// Hybrid search using Reciprocal Rank Fusion
+#[allow(clippy::too_many_arguments)]
 fn rrf_hybrid_search(
     conn: &Connection,
     query: &str,
@@ -290,6 +409,7 @@ fn rrf_hybrid_search(
     min_score: f32,
     options: &SearchOptions,
     config: &SearchConfig,
+    index: Option<&VectorIndexHandle>,
 ) -> Result<Vec<SearchResult>> {
     // Get keyword results (with all filters applied)
     let keyword_results = bm25_search_complete_with_scope_path(
@@ -305,6 +425,8 @@ fn rrf_hybrid_search(
         options.workspaces.as_deref(),
         options.tier.as_ref(),
         options.scope_path.as_deref(),
+        options.show_superseded,
+        options.validation_status,
     )?;
 
     // Get semantic results (without boost - we'll apply it to the final RRF score)
@@ -319,6 +441,8 @@ fn rrf_hybrid_search(
         workspaces: options.workspaces.clone(),
         tier: options.tier,
         scope_path: options.scope_path.clone(),
+        show_superseded: options.show_superseded,
+        validation_status: options.validation_status,
         ..Default::default()
     };
     // Create a config without project boost for sub-search (we'll apply boost to final RRF)
@@ -326,14 +450,40 @@ fn rrf_hybrid_search(
         project_context_path: None,
         ..*config
     };
+    // Hybrid sparse + dense vectors are ranked by each part separately
+    let split = config
+        .hybrid_dense_dims
+        .and_then(|dims| hybrid_parts(query_embedding, dims))
+        .is_some();
+    let semantic_part = if split {
+        VectorPart::Dense
+    } else {
+        VectorPart::Whole
+    };
     let semantic_results = semantic_only_search(
         conn,
         query_embedding,
+        semantic_part,
         limit * 2,
         0.0,
         &semantic_options,
         &no_boost_config,
+        index,
     )?;
+    let sparse_results = if split {
+        semantic_only_search(
+            conn,
+            query_embedding,
+            VectorPart::Sparse,
+            limit * 2,
+            0.0,
+            &semantic_options,
+            &no_boost_config,
+            None,
+        )?
+    } else {
+        Vec::new()
+    };
 
     // Build rank maps
     let mut keyword_ranks: HashMap<MemoryId, usize> = HashMap::new();
@@ -350,10 +500,19 @@ fn rrf_hybrid_search(
         semantic_scores.insert(result.memory.id, result.score);
     }
 
+    // Sparse results with no similarity at all don't rank
+    let sparse_ranks: HashMap<MemoryId, usize> = sparse_results
+        .iter()
+        .filter(|result| result.score > 0.0)
+        .enumerate()
+        .map(|(rank, result)| (result.memory.id, rank + 1))
+        .collect();
+
     // Collect all unique memory IDs
     let mut all_ids: Vec<MemoryId> = keyword_ranks
         .keys()
         .chain(semantic_ranks.keys())
+        .chain(sparse_ranks.keys())
         .cloned()
         .collect();
     all_ids.sort();
@@ -374,7 +533,12 @@ fn rrf_hybrid_search(
             .map(|&rank| config.semantic_weight / (k + rank as f32))
             .unwrap_or(0.0);
 
-        let rrf_score = keyword_contribution + semantic_contribution;
+        let sparse_contribution = sparse_ranks
+            .get(&id)
+            .map(|&rank| config.sparse_weight / (k + rank as f32))
+            .unwrap_or(0.0);
+
+        let rrf_score = keyword_contribution + semantic_contribution + sparse_contribution;
 
         if rrf_score >= min_score * 0.01 {
             // Adjusted threshold for RRF
@@ -402,6 +566,7 @@ fn rrf_hybrid_search(
             .or_else(|| {
                 semantic_results
                     .iter()
+                    .chain(&sparse_results)
                     .find(|r| r.memory.id == id)
                     .map(|r| r.memory.clone())
             });
@@ -439,6 +604,7 @@ fn rrf_hybrid_search(
                     highlights,
                     semantic_score,
                     keyword_score,
+                    boosts: Vec::new(),
                 },
             });
         }
@@ -462,4 +628,127 @@ mod tests {
         // First rank should have higher score
         assert!(score1 > score2);
     }
+
+    #[test]
+    fn test_vector_part_similarity() {
+        use super::{SearchConfig, VectorPart};
+
+        let query = [1.0, 0.0, 0.0, 1.0];
+        let stored = [1.0, 0.0, 1.0, 0.0];
+        let plain = SearchConfig::default();
+        assert!(VectorPart::Dense
+            .similarity(&query, &stored, &plain)
+            .is_none());
+        assert!(
+            (VectorPart::Whole
+                .similarity(&query, &stored, &plain)
+                .unwrap()
+                - 0.5)
+                .abs()
+                < 1e-6
+        );
+
+        let split = SearchConfig {
+            hybrid_dense_dims: Some(2),
+            ..Default::default()
+        };
+        let dense = VectorPart::Dense
+            .similarity(&query, &stored, &split)
+            .unwrap();
+        let sparse = VectorPart::Sparse
+            .similarity(&query, &stored, &split)
+            .unwrap();
+        assert!((dense - 1.0).abs() < 1e-6);
+        assert!(sparse.abs() < 1e-6);
+        assert!(VectorPart::Sparse
+            .similarity(&query, &stored[..3], &split)
+            .is_none());
+    }
+
+    #[test]
+    fn test_semantic_search_uses_vector_index() {
+        use super::{hybrid_search, hybrid_search_with_index, SearchConfig};
+        use crate::embedding::store_embedding;
+        use crate::search::vector_index::{VectorIndexConfig, VectorIndexHandle, VectorIndexKind};
+        use crate::storage::queries::create_memory;
+        use crate::storage::Storage;
+        use crate::types::{CreateMemoryInput, SearchOptions, SearchStrategy};
+
+        let storage = Storage::open_in_memory().unwrap();
+        let index = VectorIndexHandle::new(VectorIndexConfig {
+            kind: VectorIndexKind::Flat,
+            ..Default::default()
+        });
+        let query = [1.0, 0.0, 0.0];
+        let options = SearchOptions {
+            strategy: Some(SearchStrategy::SemanticOnly),
+            limit: Some(5),
+            min_score: Some(0.0),
+            ..Default::default()
+        };
+        let config = SearchConfig::default();
+
+        storage
+            .with_connection(|conn| {
+                let create = |content: &str, tags: Vec<String>, vector: &[f32]| {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            tags,
+                            defer_embedding: true,
+                            ..Default::default()
+                        },
+                    )?;
+                    store_embedding(conn, memory.id, vector, "test", 3, None, "now")?;
+                    Ok::<_, crate::error::EngramError>(memory.id)
+                };
+                for i in 0..150 {
+                    create(
+                        &format!("near {}", i),
+                        vec![],
+                        &[1.0, 0.1 + i as f32 * 0.01, 0.0],
+                    )?;
+                }
+                let rare = create("far away", vec!["rare".to_string()], &[0.0, 0.0, 1.0])?;
+                index.rebuild(conn, None)?;
+
+                let search = |options: &SearchOptions| {
+                    hybrid_search_with_index(
+                        conn,
+                        "q",
+                        Some(&query),
+                        options,
+                        &config,
+                        Some(&index),
+                    )
+                };
+                let results = search(&options)?;
+                assert_eq!(results.len(), 5);
+                assert_eq!(results[0].memory.content, "near 0");
+
+                // The filter leaves no index candidate, so every match is scanned
+                let rare_only = SearchOptions {
+                    tags: Some(vec!["rare".to_string()]),
+                    ..options.clone()
+                };
+                let results = search(&rare_only)?;
+                assert_eq!(results.len(), 1);
+                assert_eq!(results[0].memory.id, rare);
+
+                // Embeddings stored after the build only match once inserted
+                let exact = create("exact match", vec![], &query)?;
+                assert_ne!(search(&options)?[0].memory.id, exact);
+                assert_eq!(
+                    hybrid_search(conn, "q", Some(&query), &options, &config)?[0]
+                        .memory
+                        .id,
+                    exact
+                );
+                index.insert(exact, &query);
+                assert_eq!(search(&options)?[0].memory.id, exact);
+                Ok(())
+            })
+            .unwrap();
+    }
 }
@@ -4,14 +4,18 @@
 //! - BM25 full-text search (RML-876)
 //! - Fuzzy/typo-tolerant search (RML-877)
 //! - Search result explanation (RML-878)
-//! - Adaptive search strategy (RML-898)
+//! - Adaptive search strategy (RML-898), learned from outcomes per workspace
 //! - Hybrid search with RRF
 //! - Aggregation queries (RML-880)
 //! - Search result reranking (RML-927)
 //! - Search result caching with adaptive thresholds (ENG-36)
+//! - A/B testing of ranking configurations
+//! - Contextual boost rules
 
 mod aggregation;
 mod bm25;
+pub mod boost_rules;
+pub mod experiments;
 pub mod explain;
 pub mod feedback;
 mod fuzzy;
@@ -21,10 +25,14 @@ pub mod mmr;
 mod rerank;
 pub mod result_cache;
 pub mod semantic_cache;
+pub mod strategy_learning;
 pub mod utility;
+pub mod vector_index;
 
 #[cfg(feature = "neural-rerank")]
 pub mod neural_rerank;
+#[cfg(feature = "api-rerank")]
+pub mod rerank_api;
 
 pub use aggregation::*;
 pub use bm25::*;
@@ -40,27 +48,32 @@ use crate::types::SearchStrategy;
 
 /// Analyze query to determine optimal search strategy (RML-898)
 pub fn select_search_strategy(query: &str) -> SearchStrategy {
-    let query = query.trim();
-    let word_count = query.split_whitespace().count();
-    let has_quotes = query.contains('"');
-    let has_operators = query.contains(':')
-        || query.contains(" AND ")
-        || query.contains(" OR ")
-        || query.contains(" NOT ");
-    let has_special = query.contains('*') || query.contains('?');
+    let config = SearchConfig::default();
+    select_search_strategy_with(query, config.short_threshold, config.long_threshold)
+}
 
+/// [`select_search_strategy`] with explicit word-count cut-offs: queries of at
+/// most `short_threshold` words go keyword-only, queries of at least
+/// `long_threshold` words go semantic-only.
+pub fn select_search_strategy_with(
+    query: &str,
+    short_threshold: usize,
+    long_threshold: usize,
+) -> SearchStrategy {
     // Explicit search syntax → keyword only
-    if has_quotes || has_operators || has_special {
+    if has_search_syntax(query) {
         return SearchStrategy::KeywordOnly;
     }
 
+    let word_count = query.split_whitespace().count();
+
     // Very short queries → keyword (faster, usually precise enough)
-    if word_count <= 2 {
+    if word_count <= short_threshold {
         return SearchStrategy::KeywordOnly;
     }
 
     // Long conceptual queries → semantic
-    if word_count >= 8 {
+    if word_count >= long_threshold {
         return SearchStrategy::SemanticOnly;
     }
 
@@ -68,6 +81,19 @@ pub fn select_search_strategy(query: &str) -> SearchStrategy {
     SearchStrategy::Hybrid
 }
 
+/// Whether the query uses quotes, boolean operators or wildcards, which only
+/// the keyword index understands.
+pub fn has_search_syntax(query: &str) -> bool {
+    let query = query.trim();
+    let has_quotes = query.contains('"');
+    let has_operators = query.contains(':')
+        || query.contains(" AND ")
+        || query.contains(" OR ")
+        || query.contains(" NOT ");
+    let has_special = query.contains('*') || query.contains('?');
+    has_quotes || has_operators || has_special
+}
+
 /// Strategy for deduplicating search results across result sets
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum DedupeStrategy {
@@ -100,6 +126,13 @@ pub struct SearchConfig {
     pub project_context_path: Option<String>,
     /// Deduplication strategy for hybrid search
     pub dedupe_strategy: DedupeStrategy,
+    /// Dense components leading each stored vector when embeddings are
+    /// hybrid sparse + dense vectors (see
+    /// [`HybridEmbedder`](crate::embedding::HybridEmbedder)); hybrid search
+    /// then ranks the sparse part separately
+    pub hybrid_dense_dims: Option<usize>,
+    /// Weight for the sparse part of hybrid embeddings in hybrid search
+    pub sparse_weight: f32,
 }
 
 impl Default for SearchConfig {
@@ -114,6 +147,8 @@ impl Default for SearchConfig {
             project_context_boost: 0.2,
             project_context_path: None,
             dedupe_strategy: DedupeStrategy::default(),
+            hybrid_dense_dims: None,
+            sparse_weight: 0.3,
         }
     }
 }
@@ -3,14 +3,20 @@
 //! Provides neural reranking using ONNX-based cross-encoder models
 //! (e.g., ms-marco-MiniLM-L-6-v2) for high-quality query-document
 //! relevance scoring. This module complements the heuristic-based
-//! reranker in `rerank.rs` with learned scoring.
+//! reranker in `rerank.rs` with learned scoring, and backs
+//! `RerankStrategy::CrossEncoder` through the [`CrossEncoder`] trait.
 //!
 //! Feature-gated behind `neural-rerank` to keep the default binary lean.
 
 #![cfg(feature = "neural-rerank")]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use ort::value::Tensor;
+
+use super::rerank::CrossEncoder;
+use crate::embedding::WordPieceTokenizer;
 use crate::error::{EngramError, Result};
 use crate::types::Memory;
 
@@ -73,6 +79,9 @@ pub trait Reranker: Send + Sync {
 pub struct CrossEncoderConfig {
     /// Path to the ONNX model file (e.g., `model.onnx`).
     pub model_path: PathBuf,
+    /// Path to the model's `tokenizer.json` or `vocab.txt`.  Defaults to
+    /// `tokenizer.json` next to the model.
+    pub tokenizer_path: Option<PathBuf>,
     /// Maximum total token length for `[CLS] query [SEP] document [SEP]`.
     /// Defaults to 512.
     pub max_length: usize,
@@ -88,6 +97,7 @@ impl Default for CrossEncoderConfig {
     fn default() -> Self {
         Self {
             model_path: PathBuf::from("model.onnx"),
+            tokenizer_path: None,
             max_length: 512,
             batch_size: 32,
             threshold: 0.0,
@@ -102,25 +112,68 @@ impl Default for CrossEncoderConfig {
 /// ONNX-based cross-encoder reranker.
 ///
 /// Uses an ms-marco-MiniLM-L-6-v2 compatible model to score each
-/// `(query, document)` pair.  The raw logits are min-max normalised to
-/// `[0, 1]` across the batch, then filtered by `config.threshold`.
+/// `(query, document)` pair.  As a [`Reranker`], the raw logits are min-max
+/// normalised to `[0, 1]` across the batch, then filtered by
+/// `config.threshold`; as a [`CrossEncoder`], each logit goes through a
+/// sigmoid so scores compare across queries.
 pub struct CrossEncoderReranker {
     config: CrossEncoderConfig,
-    /// ONNX inference session.  `None` when running under `cfg(test)` or
-    /// when the model file was not found.
-    session: Option<ort::session::Session>,
+    session: Mutex<ort::session::Session>,
+    tokenizer: WordPieceTokenizer,
+    /// Whether the model takes `token_type_ids` (BERT exports do, some
+    /// distilled ones don't).
+    uses_token_types: bool,
+    model_name: String,
 }
 
 impl CrossEncoderReranker {
     /// Load the ONNX cross-encoder from `config.model_path`.
     ///
-    /// Returns an error if the model file cannot be opened or the session
-    /// cannot be initialised.
+    /// Returns an error if the model or tokenizer file cannot be opened or
+    /// the session cannot be initialised.
     pub fn new(config: CrossEncoderConfig) -> Result<Self> {
         let session = Self::load_session(&config)?;
+        let tokenizer_path = config
+            .tokenizer_path
+            .clone()
+            .unwrap_or_else(|| config.model_path.with_file_name("tokenizer.json"));
+        let tokenizer = WordPieceTokenizer::from_file(&tokenizer_path)?;
+        let uses_token_types = session
+            .inputs()
+            .iter()
+            .any(|input| input.name() == "token_type_ids");
+        let model_name = config
+            .model_path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "cross-encoder".to_string());
         Ok(Self {
             config,
-            session: Some(session),
+            session: Mutex::new(session),
+            tokenizer,
+            uses_token_types,
+            model_name,
+        })
+    }
+
+    /// Load `model.onnx` and `tokenizer.json` (or `vocab.txt`) from a model
+    /// directory as downloaded from HuggingFace.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let tokenizer_path = ["tokenizer.json", "vocab.txt"]
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                EngramError::Config(format!(
+                    "No tokenizer.json or vocab.txt found in {}",
+                    dir.display()
+                ))
+            })?;
+        Self::new(CrossEncoderConfig {
+            model_path: dir.join("model.onnx"),
+            tokenizer_path: Some(tokenizer_path),
+            ..Default::default()
         })
     }
 
@@ -139,42 +192,92 @@ impl CrossEncoderReranker {
             })
     }
 
-    /// Build the cross-encoder input string for a single pair.
-    ///
-    /// Format: `[CLS] {query} [SEP] {content} [SEP]`
-    fn build_input(query: &str, content: &str, max_length: usize) -> String {
-        let raw = format!("[CLS] {query} [SEP] {content} [SEP]");
-        // Truncate at character boundary (not perfect but avoids panics)
-        if raw.chars().count() <= max_length {
-            raw
-        } else {
-            raw.chars().take(max_length).collect()
+    /// Raw relevance logits of `documents` for `query`, in batches of
+    /// `config.batch_size` pairs padded to their longest.
+    fn score_raw(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        let mut scores = Vec::with_capacity(documents.len());
+        for chunk in documents.chunks(self.config.batch_size.max(1)) {
+            let encoded: Vec<(Vec<i64>, Vec<i64>)> = chunk
+                .iter()
+                .map(|doc| {
+                    self.tokenizer
+                        .encode_pair(query, doc, self.config.max_length)
+                })
+                .collect();
+            scores.extend(self.score_batch(&encoded)?);
         }
+        Ok(scores)
     }
 
-    /// Score a single batch of inputs via the ONNX session.
+    /// Score one padded batch of `(input_ids, token_type_ids)` pairs.
     ///
-    /// Returns a vector of raw logit scores (one per input).
-    fn score_batch(&self, inputs: &[String]) -> Result<Vec<f32>> {
-        let session = self
-            .session
-            .as_ref()
-            .ok_or_else(|| EngramError::Config("ONNX session not initialised".to_string()))?;
+    /// Returns one raw logit per pair: the only output column, or the
+    /// "relevant" (last) column of two-class models.
+    fn score_batch(&self, encoded: &[(Vec<i64>, Vec<i64>)]) -> Result<Vec<f32>> {
+        let batch = encoded.len();
+        let seq_len = encoded.iter().map(|(ids, _)| ids.len()).max().unwrap_or(0);
+        let mut ids = Vec::with_capacity(batch * seq_len);
+        let mut mask = Vec::with_capacity(batch * seq_len);
+        let mut types = Vec::with_capacity(batch * seq_len);
+        for (input_ids, type_ids) in encoded {
+            let padding = seq_len - input_ids.len();
+            ids.extend(
+                input_ids
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat_n(0, padding)),
+            );
+            mask.extend(
+                std::iter::repeat_n(1i64, input_ids.len()).chain(std::iter::repeat_n(0, padding)),
+            );
+            types.extend(
+                type_ids
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat_n(0, padding)),
+            );
+        }
 
-        // Simple whitespace tokenisation — a production implementation
-        // would use a proper BERT tokeniser (e.g., via `tokenizers` crate).
-        // This is intentionally minimal; the `ort` integration wires up the
-        // forward pass contract.
-        let _session_ref = session; // silence unused-variable lint until ort types are available
+        let tensor = |data: Vec<i64>, name: &str| {
+            Tensor::from_array(([batch, seq_len], data))
+                .map_err(|e| EngramError::Search(format!("Failed to build {name} tensor: {e}")))
+        };
+        let ids = tensor(ids, "input_ids")?;
+        let mask = tensor(mask, "attention_mask")?;
+        let types = tensor(types, "token_type_ids")?;
 
-        // Placeholder: return zero scores for each input.
-        // Real implementation would:
-        //   1. Tokenise each `input` string with BertTokenizer
-        //   2. Pad/truncate to `config.max_length`
-        //   3. Stack into a 2-D tensor (batch × seq_len)
-        //   4. Run `session.run([input_ids, attention_mask])` → logits
-        //   5. Extract the relevance logit from the model output
-        Ok(vec![0.0_f32; inputs.len()])
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|e| EngramError::Search(format!("Failed to lock ONNX session: {e}")))?;
+        let outputs = if self.uses_token_types {
+            session.run(ort::inputs![
+                "input_ids" => ids,
+                "attention_mask" => mask,
+                "token_type_ids" => types
+            ])
+        } else {
+            session.run(ort::inputs![
+                "input_ids" => ids,
+                "attention_mask" => mask
+            ])
+        }
+        .map_err(|e| EngramError::Search(format!("ONNX inference error: {e}")))?;
+
+        let (shape, data) = outputs[0].try_extract_tensor::<f32>().map_err(|e| {
+            EngramError::Search(format!("Failed to extract ONNX output tensor: {e}"))
+        })?;
+        let columns = match &shape[..] {
+            [rows] if *rows as usize == batch => 1,
+            [rows, columns] if *rows as usize == batch && *columns > 0 => *columns as usize,
+            _ => {
+                return Err(EngramError::Search(format!(
+                    "Expected cross-encoder logits [{batch}, n], got shape {:?}",
+                    &*shape
+                )))
+            }
+        };
+        Ok(data.chunks(columns).map(|row| row[columns - 1]).collect())
     }
 
     /// Min-max normalise a slice of scores to `[0, 1]`.
@@ -196,6 +299,20 @@ impl CrossEncoderReranker {
     }
 }
 
+impl CrossEncoder for CrossEncoderReranker {
+    fn score(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        Ok(self
+            .score_raw(query, documents)?
+            .into_iter()
+            .map(|logit| 1.0 / (1.0 + (-logit).exp()))
+            .collect())
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+}
+
 impl Reranker for CrossEncoderReranker {
     /// Run the three-stage reranking pipeline:
     ///
@@ -214,17 +331,11 @@ impl Reranker for CrossEncoderReranker {
         }
 
         // ── Stage 1: Score ──────────────────────────────────────────────────
-        let inputs: Vec<String> = candidates
+        let documents: Vec<&str> = candidates
             .iter()
-            .map(|c| Self::build_input(query, &c.memory.content, self.config.max_length))
+            .map(|c| c.memory.content.as_str())
             .collect();
-
-        // Process in batches to respect memory constraints
-        let mut raw_scores: Vec<f32> = Vec::with_capacity(inputs.len());
-        for chunk in inputs.chunks(self.config.batch_size) {
-            let batch_scores = self.score_batch(&chunk.to_vec())?;
-            raw_scores.extend(batch_scores);
-        }
+        let mut raw_scores = self.score_raw(query, &documents)?;
 
         // ── Stage 2: Normalize ──────────────────────────────────────────────
         Self::normalize(&mut raw_scores);
@@ -396,6 +507,7 @@ mod tests {
             procedure_failure_count: 0,
             summary_of_id: None,
             lifecycle_state: LifecycleState::Active,
+            media_url: None,
         }
     }
 
@@ -570,22 +682,6 @@ mod tests {
         assert!((scores[2] - 1.0).abs() < f32::EPSILON);
     }
 
-    #[test]
-    fn test_build_input_format() {
-        let input = CrossEncoderReranker::build_input("my query", "document text", 512);
-        assert!(input.starts_with("[CLS]"));
-        assert!(input.contains("my query"));
-        assert!(input.contains("[SEP]"));
-        assert!(input.contains("document text"));
-    }
-
-    #[test]
-    fn test_build_input_truncation() {
-        // max_length of 10 chars — result must not exceed that count
-        let input = CrossEncoderReranker::build_input("query", "very long document content", 10);
-        assert!(input.chars().count() <= 10);
-    }
-
     #[test]
     fn test_pipeline_delegates_to_reranker() {
         let candidates = vec![
@@ -604,6 +700,7 @@ mod tests {
     #[test]
     fn test_cross_encoder_config_defaults() {
         let config = CrossEncoderConfig::default();
+        assert!(config.tokenizer_path.is_none());
         assert_eq!(config.max_length, 512);
         assert_eq!(config.batch_size, 32);
         assert!((config.threshold - 0.0).abs() < f32::EPSILON);
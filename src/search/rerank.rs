@@ -9,10 +9,19 @@
 //!
 //! Supports pluggable reranking strategies with a default heuristic-based
 //! approach and optional integration with cross-encoder models.
+//!
+//! `RerankStrategy::CrossEncoder` scores each query/document pair of the top
+//! `cross_encoder_top_k` results with a [`CrossEncoder`]: a local ONNX model
+//! (`neural-rerank` feature) or a hosted rerank API such as Cohere, Jina,
+//! Voyage or a text-embeddings-inference server (`api-rerank` feature).
+//! Without one, or if scoring fails, it falls back to the heuristic.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::error::{EngramError, Result};
 use crate::types::{Memory, MemoryType, SearchResult};
 
 /// Configuration for the reranker
@@ -40,6 +49,16 @@ pub struct RerankConfig {
     pub min_results: usize,
     /// Maximum number of results to rerank (for performance)
     pub max_rerank_candidates: usize,
+    /// Results scored by the cross-encoder; the rest keep their order below
+    #[serde(default = "default_cross_encoder_top_k")]
+    pub cross_encoder_top_k: usize,
+    /// Model behind `RerankStrategy::CrossEncoder`
+    #[serde(default)]
+    pub cross_encoder: Option<CrossEncoderBackend>,
+}
+
+fn default_cross_encoder_top_k() -> usize {
+    20
 }
 
 impl Default for RerankConfig {
@@ -56,6 +75,8 @@ impl Default for RerankConfig {
             exact_match_boost: 0.2,
             min_results: 3,
             max_rerank_candidates: 100,
+            cross_encoder_top_k: default_cross_encoder_top_k(),
+            cross_encoder: None,
         }
     }
 }
@@ -69,7 +90,8 @@ pub enum RerankStrategy {
     /// Heuristic-based reranking using query features
     #[default]
     Heuristic,
-    /// Cross-encoder model (requires external API or local model)
+    /// Cross-encoder model scoring query/document pairs (a local ONNX model
+    /// or a rerank API); heuristic when none is configured
     CrossEncoder,
     /// Reciprocal Rank Fusion with multiple signals
     MultiSignal,
@@ -118,24 +140,137 @@ pub struct RerankComponents {
     pub type_relevance: f32,
     /// Score from tag matches
     pub tag_match: f32,
+    /// Relevance from the cross-encoder model (0-1)
+    #[serde(default)]
+    pub cross_encoder: f32,
+}
+
+/// A model scoring how relevant each document is to a query, reading both
+/// together rather than comparing separate embeddings
+pub trait CrossEncoder: Send + Sync {
+    /// Relevance of each of `documents` to `query` in `[0, 1]`, in order
+    fn score(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>>;
+
+    /// Model name, for explanations
+    fn model_name(&self) -> &str;
+}
+
+/// Where cross-encoder scores come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum CrossEncoderBackend {
+    /// Local ONNX model directory with `model.onnx` and `tokenizer.json`
+    /// (or `vocab.txt`); requires the `neural-rerank` feature
+    Onnx { model_dir: PathBuf },
+    /// Hosted rerank endpoint; requires the `api-rerank` feature
+    Api {
+        provider: RerankApiProvider,
+        /// Model name; the provider's default if unset
+        #[serde(default)]
+        model: Option<String>,
+        /// API base URL; the provider's default if unset (required for `tei`)
+        #[serde(default)]
+        base_url: Option<String>,
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+}
+
+/// Hosted rerank APIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RerankApiProvider {
+    /// Cohere `/v1/rerank`
+    Cohere,
+    /// Jina AI `/v1/rerank` (Cohere-compatible)
+    Jina,
+    /// Voyage AI `/v1/rerank`
+    Voyage,
+    /// A Hugging Face text-embeddings-inference server's `/rerank`
+    Tei,
+}
+
+impl std::str::FromStr for RerankApiProvider {
+    type Err = EngramError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "cohere" => Ok(Self::Cohere),
+            "jina" => Ok(Self::Jina),
+            "voyage" => Ok(Self::Voyage),
+            "tei" => Ok(Self::Tei),
+            other => Err(EngramError::Config(format!(
+                "Unknown rerank provider '{}': expected cohere, jina, voyage or tei",
+                other
+            ))),
+        }
+    }
+}
+
+/// Load the cross-encoder described by `backend`
+pub fn create_cross_encoder(backend: &CrossEncoderBackend) -> Result<Arc<dyn CrossEncoder>> {
+    match backend {
+        #[cfg(feature = "neural-rerank")]
+        CrossEncoderBackend::Onnx { model_dir } => Ok(Arc::new(
+            super::neural_rerank::CrossEncoderReranker::from_dir(model_dir)?,
+        )),
+        #[cfg(not(feature = "neural-rerank"))]
+        CrossEncoderBackend::Onnx { .. } => Err(EngramError::Config(
+            "ONNX cross-encoder reranking requires the 'neural-rerank' feature. Build with: cargo build --features neural-rerank".to_string(),
+        )),
+        #[cfg(feature = "api-rerank")]
+        CrossEncoderBackend::Api {
+            provider,
+            model,
+            base_url,
+            api_key,
+        } => Ok(Arc::new(super::rerank_api::ApiCrossEncoder::new(
+            *provider,
+            model.clone(),
+            base_url.clone(),
+            api_key.clone(),
+        )?)),
+        #[cfg(not(feature = "api-rerank"))]
+        CrossEncoderBackend::Api { .. } => Err(EngramError::Config(
+            "API cross-encoder reranking requires the 'api-rerank' feature. Build with: cargo build --features api-rerank".to_string(),
+        )),
+    }
 }
 
 /// Reranker for search results
 pub struct Reranker {
     config: RerankConfig,
+    cross_encoder: Option<Arc<dyn CrossEncoder>>,
 }
 
 impl Reranker {
     /// Create a new reranker with default config
     pub fn new() -> Self {
-        Self {
-            config: RerankConfig::default(),
-        }
+        Self::with_config(RerankConfig::default())
     }
 
     /// Create a new reranker with custom config
     pub fn with_config(config: RerankConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cross_encoder: None,
+        }
+    }
+
+    /// Create a reranker loading the cross-encoder in `config`, if any
+    pub fn from_config(config: RerankConfig) -> Result<Self> {
+        let cross_encoder = config
+            .cross_encoder
+            .as_ref()
+            .map(create_cross_encoder)
+            .transpose()?;
+        Ok(Self::with_config(config).with_cross_encoder(cross_encoder))
+    }
+
+    /// Use an already loaded cross-encoder, e.g. one shared across searches
+    pub fn with_cross_encoder(mut self, cross_encoder: Option<Arc<dyn CrossEncoder>>) -> Self {
+        self.cross_encoder = cross_encoder;
+        self
     }
 
     /// Rerank search results
@@ -167,10 +302,15 @@ impl Reranker {
         match self.config.strategy {
             RerankStrategy::None => self.no_rerank(results),
             RerankStrategy::Heuristic => self.heuristic_rerank(results, query, query_entities),
-            RerankStrategy::CrossEncoder => {
-                // Cross-encoder requires external model, fallback to heuristic
-                self.heuristic_rerank(results, query, query_entities)
-            }
+            RerankStrategy::CrossEncoder => match &self.cross_encoder {
+                Some(cross_encoder) => self.cross_encoder_rerank(
+                    cross_encoder.as_ref(),
+                    results,
+                    query,
+                    query_entities,
+                ),
+                None => self.heuristic_rerank(results, query, query_entities),
+            },
             RerankStrategy::MultiSignal => self.multi_signal_rerank(results, query, query_entities),
         }
     }
@@ -250,6 +390,94 @@ impl Reranker {
         rerank_results
     }
 
+    /// Cross-encoder reranking of the top `cross_encoder_top_k` results.
+    /// The model's relevance takes the place of the heuristic rerank score;
+    /// results past the top k follow in their original order.
+    fn cross_encoder_rerank(
+        &self,
+        cross_encoder: &dyn CrossEncoder,
+        mut results: Vec<SearchResult>,
+        query: &str,
+        query_entities: Option<&[String]>,
+    ) -> Vec<RerankResult> {
+        let top_k = self.config.cross_encoder_top_k.min(results.len());
+        let documents: Vec<&str> = results[..top_k]
+            .iter()
+            .map(|r| r.memory.content.as_str())
+            .collect();
+        let scores = match cross_encoder.score(query, &documents) {
+            Ok(scores) if scores.len() == top_k => scores,
+            Ok(scores) => {
+                tracing::warn!(
+                    "Cross-encoder {} returned {} scores for {} documents; using heuristic reranking",
+                    cross_encoder.model_name(),
+                    scores.len(),
+                    top_k
+                );
+                return self.heuristic_rerank(results, query, query_entities);
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Cross-encoder {} failed, using heuristic reranking: {}",
+                    cross_encoder.model_name(),
+                    e
+                );
+                return self.heuristic_rerank(results, query, query_entities);
+            }
+        };
+
+        let query_terms = extract_terms(query);
+        let query_lower = query.to_lowercase();
+        let rest = results.split_off(top_k);
+        let mut reranked: Vec<RerankResult> = results
+            .into_iter()
+            .zip(scores)
+            .enumerate()
+            .map(|(i, (r, score))| {
+                let mut components = self.compute_rerank_components(
+                    &r.memory,
+                    &query_terms,
+                    &query_lower,
+                    query_entities,
+                );
+                components.cross_encoder = score;
+                RerankResult {
+                    rerank_info: RerankInfo {
+                        original_score: r.score,
+                        final_score: self.config.original_score_weight * r.score
+                            + self.config.rerank_score_weight * score,
+                        rerank_score: score,
+                        components,
+                    },
+                    result: r,
+                    original_rank: i + 1,
+                    new_rank: 0,
+                }
+            })
+            .collect();
+        reranked.sort_by(|a, b| {
+            b.rerank_info
+                .final_score
+                .total_cmp(&a.rerank_info.final_score)
+        });
+
+        reranked.extend(rest.into_iter().enumerate().map(|(i, r)| RerankResult {
+            rerank_info: RerankInfo {
+                original_score: r.score,
+                final_score: self.config.original_score_weight * r.score,
+                rerank_score: 0.0,
+                components: RerankComponents::default(),
+            },
+            result: r,
+            original_rank: top_k + i + 1,
+            new_rank: 0,
+        }));
+        for (i, result) in reranked.iter_mut().enumerate() {
+            result.new_rank = i + 1;
+        }
+        reranked
+    }
+
     /// Multi-signal reranking using RRF across multiple signals
     fn multi_signal_rerank(
         &self,
@@ -366,6 +594,7 @@ impl Reranker {
             },
             type_relevance: self.compute_type_relevance(memory),
             tag_match: self.compute_tag_match_score(memory, query_terms),
+            cross_encoder: 0.0,
         }
     }
 
@@ -532,6 +761,7 @@ mod tests {
                 highlights: vec![],
                 semantic_score: None,
                 keyword_score: Some(score),
+                boosts: Vec::new(),
             },
         }
     }
@@ -681,4 +911,62 @@ mod tests {
             assert!(r.rerank_info.final_score > 0.0);
         }
     }
+
+    /// Scores documents by whether they mention "rollback"; fails on "boom"
+    struct KeywordCrossEncoder;
+
+    impl CrossEncoder for KeywordCrossEncoder {
+        fn score(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+            if query == "boom" {
+                return Err(EngramError::Internal("model unavailable".to_string()));
+            }
+            Ok(documents
+                .iter()
+                .map(|d| if d.contains("rollback") { 0.95 } else { 0.05 })
+                .collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "keyword"
+        }
+    }
+
+    #[test]
+    fn test_cross_encoder_rerank_top_k() {
+        let config = RerankConfig {
+            strategy: RerankStrategy::CrossEncoder,
+            cross_encoder_top_k: 3,
+            ..Default::default()
+        };
+        let reranker =
+            Reranker::with_config(config).with_cross_encoder(Some(Arc::new(KeywordCrossEncoder)));
+        let results = || {
+            vec![
+                create_test_result(create_test_memory("Deploy steps", 0.5), 0.9),
+                create_test_result(create_test_memory("Database notes", 0.5), 0.8),
+                create_test_result(create_test_memory("How to rollback", 0.5), 0.7),
+                create_test_result(create_test_memory("Old rollback plan", 0.5), 0.6),
+            ]
+        };
+
+        let reranked = reranker.rerank(results(), "undo a release", None);
+        assert_eq!(reranked.len(), 4);
+        assert_eq!(reranked[0].result.memory.content, "How to rollback");
+        assert_eq!(reranked[0].original_rank, 3);
+        assert!((reranked[0].rerank_info.components.cross_encoder - 0.95).abs() < 1e-6);
+        // Past the top k: not scored, kept last
+        assert_eq!(reranked[3].result.memory.content, "Old rollback plan");
+        assert_eq!(reranked[3].rerank_info.rerank_score, 0.0);
+        assert_eq!(
+            reranked.iter().map(|r| r.new_rank).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+
+        // A failing model falls back to the heuristic
+        let fallback = reranker.rerank(results(), "boom", None);
+        assert_eq!(fallback.len(), 4);
+        assert!(fallback
+            .iter()
+            .all(|r| r.rerank_info.components.cross_encoder == 0.0));
+    }
 }
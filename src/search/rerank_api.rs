@@ -0,0 +1,210 @@
+//! Hosted cross-encoder rerank APIs
+//!
+//! [`ApiCrossEncoder`] sends a query and documents to a rerank endpoint and
+//! reads back one relevance score per document:
+//! - Cohere (`/v1/rerank`) and Jina AI (same request and response shape)
+//! - Voyage AI (`/v1/rerank`, results under `data`)
+//! - a Hugging Face text-embeddings-inference server (`/rerank`, documents
+//!   under `texts`), for self-hosted models such as `bge-reranker-base`
+//!
+//! Feature-gated behind `api-rerank`.
+
+#![cfg(feature = "api-rerank")]
+
+use serde_json::{json, Value};
+
+use super::rerank::{CrossEncoder, RerankApiProvider};
+use crate::embedding::block_on;
+use crate::error::{EngramError, Result};
+
+impl RerankApiProvider {
+    fn default_base_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Cohere => Some("https://api.cohere.ai/v1"),
+            Self::Jina => Some("https://api.jina.ai/v1"),
+            Self::Voyage => Some("https://api.voyageai.com/v1"),
+            Self::Tei => None,
+        }
+    }
+
+    fn default_model(&self) -> &'static str {
+        match self {
+            Self::Cohere => "rerank-english-v3.0",
+            Self::Jina => "jina-reranker-v2-base-multilingual",
+            Self::Voyage => "rerank-2",
+            Self::Tei => "tei",
+        }
+    }
+}
+
+/// Cross-encoder scores from a hosted rerank endpoint
+pub struct ApiCrossEncoder {
+    provider: RerankApiProvider,
+    model: String,
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl ApiCrossEncoder {
+    /// Client for `provider`, with its default model and base URL unless
+    /// given. TEI servers have no default URL; hosted providers need a key.
+    pub fn new(
+        provider: RerankApiProvider,
+        model: Option<String>,
+        base_url: Option<String>,
+        api_key: Option<String>,
+    ) -> Result<Self> {
+        let base_url = base_url
+            .or_else(|| provider.default_base_url().map(String::from))
+            .ok_or_else(|| EngramError::Config("A TEI reranker needs a base URL".to_string()))?;
+        if api_key.is_none() && provider != RerankApiProvider::Tei {
+            return Err(EngramError::Config(format!(
+                "The {:?} reranker needs an API key",
+                provider
+            )));
+        }
+        Ok(Self {
+            provider,
+            model: model.unwrap_or_else(|| provider.default_model().to_string()),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn request_body(&self, query: &str, documents: &[&str]) -> Value {
+        match self.provider {
+            RerankApiProvider::Tei => json!({"query": query, "texts": documents}),
+            _ => json!({
+                "model": self.model,
+                "query": query,
+                "documents": documents,
+                "top_n": documents.len(),
+            }),
+        }
+    }
+
+    /// Async call to the `/rerank` endpoint
+    pub async fn score_async(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut request = self
+            .client
+            .post(format!("{}/rerank", self.base_url))
+            .json(&self.request_body(query, documents));
+        if let Some(ref key) = self.api_key {
+            request = request.header("Authorization", format!("Bearer {key}"));
+        }
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(EngramError::Search(format!(
+                "Rerank API error {status}: {body}"
+            )));
+        }
+        parse_scores(&response.json().await?, documents.len())
+    }
+}
+
+/// Scores in document order from `[{index, relevance_score | score}]`,
+/// either at the top level or under `results` / `data`
+fn parse_scores(response: &Value, documents: usize) -> Result<Vec<f32>> {
+    let entries = response
+        .get("results")
+        .or_else(|| response.get("data"))
+        .unwrap_or(response)
+        .as_array()
+        .ok_or_else(|| EngramError::Search("Rerank response has no results".to_string()))?;
+
+    let mut scores = vec![None; documents];
+    for entry in entries {
+        let index = entry["index"].as_u64().map(|i| i as usize);
+        let score = entry
+            .get("relevance_score")
+            .or_else(|| entry.get("score"))
+            .and_then(Value::as_f64);
+        if let (Some(index), Some(score)) = (index, score) {
+            if let Some(slot) = scores.get_mut(index) {
+                *slot = Some(score as f32);
+            }
+        }
+    }
+    scores
+        .into_iter()
+        .enumerate()
+        .map(|(i, score)| {
+            score.ok_or_else(|| {
+                EngramError::Search(format!("Rerank response has no score for document {i}"))
+            })
+        })
+        .collect()
+}
+
+impl CrossEncoder for ApiCrossEncoder {
+    fn score(&self, query: &str, documents: &[&str]) -> Result<Vec<f32>> {
+        block_on(self.score_async(query, documents))
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scores_across_providers() {
+        // Cohere / Jina: sorted by relevance, under `results`
+        let cohere = json!({"results": [
+            {"index": 1, "relevance_score": 0.9},
+            {"index": 0, "relevance_score": 0.2}
+        ]});
+        assert_eq!(parse_scores(&cohere, 2).unwrap(), vec![0.2, 0.9]);
+
+        // Voyage: under `data`
+        let voyage = json!({"data": [{"index": 0, "relevance_score": 0.5}]});
+        assert_eq!(parse_scores(&voyage, 1).unwrap(), vec![0.5]);
+
+        // TEI: a bare array with `score`
+        let tei = json!([{"index": 0, "score": 0.7}, {"index": 1, "score": 0.1}]);
+        assert_eq!(parse_scores(&tei, 2).unwrap(), vec![0.7, 0.1]);
+
+        assert!(parse_scores(&tei, 3).is_err());
+        assert!(parse_scores(&json!({"error": "bad key"}), 1).is_err());
+    }
+
+    #[test]
+    fn test_request_bodies() {
+        let tei = ApiCrossEncoder::new(
+            RerankApiProvider::Tei,
+            None,
+            Some("http://localhost:8080/".to_string()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(tei.base_url, "http://localhost:8080");
+        assert_eq!(
+            tei.request_body("q", &["a"]),
+            json!({"query": "q", "texts": ["a"]})
+        );
+
+        let cohere = ApiCrossEncoder::new(
+            RerankApiProvider::Cohere,
+            None,
+            None,
+            Some("key".to_string()),
+        )
+        .unwrap();
+        assert_eq!(cohere.request_body("q", &["a", "b"])["top_n"], 2);
+        assert_eq!(cohere.model_name(), "rerank-english-v3.0");
+
+        assert!(ApiCrossEncoder::new(RerankApiProvider::Tei, None, None, None).is_err());
+        assert!(ApiCrossEncoder::new(RerankApiProvider::Voyage, None, None, None).is_err());
+    }
+}
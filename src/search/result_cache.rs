@@ -6,7 +6,7 @@
 //! - TTL-based expiration
 //! - Cache invalidation on memory changes
 
-use crate::types::{MemoryType, SearchResult};
+use crate::types::{MemoryType, SearchResult, ValidationStatus};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
@@ -23,6 +23,9 @@ pub struct CacheFilterParams {
     pub include_archived: bool,
     pub include_transcripts: bool,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub show_superseded: bool,
+    pub validation_status: Option<ValidationStatus>,
 }
 
 /// A cached search result entry
@@ -447,6 +450,7 @@ mod tests {
                 highlights: vec![],
                 semantic_score: None,
                 keyword_score: Some(score),
+                boosts: Vec::new(),
             },
         }
     }
@@ -0,0 +1,697 @@
+//! Adaptive search strategy learning
+//!
+//! [`select_search_strategy`](super::select_search_strategy) routes queries by
+//! fixed word-count cut-offs. This module learns those cut-offs per
+//! workspace from outcomes: every search logs the strategy that served it
+//! together with the query class (word count, whether it names entities),
+//! agents report when one of its results was actually used, and the
+//! keyword/semantic thresholds are refit to maximize the share of searches
+//! whose results get used.
+//!
+//! Without variation every word count would only ever see one strategy, so a
+//! small, query-hash-deterministic share of searches explores the
+//! neighbouring strategy.
+
+use chrono::{Duration, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use super::experiments::{query_bucket, query_hash};
+use super::{has_search_syntax, SearchConfig};
+use crate::error::Result;
+use crate::types::SearchStrategy;
+
+// ---------------------------------------------------------------------------
+// DDL
+// ---------------------------------------------------------------------------
+
+/// SQL for creating the strategy log and learned thresholds tables.
+/// Safe to call on an existing database — uses `CREATE TABLE IF NOT EXISTS`.
+pub const CREATE_STRATEGY_LEARNING_TABLES: &str = r#"
+CREATE TABLE IF NOT EXISTS search_strategy_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    workspace TEXT NOT NULL,
+    query_hash TEXT NOT NULL,
+    word_count INTEGER NOT NULL,
+    has_entities INTEGER NOT NULL,
+    strategy TEXT NOT NULL,
+    explored INTEGER NOT NULL DEFAULT 0,
+    used_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+CREATE INDEX IF NOT EXISTS idx_strategy_log_query ON search_strategy_log(workspace, query_hash);
+CREATE INDEX IF NOT EXISTS idx_strategy_log_class ON search_strategy_log(workspace, has_entities, created_at);
+CREATE TABLE IF NOT EXISTS search_strategy_thresholds (
+    workspace TEXT NOT NULL,
+    has_entities INTEGER NOT NULL,
+    short_threshold INTEGER NOT NULL,
+    long_threshold INTEGER NOT NULL,
+    samples INTEGER NOT NULL,
+    used_rate REAL NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (workspace, has_entities)
+);
+"#;
+
+/// Word counts above this share one bucket.
+pub const MAX_WORD_BUCKET: usize = 12;
+
+/// Searches a query class needs before its thresholds are refit.
+pub const MIN_LEARNING_SAMPLES: i64 = 30;
+
+/// Share of searches routed to a neighbouring strategy (default 5%).
+pub const DEFAULT_EXPLORATION_RATE: f64 = 0.05;
+
+/// Only searches from this many recent days inform the fit.
+const LEARNING_WINDOW_DAYS: i64 = 90;
+
+/// Pseudo-count pulling sparse (word count, strategy) cells toward the
+/// class-wide used rate.
+const PRIOR_WEIGHT: f64 = 2.0;
+
+/// Salt separating exploration buckets from experiment arm assignment.
+const EXPLORATION_SALT: i64 = -1;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What the learner conditions on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryClass {
+    pub workspace: String,
+    /// Word count, capped at [`MAX_WORD_BUCKET`].
+    pub word_count: usize,
+    pub has_entities: bool,
+}
+
+impl QueryClass {
+    pub fn of(query: &str, workspace: &str) -> Self {
+        Self {
+            workspace: workspace.to_string(),
+            word_count: query.split_whitespace().count().min(MAX_WORD_BUCKET),
+            has_entities: has_entities(query),
+        }
+    }
+}
+
+/// Whether the query names something specific: a capitalized word after
+/// the first, or a token with digits, `_`, `::` or an inner `.` (versions,
+/// identifiers, paths).
+pub fn has_entities(query: &str) -> bool {
+    query.split_whitespace().enumerate().any(|(i, word)| {
+        let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+        (i > 0 && word.chars().next().is_some_and(char::is_uppercase))
+            || word.chars().any(|c| c.is_ascii_digit())
+            || word.contains('_')
+            || word.contains("::")
+            || word.contains('.')
+    })
+}
+
+/// Word-count cut-offs between keyword, hybrid and semantic search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StrategyThresholds {
+    /// At most this many words → keyword only.
+    pub short_threshold: usize,
+    /// At least this many words → semantic only.
+    pub long_threshold: usize,
+}
+
+impl StrategyThresholds {
+    pub fn from_config(config: &SearchConfig) -> Self {
+        Self {
+            short_threshold: config.short_threshold,
+            long_threshold: config.long_threshold,
+        }
+    }
+
+    pub fn select(&self, word_count: usize) -> SearchStrategy {
+        if word_count <= self.short_threshold {
+            SearchStrategy::KeywordOnly
+        } else if word_count >= self.long_threshold {
+            SearchStrategy::SemanticOnly
+        } else {
+            SearchStrategy::Hybrid
+        }
+    }
+}
+
+/// Where the thresholds behind a decision came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdSource {
+    /// The server's [`SearchConfig`].
+    Default,
+    /// Refit from this workspace's outcomes.
+    Learned,
+}
+
+/// Thresholds learned for one workspace and entity class.
+#[derive(Debug, Clone, Serialize)]
+pub struct LearnedThresholds {
+    pub workspace: String,
+    pub has_entities: bool,
+    pub thresholds: StrategyThresholds,
+    pub samples: i64,
+    /// Share of logged searches with a used result.
+    pub used_rate: f64,
+    pub updated_at: String,
+}
+
+/// The strategy chosen for a query and why — the `strategy_selection` field
+/// of `memory_search` explanations.
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyDecision {
+    pub strategy: SearchStrategy,
+    pub class: QueryClass,
+    pub thresholds: StrategyThresholds,
+    pub source: ThresholdSource,
+    /// Searches behind learned thresholds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samples: Option<i64>,
+    /// The strategy was swapped for a neighbour to keep collecting evidence.
+    pub explored: bool,
+    pub reason: String,
+}
+
+/// Served and used counts for one (word count, strategy) cell.
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyCell {
+    pub word_count: usize,
+    pub has_entities: bool,
+    pub strategy: SearchStrategy,
+    pub served: i64,
+    pub used: i64,
+}
+
+/// Everything the learner knows about a workspace.
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyStats {
+    pub workspace: String,
+    pub cells: Vec<StrategyCell>,
+    pub learned: Vec<LearnedThresholds>,
+}
+
+// ---------------------------------------------------------------------------
+// Selection
+// ---------------------------------------------------------------------------
+
+/// Exploration rate from `ENGRAM_STRATEGY_EXPLORATION` (0 disables).
+pub fn exploration_rate() -> f64 {
+    std::env::var("ENGRAM_STRATEGY_EXPLORATION")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .map(|v| v.clamp(0.0, 1.0))
+        .unwrap_or(DEFAULT_EXPLORATION_RATE)
+}
+
+/// Pick a strategy for `query`, preferring thresholds learned for its
+/// workspace and entity class over `config`'s.
+pub fn choose_strategy(
+    conn: &Connection,
+    query: &str,
+    workspace: Option<&str>,
+    config: &SearchConfig,
+    exploration: f64,
+) -> Result<StrategyDecision> {
+    let class = QueryClass::of(query, workspace.unwrap_or("default"));
+    let learned = get_learned_thresholds(conn, &class.workspace, class.has_entities)?;
+    let (thresholds, source, samples) = match &learned {
+        Some(l) => (l.thresholds, ThresholdSource::Learned, Some(l.samples)),
+        None => (
+            StrategyThresholds::from_config(config),
+            ThresholdSource::Default,
+            None,
+        ),
+    };
+
+    if has_search_syntax(query) {
+        return Ok(StrategyDecision {
+            strategy: SearchStrategy::KeywordOnly,
+            class,
+            thresholds,
+            source,
+            samples,
+            explored: false,
+            reason: "query uses quotes, operators or wildcards, which only keyword search supports"
+                .to_string(),
+        });
+    }
+
+    let planned = thresholds.select(class.word_count);
+    let explored = exploration > 0.0 && query_bucket(EXPLORATION_SALT, query) < exploration;
+    let strategy = if explored {
+        neighbour(planned, class.word_count, &thresholds)
+    } else {
+        planned
+    };
+
+    let origin = match source {
+        ThresholdSource::Default => "default thresholds".to_string(),
+        ThresholdSource::Learned => format!(
+            "thresholds learned for workspace '{}' from {} searches",
+            class.workspace,
+            samples.unwrap_or(0)
+        ),
+    };
+    let words = if class.word_count >= MAX_WORD_BUCKET {
+        format!("{}+ words", MAX_WORD_BUCKET)
+    } else {
+        format!("{} words", class.word_count)
+    };
+    let mut reason = format!(
+        "{} with keyword at <= {} and semantic at >= {} words ({}) selects {}",
+        words,
+        thresholds.short_threshold,
+        thresholds.long_threshold,
+        origin,
+        strategy_name(planned)
+    );
+    if explored {
+        reason.push_str(&format!(
+            "; exploring {} to keep measuring alternatives",
+            strategy_name(strategy)
+        ));
+    }
+
+    Ok(StrategyDecision {
+        strategy,
+        class,
+        thresholds,
+        source,
+        samples,
+        explored,
+        reason,
+    })
+}
+
+/// The strategy one step away from `planned`; hybrid leans toward whichever
+/// end the query is closer to.
+fn neighbour(
+    planned: SearchStrategy,
+    word_count: usize,
+    thresholds: &StrategyThresholds,
+) -> SearchStrategy {
+    match planned {
+        SearchStrategy::KeywordOnly | SearchStrategy::SemanticOnly => SearchStrategy::Hybrid,
+        SearchStrategy::Hybrid => {
+            if word_count - thresholds.short_threshold <= thresholds.long_threshold - word_count {
+                SearchStrategy::KeywordOnly
+            } else {
+                SearchStrategy::SemanticOnly
+            }
+        }
+    }
+}
+
+fn strategy_name(strategy: SearchStrategy) -> &'static str {
+    match strategy {
+        SearchStrategy::KeywordOnly => "keyword",
+        SearchStrategy::SemanticOnly => "semantic",
+        SearchStrategy::Hybrid => "hybrid",
+    }
+}
+
+fn parse_strategy(s: &str) -> Option<SearchStrategy> {
+    match s {
+        "keyword" => Some(SearchStrategy::KeywordOnly),
+        "semantic" => Some(SearchStrategy::SemanticOnly),
+        "hybrid" => Some(SearchStrategy::Hybrid),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Storage functions
+// ---------------------------------------------------------------------------
+
+/// Log that `decision` served `query`.
+pub fn record_served(conn: &Connection, query: &str, decision: &StrategyDecision) -> Result<()> {
+    conn.execute(
+        "INSERT INTO search_strategy_log
+            (workspace, query_hash, word_count, has_entities, strategy, explored)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            decision.class.workspace,
+            query_hash(query),
+            decision.class.word_count as i64,
+            decision.class.has_entities,
+            strategy_name(decision.strategy),
+            decision.explored,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Mark the latest search for `query` in `workspace` as having produced a
+/// used result, then refit that class's thresholds. Returns the strategy
+/// credited, or `None` when no logged search matches.
+pub fn record_used(
+    conn: &Connection,
+    query: &str,
+    workspace: &str,
+) -> Result<Option<SearchStrategy>> {
+    let row: Option<(i64, bool, String)> = conn
+        .query_row(
+            "SELECT id, has_entities, strategy FROM search_strategy_log
+             WHERE workspace = ? AND query_hash = ?
+             ORDER BY id DESC LIMIT 1",
+            params![workspace, query_hash(query)],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        )
+        .optional()?;
+    let Some((id, has_entities, strategy)) = row else {
+        return Ok(None);
+    };
+
+    conn.execute(
+        "UPDATE search_strategy_log
+         SET used_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+         WHERE id = ? AND used_at IS NULL",
+        params![id],
+    )?;
+    refit_thresholds(
+        conn,
+        workspace,
+        has_entities,
+        &SearchConfig::default(),
+        MIN_LEARNING_SAMPLES,
+    )?;
+    Ok(parse_strategy(&strategy))
+}
+
+/// Refit the thresholds of one workspace and entity class from the recent
+/// log, pruning entries older than the learning window. Classes with fewer
+/// than `min_samples` searches revert to the defaults.
+pub fn refit_thresholds(
+    conn: &Connection,
+    workspace: &str,
+    has_entities: bool,
+    defaults: &SearchConfig,
+    min_samples: i64,
+) -> Result<Option<LearnedThresholds>> {
+    let cutoff = (Utc::now() - Duration::days(LEARNING_WINDOW_DAYS))
+        .to_rfc3339_opts(SecondsFormat::Millis, true);
+    conn.execute(
+        "DELETE FROM search_strategy_log WHERE workspace = ? AND created_at < ?",
+        params![workspace, cutoff],
+    )?;
+
+    let cells: Vec<StrategyCell> = load_cells(conn, workspace)?
+        .into_iter()
+        .filter(|c| c.has_entities == has_entities)
+        .collect();
+    let samples: i64 = cells.iter().map(|c| c.served).sum();
+    if samples < min_samples {
+        conn.execute(
+            "DELETE FROM search_strategy_thresholds WHERE workspace = ? AND has_entities = ?",
+            params![workspace, has_entities],
+        )?;
+        return Ok(None);
+    }
+
+    let (thresholds, _) = fit_thresholds(&cells, StrategyThresholds::from_config(defaults));
+    let used: i64 = cells.iter().map(|c| c.used).sum();
+    let used_rate = used as f64 / samples as f64;
+    conn.execute(
+        "INSERT INTO search_strategy_thresholds
+            (workspace, has_entities, short_threshold, long_threshold, samples, used_rate, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+         ON CONFLICT(workspace, has_entities) DO UPDATE SET
+            short_threshold = excluded.short_threshold,
+            long_threshold = excluded.long_threshold,
+            samples = excluded.samples,
+            used_rate = excluded.used_rate,
+            updated_at = excluded.updated_at",
+        params![
+            workspace,
+            has_entities,
+            thresholds.short_threshold as i64,
+            thresholds.long_threshold as i64,
+            samples,
+            used_rate,
+        ],
+    )?;
+    get_learned_thresholds(conn, workspace, has_entities)
+}
+
+/// Thresholds maximizing the expected number of searches with a used
+/// result, and that expectation. Each (word count, strategy) cell's used rate
+/// is smoothed toward the class-wide rate, so sparse cells barely move the
+/// fit; ties keep `defaults`.
+pub fn fit_thresholds(
+    cells: &[StrategyCell],
+    defaults: StrategyThresholds,
+) -> (StrategyThresholds, f64) {
+    let served: i64 = cells.iter().map(|c| c.served).sum();
+    let used: i64 = cells.iter().map(|c| c.used).sum();
+    let prior = if served > 0 {
+        used as f64 / served as f64
+    } else {
+        0.0
+    };
+
+    // volume[w] and rate[w][strategy] over word-count buckets 0..=MAX.
+    let mut volume = [0i64; MAX_WORD_BUCKET + 1];
+    let mut counts = [[(0i64, 0i64); 3]; MAX_WORD_BUCKET + 1];
+    for cell in cells {
+        let w = cell.word_count.min(MAX_WORD_BUCKET);
+        volume[w] += cell.served;
+        let slot = &mut counts[w][strategy_slot(cell.strategy)];
+        slot.0 += cell.served;
+        slot.1 += cell.used;
+    }
+    let rate = |w: usize, strategy: SearchStrategy| {
+        let (served, used) = counts[w][strategy_slot(strategy)];
+        (used as f64 + PRIOR_WEIGHT * prior) / (served as f64 + PRIOR_WEIGHT)
+    };
+    let score = |t: &StrategyThresholds| -> f64 {
+        (0..=MAX_WORD_BUCKET)
+            .map(|w| volume[w] as f64 * rate(w, t.select(w)))
+            .sum()
+    };
+
+    let mut best = (defaults, score(&defaults));
+    for short_threshold in 0..=MAX_WORD_BUCKET {
+        for long_threshold in short_threshold + 1..=MAX_WORD_BUCKET + 1 {
+            let candidate = StrategyThresholds {
+                short_threshold,
+                long_threshold,
+            };
+            let candidate_score = score(&candidate);
+            if candidate_score > best.1 + 1e-9 {
+                best = (candidate, candidate_score);
+            }
+        }
+    }
+    best
+}
+
+fn strategy_slot(strategy: SearchStrategy) -> usize {
+    match strategy {
+        SearchStrategy::KeywordOnly => 0,
+        SearchStrategy::Hybrid => 1,
+        SearchStrategy::SemanticOnly => 2,
+    }
+}
+
+/// Thresholds currently learned for a workspace and entity class.
+pub fn get_learned_thresholds(
+    conn: &Connection,
+    workspace: &str,
+    has_entities: bool,
+) -> Result<Option<LearnedThresholds>> {
+    conn.query_row(
+        "SELECT workspace, has_entities, short_threshold, long_threshold, samples, used_rate, updated_at
+         FROM search_strategy_thresholds WHERE workspace = ? AND has_entities = ?",
+        params![workspace, has_entities],
+        row_to_learned,
+    )
+    .optional()
+    .map_err(Into::into)
+}
+
+/// Per-cell counts and learned thresholds for a workspace.
+pub fn strategy_stats(conn: &Connection, workspace: &str) -> Result<StrategyStats> {
+    let cells = load_cells(conn, workspace)?;
+    let mut stmt = conn.prepare(
+        "SELECT workspace, has_entities, short_threshold, long_threshold, samples, used_rate, updated_at
+         FROM search_strategy_thresholds WHERE workspace = ? ORDER BY has_entities",
+    )?;
+    let learned = stmt
+        .query_map(params![workspace], row_to_learned)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(StrategyStats {
+        workspace: workspace.to_string(),
+        cells,
+        learned,
+    })
+}
+
+fn load_cells(conn: &Connection, workspace: &str) -> Result<Vec<StrategyCell>> {
+    let mut stmt = conn.prepare(
+        "SELECT word_count, has_entities, strategy, COUNT(*), COUNT(used_at)
+         FROM search_strategy_log
+         WHERE workspace = ?
+         GROUP BY word_count, has_entities, strategy
+         ORDER BY has_entities, word_count, strategy",
+    )?;
+    let rows = stmt.query_map(params![workspace], |r| {
+        Ok((
+            r.get::<_, i64>(0)?,
+            r.get::<_, bool>(1)?,
+            r.get::<_, String>(2)?,
+            r.get::<_, i64>(3)?,
+            r.get::<_, i64>(4)?,
+        ))
+    })?;
+
+    let mut cells = Vec::new();
+    for row in rows {
+        let (word_count, has_entities, strategy, served, used) = row?;
+        if let Some(strategy) = parse_strategy(&strategy) {
+            cells.push(StrategyCell {
+                word_count: word_count.max(0) as usize,
+                has_entities,
+                strategy,
+                served,
+                used,
+            });
+        }
+    }
+    Ok(cells)
+}
+
+fn row_to_learned(r: &rusqlite::Row<'_>) -> rusqlite::Result<LearnedThresholds> {
+    Ok(LearnedThresholds {
+        workspace: r.get(0)?,
+        has_entities: r.get(1)?,
+        thresholds: StrategyThresholds {
+            short_threshold: r.get::<_, i64>(2)?.max(0) as usize,
+            long_threshold: r.get::<_, i64>(3)?.max(0) as usize,
+        },
+        samples: r.get(4)?,
+        used_rate: r.get(5)?,
+        updated_at: r.get(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CREATE_STRATEGY_LEARNING_TABLES).unwrap();
+        conn
+    }
+
+    fn cell(word_count: usize, strategy: SearchStrategy, served: i64, used: i64) -> StrategyCell {
+        StrategyCell {
+            word_count,
+            has_entities: false,
+            strategy,
+            served,
+            used,
+        }
+    }
+
+    #[test]
+    fn test_query_class_and_entities() {
+        let class = QueryClass::of("how does Postgres handle vacuum", "ops");
+        assert_eq!(class.word_count, 5);
+        assert!(class.has_entities);
+        assert!(has_entities("upgrade to v2.3"));
+        assert!(has_entities("where is parse_filter defined"));
+        assert!(!has_entities("How does authentication work"));
+        assert_eq!(QueryClass::of(&"word ".repeat(30), "ops").word_count, 12);
+    }
+
+    #[test]
+    fn test_fit_moves_threshold_toward_used_strategy() {
+        let defaults = StrategyThresholds::from_config(&SearchConfig::default());
+        // Three-word queries land better on keyword than on hybrid.
+        let cells = vec![
+            cell(3, SearchStrategy::Hybrid, 100, 20),
+            cell(3, SearchStrategy::KeywordOnly, 20, 15),
+            cell(5, SearchStrategy::Hybrid, 100, 60),
+            cell(5, SearchStrategy::KeywordOnly, 20, 2),
+        ];
+        let (fitted, _) = fit_thresholds(&cells, defaults);
+        assert_eq!(fitted.short_threshold, 3);
+        assert!(fitted.long_threshold > 5);
+
+        // No evidence either way keeps the defaults.
+        assert_eq!(fit_thresholds(&[], defaults).0, defaults);
+    }
+
+    #[test]
+    fn test_outcomes_refit_workspace_thresholds() {
+        let conn = setup();
+        let config = SearchConfig::default();
+        let query = "rotate the signing keys";
+
+        let decision = choose_strategy(&conn, query, Some("ops"), &config, 0.0).unwrap();
+        assert_eq!(decision.source, ThresholdSource::Default);
+        assert_eq!(decision.strategy, SearchStrategy::Hybrid);
+        assert!(decision.reason.contains("default thresholds"));
+        assert!(record_used(&conn, query, "ops").unwrap().is_none());
+
+        // Four-word searches served by keyword keep getting used; hybrid ones
+        // are ignored.
+        for i in 0..40 {
+            let strategy = if i % 2 == 0 {
+                SearchStrategy::KeywordOnly
+            } else {
+                SearchStrategy::Hybrid
+            };
+            let query = format!("rotate signing keys {}", i);
+            let decision = StrategyDecision {
+                strategy,
+                explored: false,
+                ..choose_strategy(&conn, &query, Some("ops"), &config, 0.0).unwrap()
+            };
+            record_served(&conn, &query, &decision).unwrap();
+            if strategy == SearchStrategy::KeywordOnly {
+                assert_eq!(
+                    record_used(&conn, &query, "ops").unwrap(),
+                    Some(SearchStrategy::KeywordOnly)
+                );
+            }
+        }
+
+        let learned = get_learned_thresholds(&conn, "ops", true).unwrap().unwrap();
+        assert!(learned.samples >= MIN_LEARNING_SAMPLES);
+        assert!(learned.thresholds.short_threshold >= 4);
+
+        let decision =
+            choose_strategy(&conn, "rotate signing keys 99", Some("ops"), &config, 0.0).unwrap();
+        assert_eq!(decision.source, ThresholdSource::Learned);
+        assert_eq!(decision.strategy, SearchStrategy::KeywordOnly);
+
+        // Other workspaces are unaffected.
+        let other = choose_strategy(&conn, "rotate signing keys 99", None, &config, 0.0).unwrap();
+        assert_eq!(other.source, ThresholdSource::Default);
+
+        let stats = strategy_stats(&conn, "ops").unwrap();
+        assert_eq!(stats.learned.len(), 1);
+        assert_eq!(stats.cells.iter().map(|c| c.used).sum::<i64>(), 20);
+    }
+
+    #[test]
+    fn test_exploration_and_syntax_override() {
+        let conn = setup();
+        let config = SearchConfig::default();
+
+        let decision = choose_strategy(&conn, "auth", None, &config, 1.0).unwrap();
+        assert!(decision.explored);
+        assert_eq!(decision.strategy, SearchStrategy::Hybrid);
+        assert!(decision.reason.contains("exploring hybrid"));
+
+        let decision =
+            choose_strategy(&conn, "\"exact phrase\" lookup", None, &config, 1.0).unwrap();
+        assert!(!decision.explored);
+        assert_eq!(decision.strategy, SearchStrategy::KeywordOnly);
+    }
+}
@@ -0,0 +1,1169 @@
+//! Pluggable approximate nearest-neighbour indexes over memory embeddings
+//!
+//! `VectorIndex` abstracts the structure answering top-k similarity queries
+//! so backends can be swapped through configuration:
+//! - `flat`: exact brute-force scan, also the baseline for recall estimates
+//! - `hnsw`: hierarchical navigable small-world graph
+//! - `ivf_pq`: inverted file over product-quantized codes, trading some
+//!   accuracy for a much smaller footprint
+//!
+//! The backend is selected with `ENGRAM_VECTOR_INDEX` and rebuilt from the
+//! `embeddings` table on demand (`memory_vector_index_rebuild`).
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::str::FromStr;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::types::MemoryId;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Available index backends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorIndexKind {
+    /// Exact linear scan.
+    Flat,
+    /// Hierarchical navigable small-world graph.
+    #[default]
+    Hnsw,
+    /// Inverted file with product quantization.
+    IvfPq,
+}
+
+impl VectorIndexKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VectorIndexKind::Flat => "flat",
+            VectorIndexKind::Hnsw => "hnsw",
+            VectorIndexKind::IvfPq => "ivf_pq",
+        }
+    }
+}
+
+impl FromStr for VectorIndexKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "flat" | "exact" => Ok(VectorIndexKind::Flat),
+            "hnsw" => Ok(VectorIndexKind::Hnsw),
+            "ivf_pq" | "ivf-pq" | "ivfpq" => Ok(VectorIndexKind::IvfPq),
+            other => Err(format!(
+                "Unknown vector index backend: {} (expected flat, hnsw or ivf_pq)",
+                other
+            )),
+        }
+    }
+}
+
+/// HNSW graph parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswParams {
+    /// Neighbours kept per node on upper layers (layer 0 keeps twice this).
+    pub m: usize,
+    /// Candidate list size while inserting.
+    pub ef_construction: usize,
+    /// Candidate list size while searching.
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+        }
+    }
+}
+
+/// IVF-PQ parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IvfPqParams {
+    /// Number of coarse clusters (inverted lists).
+    pub nlist: usize,
+    /// Inverted lists scanned per query.
+    pub nprobe: usize,
+    /// Number of sub-vectors each embedding is split into.
+    pub subspaces: usize,
+    /// Codewords per subspace (at most 256, codes are stored as bytes).
+    pub codebook_size: usize,
+    /// k-means iterations used to train both quantizers.
+    pub train_iterations: usize,
+}
+
+impl Default for IvfPqParams {
+    fn default() -> Self {
+        Self {
+            nlist: 64,
+            nprobe: 8,
+            subspaces: 8,
+            codebook_size: 256,
+            train_iterations: 10,
+        }
+    }
+}
+
+/// Backend selection and tuning for the vector index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorIndexConfig {
+    pub kind: VectorIndexKind,
+    pub hnsw: HnswParams,
+    pub ivf_pq: IvfPqParams,
+    /// Seed for level assignment, k-means initialisation and recall sampling.
+    pub seed: u64,
+    /// Number of indexed vectors replayed as queries to estimate recall.
+    pub recall_sample: usize,
+    /// Cut-off used for the recall estimate (recall@k).
+    pub recall_k: usize,
+}
+
+impl Default for VectorIndexConfig {
+    fn default() -> Self {
+        Self {
+            kind: VectorIndexKind::default(),
+            hnsw: HnswParams::default(),
+            ivf_pq: IvfPqParams::default(),
+            seed: 42,
+            recall_sample: 50,
+            recall_k: 10,
+        }
+    }
+}
+
+impl VectorIndexConfig {
+    /// Defaults overridden by `ENGRAM_VECTOR_INDEX` (`flat`, `hnsw`, `ivf_pq`).
+    /// Unknown values are ignored with a warning.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(value) = std::env::var("ENGRAM_VECTOR_INDEX") {
+            match value.parse() {
+                Ok(kind) => config.kind = kind,
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+        config
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Trait
+// ---------------------------------------------------------------------------
+
+/// A nearest-neighbour hit. `score` is cosine similarity, approximate for
+/// quantized backends.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct VectorHit {
+    pub memory_id: MemoryId,
+    pub score: f32,
+}
+
+/// A swappable nearest-neighbour index over embeddings.
+///
+/// Vectors are normalized on the way in, so every backend ranks by cosine
+/// similarity. Vectors whose dimensionality differs from the first one seen
+/// are ignored.
+pub trait VectorIndex: Send + Sync {
+    fn kind(&self) -> VectorIndexKind;
+
+    /// Replace the index contents, training any quantizers on `vectors`.
+    fn build(&mut self, vectors: &[(MemoryId, Vec<f32>)]);
+
+    /// Add or replace a single vector.
+    fn insert(&mut self, id: MemoryId, vector: &[f32]);
+
+    /// Drop a vector; returns whether it was present.
+    fn remove(&mut self, id: MemoryId) -> bool;
+
+    /// The `k` most similar vectors, best first.
+    fn search(&self, query: &[f32], k: usize) -> Vec<VectorHit>;
+
+    /// Number of live vectors.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn dimensions(&self) -> Option<usize>;
+
+    /// Approximate heap footprint of the index structures in bytes.
+    fn memory_bytes(&self) -> usize;
+}
+
+/// Instantiate the configured backend, empty.
+pub fn create_vector_index(config: &VectorIndexConfig) -> Box<dyn VectorIndex> {
+    match config.kind {
+        VectorIndexKind::Flat => Box::new(FlatIndex::new()),
+        VectorIndexKind::Hnsw => Box::new(HnswIndex::new(config.hnsw.clone(), config.seed)),
+        VectorIndexKind::IvfPq => Box::new(IvfPqIndex::new(config.ivf_pq.clone(), config.seed)),
+    }
+}
+
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|x| x / norm).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn squared_distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Best `k` hits, highest score first with ties broken by id.
+fn top_k(mut hits: Vec<VectorHit>, k: usize) -> Vec<VectorHit> {
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.memory_id.cmp(&b.memory_id))
+    });
+    hits.truncate(k);
+    hits
+}
+
+// ---------------------------------------------------------------------------
+// Flat
+// ---------------------------------------------------------------------------
+
+/// Exact index: every query scans all vectors.
+#[derive(Default)]
+pub struct FlatIndex {
+    dims: Option<usize>,
+    vectors: HashMap<MemoryId, Vec<f32>>,
+}
+
+impl FlatIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl VectorIndex for FlatIndex {
+    fn kind(&self) -> VectorIndexKind {
+        VectorIndexKind::Flat
+    }
+
+    fn build(&mut self, vectors: &[(MemoryId, Vec<f32>)]) {
+        self.dims = None;
+        self.vectors.clear();
+        for (id, vector) in vectors {
+            self.insert(*id, vector);
+        }
+    }
+
+    fn insert(&mut self, id: MemoryId, vector: &[f32]) {
+        if *self.dims.get_or_insert(vector.len()) != vector.len() {
+            return;
+        }
+        self.vectors.insert(id, normalize(vector));
+    }
+
+    fn remove(&mut self, id: MemoryId) -> bool {
+        self.vectors.remove(&id).is_some()
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Vec<VectorHit> {
+        if self.dims != Some(query.len()) {
+            return Vec::new();
+        }
+        let query = normalize(query);
+        let hits = self
+            .vectors
+            .iter()
+            .map(|(id, vector)| VectorHit {
+                memory_id: *id,
+                score: dot(&query, vector),
+            })
+            .collect();
+        top_k(hits, k)
+    }
+
+    fn len(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dims
+    }
+
+    fn memory_bytes(&self) -> usize {
+        let dims = self.dims.unwrap_or(0);
+        self.vectors.len() * (std::mem::size_of::<MemoryId>() + dims * 4)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HNSW
+// ---------------------------------------------------------------------------
+
+/// Similarity paired with a node, ordered by similarity.
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate(f32, usize);
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(other.1.cmp(&self.1))
+    }
+}
+
+/// Hierarchical navigable small-world graph. Removals are tombstones: the
+/// node keeps routing traffic but is filtered from results until the next
+/// rebuild.
+pub struct HnswIndex {
+    params: HnswParams,
+    seed: u64,
+    rng: StdRng,
+    dims: Option<usize>,
+    ids: Vec<MemoryId>,
+    vectors: Vec<Vec<f32>>,
+    /// node -> layer -> neighbour nodes
+    links: Vec<Vec<Vec<usize>>>,
+    deleted: Vec<bool>,
+    positions: HashMap<MemoryId, usize>,
+    entry: Option<usize>,
+    max_level: usize,
+}
+
+impl HnswIndex {
+    pub fn new(params: HnswParams, seed: u64) -> Self {
+        Self {
+            params,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            dims: None,
+            ids: Vec::new(),
+            vectors: Vec::new(),
+            links: Vec::new(),
+            deleted: Vec::new(),
+            positions: HashMap::new(),
+            entry: None,
+            max_level: 0,
+        }
+    }
+
+    fn max_neighbours(&self, layer: usize) -> usize {
+        let m = self.params.m.max(2);
+        if layer == 0 {
+            m * 2
+        } else {
+            m
+        }
+    }
+
+    fn random_level(&mut self) -> usize {
+        let ml = 1.0 / (self.params.m.max(2) as f64).ln();
+        let u: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        (-u.ln() * ml).floor() as usize
+    }
+
+    /// Best-first search of one layer, returning up to `ef` nodes best first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for &node in entries {
+            let c = Candidate(dot(query, &self.vectors[node]), node);
+            candidates.push(c);
+            results.push(Reverse(c));
+        }
+
+        while let Some(current) = candidates.pop() {
+            let worst = results.peek().map(|r: &Reverse<Candidate>| r.0 .0);
+            if results.len() >= ef && worst.is_some_and(|w| current.0 < w) {
+                break;
+            }
+            let Some(neighbours) = self.links[current.1].get(layer) else {
+                continue;
+            };
+            for &next in neighbours {
+                if !visited.insert(next) {
+                    continue;
+                }
+                let c = Candidate(dot(query, &self.vectors[next]), next);
+                let worst = results.peek().map(|r| r.0 .0);
+                if results.len() < ef || worst.is_some_and(|w| c.0 > w) {
+                    candidates.push(c);
+                    results.push(Reverse(c));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut found: Vec<Candidate> = results.into_iter().map(|r| r.0).collect();
+        found.sort_by(|a, b| b.cmp(a));
+        found
+    }
+
+    /// Greedy descent through the layers above `target`.
+    fn descend(&self, query: &[f32], target: usize) -> Option<usize> {
+        let mut current = self.entry?;
+        for layer in (target + 1..=self.max_level).rev() {
+            if let Some(best) = self.search_layer(query, &[current], 1, layer).first() {
+                current = best.1;
+            }
+        }
+        Some(current)
+    }
+
+    fn prune(&mut self, node: usize, layer: usize) {
+        let limit = self.max_neighbours(layer);
+        if self.links[node][layer].len() <= limit {
+            return;
+        }
+        let base = &self.vectors[node];
+        let mut scored: Vec<Candidate> = self.links[node][layer]
+            .iter()
+            .map(|&n| Candidate(dot(base, &self.vectors[n]), n))
+            .collect();
+        scored.sort_by(|a, b| b.cmp(a));
+        self.links[node][layer] = scored.into_iter().take(limit).map(|c| c.1).collect();
+    }
+}
+
+impl VectorIndex for HnswIndex {
+    fn kind(&self) -> VectorIndexKind {
+        VectorIndexKind::Hnsw
+    }
+
+    fn build(&mut self, vectors: &[(MemoryId, Vec<f32>)]) {
+        *self = Self::new(self.params.clone(), self.seed);
+        for (id, vector) in vectors {
+            self.insert(*id, vector);
+        }
+    }
+
+    fn insert(&mut self, id: MemoryId, vector: &[f32]) {
+        if *self.dims.get_or_insert(vector.len()) != vector.len() {
+            return;
+        }
+        self.remove(id);
+
+        let node = self.ids.len();
+        let level = self.random_level();
+        let vector = normalize(vector);
+        self.ids.push(id);
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.deleted.push(false);
+        self.positions.insert(id, node);
+
+        // The node is unreachable until linked, so searches below skip it.
+        let Some(mut current) = self.descend(&vector, level) else {
+            self.vectors.push(vector);
+            self.entry = Some(node);
+            self.max_level = level;
+            return;
+        };
+        self.vectors.push(vector);
+
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let query = &self.vectors[node];
+            let found = self.search_layer(query, &[current], self.params.ef_construction, layer);
+            let neighbours: Vec<usize> = found
+                .iter()
+                .take(self.max_neighbours(layer))
+                .map(|c| c.1)
+                .collect();
+            if let Some(best) = found.first() {
+                current = best.1;
+            }
+            for &neighbour in &neighbours {
+                self.links[neighbour][layer].push(node);
+                self.prune(neighbour, layer);
+            }
+            self.links[node][layer] = neighbours;
+        }
+
+        if level > self.max_level {
+            self.entry = Some(node);
+            self.max_level = level;
+        }
+    }
+
+    fn remove(&mut self, id: MemoryId) -> bool {
+        match self.positions.remove(&id) {
+            Some(node) => {
+                self.deleted[node] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Vec<VectorHit> {
+        if self.dims != Some(query.len()) || k == 0 {
+            return Vec::new();
+        }
+        let query = normalize(query);
+        let Some(entry) = self.descend(&query, 0) else {
+            return Vec::new();
+        };
+        // Widen the beam by the tombstone count so deletions don't starve k.
+        let tombstones = self.ids.len() - self.positions.len();
+        let ef = self.params.ef_search.max(k) + tombstones.min(self.params.ef_search);
+        let hits = self
+            .search_layer(&query, &[entry], ef, 0)
+            .into_iter()
+            .filter(|c| !self.deleted[c.1])
+            .map(|c| VectorHit {
+                memory_id: self.ids[c.1],
+                score: c.0,
+            })
+            .collect();
+        top_k(hits, k)
+    }
+
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dims
+    }
+
+    fn memory_bytes(&self) -> usize {
+        let dims = self.dims.unwrap_or(0);
+        let edges: usize = self
+            .links
+            .iter()
+            .flat_map(|layers| layers.iter().map(Vec::len))
+            .sum();
+        self.ids.len() * (std::mem::size_of::<MemoryId>() + dims * 4 + 1)
+            + edges * std::mem::size_of::<usize>()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// IVF-PQ
+// ---------------------------------------------------------------------------
+
+/// Inverted file over product-quantized codes. Each vector is filed under
+/// its nearest coarse centroid and stored as one byte per subspace; queries
+/// scan the `nprobe` closest lists using per-subspace lookup tables.
+/// Vectors inserted before the first `build` are kept raw and scanned
+/// exactly.
+pub struct IvfPqIndex {
+    params: IvfPqParams,
+    seed: u64,
+    dims: Option<usize>,
+    centroids: Vec<Vec<f32>>,
+    /// (start, len) of each subspace within a vector.
+    subspaces: Vec<(usize, usize)>,
+    /// subspace -> codeword -> sub-vector
+    codebooks: Vec<Vec<Vec<f32>>>,
+    lists: Vec<Vec<(MemoryId, Vec<u8>)>>,
+    assignments: HashMap<MemoryId, usize>,
+    pending: HashMap<MemoryId, Vec<f32>>,
+}
+
+impl IvfPqIndex {
+    pub fn new(params: IvfPqParams, seed: u64) -> Self {
+        Self {
+            params,
+            seed,
+            dims: None,
+            centroids: Vec::new(),
+            subspaces: Vec::new(),
+            codebooks: Vec::new(),
+            lists: Vec::new(),
+            assignments: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    fn nearest_centroid(&self, vector: &[f32]) -> usize {
+        nearest(&self.centroids, vector)
+    }
+
+    fn encode(&self, vector: &[f32]) -> Vec<u8> {
+        self.subspaces
+            .iter()
+            .zip(&self.codebooks)
+            .map(|(&(start, len), codebook)| nearest(codebook, &vector[start..start + len]) as u8)
+            .collect()
+    }
+
+    fn file(&mut self, id: MemoryId, vector: &[f32]) {
+        let list = self.nearest_centroid(vector);
+        let code = self.encode(vector);
+        self.lists[list].push((id, code));
+        self.assignments.insert(id, list);
+    }
+}
+
+/// Split `dims` into `parts` contiguous, near-equal ranges.
+fn split_dimensions(dims: usize, parts: usize) -> Vec<(usize, usize)> {
+    let parts = parts.clamp(1, dims.max(1));
+    let base = dims / parts;
+    let extra = dims % parts;
+    let mut start = 0;
+    (0..parts)
+        .map(|i| {
+            let len = base + usize::from(i < extra);
+            let range = (start, len);
+            start += len;
+            range
+        })
+        .collect()
+}
+
+fn nearest(points: &[Vec<f32>], vector: &[f32]) -> usize {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i, squared_distance(p, vector)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Lloyd's k-means seeded from a random sample; empty clusters keep their
+/// previous centroid.
+fn kmeans(data: &[&[f32]], k: usize, iterations: usize, rng: &mut StdRng) -> Vec<Vec<f32>> {
+    let k = k.min(data.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    let dims = data[0].len();
+    let mut centroids: Vec<Vec<f32>> = data
+        .choose_multiple(rng, k)
+        .map(|point| point.to_vec())
+        .collect();
+
+    for _ in 0..iterations {
+        let mut sums = vec![vec![0.0f32; dims]; k];
+        let mut counts = vec![0usize; k];
+        for point in data {
+            let cluster = nearest(&centroids, point);
+            counts[cluster] += 1;
+            for (sum, x) in sums[cluster].iter_mut().zip(point.iter()) {
+                *sum += x;
+            }
+        }
+        for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+            if count > 0 {
+                *centroid = sum.into_iter().map(|s| s / count as f32).collect();
+            }
+        }
+    }
+    centroids
+}
+
+impl VectorIndex for IvfPqIndex {
+    fn kind(&self) -> VectorIndexKind {
+        VectorIndexKind::IvfPq
+    }
+
+    fn build(&mut self, vectors: &[(MemoryId, Vec<f32>)]) {
+        *self = Self::new(self.params.clone(), self.seed);
+        let Some(dims) = vectors.first().map(|(_, v)| v.len()) else {
+            return;
+        };
+        self.dims = Some(dims);
+
+        let mut normalized: Vec<(MemoryId, Vec<f32>)> = Vec::with_capacity(vectors.len());
+        for (id, vector) in vectors {
+            if vector.len() == dims {
+                normalized.push((*id, normalize(vector)));
+            }
+        }
+        // A later duplicate id replaces the earlier vector, as with insert.
+        let mut latest: HashMap<MemoryId, usize> = HashMap::new();
+        for (i, (id, _)) in normalized.iter().enumerate() {
+            latest.insert(*id, i);
+        }
+        normalized = normalized
+            .into_iter()
+            .enumerate()
+            .filter(|(i, (id, _))| latest.get(id) == Some(i))
+            .map(|(_, entry)| entry)
+            .collect();
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let data: Vec<&[f32]> = normalized.iter().map(|(_, v)| v.as_slice()).collect();
+        let iterations = self.params.train_iterations;
+
+        self.centroids = kmeans(&data, self.params.nlist.max(1), iterations, &mut rng);
+        self.subspaces = split_dimensions(dims, self.params.subspaces);
+        let codebook_size = self.params.codebook_size.clamp(1, 256);
+        self.codebooks = self
+            .subspaces
+            .iter()
+            .map(|&(start, len)| {
+                let sub: Vec<&[f32]> = data.iter().map(|v| &v[start..start + len]).collect();
+                kmeans(&sub, codebook_size, iterations, &mut rng)
+            })
+            .collect();
+        self.lists = vec![Vec::new(); self.centroids.len()];
+
+        for (id, vector) in &normalized {
+            self.file(*id, vector);
+        }
+    }
+
+    fn insert(&mut self, id: MemoryId, vector: &[f32]) {
+        if *self.dims.get_or_insert(vector.len()) != vector.len() {
+            return;
+        }
+        self.remove(id);
+        let vector = normalize(vector);
+        if self.centroids.is_empty() {
+            self.pending.insert(id, vector);
+        } else {
+            self.file(id, &vector);
+        }
+    }
+
+    fn remove(&mut self, id: MemoryId) -> bool {
+        if self.pending.remove(&id).is_some() {
+            return true;
+        }
+        match self.assignments.remove(&id) {
+            Some(list) => {
+                self.lists[list].retain(|(member, _)| *member != id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Vec<VectorHit> {
+        if self.dims != Some(query.len()) || k == 0 {
+            return Vec::new();
+        }
+        let query = normalize(query);
+        let mut hits: Vec<VectorHit> = self
+            .pending
+            .iter()
+            .map(|(id, vector)| VectorHit {
+                memory_id: *id,
+                score: dot(&query, vector),
+            })
+            .collect();
+
+        if !self.centroids.is_empty() {
+            let tables: Vec<Vec<f32>> = self
+                .subspaces
+                .iter()
+                .zip(&self.codebooks)
+                .map(|(&(start, len), codebook)| {
+                    let sub = &query[start..start + len];
+                    codebook.iter().map(|word| dot(sub, word)).collect()
+                })
+                .collect();
+
+            let mut probes: Vec<(usize, f32)> = self
+                .centroids
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i, dot(&query, c)))
+                .collect();
+            probes.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            for (list, _) in probes.into_iter().take(self.params.nprobe.max(1)) {
+                for (id, code) in &self.lists[list] {
+                    let score = code
+                        .iter()
+                        .zip(&tables)
+                        .map(|(&c, table)| table[c as usize])
+                        .sum();
+                    hits.push(VectorHit {
+                        memory_id: *id,
+                        score,
+                    });
+                }
+            }
+        }
+        top_k(hits, k)
+    }
+
+    fn len(&self) -> usize {
+        self.assignments.len() + self.pending.len()
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dims
+    }
+
+    fn memory_bytes(&self) -> usize {
+        let dims = self.dims.unwrap_or(0);
+        let codewords: usize = self.codebooks.iter().flatten().map(Vec::len).sum();
+        let id_size = std::mem::size_of::<MemoryId>();
+        self.centroids.len() * dims * 4
+            + codewords * 4
+            + self.assignments.len() * (id_size + self.subspaces.len())
+            + self.pending.len() * (id_size + dims * 4)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared handle
+// ---------------------------------------------------------------------------
+
+/// Snapshot describing the active index.
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorIndexStats {
+    pub backend: VectorIndexKind,
+    pub vectors: usize,
+    pub dimensions: Option<usize>,
+    pub memory_bytes: usize,
+    /// Mean recall@k against an exact scan over sampled indexed vectors,
+    /// measured at build time. `None` when the index was empty.
+    pub recall_estimate: Option<f32>,
+    pub recall_k: usize,
+    pub recall_queries: usize,
+    /// Embeddings skipped because their dimensionality did not match.
+    pub skipped: usize,
+    pub build_ms: u64,
+    pub built_at: DateTime<Utc>,
+}
+
+struct IndexState {
+    index: Box<dyn VectorIndex>,
+    stats: VectorIndexStats,
+}
+
+/// Process-wide vector index shared by the MCP handlers. Empty until the
+/// first rebuild.
+pub struct VectorIndexHandle {
+    config: VectorIndexConfig,
+    state: RwLock<Option<IndexState>>,
+}
+
+impl Default for VectorIndexHandle {
+    fn default() -> Self {
+        Self::new(VectorIndexConfig::default())
+    }
+}
+
+impl VectorIndexHandle {
+    pub fn new(config: VectorIndexConfig) -> Self {
+        Self {
+            config,
+            state: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &VectorIndexConfig {
+        &self.config
+    }
+
+    /// Rebuild from stored embeddings of live memories, optionally switching
+    /// backend, and re-estimate recall.
+    pub fn rebuild(
+        &self,
+        conn: &Connection,
+        kind: Option<VectorIndexKind>,
+    ) -> Result<VectorIndexStats> {
+        let mut config = self.config.clone();
+        if let Some(kind) = kind {
+            config.kind = kind;
+        }
+
+        let started = Instant::now();
+        let vectors = load_embeddings(conn)?;
+        let mut index = create_vector_index(&config);
+        index.build(&vectors);
+        let build_ms = started.elapsed().as_millis() as u64;
+
+        let skipped = vectors.len() - index.len();
+        let (recall_estimate, recall_queries) = estimate_recall(index.as_ref(), &vectors, &config);
+        let stats = VectorIndexStats {
+            backend: config.kind,
+            vectors: index.len(),
+            dimensions: index.dimensions(),
+            memory_bytes: index.memory_bytes(),
+            recall_estimate,
+            recall_k: config.recall_k,
+            recall_queries,
+            skipped,
+            build_ms,
+            built_at: Utc::now(),
+        };
+        *self.state.write() = Some(IndexState {
+            index,
+            stats: stats.clone(),
+        });
+        Ok(stats)
+    }
+
+    /// Current stats, or `None` before the first rebuild.
+    pub fn stats(&self) -> Option<VectorIndexStats> {
+        self.state.read().as_ref().map(|state| {
+            let mut stats = state.stats.clone();
+            stats.vectors = state.index.len();
+            stats.memory_bytes = state.index.memory_bytes();
+            stats
+        })
+    }
+
+    /// Query the index; `None` before the first rebuild.
+    pub fn search(&self, query: &[f32], k: usize) -> Option<Vec<VectorHit>> {
+        self.state
+            .read()
+            .as_ref()
+            .map(|state| state.index.search(query, k))
+    }
+
+    pub fn insert(&self, id: MemoryId, vector: &[f32]) {
+        if let Some(state) = self.state.write().as_mut() {
+            state.index.insert(id, vector);
+        }
+    }
+
+    pub fn remove(&self, id: MemoryId) {
+        if let Some(state) = self.state.write().as_mut() {
+            state.index.remove(id);
+        }
+    }
+}
+
+/// Embeddings of memories that are still current.
+fn load_embeddings(conn: &Connection) -> Result<Vec<(MemoryId, Vec<f32>)>> {
+    let active = crate::embedding::adapter::active_embedding_model(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT e.memory_id, e.embedding, e.model
+         FROM embeddings e
+         JOIN memories m ON m.id = e.memory_id
+         WHERE m.valid_to IS NULL
+         ORDER BY e.memory_id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, MemoryId>(0)?,
+            row.get::<_, Vec<u8>>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut vectors = Vec::new();
+    for row in rows {
+        let (id, bytes, model) = row?;
+        if bytes.len() % 4 != 0 {
+            return Err(EngramError::InvalidInput(format!(
+                "Embedding for memory {} has invalid byte length {}",
+                id,
+                bytes.len()
+            )));
+        }
+        let vector = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        if let Some(vector) =
+            crate::embedding::adapter::compatible_vector(conn, active.as_deref(), &model, vector)?
+        {
+            vectors.push((id, vector));
+        }
+    }
+    Ok(vectors)
+}
+
+/// Replay sampled indexed vectors as queries and compare the index's top-k
+/// with an exact scan. Returns the mean recall and the number of queries.
+fn estimate_recall(
+    index: &dyn VectorIndex,
+    vectors: &[(MemoryId, Vec<f32>)],
+    config: &VectorIndexConfig,
+) -> (Option<f32>, usize) {
+    if index.is_empty() || config.recall_sample == 0 || config.recall_k == 0 {
+        return (None, 0);
+    }
+    let mut exact = FlatIndex::new();
+    exact.build(vectors);
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let sample: Vec<&(MemoryId, Vec<f32>)> = vectors
+        .iter()
+        .filter(|(_, v)| Some(v.len()) == index.dimensions())
+        .collect::<Vec<_>>()
+        .choose_multiple(&mut rng, config.recall_sample)
+        .copied()
+        .collect();
+
+    let mut total = 0.0;
+    for (_, query) in &sample {
+        let truth: HashSet<MemoryId> = exact
+            .search(query, config.recall_k)
+            .into_iter()
+            .map(|hit| hit.memory_id)
+            .collect();
+        if truth.is_empty() {
+            continue;
+        }
+        let found = index
+            .search(query, config.recall_k)
+            .into_iter()
+            .filter(|hit| truth.contains(&hit.memory_id))
+            .count();
+        total += found as f32 / truth.len() as f32;
+    }
+    if sample.is_empty() {
+        (None, 0)
+    } else {
+        (Some(total / sample.len() as f32), sample.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_vectors(n: usize, dims: usize, seed: u64) -> Vec<(MemoryId, Vec<f32>)> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|i| {
+                let v = (0..dims).map(|_| rng.gen_range(-1.0..1.0)).collect();
+                (i as MemoryId + 1, v)
+            })
+            .collect()
+    }
+
+    fn recall_of(kind: VectorIndexKind, vectors: &[(MemoryId, Vec<f32>)]) -> f32 {
+        let config = VectorIndexConfig {
+            kind,
+            ivf_pq: IvfPqParams {
+                nlist: 8,
+                nprobe: 4,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut index = create_vector_index(&config);
+        index.build(vectors);
+        estimate_recall(index.as_ref(), vectors, &config).0.unwrap()
+    }
+
+    #[test]
+    fn test_backends_find_exact_match_and_estimate_recall() {
+        let vectors = random_vectors(250, 32, 7);
+
+        assert!((recall_of(VectorIndexKind::Flat, &vectors) - 1.0).abs() < 1e-6);
+        assert!(recall_of(VectorIndexKind::Hnsw, &vectors) > 0.9);
+        assert!(recall_of(VectorIndexKind::IvfPq, &vectors) > 0.3);
+
+        for kind in [
+            VectorIndexKind::Flat,
+            VectorIndexKind::Hnsw,
+            VectorIndexKind::IvfPq,
+        ] {
+            let mut index = create_vector_index(&VectorIndexConfig {
+                kind,
+                ..Default::default()
+            });
+            index.build(&vectors);
+            assert_eq!(index.len(), 250);
+            let hits = index.search(&vectors[10].1, 5);
+            assert_eq!(hits.len(), 5, "{:?}", kind);
+            if kind != VectorIndexKind::IvfPq {
+                assert_eq!(hits[0].memory_id, 11, "{:?}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert_remove_and_footprint() {
+        let vectors = random_vectors(200, 16, 3);
+        let mut flat = FlatIndex::new();
+        let mut hnsw = HnswIndex::new(HnswParams::default(), 42);
+        let mut ivf = IvfPqIndex::new(IvfPqParams::default(), 42);
+        flat.build(&vectors);
+        hnsw.build(&vectors);
+        ivf.build(&vectors);
+
+        for index in [
+            &mut flat as &mut dyn VectorIndex,
+            &mut hnsw as &mut dyn VectorIndex,
+            &mut ivf as &mut dyn VectorIndex,
+        ] {
+            assert!(index.remove(1));
+            assert!(!index.remove(1));
+            assert_eq!(index.len(), 199);
+            assert!(index
+                .search(&vectors[0].1, 10)
+                .iter()
+                .all(|h| h.memory_id != 1));
+
+            index.insert(1, &vectors[0].1);
+            index.insert(999, &[1.0, 2.0]); // wrong dimensionality
+            assert_eq!(index.len(), 200);
+        }
+
+        // Codes take one byte per subspace instead of four per dimension.
+        assert!(ivf.memory_bytes() < flat.memory_bytes() + 256 * 16 * 4);
+        assert!(hnsw.memory_bytes() > flat.memory_bytes());
+    }
+
+    #[test]
+    fn test_handle_rebuilds_from_embeddings() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE memories (id INTEGER PRIMARY KEY, valid_to TEXT);
+             CREATE TABLE embeddings (
+                 memory_id INTEGER PRIMARY KEY, embedding BLOB, model TEXT DEFAULT 'test'
+             );
+             CREATE TABLE embedding_space (id INTEGER PRIMARY KEY, model TEXT, updated_at TEXT);",
+        )
+        .unwrap();
+        for (id, vector) in random_vectors(30, 8, 1) {
+            let valid_to = (id == 30).then_some("2024-01-01T00:00:00Z");
+            conn.execute(
+                "INSERT INTO memories (id, valid_to) VALUES (?, ?)",
+                rusqlite::params![id, valid_to],
+            )
+            .unwrap();
+            let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_le_bytes()).collect();
+            conn.execute(
+                "INSERT INTO embeddings (memory_id, embedding) VALUES (?, ?)",
+                rusqlite::params![id, bytes],
+            )
+            .unwrap();
+        }
+
+        let handle = VectorIndexHandle::default();
+        assert!(handle.stats().is_none());
+        let stats = handle.rebuild(&conn, Some(VectorIndexKind::Flat)).unwrap();
+        assert_eq!(stats.backend, VectorIndexKind::Flat);
+        assert_eq!(stats.vectors, 29);
+        assert_eq!(stats.dimensions, Some(8));
+        assert_eq!(stats.recall_estimate, Some(1.0));
+
+        handle.remove(5);
+        assert_eq!(handle.stats().unwrap().vectors, 28);
+        assert_eq!("ivf-pq".parse(), Ok(VectorIndexKind::IvfPq));
+    }
+}
@@ -112,7 +112,7 @@ impl SnapshotLoader {
             // Merge: skip if content_hash already exists in target workspace
             if strategy == LoadStrategy::Merge {
                 if let Some(hash) = &memory.content_hash {
-                    let exists = Self::content_hash_exists(storage, hash, &ws)?;
+                    let exists = Self::content_hash_exists(storage, hash.as_str(), &ws)?;
                     if exists {
                         memories_skipped += 1;
                         continue;
@@ -357,7 +357,7 @@ impl SnapshotLoader {
 
         for memory in memories {
             if let Some(hash) = &memory.content_hash {
-                let exists = Self::content_hash_exists(storage, hash, workspace)?;
+                let exists = Self::content_hash_exists(storage, hash.as_str(), workspace)?;
                 if exists {
                     would_skip += 1;
                 } else {
@@ -0,0 +1,388 @@
+//! Bulk write path for high-volume ingestion
+//!
+//! [`bulk_create_memories`] writes many memories through one connection with
+//! far less per-row overhead than calling `create_memory` in a loop:
+//!
+//! - rows are committed in batches instead of one transaction per row
+//! - the FTS insert trigger is suspended inside each batch and the new rows
+//!   are indexed with a single `INSERT … SELECT` before the batch commits
+//! - rows are not read back after insertion, and the hot statements are
+//!   served from the prepared-statement cache
+//! - `synchronous=OFF` and WAL auto-checkpointing are disabled for the duration
+//!   of the call and restored (followed by a passive checkpoint) afterwards
+//!
+//! Embedding generation is already asynchronous; rows are only added to the
+//! embedding queue, which the worker drains once the batch commits. Each batch
+//! is atomic, so a crash loses at most the batch in flight — the trigger swap
+//! and the FTS backfill commit or roll back together with the rows.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::time::Instant;
+
+use super::queries::{insert_memory, BatchError};
+use super::workspace_config::effective_dedup_mode;
+use super::Storage;
+use crate::error::{EngramError, Result};
+use crate::types::{CreateMemoryInput, DedupMode};
+
+/// Default number of rows committed per transaction
+pub const DEFAULT_BULK_BATCH_SIZE: usize = 500;
+
+/// Page cache size (in KiB, as a negative `cache_size`) used while bulk writing
+const BULK_CACHE_SIZE_KIB: i64 = 131_072;
+
+/// Name of the trigger that indexes new memories into `memories_fts`
+const FTS_INSERT_TRIGGER: &str = "memories_ai";
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// Tuning knobs for [`bulk_create_memories`]
+#[derive(Debug, Clone)]
+pub struct BulkWriteOptions {
+    /// Rows committed per transaction
+    pub batch_size: usize,
+    /// Relax `synchronous` and WAL checkpointing while writing
+    pub relax_durability: bool,
+    /// Index FTS once per batch instead of once per row
+    pub defer_fts: bool,
+}
+
+impl Default for BulkWriteOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BULK_BATCH_SIZE,
+            relax_durability: true,
+            defer_fts: true,
+        }
+    }
+}
+
+/// Outcome of a bulk write
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BulkWriteResult {
+    /// IDs of the written memories, in input order; under `Skip`/`Merge`
+    /// dedup this is the ID of the existing memory
+    pub ids: Vec<i64>,
+    /// Inputs that were rejected; the rest of their batch is still written
+    pub failed: Vec<BatchError>,
+    /// Number of transactions committed
+    pub batches: usize,
+    pub duration_ms: u64,
+    /// The call's time budget ran out; inputs after the last committed
+    /// batch were not written
+    pub truncated: bool,
+}
+
+// =============================================================================
+// Storage functions
+// =============================================================================
+
+/// Create many memories using the bulk write path.
+///
+/// A failing input (invalid workspace, `Reject` duplicate, …) is rolled back
+/// on its own and reported in [`BulkWriteResult::failed`]; storage errors
+/// abort the call, keeping every batch committed before it. Once the tool
+/// call's time budget runs out no further batch is started.
+pub fn bulk_create_memories(
+    storage: &Storage,
+    inputs: &[CreateMemoryInput],
+    options: &BulkWriteOptions,
+) -> Result<BulkWriteResult> {
+    if options.batch_size == 0 {
+        return Err(EngramError::InvalidInput(
+            "batch_size must be greater than 0".to_string(),
+        ));
+    }
+
+    let start = Instant::now();
+    let mut result = BulkWriteResult::default();
+
+    storage.with_connection(|conn| {
+        let _durability = if options.relax_durability {
+            Some(RelaxedDurability::enter(conn)?)
+        } else {
+            None
+        };
+
+        for (batch_index, batch) in inputs.chunks(options.batch_size).enumerate() {
+            if batch_index > 0 && crate::budget::checkpoint() {
+                result.truncated = true;
+                break;
+            }
+            write_batch(
+                conn,
+                batch,
+                batch_index * options.batch_size,
+                options.defer_fts,
+                &mut result,
+            )?;
+            result.batches += 1;
+        }
+        Ok(())
+    })?;
+
+    result.duration_ms = start.elapsed().as_millis() as u64;
+    Ok(result)
+}
+
+// =============================================================================
+// Internal helpers
+// =============================================================================
+
+/// Write one batch in a single transaction.
+fn write_batch(
+    conn: &Connection,
+    batch: &[CreateMemoryInput],
+    base_index: usize,
+    defer_fts: bool,
+    result: &mut BulkWriteResult,
+) -> Result<()> {
+    let mut tx = conn.unchecked_transaction()?;
+    let first_new_id: i64 =
+        tx.query_row("SELECT COALESCE(MAX(id), 0) + 1 FROM memories", [], |row| {
+            row.get(0)
+        })?;
+
+    // A merge updates an existing row, and the FTS update trigger would then
+    // try to remove index entries for a row inserted earlier in this batch
+    // that was never indexed. Such batches, including those merging by
+    // workspace default, keep per-row indexing.
+    let defer_fts = defer_fts
+        && batch
+            .iter()
+            .all(|i| !matches!(effective_dedup_mode(&tx, i), Ok(DedupMode::Merge)));
+    let suspended_trigger = if defer_fts {
+        suspend_fts_trigger(&tx)?
+    } else {
+        None
+    };
+
+    for (offset, input) in batch.iter().enumerate() {
+        let row = tx.savepoint()?;
+        match insert_memory(&row, input) {
+            Ok(id) => {
+                row.commit()?;
+                result.ids.push(id);
+            }
+            Err(e) => {
+                drop(row);
+                result.failed.push(BatchError {
+                    index: base_index + offset,
+                    id: None,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    if let Some(trigger_sql) = suspended_trigger {
+        tx.execute(
+            "INSERT INTO memories_fts(rowid, content, tags, metadata)
+             SELECT id, content, '', metadata FROM memories WHERE id >= ?",
+            params![first_new_id],
+        )?;
+        tx.execute_batch(&trigger_sql)?;
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Drop the FTS insert trigger, returning its SQL so it can be recreated.
+///
+/// Returns `None` when the trigger does not exist, in which case new rows are
+/// not indexed either.
+fn suspend_fts_trigger(conn: &Connection) -> Result<Option<String>> {
+    let sql: Option<String> = conn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = ?",
+            params![FTS_INSERT_TRIGGER],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if sql.is_some() {
+        conn.execute_batch(&format!("DROP TRIGGER {FTS_INSERT_TRIGGER}"))?;
+    }
+    Ok(sql)
+}
+
+/// Connection settings traded for throughput, restored on drop.
+struct RelaxedDurability<'c> {
+    conn: &'c Connection,
+    synchronous: i64,
+    wal_autocheckpoint: i64,
+    cache_size: i64,
+}
+
+impl<'c> RelaxedDurability<'c> {
+    fn enter(conn: &'c Connection) -> Result<Self> {
+        let pragma = |name: &str| -> Result<i64> {
+            Ok(conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))?)
+        };
+        let guard = Self {
+            conn,
+            synchronous: pragma("synchronous")?,
+            wal_autocheckpoint: pragma("wal_autocheckpoint")?,
+            cache_size: pragma("cache_size")?,
+        };
+        conn.execute_batch(&format!(
+            "PRAGMA synchronous=OFF;
+             PRAGMA wal_autocheckpoint=0;
+             PRAGMA cache_size=-{BULK_CACHE_SIZE_KIB};"
+        ))?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RelaxedDurability<'_> {
+    fn drop(&mut self) {
+        let restore = format!(
+            "PRAGMA synchronous={};
+             PRAGMA wal_autocheckpoint={};
+             PRAGMA cache_size={};
+             PRAGMA wal_checkpoint(PASSIVE);",
+            self.synchronous, self.wal_autocheckpoint, self.cache_size
+        );
+        if let Err(e) = self.conn.execute_batch(&restore) {
+            tracing::warn!(
+                "Failed to restore connection settings after bulk write: {}",
+                e
+            );
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::get_memory;
+
+    fn input(content: &str) -> CreateMemoryInput {
+        CreateMemoryInput {
+            content: content.to_string(),
+            tags: vec!["bulk".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn fts_hits(storage: &Storage, term: &str) -> i64 {
+        storage
+            .with_connection(|conn| {
+                Ok(conn.query_row(
+                    "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH ?",
+                    params![term],
+                    |row| row.get(0),
+                )?)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bulk_create_indexes_and_queues_every_row() {
+        let storage = Storage::open_in_memory().unwrap();
+        let inputs: Vec<_> = (0..25)
+            .map(|i| input(&format!("bulk row {i} zebrafish")))
+            .collect();
+        let options = BulkWriteOptions {
+            batch_size: 10,
+            ..Default::default()
+        };
+
+        let result = bulk_create_memories(&storage, &inputs, &options).unwrap();
+        assert_eq!(result.ids.len(), 25);
+        assert_eq!(result.batches, 3);
+        assert!(result.failed.is_empty());
+        assert_eq!(fts_hits(&storage, "zebrafish"), 25);
+
+        storage
+            .with_connection(|conn| {
+                let memory = get_memory(conn, result.ids[7])?;
+                assert_eq!(memory.content, "bulk row 7 zebrafish");
+                assert_eq!(memory.tags, vec!["bulk".to_string()]);
+                let queued: i64 =
+                    conn.query_row("SELECT COUNT(*) FROM embedding_queue", [], |r| r.get(0))?;
+                assert_eq!(queued, 25);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_bulk_create_restores_trigger_and_pragmas() {
+        let storage = Storage::open_in_memory().unwrap();
+        let before: (i64, i64) = storage
+            .with_connection(|conn| {
+                Ok((
+                    conn.query_row("PRAGMA synchronous", [], |r| r.get(0))?,
+                    conn.query_row("PRAGMA cache_size", [], |r| r.get(0))?,
+                ))
+            })
+            .unwrap();
+
+        bulk_create_memories(&storage, &[input("okapi")], &Default::default()).unwrap();
+
+        storage
+            .with_connection(|conn| {
+                let after: (i64, i64) = (
+                    conn.query_row("PRAGMA synchronous", [], |r| r.get(0))?,
+                    conn.query_row("PRAGMA cache_size", [], |r| r.get(0))?,
+                );
+                assert_eq!(after, before);
+                let trigger: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name = ?",
+                    params![FTS_INSERT_TRIGGER],
+                    |r| r.get(0),
+                )?;
+                assert_eq!(trigger, 1);
+                crate::storage::queries::create_memory(conn, &input("okapi again"))?;
+                Ok(())
+            })
+            .unwrap();
+        // The regular path indexes through the restored trigger.
+        assert_eq!(fts_hits(&storage, "okapi"), 2);
+    }
+
+    #[test]
+    fn test_bulk_create_isolates_failed_rows() {
+        let storage = Storage::open_in_memory().unwrap();
+        let mut inputs = vec![input("first"), input("second"), input("third")];
+        inputs[1].workspace = Some("Not A Valid Workspace!".to_string());
+
+        let result = bulk_create_memories(&storage, &inputs, &Default::default()).unwrap();
+        assert_eq!(result.ids.len(), 2);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].index, 1);
+        assert_eq!(fts_hits(&storage, "third"), 1);
+        assert_eq!(fts_hits(&storage, "second"), 0);
+    }
+
+    #[test]
+    fn test_bulk_create_merge_batch_keeps_fts_consistent() {
+        let storage = Storage::open_in_memory().unwrap();
+        let mut duplicate = input("pangolin notes");
+        duplicate.dedup_mode = DedupMode::Merge;
+        duplicate.tags = vec!["extra".to_string()];
+        let inputs = vec![input("pangolin notes"), duplicate];
+
+        let result = bulk_create_memories(&storage, &inputs, &Default::default()).unwrap();
+        assert_eq!(result.ids[0], result.ids[1]);
+        assert_eq!(fts_hits(&storage, "pangolin"), 1);
+    }
+
+    #[test]
+    fn test_bulk_create_rejects_zero_batch_size() {
+        let storage = Storage::open_in_memory().unwrap();
+        let options = BulkWriteOptions {
+            batch_size: 0,
+            ..Default::default()
+        };
+        assert!(bulk_create_memories(&storage, &[input("x")], &options).is_err());
+    }
+}
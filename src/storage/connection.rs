@@ -10,7 +10,7 @@ use std::sync::Arc;
 
 use super::migrations::run_migrations;
 use crate::error::Result;
-use crate::types::{StorageConfig, StorageMode};
+use crate::types::{MemoryId, StorageConfig, StorageMode};
 
 /// Storage engine wrapping SQLite with connection pooling
 pub struct Storage {
@@ -113,6 +113,30 @@ impl Storage {
         Ok(())
     }
 
+    /// Call `hook` with the id of every `memories` row written through this
+    /// storage, whichever code path writes it. Replaces any earlier hook.
+    ///
+    /// The hook runs inside the writing statement, before the transaction
+    /// commits, and must not use the connection.
+    pub fn on_memory_write<F>(&self, mut hook: F)
+    where
+        F: FnMut(MemoryId) + Send + 'static,
+    {
+        self.conn
+            .lock()
+            .update_hook(Some(move |_, _: &str, table: &str, rowid: i64| {
+                if table == "memories" {
+                    hook(rowid);
+                }
+            }));
+    }
+
+    /// The shared connection itself, for components that lock it directly
+    /// (such as the embedding worker)
+    pub fn shared_connection(&self) -> Arc<Mutex<Connection>> {
+        self.conn.clone()
+    }
+
     /// Get a reference to the connection (for single-threaded use)
     pub fn connection(&self) -> parking_lot::MutexGuard<'_, Connection> {
         self.conn.lock()
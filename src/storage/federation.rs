@@ -0,0 +1,584 @@
+//! Federated search across several Engram databases
+//!
+//! Users who keep separate databases (personal, work, archive) can attach
+//! them read-only next to the primary store and search or list across all of
+//! them at once. Sources are declared in a JSON config file:
+//!
+//! ```json
+//! {
+//!   "local_label": "personal",
+//!   "sources": [
+//!     {"label": "work", "path": "~/engram/work.db"},
+//!     {"label": "archive", "path": "~/engram/archive.db", "weight": 0.5}
+//!   ]
+//! }
+//! ```
+//!
+//! Every hit carries the label of the database it came from. Rankings are
+//! merged with weighted reciprocal rank fusion, so scores from databases of
+//! different sizes stay comparable. Attached databases are opened with
+//! `SQLITE_OPEN_READ_ONLY` and never migrated.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+
+use super::connection::Storage;
+use super::queries::list_memories;
+use crate::error::{EngramError, Result};
+use crate::search::{hybrid_search, SearchConfig};
+use crate::types::{ListOptions, Memory, SearchOptions, SearchResult, SortField, SortOrder};
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_local_label() -> String {
+    "local".to_string()
+}
+
+/// One attached database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationSource {
+    /// Label reported on every result from this database
+    pub label: String,
+    /// Path to the SQLite file (`~` is expanded)
+    pub path: String,
+    /// Multiplier on this source's fused score
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+/// Federation config file contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationConfig {
+    /// Label for the primary (writable) database
+    #[serde(default = "default_local_label")]
+    pub local_label: String,
+    /// Databases attached read-only
+    #[serde(default)]
+    pub sources: Vec<FederationSource>,
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            local_label: default_local_label(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl FederationConfig {
+    /// Load and validate a config file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            EngramError::Config(format!(
+                "Failed to read federation config {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let config: Self = serde_json::from_str(&contents).map_err(|e| {
+            EngramError::Config(format!(
+                "Invalid federation config {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// `~/.config/engram/federation.json` (platform config dir)
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("engram").join("federation.json"))
+    }
+
+    /// Load from `path`, else from the default path when that file exists.
+    ///
+    /// Returns `Ok(None)` when no path is given and there is no default file,
+    /// so federation stays off unless it has been configured.
+    pub fn load_optional(path: Option<&Path>) -> Result<Option<Self>> {
+        match path {
+            Some(path) => Self::load(path).map(Some),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::load(&path).map(Some),
+                _ => Ok(None),
+            },
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut seen = vec![self.local_label.as_str()];
+        for source in &self.sources {
+            if source.label.trim().is_empty() {
+                return Err(EngramError::Config(
+                    "Federation source label must not be empty".to_string(),
+                ));
+            }
+            if seen.contains(&source.label.as_str()) {
+                return Err(EngramError::Config(format!(
+                    "Duplicate federation source label: {}",
+                    source.label
+                )));
+            }
+            if !(source.weight.is_finite() && source.weight > 0.0) {
+                return Err(EngramError::Config(format!(
+                    "Federation source {} must have a positive weight",
+                    source.label
+                )));
+            }
+            seen.push(&source.label);
+        }
+        Ok(())
+    }
+}
+
+/// Summary of an attached database for `federation_sources`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationSourceInfo {
+    pub label: String,
+    pub path: Option<String>,
+    pub weight: f32,
+    pub read_only: bool,
+    pub memories: i64,
+    pub schema_version: Option<i64>,
+}
+
+/// A search hit labelled with its database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedSearchResult {
+    pub source: String,
+    /// Weighted reciprocal rank fusion score used for the merged order
+    pub federated_score: f32,
+    /// Rank within the source (1-based)
+    pub source_rank: usize,
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
+
+/// A listed memory labelled with its database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedMemory {
+    pub source: String,
+    #[serde(flatten)]
+    pub memory: Memory,
+}
+
+/// A source that failed; the others still answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationError {
+    pub source: String,
+    pub error: String,
+}
+
+/// Merged results plus per-source failures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedResults<T> {
+    pub results: Vec<T>,
+    pub sources: Vec<String>,
+    pub errors: Vec<FederationError>,
+}
+
+struct AttachedSource {
+    source: FederationSource,
+    conn: Mutex<Connection>,
+}
+
+/// Primary store plus read-only attached databases
+pub struct Federation {
+    local_label: String,
+    sources: Vec<AttachedSource>,
+}
+
+impl Federation {
+    /// Open every configured source read-only.
+    pub fn open(config: &FederationConfig) -> Result<Self> {
+        config.validate()?;
+        let mut sources = Vec::with_capacity(config.sources.len());
+        for source in &config.sources {
+            let conn = open_read_only(&source.path).map_err(|e| {
+                EngramError::Config(format!(
+                    "Failed to attach federation source {} ({}): {}",
+                    source.label, source.path, e
+                ))
+            })?;
+            sources.push(AttachedSource {
+                source: source.clone(),
+                conn: Mutex::new(conn),
+            });
+        }
+        Ok(Self {
+            local_label: config.local_label.clone(),
+            sources,
+        })
+    }
+
+    /// Label of the primary database
+    pub fn local_label(&self) -> &str {
+        &self.local_label
+    }
+
+    /// Labels of every database, primary first
+    pub fn labels(&self) -> Vec<String> {
+        std::iter::once(self.local_label.clone())
+            .chain(self.sources.iter().map(|s| s.source.label.clone()))
+            .collect()
+    }
+
+    /// Describe the primary and attached databases.
+    pub fn describe(&self, local: &Storage) -> Result<Vec<FederationSourceInfo>> {
+        let mut infos =
+            vec![local
+                .with_connection(|conn| source_info(conn, &self.local_label, None, 1.0, false))?];
+        for attached in &self.sources {
+            let conn = attached.conn.lock();
+            infos.push(source_info(
+                &conn,
+                &attached.source.label,
+                Some(attached.source.path.clone()),
+                attached.source.weight,
+                true,
+            )?);
+        }
+        Ok(infos)
+    }
+
+    /// Run `f` against each selected database, collecting failures.
+    fn each_source<T>(
+        &self,
+        local: &Storage,
+        only: Option<&[String]>,
+        mut f: impl FnMut(&Connection) -> Result<T>,
+    ) -> Result<(Vec<(String, f32, T)>, Vec<FederationError>)> {
+        let selected = |label: &str| only.is_none_or(|only| only.iter().any(|l| l == label));
+        if let Some(only) = only {
+            let labels = self.labels();
+            if let Some(unknown) = only.iter().find(|l| !labels.contains(l)) {
+                return Err(EngramError::InvalidInput(format!(
+                    "Unknown federation source: {}",
+                    unknown
+                )));
+            }
+        }
+
+        let mut answers = Vec::new();
+        let mut errors = Vec::new();
+        let mut record = |label: &str, weight: f32, result: Result<T>| match result {
+            Ok(value) => answers.push((label.to_string(), weight, value)),
+            Err(e) => errors.push(FederationError {
+                source: label.to_string(),
+                error: e.to_string(),
+            }),
+        };
+
+        if selected(&self.local_label) {
+            record(&self.local_label, 1.0, local.with_connection(&mut f));
+        }
+        for attached in &self.sources {
+            if selected(&attached.source.label) {
+                let conn = attached.conn.lock();
+                record(&attached.source.label, attached.source.weight, f(&conn));
+            }
+        }
+        Ok((answers, errors))
+    }
+
+    /// Search every selected database and merge the rankings.
+    ///
+    /// `query_embedding` is used only on databases holding embeddings from the
+    /// same model and dimensions; the rest fall back to keyword search.
+    pub fn search(
+        &self,
+        local: &Storage,
+        query: &str,
+        query_embedding: Option<(&[f32], &str)>,
+        options: &SearchOptions,
+        config: &SearchConfig,
+        only: Option<&[String]>,
+    ) -> Result<FederatedResults<FederatedSearchResult>> {
+        let limit = options.limit.unwrap_or(20).max(1) as usize;
+        let (answers, errors) = self.each_source(local, only, |conn| {
+            let embedding = query_embedding
+                .filter(|(vector, model)| has_compatible_embeddings(conn, model, vector.len()))
+                .map(|(vector, _)| vector);
+            hybrid_search(conn, query, embedding, options, config)
+        })?;
+
+        let sources = answers.iter().map(|(label, _, _)| label.clone()).collect();
+        let mut merged: Vec<FederatedSearchResult> = answers
+            .into_iter()
+            .flat_map(|(label, weight, results)| {
+                results
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(idx, result)| FederatedSearchResult {
+                        source: label.clone(),
+                        federated_score: weight / (config.rrf_k + (idx + 1) as f32),
+                        source_rank: idx + 1,
+                        result,
+                    })
+            })
+            .collect();
+        merged.sort_by(|a, b| {
+            b.federated_score
+                .partial_cmp(&a.federated_score)
+                .unwrap_or(Ordering::Equal)
+                .then(
+                    b.result
+                        .score
+                        .partial_cmp(&a.result.score)
+                        .unwrap_or(Ordering::Equal),
+                )
+        });
+        merged.truncate(limit);
+
+        Ok(FederatedResults {
+            results: merged,
+            sources,
+            errors,
+        })
+    }
+
+    /// List memories from every selected database in one sorted page.
+    pub fn list(
+        &self,
+        local: &Storage,
+        options: &ListOptions,
+        only: Option<&[String]>,
+    ) -> Result<FederatedResults<FederatedMemory>> {
+        let limit = options.limit.unwrap_or(100).max(0) as usize;
+        let offset = options.offset.unwrap_or(0).max(0) as usize;
+
+        // Each source returns its first offset + limit rows so the merged
+        // page is exact.
+        let per_source = ListOptions {
+            limit: Some((offset + limit) as i64),
+            offset: Some(0),
+            ..options.clone()
+        };
+        let (answers, errors) =
+            self.each_source(local, only, |conn| list_memories(conn, &per_source))?;
+
+        let sources = answers.iter().map(|(label, _, _)| label.clone()).collect();
+        let mut merged: Vec<FederatedMemory> = answers
+            .into_iter()
+            .flat_map(|(label, _, memories)| {
+                memories.into_iter().map(move |memory| FederatedMemory {
+                    source: label.clone(),
+                    memory,
+                })
+            })
+            .collect();
+
+        let field = options.sort_by.unwrap_or_default();
+        let descending = options.sort_order.unwrap_or_default() == SortOrder::Desc;
+        merged.sort_by(|a, b| {
+            let ordering = compare_by(&a.memory, &b.memory, field);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        Ok(FederatedResults {
+            results: merged.into_iter().skip(offset).take(limit).collect(),
+            sources,
+            errors,
+        })
+    }
+}
+
+fn open_read_only(path: &str) -> Result<Connection> {
+    let path = shellexpand::tilde(path).to_string();
+    if !Path::new(&path).exists() {
+        return Err(EngramError::Config(format!("{} does not exist", path)));
+    }
+    let conn = Connection::open_with_flags(
+        &path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.execute_batch("PRAGMA query_only=ON; PRAGMA busy_timeout=30000;")?;
+
+    let has_memories: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'memories')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_memories {
+        return Err(EngramError::Config(format!(
+            "{} is not an Engram database",
+            path
+        )));
+    }
+    Ok(conn)
+}
+
+fn source_info(
+    conn: &Connection,
+    label: &str,
+    path: Option<String>,
+    weight: f32,
+    read_only: bool,
+) -> Result<FederationSourceInfo> {
+    let memories: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM memories WHERE valid_to IS NULL",
+        [],
+        |row| row.get(0),
+    )?;
+    let schema_version: Option<i64> = conn
+        .query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+            row.get(0)
+        })
+        .ok()
+        .flatten();
+    Ok(FederationSourceInfo {
+        label: label.to_string(),
+        path,
+        weight,
+        read_only,
+        memories,
+        schema_version,
+    })
+}
+
+fn has_compatible_embeddings(conn: &Connection, model: &str, dimensions: usize) -> bool {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM embeddings WHERE model = ? AND dimensions = ?)",
+        params![model, dimensions as i64],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+fn compare_by(a: &Memory, b: &Memory, field: SortField) -> Ordering {
+    match field {
+        SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+        SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+        SortField::LastAccessedAt => a.last_accessed_at.cmp(&b.last_accessed_at),
+        SortField::Importance => a
+            .importance
+            .partial_cmp(&b.importance)
+            .unwrap_or(Ordering::Equal),
+        SortField::AccessCount => a.access_count.cmp(&b.access_count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::types::{CreateMemoryInput, StorageConfig, StorageMode};
+
+    fn file_storage(path: &Path) -> Storage {
+        Storage::open(StorageConfig {
+            db_path: path.to_string_lossy().to_string(),
+            storage_mode: StorageMode::Local,
+            cloud_uri: None,
+            encrypt_cloud: false,
+            confidence_half_life_days: 30.0,
+            auto_sync: false,
+            sync_debounce_ms: 5000,
+        })
+        .unwrap()
+    }
+
+    fn add(storage: &Storage, content: &str) {
+        storage
+            .with_connection(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_federated_search_and_list_label_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let work_path = dir.path().join("work.db");
+        {
+            let work = file_storage(&work_path);
+            add(&work, "quarterly roadmap review for the payments team");
+            add(&work, "payments incident retro notes");
+        }
+
+        let local = Storage::open_in_memory().unwrap();
+        add(&local, "personal roadmap for learning rust");
+
+        let config: FederationConfig = serde_json::from_value(serde_json::json!({
+            "local_label": "personal",
+            "sources": [{"label": "work", "path": work_path.to_string_lossy()}]
+        }))
+        .unwrap();
+        let federation = Federation::open(&config).unwrap();
+        assert_eq!(federation.labels(), vec!["personal", "work"]);
+
+        let infos = federation.describe(&local).unwrap();
+        assert_eq!(infos[1].memories, 2);
+        assert!(infos[1].read_only);
+
+        let options = SearchOptions {
+            limit: Some(10),
+            ..Default::default()
+        };
+        let found = federation
+            .search(
+                &local,
+                "roadmap",
+                None,
+                &options,
+                &SearchConfig::default(),
+                None,
+            )
+            .unwrap();
+        assert!(found.errors.is_empty());
+        let labels: Vec<&str> = found.results.iter().map(|r| r.source.as_str()).collect();
+        assert_eq!(labels.len(), 2);
+        assert!(labels.contains(&"personal") && labels.contains(&"work"));
+        // Equal weights: both top hits share rank 1
+        assert!(found.results.iter().all(|r| r.source_rank == 1));
+
+        let only_work = ["work".to_string()];
+        let listed = federation
+            .list(
+                &local,
+                &ListOptions {
+                    limit: Some(2),
+                    ..Default::default()
+                },
+                Some(&only_work),
+            )
+            .unwrap();
+        assert_eq!(listed.sources, vec!["work"]);
+        assert_eq!(listed.results.len(), 2);
+        assert!(listed.results.iter().all(|m| m.source == "work"));
+
+        let all = federation
+            .list(&local, &ListOptions::default(), None)
+            .unwrap();
+        assert_eq!(all.results.len(), 3);
+
+        assert!(federation
+            .list(&local, &ListOptions::default(), Some(&["nope".to_string()]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_config_rejects_duplicate_labels() {
+        let config: FederationConfig = serde_json::from_value(serde_json::json!({
+            "sources": [{"label": "local", "path": "/tmp/x.db"}]
+        }))
+        .unwrap();
+        assert!(matches!(config.validate(), Err(EngramError::Config(_))));
+    }
+}
@@ -18,6 +18,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::tag_synonyms::{tag_match_sql, TAG_MATCH_PARAMS};
 use crate::error::{EngramError, Result};
 
 /// A filter expression that can be evaluated against a memory
@@ -261,10 +262,14 @@ impl SqlBuilder {
                         "tags filter requires a string value".to_string(),
                     )
                 })?;
-                self.params.push(Box::new(tag.to_string()));
-                Ok(
-                    "EXISTS (SELECT 1 FROM memory_tags mt JOIN tags t ON mt.tag_id = t.id WHERE mt.memory_id = m.id AND t.name = ?)".to_string()
-                )
+                // Any tag in the synonym group counts as this tag
+                for _ in 0..TAG_MATCH_PARAMS {
+                    self.params.push(Box::new(tag.to_string()));
+                }
+                Ok(format!(
+                    "EXISTS (SELECT 1 FROM memory_tags mt JOIN tags t ON mt.tag_id = t.id WHERE mt.memory_id = m.id AND {})",
+                    tag_match_sql("t.name")
+                ))
             }
             (FieldPath::Tags, FilterOp::NotContains(value))
             | (FieldPath::Tags, FilterOp::Neq(value)) => {
@@ -274,10 +279,14 @@ impl SqlBuilder {
                         "tags filter requires a string value".to_string(),
                     )
                 })?;
-                self.params.push(Box::new(tag.to_string()));
-                Ok(
-                    "NOT EXISTS (SELECT 1 FROM memory_tags mt JOIN tags t ON mt.tag_id = t.id WHERE mt.memory_id = m.id AND t.name = ?)".to_string()
-                )
+                // Any tag in the synonym group counts as this tag
+                for _ in 0..TAG_MATCH_PARAMS {
+                    self.params.push(Box::new(tag.to_string()));
+                }
+                Ok(format!(
+                    "NOT EXISTS (SELECT 1 FROM memory_tags mt JOIN tags t ON mt.tag_id = t.id WHERE mt.memory_id = m.id AND {})",
+                    tag_match_sql("t.name")
+                ))
             }
             (FieldPath::Tags, FilterOp::Exists(exists)) => {
                 // Check if memory has any tags at all
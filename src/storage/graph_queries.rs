@@ -6,7 +6,9 @@
 
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
 use crate::error::Result;
 use crate::types::{CrossReference, EdgeType, MemoryId, RelationSource};
@@ -36,6 +38,9 @@ pub struct TraversalOptions {
     /// Direction of traversal
     #[serde(default)]
     pub direction: TraversalDirection,
+    /// Fail with a limit error once more nodes than this are reached
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
 }
 
 fn default_depth() -> usize {
@@ -50,6 +55,10 @@ fn default_include_entities() -> bool {
     true
 }
 
+fn default_max_nodes() -> usize {
+    crate::limits::DEFAULT_MAX_GRAPH_NODES
+}
+
 impl Default for TraversalOptions {
     fn default() -> Self {
         Self {
@@ -60,6 +69,7 @@ impl Default for TraversalOptions {
             limit_per_hop: 50,
             include_entities: true,
             direction: TraversalDirection::Both,
+            max_nodes: default_max_nodes(),
         }
     }
 }
@@ -117,6 +127,9 @@ pub struct TraversalResult {
     pub discovery_edges: Vec<CrossReference>,
     /// Statistics about the traversal
     pub stats: TraversalStats,
+    /// The call's time budget ran out before the full depth was explored
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -164,7 +177,12 @@ pub fn get_related_multi_hop(
         .or_insert(0) += 1;
 
     // Level-based BFS traversal
+    let mut truncated = false;
     while !queue.is_empty() {
+        if crate::budget::checkpoint() {
+            truncated = true;
+            break;
+        }
         let level_size = queue.len();
         let mut current_batch = Vec::with_capacity(level_size);
         for _ in 0..level_size {
@@ -316,6 +334,13 @@ pub fn get_related_multi_hop(
                     stats.max_depth_reached = stats.max_depth_reached.max(new_depth);
                 }
             }
+
+            crate::limits::check(
+                crate::limits::Resource::GraphNodes,
+                nodes.len(),
+                options.max_nodes,
+                crate::limits::TRAVERSAL_HINT,
+            )?;
         }
     }
 
@@ -326,13 +351,16 @@ pub fn get_related_multi_hop(
         nodes,
         discovery_edges,
         stats,
+        truncated,
     })
 }
 
 /// Get edges for multiple memory IDs with per-node SQL limiting
 ///
-/// Uses ROW_NUMBER() window function to limit results per source node in SQL,
-/// preventing memory/time blowup on high-degree nodes.
+/// Reads the `graph_adjacency` table, where each live crossref is listed
+/// under both of its endpoints, and uses a ROW_NUMBER() window to keep the
+/// `limit_per_node` strongest edges per node and direction. This prevents
+/// memory/time blowup on high-degree nodes.
 fn get_edges_for_traversal_batch(
     conn: &Connection,
     memory_ids: &[MemoryId],
@@ -347,122 +375,49 @@ fn get_edges_for_traversal_batch(
     }
 
     let mut result: HashMap<MemoryId, Vec<CrossReference>> = HashMap::new();
-    let id_set: HashSet<MemoryId> = memory_ids.iter().cloned().collect();
+
+    let edge_type_clause = if edge_types.is_empty() {
+        String::new()
+    } else {
+        let types: Vec<String> = edge_types
+            .iter()
+            .map(|e| format!("'{}'", e.as_str()))
+            .collect();
+        format!(" AND a.edge_type IN ({})", types.join(", "))
+    };
+
+    let direction_clause = match direction {
+        TraversalDirection::Outgoing => " AND a.outgoing = 1",
+        TraversalDirection::Incoming => " AND a.outgoing = 0",
+        // A self-loop is listed twice under the same node; keep one
+        TraversalDirection::Both => " AND (a.outgoing = 1 OR a.neighbor_id != a.node_id)",
+    };
 
     // SQLite limit safety: chunk the IDs
     for chunk in memory_ids.chunks(100) {
         let placeholders = chunk.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
 
-        let edge_type_clause = if edge_types.is_empty() {
-            String::new()
-        } else {
-            let types: Vec<String> = edge_types
-                .iter()
-                .map(|e| format!("'{}'", e.as_str()))
-                .collect();
-            format!(" AND edge_type IN ({})", types.join(", "))
-        };
-
-        // Build query based on direction, using ROW_NUMBER() to limit per source node
-        let (partition_col, filter_clause) = match direction {
-            TraversalDirection::Outgoing => ("from_id", format!("from_id IN ({})", placeholders)),
-            TraversalDirection::Incoming => ("to_id", format!("to_id IN ({})", placeholders)),
-            TraversalDirection::Both => {
-                // For Both direction, we need a UNION approach to properly partition
-                // by source node from both directions
-                let query = format!(
-                    r#"
-                    WITH ranked_edges AS (
-                        SELECT *, ROW_NUMBER() OVER (
-                            PARTITION BY from_id ORDER BY score * confidence DESC
-                        ) as rn
-                        FROM crossrefs
-                        WHERE from_id IN ({placeholders}) AND valid_to IS NULL
-                          AND score >= ? AND confidence >= ?
-                          {edge_type_clause}
-                        UNION ALL
-                        SELECT *, ROW_NUMBER() OVER (
-                            PARTITION BY to_id ORDER BY score * confidence DESC
-                        ) as rn
-                        FROM crossrefs
-                        WHERE to_id IN ({placeholders}) AND from_id NOT IN ({placeholders}) AND valid_to IS NULL
-                          AND score >= ? AND confidence >= ?
-                          {edge_type_clause}
-                    )
-                    SELECT from_id, to_id, edge_type, score, confidence, strength, source,
-                           source_context, created_at, valid_from, valid_to, pinned, metadata
-                    FROM ranked_edges
-                    WHERE rn <= ?
-                    "#,
-                    placeholders = placeholders,
-                    edge_type_clause = edge_type_clause,
-                );
-
-                let mut stmt = conn.prepare(&query)?;
-                let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-                // First subquery params: from_id IN, min_score, min_confidence
-                for id in chunk {
-                    params.push(Box::new(*id));
-                }
-                params.push(Box::new(min_score));
-                params.push(Box::new(min_confidence));
-
-                // Second subquery params: to_id IN, from_id NOT IN, min_score, min_confidence
-                for id in chunk {
-                    params.push(Box::new(*id));
-                }
-                for id in chunk {
-                    params.push(Box::new(*id));
-                }
-                params.push(Box::new(min_score));
-                params.push(Box::new(min_confidence));
-
-                // Limit param
-                params.push(Box::new(limit_per_node as i64));
-
-                let param_refs: Vec<&dyn rusqlite::ToSql> =
-                    params.iter().map(|p| p.as_ref()).collect();
-
-                let crossrefs = stmt
-                    .query_map(param_refs.as_slice(), crossref_from_row)?
-                    .filter_map(|r| r.ok());
-
-                for crossref in crossrefs {
-                    if id_set.contains(&crossref.from_id) {
-                        result
-                            .entry(crossref.from_id)
-                            .or_default()
-                            .push(crossref.clone());
-                    }
-                    if id_set.contains(&crossref.to_id) && crossref.from_id != crossref.to_id {
-                        result.entry(crossref.to_id).or_default().push(crossref);
-                    }
-                }
-
-                continue; // Skip the common path below for Both direction
-            }
-        };
-
-        // Common path for Outgoing and Incoming directions
         let query = format!(
             r#"
             WITH ranked_edges AS (
-                SELECT *, ROW_NUMBER() OVER (
-                    PARTITION BY {partition_col} ORDER BY score * confidence DESC
+                SELECT a.node_id, a.outgoing, a.crossref_id, ROW_NUMBER() OVER (
+                    PARTITION BY a.node_id, a.outgoing ORDER BY a.weight DESC, a.crossref_id
                 ) as rn
-                FROM crossrefs
-                WHERE {filter_clause} AND valid_to IS NULL
-                  AND score >= ? AND confidence >= ?
-                  {edge_type_clause}
+                FROM graph_adjacency a
+                WHERE a.node_id IN ({placeholders})
+                  AND a.score >= ? AND a.confidence >= ?
+                  {direction_clause}{edge_type_clause}
             )
-            SELECT from_id, to_id, edge_type, score, confidence, strength, source,
-                   source_context, created_at, valid_from, valid_to, pinned, metadata
-            FROM ranked_edges
-            WHERE rn <= ?
+            SELECT r.node_id, c.from_id, c.to_id, c.edge_type, c.score, c.confidence,
+                   c.strength, c.source, c.source_context, c.created_at, c.valid_from,
+                   c.valid_to, c.pinned, c.metadata
+            FROM ranked_edges r
+            JOIN crossrefs c ON c.id = r.crossref_id
+            WHERE r.rn <= ?
+            ORDER BY r.node_id, r.outgoing DESC, r.rn
             "#,
-            partition_col = partition_col,
-            filter_clause = filter_clause,
+            placeholders = placeholders,
+            direction_clause = direction_clause,
             edge_type_clause = edge_type_clause,
         );
 
@@ -478,30 +433,81 @@ fn get_edges_for_traversal_batch(
 
         let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
-        let crossrefs = stmt
-            .query_map(param_refs.as_slice(), crossref_from_row)?
+        let edges = stmt
+            .query_map(param_refs.as_slice(), |row| {
+                Ok((row.get::<_, MemoryId>("node_id")?, crossref_from_row(row)?))
+            })?
             .filter_map(|r| r.ok());
 
-        for crossref in crossrefs {
-            match direction {
-                TraversalDirection::Outgoing => {
-                    if id_set.contains(&crossref.from_id) {
-                        result.entry(crossref.from_id).or_default().push(crossref);
-                    }
-                }
-                TraversalDirection::Incoming => {
-                    if id_set.contains(&crossref.to_id) {
-                        result.entry(crossref.to_id).or_default().push(crossref);
-                    }
-                }
-                TraversalDirection::Both => unreachable!(), // Handled above with continue
-            }
+        for (node_id, crossref) in edges {
+            result.entry(node_id).or_default().push(crossref);
         }
     }
 
     Ok(result)
 }
 
+/// Statistics from rebuilding the adjacency table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdjacencyRebuild {
+    /// Live cross-references indexed
+    pub edges: usize,
+    /// Rows added or removed to bring the table in line with `crossrefs`
+    pub repaired: usize,
+}
+
+/// Rebuild the `graph_adjacency` table from the live cross-references.
+///
+/// Triggers on `crossrefs` keep the table current, so this is only needed
+/// when it may have drifted, e.g. after crossrefs were written with the
+/// triggers absent or a database file was patched by hand.
+pub fn rebuild_adjacency(conn: &Connection) -> Result<AdjacencyRebuild> {
+    let stale: usize = conn.query_row(
+        r#"
+        WITH expected AS (
+            SELECT from_id AS node_id, 1 AS outgoing, id AS crossref_id, to_id AS neighbor_id,
+                   edge_type, score, confidence
+            FROM crossrefs WHERE valid_to IS NULL
+            UNION ALL
+            SELECT to_id, 0, id, from_id, edge_type, score, confidence
+            FROM crossrefs WHERE valid_to IS NULL
+        ),
+        actual AS (
+            SELECT node_id, outgoing, crossref_id, neighbor_id, edge_type, score, confidence
+            FROM graph_adjacency
+        )
+        SELECT (SELECT COUNT(*) FROM (SELECT * FROM expected EXCEPT SELECT * FROM actual))
+             + (SELECT COUNT(*) FROM (SELECT * FROM actual EXCEPT SELECT * FROM expected))
+        "#,
+        [],
+        |row| row.get::<_, i64>(0),
+    )? as usize;
+
+    conn.execute("DELETE FROM graph_adjacency", [])?;
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO graph_adjacency
+            (node_id, outgoing, crossref_id, neighbor_id, edge_type, score, confidence, weight)
+        SELECT from_id, 1, id, to_id, edge_type, score, confidence, score * confidence
+        FROM crossrefs WHERE valid_to IS NULL
+        UNION ALL
+        SELECT to_id, 0, id, from_id, edge_type, score, confidence, score * confidence
+        FROM crossrefs WHERE valid_to IS NULL
+        "#,
+        [],
+    )?;
+    let edges: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM crossrefs WHERE valid_to IS NULL",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(AdjacencyRebuild {
+        edges: edges as usize,
+        repaired: stale,
+    })
+}
+
 /// Get memories connected through shared entities for multiple memory IDs
 fn get_entity_connections_batch(
     conn: &Connection,
@@ -619,6 +625,207 @@ pub fn find_path(
     Ok(result.nodes.into_iter().find(|n| n.memory_id == to_id))
 }
 
+/// Base strength of a shared-entity connection, as in `get_related_multi_hop`
+const ENTITY_CONNECTION_SCORE: f32 = 0.5;
+
+/// A path found by `find_weighted_path`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightedPath {
+    #[serde(flatten)]
+    pub node: TraversalNode,
+    /// Sum of edge costs along the path, where each edge costs
+    /// `1 - score * confidence`
+    pub cost: f32,
+}
+
+/// Dijkstra frontier entry, ordered so the heap pops the cheapest path first
+/// and breaks cost ties by fewer hops
+#[derive(Debug, PartialEq)]
+struct Frontier {
+    cost: f32,
+    hops: usize,
+    id: MemoryId,
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.hops.cmp(&self.hops))
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A neighbor of a node: (id, edge label, edge strength, connection type)
+type Neighbor = (MemoryId, String, f32, ConnectionType);
+
+/// Find the cheapest path between two memories, preferring strong
+/// relationships over fewest hops.
+///
+/// Runs Dijkstra over cross-references in both directions, with each edge
+/// costing `1 - score * confidence`; shared entities count as edges of
+/// strength 0.5. Paths are limited to `max_depth` hops, so search states are
+/// `(memory, hops)` pairs: a node reached cheaply over many hops doesn't hide
+/// a costlier route to it that leaves enough hops to reach the target.
+pub fn find_weighted_path(
+    conn: &Connection,
+    from_id: MemoryId,
+    to_id: MemoryId,
+    max_depth: usize,
+) -> Result<Option<WeightedPath>> {
+    let limit_per_node = default_limit_per_hop();
+    let mut best: HashMap<(MemoryId, usize), f32> = HashMap::new();
+    // (node, hops) -> (previous node, edge label, edge strength, connection type)
+    let mut came_from: HashMap<(MemoryId, usize), Neighbor> = HashMap::new();
+    // Fewest hops each node was settled with; a later (costlier) state of
+    // the node is only worth expanding with fewer hops than that
+    let mut settled: HashMap<MemoryId, usize> = HashMap::new();
+    let mut neighbors_of: HashMap<MemoryId, Vec<Neighbor>> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    let mut found: Option<(f32, usize)> = None;
+
+    best.insert((from_id, 0), 0.0);
+    heap.push(Frontier {
+        cost: 0.0,
+        hops: 0,
+        id: from_id,
+    });
+
+    while let Some(Frontier { cost, hops, id }) = heap.pop() {
+        if crate::budget::checkpoint() {
+            break;
+        }
+        if settled.get(&id).is_some_and(|&h| h <= hops) {
+            continue;
+        }
+        settled.insert(id, hops);
+        if id == to_id {
+            found = Some((cost, hops));
+            break;
+        }
+        if hops >= max_depth {
+            continue;
+        }
+
+        let neighbors = match neighbors_of.entry(id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(load_weighted_neighbors(conn, id, limit_per_node)?)
+            }
+        };
+        for (neighbor_id, label, strength, connection_type) in neighbors.iter() {
+            if settled.get(neighbor_id).is_some_and(|&h| h <= hops + 1) {
+                continue;
+            }
+            let state = (*neighbor_id, hops + 1);
+            let next = cost + (1.0 - strength).clamp(0.0, 1.0);
+            if best.get(&state).is_none_or(|&c| next < c) {
+                best.insert(state, next);
+                came_from.insert(
+                    state,
+                    (id, label.clone(), *strength, connection_type.clone()),
+                );
+                heap.push(Frontier {
+                    cost: next,
+                    hops: hops + 1,
+                    id: *neighbor_id,
+                });
+            }
+        }
+    }
+
+    let Some((cost, depth)) = found else {
+        return Ok(None);
+    };
+
+    let mut path = vec![to_id];
+    let mut edge_path = Vec::new();
+    let mut cumulative_score = 1.0;
+    let mut connection_type = ConnectionType::Origin;
+    let mut current = (to_id, depth);
+    while let Some((prev, label, strength, conn_type)) = came_from.get(&current) {
+        if current.0 == to_id {
+            connection_type = conn_type.clone();
+        }
+        path.push(*prev);
+        edge_path.push(label.clone());
+        cumulative_score *= strength;
+        current = (*prev, current.1 - 1);
+    }
+    path.reverse();
+    edge_path.reverse();
+
+    Ok(Some(WeightedPath {
+        node: TraversalNode {
+            memory_id: to_id,
+            depth,
+            path,
+            edge_path,
+            cumulative_score,
+            connection_type,
+        },
+        cost,
+    }))
+}
+
+/// Cross-reference (both directions) and shared-entity neighbors of a node
+/// for `find_weighted_path`
+fn load_weighted_neighbors(
+    conn: &Connection,
+    id: MemoryId,
+    limit_per_node: usize,
+) -> Result<Vec<Neighbor>> {
+    let mut neighbors = Vec::new();
+    let crossrefs = get_edges_for_traversal_batch(
+        conn,
+        &[id],
+        &[],
+        0.0,
+        0.0,
+        TraversalDirection::Both,
+        limit_per_node,
+    )?;
+    for crossref in crossrefs.get(&id).into_iter().flatten() {
+        let neighbor_id = if crossref.from_id == id {
+            crossref.to_id
+        } else {
+            crossref.from_id
+        };
+        neighbors.push((
+            neighbor_id,
+            crossref.edge_type.as_str().to_string(),
+            crossref.score * crossref.confidence,
+            ConnectionType::CrossReference,
+        ));
+    }
+    let entity_connections = get_entity_connections_batch(conn, &[id], limit_per_node)?;
+    for (neighbor_id, entity_name) in entity_connections
+        .get(&id)
+        .into_iter()
+        .flatten()
+        .take(limit_per_node)
+    {
+        neighbors.push((
+            *neighbor_id,
+            format!("entity:{}", entity_name),
+            ENTITY_CONNECTION_SCORE,
+            ConnectionType::SharedEntity {
+                entity_name: entity_name.clone(),
+            },
+        ));
+    }
+    Ok(neighbors)
+}
+
 /// Get all memories within a certain graph distance
 pub fn get_neighborhood(
     conn: &Connection,
@@ -663,7 +870,7 @@ mod tests {
             event_duration_seconds: None,
             trigger_pattern: None,
             summary_of_id: None,
-                media_url: None,
+            media_url: None,
         };
         create_memory(conn, &input).unwrap().id
     }
@@ -824,6 +1031,90 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_find_weighted_path_prefers_strong_edges() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let id_a = create_test_memory(conn, "Start");
+                let id_b = create_test_memory(conn, "Strong hop 1");
+                let id_c = create_test_memory(conn, "Strong hop 2");
+                let id_d = create_test_memory(conn, "End");
+
+                // Direct but weak A -> D, versus a strong A -> B -> C -> D chain
+                create_test_crossref(conn, id_a, id_d, EdgeType::RelatedTo)?;
+                conn.execute(
+                    "UPDATE crossrefs SET score = 0.2, confidence = 0.5
+                     WHERE from_id = ? AND to_id = ?",
+                    rusqlite::params![id_a, id_d],
+                )?;
+                create_test_crossref(conn, id_a, id_b, EdgeType::RelatedTo)?;
+                create_test_crossref(conn, id_b, id_c, EdgeType::RelatedTo)?;
+                create_test_crossref(conn, id_c, id_d, EdgeType::DependsOn)?;
+
+                let bfs = find_path(conn, id_a, id_d, 5)?.unwrap();
+                assert_eq!(bfs.path, vec![id_a, id_d]);
+
+                let weighted = find_weighted_path(conn, id_a, id_d, 5)?.unwrap();
+                assert_eq!(weighted.node.path, vec![id_a, id_b, id_c, id_d]);
+                assert_eq!(weighted.node.depth, 3);
+                assert_eq!(
+                    weighted.node.edge_path,
+                    vec!["related_to", "related_to", "depends_on"]
+                );
+                assert!(weighted.cost < 0.9);
+
+                // With too few hops allowed, only the weak edge remains
+                let short = find_weighted_path(conn, id_a, id_d, 1)?.unwrap();
+                assert_eq!(short.node.path, vec![id_a, id_d]);
+                assert!((short.cost - 0.9).abs() < 1e-6);
+
+                let unrelated = create_test_memory(conn, "Island");
+                assert!(find_weighted_path(conn, id_a, unrelated, 5)?.is_none());
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_find_weighted_path_keeps_hop_budget_for_target() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let id_a = create_test_memory(conn, "Start");
+                let id_b = create_test_memory(conn, "Detour 1");
+                let id_c = create_test_memory(conn, "Detour 2");
+                let id_x = create_test_memory(conn, "Junction");
+                let id_d = create_test_memory(conn, "End");
+
+                // X is cheapest over the strong A -> B -> C -> X detour, but
+                // only the weak direct A -> X edge leaves a hop for X -> D
+                create_test_crossref(conn, id_a, id_b, EdgeType::RelatedTo)?;
+                create_test_crossref(conn, id_b, id_c, EdgeType::RelatedTo)?;
+                create_test_crossref(conn, id_c, id_x, EdgeType::RelatedTo)?;
+                create_test_crossref(conn, id_a, id_x, EdgeType::RelatedTo)?;
+                conn.execute(
+                    "UPDATE crossrefs SET score = 0.2, confidence = 0.5
+                     WHERE from_id = ? AND to_id = ?",
+                    rusqlite::params![id_a, id_x],
+                )?;
+                create_test_crossref(conn, id_x, id_d, EdgeType::DependsOn)?;
+
+                let unbounded = find_weighted_path(conn, id_a, id_d, 5)?.unwrap();
+                assert_eq!(unbounded.node.path, vec![id_a, id_b, id_c, id_x, id_d]);
+
+                let bounded = find_weighted_path(conn, id_a, id_d, 3)?.unwrap();
+                assert_eq!(bounded.node.path, vec![id_a, id_x, id_d]);
+                assert_eq!(bounded.node.depth, 2);
+                assert_eq!(bounded.node.edge_path, vec!["related_to", "depends_on"]);
+                assert!((bounded.cost - 0.9).abs() < 1e-6);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
     #[test]
     fn test_traversal_direction() {
         let storage = Storage::open_in_memory().unwrap();
@@ -890,4 +1181,119 @@ mod tests {
             })
             .unwrap();
     }
+
+    #[test]
+    fn test_adjacency_follows_crossref_writes() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let id_a = create_test_memory(conn, "A");
+                let id_b = create_test_memory(conn, "B");
+                let id_c = create_test_memory(conn, "C");
+                let adjacency = |node: MemoryId| -> Vec<(MemoryId, bool)> {
+                    let mut stmt = conn
+                        .prepare(
+                            "SELECT neighbor_id, outgoing FROM graph_adjacency
+                             WHERE node_id = ? ORDER BY neighbor_id",
+                        )
+                        .unwrap();
+                    stmt.query_map([node], |row| Ok((row.get(0)?, row.get(1)?)))
+                        .unwrap()
+                        .map(|r| r.unwrap())
+                        .collect()
+                };
+
+                create_test_crossref(conn, id_a, id_b, EdgeType::RelatedTo)?;
+                create_test_crossref(conn, id_b, id_c, EdgeType::RelatedTo)?;
+                assert_eq!(adjacency(id_b), vec![(id_a, false), (id_c, true)]);
+
+                conn.execute(
+                    "UPDATE crossrefs SET confidence = 0.25 WHERE from_id = ?",
+                    [id_a],
+                )?;
+                let weight: f64 = conn.query_row(
+                    "SELECT weight FROM graph_adjacency WHERE node_id = ?",
+                    [id_a],
+                    |row| row.get(0),
+                )?;
+                assert!((weight - 0.25).abs() < 1e-6);
+
+                crate::storage::queries::delete_crossref(conn, id_b, id_c, EdgeType::RelatedTo)?;
+                assert_eq!(adjacency(id_b), vec![(id_a, false)]);
+                assert!(adjacency(id_c).is_empty());
+
+                let options = TraversalOptions {
+                    depth: 3,
+                    include_entities: false,
+                    ..Default::default()
+                };
+                let result = get_related_multi_hop(conn, id_c, &options)?;
+                assert_eq!(result.nodes.len(), 1);
+
+                // Drift is reported and repaired
+                conn.execute("DELETE FROM graph_adjacency WHERE node_id = ?", [id_b])?;
+                assert_eq!(get_related_multi_hop(conn, id_b, &options)?.nodes.len(), 1);
+                let rebuild = rebuild_adjacency(conn)?;
+                assert_eq!(rebuild.edges, 1);
+                assert_eq!(rebuild.repaired, 1);
+                assert_eq!(get_related_multi_hop(conn, id_b, &options)?.nodes.len(), 2);
+                assert_eq!(rebuild_adjacency(conn)?.repaired, 0);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_traversal_stops_at_budget() {
+        use crate::budget::{self, CancelHandle};
+
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let id_a = create_test_memory(conn, "A");
+                let id_b = create_test_memory(conn, "B");
+                create_test_crossref(conn, id_a, id_b, EdgeType::RelatedTo)?;
+
+                let result = get_related_multi_hop(conn, id_a, &TraversalOptions::default())?;
+                assert!(!result.truncated);
+                assert_eq!(result.nodes.len(), 2);
+
+                let _budget = budget::enter(Some(std::time::Duration::ZERO), CancelHandle::new());
+                let result = get_related_multi_hop(conn, id_a, &TraversalOptions::default())?;
+                assert!(result.truncated);
+                assert_eq!(result.nodes.len(), 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_traversal_fails_over_node_limit() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let hub = create_test_memory(conn, "hub");
+                for i in 0..3 {
+                    let spoke = create_test_memory(conn, &format!("spoke {i}"));
+                    create_test_crossref(conn, hub, spoke, EdgeType::RelatedTo)?;
+                }
+
+                let options = TraversalOptions {
+                    max_nodes: 4,
+                    ..Default::default()
+                };
+                assert_eq!(get_related_multi_hop(conn, hub, &options)?.nodes.len(), 4);
+
+                let options = TraversalOptions {
+                    max_nodes: 3,
+                    ..Default::default()
+                };
+                let err = get_related_multi_hop(conn, hub, &options).unwrap_err();
+                assert!(matches!(err, crate::error::EngramError::LimitExceeded(_)));
+                assert!(err.to_string().contains("limit_per_hop"));
+                Ok(())
+            })
+            .unwrap();
+    }
 }
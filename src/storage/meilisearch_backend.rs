@@ -1,8 +1,7 @@
 use super::backend::{BatchCreateResult, BatchDeleteResult, HealthStatus, StorageBackend};
 use crate::error::EngramError;
-use crate::storage::queries::compute_content_hash;
 use crate::types::{
-    normalize_workspace, CreateMemoryInput, CrossReference, EdgeType, LifecycleState, ListOptions,
+    normalize_workspace, ContentHash, CreateMemoryInput, CrossReference, EdgeType, LifecycleState, ListOptions,
     MatchInfo, Memory, MemoryId, MemoryScope, MemoryTier, SearchOptions, SearchResult,
     SearchStrategy, SortField, SortOrder, StorageStats, UpdateMemoryInput, Visibility,
 };
@@ -71,7 +70,7 @@ impl From<&Memory> for MeilisearchMemory {
             version: m.version,
             has_embedding: m.has_embedding,
             expires_at: m.expires_at.map(|t| t.timestamp()),
-            content_hash: m.content_hash.clone(),
+            content_hash: m.content_hash.as_ref().map(|h| h.to_string()),
             event_time: m.event_time.map(|t| t.timestamp()),
             event_duration_seconds: m.event_duration_seconds,
             trigger_pattern: m.trigger_pattern.clone(),
@@ -128,7 +127,7 @@ fn build_memory_from_doc(doc: MeilisearchMemory) -> Memory {
         version: doc.version,
         has_embedding: doc.has_embedding,
         expires_at: opt_timestamp_to_datetime(doc.expires_at),
-        content_hash: doc.content_hash,
+        content_hash: doc.content_hash.map(ContentHash::from_stored),
         event_time: opt_timestamp_to_datetime(doc.event_time),
         event_duration_seconds: doc.event_duration_seconds,
         trigger_pattern: doc.trigger_pattern,
@@ -335,7 +334,7 @@ fn build_memory_from_input(
         }
     };
 
-    let content_hash = Some(compute_content_hash(&input.content));
+    let content_hash = Some(ContentHash::of(&input.content));
 
     Ok(Memory {
         id,
@@ -574,7 +573,7 @@ impl StorageBackend for MeilisearchBackend {
 
         if let Some(content) = input.content {
             memory.content = content;
-            memory.content_hash = Some(compute_content_hash(&memory.content));
+            memory.content_hash = Some(ContentHash::of(&memory.content));
             changed = true;
         }
         if let Some(memory_type) = input.memory_type {
@@ -802,6 +801,7 @@ impl StorageBackend for MeilisearchBackend {
                             highlights: vec![],
                             semantic_score: None,
                             keyword_score: Some(score),
+                            boosts: Vec::new(),
                         },
                     }
                 })
@@ -1277,7 +1277,7 @@ mod tests {
         assert_eq!(memory.workspace, "default");
         assert_eq!(memory.tier, MemoryTier::Permanent);
         assert!(memory.has_embedding);
-        assert_eq!(memory.content_hash, Some("abc123".to_string()));
+        assert_eq!(memory.content_hash, Some(ContentHash::from_stored("abc123")));
         assert!(matches!(memory.scope, MemoryScope::Global));
         assert_eq!(memory.visibility, Visibility::Private);
         assert_eq!(memory.lifecycle_state, LifecycleState::Active);
@@ -0,0 +1,587 @@
+//! Read-through cache of hot memories
+//!
+//! Pinned context and project instructions are fetched by ID on nearly every
+//! agent turn. `MemoryCache` keeps the most recently used memories in process
+//! so those lookups skip the row read and tag join:
+//! - LRU eviction bounded by entry count
+//! - Version-based refresh: a cached entry is only ever replaced by a newer
+//!   version of the same memory, so a slow reader can't clobber a fresher write
+//! - Write-through invalidation: once [`MemoryCache::watch`]ing a storage,
+//!   every write to a memory row drops that memory, whichever handler,
+//!   background job or sync wrote it; a full clear when a snapshot load
+//!   rewrites the store underneath us
+//! - Atomic hit/miss/invalidation/eviction counters
+//! - Buffered access tracking: cache hits accumulate access counts in memory
+//!   and write them back in one batch, either when enough have piled up or
+//!   when the server's periodic flush runs
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::realtime::{EventType, RealtimeEvent};
+use crate::types::{Memory, MemoryId};
+
+use super::queries::{get_memory, record_access_batch};
+use super::Storage;
+
+/// Default number of memories kept in the cache
+pub const DEFAULT_MEMORY_CACHE_CAPACITY: usize = 512;
+
+/// Default number of buffered cache-hit accesses that triggers an inline flush
+pub const DEFAULT_ACCESS_FLUSH_THRESHOLD: u64 = 256;
+
+/// Statistics for the memory cache
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub capacity: usize,
+    /// Cache-hit accesses not yet written back to the store
+    pub pending_accesses: u64,
+    /// Number of batched access-count flushes performed
+    pub access_flushes: u64,
+    /// Hit rate as percentage (0.0 - 100.0)
+    pub hit_rate: f64,
+}
+
+struct CacheEntry {
+    memory: Memory,
+    /// Logical clock value of the last access, for LRU eviction
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<MemoryId, CacheEntry>,
+    clock: u64,
+}
+
+/// Accesses recorded against one memory since the last flush
+struct PendingAccess {
+    count: i64,
+    last_accessed: DateTime<Utc>,
+}
+
+impl CacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+/// Thread-safe LRU cache of memories keyed by ID
+pub struct MemoryCache {
+    state: Mutex<CacheState>,
+    capacity: usize,
+    pending: Mutex<HashMap<MemoryId, PendingAccess>>,
+    pending_total: AtomicU64,
+    flush_threshold: u64,
+    /// Set while the cache writes its own buffered accesses back
+    flushing: AtomicBool,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    evictions: AtomicU64,
+    access_flushes: AtomicU64,
+}
+
+impl MemoryCache {
+    /// Create a cache holding at most `capacity` memories
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(CacheState::default()),
+            capacity,
+            pending: Mutex::new(HashMap::new()),
+            pending_total: AtomicU64::new(0),
+            flush_threshold: DEFAULT_ACCESS_FLUSH_THRESHOLD,
+            flushing: AtomicBool::new(false),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            access_flushes: AtomicU64::new(0),
+        }
+    }
+
+    /// Flush buffered accesses inline once `threshold` of them have piled up
+    /// (1 writes every hit through immediately)
+    pub fn with_access_flush_threshold(mut self, threshold: u64) -> Self {
+        self.flush_threshold = threshold.max(1);
+        self
+    }
+
+    /// Get a cached memory, dropping it if it has expired since it was cached
+    pub fn get(&self, id: MemoryId) -> Option<Memory> {
+        let mut state = self.state.lock();
+        let now = state.tick();
+        let expired = match state.entries.get_mut(&id) {
+            Some(entry) => {
+                if entry.memory.expires_at.is_some_and(|at| at <= Utc::now()) {
+                    true
+                } else {
+                    entry.last_used = now;
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Some(entry.memory.clone());
+                }
+            }
+            None => false,
+        };
+        if expired {
+            state.entries.remove(&id);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        None
+    }
+
+    /// Cache a memory unless a newer version of it is already cached
+    pub fn insert(&self, memory: Memory) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock();
+        let now = state.tick();
+        if let Some(entry) = state.entries.get_mut(&memory.id) {
+            if entry.memory.version <= memory.version {
+                entry.memory = memory;
+            }
+            entry.last_used = now;
+            return;
+        }
+        while state.entries.len() >= self.capacity {
+            let lru = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(id, _)| *id);
+            match lru {
+                Some(id) => {
+                    state.entries.remove(&id);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+                None => break,
+            }
+        }
+        state.entries.insert(
+            memory.id,
+            CacheEntry {
+                memory,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Replace a cached memory with a freshly written version.
+    ///
+    /// Does nothing if the memory isn't cached, so writes don't pull cold
+    /// memories in, and ignores versions older than the cached one.
+    pub fn refresh(&self, memory: &Memory) {
+        let mut state = self.state.lock();
+        if let Some(entry) = state.entries.get_mut(&memory.id) {
+            if entry.memory.version < memory.version {
+                entry.memory = memory.clone();
+                self.invalidations.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Drop a memory from the cache
+    pub fn invalidate(&self, id: MemoryId) {
+        if self.state.lock().entries.remove(&id).is_some() {
+            self.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Drop memories from the cache whenever `storage` writes their rows.
+    ///
+    /// The cache's own access-count flushes don't count: the cached copies
+    /// already carry those counts.
+    pub fn watch(self: &Arc<Self>, storage: &Storage) {
+        let cache = self.clone();
+        storage.on_memory_write(move |id| {
+            if !cache.flushing.load(Ordering::Relaxed) {
+                cache.invalidate(id);
+            }
+        });
+    }
+
+    /// Drop every cached memory
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        let cleared = state.entries.len() as u64;
+        state.entries.clear();
+        self.invalidations.fetch_add(cleared, Ordering::Relaxed);
+    }
+
+    /// Read-through lookup: serve `id` from the cache, loading it with
+    /// `get_memory` on a miss.
+    ///
+    /// Access tracking still happens on hits, but is buffered and written
+    /// back by [`MemoryCache::flush_accesses`]; the cached copy's counters
+    /// are bumped so it reads the same as a fresh row would.
+    pub fn get_or_load(&self, conn: &Connection, id: MemoryId) -> Result<Memory> {
+        let memory = match self.get(id) {
+            Some(memory) => {
+                self.buffer_access(id);
+                if self.pending_total.load(Ordering::Relaxed) >= self.flush_threshold {
+                    self.flush_accesses(conn)?;
+                }
+                memory
+            }
+            None => get_memory(conn, id)?,
+        };
+
+        let mut accessed = memory.clone();
+        accessed.access_count += 1;
+        accessed.last_accessed_at = Some(Utc::now());
+        self.insert(accessed);
+        Ok(memory)
+    }
+
+    fn buffer_access(&self, id: MemoryId) {
+        let now = Utc::now();
+        let mut pending = self.pending.lock();
+        let entry = pending.entry(id).or_insert(PendingAccess {
+            count: 0,
+            last_accessed: now,
+        });
+        entry.count += 1;
+        entry.last_accessed = now;
+        self.pending_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Write buffered access counts back to the store in one batch.
+    ///
+    /// Counts are only dropped from the buffer once written; if the write
+    /// fails they are merged back for the next flush. Returns the number of
+    /// memories updated.
+    pub fn flush_accesses(&self, conn: &Connection) -> Result<usize> {
+        let drained: Vec<(MemoryId, PendingAccess)> = {
+            let mut pending = self.pending.lock();
+            self.pending_total.store(0, Ordering::Relaxed);
+            pending.drain().collect()
+        };
+        if drained.is_empty() {
+            return Ok(0);
+        }
+
+        let batch: Vec<(MemoryId, i64, DateTime<Utc>)> = drained
+            .iter()
+            .map(|(id, access)| (*id, access.count, access.last_accessed))
+            .collect();
+        self.flushing.store(true, Ordering::Relaxed);
+        let written = record_access_batch(conn, &batch);
+        self.flushing.store(false, Ordering::Relaxed);
+        match written {
+            Ok(updated) => {
+                self.access_flushes.fetch_add(1, Ordering::Relaxed);
+                Ok(updated)
+            }
+            Err(e) => {
+                let mut pending = self.pending.lock();
+                for (id, access) in drained {
+                    self.pending_total
+                        .fetch_add(access.count as u64, Ordering::Relaxed);
+                    let entry = pending.entry(id).or_insert(PendingAccess {
+                        count: 0,
+                        last_accessed: access.last_accessed,
+                    });
+                    entry.count += access.count;
+                    entry.last_accessed = entry.last_accessed.max(access.last_accessed);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Keep the cache coherent with writes made outside this process's
+    /// handlers, as seen on the realtime event stream
+    pub fn apply_event(&self, event: &RealtimeEvent) {
+        match event.event_type {
+            EventType::MemoryUpdated | EventType::MemoryDeleted => {
+                for id in event.affected_ids() {
+                    self.invalidate(id);
+                }
+            }
+            // A pull replaces rows wholesale without per-memory events
+            EventType::SyncCompleted => self.clear(),
+            _ => {}
+        }
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> MemoryCacheStats {
+        let entries = self.state.lock().entries.len();
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        MemoryCacheStats {
+            hits,
+            misses,
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries,
+            capacity: self.capacity,
+            pending_accesses: self.pending_total.load(Ordering::Relaxed),
+            access_flushes: self.access_flushes.load(Ordering::Relaxed),
+            hit_rate: if total > 0 {
+                (hits as f64 / total as f64) * 100.0
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Get the number of cached memories
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEMORY_CACHE_CAPACITY)
+    }
+}
+
+/// Apply realtime events to the cache until the channel closes.
+///
+/// A lagged receiver may have missed invalidations, so the cache is cleared.
+pub async fn follow_events(
+    cache: std::sync::Arc<MemoryCache>,
+    mut events: tokio::sync::broadcast::Receiver<RealtimeEvent>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        match events.recv().await {
+            Ok(event) => cache.apply_event(&event),
+            Err(RecvError::Lagged(_)) => cache.clear(),
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_memory, delete_memory, peek_memory, update_memory};
+    use crate::storage::Storage;
+    use crate::types::{CreateMemoryInput, UpdateMemoryInput};
+
+    fn create(storage: &Storage, content: &str) -> Memory {
+        storage
+            .with_connection(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_read_through_hits_and_tracks_access() {
+        let storage = Storage::open_in_memory().unwrap();
+        let memory = create(&storage, "project instructions");
+        let cache = MemoryCache::new(8);
+
+        storage
+            .with_connection(|conn| {
+                let first = cache.get_or_load(conn, memory.id)?;
+                let second = cache.get_or_load(conn, memory.id)?;
+                assert_eq!(second.content, "project instructions");
+                assert_eq!(second.access_count, first.access_count + 1);
+
+                // The hit is buffered until flushed
+                let stored = peek_memory(conn, memory.id)?;
+                assert_eq!(stored.access_count, first.access_count + 1);
+                assert_eq!(cache.stats().pending_accesses, 1);
+
+                assert_eq!(cache.flush_accesses(conn)?, 1);
+                let stored = peek_memory(conn, memory.id)?;
+                assert_eq!(stored.access_count, first.access_count + 2);
+                Ok(())
+            })
+            .unwrap();
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.pending_accesses, 0);
+        assert_eq!(stats.access_flushes, 1);
+    }
+
+    #[test]
+    fn test_buffered_accesses_flush_at_threshold() {
+        let storage = Storage::open_in_memory().unwrap();
+        let memory = create(&storage, "pinned context");
+        let cache = MemoryCache::new(8).with_access_flush_threshold(3);
+
+        storage
+            .with_connection(|conn| {
+                let first = cache.get_or_load(conn, memory.id)?;
+                cache.get_or_load(conn, memory.id)?;
+                cache.get_or_load(conn, memory.id)?;
+                assert_eq!(
+                    peek_memory(conn, memory.id)?.access_count,
+                    first.access_count + 1
+                );
+
+                // The third hit crosses the threshold and writes all three
+                cache.get_or_load(conn, memory.id)?;
+                assert_eq!(
+                    peek_memory(conn, memory.id)?.access_count,
+                    first.access_count + 4
+                );
+                assert_eq!(cache.flush_accesses(conn)?, 0);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(cache.stats().access_flushes, 1);
+    }
+
+    #[test]
+    fn test_refresh_only_moves_versions_forward() {
+        let storage = Storage::open_in_memory().unwrap();
+        let memory = create(&storage, "v1");
+        let cache = MemoryCache::new(8);
+
+        let updated = storage
+            .with_connection(|conn| {
+                cache.get_or_load(conn, memory.id)?;
+                update_memory(
+                    conn,
+                    memory.id,
+                    &UpdateMemoryInput {
+                        content: Some("v2".to_string()),
+                        memory_type: None,
+                        tags: None,
+                        metadata: None,
+                        importance: None,
+                        scope: None,
+                        ttl_seconds: None,
+                        event_time: None,
+                        trigger_pattern: None,
+                        media_url: None,
+                    },
+                )
+            })
+            .unwrap();
+
+        cache.refresh(&updated);
+        assert_eq!(cache.get(memory.id).unwrap().content, "v2");
+
+        // A reader that loaded the old row before the write can't win
+        cache.insert(memory.clone());
+        cache.refresh(&memory);
+        assert_eq!(cache.get(memory.id).unwrap().content, "v2");
+
+        // Uncached memories aren't pulled in by writes
+        let other = create(&storage, "cold");
+        cache.refresh(&other);
+        assert!(cache.get(other.id).is_none());
+    }
+
+    #[test]
+    fn test_invalidation_on_delete_and_sync() {
+        let storage = Storage::open_in_memory().unwrap();
+        let a = create(&storage, "a");
+        let b = create(&storage, "b");
+        let cache = MemoryCache::new(8);
+
+        storage
+            .with_connection(|conn| {
+                cache.get_or_load(conn, a.id)?;
+                cache.get_or_load(conn, b.id)?;
+                delete_memory(conn, a.id)
+            })
+            .unwrap();
+
+        cache.apply_event(&RealtimeEvent::memory_deleted(a.id));
+        assert!(cache.get(a.id).is_none());
+        assert!(storage
+            .with_connection(|conn| cache.get_or_load(conn, a.id))
+            .is_err());
+        assert_eq!(cache.len(), 1);
+
+        cache.apply_event(&RealtimeEvent::sync_completed("pull", 1));
+        assert!(cache.is_empty());
+        assert_eq!(cache.stats().invalidations, 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let storage = Storage::open_in_memory().unwrap();
+        let ids: Vec<MemoryId> = (0..3)
+            .map(|i| create(&storage, &format!("m{}", i)).id)
+            .collect();
+        let cache = MemoryCache::new(2);
+
+        storage
+            .with_connection(|conn| {
+                cache.get_or_load(conn, ids[0])?;
+                cache.get_or_load(conn, ids[1])?;
+                // Touch the first so the second becomes least recently used
+                cache.get_or_load(conn, ids[0])?;
+                cache.get_or_load(conn, ids[2])?;
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(cache.get(ids[0]).is_some());
+        assert!(cache.get(ids[1]).is_none());
+        assert!(cache.get(ids[2]).is_some());
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_watch_drops_memories_on_any_write() {
+        let storage = Storage::open_in_memory().unwrap();
+        let memory = create(&storage, "pinned context");
+        let cache = Arc::new(MemoryCache::new(8));
+        cache.watch(&storage);
+
+        storage
+            .with_connection(|conn| {
+                cache.get_or_load(conn, memory.id)?;
+                cache.get_or_load(conn, memory.id)?;
+
+                // Our own access flush leaves the entry in place
+                assert_eq!(cache.flush_accesses(conn)?, 1);
+                assert!(cache.get(memory.id).is_some());
+
+                // A TTL set by any code path is honored on the next read
+                conn.execute(
+                    "UPDATE memories SET expires_at = ? WHERE id = ?",
+                    rusqlite::params![
+                        (Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+                        memory.id
+                    ],
+                )?;
+                assert!(cache.get(memory.id).is_none());
+                assert!(cache.get_or_load(conn, memory.id)?.expires_at.is_some());
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -5,7 +5,7 @@ use rusqlite::Connection;
 use crate::error::Result;
 
 /// Current schema version
-pub const SCHEMA_VERSION: i32 = 34;
+pub const SCHEMA_VERSION: i32 = 59;
 
 /// Run all migrations
 pub fn run_migrations(conn: &Connection) -> Result<()> {
@@ -158,10 +158,110 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
         migrate_v33(conn)?;
     }
 
-    if current_version < SCHEMA_VERSION {
+    if current_version < 34 {
         migrate_v34(conn)?;
     }
 
+    if current_version < 35 {
+        migrate_v35(conn)?;
+    }
+
+    if current_version < 36 {
+        migrate_v36(conn)?;
+    }
+
+    if current_version < 37 {
+        migrate_v37(conn)?;
+    }
+
+    if current_version < 38 {
+        migrate_v38(conn)?;
+    }
+
+    if current_version < 39 {
+        migrate_v39(conn)?;
+    }
+
+    if current_version < 40 {
+        migrate_v40(conn)?;
+    }
+
+    if current_version < 41 {
+        migrate_v41(conn)?;
+    }
+
+    if current_version < 42 {
+        migrate_v42(conn)?;
+    }
+
+    if current_version < 43 {
+        migrate_v43(conn)?;
+    }
+
+    if current_version < 44 {
+        migrate_v44(conn)?;
+    }
+
+    if current_version < 45 {
+        migrate_v45(conn)?;
+    }
+
+    if current_version < 46 {
+        migrate_v46(conn)?;
+    }
+
+    if current_version < 47 {
+        migrate_v47(conn)?;
+    }
+
+    if current_version < 48 {
+        migrate_v48(conn)?;
+    }
+
+    if current_version < 49 {
+        migrate_v49(conn)?;
+    }
+
+    if current_version < 50 {
+        migrate_v50(conn)?;
+    }
+
+    if current_version < 51 {
+        migrate_v51(conn)?;
+    }
+
+    if current_version < 52 {
+        migrate_v52(conn)?;
+    }
+
+    if current_version < 53 {
+        migrate_v53(conn)?;
+    }
+
+    if current_version < 54 {
+        migrate_v54(conn)?;
+    }
+
+    if current_version < 55 {
+        migrate_v55(conn)?;
+    }
+
+    if current_version < 56 {
+        migrate_v56(conn)?;
+    }
+
+    if current_version < 57 {
+        migrate_v57(conn)?;
+    }
+
+    if current_version < 58 {
+        migrate_v58(conn)?;
+    }
+
+    if current_version < SCHEMA_VERSION {
+        migrate_v59(conn)?;
+    }
+
     Ok(())
 }
 
@@ -1753,6 +1853,779 @@ fn migrate_v34(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// v35: Search ranking experiments (A/B testing)
+fn migrate_v35(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v35: Creating search experiment tables...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS search_experiments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            workspace TEXT,
+            variant_a TEXT NOT NULL DEFAULT '{}',
+            variant_b TEXT NOT NULL DEFAULT '{}',
+            traffic_b REAL NOT NULL DEFAULT 0.5,
+            status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active', 'stopped')),
+            created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+            stopped_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS search_experiment_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            experiment_id INTEGER NOT NULL,
+            arm TEXT NOT NULL CHECK(arm IN ('a', 'b')),
+            event TEXT NOT NULL CHECK(event IN ('exposure', 'used', 'useful', 'irrelevant')),
+            query_hash TEXT NOT NULL,
+            memory_id INTEGER,
+            created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_experiment_events_exp ON search_experiment_events(experiment_id, arm, event);
+
+        INSERT INTO schema_version (version) VALUES (35);
+        "#,
+    )?;
+
+    tracing::info!("Migration v35 complete: search experiment tables created");
+
+    Ok(())
+}
+
+/// v36: Bitemporal query indexes
+fn migrate_v36(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v36: Creating bitemporal query indexes...");
+
+    conn.execute_batch(
+        r#"
+        -- Record-time "as of" lookups: rewind each memory to the version then current
+        CREATE INDEX IF NOT EXISTS idx_versions_memory_created
+            ON memory_versions(memory_id, created_at);
+
+        -- Record-time visibility window (created_at <= T < valid_to)
+        CREATE INDEX IF NOT EXISTS idx_memories_record_time
+            ON memories(created_at, valid_to);
+
+        -- Event-time range scans scoped to a workspace
+        CREATE INDEX IF NOT EXISTS idx_memories_workspace_event_time
+            ON memories(workspace, event_time) WHERE event_time IS NOT NULL;
+
+        INSERT INTO schema_version (version) VALUES (36);
+        "#,
+    )?;
+
+    tracing::info!("Migration v36 complete: bitemporal indexes created");
+
+    Ok(())
+}
+
+/// v37: Contextual boost rules
+fn migrate_v37(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v37: Creating boost_rules table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS boost_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            condition TEXT NOT NULL DEFAULT '{}',
+            target TEXT NOT NULL DEFAULT '{}',
+            weight REAL NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        );
+
+        INSERT INTO schema_version (version) VALUES (37);
+        "#,
+    )?;
+
+    tracing::info!("Migration v37 complete: boost_rules table created");
+
+    Ok(())
+}
+
+/// v38: Superseded-memory markers (excluded from retrieval by default)
+fn migrate_v38(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v38: Adding superseded columns to memories...");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE memories ADD COLUMN superseded_by INTEGER;
+        ALTER TABLE memories ADD COLUMN superseded_at TEXT;
+        ALTER TABLE memories ADD COLUMN superseded_reason TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_memories_superseded
+            ON memories(superseded_at)
+            WHERE superseded_at IS NOT NULL;
+
+        INSERT INTO schema_version (version) VALUES (38);
+        "#,
+    )?;
+
+    tracing::info!("Migration v38 complete: superseded columns added");
+
+    Ok(())
+}
+
+/// v39: Fact review queue for long-unverified facts
+fn migrate_v39(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v39: Creating fact_review_queue table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS fact_review_queue (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            memory_id INTEGER NOT NULL UNIQUE REFERENCES memories(id) ON DELETE CASCADE,
+            reason TEXT NOT NULL,
+            access_count INTEGER NOT NULL DEFAULT 0,
+            queued_at TEXT NOT NULL,
+            resolved_at TEXT,
+            verdict TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_fact_review_queue_open
+            ON fact_review_queue(queued_at)
+            WHERE resolved_at IS NULL;
+
+        CREATE INDEX IF NOT EXISTS idx_memories_validation_status
+            ON memories(validation_status);
+
+        INSERT INTO schema_version (version) VALUES (39);
+        "#,
+    )?;
+
+    tracing::info!("Migration v39 complete: fact_review_queue table created");
+
+    Ok(())
+}
+
+/// v40: Structured fact store with one current value per subject and predicate
+fn migrate_v40(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v40: Creating fact_store table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS fact_store (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workspace TEXT NOT NULL DEFAULT 'default',
+            subject TEXT NOT NULL,
+            predicate TEXT NOT NULL,
+            object TEXT NOT NULL,
+            subject_key TEXT NOT NULL,
+            confidence REAL NOT NULL DEFAULT 1.0,
+            source_memory_id INTEGER REFERENCES memories(id) ON DELETE SET NULL,
+            created_at TEXT NOT NULL,
+            superseded_by INTEGER,
+            superseded_at TEXT
+        );
+
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_fact_store_current
+            ON fact_store(workspace, subject_key, predicate)
+            WHERE superseded_at IS NULL;
+        CREATE INDEX IF NOT EXISTS idx_fact_store_lookup
+            ON fact_store(workspace, subject_key, predicate, id);
+        CREATE INDEX IF NOT EXISTS idx_fact_store_source ON fact_store(source_memory_id);
+
+        INSERT INTO schema_version (version) VALUES (40);
+        "#,
+    )?;
+
+    tracing::info!("Migration v40 complete: fact_store table created");
+
+    Ok(())
+}
+
+/// v41: Item counts and ETA on sync_tasks
+fn migrate_v41(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v41: Adding progress columns to sync_tasks...");
+
+    conn.execute_batch(
+        r#"
+        -- Generic progress columns so long-running jobs other than Langfuse sync
+        -- (e.g. embedding rebuilds) can report counts and an ETA
+        ALTER TABLE sync_tasks ADD COLUMN items_total INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE sync_tasks ADD COLUMN items_processed INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE sync_tasks ADD COLUMN eta_seconds INTEGER;
+
+        INSERT INTO schema_version (version) VALUES (41);
+        "#,
+    )?;
+
+    tracing::info!("Migration v41 complete: sync_tasks progress columns added");
+
+    Ok(())
+}
+
+/// v42: Agent personas
+fn migrate_v42(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v42: Creating personas table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS personas (
+            name TEXT PRIMARY KEY,
+            description TEXT,
+            default_workspace TEXT,
+            ranking TEXT NOT NULL DEFAULT '{}',
+            pinned_memory_ids TEXT NOT NULL DEFAULT '[]',
+            tool_allowlist TEXT NOT NULL DEFAULT '[]',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO schema_version (version) VALUES (42);
+        "#,
+    )?;
+
+    tracing::info!("Migration v42 complete: personas table created");
+
+    Ok(())
+}
+
+/// v43: Langfuse trace → memory mapping
+fn migrate_v43(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v43: Creating langfuse_trace_memories table...");
+
+    conn.execute_batch(
+        r#"
+        -- One memory per trace and workspace, so re-syncing an overlapping
+        -- window updates or skips instead of duplicating. `fingerprint` hashes
+        -- what was last imported to tell a changed trace from an unchanged one.
+        CREATE TABLE IF NOT EXISTS langfuse_trace_memories (
+            trace_id TEXT NOT NULL,
+            workspace TEXT NOT NULL DEFAULT 'default',
+            memory_id INTEGER NOT NULL,
+            fingerprint TEXT NOT NULL DEFAULT '',
+            imported_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (trace_id, workspace)
+        );
+        CREATE INDEX IF NOT EXISTS idx_langfuse_trace_memories_memory
+            ON langfuse_trace_memories(memory_id);
+
+        -- Memories imported before the table existed; the oldest wins. The
+        -- empty fingerprint makes the next sync refresh them.
+        INSERT OR IGNORE INTO langfuse_trace_memories
+            (trace_id, workspace, memory_id, fingerprint, imported_at, updated_at)
+        SELECT json_extract(metadata, '$.langfuse_trace_id'), workspace, id, '',
+               created_at, updated_at
+        FROM memories
+        WHERE valid_to IS NULL
+          AND json_extract(metadata, '$.langfuse_trace_id') IS NOT NULL
+        ORDER BY id;
+
+        INSERT INTO schema_version (version) VALUES (43);
+        "#,
+    )?;
+
+    tracing::info!("Migration v43 complete: langfuse_trace_memories table created");
+
+    Ok(())
+}
+
+/// v44: Materialized adjacency for graph traversal
+fn migrate_v44(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v44: Creating graph_adjacency table...");
+
+    conn.execute_batch(
+        r#"
+        -- Every live crossref twice, once from each end, so a traversal step
+        -- in either direction is one range scan on `node_id` instead of a
+        -- UNION over `crossrefs`. Kept in step by the triggers below.
+        CREATE TABLE IF NOT EXISTS graph_adjacency (
+            node_id INTEGER NOT NULL,
+            outgoing INTEGER NOT NULL,
+            crossref_id INTEGER NOT NULL,
+            neighbor_id INTEGER NOT NULL,
+            edge_type TEXT NOT NULL,
+            score REAL NOT NULL,
+            confidence REAL NOT NULL,
+            weight REAL NOT NULL,
+            PRIMARY KEY (node_id, outgoing, crossref_id)
+        ) WITHOUT ROWID;
+        CREATE INDEX IF NOT EXISTS idx_graph_adjacency_weight
+            ON graph_adjacency(node_id, outgoing, weight DESC);
+
+        CREATE TRIGGER IF NOT EXISTS crossrefs_adjacency_ai AFTER INSERT ON crossrefs
+        WHEN new.valid_to IS NULL BEGIN
+            INSERT OR REPLACE INTO graph_adjacency
+                (node_id, outgoing, crossref_id, neighbor_id, edge_type, score, confidence, weight)
+            VALUES
+                (new.from_id, 1, new.id, new.to_id, new.edge_type, new.score,
+                 new.confidence, new.score * new.confidence),
+                (new.to_id, 0, new.id, new.from_id, new.edge_type, new.score,
+                 new.confidence, new.score * new.confidence);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS crossrefs_adjacency_ad AFTER DELETE ON crossrefs BEGIN
+            DELETE FROM graph_adjacency
+            WHERE node_id IN (old.from_id, old.to_id) AND crossref_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS crossrefs_adjacency_au
+        AFTER UPDATE OF from_id, to_id, edge_type, score, confidence, valid_to ON crossrefs
+        BEGIN
+            DELETE FROM graph_adjacency
+            WHERE node_id IN (old.from_id, old.to_id) AND crossref_id = old.id;
+            INSERT OR REPLACE INTO graph_adjacency
+                (node_id, outgoing, crossref_id, neighbor_id, edge_type, score, confidence, weight)
+            SELECT new.from_id, 1, new.id, new.to_id, new.edge_type, new.score,
+                   new.confidence, new.score * new.confidence
+            WHERE new.valid_to IS NULL
+            UNION ALL
+            SELECT new.to_id, 0, new.id, new.from_id, new.edge_type, new.score,
+                   new.confidence, new.score * new.confidence
+            WHERE new.valid_to IS NULL;
+        END;
+
+        INSERT OR REPLACE INTO graph_adjacency
+            (node_id, outgoing, crossref_id, neighbor_id, edge_type, score, confidence, weight)
+        SELECT from_id, 1, id, to_id, edge_type, score, confidence, score * confidence
+        FROM crossrefs WHERE valid_to IS NULL
+        UNION ALL
+        SELECT to_id, 0, id, from_id, edge_type, score, confidence, score * confidence
+        FROM crossrefs WHERE valid_to IS NULL;
+
+        INSERT INTO schema_version (version) VALUES (44);
+        "#,
+    )?;
+
+    tracing::info!("Migration v44 complete: graph_adjacency table created");
+
+    Ok(())
+}
+
+/// v45: Per-workspace memory defaults
+fn migrate_v45(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v45: Creating workspace_settings table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS workspace_settings (
+            workspace TEXT PRIMARY KEY,
+            base_tags TEXT NOT NULL DEFAULT '[]',
+            default_tier TEXT,
+            default_ttl_seconds INTEGER,
+            dedup_mode TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO schema_version (version) VALUES (45);
+        "#,
+    )?;
+
+    tracing::info!("Migration v45 complete: workspace_settings table created");
+
+    Ok(())
+}
+
+/// v46: Per-workspace semantic dedup threshold
+fn migrate_v46(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v46: Adding workspace_settings.dedup_threshold...");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE workspace_settings ADD COLUMN dedup_threshold REAL;
+
+        INSERT INTO schema_version (version) VALUES (46);
+        "#,
+    )?;
+
+    tracing::info!("Migration v46 complete: workspace_settings.dedup_threshold added");
+
+    Ok(())
+}
+
+/// v47: MinHash signature index for textual near-duplicate lookup
+fn migrate_v47(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v47: Creating text signature index...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS memory_text_signatures (
+            memory_id INTEGER PRIMARY KEY REFERENCES memories(id) ON DELETE CASCADE,
+            signature BLOB NOT NULL
+        );
+
+        -- One row per LSH band of each signature; memories sharing a
+        -- (band, bucket) are near-duplicate candidates
+        CREATE TABLE IF NOT EXISTS memory_signature_bands (
+            band INTEGER NOT NULL,
+            bucket INTEGER NOT NULL,
+            memory_id INTEGER NOT NULL REFERENCES memories(id) ON DELETE CASCADE,
+            PRIMARY KEY (band, bucket, memory_id)
+        ) WITHOUT ROWID;
+        CREATE INDEX IF NOT EXISTS idx_memory_signature_bands_memory
+            ON memory_signature_bands(memory_id);
+        "#,
+    )?;
+
+    let indexed = super::text_signatures::rebuild_text_signatures(conn)?.indexed;
+
+    conn.execute("INSERT INTO schema_version (version) VALUES (47)", [])?;
+
+    tracing::info!(
+        "Migration v47 complete: text signatures computed for {} memories",
+        indexed
+    );
+
+    Ok(())
+}
+
+/// v48: Search strategy outcome log and learned per-workspace thresholds
+fn migrate_v48(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v48: Creating search strategy learning tables...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS search_strategy_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workspace TEXT NOT NULL,
+            query_hash TEXT NOT NULL,
+            word_count INTEGER NOT NULL,
+            has_entities INTEGER NOT NULL,
+            strategy TEXT NOT NULL,
+            explored INTEGER NOT NULL DEFAULT 0,
+            used_at TEXT,
+            created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_strategy_log_query ON search_strategy_log(workspace, query_hash);
+        CREATE INDEX IF NOT EXISTS idx_strategy_log_class ON search_strategy_log(workspace, has_entities, created_at);
+
+        CREATE TABLE IF NOT EXISTS search_strategy_thresholds (
+            workspace TEXT NOT NULL,
+            has_entities INTEGER NOT NULL,
+            short_threshold INTEGER NOT NULL,
+            long_threshold INTEGER NOT NULL,
+            samples INTEGER NOT NULL,
+            used_rate REAL NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+            PRIMARY KEY (workspace, has_entities)
+        );
+
+        INSERT INTO schema_version (version) VALUES (48);
+        "#,
+    )?;
+
+    tracing::info!("Migration v48 complete: search strategy learning tables created");
+
+    Ok(())
+}
+
+/// v49: Embedding dimensionality reduction state and kept originals
+fn migrate_v49(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v49: Creating embedding reduction tables...");
+
+    conn.execute_batch(
+        r#"
+        -- Full-size vectors replaced by reduced ones, kept for restoring
+        CREATE TABLE IF NOT EXISTS embedding_originals (
+            memory_id INTEGER PRIMARY KEY REFERENCES memories(id) ON DELETE CASCADE,
+            embedding BLOB NOT NULL,
+            model TEXT NOT NULL,
+            dimensions INTEGER NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        -- The active reduction (single row); PCA models are stored as JSON
+        CREATE TABLE IF NOT EXISTS embedding_reduction (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            method TEXT NOT NULL,
+            target_dims INTEGER NOT NULL,
+            source_dims INTEGER,
+            model TEXT,
+            fitted_at TEXT NOT NULL
+        );
+
+        INSERT INTO schema_version (version) VALUES (49);
+        "#,
+    )?;
+
+    tracing::info!("Migration v49 complete: embedding reduction tables created");
+
+    Ok(())
+}
+
+/// v50: Freshness contracts per tag and the memories violating them
+fn migrate_v50(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v50: Creating freshness contract tables...");
+
+    conn.execute_batch(
+        r#"
+        -- workspace NULL applies the contract to every workspace
+        CREATE TABLE IF NOT EXISTS freshness_contracts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tag TEXT NOT NULL,
+            workspace TEXT,
+            max_age_days INTEGER NOT NULL,
+            penalty REAL NOT NULL DEFAULT 0.2,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_freshness_contracts_tag ON freshness_contracts(tag);
+
+        CREATE TABLE IF NOT EXISTS freshness_violations (
+            memory_id INTEGER PRIMARY KEY REFERENCES memories(id) ON DELETE CASCADE,
+            contract_id INTEGER NOT NULL REFERENCES freshness_contracts(id) ON DELETE CASCADE,
+            last_verified_at TEXT NOT NULL,
+            due_at TEXT NOT NULL,
+            flagged_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_freshness_violations_contract ON freshness_violations(contract_id);
+
+        INSERT INTO schema_version (version) VALUES (50);
+        "#,
+    )?;
+
+    tracing::info!("Migration v50 complete: freshness contract tables created");
+
+    Ok(())
+}
+
+/// v51: Checkpoints for resumable embedding model migrations
+fn migrate_v51(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v51: Creating embedding_migrations table...");
+
+    conn.execute_batch(
+        r#"
+        -- cursor is the last memory id re-embedded; runs resume after it
+        CREATE TABLE IF NOT EXISTS embedding_migrations (
+            id TEXT PRIMARY KEY,
+            target_model TEXT NOT NULL,
+            target_dimensions INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'running',
+            cursor INTEGER NOT NULL DEFAULT 0,
+            batch_size INTEGER NOT NULL,
+            requests_per_minute INTEGER,
+            items_total INTEGER NOT NULL DEFAULT 0,
+            items_embedded INTEGER NOT NULL DEFAULT 0,
+            items_skipped INTEGER NOT NULL DEFAULT 0,
+            eta_seconds INTEGER,
+            last_error TEXT,
+            started_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            completed_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_embedding_migrations_status ON embedding_migrations(status);
+
+        INSERT INTO schema_version (version) VALUES (51);
+        "#,
+    )?;
+
+    tracing::info!("Migration v51 complete: embedding_migrations table created");
+
+    Ok(())
+}
+
+/// v52: Share links exposing a workspace read-only over HTTP
+fn migrate_v52(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v52: Creating workspace_shares table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS workspace_shares (
+            token TEXT PRIMARY KEY,
+            workspace TEXT NOT NULL,
+            label TEXT,
+            requests_per_minute INTEGER NOT NULL DEFAULT 60,
+            created_at TEXT NOT NULL,
+            expires_at TEXT,
+            revoked_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_workspace_shares_workspace ON workspace_shares(workspace);
+
+        INSERT INTO schema_version (version) VALUES (52);
+        "#,
+    )?;
+
+    tracing::info!("Migration v52 complete: workspace_shares table created");
+
+    Ok(())
+}
+
+/// v53: Dead-letter table for embeddings that failed permanently
+fn migrate_v53(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v53: Creating embedding_dead_letters table...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS embedding_dead_letters (
+            memory_id INTEGER PRIMARY KEY,
+            priority TEXT NOT NULL DEFAULT 'normal',
+            error TEXT NOT NULL,
+            attempts INTEGER NOT NULL,
+            dead_at TEXT NOT NULL,
+            FOREIGN KEY (memory_id) REFERENCES memories(id) ON DELETE CASCADE
+        );
+
+        INSERT INTO schema_version (version) VALUES (53);
+        "#,
+    )?;
+
+    tracing::info!("Migration v53 complete: embedding_dead_letters table created");
+
+    Ok(())
+}
+
+/// Persistent TF-IDF vocabulary (v54)
+///
+/// Created empty: the vocabulary is built when a TF-IDF server first starts
+/// or on `memory_rebuild_vocabulary`.
+fn migrate_v54(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v54: Creating TF-IDF vocabulary tables...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS tfidf_vocabulary (
+            term TEXT PRIMARY KEY,
+            doc_freq INTEGER NOT NULL,
+            revision INTEGER NOT NULL
+        ) WITHOUT ROWID;
+        CREATE INDEX IF NOT EXISTS idx_tfidf_vocabulary_revision
+            ON tfidf_vocabulary(revision);
+
+        CREATE TABLE IF NOT EXISTS tfidf_corpus (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            documents INTEGER NOT NULL,
+            generation INTEGER NOT NULL,
+            built_at TEXT NOT NULL
+        );
+
+        INSERT INTO schema_version (version) VALUES (54);
+        "#,
+    )?;
+
+    tracing::info!("Migration v54 complete: TF-IDF vocabulary tables created");
+
+    Ok(())
+}
+
+/// Embedding representation (v55)
+///
+/// `summarized_by` names the summarizer when a memory was too long to embed
+/// and its summary was embedded instead; NULL means the content itself.
+fn migrate_v55(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v55: Recording summarized embeddings...");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE embeddings ADD COLUMN summarized_by TEXT;
+
+        INSERT INTO schema_version (version) VALUES (55);
+        "#,
+    )?;
+
+    tracing::info!("Migration v55 complete: embeddings.summarized_by added");
+
+    Ok(())
+}
+
+/// Embedding spaces (v56)
+///
+/// `embedding_space` records the model queries are embedded with, so that
+/// similarity is only computed between vectors of that model.
+/// `embedding_adapters` holds linear projections from another model's space
+/// into it, fitted on memories embedded with both.
+fn migrate_v56(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v56: Creating embedding space tables...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS embedding_space (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            model TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS embedding_adapters (
+            source_model TEXT NOT NULL,
+            source_dims INTEGER NOT NULL,
+            target_model TEXT NOT NULL,
+            target_dims INTEGER NOT NULL,
+            weights BLOB NOT NULL,
+            samples INTEGER NOT NULL,
+            holdout_cosine REAL,
+            fitted_at TEXT NOT NULL,
+            PRIMARY KEY (source_model, source_dims, target_model)
+        );
+
+        INSERT INTO schema_version (version) VALUES (56);
+        "#,
+    )?;
+
+    tracing::info!("Migration v56 complete: embedding space tables created");
+
+    Ok(())
+}
+
+/// v57: Per-workspace timezone and date order for date expressions
+fn migrate_v57(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v57: Adding workspace_settings.timezone and date_order...");
+
+    conn.execute_batch(
+        r#"
+        ALTER TABLE workspace_settings ADD COLUMN timezone TEXT;
+        ALTER TABLE workspace_settings ADD COLUMN date_order TEXT;
+
+        INSERT INTO schema_version (version) VALUES (57);
+        "#,
+    )?;
+
+    tracing::info!("Migration v57 complete: workspace_settings.timezone and date_order added");
+
+    Ok(())
+}
+
+/// v58: Tag synonym groups and tag normalization rules
+fn migrate_v58(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v58: Adding tag_synonyms and tag_normalization...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS tag_synonyms (
+            alias TEXT PRIMARY KEY COLLATE NOCASE,
+            canonical TEXT NOT NULL COLLATE NOCASE,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_tag_synonyms_canonical ON tag_synonyms(canonical);
+
+        CREATE TABLE IF NOT EXISTS tag_normalization (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            rules TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO schema_version (version) VALUES (58);
+        "#,
+    )?;
+
+    tracing::info!("Migration v58 complete: tag_synonyms and tag_normalization added");
+
+    Ok(())
+}
+
+/// v59: Tag governance policy
+fn migrate_v59(conn: &Connection) -> Result<()> {
+    tracing::info!("Migration v59: Adding tag_policy...");
+
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS tag_policy (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            policy TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO schema_version (version) VALUES (59);
+        "#,
+    )?;
+
+    tracing::info!("Migration v59 complete: tag_policy added");
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1774,12 +2647,12 @@ mod tests {
                 |row| row.get(0),
             )
             .expect("query schema version");
-        assert_eq!(version, 34);
+        assert_eq!(version, 59);
     }
 
     #[test]
     fn test_schema_version_constant_is_19() {
-        assert_eq!(SCHEMA_VERSION, 34);
+        assert_eq!(SCHEMA_VERSION, 59);
     }
 
     #[test]
@@ -1934,7 +2807,7 @@ mod tests {
                 |row| row.get(0),
             )
             .expect("query schema version");
-        assert_eq!(version, 34, "should reach v34 after full migration");
+        assert_eq!(version, 59, "should reach v59 after full migration");
 
         // Verify both new tables exist
         let auto_links_exists: i32 = conn
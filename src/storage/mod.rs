@@ -21,22 +21,35 @@ pub mod agent_registry;
 mod audit;
 pub mod auto_linker;
 pub mod backend;
+pub mod bulk;
 #[cfg(feature = "emergent-graph")]
 pub mod clustering;
 mod confidence;
 mod connection;
 pub mod entity_queries;
+pub mod federation;
 pub mod filter;
 pub mod graph_queries;
 pub mod identity_links;
 pub mod image_storage;
 pub mod memory_blocks;
-mod migrations;
+pub mod memory_cache;
+pub(crate) mod migrations;
+pub mod personas;
+pub mod preferences;
 pub mod queries;
 pub mod scope_grants;
 pub mod scoping;
 pub mod sqlite_backend;
+pub mod supersession;
+pub mod tag_policy;
+pub mod tag_synonyms;
 pub mod temporal;
+pub mod text_signatures;
+pub mod tfidf_vocabulary;
+pub mod workspace_config;
+pub mod workspace_ops;
+pub mod workspace_shares;
 
 #[cfg(feature = "meilisearch")]
 pub mod meilisearch_backend;
@@ -64,6 +77,7 @@ pub use backend::{
     CloudSyncBackend, HealthStatus, StorageBackend, StorageStats, SyncDelta as BackendSyncDelta,
     SyncResult, SyncState, TransactionalBackend,
 };
+pub use bulk::{bulk_create_memories, BulkWriteOptions, BulkWriteResult, DEFAULT_BULK_BATCH_SIZE};
 #[cfg(feature = "emergent-graph")]
 pub use clustering::{
     get_cluster, list_clusters, run_louvain_clustering, Cluster, ClusteringResult, LouvainOptions,
@@ -75,9 +89,11 @@ pub use entity_queries::{
     get_memories_for_entity, link_entity_to_memory, list_entities, search_entities,
     unlink_entity_from_memory, upsert_entity, EntityStats,
 };
+pub use federation::{Federation, FederationConfig, FederationSource, FederationSourceInfo};
 pub use graph_queries::{
-    find_path, get_neighborhood, get_related_multi_hop, ConnectionType, TraversalDirection,
-    TraversalNode, TraversalOptions, TraversalResult, TraversalStats,
+    find_path, find_weighted_path, get_neighborhood, get_related_multi_hop, rebuild_adjacency,
+    AdjacencyRebuild, ConnectionType, TraversalDirection, TraversalNode, TraversalOptions,
+    TraversalResult, TraversalStats, WeightedPath,
 };
 pub use identity_links::{
     add_alias, create_identity, delete_identity, get_aliases, get_identity, get_identity_memories,
@@ -93,6 +109,16 @@ pub use image_storage::{
 pub use meilisearch_backend::MeilisearchBackend;
 #[cfg(feature = "meilisearch")]
 pub use meilisearch_indexer::MeilisearchIndexer;
+pub use memory_cache::{MemoryCache, MemoryCacheStats};
+pub use personas::{
+    delete_persona, get_persona, list_personas, upsert_persona, ActivePersona, Persona,
+    UpsertPersonaInput,
+};
+pub use preferences::{
+    get_preference, list_preferences, preference_history, set_preference, unset_preference,
+    Preference, PreferenceChange, PreferenceContext, PreferenceHistoryEntry, PreferenceScope,
+    ResolvedPreference,
+};
 pub use queries::{
     acknowledge_share,
     boost_memory,
@@ -147,8 +173,43 @@ pub use queries::{
     TagValidationResult,
 };
 pub use sqlite_backend::SqliteBackend;
+pub use supersession::{
+    get_superseded, is_superseded, list_superseded, mark_superseded, restore_superseded,
+    SupersededMemory,
+};
+pub use tag_policy::{
+    check_tag_policy, get_tag_policy, set_tag_policy, tag_policy_report, MemoryTagViolations,
+    TagPolicy, TagPolicyReport, TagPolicyReportOptions, TagPolicyRule, TagPolicyViolation,
+};
+pub use tag_synonyms::{
+    add_tag_synonyms, expand_tag_filter, get_tag_normalization, list_tag_synonyms,
+    remove_tag_synonym, resolve_tag, set_tag_normalization, suggest_tag_synonyms,
+    TagNormalization, TagRewriteReport, TagSynonymGroup, TagSynonymSuggestOptions,
+    TagSynonymSuggestion,
+};
 pub use temporal::{
     MemorySnapshot, StateDiff, TemporalMemory, TemporalQueryEngine, TemporalQueryOptions,
 };
+pub use text_signatures::{
+    find_signature_matches, index_signature, rebuild_text_signatures, SignatureMatch,
+    SignatureRebuild,
+};
+pub use tfidf_vocabulary::{
+    observe_tfidf_document, rebuild_tfidf_vocabulary, tfidf_vocabulary_stats, VocabularyStats,
+};
 #[cfg(feature = "turso")]
 pub use turso_backend::{TursoBackend, TursoConfig};
+pub use workspace_config::{
+    delete_workspace_config, get_workspace_config, list_workspace_configs, set_workspace_config,
+    set_workspace_dedup_threshold, workspace_temporal_context, SetWorkspaceConfigInput,
+    WorkspaceConfig,
+};
+pub use workspace_ops::{
+    merge_workspaces, split_workspace, DuplicateMerge, MergeOptions, WorkspaceMergeReport,
+    WorkspaceSplitReport,
+};
+pub use workspace_shares::{
+    create_workspace_share, get_workspace_share, list_workspace_shares, resolve_workspace_share,
+    revoke_workspace_share, CreateWorkspaceShareInput, WorkspaceShare,
+    DEFAULT_SHARE_REQUESTS_PER_MINUTE,
+};
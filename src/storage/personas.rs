@@ -0,0 +1,290 @@
+//! Agent personas
+//!
+//! A persona is a named bundle of retrieval behavior for one kind of agent,
+//! stored in the `personas` table (schema v42):
+//! - a default workspace for retrieval calls that don't name one
+//! - a ranking profile, applied on top of the server's search config
+//! - pinned memories returned as a context pack on activation
+//! - a tool allowlist (empty allows every tool)
+//!
+//! One server can then host a "coder" agent and a "PA" agent side by side,
+//! each activating its own persona at session start. The active persona is
+//! process state held in [`ActivePersona`], not a database row.
+
+use chrono::Utc;
+use parking_lot::RwLock;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::search::experiments::RankingVariant;
+use crate::types::{normalize_workspace, MemoryId};
+
+/// Tools that stay callable whatever the active persona's allowlist says,
+/// so an agent can always switch or drop its persona
+pub const PERSONA_CONTROL_TOOLS: &[&str] = &[
+    "persona_activate",
+    "persona_deactivate",
+    "persona_get",
+    "persona_list",
+];
+
+/// A named agent persona
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Persona {
+    pub name: String,
+    pub description: Option<String>,
+    /// Workspace used by retrieval tools when the call doesn't specify one
+    pub default_workspace: Option<String>,
+    /// Ranking overrides applied to every search while active
+    pub ranking: RankingVariant,
+    /// Memories loaded into the context pack on activation, in order
+    pub pinned_memory_ids: Vec<MemoryId>,
+    /// Tools this persona may call; empty means all tools
+    pub tool_allowlist: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Persona {
+    /// Whether `tool` may be called while this persona is active
+    pub fn allows_tool(&self, tool: &str) -> bool {
+        self.tool_allowlist.is_empty()
+            || PERSONA_CONTROL_TOOLS.contains(&tool)
+            || self.tool_allowlist.iter().any(|t| t == tool)
+    }
+}
+
+/// Input for creating or replacing a persona (upsert by name)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpsertPersonaInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default_workspace: Option<String>,
+    #[serde(default)]
+    pub ranking: RankingVariant,
+    #[serde(default)]
+    pub pinned_memory_ids: Vec<MemoryId>,
+    #[serde(default)]
+    pub tool_allowlist: Vec<String>,
+}
+
+/// Parse a Persona from a rusqlite row.
+///
+/// Columns expected in order: name, description, default_workspace, ranking,
+/// pinned_memory_ids, tool_allowlist, created_at, updated_at
+fn persona_from_row(row: &rusqlite::Row) -> rusqlite::Result<Persona> {
+    let ranking_str: String = row.get(3)?;
+    let pinned_str: String = row.get(4)?;
+    let allowlist_str: String = row.get(5)?;
+
+    Ok(Persona {
+        name: row.get(0)?,
+        description: row.get(1)?,
+        default_workspace: row.get(2)?,
+        ranking: serde_json::from_str(&ranking_str).unwrap_or_default(),
+        pinned_memory_ids: serde_json::from_str(&pinned_str).unwrap_or_default(),
+        tool_allowlist: serde_json::from_str(&allowlist_str).unwrap_or_default(),
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+const PERSONA_COLUMNS: &str = "name, description, default_workspace, ranking, pinned_memory_ids, \
+                               tool_allowlist, created_at, updated_at";
+
+/// Create a persona, or replace every setting of an existing one with the
+/// same name. `created_at` is preserved on replace.
+pub fn upsert_persona(conn: &Connection, input: &UpsertPersonaInput) -> Result<Persona> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "persona name must not be empty".to_string(),
+        ));
+    }
+    let default_workspace = input
+        .default_workspace
+        .as_deref()
+        .map(normalize_workspace)
+        .transpose()
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))?;
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        r#"
+        INSERT INTO personas
+            (name, description, default_workspace, ranking, pinned_memory_ids,
+             tool_allowlist, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(name) DO UPDATE SET
+            description       = excluded.description,
+            default_workspace = excluded.default_workspace,
+            ranking           = excluded.ranking,
+            pinned_memory_ids = excluded.pinned_memory_ids,
+            tool_allowlist    = excluded.tool_allowlist,
+            updated_at        = excluded.updated_at
+        "#,
+        params![
+            name,
+            input.description,
+            default_workspace,
+            serde_json::to_string(&input.ranking)?,
+            serde_json::to_string(&input.pinned_memory_ids)?,
+            serde_json::to_string(&input.tool_allowlist)?,
+            now,
+            now,
+        ],
+    )?;
+
+    get_persona(conn, name)?
+        .ok_or_else(|| EngramError::Storage("Persona not found after insert".to_string()))
+}
+
+/// Retrieve a persona by name.
+pub fn get_persona(conn: &Connection, name: &str) -> Result<Option<Persona>> {
+    conn.query_row(
+        &format!("SELECT {} FROM personas WHERE name = ?", PERSONA_COLUMNS),
+        params![name],
+        persona_from_row,
+    )
+    .optional()
+    .map_err(EngramError::from)
+}
+
+/// List all personas by name.
+pub fn list_personas(conn: &Connection) -> Result<Vec<Persona>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM personas ORDER BY name",
+        PERSONA_COLUMNS
+    ))?;
+    let personas = stmt
+        .query_map([], persona_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(personas)
+}
+
+/// Delete a persona. Returns `true` if it existed.
+pub fn delete_persona(conn: &Connection, name: &str) -> Result<bool> {
+    let affected = conn.execute("DELETE FROM personas WHERE name = ?", params![name])?;
+    Ok(affected > 0)
+}
+
+/// The persona active for this server process, if any
+#[derive(Debug, Default)]
+pub struct ActivePersona {
+    persona: RwLock<Option<Persona>>,
+}
+
+impl ActivePersona {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The active persona
+    pub fn get(&self) -> Option<Persona> {
+        self.persona.read().clone()
+    }
+
+    /// Make `persona` active, returning the one it replaces
+    pub fn activate(&self, persona: Persona) -> Option<Persona> {
+        self.persona.write().replace(persona)
+    }
+
+    /// Drop the active persona, returning it
+    pub fn deactivate(&self) -> Option<Persona> {
+        self.persona.write().take()
+    }
+
+    /// Refresh the active persona after it was edited, or drop it if it was
+    /// deleted (`None`)
+    pub fn reload(&self, name: &str, persona: Option<Persona>) {
+        let mut active = self.persona.write();
+        if active.as_ref().is_some_and(|p| p.name == name) {
+            *active = persona;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+
+    #[test]
+    fn test_upsert_preserves_created_at_and_replaces_settings() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let coder = upsert_persona(
+                    conn,
+                    &UpsertPersonaInput {
+                        name: "coder".to_string(),
+                        default_workspace: Some("engram".to_string()),
+                        ranking: RankingVariant {
+                            keyword_weight: Some(0.7),
+                            ..Default::default()
+                        },
+                        pinned_memory_ids: vec![3, 1],
+                        tool_allowlist: vec!["memory_search".to_string()],
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(coder.ranking.keyword_weight, Some(0.7));
+                assert_eq!(coder.pinned_memory_ids, vec![3, 1]);
+
+                let replaced = upsert_persona(
+                    conn,
+                    &UpsertPersonaInput {
+                        name: "coder".to_string(),
+                        description: Some("writes code".to_string()),
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(replaced.created_at, coder.created_at);
+                assert_eq!(replaced.default_workspace, None);
+                assert!(replaced.tool_allowlist.is_empty());
+
+                upsert_persona(
+                    conn,
+                    &UpsertPersonaInput {
+                        name: "assistant".to_string(),
+                        ..Default::default()
+                    },
+                )?;
+                let names: Vec<String> = list_personas(conn)?.into_iter().map(|p| p.name).collect();
+                assert_eq!(names, vec!["assistant", "coder"]);
+
+                assert!(delete_persona(conn, "coder")?);
+                assert!(get_persona(conn, "coder")?.is_none());
+                assert!(upsert_persona(conn, &UpsertPersonaInput::default()).is_err());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_allowlist_always_permits_persona_control() {
+        let persona = Persona {
+            name: "pa".to_string(),
+            description: None,
+            default_workspace: None,
+            ranking: RankingVariant::default(),
+            pinned_memory_ids: vec![],
+            tool_allowlist: vec!["memory_search".to_string()],
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        assert!(persona.allows_tool("memory_search"));
+        assert!(persona.allows_tool("persona_deactivate"));
+        assert!(!persona.allows_tool("memory_delete"));
+
+        let active = ActivePersona::new();
+        active.activate(persona.clone());
+        active.reload("other", None);
+        assert_eq!(active.get(), Some(persona));
+        active.reload("pa", None);
+        assert!(active.get().is_none());
+    }
+}
@@ -0,0 +1,608 @@
+//! Preference memories with scoped precedence.
+//!
+//! A preference is a [`MemoryType::Preference`] memory whose metadata carries a
+//! namespaced key (e.g. `editor.indent_width`), a JSON value and a scope. The
+//! same key may be set at several scopes; lookups resolve the most specific
+//! one that applies, in the order global < workspace < project < session.
+//! Changing a value updates the memory in place, so every change is kept in
+//! `memory_versions` and available through [`preference_history`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{EngramError, Result};
+use crate::storage::queries::{create_memory, delete_memory, get_memory_versions, update_memory};
+use crate::types::{CreateMemoryInput, Memory, MemoryType, UpdateMemoryInput};
+
+const KEY_FIELD: &str = "preference_key";
+const VALUE_FIELD: &str = "preference_value";
+const SCOPE_FIELD: &str = "preference_scope";
+const SCOPE_ID_FIELD: &str = "preference_scope_id";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Scope a preference applies to, from least to most specific.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "level", content = "id", rename_all = "snake_case")]
+pub enum PreferenceScope {
+    Global,
+    Workspace(String),
+    Project(String),
+    Session(String),
+}
+
+impl PreferenceScope {
+    /// Build a scope from a level name and identifier.
+    ///
+    /// Every level except `global` requires a non-empty `id`.
+    pub fn from_parts(level: &str, id: Option<&str>) -> Result<Self> {
+        let id = id.map(str::trim).filter(|s| !s.is_empty());
+        let scope = match (level.to_lowercase().as_str(), id) {
+            ("global", _) => PreferenceScope::Global,
+            ("workspace", Some(id)) => PreferenceScope::Workspace(id.to_string()),
+            ("project", Some(id)) => PreferenceScope::Project(id.to_string()),
+            ("session", Some(id)) => PreferenceScope::Session(id.to_string()),
+            ("workspace" | "project" | "session", None) => {
+                return Err(EngramError::InvalidInput(format!(
+                    "scope '{}' requires a scope_id",
+                    level
+                )))
+            }
+            _ => {
+                return Err(EngramError::InvalidInput(format!(
+                    "Unknown preference scope '{}'. Valid: global, workspace, project, session",
+                    level
+                )))
+            }
+        };
+        Ok(scope)
+    }
+
+    pub fn level(&self) -> &'static str {
+        match self {
+            PreferenceScope::Global => "global",
+            PreferenceScope::Workspace(_) => "workspace",
+            PreferenceScope::Project(_) => "project",
+            PreferenceScope::Session(_) => "session",
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            PreferenceScope::Global => None,
+            PreferenceScope::Workspace(id)
+            | PreferenceScope::Project(id)
+            | PreferenceScope::Session(id) => Some(id),
+        }
+    }
+
+    /// Precedence rank; higher wins.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            PreferenceScope::Global => 0,
+            PreferenceScope::Workspace(_) => 1,
+            PreferenceScope::Project(_) => 2,
+            PreferenceScope::Session(_) => 3,
+        }
+    }
+
+    /// Whether a preference at this scope applies in `context`.
+    pub fn applies_to(&self, context: &PreferenceContext) -> bool {
+        match self {
+            PreferenceScope::Global => true,
+            PreferenceScope::Workspace(id) => context.workspace.as_deref() == Some(id),
+            PreferenceScope::Project(id) => context.project.as_deref() == Some(id),
+            PreferenceScope::Session(id) => context.session.as_deref() == Some(id),
+        }
+    }
+}
+
+impl fmt::Display for PreferenceScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.id() {
+            Some(id) => write!(f, "{}:{}", self.level(), id),
+            None => f.write_str(self.level()),
+        }
+    }
+}
+
+impl FromStr for PreferenceScope {
+    type Err = EngramError;
+
+    /// Parse `global` or `<level>:<id>`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some((level, id)) => PreferenceScope::from_parts(level, Some(id)),
+            None => PreferenceScope::from_parts(s, None),
+        }
+    }
+}
+
+/// Where a preference lookup happens; unset fields match no scoped values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreferenceContext {
+    pub workspace: Option<String>,
+    pub project: Option<String>,
+    pub session: Option<String>,
+}
+
+/// A preference value stored at one scope.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preference {
+    pub memory_id: i64,
+    pub key: String,
+    pub value: Value,
+    pub scope: PreferenceScope,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Result of [`set_preference`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreferenceChange {
+    pub preference: Preference,
+    /// Value at the same scope before this call, if one existed
+    pub previous_value: Option<Value>,
+    /// False when the stored value already matched
+    pub changed: bool,
+    /// Values for the same key at other scopes that disagree with this one
+    pub conflicts: Vec<Preference>,
+}
+
+/// Result of [`get_preference`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedPreference {
+    /// The value that wins in the lookup context
+    pub preference: Preference,
+    /// Less specific values that were overridden, most specific first
+    pub overridden: Vec<Preference>,
+}
+
+/// One entry in a preference's change history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreferenceHistoryEntry {
+    pub version: i32,
+    pub value: Value,
+    pub changed_at: DateTime<Utc>,
+}
+
+// ---------------------------------------------------------------------------
+// Storage functions
+// ---------------------------------------------------------------------------
+
+/// Set `key` to `value` at `scope`, creating or updating its memory.
+pub fn set_preference(
+    conn: &Connection,
+    key: &str,
+    value: Value,
+    scope: &PreferenceScope,
+) -> Result<PreferenceChange> {
+    let key = normalize_key(key)?;
+    let existing = find_preferences(conn, &key)?
+        .into_iter()
+        .find(|p| &p.scope == scope);
+
+    let preference = match &existing {
+        Some(current) if current.value == value => current.clone(),
+        Some(current) => {
+            let metadata_json: String = conn.query_row(
+                "SELECT metadata FROM memories WHERE id = ?1",
+                params![current.memory_id],
+                |row| row.get(0),
+            )?;
+            let mut metadata: HashMap<String, Value> = serde_json::from_str(&metadata_json)?;
+            metadata.insert(VALUE_FIELD.to_string(), value.clone());
+            let updated = update_memory(
+                conn,
+                current.memory_id,
+                &UpdateMemoryInput {
+                    content: Some(render_content(&key, &value, scope)),
+                    memory_type: None,
+                    tags: None,
+                    metadata: Some(metadata),
+                    importance: None,
+                    scope: None,
+                    ttl_seconds: None,
+                    event_time: None,
+                    trigger_pattern: None,
+                    media_url: None,
+                },
+            )?;
+            to_preference(&updated).ok_or(EngramError::NotFound(current.memory_id))?
+        }
+        None => {
+            let mut metadata = HashMap::new();
+            metadata.insert(KEY_FIELD.to_string(), Value::String(key.clone()));
+            metadata.insert(VALUE_FIELD.to_string(), value.clone());
+            metadata.insert(
+                SCOPE_FIELD.to_string(),
+                Value::String(scope.level().to_string()),
+            );
+            metadata.insert(
+                SCOPE_ID_FIELD.to_string(),
+                scope
+                    .id()
+                    .map_or(Value::Null, |id| Value::String(id.to_string())),
+            );
+            let memory = create_memory(
+                conn,
+                &CreateMemoryInput {
+                    content: render_content(&key, &value, scope),
+                    memory_type: MemoryType::Preference,
+                    tags: vec!["preference".to_string(), format!("pref:{}", key)],
+                    metadata,
+                    workspace: match scope {
+                        PreferenceScope::Workspace(ws) => Some(ws.clone()),
+                        _ => None,
+                    },
+                    ..Default::default()
+                },
+            )?;
+            to_preference(&memory).ok_or(EngramError::NotFound(memory.id))?
+        }
+    };
+
+    let conflicts = find_preferences(conn, &key)?
+        .into_iter()
+        .filter(|p| &p.scope != scope && p.value != value)
+        .collect();
+
+    Ok(PreferenceChange {
+        changed: existing.as_ref().is_none_or(|p| p.value != value),
+        previous_value: existing.map(|p| p.value),
+        preference,
+        conflicts,
+    })
+}
+
+/// Resolve `key` in `context`, returning the most specific applicable value.
+pub fn get_preference(
+    conn: &Connection,
+    key: &str,
+    context: &PreferenceContext,
+) -> Result<Option<ResolvedPreference>> {
+    let key = normalize_key(key)?;
+    let mut applicable: Vec<Preference> = find_preferences(conn, &key)?
+        .into_iter()
+        .filter(|p| p.scope.applies_to(context))
+        .collect();
+    applicable.sort_by_key(|p| std::cmp::Reverse(p.scope.precedence()));
+
+    let mut applicable = applicable.into_iter();
+    Ok(applicable.next().map(|preference| ResolvedPreference {
+        preference,
+        overridden: applicable.collect(),
+    }))
+}
+
+/// List preferences, optionally under a key namespace (`editor` matches
+/// `editor.theme`).
+///
+/// With a `context`, only the winning value of each key is returned; without
+/// one, every stored scope is listed.
+pub fn list_preferences(
+    conn: &Connection,
+    namespace: Option<&str>,
+    context: Option<&PreferenceContext>,
+) -> Result<Vec<Preference>> {
+    let prefix = namespace.map(|ns| format!("{}.", ns.trim_end_matches('.')));
+    let preferences: Vec<Preference> = query_preferences(conn, None)?
+        .into_iter()
+        .filter(|pref| match (&prefix, namespace) {
+            (Some(prefix), Some(ns)) => pref.key == ns || pref.key.starts_with(prefix),
+            _ => true,
+        })
+        .collect();
+
+    let Some(context) = context else {
+        return Ok(preferences);
+    };
+    let mut effective: Vec<Preference> = Vec::new();
+    for pref in preferences
+        .into_iter()
+        .filter(|p| p.scope.applies_to(context))
+    {
+        match effective.iter_mut().find(|p| p.key == pref.key) {
+            Some(current) if current.scope.precedence() < pref.scope.precedence() => {
+                *current = pref
+            }
+            Some(_) => {}
+            None => effective.push(pref),
+        }
+    }
+    Ok(effective)
+}
+
+/// Values `key` has had at `scope`, newest first.
+pub fn preference_history(
+    conn: &Connection,
+    key: &str,
+    scope: &PreferenceScope,
+) -> Result<Vec<PreferenceHistoryEntry>> {
+    let key = normalize_key(key)?;
+    let Some(current) = find_preferences(conn, &key)?
+        .into_iter()
+        .find(|p| &p.scope == scope)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut history: Vec<PreferenceHistoryEntry> = Vec::new();
+    for version in get_memory_versions(conn, current.memory_id)? {
+        let Some(value) = version.metadata.get(VALUE_FIELD) else {
+            continue;
+        };
+        // Updates that did not touch the value (e.g. tag edits) repeat it.
+        if history.last().is_some_and(|newer| &newer.value == value) {
+            history.pop();
+        }
+        history.push(PreferenceHistoryEntry {
+            version: version.version,
+            value: value.clone(),
+            changed_at: version.created_at,
+        });
+    }
+    Ok(history)
+}
+
+/// Remove `key` at `scope`. Returns `false` if it was not set there.
+pub fn unset_preference(conn: &Connection, key: &str, scope: &PreferenceScope) -> Result<bool> {
+    let key = normalize_key(key)?;
+    match find_preferences(conn, &key)?
+        .into_iter()
+        .find(|p| &p.scope == scope)
+    {
+        Some(pref) => {
+            delete_memory(conn, pref.memory_id)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/// Lowercase, dot-separated keys of `[a-z0-9_-]` segments.
+fn normalize_key(key: &str) -> Result<String> {
+    let key = key.trim().to_lowercase();
+    let valid = !key.is_empty()
+        && key.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    if valid {
+        Ok(key)
+    } else {
+        Err(EngramError::InvalidInput(format!(
+            "Invalid preference key '{}': use dot-separated segments of letters, digits, '_' or '-'",
+            key
+        )))
+    }
+}
+
+fn find_preferences(conn: &Connection, key: &str) -> Result<Vec<Preference>> {
+    query_preferences(conn, Some(key))
+}
+
+/// Read preference memories straight from the table so lookups do not count
+/// as memory accesses.
+fn query_preferences(conn: &Connection, key: Option<&str>) -> Result<Vec<Preference>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, metadata, updated_at FROM memories
+         WHERE memory_type = 'preference' AND valid_to IS NULL
+           AND json_extract(metadata, '$.preference_key') IS NOT NULL
+           AND (?1 IS NULL OR json_extract(metadata, '$.preference_key') = ?1)
+         ORDER BY json_extract(metadata, '$.preference_key'), id",
+    )?;
+    let rows = stmt
+        .query_map(params![key], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, metadata, updated_at)| {
+            let metadata: HashMap<String, Value> = serde_json::from_str(&metadata).ok()?;
+            let updated_at = DateTime::parse_from_rfc3339(&updated_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()?;
+            from_metadata(id, &metadata, updated_at)
+        })
+        .collect())
+}
+
+fn to_preference(memory: &Memory) -> Option<Preference> {
+    from_metadata(memory.id, &memory.metadata, memory.updated_at)
+}
+
+fn from_metadata(
+    memory_id: i64,
+    metadata: &HashMap<String, Value>,
+    updated_at: DateTime<Utc>,
+) -> Option<Preference> {
+    let key = metadata.get(KEY_FIELD)?.as_str()?.to_string();
+    let value = metadata.get(VALUE_FIELD)?.clone();
+    let level = metadata.get(SCOPE_FIELD)?.as_str()?;
+    let scope_id = metadata.get(SCOPE_ID_FIELD).and_then(|v| v.as_str());
+    Some(Preference {
+        memory_id,
+        key,
+        value,
+        scope: PreferenceScope::from_parts(level, scope_id).ok()?,
+        updated_at,
+    })
+}
+
+fn render_content(key: &str, value: &Value, scope: &PreferenceScope) -> String {
+    let value = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    format!("Preference {} = {} ({})", key, value, scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+    use serde_json::json;
+
+    fn context(workspace: &str, project: &str, session: &str) -> PreferenceContext {
+        PreferenceContext {
+            workspace: Some(workspace.to_string()),
+            project: Some(project.to_string()),
+            session: Some(session.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_scope_precedence() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                let key = "editor.indent_width";
+                set_preference(conn, key, json!(4), &PreferenceScope::Global)?;
+                set_preference(conn, key, json!(2), &"project:web".parse()?)?;
+                set_preference(conn, key, json!(8), &"session:s1".parse()?)?;
+
+                let resolved =
+                    get_preference(conn, key, &context("default", "web", "s2"))?.expect("resolved");
+                assert_eq!(resolved.preference.value, json!(2));
+                assert_eq!(resolved.overridden.len(), 1);
+
+                let resolved =
+                    get_preference(conn, key, &context("default", "web", "s1"))?.expect("resolved");
+                assert_eq!(resolved.preference.value, json!(8));
+                assert_eq!(resolved.overridden.len(), 2);
+
+                let resolved =
+                    get_preference(conn, key, &PreferenceContext::default())?.expect("resolved");
+                assert_eq!(resolved.preference.scope, PreferenceScope::Global);
+
+                assert!(
+                    get_preference(conn, "editor.theme", &PreferenceContext::default())?.is_none()
+                );
+                Ok(())
+            })
+            .expect("transaction");
+    }
+
+    #[test]
+    fn test_set_reports_changes_conflicts_and_history() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                let scope = PreferenceScope::Workspace("acme".to_string());
+                let first = set_preference(conn, "Style.Quotes", json!("single"), &scope)?;
+                assert!(first.changed);
+                assert_eq!(first.previous_value, None);
+                assert_eq!(first.preference.key, "style.quotes");
+
+                let same = set_preference(conn, "style.quotes", json!("single"), &scope)?;
+                assert!(!same.changed);
+
+                set_preference(
+                    conn,
+                    "style.quotes",
+                    json!("single"),
+                    &PreferenceScope::Global,
+                )?;
+                let second = set_preference(conn, "style.quotes", json!("double"), &scope)?;
+                assert!(second.changed);
+                assert_eq!(second.previous_value, Some(json!("single")));
+                assert_eq!(second.preference.memory_id, first.preference.memory_id);
+                assert_eq!(second.conflicts.len(), 1);
+                assert_eq!(second.conflicts[0].scope, PreferenceScope::Global);
+
+                let history = preference_history(conn, "style.quotes", &scope)?;
+                let values: Vec<Value> = history.into_iter().map(|h| h.value).collect();
+                assert_eq!(values, vec![json!("double"), json!("single")]);
+                Ok(())
+            })
+            .expect("transaction");
+    }
+
+    #[test]
+    fn test_list_namespace_and_effective_values() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                set_preference(
+                    conn,
+                    "editor.theme",
+                    json!("dark"),
+                    &PreferenceScope::Global,
+                )?;
+                set_preference(
+                    conn,
+                    "editor.theme",
+                    json!("light"),
+                    &"project:web".parse()?,
+                )?;
+                set_preference(conn, "editor.font", json!("mono"), &PreferenceScope::Global)?;
+                set_preference(
+                    conn,
+                    "editorial.tone",
+                    json!("dry"),
+                    &PreferenceScope::Global,
+                )?;
+
+                assert_eq!(list_preferences(conn, Some("editor"), None)?.len(), 3);
+
+                let ctx = context("default", "web", "s1");
+                let effective = list_preferences(conn, Some("editor"), Some(&ctx))?;
+                assert_eq!(effective.len(), 2);
+                let theme = effective
+                    .iter()
+                    .find(|p| p.key == "editor.theme")
+                    .expect("theme");
+                assert_eq!(theme.value, json!("light"));
+
+                assert!(unset_preference(
+                    conn,
+                    "editor.theme",
+                    &"project:web".parse()?
+                )?);
+                assert!(!unset_preference(
+                    conn,
+                    "editor.theme",
+                    &"project:web".parse()?
+                )?);
+                let resolved = get_preference(conn, "editor.theme", &ctx)?.expect("global");
+                assert_eq!(resolved.preference.value, json!("dark"));
+                Ok(())
+            })
+            .expect("transaction");
+    }
+
+    #[test]
+    fn test_invalid_keys_and_scopes() {
+        assert!(normalize_key("").is_err());
+        assert!(normalize_key("editor..theme").is_err());
+        assert!(normalize_key("editor theme").is_err());
+        assert_eq!(normalize_key(" Editor.Theme ").unwrap(), "editor.theme");
+
+        assert!("project".parse::<PreferenceScope>().is_err());
+        assert!("planet:earth".parse::<PreferenceScope>().is_err());
+        assert_eq!(
+            "session:abc".parse::<PreferenceScope>().unwrap(),
+            PreferenceScope::Session("abc".to_string())
+        );
+    }
+}
@@ -3,10 +3,10 @@
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
 use crate::error::{EngramError, Result};
+use crate::intelligence::importance::{auto_importance, ImportanceSignals, ImportanceSource};
 use crate::storage::filter::{parse_filter, SqlBuilder};
 use crate::types::*;
 
@@ -37,6 +37,7 @@ pub fn memory_from_row(row: &Row) -> rusqlite::Result<Memory> {
 
     // Content hash column (with fallback for backward compatibility)
     let content_hash: Option<String> = row.get("content_hash").unwrap_or(None);
+    let content_hash = content_hash.map(ContentHash::from_stored);
 
     let memory_type = memory_type_str.parse().unwrap_or(MemoryType::Note);
     let visibility = match visibility_str.as_str() {
@@ -188,18 +189,23 @@ fn get_memory_internal(conn: &Connection, id: i64, track_access: bool) -> Result
     memory.tags = load_tags(conn, id)?;
 
     if track_access {
-        // Update access tracking
-        let now = Utc::now().to_rfc3339();
-        conn.execute(
-            "UPDATE memories SET access_count = access_count + 1, last_accessed_at = ?
-             WHERE id = ?",
-            params![now, id],
-        )?;
+        record_access(conn, id)?;
     }
 
     Ok(memory)
 }
 
+/// Bump a memory's access count and last-accessed time without reading it
+pub fn record_access(conn: &Connection, id: i64) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE memories SET access_count = access_count + 1, last_accessed_at = ?
+         WHERE id = ?",
+        params![now, id],
+    )?;
+    Ok(())
+}
+
 /// Load tags for a memory
 pub fn load_tags(conn: &Connection, memory_id: i64) -> Result<Vec<String>> {
     let mut stmt = conn.prepare_cached(
@@ -216,18 +222,9 @@ pub fn load_tags(conn: &Connection, memory_id: i64) -> Result<Vec<String>> {
     Ok(tags)
 }
 
-/// Compute SHA256 hash of normalized content for deduplication
+/// Compute the dedup hash of a memory's content, as stored in `content_hash`
 pub fn compute_content_hash(content: &str) -> String {
-    // Normalize: lowercase, collapse whitespace, trim
-    let normalized = content
-        .to_lowercase()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ");
-
-    let mut hasher = Sha256::new();
-    hasher.update(normalized.as_bytes());
-    format!("sha256:{}", hex::encode(hasher.finalize()))
+    ContentHash::of(content).into_string()
 }
 
 /// Find a memory by content hash within the same scope and workspace (exact duplicate detection)
@@ -347,6 +344,28 @@ pub fn find_similar_by_embedding(
     }
 }
 
+/// Most similar memory at or above `threshold`, the textual counterpart of
+/// [`find_similar_by_embedding`]
+pub fn find_similar_by_signature(
+    conn: &Connection,
+    content: &str,
+    scope: &MemoryScope,
+    workspace: Option<&str>,
+    threshold: f32,
+) -> Result<Option<(Memory, f32)>> {
+    use super::text_signatures::find_signature_matches;
+
+    let workspace = workspace.unwrap_or("default");
+    let best = find_signature_matches(conn, content, scope, workspace, threshold, 1)?.pop();
+    match best {
+        Some(found) => {
+            let memory = get_memory_internal(conn, found.memory_id, false)?;
+            Ok(Some((memory, found.similarity)))
+        }
+        None => Ok(None),
+    }
+}
+
 /// A pair of potentially duplicate memories with their similarity score
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DuplicatePair {
@@ -623,6 +642,17 @@ pub fn find_duplicates_by_embedding(
 
 /// Create a new memory with deduplication support
 pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Memory> {
+    let id = insert_memory(conn, input)?;
+    get_memory_internal(conn, id, false)
+}
+
+/// Write a memory without reading it back, returning its ID.
+///
+/// This is the body of [`create_memory`]; bulk writers call it directly to
+/// skip the read-back. Under `Skip`/`Merge` dedup the ID of the existing
+/// memory is returned. Hot statements go through the prepared-statement
+/// cache, so repeated calls on one connection don't re-parse SQL.
+pub(crate) fn insert_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<i64> {
     let now = Utc::now();
     let now_str = now.to_rfc3339();
     let metadata_json = serde_json::to_string(&input.metadata)?;
@@ -638,6 +668,18 @@ pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Mem
         None => "default".to_string(),
     };
 
+    // Fill in what the input leaves unset from the workspace's defaults
+    let configured;
+    let input = match crate::storage::workspace_config::get_workspace_config(conn, &workspace)? {
+        Some(config) => {
+            let mut with_defaults = input.clone();
+            config.apply(&mut with_defaults);
+            configured = with_defaults;
+            &configured
+        }
+        None => input,
+    };
+
     // Check for duplicates based on dedup_mode (scoped to same scope AND workspace)
     if input.dedup_mode != DedupMode::Allow {
         if let Some(existing) =
@@ -655,7 +697,7 @@ pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Mem
                 }
                 DedupMode::Skip => {
                     // Return existing memory without modification
-                    return Ok(existing);
+                    return Ok(existing.id);
                 }
                 DedupMode::Merge => {
                     // Merge: update existing memory with new tags and metadata
@@ -684,7 +726,7 @@ pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Mem
                         media_url: input.media_url.clone().map(Some),
                     };
 
-                    return update_memory(conn, existing.id, &update_input);
+                    return update_memory(conn, existing.id, &update_input).map(|m| m.id);
                 }
                 DedupMode::Allow => unreachable!(),
             }
@@ -723,10 +765,11 @@ pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Mem
 
     let event_time = input.event_time.map(|dt| dt.to_rfc3339());
 
-    conn.execute(
+    conn.prepare_cached(
         "INSERT INTO memories (content, memory_type, importance, metadata, created_at, updated_at, valid_from, scope_type, scope_id, workspace, tier, expires_at, content_hash, event_time, event_duration_seconds, trigger_pattern, summary_of_id, media_url)
          VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        params![
+    )?
+    .execute(params![
             input.content,
             input.memory_type.as_str(),
             importance,
@@ -745,37 +788,41 @@ pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Mem
             input.trigger_pattern,
             input.summary_of_id,
             input.media_url,
-        ],
-    )?;
+    ])?;
 
     let id = conn.last_insert_rowid();
+    super::text_signatures::index_signature(conn, id, &input.content)?;
+    super::tfidf_vocabulary::observe_tfidf_document(conn, &input.content)?;
 
-    // Insert tags
-    for tag in &input.tags {
+    // Insert tags, normalized and resolved through synonym groups
+    let tags = super::tag_synonyms::resolve_tags(conn, &input.tags)?;
+    for tag in &tags {
         ensure_tag(conn, tag)?;
-        conn.execute(
+        conn.prepare_cached(
             "INSERT OR IGNORE INTO memory_tags (memory_id, tag_id)
              SELECT ?, id FROM tags WHERE name = ?",
-            params![id, tag],
-        )?;
+        )?
+        .execute(params![id, tag])?;
     }
 
     // Queue for embedding if not deferred
     if !input.defer_embedding {
-        conn.execute(
-            "INSERT INTO embedding_queue (memory_id, status, queued_at)
-             VALUES (?, 'pending', ?)",
-            params![id, now_str],
-        )?;
+        queue_embedding(conn, id, &now_str)?;
     }
 
     // Create initial version
-    let tags_json = serde_json::to_string(&input.tags)?;
-    conn.execute(
+    let tags_json = serde_json::to_string(&tags)?;
+    conn.prepare_cached(
         "INSERT INTO memory_versions (memory_id, version, content, tags, metadata, created_at)
          VALUES (?, 1, ?, ?, ?, ?)",
-        params![id, input.content, tags_json, metadata_json, now_str],
-    )?;
+    )?
+    .execute(params![
+        id,
+        input.content,
+        tags_json,
+        metadata_json,
+        now_str
+    ])?;
 
     // Record event for sync delta tracking
     record_event(
@@ -790,21 +837,32 @@ pub fn create_memory(conn: &Connection, input: &CreateMemoryInput) -> Result<Mem
     )?;
 
     // Update sync state (version now tracks event count for delta sync)
-    conn.execute(
+    conn.prepare_cached(
         "UPDATE sync_state SET pending_changes = pending_changes + 1, version = (SELECT MAX(id) FROM memory_events) WHERE id = 1",
-        [],
-    )?;
+    )?
+    .execute([])?;
 
-    get_memory_internal(conn, id, false)
+    Ok(id)
+}
+
+/// Add a memory to the pending embedding queue
+pub(crate) fn queue_embedding(conn: &Connection, memory_id: i64, queued_at: &str) -> Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO embedding_queue (memory_id, status, queued_at)
+         VALUES (?, 'pending', ?)",
+    )?
+    .execute(params![memory_id, queued_at])?;
+    Ok(())
 }
 
 /// Ensure a tag exists and return its ID
 fn ensure_tag(conn: &Connection, tag: &str) -> Result<i64> {
-    conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", params![tag])?;
+    conn.prepare_cached("INSERT OR IGNORE INTO tags (name) VALUES (?)")?
+        .execute(params![tag])?;
 
-    let id: i64 = conn.query_row("SELECT id FROM tags WHERE name = ?", params![tag], |row| {
-        row.get(0)
-    })?;
+    let id: i64 = conn
+        .prepare_cached("SELECT id FROM tags WHERE name = ?")?
+        .query_row(params![tag], |row| row.get(0))?;
 
     Ok(id)
 }
@@ -814,6 +872,52 @@ pub fn get_memory(conn: &Connection, id: i64) -> Result<Memory> {
     get_memory_internal(conn, id, true)
 }
 
+/// Get a memory by ID without recording an access
+pub fn peek_memory(conn: &Connection, id: i64) -> Result<Memory> {
+    get_memory_internal(conn, id, false)
+}
+
+/// Read `length` characters of a memory's content starting at `offset`.
+///
+/// The slice is taken by SQLite, so only the requested range is copied out of
+/// the row. `length: None` reads to the end; an offset past the end returns an
+/// empty range.
+pub fn get_memory_content_range(
+    conn: &Connection,
+    id: i64,
+    offset: usize,
+    length: Option<usize>,
+) -> Result<ContentRange> {
+    let now = Utc::now().to_rfc3339();
+    // SQLite treats a negative substr length as "characters before", so an
+    // unbounded read uses the total length instead.
+    let (total_chars, total_bytes, content): (i64, i64, String) = conn
+        .query_row(
+            "SELECT length(content), length(CAST(content AS BLOB)),
+                    substr(content, ?1 + 1, COALESCE(?2, length(content)))
+             FROM memories
+             WHERE id = ?3 AND valid_to IS NULL
+               AND (expires_at IS NULL OR expires_at > ?4)",
+            params![offset as i64, length.map(|l| l as i64), id, now],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or(EngramError::NotFound(id))?;
+
+    let total_chars = total_chars as usize;
+    let returned = content.chars().count();
+    let end = offset.saturating_add(returned);
+    Ok(ContentRange {
+        memory_id: id,
+        offset,
+        length: returned,
+        total_chars,
+        total_bytes: total_bytes as usize,
+        next_offset: (end < total_chars).then_some(end),
+        content,
+    })
+}
+
 /// Update a memory
 pub fn update_memory(conn: &Connection, id: i64, input: &UpdateMemoryInput) -> Result<Memory> {
     // Get current memory for versioning
@@ -915,7 +1019,11 @@ pub fn update_memory(conn: &Connection, id: i64, input: &UpdateMemoryInput) -> R
     conn.execute(&sql, params.as_slice())?;
 
     // Update tags if provided
-    if let Some(ref tags) = input.tags {
+    let resolved_tags = match input.tags {
+        Some(ref tags) => Some(super::tag_synonyms::resolve_tags(conn, tags)?),
+        None => None,
+    };
+    if let Some(ref tags) = resolved_tags {
         conn.execute("DELETE FROM memory_tags WHERE memory_id = ?", params![id])?;
         for tag in tags {
             ensure_tag(conn, tag)?;
@@ -929,7 +1037,7 @@ pub fn update_memory(conn: &Connection, id: i64, input: &UpdateMemoryInput) -> R
 
     // Create new version
     let new_content = input.content.as_ref().unwrap_or(&current.content);
-    let new_tags = input.tags.as_ref().unwrap_or(&current.tags);
+    let new_tags = resolved_tags.as_ref().unwrap_or(&current.tags);
     let new_metadata = input.metadata.as_ref().unwrap_or(&current.metadata);
     let tags_json = serde_json::to_string(new_tags)?;
     let metadata_json = serde_json::to_string(new_metadata)?;
@@ -940,8 +1048,8 @@ pub fn update_memory(conn: &Connection, id: i64, input: &UpdateMemoryInput) -> R
         params![id, id, new_content, tags_json, metadata_json, now],
     )?;
 
-    // Re-queue for embedding if content changed
-    if input.content.is_some() {
+    // Re-queue for embedding and re-sign if content changed
+    if let Some(ref content) = input.content {
         conn.execute(
             "INSERT OR REPLACE INTO embedding_queue (memory_id, status, queued_at)
              VALUES (?, 'pending', ?)",
@@ -951,6 +1059,7 @@ pub fn update_memory(conn: &Connection, id: i64, input: &UpdateMemoryInput) -> R
             "UPDATE memories SET has_embedding = 0 WHERE id = ?",
             params![id],
         )?;
+        super::text_signatures::index_signature(conn, id, content)?;
     }
 
     // Build list of changed fields for event data
@@ -1344,42 +1453,30 @@ pub fn delete_memory(conn: &Connection, id: i64) -> Result<()> {
     Ok(())
 }
 
-/// List memories with filtering and pagination
-pub fn list_memories(conn: &Connection, options: &ListOptions) -> Result<Vec<Memory>> {
-    let now = Utc::now().to_rfc3339();
-
-    let mut sql = String::from(
-        "SELECT DISTINCT m.id, m.content, m.memory_type, m.importance, m.access_count,
-                m.created_at, m.updated_at, m.last_accessed_at, m.owner_id,
-                m.visibility, m.version, m.has_embedding, m.metadata,
-                m.scope_type, m.scope_id, m.workspace, m.tier, m.expires_at, m.content_hash,
-                m.event_time, m.event_duration_seconds, m.trigger_pattern, m.procedure_success_count,
-                m.procedure_failure_count, m.summary_of_id, m.lifecycle_state, m.media_url
-         FROM memories m",
-    );
-
-    let mut conditions = vec!["m.valid_to IS NULL".to_string()];
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
-
-    // Exclude expired memories
-    conditions.push("(m.expires_at IS NULL OR m.expires_at > ?)".to_string());
-    params.push(Box::new(now));
-
-    // Tag filter (requires join)
-    if let Some(ref tags) = options.tags {
-        if !tags.is_empty() {
-            sql.push_str(
-                " JOIN memory_tags mt ON m.id = mt.memory_id
-                  JOIN tags t ON mt.tag_id = t.id",
-            );
-            let placeholders: Vec<String> = tags.iter().map(|_| "?".to_string()).collect();
-            conditions.push(format!("t.name IN ({})", placeholders.join(", ")));
-            for tag in tags {
-                params.push(Box::new(tag.clone()));
-            }
-        }
+/// Append `event_after` / `event_before` conditions (memories without an
+/// event time never match an event-time range).
+fn push_event_time_filters(
+    options: &ListOptions,
+    conditions: &mut Vec<String>,
+    params: &mut Vec<Box<dyn rusqlite::ToSql>>,
+) {
+    if let Some(after) = options.event_after {
+        conditions.push("m.event_time >= ?".to_string());
+        params.push(Box::new(after.to_rfc3339()));
+    }
+    if let Some(before) = options.event_before {
+        conditions.push("m.event_time <= ?".to_string());
+        params.push(Box::new(before.to_rfc3339()));
     }
+}
 
+/// Column filters of a memory listing other than tags, expiry and
+/// supersession, shared by [`list_memories`] and its `as_of` variant.
+pub(crate) fn push_list_filters(
+    options: &ListOptions,
+    conditions: &mut Vec<String>,
+    params: &mut Vec<Box<dyn rusqlite::ToSql>>,
+) -> Result<()> {
     // Type filter
     if let Some(ref memory_type) = options.memory_type {
         conditions.push("m.memory_type = ?".to_string());
@@ -1398,7 +1495,7 @@ pub fn list_memories(conn: &Connection, options: &ListOptions) -> Result<Vec<Mem
     } else if let Some(ref metadata_filter) = options.metadata_filter {
         // Legacy metadata filter (JSON) - deprecated in favor of `filter`
         for (key, value) in metadata_filter {
-            metadata_value_to_param(key, value, &mut conditions, &mut params)?;
+            metadata_value_to_param(key, value, conditions, params)?;
         }
     }
 
@@ -1426,6 +1523,67 @@ pub fn list_memories(conn: &Connection, options: &ListOptions) -> Result<Vec<Mem
         params.push(Box::new(tier.as_str().to_string()));
     }
 
+    // Event-time range
+    push_event_time_filters(options, conditions, params);
+
+    if let Some(status) = options.validation_status {
+        conditions.push("COALESCE(m.validation_status, 'unverified') = ?".to_string());
+        params.push(Box::new(status.as_str().to_string()));
+    }
+
+    Ok(())
+}
+
+/// List memories with filtering and pagination
+pub fn list_memories(conn: &Connection, options: &ListOptions) -> Result<Vec<Memory>> {
+    // Record-time "as of" queries rewind content through the version history.
+    if let Some(as_of) = options.as_of {
+        return crate::storage::temporal::TemporalQueryEngine::new(conn)
+            .list_memories_at(as_of, options);
+    }
+
+    let now = Utc::now().to_rfc3339();
+
+    let mut sql = String::from(
+        "SELECT DISTINCT m.id, m.content, m.memory_type, m.importance, m.access_count,
+                m.created_at, m.updated_at, m.last_accessed_at, m.owner_id,
+                m.visibility, m.version, m.has_embedding, m.metadata,
+                m.scope_type, m.scope_id, m.workspace, m.tier, m.expires_at, m.content_hash,
+                m.event_time, m.event_duration_seconds, m.trigger_pattern, m.procedure_success_count,
+                m.procedure_failure_count, m.summary_of_id, m.lifecycle_state, m.media_url
+         FROM memories m",
+    );
+
+    let mut conditions = vec!["m.valid_to IS NULL".to_string()];
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![];
+
+    // Exclude expired memories
+    conditions.push("(m.expires_at IS NULL OR m.expires_at > ?)".to_string());
+    params.push(Box::new(now));
+
+    // Tag filter (requires join), matching whole synonym groups
+    if let Some(ref tags) = options.tags {
+        let tags = &super::tag_synonyms::expand_tag_filter(conn, tags)?;
+        if !tags.is_empty() {
+            sql.push_str(
+                " JOIN memory_tags mt ON m.id = mt.memory_id
+                  JOIN tags t ON mt.tag_id = t.id",
+            );
+            let placeholders: Vec<String> = tags.iter().map(|_| "?".to_string()).collect();
+            conditions.push(format!("t.name IN ({})", placeholders.join(", ")));
+            for tag in tags {
+                params.push(Box::new(tag.clone()));
+            }
+        }
+    }
+
+    push_list_filters(options, &mut conditions, &mut params)?;
+
+    // Superseded memories are kept for audit but hidden by default
+    if !options.show_superseded {
+        conditions.push("m.superseded_at IS NULL".to_string());
+    }
+
     sql.push_str(" WHERE ");
     sql.push_str(&conditions.join(" AND "));
 
@@ -1511,6 +1669,7 @@ pub fn get_episodic_timeline(
     }
 
     if let Some(tag_list) = tags {
+        let tag_list = &super::tag_synonyms::expand_tag_filter(conn, tag_list)?;
         if !tag_list.is_empty() {
             sql.push_str(
                 " JOIN memory_tags mt ON m.id = mt.memory_id
@@ -1874,17 +2033,300 @@ pub fn set_memory_expiration(
     get_memory_internal(conn, id, false)
 }
 
-/// Delete all expired memories (cleanup job)
+/// Filter selecting the memories of a bulk TTL change
 ///
-/// Returns the number of memories deleted
-pub fn cleanup_expired_memories(conn: &Connection) -> Result<i64> {
+/// The regular [`ListOptions`] filters (tags, type, workspace, tier,
+/// metadata or advanced filter) plus memory age; pagination, sorting and
+/// `as_of` are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpirationFilter {
+    #[serde(flatten)]
+    pub list: ListOptions,
+    /// Only memories created at least this many days ago
+    pub older_than_days: Option<f64>,
+    /// Only memories created at most this many days ago
+    pub newer_than_days: Option<f64>,
+}
+
+impl ExpirationFilter {
+    fn is_empty(&self) -> bool {
+        let list = &self.list;
+        list.tags.as_ref().is_none_or(|t| t.is_empty())
+            && list.memory_type.is_none()
+            && list.workspace.is_none()
+            && list.workspaces.as_ref().is_none_or(|w| w.is_empty())
+            && list.tier.is_none()
+            && list.filter.is_none()
+            && list.metadata_filter.is_none()
+            && list.scope.is_none()
+            && list.event_after.is_none()
+            && list.event_before.is_none()
+            && list.validation_status.is_none()
+            && self.older_than_days.is_none()
+            && self.newer_than_days.is_none()
+    }
+
+    /// The list options matching every selected memory, with the age bounds
+    /// folded into the advanced filter
+    fn to_list_options(&self, now: DateTime<Utc>) -> Result<ListOptions> {
+        let mut conditions: Vec<serde_json::Value> = self.list.filter.iter().cloned().collect();
+        for (days, op) in [(self.older_than_days, "lte"), (self.newer_than_days, "gte")] {
+            if let Some(days) = days {
+                if !days.is_finite() || days < 0.0 {
+                    return Err(EngramError::InvalidInput(format!(
+                        "Invalid age in days: {}",
+                        days
+                    )));
+                }
+                let cutoff = now - chrono::Duration::seconds((days * 86400.0) as i64);
+                conditions.push(serde_json::json!({"created_at": {op: cutoff.to_rfc3339()}}));
+            }
+        }
+        let filter = match conditions.len() {
+            0 => None,
+            1 => conditions.pop(),
+            _ => Some(serde_json::json!({"AND": conditions})),
+        };
+
+        Ok(ListOptions {
+            limit: Some(i64::MAX),
+            offset: None,
+            sort_by: None,
+            sort_order: None,
+            as_of: None,
+            filter,
+            ..self.list.clone()
+        })
+    }
+}
+
+/// Result of a bulk TTL change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkExpirationReport {
+    pub dry_run: bool,
+    /// Memories matching the filter
+    pub matched: usize,
+    /// Matched memories whose tier changes
+    pub retiered: usize,
+    /// New expiration of every matched memory (None = never expires)
+    pub expires_at: Option<DateTime<Utc>>,
+    pub memory_ids: Vec<i64>,
+}
+
+/// Set the expiration of every memory matching `filter`
+///
+/// A positive `ttl_seconds` expires the memories that many seconds from now
+/// and moves them to the daily tier; `None` or `Some(0)` removes their
+/// expiration and moves them to the permanent tier, keeping the tier
+/// invariants of [`MemoryTier`]. An empty filter is rejected. Call inside a
+/// transaction so the change applies to all memories or none.
+pub fn set_memory_expiration_bulk(
+    conn: &Connection,
+    filter: &ExpirationFilter,
+    ttl_seconds: Option<i64>,
+    dry_run: bool,
+) -> Result<BulkExpirationReport> {
+    if filter.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "bulk TTL change requires at least one filter".to_string(),
+        ));
+    }
+    let ttl = match ttl_seconds {
+        Some(ttl) if ttl < 0 => {
+            return Err(EngramError::InvalidInput(
+                "ttl_seconds must not be negative".to_string(),
+            ))
+        }
+        Some(0) | None => None,
+        Some(ttl) => Some(ttl),
+    };
+
+    let now = Utc::now();
+    let memories = list_memories(conn, &filter.to_list_options(now)?)?;
+    let expires_at = ttl.map(|ttl| now + chrono::Duration::seconds(ttl));
+    let tier = if expires_at.is_some() {
+        MemoryTier::Daily
+    } else {
+        MemoryTier::Permanent
+    };
+
+    let report = BulkExpirationReport {
+        dry_run,
+        matched: memories.len(),
+        retiered: memories.iter().filter(|m| m.tier != tier).count(),
+        expires_at,
+        memory_ids: memories.iter().map(|m| m.id).collect(),
+    };
+    if dry_run || report.memory_ids.is_empty() {
+        return Ok(report);
+    }
+
+    let now_str = now.to_rfc3339();
+    let expires_at_str = expires_at.map(|dt| dt.to_rfc3339());
+    for id in &report.memory_ids {
+        conn.prepare_cached(
+            "UPDATE memories SET expires_at = ?, tier = ?, updated_at = ? WHERE id = ?",
+        )?
+        .execute(params![expires_at_str, tier.as_str(), now_str, id])?;
+        record_event(
+            conn,
+            MemoryEventType::Updated,
+            Some(*id),
+            None,
+            serde_json::json!({
+                "changed_fields": ["expires_at", "tier"],
+                "action": "set_expiration_bulk",
+            }),
+        )?;
+    }
+    conn.execute(
+        "UPDATE sync_state SET pending_changes = pending_changes + ?, version = (SELECT MAX(id) FROM memory_events) WHERE id = 1",
+        params![report.memory_ids.len() as i64],
+    )?;
+
+    Ok(report)
+}
+
+/// Days an expired memory stays recoverable before cleanup purges it
+pub const DEFAULT_EXPIRED_GRACE_DAYS: i64 = 7;
+
+/// An expired memory still in its grace period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiredMemory {
+    #[serde(flatten)]
+    pub memory: Memory,
+    /// When cleanup will purge it
+    pub purge_at: DateTime<Utc>,
+}
+
+/// List expired memories that cleanup hasn't purged yet, most recently
+/// expired first
+///
+/// These are invisible to every other query but can be brought back with
+/// [`resurrect_memory`].
+pub fn list_expired_memories(
+    conn: &Connection,
+    workspace: Option<&str>,
+    limit: i64,
+    grace_days: i64,
+) -> Result<Vec<ExpiredMemory>> {
     let now = Utc::now().to_rfc3339();
+    // Past this cutoff cleanup purges them, so they're no longer recoverable
+    let cutoff = (Utc::now() - chrono::Duration::days(grace_days.max(0))).to_rfc3339();
+    let workspace = workspace
+        .map(crate::types::normalize_workspace)
+        .transpose()
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, content, memory_type, importance, access_count,
+                created_at, updated_at, last_accessed_at, owner_id,
+                visibility, version, has_embedding, metadata,
+                scope_type, scope_id, workspace, tier, expires_at, content_hash,
+                event_time, event_duration_seconds, trigger_pattern, procedure_success_count,
+                procedure_failure_count, summary_of_id, lifecycle_state, media_url
+         FROM memories
+         WHERE valid_to IS NULL AND expires_at IS NOT NULL AND expires_at <= ?
+           AND expires_at > ?
+           AND (? IS NULL OR workspace = ?)
+         ORDER BY expires_at DESC
+         LIMIT ?",
+    )?;
+    let memories = stmt
+        .query_map(
+            params![now, cutoff, workspace, workspace, limit],
+            memory_from_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut expired = Vec::with_capacity(memories.len());
+    for mut memory in memories {
+        memory.tags = load_tags(conn, memory.id)?;
+        let expires_at = memory.expires_at.unwrap_or_else(Utc::now);
+        expired.push(ExpiredMemory {
+            purge_at: expires_at + chrono::Duration::days(grace_days),
+            memory,
+        });
+    }
+    Ok(expired)
+}
+
+/// Bring back an expired memory that is still in its grace period
+///
+/// A positive `ttl_seconds` gives it a new expiration that long from now;
+/// otherwise its expiration is cleared and it moves to the permanent tier.
+/// Memories that expired more than `grace_days` ago are due for purging and
+/// return `NotFound`, even if cleanup hasn't run yet.
+pub fn resurrect_memory(
+    conn: &Connection,
+    id: i64,
+    ttl_seconds: Option<i64>,
+    grace_days: i64,
+) -> Result<Memory> {
+    let now = Utc::now();
+    let cutoff = (now - chrono::Duration::days(grace_days.max(0))).to_rfc3339();
+    let expired: bool = conn
+        .query_row(
+            "SELECT expires_at IS NOT NULL AND expires_at <= ? FROM memories
+             WHERE id = ? AND valid_to IS NULL
+               AND (expires_at IS NULL OR expires_at > ?)",
+            params![now.to_rfc3339(), id, cutoff],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or(EngramError::NotFound(id))?;
+    if !expired {
+        return Err(EngramError::InvalidInput(format!(
+            "Memory {} has not expired",
+            id
+        )));
+    }
+
+    let (expires_at, tier) = match ttl_seconds {
+        Some(ttl) if ttl > 0 => (
+            Some((now + chrono::Duration::seconds(ttl)).to_rfc3339()),
+            MemoryTier::Daily,
+        ),
+        _ => (None, MemoryTier::Permanent),
+    };
+    conn.execute(
+        "UPDATE memories SET expires_at = ?, tier = ?, updated_at = ? WHERE id = ?",
+        params![expires_at, tier.as_str(), now.to_rfc3339(), id],
+    )?;
+
+    record_event(
+        conn,
+        MemoryEventType::Updated,
+        Some(id),
+        None,
+        serde_json::json!({
+            "changed_fields": ["expires_at", "tier"],
+            "action": "resurrect",
+        }),
+    )?;
+    conn.execute(
+        "UPDATE sync_state SET pending_changes = pending_changes + 1, version = (SELECT MAX(id) FROM memory_events) WHERE id = 1",
+        [],
+    )?;
+
+    get_memory_internal(conn, id, false)
+}
+
+/// Purge memories that expired more than `grace_days` ago (cleanup job)
+///
+/// Expired memories are invisible as soon as they expire; until the grace
+/// period ends they keep their tags, links and entities and can be brought
+/// back with [`resurrect_memory`]. Purging soft-deletes them and drops those.
+/// Returns the number of memories purged.
+pub fn cleanup_expired_memories(conn: &Connection, grace_days: i64) -> Result<i64> {
+    let now = Utc::now().to_rfc3339();
+    let cutoff = (Utc::now() - chrono::Duration::days(grace_days.max(0))).to_rfc3339();
 
     // Soft delete expired memories by setting valid_to
     let affected = conn.execute(
         "UPDATE memories SET valid_to = ?
          WHERE expires_at IS NOT NULL AND expires_at <= ? AND valid_to IS NULL",
-        params![now, now],
+        params![now, cutoff],
     )?;
 
     if affected > 0 {
@@ -1895,7 +2337,7 @@ pub fn cleanup_expired_memories(conn: &Connection) -> Result<i64> {
                  from_id IN (SELECT id FROM memories WHERE valid_to IS NOT NULL AND expires_at IS NOT NULL AND expires_at <= ?)
                  OR to_id IN (SELECT id FROM memories WHERE valid_to IS NOT NULL AND expires_at IS NOT NULL AND expires_at <= ?)
              )",
-            params![now, now, now],
+            params![now, cutoff, cutoff],
         )?;
 
         // Remove memory_entities links for expired memories
@@ -1906,7 +2348,7 @@ pub fn cleanup_expired_memories(conn: &Connection) -> Result<i64> {
                  SELECT id FROM memories
                  WHERE valid_to IS NOT NULL AND expires_at IS NOT NULL AND expires_at <= ?
              )",
-            params![now],
+            params![cutoff],
         )?;
 
         // Remove memory_tags links for expired memories
@@ -1916,7 +2358,7 @@ pub fn cleanup_expired_memories(conn: &Connection) -> Result<i64> {
                  SELECT id FROM memories
                  WHERE valid_to IS NOT NULL AND expires_at IS NOT NULL AND expires_at <= ?
              )",
-            params![now],
+            params![cutoff],
         )?;
 
         // Record batch event for sync delta tracking
@@ -2327,8 +2769,9 @@ pub fn list_memories_compact(
     conditions.push("(m.expires_at IS NULL OR m.expires_at > ?)".to_string());
     params.push(Box::new(now));
 
-    // Tag filter (requires join)
+    // Tag filter (requires join), matching whole synonym groups
     if let Some(ref tags) = options.tags {
+        let tags = &super::tag_synonyms::expand_tag_filter(conn, tags)?;
         if !tags.is_empty() {
             sql.push_str(
                 " JOIN memory_tags mt ON m.id = mt.memory_id
@@ -2379,6 +2822,19 @@ pub fn list_memories_compact(
         params.push(Box::new(tier.as_str().to_string()));
     }
 
+    // Event-time range
+    push_event_time_filters(options, &mut conditions, &mut params);
+
+    // Superseded memories are kept for audit but hidden by default
+    if !options.show_superseded {
+        conditions.push("m.superseded_at IS NULL".to_string());
+    }
+
+    if let Some(status) = options.validation_status {
+        conditions.push("COALESCE(m.validation_status, 'unverified') = ?".to_string());
+        params.push(Box::new(status.as_str().to_string()));
+    }
+
     sql.push_str(" WHERE ");
     sql.push_str(&conditions.join(" AND "));
 
@@ -2726,51 +3182,59 @@ pub fn list_tags(conn: &Connection) -> Result<Vec<TagInfo>> {
 pub struct TagHierarchyNode {
     pub name: String,
     pub full_path: String,
+    /// Memories tagged with this path or any path below it
     pub count: i64,
     pub children: Vec<TagHierarchyNode>,
 }
 
 /// Build tag hierarchy from slash-separated tags (e.g., "project/engram/core")
+///
+/// Every path prefix becomes a node, even when no memory carries it as a tag
+/// itself. Roots and children are sorted by name; empty segments (as in
+/// `a//b`) are skipped.
 pub fn get_tag_hierarchy(conn: &Connection) -> Result<Vec<TagHierarchyNode>> {
-    let tags = list_tags(conn)?;
-
-    // Build hierarchy from slash-separated paths
-    let mut root_nodes: HashMap<String, TagHierarchyNode> = HashMap::new();
-
-    for tag in tags {
-        let parts: Vec<&str> = tag.name.split('/').collect();
-        if parts.is_empty() {
-            continue;
-        }
-
-        let root_name = parts[0].to_string();
-        if !root_nodes.contains_key(&root_name) {
-            root_nodes.insert(
-                root_name.clone(),
-                TagHierarchyNode {
-                    name: root_name.clone(),
-                    full_path: root_name.clone(),
+    fn insert(level: &mut Vec<TagHierarchyNode>, parent: &str, parts: &[&str], count: i64) {
+        let Some((name, rest)) = parts.split_first() else {
+            return;
+        };
+        let index = match level.iter().position(|n| n.name == *name) {
+            Some(index) => index,
+            None => {
+                let full_path = if parent.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", parent, name)
+                };
+                level.push(TagHierarchyNode {
+                    name: name.to_string(),
+                    full_path,
                     count: 0,
                     children: Vec::new(),
-                },
-            );
-        }
-
-        // Add count to appropriate level
-        if parts.len() == 1 {
-            if let Some(node) = root_nodes.get_mut(&root_name) {
-                node.count += tag.count;
-            }
-        } else {
-            // For nested tags, we'd need recursive building
-            // For now, just add to root count
-            if let Some(node) = root_nodes.get_mut(&root_name) {
-                node.count += tag.count;
+                });
+                level.len() - 1
             }
+        };
+        let node = &mut level[index];
+        node.count += count;
+        let path = node.full_path.clone();
+        insert(&mut node.children, &path, rest, count);
+    }
+
+    fn sort(level: &mut [TagHierarchyNode]) {
+        level.sort_by(|a, b| a.name.cmp(&b.name));
+        for node in level {
+            sort(&mut node.children);
         }
     }
 
-    Ok(root_nodes.into_values().collect())
+    let mut roots = Vec::new();
+    for tag in list_tags(conn)? {
+        let parts: Vec<&str> = tag.name.split('/').filter(|p| !p.is_empty()).collect();
+        insert(&mut roots, "", &parts, tag.count);
+    }
+    sort(&mut roots);
+
+    Ok(roots)
 }
 
 /// Tag validation result
@@ -2853,6 +3317,7 @@ pub fn export_memories(conn: &Connection) -> Result<ExportData> {
         conn,
         &ListOptions {
             limit: Some(100000),
+            show_superseded: true,
             ..Default::default()
         },
     )?;
@@ -3010,12 +3475,18 @@ pub fn create_section_memory(
         metadata.insert("parent_memory_id".to_string(), serde_json::json!(pid));
     }
 
+    let content = format!("# {}\n\n{}", title, content);
+    let importance = auto_importance(&ImportanceSignals::new(
+        ImportanceSource::DocumentSection,
+        MemoryType::Context,
+        &content,
+    ));
     let input = CreateMemoryInput {
-        content: format!("# {}\n\n{}", title, content),
+        content,
         memory_type: MemoryType::Context,
         tags: vec!["section".to_string()],
         metadata,
-        importance: Some(0.6),
+        importance: Some(importance),
         scope: MemoryScope::Global,
         workspace: workspace.map(String::from),
         tier: MemoryTier::Permanent,
@@ -3051,12 +3522,18 @@ pub fn create_checkpoint(
         serde_json::json!(Utc::now().to_rfc3339()),
     );
 
+    let content = format!("Session Checkpoint: {}\n\n{}", session_id, summary);
+    let importance = auto_importance(&ImportanceSignals::new(
+        ImportanceSource::Checkpoint,
+        MemoryType::Context,
+        &content,
+    ));
     let input = CreateMemoryInput {
-        content: format!("Session Checkpoint: {}\n\n{}", session_id, summary),
+        content,
         memory_type: MemoryType::Context,
         tags: vec!["checkpoint".to_string(), format!("session:{}", session_id)],
         metadata,
-        importance: Some(0.7),
+        importance: Some(importance),
         scope: MemoryScope::Global,
         workspace: workspace.map(String::from),
         tier: MemoryTier::Permanent,
@@ -3074,46 +3551,72 @@ pub fn create_checkpoint(
     create_memory(conn, &input)
 }
 
-/// Temporarily boost a memory's importance
+/// Temporarily boost a memory's importance.
+///
+/// The increment and clamp happen in a single `UPDATE`, so concurrent boosts
+/// of the same memory accumulate instead of overwriting each other. When a
+/// duration is given, the pre-boost importance is captured from the same row
+/// version the boost applied to. Deleted, expired and superseded memories
+/// can't be boosted and return `NotFound`.
 pub fn boost_memory(
     conn: &Connection,
     id: i64,
     boost_amount: f32,
     duration_seconds: Option<i64>,
 ) -> Result<Memory> {
-    let memory = get_memory(conn, id)?;
-    let new_importance = (memory.importance + boost_amount).min(1.0);
     let now = Utc::now();
-
-    // Update importance
-    conn.execute(
-        "UPDATE memories SET importance = ?, updated_at = ? WHERE id = ?",
-        params![new_importance, now.to_rfc3339(), id],
+    let expires =
+        duration_seconds.map(|duration| (now + chrono::Duration::seconds(duration)).to_rfc3339());
+
+    // Column references on the right-hand side see the pre-update row, so
+    // `boost_original_importance` records the importance before this boost
+    let updated = conn.execute(
+        "UPDATE memories
+         SET importance = MAX(0.0, MIN(1.0, importance + ?1)),
+             updated_at = ?2,
+             metadata = CASE WHEN ?3 IS NULL THEN metadata
+                 ELSE json_set(COALESCE(metadata, '{}'),
+                               '$.boost_expires', ?3,
+                               '$.boost_original_importance', importance)
+             END
+         WHERE id = ?4 AND valid_to IS NULL AND superseded_at IS NULL
+           AND (expires_at IS NULL OR expires_at > ?2)",
+        params![boost_amount, now.to_rfc3339(), expires, id],
     )?;
-
-    // If duration specified, store boost info in metadata for later decay
-    if let Some(duration) = duration_seconds {
-        let expires = now + chrono::Duration::seconds(duration);
-        let mut metadata = memory.metadata.clone();
-        metadata.insert(
-            "boost_expires".to_string(),
-            serde_json::json!(expires.to_rfc3339()),
-        );
-        metadata.insert(
-            "boost_original_importance".to_string(),
-            serde_json::json!(memory.importance),
-        );
-
-        let metadata_json = serde_json::to_string(&metadata)?;
-        conn.execute(
-            "UPDATE memories SET metadata = ? WHERE id = ?",
-            params![metadata_json, id],
-        )?;
+    if updated == 0 {
+        return Err(EngramError::NotFound(id));
     }
 
     get_memory(conn, id)
 }
 
+/// Apply buffered access counts in one transaction-friendly pass.
+///
+/// Each entry adds `count` to the memory's access count and moves
+/// `last_accessed_at` forward to `last_accessed`, never backward, so a late
+/// flush can't undo a fresher direct access. Returns the number of memories
+/// updated; IDs that no longer exist are skipped.
+pub fn record_access_batch(
+    conn: &Connection,
+    accesses: &[(i64, i64, DateTime<Utc>)],
+) -> Result<usize> {
+    let mut stmt = conn.prepare_cached(
+        "UPDATE memories
+         SET access_count = access_count + ?1,
+             last_accessed_at = CASE
+                 WHEN last_accessed_at IS NULL OR last_accessed_at < ?2 THEN ?2
+                 ELSE last_accessed_at
+             END
+         WHERE id = ?3",
+    )?;
+
+    let mut updated = 0;
+    for (id, count, last_accessed) in accesses {
+        updated += stmt.execute(params![count, last_accessed.to_rfc3339(), id])?;
+    }
+    Ok(updated)
+}
+
 // =============================================================================
 // Event System
 // =============================================================================
@@ -3185,17 +3688,17 @@ pub fn record_event(
     let now = Utc::now();
     let data_json = serde_json::to_string(&data)?;
 
-    conn.execute(
+    conn.prepare_cached(
         "INSERT INTO memory_events (event_type, memory_id, agent_id, data, created_at)
          VALUES (?, ?, ?, ?, ?)",
-        params![
-            event_type.to_string(),
-            memory_id,
-            agent_id,
-            data_json,
-            now.to_rfc3339()
-        ],
-    )?;
+    )?
+    .execute(params![
+        event_type.to_string(),
+        memory_id,
+        agent_id,
+        data_json,
+        now.to_rfc3339()
+    ])?;
 
     Ok(conn.last_insert_rowid())
 }
@@ -3323,6 +3826,9 @@ pub struct SyncVersion {
 }
 
 /// Sync task status record (Phase 3 - Langfuse integration)
+///
+/// Also used by other long-running jobs (embedding rebuilds), which report
+/// through the generic `items_*` counters and `eta_seconds`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncTask {
     pub task_id: String,
@@ -3334,6 +3840,12 @@ pub struct SyncTask {
     pub error_message: Option<String>,
     pub started_at: String,
     pub completed_at: Option<String>,
+    #[serde(default)]
+    pub items_total: i64,
+    #[serde(default)]
+    pub items_processed: i64,
+    #[serde(default)]
+    pub eta_seconds: Option<i64>,
 }
 
 /// Get the current sync version
@@ -3374,9 +3886,9 @@ pub fn upsert_sync_task(conn: &Connection, task: &SyncTask) -> Result<()> {
         r#"
         INSERT INTO sync_tasks (
             task_id, task_type, status, progress_percent, traces_processed, memories_created,
-            error_message, started_at, completed_at
+            error_message, started_at, completed_at, items_total, items_processed, eta_seconds
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(task_id) DO UPDATE SET
             task_type = excluded.task_type,
             status = excluded.status,
@@ -3385,7 +3897,10 @@ pub fn upsert_sync_task(conn: &Connection, task: &SyncTask) -> Result<()> {
             memories_created = excluded.memories_created,
             error_message = excluded.error_message,
             started_at = excluded.started_at,
-            completed_at = excluded.completed_at
+            completed_at = excluded.completed_at,
+            items_total = excluded.items_total,
+            items_processed = excluded.items_processed,
+            eta_seconds = excluded.eta_seconds
         "#,
         params![
             task.task_id,
@@ -3396,7 +3911,10 @@ pub fn upsert_sync_task(conn: &Connection, task: &SyncTask) -> Result<()> {
             task.memories_created,
             task.error_message,
             task.started_at,
-            task.completed_at
+            task.completed_at,
+            task.items_total,
+            task.items_processed,
+            task.eta_seconds
         ],
     )?;
 
@@ -3408,7 +3926,7 @@ pub fn get_sync_task(conn: &Connection, task_id: &str) -> Result<Option<SyncTask
     let mut stmt = conn.prepare(
         r#"
         SELECT task_id, task_type, status, progress_percent, traces_processed, memories_created,
-               error_message, started_at, completed_at
+               error_message, started_at, completed_at, items_total, items_processed, eta_seconds
         FROM sync_tasks
         WHERE task_id = ?
         "#,
@@ -3416,22 +3934,119 @@ pub fn get_sync_task(conn: &Connection, task_id: &str) -> Result<Option<SyncTask
 
     let mut rows = stmt.query(params![task_id])?;
     if let Some(row) = rows.next()? {
-        Ok(Some(SyncTask {
-            task_id: row.get("task_id")?,
-            task_type: row.get("task_type")?,
-            status: row.get("status")?,
-            progress_percent: row.get("progress_percent")?,
-            traces_processed: row.get("traces_processed")?,
-            memories_created: row.get("memories_created")?,
-            error_message: row.get("error_message")?,
-            started_at: row.get("started_at")?,
-            completed_at: row.get("completed_at")?,
-        }))
+        Ok(Some(sync_task_from_row(row)?))
     } else {
         Ok(None)
     }
 }
 
+/// Most recently started sync tasks, optionally of one type
+pub fn list_sync_tasks(
+    conn: &Connection,
+    task_type: Option<&str>,
+    limit: i64,
+) -> Result<Vec<SyncTask>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT task_id, task_type, status, progress_percent, traces_processed, memories_created,
+               error_message, started_at, completed_at, items_total, items_processed, eta_seconds
+        FROM sync_tasks
+        WHERE ?1 IS NULL OR task_type = ?1
+        ORDER BY started_at DESC
+        LIMIT ?2
+        "#,
+    )?;
+
+    let tasks = stmt
+        .query_map(params![task_type, limit], sync_task_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(tasks)
+}
+
+fn sync_task_from_row(row: &rusqlite::Row) -> rusqlite::Result<SyncTask> {
+    Ok(SyncTask {
+        task_id: row.get("task_id")?,
+        task_type: row.get("task_type")?,
+        status: row.get("status")?,
+        progress_percent: row.get("progress_percent")?,
+        traces_processed: row.get("traces_processed")?,
+        memories_created: row.get("memories_created")?,
+        error_message: row.get("error_message")?,
+        started_at: row.get("started_at")?,
+        completed_at: row.get("completed_at")?,
+        items_total: row.get("items_total")?,
+        items_processed: row.get("items_processed")?,
+        eta_seconds: row.get("eta_seconds")?,
+    })
+}
+
+/// The memory a Langfuse trace was imported into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceMemoryLink {
+    pub trace_id: String,
+    pub workspace: String,
+    pub memory_id: i64,
+    /// Hash of what was last imported; empty for links recorded by the v43
+    /// backfill
+    pub fingerprint: String,
+    pub imported_at: String,
+    pub updated_at: String,
+}
+
+/// Memory a trace was imported into within a workspace, if any
+pub fn get_trace_memory(
+    conn: &Connection,
+    trace_id: &str,
+    workspace: &str,
+) -> Result<Option<TraceMemoryLink>> {
+    let link = conn
+        .query_row(
+            "SELECT trace_id, workspace, memory_id, fingerprint, imported_at, updated_at
+             FROM langfuse_trace_memories
+             WHERE trace_id = ? AND workspace = ?",
+            params![trace_id, workspace],
+            |row| {
+                Ok(TraceMemoryLink {
+                    trace_id: row.get(0)?,
+                    workspace: row.get(1)?,
+                    memory_id: row.get(2)?,
+                    fingerprint: row.get(3)?,
+                    imported_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            },
+        )
+        .optional()?;
+    Ok(link)
+}
+
+/// Record that a trace was imported into `memory_id`. `imported_at` is kept
+/// while the trace maps to the same memory.
+pub fn upsert_trace_memory(
+    conn: &Connection,
+    trace_id: &str,
+    workspace: &str,
+    memory_id: i64,
+    fingerprint: &str,
+) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        r#"
+        INSERT INTO langfuse_trace_memories
+            (trace_id, workspace, memory_id, fingerprint, imported_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+        ON CONFLICT(trace_id, workspace) DO UPDATE SET
+            imported_at = CASE WHEN memory_id = excluded.memory_id
+                               THEN imported_at ELSE excluded.imported_at END,
+            memory_id = excluded.memory_id,
+            fingerprint = excluded.fingerprint,
+            updated_at = excluded.updated_at
+        "#,
+        params![trace_id, workspace, memory_id, fingerprint, now],
+    )?;
+    Ok(())
+}
+
 /// Delta entry for sync
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncDelta {
@@ -3743,12 +4358,17 @@ pub fn search_by_identity(
     Ok(memories)
 }
 
-/// Search within session transcript chunks
+/// Search within session transcript chunks.
+///
+/// `filter` narrows results to chunks holding a message with the given role,
+/// kind and/or tool; chunks indexed before message facets were recorded never
+/// match a non-empty filter.
 pub fn search_sessions(
     conn: &Connection,
     query_text: &str,
     session_id: Option<&str>,
     workspace: Option<&str>,
+    filter: &crate::intelligence::session_indexing::TranscriptFilter,
     limit: Option<usize>,
 ) -> Result<Vec<Memory>> {
     let limit = limit.unwrap_or(20);
@@ -3778,6 +4398,28 @@ pub fn search_sessions(
         params_vec.push(Box::new(ws.to_string()));
     }
 
+    // Role-aware filters: some message in the chunk must match them all
+    let mut facet_conditions = Vec::new();
+    if let Some(role) = &filter.role {
+        facet_conditions.push("json_extract(f.value, '$.role') = ?");
+        params_vec.push(Box::new(role.clone()));
+    }
+    if let Some(kind) = filter.kind {
+        facet_conditions.push("json_extract(f.value, '$.kind') = ?");
+        params_vec.push(Box::new(kind.as_str()));
+    }
+    if let Some(tool_name) = &filter.tool_name {
+        facet_conditions.push("json_extract(f.value, '$.tool_name') = ?");
+        params_vec.push(Box::new(tool_name.clone()));
+    }
+    let facet_clause = format!(
+        "EXISTS (SELECT 1 FROM json_each(m.metadata, '$.message_facets') f WHERE {})",
+        facet_conditions.join(" AND ")
+    );
+    if !filter.is_empty() {
+        conditions.push(&facet_clause);
+    }
+
     // Add content search
     conditions.push("m.content LIKE ?");
     params_vec.push(Box::new(pattern));
@@ -4392,6 +5034,84 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_set_memory_expiration_bulk() {
+        let storage = Storage::open_in_memory().unwrap();
+
+        storage
+            .with_transaction(|conn| {
+                let mut seeded = vec![];
+                for i in 0..3 {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: format!("Seeded fact {}", i),
+                            tags: vec!["seed".to_string()],
+                            workspace: Some("facts".to_string()),
+                            defer_embedding: true,
+                            ..Default::default()
+                        },
+                    )?;
+                    seeded.push(memory.id);
+                }
+                let other = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "Hand-written fact".to_string(),
+                        workspace: Some("facts".to_string()),
+                        defer_embedding: true,
+                        ..Default::default()
+                    },
+                )?;
+                // Backdate one seeded fact by ten days
+                conn.execute(
+                    "UPDATE memories SET created_at = ? WHERE id = ?",
+                    params![
+                        (Utc::now() - chrono::Duration::days(10)).to_rfc3339(),
+                        seeded[0]
+                    ],
+                )?;
+
+                let seeds = ExpirationFilter {
+                    list: ListOptions {
+                        tags: Some(vec!["seed".to_string()]),
+                        workspace: Some("facts".to_string()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                let preview = set_memory_expiration_bulk(conn, &seeds, Some(3600), true)?;
+                assert_eq!((preview.matched, preview.retiered), (3, 3));
+                assert!(get_memory(conn, seeded[1])?.expires_at.is_none());
+
+                let report = set_memory_expiration_bulk(conn, &seeds, Some(3600), false)?;
+                assert_eq!(report.matched, 3);
+                for id in &seeded {
+                    let memory = get_memory(conn, *id)?;
+                    assert_eq!(memory.tier, MemoryTier::Daily);
+                    assert_eq!(memory.expires_at, report.expires_at);
+                }
+                assert!(get_memory(conn, other.id)?.expires_at.is_none());
+
+                // Only the backdated fact is old enough; null makes it permanent
+                let old = ExpirationFilter {
+                    older_than_days: Some(7.0),
+                    ..seeds.clone()
+                };
+                let report = set_memory_expiration_bulk(conn, &old, None, false)?;
+                assert_eq!(report.memory_ids, vec![seeded[0]]);
+                let memory = get_memory(conn, seeded[0])?;
+                assert_eq!(memory.tier, MemoryTier::Permanent);
+                assert!(memory.expires_at.is_none());
+
+                let everything = ExpirationFilter::default();
+                assert!(set_memory_expiration_bulk(conn, &everything, None, true).is_err());
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
     #[test]
     fn test_cleanup_expired_memories() {
         let storage = Storage::open_in_memory().unwrap();
@@ -4470,7 +5190,7 @@ mod tests {
                 assert_eq!(expired_count, 3);
 
                 // Cleanup should delete 3
-                let deleted = cleanup_expired_memories(conn)?;
+                let deleted = cleanup_expired_memories(conn, 0)?;
                 assert_eq!(deleted, 3);
 
                 // Verify only 2 remain
@@ -4486,6 +5206,111 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_expired_memories_keep_a_grace_period() {
+        let storage = Storage::open_in_memory().unwrap();
+
+        storage
+            .with_transaction(|conn| {
+                let mut ids = vec![];
+                for days_ago in [1, 10] {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: format!("Expired {} days ago", days_ago),
+                            tier: MemoryTier::Daily,
+                            ttl_seconds: Some(3600),
+                            defer_embedding: true,
+                            ..Default::default()
+                        },
+                    )?;
+                    conn.execute(
+                        "UPDATE memories SET expires_at = ? WHERE id = ?",
+                        params![
+                            (Utc::now() - chrono::Duration::days(days_ago)).to_rfc3339(),
+                            memory.id
+                        ],
+                    )?;
+                    ids.push(memory.id);
+                }
+
+                // Hidden from normal queries; only the one still in its grace
+                // period is listed as expired
+                assert!(list_memories(conn, &ListOptions::default())?.is_empty());
+                let expired = list_expired_memories(conn, None, 10, 7)?;
+                assert_eq!(expired.len(), 1);
+                assert_eq!(expired[0].memory.id, ids[0]);
+                assert!(expired[0].purge_at > Utc::now());
+                assert_eq!(list_expired_memories(conn, None, 10, 30)?.len(), 2);
+
+                // Only the memory past its grace period is purged
+                assert_eq!(cleanup_expired_memories(conn, 7)?, 1);
+                let expired = list_expired_memories(conn, None, 10, 7)?;
+                assert_eq!(expired.len(), 1);
+
+                let memory = resurrect_memory(conn, ids[0], Some(600), 7)?;
+                assert_eq!(memory.tier, MemoryTier::Daily);
+                assert!(memory.expires_at.unwrap() > Utc::now());
+                assert_eq!(list_memories(conn, &ListOptions::default())?.len(), 1);
+
+                assert!(matches!(
+                    resurrect_memory(conn, ids[0], None, 7),
+                    Err(EngramError::InvalidInput(_))
+                ));
+                assert!(matches!(
+                    resurrect_memory(conn, ids[1], None, 7),
+                    Err(EngramError::NotFound(_))
+                ));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_resurrect_refuses_memories_past_grace_before_cleanup() {
+        let storage = Storage::open_in_memory().unwrap();
+
+        storage
+            .with_transaction(|conn| {
+                let memory = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "Expired long ago".to_string(),
+                        tier: MemoryTier::Daily,
+                        ttl_seconds: Some(3600),
+                        defer_embedding: true,
+                        ..Default::default()
+                    },
+                )?;
+                conn.execute(
+                    "UPDATE memories SET expires_at = ? WHERE id = ?",
+                    params![
+                        (Utc::now() - chrono::Duration::days(10)).to_rfc3339(),
+                        memory.id
+                    ],
+                )?;
+
+                // Cleanup hasn't run, but the grace period is over
+                assert!(matches!(
+                    resurrect_memory(conn, memory.id, None, 7),
+                    Err(EngramError::NotFound(id)) if id == memory.id
+                ));
+                let expires_at: Option<String> = conn.query_row(
+                    "SELECT expires_at FROM memories WHERE id = ?",
+                    params![memory.id],
+                    |row| row.get(0),
+                )?;
+                assert!(expires_at.is_some());
+
+                // A longer grace period still covers it
+                let restored = resurrect_memory(conn, memory.id, None, 30)?;
+                assert_eq!(restored.tier, MemoryTier::Permanent);
+                assert!(restored.expires_at.is_none());
+                Ok(())
+            })
+            .unwrap();
+    }
+
     #[test]
     fn test_advanced_filter_comparison_operators() {
         let storage = Storage::open_in_memory().unwrap();
@@ -5082,7 +5907,7 @@ mod tests {
                 // Content hash should be set
                 assert!(memory.content_hash.is_some());
                 let hash = memory.content_hash.as_ref().unwrap();
-                assert!(hash.starts_with("sha256:"));
+                assert!(hash.as_str().starts_with("sha256:"));
 
                 // Fetch from DB and verify hash is persisted
                 let fetched = get_memory(conn, memory.id)?;
@@ -5149,7 +5974,7 @@ mod tests {
 
                 // Verify against expected hash
                 let expected_hash = compute_content_hash("Updated content");
-                assert_eq!(updated.content_hash.as_ref().unwrap(), &expected_hash);
+                assert_eq!(updated.content_hash.as_ref().unwrap(), expected_hash.as_str());
 
                 Ok(())
             })
@@ -5559,7 +6384,7 @@ mod tests {
 
     #[test]
     fn test_schema_migration_v34_idempotent() {
-        use crate::storage::migrations::run_migrations;
+        use crate::storage::migrations::{run_migrations, SCHEMA_VERSION};
         let conn =
             rusqlite::Connection::open_in_memory().expect("in-memory db");
         run_migrations(&conn).expect("run migrations");
@@ -5572,7 +6397,7 @@ mod tests {
                 |row| row.get(0),
             )
             .expect("query version");
-        assert_eq!(version, 34);
+        assert_eq!(version, SCHEMA_VERSION);
     }
 
     // ========== Advanced Filter Integration Tests (RML-932) ==========
@@ -5763,4 +6588,234 @@ mod tests {
             })
             .unwrap();
     }
+
+    #[test]
+    fn test_get_memory_content_range_pages_by_character() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let memory = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "héllo wörld".to_string(),
+                        defer_embedding: true,
+                        ..Default::default()
+                    },
+                )?;
+
+                let first = get_memory_content_range(conn, memory.id, 0, Some(5))?;
+                assert_eq!(first.content, "héllo");
+                assert_eq!(first.total_chars, 11);
+                assert_eq!(first.total_bytes, "héllo wörld".len());
+                assert_eq!(first.next_offset, Some(5));
+
+                let rest = get_memory_content_range(conn, memory.id, 5, None)?;
+                assert_eq!(rest.content, " wörld");
+                assert_eq!(rest.length, 6);
+                assert_eq!(rest.next_offset, None);
+                assert_eq!(
+                    rest,
+                    ContentRange::from_content(memory.id, &memory.content, 5, None)
+                );
+
+                let past_end = get_memory_content_range(conn, memory.id, 50, Some(5))?;
+                assert!(past_end.content.is_empty());
+                assert_eq!(past_end.next_offset, None);
+
+                assert!(matches!(
+                    get_memory_content_range(conn, memory.id + 1, 0, None),
+                    Err(EngramError::NotFound(_))
+                ));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_list_sync_tasks_newest_first() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                for (id, task_type, started_at) in [
+                    ("a", "embedding_rebuild", "2024-01-01T00:00:00Z"),
+                    ("b", "langfuse_sync", "2024-01-02T00:00:00Z"),
+                    ("c", "embedding_rebuild", "2024-01-03T00:00:00Z"),
+                ] {
+                    upsert_sync_task(
+                        conn,
+                        &SyncTask {
+                            task_id: id.to_string(),
+                            task_type: task_type.to_string(),
+                            status: "completed".to_string(),
+                            progress_percent: 100,
+                            traces_processed: 0,
+                            memories_created: 0,
+                            error_message: None,
+                            started_at: started_at.to_string(),
+                            completed_at: None,
+                            items_total: 0,
+                            items_processed: 0,
+                            eta_seconds: None,
+                        },
+                    )?;
+                }
+
+                let ids = |tasks: Vec<SyncTask>| {
+                    tasks.into_iter().map(|t| t.task_id).collect::<Vec<_>>()
+                };
+                assert_eq!(ids(list_sync_tasks(conn, None, 10)?), ["c", "b", "a"]);
+                assert_eq!(
+                    ids(list_sync_tasks(conn, Some("embedding_rebuild"), 10)?),
+                    ["c", "a"]
+                );
+                assert_eq!(ids(list_sync_tasks(conn, None, 1)?), ["c"]);
+                assert_eq!(get_sync_task(conn, "b")?.unwrap().task_type, "langfuse_sync");
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_tag_hierarchy_nests_paths() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                for tags in [
+                    vec!["project/engram/core"],
+                    vec!["project/engram", "todo"],
+                    vec!["project/web", "project//engram/"],
+                ] {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: tags.join(" "),
+                            tags: tags.iter().map(|t| t.to_string()).collect(),
+                            ..Default::default()
+                        },
+                    )?;
+                }
+
+                let roots = get_tag_hierarchy(conn)?;
+                let names: Vec<&str> = roots.iter().map(|n| n.name.as_str()).collect();
+                assert_eq!(names, ["project", "todo"]);
+                let project = &roots[0];
+                assert_eq!(project.count, 4);
+                let children: Vec<(&str, i64)> = project
+                    .children
+                    .iter()
+                    .map(|n| (n.full_path.as_str(), n.count))
+                    .collect();
+                assert_eq!(children, [("project/engram", 3), ("project/web", 1)]);
+                let core = &project.children[0].children[0];
+                assert_eq!((core.full_path.as_str(), core.count), ("project/engram/core", 1));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_trace_memory_link() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                assert!(get_trace_memory(conn, "t1", "default")?.is_none());
+
+                upsert_trace_memory(conn, "t1", "default", 1, "a")?;
+                let first = get_trace_memory(conn, "t1", "default")?.unwrap();
+                assert_eq!((first.memory_id, first.fingerprint.as_str()), (1, "a"));
+                assert!(get_trace_memory(conn, "t1", "other")?.is_none());
+
+                // Same memory: only the fingerprint moves
+                upsert_trace_memory(conn, "t1", "default", 1, "b")?;
+                let refreshed = get_trace_memory(conn, "t1", "default")?.unwrap();
+                assert_eq!(refreshed.fingerprint, "b");
+                assert_eq!(refreshed.imported_at, first.imported_at);
+
+                upsert_trace_memory(conn, "t1", "default", 2, "b")?;
+                assert_eq!(get_trace_memory(conn, "t1", "default")?.unwrap().memory_id, 2);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_boost_memory_accumulates_and_clamps() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let memory = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "boost me".to_string(),
+                        importance: Some(0.5),
+                        ..Default::default()
+                    },
+                )?;
+
+                boost_memory(conn, memory.id, 0.2, None)?;
+                let boosted = boost_memory(conn, memory.id, 0.2, Some(60))?;
+                assert!((boosted.importance - 0.9).abs() < 1e-6);
+                let original = boosted.metadata["boost_original_importance"]
+                    .as_f64()
+                    .unwrap();
+                assert!((original - 0.7).abs() < 1e-6);
+                assert!(boosted.metadata.contains_key("boost_expires"));
+
+                assert_eq!(boost_memory(conn, memory.id, 5.0, None)?.importance, 1.0);
+                assert_eq!(boost_memory(conn, memory.id, -5.0, None)?.importance, 0.0);
+                assert!(matches!(
+                    boost_memory(conn, memory.id + 1000, 0.1, None),
+                    Err(EngramError::NotFound(_))
+                ));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_boost_memory_skips_expired_and_superseded() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let create = |content: &str| {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            importance: Some(0.5),
+                            ..Default::default()
+                        },
+                    )
+                };
+                let expired = create("expired")?;
+                conn.execute(
+                    "UPDATE memories SET expires_at = ? WHERE id = ?",
+                    params![
+                        (Utc::now() - chrono::Duration::hours(1)).to_rfc3339(),
+                        expired.id
+                    ],
+                )?;
+                let old = create("old decision")?;
+                let new = create("new decision")?;
+                crate::storage::supersession::mark_superseded(conn, old.id, Some(new.id), None)?;
+
+                assert!(matches!(
+                    boost_memory(conn, expired.id, 0.2, None),
+                    Err(EngramError::NotFound(id)) if id == expired.id
+                ));
+                assert!(matches!(
+                    boost_memory(conn, old.id, 0.2, Some(60)),
+                    Err(EngramError::NotFound(id)) if id == old.id
+                ));
+                let untouched: f32 = conn.query_row(
+                    "SELECT importance FROM memories WHERE id = ?",
+                    params![old.id],
+                    |row| row.get(0),
+                )?;
+                assert!((untouched - 0.5).abs() < 1e-6);
+                assert!((boost_memory(conn, new.id, 0.2, None)?.importance - 0.7).abs() < 1e-6);
+                Ok(())
+            })
+            .unwrap();
+    }
 }
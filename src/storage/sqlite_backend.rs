@@ -150,6 +150,7 @@ impl StorageBackend for SqliteBackend {
 
             // Tag filter (requires join)
             if let Some(ref tags) = options.tags {
+                let tags = &crate::storage::expand_tag_filter(conn, tags)?;
                 if !tags.is_empty() {
                     sql.push_str(
                         " JOIN memory_tags mt ON m.id = mt.memory_id
@@ -0,0 +1,254 @@
+//! Superseded ("blocklisted") memories.
+//!
+//! A superseded memory is known to be wrong or replaced. It stays in the
+//! database for audit, but search, listing and context assembly skip it unless
+//! the caller passes `show_superseded`. Memories are marked explicitly with
+//! [`mark_superseded`] or automatically when a contradiction is resolved in
+//! favour of the other memory.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::storage::queries::{record_event, MemoryEventType};
+
+/// Supersession record of a single memory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SupersededMemory {
+    pub memory_id: i64,
+    /// Memory that replaces this one, if any
+    pub superseded_by: Option<i64>,
+    pub superseded_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub workspace: String,
+}
+
+/// Mark `memory_id` as superseded, optionally by `superseded_by`.
+///
+/// Re-marking an already superseded memory overwrites the previous record.
+pub fn mark_superseded(
+    conn: &Connection,
+    memory_id: i64,
+    superseded_by: Option<i64>,
+    reason: Option<&str>,
+) -> Result<SupersededMemory> {
+    if superseded_by == Some(memory_id) {
+        return Err(EngramError::InvalidInput(
+            "A memory cannot supersede itself".to_string(),
+        ));
+    }
+    if let Some(winner) = superseded_by {
+        ensure_active(conn, winner)?;
+    }
+    ensure_active(conn, memory_id)?;
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE memories
+         SET superseded_by = ?, superseded_at = ?, superseded_reason = ?
+         WHERE id = ? AND valid_to IS NULL",
+        params![superseded_by, now, reason, memory_id],
+    )?;
+    record_event(
+        conn,
+        MemoryEventType::Updated,
+        Some(memory_id),
+        None,
+        serde_json::json!({
+            "changes": ["superseded"],
+            "superseded_by": superseded_by,
+            "reason": reason,
+        }),
+    )?;
+
+    get_superseded(conn, memory_id)?.ok_or(EngramError::NotFound(memory_id))
+}
+
+/// Clear the superseded marker so the memory is retrievable again.
+///
+/// Returns `false` if the memory was not superseded.
+pub fn restore_superseded(conn: &Connection, memory_id: i64) -> Result<bool> {
+    ensure_active(conn, memory_id)?;
+    let updated = conn.execute(
+        "UPDATE memories
+         SET superseded_by = NULL, superseded_at = NULL, superseded_reason = NULL
+         WHERE id = ? AND valid_to IS NULL AND superseded_at IS NOT NULL",
+        params![memory_id],
+    )?;
+    if updated > 0 {
+        record_event(
+            conn,
+            MemoryEventType::Updated,
+            Some(memory_id),
+            None,
+            serde_json::json!({"changes": ["restored"]}),
+        )?;
+    }
+    Ok(updated > 0)
+}
+
+/// Supersession record of `memory_id`, or `None` if it is not superseded.
+pub fn get_superseded(conn: &Connection, memory_id: i64) -> Result<Option<SupersededMemory>> {
+    let record = conn
+        .query_row(
+            "SELECT id, superseded_by, superseded_at, superseded_reason, workspace
+             FROM memories
+             WHERE id = ? AND valid_to IS NULL AND superseded_at IS NOT NULL",
+            params![memory_id],
+            row_to_superseded,
+        )
+        .optional()?;
+    Ok(record)
+}
+
+/// Superseded memories, most recently superseded first.
+pub fn list_superseded(
+    conn: &Connection,
+    workspace: Option<&str>,
+    limit: i64,
+) -> Result<Vec<SupersededMemory>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, superseded_by, superseded_at, superseded_reason, workspace
+         FROM memories
+         WHERE valid_to IS NULL AND superseded_at IS NOT NULL
+           AND (?1 IS NULL OR workspace = ?1)
+         ORDER BY superseded_at DESC, id DESC
+         LIMIT ?2",
+    )?;
+    let records = stmt
+        .query_map(params![workspace, limit], row_to_superseded)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(records)
+}
+
+/// Whether `memory_id` is currently superseded.
+pub fn is_superseded(conn: &Connection, memory_id: i64) -> Result<bool> {
+    Ok(get_superseded(conn, memory_id)?.is_some())
+}
+
+fn ensure_active(conn: &Connection, memory_id: i64) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM memories WHERE id = ? AND valid_to IS NULL)",
+        params![memory_id],
+        |row| row.get(0),
+    )?;
+    if exists {
+        Ok(())
+    } else {
+        Err(EngramError::NotFound(memory_id))
+    }
+}
+
+fn row_to_superseded(row: &rusqlite::Row) -> rusqlite::Result<SupersededMemory> {
+    let superseded_at: String = row.get(2)?;
+    Ok(SupersededMemory {
+        memory_id: row.get(0)?,
+        superseded_by: row.get(1)?,
+        superseded_at: DateTime::parse_from_rfc3339(&superseded_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        reason: row.get(3)?,
+        workspace: row.get(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_memory, get_memory, list_memories};
+    use crate::storage::Storage;
+    use crate::types::{CreateMemoryInput, ListOptions};
+
+    fn create(conn: &Connection, content: &str) -> i64 {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                ..Default::default()
+            },
+        )
+        .expect("create")
+        .id
+    }
+
+    #[test]
+    fn test_superseded_memories_hidden_from_list_by_default() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                let old = create(conn, "The API listens on port 8080");
+                let new = create(conn, "The API listens on port 9090");
+
+                let record = mark_superseded(conn, old, Some(new), Some("port changed"))?;
+                assert_eq!(record.superseded_by, Some(new));
+                assert!(is_superseded(conn, old)?);
+
+                let visible: Vec<i64> = list_memories(conn, &ListOptions::default())?
+                    .into_iter()
+                    .map(|m| m.id)
+                    .collect();
+                assert_eq!(visible, vec![new]);
+
+                let all = list_memories(
+                    conn,
+                    &ListOptions {
+                        show_superseded: true,
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(all.len(), 2);
+
+                // Still retrievable directly for audit.
+                assert_eq!(get_memory(conn, old)?.id, old);
+                Ok(())
+            })
+            .expect("transaction");
+    }
+
+    #[test]
+    fn test_restore_and_list_superseded() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                let a = create(conn, "alpha");
+                let b = create(conn, "beta");
+                mark_superseded(conn, a, None, None)?;
+                mark_superseded(conn, b, None, Some("wrong"))?;
+
+                let listed = list_superseded(conn, Some("default"), 10)?;
+                assert_eq!(listed.len(), 2);
+                assert_eq!(list_superseded(conn, Some("other"), 10)?.len(), 0);
+
+                assert!(restore_superseded(conn, a)?);
+                assert!(!restore_superseded(conn, a)?);
+                assert!(!is_superseded(conn, a)?);
+                assert_eq!(list_superseded(conn, None, 10)?[0].memory_id, b);
+                Ok(())
+            })
+            .expect("transaction");
+    }
+
+    #[test]
+    fn test_mark_superseded_rejects_bad_ids() {
+        let storage = Storage::open_in_memory().expect("open");
+        storage
+            .with_transaction(|conn| {
+                let a = create(conn, "alpha");
+                assert!(matches!(
+                    mark_superseded(conn, a, Some(a), None),
+                    Err(EngramError::InvalidInput(_))
+                ));
+                assert!(matches!(
+                    mark_superseded(conn, a, Some(999), None),
+                    Err(EngramError::NotFound(999))
+                ));
+                assert!(matches!(
+                    mark_superseded(conn, 999, None, None),
+                    Err(EngramError::NotFound(999))
+                ));
+                Ok(())
+            })
+            .expect("transaction");
+    }
+}
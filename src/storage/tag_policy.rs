@@ -0,0 +1,506 @@
+//! Tag governance policies
+//!
+//! `validate_tags` only reports on the tag table; a policy (schema v59) is
+//! enforced when clients create or update memories:
+//! - allowed prefixes (`category:`, `entity:`, `project/`): every tag must
+//!   start with one, optionally letting tags without a `:` namespace through
+//! - regex patterns: every tag must match at least one
+//! - a maximum number of tags per memory
+//! - reserved namespaces (`status`, `session`): written by engram itself,
+//!   rejected in client writes and skipped when auditing existing tags
+//!
+//! Tags are checked after normalization and synonym resolution, i.e. as they
+//! would be stored. [`tag_policy_report`] audits the memories already stored.
+
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+
+/// Rules tags written by clients must follow; an empty policy allows anything
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagPolicy {
+    /// Prefixes tags must start with (case-insensitive); empty allows any
+    #[serde(default)]
+    pub allowed_prefixes: Vec<String>,
+    /// Let tags without a `namespace:` through `allowed_prefixes`
+    #[serde(default = "default_true")]
+    pub allow_plain: bool,
+    /// Regexes; every tag must match at least one
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Most tags per memory
+    #[serde(default)]
+    pub max_tags: Option<usize>,
+    /// Namespaces (before the first `:`) only engram writes
+    #[serde(default)]
+    pub reserved_namespaces: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for TagPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_prefixes: Vec::new(),
+            allow_plain: true,
+            patterns: Vec::new(),
+            max_tags: None,
+            reserved_namespaces: Vec::new(),
+        }
+    }
+}
+
+/// The rule a tag breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagPolicyRule {
+    PrefixNotAllowed,
+    PatternMismatch,
+    TooManyTags,
+    ReservedNamespace,
+}
+
+impl TagPolicyRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PrefixNotAllowed => "prefix_not_allowed",
+            Self::PatternMismatch => "pattern_mismatch",
+            Self::TooManyTags => "too_many_tags",
+            Self::ReservedNamespace => "reserved_namespace",
+        }
+    }
+}
+
+/// One broken rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagPolicyViolation {
+    pub rule: TagPolicyRule,
+    /// The offending tag; `None` for [`TagPolicyRule::TooManyTags`]
+    pub tag: Option<String>,
+    pub message: String,
+}
+
+/// A policy with its patterns compiled
+struct CompiledPolicy<'a> {
+    policy: &'a TagPolicy,
+    patterns: Vec<Regex>,
+}
+
+impl TagPolicy {
+    /// Whether the policy has no rules
+    pub fn is_empty(&self) -> bool {
+        self.allowed_prefixes.is_empty()
+            && self.patterns.is_empty()
+            && self.max_tags.is_none()
+            && self.reserved_namespaces.is_empty()
+    }
+
+    /// Every rule `tags` break, as written by a client
+    pub fn violations(&self, tags: &[String]) -> Result<Vec<TagPolicyViolation>> {
+        Ok(self.compile()?.violations(tags, true))
+    }
+
+    fn compile(&self) -> Result<CompiledPolicy<'_>> {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    EngramError::InvalidInput(format!("Invalid tag pattern '{}': {}", pattern, e))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(CompiledPolicy {
+            policy: self,
+            patterns,
+        })
+    }
+
+    fn is_reserved(&self, tag: &str) -> bool {
+        namespace(tag).is_some_and(|ns| {
+            self.reserved_namespaces
+                .iter()
+                .any(|reserved| reserved.trim_end_matches(':').eq_ignore_ascii_case(ns))
+        })
+    }
+}
+
+impl CompiledPolicy<'_> {
+    /// With `client` false, tags in reserved namespaces are engram's own and
+    /// exempt from the other rules
+    fn violations(&self, tags: &[String], client: bool) -> Vec<TagPolicyViolation> {
+        let policy = self.policy;
+        let mut violations = Vec::new();
+        if let Some(max) = policy.max_tags {
+            if tags.len() > max {
+                violations.push(TagPolicyViolation {
+                    rule: TagPolicyRule::TooManyTags,
+                    tag: None,
+                    message: format!("{} tags, at most {} allowed", tags.len(), max),
+                });
+            }
+        }
+
+        for tag in tags {
+            let mut violation = |rule, message| {
+                violations.push(TagPolicyViolation {
+                    rule,
+                    tag: Some(tag.clone()),
+                    message,
+                })
+            };
+            if policy.is_reserved(tag) {
+                if client {
+                    violation(
+                        TagPolicyRule::ReservedNamespace,
+                        format!(
+                            "Tag '{}' is in a reserved namespace and can't be set directly",
+                            tag
+                        ),
+                    );
+                }
+                continue;
+            }
+            let lower = tag.to_lowercase();
+            let plain = namespace(tag).is_none() && policy.allow_plain;
+            if !policy.allowed_prefixes.is_empty()
+                && !plain
+                && !policy
+                    .allowed_prefixes
+                    .iter()
+                    .any(|prefix| lower.starts_with(&prefix.to_lowercase()))
+            {
+                violation(
+                    TagPolicyRule::PrefixNotAllowed,
+                    format!(
+                        "Tag '{}' must start with one of: {}",
+                        tag,
+                        policy.allowed_prefixes.join(", ")
+                    ),
+                );
+            }
+            if !self.patterns.is_empty() && !self.patterns.iter().any(|re| re.is_match(tag)) {
+                violation(
+                    TagPolicyRule::PatternMismatch,
+                    format!(
+                        "Tag '{}' matches none of the patterns: {}",
+                        tag,
+                        policy.patterns.join(", ")
+                    ),
+                );
+            }
+        }
+        violations
+    }
+}
+
+/// The part of `tag` before the first `:`, if any
+fn namespace(tag: &str) -> Option<&str> {
+    tag.split_once(':')
+        .map(|(ns, _)| ns.trim())
+        .filter(|ns| !ns.is_empty())
+}
+
+/// The stored tag policy; empty if none was set
+pub fn get_tag_policy(conn: &Connection) -> Result<TagPolicy> {
+    let policy: Option<String> = conn
+        .prepare_cached("SELECT policy FROM tag_policy WHERE id = 1")?
+        .query_row([], |row| row.get(0))
+        .optional()?;
+    Ok(policy
+        .and_then(|p| serde_json::from_str(&p).ok())
+        .unwrap_or_default())
+}
+
+/// Replace the tag policy. Fails without storing it if a pattern is not a
+/// valid regex.
+pub fn set_tag_policy(conn: &Connection, policy: &TagPolicy) -> Result<()> {
+    policy.compile()?;
+    conn.execute(
+        "INSERT INTO tag_policy (id, policy, updated_at) VALUES (1, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET policy = excluded.policy, updated_at = excluded.updated_at",
+        params![serde_json::to_string(policy)?, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Check tags a client is about to write against the stored policy, as they
+/// would be stored after normalization and synonym resolution.
+///
+/// # Errors
+///
+/// Returns [`EngramError::InvalidInput`] listing every broken rule.
+pub fn check_tag_policy(conn: &Connection, tags: &[String]) -> Result<()> {
+    let policy = get_tag_policy(conn)?;
+    if policy.is_empty() || tags.is_empty() {
+        return Ok(());
+    }
+    let tags = super::tag_synonyms::resolve_tags(conn, tags)?;
+    let violations = policy.violations(&tags)?;
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+    Err(EngramError::InvalidInput(format!(
+        "Tag policy violation: {}",
+        messages.join("; ")
+    )))
+}
+
+/// Options for [`tag_policy_report`]
+#[derive(Debug, Clone, Deserialize)]
+pub struct TagPolicyReportOptions {
+    /// Only memories in this workspace
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// Most memories listed in the report; counts cover every memory
+    #[serde(default = "default_report_limit")]
+    pub limit: usize,
+}
+
+fn default_report_limit() -> usize {
+    100
+}
+
+impl Default for TagPolicyReportOptions {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            limit: default_report_limit(),
+        }
+    }
+}
+
+/// A stored memory whose tags break the policy
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryTagViolations {
+    pub memory_id: i64,
+    pub workspace: String,
+    pub tags: Vec<String>,
+    pub violations: Vec<TagPolicyViolation>,
+}
+
+/// Policy violations in stored memories
+#[derive(Debug, Clone, Serialize)]
+pub struct TagPolicyReport {
+    pub policy: TagPolicy,
+    pub memories_checked: usize,
+    pub memories_in_violation: usize,
+    /// Violations per rule
+    pub by_rule: BTreeMap<String, usize>,
+    /// Offending tags with the number of memories carrying them, most first
+    pub offending_tags: Vec<(String, usize)>,
+    /// Memories in violation, by ID, up to the report limit
+    pub memories: Vec<MemoryTagViolations>,
+}
+
+/// Audit stored memories against the policy. Tags in reserved namespaces are
+/// engram's own, so they only count towards `max_tags`.
+pub fn tag_policy_report(
+    conn: &Connection,
+    options: &TagPolicyReportOptions,
+) -> Result<TagPolicyReport> {
+    let policy = get_tag_policy(conn)?;
+    let compiled = policy.compile()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.workspace, t.name
+         FROM memories m
+         JOIN memory_tags mt ON mt.memory_id = m.id
+         JOIN tags t ON t.id = mt.tag_id
+         WHERE m.valid_to IS NULL AND (?1 IS NULL OR m.workspace = ?1)
+         ORDER BY m.id, t.name",
+    )?;
+    let rows = stmt
+        .query_map(params![options.workspace], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut memories: Vec<(i64, String, Vec<String>)> = Vec::new();
+    for (id, workspace, tag) in rows {
+        match memories.last_mut() {
+            Some((last, _, tags)) if *last == id => tags.push(tag),
+            _ => memories.push((id, workspace, vec![tag])),
+        }
+    }
+
+    let mut report = TagPolicyReport {
+        policy: policy.clone(),
+        memories_checked: memories.len(),
+        memories_in_violation: 0,
+        by_rule: BTreeMap::new(),
+        offending_tags: Vec::new(),
+        memories: Vec::new(),
+    };
+    let mut offending: BTreeMap<String, usize> = BTreeMap::new();
+    for (memory_id, workspace, tags) in memories {
+        let violations = compiled.violations(&tags, false);
+        if violations.is_empty() {
+            continue;
+        }
+        report.memories_in_violation += 1;
+        let mut memory_tags: Vec<&str> = Vec::new();
+        for violation in &violations {
+            *report
+                .by_rule
+                .entry(violation.rule.as_str().to_string())
+                .or_default() += 1;
+            if let Some(ref tag) = violation.tag {
+                if !memory_tags.contains(&tag.as_str()) {
+                    memory_tags.push(tag);
+                    *offending.entry(tag.clone()).or_default() += 1;
+                }
+            }
+        }
+        if report.memories.len() < options.limit {
+            report.memories.push(MemoryTagViolations {
+                memory_id,
+                workspace,
+                tags,
+                violations,
+            });
+        }
+    }
+
+    report.offending_tags = offending.into_iter().collect();
+    report
+        .offending_tags
+        .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::storage::{set_tag_normalization, Storage, TagNormalization};
+    use crate::types::CreateMemoryInput;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn policy() -> TagPolicy {
+        TagPolicy {
+            allowed_prefixes: tags(&["category:", "entity:"]),
+            allow_plain: true,
+            patterns: tags(&["^[a-z0-9:/-]+$"]),
+            max_tags: Some(3),
+            reserved_namespaces: tags(&["status"]),
+        }
+    }
+
+    #[test]
+    fn test_policy_violations() {
+        let policy = policy();
+        assert!(policy
+            .violations(&tags(&["category:infra", "entity:postgres", "urgent"]))
+            .unwrap()
+            .is_empty());
+
+        let violations = policy
+            .violations(&tags(&["team:sre", "Urgent", "status:verified", "category:a"]))
+            .unwrap();
+        let rules: Vec<(TagPolicyRule, Option<&str>)> = violations
+            .iter()
+            .map(|v| (v.rule, v.tag.as_deref()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                (TagPolicyRule::TooManyTags, None),
+                (TagPolicyRule::PrefixNotAllowed, Some("team:sre")),
+                (TagPolicyRule::PatternMismatch, Some("Urgent")),
+                (TagPolicyRule::ReservedNamespace, Some("status:verified")),
+            ]
+        );
+
+        let strict = TagPolicy {
+            allow_plain: false,
+            ..policy
+        };
+        assert_eq!(
+            strict.violations(&tags(&["urgent"])).unwrap()[0].rule,
+            TagPolicyRule::PrefixNotAllowed
+        );
+        assert!(TagPolicy::default()
+            .violations(&tags(&["Anything Goes"]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_check_and_report() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                for (content, memory_tags) in [
+                    ("deploy runbook", vec!["category:ops", "status:verified"]),
+                    ("legacy note", vec!["misc:old", "todo"]),
+                    ("another legacy note", vec!["misc:old"]),
+                ] {
+                    create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            tags: tags(&memory_tags),
+                            ..Default::default()
+                        },
+                    )?;
+                }
+
+                assert!(set_tag_policy(
+                    conn,
+                    &TagPolicy {
+                        patterns: tags(&["("]),
+                        ..Default::default()
+                    }
+                )
+                .is_err());
+                assert!(get_tag_policy(conn)?.is_empty());
+
+                set_tag_policy(conn, &policy())?;
+                check_tag_policy(conn, &tags(&["category:ops", "todo"]))?;
+                let err = check_tag_policy(conn, &tags(&["status:verified"]))
+                    .unwrap_err()
+                    .to_string();
+                assert!(err.contains("reserved namespace"), "{}", err);
+
+                // Checked as stored: normalization lowercases first
+                assert!(check_tag_policy(conn, &tags(&["Category:Ops"])).is_err());
+                set_tag_normalization(
+                    conn,
+                    &TagNormalization {
+                        lowercase: true,
+                        ..Default::default()
+                    },
+                    false,
+                )?;
+                check_tag_policy(conn, &tags(&["Category:Ops"]))?;
+
+                let report = tag_policy_report(conn, &TagPolicyReportOptions::default())?;
+                assert_eq!(report.memories_checked, 3);
+                assert_eq!(report.memories_in_violation, 2);
+                assert_eq!(report.by_rule.get("prefix_not_allowed"), Some(&2));
+                assert_eq!(report.offending_tags, vec![("misc:old".to_string(), 2)]);
+                assert!(report.memories.iter().all(|m| !m
+                    .violations
+                    .iter()
+                    .any(|v| v.rule == TagPolicyRule::ReservedNamespace)));
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,694 @@
+//! Tag normalization and synonym groups
+//!
+//! Tags drift into variants of one another (`bugs`, `Bug`, `bug_fix`). Two
+//! mechanisms keep them together (schema v58):
+//! - normalization rules (`tag_normalization`): lowercasing, hyphenating
+//!   spaces and underscores, and singularizing English plurals, applied to
+//!   every tag `create_memory` and `update_memory` write
+//! - synonym groups (`tag_synonyms`): aliases resolving to a canonical tag,
+//!   both when memories are tagged and when searches and lists filter on tags
+//!
+//! [`suggest_tag_synonyms`] proposes groups from edit distance between tag
+//! names and how similar the tags they co-occur with are.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::search::levenshtein;
+
+/// Plural-looking words that are not plurals
+const SINGULAR_EXCEPTIONS: &[&str] = &[
+    "alias",
+    "analysis",
+    "analytics",
+    "atlas",
+    "bias",
+    "canvas",
+    "chaos",
+    "devops",
+    "express",
+    "jenkins",
+    "kubernetes",
+    "less",
+    "logistics",
+    "macos",
+    "news",
+    "physics",
+    "postgres",
+    "process",
+    "progress",
+    "redis",
+    "sales",
+    "series",
+    "species",
+    "status",
+    "windows",
+];
+
+/// Rules applied to tags before they are stored or filtered on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagNormalization {
+    /// `Bug` → `bug`
+    #[serde(default)]
+    pub lowercase: bool,
+    /// `bug fix`, `bug_fix` → `bug-fix`
+    #[serde(default)]
+    pub hyphenate: bool,
+    /// `bugs` → `bug`, `patches` → `patch`, `stories` → `story`; the last
+    /// word of each `/` segment only
+    #[serde(default)]
+    pub singularize: bool,
+}
+
+impl TagNormalization {
+    /// Every rule on
+    pub fn all() -> Self {
+        Self {
+            lowercase: true,
+            hyphenate: true,
+            singularize: true,
+        }
+    }
+
+    /// The normalized form of `tag`
+    pub fn apply(&self, tag: &str) -> String {
+        let mut tag = tag.trim().to_string();
+        if self.lowercase {
+            tag = tag.to_lowercase();
+        }
+        if self.hyphenate {
+            let mut hyphenated = String::with_capacity(tag.len());
+            for c in tag.chars() {
+                let c = if c.is_whitespace() || c == '_' {
+                    '-'
+                } else {
+                    c
+                };
+                if c != '-' || !hyphenated.ends_with('-') {
+                    hyphenated.push(c);
+                }
+            }
+            tag = hyphenated.trim_matches('-').to_string();
+        }
+        if self.singularize {
+            tag = tag
+                .split('/')
+                .map(|segment| match segment.rfind(['-', ' ', '_']) {
+                    Some(pos) => format!("{}{}", &segment[..=pos], singular(&segment[pos + 1..])),
+                    None => singular(segment),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+        }
+        tag
+    }
+}
+
+/// English singular of a lowercase word; anything else is returned as is
+fn singular(word: &str) -> String {
+    if word.len() <= 3
+        || !word.bytes().all(|b| b.is_ascii_lowercase())
+        || SINGULAR_EXCEPTIONS.contains(&word)
+    {
+        return word.to_string();
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        return format!("{}y", stem);
+    }
+    for suffix in ["sses", "xes", "ches", "shes"] {
+        if word.ends_with(suffix) {
+            return word[..word.len() - 2].to_string();
+        }
+    }
+    if word.ends_with('s') && !["ss", "us", "is"].iter().any(|s| word.ends_with(s)) {
+        return word[..word.len() - 1].to_string();
+    }
+    word.to_string()
+}
+
+/// The stored normalization rules; all off if none were set
+pub fn get_tag_normalization(conn: &Connection) -> Result<TagNormalization> {
+    let rules: Option<String> = conn
+        .prepare_cached("SELECT rules FROM tag_normalization WHERE id = 1")?
+        .query_row([], |row| row.get(0))
+        .optional()?;
+    Ok(rules
+        .and_then(|r| serde_json::from_str(&r).ok())
+        .unwrap_or_default())
+}
+
+/// Store the normalization rules for tags written from now on. With
+/// `apply_existing`, existing tags are renamed or merged into their
+/// normalized forms too.
+pub fn set_tag_normalization(
+    conn: &Connection,
+    rules: &TagNormalization,
+    apply_existing: bool,
+) -> Result<TagRewriteReport> {
+    conn.execute(
+        "INSERT INTO tag_normalization (id, rules, updated_at) VALUES (1, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET rules = excluded.rules, updated_at = excluded.updated_at",
+        params![serde_json::to_string(rules)?, Utc::now().to_rfc3339()],
+    )?;
+
+    let mut report = TagRewriteReport::default();
+    if apply_existing {
+        let names: Vec<String> = conn
+            .prepare("SELECT name FROM tags ORDER BY id")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        for name in names {
+            let resolved = resolve_tag(conn, &name)?;
+            if resolved != name {
+                report.memories_retagged += merge_tag(conn, &name, &resolved)?;
+                report.tags_rewritten.push((name, resolved));
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Tags renamed or merged by a rewrite
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TagRewriteReport {
+    /// `(old, new)` tag names
+    pub tags_rewritten: Vec<(String, String)>,
+    /// Memories that had a rewritten tag
+    pub memories_retagged: usize,
+}
+
+/// A canonical tag and the aliases resolving to it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagSynonymGroup {
+    pub canonical: String,
+    pub aliases: Vec<String>,
+}
+
+/// The canonical tag `tag` resolves to: its normalized form, or the
+/// canonical tag of the group that form is an alias in
+pub fn resolve_tag(conn: &Connection, tag: &str) -> Result<String> {
+    let normalized = get_tag_normalization(conn)?.apply(tag);
+    canonical_of(conn, &normalized)
+}
+
+fn canonical_of(conn: &Connection, tag: &str) -> Result<String> {
+    let canonical: Option<String> = conn
+        .prepare_cached("SELECT canonical FROM tag_synonyms WHERE alias = ?")?
+        .query_row(params![tag], |row| row.get(0))
+        .optional()?;
+    Ok(canonical.unwrap_or_else(|| tag.to_string()))
+}
+
+/// [`resolve_tag`] for each of `tags`, without duplicates
+pub(crate) fn resolve_tags(conn: &Connection, tags: &[String]) -> Result<Vec<String>> {
+    let rules = get_tag_normalization(conn)?;
+    let mut resolved: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = canonical_of(conn, &rules.apply(tag))?;
+        if !resolved.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            resolved.push(tag);
+        }
+    }
+    Ok(resolved)
+}
+
+/// Tag names a filter on `tags` should match: each tag as given, its
+/// canonical tag and every alias of that canonical tag
+pub fn expand_tag_filter(conn: &Connection, tags: &[String]) -> Result<Vec<String>> {
+    let mut expanded: Vec<String> = Vec::with_capacity(tags.len());
+    let mut push = |tag: String| {
+        if !expanded.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            expanded.push(tag);
+        }
+    };
+    let mut aliases = conn.prepare_cached("SELECT alias FROM tag_synonyms WHERE canonical = ?")?;
+    for tag in tags {
+        push(tag.clone());
+        let canonical = resolve_tag(conn, tag)?;
+        for alias in aliases
+            .query_map(params![canonical], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+        {
+            push(alias);
+        }
+        push(canonical);
+    }
+    Ok(expanded)
+}
+
+/// SQL condition matching `column` against one tag or any name in its
+/// synonym group; bind the tag [`TAG_MATCH_PARAMS`] times
+pub(crate) fn tag_match_sql(column: &str) -> String {
+    format!(
+        "({column} = ? \
+         OR {column} = (SELECT canonical FROM tag_synonyms WHERE alias = ?) \
+         OR {column} IN (SELECT alias FROM tag_synonyms WHERE canonical = \
+             COALESCE((SELECT canonical FROM tag_synonyms WHERE alias = ?), ?)))"
+    )
+}
+
+/// Placeholders in [`tag_match_sql`]
+pub(crate) const TAG_MATCH_PARAMS: usize = 4;
+
+/// Make `aliases` resolve to `canonical`. With `retag`, memories tagged with
+/// an alias are tagged with the canonical tag instead.
+///
+/// The canonical tag is normalized and resolved first, so adding to an
+/// alias adds to its group; an alias that was itself canonical brings its
+/// aliases along.
+pub fn add_tag_synonyms(
+    conn: &Connection,
+    canonical: &str,
+    aliases: &[String],
+    retag: bool,
+) -> Result<(TagSynonymGroup, TagRewriteReport)> {
+    let rules = get_tag_normalization(conn)?;
+    let canonical = resolve_tag(conn, canonical)?;
+    if canonical.is_empty() {
+        return Err(EngramError::InvalidInput(
+            "canonical tag must not be empty".to_string(),
+        ));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let mut report = TagRewriteReport::default();
+    for alias in aliases {
+        let alias = rules.apply(alias);
+        if alias.is_empty() || alias.eq_ignore_ascii_case(&canonical) {
+            continue;
+        }
+        conn.execute(
+            "UPDATE tag_synonyms SET canonical = ? WHERE canonical = ?",
+            params![canonical, alias],
+        )?;
+        conn.execute(
+            "INSERT INTO tag_synonyms (alias, canonical, created_at) VALUES (?, ?, ?)
+             ON CONFLICT(alias) DO UPDATE SET canonical = excluded.canonical",
+            params![alias, canonical, now],
+        )?;
+        if retag {
+            let retagged = merge_tag(conn, &alias, &canonical)?;
+            if retagged > 0 {
+                report.memories_retagged += retagged;
+                report.tags_rewritten.push((alias, canonical.clone()));
+            }
+        }
+    }
+
+    let group = list_tag_synonyms(conn)?
+        .into_iter()
+        .find(|g| g.canonical.eq_ignore_ascii_case(&canonical))
+        .unwrap_or(TagSynonymGroup {
+            canonical,
+            aliases: Vec::new(),
+        });
+    Ok((group, report))
+}
+
+/// Stop `alias` resolving to its canonical tag. Returns `true` if it was an
+/// alias. Memories already retagged keep the canonical tag.
+pub fn remove_tag_synonym(conn: &Connection, alias: &str) -> Result<bool> {
+    let alias = get_tag_normalization(conn)?.apply(alias);
+    let removed = conn.execute("DELETE FROM tag_synonyms WHERE alias = ?", params![alias])?;
+    Ok(removed > 0)
+}
+
+/// Every synonym group by canonical tag
+pub fn list_tag_synonyms(conn: &Connection) -> Result<Vec<TagSynonymGroup>> {
+    let mut stmt =
+        conn.prepare("SELECT canonical, alias FROM tag_synonyms ORDER BY canonical, alias")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut groups: Vec<TagSynonymGroup> = Vec::new();
+    for (canonical, alias) in rows {
+        match groups.last_mut() {
+            Some(group) if group.canonical.eq_ignore_ascii_case(&canonical) => {
+                group.aliases.push(alias)
+            }
+            _ => groups.push(TagSynonymGroup {
+                canonical,
+                aliases: vec![alias],
+            }),
+        }
+    }
+    Ok(groups)
+}
+
+/// Move every memory tagged `from` to `to` and drop `from`. Tags differing
+/// only in case share a row, which is renamed. Returns the memories moved.
+fn merge_tag(conn: &Connection, from: &str, to: &str) -> Result<usize> {
+    let tag_id = |name: &str| -> Result<Option<i64>> {
+        Ok(conn
+            .prepare_cached("SELECT id FROM tags WHERE name = ?")?
+            .query_row(params![name], |row| row.get(0))
+            .optional()?)
+    };
+    let Some(from_id) = tag_id(from)? else {
+        return Ok(0);
+    };
+    let moved: usize = conn.query_row(
+        "SELECT COUNT(*) FROM memory_tags WHERE tag_id = ?",
+        params![from_id],
+        |row| row.get(0),
+    )?;
+
+    match tag_id(to)? {
+        Some(to_id) if to_id == from_id => {
+            conn.execute(
+                "UPDATE tags SET name = ? WHERE id = ?",
+                params![to, from_id],
+            )?;
+        }
+        Some(to_id) => {
+            conn.execute(
+                "INSERT OR IGNORE INTO memory_tags (memory_id, tag_id)
+                 SELECT memory_id, ? FROM memory_tags WHERE tag_id = ?",
+                params![to_id, from_id],
+            )?;
+            conn.execute("DELETE FROM memory_tags WHERE tag_id = ?", params![from_id])?;
+            conn.execute("DELETE FROM tags WHERE id = ?", params![from_id])?;
+        }
+        None => {
+            conn.execute(
+                "UPDATE tags SET name = ? WHERE id = ?",
+                params![to, from_id],
+            )?;
+        }
+    }
+    Ok(moved)
+}
+
+/// Options for [`suggest_tag_synonyms`]
+#[derive(Debug, Clone, Deserialize)]
+pub struct TagSynonymSuggestOptions {
+    /// Minimum combined score (0-1)
+    #[serde(default = "default_min_score")]
+    pub min_score: f32,
+    #[serde(default = "default_suggest_limit")]
+    pub limit: usize,
+}
+
+fn default_min_score() -> f32 {
+    0.75
+}
+
+fn default_suggest_limit() -> usize {
+    50
+}
+
+impl Default for TagSynonymSuggestOptions {
+    fn default() -> Self {
+        Self {
+            min_score: default_min_score(),
+            limit: default_suggest_limit(),
+        }
+    }
+}
+
+/// A pair of tags that look like one tag
+#[derive(Debug, Clone, Serialize)]
+pub struct TagSynonymSuggestion {
+    /// The more used tag
+    pub canonical: String,
+    pub alias: String,
+    pub canonical_count: i64,
+    pub alias_count: i64,
+    /// Both normalize to the same tag with every rule on
+    pub same_normalized: bool,
+    /// 1 - edit distance / length, on lowercased and hyphenated names
+    pub name_similarity: f32,
+    /// Cosine similarity of the tags each co-occurs with
+    pub context_similarity: f32,
+    pub score: f32,
+}
+
+/// Tags considered for suggestions, most used first
+const MAX_SUGGEST_TAGS: usize = 1000;
+
+/// Propose synonym groups: tags that normalize to the same form, or whose
+/// names are close and which are used alongside the same other tags.
+/// Tags already in a group are left out.
+pub fn suggest_tag_synonyms(
+    conn: &Connection,
+    options: &TagSynonymSuggestOptions,
+) -> Result<Vec<TagSynonymSuggestion>> {
+    let aliases: HashSet<String> = conn
+        .prepare("SELECT lower(alias) FROM tag_synonyms")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let tags: Vec<(i64, String, i64)> = conn
+        .prepare(
+            "SELECT t.id, t.name, COUNT(mt.memory_id) AS uses
+             FROM tags t JOIN memory_tags mt ON mt.tag_id = t.id
+             GROUP BY t.id ORDER BY uses DESC, t.name LIMIT ?",
+        )?
+        .query_map(params![MAX_SUGGEST_TAGS as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<rusqlite::Result<Vec<(i64, String, i64)>>>()?
+        .into_iter()
+        .filter(|(_, name, _)| !aliases.contains(&name.to_lowercase()))
+        .collect();
+
+    // Co-occurrence counts per tag
+    let index: HashMap<i64, usize> = tags.iter().enumerate().map(|(i, t)| (t.0, i)).collect();
+    let mut by_memory: HashMap<i64, Vec<usize>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT memory_id, tag_id FROM memory_tags")?;
+    for row in stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))? {
+        let (memory_id, tag_id) = row?;
+        if let Some(&i) = index.get(&tag_id) {
+            by_memory.entry(memory_id).or_default().push(i);
+        }
+    }
+    let mut context: Vec<HashMap<usize, f32>> = vec![HashMap::new(); tags.len()];
+    for memory_tags in by_memory.values() {
+        for &a in memory_tags {
+            for &b in memory_tags {
+                if a != b {
+                    *context[a].entry(b).or_default() += 1.0;
+                }
+            }
+        }
+    }
+
+    let loose = TagNormalization {
+        lowercase: true,
+        hyphenate: true,
+        singularize: false,
+    };
+    let forms: Vec<(String, String)> = tags
+        .iter()
+        .map(|(_, name, _)| (loose.apply(name), TagNormalization::all().apply(name)))
+        .collect();
+
+    let mut suggestions = Vec::new();
+    for a in 0..tags.len() {
+        for b in (a + 1)..tags.len() {
+            let same_normalized = forms[a].1 == forms[b].1;
+            let (la, lb) = (&forms[a].0, &forms[b].0);
+            let longest = la.chars().count().max(lb.chars().count()).max(1);
+            let name_similarity = 1.0 - levenshtein(la, lb) as f32 / longest as f32;
+            if !same_normalized && name_similarity < 0.5 {
+                continue;
+            }
+            let context_similarity = context_cosine(&context[a], &context[b], a, b);
+            let score = if same_normalized {
+                1.0
+            } else {
+                0.6 * name_similarity + 0.4 * context_similarity
+            };
+            if score < options.min_score {
+                continue;
+            }
+            // `tags` is sorted by use, so `a` is the more used
+            suggestions.push(TagSynonymSuggestion {
+                canonical: tags[a].1.clone(),
+                alias: tags[b].1.clone(),
+                canonical_count: tags[a].2,
+                alias_count: tags[b].2,
+                same_normalized,
+                name_similarity,
+                context_similarity,
+                score,
+            });
+        }
+    }
+
+    suggestions.sort_by(|x, y| {
+        y.score
+            .partial_cmp(&x.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(y.alias_count.cmp(&x.alias_count))
+    });
+    suggestions.truncate(options.limit);
+    Ok(suggestions)
+}
+
+/// Cosine similarity of two tags' co-occurrence counts, ignoring each other
+fn context_cosine(a: &HashMap<usize, f32>, b: &HashMap<usize, f32>, ia: usize, ib: usize) -> f32 {
+    let norm = |m: &HashMap<usize, f32>| {
+        m.iter()
+            .filter(|(k, _)| **k != ia && **k != ib)
+            .map(|(_, v)| v * v)
+            .sum::<f32>()
+            .sqrt()
+    };
+    let (na, nb) = (norm(a), norm(b));
+    if na == 0.0 || nb == 0.0 {
+        return 0.0;
+    }
+    let dot: f32 = a
+        .iter()
+        .filter(|(k, _)| **k != ia && **k != ib)
+        .filter_map(|(k, v)| b.get(k).map(|w| v * w))
+        .sum();
+    dot / (na * nb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_memory, list_memories, update_memory};
+    use crate::storage::Storage;
+    use crate::types::{CreateMemoryInput, ListOptions, UpdateMemoryInput};
+
+    fn tagged(conn: &Connection, content: &str, tags: &[&str]) -> Result<crate::types::Memory> {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_normalization_rules() {
+        let rules = TagNormalization::all();
+        assert_eq!(rules.apply(" Bugs "), "bug");
+        assert_eq!(rules.apply("Bug_Fixes"), "bug-fix");
+        assert_eq!(rules.apply("user  stories"), "user-story");
+        assert_eq!(rules.apply("project/Patches"), "project/patch");
+        assert_eq!(rules.apply("status"), "status");
+        assert_eq!(rules.apply("kubernetes"), "kubernetes");
+        assert_eq!(rules.apply("ops"), "ops");
+        assert_eq!(TagNormalization::default().apply(" Bugs "), "Bugs");
+    }
+
+    #[test]
+    fn test_synonyms_resolve_on_write_and_filter() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let old = tagged(conn, "old crash report", &["defect"])?;
+                set_tag_normalization(conn, &TagNormalization::all(), false)?;
+                let (group, report) = add_tag_synonyms(
+                    conn,
+                    "Bugs",
+                    &["defects".to_string(), "issue".to_string()],
+                    true,
+                )?;
+                assert_eq!(group.canonical, "bug");
+                assert_eq!(group.aliases, vec!["defect", "issue"]);
+                assert_eq!(report.memories_retagged, 1);
+
+                let memory = tagged(conn, "login fails", &["Issues", "auth"])?;
+                assert_eq!(memory.tags, vec!["bug", "auth"]);
+                assert_eq!(get_memory_tags(conn, old.id)?, vec!["bug".to_string()]);
+                let updated = update_memory(
+                    conn,
+                    memory.id,
+                    &UpdateMemoryInput {
+                        content: None,
+                        memory_type: None,
+                        tags: Some(vec!["defect".to_string(), "bug".to_string()]),
+                        metadata: None,
+                        importance: None,
+                        scope: None,
+                        ttl_seconds: None,
+                        event_time: None,
+                        trigger_pattern: None,
+                        media_url: None,
+                    },
+                )?;
+                assert_eq!(updated.tags, vec!["bug"]);
+
+                // Filtering on an alias finds memories tagged before the
+                // group existed, which were never retagged
+                remove_tag_synonym(conn, "issue")?;
+                let untouched = tagged(conn, "flaky test", &["issue"])?;
+                add_tag_synonyms(conn, "bug", &["issue".to_string()], false)?;
+                let listed = list_memories(
+                    conn,
+                    &ListOptions {
+                        tags: Some(vec!["Defects".to_string()]),
+                        ..Default::default()
+                    },
+                )?;
+                let ids: HashSet<i64> = listed.iter().map(|m| m.id).collect();
+                assert_eq!(ids, HashSet::from([old.id, memory.id, untouched.id]));
+
+                let filtered = list_memories(
+                    conn,
+                    &ListOptions {
+                        filter: Some(serde_json::json!({"tags": {"contains": "bug"}})),
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(filtered.len(), 3);
+
+                // An alias made canonical's alias brings its group along
+                add_tag_synonyms(conn, "defect-report", &["bug".to_string()], true)?;
+                let groups = list_tag_synonyms(conn)?;
+                assert_eq!(groups.len(), 1);
+                assert_eq!(groups[0].aliases, vec!["bug", "defect", "issue"]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                for i in 0..3 {
+                    tagged(conn, &format!("deploy {}", i), &["deployment", "infra"])?;
+                }
+                tagged(conn, "deploy 4", &["deployments", "infra"])?;
+                tagged(conn, "rollout", &["deploymnt", "infra"])?;
+                tagged(conn, "budget", &["finance"])?;
+
+                let suggestions = suggest_tag_synonyms(conn, &TagSynonymSuggestOptions::default())?;
+                assert_eq!(suggestions[0].canonical, "deployment");
+                assert_eq!(suggestions[0].alias, "deployments");
+                assert!(suggestions[0].same_normalized);
+                assert!(suggestions
+                    .iter()
+                    .any(|s| s.alias == "deploymnt" && s.context_similarity > 0.99));
+                assert!(suggestions.iter().all(|s| s.alias != "finance"));
+
+                set_tag_normalization(conn, &TagNormalization::all(), true)?;
+                let names: Vec<String> = conn
+                    .prepare("SELECT name FROM tags ORDER BY name")?
+                    .query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()?;
+                assert_eq!(names, vec!["deployment", "deploymnt", "finance", "infra"]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn get_memory_tags(conn: &Connection, id: i64) -> Result<Vec<String>> {
+        Ok(crate::storage::queries::get_memory(conn, id)?.tags)
+    }
+}
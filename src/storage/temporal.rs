@@ -5,13 +5,18 @@
 //! - Query cross-references valid at a specific time
 //! - Historical graph traversal
 //! - Time-range queries
+//! - Listing and keyword search over memory state "as of" a past timestamp
 
 use crate::error::{EngramError, Result};
-use crate::types::{CrossReference, EdgeType, Memory, MemoryScope, MemoryTier, Visibility};
+use crate::storage::queries::{load_tags, memory_from_row};
+use crate::types::{
+    CrossReference, EdgeType, ListOptions, MatchInfo, Memory, MemoryScope, MemoryTier, MemoryType,
+    SearchOptions, SearchResult, SearchStrategy, SortField, SortOrder, Visibility,
+};
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Options for point-in-time queries
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -26,6 +31,10 @@ pub struct TemporalQueryOptions {
     pub updated_after: Option<DateTime<Utc>>,
     /// Include memories updated before this time
     pub updated_before: Option<DateTime<Utc>>,
+    /// Include memories whose event happened at or after this time
+    pub event_after: Option<DateTime<Utc>>,
+    /// Include memories whose event happened at or before this time
+    pub event_before: Option<DateTime<Utc>>,
     /// Include deleted memories (if tracking soft deletes)
     #[serde(default)]
     pub include_deleted: bool,
@@ -40,6 +49,16 @@ impl TemporalQueryOptions {
         }
     }
 
+    /// Create options for an event-time range (when things happened,
+    /// as opposed to when they were recorded)
+    pub fn event_range(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            event_after: Some(start),
+            event_before: Some(end),
+            ..Default::default()
+        }
+    }
+
     /// Create options for a time range
     pub fn time_range(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
         Self {
@@ -178,7 +197,7 @@ impl<'a> TemporalQueryEngine<'a> {
         self.conn
             .query_row(
                 r#"
-                SELECT id, content, type, importance, access_count, created_at, updated_at,
+                SELECT id, content, memory_type, importance, access_count, created_at, updated_at,
                        last_accessed_at, owner_id, visibility, version, has_embedding
                 FROM memories
                 WHERE id = ?1
@@ -263,9 +282,19 @@ impl<'a> TemporalQueryEngine<'a> {
             params.push(Box::new(before.to_rfc3339()));
         }
 
+        if let Some(ref after) = options.event_after {
+            conditions.push(format!("event_time >= ?{}", params.len() + 1));
+            params.push(Box::new(after.to_rfc3339()));
+        }
+
+        if let Some(ref before) = options.event_before {
+            conditions.push(format!("event_time <= ?{}", params.len() + 1));
+            params.push(Box::new(before.to_rfc3339()));
+        }
+
         let sql = format!(
             r#"
-            SELECT id, content, type, importance, access_count, created_at, updated_at,
+            SELECT id, content, memory_type, importance, access_count, created_at, updated_at,
                    last_accessed_at, owner_id, visibility, version, has_embedding
             FROM memories
             WHERE {}
@@ -351,37 +380,27 @@ impl<'a> TemporalQueryEngine<'a> {
         )?;
 
         let crossrefs = stmt
-            .query_map(params![memory_id, as_of.to_rfc3339()], |row| {
-                let edge_type_str: String = row.get(2)?;
-                let source_str: String = row.get(6)?;
-
-                Ok(CrossReference {
-                    from_id: row.get(0)?,
-                    to_id: row.get(1)?,
-                    edge_type: edge_type_str.parse().unwrap_or_default(),
-                    score: row.get(3)?,
-                    confidence: row.get(4)?,
-                    strength: row.get(5)?,
-                    source: match source_str.as_str() {
-                        "manual" => crate::types::RelationSource::Manual,
-                        "llm" => crate::types::RelationSource::Llm,
-                        _ => crate::types::RelationSource::Auto,
-                    },
-                    source_context: row.get(7)?,
-                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    valid_from: DateTime::parse_from_rfc3339(&row.get::<_, String>(9)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    valid_to: row
-                        .get::<_, Option<String>>(10)?
-                        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                    pinned: row.get(11)?,
-                    metadata: HashMap::new(),
-                })
-            })?
+            .query_map(params![memory_id, as_of.to_rfc3339()], crossref_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(crossrefs)
+    }
+
+    /// All cross-references valid at a specific point in time
+    pub fn crossrefs_at(&self, as_of: DateTime<Utc>) -> Result<Vec<CrossReference>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT from_id, to_id, edge_type, score, confidence, strength, source,
+                   source_context, created_at, valid_from, valid_to, pinned
+            FROM crossrefs
+            WHERE valid_from <= ?1
+              AND (valid_to IS NULL OR valid_to > ?1)
+            ORDER BY from_id, to_id, edge_type
+            "#,
+        )?;
+
+        let crossrefs = stmt
+            .query_map(params![as_of.to_rfc3339()], crossref_from_row)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(crossrefs)
@@ -503,6 +522,216 @@ impl<'a> TemporalQueryEngine<'a> {
         Ok(results)
     }
 
+    /// List memories as they existed at `as_of`.
+    ///
+    /// Memories created after `as_of` or deleted before it are excluded, and
+    /// content, tags and metadata are rewound to the version current at that
+    /// time. Takes the same filters as [`list_memories`]; tag, content and
+    /// metadata filters match the historical values.
+    ///
+    /// [`list_memories`]: crate::storage::queries::list_memories
+    pub fn list_memories_at(
+        &self,
+        as_of: DateTime<Utc>,
+        options: &ListOptions,
+    ) -> Result<Vec<Memory>> {
+        let mut filter = SnapshotFilter {
+            show_superseded: options.show_superseded,
+            ..Default::default()
+        };
+        super::queries::push_list_filters(options, &mut filter.conditions, &mut filter.params)?;
+        let mut memories = self.memories_at(as_of, &filter)?;
+
+        if let Some(ref tags) = options.tags {
+            let tags = super::tag_synonyms::expand_tag_filter(self.conn, tags)?;
+            if !tags.is_empty() {
+                memories.retain(|m| {
+                    m.tags
+                        .iter()
+                        .any(|t| tags.iter().any(|f| f.eq_ignore_ascii_case(t)))
+                });
+            }
+        }
+
+        let desc = options.sort_order.unwrap_or_default() == SortOrder::Desc;
+        memories.sort_by(|a, b| {
+            let ord = match options.sort_by.unwrap_or_default() {
+                SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+                SortField::LastAccessedAt => a.last_accessed_at.cmp(&b.last_accessed_at),
+                SortField::Importance => a.importance.total_cmp(&b.importance),
+                SortField::AccessCount => a.access_count.cmp(&b.access_count),
+            };
+            if desc {
+                ord.reverse()
+            } else {
+                ord
+            }
+        });
+
+        let offset = options.offset.unwrap_or(0).max(0) as usize;
+        let limit = options.limit.unwrap_or(100).max(0) as usize;
+        Ok(memories.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Keyword search over memories as they existed at `as_of`.
+    ///
+    /// Embeddings and the FTS index only reflect current content, so this
+    /// scores the rewound content directly: the fraction of query terms each
+    /// memory contains, with importance as the tie-breaker.
+    pub fn search_at(
+        &self,
+        query: &str,
+        as_of: DateTime<Utc>,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let terms = query_terms(query);
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let min_score = options.min_score.unwrap_or(0.0);
+        let mut results: Vec<SearchResult> = self
+            .memories_at(
+                as_of,
+                &SnapshotFilter {
+                    workspace: options.workspace.as_deref(),
+                    memory_type: options.memory_type,
+                    tier: options.tier,
+                    show_superseded: options.show_superseded,
+                    ..Default::default()
+                },
+            )?
+            .into_iter()
+            .filter(|m| options.include_transcripts || m.memory_type != MemoryType::TranscriptChunk)
+            .filter(|m| match options.tags {
+                Some(ref tags) if !tags.is_empty() => m.tags.iter().any(|t| tags.contains(t)),
+                _ => true,
+            })
+            .filter_map(|memory| {
+                let content_terms: HashSet<String> =
+                    query_terms(&memory.content).into_iter().collect();
+                let matched: Vec<String> = terms
+                    .iter()
+                    .filter(|t| content_terms.contains(*t))
+                    .cloned()
+                    .collect();
+                let score = matched.len() as f32 / terms.len() as f32;
+                if matched.is_empty() || score < min_score {
+                    return None;
+                }
+                Some(SearchResult {
+                    memory,
+                    score,
+                    match_info: MatchInfo {
+                        strategy: SearchStrategy::KeywordOnly,
+                        matched_terms: matched,
+                        highlights: vec![],
+                        semantic_score: None,
+                        keyword_score: Some(score),
+                        boosts: Vec::new(),
+                    },
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.memory.importance.total_cmp(&a.memory.importance))
+        });
+        results.truncate(options.limit.unwrap_or(20).max(0) as usize);
+        Ok(results)
+    }
+
+    /// Memories alive at `as_of`, rewound to the version then current.
+    fn memories_at(&self, as_of: DateTime<Utc>, filter: &SnapshotFilter) -> Result<Vec<Memory>> {
+        let mut conditions =
+            vec!["m.created_at <= ?1 AND (m.valid_to IS NULL OR m.valid_to > ?1)".to_string()];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(as_of.to_rfc3339())];
+
+        if let Some(workspace) = filter.workspace {
+            params.push(Box::new(workspace.to_string()));
+            conditions.push(format!("m.workspace = ?{}", params.len()));
+        }
+        if let Some(memory_type) = filter.memory_type {
+            params.push(Box::new(memory_type.as_str().to_string()));
+            conditions.push(format!("m.memory_type = ?{}", params.len()));
+        }
+        if let Some(tier) = filter.tier {
+            params.push(Box::new(tier.as_str().to_string()));
+            conditions.push(format!("m.tier = ?{}", params.len()));
+        }
+        if let Some(after) = filter.event_after {
+            params.push(Box::new(after.to_rfc3339()));
+            conditions.push(format!("m.event_time >= ?{}", params.len()));
+        }
+        if let Some(before) = filter.event_before {
+            params.push(Box::new(before.to_rfc3339()));
+            conditions.push(format!("m.event_time <= ?{}", params.len()));
+        }
+        if !filter.show_superseded {
+            // Only hide memories that had already been superseded at `as_of`.
+            conditions.push("(m.superseded_at IS NULL OR m.superseded_at > ?1)".to_string());
+        }
+        // Unnumbered placeholders continue after the numbered ones above
+        conditions.extend(filter.conditions.iter().map(|c| rewind_columns(c)));
+        let params: Vec<&dyn rusqlite::ToSql> = params
+            .iter()
+            .chain(&filter.params)
+            .map(|p| p.as_ref())
+            .collect();
+
+        let sql = format!(
+            r#"
+            SELECT m.*, v.version AS v_version, v.content AS v_content,
+                   v.tags AS v_tags, v.metadata AS v_metadata
+            FROM memories m
+            LEFT JOIN memory_versions v ON v.id = (
+                SELECT id FROM memory_versions
+                WHERE memory_id = m.id AND created_at <= ?1
+                ORDER BY version DESC
+                LIMIT 1
+            )
+            WHERE {}
+            "#,
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params.as_slice(), |row| {
+                let memory = memory_from_row(row)?;
+                let version: Option<(i32, String, String, String)> =
+                    match row.get::<_, Option<i32>>("v_version")? {
+                        Some(v) => Some((
+                            v,
+                            row.get("v_content")?,
+                            row.get("v_tags")?,
+                            row.get("v_metadata")?,
+                        )),
+                        None => None,
+                    };
+                Ok((memory, version))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut memories = Vec::with_capacity(rows.len());
+        for (mut memory, version) in rows {
+            match version {
+                Some((version, content, tags_json, metadata_json)) => {
+                    memory.version = version;
+                    memory.content = content;
+                    memory.tags = serde_json::from_str(&tags_json).unwrap_or_default();
+                    memory.metadata = serde_json::from_str(&metadata_json).unwrap_or_default();
+                }
+                None => memory.tags = load_tags(self.conn, memory.id)?,
+            }
+            memories.push(memory);
+        }
+        Ok(memories)
+    }
+
     /// Compare two points in time
     pub fn compare_states(
         &self,
@@ -556,6 +785,98 @@ pub struct StateDiff {
     pub crossrefs_removed: Vec<CrossReference>,
 }
 
+/// Map a row of `from_id, to_id, edge_type, score, confidence, strength,
+/// source, source_context, created_at, valid_from, valid_to, pinned`.
+fn crossref_from_row(row: &rusqlite::Row) -> rusqlite::Result<CrossReference> {
+    let edge_type_str: String = row.get(2)?;
+    let source_str: String = row.get(6)?;
+
+    Ok(CrossReference {
+        from_id: row.get(0)?,
+        to_id: row.get(1)?,
+        edge_type: edge_type_str.parse().unwrap_or_default(),
+        score: row.get(3)?,
+        confidence: row.get(4)?,
+        strength: row.get(5)?,
+        source: match source_str.as_str() {
+            "manual" => crate::types::RelationSource::Manual,
+            "llm" => crate::types::RelationSource::Llm,
+            _ => crate::types::RelationSource::Auto,
+        },
+        source_context: row.get(7)?,
+        created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        valid_from: DateTime::parse_from_rfc3339(&row.get::<_, String>(9)?)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        valid_to: row
+            .get::<_, Option<String>>(10)?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        pinned: row.get(11)?,
+        metadata: HashMap::new(),
+    })
+}
+
+/// Column filters applied when reconstructing memory state at a timestamp.
+#[derive(Default)]
+struct SnapshotFilter<'a> {
+    workspace: Option<&'a str>,
+    memory_type: Option<MemoryType>,
+    tier: Option<MemoryTier>,
+    event_after: Option<DateTime<Utc>>,
+    event_before: Option<DateTime<Utc>>,
+    show_superseded: bool,
+    /// Further conditions on `m` with unnumbered placeholders, as built for
+    /// [`crate::storage::queries::list_memories`]
+    conditions: Vec<String>,
+    params: Vec<Box<dyn rusqlite::ToSql>>,
+}
+
+/// Point a list filter's content and metadata columns at the version current
+/// at the snapshot time (`v`), falling back to the live row.
+fn rewind_columns(condition: &str) -> String {
+    let mut rewound = String::with_capacity(condition.len());
+    let mut rest = condition;
+    while let Some(start) = rest.find("m.") {
+        let preceded = rest[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        let after = &rest[start + 2..];
+        let column = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .map_or(after, |end| &after[..end]);
+        rewound.push_str(&rest[..start]);
+        match column {
+            "content" | "metadata" if !preceded => {
+                rewound.push_str(&format!("COALESCE(v.{column}, m.{column})"));
+            }
+            _ => {
+                rewound.push_str("m.");
+                rewound.push_str(column);
+            }
+        }
+        rest = &after[column.len()..];
+    }
+    rewound.push_str(rest);
+    rewound
+}
+
+/// Lowercased alphanumeric terms used by [`TemporalQueryEngine::search_at`].
+fn query_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -583,4 +904,268 @@ mod tests {
         assert_eq!(options.created_after, Some(start));
         assert_eq!(options.created_before, Some(end));
     }
+
+    fn seed_versioned_memory(conn: &Connection) -> i64 {
+        use crate::storage::queries::{create_memory, update_memory};
+        use crate::types::{CreateMemoryInput, UpdateMemoryInput};
+
+        let memory = create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: "Use REST for the public API".to_string(),
+                tags: vec!["api".to_string()],
+                defer_embedding: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        update_memory(
+            conn,
+            memory.id,
+            &UpdateMemoryInput {
+                content: Some("Use GraphQL for the public API".to_string()),
+                memory_type: None,
+                tags: Some(vec!["api".to_string(), "graphql".to_string()]),
+                metadata: None,
+                importance: None,
+                scope: None,
+                ttl_seconds: None,
+                event_time: None,
+                trigger_pattern: None,
+                media_url: None,
+            },
+        )
+        .unwrap();
+
+        // Pin the timeline: created in January, rewritten in February.
+        conn.execute_batch(&format!(
+            "UPDATE memories SET created_at = '2026-01-01T00:00:00+00:00' WHERE id = {id};
+             UPDATE memory_versions SET created_at = '2026-01-01T00:00:00+00:00'
+                 WHERE memory_id = {id} AND version = 1;
+             UPDATE memory_versions SET created_at = '2026-02-01T00:00:00+00:00'
+                 WHERE memory_id = {id} AND version = 2;",
+            id = memory.id
+        ))
+        .unwrap();
+        memory.id
+    }
+
+    fn ts(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_list_memories_at_rewinds_versions() {
+        let storage = crate::storage::Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let id = seed_versioned_memory(conn);
+                let engine = TemporalQueryEngine::new(conn);
+                let options = ListOptions::default();
+
+                assert!(engine
+                    .list_memories_at(ts("2025-12-31T00:00:00Z"), &options)?
+                    .is_empty());
+
+                let january = engine.list_memories_at(ts("2026-01-15T00:00:00Z"), &options)?;
+                assert_eq!(january.len(), 1);
+                assert_eq!(january[0].content, "Use REST for the public API");
+                assert_eq!(january[0].version, 1);
+                assert_eq!(january[0].tags, vec!["api".to_string()]);
+
+                let graphql_only = ListOptions {
+                    tags: Some(vec!["graphql".to_string()]),
+                    ..Default::default()
+                };
+                assert!(engine
+                    .list_memories_at(ts("2026-01-15T00:00:00Z"), &graphql_only)?
+                    .is_empty());
+
+                let march = engine.list_memories_at(ts("2026-03-01T00:00:00Z"), &options)?;
+                assert_eq!(march[0].content, "Use GraphQL for the public API");
+
+                // Deleted today, but still visible when looking back.
+                crate::storage::queries::delete_memory(conn, id)?;
+                let march = engine.list_memories_at(ts("2026-03-01T00:00:00Z"), &options)?;
+                assert_eq!(march.len(), 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_list_memories_at_applies_list_filters() {
+        use crate::storage::queries::create_memory;
+        use crate::types::CreateMemoryInput;
+        use serde_json::json;
+
+        let storage = crate::storage::Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let versioned = seed_versioned_memory(conn);
+                let decision = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: "Version the public API".to_string(),
+                        memory_type: MemoryType::Decision,
+                        tags: vec!["api".to_string()],
+                        metadata: HashMap::from([("team".to_string(), json!("web"))]),
+                        defer_embedding: true,
+                        ..Default::default()
+                    },
+                )?;
+                conn.execute(
+                    "UPDATE memories SET created_at = '2026-01-01T00:00:00+00:00' WHERE id = ?",
+                    [decision.id],
+                )?;
+                let engine = TemporalQueryEngine::new(conn);
+                let january = ts("2026-01-15T00:00:00Z");
+                let ids = |options: &ListOptions| -> Result<Vec<i64>> {
+                    Ok(engine
+                        .list_memories_at(january, options)?
+                        .iter()
+                        .map(|m| m.id)
+                        .collect())
+                };
+                let api = Some(vec!["api".to_string()]);
+
+                let decisions = ListOptions {
+                    tags: api.clone(),
+                    memory_type: Some(MemoryType::Decision),
+                    ..Default::default()
+                };
+                assert_eq!(ids(&decisions)?, vec![decision.id]);
+
+                let web_team = ListOptions {
+                    tags: api.clone(),
+                    metadata_filter: Some(HashMap::from([("team".to_string(), json!("web"))])),
+                    ..Default::default()
+                };
+                assert_eq!(ids(&web_team)?, vec![decision.id]);
+
+                // Content filters match the content of the time, not today's
+                let rest = ListOptions {
+                    tags: api,
+                    filter: Some(json!({"content": {"contains": "REST"}})),
+                    ..Default::default()
+                };
+                assert_eq!(ids(&rest)?, vec![versioned]);
+                let graphql = ListOptions {
+                    filter: Some(json!({"content": {"contains": "GraphQL"}})),
+                    ..Default::default()
+                };
+                assert!(ids(&graphql)?.is_empty());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_search_at_matches_historical_content() {
+        let storage = crate::storage::Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                seed_versioned_memory(conn);
+                let engine = TemporalQueryEngine::new(conn);
+                let options = SearchOptions::default();
+                let january = ts("2026-01-15T00:00:00Z");
+
+                let rest = engine.search_at("REST api", january, &options)?;
+                assert_eq!(rest.len(), 1);
+                assert_eq!(rest[0].score, 1.0);
+                assert_eq!(rest[0].match_info.strategy, SearchStrategy::KeywordOnly);
+
+                assert!(engine.search_at("graphql", january, &options)?.is_empty());
+                assert_eq!(
+                    engine
+                        .search_at("graphql", ts("2026-03-01T00:00:00Z"), &options)?
+                        .len(),
+                    1
+                );
+                assert!(engine.search_at("  ", january, &options)?.is_empty());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_bitemporal_list_filters() {
+        use crate::storage::queries::{create_memory, list_memories};
+        use crate::types::CreateMemoryInput;
+
+        let storage = crate::storage::Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                // Event in January recorded in January; event in March recorded in April.
+                for (content, event, recorded) in [
+                    (
+                        "Kickoff meeting",
+                        "2026-01-10T00:00:00Z",
+                        "2026-01-11T00:00:00Z",
+                    ),
+                    (
+                        "Launch retro",
+                        "2026-03-10T00:00:00Z",
+                        "2026-04-01T00:00:00Z",
+                    ),
+                ] {
+                    let memory = create_memory(
+                        conn,
+                        &CreateMemoryInput {
+                            content: content.to_string(),
+                            memory_type: MemoryType::Episodic,
+                            event_time: Some(ts(event)),
+                            defer_embedding: true,
+                            ..Default::default()
+                        },
+                    )?;
+                    conn.execute(
+                        "UPDATE memories SET created_at = ?1 WHERE id = ?2",
+                        params![ts(recorded).to_rfc3339(), memory.id],
+                    )?;
+                    conn.execute(
+                        "UPDATE memory_versions SET created_at = ?1 WHERE memory_id = ?2",
+                        params![ts(recorded).to_rfc3339(), memory.id],
+                    )?;
+                }
+
+                let q1 = ListOptions {
+                    event_after: Some(ts("2026-01-01T00:00:00Z")),
+                    event_before: Some(ts("2026-02-01T00:00:00Z")),
+                    ..Default::default()
+                };
+                let hits = list_memories(conn, &q1)?;
+                assert_eq!(hits.len(), 1);
+                assert_eq!(hits[0].content, "Kickoff meeting");
+
+                // Both events happened before mid-March, but only one was known then.
+                let known_in_march = ListOptions {
+                    event_before: Some(ts("2026-03-15T00:00:00Z")),
+                    as_of: Some(ts("2026-03-15T00:00:00Z")),
+                    ..Default::default()
+                };
+                let hits = list_memories(conn, &known_in_march)?;
+                assert_eq!(hits.len(), 1);
+                assert_eq!(hits[0].content, "Kickoff meeting");
+
+                let known_now = ListOptions {
+                    event_before: Some(ts("2026-03-15T00:00:00Z")),
+                    ..Default::default()
+                };
+                assert_eq!(list_memories(conn, &known_now)?.len(), 2);
+
+                let engine = TemporalQueryEngine::new(conn);
+                let march_events = engine.query_time_range(
+                    &TemporalQueryOptions::event_range(
+                        ts("2026-03-01T00:00:00Z"),
+                        ts("2026-03-31T00:00:00Z"),
+                    ),
+                    10,
+                )?;
+                assert_eq!(march_events.len(), 1);
+                assert_eq!(march_events[0].content, "Launch retro");
+                Ok(())
+            })
+            .unwrap();
+    }
 }
@@ -0,0 +1,398 @@
+//! MinHash signature index for textual near-duplicates.
+//!
+//! Every memory gets a MinHash signature over the word shingles of its
+//! normalized content ([`ContentHash::normalize`]), stored in
+//! `memory_text_signatures`. The signature is cut into [`SIGNATURE_BANDS`]
+//! bands and each band's hash goes into `memory_signature_bands`, so finding
+//! near-duplicates of new content is one indexed lookup per band rather than
+//! a scan. Candidates are then ranked by the share of signature positions
+//! they agree on, an estimate of shingle Jaccard similarity.
+//!
+//! This needs no embeddings, so `memory_create` uses it ahead of semantic
+//! dedup: content whose estimated similarity already reaches the dedup
+//! threshold is a duplicate without embedding anything.
+//!
+//! With 16 bands of 4 rows, pairs at similarity 0.5 share a band about 65%
+//! of the time and pairs at 0.8 more than 99.9% of the time.
+//!
+//! [`insert_memory`](super::queries::insert_memory) and
+//! [`update_memory`](super::queries::update_memory) keep the index current;
+//! [`rebuild_text_signatures`] recomputes it from scratch.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::types::{ContentHash, MemoryId, MemoryScope};
+
+/// Words per shingle
+pub const SHINGLE_SIZE: usize = 3;
+
+/// Length of stored signatures
+pub const SIGNATURE_HASHES: usize = 64;
+
+/// Bands the stored signature is split into for lookup
+pub const SIGNATURE_BANDS: usize = 16;
+
+const ROWS_PER_BAND: usize = SIGNATURE_HASHES / SIGNATURE_BANDS;
+
+/// A memory whose signature is close to the queried content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureMatch {
+    pub memory_id: MemoryId,
+    /// Estimated shingle Jaccard similarity
+    pub similarity: f32,
+}
+
+/// Statistics from [`rebuild_text_signatures`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignatureRebuild {
+    /// Memories scanned
+    pub memories: usize,
+    /// Memories with a signature (content with at least one word)
+    pub indexed: usize,
+}
+
+/// Sorted, deduplicated hashes of the word shingles of normalized `content`.
+/// Texts shorter than one shingle become a single shingle.
+pub fn shingles(content: &str, size: usize) -> Vec<u64> {
+    let normalized = ContentHash::normalize(content);
+    let words: Vec<&str> = normalized.split(' ').filter(|w| !w.is_empty()).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let mut hashes: Vec<u64> = words
+        .windows(size.clamp(1, words.len()))
+        .map(|window| fnv1a(window.join(" ").as_bytes()))
+        .collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+    hashes
+}
+
+/// MinHash signature of `num_hashes` positions over a shingle set
+pub fn minhash(shingles: &[u64], num_hashes: usize) -> Vec<u64> {
+    (0..num_hashes as u64)
+        .map(|i| {
+            let seed = splitmix64(i.wrapping_add(0x5eed));
+            shingles
+                .iter()
+                .map(|&s| splitmix64(s ^ seed))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// Hash of each band of `rows` consecutive signature positions
+pub fn band_hashes(signature: &[u64], rows: usize) -> Vec<u64> {
+    signature
+        .chunks(rows.max(1))
+        .map(|band| {
+            let bytes: Vec<u8> = band.iter().flat_map(|v| v.to_le_bytes()).collect();
+            fnv1a(&bytes)
+        })
+        .collect()
+}
+
+/// Exact Jaccard similarity of two sorted, deduplicated sets
+pub fn jaccard(a: &[u64], b: &[u64]) -> f32 {
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let union = a.len() + b.len() - shared;
+    if union == 0 {
+        return 0.0;
+    }
+    shared as f32 / union as f32
+}
+
+/// Jaccard similarity estimated from two signatures of equal length
+pub fn estimate_similarity(a: &[u64], b: &[u64]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let agreeing = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    agreeing as f32 / a.len() as f32
+}
+
+/// Signature of `content` as stored in the index, or `None` for content
+/// without any words
+pub fn content_signature(content: &str) -> Option<Vec<u64>> {
+    let shingles = shingles(content, SHINGLE_SIZE);
+    (!shingles.is_empty()).then(|| minhash(&shingles, SIGNATURE_HASHES))
+}
+
+/// Store (or replace) the signature of a memory's content
+pub fn index_signature(conn: &Connection, memory_id: MemoryId, content: &str) -> Result<()> {
+    conn.prepare_cached("DELETE FROM memory_signature_bands WHERE memory_id = ?")?
+        .execute(params![memory_id])?;
+
+    let Some(signature) = content_signature(content) else {
+        conn.prepare_cached("DELETE FROM memory_text_signatures WHERE memory_id = ?")?
+            .execute(params![memory_id])?;
+        return Ok(());
+    };
+
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO memory_text_signatures (memory_id, signature) VALUES (?, ?)",
+    )?
+    .execute(params![memory_id, encode(&signature)])?;
+
+    let mut insert_band = conn.prepare_cached(
+        "INSERT OR IGNORE INTO memory_signature_bands (band, bucket, memory_id) VALUES (?, ?, ?)",
+    )?;
+    for (band, bucket) in band_hashes(&signature, ROWS_PER_BAND)
+        .into_iter()
+        .enumerate()
+    {
+        insert_band.execute(params![band as i64, bucket as i64, memory_id])?;
+    }
+    Ok(())
+}
+
+/// Live memories in one scope and workspace whose content is textually
+/// close to `content`, most similar first
+pub fn find_signature_matches(
+    conn: &Connection,
+    content: &str,
+    scope: &MemoryScope,
+    workspace: &str,
+    threshold: f32,
+    limit: usize,
+) -> Result<Vec<SignatureMatch>> {
+    let Some(signature) = content_signature(content) else {
+        return Ok(Vec::new());
+    };
+    let now = Utc::now().to_rfc3339();
+    let scope_type = scope.scope_type();
+    let scope_id = scope.scope_id().map(|s| s.to_string());
+
+    let mut band_stmt = conn.prepare_cached(
+        "SELECT b.memory_id FROM memory_signature_bands b
+         JOIN memories m ON m.id = b.memory_id
+         WHERE b.band = ? AND b.bucket = ?
+           AND m.valid_to IS NULL
+           AND (m.expires_at IS NULL OR m.expires_at > ?)
+           AND m.scope_type = ?
+           AND (m.scope_id = ? OR (m.scope_id IS NULL AND ? IS NULL))
+           AND m.workspace = ?",
+    )?;
+    let mut candidates = HashSet::new();
+    for (band, bucket) in band_hashes(&signature, ROWS_PER_BAND)
+        .into_iter()
+        .enumerate()
+    {
+        let ids = band_stmt.query_map(
+            params![
+                band as i64,
+                bucket as i64,
+                now,
+                scope_type,
+                scope_id,
+                scope_id,
+                workspace
+            ],
+            |row| row.get::<_, MemoryId>(0),
+        )?;
+        for id in ids {
+            candidates.insert(id?);
+        }
+    }
+
+    let mut signature_stmt =
+        conn.prepare_cached("SELECT signature FROM memory_text_signatures WHERE memory_id = ?")?;
+    let mut matches = Vec::new();
+    for memory_id in candidates {
+        let stored: Option<Vec<u8>> = signature_stmt
+            .query_row(params![memory_id], |row| row.get(0))
+            .optional()?;
+        let similarity = stored.map_or(0.0, |bytes| {
+            estimate_similarity(&signature, &decode(&bytes))
+        });
+        if similarity >= threshold {
+            matches.push(SignatureMatch {
+                memory_id,
+                similarity,
+            });
+        }
+    }
+    matches.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then(a.memory_id.cmp(&b.memory_id))
+    });
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Recompute every memory's signature
+pub fn rebuild_text_signatures(conn: &Connection) -> Result<SignatureRebuild> {
+    conn.execute("DELETE FROM memory_signature_bands", [])?;
+    conn.execute("DELETE FROM memory_text_signatures", [])?;
+
+    let mut stmt = conn.prepare("SELECT id, content FROM memories")?;
+    let mut rows = stmt.query([])?;
+    let mut rebuild = SignatureRebuild::default();
+    while let Some(row) = rows.next()? {
+        let id: MemoryId = row.get(0)?;
+        let content: String = row.get(1)?;
+        index_signature(conn, id, &content)?;
+        rebuild.memories += 1;
+        if content_signature(&content).is_some() {
+            rebuild.indexed += 1;
+        }
+    }
+    Ok(rebuild)
+}
+
+fn encode(signature: &[u64]) -> Vec<u8> {
+    signature.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("8-byte chunk")))
+        .collect()
+}
+
+/// FNV-1a: stable across builds, unlike `DefaultHasher`, which matters for
+/// hashes stored in the database
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{create_memory, update_memory};
+    use crate::storage::Storage;
+    use crate::types::{CreateMemoryInput, UpdateMemoryInput};
+
+    const NOTE: &str = "the staging database is refreshed from production every sunday \
+                        night and anonymized before engineers get access to it";
+
+    fn create(conn: &Connection, content: &str, workspace: &str) -> MemoryId {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                workspace: Some(workspace.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_shingles_and_similarity() {
+        let a = shingles("The quick brown fox jumps", 3);
+        assert_eq!(a.len(), 3);
+        assert_eq!(jaccard(&a, &shingles("the  QUICK brown fox jumps", 3)), 1.0);
+        assert_eq!(shingles("two words", 3).len(), 1);
+        assert!(shingles("   ", 3).is_empty());
+
+        let sig = content_signature(NOTE).unwrap();
+        assert_eq!(sig.len(), SIGNATURE_HASHES);
+        assert_eq!(estimate_similarity(&sig, &sig), 1.0);
+        let other = content_signature("completely different words about the lunch menu").unwrap();
+        assert!(estimate_similarity(&sig, &other) < 0.2);
+    }
+
+    #[test]
+    fn test_lookup_finds_near_duplicates_in_scope() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let id = create(conn, NOTE, "ops");
+                create(conn, NOTE, "elsewhere");
+                create(
+                    conn,
+                    "unrelated note about the team offsite in march",
+                    "ops",
+                );
+
+                let near = format!("{} team", NOTE);
+                let matches =
+                    find_signature_matches(conn, &near, &MemoryScope::Global, "ops", 0.7, 10)?;
+                assert_eq!(matches.len(), 1);
+                assert_eq!(matches[0].memory_id, id);
+                assert!(matches[0].similarity >= 0.7);
+
+                let (memory, _) = crate::storage::queries::find_similar_by_signature(
+                    conn,
+                    &near,
+                    &MemoryScope::Global,
+                    Some("ops"),
+                    0.7,
+                )?
+                .unwrap();
+                assert_eq!(memory.id, id);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_update_reindexes_and_rebuild_restores() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let id = create(conn, NOTE, "ops");
+                update_memory(
+                    conn,
+                    id,
+                    &UpdateMemoryInput {
+                        content: Some("rewritten entirely to talk about release trains".into()),
+                        memory_type: None,
+                        tags: None,
+                        metadata: None,
+                        importance: None,
+                        scope: None,
+                        ttl_seconds: None,
+                        event_time: None,
+                        trigger_pattern: None,
+                        media_url: None,
+                    },
+                )?;
+                let lookup = |conn: &Connection| {
+                    find_signature_matches(conn, NOTE, &MemoryScope::Global, "ops", 0.5, 10)
+                };
+                assert!(lookup(conn)?.is_empty());
+
+                conn.execute(
+                    "UPDATE memories SET content = ? WHERE id = ?",
+                    params![NOTE, id],
+                )?;
+                assert!(lookup(conn)?.is_empty());
+                let rebuild = rebuild_text_signatures(conn)?;
+                assert_eq!((rebuild.memories, rebuild.indexed), (1, 1));
+                assert_eq!(lookup(conn)?.len(), 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+}
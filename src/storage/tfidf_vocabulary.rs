@@ -0,0 +1,218 @@
+//! Persistent TF-IDF vocabulary.
+//!
+//! Document frequencies for the TF-IDF embedder live in `tfidf_vocabulary`,
+//! with the corpus size in `tfidf_corpus`, so IDF weights (and therefore
+//! vectors) are the same after a restart. The vocabulary is enabled by
+//! building it ([`rebuild_tfidf_vocabulary`], which the server does on start
+//! when it embeds with TF-IDF); from then on
+//! [`insert_memory`](super::queries::insert_memory) counts every new memory
+//! in ([`observe_tfidf_document`]).
+//!
+//! Updates and deletes are not subtracted, so frequencies drift from the
+//! live corpus until the next rebuild.
+//!
+//! Each term row records the corpus size when it last changed (`revision`),
+//! and every rebuild bumps the corpus `generation`, which lets in-memory
+//! copies ([`TfIdfVocabulary`](crate::embedding::TfIdfVocabulary)) load only
+//! what changed since they last looked.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::embedding::TfIdfEmbedder;
+use crate::error::Result;
+
+/// Size of a stored vocabulary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VocabularyStats {
+    /// Documents counted
+    pub documents: u64,
+    /// Distinct terms
+    pub terms: u64,
+    /// Incremented by every rebuild
+    pub generation: u64,
+    /// When the vocabulary was last rebuilt
+    pub built_at: DateTime<Utc>,
+}
+
+/// Corpus size and generation, when the vocabulary is enabled
+pub fn tfidf_corpus(conn: &Connection) -> Result<Option<(u64, u64)>> {
+    Ok(conn
+        .query_row(
+            "SELECT documents, generation FROM tfidf_corpus WHERE id = 1",
+            [],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64)),
+        )
+        .optional()?)
+}
+
+/// Count a new document's terms, if the vocabulary is enabled
+pub fn observe_tfidf_document(conn: &Connection, content: &str) -> Result<()> {
+    let documents: Option<i64> = conn
+        .prepare_cached(
+            "UPDATE tfidf_corpus SET documents = documents + 1 WHERE id = 1 RETURNING documents",
+        )?
+        .query_row([], |row| row.get(0))
+        .optional()?;
+    let Some(revision) = documents else {
+        return Ok(());
+    };
+    let mut upsert = conn.prepare_cached(
+        "INSERT INTO tfidf_vocabulary (term, doc_freq, revision) VALUES (?, 1, ?)
+         ON CONFLICT(term) DO UPDATE SET doc_freq = doc_freq + 1, revision = excluded.revision",
+    )?;
+    for term in distinct_terms(content) {
+        upsert.execute(params![term, revision])?;
+    }
+    Ok(())
+}
+
+/// Recount the vocabulary over all live memories, enabling it if needed
+pub fn rebuild_tfidf_vocabulary(conn: &Connection) -> Result<VocabularyStats> {
+    let mut doc_freq: HashMap<String, u64> = HashMap::new();
+    let mut documents = 0u64;
+    {
+        let mut stmt = conn.prepare("SELECT content FROM memories WHERE valid_to IS NULL")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let content: String = row.get(0)?;
+            for term in distinct_terms(&content) {
+                *doc_freq.entry(term).or_insert(0) += 1;
+            }
+            documents += 1;
+        }
+    }
+
+    let generation = tfidf_corpus(conn)?.map_or(1, |(_, generation)| generation + 1);
+    let built_at = Utc::now();
+    conn.execute("DELETE FROM tfidf_vocabulary", [])?;
+    {
+        let mut insert = conn
+            .prepare("INSERT INTO tfidf_vocabulary (term, doc_freq, revision) VALUES (?, ?, ?)")?;
+        for (term, count) in &doc_freq {
+            insert.execute(params![term, *count as i64, documents as i64])?;
+        }
+    }
+    conn.execute(
+        "INSERT INTO tfidf_corpus (id, documents, generation, built_at) VALUES (1, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET documents = excluded.documents,
+             generation = excluded.generation, built_at = excluded.built_at",
+        params![documents as i64, generation as i64, built_at.to_rfc3339()],
+    )?;
+
+    Ok(VocabularyStats {
+        documents,
+        terms: doc_freq.len() as u64,
+        generation,
+        built_at,
+    })
+}
+
+/// Size of the stored vocabulary, if it is enabled
+pub fn tfidf_vocabulary_stats(conn: &Connection) -> Result<Option<VocabularyStats>> {
+    let corpus = conn
+        .query_row(
+            "SELECT documents, generation, built_at FROM tfidf_corpus WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((documents, generation, built_at)) = corpus else {
+        return Ok(None);
+    };
+    let terms: i64 = conn.query_row("SELECT COUNT(*) FROM tfidf_vocabulary", [], |row| {
+        row.get(0)
+    })?;
+    Ok(Some(VocabularyStats {
+        documents: documents as u64,
+        terms: terms as u64,
+        generation: generation as u64,
+        built_at: DateTime::parse_from_rfc3339(&built_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    }))
+}
+
+/// Terms whose frequency changed after corpus size `since` (all terms for 0)
+pub fn tfidf_terms_since(conn: &Connection, since: u64) -> Result<Vec<(String, u64)>> {
+    let mut stmt =
+        conn.prepare_cached("SELECT term, doc_freq FROM tfidf_vocabulary WHERE revision > ?")?;
+    let terms = stmt
+        .query_map(params![since as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(terms)
+}
+
+fn distinct_terms(content: &str) -> BTreeSet<String> {
+    TfIdfEmbedder::tokenize(content).into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+    use crate::types::CreateMemoryInput;
+
+    fn create(storage: &Storage, content: &str) {
+        storage
+            .with_transaction(|conn| {
+                create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        content: content.to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_vocabulary_counts_documents_once_enabled() {
+        let storage = Storage::open_in_memory().unwrap();
+        create(&storage, "rust borrow checker");
+        // Not enabled yet: nothing is counted
+        assert!(storage
+            .with_connection(tfidf_vocabulary_stats)
+            .unwrap()
+            .is_none());
+
+        let built = storage.with_transaction(rebuild_tfidf_vocabulary).unwrap();
+        assert_eq!(built.documents, 1);
+        assert_eq!(built.terms, 3);
+        assert_eq!(built.generation, 1);
+
+        create(&storage, "rust rust async runtime");
+        let stats = storage
+            .with_connection(tfidf_vocabulary_stats)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stats.documents, 2);
+        assert_eq!(stats.terms, 5);
+
+        let changed: HashMap<String, u64> = storage
+            .with_connection(|conn| tfidf_terms_since(conn, 1))
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(changed.len(), 3);
+        assert_eq!(changed["rust"], 2);
+        assert_eq!(changed["async"], 1);
+
+        let rebuilt = storage.with_transaction(rebuild_tfidf_vocabulary).unwrap();
+        assert_eq!(rebuilt.documents, 2);
+        assert_eq!(rebuilt.generation, 2);
+    }
+}
@@ -38,7 +38,7 @@ use crate::error::{EngramError, Result};
 use crate::storage::migrations::SCHEMA_VERSION;
 use crate::storage::queries::compute_content_hash;
 use crate::types::{
-    normalize_workspace, CreateMemoryInput, CrossReference, EdgeType, LifecycleState, ListOptions,
+    normalize_workspace, ContentHash, CreateMemoryInput, CrossReference, EdgeType, LifecycleState, ListOptions,
     MatchInfo, Memory, MemoryId, MemoryScope, MemoryTier, MemoryType, RelationSource,
     SearchOptions, SearchResult, SearchStrategy, SortField, SortOrder, UpdateMemoryInput,
     Visibility,
@@ -551,7 +551,7 @@ impl TursoBackend {
             version,
             has_embedding: has_embedding != 0,
             expires_at: Self::parse_datetime(expires_at),
-            content_hash,
+            content_hash: content_hash.map(ContentHash::from_stored),
             event_time: Self::parse_datetime(event_time),
             event_duration_seconds,
             trigger_pattern,
@@ -1120,6 +1120,7 @@ impl StorageBackend for TursoBackend {
                         highlights: Vec::new(),
                         semantic_score: None,
                         keyword_score: Some(1.0),
+                        boosts: Vec::new(),
                     },
                 })
                 .collect())
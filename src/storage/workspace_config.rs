@@ -0,0 +1,489 @@
+//! Per-workspace memory defaults
+//!
+//! A workspace can carry defaults that `create_memory` applies to every new
+//! memory in it, stored in the `workspace_settings` table (schema v45):
+//! - base tags, always added to the memory's own tags
+//! - a default tier, used when the input asks for the permanent tier
+//!   without an explicit `ttl_seconds: 0`
+//! - a default TTL, used for daily memories created without one
+//! - a dedup mode, used when the input leaves it at `allow`
+//! - a semantic dedup threshold (schema v46), used when the input has none;
+//!   usually written by dedup calibration rather than by hand
+//!
+//! It can also carry a UTC offset and date order (schema v57) for reading
+//! date expressions such as "yesterday" or "03/04" in search and timeline
+//! requests; see [`crate::intelligence::temporal_expr`].
+//!
+//! `CreateMemoryInput` can't tell an omitted tier or dedup mode from an
+//! explicit default, so those defaults count as omitted.
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::intelligence::temporal_expr::{parse_utc_offset, DateOrder, TemporalContext};
+use crate::types::{normalize_workspace, CreateMemoryInput, DedupMode, MemoryTier};
+
+/// Defaults applied to memories created in a workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    pub workspace: String,
+    /// Tags added to every memory
+    pub base_tags: Vec<String>,
+    /// Tier for memories that don't choose one
+    pub default_tier: Option<MemoryTier>,
+    /// TTL for daily memories that don't set one
+    pub default_ttl_seconds: Option<i64>,
+    /// Dedup mode for memories that don't choose one
+    pub dedup_mode: Option<DedupMode>,
+    /// Semantic dedup threshold for memories that don't set one
+    pub dedup_threshold: Option<f32>,
+    /// UTC offset for date expressions, such as `+02:00`
+    pub timezone: Option<String>,
+    /// Order of all-numeric dates in date expressions
+    pub date_order: Option<DateOrder>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl WorkspaceConfig {
+    /// Fill in the parts of `input` it leaves at their defaults
+    pub fn apply(&self, input: &mut CreateMemoryInput) {
+        for tag in &self.base_tags {
+            if !input.tags.contains(tag) {
+                input.tags.push(tag.clone());
+            }
+        }
+        if let Some(tier) = self.default_tier {
+            if input.tier == MemoryTier::Permanent && input.ttl_seconds != Some(0) {
+                input.tier = tier;
+            }
+        }
+        if input.tier == MemoryTier::Daily && input.ttl_seconds.is_none() {
+            input.ttl_seconds = self.default_ttl_seconds;
+        }
+        if input.dedup_mode == DedupMode::Allow {
+            if let Some(mode) = self.dedup_mode {
+                input.dedup_mode = mode;
+            }
+        }
+        if input.dedup_threshold.is_none() {
+            input.dedup_threshold = self.dedup_threshold;
+        }
+    }
+
+    /// Context for reading date expressions in this workspace
+    pub fn temporal_context(&self) -> TemporalContext {
+        let mut ctx = TemporalContext::default();
+        if let Some(offset) = self
+            .timezone
+            .as_deref()
+            .and_then(|tz| parse_utc_offset(tz).ok())
+        {
+            ctx = ctx.with_offset(offset);
+        }
+        if let Some(order) = self.date_order {
+            ctx = ctx.with_date_order(order);
+        }
+        ctx
+    }
+}
+
+/// Input for setting a workspace's defaults; replaces all of them
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SetWorkspaceConfigInput {
+    pub workspace: String,
+    #[serde(default)]
+    pub base_tags: Vec<String>,
+    #[serde(default)]
+    pub default_tier: Option<MemoryTier>,
+    #[serde(default)]
+    pub default_ttl_seconds: Option<i64>,
+    #[serde(default)]
+    pub dedup_mode: Option<DedupMode>,
+    #[serde(default)]
+    pub dedup_threshold: Option<f32>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub date_order: Option<DateOrder>,
+}
+
+/// Parse a WorkspaceConfig from a rusqlite row.
+///
+/// Columns expected in order: workspace, base_tags, default_tier,
+/// default_ttl_seconds, dedup_mode, created_at, updated_at, dedup_threshold,
+/// timezone, date_order
+fn config_from_row(row: &rusqlite::Row) -> rusqlite::Result<WorkspaceConfig> {
+    let base_tags: String = row.get(1)?;
+    let default_tier: Option<String> = row.get(2)?;
+    let dedup_mode: Option<String> = row.get(4)?;
+    let date_order: Option<String> = row.get(9)?;
+
+    Ok(WorkspaceConfig {
+        workspace: row.get(0)?,
+        base_tags: serde_json::from_str(&base_tags).unwrap_or_default(),
+        default_tier: default_tier.and_then(|s| s.parse().ok()),
+        default_ttl_seconds: row.get(3)?,
+        dedup_mode: dedup_mode
+            .and_then(|s| serde_json::from_value(serde_json::Value::String(s)).ok()),
+        dedup_threshold: row.get(7)?,
+        timezone: row.get(8)?,
+        date_order: date_order.and_then(|s| s.parse().ok()),
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+const CONFIG_COLUMNS: &str = "workspace, base_tags, default_tier, default_ttl_seconds, \
+                              dedup_mode, created_at, updated_at, dedup_threshold, \
+                              timezone, date_order";
+
+fn normalize(workspace: &str) -> Result<String> {
+    normalize_workspace(workspace)
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))
+}
+
+fn validate_dedup_threshold(threshold: Option<f32>) -> Result<()> {
+    match threshold {
+        Some(t) if !(0.0..=1.0).contains(&t) => Err(EngramError::InvalidInput(
+            "dedup_threshold must be between 0 and 1".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Set every default of a workspace, replacing the previous ones.
+/// `created_at` is preserved on replace.
+pub fn set_workspace_config(
+    conn: &Connection,
+    input: &SetWorkspaceConfigInput,
+) -> Result<WorkspaceConfig> {
+    let workspace = normalize(&input.workspace)?;
+    validate_dedup_threshold(input.dedup_threshold)?;
+    let timezone = input
+        .timezone
+        .as_deref()
+        .map(parse_utc_offset)
+        .transpose()?
+        .map(|offset| offset.to_string());
+    if let Some(ttl) = input.default_ttl_seconds {
+        if ttl <= 0 {
+            return Err(EngramError::InvalidInput(
+                "default_ttl_seconds must be positive".to_string(),
+            ));
+        }
+        if input.default_tier == Some(MemoryTier::Permanent) {
+            return Err(EngramError::InvalidInput(
+                "default_ttl_seconds only applies to daily memories, but default_tier is permanent"
+                    .to_string(),
+            ));
+        }
+    }
+
+    let mut base_tags: Vec<String> = Vec::new();
+    for tag in &input.base_tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !base_tags.iter().any(|t| t == tag) {
+            base_tags.push(tag.to_string());
+        }
+    }
+    let dedup_mode = input
+        .dedup_mode
+        .map(serde_json::to_value)
+        .transpose()?
+        .and_then(|v| v.as_str().map(str::to_string));
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        r#"
+        INSERT INTO workspace_settings
+            (workspace, base_tags, default_tier, default_ttl_seconds, dedup_mode,
+             dedup_threshold, timezone, date_order, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(workspace) DO UPDATE SET
+            base_tags           = excluded.base_tags,
+            default_tier        = excluded.default_tier,
+            default_ttl_seconds = excluded.default_ttl_seconds,
+            dedup_mode          = excluded.dedup_mode,
+            dedup_threshold     = excluded.dedup_threshold,
+            timezone            = excluded.timezone,
+            date_order          = excluded.date_order,
+            updated_at          = excluded.updated_at
+        "#,
+        params![
+            workspace,
+            serde_json::to_string(&base_tags)?,
+            input.default_tier.map(|t| t.as_str()),
+            input.default_ttl_seconds,
+            dedup_mode,
+            input.dedup_threshold,
+            timezone,
+            input.date_order.map(|o| o.as_str()),
+            now,
+            now,
+        ],
+    )?;
+
+    get_workspace_config(conn, &workspace)?
+        .ok_or_else(|| EngramError::Storage("Workspace config not found after insert".to_string()))
+}
+
+/// Set only the semantic dedup threshold of a workspace, keeping its other
+/// defaults (or creating an otherwise empty config). `None` clears it.
+pub fn set_workspace_dedup_threshold(
+    conn: &Connection,
+    workspace: &str,
+    threshold: Option<f32>,
+) -> Result<WorkspaceConfig> {
+    let workspace = normalize(workspace)?;
+    validate_dedup_threshold(threshold)?;
+
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        r#"
+        INSERT INTO workspace_settings (workspace, dedup_threshold, created_at, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(workspace) DO UPDATE SET
+            dedup_threshold = excluded.dedup_threshold,
+            updated_at      = excluded.updated_at
+        "#,
+        params![workspace, threshold, now, now],
+    )?;
+
+    get_workspace_config(conn, &workspace)?
+        .ok_or_else(|| EngramError::Storage("Workspace config not found after insert".to_string()))
+}
+
+/// Retrieve the defaults of a workspace, if any were set.
+pub fn get_workspace_config(conn: &Connection, workspace: &str) -> Result<Option<WorkspaceConfig>> {
+    let workspace = normalize(workspace)?;
+    conn.prepare_cached(&format!(
+        "SELECT {} FROM workspace_settings WHERE workspace = ?",
+        CONFIG_COLUMNS
+    ))?
+    .query_row(params![workspace], config_from_row)
+    .optional()
+    .map_err(EngramError::from)
+}
+
+/// List the defaults of every configured workspace by name.
+pub fn list_workspace_configs(conn: &Connection) -> Result<Vec<WorkspaceConfig>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM workspace_settings ORDER BY workspace",
+        CONFIG_COLUMNS
+    ))?;
+    let configs = stmt
+        .query_map([], config_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(configs)
+}
+
+/// Remove the defaults of a workspace. Returns `true` if it had any.
+pub fn delete_workspace_config(conn: &Connection, workspace: &str) -> Result<bool> {
+    let workspace = normalize(workspace)?;
+    let affected = conn.execute(
+        "DELETE FROM workspace_settings WHERE workspace = ?",
+        params![workspace],
+    )?;
+    Ok(affected > 0)
+}
+
+/// Context for reading date expressions in `workspace`: its configured
+/// timezone and date order, or UTC and month-first dates
+pub fn workspace_temporal_context(conn: &Connection, workspace: &str) -> Result<TemporalContext> {
+    Ok(get_workspace_config(conn, workspace)?
+        .map(|config| config.temporal_context())
+        .unwrap_or_default())
+}
+
+/// Dedup mode `create_memory` will use for `input` once workspace defaults
+/// are applied
+pub(crate) fn effective_dedup_mode(
+    conn: &Connection,
+    input: &CreateMemoryInput,
+) -> Result<DedupMode> {
+    if input.dedup_mode != DedupMode::Allow {
+        return Ok(input.dedup_mode);
+    }
+    let config = get_workspace_config(conn, input.workspace.as_deref().unwrap_or("default"))?;
+    Ok(config
+        .and_then(|c| c.dedup_mode)
+        .unwrap_or(DedupMode::Allow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::create_memory;
+    use crate::storage::Storage;
+
+    fn input(content: &str) -> CreateMemoryInput {
+        CreateMemoryInput {
+            content: content.to_string(),
+            workspace: Some("Scratch".to_string()),
+            tags: vec!["draft".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_defaults_fill_omitted_fields() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let config = set_workspace_config(
+                    conn,
+                    &SetWorkspaceConfigInput {
+                        workspace: "scratch".to_string(),
+                        base_tags: vec!["scratch".to_string(), "draft".to_string()],
+                        default_tier: Some(MemoryTier::Daily),
+                        default_ttl_seconds: Some(3600),
+                        dedup_mode: Some(DedupMode::Skip),
+                        dedup_threshold: None,
+                        timezone: None,
+                        date_order: None,
+                    },
+                )?;
+                assert_eq!(config.dedup_mode, Some(DedupMode::Skip));
+
+                let memory = create_memory(conn, &input("note"))?;
+                assert_eq!(memory.tags.len(), 2);
+                assert!(memory.tags.contains(&"scratch".to_string()));
+                assert_eq!(memory.tier, MemoryTier::Daily);
+                let ttl = memory.expires_at.unwrap() - memory.created_at;
+                assert!((ttl.num_seconds() - 3600).abs() <= 1);
+
+                // Skip dedup returns the first memory
+                assert_eq!(create_memory(conn, &input("note"))?.id, memory.id);
+
+                // An explicit `ttl_seconds: 0` keeps a memory permanent
+                let permanent = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        ttl_seconds: Some(0),
+                        ..input("keep")
+                    },
+                )?;
+                assert_eq!(permanent.tier, MemoryTier::Permanent);
+                assert!(permanent.expires_at.is_none());
+
+                // Explicit values win
+                let daily = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        tier: MemoryTier::Daily,
+                        ttl_seconds: Some(60),
+                        dedup_mode: DedupMode::Reject,
+                        ..input("note")
+                    },
+                );
+                assert!(matches!(daily, Err(EngramError::Duplicate { .. })));
+
+                // Other workspaces are untouched
+                let other = create_memory(
+                    conn,
+                    &CreateMemoryInput {
+                        workspace: None,
+                        ..input("note")
+                    },
+                )?;
+                assert_eq!(other.tags, vec!["draft".to_string()]);
+                assert_eq!(other.tier, MemoryTier::Permanent);
+
+                assert!(delete_workspace_config(conn, "scratch")?);
+                assert!(get_workspace_config(conn, "scratch")?.is_none());
+                assert_ne!(create_memory(conn, &input("note"))?.id, memory.id);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rejects_ttl_for_permanent_tier() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let result = set_workspace_config(
+                    conn,
+                    &SetWorkspaceConfigInput {
+                        workspace: "archive".to_string(),
+                        default_tier: Some(MemoryTier::Permanent),
+                        default_ttl_seconds: Some(60),
+                        ..Default::default()
+                    },
+                );
+                assert!(matches!(result, Err(EngramError::InvalidInput(_))));
+                assert!(list_workspace_configs(conn)?.is_empty());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_dedup_threshold_update_keeps_other_defaults() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                set_workspace_config(
+                    conn,
+                    &SetWorkspaceConfigInput {
+                        workspace: "notes".to_string(),
+                        base_tags: vec!["notes".to_string()],
+                        ..Default::default()
+                    },
+                )?;
+                let config = set_workspace_dedup_threshold(conn, "Notes", Some(0.93))?;
+                assert_eq!(config.base_tags, vec!["notes".to_string()]);
+                assert_eq!(config.dedup_threshold, Some(0.93));
+
+                let mut input = CreateMemoryInput::default();
+                config.apply(&mut input);
+                assert_eq!(input.dedup_threshold, Some(0.93));
+
+                let fresh = set_workspace_dedup_threshold(conn, "other", Some(0.9))?;
+                assert!(fresh.base_tags.is_empty());
+                assert!(set_workspace_dedup_threshold(conn, "other", Some(1.5)).is_err());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_timezone_and_date_order() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let config = set_workspace_config(
+                    conn,
+                    &SetWorkspaceConfigInput {
+                        workspace: "berlin".to_string(),
+                        timezone: Some("UTC+1".to_string()),
+                        date_order: Some(DateOrder::Dmy),
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(config.timezone.as_deref(), Some("+01:00"));
+                assert_eq!(config.date_order, Some(DateOrder::Dmy));
+
+                let ctx = workspace_temporal_context(conn, "Berlin")?;
+                assert_eq!(ctx.offset.local_minus_utc(), 3600);
+                assert_eq!(ctx.date_order, DateOrder::Dmy);
+                let ctx = workspace_temporal_context(conn, "elsewhere")?;
+                assert_eq!(ctx.offset.local_minus_utc(), 0);
+
+                let invalid = set_workspace_config(
+                    conn,
+                    &SetWorkspaceConfigInput {
+                        workspace: "berlin".to_string(),
+                        timezone: Some("Europe/Berlin".to_string()),
+                        ..Default::default()
+                    },
+                );
+                assert!(matches!(invalid, Err(EngramError::InvalidInput(_))));
+                Ok(())
+            })
+            .unwrap();
+    }
+}
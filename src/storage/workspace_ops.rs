@@ -0,0 +1,703 @@
+//! Bulk workspace reorganization.
+//!
+//! - [`merge_workspaces`] moves every memory of one workspace into another,
+//!   collapsing exact duplicates and rewiring their cross-references onto the
+//!   surviving memory.
+//! - [`split_workspace`] moves a filtered subset of a workspace into a new one.
+//!
+//! Both operations plan first and only write when `dry_run` is false, so a dry
+//! run reports exactly what the real run would do. Run them inside
+//! `Storage::with_transaction` so a failure leaves the workspaces untouched.
+//!
+//! Both move the same memories: everything not deleted or expired, archived
+//! memories included, so nothing is stranded in the old workspace. A merge
+//! only collapses a memory into an unarchived copy, since folding it into an
+//! archived one would archive it too. Every move records a new version.
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::storage::queries::{
+    delete_memory, list_memories, load_tags, record_event, MemoryEventType,
+};
+use crate::types::{normalize_workspace, ListOptions};
+
+/// Options for [`merge_workspaces`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeOptions {
+    /// Tag added to every moved memory so its origin stays discoverable
+    pub origin_tag: Option<String>,
+    /// Collapse memories whose content already exists in the target workspace
+    #[serde(default)]
+    pub dedup: bool,
+    /// Plan only; do not write anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A source memory collapsed into an identical memory of the target workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateMerge {
+    /// Memory from the source workspace that is removed
+    pub source_id: i64,
+    /// Memory in the target workspace that absorbs its tags and links
+    pub kept_id: i64,
+}
+
+/// Outcome (or plan, for dry runs) of a workspace merge.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceMergeReport {
+    pub from: String,
+    pub into: String,
+    pub dry_run: bool,
+    /// Memories moved into the target workspace
+    pub moved: usize,
+    /// Source memories collapsed into existing target memories
+    pub duplicates: Vec<DuplicateMerge>,
+    /// Cross-references re-pointed from removed duplicates to kept memories
+    pub crossrefs_rewired: i64,
+    /// Cross-references dropped because the kept memory already had an
+    /// equivalent edge (or the edge would have become a self-loop)
+    pub crossrefs_dropped: i64,
+    pub origin_tag: Option<String>,
+}
+
+/// Outcome (or plan, for dry runs) of a workspace split.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceSplitReport {
+    pub source: String,
+    pub new_workspace: String,
+    pub dry_run: bool,
+    /// IDs of the memories selected by the filter
+    pub memory_ids: Vec<i64>,
+    /// Active cross-references that will link the two workspaces after the split
+    pub crossrefs_spanning: i64,
+}
+
+/// Merge every active memory of `from` into `into`.
+pub fn merge_workspaces(
+    conn: &Connection,
+    from: &str,
+    into: &str,
+    options: &MergeOptions,
+) -> Result<WorkspaceMergeReport> {
+    let from = normalize(from)?;
+    let into = normalize(into)?;
+    if from == into {
+        return Err(EngramError::InvalidInput(
+            "Cannot merge a workspace into itself".to_string(),
+        ));
+    }
+
+    let sources = active_memories(conn, &from)?;
+    if sources.is_empty() {
+        return Err(EngramError::InvalidInput(format!(
+            "Workspace '{}' is empty or does not exist",
+            from
+        )));
+    }
+
+    // Plan: which source memories already exist in the target workspace.
+    let mut report = WorkspaceMergeReport {
+        from: from.clone(),
+        into: into.clone(),
+        dry_run: options.dry_run,
+        origin_tag: options.origin_tag.clone(),
+        ..Default::default()
+    };
+    let mut moved_ids = Vec::new();
+    for source in &sources {
+        let kept = if options.dedup {
+            find_duplicate_in(conn, source, &into)?
+        } else {
+            None
+        };
+        match kept {
+            Some(kept_id) => {
+                let (rewired, dropped) = plan_rewire(conn, source.id, kept_id)?;
+                report.crossrefs_rewired += rewired;
+                report.crossrefs_dropped += dropped;
+                report.duplicates.push(DuplicateMerge {
+                    source_id: source.id,
+                    kept_id,
+                });
+            }
+            None => moved_ids.push(source.id),
+        }
+    }
+    report.moved = moved_ids.len();
+
+    if options.dry_run {
+        return Ok(report);
+    }
+
+    let now = Utc::now().to_rfc3339();
+    for id in &moved_ids {
+        if let Some(ref tag) = options.origin_tag {
+            add_tag(conn, *id, tag)?;
+        }
+        move_memory(conn, *id, &into, &now)?;
+        record_event(
+            conn,
+            MemoryEventType::Updated,
+            Some(*id),
+            None,
+            serde_json::json!({
+                "changed_fields": ["workspace"],
+                "action": "workspace_merge",
+                "from": from,
+                "into": into,
+            }),
+        )?;
+    }
+
+    for dup in &report.duplicates {
+        rewire_crossrefs(conn, dup.source_id, dup.kept_id)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO memory_tags (memory_id, tag_id)
+             SELECT ?, tag_id FROM memory_tags WHERE memory_id = ?",
+            params![dup.kept_id, dup.source_id],
+        )?;
+        delete_memory(conn, dup.source_id)?;
+    }
+
+    bump_sync_state(conn, moved_ids.len() as i64)?;
+
+    tracing::info!(
+        from = %from,
+        into = %into,
+        moved = report.moved,
+        duplicates = report.duplicates.len(),
+        "Merged workspace"
+    );
+
+    Ok(report)
+}
+
+/// Move the memories of `source` matching `filter` into `new_workspace`.
+///
+/// `filter` uses the regular [`ListOptions`] filters (tags, type, tier,
+/// metadata or advanced filter); its workspace, pagination and `as_of` fields
+/// are ignored. An empty filter is rejected — rename the workspace instead.
+pub fn split_workspace(
+    conn: &Connection,
+    source: &str,
+    new_workspace: &str,
+    filter: &ListOptions,
+    dry_run: bool,
+) -> Result<WorkspaceSplitReport> {
+    let source = normalize(source)?;
+    let new_workspace = normalize(new_workspace)?;
+    if source == new_workspace {
+        return Err(EngramError::InvalidInput(
+            "Split target must differ from the source workspace".to_string(),
+        ));
+    }
+
+    let has_filter = filter.tags.as_ref().is_some_and(|t| !t.is_empty())
+        || filter.memory_type.is_some()
+        || filter.tier.is_some()
+        || filter.filter.is_some()
+        || filter.metadata_filter.is_some()
+        || filter.scope.is_some()
+        || filter.event_after.is_some()
+        || filter.event_before.is_some();
+    if !has_filter {
+        return Err(EngramError::InvalidInput(
+            "workspace split requires at least one filter".to_string(),
+        ));
+    }
+
+    let options = ListOptions {
+        workspace: Some(source.clone()),
+        workspaces: None,
+        limit: Some(i64::MAX),
+        offset: None,
+        as_of: None,
+        show_superseded: true,
+        ..filter.clone()
+    };
+    let memory_ids: Vec<i64> = list_memories(conn, &options)?
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+
+    let crossrefs_spanning = count_spanning_crossrefs(conn, &memory_ids, &source)?;
+    let report = WorkspaceSplitReport {
+        source: source.clone(),
+        new_workspace: new_workspace.clone(),
+        dry_run,
+        memory_ids,
+        crossrefs_spanning,
+    };
+
+    if dry_run || report.memory_ids.is_empty() {
+        return Ok(report);
+    }
+
+    let now = Utc::now().to_rfc3339();
+    for id in &report.memory_ids {
+        move_memory(conn, *id, &new_workspace, &now)?;
+        record_event(
+            conn,
+            MemoryEventType::Updated,
+            Some(*id),
+            None,
+            serde_json::json!({
+                "changed_fields": ["workspace"],
+                "action": "workspace_split",
+                "from": source,
+                "into": new_workspace,
+            }),
+        )?;
+    }
+    bump_sync_state(conn, report.memory_ids.len() as i64)?;
+
+    tracing::info!(
+        source = %source,
+        new_workspace = %new_workspace,
+        moved = report.memory_ids.len(),
+        "Split workspace"
+    );
+
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+struct SourceMemory {
+    id: i64,
+    content_hash: Option<String>,
+    scope_type: String,
+    scope_id: Option<String>,
+}
+
+fn normalize(workspace: &str) -> Result<String> {
+    normalize_workspace(workspace)
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))
+}
+
+/// Memories of `workspace` a merge moves: the ones `list_memories` (and so a
+/// split) selects from, i.e. neither deleted nor expired.
+fn active_memories(conn: &Connection, workspace: &str) -> Result<Vec<SourceMemory>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, content_hash, scope_type, scope_id FROM memories
+         WHERE workspace = ? AND valid_to IS NULL
+           AND (expires_at IS NULL OR expires_at > ?)
+         ORDER BY id",
+    )?;
+    let rows = stmt
+        .query_map(params![workspace, Utc::now().to_rfc3339()], |row| {
+            Ok(SourceMemory {
+                id: row.get(0)?,
+                content_hash: row.get(1)?,
+                scope_type: row.get(2)?,
+                scope_id: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Oldest unarchived memory in `workspace` with the same content hash and
+/// scope.
+fn find_duplicate_in(
+    conn: &Connection,
+    source: &SourceMemory,
+    workspace: &str,
+) -> Result<Option<i64>> {
+    let Some(ref hash) = source.content_hash else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare_cached(
+        "SELECT id FROM memories
+         WHERE workspace = ? AND content_hash = ? AND scope_type = ? AND scope_id IS ?
+           AND valid_to IS NULL AND COALESCE(lifecycle_state, 'active') != 'archived'
+           AND (expires_at IS NULL OR expires_at > ?)
+         ORDER BY id LIMIT 1",
+    )?;
+    let mut rows = stmt.query(params![
+        workspace,
+        hash,
+        source.scope_type,
+        source.scope_id,
+        Utc::now().to_rfc3339()
+    ])?;
+    Ok(match rows.next()? {
+        Some(row) => Some(row.get(0)?),
+        None => None,
+    })
+}
+
+/// Count active edges of `dup` that can move to `kept` versus those that
+/// collide with an active edge of `kept` or would become self-loops.
+fn plan_rewire(conn: &Connection, dup: i64, kept: i64) -> Result<(i64, i64)> {
+    let total: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM crossrefs
+         WHERE valid_to IS NULL AND (from_id = ?1 OR to_id = ?1)",
+        params![dup],
+        |row| row.get(0),
+    )?;
+    let rewired: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM crossrefs c
+         WHERE c.valid_to IS NULL AND (
+             (c.from_id = ?1 AND c.to_id != ?2 AND NOT EXISTS (
+                 SELECT 1 FROM crossrefs e
+                 WHERE e.from_id = ?2 AND e.to_id = c.to_id AND e.edge_type = c.edge_type
+                   AND e.valid_to IS NULL))
+          OR (c.to_id = ?1 AND c.from_id != ?2 AND NOT EXISTS (
+                 SELECT 1 FROM crossrefs e
+                 WHERE e.from_id = c.from_id AND e.to_id = ?2 AND e.edge_type = c.edge_type
+                   AND e.valid_to IS NULL))
+         )",
+        params![dup, kept],
+        |row| row.get(0),
+    )?;
+    Ok((rewired, total - rewired))
+}
+
+/// Re-point active edges from `dup` to `kept`. An invalidated edge of `kept`
+/// with the same endpoints and type (which the unique key would collide with)
+/// is revived with the moving edge's values instead. Edges colliding with an
+/// active edge are left on `dup` and invalidated when it is deleted.
+fn rewire_crossrefs(conn: &Connection, dup: i64, kept: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE crossrefs AS e
+         SET valid_from = c.valid_from, valid_to = NULL, score = c.score,
+             confidence = c.confidence, strength = c.strength, source = c.source,
+             source_context = c.source_context, pinned = c.pinned, metadata = c.metadata
+         FROM crossrefs AS c
+         WHERE e.valid_to IS NOT NULL AND c.valid_to IS NULL AND e.edge_type = c.edge_type
+           AND ((c.from_id = ?1 AND c.to_id != ?2 AND e.from_id = ?2 AND e.to_id = c.to_id)
+             OR (c.to_id = ?1 AND c.from_id != ?2 AND e.to_id = ?2 AND e.from_id = c.from_id))",
+        params![dup, kept],
+    )?;
+    conn.execute(
+        "UPDATE OR IGNORE crossrefs SET from_id = ?2
+         WHERE from_id = ?1 AND to_id != ?2 AND valid_to IS NULL",
+        params![dup, kept],
+    )?;
+    conn.execute(
+        "UPDATE OR IGNORE crossrefs SET to_id = ?2
+         WHERE to_id = ?1 AND from_id != ?2 AND valid_to IS NULL",
+        params![dup, kept],
+    )?;
+    Ok(())
+}
+
+fn count_spanning_crossrefs(conn: &Connection, ids: &[i64], source: &str) -> Result<i64> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let id_list = ids
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let sql = format!(
+        "SELECT COUNT(*) FROM crossrefs c
+         JOIN memories m ON m.id = CASE WHEN c.from_id IN ({ids}) THEN c.to_id ELSE c.from_id END
+         WHERE c.valid_to IS NULL
+           AND (c.from_id IN ({ids})) != (c.to_id IN ({ids}))
+           AND m.workspace = ? AND m.valid_to IS NULL",
+        ids = id_list
+    );
+    Ok(conn.query_row(&sql, params![source], |row| row.get(0))?)
+}
+
+/// Move `id` to `workspace`, recording the move as a new version like any
+/// other update.
+fn move_memory(conn: &Connection, id: i64, workspace: &str, now: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE memories SET workspace = ?, updated_at = ?, version = version + 1 WHERE id = ?",
+        params![workspace, now, id],
+    )?;
+    let tags_json = serde_json::to_string(&load_tags(conn, id)?)?;
+    conn.execute(
+        "INSERT INTO memory_versions (memory_id, version, content, tags, metadata, created_at, change_summary)
+         SELECT id, version, content, ?, metadata, ?, ? FROM memories WHERE id = ?",
+        params![
+            tags_json,
+            now,
+            format!("Moved to workspace '{}'", workspace),
+            id
+        ],
+    )?;
+    Ok(())
+}
+
+fn add_tag(conn: &Connection, id: i64, tag: &str) -> Result<()> {
+    conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", params![tag])?;
+    conn.execute(
+        "INSERT OR IGNORE INTO memory_tags (memory_id, tag_id)
+         SELECT ?, id FROM tags WHERE name = ?",
+        params![id, tag],
+    )?;
+    Ok(())
+}
+
+fn bump_sync_state(conn: &Connection, changes: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE sync_state SET pending_changes = pending_changes + ?, version = (SELECT COALESCE(MAX(id), 0) FROM memory_events) WHERE id = 1",
+        params![changes],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::queries::{
+        create_crossref, create_memory, get_memory, get_memory_versions,
+    };
+    use crate::storage::Storage;
+    use crate::types::{CreateCrossRefInput, CreateMemoryInput, EdgeType};
+
+    fn memory(conn: &Connection, content: &str, workspace: &str, tags: &[&str]) -> i64 {
+        create_memory(
+            conn,
+            &CreateMemoryInput {
+                content: content.to_string(),
+                workspace: Some(workspace.to_string()),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                defer_embedding: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .id
+    }
+
+    fn link(conn: &Connection, from_id: i64, to_id: i64) {
+        create_crossref(
+            conn,
+            &CreateCrossRefInput {
+                from_id,
+                to_id,
+                edge_type: EdgeType::RelatedTo,
+                strength: None,
+                source_context: None,
+                pinned: false,
+            },
+        )
+        .unwrap();
+    }
+
+    fn workspace_of(conn: &Connection, id: i64) -> String {
+        conn.query_row(
+            "SELECT workspace FROM memories WHERE id = ?",
+            params![id],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_dedups_and_rewires() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let kept = memory(conn, "Deploys go through CI", "platform", &["ci"]);
+                let dup = memory(conn, "Deploys go through CI", "infra", &["deploy"]);
+                let unique = memory(conn, "Terraform state lives in S3", "infra", &[]);
+                link(conn, dup, unique);
+
+                let options = MergeOptions {
+                    origin_tag: Some("from-infra".to_string()),
+                    dedup: true,
+                    dry_run: true,
+                };
+                let plan = merge_workspaces(conn, "infra", "platform", &options)?;
+                assert_eq!(plan.moved, 1);
+                assert_eq!(
+                    plan.duplicates,
+                    vec![DuplicateMerge {
+                        source_id: dup,
+                        kept_id: kept
+                    }]
+                );
+                assert_eq!(plan.crossrefs_rewired, 1);
+                assert_eq!(workspace_of(conn, unique), "infra", "dry run writes nothing");
+
+                let report = merge_workspaces(
+                    conn,
+                    "infra",
+                    "platform",
+                    &MergeOptions {
+                        dry_run: false,
+                        ..options
+                    },
+                )?;
+                assert_eq!(report.moved, plan.moved);
+                assert_eq!(workspace_of(conn, unique), "platform");
+                assert!(get_memory(conn, dup).is_err());
+
+                let kept_memory = get_memory(conn, kept)?;
+                assert!(kept_memory.tags.contains(&"deploy".to_string()));
+                assert!(get_memory(conn, unique)?
+                    .tags
+                    .contains(&"from-infra".to_string()));
+
+                let rewired: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM crossrefs WHERE from_id = ? AND to_id = ? AND valid_to IS NULL",
+                    params![kept, unique],
+                    |row| row.get(0),
+                )?;
+                assert_eq!(rewired, 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_merge_rejects_invalid_targets() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                memory(conn, "Something", "alpha", &[]);
+                let options = MergeOptions::default();
+                assert!(merge_workspaces(conn, "alpha", "ALPHA", &options).is_err());
+                assert!(merge_workspaces(conn, "missing", "alpha", &options).is_err());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_split_moves_filtered_subset() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let api = memory(conn, "API uses JWT", "monorepo", &["api"]);
+                let web = memory(conn, "Web uses React", "monorepo", &["web"]);
+                link(conn, api, web);
+
+                let filter = ListOptions {
+                    tags: Some(vec!["api".to_string()]),
+                    ..Default::default()
+                };
+                let plan = split_workspace(conn, "monorepo", "api-service", &filter, true)?;
+                assert_eq!(plan.memory_ids, vec![api]);
+                assert_eq!(plan.crossrefs_spanning, 1);
+                assert_eq!(workspace_of(conn, api), "monorepo");
+
+                split_workspace(conn, "monorepo", "api-service", &filter, false)?;
+                assert_eq!(workspace_of(conn, api), "api-service");
+                assert_eq!(workspace_of(conn, web), "monorepo");
+
+                let unfiltered = ListOptions::default();
+                assert!(split_workspace(conn, "monorepo", "other", &unfiltered, true).is_err());
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_merge_rewires_onto_invalidated_edges() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let kept = memory(conn, "Deploys go through CI", "platform", &[]);
+                let dup = memory(conn, "Deploys go through CI", "infra", &[]);
+                let target = memory(conn, "CI runs on GitHub Actions", "platform", &[]);
+                link(conn, kept, target);
+                conn.execute(
+                    "UPDATE crossrefs SET valid_to = '2026-01-01T00:00:00+00:00' WHERE from_id = ?",
+                    params![kept],
+                )?;
+                link(conn, dup, target);
+
+                let options = MergeOptions {
+                    dedup: true,
+                    ..Default::default()
+                };
+                let report = merge_workspaces(conn, "infra", "platform", &options)?;
+                assert_eq!(report.crossrefs_rewired, 1);
+                assert_eq!(report.crossrefs_dropped, 0);
+
+                let active: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM crossrefs WHERE from_id = ? AND to_id = ? AND valid_to IS NULL",
+                    params![kept, target],
+                    |row| row.get(0),
+                )?;
+                assert_eq!(active, 1);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_moves_record_versions() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let merged = memory(conn, "Runbooks live in the wiki", "ops", &["docs"]);
+                let split = memory(conn, "API uses JWT", "monorepo", &["api"]);
+
+                let options = MergeOptions {
+                    origin_tag: Some("from-ops".to_string()),
+                    ..Default::default()
+                };
+                merge_workspaces(conn, "ops", "platform", &options)?;
+                let filter = ListOptions {
+                    tags: Some(vec!["api".to_string()]),
+                    ..Default::default()
+                };
+                split_workspace(conn, "monorepo", "api-service", &filter, false)?;
+
+                for id in [merged, split] {
+                    let memory = get_memory(conn, id)?;
+                    let versions = get_memory_versions(conn, id)?;
+                    let latest = versions.iter().max_by_key(|v| v.version).unwrap();
+                    assert_eq!(latest.version, memory.version);
+                    assert_eq!(latest.tags, memory.tags);
+                }
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_merge_and_split_treat_archived_alike() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_transaction(|conn| {
+                let archive = |id: i64| {
+                    conn.execute(
+                        "UPDATE memories SET lifecycle_state = 'archived' WHERE id = ?",
+                        params![id],
+                    )
+                };
+                let old_copy = memory(conn, "Deploys go through CI", "platform", &[]);
+                archive(old_copy)?;
+                let source = memory(conn, "Deploys go through CI", "infra", &[]);
+                let archived = memory(conn, "Old deploy notes", "infra", &[]);
+                archive(archived)?;
+
+                // Archived memories move, and nothing folds into an archived copy
+                let options = MergeOptions {
+                    dedup: true,
+                    ..Default::default()
+                };
+                let report = merge_workspaces(conn, "infra", "platform", &options)?;
+                assert!(report.duplicates.is_empty());
+                assert_eq!(report.moved, 2);
+                assert_eq!(workspace_of(conn, source), "platform");
+                assert_eq!(workspace_of(conn, archived), "platform");
+
+                let filter = ListOptions {
+                    memory_type: Some(crate::types::MemoryType::Note),
+                    ..Default::default()
+                };
+                let split = split_workspace(conn, "platform", "archive", &filter, true)?;
+                assert!(split.memory_ids.contains(&archived));
+                assert!(split.memory_ids.contains(&old_copy));
+                Ok(())
+            })
+            .unwrap();
+    }
+}
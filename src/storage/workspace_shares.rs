@@ -0,0 +1,290 @@
+//! Read-only share links for a workspace
+//!
+//! A share publishes one workspace over the unauthenticated
+//! `/public/:token` HTTP routes, stored in the `workspace_shares` table
+//! (schema v52). The token is the whole credential: anyone holding the link
+//! can search and browse the graph of that workspace, nothing else, until
+//! the share expires or is revoked. Each share carries its own request rate
+//! limit, enforced by the HTTP transport.
+
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{EngramError, Result};
+use crate::types::normalize_workspace;
+
+/// Requests per minute allowed on a share link when none is given
+pub const DEFAULT_SHARE_REQUESTS_PER_MINUTE: u32 = 60;
+
+/// A share link publishing a workspace read-only
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceShare {
+    pub token: String,
+    pub workspace: String,
+    pub label: Option<String>,
+    pub requests_per_minute: u32,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+impl WorkspaceShare {
+    /// Whether the link still grants access at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        if self.revoked_at.is_some() {
+            return false;
+        }
+        match self.expires_at.as_deref().map(DateTime::parse_from_rfc3339) {
+            Some(Ok(expires_at)) => expires_at > now,
+            Some(Err(_)) => false,
+            None => true,
+        }
+    }
+
+    /// Path of the share under the HTTP transport
+    pub fn path(&self) -> String {
+        format!("/public/{}", self.token)
+    }
+}
+
+/// Input for creating a share link
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateWorkspaceShareInput {
+    pub workspace: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Days until the link stops working; `None` never expires
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+}
+
+/// Parse a WorkspaceShare from a rusqlite row.
+///
+/// Columns expected in order: token, workspace, label, requests_per_minute,
+/// created_at, expires_at, revoked_at
+fn share_from_row(row: &rusqlite::Row) -> rusqlite::Result<WorkspaceShare> {
+    Ok(WorkspaceShare {
+        token: row.get(0)?,
+        workspace: row.get(1)?,
+        label: row.get(2)?,
+        requests_per_minute: row.get(3)?,
+        created_at: row.get(4)?,
+        expires_at: row.get(5)?,
+        revoked_at: row.get(6)?,
+    })
+}
+
+const SHARE_COLUMNS: &str =
+    "token, workspace, label, requests_per_minute, created_at, expires_at, revoked_at";
+
+/// Generate an unguessable share token
+fn generate_share_token() -> String {
+    let mut rng = rand::thread_rng();
+    let bytes: Vec<u8> = (0..24).map(|_| rng.gen()).collect();
+    format!("shr_{}", hex::encode(bytes))
+}
+
+/// Create a share link for a workspace
+pub fn create_workspace_share(
+    conn: &Connection,
+    input: &CreateWorkspaceShareInput,
+) -> Result<WorkspaceShare> {
+    let workspace = normalize_workspace(&input.workspace)
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))?;
+    let requests_per_minute = input
+        .requests_per_minute
+        .unwrap_or(DEFAULT_SHARE_REQUESTS_PER_MINUTE);
+    if requests_per_minute == 0 {
+        return Err(EngramError::InvalidInput(
+            "requests_per_minute must be positive".to_string(),
+        ));
+    }
+    let now = Utc::now();
+    let expires_at = match input.expires_in_days {
+        Some(days) if days <= 0 => {
+            return Err(EngramError::InvalidInput(
+                "expires_in_days must be positive".to_string(),
+            ))
+        }
+        Some(days) => Some((now + Duration::days(days)).to_rfc3339()),
+        None => None,
+    };
+    let label = input
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string);
+
+    let share = WorkspaceShare {
+        token: generate_share_token(),
+        workspace,
+        label,
+        requests_per_minute,
+        created_at: now.to_rfc3339(),
+        expires_at,
+        revoked_at: None,
+    };
+    conn.execute(
+        &format!(
+            "INSERT INTO workspace_shares ({}) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            SHARE_COLUMNS
+        ),
+        params![
+            share.token,
+            share.workspace,
+            share.label,
+            share.requests_per_minute,
+            share.created_at,
+            share.expires_at,
+            share.revoked_at,
+        ],
+    )?;
+    Ok(share)
+}
+
+/// Look up a share link by token, whether or not it is still active.
+pub fn get_workspace_share(conn: &Connection, token: &str) -> Result<Option<WorkspaceShare>> {
+    conn.prepare_cached(&format!(
+        "SELECT {} FROM workspace_shares WHERE token = ?",
+        SHARE_COLUMNS
+    ))?
+    .query_row(params![token], share_from_row)
+    .optional()
+    .map_err(EngramError::from)
+}
+
+/// Look up a share link that still grants access at `now`.
+pub fn resolve_workspace_share(
+    conn: &Connection,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<WorkspaceShare>> {
+    Ok(get_workspace_share(conn, token)?.filter(|share| share.is_active(now)))
+}
+
+/// List share links, newest first, optionally for one workspace.
+pub fn list_workspace_shares(
+    conn: &Connection,
+    workspace: Option<&str>,
+    include_inactive: bool,
+) -> Result<Vec<WorkspaceShare>> {
+    let workspace = workspace
+        .map(normalize_workspace)
+        .transpose()
+        .map_err(|e| EngramError::InvalidInput(format!("Invalid workspace: {}", e)))?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM workspace_shares WHERE (?1 IS NULL OR workspace = ?1) \
+         ORDER BY created_at DESC, token",
+        SHARE_COLUMNS
+    ))?;
+    let now = Utc::now();
+    let shares = stmt
+        .query_map(params![workspace], share_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|share| include_inactive || share.is_active(now))
+        .collect();
+    Ok(shares)
+}
+
+/// Revoke a share link. Returns `false` if it doesn't exist or was already
+/// revoked.
+pub fn revoke_workspace_share(conn: &Connection, token: &str) -> Result<bool> {
+    let affected = conn.execute(
+        "UPDATE workspace_shares SET revoked_at = ? WHERE token = ? AND revoked_at IS NULL",
+        params![Utc::now().to_rfc3339(), token],
+    )?;
+    Ok(affected > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+
+    fn input(workspace: &str) -> CreateWorkspaceShareInput {
+        CreateWorkspaceShareInput {
+            workspace: workspace.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_share_lifecycle() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let share = create_workspace_share(conn, &input("Handbook"))?;
+                assert!(share.token.starts_with("shr_"));
+                assert_eq!(share.workspace, "handbook");
+                assert_eq!(share.requests_per_minute, DEFAULT_SHARE_REQUESTS_PER_MINUTE);
+                assert_eq!(share.path(), format!("/public/{}", share.token));
+
+                let other = create_workspace_share(conn, &input("notes"))?;
+                assert_ne!(share.token, other.token);
+
+                let now = Utc::now();
+                assert_eq!(
+                    resolve_workspace_share(conn, &share.token, now)?,
+                    Some(share.clone())
+                );
+                assert!(resolve_workspace_share(conn, "shr_unknown", now)?.is_none());
+                assert_eq!(
+                    list_workspace_shares(conn, Some("handbook"), false)?.len(),
+                    1
+                );
+
+                assert!(revoke_workspace_share(conn, &share.token)?);
+                assert!(!revoke_workspace_share(conn, &share.token)?);
+                assert!(resolve_workspace_share(conn, &share.token, now)?.is_none());
+                assert!(list_workspace_shares(conn, Some("handbook"), false)?.is_empty());
+                assert_eq!(list_workspace_shares(conn, None, true)?.len(), 2);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_share_expiry_and_validation() {
+        let storage = Storage::open_in_memory().unwrap();
+        storage
+            .with_connection(|conn| {
+                let share = create_workspace_share(
+                    conn,
+                    &CreateWorkspaceShareInput {
+                        expires_in_days: Some(7),
+                        requests_per_minute: Some(10),
+                        label: Some("  Public docs ".to_string()),
+                        ..input("docs")
+                    },
+                )?;
+                assert_eq!(share.label.as_deref(), Some("Public docs"));
+                assert!(share.is_active(Utc::now()));
+                assert!(!share.is_active(Utc::now() + Duration::days(8)));
+
+                let zero_rate = create_workspace_share(
+                    conn,
+                    &CreateWorkspaceShareInput {
+                        requests_per_minute: Some(0),
+                        ..input("docs")
+                    },
+                );
+                assert!(matches!(zero_rate, Err(EngramError::InvalidInput(_))));
+                let past = create_workspace_share(
+                    conn,
+                    &CreateWorkspaceShareInput {
+                        expires_in_days: Some(0),
+                        ..input("docs")
+                    },
+                );
+                assert!(matches!(past, Err(EngramError::InvalidInput(_))));
+                Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -75,158 +75,83 @@ impl ThreeWayMerge {
     }
 
     /// Perform three-way merge
+    ///
+    /// Lines are aligned against `base` by longest common subsequence (diff3).
+    /// Lines unchanged on both sides are kept; a region changed on only one
+    /// side takes that side; a region changed identically on both sides is
+    /// taken once; anything else becomes a conflict block with both versions.
     pub fn merge(&self, base: &str, local: &str, remote: &str) -> MergeResult {
-        let base_lines: Vec<&str> = base.lines().collect();
-        let local_lines: Vec<&str> = local.lines().collect();
-        let remote_lines: Vec<&str> = remote.lines().collect();
+        // Split on '\n' rather than `lines()` so trailing newlines and
+        // carriage returns survive the round trip through `join`.
+        let base_lines: Vec<&str> = base.split('\n').collect();
+        let local_lines: Vec<&str> = local.split('\n').collect();
+        let remote_lines: Vec<&str> = remote.split('\n').collect();
 
-        let mut result = Vec::new();
+        let local_match = lcs_matches(&base_lines, &local_lines);
+        let remote_match = lcs_matches(&base_lines, &remote_lines);
+
+        let mut result: Vec<String> = Vec::new();
         let mut stats = MergeStats::default();
         let mut conflict_lines = Vec::new();
-        let mut has_conflicts = false;
-
-        let max_len = base_lines
-            .len()
-            .max(local_lines.len())
-            .max(remote_lines.len());
-        let mut base_idx = 0;
-        let mut local_idx = 0;
-        let mut remote_idx = 0;
-
-        while base_idx < base_lines.len()
-            || local_idx < local_lines.len()
-            || remote_idx < remote_lines.len()
-        {
-            let base_line = base_lines.get(base_idx);
-            let local_line = local_lines.get(local_idx);
-            let remote_line = remote_lines.get(remote_idx);
-
-            match (base_line, local_line, remote_line) {
-                // All three match - keep it
-                (Some(b), Some(l), Some(r)) if b == l && l == r => {
-                    result.push((*l).to_string());
-                    stats.base_kept += 1;
-                    base_idx += 1;
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Local changed, remote unchanged - take local
-                (Some(b), Some(l), Some(r)) if b == r && b != l => {
-                    result.push((*l).to_string());
-                    stats.local_added += 1;
-                    base_idx += 1;
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Remote changed, local unchanged - take remote
-                (Some(b), Some(l), Some(r)) if b == l && b != r => {
-                    result.push((*r).to_string());
-                    stats.remote_added += 1;
-                    base_idx += 1;
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Both changed differently - conflict!
-                (Some(_), Some(l), Some(r)) if l != r => {
-                    has_conflicts = true;
-                    stats.conflicts += 1;
-                    conflict_lines.push(result.len());
-
-                    result.push(self.local_marker.clone());
-                    result.push((*l).to_string());
-                    result.push(self.separator.clone());
-                    result.push((*r).to_string());
-                    result.push(self.remote_marker.clone());
-
-                    base_idx += 1;
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Both changed to same - take it
-                (Some(_), Some(l), Some(r)) if l == r => {
-                    result.push((*l).to_string());
-                    stats.local_added += 1;
-                    base_idx += 1;
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Local added line (past base)
-                (None, Some(l), None) => {
-                    result.push((*l).to_string());
-                    stats.local_added += 1;
-                    local_idx += 1;
-                }
-
-                // Remote added line (past base)
-                (None, None, Some(r)) => {
-                    result.push((*r).to_string());
-                    stats.remote_added += 1;
-                    remote_idx += 1;
-                }
-
-                // Both added different lines - conflict
-                (None, Some(l), Some(r)) if l != r => {
-                    has_conflicts = true;
-                    stats.conflicts += 1;
-                    conflict_lines.push(result.len());
-
-                    result.push(self.local_marker.clone());
-                    result.push((*l).to_string());
-                    result.push(self.separator.clone());
-                    result.push((*r).to_string());
-                    result.push(self.remote_marker.clone());
-
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Both added same line
-                (None, Some(l), Some(r)) if l == r => {
-                    result.push((*l).to_string());
-                    stats.local_added += 1;
-                    local_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Local deleted (remote unchanged)
-                (Some(_), None, Some(r)) if base_lines.get(base_idx) == Some(r) => {
-                    stats.local_deleted += 1;
-                    base_idx += 1;
-                    remote_idx += 1;
-                }
-
-                // Remote deleted (local unchanged)
-                (Some(_), Some(l), None) if base_lines.get(base_idx) == Some(l) => {
-                    stats.remote_deleted += 1;
-                    base_idx += 1;
-                    local_idx += 1;
-                }
-
-                // Fallback: take what we have
-                _ => {
-                    if let Some(l) = local_line {
-                        result.push((*l).to_string());
-                    }
-                    if let Some(r) = remote_line {
-                        if local_line.map(|l| l != r).unwrap_or(true) {
-                            result.push((*r).to_string());
-                        }
-                    }
-                    break; // Prevent infinite loop
-                }
+
+        let (mut base_idx, mut local_idx, mut remote_idx) = (0, 0, 0);
+        loop {
+            // Unchanged on both sides
+            if base_idx < base_lines.len()
+                && local_match[base_idx] == Some(local_idx)
+                && remote_match[base_idx] == Some(remote_idx)
+            {
+                result.push(base_lines[base_idx].to_string());
+                stats.base_kept += 1;
+                base_idx += 1;
+                local_idx += 1;
+                remote_idx += 1;
+                continue;
             }
 
-            // Safety: prevent infinite loops
-            if base_idx + local_idx + remote_idx > max_len * 3 + 10 {
+            // Next base line both sides still contain, or the end of input
+            let (base_end, local_end, remote_end) = (base_idx..base_lines.len())
+                .find_map(|i| match (local_match[i], remote_match[i]) {
+                    (Some(l), Some(r)) => Some((i, l, r)),
+                    _ => None,
+                })
+                .unwrap_or((base_lines.len(), local_lines.len(), remote_lines.len()));
+
+            let base_chunk = &base_lines[base_idx..base_end];
+            let local_chunk = &local_lines[local_idx..local_end];
+            let remote_chunk = &remote_lines[remote_idx..remote_end];
+
+            if local_chunk == base_chunk {
+                result.extend(remote_chunk.iter().map(|l| l.to_string()));
+                stats.remote_added += remote_chunk.len();
+                stats.remote_deleted += base_chunk.len();
+            } else if remote_chunk == base_chunk || local_chunk == remote_chunk {
+                result.extend(local_chunk.iter().map(|l| l.to_string()));
+                stats.local_added += local_chunk.len();
+                stats.local_deleted += base_chunk.len();
+            } else {
+                stats.conflicts += 1;
+                conflict_lines.push(result.len());
+
+                result.push(self.local_marker.clone());
+                result.extend(local_chunk.iter().map(|l| l.to_string()));
+                result.push(self.separator.clone());
+                result.extend(remote_chunk.iter().map(|l| l.to_string()));
+                result.push(self.remote_marker.clone());
+            }
+
+            if base_end == base_lines.len()
+                && local_end == local_lines.len()
+                && remote_end == remote_lines.len()
+            {
                 break;
             }
+            base_idx = base_end;
+            local_idx = local_end;
+            remote_idx = remote_end;
         }
 
+        let has_conflicts = stats.conflicts > 0;
         MergeResult {
             content: result.join("\n"),
             success: !has_conflicts,
@@ -237,87 +162,126 @@ impl ThreeWayMerge {
     }
 
     /// Merge tags by taking union
-    pub fn merge_tags(&self, base: &[String], local: &[String], remote: &[String]) -> Vec<String> {
-        let mut result: HashSet<String> = HashSet::new();
-
-        // Start with base
-        result.extend(base.iter().cloned());
-
-        // Add local additions
-        for tag in local {
-            result.insert(tag.clone());
-        }
-
-        // Add remote additions
-        for tag in remote {
-            result.insert(tag.clone());
-        }
-
-        // Remove tags deleted by both
-        let local_set: HashSet<_> = local.iter().collect();
-        let remote_set: HashSet<_> = remote.iter().collect();
-
-        result
-            .into_iter()
-            .filter(|tag| {
-                // Keep if: in local OR in remote OR (in base AND (in local OR in remote))
-                local_set.contains(tag) || remote_set.contains(tag)
-            })
+    ///
+    /// Local order is kept, followed by tags only remote has. A tag is dropped
+    /// only when both sides removed it.
+    pub fn merge_tags(&self, _base: &[String], local: &[String], remote: &[String]) -> Vec<String> {
+        let mut seen: HashSet<&String> = HashSet::new();
+        local
+            .iter()
+            .chain(remote)
+            .filter(|tag| seen.insert(tag))
+            .cloned()
             .collect()
     }
 
     /// Merge metadata by combining with local preference for conflicts
-    /// Now works with HashMap directly
+    ///
+    /// A key changed (or removed) on one side only takes that side's value.
+    /// When both sides changed a key differently, local wins, except that a
+    /// local removal does not discard a remote edit.
     pub fn merge_metadata_map(
         &self,
         base: Option<&HashMap<String, serde_json::Value>>,
         local: &HashMap<String, serde_json::Value>,
         remote: &HashMap<String, serde_json::Value>,
     ) -> HashMap<String, serde_json::Value> {
-        let mut result = HashMap::new();
-
         // If local and remote are the same, return local
         if local == remote {
             return local.clone();
         }
 
-        // Start with base if present, otherwise start fresh
-        if let Some(base_map) = base {
-            result = base_map.clone();
-        }
+        let keys: HashSet<&String> = base
+            .into_iter()
+            .flat_map(|b| b.keys())
+            .chain(local.keys())
+            .chain(remote.keys())
+            .collect();
 
-        // Apply local changes
-        for (k, v) in local {
-            let base_value = base.and_then(|b| b.get(k));
-            if base_value != Some(v) {
-                result.insert(k.clone(), v.clone());
+        let mut result = HashMap::new();
+        for key in keys {
+            let base_value = base.and_then(|b| b.get(key));
+            let local_value = local.get(key);
+            let remote_value = remote.get(key);
+
+            let merged = if local_value == base_value {
+                remote_value
+            } else if remote_value == base_value {
+                local_value
+            } else {
+                local_value.or(remote_value)
+            };
+            if let Some(value) = merged {
+                result.insert(key.clone(), value.clone());
             }
         }
 
-        // Apply remote changes (if not conflicting with local)
-        for (k, v) in remote {
-            let base_value = base.and_then(|b| b.get(k));
-            let local_value = local.get(k);
-            // Only apply remote change if local didn't change this key from base
-            if base_value != Some(v) && local_value == base_value {
-                result.insert(k.clone(), v.clone());
-            }
-        }
+        result
+    }
+}
 
-        // Add any keys that are only in local or remote (not in base)
-        for (k, v) in local {
-            if !result.contains_key(k) {
-                result.insert(k.clone(), v.clone());
-            }
+/// Largest middle section (after trimming the common prefix and suffix) that
+/// is aligned line by line; bigger sections are treated as entirely changed.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// For each line of `base`, the index of the matching line in `other` along a
+/// longest common subsequence.
+fn lcs_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+
+    let prefix = base.iter().zip(other).take_while(|(b, o)| b == o).count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(other[prefix..].iter().rev())
+        .take_while(|(b, o)| b == o)
+        .count();
+    for (i, slot) in matches.iter_mut().enumerate().take(prefix) {
+        *slot = Some(i);
+    }
+    for k in 0..suffix {
+        matches[base.len() - 1 - k] = Some(other.len() - 1 - k);
+    }
+
+    let base_mid = &base[prefix..base.len() - suffix];
+    let other_mid = &other[prefix..other.len() - suffix];
+    if (base_mid.len() + 1).saturating_mul(other_mid.len() + 1) <= MAX_LCS_CELLS {
+        for (i, j) in lcs_pairs(base_mid, other_mid) {
+            matches[prefix + i] = Some(prefix + j);
         }
-        for (k, v) in remote {
-            if !result.contains_key(k) {
-                result.insert(k.clone(), v.clone());
-            }
+    }
+    matches
+}
+
+/// Matched index pairs of a longest common subsequence of `base` and `other`.
+fn lcs_pairs(base: &[&str], other: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (base.len(), other.len());
+    // lengths[i][j] = LCS length of base[i..] and other[j..]
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if base[i] == other[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
         }
+    }
 
-        result
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if base[i] == other[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
     }
+    pairs
 }
 
 #[cfg(test)]
@@ -14,7 +14,7 @@ pub use detector::{ConflictDetector, ConflictInfo, ConflictType};
 pub use merge::{MergeResult, ThreeWayMerge};
 pub use resolver::{ConflictQueue, ConflictResolver, Resolution, ResolutionStrategy};
 
-use crate::types::Memory;
+use crate::types::{ContentHash, Memory};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -47,17 +47,10 @@ impl SyncMemoryVersion {
         }
     }
 
-    /// Compute hash of memory content
+    /// Exact hash of the memory's content and metadata
     fn compute_hash(memory: &Memory) -> String {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(memory.content.as_bytes());
-        hasher.update(
-            serde_json::to_string(&memory.metadata)
-                .unwrap_or_default()
-                .as_bytes(),
-        );
-        hex::encode(hasher.finalize())[..16].to_string()
+        let metadata = serde_json::to_string(&memory.metadata).unwrap_or_default();
+        ContentHash::of_parts([memory.content.as_bytes(), metadata.as_bytes()]).into_string()
     }
 
     /// Check if this version has the same content as another
@@ -0,0 +1,472 @@
+//! Test fixtures for crates that build on Engram
+//!
+//! Enabled with the `testing` feature. Provides what Engram's own tests use
+//! to get going without a model or an API key:
+//!
+//! - [`TestEngram`]: an in-memory store with an embedder, seeded memories,
+//!   a search shortcut and an MCP [`HandlerContext`]
+//! - [`FakeEmbedder`]: deterministic vectors whose similarities are set by
+//!   keywords, so tests can say which memories should match a query
+//! - [`seed_memories`]: reproducible memories spread over [`SEED_TOPICS`]
+//! - assertions over search results ([`assert_top_result`] and friends)
+//!
+//! ```rust,ignore
+//! use engram::testing::{assert_top_result, TestEngram};
+//!
+//! let mut engram = TestEngram::seeded(50, 7)?;
+//! let memory = engram.add("Billing retries failed payments three times")?;
+//! let results = engram.search("billing retries")?;
+//! assert_top_result(&results, memory.id);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::embedding::{Embedder, EmbeddingCache};
+use crate::error::Result;
+use crate::mcp::handlers::HandlerContext;
+use crate::search::{
+    hybrid_search, AdaptiveCacheConfig, FuzzyEngine, SearchConfig, SearchResultCache,
+};
+use crate::storage::{queries, Storage};
+use crate::types::{CreateMemoryInput, Memory, MemoryId, MemoryType, SearchOptions, SearchResult};
+
+/// Topics [`seed_memories`] draws from. [`FakeEmbedder::for_seed_topics`]
+/// gives each its own axis.
+pub const SEED_TOPICS: &[&str] = &[
+    "billing",
+    "deploys",
+    "oncall",
+    "roadmap",
+    "hiring",
+    "security",
+    "database",
+    "onboarding",
+];
+
+const SEED_TEMPLATES: &[&str] = &[
+    "Notes on {topic}: {detail}",
+    "Decided during the {topic} review that {detail}",
+    "Reminder about {topic}: {detail}",
+    "Open question on {topic}: {detail}",
+];
+
+const SEED_DETAILS: &[&str] = &[
+    "the owner changes next quarter",
+    "the checklist needs another pass",
+    "numbers look better than last month",
+    "follow up with the platform team",
+    "keep the current process for now",
+    "write it down in the handbook",
+];
+
+const SEED_TYPES: &[MemoryType] = &[
+    MemoryType::Note,
+    MemoryType::Decision,
+    MemoryType::Todo,
+    MemoryType::Learning,
+];
+
+// ---------------------------------------------------------------------------
+// Fake embedder
+// ---------------------------------------------------------------------------
+
+/// Deterministic embedder with controllable similarities.
+///
+/// A text containing a registered topic keyword (case-insensitive) points
+/// along that topic's axis, plus a little per-text noise: texts sharing one
+/// topic have a cosine near 1, texts with disjoint topics near 0, and a text
+/// with two topics sits at about 0.7 to each. Texts without a topic get a
+/// pseudo-random direction derived from their hash. [`with_vector`] pins the
+/// exact vector of a text.
+///
+/// [`with_vector`]: FakeEmbedder::with_vector
+#[derive(Debug, Clone)]
+pub struct FakeEmbedder {
+    dimensions: usize,
+    topics: Vec<(String, usize)>,
+    vectors: HashMap<String, Vec<f32>>,
+    noise: f32,
+}
+
+impl FakeEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(1),
+            topics: Vec::new(),
+            vectors: HashMap::new(),
+            noise: 0.05,
+        }
+    }
+
+    /// A 32-dimensional embedder with one axis per [`SEED_TOPICS`] entry.
+    pub fn for_seed_topics() -> Self {
+        SEED_TOPICS
+            .iter()
+            .enumerate()
+            .fold(Self::new(32), |embedder, (axis, topic)| {
+                embedder.with_topic(topic, axis)
+            })
+    }
+
+    /// Texts containing `keyword` point along `axis`.
+    pub fn with_topic(mut self, keyword: &str, axis: usize) -> Self {
+        self.topics
+            .push((keyword.to_lowercase(), axis % self.dimensions));
+        self
+    }
+
+    /// Embed `text` as exactly `vector` (normalized).
+    pub fn with_vector(mut self, text: &str, vector: Vec<f32>) -> Self {
+        self.vectors.insert(text.to_string(), vector);
+        self
+    }
+
+    /// Size of the per-text noise added to topic vectors (default 0.05).
+    pub fn with_noise(mut self, noise: f32) -> Self {
+        self.noise = noise.max(0.0);
+        self
+    }
+
+    /// The vector `text` embeds to.
+    pub fn vector_for(&self, text: &str) -> Vec<f32> {
+        if let Some(vector) = self.vectors.get(text) {
+            return normalize(vector.clone());
+        }
+
+        // FNV-1a, so vectors stay the same across Rust versions
+        let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        let mut rng = StdRng::seed_from_u64(hash);
+
+        let lower = text.to_lowercase();
+        let mut vector = vec![0.0f32; self.dimensions];
+        let mut matched = false;
+        for (keyword, axis) in &self.topics {
+            if lower.contains(keyword.as_str()) {
+                vector[*axis] = 1.0;
+                matched = true;
+            }
+        }
+        let noise = if matched { self.noise } else { 1.0 };
+        for value in vector.iter_mut() {
+            *value += noise * rng.gen_range(-1.0f32..1.0);
+        }
+        normalize(vector)
+    }
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+impl Embedder for FakeEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.vector_for(text))
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_name(&self) -> &str {
+        "fake"
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Seeding
+// ---------------------------------------------------------------------------
+
+/// Create `count` memories spread over [`SEED_TOPICS`] and store their
+/// embeddings. The same `seed` always produces the same memories.
+pub fn seed_memories(
+    storage: &Storage,
+    embedder: &dyn Embedder,
+    count: usize,
+    seed: u64,
+) -> Result<Vec<Memory>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let inputs: Vec<CreateMemoryInput> = (0..count)
+        .map(|i| {
+            let topic = SEED_TOPICS[i % SEED_TOPICS.len()];
+            let template = SEED_TEMPLATES[rng.gen_range(0..SEED_TEMPLATES.len())];
+            let detail = SEED_DETAILS[rng.gen_range(0..SEED_DETAILS.len())];
+            CreateMemoryInput {
+                content: template
+                    .replace("{topic}", topic)
+                    .replace("{detail}", detail),
+                memory_type: SEED_TYPES[i % SEED_TYPES.len()],
+                tags: vec![topic.to_string(), "seed".to_string()],
+                importance: Some((rng.gen_range(1..=10) as f32) / 10.0),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    inputs
+        .iter()
+        .map(|input| create_embedded(storage, embedder, input))
+        .collect()
+}
+
+/// Create a memory and store its embedding right away.
+fn create_embedded(
+    storage: &Storage,
+    embedder: &dyn Embedder,
+    input: &CreateMemoryInput,
+) -> Result<Memory> {
+    let vector = embedder.embed(&input.content)?;
+    storage.with_transaction(|conn| {
+        let memory = queries::create_memory(conn, input)?;
+        crate::embedding::store_embedding(
+            conn,
+            memory.id,
+            &vector,
+            embedder.model_name(),
+            vector.len(),
+            None,
+            &Utc::now().to_rfc3339(),
+        )?;
+        queries::get_memory(conn, memory.id)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Harness
+// ---------------------------------------------------------------------------
+
+/// An in-memory Engram with an embedder, ready for tests.
+pub struct TestEngram {
+    pub storage: Storage,
+    pub embedder: Arc<dyn Embedder>,
+    /// Memories created through [`seed`](TestEngram::seed) and
+    /// [`add`](TestEngram::add), in creation order.
+    pub memories: Vec<Memory>,
+}
+
+impl TestEngram {
+    /// An empty store embedding with [`FakeEmbedder::for_seed_topics`].
+    pub fn new() -> Result<Self> {
+        Self::with_embedder(Arc::new(FakeEmbedder::for_seed_topics()))
+    }
+
+    pub fn with_embedder(embedder: Arc<dyn Embedder>) -> Result<Self> {
+        Ok(Self {
+            storage: Storage::open_in_memory()?,
+            embedder,
+            memories: Vec::new(),
+        })
+    }
+
+    /// A store holding `count` memories from [`seed_memories`].
+    pub fn seeded(count: usize, seed: u64) -> Result<Self> {
+        let mut engram = Self::new()?;
+        engram.seed(count, seed)?;
+        Ok(engram)
+    }
+
+    /// Add `count` seeded memories.
+    pub fn seed(&mut self, count: usize, seed: u64) -> Result<&[Memory]> {
+        let start = self.memories.len();
+        self.memories.extend(seed_memories(
+            &self.storage,
+            self.embedder.as_ref(),
+            count,
+            seed,
+        )?);
+        Ok(&self.memories[start..])
+    }
+
+    /// Add a note with `content`, embedded.
+    pub fn add(&mut self, content: &str) -> Result<Memory> {
+        self.add_input(CreateMemoryInput {
+            content: content.to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Add a memory, embedded.
+    pub fn add_input(&mut self, input: CreateMemoryInput) -> Result<Memory> {
+        let memory = create_embedded(&self.storage, self.embedder.as_ref(), &input)?;
+        self.memories.push(memory.clone());
+        Ok(memory)
+    }
+
+    /// Hybrid search with default options.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
+        self.search_with(query, &SearchOptions::default())
+    }
+
+    pub fn search_with(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let embedding = self.embedder.embed_query(query)?;
+        self.storage.with_connection(|conn| {
+            hybrid_search(
+                conn,
+                query,
+                Some(&embedding),
+                options,
+                &SearchConfig::default(),
+            )
+        })
+    }
+
+    /// A handler context over this store for calling MCP tools through
+    /// [`dispatch`](crate::mcp::handlers::dispatch).
+    pub fn handler_context(&self) -> HandlerContext {
+        let ctx = HandlerContext {
+            storage: self.storage.clone(),
+            embedder: self.embedder.clone(),
+            fuzzy_engine: Arc::new(Mutex::new(FuzzyEngine::new())),
+            search_config: SearchConfig::default(),
+            realtime: None,
+            embedding_cache: Arc::new(EmbeddingCache::default()),
+            embedding_queue: None,
+            search_cache: Arc::new(SearchResultCache::new(AdaptiveCacheConfig::default())),
+            memory_cache: Arc::new(crate::storage::MemoryCache::default()),
+            persona: Arc::new(crate::storage::ActivePersona::new()),
+            vector_index: Arc::new(crate::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: crate::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
+            #[cfg(feature = "meilisearch")]
+            meili: None,
+            #[cfg(feature = "meilisearch")]
+            meili_indexer: None,
+            #[cfg(feature = "meilisearch")]
+            meili_sync_interval: 60,
+            #[cfg(feature = "langfuse")]
+            langfuse_runtime: Arc::new(tokio::runtime::Runtime::new().expect("langfuse runtime")),
+        };
+        ctx.memory_cache.watch(&self.storage);
+        ctx
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Assertions
+// ---------------------------------------------------------------------------
+
+/// Memory ids of `results`, in rank order.
+pub fn result_ids(results: &[SearchResult]) -> Vec<MemoryId> {
+    results.iter().map(|r| r.memory.id).collect()
+}
+
+fn rank_of(results: &[SearchResult], id: MemoryId) -> Option<usize> {
+    results.iter().position(|r| r.memory.id == id)
+}
+
+/// Panic unless memory `id` is the first result.
+#[track_caller]
+pub fn assert_top_result(results: &[SearchResult], id: MemoryId) {
+    assert_eq!(
+        results.first().map(|r| r.memory.id),
+        Some(id),
+        "expected memory {} first, got {:?}",
+        id,
+        result_ids(results)
+    );
+}
+
+/// Panic unless memory `id` is among the results.
+#[track_caller]
+pub fn assert_contains_result(results: &[SearchResult], id: MemoryId) {
+    assert!(
+        rank_of(results, id).is_some(),
+        "expected memory {} in {:?}",
+        id,
+        result_ids(results)
+    );
+}
+
+/// Panic if memory `id` is among the results.
+#[track_caller]
+pub fn assert_excludes_result(results: &[SearchResult], id: MemoryId) {
+    assert!(
+        rank_of(results, id).is_none(),
+        "expected memory {} not to be in {:?}",
+        id,
+        result_ids(results)
+    );
+}
+
+/// Panic unless both memories are results and `first` ranks above `second`.
+#[track_caller]
+pub fn assert_ranked_before(results: &[SearchResult], first: MemoryId, second: MemoryId) {
+    match (rank_of(results, first), rank_of(results, second)) {
+        (Some(a), Some(b)) => assert!(
+            a < b,
+            "expected memory {} (rank {}) before memory {} (rank {}) in {:?}",
+            first,
+            a + 1,
+            second,
+            b + 1,
+            result_ids(results)
+        ),
+        _ => panic!(
+            "expected memories {} and {} in {:?}",
+            first,
+            second,
+            result_ids(results)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::cosine_similarity;
+
+    #[test]
+    fn test_fake_embedder_similarities() {
+        let embedder = FakeEmbedder::for_seed_topics();
+        let a = embedder.vector_for("Billing retries");
+        let b = embedder.vector_for("The billing dashboard");
+        let c = embedder.vector_for("Hiring plan");
+        let both = embedder.vector_for("Hiring for billing");
+        assert!(cosine_similarity(&a, &b) > 0.95);
+        assert!(cosine_similarity(&a, &c) < 0.2);
+        assert!((cosine_similarity(&a, &both) - 0.7).abs() < 0.1);
+        assert_eq!(a, embedder.vector_for("Billing retries"));
+
+        let pinned = embedder.with_vector("x", vec![3.0, 4.0]);
+        assert_eq!(pinned.vector_for("x"), vec![0.6, 0.8]);
+    }
+
+    #[test]
+    fn test_seeded_engram_search() {
+        let first = TestEngram::seeded(16, 7).unwrap();
+        let second = TestEngram::seeded(16, 7).unwrap();
+        let contents = |e: &TestEngram| -> Vec<String> {
+            e.memories.iter().map(|m| m.content.clone()).collect()
+        };
+        assert_eq!(contents(&first), contents(&second));
+
+        let mut engram = first;
+        let target = engram
+            .add("Security review: rotate the signing keys")
+            .unwrap();
+        let results = engram.search("security signing keys").unwrap();
+        assert_top_result(&results, target.id);
+        // memories[5] is a seeded security memory
+        assert_ranked_before(&results, target.id, engram.memories[5].id);
+
+        let ctx = engram.handler_context();
+        let response = crate::mcp::handlers::dispatch(
+            &ctx,
+            "memory_get",
+            serde_json::json!({"id": target.id}),
+        );
+        assert_eq!(response["content"], target.content);
+    }
+}
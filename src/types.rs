@@ -4,6 +4,12 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
+pub mod content_hash;
+pub mod projection;
+
+pub use content_hash::{ContentHash, CONTENT_HASH_ALGORITHM};
+pub use projection::{FieldSelection, FieldSpec, ItemKind, OutputShape, Verbosity};
+
 /// Unique identifier for a memory
 pub type MemoryId = i64;
 
@@ -57,8 +63,8 @@ pub struct Memory {
     pub has_embedding: bool,
     /// When the memory expires (None = never for permanent, required for daily)
     pub expires_at: Option<DateTime<Utc>>,
-    /// Content hash for deduplication (SHA256 of normalized content)
-    pub content_hash: Option<String>,
+    /// Content hash for deduplication ([`ContentHash::of`] the content)
+    pub content_hash: Option<ContentHash>,
     // Phase 1 - Cognitive memory fields (ENG-33)
     /// Timestamp when the event occurred (for Episodic memories)
     pub event_time: Option<DateTime<Utc>>,
@@ -119,6 +125,46 @@ impl std::str::FromStr for LifecycleState {
     }
 }
 
+/// Validation status of the claim a memory makes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationStatus {
+    /// Not yet checked - the default for every memory
+    #[default]
+    Unverified,
+    /// Confirmed by evidence or a reviewer
+    Verified,
+    /// Contradicted by evidence
+    Disputed,
+    /// Was verified but may no longer hold
+    Stale,
+}
+
+impl ValidationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationStatus::Unverified => "unverified",
+            ValidationStatus::Verified => "verified",
+            ValidationStatus::Disputed => "disputed",
+            ValidationStatus::Stale => "stale",
+        }
+    }
+}
+
+impl std::str::FromStr for ValidationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unverified" => Ok(ValidationStatus::Unverified),
+            "verified" => Ok(ValidationStatus::Verified),
+            "disputed" => Ok(ValidationStatus::Disputed),
+            "stale" => Ok(ValidationStatus::Stale),
+            _ => Err(format!("Unknown validation status: {}", s)),
+        }
+    }
+}
+
 fn default_workspace() -> String {
     "default".to_string()
 }
@@ -507,6 +553,7 @@ pub enum EdgeType {
     RelatedTo,
     Supersedes,
     Contradicts,
+    Supports,
     Implements,
     Extends,
     References,
@@ -521,6 +568,7 @@ impl EdgeType {
             EdgeType::RelatedTo => "related_to",
             EdgeType::Supersedes => "supersedes",
             EdgeType::Contradicts => "contradicts",
+            EdgeType::Supports => "supports",
             EdgeType::Implements => "implements",
             EdgeType::Extends => "extends",
             EdgeType::References => "references",
@@ -535,6 +583,7 @@ impl EdgeType {
             EdgeType::RelatedTo,
             EdgeType::Supersedes,
             EdgeType::Contradicts,
+            EdgeType::Supports,
             EdgeType::Implements,
             EdgeType::Extends,
             EdgeType::References,
@@ -553,6 +602,7 @@ impl std::str::FromStr for EdgeType {
             "related_to" | "related" => Ok(EdgeType::RelatedTo),
             "supersedes" => Ok(EdgeType::Supersedes),
             "contradicts" => Ok(EdgeType::Contradicts),
+            "supports" => Ok(EdgeType::Supports),
             "implements" => Ok(EdgeType::Implements),
             "extends" => Ok(EdgeType::Extends),
             "references" => Ok(EdgeType::References),
@@ -600,6 +650,18 @@ pub struct MatchInfo {
     pub semantic_score: Option<f32>,
     /// Keyword/BM25 score (if used)
     pub keyword_score: Option<f32>,
+    /// Contextual boost rules that adjusted the score
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boosts: Vec<AppliedBoost>,
+}
+
+/// A boost rule applied to a search hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedBoost {
+    pub rule_id: i64,
+    pub rule: String,
+    /// Amount added to the score (negative for penalties)
+    pub weight: f32,
 }
 
 /// Search strategy used
@@ -660,6 +722,54 @@ pub struct MemoryVersion {
     pub change_summary: Option<String>,
 }
 
+/// A slice of a memory's content, for paging through large memories
+///
+/// Offsets and lengths count characters (Unicode scalar values), so a range
+/// never splits a multi-byte character.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentRange {
+    pub memory_id: MemoryId,
+    /// Character offset of the first returned character
+    pub offset: usize,
+    /// Number of characters returned
+    pub length: usize,
+    /// Total content length in characters
+    pub total_chars: usize,
+    /// Total content length in UTF-8 bytes
+    pub total_bytes: usize,
+    /// Offset to request next, or `None` at the end of the content
+    pub next_offset: Option<usize>,
+    pub content: String,
+}
+
+impl ContentRange {
+    /// Slice an in-memory string the same way the storage layer does.
+    pub fn from_content(
+        memory_id: MemoryId,
+        full: &str,
+        offset: usize,
+        length: Option<usize>,
+    ) -> Self {
+        let total_chars = full.chars().count();
+        let content: String = full
+            .chars()
+            .skip(offset)
+            .take(length.unwrap_or(usize::MAX))
+            .collect();
+        let returned = content.chars().count();
+        let end = offset.saturating_add(returned);
+        Self {
+            memory_id,
+            offset,
+            length: returned,
+            total_chars,
+            total_bytes: full.len(),
+            next_offset: (end < total_chars).then_some(end),
+            content,
+        }
+    }
+}
+
 /// Statistics about the memory store
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct StorageStats {
@@ -729,7 +839,7 @@ pub enum StorageMode {
 /// Embedding model configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
-    /// Model to use: "openai", "local", "tfidf"
+    /// Model to use: "openai", "local", "candle", "cohere", "voyage", "hf", "tfidf"
     pub model: String,
     /// OpenAI API key (for openai model)
     pub api_key: Option<String>,
@@ -746,12 +856,113 @@ pub struct EmbeddingConfig {
     /// Batch size for async queue
     #[serde(default = "default_batch_size")]
     pub batch_size: usize,
+    /// Batches the async queue embeds concurrently
+    #[serde(default = "default_worker_concurrency")]
+    pub worker_concurrency: usize,
+    /// Text preprocessing applied before documents and queries are embedded
+    #[serde(default)]
+    pub normalization: TextNormalization,
+    /// Texts longer than this many characters are summarized and the
+    /// summary embedded instead (`None`: always embed the text)
+    #[serde(default)]
+    pub summarize_over_chars: Option<usize>,
+    /// Summarizer for long texts: "extractive" or "llm"
+    #[serde(default = "default_embedding_summarizer")]
+    pub summarizer: String,
+    /// Prepended to search queries, e.g. "query: " for E5 models
+    #[serde(default)]
+    pub query_prefix: Option<String>,
+    /// Prepended to memory content when it is embedded, e.g. "passage: "
+    #[serde(default)]
+    pub document_prefix: Option<String>,
+    /// Most tokens sent to the model per input (`None`: the model's limit
+    /// where known, 8191 for OpenAI; 0: no limit)
+    #[serde(default)]
+    pub max_input_tokens: Option<usize>,
+    /// What happens to inputs over `max_input_tokens`
+    #[serde(default)]
+    pub long_text: LongTextStrategy,
+    /// Where local candle models run: "auto", "cpu", "metal[:N]" or
+    /// "cuda[:N]" (`None`: auto, the GPU backend compiled in if any)
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Texts per forward pass for local candle models (`None`: 32)
+    #[serde(default)]
+    pub inference_batch_size: Option<usize>,
+    /// Most padded tokens per forward pass for local candle models, so
+    /// batches of long texts fit in GPU memory (`None`: 16384)
+    #[serde(default)]
+    pub max_batch_tokens: Option<usize>,
+}
+
+/// Text preprocessing before embedding, so vectors don't differ by
+/// formatting noise. The default changes nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextNormalization {
+    /// Lowercase the text
+    pub lowercase: bool,
+    /// Reduce markdown to its text (emphasis, headings, link targets go)
+    pub strip_markdown: bool,
+    /// What happens to fenced code blocks
+    pub code_blocks: CodeBlockPolicy,
+    /// Remove `http(s)://` and `www.` URLs
+    pub strip_urls: bool,
+    /// Collapse runs of whitespace into single spaces
+    pub collapse_whitespace: bool,
+    /// Longest text embedded, in characters
+    pub max_chars: Option<usize>,
+    /// Which part of an overlong text is kept
+    pub truncation: Truncation,
+}
+
+/// Handling of fenced code blocks before embedding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeBlockPolicy {
+    /// Embed the code like any other text
+    #[default]
+    Keep,
+    /// Drop code blocks
+    Remove,
+    /// Replace each block with `[code]` (or `[code: <language>]`)
+    Placeholder,
+}
+
+/// Which part of a text longer than `max_chars` is embedded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Truncation {
+    /// The beginning
+    #[default]
+    Head,
+    /// The beginning and the end
+    HeadTail,
+}
+
+/// Handling of inputs longer than the embedding model's token limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LongTextStrategy {
+    /// Embed the first `max_input_tokens` tokens
+    #[default]
+    Truncate,
+    /// Embed every `max_input_tokens` chunk and average the vectors
+    ChunkAverage,
 }
 
 fn default_batch_size() -> usize {
     100
 }
 
+fn default_worker_concurrency() -> usize {
+    2
+}
+
+fn default_embedding_summarizer() -> String {
+    "extractive".to_string()
+}
+
 impl Default for EmbeddingConfig {
     fn default() -> Self {
         Self {
@@ -762,6 +973,17 @@ impl Default for EmbeddingConfig {
             model_path: None,
             dimensions: 384,
             batch_size: 100,
+            worker_concurrency: default_worker_concurrency(),
+            normalization: TextNormalization::default(),
+            summarize_over_chars: None,
+            summarizer: default_embedding_summarizer(),
+            query_prefix: None,
+            document_prefix: None,
+            max_input_tokens: None,
+            long_text: LongTextStrategy::default(),
+            device: None,
+            inference_batch_size: None,
+            max_batch_tokens: None,
         }
     }
 }
@@ -891,6 +1113,18 @@ pub struct ListOptions {
     /// Include archived memories in results (default: false)
     #[serde(default)]
     pub include_archived: bool,
+    // Bitemporal filters: event time (when it happened) vs record time (when it was known)
+    /// Only memories whose `event_time` is at or after this instant
+    pub event_after: Option<DateTime<Utc>>,
+    /// Only memories whose `event_time` is at or before this instant
+    pub event_before: Option<DateTime<Utc>>,
+    /// Record-time "as of": memories as they were stored at this instant
+    pub as_of: Option<DateTime<Utc>>,
+    /// Include memories marked as superseded (default: false)
+    #[serde(default)]
+    pub show_superseded: bool,
+    /// Only memories with this validation status
+    pub validation_status: Option<ValidationStatus>,
 }
 
 /// Fields to sort by
@@ -953,6 +1187,11 @@ pub struct SearchOptions {
     /// are returned. For example, `"global/org:acme"` will match memories at
     /// `"global/org:acme"`, `"global/org:acme/user:alice"`, etc.
     pub scope_path: Option<String>,
+    /// Include memories marked as superseded in search results (default: false)
+    #[serde(default)]
+    pub show_superseded: bool,
+    /// Only memories with this validation status
+    pub validation_status: Option<ValidationStatus>,
 }
 
 /// Sync status information
@@ -972,6 +1211,12 @@ pub struct EmbeddingStatus {
     pub queued_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub error: Option<String>,
+    /// Set when the embedding failed permanently
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_letter: Option<EmbeddingDeadLetter>,
+    /// Summarizer whose summary was embedded in place of the content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summarized_by: Option<String>,
 }
 
 /// State of embedding computation
@@ -982,4 +1227,53 @@ pub enum EmbeddingState {
     Processing,
     Complete,
     Failed,
+    /// Gave up after permanent or repeated failures; see the dead-letter entry
+    Dead,
+}
+
+/// Lane of the embedding queue. Higher lanes are always drained first, so
+/// interactive writes don't wait behind bulk ingest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingPriority {
+    /// Bulk work such as document ingest and imports
+    Low,
+    #[default]
+    Normal,
+    /// Interactive writes like `memory_create`
+    High,
+}
+
+impl EmbeddingPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingPriority::Low => "low",
+            EmbeddingPriority::Normal => "normal",
+            EmbeddingPriority::High => "high",
+        }
+    }
+}
+
+impl std::str::FromStr for EmbeddingPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(EmbeddingPriority::Low),
+            "normal" => Ok(EmbeddingPriority::Normal),
+            "high" => Ok(EmbeddingPriority::High),
+            _ => Err(format!("Unknown embedding priority: {}", s)),
+        }
+    }
+}
+
+/// A memory whose embedding failed permanently
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingDeadLetter {
+    pub memory_id: MemoryId,
+    pub priority: EmbeddingPriority,
+    /// Last error reported by the embedder
+    pub error: String,
+    pub attempts: i32,
+    pub dead_at: DateTime<Utc>,
 }
@@ -0,0 +1,193 @@
+//! Canonical content hashing.
+//!
+//! Every place that fingerprints text — exact-duplicate detection on create,
+//! project file scanning, document chunking, sync conflict checks and the
+//! update log — goes through [`ContentHash`], so two hashes of the same input
+//! always compare equal no matter which path produced them.
+//!
+//! The wire format is `sha256:<64 lowercase hex digits>`. Two flavours share
+//! it:
+//! - [`ContentHash::of`] hashes *normalized* text (lowercased, whitespace runs
+//!   collapsed to one space, trimmed). This is the hash stored on
+//!   [`Memory::content_hash`](super::Memory::content_hash) and used for dedup,
+//!   so clients can compute it locally and look it up before uploading.
+//! - [`ContentHash::of_bytes`] hashes input exactly as given, for change
+//!   detection where case and whitespace edits matter.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Hash algorithm prefix used in the serialized form.
+pub const CONTENT_HASH_ALGORITHM: &str = "sha256";
+
+/// A `sha256:<hex>` content fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ContentHash(String);
+
+impl ContentHash {
+    /// Normalize text the way dedup compares it: lowercase, whitespace runs
+    /// collapsed to a single space, leading/trailing whitespace dropped.
+    pub fn normalize(content: &str) -> String {
+        content
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Hash of normalized text — the dedup hash stored on memories.
+    pub fn of(content: &str) -> Self {
+        Self::of_bytes(Self::normalize(content))
+    }
+
+    /// Hash of the exact bytes given, with no normalization.
+    pub fn of_bytes(content: impl AsRef<[u8]>) -> Self {
+        Self::of_parts([content])
+    }
+
+    /// Hash of several byte strings fed in order, with no separator or
+    /// normalization.
+    pub fn of_parts<I, B>(parts: I) -> Self
+    where
+        I: IntoIterator<Item = B>,
+        B: AsRef<[u8]>,
+    {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part.as_ref());
+        }
+        Self(format!(
+            "{}:{}",
+            CONTENT_HASH_ALGORITHM,
+            hex::encode(hasher.finalize())
+        ))
+    }
+
+    /// Wrap a hash read back from storage without validating it.
+    ///
+    /// Rows written by older versions may carry hashes in other formats;
+    /// they simply won't match freshly computed ones.
+    pub fn from_stored(hash: impl Into<String>) -> Self {
+        Self(hash.into())
+    }
+
+    /// The full `sha256:<hex>` string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The hex digest without the algorithm prefix.
+    pub fn digest(&self) -> &str {
+        self.0
+            .split_once(':')
+            .map(|(_, digest)| digest)
+            .unwrap_or(&self.0)
+    }
+
+    /// Consume the hash, returning its string form.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl std::fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for ContentHash {
+    type Err = String;
+
+    /// Parse a client-supplied hash, accepting upper- or lowercase hex.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digest = s
+            .strip_prefix(CONTENT_HASH_ALGORITHM)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid content hash '{}': expected '{}:<hex>'",
+                    s, CONTENT_HASH_ALGORITHM
+                )
+            })?;
+        if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "Invalid content hash '{}': digest must be 64 hex digits",
+                s
+            ));
+        }
+        Ok(Self(format!(
+            "{}:{}",
+            CONTENT_HASH_ALGORITHM,
+            digest.to_ascii_lowercase()
+        )))
+    }
+}
+
+impl AsRef<str> for ContentHash {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for ContentHash {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<String> for ContentHash {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl PartialEq<&str> for ContentHash {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_hash_ignores_case_and_whitespace() {
+        let a = ContentHash::of("Hello   World\n");
+        let b = ContentHash::of("  hello world");
+        assert_eq!(a, b);
+        assert_ne!(a, ContentHash::of("hello worlds"));
+
+        assert!(a.as_str().starts_with("sha256:"));
+        assert_eq!(a.digest().len(), 64);
+        assert_eq!(a, ContentHash::of_bytes("hello world"));
+    }
+
+    #[test]
+    fn test_exact_hash_keeps_case_and_whitespace() {
+        assert_ne!(
+            ContentHash::of_bytes("Hello world"),
+            ContentHash::of_bytes("hello world")
+        );
+        assert_eq!(
+            ContentHash::of_parts(["hello ", "world"]),
+            ContentHash::of_bytes("hello world")
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trips_and_rejects_garbage() {
+        let hash = ContentHash::of("some content");
+        let upper = format!("sha256:{}", hash.digest().to_uppercase());
+        assert_eq!(upper.parse::<ContentHash>().unwrap(), hash);
+        assert_eq!(hash.to_string().parse::<ContentHash>().unwrap(), hash);
+
+        assert!("md5:abc".parse::<ContentHash>().is_err());
+        assert!("sha256:xyz".parse::<ContentHash>().is_err());
+
+        let json = serde_json::to_value(&hash).unwrap();
+        assert_eq!(json, serde_json::json!(hash.as_str()));
+    }
+}
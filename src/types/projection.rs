@@ -0,0 +1,547 @@
+//! Response projection for token-efficient tool output.
+//!
+//! Tools that return memories serialize full [`Memory`](super::Memory) structs,
+//! which carry metadata, timestamps and bookkeeping flags that agents rarely
+//! need. [`Verbosity`] trims those payloads after serialization so every tool
+//! shares the same wire format regardless of how it built its response.
+//!
+//! Each projectable tool declares its output shape as a list of
+//! [`OutputShape`]s: where in the response its items sit and whether they are
+//! memories, search hits, graph edges or graph query rows. Only those items
+//! are projected; the rest of the response is left untouched.
+//!
+//! [`FieldSelection`] is the finer-grained alternative: callers name exactly
+//! which fields to keep, optionally truncating string fields with a
+//! `name:max_chars` suffix (e.g. `["id", "content:200", "tags", "score"]`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Fields kept for memories in [`Verbosity::Compact`] mode.
+const COMPACT_MEMORY_FIELDS: &[&str] =
+    &["id", "type", "content", "tags", "importance", "workspace"];
+
+/// Fields kept for edges in [`Verbosity::Compact`] mode.
+const COMPACT_EDGE_FIELDS: &[&str] = &["from_id", "to_id", "edge_type", "score"];
+
+/// Per-hit diagnostics dropped from search results outside `Full` mode.
+const HIT_DIAGNOSTIC_FIELDS: &[&str] = &["match_info", "rerank_info"];
+
+/// How much of each memory a tool response should include.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    /// Only identifiers (and scores for search hits).
+    IdsOnly,
+    /// Content, type, tags, importance and workspace — no metadata or timestamps.
+    Compact,
+    /// The complete serialized structs (default).
+    #[default]
+    Full,
+}
+
+impl Verbosity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verbosity::IdsOnly => "ids_only",
+            Verbosity::Compact => "compact",
+            Verbosity::Full => "full",
+        }
+    }
+
+    /// Rewrite a serialized tool response with the given output shape in place.
+    pub fn project(self, value: &mut Value, shape: &[OutputShape]) {
+        if self != Verbosity::Full {
+            for_each_item(value, shape, |item, kind| project_item(item, kind, self));
+        }
+    }
+}
+
+impl std::str::FromStr for Verbosity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ids_only" | "ids" => Ok(Verbosity::IdsOnly),
+            "compact" => Ok(Verbosity::Compact),
+            "full" => Ok(Verbosity::Full),
+            _ => Err(format!("Unknown verbosity: {}", s)),
+        }
+    }
+}
+
+/// The kind of item a tool response carries at an [`OutputShape`] path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    /// A serialized [`Memory`](super::Memory). `labels` are fields the tool
+    /// flattens into it (the federated `source`, an expired memory's
+    /// `purge_at`); verbosity keeps them in every mode.
+    Memory { labels: &'static [&'static str] },
+    /// A search hit wrapping a memory (`memory` + `score`).
+    Hit,
+    /// A graph edge (`from_id`, `to_id`, `edge_type`).
+    Edge,
+    /// A graph query row keyed by variable. Node bindings (`{id, ...}`) are
+    /// projected as memories; relationship bindings are left as is.
+    GraphRow,
+}
+
+impl ItemKind {
+    /// A plain memory with no flattened labels.
+    pub const MEMORY: ItemKind = ItemKind::Memory { labels: &[] };
+}
+
+/// Where a tool response carries projectable items.
+///
+/// `path` is `""` for the response itself, `"[]"` for each element of a
+/// top-level array and `"key[]"` for each element of the array under `key`.
+/// Paths that are missing from a response (error objects, alternate shapes)
+/// are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputShape {
+    pub path: &'static str,
+    pub kind: ItemKind,
+}
+
+impl OutputShape {
+    pub const fn new(path: &'static str, kind: ItemKind) -> Self {
+        Self { path, kind }
+    }
+}
+
+/// Call `f` on every item the declared `shape` locates in `value`.
+fn for_each_item(
+    value: &mut Value,
+    shape: &[OutputShape],
+    mut f: impl FnMut(&mut Value, ItemKind),
+) {
+    for entry in shape {
+        let (key, each) = match entry.path.strip_suffix("[]") {
+            Some(key) => (key, true),
+            None => (entry.path, false),
+        };
+        let target = if key.is_empty() {
+            Some(&mut *value)
+        } else {
+            value.get_mut(key)
+        };
+        match target {
+            Some(Value::Array(items)) if each => {
+                for item in items {
+                    f(item, entry.kind);
+                }
+            }
+            Some(item) if !each => f(item, entry.kind),
+            _ => {}
+        }
+    }
+}
+
+/// The node bindings of a graph query row.
+fn graph_nodes(row: &mut Map<String, Value>) -> impl Iterator<Item = &mut Value> {
+    row.values_mut()
+        .filter(|binding| binding.get("id").is_some())
+}
+
+/// A single entry of a [`FieldSelection`], e.g. `content:200`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub name: String,
+    /// Truncate string values to this many characters.
+    pub max_chars: Option<usize>,
+}
+
+impl std::str::FromStr for FieldSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, max_chars) = match s.split_once(':') {
+            Some((name, len)) => {
+                let len = len
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid truncation length in field '{}'", s))?;
+                (name.trim(), Some(len))
+            }
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(format!("Invalid field spec: '{}'", s));
+        }
+        Ok(FieldSpec {
+            name: name.to_string(),
+            max_chars,
+        })
+    }
+}
+
+/// Explicit list of fields to return for each memory or search hit.
+///
+/// Memories and graph query nodes keep only the selected fields. Search hits
+/// keep the selected hit-level fields (`score`, `match_info`, ...) and project
+/// the wrapped memory with the same selection. Graph edges are left as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSelection {
+    fields: Vec<FieldSpec>,
+}
+
+impl FieldSelection {
+    /// Parse a list of specs like `["id", "content:200"]`.
+    pub fn parse<S: AsRef<str>>(specs: &[S]) -> Result<Self, String> {
+        let fields = specs
+            .iter()
+            .map(|s| s.as_ref().parse())
+            .collect::<Result<Vec<FieldSpec>, _>>()?;
+        if fields.is_empty() {
+            return Err("fields must name at least one field".to_string());
+        }
+        Ok(Self { fields })
+    }
+
+    /// Parse a JSON tool argument: an array of strings or a comma-separated string.
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Array(items) => {
+                let specs = items
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .ok_or_else(|| "fields must be an array of strings".to_string())
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::parse(&specs)
+            }
+            Value::String(s) => Self::parse(&s.split(',').collect::<Vec<_>>()),
+            _ => Err("fields must be an array of strings".to_string()),
+        }
+    }
+
+    pub fn fields(&self) -> &[FieldSpec] {
+        &self.fields
+    }
+
+    /// Rewrite a serialized tool response with the given output shape in place.
+    pub fn project(&self, value: &mut Value, shape: &[OutputShape]) {
+        for_each_item(value, shape, |item, kind| self.project_item(item, kind));
+    }
+
+    fn project_item(&self, item: &mut Value, kind: ItemKind) {
+        let Value::Object(obj) = item else {
+            return;
+        };
+        match kind {
+            ItemKind::Memory { .. } => self.select(obj),
+            ItemKind::Hit => {
+                let memory = obj.remove("memory");
+                self.select(obj);
+                if let Some(mut memory) = memory {
+                    self.project_item(&mut memory, ItemKind::MEMORY);
+                    if memory.as_object().is_some_and(|m| !m.is_empty()) {
+                        obj.insert("memory".to_string(), memory);
+                    }
+                }
+            }
+            ItemKind::Edge => {}
+            ItemKind::GraphRow => {
+                for node in graph_nodes(obj) {
+                    self.project_item(node, ItemKind::MEMORY);
+                }
+            }
+        }
+    }
+
+    fn select(&self, obj: &mut Map<String, Value>) {
+        obj.retain(|key, _| self.fields.iter().any(|f| f.name == *key));
+        for spec in &self.fields {
+            if let (Some(max), Some(Value::String(s))) = (spec.max_chars, obj.get_mut(&spec.name)) {
+                truncate_chars(s, max);
+            }
+        }
+    }
+}
+
+/// Truncate to `max` grapheme clusters, appending `...` when cut.
+fn truncate_chars(s: &mut String, max: usize) {
+    if let Some((idx, _)) = s.grapheme_indices(true).nth(max) {
+        s.truncate(idx);
+        s.push_str("...");
+    }
+}
+
+fn project_item(item: &mut Value, kind: ItemKind, verbosity: Verbosity) {
+    let Value::Object(obj) = item else {
+        return;
+    };
+    match kind {
+        ItemKind::Memory { labels } => obj.retain(|key, _| {
+            memory_fields(verbosity).contains(&key.as_str()) || labels.contains(&key.as_str())
+        }),
+        ItemKind::Hit => project_hit(obj, verbosity),
+        ItemKind::Edge => retain_fields(obj, edge_fields(verbosity)),
+        ItemKind::GraphRow => {
+            for node in graph_nodes(obj) {
+                project_item(node, ItemKind::MEMORY, verbosity);
+            }
+        }
+    }
+}
+
+fn project_hit(obj: &mut Map<String, Value>, verbosity: Verbosity) {
+    for field in HIT_DIAGNOSTIC_FIELDS {
+        obj.remove(*field);
+    }
+    match verbosity {
+        Verbosity::IdsOnly => {
+            // Flatten to `{id, score}` — the wrapper carries nothing else useful.
+            let id = obj
+                .get("memory")
+                .and_then(|m| m.get("id"))
+                .cloned()
+                .unwrap_or(Value::Null);
+            obj.remove("memory");
+            obj.insert("id".to_string(), id);
+        }
+        _ => {
+            if let Some(memory) = obj.get_mut("memory") {
+                project_item(memory, ItemKind::MEMORY, verbosity);
+            }
+        }
+    }
+}
+
+fn memory_fields(verbosity: Verbosity) -> &'static [&'static str] {
+    match verbosity {
+        Verbosity::IdsOnly => &["id"],
+        _ => COMPACT_MEMORY_FIELDS,
+    }
+}
+
+fn edge_fields(verbosity: Verbosity) -> &'static [&'static str] {
+    match verbosity {
+        Verbosity::IdsOnly => &["from_id", "to_id"],
+        _ => COMPACT_EDGE_FIELDS,
+    }
+}
+
+fn retain_fields(obj: &mut Map<String, Value>, keep: &[&str]) {
+    obj.retain(|key, _| keep.contains(&key.as_str()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LIST: &[OutputShape] = &[OutputShape::new("[]", ItemKind::MEMORY)];
+    const HITS: &[OutputShape] = &[
+        OutputShape::new("[]", ItemKind::Hit),
+        OutputShape::new("results[]", ItemKind::Hit),
+    ];
+    const EDGES: &[OutputShape] = &[
+        OutputShape::new("[]", ItemKind::Edge),
+        OutputShape::new("discovery_edges[]", ItemKind::Edge),
+    ];
+
+    fn memory(id: i64) -> Value {
+        json!({
+            "id": id,
+            "content": "Use JWT for auth",
+            "type": "decision",
+            "tags": ["auth"],
+            "metadata": {"source": "meeting"},
+            "importance": 0.8,
+            "workspace": "default",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+            "has_embedding": true,
+            "version": 1
+        })
+    }
+
+    #[test]
+    fn test_parse_verbosity() {
+        assert_eq!("ids".parse::<Verbosity>().unwrap(), Verbosity::IdsOnly);
+        assert_eq!("IDS_ONLY".parse::<Verbosity>().unwrap(), Verbosity::IdsOnly);
+        assert_eq!("compact".parse::<Verbosity>().unwrap(), Verbosity::Compact);
+        assert_eq!("full".parse::<Verbosity>().unwrap(), Verbosity::Full);
+        assert!("verbose".parse::<Verbosity>().is_err());
+    }
+
+    #[test]
+    fn test_full_is_identity() {
+        let original = json!([memory(1), memory(2)]);
+        let mut value = original.clone();
+        Verbosity::Full.project(&mut value, LIST);
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn test_compact_memory_list() {
+        let mut value = json!([memory(1), memory(2)]);
+        Verbosity::Compact.project(&mut value, LIST);
+        let first = value[0].as_object().unwrap();
+        assert_eq!(first.len(), COMPACT_MEMORY_FIELDS.len());
+        assert_eq!(first["content"], "Use JWT for auth");
+        assert!(!first.contains_key("metadata"));
+        assert!(!first.contains_key("created_at"));
+    }
+
+    #[test]
+    fn test_declared_labels_survive_projection() {
+        let mut expired = memory(3);
+        expired["purge_at"] = json!("2026-01-08T00:00:00Z");
+        expired["source"] = json!("team");
+        let mut value = json!({"count": 1, "memories": [expired]});
+        let shape = [OutputShape::new(
+            "memories[]",
+            ItemKind::Memory {
+                labels: &["purge_at"],
+            },
+        )];
+        Verbosity::IdsOnly.project(&mut value, &shape);
+        assert_eq!(
+            value,
+            json!({"count": 1, "memories": [{"id": 3, "purge_at": "2026-01-08T00:00:00Z"}]})
+        );
+    }
+
+    #[test]
+    fn test_graph_rows_project_nodes() {
+        let node = json!({
+            "id": 1, "type": "note", "content": "Graph node", "importance": 0.5,
+            "tags": ["graph"], "workspace": "default"
+        });
+        let edge =
+            json!({"from": 1, "to": 1, "type": "related_to", "score": 0.5, "confidence": 1.0});
+        let shape = [OutputShape::new("rows[]", ItemKind::GraphRow)];
+
+        let mut ids =
+            json!({"count": 1, "rows": [{"a": node.clone(), "r": edge.clone(), "b": null}]});
+        Verbosity::IdsOnly.project(&mut ids, &shape);
+        assert_eq!(
+            ids,
+            json!({"count": 1, "rows": [{"a": {"id": 1}, "r": edge.clone(), "b": null}]})
+        );
+
+        let mut selected = json!({"rows": [{"a": node, "r": edge.clone()}]});
+        FieldSelection::parse(&["id", "tags"])
+            .unwrap()
+            .project(&mut selected, &shape);
+        assert_eq!(
+            selected,
+            json!({"rows": [{"a": {"id": 1, "tags": ["graph"]}, "r": edge}]})
+        );
+    }
+
+    #[test]
+    fn test_ids_only_search_hits_flatten() {
+        let mut value = json!({
+            "results": [
+                {"memory": memory(7), "score": 0.9, "match_info": {"strategy": "hybrid"}}
+            ]
+        });
+        Verbosity::IdsOnly.project(&mut value, HITS);
+        assert_eq!(value, json!({"results": [{"id": 7, "score": 0.9}]}));
+    }
+
+    #[test]
+    fn test_compact_search_hits_drop_diagnostics() {
+        let mut value = json!([{"memory": memory(7), "score": 0.9, "match_info": {}}]);
+        Verbosity::Compact.project(&mut value, HITS);
+        let hit = value[0].as_object().unwrap();
+        assert!(!hit.contains_key("match_info"));
+        assert_eq!(hit["score"], 0.9);
+        assert!(!hit["memory"].as_object().unwrap().contains_key("metadata"));
+    }
+
+    #[test]
+    fn test_edges_projected() {
+        let edge = json!({
+            "from_id": 1, "to_id": 2, "edge_type": "related_to", "score": 0.5,
+            "confidence": 1.0, "created_at": "2026-01-01T00:00:00Z", "metadata": {}
+        });
+        let mut compact = json!([edge.clone()]);
+        Verbosity::Compact.project(&mut compact, EDGES);
+        assert_eq!(
+            compact,
+            json!([{"from_id": 1, "to_id": 2, "edge_type": "related_to", "score": 0.5}])
+        );
+
+        let mut ids = json!({"discovery_edges": [edge]});
+        Verbosity::IdsOnly.project(&mut ids, EDGES);
+        assert_eq!(
+            ids,
+            json!({"discovery_edges": [{"from_id": 1, "to_id": 2}]})
+        );
+    }
+
+    #[test]
+    fn test_field_spec_parse() {
+        let spec: FieldSpec = "content:200".parse().unwrap();
+        assert_eq!(spec.name, "content");
+        assert_eq!(spec.max_chars, Some(200));
+        assert_eq!("tags".parse::<FieldSpec>().unwrap().max_chars, None);
+        assert!("content:abc".parse::<FieldSpec>().is_err());
+        assert!(":10".parse::<FieldSpec>().is_err());
+        assert!(FieldSelection::parse::<&str>(&[]).is_err());
+        assert!(FieldSelection::from_value(&json!([1, 2])).is_err());
+        assert_eq!(
+            FieldSelection::from_value(&json!("id, tags"))
+                .unwrap()
+                .fields()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_field_selection_memories() {
+        let selection = FieldSelection::parse(&["id", "content:3", "tags"]).unwrap();
+        let mut value = json!([memory(1)]);
+        selection.project(&mut value, LIST);
+        assert_eq!(
+            value,
+            json!([{"id": 1, "content": "Use...", "tags": ["auth"]}])
+        );
+    }
+
+    #[test]
+    fn test_field_selection_search_hits() {
+        let selection = FieldSelection::parse(&["id", "content:200", "score"]).unwrap();
+        let mut value = json!({
+            "results": [{"memory": memory(7), "score": 0.9, "match_info": {}}]
+        });
+        selection.project(&mut value, HITS);
+        assert_eq!(
+            value,
+            json!({"results": [{"score": 0.9, "memory": {"id": 7, "content": "Use JWT for auth"}}]})
+        );
+
+        let scores_only = FieldSelection::parse(&["score"]).unwrap();
+        let mut value = json!([{"memory": memory(7), "score": 0.9}]);
+        scores_only.project(&mut value, HITS);
+        assert_eq!(value, json!([{"score": 0.9}]));
+    }
+
+    #[test]
+    fn test_truncation_is_char_safe() {
+        let mut s = "héllo wörld".to_string();
+        truncate_chars(&mut s, 7);
+        assert_eq!(s, "héllo w...");
+        let mut short = "ok".to_string();
+        truncate_chars(&mut short, 5);
+        assert_eq!(short, "ok");
+    }
+
+    #[test]
+    fn test_undeclared_values_untouched() {
+        let original =
+            json!({"count": 2, "error": null, "stats": {"nodes": 3}, "memory": memory(1)});
+        let mut value = original.clone();
+        Verbosity::IdsOnly.project(&mut value, LIST);
+        Verbosity::IdsOnly.project(&mut value, HITS);
+        assert_eq!(value, original);
+    }
+}
@@ -308,5 +308,68 @@ mod chunking_golden {
             7 * 24 * 60 * 60,
             "Default TTL changed"
         ); // 7 days
+        assert_eq!(config.max_system_chars, 1000);
+        assert_eq!(config.max_argument_chars, 300);
+        assert_eq!(config.max_tool_result_chars, 2000);
+        assert!(config.denoise.is_none(), "De-noising must stay opt-in");
+    }
+}
+
+// ============================================================================
+// THREE-WAY MERGE GOLDEN TESTS
+// ============================================================================
+
+mod merge_golden {
+    use super::*;
+    use engram::sync::conflict::ThreeWayMerge;
+
+    #[derive(Debug, Deserialize)]
+    struct TestCase {
+        name: String,
+        base: String,
+        local: String,
+        remote: String,
+        expected: String,
+        success: bool,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Fixture {
+        test_cases: Vec<TestCase>,
+    }
+
+    #[test]
+    fn test_three_way_merge_golden() {
+        let fixture_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/three_way_merge.json"
+        );
+        let content =
+            fs::read_to_string(fixture_path).expect("Failed to read three_way_merge.json fixture");
+        let fixture: Fixture =
+            serde_json::from_str(&content).expect("Failed to parse fixture JSON");
+
+        let merger = ThreeWayMerge::new();
+        for case in fixture.test_cases {
+            let result = merger.merge(&case.base, &case.local, &case.remote);
+            assert_eq!(
+                result.content, case.expected,
+                "Case '{}': merged content mismatch",
+                case.name
+            );
+            assert_eq!(
+                result.success, case.success,
+                "Case '{}': success mismatch",
+                case.name
+            );
+
+            // Swapping sides must agree on whether the merge is clean.
+            let swapped = merger.merge(&case.base, &case.remote, &case.local);
+            assert_eq!(
+                swapped.success, case.success,
+                "Case '{}': swapped sides disagree",
+                case.name
+            );
+        }
     }
 }
@@ -41,7 +41,14 @@ impl TestHandler {
             search_config: SearchConfig::default(),
             realtime: None,
             embedding_cache: Arc::new(EmbeddingCache::default()),
+            embedding_queue: None,
             search_cache: Arc::new(SearchResultCache::new(AdaptiveCacheConfig::default())),
+            memory_cache: Arc::new(engram::storage::MemoryCache::default()),
+            persona: Arc::new(engram::storage::ActivePersona::new()),
+            vector_index: Arc::new(engram::search::vector_index::VectorIndexHandle::default()),
+            cross_encoder: None,
+            federation: None,
+            expired_grace_days: engram::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
             #[cfg(feature = "meilisearch")]
             meili: None,
             #[cfg(feature = "meilisearch")]
@@ -51,6 +58,7 @@ impl TestHandler {
             #[cfg(feature = "langfuse")]
             langfuse_runtime: Arc::new(tokio::runtime::Runtime::new().expect("langfuse runtime")),
         };
+        ctx.memory_cache.watch(&storage);
         Self { storage, ctx }
     }
 }
@@ -939,3 +947,1222 @@ fn test_recent_activity_preview_truncated_at_100_chars() {
         );
     }
 }
+
+// ---------------------------------------------------------------------------
+// Response verbosity tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_memory_list_verbosity_projection() {
+    let handler = TestHandler::new();
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Verbosity test memory", "metadata": {"k": "v"}}),
+    );
+
+    let full = handlers::dispatch(&handler.ctx, "memory_list", json!({}));
+    assert!(
+        full[0]["metadata"].is_object(),
+        "full keeps metadata: {}",
+        full
+    );
+
+    let compact = handlers::dispatch(&handler.ctx, "memory_list", json!({"verbosity": "compact"}));
+    assert_eq!(compact[0]["content"], "Verbosity test memory");
+    assert!(compact[0].get("metadata").is_none());
+    assert!(compact[0].get("created_at").is_none());
+
+    let ids = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"verbosity": "ids_only"}),
+    );
+    assert_eq!(ids[0].as_object().unwrap().len(), 1);
+    assert!(ids[0]["id"].is_number());
+
+    let invalid = handlers::dispatch(&handler.ctx, "memory_list", json!({"verbosity": "loud"}));
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_memory_list_field_selection() {
+    let handler = TestHandler::new();
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Field selection test memory", "tags": ["proj"]}),
+    );
+
+    let selected = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"fields": ["id", "content:5", "tags"], "verbosity": "ids_only"}),
+    );
+    let first = selected[0].as_object().unwrap();
+    assert_eq!(first.len(), 3, "fields override verbosity: {:?}", first);
+    assert_eq!(first["content"], "Field...");
+    assert_eq!(first["tags"], json!(["proj"]));
+
+    let invalid = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"fields": ["content:lots"]}),
+    );
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_graph_query_verbosity_projection() {
+    let handler = TestHandler::new();
+    let a = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Projection graph source", "tags": ["graph"]}),
+    );
+    let b = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Projection graph target"}),
+    );
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_link",
+        json!({"from_id": a["id"], "to_id": b["id"], "edge_type": "depends_on"}),
+    );
+    let query = "MATCH (a)-[r:depends_on]->(b) RETURN a, r, b";
+
+    let full = handlers::dispatch(&handler.ctx, "memory_graph_query", json!({"query": query}));
+    assert_eq!(full["count"], 1, "{}", full);
+    assert_eq!(full["rows"][0]["a"]["content"], "Projection graph source");
+
+    let ids = handlers::dispatch(
+        &handler.ctx,
+        "memory_graph_query",
+        json!({"query": query, "verbosity": "ids_only"}),
+    );
+    assert_eq!(ids["rows"][0]["a"], json!({"id": a["id"]}));
+    assert_eq!(ids["rows"][0]["b"], json!({"id": b["id"]}));
+    assert_eq!(ids["rows"][0]["r"]["type"], "depends_on");
+
+    let selected = handlers::dispatch(
+        &handler.ctx,
+        "memory_graph_query",
+        json!({"query": query, "fields": ["id", "tags"]}),
+    );
+    assert_eq!(
+        selected["rows"][0]["a"],
+        json!({"id": a["id"], "tags": ["graph"]})
+    );
+}
+
+#[test]
+fn test_projection_skips_undeclared_shapes() {
+    let handler = TestHandler::new();
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Paged projection memory"}),
+    );
+
+    let range = handlers::dispatch(
+        &handler.ctx,
+        "memory_get",
+        json!({"id": created["id"], "length": 5, "verbosity": "ids_only"}),
+    );
+    assert_eq!(range["content"], "Paged", "{}", range);
+    assert_eq!(range["next_offset"], 5);
+
+    let missing = handlers::dispatch(
+        &handler.ctx,
+        "memory_get",
+        json!({"id": 999_999, "verbosity": "ids_only"}),
+    );
+    assert!(missing["error"].is_string(), "{}", missing);
+}
+
+#[test]
+fn test_memory_get_rejects_empty_ranges() {
+    let handler = TestHandler::new();
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Paged content"}),
+    );
+    let id = created["id"].as_i64().unwrap();
+
+    let range = handlers::dispatch(
+        &handler.ctx,
+        "memory_get",
+        json!({"id": id, "offset": 2, "length": 0}),
+    );
+    assert_eq!(range["error"], "length must be at least 1");
+
+    let range = handlers::dispatch(
+        &handler.ctx,
+        "memory_get",
+        json!({"id": id, "offset": 2, "length": 3}),
+    );
+    assert_eq!(range["content"], "ged");
+    assert_eq!(range["next_offset"], 5);
+}
+
+#[test]
+fn test_memory_get_sees_writes_from_every_tool() {
+    let handler = TestHandler::new();
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Cached memory", "importance": 0.5}),
+    );
+    let id = created["id"].as_i64().unwrap();
+    handlers::dispatch(&handler.ctx, "memory_get", json!({"id": id}));
+
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_set_expiration",
+        json!({"id": id, "ttl_seconds": 3600}),
+    );
+    let memory = handlers::dispatch(&handler.ctx, "memory_get", json!({"id": id}));
+    assert!(memory["expires_at"].is_string(), "{}", memory);
+
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_boost",
+        json!({"id": id, "boost_amount": 0.3}),
+    );
+    let memory = handlers::dispatch(&handler.ctx, "memory_get", json!({"id": id}));
+    assert!(memory["importance"].as_f64().unwrap() > 0.7, "{}", memory);
+}
+
+#[test]
+fn test_memory_writes_reach_the_embedding_worker() {
+    let mut handler = TestHandler::new();
+    let queue = engram::embedding::EmbeddingQueue::new(10);
+    handler.ctx.embedding_queue = Some(queue.clone());
+
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Embed me first"}),
+    );
+    let id = created["id"].as_i64().unwrap();
+    assert_eq!(queue.len_for(engram::types::EmbeddingPriority::High), 1);
+    assert_eq!(queue.try_recv().unwrap().memory_id, id);
+
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_update",
+        json!({"id": id, "content": "Embed me again"}),
+    );
+    let request = queue.try_recv().unwrap();
+    assert_eq!(request.content, "Embed me again");
+    assert_eq!(request.priority, engram::types::EmbeddingPriority::Normal);
+}
+
+// ---------------------------------------------------------------------------
+// Time-travel tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_as_of_list_and_search() {
+    let handler = TestHandler::new();
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Time travel test memory"}),
+    );
+
+    let past = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"as_of": "2000-01-01T00:00:00Z"}),
+    );
+    assert_eq!(past, json!([]));
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let current = handlers::dispatch(
+        &handler.ctx,
+        "memory_search",
+        json!({"query": "travel", "as_of": now}),
+    );
+    assert_eq!(current.as_array().unwrap().len(), 1, "{}", current);
+
+    let invalid = handlers::dispatch(
+        &handler.ctx,
+        "memory_search",
+        json!({"query": "travel", "as_of": "yesterday"}),
+    );
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_superseded_memories_hidden_from_search() {
+    let handler = TestHandler::new();
+    let old = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "The staging database lives on host alpha"}),
+    );
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "The staging database lives on host beta"}),
+    );
+    let old_id = old["id"].as_i64().unwrap();
+
+    let marked = handlers::dispatch(
+        &handler.ctx,
+        "memory_supersede",
+        json!({"id": old_id, "reason": "host migrated"}),
+    );
+    assert_eq!(marked["memory_id"], json!(old_id), "{}", marked);
+
+    let search_ids = |show_superseded: bool| -> Vec<i64> {
+        let params = json!({
+            "query": "staging database",
+            "rerank": false,
+            "show_superseded": show_superseded,
+        });
+        let results = handlers::dispatch(&handler.ctx, "memory_search", params);
+        results
+            .as_array()
+            .unwrap_or_else(|| results["results"].as_array().unwrap())
+            .iter()
+            .map(|r| r["memory"]["id"].as_i64().unwrap())
+            .collect()
+    };
+    assert!(!search_ids(false).contains(&old_id));
+    assert!(search_ids(true).contains(&old_id));
+
+    let listed = handlers::dispatch(&handler.ctx, "memory_list_superseded", json!({}));
+    assert_eq!(listed["count"], json!(1));
+
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_restore_superseded",
+        json!({"id": old_id}),
+    );
+    assert!(search_ids(false).contains(&old_id));
+}
+
+// ---------------------------------------------------------------------------
+// Persona tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_persona_activation_shapes_retrieval() {
+    let handler = TestHandler::new();
+    let pinned = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Always run clippy before committing", "workspace": "engram"}),
+    );
+    let pinned_id = pinned["id"].as_i64().unwrap();
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Dentist on Tuesday", "workspace": "personal"}),
+    );
+
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "persona_upsert",
+        json!({
+            "name": "coder",
+            "default_workspace": "engram",
+            "ranking": {"keyword_weight": 0.8, "semantic_weight": 0.2},
+            "pinned_memory_ids": [pinned_id, 9999],
+            "tool_allowlist": ["memory_list", "memory_search"]
+        }),
+    );
+    assert_eq!(created["name"], "coder", "{}", created);
+
+    let pack = handlers::dispatch(&handler.ctx, "persona_activate", json!({"name": "coder"}));
+    assert_eq!(
+        pack["pinned_memories"][0]["id"],
+        json!(pinned_id),
+        "{}",
+        pack
+    );
+    assert_eq!(pack["missing_pinned_ids"], json!([9999]));
+    assert_eq!(
+        handler.ctx.effective_search_config().keyword_weight,
+        0.8,
+        "ranking profile applies while active"
+    );
+
+    // Retrieval defaults to the persona's workspace unless the call names one
+    let listed = handlers::dispatch(&handler.ctx, "memory_list", json!({}));
+    let workspaces: Vec<&str> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["workspace"].as_str().unwrap())
+        .collect();
+    assert_eq!(workspaces, vec!["engram"]);
+    let listed = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"workspace": "personal"}),
+    );
+    assert_eq!(listed[0]["workspace"], "personal");
+
+    let denied = handlers::dispatch(&handler.ctx, "memory_delete", json!({"id": pinned_id}));
+    assert!(
+        denied["error"].as_str().unwrap().contains("not allowed"),
+        "{}",
+        denied
+    );
+
+    let deactivated = handlers::dispatch(&handler.ctx, "persona_deactivate", json!({}));
+    assert_eq!(deactivated["previous"], "coder");
+    let listed = handlers::dispatch(&handler.ctx, "memory_list", json!({}));
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+}
+
+// ---------------------------------------------------------------------------
+// Session transcript tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_session_search_role_filters() {
+    let handler = TestHandler::new();
+    let indexed = handlers::dispatch(
+        &handler.ctx,
+        "session_index",
+        json!({
+            "session_id": "s1",
+            "max_messages": 2,
+            "overlap": 0,
+            "messages": [
+                {"role": "user", "content": "Why does the deploy fail?"},
+                {"role": "assistant", "content": "Let me check the deploy logs"},
+                {"role": "assistant", "tool_name": "read_logs", "arguments": {"service": "deploy"}},
+                {"role": "tool", "name": "read_logs", "content": "deploy: permission denied"}
+            ]
+        }),
+    );
+    assert_eq!(indexed["session"]["chunk_count"], 2, "{}", indexed);
+
+    let search = |filters: Value| {
+        let mut params = json!({"query": "deploy", "session_id": "s1"});
+        params
+            .as_object_mut()
+            .unwrap()
+            .extend(filters.as_object().unwrap().clone());
+        let result = handlers::dispatch(&handler.ctx, "memory_session_search", params);
+        result["memories"].as_array().unwrap().len()
+    };
+    assert_eq!(search(json!({})), 2);
+    assert_eq!(search(json!({"role": "User"})), 1);
+    assert_eq!(
+        search(json!({"kind": "tool_result", "tool_name": "read_logs"})),
+        1
+    );
+    assert_eq!(
+        search(json!({"kind": "tool_call", "tool_name": "other"})),
+        0
+    );
+
+    let invalid = handlers::dispatch(
+        &handler.ctx,
+        "memory_session_search",
+        json!({"query": "deploy", "kind": "chat"}),
+    );
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_session_index_generates_title_and_summary() {
+    let handler = TestHandler::new();
+    let messages = json!([
+        {"role": "user", "content": "Why does the staging deploy fail? It worked yesterday."},
+        {"role": "assistant", "content": "The staging deploy fails because the deploy user lost read access to the config bucket."},
+        {"role": "user", "content": "Please restore read access to the config bucket for the deploy user."},
+        {"role": "assistant", "content": "Read access is restored and the staging deploy passes again."}
+    ]);
+    let indexed = handlers::dispatch(
+        &handler.ctx,
+        "session_index",
+        json!({"session_id": "deploy", "messages": messages}),
+    );
+    let session = &indexed["session"];
+    assert_eq!(
+        session["title"], "Why does the staging deploy fail?",
+        "{}",
+        indexed
+    );
+    let summary = session["summary"].as_str().unwrap();
+    assert!(summary.contains("lost read access"), "{}", summary);
+    assert_eq!(session["metadata"]["generated"]["source"], "extractive");
+
+    let listed = handlers::dispatch(&handler.ctx, "session_list", json!({}));
+    assert_eq!(listed["sessions"][0]["summary"], summary);
+
+    let found = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"tags": ["session_summary"]}),
+    );
+    let found = found.as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(found[0]["content"]
+        .as_str()
+        .unwrap()
+        .starts_with("Why does the staging deploy fail?"));
+
+    // A caller title wins, and re-indexing replaces the summary memory
+    let reindexed = handlers::dispatch(
+        &handler.ctx,
+        "session_index",
+        json!({"session_id": "deploy", "title": "Staging outage", "messages": messages}),
+    );
+    assert_eq!(reindexed["session"]["title"], "Staging outage");
+    let found = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"tags": ["session_summary"]}),
+    );
+    assert_eq!(found.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_session_index_denoise() {
+    let handler = TestHandler::new();
+    let indexed = handlers::dispatch(
+        &handler.ctx,
+        "session_index",
+        json!({
+            "session_id": "noisy",
+            "denoise": true,
+            "messages": [
+                {"role": "user", "content": "Hi!"},
+                {"role": "user", "content": "Why does the deploy fail?"},
+                {"role": "assistant", "tool_name": "read_logs", "arguments": {"service": "deploy"}},
+                {"role": "tool", "name": "read_logs", "content": "Error: permission denied at 10:02"},
+                {"role": "assistant", "tool_name": "read_logs", "arguments": {"service": "deploy"}},
+                {"role": "tool", "name": "read_logs", "content": "Error: permission denied at 10:03"},
+                {"role": "assistant", "content": "The deploy user lacks read access to the logs."}
+            ]
+        }),
+    );
+    let session = &indexed["session"];
+    assert_eq!(session["message_count"], 7, "{}", indexed);
+    assert_eq!(session["metadata"]["denoise"]["greetings"], 1);
+    assert_eq!(session["metadata"]["denoise"]["repeated_errors"], 2);
+
+    // Delta updates reuse the recorded config and accumulate counts
+    let delta = handlers::dispatch(
+        &handler.ctx,
+        "session_index_delta",
+        json!({
+            "session_id": "noisy",
+            "messages": [{"role": "user", "content": "ok thanks"}]
+        }),
+    );
+    assert_eq!(delta["session"]["message_count"], 8, "{}", delta);
+    assert_eq!(delta["session"]["chunk_count"], 1);
+    assert_eq!(delta["session"]["metadata"]["denoise"]["greetings"], 2);
+    assert_eq!(delta["session"]["metadata"]["denoise"]["messages_in"], 8);
+
+    let invalid = handlers::dispatch(
+        &handler.ctx,
+        "session_index",
+        json!({
+            "session_id": "bad",
+            "denoise": {"boilerplate_patterns": ["("]},
+            "messages": [{"role": "user", "content": "hello world"}]
+        }),
+    );
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_detect_contradiction_cycles() {
+    let handler = TestHandler::new();
+    let ids: Vec<i64> = [
+        "The cache is flushed on every deploy",
+        "Deploys always start from a cold cache",
+        "Warm cache hits are visible right after deploys",
+    ]
+    .iter()
+    .map(|content| {
+        let created =
+            handlers::dispatch(&handler.ctx, "memory_create", json!({"content": content}));
+        created["id"].as_i64().unwrap()
+    })
+    .collect();
+    for (from, to, edge_type) in [
+        (ids[0], ids[1], "supports"),
+        (ids[1], ids[2], "contradicts"),
+        (ids[2], ids[0], "supports"),
+    ] {
+        let linked = handlers::dispatch(
+            &handler.ctx,
+            "memory_link",
+            json!({"from_id": from, "to_id": to, "edge_type": edge_type}),
+        );
+        assert!(linked.get("error").is_none(), "{}", linked);
+    }
+
+    let found = handlers::dispatch(
+        &handler.ctx,
+        "memory_detect_contradiction_cycles",
+        json!({}),
+    );
+    assert_eq!(found["cycle_count"], 1, "{}", found);
+    let mut affected: Vec<i64> = found["affected_memories"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_i64().unwrap())
+        .collect();
+    affected.sort();
+    assert_eq!(affected, ids);
+    let cycle = &found["cycles"][0];
+    assert_eq!(cycle["contradictions"], 1);
+    assert_eq!(cycle["suggestions"][0]["action"], "review_edge");
+    assert!(cycle["memories"][0]["label"].is_string());
+}
+
+#[test]
+fn test_suggest_links() {
+    let handler = TestHandler::new();
+    let ids: Vec<i64> = [
+        "Billing service runs on Postgres",
+        "Postgres backups run nightly",
+        "Invoices are generated by the billing service",
+        "Invoice PDFs are archived with the backups",
+    ]
+    .iter()
+    .map(|content| {
+        let created =
+            handlers::dispatch(&handler.ctx, "memory_create", json!({"content": content}));
+        created["id"].as_i64().unwrap()
+    })
+    .collect();
+    // A square 0-1-3-2-0: the diagonals share two neighbors each
+    for (from, to) in [(0, 1), (1, 3), (3, 2), (2, 0)] {
+        let linked = handlers::dispatch(
+            &handler.ctx,
+            "memory_link",
+            json!({"from_id": ids[from], "to_id": ids[to]}),
+        );
+        assert!(linked.get("error").is_none(), "{}", linked);
+    }
+
+    let found = handlers::dispatch(
+        &handler.ctx,
+        "memory_suggest_links",
+        json!({"id": ids[0], "method": "common_neighbors"}),
+    );
+    assert_eq!(found["method"], "common_neighbors", "{}", found);
+    let suggestions = found["suggestions"].as_array().unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0]["from_id"], ids[0]);
+    assert_eq!(suggestions[0]["to_id"], ids[3]);
+    assert_eq!(suggestions[0]["common_neighbors"], 2);
+
+    // Suggestions can be accepted as they are
+    let accepted = handlers::dispatch(
+        &handler.ctx,
+        "memory_link",
+        json!({"from_id": suggestions[0]["from_id"], "to_id": suggestions[0]["to_id"]}),
+    );
+    assert!(accepted.get("error").is_none(), "{}", accepted);
+    let all = handlers::dispatch(&handler.ctx, "memory_suggest_links", json!({}));
+    let pairs: Vec<(i64, i64)> = all["suggestions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["from_id"].as_i64().unwrap(), s["to_id"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(pairs, vec![(ids[1], ids[2])]);
+
+    let invalid = handlers::dispatch(
+        &handler.ctx,
+        "memory_suggest_links",
+        json!({"method": "katz"}),
+    );
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_export_neighborhood() {
+    let handler = TestHandler::new();
+    let ids: Vec<i64> = [
+        ("Checkout latency spiked after the release", "issue"),
+        ("Release notes for 4.2", "note"),
+        ("Roll back the payment client upgrade", "decision"),
+        ("Pin the payment client to 3.x", "decision"),
+    ]
+    .iter()
+    .map(|(content, memory_type)| {
+        let created = handlers::dispatch(
+            &handler.ctx,
+            "memory_create",
+            json!({"content": content, "type": memory_type}),
+        );
+        created["id"].as_i64().unwrap()
+    })
+    .collect();
+    for (from, to) in [(0, 1), (0, 2), (2, 3)] {
+        let linked = handlers::dispatch(
+            &handler.ctx,
+            "memory_link",
+            json!({"from_id": ids[from], "to_id": ids[to]}),
+        );
+        assert!(linked.get("error").is_none(), "{}", linked);
+    }
+
+    let near = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_neighborhood",
+        json!({"id": ids[0], "depth": 1, "format": "json"}),
+    );
+    assert_eq!(near["nodes"].as_array().unwrap().len(), 3, "{}", near);
+    assert_eq!(near["edges"].as_array().unwrap().len(), 2);
+
+    let decisions = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_neighborhood",
+        json!({
+            "id": ids[0],
+            "depth": 2,
+            "format": "dot",
+            "filter": {"memory_types": ["decision"]}
+        }),
+    );
+    let dot = decisions["dot"].as_str().unwrap();
+    assert!(dot.contains("Pin the payment client"), "{}", dot);
+    assert!(!dot.contains("Release notes"));
+
+    let html = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_neighborhood",
+        json!({"id": ids[3]}),
+    );
+    assert!(html["html"].as_str().unwrap().contains("vis-network"));
+
+    let missing = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_neighborhood",
+        json!({"id": 99999}),
+    );
+    assert!(missing["error"].is_string());
+    let invalid = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_neighborhood",
+        json!({"id": ids[0], "filter": {"min_importance": "high"}}),
+    );
+    assert!(invalid["error"].is_string());
+}
+
+#[test]
+fn test_auto_workspace_assignment() {
+    let handler = TestHandler::new();
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Billing API rate limits", "workspace": "billing-api"}),
+    );
+
+    let from_cwd = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({
+            "content": "Retry webhooks with exponential backoff",
+            "metadata": {"cwd": "/home/dev/Billing-API/src/webhooks"},
+            "auto_workspace": true
+        }),
+    );
+    assert_eq!(from_cwd["workspace"], "billing-api", "{}", from_cwd);
+    let assignment = &from_cwd["metadata"]["workspace_assignment"];
+    assert_eq!(assignment["source"], "path");
+    assert!(assignment["reason"].as_str().unwrap().contains("billing-api"));
+
+    // Explicit workspaces and opting out are left alone
+    let explicit = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({
+            "content": "Quarterly planning notes",
+            "workspace": "planning",
+            "metadata": {"cwd": "/home/dev/Billing-API"},
+            "auto_workspace": true
+        }),
+    );
+    assert_eq!(explicit["workspace"], "planning");
+    assert!(explicit["metadata"].get("workspace_assignment").is_none());
+    let opted_out = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({
+            "content": "Scratch note",
+            "metadata": {"cwd": "/home/dev/Billing-API"},
+            "auto_workspace": false
+        }),
+    );
+    assert_eq!(opted_out["workspace"], "default");
+
+    handlers::dispatch(
+        &handler.ctx,
+        "persona_upsert",
+        json!({"name": "support", "default_workspace": "tickets"}),
+    );
+    handlers::dispatch(&handler.ctx, "persona_activate", json!({"name": "support"}));
+    let from_persona = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Customer asked about refunds", "auto_workspace": true}),
+    );
+    assert_eq!(from_persona["workspace"], "tickets", "{}", from_persona);
+    assert_eq!(
+        from_persona["metadata"]["workspace_assignment"]["source"],
+        "persona"
+    );
+}
+
+#[test]
+fn test_session_link_topics() {
+    let handler = TestHandler::new();
+    for (id, text) in [
+        (
+            "mig-1",
+            "Planning the PostgreSQL migration with Alice Johnson on Kubernetes.",
+        ),
+        (
+            "mig-2",
+            "Alice Johnson says the PostgreSQL migration stalls on Kubernetes.",
+        ),
+        ("trip", "Booked flights to Paris for the offsite."),
+    ] {
+        let indexed = handlers::dispatch(
+            &handler.ctx,
+            "session_index",
+            json!({"session_id": id, "messages": [{"role": "user", "content": text}]}),
+        );
+        assert!(indexed.get("error").is_none(), "{}", indexed);
+    }
+
+    let dry = handlers::dispatch(
+        &handler.ctx,
+        "session_link_topics",
+        json!({"dry_run": true}),
+    );
+    assert_eq!(
+        dry["report"]["links"].as_array().unwrap().len(),
+        1,
+        "{}",
+        dry
+    );
+    assert!(dry["report"]["topics"][0]["memory_id"].is_null());
+
+    let linked = handlers::dispatch(&handler.ctx, "session_link_topics", json!({}));
+    assert_eq!(
+        linked["report"]["topics"][0]["sessions"],
+        json!(["mig-1", "mig-2"])
+    );
+    assert!(linked["report"]["crossrefs_created"].as_u64().unwrap() >= 1);
+
+    let listed = handlers::dispatch(&handler.ctx, "session_list", json!({}));
+    let sessions = listed["sessions"].as_array().unwrap();
+    let mig1 = sessions
+        .iter()
+        .find(|s| s["session_id"] == "mig-1")
+        .unwrap();
+    assert_eq!(
+        mig1["metadata"]["related_sessions"][0]["session_id"],
+        "mig-2"
+    );
+
+    let topics = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"tags": ["session_topic"]}),
+    );
+    assert_eq!(topics.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_export_graph_bipartite() {
+    let handler = TestHandler::new();
+    let ids: Vec<i64> = [
+        "Met with Alice Johnson about the billing migration",
+        "Alice Johnson approved the rollout plan",
+    ]
+    .iter()
+    .map(|content| {
+        let created =
+            handlers::dispatch(&handler.ctx, "memory_create", json!({"content": content}));
+        let id = created["id"].as_i64().unwrap();
+        handlers::dispatch(
+            &handler.ctx,
+            "memory_extract_entities",
+            json!({"memory_id": id}),
+        );
+        id
+    })
+    .collect();
+
+    let plain = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"format": "json"}),
+    );
+    assert_eq!(plain["nodes"].as_array().unwrap().len(), 2, "{}", plain);
+
+    let exported = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"format": "json", "mode": "bipartite", "min_entity_links": 2}),
+    );
+    let nodes = exported["nodes"].as_array().unwrap();
+    let alice = nodes
+        .iter()
+        .find(|n| n["label"] == "Alice Johnson")
+        .unwrap_or_else(|| panic!("no entity node in {}", exported));
+    assert!(alice["id"].as_i64().unwrap() < 0);
+    assert!(alice["group"].as_str().unwrap().starts_with("entity:"));
+    let mut linked: Vec<i64> = exported["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["to"] == alice["id"])
+        .map(|e| e["from"].as_i64().unwrap())
+        .collect();
+    linked.sort();
+    assert_eq!(linked, ids);
+
+    let bad = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"mode": "entities"}),
+    );
+    assert!(bad.get("error").is_some());
+}
+
+#[test]
+fn test_export_graph_svg() {
+    let handler = TestHandler::new();
+    for content in ["Billing runs on Postgres", "Postgres backups run nightly"] {
+        handlers::dispatch(&handler.ctx, "memory_create", json!({"content": content}));
+    }
+
+    let exported = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"format": "svg", "width": 640, "height": 480}),
+    );
+    let svg = exported["svg"]
+        .as_str()
+        .unwrap_or_else(|| panic!("no svg in {}", exported));
+    assert!(
+        svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"640\" height=\"480\"")
+    );
+    assert!(svg.contains("Billing runs on Postgres"));
+    assert!(!svg.contains("vis-network"));
+}
+
+#[cfg(feature = "otel")]
+#[test]
+fn test_otel_ingest_traces() {
+    let handler = TestHandler::new();
+    let attr = |key: &str, value: &str| json!({"key": key, "value": {"stringValue": value}});
+    let failed_span = |id: &str| {
+        json!({
+            "traceId": "4bf92f3577b34da6a3ce929d0e0e4736",
+            "spanId": id,
+            "name": "chat gpt-4o",
+            "startTimeUnixNano": "1700000000000000000",
+            "status": {"code": 2, "message": "rate limited"},
+            "attributes": [attr("gen_ai.request.model", "gpt-4o")]
+        })
+    };
+    let body = json!({
+        "workspace": "otel-test",
+        "resourceSpans": [{
+            "resource": {"attributes": [attr("service.name", "chatbot")]},
+            "scopeSpans": [{"spans": [
+                {
+                    "traceId": "4bf92f3577b34da6a3ce929d0e0e4736",
+                    "spanId": "00f067aa0ba902b7",
+                    "name": "chat gpt-4o",
+                    "attributes": [
+                        attr("gen_ai.request.model", "gpt-4o"),
+                        attr("gen_ai.prompt", "What is engram?"),
+                        attr("gen_ai.completion", "A memory server")
+                    ]
+                },
+                {"traceId": "4bf92f3577b34da6a3ce929d0e0e4736", "spanId": "1111", "name": "GET /"},
+                failed_span("a1"),
+                failed_span("a2"),
+                failed_span("a3")
+            ]}]
+        }]
+    });
+
+    let first = handlers::dispatch(&handler.ctx, "otel_ingest_traces", body.clone());
+    assert_eq!(first["spans_received"], 5, "{}", first);
+    assert_eq!(first["genai_spans"], 4);
+    assert_eq!(first["memories_created"], 4);
+    assert_eq!(first["patterns_created"], 1);
+
+    // Re-sent batches are deduplicated by span ID
+    let second = handlers::dispatch(&handler.ctx, "otel_ingest_traces", body);
+    assert_eq!(second["memories_created"], 0, "{}", second);
+    assert_eq!(second["spans_skipped"], 4);
+    assert_eq!(second["patterns_created"], 0);
+
+    let found = handlers::dispatch(
+        &handler.ctx,
+        "memory_list",
+        json!({"workspace": "otel-test", "tags": ["otel"], "limit": 10}),
+    );
+    let memories = found
+        .as_array()
+        .unwrap_or_else(|| panic!("no memories in {}", found));
+    assert_eq!(memories.len(), 5);
+    assert!(memories.iter().any(|m| m["content"]
+        .as_str()
+        .unwrap()
+        .contains("## Output\nA memory server")));
+}
+
+#[test]
+fn test_workspace_config_defaults() {
+    let handler = TestHandler::new();
+    let config = handlers::dispatch(
+        &handler.ctx,
+        "workspace_config_set",
+        json!({
+            "workspace": "scratch",
+            "base_tags": ["scratch"],
+            "default_tier": "daily",
+            "default_ttl_seconds": 7200
+        }),
+    );
+    assert_eq!(config["default_tier"], "daily", "{}", config);
+
+    let memory = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Try the new parser", "workspace": "scratch", "tags": ["idea"]}),
+    );
+    assert_eq!(memory["tier"], "daily", "{}", memory);
+    assert_eq!(memory["tags"], json!(["idea", "scratch"]));
+    assert!(memory["expires_at"].is_string());
+
+    let listed = handlers::dispatch(&handler.ctx, "workspace_config_list", json!({}));
+    assert_eq!(listed["count"], 1);
+
+    let deleted = handlers::dispatch(
+        &handler.ctx,
+        "workspace_config_delete",
+        json!({"workspace": "scratch"}),
+    );
+    assert_eq!(deleted["success"], true);
+    let config = handlers::dispatch(
+        &handler.ctx,
+        "workspace_config_get",
+        json!({"workspace": "scratch"}),
+    );
+    assert!(config["config"].is_null());
+}
+
+#[test]
+fn test_set_ttl_bulk() {
+    let handler = TestHandler::new();
+    for i in 0..3 {
+        handlers::dispatch(
+            &handler.ctx,
+            "memory_create",
+            json!({"content": format!("Seeded fact {}", i), "tags": ["seed"], "workspace": "facts"}),
+        );
+    }
+
+    let preview = handlers::dispatch(
+        &handler.ctx,
+        "memory_set_ttl_bulk",
+        json!({"filter": {"tags": ["seed"]}, "ttl_seconds": 86400, "dry_run": true}),
+    );
+    assert_eq!(preview["matched"], 3, "{}", preview);
+    assert_eq!(preview["retiered"], 3);
+
+    let applied = handlers::dispatch(
+        &handler.ctx,
+        "memory_set_ttl_bulk",
+        json!({"filter": {"workspace": "facts"}, "ttl_seconds": 86400}),
+    );
+    let id = applied["memory_ids"][0].as_i64().unwrap();
+    let memory = handlers::dispatch(&handler.ctx, "memory_get", json!({"id": id}));
+    assert_eq!(memory["tier"], "daily");
+    assert_eq!(memory["expires_at"], applied["expires_at"]);
+
+    let cleared = handlers::dispatch(
+        &handler.ctx,
+        "memory_set_ttl_bulk",
+        json!({"filter": {"tier": "daily"}, "ttl_seconds": null}),
+    );
+    assert_eq!(cleared["retiered"], 3);
+    assert!(cleared["expires_at"].is_null());
+
+    let missing = handlers::dispatch(
+        &handler.ctx,
+        "memory_set_ttl_bulk",
+        json!({"filter": {}, "ttl_seconds": null}),
+    );
+    assert!(missing["error"].is_string());
+}
+
+#[test]
+fn test_expired_grace_and_resurrect() {
+    let handler = TestHandler::new();
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Short-lived note", "tier": "daily", "ttl_seconds": 60}),
+    );
+    let id = created["id"].as_i64().unwrap();
+    handler
+        .storage
+        .with_connection(|conn| {
+            conn.execute(
+                "UPDATE memories SET expires_at = ? WHERE id = ?",
+                rusqlite::params![
+                    (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339(),
+                    id
+                ],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+    let cleanup = handlers::dispatch(
+        &handler.ctx,
+        "memory_cleanup_expired",
+        json!({"grace_days": 7}),
+    );
+    assert_eq!(cleanup["deleted"], 0, "{}", cleanup);
+    assert_eq!(cleanup["in_grace"], 1);
+
+    let expired = handlers::dispatch(
+        &handler.ctx,
+        "memory_expired_list",
+        json!({"grace_days": 7}),
+    );
+    assert_eq!(expired["count"], 1, "{}", expired);
+    assert_eq!(expired["memories"][0]["id"], id);
+    assert!(expired["memories"][0]["purge_at"].is_string());
+
+    let resurrected = handlers::dispatch(&handler.ctx, "memory_resurrect", json!({"id": id}));
+    assert_eq!(resurrected["tier"], "permanent", "{}", resurrected);
+    assert!(resurrected["expires_at"].is_null());
+
+    let again = handlers::dispatch(&handler.ctx, "memory_resurrect", json!({"id": id}));
+    assert!(again["error"].is_string());
+}
+
+#[test]
+fn test_expired_grace_comes_from_server_config() {
+    let mut handler = TestHandler::new();
+    handler.ctx.expired_grace_days = 0;
+    let created = handlers::dispatch(
+        &handler.ctx,
+        "memory_create",
+        json!({"content": "Short-lived note", "tier": "daily", "ttl_seconds": 60}),
+    );
+    let id = created["id"].as_i64().unwrap();
+    handler
+        .storage
+        .with_connection(|conn| {
+            conn.execute(
+                "UPDATE memories SET expires_at = ? WHERE id = ?",
+                rusqlite::params![
+                    (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339(),
+                    id
+                ],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+    // No grace period configured: nothing is recoverable
+    let expired = handlers::dispatch(&handler.ctx, "memory_expired_list", json!({}));
+    assert_eq!(expired["grace_days"], 0, "{}", expired);
+    assert_eq!(expired["count"], 0);
+    let resurrected = handlers::dispatch(&handler.ctx, "memory_resurrect", json!({"id": id}));
+    assert!(resurrected["error"].is_string(), "{}", resurrected);
+
+    let cleanup = handlers::dispatch(&handler.ctx, "memory_cleanup_expired", json!({}));
+    assert_eq!(cleanup["grace_days"], 0);
+    assert_eq!(cleanup["deleted"], 1, "{}", cleanup);
+}
+
+#[test]
+fn test_cancelled_call_is_truncated() {
+    let handler = TestHandler::new();
+    let a = handlers::dispatch(&handler.ctx, "memory_create", json!({"content": "Alpha"}));
+    let b = handlers::dispatch(&handler.ctx, "memory_create", json!({"content": "Beta"}));
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_link",
+        json!({"from_id": a["id"], "to_id": b["id"]}),
+    );
+
+    let full = handlers::dispatch(&handler.ctx, "memory_traverse", json!({"id": a["id"]}));
+    assert!(full.get("truncated").is_none_or(|t| t == false), "{}", full);
+
+    let cancel = engram::budget::CancelHandle::new();
+    cancel.cancel();
+    let partial = handlers::dispatch_cancellable(
+        &handler.ctx,
+        "memory_traverse",
+        json!({"id": a["id"]}),
+        cancel,
+    );
+    assert_eq!(partial["truncated"], true, "{}", partial);
+}
+
+#[test]
+fn test_export_graph_limits_and_streaming() {
+    let handler = TestHandler::new();
+    let a = handlers::dispatch(&handler.ctx, "memory_create", json!({"content": "Alpha"}));
+    let b = handlers::dispatch(&handler.ctx, "memory_create", json!({"content": "Beta"}));
+    handlers::dispatch(
+        &handler.ctx,
+        "memory_link",
+        json!({"from_id": a["id"], "to_id": b["id"]}),
+    );
+
+    let refused = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"format": "json", "max_nodes": 1_000_000}),
+    );
+    let error = refused["error"].as_str().expect("over the node cap");
+    assert!(error.contains("ENGRAM_MAX_GRAPH_NODES"), "{}", error);
+    assert!(error.contains("output_path"), "{}", error);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("graph.json");
+    let streamed = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"format": "json", "max_nodes": 1_000_000, "output_path": path}),
+    );
+    assert_eq!(streamed["node_count"], 2, "{}", streamed);
+    assert_eq!(streamed["edge_count"], 1, "{}", streamed);
+    let written: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written["nodes"].as_array().unwrap().len(), 2);
+    assert_eq!(streamed["bytes"], std::fs::metadata(&path).unwrap().len());
+
+    let html = handlers::dispatch(
+        &handler.ctx,
+        "memory_export_graph",
+        json!({"format": "html", "output_path": path}),
+    );
+    let error = html["error"].as_str().unwrap();
+    assert!(error.contains("can't be streamed"), "{}", error);
+}
@@ -222,6 +222,7 @@ mod chunking_tests {
                 content: "x".repeat(content_len),
                 timestamp: Utc::now(),
                 id: None,
+                ..Default::default()
             })
             .collect()
     }
@@ -309,6 +310,7 @@ mod edge_type_tests {
             Just(EdgeType::FollowsUp),
             Just(EdgeType::Supersedes),
             Just(EdgeType::Contradicts),
+            Just(EdgeType::Supports),
             Just(EdgeType::Implements),
             Just(EdgeType::Extends),
         ]) {
@@ -318,3 +320,314 @@ mod edge_type_tests {
         }
     }
 }
+
+// ============================================================================
+// THREE-WAY MERGE TESTS
+// ============================================================================
+
+mod merge_tests {
+    use super::*;
+    use engram::sync::conflict::ThreeWayMerge;
+    use std::collections::{HashMap, HashSet};
+
+    /// Line-based text over a tiny alphabet so concurrent edits collide often.
+    fn small_text() -> impl Strategy<Value = String> {
+        prop::collection::vec("[abc]{0,2}", 0..8).prop_map(|lines| lines.join("\n"))
+    }
+
+    /// Which side rewrites a block: 0 = nobody, 1 = local, 2 = remote.
+    type Block = (u8, usize, usize);
+
+    /// Build base/local/remote/expected where every block is rewritten by at
+    /// most one side and blocks are separated by untouched lines.
+    fn non_overlapping(blocks: &[Block]) -> (String, String, String, String) {
+        let (mut base, mut local, mut remote, mut expected) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (k, &(owner, base_len, new_len)) in blocks.iter().enumerate() {
+            let sep = format!("sep {}", k);
+            for side in [&mut base, &mut local, &mut remote, &mut expected] {
+                side.push(sep.clone());
+            }
+            let original: Vec<String> =
+                (0..base_len).map(|j| format!("base {} {}", k, j)).collect();
+            let rewritten: Vec<String> = (0..new_len)
+                .map(|j| {
+                    format!(
+                        "{} {} {}",
+                        if owner == 1 { "local" } else { "remote" },
+                        k,
+                        j
+                    )
+                })
+                .collect();
+            base.extend(original.clone());
+            match owner {
+                1 => {
+                    local.extend(rewritten.clone());
+                    remote.extend(original);
+                    expected.extend(rewritten);
+                }
+                2 => {
+                    local.extend(original);
+                    remote.extend(rewritten.clone());
+                    expected.extend(rewritten);
+                }
+                _ => {
+                    local.extend(original.clone());
+                    remote.extend(original.clone());
+                    expected.extend(original);
+                }
+            }
+        }
+        for side in [&mut base, &mut local, &mut remote, &mut expected] {
+            side.push("end".to_string());
+        }
+        (
+            base.join("\n"),
+            local.join("\n"),
+            remote.join("\n"),
+            expected.join("\n"),
+        )
+    }
+
+    fn tags() -> impl Strategy<Value = Vec<String>> {
+        prop::collection::vec("[a-e]", 0..6)
+    }
+
+    fn metadata() -> impl Strategy<Value = HashMap<String, serde_json::Value>> {
+        prop::collection::hash_map("[a-d]", (0i64..3).prop_map(serde_json::Value::from), 0..4)
+    }
+
+    proptest! {
+        /// Invariant: merge never panics on any input
+        #[test]
+        fn never_panics(base in "\\PC{0,200}", local in "\\PC{0,200}", remote in "\\PC{0,200}") {
+            let _ = ThreeWayMerge::new().merge(&base, &local, &remote);
+        }
+
+        /// Invariant: edits to separate regions merge cleanly with nothing lost
+        #[test]
+        fn non_overlapping_edits_merge_cleanly(
+            blocks in prop::collection::vec((0u8..3, 0usize..3, 0usize..3), 0..8)
+        ) {
+            let (base, local, remote, expected) = non_overlapping(&blocks);
+            let result = ThreeWayMerge::new().merge(&base, &local, &remote);
+            prop_assert!(result.success, "unexpected conflict:\n{}", result.content);
+            prop_assert_eq!(result.content, expected);
+        }
+
+        /// Invariant: unchanged input merges to itself, byte for byte
+        #[test]
+        fn identity(base in small_text()) {
+            let result = ThreeWayMerge::new().merge(&base, &base, &base);
+            prop_assert!(result.success);
+            prop_assert_eq!(result.content, base);
+        }
+
+        /// Invariant: a change on only one side is taken as-is
+        #[test]
+        fn one_sided_change_wins(base in small_text(), changed in small_text()) {
+            let merger = ThreeWayMerge::new();
+            let local_only = merger.merge(&base, &changed, &base);
+            prop_assert!(local_only.success);
+            prop_assert_eq!(local_only.content, changed.clone());
+
+            let remote_only = merger.merge(&base, &base, &changed);
+            prop_assert!(remote_only.success);
+            prop_assert_eq!(remote_only.content, changed.clone());
+        }
+
+        /// Invariant: swapping local and remote gives the same clean merge
+        #[test]
+        fn commutative(base in small_text(), local in small_text(), remote in small_text()) {
+            let merger = ThreeWayMerge::new();
+            let forward = merger.merge(&base, &local, &remote);
+            let backward = merger.merge(&base, &remote, &local);
+            prop_assert_eq!(forward.success, backward.success);
+            prop_assert_eq!(forward.stats.conflicts, backward.stats.conflicts);
+            if forward.success {
+                prop_assert_eq!(forward.content, backward.content);
+            }
+        }
+
+        /// Invariant: merging a clean result again changes nothing
+        #[test]
+        fn idempotent(base in small_text(), local in small_text(), remote in small_text()) {
+            let merger = ThreeWayMerge::new();
+            let merged = merger.merge(&base, &local, &remote);
+            if merged.success {
+                let again = merger.merge(&base, &merged.content, &merged.content);
+                prop_assert!(again.success);
+                prop_assert_eq!(&again.content, &merged.content);
+            }
+            let same = merger.merge(&base, &local, &local);
+            prop_assert!(same.success);
+            prop_assert_eq!(same.content, local);
+        }
+
+        /// Invariant: every line either side added survives, conflict or not
+        #[test]
+        fn added_lines_survive(base in small_text(), local in small_text(), remote in small_text()) {
+            let result = ThreeWayMerge::new().merge(&base, &local, &remote);
+            let base_lines: HashSet<&str> = base.lines().collect();
+            let merged_lines: HashSet<&str> = result.content.lines().collect();
+            for line in local.lines().chain(remote.lines()) {
+                if !base_lines.contains(line) {
+                    prop_assert!(merged_lines.contains(line), "lost {:?}", line);
+                }
+            }
+            prop_assert_eq!(result.success, result.conflict_lines.is_empty());
+        }
+
+        /// Invariant: tag merge is the order-independent union, without duplicates
+        #[test]
+        fn tag_merge_is_union(base in tags(), local in tags(), remote in tags()) {
+            let merger = ThreeWayMerge::new();
+            let merged = merger.merge_tags(&base, &local, &remote);
+            let merged_set: HashSet<&String> = merged.iter().collect();
+            prop_assert_eq!(merged_set.len(), merged.len());
+
+            let expected: HashSet<&String> = local.iter().chain(&remote).collect();
+            prop_assert_eq!(&merged_set, &expected);
+
+            let swapped = merger.merge_tags(&base, &remote, &local);
+            prop_assert_eq!(swapped.iter().collect::<HashSet<_>>(), expected);
+            prop_assert_eq!(merger.merge_tags(&base, &local, &remote), merged);
+        }
+
+        /// Invariant: metadata changes to different keys (including removals)
+        /// are all applied, regardless of side
+        #[test]
+        fn metadata_disjoint_changes_apply(
+            base in metadata(),
+            local_changes in metadata(),
+            remote_changes in metadata(),
+            removals in prop::collection::hash_set("[a-d]", 0..3),
+        ) {
+            let merger = ThreeWayMerge::new();
+            let mut local = base.clone();
+            let mut remote = base.clone();
+            let mut expected = base.clone();
+            for (k, v) in &local_changes {
+                local.insert(k.clone(), v.clone());
+                expected.insert(k.clone(), v.clone());
+            }
+            for (k, v) in remote_changes.iter().filter(|(k, _)| !local_changes.contains_key(*k)) {
+                remote.insert(k.clone(), v.clone());
+                expected.insert(k.clone(), v.clone());
+            }
+            for k in removals.iter().filter(|k| {
+                !local_changes.contains_key(*k) && !remote_changes.contains_key(*k)
+            }) {
+                remote.remove(k);
+                expected.remove(k);
+            }
+
+            prop_assert_eq!(&merger.merge_metadata_map(Some(&base), &local, &remote), &expected);
+            prop_assert_eq!(&merger.merge_metadata_map(Some(&base), &remote, &local), &expected);
+            prop_assert_eq!(merger.merge_metadata_map(Some(&base), &expected, &expected), expected);
+        }
+    }
+}
+
+// ============================================================================
+// CONFLICT RESOLVER TESTS
+// ============================================================================
+
+mod resolver_tests {
+    use super::*;
+    use chrono::Utc;
+    use engram::sync::conflict::{
+        Conflict, ConflictResolver, ConflictType, ResolutionStrategy, SyncMemoryVersion,
+    };
+    use engram::types::{LifecycleState, Memory, MemoryScope, MemoryTier, MemoryType, Visibility};
+    use std::collections::HashMap;
+
+    fn memory(content: &str, tags: &[String]) -> Memory {
+        Memory {
+            id: 1,
+            content: content.to_string(),
+            memory_type: MemoryType::Note,
+            tags: tags.to_vec(),
+            metadata: HashMap::new(),
+            importance: 0.5,
+            access_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_accessed_at: None,
+            owner_id: None,
+            visibility: Visibility::Private,
+            scope: MemoryScope::Global,
+            workspace: "default".to_string(),
+            tier: MemoryTier::Permanent,
+            version: 1,
+            has_embedding: false,
+            expires_at: None,
+            content_hash: None,
+            event_time: None,
+            event_duration_seconds: None,
+            trigger_pattern: None,
+            procedure_success_count: 0,
+            procedure_failure_count: 0,
+            summary_of_id: None,
+            lifecycle_state: LifecycleState::Active,
+            media_url: None,
+        }
+    }
+
+    fn conflict(base: &Memory, local: &Memory, remote: &Memory) -> Conflict {
+        Conflict::new(
+            1,
+            Some(SyncMemoryVersion::new(base.clone(), "base")),
+            SyncMemoryVersion::new(local.clone(), "local"),
+            SyncMemoryVersion::new(remote.clone(), "remote"),
+            ConflictType::ContentConflict,
+        )
+    }
+
+    fn text() -> impl Strategy<Value = String> {
+        prop::collection::vec("[abc]{1,2}", 0..6).prop_map(|lines| lines.join("\n"))
+    }
+
+    proptest! {
+        /// Invariant: resolving the resolved memory against itself is a no-op
+        #[test]
+        fn three_way_resolution_is_idempotent(
+            base in text(),
+            local in text(),
+            remote in text(),
+            local_tags in prop::collection::vec("[a-c]", 0..3),
+            remote_tags in prop::collection::vec("[a-c]", 0..3),
+        ) {
+            let resolver = ConflictResolver::new();
+            let base = memory(&base, &[]);
+            let first = resolver
+                .resolve(
+                    &conflict(&base, &memory(&local, &local_tags), &memory(&remote, &remote_tags)),
+                    ResolutionStrategy::ThreeWayMerge,
+                )
+                .expect("resolve")
+                .resolved_memory;
+
+            let second = resolver
+                .resolve(&conflict(&base, &first, &first), ResolutionStrategy::ThreeWayMerge)
+                .expect("resolve again")
+                .resolved_memory;
+            prop_assert_eq!(&second.content, &first.content);
+            prop_assert_eq!(&second.tags, &first.tags);
+            prop_assert_eq!(&second.metadata, &first.metadata);
+        }
+
+        /// Invariant: keep-local and keep-remote return their side untouched
+        #[test]
+        fn keep_strategies_preserve_side(local in text(), remote in text()) {
+            let resolver = ConflictResolver::new();
+            let base = memory("", &[]);
+            let c = conflict(&base, &memory(&local, &[]), &memory(&remote, &[]));
+            let kept_local = resolver.resolve(&c, ResolutionStrategy::KeepLocal).expect("resolve");
+            prop_assert_eq!(kept_local.resolved_memory.content, local);
+            let kept_remote = resolver.resolve(&c, ResolutionStrategy::KeepRemote).expect("resolve");
+            prop_assert_eq!(kept_remote.resolved_memory.content, remote);
+        }
+    }
+}
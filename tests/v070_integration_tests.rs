@@ -26,7 +26,14 @@ fn test_ctx() -> handlers::HandlerContext {
         search_config: SearchConfig::default(),
         realtime: None,
         embedding_cache: Arc::new(EmbeddingCache::default()),
+        embedding_queue: None,
         search_cache: Arc::new(SearchResultCache::new(AdaptiveCacheConfig::default())),
+        memory_cache: Arc::new(engram::storage::MemoryCache::default()),
+        persona: Arc::new(engram::storage::ActivePersona::new()),
+        vector_index: Arc::new(engram::search::vector_index::VectorIndexHandle::default()),
+        cross_encoder: None,
+        federation: None,
+        expired_grace_days: engram::storage::queries::DEFAULT_EXPIRED_GRACE_DAYS,
         #[cfg(feature = "meilisearch")]
         meili: None,
         #[cfg(feature = "meilisearch")]